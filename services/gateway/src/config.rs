@@ -1,7 +1,11 @@
 use anyhow::Result;
+use axum::http::Method;
 use serde::{Deserialize, Serialize};
-use shared_utils::ConfigLoader;
-use std::collections::HashMap;
+use shared_utils::{logging::LogLevelConfig, CacheConfig, ConfigLoader, ConfigReport, InternalAuthConfig};
+use std::collections::{BTreeMap, HashMap};
+
+/// 网关识别的 GATEWAY_ 前缀环境变量
+const GATEWAY_ENV_VARS: [&str; 2] = ["GATEWAY_HOST", "GATEWAY_PORT"];
 
 /// 网关配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +17,15 @@ pub struct GatewayConfig {
     pub redis: RedisConfig,
     pub cors: CorsConfig,
     pub logging: LoggingConfig,
+    /// 指标和管理接口的内部认证
+    #[serde(default)]
+    pub internal_auth: InternalAuthConfig,
+    /// 免登录的只读行情访问
+    #[serde(default)]
+    pub public_market_data: PublicMarketDataConfig,
+    /// 行情分片路由
+    #[serde(default)]
+    pub sharding: ShardRoutingConfig,
 }
 
 /// 服务器配置
@@ -50,6 +63,16 @@ pub struct AuthConfig {
     pub issuer: String,
     pub audience: String,
     pub public_paths: Vec<String>,
+    /// 已校验令牌的缓存，存活时间不超过令牌本身的过期时间
+    #[serde(default = "default_token_cache")]
+    pub token_cache: CacheConfig,
+}
+
+fn default_token_cache() -> CacheConfig {
+    CacheConfig {
+        capacity: 50_000,
+        ttl_seconds: 60,
+    }
 }
 
 impl Default for AuthConfig {
@@ -68,6 +91,7 @@ impl Default for AuthConfig {
                 "/api/v1/auth/refresh".to_string(),
                 "/api/v1/market/public".to_string(),
             ],
+            token_cache: default_token_cache(),
         }
     }
 }
@@ -96,6 +120,96 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// 公开行情配置
+///
+/// 未携带令牌的请求只能以 GET 访问这里列出的行情接口，按IP使用更严格的限流档位；
+/// 携带令牌的请求仍走正常认证和限流。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicMarketDataConfig {
+    pub enabled: bool,
+    /// 公开的行情接口路径，以 * 结尾表示前缀匹配
+    pub paths: Vec<String>,
+    /// 每个IP每个限流窗口的请求上限
+    pub requests_per_minute: u32,
+}
+
+impl Default for PublicMarketDataConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            paths: vec![
+                "/api/v1/market-data/ticker/*".to_string(),
+                "/api/v1/market-data/tick/*".to_string(),
+                "/api/v1/market-data/kline/*".to_string(),
+                "/api/v1/market-data/trade/*".to_string(),
+                "/api/v1/market-data/trades/*".to_string(),
+                "/api/v1/market-data/symbols".to_string(),
+                "/ws/market-data/trades/*".to_string(),
+            ],
+            requests_per_minute: 30,
+        }
+    }
+}
+
+impl PublicMarketDataConfig {
+    /// 未认证请求是否可以通过公开档位访问
+    pub fn allows(&self, method: &Method, path: &str) -> bool {
+        self.enabled
+            && method == Method::GET
+            && !is_restricted_path(path)
+            && self.paths.iter().any(|pattern| path_matches(pattern, path))
+    }
+
+    /// 公开路径只能指向行情服务，且不能包含管理和账户接口
+    pub fn validate(&self) -> Result<()> {
+        for path in &self.paths {
+            if !path.starts_with("/api/v1/market-data/") && !path.starts_with("/ws/market-data/") {
+                return Err(anyhow::anyhow!(
+                    "Public market data path must target the market data service: {}",
+                    path
+                ));
+            }
+            if is_restricted_path(path) {
+                return Err(anyhow::anyhow!(
+                    "Public market data path cannot expose admin or account routes: {}",
+                    path
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 行情分片路由配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShardRoutingConfig {
+    /// 行情服务发布分配使用的Redis键前缀，需与行情服务的 `sharding.key_prefix` 一致
+    pub key_prefix: String,
+}
+
+impl Default for ShardRoutingConfig {
+    fn default() -> Self {
+        Self {
+            key_prefix: shared_utils::sharding::DEFAULT_SHARD_PREFIX.to_string(),
+        }
+    }
+}
+
+/// 管理和账户相关的路径，任何情况下都不公开
+fn is_restricted_path(path: &str) -> bool {
+    path.split('/')
+        .any(|segment| matches!(segment, "admin" | "account" | "alerts"))
+}
+
+/// 路径匹配，模式以 * 结尾时按前缀匹配
+fn path_matches(pattern: &str, path: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => path.starts_with(prefix),
+        None => path == pattern,
+    }
+}
+
 /// 服务配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServicesConfig {
@@ -247,6 +361,9 @@ pub struct LoggingConfig {
     pub format: String,
     pub access_log: bool,
     pub error_log: bool,
+    /// 按模块覆盖日志级别
+    #[serde(default)]
+    pub modules: BTreeMap<String, String>,
 }
 
 impl Default for LoggingConfig {
@@ -256,6 +373,7 @@ impl Default for LoggingConfig {
             format: "json".to_string(),
             access_log: true,
             error_log: true,
+            modules: BTreeMap::new(),
         }
     }
 }
@@ -270,21 +388,46 @@ impl Default for GatewayConfig {
             redis: RedisConfig::default(),
             cors: CorsConfig::default(),
             logging: LoggingConfig::default(),
+            internal_auth: InternalAuthConfig::default(),
+            public_market_data: PublicMarketDataConfig::default(),
+            sharding: ShardRoutingConfig::default(),
         }
     }
 }
 
 impl GatewayConfig {
-    /// 加载配置
+    /// 加载配置，存在字段错误时返回完整的检查报告
     pub fn load() -> Result<Self> {
+        let (config, report) = Self::load_checked();
+        for issue in report.warnings() {
+            tracing::warn!("Config {}: {}", issue.path, issue.message);
+        }
+        report.into_result()?;
+        Ok(config)
+    }
+
+    /// 从默认值和环境变量加载配置，返回生效配置和字段级报告
+    pub fn load_checked() -> (Self, ConfigReport) {
         let mut config = Self::default();
+        let mut report = ConfigReport::new();
 
         // 从环境变量加载配置
         if let Ok(host) = std::env::var("GATEWAY_HOST") {
             config.server.host = host;
         }
         if let Ok(port) = std::env::var("GATEWAY_PORT") {
-            config.server.port = port.parse()?;
+            match port.parse() {
+                Ok(port) => config.server.port = port,
+                Err(e) => report.error(
+                    "server.port",
+                    format!("GATEWAY_PORT=\"{}\" is not a valid port: {}", port, e),
+                ),
+            }
+        }
+        for (name, _) in std::env::vars() {
+            if name.starts_with("GATEWAY_") && !GATEWAY_ENV_VARS.contains(&name.as_str()) {
+                report.warning(&name, "unknown environment variable, ignored");
+            }
         }
         if let Ok(jwt_secret) = std::env::var("JWT_SECRET") {
             config.auth.jwt_secret = jwt_secret;
@@ -316,10 +459,37 @@ impl GatewayConfig {
             config.services.analytics_service.url = analytics_service_url;
         }
 
-        // 验证配置
-        config.validate()?;
+        config.internal_auth.apply_env();
 
-        Ok(config)
+        config.check(&mut report);
+        (config, report)
+    }
+
+    /// 字段取值范围检查
+    fn check(&self, report: &mut ConfigReport) {
+        report.range("server.port", self.server.port, 1, u16::MAX);
+        report.range("server.request_timeout", self.server.request_timeout, 1, 3600);
+        report.range("auth.jwt_expiry", self.auth.jwt_expiry, 60, self.auth.refresh_token_expiry);
+        report.range(
+            "rate_limit.requests_per_minute",
+            self.rate_limit.requests_per_minute,
+            1,
+            1_000_000,
+        );
+        report.range(
+            "public_market_data.requests_per_minute",
+            self.public_market_data.requests_per_minute,
+            1,
+            self.rate_limit.requests_per_minute,
+        );
+        self.auth.token_cache.check("auth.token_cache", report);
+        self.internal_auth.check("internal_auth", report);
+        LogLevelConfig {
+            level: self.logging.level.clone(),
+            modules: self.logging.modules.clone(),
+        }
+        .check("logging", report);
+        report.merge_validation("", self.validate());
     }
 
     /// 验证配置
@@ -358,6 +528,8 @@ impl GatewayConfig {
             }
         }
 
+        self.public_market_data.validate()?;
+
         Ok(())
     }
 
@@ -377,13 +549,10 @@ impl GatewayConfig {
 
     /// 检查路径是否为公开路径
     pub fn is_public_path(&self, path: &str) -> bool {
-        self.auth.public_paths.iter().any(|public_path| {
-            if public_path.ends_with('*') {
-                path.starts_with(&public_path[..public_path.len() - 1])
-            } else {
-                path == public_path
-            }
-        })
+        self.auth
+            .public_paths
+            .iter()
+            .any(|public_path| path_matches(public_path, path))
     }
 
     /// 检查IP是否在白名单中
@@ -425,6 +594,26 @@ mod tests {
         assert!(!config.is_public_path("/api/v1/users"));
     }
 
+    #[test]
+    fn test_public_market_data_paths() {
+        let mut config = PublicMarketDataConfig::default();
+        assert!(!config.allows(&Method::GET, "/api/v1/market-data/ticker/24hr"));
+
+        config.enabled = true;
+        assert!(config.allows(&Method::GET, "/api/v1/market-data/ticker/24hr"));
+        assert!(config.allows(&Method::GET, "/api/v1/market-data/kline/binance/BTCUSDT/1m"));
+        assert!(config.allows(&Method::GET, "/ws/market-data/trades/binance/BTCUSDT"));
+        assert!(!config.allows(&Method::POST, "/api/v1/market-data/ticker/24hr"));
+        assert!(!config.allows(&Method::GET, "/api/v1/market-data/admin/stats"));
+        assert!(!config.allows(&Method::GET, "/api/v1/trading/orders"));
+        assert!(config.validate().is_ok());
+
+        config.paths.push("/api/v1/market-data/*".to_string());
+        assert!(!config.allows(&Method::GET, "/api/v1/market-data/admin/stats"));
+        config.paths.push("/api/v1/trading/*".to_string());
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_service_endpoint_lookup() {
         let config = GatewayConfig::default();
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use reqwest::Client;
+use serde_json::Value;
+use shared_utils::{current_request_id, REQUEST_ID_HEADER};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::config::GatewayConfig;
+use crate::middleware::auth::UserContext;
+
+/// 下游服务客户端
+///
+/// 供GraphQL解析器调用各服务的REST接口，自动附加用户身份头部并解包统一响应格式。
+#[derive(Clone)]
+pub struct DownstreamClient {
+    config: GatewayConfig,
+    client: Client,
+}
+
+impl DownstreamClient {
+    pub fn new(config: GatewayConfig, client: Client) -> Self {
+        Self { config, client }
+    }
+
+    /// 发送GET请求并返回响应中的data字段
+    pub async fn get(
+        &self,
+        service: &str,
+        path: &str,
+        query: &[(&str, String)],
+        user: Option<&UserContext>,
+    ) -> Result<Value> {
+        let endpoint = self
+            .config
+            .get_service_endpoint(service)
+            .ok_or_else(|| anyhow::anyhow!("Service not found: {}", service))?;
+
+        let url = format!("{}{}", endpoint.url, path);
+        let mut request = self
+            .client
+            .get(&url)
+            .query(query)
+            .timeout(Duration::from_secs(endpoint.timeout))
+            .header("x-source-service", "gateway")
+            .header("x-target-service", service);
+
+        // 下游日志与网关使用同一个请求ID
+        if let Some(request_id) = current_request_id() {
+            request = request.header(REQUEST_ID_HEADER, request_id);
+        }
+
+        if let Some(user) = user {
+            request = request
+                .header("x-user-id", &user.user_id)
+                .header("x-username", &user.username)
+                .header(
+                    "x-user-roles",
+                    serde_json::to_string(&user.roles).unwrap_or_default(),
+                )
+                .header(
+                    "x-user-permissions",
+                    serde_json::to_string(&user.permissions).unwrap_or_default(),
+                );
+        }
+
+        debug!("GraphQL downstream request: {} {}", service, path);
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            warn!("Downstream {} {} returned {}", service, path, status);
+            return Err(anyhow::anyhow!("{} returned status {}", service, status));
+        }
+
+        let body: Value = response.json().await?;
+        Ok(unwrap_data(body))
+    }
+}
+
+/// 解包 {"success": true, "data": ...} 格式的响应
+fn unwrap_data(body: Value) -> Value {
+    match body {
+        Value::Object(mut map) if map.contains_key("success") && map.contains_key("data") => {
+            map.remove("data").unwrap_or(Value::Null)
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unwrap_data() {
+        assert_eq!(
+            unwrap_data(json!({"success": true, "data": [1, 2]})),
+            json!([1, 2])
+        );
+        assert_eq!(unwrap_data(json!({"value": 1})), json!({"value": 1}));
+    }
+}
@@ -0,0 +1,152 @@
+use async_graphql::dataloader::Loader;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::client::DownstreamClient;
+use crate::middleware::auth::UserContext;
+
+/// 将交易对字段转换为字符串，兼容 "BTCUSDT" 和 {"base": "BTC", "quote": "USDT"} 两种格式
+pub fn symbol_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(symbol) => Some(symbol.to_uppercase()),
+        Value::Object(map) => Some(format!(
+            "{}{}",
+            map.get("base")?.as_str()?,
+            map.get("quote")?.as_str()?
+        )
+        .to_uppercase()),
+        _ => None,
+    }
+}
+
+/// 行情批量加载键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TickerKey {
+    pub exchange: String,
+    pub symbol: String,
+}
+
+impl TickerKey {
+    pub fn new(exchange: &str, symbol: &str) -> Self {
+        Self {
+            exchange: exchange.to_lowercase(),
+            symbol: symbol.to_uppercase(),
+        }
+    }
+
+    fn from_ticker(ticker: &Value) -> Option<Self> {
+        Some(Self::new(
+            ticker.get("exchange")?.as_str()?,
+            ticker.get("symbol")?.as_str()?,
+        ))
+    }
+}
+
+/// 24小时行情加载器
+///
+/// 同一请求内的所有行情查询合并为每个交易所一次下游调用。
+pub struct TickerLoader {
+    client: DownstreamClient,
+}
+
+impl TickerLoader {
+    pub fn new(client: DownstreamClient) -> Self {
+        Self { client }
+    }
+}
+
+impl Loader<TickerKey> for TickerLoader {
+    type Value = Value;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[TickerKey]) -> Result<HashMap<TickerKey, Value>, Self::Error> {
+        let mut exchanges: Vec<&str> = keys.iter().map(|k| k.exchange.as_str()).collect();
+        exchanges.sort_unstable();
+        exchanges.dedup();
+
+        let mut result = HashMap::new();
+        for exchange in exchanges {
+            let tickers = self
+                .client
+                .get(
+                    "market-data",
+                    "/api/v1/ticker/24hr",
+                    &[("exchange", exchange.to_string())],
+                    None,
+                )
+                .await
+                .map_err(Arc::new)?;
+
+            for ticker in tickers.as_array().into_iter().flatten() {
+                if let Some(key) = TickerKey::from_ticker(ticker) {
+                    if keys.contains(&key) {
+                        result.insert(key, ticker.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// 仓位批量加载器
+///
+/// 按用户一次拉取全部仓位，订单等节点按交易对关联时复用同一结果。
+pub struct PositionLoader {
+    client: DownstreamClient,
+    user: Option<UserContext>,
+}
+
+impl PositionLoader {
+    pub fn new(client: DownstreamClient, user: Option<UserContext>) -> Self {
+        Self { client, user }
+    }
+}
+
+impl Loader<String> for PositionLoader {
+    type Value = Value;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, symbols: &[String]) -> Result<HashMap<String, Value>, Self::Error> {
+        let positions = self
+            .client
+            .get("trading", "/api/v1/positions", &[], self.user.as_ref())
+            .await
+            .map_err(Arc::new)?;
+
+        Ok(positions
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|position| {
+                let symbol = symbol_string(position.get("symbol")?)?;
+                symbols
+                    .contains(&symbol)
+                    .then(|| (symbol, position.clone()))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_symbol_string() {
+        assert_eq!(symbol_string(&json!("btcusdt")), Some("BTCUSDT".to_string()));
+        assert_eq!(
+            symbol_string(&json!({"base": "ETH", "quote": "USDT"})),
+            Some("ETHUSDT".to_string())
+        );
+        assert_eq!(symbol_string(&json!(1)), None);
+    }
+
+    #[test]
+    fn test_ticker_key_normalization() {
+        assert_eq!(TickerKey::new("Binance", "btcusdt"), TickerKey::new("binance", "BTCUSDT"));
+    }
+}
@@ -0,0 +1,62 @@
+pub mod client;
+pub mod loaders;
+pub mod schema;
+
+use async_graphql::{
+    dataloader::DataLoader, http::GraphiQLSource, EmptyMutation, EmptySubscription, Request,
+    Response, Schema,
+};
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Extension, Json,
+};
+use std::sync::Arc;
+
+use crate::middleware::auth::UserContext;
+use crate::services::ServiceRegistry;
+use crate::state::AppState;
+
+pub use client::DownstreamClient;
+pub use loaders::{PositionLoader, TickerLoader};
+pub use schema::QueryRoot;
+
+/// 网关GraphQL Schema
+pub type GatewaySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// 构建GraphQL Schema
+pub fn build_schema(client: DownstreamClient, registry: Arc<ServiceRegistry>) -> GatewaySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(client)
+        .data(registry)
+        .limit_depth(10)
+        .limit_complexity(500)
+        .finish()
+}
+
+/// GraphQL请求处理
+///
+/// 每个请求创建独立的DataLoader，批量合并与缓存只在单次查询内生效。
+pub async fn graphql_handler(
+    State(state): State<AppState>,
+    user: Option<Extension<UserContext>>,
+    Json(request): Json<Request>,
+) -> Json<Response> {
+    let user = user.map(|Extension(user)| user);
+    let client = state.downstream_client.clone();
+
+    let request = request
+        .data(DataLoader::new(TickerLoader::new(client.clone()), tokio::spawn))
+        .data(DataLoader::new(
+            PositionLoader::new(client, user.clone()),
+            tokio::spawn,
+        ))
+        .data(user);
+
+    Json(state.graphql_schema.execute(request).await)
+}
+
+/// GraphiQL调试页面
+pub async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
@@ -0,0 +1,464 @@
+use async_graphql::{
+    dataloader::DataLoader, Context, Error, Guard, Json, Object, Result, SimpleObject,
+};
+use futures_util::future::try_join;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::client::DownstreamClient;
+use super::loaders::{symbol_string, PositionLoader, TickerKey, TickerLoader};
+use crate::middleware::auth::UserContext;
+use crate::services::ServiceRegistry;
+
+const DEFAULT_EXCHANGE: &str = "binance";
+
+/// 读取字符串字段
+fn str_field(value: &Value, field: &str) -> Option<String> {
+    value.get(field).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// 读取数值字段，兼容数字和字符串两种序列化方式
+fn decimal_field(value: &Value, field: &str) -> Option<Decimal> {
+    match value.get(field)? {
+        Value::String(s) => s.parse().ok(),
+        Value::Null => None,
+        other => serde_json::from_value(other.clone()).ok(),
+    }
+}
+
+fn as_list(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        _ => Vec::new(),
+    }
+}
+
+fn current_user<'a>(ctx: &'a Context<'_>) -> Option<&'a UserContext> {
+    ctx.data_opt::<Option<UserContext>>()
+        .and_then(|user| user.as_ref())
+}
+
+/// 要求已登录
+pub struct AuthGuard;
+
+impl Guard for AuthGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        current_user(ctx)
+            .map(|_| ())
+            .ok_or_else(|| Error::new("Unauthorized"))
+    }
+}
+
+/// 要求指定角色
+pub struct RoleGuard {
+    role: &'static str,
+}
+
+impl RoleGuard {
+    pub fn new(role: &'static str) -> Self {
+        Self { role }
+    }
+}
+
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        match current_user(ctx) {
+            Some(user) if user.has_role(self.role) => Ok(()),
+            Some(_) => Err(Error::new("Forbidden")),
+            None => Err(Error::new("Unauthorized")),
+        }
+    }
+}
+
+/// 查询根
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// 当前登录用户的账户视图
+    #[graphql(guard = "AuthGuard")]
+    async fn viewer(&self, ctx: &Context<'_>) -> Result<Viewer> {
+        let user = current_user(ctx).cloned().ok_or_else(|| Error::new("Unauthorized"))?;
+        Ok(Viewer { user })
+    }
+
+    /// 单个交易对的24小时行情
+    async fn ticker(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = "binance")] exchange: String,
+        symbol: String,
+    ) -> Result<Option<TickerNode>> {
+        load_ticker(ctx, &exchange, &symbol).await
+    }
+
+    /// 多个交易对的24小时行情
+    async fn tickers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = "binance")] exchange: String,
+        symbols: Vec<String>,
+    ) -> Result<Vec<TickerNode>> {
+        let keys: Vec<TickerKey> = symbols
+            .iter()
+            .map(|symbol| TickerKey::new(&exchange, symbol))
+            .collect();
+        let loaded = ctx
+            .data::<DataLoader<TickerLoader>>()?
+            .load_many(keys.clone())
+            .await?;
+
+        Ok(keys
+            .iter()
+            .filter_map(|key| loaded.get(key).cloned().map(TickerNode))
+            .collect())
+    }
+
+    /// 下游服务健康状态（仅管理员）
+    #[graphql(guard = "RoleGuard::new(\"admin\")")]
+    async fn services(&self, ctx: &Context<'_>) -> Result<Vec<ServiceStatusNode>> {
+        let registry = ctx.data::<Arc<ServiceRegistry>>()?;
+        let mut services: Vec<ServiceStatusNode> = registry
+            .get_all_services()
+            .await
+            .into_values()
+            .map(|service| ServiceStatusNode {
+                name: service.name,
+                url: service.url,
+                status: format!("{:?}", service.status),
+                version: service.version,
+            })
+            .collect();
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(services)
+    }
+}
+
+async fn load_ticker(ctx: &Context<'_>, exchange: &str, symbol: &str) -> Result<Option<TickerNode>> {
+    Ok(ctx
+        .data::<DataLoader<TickerLoader>>()?
+        .load_one(TickerKey::new(exchange, symbol))
+        .await?
+        .map(TickerNode))
+}
+
+/// 服务状态
+#[derive(SimpleObject)]
+pub struct ServiceStatusNode {
+    pub name: String,
+    pub url: String,
+    pub status: String,
+    pub version: String,
+}
+
+/// 当前用户视图
+pub struct Viewer {
+    user: UserContext,
+}
+
+impl Viewer {
+    async fn fetch_orders(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<&str>,
+        symbol: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<OrderNode>> {
+        let mut query = vec![("limit", limit.to_string())];
+        if let Some(status) = status {
+            query.push(("status", status.to_string()));
+        }
+        if let Some(symbol) = symbol {
+            query.push(("symbol", symbol.to_uppercase()));
+        }
+
+        let orders = ctx
+            .data::<DownstreamClient>()?
+            .get("trading", "/api/v1/orders", &query, Some(&self.user))
+            .await?;
+        Ok(as_list(orders).into_iter().map(OrderNode).collect())
+    }
+}
+
+#[Object]
+impl Viewer {
+    async fn user_id(&self) -> &str {
+        &self.user.user_id
+    }
+
+    async fn username(&self) -> &str {
+        &self.user.username
+    }
+
+    async fn roles(&self) -> &[String] {
+        &self.user.roles
+    }
+
+    /// 账户信息
+    async fn account(
+        &self,
+        ctx: &Context<'_>,
+        account_type: Option<String>,
+    ) -> Result<Json<Value>> {
+        let query: Vec<(&str, String)> = account_type
+            .map(|t| vec![("account_type", t)])
+            .unwrap_or_default();
+        let account = ctx
+            .data::<DownstreamClient>()?
+            .get("trading", "/api/v1/account", &query, Some(&self.user))
+            .await?;
+        Ok(Json(account))
+    }
+
+    /// 资金余额
+    async fn balance(&self, ctx: &Context<'_>) -> Result<Json<Value>> {
+        let balance = ctx
+            .data::<DownstreamClient>()?
+            .get("trading", "/api/v1/account/balance", &[], Some(&self.user))
+            .await?;
+        Ok(Json(balance))
+    }
+
+    /// 持仓
+    async fn positions(&self, ctx: &Context<'_>) -> Result<Vec<PositionNode>> {
+        let positions = ctx
+            .data::<DownstreamClient>()?
+            .get("trading", "/api/v1/positions", &[], Some(&self.user))
+            .await?;
+        Ok(as_list(positions).into_iter().map(PositionNode).collect())
+    }
+
+    /// 订单列表
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        symbol: Option<String>,
+        #[graphql(default = 50)] limit: u32,
+    ) -> Result<Vec<OrderNode>> {
+        self.fetch_orders(ctx, status.as_deref(), symbol.as_deref(), limit)
+            .await
+    }
+
+    /// 未完成订单（挂单和部分成交）
+    async fn open_orders(
+        &self,
+        ctx: &Context<'_>,
+        symbol: Option<String>,
+    ) -> Result<Vec<OrderNode>> {
+        let (mut pending, partial) = try_join(
+            self.fetch_orders(ctx, Some("PENDING"), symbol.as_deref(), 100),
+            self.fetch_orders(ctx, Some("PARTIALLY_FILLED"), symbol.as_deref(), 100),
+        )
+        .await?;
+        pending.extend(partial);
+        Ok(pending)
+    }
+
+    /// 最近成交订单
+    async fn recent_fills(
+        &self,
+        ctx: &Context<'_>,
+        symbol: Option<String>,
+        #[graphql(default = 20)] limit: u32,
+    ) -> Result<Vec<OrderNode>> {
+        self.fetch_orders(ctx, Some("FILLED"), symbol.as_deref(), limit)
+            .await
+    }
+}
+
+/// 持仓节点
+pub struct PositionNode(Value);
+
+#[Object]
+impl PositionNode {
+    async fn id(&self) -> Option<String> {
+        str_field(&self.0, "id")
+    }
+
+    async fn symbol(&self) -> Option<String> {
+        self.0.get("symbol").and_then(symbol_string)
+    }
+
+    async fn side(&self) -> Option<String> {
+        str_field(&self.0, "side")
+    }
+
+    async fn size(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "size")
+    }
+
+    async fn entry_price(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "entry_price")
+    }
+
+    async fn mark_price(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "mark_price")
+    }
+
+    async fn unrealized_pnl(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "unrealized_pnl")
+    }
+
+    async fn leverage(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "leverage")
+    }
+
+    /// 原始仓位数据
+    async fn raw(&self) -> Json<Value> {
+        Json(self.0.clone())
+    }
+
+    /// 对应交易对的行情
+    async fn ticker(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = "binance")] exchange: String,
+    ) -> Result<Option<TickerNode>> {
+        match self.0.get("symbol").and_then(symbol_string) {
+            Some(symbol) => load_ticker(ctx, &exchange, &symbol).await,
+            None => Ok(None),
+        }
+    }
+}
+
+/// 订单节点
+pub struct OrderNode(Value);
+
+#[Object]
+impl OrderNode {
+    async fn id(&self) -> Option<String> {
+        str_field(&self.0, "id")
+    }
+
+    async fn client_order_id(&self) -> Option<String> {
+        str_field(&self.0, "client_order_id")
+    }
+
+    async fn symbol(&self) -> Option<String> {
+        self.0.get("symbol").and_then(symbol_string)
+    }
+
+    async fn side(&self) -> Option<String> {
+        str_field(&self.0, "side")
+    }
+
+    async fn order_type(&self) -> Option<String> {
+        str_field(&self.0, "order_type")
+    }
+
+    async fn status(&self) -> Option<String> {
+        str_field(&self.0, "status")
+    }
+
+    async fn price(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "price")
+    }
+
+    async fn quantity(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "quantity")
+    }
+
+    async fn filled_quantity(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "filled_quantity")
+    }
+
+    async fn average_price(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "average_price")
+    }
+
+    async fn created_at(&self) -> Option<String> {
+        str_field(&self.0, "created_at")
+    }
+
+    /// 原始订单数据
+    async fn raw(&self) -> Json<Value> {
+        Json(self.0.clone())
+    }
+
+    /// 同一交易对的持仓
+    async fn position(&self, ctx: &Context<'_>) -> Result<Option<PositionNode>> {
+        let symbol = match self.0.get("symbol").and_then(symbol_string) {
+            Some(symbol) => symbol,
+            None => return Ok(None),
+        };
+        Ok(ctx
+            .data::<DataLoader<PositionLoader>>()?
+            .load_one(symbol)
+            .await?
+            .map(PositionNode))
+    }
+
+    /// 对应交易对的行情
+    async fn ticker(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = "binance")] exchange: String,
+    ) -> Result<Option<TickerNode>> {
+        match self.0.get("symbol").and_then(symbol_string) {
+            Some(symbol) => load_ticker(ctx, &exchange, &symbol).await,
+            None => Ok(None),
+        }
+    }
+}
+
+/// 24小时行情节点
+pub struct TickerNode(Value);
+
+#[Object]
+impl TickerNode {
+    async fn exchange(&self) -> String {
+        str_field(&self.0, "exchange")
+            .map(|e| e.to_lowercase())
+            .unwrap_or_else(|| DEFAULT_EXCHANGE.to_string())
+    }
+
+    async fn symbol(&self) -> Option<String> {
+        str_field(&self.0, "symbol")
+    }
+
+    async fn last_price(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "last_price")
+    }
+
+    async fn price_change_percent(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "price_change_percent")
+    }
+
+    async fn high_price(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "high_price")
+    }
+
+    async fn low_price(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "low_price")
+    }
+
+    async fn volume(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "volume")
+    }
+
+    async fn quote_volume(&self) -> Option<Decimal> {
+        decimal_field(&self.0, "quote_volume")
+    }
+
+    /// 原始行情数据
+    async fn raw(&self) -> Json<Value> {
+        Json(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decimal_field() {
+        let value = json!({"a": 1.5, "b": "2.25", "c": null});
+        assert_eq!(decimal_field(&value, "a"), Some(Decimal::new(15, 1)));
+        assert_eq!(decimal_field(&value, "b"), Some(Decimal::new(225, 2)));
+        assert_eq!(decimal_field(&value, "c"), None);
+        assert_eq!(decimal_field(&value, "d"), None);
+    }
+}
@@ -1,4 +1,5 @@
 mod config;
+mod graphql;
 mod handlers;
 mod middleware;
 mod routes;
@@ -8,7 +9,10 @@ mod websocket;
 
 use anyhow::Result;
 use axum::{extract::connect_info::ConnectInfo, Router};
-use shared_utils::{LoggingInitializer, AppMetrics};
+use shared_utils::{
+    check_config_requested, internal_auth_middleware, log_level_routes, request_span_middleware,
+    run_config_check, AppMetrics, LoggingInitializer,
+};
 use std::{net::SocketAddr, sync::Arc};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
@@ -30,31 +34,44 @@ async fn main() -> Result<()> {
     // 加载环境变量
     dotenvy::dotenv().ok();
 
+    // 检查配置后退出
+    if check_config_requested() {
+        let (config, report) = GatewayConfig::load_checked();
+        std::process::exit(run_config_check("gateway", Some(&config), &report));
+    }
+
     // 初始化日志
-    LoggingInitializer::init_dev()?;
+    let log_handle = LoggingInitializer::init_from_env()?;
 
     // 加载配置
     let config = GatewayConfig::load()?;
     info!("Gateway configuration loaded");
+    log_handle.apply(&config.logging.level, &config.logging.modules)?;
 
     // 初始化指标
     let metrics = Arc::new(AppMetrics::new()?);
     info!("Metrics initialized");
 
     // 创建应用状态
-    let state = AppState::new(config.clone(), metrics.clone()).await?;
+    let state = AppState::new(config.clone(), metrics.clone(), log_handle.request_logs()).await?;
     info!("Application state initialized");
 
     // 创建中间件层
     let middleware = ServiceBuilder::new()
         .layer(TraceLayer::new_for_http())
         .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        .layer(axum::middleware::from_fn(request_span_middleware))
         .layer(axum::middleware::from_fn(request_id_middleware))
+        .layer(axum::middleware::from_fn_with_state(
+            state.internal_auth.clone(),
+            internal_auth_middleware,
+        ))
         .layer(axum::middleware::from_fn_with_state(state.clone(), rate_limit_middleware))
         .layer(axum::middleware::from_fn_with_state(state.clone(), auth_middleware));
 
     // 创建路由
     let app = create_routes()
+        .merge(log_level_routes("/admin/log-level", log_handle))
         .layer(middleware)
         .with_state(state);
 
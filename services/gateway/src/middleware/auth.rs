@@ -5,10 +5,10 @@ use axum::{
     response::Response,
 };
 use shared_protocols::http::{ApiError, ApiResponse};
-use shared_utils::Claims;
+use shared_utils::{Claims, LogContext};
 use tracing::{debug, warn};
 
-use crate::state::AppState;
+use crate::{middleware::rate_limit::is_public_market_data, state::AppState};
 
 /// 认证中间件
 #[derive(Clone)]
@@ -36,6 +36,17 @@ pub async fn auth_middleware(
         return Ok(next.run(request).await);
     }
 
+    // 指标和管理接口已由内部认证处理
+    if state.internal_auth.protects(path) {
+        return Ok(next.run(request).await);
+    }
+
+    // 未携带令牌的只读行情请求走公开档位，不附带用户上下文
+    if is_public_market_data(&state, &request) {
+        debug!("Public market data accessed: {}", path);
+        return Ok(next.run(request).await);
+    }
+
     // 提取Authorization头
     let headers = request.headers();
     let auth_header = match extract_auth_header(headers) {
@@ -46,8 +57,14 @@ pub async fn auth_middleware(
         }
     };
 
-    // 验证JWT token
-    let claims = match state.jwt_service.verify_token(&auth_header) {
+    // 验证JWT token，命中缓存时跳过签名校验
+    let claims = match state
+        .token_cache
+        .get_or_try_load(auth_header.clone(), || async {
+            state.jwt_service.verify_token(&auth_header)
+        })
+        .await
+    {
         Ok(claims) => claims,
         Err(e) => {
             warn!("Invalid JWT token: {}", e);
@@ -55,6 +72,13 @@ pub async fn auth_middleware(
         }
     };
 
+    // 缓存的令牌可能已过期
+    if claims.exp <= chrono::Utc::now().timestamp() as usize {
+        state.token_cache.invalidate(&auth_header);
+        warn!("Expired JWT token for user: {}", claims.sub);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
     // 将用户信息添加到请求扩展中
     request.extensions_mut().insert(UserContext {
         user_id: claims.sub.clone(),
@@ -64,6 +88,7 @@ pub async fn auth_middleware(
         permissions: claims.permissions.clone(),
     });
 
+    LogContext::record_user(&claims.sub);
     debug!("User authenticated: {} ({})", claims.username, claims.sub);
 
     // 记录认证指标
@@ -1,6 +1,6 @@
 use axum::{
     extract::{ConnectInfo, Request, State},
-    http::StatusCode,
+    http::{header::AUTHORIZATION, StatusCode},
     middleware::Next,
     response::Response,
 };
@@ -46,14 +46,21 @@ pub async fn rate_limit_middleware(
         .get::<crate::middleware::auth::UserContext>()
         .map(|ctx| ctx.user_id.clone());
 
+    // 未携带令牌访问公开行情时按IP使用公开档位
+    let public = is_public_market_data(&state, &request);
+
     // 构建限流键
-    let rate_limit_key = match user_id {
-        Some(uid) => format!("user:{}", uid),
-        None => format!("ip:{}", client_ip),
+    let (rate_limit_key, limit) = match user_id {
+        Some(uid) => (format!("user:{}", uid), state.config.rate_limit.requests_per_minute),
+        None if public => (
+            format!("public:ip:{}", client_ip),
+            state.config.public_market_data.requests_per_minute,
+        ),
+        None => (format!("ip:{}", client_ip), state.config.rate_limit.requests_per_minute),
     };
 
     // 检查限流
-    match state.rate_limiter.check_rate_limit(&rate_limit_key).await {
+    match state.rate_limiter.check_rate_limit_with(&rate_limit_key, limit).await {
         Ok(allowed) => {
             if allowed {
                 debug!("Rate limit check passed for: {}", rate_limit_key);
@@ -89,6 +96,15 @@ pub async fn rate_limit_middleware(
     }
 }
 
+/// 未携带令牌且请求的是公开行情接口
+pub fn is_public_market_data(state: &AppState, request: &Request) -> bool {
+    !request.headers().contains_key(AUTHORIZATION)
+        && state
+            .config
+            .public_market_data
+            .allows(request.method(), request.uri().path())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
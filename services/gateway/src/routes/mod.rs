@@ -1,13 +1,16 @@
 pub mod auth;
 pub mod health;
 pub mod metrics;
+pub mod overview;
 pub mod proxy;
+pub mod trace;
 
 use axum::{
     routing::{get, post},
     Router,
 };
 
+use crate::graphql;
 use crate::state::AppState;
 
 /// 创建所有路由
@@ -20,6 +23,11 @@ pub fn create_routes() -> Router<AppState> {
         .route("/api/v1/auth/login", post(auth::login))
         .route("/api/v1/auth/logout", post(auth::logout))
         .route("/api/v1/auth/refresh", post(auth::refresh_token))
+        // GraphQL
+        .route(
+            "/graphql",
+            get(graphql::graphiql).post(graphql::graphql_handler),
+        )
         // 服务代理路由
         .route(
             "/api/v1/:service/*path",
@@ -32,12 +40,14 @@ pub fn create_routes() -> Router<AppState> {
         // WebSocket代理
         .route("/ws/:service/*path", get(proxy::proxy_websocket))
         // 管理接口
+        .route("/admin/overview", get(overview::admin_overview))
         .route("/admin/services", get(health::list_services))
         .route(
             "/admin/circuit-breakers",
             get(health::circuit_breaker_status),
         )
         .route("/admin/rate-limits", get(health::rate_limit_status))
+        .route("/admin/trace/:request_id", get(trace::request_trace))
         .route("/admin/websocket/stats", get(health::websocket_stats))
         .route(
             "/admin/websocket/connections",
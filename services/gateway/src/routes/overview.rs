@@ -0,0 +1,191 @@
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap},
+    Json,
+};
+use futures_util::future::join_all;
+use serde_json::{json, Value};
+use shared_utils::internal_auth::INTERNAL_TOKEN_HEADER;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::state::AppState;
+
+/// 参与汇总的服务统计接口：(服务, 路径)
+const STATS_SOURCES: [(&str, &str); 2] = [
+    ("trading", "/api/v1/admin/stats"),
+    ("market-data", "/health/detailed"),
+];
+
+/// 查询下游服务的超时，单个服务无响应时不拖慢整个总览
+const OVERVIEW_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 请求下游服务的状态接口，转发调用方的内部认证头，返回响应内容和耗时
+async fn fetch_service_json(
+    state: &AppState,
+    service: &str,
+    path: &str,
+    headers: &HeaderMap,
+) -> (Result<Value, String>, u64) {
+    let started = Instant::now();
+    let result = async {
+        let endpoint = state
+            .config
+            .get_service_endpoint(service)
+            .ok_or_else(|| format!("Service not found: {}", service))?;
+
+        let mut request = state
+            .service_registry
+            .client
+            .get(format!("{}{}", endpoint.url, path))
+            .timeout(OVERVIEW_TIMEOUT);
+        for name in [INTERNAL_TOKEN_HEADER, AUTHORIZATION.as_str()] {
+            if let Some(value) = headers.get(name) {
+                request = request.header(name, value.as_bytes());
+            }
+        }
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("returned status {}", response.status()));
+        }
+        response.json::<Value>().await.map_err(|e| e.to_string())
+    }
+    .await;
+    (result, started.elapsed().as_millis() as u64)
+}
+
+/// 从服务统计中摘取总览字段，缺失的统计为 null
+///
+/// `trading` 为交易引擎 `/api/v1/admin/stats` 的响应，`market_data` 为行情服务
+/// `/health/detailed` 的响应，两者都包在 `data` 字段中。
+fn summarize(trading: Option<&Value>, market_data: Option<&Value>, gateway_ws: Value) -> Value {
+    let field = |source: Option<&Value>, pointer: &str| {
+        source
+            .and_then(|value| value.pointer(pointer))
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+    let component = |name: &str| field(market_data, &format!("/data/components/{}/details", name));
+
+    json!({
+        "connectors": {
+            "market_data": field(market_data, "/data/components/exchange_manager/details/connection_status"),
+            "trading": field(trading, "/data/venues")
+        },
+        "orders": field(trading, "/data/orders"),
+        "risk_alerts": field(trading, "/data/risk_alerts"),
+        "websocket": {
+            "market_data_clients": field(market_data, "/data/metrics/active_connections"),
+            "gateway": gateway_ws
+        },
+        "storage_lag": {
+            "trading_outbox": field(trading, "/data/outbox"),
+            "market_data_writes": component("storage_writes"),
+            "market_data_kafka": component("kafka_egress")
+        }
+    })
+}
+
+/// 运维总览：并发查询所有已注册服务的健康检查和统计接口，汇总成一份文档
+///
+/// 单个服务失败不影响其余部分，失败原因记入 errors，总体状态降为 degraded。
+pub async fn admin_overview(State(state): State<AppState>, headers: HeaderMap) -> Json<Value> {
+    let services: Vec<String> = {
+        let mut names: Vec<String> = state.service_registry.get_all_services().await.into_keys().collect();
+        names.sort();
+        names
+    };
+
+    let health_lookups = services
+        .iter()
+        .map(|service| fetch_service_json(&state, service, "/health", &headers));
+    let stats_lookups = STATS_SOURCES
+        .iter()
+        .map(|(service, path)| fetch_service_json(&state, service, path, &headers));
+    let (health_results, stats_results) = tokio::join!(join_all(health_lookups), join_all(stats_lookups));
+
+    let mut status = "healthy";
+    let mut errors = BTreeMap::new();
+    let mut service_status = BTreeMap::new();
+    for (service, (result, latency_ms)) in services.iter().zip(health_results) {
+        let entry = match result {
+            Ok(_) => json!({ "status": "healthy", "latency_ms": latency_ms }),
+            Err(e) => {
+                status = "degraded";
+                json!({ "status": "unhealthy", "latency_ms": latency_ms, "error": e })
+            }
+        };
+        service_status.insert(service.clone(), entry);
+    }
+
+    let mut stats = BTreeMap::new();
+    for ((service, path), (result, _)) in STATS_SOURCES.iter().zip(stats_results) {
+        match result {
+            Ok(value) => {
+                stats.insert(*service, value);
+            }
+            Err(e) => {
+                status = "degraded";
+                errors.insert(format!("{}{}", service, path), e);
+            }
+        }
+    }
+
+    let gateway_ws = state.websocket_manager.get_pool_stats().await;
+    let mut overview = summarize(
+        stats.get("trading"),
+        stats.get("market-data"),
+        json!({
+            "total_connections": gateway_ws.total_connections,
+            "active_connections": gateway_ws.active_connections
+        }),
+    );
+    overview["status"] = json!(status);
+    overview["services"] = json!(service_status);
+    overview["errors"] = json!(errors);
+    overview["timestamp"] = json!(chrono::Utc::now().to_rfc3339());
+
+    Json(overview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_service_stats() {
+        let trading = json!({
+            "success": true,
+            "data": {
+                "orders": { "per_second": 2, "per_minute": 40, "per_hour": 900, "active_users": 12 },
+                "risk_alerts": { "open": 3, "by_severity": { "high": 1, "low": 2 } },
+                "outbox": { "pending": 5, "oldest_pending_at": null, "lag_seconds": 0 },
+                "venues": [{ "name": "binance", "environment": "live", "slow": false }]
+            }
+        });
+        let market_data = json!({
+            "success": true,
+            "data": {
+                "components": {
+                    "exchange_manager": { "details": { "connection_status": { "binance": true, "okx": false } } },
+                    "storage_writes": { "details": { "wal_pending": 7 } }
+                },
+                "metrics": { "active_connections": 42 }
+            }
+        });
+
+        let overview = summarize(Some(&trading), Some(&market_data), json!({ "active_connections": 1 }));
+        assert_eq!(overview["orders"]["per_minute"], 40);
+        assert_eq!(overview["risk_alerts"]["open"], 3);
+        assert_eq!(overview["connectors"]["market_data"]["okx"], false);
+        assert_eq!(overview["websocket"]["market_data_clients"], 42);
+        assert_eq!(overview["storage_lag"]["market_data_writes"]["wal_pending"], 7);
+        assert!(overview["storage_lag"]["market_data_kafka"].is_null());
+
+        // 服务不可用时对应字段为 null
+        let overview = summarize(None, Some(&market_data), Value::Null);
+        assert!(overview["orders"].is_null());
+        assert!(overview["connectors"]["trading"].is_null());
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, Query, Request, State},
+    extract::{ws::WebSocketUpgrade, Path, Query, Request, State},
     http::{Method, StatusCode},
     response::Response,
 };
@@ -70,8 +70,9 @@ pub async fn proxy_patch(
 pub async fn proxy_websocket(
     State(state): State<AppState>,
     Path(params): Path<HashMap<String, String>>,
+    ws_upgrade: WebSocketUpgrade,
     request: Request,
 ) -> Result<Response, StatusCode> {
     debug!("Proxying WebSocket request");
-    ServiceProxy::proxy_websocket(State(state), Path(params), request).await
+    ServiceProxy::proxy_websocket(State(state), Path(params), ws_upgrade, request).await
 }
\ No newline at end of file
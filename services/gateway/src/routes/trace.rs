@@ -0,0 +1,101 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::AUTHORIZATION, HeaderMap},
+    Json,
+};
+use futures_util::future::join_all;
+use serde::Serialize;
+use serde_json::json;
+use shared_utils::{internal_auth::INTERNAL_TOKEN_HEADER, RequestLogEntry, RequestTrace};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::state::AppState;
+
+/// 提供请求日志查询接口的下游服务
+const TRACED_SERVICES: [&str; 2] = ["trading", "market-data"];
+
+/// 查询下游服务日志的超时
+const TRACE_LOOKUP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 带服务名的请求日志
+#[derive(Debug, Serialize)]
+struct ServiceLogEntry {
+    service: String,
+    #[serde(flatten)]
+    entry: RequestLogEntry,
+}
+
+/// 查询下游服务中该请求的日志，转发调用方的内部认证头
+async fn fetch_service_trace(
+    state: &AppState,
+    service: &str,
+    request_id: &str,
+    headers: &HeaderMap,
+) -> Result<RequestTrace, String> {
+    let endpoint = state
+        .config
+        .get_service_endpoint(service)
+        .ok_or_else(|| format!("Service not found: {}", service))?;
+
+    let mut request = state
+        .service_registry
+        .client
+        .get(format!("{}/api/v1/admin/trace/{}", endpoint.url, request_id))
+        .timeout(TRACE_LOOKUP_TIMEOUT);
+    for name in [INTERNAL_TOKEN_HEADER, AUTHORIZATION.as_str()] {
+        if let Some(value) = headers.get(name) {
+            request = request.header(name, value.as_bytes());
+        }
+    }
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("returned status {}", response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+/// 按请求ID汇总网关和各服务的日志，按时间排序
+pub async fn request_trace(
+    State(state): State<AppState>,
+    Path(request_id): Path<String>,
+    headers: HeaderMap,
+) -> Json<serde_json::Value> {
+    let lookups = TRACED_SERVICES
+        .iter()
+        .map(|service| fetch_service_trace(&state, service, &request_id, &headers));
+    let results = join_all(lookups).await;
+
+    let mut traces = vec![("gateway", state.request_logs.get(&request_id))];
+    let mut errors = BTreeMap::new();
+    for (service, result) in TRACED_SERVICES.iter().zip(results) {
+        match result {
+            Ok(trace) => traces.push((*service, trace)),
+            Err(e) => {
+                errors.insert(service.to_string(), e);
+            }
+        }
+    }
+
+    let mut truncated = BTreeMap::new();
+    let mut entries = Vec::new();
+    for (service, trace) in traces {
+        if trace.truncated > 0 {
+            truncated.insert(service.to_string(), trace.truncated);
+        }
+        entries.extend(trace.entries.into_iter().map(|entry| ServiceLogEntry {
+            service: service.to_string(),
+            entry,
+        }));
+    }
+    entries.sort_by_key(|entry| entry.entry.timestamp);
+
+    Json(json!({
+        "request_id": request_id,
+        "entries": entries,
+        "truncated": truncated,
+        "errors": errors,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }))
+}
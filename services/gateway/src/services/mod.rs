@@ -2,8 +2,10 @@ pub mod circuit_breaker;
 pub mod proxy;
 pub mod rate_limiter;
 pub mod service_registry;
+pub mod shard_router;
 
 pub use circuit_breaker::CircuitBreaker;
 pub use proxy::ServiceProxy;
 pub use rate_limiter::RateLimiter;
 pub use service_registry::ServiceRegistry;
+pub use shard_router::ShardRouter;
@@ -1,12 +1,13 @@
 use anyhow::Result;
 use axum::{
     body::Body,
-    extract::{Path, Query, Request, State},
+    extract::{ws::WebSocketUpgrade, Path, Query, Request, State},
     http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri},
     response::Response,
 };
 use reqwest::Client;
 use serde_json::Value;
+use shared_utils::RetryPolicy;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
@@ -14,8 +15,9 @@ use tracing::{debug, error, info, warn};
 use crate::{
     middleware::auth::UserContext,
     middleware::request_id::RequestId,
-    services::CircuitBreaker,
+    services::{shard_router::parse_query, CircuitBreaker},
     state::AppState,
+    websocket::MessageRoute,
 };
 
 /// 服务代理
@@ -79,7 +81,14 @@ impl ServiceProxy {
         }
 
         // 构建目标URL
-        let target_url = build_target_url(&service_info.url, &path, &query)?;
+        // 行情请求转发到负责该交易对的分片实例
+        let base_url = match service_name.as_str() {
+            "market-data" => state.shard_router.route(&path, &query).await,
+            _ => None,
+        }
+        .unwrap_or_else(|| service_info.url.clone());
+
+        let target_url = build_target_url(&base_url, &path, &query)?;
 
         // 执行代理请求
         let start_time = Instant::now();
@@ -143,6 +152,7 @@ impl ServiceProxy {
     pub async fn proxy_websocket(
         State(state): State<AppState>,
         Path(params): Path<HashMap<String, String>>,
+        ws_upgrade: WebSocketUpgrade,
         request: Request,
     ) -> Result<Response, StatusCode> {
         let service_name = params.get("service")
@@ -159,26 +169,41 @@ impl ServiceProxy {
             }
         };
 
-        // 检查WebSocket升级头
-        let headers = request.headers();
-        if !headers.get("upgrade")
-            .and_then(|v| v.to_str().ok())
-            .map(|v| v.to_lowercase() == "websocket")
-            .unwrap_or(false)
-        {
-            return Err(StatusCode::BAD_REQUEST);
-        }
-
-        // 构建WebSocket目标URL
-        let ws_url = service_info.url.replace("http://", "ws://").replace("https://", "wss://");
+        // 构建WebSocket目标URL，行情订阅转发到负责该交易对的分片实例，
+        // 路径和查询参数中没有交易对时按客户端的首条订阅消息选择
         let target_path = request.uri().path().replace(&format!("/ws/{}", service_name), "");
+        let (base_url, route) = match service_name.as_str() {
+            "market-data" => {
+                let query = parse_query(request.uri().query());
+                match state.shard_router.route(request.uri().path(), &query).await {
+                    Some(url) => (url, None),
+                    None => (
+                        service_info.url.clone(),
+                        Some(MessageRoute {
+                            router: state.shard_router.clone(),
+                            path: target_path.clone(),
+                        }),
+                    ),
+                }
+            }
+            _ => (service_info.url.clone(), None),
+        };
+        let ws_url = base_url.replace("http://", "ws://").replace("https://", "wss://");
         let target_url = format!("{}{}", ws_url, target_path);
 
         info!("Proxying WebSocket to: {}", target_url);
 
-        // WebSocket升级需要在路由层处理，这里返回错误
-        error!("WebSocket upgrade should be handled at router level");
-        Err(StatusCode::BAD_REQUEST)
+        // 上游服务按用户推送，握手时转发网关认证后的身份
+        let upstream_headers = websocket_upstream_headers(&request);
+
+        state
+            .websocket_manager
+            .handle_connection(ws_upgrade, service_name, &target_url, route, upstream_headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to proxy WebSocket for {}: {}", service_name, e);
+                StatusCode::BAD_GATEWAY
+            })
     }
 }
 
@@ -188,6 +213,44 @@ impl Default for ServiceProxy {
     }
 }
 
+/// WebSocket上游握手的请求头：请求ID和认证后的用户身份
+///
+/// 客户端的请求头不转发，用户身份只来自认证中间件写入的 UserContext。
+fn websocket_upstream_headers(request: &Request) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(request_id) = request.extensions().get::<RequestId>() {
+        if let Ok(value) = HeaderValue::from_str(&request_id.to_string()) {
+            headers.insert(HeaderName::from_static("x-request-id"), value);
+        }
+    }
+    if let Some(user) = request.extensions().get::<UserContext>() {
+        RequestTransformer::add_auth_headers(&mut headers, user);
+    }
+    headers
+}
+
+/// 上游HTTP请求头：转发客户端请求头（排除逐跳头部和身份头部），再由网关设置请求ID和用户身份
+fn upstream_request_headers(
+    client_headers: &HeaderMap,
+    request_id: &str,
+    user_context: Option<&UserContext>,
+) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in client_headers {
+        if should_forward_header(name.as_str()) {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+
+    if let Ok(request_id) = HeaderValue::from_str(request_id) {
+        headers.insert(HeaderName::from_static("x-request-id"), request_id);
+    }
+    if let Some(user) = user_context {
+        RequestTransformer::add_auth_headers(&mut headers, user);
+    }
+    headers
+}
+
 /// 执行代理请求
 async fn execute_proxy_request(
     state: &AppState,
@@ -199,31 +262,7 @@ async fn execute_proxy_request(
     user_context: Option<&UserContext>,
 ) -> Result<Response, StatusCode> {
     // 准备请求头
-    let mut headers = HeaderMap::new();
-    
-    // 复制原始请求头（排除某些头部）
-    for (name, value) in request.headers() {
-        if should_forward_header(name.as_str()) {
-            headers.insert(name.clone(), value.clone());
-        }
-    }
-
-    // 添加代理相关头部
-    headers.insert(
-        HeaderName::from_static("x-request-id"),
-        HeaderValue::from_str(request_id).unwrap(),
-    );
-
-    if let Some(user) = user_context {
-        headers.insert(
-            HeaderName::from_static("x-user-id"),
-            HeaderValue::from_str(&user.user_id).unwrap(),
-        );
-        headers.insert(
-            HeaderName::from_static("x-username"),
-            HeaderValue::from_str(&user.username).unwrap(),
-        );
-    }
+    let headers = upstream_request_headers(request.headers(), request_id, user_context);
 
     // 获取请求体
     let body_bytes = match axum::body::to_bytes(request.into_body(), usize::MAX).await {
@@ -331,7 +370,13 @@ fn should_forward_header(header_name: &str) -> bool {
         "transfer-encoding",
     ];
 
-    !skip_headers.contains(&header_name.to_lowercase().as_str())
+    let name = header_name.to_lowercase();
+    !skip_headers.contains(&name.as_str()) && !is_identity_header(&name)
+}
+
+/// 用户身份请求头，只能由网关根据JWT设置，客户端传入的一律丢弃
+fn is_identity_header(name: &str) -> bool {
+    name.starts_with("x-user-") || name == "x-username"
 }
 
 /// 检查是否应该转发响应头
@@ -351,54 +396,27 @@ fn should_forward_response_header(header_name: &str) -> bool {
 
 /// 请求重试器
 pub struct RequestRetrier {
-    max_retries: u32,
-    base_delay: Duration,
-    max_delay: Duration,
+    policy: RetryPolicy,
 }
 
 impl RequestRetrier {
     pub fn new(max_retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
         Self {
-            max_retries,
-            base_delay,
-            max_delay,
+            policy: RetryPolicy::exponential(base_delay, max_delay).with_max_retries(max_retries),
         }
     }
 
-    /// 执行带重试的请求
+    /// 执行带重试的请求，指数退避并加入全抖动
     pub async fn execute_with_retry<F, Fut, T, E>(
         &self,
-        mut operation: F,
+        operation: F,
     ) -> Result<T, E>
     where
         F: FnMut() -> Fut,
         Fut: std::future::Future<Output = Result<T, E>>,
-        E: std::fmt::Debug,
+        E: std::fmt::Display,
     {
-        let mut last_error = None;
-        
-        for attempt in 0..=self.max_retries {
-            match operation().await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    last_error = Some(e);
-                    
-                    if attempt < self.max_retries {
-                        let delay = self.calculate_delay(attempt);
-                        debug!("Request failed, retrying in {:?} (attempt {})", delay, attempt + 1);
-                        tokio::time::sleep(delay).await;
-                    }
-                }
-            }
-        }
-
-        Err(last_error.unwrap())
-    }
-
-    /// 计算重试延迟（指数退避）
-    fn calculate_delay(&self, attempt: u32) -> Duration {
-        let delay = self.base_delay * 2_u32.pow(attempt);
-        std::cmp::min(delay, self.max_delay)
+        self.policy.retry(operation).await
     }
 }
 
@@ -492,6 +510,35 @@ mod tests {
         assert!(should_forward_header("authorization"));
         assert!(!should_forward_header("host"));
         assert!(!should_forward_header("connection"));
+        assert!(!should_forward_header("X-User-Id"));
+        assert!(!should_forward_header("x-user-roles"));
+        assert!(!should_forward_header("x-username"));
+    }
+
+    #[test]
+    fn test_upstream_request_headers_replace_client_identity() {
+        let mut client_headers = HeaderMap::new();
+        client_headers.insert("content-type", HeaderValue::from_static("application/json"));
+        client_headers.insert("x-user-id", HeaderValue::from_static("someone-else"));
+        client_headers.insert("x-user-permissions", HeaderValue::from_static(r#"["market_data:realtime"]"#));
+        let user = UserContext {
+            user_id: "user-42".to_string(),
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            roles: vec!["trader".to_string()],
+            permissions: vec![],
+        };
+
+        let headers = upstream_request_headers(&client_headers, "req-1", Some(&user));
+        assert_eq!(headers["content-type"], "application/json");
+        assert_eq!(headers["x-request-id"], "req-1");
+        assert_eq!(headers["x-user-id"], "user-42");
+        assert_eq!(headers["x-user-roles"], r#"["trader"]"#);
+        assert_eq!(headers["x-user-permissions"], "[]");
+
+        // 未认证的请求不带任何身份头部
+        let headers = upstream_request_headers(&client_headers, "req-2", None);
+        assert!(headers.keys().all(|name| !is_identity_header(name.as_str())));
     }
 
     #[test]
@@ -524,6 +571,103 @@ mod tests {
         assert_eq!(result, Ok("success"));
         assert_eq!(attempt_count, 2);
     }
+
+    /// 启动回显握手请求头的上游服务，经网关WebSocket代理连接 `path` 后返回上游收到的身份
+    async fn upstream_identity_via_proxy(path: &str, client_headers: &[(&str, &str)]) -> Value {
+        use axum::{extract::ws::Message, routing::get, Router};
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest};
+
+        let upstream = Router::new().route(
+            path,
+            get(|ws: WebSocketUpgrade, headers: HeaderMap| async move {
+                let header = |name: &str| {
+                    headers
+                        .get(name)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string)
+                };
+                let seen = serde_json::json!({
+                    "user_id": header("x-user-id"),
+                    "roles": header("x-user-roles"),
+                });
+                ws.on_upgrade(move |mut socket| async move {
+                    let _ = socket.send(Message::Text(seen.to_string())).await;
+                })
+            }),
+        );
+        let upstream_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream_listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(upstream_listener, upstream).await });
+
+        // 模拟认证中间件写入 UserContext 后进入 WebSocket 代理
+        let target_url = format!("ws://{}{}", upstream_addr, path);
+        let gateway = Router::new().route(
+            &format!("/ws/trading{}", path),
+            get(move |ws: WebSocketUpgrade, mut request: Request| async move {
+                request.extensions_mut().insert(UserContext {
+                    user_id: "user-42".to_string(),
+                    username: "alice".to_string(),
+                    email: "alice@example.com".to_string(),
+                    roles: vec!["trader".to_string()],
+                    permissions: vec![],
+                });
+                let headers = websocket_upstream_headers(&request);
+                crate::websocket::WebSocketManager::new()
+                    .handle_connection(ws, "trading", &target_url, None, headers)
+                    .await
+                    .unwrap()
+            }),
+        );
+        let gateway_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let gateway_addr = gateway_listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(gateway_listener, gateway).await });
+
+        let mut request = format!("ws://{}/ws/trading{}", gateway_addr, path)
+            .into_client_request()
+            .unwrap();
+        for (name, value) in client_headers {
+            request.headers_mut().insert(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        let (mut client, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+        match client.next().await {
+            Some(Ok(tungstenite::Message::Text(text))) => serde_json::from_str(&text).unwrap(),
+            other => panic!("unexpected upstream message: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_proxy_forwards_authenticated_identity() {
+        let seen =
+            upstream_identity_via_proxy("/ws/account/equity", &[("x-user-id", "someone-else")])
+                .await;
+
+        assert_eq!(seen["user_id"], "user-42");
+        assert_eq!(seen["roles"], r#"["trader"]"#);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_proxy_ignores_client_tier_header() {
+        // 行情订阅配额按 x-user-roles 确定等级，客户端自带的角色不能提升等级
+        let seen = upstream_identity_via_proxy(
+            "/ws/market-data",
+            &[("x-user-roles", r#"["premium"]"#), ("x-user-id", "someone-else")],
+        )
+        .await;
+
+        assert_eq!(seen["roles"], r#"["trader"]"#);
+        assert_eq!(seen["user_id"], "user-42");
+    }
+
+    #[tokio::test]
+    async fn test_websocket_proxy_forwards_identity_to_account_notifications() {
+        let seen = upstream_identity_via_proxy("/ws/account", &[]).await;
+
+        assert_eq!(seen["user_id"], "user-42");
+    }
 }
 
 /// 转换Axum HTTP方法到Reqwest HTTP方法
@@ -545,23 +689,21 @@ fn convert_axum_method_to_reqwest(method: &axum::http::Method) -> Result<reqwest
 fn convert_axum_headers_to_reqwest(headers: &axum::http::HeaderMap) -> Result<reqwest::header::HeaderMap, StatusCode> {
     let mut reqwest_headers = reqwest::header::HeaderMap::new();
     
+    // 请求头已按 upstream_request_headers 过滤，这里只做类型转换
     for (name, value) in headers {
-        // 只转发应该转发的头部
-        if should_forward_header(name.as_str()) {
-            // 转换头部名称
-            let reqwest_name = match reqwest::header::HeaderName::from_bytes(name.as_str().as_bytes()) {
-                Ok(name) => name,
-                Err(_) => continue, // 跳过无效的头部名称
-            };
-            
-            // 转换头部值
-            let reqwest_value = match reqwest::header::HeaderValue::from_bytes(value.as_bytes()) {
-                Ok(value) => value,
-                Err(_) => continue, // 跳过无效的头部值
-            };
-            
-            reqwest_headers.insert(reqwest_name, reqwest_value);
-        }
+        // 转换头部名称
+        let reqwest_name = match reqwest::header::HeaderName::from_bytes(name.as_str().as_bytes()) {
+            Ok(name) => name,
+            Err(_) => continue, // 跳过无效的头部名称
+        };
+        
+        // 转换头部值
+        let reqwest_value = match reqwest::header::HeaderValue::from_bytes(value.as_bytes()) {
+            Ok(value) => value,
+            Err(_) => continue, // 跳过无效的头部值
+        };
+        
+        reqwest_headers.insert(reqwest_name, reqwest_value);
     }
     
     Ok(reqwest_headers)
@@ -21,6 +21,11 @@ impl RateLimiter {
 
     /// 检查限流
     pub async fn check_rate_limit(&self, key: &str) -> Result<bool> {
+        self.check_rate_limit_with(key, self.config.requests_per_minute).await
+    }
+
+    /// 按指定的窗口请求上限检查限流，用于公开访问等独立的限流档位
+    pub async fn check_rate_limit_with(&self, key: &str, limit: u32) -> Result<bool> {
         if !self.config.enabled {
             return Ok(true);
         }
@@ -28,7 +33,7 @@ impl RateLimiter {
         let full_key = format!("{}rate_limit:{}", 
             self.get_key_prefix(), key);
 
-        match self.check_sliding_window(&full_key).await {
+        match self.check_sliding_window(&full_key, limit).await {
             Ok(allowed) => {
                 debug!("Rate limit check for {}: {}", key, if allowed { "allowed" } else { "denied" });
                 Ok(allowed)
@@ -42,7 +47,7 @@ impl RateLimiter {
     }
 
     /// 滑动窗口限流算法
-    async fn check_sliding_window(&self, key: &str) -> Result<bool> {
+    async fn check_sliding_window(&self, key: &str, limit: u32) -> Result<bool> {
         use redis::AsyncCommands;
         
         let mut conn = self.redis.write().await;
@@ -63,10 +68,10 @@ impl RateLimiter {
         // 设置过期时间
         let _: () = conn.expire(key, (self.config.window_size as i64 + 60) as i64).await?;
 
-        let allowed = count <= self.config.requests_per_minute as i64;
+        let allowed = count <= limit as i64;
         
         debug!("Sliding window check: key={}, count={}, limit={}, allowed={}", 
-            key, count, self.config.requests_per_minute, allowed);
+            key, count, limit, allowed);
 
         Ok(allowed)
     }
@@ -109,7 +114,7 @@ impl RateLimiter {
             .hset(&full_key, "tokens", current_tokens)
             .hset(&full_key, "last_refill", now)
             .expire(&full_key, (self.config.window_size as i64 + 60) as i64)
-            .query_async(&mut *conn)
+            .query_async::<_, ()>(&mut *conn)
             .await?;
 
         debug!("Token bucket check: key={}, tokens={}, allowed={}", 
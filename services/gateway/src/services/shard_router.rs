@@ -0,0 +1,193 @@
+use redis::aio::ConnectionManager;
+use serde::Deserialize;
+use shared_utils::sharding::{self, ShardAssignments, ShardMember};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// 分配缓存时长，分配变化后最多延迟该时长生效
+const CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// 行情分片路由
+///
+/// 读取行情服务发布的交易对分配，把带交易对的请求转发到负责该交易对的实例。
+/// 分配未发布或请求不涉及具体交易对时返回 None，由调用方使用默认实例。
+pub struct ShardRouter {
+    redis: Arc<RwLock<ConnectionManager>>,
+    prefix: String,
+    cache: RwLock<Option<(Instant, Option<Arc<ShardAssignments>>)>>,
+}
+
+impl ShardRouter {
+    /// `prefix` 需与行情服务发布分配使用的键前缀一致
+    pub fn new(redis: Arc<RwLock<ConnectionManager>>, prefix: String) -> Self {
+        Self {
+            redis,
+            prefix,
+            cache: RwLock::new(None),
+        }
+    }
+
+    async fn assignments(&self) -> Option<Arc<ShardAssignments>> {
+        if let Some((loaded_at, assignments)) = self.cache.read().await.as_ref() {
+            if loaded_at.elapsed() < CACHE_TTL {
+                return assignments.clone();
+            }
+        }
+
+        let loaded = {
+            let mut redis = self.redis.write().await;
+            sharding::load_assignments(&mut redis, &self.prefix).await
+        };
+        let assignments = match loaded {
+            Ok(assignments) => assignments.map(Arc::new),
+            Err(e) => {
+                warn!("Failed to load market data shard assignments: {}", e);
+                None
+            }
+        };
+        *self.cache.write().await = Some((Instant::now(), assignments.clone()));
+        assignments
+    }
+
+    /// 请求涉及的交易对所属实例的地址
+    pub async fn route(&self, path: &str, query: &HashMap<String, String>) -> Option<String> {
+        let assignments = self.assignments().await?;
+        let member = resolve(&assignments, path, query)?;
+        debug!("Routing {} to market data shard {}", path, member.instance_id);
+        Some(member.url.clone())
+    }
+
+    /// WebSocket订阅消息中的交易对所属实例的地址
+    pub async fn route_message(&self, message: &str) -> Option<String> {
+        let assignments = self.assignments().await?;
+        let member = resolve_message(&assignments, message)?;
+        debug!("Routing WebSocket subscription to market data shard {}", member.instance_id);
+        Some(member.url.clone())
+    }
+}
+
+/// 行情服务 /ws/stream 的订阅消息
+#[derive(Debug, Deserialize)]
+struct SubscribeMessage {
+    op: String,
+    #[serde(default)]
+    exchanges: Vec<String>,
+    #[serde(default)]
+    symbols: Vec<String>,
+}
+
+/// 订阅消息涉及的交易对都属于同一实例时返回该实例
+///
+/// 一条连接只能转发到一个实例，交易对分属多个实例或未指定交易所和交易对时返回 None。
+fn resolve_message<'a>(assignments: &'a ShardAssignments, message: &str) -> Option<&'a ShardMember> {
+    let request: SubscribeMessage = serde_json::from_str(message).ok()?;
+    if request.op != "subscribe" || request.exchanges.is_empty() || request.symbols.is_empty() {
+        return None;
+    }
+
+    let mut owner: Option<&ShardMember> = None;
+    for exchange in &request.exchanges {
+        for symbol in &request.symbols {
+            let member = assignments.owner(exchange, symbol)?;
+            match owner {
+                Some(current) if current.instance_id != member.instance_id => {
+                    debug!("Subscription spans market data shards, using default instance");
+                    return None;
+                }
+                _ => owner = Some(member),
+            }
+        }
+    }
+    owner
+}
+
+/// 从查询参数或路径中找出交易对并返回所属实例
+///
+/// 优先使用 exchange/symbol 查询参数；否则在路径中查找相邻的
+/// 交易所和交易对段，例如 /api/v1/tick/binance/BTCUSDT。
+fn resolve<'a>(
+    assignments: &'a ShardAssignments,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> Option<&'a ShardMember> {
+    if let (Some(exchange), Some(symbol)) = (query.get("exchange"), query.get("symbol")) {
+        return assignments.owner(exchange, symbol);
+    }
+
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    segments
+        .windows(2)
+        .find_map(|pair| assignments.owner(pair[0], pair[1]))
+}
+
+/// 解析原始查询字符串
+pub fn parse_query(query: Option<&str>) -> HashMap<String, String> {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            let key = urlencoding::decode(key).ok()?.into_owned();
+            let value = urlencoding::decode(value).ok()?.into_owned();
+            Some((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use shared_utils::sharding::shard_key;
+
+    fn assignments() -> ShardAssignments {
+        let member = |id: &str| ShardMember {
+            instance_id: id.to_string(),
+            url: format!("http://{}:8081", id),
+            last_seen: Utc::now(),
+        };
+        ShardAssignments {
+            version: 1,
+            updated_at: None,
+            members: vec![member("md-0"), member("md-1")],
+            assignments: [
+                (shard_key("binance", "BTCUSDT"), "md-0".to_string()),
+                (shard_key("binance", "ETHUSDT"), "md-1".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_shard() {
+        let assignments = assignments();
+
+        let by_path = resolve(&assignments, "/api/v1/market-data/tick/binance/ETHUSDT", &HashMap::new());
+        assert_eq!(by_path.unwrap().instance_id, "md-1");
+
+        let query = parse_query(Some("exchange=binance&symbol=btcusdt&limit=10"));
+        let by_query = resolve(&assignments, "/api/v1/market-data/trades", &query);
+        assert_eq!(by_query.unwrap().instance_id, "md-0");
+
+        assert!(resolve(&assignments, "/api/v1/market-data/ticker/24hr", &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_resolve_subscribe_message() {
+        let assignments = assignments();
+
+        let single = r#"{"op":"subscribe","exchanges":["binance"],"symbols":["ethusdt"],"types":["tick"]}"#;
+        assert_eq!(resolve_message(&assignments, single).unwrap().instance_id, "md-1");
+
+        // 交易对分属不同实例，或缺少交易所时无法确定分片
+        let spanning = r#"{"op":"subscribe","exchanges":["binance"],"symbols":["BTCUSDT","ETHUSDT"]}"#;
+        assert!(resolve_message(&assignments, spanning).is_none());
+        assert!(resolve_message(&assignments, r#"{"op":"subscribe","symbols":["BTCUSDT"]}"#).is_none());
+        assert!(resolve_message(&assignments, r#"{"op":"ping"}"#).is_none());
+        assert!(resolve_message(&assignments, "not json").is_none());
+    }
+}
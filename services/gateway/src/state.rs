@@ -1,11 +1,12 @@
 use anyhow::Result;
 use redis::aio::ConnectionManager;
-use shared_utils::{AppMetrics, JwtService};
+use shared_utils::{AppMetrics, Cache, Claims, InternalAuth, JwtService, RequestLogBuffer};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::config::GatewayConfig;
-use crate::services::{CircuitBreaker, ServiceRegistry, RateLimiter};
+use crate::graphql::{build_schema, DownstreamClient, GatewaySchema};
+use crate::services::{CircuitBreaker, ServiceRegistry, ShardRouter, RateLimiter};
 use crate::websocket::WebSocketManager;
 
 /// 应用状态
@@ -14,16 +15,29 @@ pub struct AppState {
     pub config: GatewayConfig,
     pub metrics: Arc<AppMetrics>,
     pub jwt_service: Arc<JwtService>,
+    /// 已校验令牌的 Claims 缓存
+    pub token_cache: Arc<Cache<String, Claims>>,
+    /// 指标和管理接口的内部认证
+    pub internal_auth: Arc<InternalAuth>,
+    /// 最近请求的日志，供按请求ID汇总查询
+    pub request_logs: RequestLogBuffer,
     pub redis: Arc<RwLock<ConnectionManager>>,
     pub service_registry: Arc<ServiceRegistry>,
+    pub shard_router: Arc<ShardRouter>,
     pub rate_limiter: Arc<RateLimiter>,
     pub circuit_breakers: Arc<RwLock<std::collections::HashMap<String, CircuitBreaker>>>,
     pub websocket_manager: Arc<WebSocketManager>,
+    pub downstream_client: DownstreamClient,
+    pub graphql_schema: GatewaySchema,
 }
 
 impl AppState {
     /// 创建新的应用状态
-    pub async fn new(config: GatewayConfig, metrics: Arc<AppMetrics>) -> Result<Self> {
+    pub async fn new(
+        config: GatewayConfig,
+        metrics: Arc<AppMetrics>,
+        request_logs: RequestLogBuffer,
+    ) -> Result<Self> {
         // 初始化JWT服务
         let jwt_service = Arc::new(JwtService::new(
             &config.auth.jwt_secret,
@@ -33,6 +47,12 @@ impl AppState {
             config.auth.refresh_token_expiry as i64 / 86400, // 转换为天
         ));
 
+        let token_cache = Arc::new(
+            Cache::new("gateway_tokens", config.auth.token_cache.clone()).with_metrics(metrics.clone()),
+        );
+
+        let internal_auth = Arc::new(InternalAuth::new(config.internal_auth.clone()));
+
         // 初始化Redis连接
         let redis_client = redis::Client::open(config.redis.url.as_str())?;
         let redis_conn = ConnectionManager::new(redis_client).await?;
@@ -41,6 +61,9 @@ impl AppState {
         // 初始化服务注册表
         let service_registry = Arc::new(ServiceRegistry::new(config.clone()));
 
+        // 初始化行情分片路由
+        let shard_router = Arc::new(ShardRouter::new(redis.clone(), config.sharding.key_prefix.clone()));
+
         // 初始化限流器
         let rate_limiter = Arc::new(RateLimiter::new(
             config.rate_limit.clone(),
@@ -53,15 +76,26 @@ impl AppState {
         // 初始化WebSocket管理器
         let websocket_manager = Arc::new(WebSocketManager::new());
 
+        // 初始化GraphQL
+        let downstream_client =
+            DownstreamClient::new(config.clone(), service_registry.client.clone());
+        let graphql_schema = build_schema(downstream_client.clone(), service_registry.clone());
+
         Ok(Self {
             config,
             metrics,
             jwt_service,
+            token_cache,
+            internal_auth,
+            request_logs,
             redis,
             service_registry,
+            shard_router,
             rate_limiter,
             circuit_breakers,
             websocket_manager,
+            downstream_client,
+            graphql_schema,
         })
     }
 
@@ -1,14 +1,20 @@
 use anyhow::Result;
 use axum::extract::ws::{WebSocket, Message};
+use axum::http::HeaderMap;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::Message as TungsteniteMessage};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, Message as TungsteniteMessage},
+};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::services::ShardRouter;
+
 /// WebSocket连接状态
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum ConnectionState {
@@ -18,6 +24,25 @@ pub enum ConnectionState {
     Error,
 }
 
+/// 按客户端首条订阅消息选择目标实例
+///
+/// 行情订阅的交易对在路径和查询参数中都没有时，由首条消息决定转发到哪个分片实例。
+#[derive(Clone)]
+pub struct MessageRoute {
+    pub router: Arc<ShardRouter>,
+    /// 目标实例上的WebSocket路径
+    pub path: String,
+}
+
+impl MessageRoute {
+    /// 首条消息所属实例的WebSocket地址
+    async fn target_url(&self, message: &str) -> Option<String> {
+        let base_url = self.router.route_message(message).await?;
+        let ws_url = base_url.replace("http://", "ws://").replace("https://", "wss://");
+        Some(format!("{}{}", ws_url.trim_end_matches('/'), self.path))
+    }
+}
+
 /// WebSocket连接
 #[derive(Clone)]
 pub struct WebSocketConnection {
@@ -29,6 +54,9 @@ pub struct WebSocketConnection {
     pub last_activity: Arc<RwLock<Instant>>,
     pub message_count: Arc<RwLock<u64>>,
     pub error_count: Arc<RwLock<u64>>,
+    pub route: Option<MessageRoute>,
+    /// 建立上游连接时附带的请求头，例如网关认证后的用户身份
+    pub upstream_headers: HeaderMap,
 }
 
 impl WebSocketConnection {
@@ -46,18 +74,71 @@ impl WebSocketConnection {
             last_activity: Arc::new(RwLock::new(now)),
             message_count: Arc::new(RwLock::new(0)),
             error_count: Arc::new(RwLock::new(0)),
+            route: None,
+            upstream_headers: HeaderMap::new(),
+        }
+    }
+
+    /// 上游握手请求附带的请求头
+    pub fn with_upstream_headers(mut self, headers: HeaderMap) -> Self {
+        self.upstream_headers = headers;
+        self
+    }
+
+    /// 按首条消息选择目标实例，无法确定时使用 `target_url`
+    pub fn with_message_route(mut self, route: MessageRoute) -> Self {
+        self.route = Some(route);
+        self
+    }
+
+    /// 等待客户端首条数据消息，连接在此之前关闭时返回 None
+    async fn first_message(client_ws: &mut WebSocket) -> Option<Message> {
+        while let Some(msg) = client_ws.recv().await {
+            match msg {
+                Ok(Message::Ping(data)) => {
+                    let _ = client_ws.send(Message::Pong(data)).await;
+                }
+                Ok(Message::Pong(_)) => {}
+                Ok(Message::Close(_)) | Err(_) => return None,
+                Ok(message) => return Some(message),
+            }
         }
+        None
     }
 
     /// 建立代理连接
     pub async fn establish_proxy(
         &self,
-        client_ws: WebSocket,
+        mut client_ws: WebSocket,
     ) -> Result<()> {
         info!("Establishing WebSocket proxy for connection: {}", self.id);
 
-        // 连接到目标服务
-        let (target_ws, _) = match connect_async(&self.target_url).await {
+        // 按首条订阅消息选择目标实例，该消息在连接建立后转发
+        let mut target_url = self.target_url.clone();
+        let mut first_message = None;
+        if let Some(route) = &self.route {
+            let Some(message) = Self::first_message(&mut client_ws).await else {
+                self.set_state(ConnectionState::Disconnected).await;
+                return Ok(());
+            };
+            if let Message::Text(text) = &message {
+                if let Some(url) = route.target_url(text).await {
+                    target_url = url;
+                }
+            }
+            first_message = Some(message);
+        }
+
+        // 连接到目标服务，握手请求附带用户身份
+        let mut upstream_request = target_url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| anyhow::anyhow!("Invalid target URL {}: {}", target_url, e))?;
+        upstream_request
+            .headers_mut()
+            .extend(self.upstream_headers.clone());
+
+        let (target_ws, _) = match connect_async(upstream_request).await {
             Ok(connection) => connection,
             Err(e) => {
                 error!("Failed to connect to target WebSocket: {}", e);
@@ -73,6 +154,19 @@ impl WebSocketConnection {
         let (mut client_sender, mut client_receiver) = client_ws.split();
         let (mut target_sender, mut target_receiver) = target_ws.split();
 
+        let forwarded = match first_message {
+            Some(Message::Text(text)) => Some(TungsteniteMessage::Text(text)),
+            Some(Message::Binary(data)) => Some(TungsteniteMessage::Binary(data)),
+            _ => None,
+        };
+        if let Some(message) = forwarded {
+            if let Err(e) = target_sender.send(message).await {
+                error!("Failed to forward first message to target: {}", e);
+                self.set_state(ConnectionState::Error).await;
+                return Err(anyhow::anyhow!("Forwarding failed: {}", e));
+            }
+        }
+
         // 创建消息通道
         let (client_tx, mut client_rx) = mpsc::unbounded_channel::<String>();
         let (target_tx, mut target_rx) = mpsc::unbounded_channel::<String>();
@@ -4,7 +4,7 @@ pub mod message;
 pub mod pool;
 
 pub use proxy::{WebSocketProxy, ProxyStats};
-pub use connection::{WebSocketConnection, ConnectionState};
+pub use connection::{WebSocketConnection, ConnectionState, MessageRoute};
 pub use message::{WebSocketMessage, MessageType};
 // 暂时注释pool导出，使用proxy中的简化版本
 // pub use pool::{ConnectionPool, PoolStats, PoolHealthStatus};
@@ -40,10 +40,14 @@ impl WebSocketManager {
         ws_upgrade: axum::extract::ws::WebSocketUpgrade,
         service_name: &str,
         target_url: &str,
+        route: Option<MessageRoute>,
+        upstream_headers: axum::http::HeaderMap,
     ) -> Result<axum::response::Response> {
         info!("Handling WebSocket connection for service: {}", service_name);
         
-        self.proxy.proxy_connection(ws_upgrade, service_name, target_url).await
+        self.proxy
+            .proxy_connection(ws_upgrade, service_name, target_url, route, upstream_headers)
+            .await
     }
 
     /// 获取连接池统计
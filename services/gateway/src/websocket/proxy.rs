@@ -8,7 +8,7 @@ use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
 use super::{
-    connection::{WebSocketConnection, ConnectionConfig},
+    connection::{WebSocketConnection, ConnectionConfig, MessageRoute},
     message::WebSocketMessage,
 };
 
@@ -92,6 +92,8 @@ impl WebSocketProxy {
         ws_upgrade: WebSocketUpgrade,
         service_name: &str,
         target_url: &str,
+        route: Option<MessageRoute>,
+        upstream_headers: HeaderMap,
     ) -> Result<Response> {
         info!("Creating WebSocket proxy for service: {} -> {}", service_name, target_url);
 
@@ -108,10 +110,14 @@ impl WebSocketProxy {
         }
 
         // 创建WebSocket连接对象
-        let connection = WebSocketConnection::new(
+        let mut connection = WebSocketConnection::new(
             service_name.to_string(),
             target_url.to_string(),
-        );
+        )
+        .with_upstream_headers(upstream_headers);
+        if let Some(route) = route {
+            connection = connection.with_message_route(route);
+        }
 
         let connection_id = connection.id.clone();
         let pool = self.connection_pool.clone();
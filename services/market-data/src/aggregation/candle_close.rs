@@ -0,0 +1,313 @@
+use serde::Serialize;
+use shared_models::market::{CandleClose, CandleStatus, Kline};
+use shared_protocols::kafka::KafkaTopics;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::charting::millis_to_datetime;
+use crate::config::CandleCloseConfig;
+use crate::connectors::MarketDataEvent;
+use crate::publishing::KafkaPublisher;
+use crate::websocket::{WebSocketBroadcaster, WebSocketEvent};
+
+/// 单根K线的定稿状态
+#[derive(Debug, Clone)]
+struct CandleSlot {
+    kline: Kline,
+    revision: u32,
+    finalized_at: Option<i64>,
+}
+
+/// 收盘调度统计
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct CandleCloseStats {
+    pub finalized: u64,
+    pub revisions: u64,
+    pub sealed: u64,
+    /// 定稿后重复推送的相同数据
+    pub duplicates: u64,
+    /// 宽限期结束后到达的更新
+    pub late_dropped: u64,
+}
+
+fn series_key(kline: &Kline) -> String {
+    format!(
+        "{}:{}:{}",
+        kline.exchange.as_str(),
+        kline.symbol.to_uppercase(),
+        kline.interval.as_str()
+    )
+}
+
+/// 两次推送的K线数据是否一致
+fn same_values(a: &Kline, b: &Kline) -> bool {
+    a.open == b.open
+        && a.high == b.high
+        && a.low == b.low
+        && a.close == b.close
+        && a.volume == b.volume
+        && a.quote_volume == b.quote_volume
+        && a.trades_count == b.trades_count
+}
+
+/// K线收盘调度器
+///
+/// 交易所的收盘推送可能迟到或重复，调度器以墙钟时间为准：收盘边界加上等待时间后
+/// 定稿，宽限期内数据变化的更新作为修正发布，宽限期结束后发布封存事件，
+/// 消费方据此判断数据已稳定。
+#[derive(Clone)]
+pub struct CandleCloseScheduler {
+    config: CandleCloseConfig,
+    /// exchange:symbol:interval -> open_time -> 定稿状态
+    series: Arc<RwLock<HashMap<String, BTreeMap<i64, CandleSlot>>>>,
+    stats: Arc<RwLock<CandleCloseStats>>,
+}
+
+impl CandleCloseScheduler {
+    pub fn new(config: CandleCloseConfig) -> Self {
+        Self {
+            config,
+            series: Arc::new(RwLock::new(HashMap::new())),
+            stats: Arc::new(RwLock::new(CandleCloseStats::default())),
+        }
+    }
+
+    fn grace_ms(&self) -> i64 {
+        self.config.grace_period_seconds as i64 * 1000
+    }
+
+    fn candle_close(&self, slot: &CandleSlot, status: CandleStatus) -> CandleClose {
+        let close_ms = slot.kline.open_time.timestamp_millis() + slot.kline.interval.to_millis();
+        CandleClose {
+            kline: slot.kline.clone(),
+            status,
+            revision: slot.revision,
+            finalized_at: millis_to_datetime(slot.finalized_at.unwrap_or(close_ms)),
+            stable_at: millis_to_datetime(close_ms + self.grace_ms()),
+        }
+    }
+
+    /// 处理K线更新，已定稿的K线数据变化时返回修正事件
+    pub async fn on_kline(&self, kline: &Kline, now_ms: i64) -> Option<CandleClose> {
+        let open_ms = kline.open_time.timestamp_millis();
+        let close_ms = open_ms + kline.interval.to_millis();
+
+        let mut series = self.series.write().await;
+        let slots = series.entry(series_key(kline)).or_default();
+
+        if !slots.contains_key(&open_ms) {
+            if now_ms >= close_ms + self.grace_ms() {
+                // 已封存或从未跟踪过的旧K线
+                self.stats.write().await.late_dropped += 1;
+            } else {
+                slots.insert(
+                    open_ms,
+                    CandleSlot {
+                        kline: kline.clone(),
+                        revision: 0,
+                        finalized_at: None,
+                    },
+                );
+            }
+            return None;
+        }
+
+        let slot = slots.get_mut(&open_ms)?;
+
+        if slot.finalized_at.is_none() {
+            slot.kline = kline.clone();
+            return None;
+        }
+
+        if same_values(&slot.kline, kline) {
+            self.stats.write().await.duplicates += 1;
+            return None;
+        }
+
+        slot.kline = Kline {
+            is_closed: true,
+            ..kline.clone()
+        };
+        slot.revision += 1;
+        self.stats.write().await.revisions += 1;
+        Some(self.candle_close(slot, CandleStatus::Revised))
+    }
+
+    /// 定稿到达收盘时间的K线，封存宽限期结束的K线
+    pub async fn finalize_due(&self, now_ms: i64) -> Vec<CandleClose> {
+        let settle_ms = self.config.settle_delay_ms as i64;
+        let grace_ms = self.grace_ms();
+
+        let mut events = Vec::new();
+        let mut finalized = 0;
+        let mut sealed = 0;
+
+        let mut series = self.series.write().await;
+        series.retain(|_, slots| {
+            slots.retain(|_, slot| {
+                let close_ms = slot.kline.open_time.timestamp_millis() + slot.kline.interval.to_millis();
+                if slot.finalized_at.is_none() && now_ms >= close_ms + settle_ms {
+                    slot.kline.is_closed = true;
+                    slot.finalized_at = Some(now_ms);
+                    events.push(self.candle_close(slot, CandleStatus::Final));
+                    finalized += 1;
+                }
+                if slot.finalized_at.is_some() && now_ms >= close_ms + grace_ms {
+                    events.push(self.candle_close(slot, CandleStatus::Sealed));
+                    sealed += 1;
+                    return false;
+                }
+                true
+            });
+            !slots.is_empty()
+        });
+        drop(series);
+
+        if finalized > 0 || sealed > 0 {
+            let mut stats = self.stats.write().await;
+            stats.finalized += finalized;
+            stats.sealed += sealed;
+        }
+        events
+    }
+
+    /// 获取统计信息
+    pub async fn get_stats(&self) -> CandleCloseStats {
+        self.stats.read().await.clone()
+    }
+
+    async fn publish(
+        event: CandleClose,
+        broadcaster: &WebSocketBroadcaster,
+        publisher: &KafkaPublisher,
+    ) {
+        let key = series_key(&event.kline);
+        let event_type = match event.status {
+            CandleStatus::Final => "candle_final",
+            CandleStatus::Revised => "candle_revised",
+            CandleStatus::Sealed => "candle_sealed",
+        };
+        if let Err(e) = publisher
+            .publish(KafkaTopics::MARKET_CANDLE_CLOSES, &key, event_type, &event)
+            .await
+        {
+            warn!("Failed to publish candle close {}: {}", key, e);
+        }
+
+        if let Err(e) = broadcaster.broadcast(WebSocketEvent::CandleClose(event)).await {
+            warn!("Failed to broadcast candle close {}: {}", key, e);
+        }
+    }
+
+    /// 启动调度：消费K线更新，并按固定间隔检查到期的K线
+    pub fn start(
+        &self,
+        mut events: broadcast::Receiver<MarketDataEvent>,
+        broadcaster: Arc<WebSocketBroadcaster>,
+        publisher: Arc<KafkaPublisher>,
+    ) {
+        let scheduler = self.clone();
+        let tick_interval = Duration::from_millis(self.config.tick_interval_ms.max(10));
+        tokio::spawn(async move {
+            info!("Candle close scheduler started");
+            let mut ticker = tokio::time::interval(tick_interval);
+            loop {
+                tokio::select! {
+                    event = events.recv() => match event {
+                        Ok(MarketDataEvent::Kline(kline)) => {
+                            let now_ms = chrono::Utc::now().timestamp_millis();
+                            if let Some(revision) = scheduler.on_kline(&kline, now_ms).await {
+                                debug!(
+                                    "Candle {} {} revised (revision {})",
+                                    series_key(&revision.kline),
+                                    revision.kline.open_time,
+                                    revision.revision
+                                );
+                                Self::publish(revision, &broadcaster, &publisher).await;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Candle close scheduler lagged, skipped {} events", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = ticker.tick() => {
+                        let now_ms = chrono::Utc::now().timestamp_millis();
+                        for event in scheduler.finalize_due(now_ms).await {
+                            Self::publish(event, &broadcaster, &publisher).await;
+                        }
+                    }
+                }
+            }
+            warn!("Candle close scheduler stopped");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use shared_models::common::{DataQuality, Exchange, Interval};
+
+    fn kline(open_ms: i64, close: i64, is_closed: bool) -> Kline {
+        Kline {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            interval: Interval::OneMinute,
+            open_time: millis_to_datetime(open_ms),
+            close_time: millis_to_datetime(open_ms + 59_999),
+            open: Decimal::from(100),
+            high: Decimal::from(close.max(100)),
+            low: Decimal::from(close.min(100)),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+            quote_volume: Decimal::from(close),
+            trades_count: 1,
+            taker_buy_base_volume: Decimal::ZERO,
+            taker_buy_quote_volume: Decimal::ZERO,
+            is_closed,
+            data_quality: DataQuality::Normal,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_finalize_revise_and_seal() {
+        let scheduler = CandleCloseScheduler::new(CandleCloseConfig::default());
+        let base = 1_640_995_200_000;
+        let close_ms = base + 60_000;
+
+        // 未收到收盘推送，到达收盘时间加等待时间后定稿
+        assert!(scheduler.on_kline(&kline(base, 101, false), base + 30_000).await.is_none());
+        assert!(scheduler.finalize_due(close_ms).await.is_empty());
+        let events = scheduler.finalize_due(close_ms + 1_000).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, CandleStatus::Final);
+        assert!(events[0].kline.is_closed);
+
+        // 迟到的收盘推送数据变化，发布修正；重复推送忽略
+        let revision = scheduler.on_kline(&kline(base, 102, true), close_ms + 2_000).await.unwrap();
+        assert_eq!(revision.status, CandleStatus::Revised);
+        assert_eq!(revision.revision, 1);
+        assert!(scheduler.on_kline(&kline(base, 102, true), close_ms + 3_000).await.is_none());
+
+        // 宽限期结束后封存，之后的更新丢弃
+        let events = scheduler.finalize_due(close_ms + 60_000).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, CandleStatus::Sealed);
+        assert_eq!(events[0].revision, 1);
+        assert!(scheduler.on_kline(&kline(base, 103, true), close_ms + 61_000).await.is_none());
+
+        let stats = scheduler.get_stats().await;
+        assert_eq!(stats.finalized, 1);
+        assert_eq!(stats.revisions, 1);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.sealed, 1);
+        assert_eq!(stats.late_dropped, 1);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod candle_close;
+pub mod ticker_24h;
+pub mod trade_tape;
+
+pub use candle_close::{CandleCloseScheduler, CandleCloseStats};
+pub use ticker_24h::RollingTickerAggregator;
+pub use trade_tape::{TradeFilter, TradeTape};
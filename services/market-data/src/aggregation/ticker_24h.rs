@@ -0,0 +1,403 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use shared_models::common::Exchange;
+use shared_models::market::{OrderBook, Ticker24hr, Trade};
+use shared_protocols::kafka::KafkaTopics;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::charting::{align_to_interval, millis_to_datetime};
+use crate::connectors::MarketDataEvent;
+use crate::publishing::KafkaPublisher;
+use crate::websocket::{WebSocketBroadcaster, WebSocketEvent};
+
+const BUCKET_MS: i64 = 60_000;
+
+/// 分钟级成交桶
+#[derive(Debug, Clone)]
+struct MinuteBucket {
+    start: i64,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+    volume: Decimal,
+    quote_volume: Decimal,
+    first_id: u64,
+    last_id: u64,
+    count: u32,
+}
+
+impl MinuteBucket {
+    fn new(start: i64, trade: &Trade, trade_id: u64) -> Self {
+        Self {
+            start,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.quantity,
+            quote_volume: trade.quote_quantity,
+            first_id: trade_id,
+            last_id: trade_id,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, trade: &Trade, trade_id: u64) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.quantity;
+        self.quote_volume += trade.quote_quantity;
+        self.last_id = trade_id;
+        self.count += 1;
+    }
+}
+
+/// 单个交易对的滚动窗口
+#[derive(Debug, Clone)]
+struct RollingWindow {
+    exchange: Exchange,
+    symbol: String,
+    buckets: VecDeque<MinuteBucket>,
+    /// 窗口外最后一个收盘价
+    prev_close: Option<Decimal>,
+    last_price: Decimal,
+    last_qty: Decimal,
+    last_trade_time: i64,
+    bid: Option<(Decimal, Decimal)>,
+    ask: Option<(Decimal, Decimal)>,
+}
+
+impl RollingWindow {
+    fn new(exchange: Exchange, symbol: String) -> Self {
+        Self {
+            exchange,
+            symbol,
+            buckets: VecDeque::new(),
+            prev_close: None,
+            last_price: Decimal::ZERO,
+            last_qty: Decimal::ZERO,
+            last_trade_time: 0,
+            bid: None,
+            ask: None,
+        }
+    }
+
+    fn add_trade(&mut self, trade: &Trade) {
+        let ts = trade.timestamp.timestamp_millis();
+        let bucket_start = align_to_interval(ts, BUCKET_MS);
+        let trade_id = trade.trade_id.parse::<u64>().unwrap_or_default();
+
+        match self.buckets.back_mut() {
+            Some(last) if last.start == bucket_start => last.add(trade, trade_id),
+            Some(last) if last.start > bucket_start => {
+                // 乱序成交：找到对应的桶合并，找不到则丢弃
+                if let Some(bucket) = self.buckets.iter_mut().find(|b| b.start == bucket_start) {
+                    bucket.add(trade, trade_id);
+                }
+                return;
+            }
+            _ => self
+                .buckets
+                .push_back(MinuteBucket::new(bucket_start, trade, trade_id)),
+        }
+
+        if ts >= self.last_trade_time {
+            self.last_price = trade.price;
+            self.last_qty = trade.quantity;
+            self.last_trade_time = ts;
+        }
+    }
+
+    fn evict(&mut self, window_start: i64) {
+        while let Some(front) = self.buckets.front() {
+            if front.start >= window_start {
+                break;
+            }
+            self.prev_close = Some(front.close);
+            self.buckets.pop_front();
+        }
+    }
+
+    fn snapshot(&self, now: i64, window_ms: i64) -> Option<Ticker24hr> {
+        let first = self.buckets.front()?;
+
+        let mut high = first.high;
+        let mut low = first.low;
+        let mut volume = Decimal::ZERO;
+        let mut quote_volume = Decimal::ZERO;
+        let mut count = 0u32;
+        for bucket in &self.buckets {
+            high = high.max(bucket.high);
+            low = low.min(bucket.low);
+            volume += bucket.volume;
+            quote_volume += bucket.quote_volume;
+            count += bucket.count;
+        }
+
+        let open_price = first.open;
+        let price_change = self.last_price - open_price;
+        let price_change_percent = if open_price.is_zero() {
+            Decimal::ZERO
+        } else {
+            (price_change / open_price * Decimal::from(100)).round_dp(4)
+        };
+        let weighted_avg_price = if volume.is_zero() {
+            Decimal::ZERO
+        } else {
+            (quote_volume / volume).round_dp(8)
+        };
+        let (bid_price, bid_qty) = self.bid.unwrap_or((Decimal::ZERO, Decimal::ZERO));
+        let (ask_price, ask_qty) = self.ask.unwrap_or((Decimal::ZERO, Decimal::ZERO));
+
+        Some(Ticker24hr {
+            exchange: self.exchange.clone(),
+            symbol: self.symbol.clone(),
+            timestamp: millis_to_datetime(now),
+            price_change,
+            price_change_percent,
+            weighted_avg_price,
+            prev_close_price: self.prev_close.unwrap_or(open_price),
+            last_price: self.last_price,
+            last_qty: self.last_qty,
+            bid_price,
+            bid_qty,
+            ask_price,
+            ask_qty,
+            open_price,
+            high_price: high,
+            low_price: low,
+            volume,
+            quote_volume,
+            open_time: millis_to_datetime(now - window_ms),
+            close_time: millis_to_datetime(now),
+            first_id: first.first_id,
+            last_id: self.buckets.back().map(|b| b.last_id).unwrap_or_default(),
+            count,
+        })
+    }
+}
+
+/// 24小时滚动行情聚合器
+///
+/// 以分钟为粒度维护成交桶，窗口滑动时按桶淘汰，
+/// 快照在发布周期内计算，避免每笔成交都重算整个窗口。
+#[derive(Clone)]
+pub struct RollingTickerAggregator {
+    window_ms: i64,
+    windows: Arc<RwLock<HashMap<String, RollingWindow>>>,
+    snapshots: Arc<RwLock<HashMap<String, Ticker24hr>>>,
+}
+
+impl RollingTickerAggregator {
+    /// 创建聚合器
+    pub fn new(window_hours: u32) -> Self {
+        Self {
+            window_ms: window_hours as i64 * 60 * 60 * 1000,
+            windows: Arc::new(RwLock::new(HashMap::new())),
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn key(exchange: &Exchange, symbol: &str) -> String {
+        format!("{}:{}", exchange.as_str(), symbol.to_uppercase())
+    }
+
+    /// 处理成交
+    pub async fn on_trade(&self, trade: &Trade) {
+        let key = Self::key(&trade.exchange, &trade.symbol);
+        let mut windows = self.windows.write().await;
+        windows
+            .entry(key)
+            .or_insert_with(|| RollingWindow::new(trade.exchange.clone(), trade.symbol.to_uppercase()))
+            .add_trade(trade);
+    }
+
+    /// 处理订单簿，更新最优买卖价
+    pub async fn on_orderbook(&self, book: &OrderBook) {
+        let key = Self::key(&book.exchange, &book.symbol);
+        let mut windows = self.windows.write().await;
+        if let Some(window) = windows.get_mut(&key) {
+            window.bid = book.bids.first().map(|l| (l.price, l.quantity));
+            window.ask = book.asks.first().map(|l| (l.price, l.quantity));
+        }
+    }
+
+    /// 淘汰过期数据并重新计算所有快照
+    pub async fn refresh(&self, now: DateTime<Utc>) -> Vec<Ticker24hr> {
+        let now_ms = now.timestamp_millis();
+        let window_start = now_ms - self.window_ms;
+
+        let mut windows = self.windows.write().await;
+        let mut tickers = Vec::with_capacity(windows.len());
+        windows.retain(|_, window| {
+            window.evict(window_start);
+            if let Some(ticker) = window.snapshot(now_ms, self.window_ms) {
+                tickers.push(ticker);
+                true
+            } else {
+                // 24小时内无成交的交易对不再跟踪
+                false
+            }
+        });
+        drop(windows);
+
+        let mut snapshots = self.snapshots.write().await;
+        snapshots.clear();
+        for ticker in &tickers {
+            snapshots.insert(Self::key(&ticker.exchange, &ticker.symbol), ticker.clone());
+        }
+
+        tickers
+    }
+
+    /// 获取缓存的快照
+    pub async fn get_ticker(&self, exchange: &Exchange, symbol: &str) -> Option<Ticker24hr> {
+        self.snapshots
+            .read()
+            .await
+            .get(&Self::key(exchange, symbol))
+            .cloned()
+    }
+
+    /// 获取全部缓存的快照
+    pub async fn get_all_tickers(&self, exchange: Option<&Exchange>) -> Vec<Ticker24hr> {
+        self.snapshots
+            .read()
+            .await
+            .values()
+            .filter(|t| exchange.map_or(true, |e| &t.exchange == e))
+            .cloned()
+            .collect()
+    }
+
+    /// 启动聚合任务：消费行情事件，并按周期发布24h行情到WebSocket和Kafka
+    pub fn start(
+        &self,
+        mut events: broadcast::Receiver<MarketDataEvent>,
+        broadcaster: Arc<WebSocketBroadcaster>,
+        publisher: Arc<KafkaPublisher>,
+        publish_interval: Duration,
+    ) {
+        let aggregator = self.clone();
+        tokio::spawn(async move {
+            info!("24h ticker aggregator started");
+            loop {
+                match events.recv().await {
+                    Ok(MarketDataEvent::Trade(trade)) => aggregator.on_trade(&trade).await,
+                    Ok(MarketDataEvent::OrderBook(book)) => aggregator.on_orderbook(&book).await,
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("24h ticker aggregator lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            warn!("24h ticker aggregator stopped");
+        });
+
+        let aggregator = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(publish_interval);
+            loop {
+                interval.tick().await;
+
+                let tickers = aggregator.refresh(chrono::Utc::now()).await;
+                debug!("Publishing {} 24h tickers", tickers.len());
+
+                for ticker in tickers {
+                    let key = format!("{}:{}", ticker.exchange.as_str(), ticker.symbol);
+                    if let Err(e) = publisher
+                        .publish(KafkaTopics::MARKET_TICKER24HR, &key, "ticker_24hr", &ticker)
+                        .await
+                    {
+                        warn!("Failed to publish 24h ticker {}: {}", key, e);
+                    }
+
+                    if let Err(e) = broadcaster.broadcast(WebSocketEvent::Ticker24hr(ticker)).await {
+                        warn!("Failed to broadcast 24h ticker {}: {}", key, e);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn trade(ts: i64, id: u64, price: i64, qty: i64) -> Trade {
+        Trade {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            trade_id: id.to_string(),
+            timestamp: Utc.timestamp_millis_opt(ts).unwrap(),
+            price: Decimal::from(price),
+            quantity: Decimal::from(qty),
+            quote_quantity: Decimal::from(price * qty),
+            side: "buy".to_string(),
+            is_buyer_maker: false,
+            is_best_match: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rolling_statistics() {
+        let aggregator = RollingTickerAggregator::new(24);
+        let base = 1_640_995_200_000;
+
+        aggregator.on_trade(&trade(base, 1, 100, 1)).await;
+        aggregator.on_trade(&trade(base + 30_000, 2, 120, 2)).await;
+        aggregator.on_trade(&trade(base + 3_600_000, 3, 90, 1)).await;
+        aggregator.on_trade(&trade(base + 7_200_000, 4, 110, 1)).await;
+
+        let tickers = aggregator.refresh(Utc.timestamp_millis_opt(base + 7_200_000).unwrap()).await;
+        assert_eq!(tickers.len(), 1);
+
+        let ticker = &tickers[0];
+        assert_eq!(ticker.open_price, Decimal::from(100));
+        assert_eq!(ticker.high_price, Decimal::from(120));
+        assert_eq!(ticker.low_price, Decimal::from(90));
+        assert_eq!(ticker.last_price, Decimal::from(110));
+        assert_eq!(ticker.volume, Decimal::from(5));
+        assert_eq!(ticker.price_change, Decimal::from(10));
+        assert_eq!(ticker.price_change_percent, Decimal::from(10));
+        assert_eq!(ticker.count, 4);
+        assert_eq!(ticker.first_id, 1);
+        assert_eq!(ticker.last_id, 4);
+
+        let cached = aggregator.get_ticker(&Exchange::Binance, "btcusdt").await;
+        assert!(cached.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_window_eviction() {
+        let aggregator = RollingTickerAggregator::new(1);
+        let base = 1_640_995_200_000;
+
+        aggregator.on_trade(&trade(base, 1, 100, 1)).await;
+        aggregator.on_trade(&trade(base + 3_000_000, 2, 105, 1)).await;
+
+        // 1小时窗口滑过第一笔成交
+        let tickers = aggregator
+            .refresh(Utc.timestamp_millis_opt(base + 3_700_000).unwrap())
+            .await;
+        assert_eq!(tickers[0].open_price, Decimal::from(105));
+        assert_eq!(tickers[0].prev_close_price, Decimal::from(100));
+        assert_eq!(tickers[0].count, 1);
+
+        // 窗口内无成交时移除
+        let tickers = aggregator
+            .refresh(Utc.timestamp_millis_opt(base + 10_000_000).unwrap())
+            .await;
+        assert!(tickers.is_empty());
+    }
+}
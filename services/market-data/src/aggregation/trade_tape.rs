@@ -0,0 +1,180 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use shared_models::common::Exchange;
+use shared_models::market::Trade;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
+
+use crate::connectors::MarketDataEvent;
+
+/// 成交明细过滤条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TradeFilter {
+    /// 最小成交数量
+    pub min_size: Option<Decimal>,
+    /// 最小成交额
+    pub min_notional: Option<Decimal>,
+    /// 主动方向：buy / sell
+    pub side: Option<String>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+}
+
+impl TradeFilter {
+    /// 检查成交是否满足过滤条件
+    pub fn matches(&self, trade: &Trade) -> bool {
+        if let Some(min_size) = self.min_size {
+            if trade.quantity < min_size {
+                return false;
+            }
+        }
+
+        if let Some(min_notional) = self.min_notional {
+            if trade.price * trade.quantity < min_notional {
+                return false;
+            }
+        }
+
+        if let Some(side) = &self.side {
+            if !trade.side.eq_ignore_ascii_case(side) {
+                return false;
+            }
+        }
+
+        let ts = trade.timestamp.timestamp_millis();
+        if self.start_time.map_or(false, |start| ts < start) {
+            return false;
+        }
+        if self.end_time.map_or(false, |end| ts > end) {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// 最近成交明细（按交易对保留固定数量的环形缓冲）
+#[derive(Clone)]
+pub struct TradeTape {
+    capacity: usize,
+    tapes: Arc<RwLock<HashMap<String, VecDeque<Trade>>>>,
+}
+
+impl TradeTape {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tapes: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn key(exchange: &Exchange, symbol: &str) -> String {
+        format!("{}:{}", exchange.as_str(), symbol.to_uppercase())
+    }
+
+    /// 记录成交
+    pub async fn record(&self, trade: &Trade) {
+        let key = Self::key(&trade.exchange, &trade.symbol);
+        let mut tapes = self.tapes.write().await;
+        let tape = tapes
+            .entry(key)
+            .or_insert_with(|| VecDeque::with_capacity(self.capacity));
+        if tape.len() >= self.capacity {
+            tape.pop_front();
+        }
+        tape.push_back(trade.clone());
+    }
+
+    /// 按条件查询最近成交，按时间倒序返回
+    pub async fn query(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        filter: &TradeFilter,
+        limit: usize,
+    ) -> Vec<Trade> {
+        let tapes = self.tapes.read().await;
+        match tapes.get(&Self::key(exchange, symbol)) {
+            Some(tape) => tape
+                .iter()
+                .rev()
+                .filter(|trade| filter.matches(trade))
+                .take(limit)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// 启动成交记录任务
+    pub fn start(&self, mut events: broadcast::Receiver<MarketDataEvent>) {
+        let tape = self.clone();
+        tokio::spawn(async move {
+            info!("Trade tape recorder started");
+            loop {
+                match events.recv().await {
+                    Ok(MarketDataEvent::Trade(trade)) => tape.record(&trade).await,
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Trade tape recorder lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            warn!("Trade tape recorder stopped");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn trade(ts: i64, price: i64, qty: i64, side: &str) -> Trade {
+        Trade {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            trade_id: ts.to_string(),
+            timestamp: Utc.timestamp_millis_opt(ts).unwrap(),
+            price: Decimal::from(price),
+            quantity: Decimal::from(qty),
+            quote_quantity: Decimal::from(price * qty),
+            side: side.to_string(),
+            is_buyer_maker: side == "sell",
+            is_best_match: true,
+        }
+    }
+
+    #[test]
+    fn test_trade_filter() {
+        let t = trade(1_000, 100, 5, "buy");
+
+        assert!(TradeFilter::default().matches(&t));
+        assert!(TradeFilter { min_size: Some(Decimal::from(5)), ..Default::default() }.matches(&t));
+        assert!(!TradeFilter { min_size: Some(Decimal::from(6)), ..Default::default() }.matches(&t));
+        assert!(TradeFilter { min_notional: Some(Decimal::from(500)), ..Default::default() }.matches(&t));
+        assert!(!TradeFilter { min_notional: Some(Decimal::from(501)), ..Default::default() }.matches(&t));
+        assert!(!TradeFilter { side: Some("sell".to_string()), ..Default::default() }.matches(&t));
+        assert!(!TradeFilter { start_time: Some(1_001), ..Default::default() }.matches(&t));
+        assert!(!TradeFilter { end_time: Some(999), ..Default::default() }.matches(&t));
+    }
+
+    #[tokio::test]
+    async fn test_tape_capacity_and_order() {
+        let tape = TradeTape::new(3);
+        for i in 0..5 {
+            tape.record(&trade(i, 100, 1, "buy")).await;
+        }
+
+        let trades = tape
+            .query(&Exchange::Binance, "BTCUSDT", &TradeFilter::default(), 10)
+            .await;
+        assert_eq!(trades.len(), 3);
+        assert_eq!(trades[0].trade_id, "4");
+        assert_eq!(trades[2].trade_id, "2");
+    }
+}
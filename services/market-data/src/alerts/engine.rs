@@ -0,0 +1,299 @@
+use chrono::Utc;
+use serde_json::json;
+use shared_models::market::{Trade, WhaleTrade, WhaleTradeKind};
+use shared_protocols::kafka::{
+    KafkaTopics, NotificationEvent, NotificationPriority, NotificationType,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::rules::AlertRule;
+use crate::connectors::MarketDataEvent;
+use crate::publishing::KafkaPublisher;
+
+/// 告警规则引擎
+///
+/// 根据行情事件匹配用户规则，触发后发布通知事件到Kafka由通知服务投递。
+#[derive(Clone)]
+pub struct AlertRuleEngine {
+    rules: Arc<RwLock<HashMap<Uuid, AlertRule>>>,
+    publisher: Arc<KafkaPublisher>,
+}
+
+impl AlertRuleEngine {
+    pub fn new(publisher: Arc<KafkaPublisher>) -> Self {
+        Self {
+            rules: Arc::new(RwLock::new(HashMap::new())),
+            publisher,
+        }
+    }
+
+    /// 添加规则
+    pub async fn add_rule(&self, rule: AlertRule) -> AlertRule {
+        info!(
+            "Alert rule {} added for user {} on {}",
+            rule.id, rule.user_id, rule.symbol
+        );
+        self.rules.write().await.insert(rule.id, rule.clone());
+        rule
+    }
+
+    /// 删除用户自己的规则，规则不存在或属于其他用户时返回 None
+    pub async fn remove_rule(&self, user_id: Uuid, rule_id: Uuid) -> Option<AlertRule> {
+        let mut rules = self.rules.write().await;
+        if rules.get(&rule_id)?.user_id != user_id {
+            return None;
+        }
+        rules.remove(&rule_id)
+    }
+
+    /// 获取规则
+    pub async fn get_rule(&self, rule_id: Uuid) -> Option<AlertRule> {
+        self.rules.read().await.get(&rule_id).cloned()
+    }
+
+    /// 列出用户的规则
+    pub async fn list_rules(&self, user_id: Uuid) -> Vec<AlertRule> {
+        let mut rules: Vec<AlertRule> = self
+            .rules
+            .read()
+            .await
+            .values()
+            .filter(|rule| rule.user_id == user_id)
+            .cloned()
+            .collect();
+        rules.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        rules
+    }
+
+    /// 匹配价格规则
+    pub async fn evaluate_trade(&self, trade: &Trade) -> Vec<NotificationEvent> {
+        let now = Utc::now();
+        let mut notifications = Vec::new();
+
+        let mut rules = self.rules.write().await;
+        for rule in rules.values_mut() {
+            if !rule.applies_to(&trade.exchange, &trade.symbol)
+                || rule.in_cooldown(now)
+                || !rule.condition.matches_trade(trade)
+            {
+                continue;
+            }
+
+            rule.last_triggered_at = Some(now);
+            if rule.condition.is_one_shot() {
+                rule.enabled = false;
+            }
+
+            let mut metadata = HashMap::new();
+            metadata.insert("rule_id".to_string(), json!(rule.id));
+            metadata.insert("exchange".to_string(), json!(trade.exchange.as_str()));
+            metadata.insert("symbol".to_string(), json!(trade.symbol));
+            metadata.insert("price".to_string(), json!(trade.price));
+
+            notifications.push(NotificationEvent {
+                user_id: rule.user_id,
+                notification_type: NotificationType::PriceAlert,
+                title: format!("{} price alert", trade.symbol),
+                message: format!("{} traded at {} on {}", trade.symbol, trade.price, trade.exchange),
+                channels: rule.channels.clone(),
+                priority: NotificationPriority::Normal,
+                metadata,
+            });
+        }
+
+        notifications
+    }
+
+    /// 匹配大额成交规则
+    pub async fn evaluate_whale(&self, whale: &WhaleTrade) -> Vec<NotificationEvent> {
+        let now = Utc::now();
+        let mut notifications = Vec::new();
+
+        let mut rules = self.rules.write().await;
+        for rule in rules.values_mut() {
+            if !rule.applies_to(&whale.exchange, &whale.symbol)
+                || rule.in_cooldown(now)
+                || !rule.condition.matches_whale(whale)
+            {
+                continue;
+            }
+
+            rule.last_triggered_at = Some(now);
+
+            let mut metadata = HashMap::new();
+            metadata.insert("rule_id".to_string(), json!(rule.id));
+            metadata.insert("whale_trade".to_string(), json!(whale));
+
+            let kind = match whale.kind {
+                WhaleTradeKind::Single => "trade",
+                WhaleTradeKind::Burst => "burst",
+            };
+
+            notifications.push(NotificationEvent {
+                user_id: rule.user_id,
+                notification_type: NotificationType::TradingAlert,
+                title: format!("Whale {} on {}", whale.side, whale.symbol),
+                message: format!(
+                    "{} {} {} of {} at {} on {} (impact {} bps)",
+                    whale.symbol,
+                    whale.side,
+                    kind,
+                    whale.notional,
+                    whale.price,
+                    whale.exchange,
+                    whale.price_impact_bps
+                ),
+                channels: rule.channels.clone(),
+                priority: NotificationPriority::High,
+                metadata,
+            });
+        }
+
+        notifications
+    }
+
+    /// 处理大额成交事件并发送通知
+    pub async fn on_whale_trade(&self, whale: &WhaleTrade) {
+        let notifications = self.evaluate_whale(whale).await;
+        self.dispatch(notifications).await;
+    }
+
+    /// 发布通知事件
+    async fn dispatch(&self, notifications: Vec<NotificationEvent>) {
+        for notification in notifications {
+            let key = notification.user_id.to_string();
+            debug!("Dispatching alert notification to user {}", key);
+            if let Err(e) = self
+                .publisher
+                .publish(KafkaTopics::NOTIFICATIONS, &key, "alert_triggered", &notification)
+                .await
+            {
+                warn!("Failed to publish alert notification for {}: {}", key, e);
+            }
+        }
+    }
+
+    /// 启动价格规则匹配任务
+    pub fn start(&self, mut events: broadcast::Receiver<MarketDataEvent>) {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            info!("Alert rule engine started");
+            loop {
+                match events.recv().await {
+                    Ok(MarketDataEvent::Trade(trade)) => {
+                        let notifications = engine.evaluate_trade(&trade).await;
+                        engine.dispatch(notifications).await;
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Alert rule engine lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            warn!("Alert rule engine stopped");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::AlertCondition;
+    use rust_decimal::Decimal;
+    use shared_models::common::Exchange;
+    use shared_protocols::kafka::NotificationChannel;
+
+    fn whale(notional: i64, side: &str) -> WhaleTrade {
+        let now = Utc::now();
+        WhaleTrade {
+            id: Uuid::new_v4(),
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            kind: WhaleTradeKind::Single,
+            side: side.to_string(),
+            price: Decimal::from(10_000),
+            quantity: Decimal::from(notional / 10_000),
+            notional: Decimal::from(notional),
+            trade_count: 1,
+            threshold: Decimal::from(500_000),
+            reference_price: Decimal::from(10_000),
+            end_price: Decimal::from(10_000),
+            price_impact_bps: Decimal::ZERO,
+            first_trade_time: now,
+            last_trade_time: now,
+        }
+    }
+
+    fn rule(condition: AlertCondition, cooldown_seconds: u64) -> AlertRule {
+        AlertRule::new(
+            Uuid::new_v4(),
+            Some(Exchange::Binance),
+            "btcusdt".to_string(),
+            condition,
+            vec![NotificationChannel::InApp],
+            cooldown_seconds,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_whale_rule_matching_and_cooldown() {
+        let engine = AlertRuleEngine::new(Arc::new(KafkaPublisher::disabled()));
+        engine
+            .add_rule(rule(
+                AlertCondition::WhaleTrade {
+                    min_notional: Some(Decimal::from(1_000_000)),
+                    side: Some("buy".to_string()),
+                },
+                60,
+            ))
+            .await;
+
+        assert!(engine.evaluate_whale(&whale(600_000, "buy")).await.is_empty());
+        assert!(engine.evaluate_whale(&whale(2_000_000, "sell")).await.is_empty());
+        assert_eq!(engine.evaluate_whale(&whale(2_000_000, "buy")).await.len(), 1);
+        // 冷却期内不再触发
+        assert!(engine.evaluate_whale(&whale(2_000_000, "buy")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rules_scoped_to_owner() {
+        let engine = AlertRuleEngine::new(Arc::new(KafkaPublisher::disabled()));
+        let condition = AlertCondition::WhaleTrade {
+            min_notional: None,
+            side: None,
+        };
+        let owned = engine.add_rule(rule(condition.clone(), 60)).await;
+        let other = engine.add_rule(rule(condition, 60)).await;
+
+        let listed = engine.list_rules(owned.user_id).await;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, owned.id);
+
+        // 其他用户的规则不能删除
+        assert!(engine.remove_rule(owned.user_id, other.id).await.is_none());
+        assert!(engine.get_rule(other.id).await.is_some());
+        assert_eq!(engine.remove_rule(owned.user_id, owned.id).await.map(|r| r.id), Some(owned.id));
+    }
+
+    #[test]
+    fn test_condition_validation() {
+        assert!(AlertCondition::PriceAbove { price: Decimal::ZERO }.validate().is_err());
+        assert!(AlertCondition::WhaleTrade {
+            min_notional: None,
+            side: Some("long".to_string()),
+        }
+        .validate()
+        .is_err());
+        assert!(AlertCondition::WhaleTrade {
+            min_notional: None,
+            side: None,
+        }
+        .validate()
+        .is_ok());
+    }
+}
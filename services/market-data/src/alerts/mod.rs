@@ -0,0 +1,5 @@
+pub mod engine;
+pub mod rules;
+
+pub use engine::AlertRuleEngine;
+pub use rules::{AlertCondition, AlertRule};
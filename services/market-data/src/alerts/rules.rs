@@ -0,0 +1,129 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared_models::common::Exchange;
+use shared_models::market::{Trade, WhaleTrade};
+use shared_protocols::kafka::NotificationChannel;
+use uuid::Uuid;
+
+/// 告警触发条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// 价格上穿
+    PriceAbove { price: Decimal },
+    /// 价格下穿
+    PriceBelow { price: Decimal },
+    /// 大额成交，未设置时使用检测器阈值
+    WhaleTrade {
+        min_notional: Option<Decimal>,
+        side: Option<String>,
+    },
+}
+
+impl AlertCondition {
+    /// 验证条件参数
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            AlertCondition::PriceAbove { price } | AlertCondition::PriceBelow { price } => {
+                if *price <= Decimal::ZERO {
+                    return Err(anyhow::anyhow!("Alert price must be positive"));
+                }
+            }
+            AlertCondition::WhaleTrade { min_notional, side } => {
+                if min_notional.map_or(false, |n| n <= Decimal::ZERO) {
+                    return Err(anyhow::anyhow!("Whale alert min_notional must be positive"));
+                }
+                if let Some(side) = side {
+                    if !side.eq_ignore_ascii_case("buy") && !side.eq_ignore_ascii_case("sell") {
+                        return Err(anyhow::anyhow!("Whale alert side must be buy or sell"));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 价格条件是否被成交触发
+    pub fn matches_trade(&self, trade: &Trade) -> bool {
+        match self {
+            AlertCondition::PriceAbove { price } => trade.price >= *price,
+            AlertCondition::PriceBelow { price } => trade.price <= *price,
+            AlertCondition::WhaleTrade { .. } => false,
+        }
+    }
+
+    /// 大额成交条件是否被触发
+    pub fn matches_whale(&self, whale: &WhaleTrade) -> bool {
+        match self {
+            AlertCondition::WhaleTrade { min_notional, side } => {
+                min_notional.map_or(true, |n| whale.notional >= n)
+                    && side
+                        .as_ref()
+                        .map_or(true, |s| whale.side.eq_ignore_ascii_case(s))
+            }
+            _ => false,
+        }
+    }
+
+    /// 价格条件触发后即失效，大额成交条件按冷却时间重复触发
+    pub fn is_one_shot(&self) -> bool {
+        !matches!(self, AlertCondition::WhaleTrade { .. })
+    }
+}
+
+/// 用户告警规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 为空时匹配所有交易所
+    pub exchange: Option<Exchange>,
+    pub symbol: String,
+    pub condition: AlertCondition,
+    pub channels: Vec<NotificationChannel>,
+    /// 两次触发之间的最小间隔（秒）
+    pub cooldown_seconds: u64,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+}
+
+impl AlertRule {
+    pub fn new(
+        user_id: Uuid,
+        exchange: Option<Exchange>,
+        symbol: String,
+        condition: AlertCondition,
+        channels: Vec<NotificationChannel>,
+        cooldown_seconds: u64,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            exchange,
+            symbol: symbol.to_uppercase(),
+            condition,
+            channels,
+            cooldown_seconds,
+            enabled: true,
+            created_at: Utc::now(),
+            last_triggered_at: None,
+        }
+    }
+
+    /// 规则是否作用于指定交易对
+    pub fn applies_to(&self, exchange: &Exchange, symbol: &str) -> bool {
+        self.enabled
+            && self.exchange.as_ref().map_or(true, |e| e == exchange)
+            && self.symbol.eq_ignore_ascii_case(symbol)
+    }
+
+    /// 是否处于冷却期
+    pub fn in_cooldown(&self, now: DateTime<Utc>) -> bool {
+        self.last_triggered_at.map_or(false, |last| {
+            (now - last).num_seconds() < self.cooldown_seconds as i64
+        })
+    }
+}
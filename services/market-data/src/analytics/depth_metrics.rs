@@ -0,0 +1,250 @@
+use rust_decimal::Decimal;
+use shared_models::market::{DepthImbalance, DepthMetrics, LiquidityBand, OrderBook};
+use shared_protocols::kafka::KafkaTopics;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
+
+use crate::config::DepthMetricsConfig;
+use crate::connectors::MarketDataEvent;
+use crate::publishing::KafkaPublisher;
+use crate::websocket::{WebSocketBroadcaster, WebSocketEvent};
+
+/// 根据订单簿计算深度指标，订单簿为空的一侧时返回None
+pub fn compute_depth_metrics(
+    book: &OrderBook,
+    imbalance_levels: &[usize],
+    liquidity_bands_bps: &[u32],
+) -> Option<DepthMetrics> {
+    let best_bid = book.bids.first()?;
+    let best_ask = book.asks.first()?;
+
+    let mid_price = (best_bid.price + best_ask.price) / Decimal::TWO;
+    if mid_price.is_zero() {
+        return None;
+    }
+
+    let spread_bps = ((best_ask.price - best_bid.price) / mid_price * Decimal::from(10_000)).round_dp(4);
+
+    // 微观价格：按对手方数量加权，买盘越厚价格越靠近卖一
+    let top_quantity = best_bid.quantity + best_ask.quantity;
+    let microprice = if top_quantity.is_zero() {
+        mid_price
+    } else {
+        ((best_bid.price * best_ask.quantity + best_ask.price * best_bid.quantity) / top_quantity)
+            .round_dp(8)
+    };
+
+    let imbalances = imbalance_levels
+        .iter()
+        .map(|&levels| {
+            let bid_quantity: Decimal = book.bids.iter().take(levels).map(|l| l.quantity).sum();
+            let ask_quantity: Decimal = book.asks.iter().take(levels).map(|l| l.quantity).sum();
+            let total = bid_quantity + ask_quantity;
+            let imbalance = if total.is_zero() {
+                Decimal::ZERO
+            } else {
+                ((bid_quantity - ask_quantity) / total).round_dp(6)
+            };
+            DepthImbalance {
+                levels,
+                bid_quantity,
+                ask_quantity,
+                imbalance,
+            }
+        })
+        .collect();
+
+    let liquidity = liquidity_bands_bps
+        .iter()
+        .map(|&bps| {
+            let offset = mid_price * Decimal::from(bps) / Decimal::from(10_000);
+            let bid_floor = mid_price - offset;
+            let ask_ceiling = mid_price + offset;
+            LiquidityBand {
+                bps,
+                bid_notional: book
+                    .bids
+                    .iter()
+                    .take_while(|l| l.price >= bid_floor)
+                    .map(|l| l.price * l.quantity)
+                    .sum(),
+                ask_notional: book
+                    .asks
+                    .iter()
+                    .take_while(|l| l.price <= ask_ceiling)
+                    .map(|l| l.price * l.quantity)
+                    .sum(),
+            }
+        })
+        .collect();
+
+    Some(DepthMetrics {
+        exchange: book.exchange.clone(),
+        symbol: book.symbol.clone(),
+        timestamp: book.timestamp,
+        best_bid: best_bid.price,
+        best_ask: best_ask.price,
+        mid_price,
+        spread_bps,
+        microprice,
+        imbalances,
+        liquidity,
+    })
+}
+
+/// 订单簿深度指标处理器
+#[derive(Clone)]
+pub struct DepthMetricsProcessor {
+    config: DepthMetricsConfig,
+    latest: Arc<RwLock<HashMap<String, DepthMetrics>>>,
+}
+
+impl DepthMetricsProcessor {
+    pub fn new(config: DepthMetricsConfig) -> Self {
+        Self {
+            config,
+            latest: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn key(exchange: &str, symbol: &str) -> String {
+        format!("{}:{}", exchange, symbol.to_uppercase())
+    }
+
+    /// 处理订单簿更新，按最小间隔节流，返回需要发布的指标
+    pub async fn on_orderbook(&self, book: &OrderBook) -> Option<DepthMetrics> {
+        let key = Self::key(book.exchange.as_str(), &book.symbol);
+
+        let mut latest = self.latest.write().await;
+        if let Some(previous) = latest.get(&key) {
+            let elapsed = (book.timestamp - previous.timestamp).num_milliseconds();
+            if elapsed >= 0 && (elapsed as u64) < self.config.min_interval_ms {
+                return None;
+            }
+        }
+
+        let metrics = compute_depth_metrics(
+            book,
+            &self.config.imbalance_levels,
+            &self.config.liquidity_bands_bps,
+        )?;
+        latest.insert(key, metrics.clone());
+        Some(metrics)
+    }
+
+    /// 获取最新的深度指标
+    pub async fn get_latest(&self, exchange: &str, symbol: &str) -> Option<DepthMetrics> {
+        self.latest
+            .read()
+            .await
+            .get(&Self::key(exchange, symbol))
+            .cloned()
+    }
+
+    /// 启动处理任务
+    pub fn start(
+        &self,
+        mut events: broadcast::Receiver<MarketDataEvent>,
+        broadcaster: Arc<WebSocketBroadcaster>,
+        publisher: Arc<KafkaPublisher>,
+    ) {
+        let processor = self.clone();
+        tokio::spawn(async move {
+            info!("Depth metrics processor started");
+            loop {
+                let book = match events.recv().await {
+                    Ok(MarketDataEvent::OrderBook(book)) => book,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Depth metrics processor lagged, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let metrics = match processor.on_orderbook(&book).await {
+                    Some(metrics) => metrics,
+                    None => continue,
+                };
+
+                let key = Self::key(metrics.exchange.as_str(), &metrics.symbol);
+                if let Err(e) = publisher
+                    .publish(KafkaTopics::MARKET_DEPTH_METRICS, &key, "depth_metrics", &metrics)
+                    .await
+                {
+                    warn!("Failed to publish depth metrics {}: {}", key, e);
+                }
+
+                if let Err(e) = broadcaster.broadcast(WebSocketEvent::DepthMetrics(metrics)).await {
+                    warn!("Failed to broadcast depth metrics {}: {}", key, e);
+                }
+            }
+            warn!("Depth metrics processor stopped");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use shared_models::common::Exchange;
+    use shared_models::market::OrderBookLevel;
+
+    fn level(price: i64, quantity: i64) -> OrderBookLevel {
+        OrderBookLevel {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        }
+    }
+
+    fn book() -> OrderBook {
+        OrderBook {
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            timestamp: Utc::now(),
+            last_update_id: 1,
+            bids: vec![level(9_999, 3), level(9_990, 2), level(9_900, 10)],
+            asks: vec![level(10_001, 1), level(10_010, 1), level(10_100, 10)],
+        }
+    }
+
+    #[test]
+    fn test_depth_metrics() {
+        let metrics = compute_depth_metrics(&book(), &[1, 2], &[10]).unwrap();
+
+        assert_eq!(metrics.mid_price, Decimal::from(10_000));
+        assert_eq!(metrics.spread_bps, Decimal::from(2));
+
+        // 买一数量更大，微观价格偏向卖一
+        assert!(metrics.microprice > metrics.mid_price);
+        assert_eq!(metrics.microprice, Decimal::new(100005, 1));
+
+        assert_eq!(metrics.imbalances[0].imbalance, Decimal::new(5, 1));
+        assert_eq!(metrics.imbalances[1].bid_quantity, Decimal::from(5));
+        assert_eq!(metrics.imbalances[1].ask_quantity, Decimal::from(2));
+
+        // 10bps 范围: 9990 - 10010
+        assert_eq!(metrics.liquidity[0].bid_notional, Decimal::from(9_999 * 3 + 9_990 * 2));
+        assert_eq!(metrics.liquidity[0].ask_notional, Decimal::from(10_001 + 10_010));
+    }
+
+    #[test]
+    fn test_empty_side_returns_none() {
+        let mut book = book();
+        book.asks.clear();
+        assert!(compute_depth_metrics(&book, &[1], &[10]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_processor_throttling() {
+        let processor = DepthMetricsProcessor::new(DepthMetricsConfig::default());
+        let book = book();
+
+        assert!(processor.on_orderbook(&book).await.is_some());
+        assert!(processor.on_orderbook(&book).await.is_none());
+        assert!(processor.get_latest("binance", "btcusdt").await.is_some());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod depth_metrics;
+pub mod query;
+pub mod seasonality;
+pub mod whale;
+
+pub use depth_metrics::{compute_depth_metrics, DepthMetricsProcessor};
+pub use query::{AnalyticsQuery, AnalyticsQueryEngine, QueryResult};
+pub use seasonality::SeasonalityAnalyzer;
+pub use whale::WhaleDetector;
@@ -0,0 +1,621 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shared_models::common::Interval;
+use std::collections::HashSet;
+
+use crate::charting::millis_to_datetime;
+use crate::compaction::sql::{datetime_literal, quote};
+use crate::config::{AnalyticsQueryConfig, ClickHouseConfig, CompactionConfig, RollupConfig};
+use crate::rollups::sql::interval_expr;
+
+/// 时间维度名称
+pub const TIME_DIMENSION: &str = "time";
+
+/// 别名最大长度
+const MAX_ALIAS_LEN: usize = 64;
+
+/// 查询数据集
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dataset {
+    /// 已收盘的1分钟K线
+    Klines,
+    /// 原始Tick
+    Ticks,
+}
+
+/// 数据集允许查询的列
+struct DatasetSchema {
+    time_column: &'static str,
+    base_filter: Option<&'static str>,
+    /// 最小时间分桶
+    min_granularity: Interval,
+    dimensions: &'static [&'static str],
+    numeric: &'static [&'static str],
+}
+
+const KLINE_SCHEMA: DatasetSchema = DatasetSchema {
+    time_column: "open_time",
+    base_filter: Some("interval = '1m' AND is_closed = 1"),
+    min_granularity: Interval::OneMinute,
+    dimensions: &["exchange", "symbol", "data_quality"],
+    numeric: &[
+        "open",
+        "high",
+        "low",
+        "close",
+        "volume",
+        "quote_volume",
+        "trades_count",
+        "taker_buy_base_volume",
+        "taker_buy_quote_volume",
+    ],
+};
+
+const TICK_SCHEMA: DatasetSchema = DatasetSchema {
+    time_column: "timestamp",
+    base_filter: None,
+    min_granularity: Interval::OneSecond,
+    dimensions: &["exchange", "symbol", "data_quality"],
+    numeric: &["price", "volume", "bid", "ask"],
+};
+
+impl Dataset {
+    fn schema(&self) -> &'static DatasetSchema {
+        match self {
+            Dataset::Klines => &KLINE_SCHEMA,
+            Dataset::Ticks => &TICK_SCHEMA,
+        }
+    }
+}
+
+/// 聚合函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+    /// 时间最早的值
+    First,
+    /// 时间最晚的值
+    Last,
+}
+
+impl Aggregation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Aggregation::Count => "count",
+            Aggregation::Sum => "sum",
+            Aggregation::Avg => "avg",
+            Aggregation::Min => "min",
+            Aggregation::Max => "max",
+            Aggregation::First => "first",
+            Aggregation::Last => "last",
+        }
+    }
+}
+
+/// 度量，`count` 可以不指定字段
+#[derive(Debug, Clone, Deserialize)]
+pub struct Measure {
+    pub aggregation: Aggregation,
+    #[serde(default)]
+    pub field: Option<String>,
+    /// 结果列名，默认为 `<聚合>_<字段>`
+    #[serde(default)]
+    pub alias: Option<String>,
+}
+
+/// 过滤操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    In,
+    NotIn,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl FilterOp {
+    fn sql(&self) -> &'static str {
+        match self {
+            FilterOp::Eq => "=",
+            FilterOp::Ne => "!=",
+            FilterOp::In => "IN",
+            FilterOp::NotIn => "NOT IN",
+            FilterOp::Gt => ">",
+            FilterOp::Gte => ">=",
+            FilterOp::Lt => "<",
+            FilterOp::Lte => "<=",
+        }
+    }
+
+    fn is_list(&self) -> bool {
+        matches!(self, FilterOp::In | FilterOp::NotIn)
+    }
+
+    fn is_comparison(&self) -> bool {
+        matches!(self, FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte)
+    }
+}
+
+/// 过滤条件，`value` 为单个值或数组
+#[derive(Debug, Clone, Deserialize)]
+pub struct Filter {
+    pub field: String,
+    pub op: FilterOp,
+    pub value: Value,
+}
+
+/// 分析查询
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnalyticsQuery {
+    pub dataset: Dataset,
+    pub measures: Vec<Measure>,
+    /// 分组维度，`time` 表示按 `granularity` 分桶的时间
+    #[serde(default)]
+    pub dimensions: Vec<String>,
+    #[serde(default)]
+    pub granularity: Option<Interval>,
+    /// 起始时间（毫秒时间戳，含）
+    pub start_time: i64,
+    /// 结束时间（毫秒时间戳，不含）
+    pub end_time: i64,
+    #[serde(default)]
+    pub filters: Vec<Filter>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// 查询不符合DSL或超出限制
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct QueryRejected(pub String);
+
+fn reject<T>(message: impl Into<String>) -> Result<T, QueryRejected> {
+    Err(QueryRejected(message.into()))
+}
+
+/// 编译后的查询
+#[derive(Debug, Clone)]
+pub struct CompiledQuery {
+    pub sql: String,
+    /// 结果列，维度在前
+    pub columns: Vec<String>,
+    pub limit: u32,
+}
+
+/// 查询结果
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    /// 每行一个对象，数值以字符串返回以保留精度
+    pub rows: Vec<Value>,
+    /// 结果超过 limit 被截断
+    pub truncated: bool,
+}
+
+/// 单行JSON结果
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct JsonRow {
+    row: String,
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_ALIAS_LEN
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// 过滤值转为文本，只接受字符串和数字
+fn filter_values(filter: &Filter) -> Result<Vec<String>, QueryRejected> {
+    let scalar = |value: &Value| match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        _ => reject(format!("Filter on {} accepts only strings and numbers", filter.field)),
+    };
+    match &filter.value {
+        Value::Array(values) => values.iter().map(scalar).collect(),
+        value => Ok(vec![scalar(value)?]),
+    }
+}
+
+/// 即席分析查询
+///
+/// 只接受度量、维度、时间范围和过滤条件组成的DSL，字段限定在数据集白名单内，
+/// 字符串值统一转义，编译时检查时间跨度、分桶数和结果行数，
+/// 扫描行数、分组数和执行时间作为查询设置交给ClickHouse强制执行。
+pub struct AnalyticsQueryEngine {
+    config: AnalyticsQueryConfig,
+    database: String,
+    klines_table: String,
+    ticks_table: String,
+    client: Option<clickhouse::Client>,
+}
+
+impl AnalyticsQueryEngine {
+    pub fn new(
+        config: AnalyticsQueryConfig,
+        rollups: &RollupConfig,
+        compaction: &CompactionConfig,
+        clickhouse_config: Option<&ClickHouseConfig>,
+    ) -> Self {
+        let client = clickhouse_config.filter(|_| config.enabled).map(|c| {
+            clickhouse::Client::default()
+                .with_url(&c.url)
+                .with_database(&c.database)
+                .with_user(&c.username)
+                .with_password(&c.password)
+        });
+
+        Self {
+            config,
+            database: clickhouse_config
+                .map(|c| c.database.clone())
+                .unwrap_or_else(|| "market_data".to_string()),
+            klines_table: rollups.source_table.clone(),
+            ticks_table: compaction.ticks_table.clone(),
+            client,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// 校验查询并编译为SQL
+    pub fn compile(&self, query: &AnalyticsQuery) -> Result<CompiledQuery, QueryRejected> {
+        let config = &self.config;
+        let schema = query.dataset.schema();
+
+        if query.measures.is_empty() {
+            return reject("At least one measure is required");
+        }
+        if query.measures.len() > config.max_measures {
+            return reject(format!("At most {} measures are allowed", config.max_measures));
+        }
+        if query.dimensions.len() > config.max_dimensions {
+            return reject(format!("At most {} dimensions are allowed", config.max_dimensions));
+        }
+        if query.filters.len() > config.max_filters {
+            return reject(format!("At most {} filters are allowed", config.max_filters));
+        }
+
+        // 时间范围
+        let start = millis_to_datetime(query.start_time);
+        let end = millis_to_datetime(query.end_time);
+        if query.start_time >= query.end_time {
+            return reject("start_time must be before end_time");
+        }
+        let max_range = match query.dataset {
+            Dataset::Klines => Duration::days(config.max_kline_range_days as i64),
+            Dataset::Ticks => Duration::hours(config.max_tick_range_hours as i64),
+        };
+        if end - start > max_range {
+            return reject(format!(
+                "Time range exceeds {} hours for {:?}",
+                max_range.num_hours(),
+                query.dataset
+            ));
+        }
+
+        // 维度
+        let mut columns = Vec::new();
+        let mut group_by = Vec::new();
+        let mut seen = HashSet::new();
+        for dimension in &query.dimensions {
+            if !seen.insert(dimension.as_str()) {
+                return reject(format!("Duplicate dimension {}", dimension));
+            }
+            if dimension == TIME_DIMENSION {
+                let granularity = self.granularity(query, schema, start, end)?;
+                group_by.push(format!(
+                    "toUnixTimestamp64Milli(toStartOfInterval({}, {})) AS {}",
+                    schema.time_column,
+                    interval_expr(&granularity),
+                    TIME_DIMENSION
+                ));
+            } else if schema.dimensions.contains(&dimension.as_str()) {
+                group_by.push(dimension.clone());
+            } else {
+                return reject(format!("Unknown dimension {} for {:?}", dimension, query.dataset));
+            }
+            columns.push(dimension.clone());
+        }
+
+        // 度量
+        let mut measures = Vec::new();
+        for measure in &query.measures {
+            let expr = match (measure.aggregation, measure.field.as_deref()) {
+                (Aggregation::Count, None) => "count()".to_string(),
+                (_, None) => {
+                    return reject(format!("Measure {} requires a field", measure.aggregation.as_str()))
+                }
+                (_, Some(field)) if !schema.numeric.contains(&field) => {
+                    return reject(format!("Unknown measure field {} for {:?}", field, query.dataset))
+                }
+                (Aggregation::Count, Some(field)) => format!("count({})", field),
+                (Aggregation::First, Some(field)) => format!("argMin({}, {})", field, schema.time_column),
+                (Aggregation::Last, Some(field)) => format!("argMax({}, {})", field, schema.time_column),
+                (aggregation, Some(field)) => format!("{}({})", aggregation.as_str(), field),
+            };
+            let alias = match (&measure.alias, &measure.field) {
+                (Some(alias), _) => alias.clone(),
+                (None, Some(field)) => format!("{}_{}", measure.aggregation.as_str(), field),
+                (None, None) => measure.aggregation.as_str().to_string(),
+            };
+            // 别名与列同名时ClickHouse会用别名替换列引用
+            if !is_identifier(&alias)
+                || schema.numeric.contains(&alias.as_str())
+                || schema.dimensions.contains(&alias.as_str())
+                || alias == schema.time_column
+                || alias == TIME_DIMENSION
+            {
+                return reject(format!("Invalid measure alias {}", alias));
+            }
+            if !seen.insert(alias.as_str()) {
+                return reject(format!("Duplicate column {}", alias));
+            }
+            measures.push(format!("toString({}) AS {}", expr, alias));
+            columns.push(alias);
+        }
+
+        // 过滤
+        let mut predicates = vec![
+            format!("{} >= {}", schema.time_column, datetime_literal(start)),
+            format!("{} < {}", schema.time_column, datetime_literal(end)),
+        ];
+        if let Some(base) = schema.base_filter {
+            predicates.push(base.to_string());
+        }
+        for filter in &query.filters {
+            predicates.push(self.filter_predicate(query.dataset, schema, filter)?);
+        }
+
+        let limit = query
+            .limit
+            .unwrap_or(config.default_limit)
+            .clamp(1, config.max_rows);
+
+        let mut inner = format!(
+            "SELECT {}\nFROM {}.{}\nWHERE {}",
+            group_by.iter().chain(measures.iter()).cloned().collect::<Vec<_>>().join(", "),
+            self.database,
+            match query.dataset {
+                Dataset::Klines => &self.klines_table,
+                Dataset::Ticks => &self.ticks_table,
+            },
+            predicates.join(" AND ")
+        );
+        if !query.dimensions.is_empty() {
+            let keys = query.dimensions.join(", ");
+            inner.push_str(&format!("\nGROUP BY {keys}\nORDER BY {keys}"));
+        }
+        // 多取一行用于判断是否截断
+        inner.push_str(&format!("\nLIMIT {}", limit as u64 + 1));
+
+        let sql = format!(
+            "SELECT formatRowNoNewline('JSONEachRow', {}) AS row FROM (\n{}\n)\nSETTINGS max_execution_time = {}, max_rows_to_read = {}, max_rows_to_group_by = {}, group_by_overflow_mode = 'throw'",
+            columns.join(", "),
+            inner,
+            config.max_execution_seconds,
+            config.max_rows_to_read,
+            config.max_groups
+        );
+
+        Ok(CompiledQuery { sql, columns, limit })
+    }
+
+    /// 时间分桶周期，不能小于数据集精度，分桶数不能超过上限
+    fn granularity(
+        &self,
+        query: &AnalyticsQuery,
+        schema: &DatasetSchema,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Interval, QueryRejected> {
+        let Some(granularity) = query.granularity.clone() else {
+            return reject("granularity is required for the time dimension");
+        };
+        if granularity.to_seconds() < schema.min_granularity.to_seconds() {
+            return reject(format!(
+                "Granularity for {:?} must be at least {}",
+                query.dataset,
+                schema.min_granularity.as_str()
+            ));
+        }
+        let buckets = (end - start).num_seconds() as u64 / granularity.to_seconds().max(1);
+        if buckets > self.config.max_time_buckets as u64 {
+            return reject(format!(
+                "Query spans {} time buckets, limit is {}",
+                buckets, self.config.max_time_buckets
+            ));
+        }
+        Ok(granularity)
+    }
+
+    fn filter_predicate(
+        &self,
+        dataset: Dataset,
+        schema: &DatasetSchema,
+        filter: &Filter,
+    ) -> Result<String, QueryRejected> {
+        let values = filter_values(filter)?;
+        if values.is_empty() {
+            return reject(format!("Filter on {} requires a value", filter.field));
+        }
+        if filter.op.is_list() {
+            if values.len() > self.config.max_filter_values {
+                return reject(format!(
+                    "Filter on {} accepts at most {} values",
+                    filter.field, self.config.max_filter_values
+                ));
+            }
+        } else if values.len() != 1 {
+            return reject(format!("Filter on {} requires a single value", filter.field));
+        }
+
+        let field = filter.field.as_str();
+        let literals: Vec<String> = if schema.dimensions.contains(&field) {
+            if filter.op.is_comparison() {
+                return reject(format!("Filter on {} supports only eq/ne/in/not_in", field));
+            }
+            values
+                .iter()
+                .map(|value| match field {
+                    "symbol" => quote(&value.to_uppercase()),
+                    "exchange" => quote(&value.to_lowercase()),
+                    _ => quote(value),
+                })
+                .collect()
+        } else if schema.numeric.contains(&field) {
+            values
+                .iter()
+                .map(|value| {
+                    let number = value.trim().parse::<Decimal>().map_err(|_| {
+                        QueryRejected(format!("Filter on {} requires numeric values", field))
+                    })?;
+                    Ok(format!("toDecimal128({}, {})", quote(&number.to_string()), number.scale()))
+                })
+                .collect::<Result<_, QueryRejected>>()?
+        } else {
+            return reject(format!("Unknown filter field {} for {:?}", field, dataset));
+        };
+
+        Ok(if filter.op.is_list() {
+            format!("{} {} ({})", field, filter.op.sql(), literals.join(", "))
+        } else {
+            format!("{} {} {}", field, filter.op.sql(), literals[0])
+        })
+    }
+
+    /// 执行编译后的查询
+    pub async fn execute(&self, compiled: CompiledQuery) -> Result<QueryResult> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| anyhow!("Analytics queries are disabled"))?;
+        let rows = client.query(&compiled.sql).fetch_all::<JsonRow>().await?;
+
+        let truncated = rows.len() > compiled.limit as usize;
+        let rows = rows
+            .into_iter()
+            .take(compiled.limit as usize)
+            .map(|row| serde_json::from_str(&row.row))
+            .collect::<Result<Vec<Value>, _>>()?;
+
+        Ok(QueryResult {
+            columns: compiled.columns,
+            rows,
+            truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn engine() -> AnalyticsQueryEngine {
+        AnalyticsQueryEngine::new(
+            AnalyticsQueryConfig::default(),
+            &RollupConfig::default(),
+            &CompactionConfig::default(),
+            None,
+        )
+    }
+
+    fn query(value: Value) -> AnalyticsQuery {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_compile_query() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().timestamp_millis();
+        let end = start + 86_400_000;
+        let compiled = engine()
+            .compile(&query(json!({
+                "dataset": "klines",
+                "measures": [
+                    {"aggregation": "sum", "field": "volume"},
+                    {"aggregation": "last", "field": "close", "alias": "last_close"},
+                    {"aggregation": "count"}
+                ],
+                "dimensions": ["symbol", "time"],
+                "granularity": "1h",
+                "start_time": start,
+                "end_time": end,
+                "filters": [
+                    {"field": "symbol", "op": "in", "value": ["btcusdt", "eth'usdt"]},
+                    {"field": "volume", "op": "gt", "value": 1.5}
+                ]
+            })))
+            .unwrap();
+
+        assert_eq!(compiled.columns, vec!["symbol", "time", "sum_volume", "last_close", "count"]);
+        assert_eq!(compiled.limit, 1000);
+        let sql = &compiled.sql;
+        assert!(sql.contains("toString(sum(volume)) AS sum_volume"));
+        assert!(sql.contains("toString(argMax(close, open_time)) AS last_close"));
+        assert!(sql.contains("toStartOfInterval(open_time, INTERVAL 3600 SECOND)) AS time"));
+        assert!(sql.contains("symbol IN ('BTCUSDT', 'ETH\\'USDT')"));
+        assert!(sql.contains("volume > toDecimal128('1.5', 1)"));
+        assert!(sql.contains("interval = '1m' AND is_closed = 1"));
+        assert!(sql.contains("GROUP BY symbol, time"));
+        assert!(sql.contains("LIMIT 1001"));
+        assert!(sql.contains("group_by_overflow_mode = 'throw'"));
+    }
+
+    #[test]
+    fn test_query_guardrails() {
+        let engine = engine();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().timestamp_millis();
+        let base = json!({
+            "dataset": "ticks",
+            "measures": [{"aggregation": "avg", "field": "price"}],
+            "start_time": start,
+            "end_time": start + 3_600_000
+        });
+        assert!(engine.compile(&query(base.clone())).is_ok());
+
+        let with = |patch: Value| {
+            let mut value = base.clone();
+            for (key, field) in patch.as_object().unwrap() {
+                value[key] = field.clone();
+            }
+            engine.compile(&query(value))
+        };
+
+        // 超过Tick数据集时间跨度
+        assert!(with(json!({"end_time": start + 2 * 86_400_000})).is_err());
+        // 字段不在白名单
+        assert!(with(json!({"measures": [{"aggregation": "sum", "field": "1; DROP TABLE x"}]})).is_err());
+        assert!(with(json!({"dimensions": ["user_id"]})).is_err());
+        // 别名只能是标识符且不能遮蔽列名
+        assert!(with(json!({"measures": [{"aggregation": "avg", "field": "price", "alias": "a b"}]})).is_err());
+        assert!(with(json!({"measures": [{"aggregation": "avg", "field": "price", "alias": "price"}]})).is_err());
+        // 时间维度需要分桶周期，分桶数有上限
+        assert!(with(json!({"dimensions": ["time"]})).is_err());
+        assert!(with(json!({"dimensions": ["time"], "granularity": "1s"})).is_ok());
+        assert!(with(json!({"end_time": start + 86_400_000, "dimensions": ["time"], "granularity": "1s"})).is_err());
+        // 维度字段不支持比较，数值字段要求数字
+        assert!(with(json!({"filters": [{"field": "symbol", "op": "gt", "value": "A"}]})).is_err());
+        assert!(with(json!({"filters": [{"field": "price", "op": "eq", "value": "abc"}]})).is_err());
+        assert!(with(json!({"filters": [{"field": "price", "op": "eq", "value": [1, 2]}]})).is_err());
+        // limit 不超过上限
+        assert_eq!(with(json!({"limit": 1_000_000})).unwrap().limit, 10_000);
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use shared_models::common::Exchange;
+use shared_models::market::{SeasonalityBucket, SeasonalityProfile};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::RwLock;
+
+use crate::charting::millis_to_datetime;
+use crate::config::{ClickHouseConfig, RollupConfig, SeasonalityConfig};
+use crate::rollups::sql::range_predicate;
+
+/// 1分钟K线按小时聚合后的一行
+#[derive(Debug, Clone, clickhouse::Row, Deserialize)]
+pub struct HourlyBar {
+    pub hour_ms: i64,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub volume: String,
+}
+
+/// 按小时聚合已收盘的1分钟K线
+fn hourly_bars_query(
+    database: &str,
+    source_table: &str,
+    exchange: &str,
+    symbol: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> String {
+    format!(
+        "SELECT
+    toInt64(toUnixTimestamp(toStartOfHour(open_time))) * 1000 AS hour_ms,
+    toString(argMin(open, open_time)) AS open,
+    toString(max(high)) AS high,
+    toString(min(low)) AS low,
+    toString(sum(volume)) AS volume
+FROM {database}.{source_table}
+WHERE interval = '1m' AND is_closed = 1 AND {}
+GROUP BY hour_ms
+ORDER BY hour_ms",
+        range_predicate(exchange, symbol, "open_time", start, end)
+    )
+}
+
+fn ratio(value: Decimal, mean: Decimal) -> Decimal {
+    if mean.is_zero() {
+        Decimal::ZERO
+    } else {
+        (value / mean).round_dp(4)
+    }
+}
+
+/// 由小时K线计算星期×小时的成交量和振幅画像，无法解析的行被跳过
+pub fn compute_seasonality(
+    exchange: Exchange,
+    symbol: &str,
+    bars: &[HourlyBar],
+    lookback_days: u32,
+    now: DateTime<Utc>,
+) -> SeasonalityProfile {
+    let mut slots: BTreeMap<(u8, u8), (u32, Decimal, Decimal)> = BTreeMap::new();
+    let (mut total_volume, mut total_range, mut total_samples) = (Decimal::ZERO, Decimal::ZERO, 0u32);
+
+    for bar in bars {
+        let (Ok(open), Ok(high), Ok(low), Ok(volume)) = (
+            bar.open.parse::<Decimal>(),
+            bar.high.parse::<Decimal>(),
+            bar.low.parse::<Decimal>(),
+            bar.volume.parse::<Decimal>(),
+        ) else {
+            continue;
+        };
+        if open <= Decimal::ZERO {
+            continue;
+        }
+        let range_bps = (high - low) / open * Decimal::from(10_000);
+        let at = millis_to_datetime(bar.hour_ms);
+        let key = (at.weekday().number_from_monday() as u8, at.hour() as u8);
+
+        let slot = slots.entry(key).or_insert((0, Decimal::ZERO, Decimal::ZERO));
+        slot.0 += 1;
+        slot.1 += volume;
+        slot.2 += range_bps;
+        total_volume += volume;
+        total_range += range_bps;
+        total_samples += 1;
+    }
+
+    let (mean_volume, mean_range) = if total_samples == 0 {
+        (Decimal::ZERO, Decimal::ZERO)
+    } else {
+        let samples = Decimal::from(total_samples);
+        (total_volume / samples, total_range / samples)
+    };
+
+    let buckets = slots
+        .into_iter()
+        .map(|((day_of_week, hour), (samples, volume, range))| {
+            let avg_volume = volume / Decimal::from(samples);
+            let avg_range_bps = range / Decimal::from(samples);
+            SeasonalityBucket {
+                day_of_week,
+                hour,
+                samples,
+                avg_volume: avg_volume.round_dp(8),
+                volume_ratio: ratio(avg_volume, mean_volume),
+                avg_range_bps: avg_range_bps.round_dp(2),
+                volatility_ratio: ratio(avg_range_bps, mean_range),
+            }
+        })
+        .collect();
+
+    SeasonalityProfile {
+        exchange,
+        symbol: symbol.to_string(),
+        lookback_days,
+        computed_at: now,
+        buckets,
+    }
+}
+
+/// 日内季节性分析
+///
+/// 从ClickHouse中的1分钟K线按需计算，结果按交易对缓存 `cache_ttl_seconds`。
+pub struct SeasonalityAnalyzer {
+    config: SeasonalityConfig,
+    database: String,
+    source_table: String,
+    client: Option<clickhouse::Client>,
+    cache: RwLock<HashMap<(String, String), SeasonalityProfile>>,
+}
+
+impl SeasonalityAnalyzer {
+    pub fn new(
+        config: SeasonalityConfig,
+        rollups: &RollupConfig,
+        clickhouse_config: Option<&ClickHouseConfig>,
+    ) -> Self {
+        let client = clickhouse_config.filter(|_| config.enabled).map(|c| {
+            clickhouse::Client::default()
+                .with_url(&c.url)
+                .with_database(&c.database)
+                .with_user(&c.username)
+                .with_password(&c.password)
+        });
+
+        Self {
+            config,
+            database: clickhouse_config
+                .map(|c| c.database.clone())
+                .unwrap_or_else(|| "market_data".to_string()),
+            source_table: rollups.source_table.clone(),
+            client,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// 获取交易对的季节性画像，缓存过期时重新计算
+    pub async fn profile(&self, exchange: Exchange, symbol: &str) -> Result<SeasonalityProfile> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| anyhow!("Seasonality analytics are disabled"))?;
+        let symbol = symbol.to_uppercase();
+        let key = (exchange.as_str().to_string(), symbol.clone());
+        let now = Utc::now();
+        let ttl = Duration::seconds(self.config.cache_ttl_seconds as i64);
+
+        if let Some(profile) = self.cache.read().await.get(&key) {
+            if now - profile.computed_at < ttl {
+                return Ok(profile.clone());
+            }
+        }
+
+        let start = now - Duration::days(self.config.lookback_days as i64);
+        let bars = client
+            .query(&hourly_bars_query(
+                &self.database,
+                &self.source_table,
+                exchange.as_str(),
+                &symbol,
+                start,
+                now,
+            ))
+            .fetch_all::<HourlyBar>()
+            .await?;
+        let profile = compute_seasonality(exchange, &symbol, &bars, self.config.lookback_days, now);
+
+        self.cache.write().await.insert(key, profile.clone());
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn bar(at: DateTime<Utc>, low: &str, high: &str, volume: &str) -> HourlyBar {
+        HourlyBar {
+            hour_ms: at.timestamp_millis(),
+            open: "100".to_string(),
+            high: high.to_string(),
+            low: low.to_string(),
+            volume: volume.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_compute_seasonality() {
+        // 2024-01-01 为周一
+        let monday = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let bars = vec![
+            bar(monday + Duration::hours(14), "99", "101", "300"),
+            bar(monday + Duration::days(7) + Duration::hours(14), "99", "101", "300"),
+            bar(monday + Duration::hours(3), "99.5", "100.5", "100"),
+            bar(monday + Duration::days(6) + Duration::hours(3), "99.5", "100.5", "100"),
+            HourlyBar {
+                open: "bad".to_string(),
+                ..bar(monday, "0", "0", "0")
+            },
+        ];
+
+        let profile = compute_seasonality(Exchange::Binance, "BTCUSDT", &bars, 28, monday);
+        assert_eq!(profile.buckets.len(), 3);
+
+        let busy = profile.bucket(1, 14).unwrap();
+        assert_eq!(busy.samples, 2);
+        assert_eq!(busy.avg_volume, Decimal::from(300));
+        assert_eq!(busy.volume_ratio, Decimal::new(15, 1));
+        assert_eq!(busy.avg_range_bps, Decimal::from(200));
+
+        let quiet = profile.bucket(7, 3).unwrap();
+        assert_eq!(quiet.volume_ratio, Decimal::new(5, 1));
+        assert!(quiet.volatility_ratio < Decimal::ONE);
+        assert!(profile.bucket(1, 0).is_none());
+    }
+}
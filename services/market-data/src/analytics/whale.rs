@@ -0,0 +1,340 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use shared_models::market::{Trade, WhaleTrade, WhaleTradeKind};
+use shared_protocols::kafka::KafkaTopics;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::alerts::AlertRuleEngine;
+use crate::config::WhaleDetectionConfig;
+use crate::connectors::MarketDataEvent;
+use crate::publishing::KafkaPublisher;
+use crate::websocket::{WebSocketBroadcaster, WebSocketEvent};
+
+/// 同方向连续成交累计
+#[derive(Debug, Clone)]
+struct Burst {
+    side: String,
+    trade_count: u32,
+    quantity: Decimal,
+    notional: Decimal,
+    reference_price: Decimal,
+    last_price: Decimal,
+    first_trade_time: DateTime<Utc>,
+    last_trade_time: DateTime<Utc>,
+}
+
+impl Burst {
+    fn start(trade: &Trade, reference_price: Decimal) -> Self {
+        Self {
+            side: trade.side.clone(),
+            trade_count: 1,
+            quantity: trade.quantity,
+            notional: trade.price * trade.quantity,
+            reference_price,
+            last_price: trade.price,
+            first_trade_time: trade.timestamp,
+            last_trade_time: trade.timestamp,
+        }
+    }
+
+    fn add(&mut self, trade: &Trade) {
+        self.trade_count += 1;
+        self.quantity += trade.quantity;
+        self.notional += trade.price * trade.quantity;
+        self.last_price = trade.price;
+        self.last_trade_time = trade.timestamp;
+    }
+}
+
+/// 单个交易对的检测状态
+#[derive(Debug, Default)]
+struct SymbolState {
+    last_price: Option<Decimal>,
+    burst: Option<Burst>,
+}
+
+/// 计算价格冲击（基点），参考价格为零时返回零
+pub fn price_impact_bps(reference_price: Decimal, end_price: Decimal) -> Decimal {
+    if reference_price.is_zero() {
+        return Decimal::ZERO;
+    }
+    ((end_price - reference_price) / reference_price * Decimal::from(10_000)).round_dp(2)
+}
+
+/// 大额成交检测器
+#[derive(Clone)]
+pub struct WhaleDetector {
+    config: WhaleDetectionConfig,
+    states: Arc<RwLock<HashMap<String, SymbolState>>>,
+    history: Arc<RwLock<HashMap<String, VecDeque<WhaleTrade>>>>,
+}
+
+impl WhaleDetector {
+    pub fn new(config: WhaleDetectionConfig) -> Self {
+        Self {
+            config,
+            states: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn key(exchange: &str, symbol: &str) -> String {
+        format!("{}:{}", exchange, symbol.to_uppercase())
+    }
+
+    /// 处理成交，超过阈值的单笔成交或连续成交返回大额成交事件
+    pub async fn on_trade(&self, trade: &Trade) -> Option<WhaleTrade> {
+        let key = Self::key(trade.exchange.as_str(), &trade.symbol);
+        let threshold = self.config.threshold_for(&trade.symbol);
+        let notional = trade.price * trade.quantity;
+
+        let whale = {
+            let mut states = self.states.write().await;
+            let state = states.entry(key.clone()).or_default();
+            let reference_price = state.last_price.unwrap_or(trade.price);
+            state.last_price = Some(trade.price);
+
+            // 1. 单笔成交超过阈值
+            if notional >= threshold {
+                state.burst = None;
+                Some(self.build_whale(
+                    trade,
+                    WhaleTradeKind::Single,
+                    Burst::start(trade, reference_price),
+                    threshold,
+                ))
+            } else {
+                // 2. 累计同方向、窗口内的连续成交
+                let window_ms = self.config.burst_window_ms;
+                match state.burst.as_mut() {
+                    Some(burst)
+                        if burst.side.eq_ignore_ascii_case(&trade.side)
+                            && (trade.timestamp - burst.first_trade_time).num_milliseconds()
+                                <= window_ms =>
+                    {
+                        burst.add(trade);
+                    }
+                    _ => state.burst = Some(Burst::start(trade, reference_price)),
+                }
+
+                // 3. 累计成交额超过阈值
+                match state.burst.take() {
+                    Some(burst) if burst.notional >= threshold => Some(self.build_whale(
+                        trade,
+                        WhaleTradeKind::Burst,
+                        burst,
+                        threshold,
+                    )),
+                    burst => {
+                        state.burst = burst;
+                        None
+                    }
+                }
+            }
+        }?;
+
+        let mut history = self.history.write().await;
+        let entries = history.entry(key).or_insert_with(VecDeque::new);
+        if entries.len() >= self.config.history_size {
+            entries.pop_front();
+        }
+        entries.push_back(whale.clone());
+
+        Some(whale)
+    }
+
+    fn build_whale(
+        &self,
+        trade: &Trade,
+        kind: WhaleTradeKind,
+        burst: Burst,
+        threshold: Decimal,
+    ) -> WhaleTrade {
+        let price = if burst.quantity.is_zero() {
+            burst.last_price
+        } else {
+            (burst.notional / burst.quantity).round_dp(8)
+        };
+
+        WhaleTrade {
+            id: Uuid::new_v4(),
+            exchange: trade.exchange.clone(),
+            symbol: trade.symbol.to_uppercase(),
+            kind,
+            side: burst.side,
+            price,
+            quantity: burst.quantity,
+            notional: burst.notional,
+            trade_count: burst.trade_count,
+            threshold,
+            reference_price: burst.reference_price,
+            end_price: burst.last_price,
+            price_impact_bps: price_impact_bps(burst.reference_price, burst.last_price),
+            first_trade_time: burst.first_trade_time,
+            last_trade_time: burst.last_trade_time,
+        }
+    }
+
+    /// 获取最近的大额成交，按时间倒序返回
+    pub async fn get_recent(&self, exchange: &str, symbol: &str, limit: usize) -> Vec<WhaleTrade> {
+        self.history
+            .read()
+            .await
+            .get(&Self::key(exchange, symbol))
+            .map(|entries| entries.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 启动检测任务
+    pub fn start(
+        &self,
+        mut events: broadcast::Receiver<MarketDataEvent>,
+        broadcaster: Arc<WebSocketBroadcaster>,
+        publisher: Arc<KafkaPublisher>,
+        alert_engine: Arc<AlertRuleEngine>,
+    ) {
+        let detector = self.clone();
+        tokio::spawn(async move {
+            info!("Whale trade detector started");
+            loop {
+                let trade = match events.recv().await {
+                    Ok(MarketDataEvent::Trade(trade)) => trade,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Whale trade detector lagged, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let whale = match detector.on_trade(&trade).await {
+                    Some(whale) => whale,
+                    None => continue,
+                };
+
+                info!(
+                    "Whale {} detected on {}:{} - {} notional {} ({} bps)",
+                    whale.side,
+                    whale.exchange,
+                    whale.symbol,
+                    match whale.kind {
+                        WhaleTradeKind::Single => "trade",
+                        WhaleTradeKind::Burst => "burst",
+                    },
+                    whale.notional,
+                    whale.price_impact_bps
+                );
+
+                let key = Self::key(whale.exchange.as_str(), &whale.symbol);
+                if let Err(e) = publisher
+                    .publish(KafkaTopics::MARKET_WHALE_TRADES, &key, "whale_trade", &whale)
+                    .await
+                {
+                    warn!("Failed to publish whale trade {}: {}", key, e);
+                }
+
+                alert_engine.on_whale_trade(&whale).await;
+
+                if let Err(e) = broadcaster.broadcast(WebSocketEvent::WhaleTrade(whale)).await {
+                    warn!("Failed to broadcast whale trade {}: {}", key, e);
+                }
+            }
+            warn!("Whale trade detector stopped");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use shared_models::common::Exchange;
+
+    fn trade(ts: i64, price: i64, qty: i64, side: &str) -> Trade {
+        Trade {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            trade_id: ts.to_string(),
+            timestamp: Utc.timestamp_millis_opt(ts).unwrap(),
+            price: Decimal::from(price),
+            quantity: Decimal::from(qty),
+            quote_quantity: Decimal::from(price * qty),
+            side: side.to_string(),
+            is_buyer_maker: side == "sell",
+            is_best_match: true,
+        }
+    }
+
+    fn detector() -> WhaleDetector {
+        let mut config = WhaleDetectionConfig::default();
+        config.default_min_notional = Decimal::from(1_000_000);
+        config
+            .symbol_thresholds
+            .insert("ETHUSDT".to_string(), Decimal::from(100_000));
+        WhaleDetector::new(config)
+    }
+
+    #[test]
+    fn test_price_impact() {
+        assert_eq!(price_impact_bps(Decimal::from(10_000), Decimal::from(10_010)), Decimal::from(10));
+        assert_eq!(price_impact_bps(Decimal::from(10_000), Decimal::from(9_990)), Decimal::from(-10));
+        assert_eq!(price_impact_bps(Decimal::ZERO, Decimal::from(1)), Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_single_whale_trade() {
+        let detector = detector();
+
+        assert!(detector.on_trade(&trade(0, 10_000, 1, "buy")).await.is_none());
+
+        let whale = detector.on_trade(&trade(5_000, 10_020, 100, "buy")).await.unwrap();
+        assert_eq!(whale.kind, WhaleTradeKind::Single);
+        assert_eq!(whale.notional, Decimal::from(1_002_000));
+        assert_eq!(whale.reference_price, Decimal::from(10_000));
+        assert_eq!(whale.price_impact_bps, Decimal::from(20));
+
+        let recent = detector.get_recent("binance", "btcusdt", 10).await;
+        assert_eq!(recent.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_burst_detection() {
+        let detector = detector();
+
+        // 窗口内同方向成交累计超过阈值
+        assert!(detector.on_trade(&trade(0, 10_000, 40, "sell")).await.is_none());
+        assert!(detector.on_trade(&trade(200, 9_990, 40, "sell")).await.is_none());
+        let whale = detector.on_trade(&trade(400, 9_980, 30, "sell")).await.unwrap();
+
+        assert_eq!(whale.kind, WhaleTradeKind::Burst);
+        assert_eq!(whale.trade_count, 3);
+        assert_eq!(whale.quantity, Decimal::from(110));
+        assert_eq!(whale.end_price, Decimal::from(9_980));
+        assert!(whale.price_impact_bps < Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_burst_resets_on_side_change_and_window() {
+        let detector = detector();
+
+        assert!(detector.on_trade(&trade(0, 10_000, 60, "buy")).await.is_none());
+        assert!(detector.on_trade(&trade(100, 10_000, 60, "sell")).await.is_none());
+        // 超出累计窗口
+        assert!(detector.on_trade(&trade(1_500, 10_000, 60, "sell")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_symbol_threshold_override() {
+        let detector = detector();
+        let mut eth = trade(0, 2_000, 60, "buy");
+        eth.symbol = "ETHUSDT".to_string();
+
+        let whale = detector.on_trade(&eth).await.unwrap();
+        assert_eq!(whale.threshold, Decimal::from(100_000));
+    }
+}
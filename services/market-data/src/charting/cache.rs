@@ -0,0 +1,127 @@
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use super::ChartCandle;
+use crate::config::{ChartConfig, RedisConfig};
+
+/// 图表数据Redis缓存
+#[derive(Clone)]
+pub struct ChartCache {
+    config: ChartConfig,
+    key_prefix: String,
+    redis: Option<Arc<RwLock<ConnectionManager>>>,
+}
+
+impl ChartCache {
+    /// 创建图表缓存，Redis不可用时降级为无缓存
+    pub async fn new(config: ChartConfig, redis_config: Option<&RedisConfig>) -> Self {
+        let key_prefix = redis_config
+            .map(|c| c.key_prefix.clone())
+            .unwrap_or_else(|| "market_data:".to_string());
+
+        let redis = match redis_config {
+            Some(redis_config) if config.cache_enabled => {
+                match Self::connect(&redis_config.url).await {
+                    Ok(conn) => Some(Arc::new(RwLock::new(conn))),
+                    Err(e) => {
+                        warn!("Chart cache disabled, failed to connect to Redis: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        Self {
+            config,
+            key_prefix,
+            redis,
+        }
+    }
+
+    async fn connect(url: &str) -> Result<ConnectionManager> {
+        let client = redis::Client::open(url)?;
+        Ok(ConnectionManager::new(client).await?)
+    }
+
+    /// 构建缓存键
+    pub fn cache_key(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        interval: &str,
+        start_time: i64,
+        end_time: i64,
+        fill_gaps: bool,
+    ) -> String {
+        format!(
+            "{}chart:{}:{}:{}:{}:{}:{}",
+            self.key_prefix,
+            exchange,
+            symbol.to_uppercase(),
+            interval,
+            start_time,
+            end_time,
+            fill_gaps as u8
+        )
+    }
+
+    /// 读取缓存
+    pub async fn get(&self, key: &str) -> Option<Vec<ChartCandle>> {
+        use redis::AsyncCommands;
+
+        let redis = self.redis.as_ref()?;
+        let mut conn = redis.write().await;
+        match conn.get::<_, Option<String>>(key).await {
+            Ok(Some(json)) => {
+                debug!("Chart cache hit: {}", key);
+                serde_json::from_str(&json).ok()
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Chart cache read failed for {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    /// 写入缓存
+    ///
+    /// 包含当前未收盘周期的区间使用较短的TTL。
+    pub async fn set(&self, key: &str, candles: &[ChartCandle], end_time: i64) {
+        use redis::AsyncCommands;
+
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return,
+        };
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let ttl = if end_time >= now - self.config.hot_range_ms {
+            self.config.hot_ttl_seconds
+        } else {
+            self.config.cold_ttl_seconds
+        };
+
+        let json = match serde_json::to_string(candles) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialize chart candles: {}", e);
+                return;
+            }
+        };
+
+        let mut conn = redis.write().await;
+        if let Err(e) = conn.set_ex::<_, _, ()>(key, json, ttl).await {
+            warn!("Chart cache write failed for {}: {}", key, e);
+        }
+    }
+
+    /// 缓存是否可用
+    pub fn is_enabled(&self) -> bool {
+        self.redis.is_some()
+    }
+}
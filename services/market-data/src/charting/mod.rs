@@ -0,0 +1,233 @@
+pub mod cache;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared_models::common::Interval;
+use shared_models::market::Kline;
+
+pub use cache::ChartCache;
+
+/// 图表K线（支持降采样和间隙填充）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartCandle {
+    pub open_time: i64,
+    pub close_time: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub quote_volume: Decimal,
+    pub trades_count: u32,
+    /// 是否为间隙填充生成的合成数据
+    pub synthetic: bool,
+}
+
+impl ChartCandle {
+    /// 从K线创建
+    pub fn from_kline(kline: &Kline) -> Self {
+        Self {
+            open_time: kline.open_time.timestamp_millis(),
+            close_time: kline.close_time.timestamp_millis(),
+            open: kline.open,
+            high: kline.high,
+            low: kline.low,
+            close: kline.close,
+            volume: kline.volume,
+            quote_volume: kline.quote_volume,
+            trades_count: kline.trades_count,
+            synthetic: false,
+        }
+    }
+
+    /// 创建合成K线（价格沿用上一根收盘价，成交量为0）
+    pub fn synthetic(open_time: i64, interval_ms: i64, price: Decimal) -> Self {
+        Self {
+            open_time,
+            close_time: open_time + interval_ms - 1,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: Decimal::ZERO,
+            quote_volume: Decimal::ZERO,
+            trades_count: 0,
+            synthetic: true,
+        }
+    }
+
+    /// 合并后一根K线
+    fn merge(&mut self, other: &ChartCandle) {
+        self.high = self.high.max(other.high);
+        self.low = self.low.min(other.low);
+        self.close = other.close;
+        self.close_time = other.close_time;
+        self.volume += other.volume;
+        self.quote_volume += other.quote_volume;
+        self.trades_count += other.trades_count;
+        // 只要包含真实数据就不算合成
+        self.synthetic = self.synthetic && other.synthetic;
+    }
+}
+
+/// 对齐到周期起点
+pub fn align_to_interval(timestamp_ms: i64, interval_ms: i64) -> i64 {
+    timestamp_ms - timestamp_ms.rem_euclid(interval_ms)
+}
+
+/// 向上对齐到周期终点，包含 `timestamp_ms` 所在的未收盘周期
+pub fn align_end_to_interval(timestamp_ms: i64, interval_ms: i64) -> i64 {
+    align_to_interval(timestamp_ms + interval_ms - 1, interval_ms)
+}
+
+/// 将K线降采样到目标周期（输入需按 open_time 升序）
+pub fn downsample(candles: &[ChartCandle], target: &Interval) -> Vec<ChartCandle> {
+    let target_ms = target.to_millis();
+    let mut result: Vec<ChartCandle> = Vec::new();
+
+    for candle in candles {
+        let bucket = align_to_interval(candle.open_time, target_ms);
+        match result.last_mut() {
+            Some(last) if last.open_time == bucket => last.merge(candle),
+            _ => {
+                let mut aggregated = candle.clone();
+                aggregated.open_time = bucket;
+                result.push(aggregated);
+            }
+        }
+    }
+
+    // 收盘时间统一对齐到目标周期末尾
+    for candle in result.iter_mut() {
+        candle.close_time = candle.open_time + target_ms - 1;
+    }
+
+    result
+}
+
+/// 填充缺失的K线
+///
+/// `tolerance_ms` 为连续性检测器对该周期允许的时间误差，
+/// 误差范围内的偏移不视为间隙。
+pub fn fill_gaps(
+    candles: &[ChartCandle],
+    interval: &Interval,
+    tolerance_ms: i64,
+) -> Vec<ChartCandle> {
+    let interval_ms = interval.to_millis();
+    let mut result: Vec<ChartCandle> = Vec::with_capacity(candles.len());
+
+    for candle in candles {
+        if let Some(prev) = result.last() {
+            let mut expected = prev.open_time + interval_ms;
+            let price = prev.close;
+            while candle.open_time - expected > tolerance_ms {
+                result.push(ChartCandle::synthetic(expected, interval_ms, price));
+                expected += interval_ms;
+            }
+        }
+        result.push(candle.clone());
+    }
+
+    result
+}
+
+/// 毫秒时间戳转换为UTC时间
+pub fn millis_to_datetime(timestamp_ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(timestamp_ms)
+        .single()
+        .unwrap_or_else(Utc::now)
+}
+
+/// 为目标周期选择合适的源周期
+pub fn source_interval_for(target: &Interval) -> Interval {
+    match target {
+        Interval::OneSecond => Interval::OneSecond,
+        Interval::OneMinute
+        | Interval::ThreeMinutes
+        | Interval::FiveMinutes
+        | Interval::FifteenMinutes
+        | Interval::ThirtyMinutes => Interval::OneMinute,
+        Interval::OneHour | Interval::TwoHours | Interval::FourHours => Interval::FiveMinutes,
+        Interval::SixHours | Interval::EightHours | Interval::TwelveHours => Interval::OneHour,
+        Interval::OneDay | Interval::ThreeDays => Interval::OneHour,
+        Interval::OneWeek | Interval::OneMonth => Interval::OneDay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(open_time: i64, open: i64, high: i64, low: i64, close: i64, volume: i64) -> ChartCandle {
+        ChartCandle {
+            open_time,
+            close_time: open_time + 59_999,
+            open: Decimal::from(open),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(close),
+            volume: Decimal::from(volume),
+            quote_volume: Decimal::ZERO,
+            trades_count: 1,
+            synthetic: false,
+        }
+    }
+
+    #[test]
+    fn test_downsample_minutes_to_five_minutes() {
+        let base = 1_640_995_200_000; // 2022-01-01 00:00:00
+        let candles: Vec<_> = (0..10)
+            .map(|i| candle(base + i * 60_000, 100 + i, 110 + i, 90 + i, 101 + i, 1))
+            .collect();
+
+        let result = downsample(&candles, &Interval::FiveMinutes);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].open_time, base);
+        assert_eq!(result[0].open, Decimal::from(100));
+        assert_eq!(result[0].high, Decimal::from(114));
+        assert_eq!(result[0].low, Decimal::from(90));
+        assert_eq!(result[0].close, Decimal::from(105));
+        assert_eq!(result[0].volume, Decimal::from(5));
+        assert_eq!(result[0].trades_count, 5);
+        assert_eq!(result[1].open_time, base + 300_000);
+        assert_eq!(result[1].close_time, base + 600_000 - 1);
+    }
+
+    #[test]
+    fn test_fill_gaps_marks_synthetic() {
+        let base = 1_640_995_200_000;
+        let candles = vec![
+            candle(base, 100, 101, 99, 100, 1),
+            candle(base + 180_000, 102, 103, 101, 102, 1),
+        ];
+
+        let result = fill_gaps(&candles, &Interval::OneMinute, 5_000);
+        assert_eq!(result.len(), 4);
+        assert!(result[1].synthetic);
+        assert!(result[2].synthetic);
+        assert_eq!(result[1].close, Decimal::from(100));
+        assert_eq!(result[1].volume, Decimal::ZERO);
+        assert!(!result[3].synthetic);
+    }
+
+    #[test]
+    fn test_fill_gaps_respects_tolerance() {
+        let base = 1_640_995_200_000;
+        let candles = vec![
+            candle(base, 100, 101, 99, 100, 1),
+            candle(base + 63_000, 100, 101, 99, 100, 1),
+        ];
+
+        assert_eq!(fill_gaps(&candles, &Interval::OneMinute, 5_000).len(), 2);
+    }
+
+    #[test]
+    fn test_align_to_interval() {
+        assert_eq!(align_to_interval(1_640_995_230_000, 60_000), 1_640_995_200_000);
+        assert_eq!(align_to_interval(1_640_995_200_000, 60_000), 1_640_995_200_000);
+        assert_eq!(align_end_to_interval(1_640_995_230_000, 60_000), 1_640_995_260_000);
+        assert_eq!(align_end_to_interval(1_640_995_260_000, 60_000), 1_640_995_260_000);
+    }
+}
@@ -0,0 +1,422 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use shared_utils::{Fence, LeaderElection};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{error, info, warn};
+
+use super::sql;
+use crate::config::{BarResolution, ClickHouseConfig, CompactionConfig};
+use crate::schema::DecimalSchema;
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct CountRow {
+    count: u64,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct SymbolRow {
+    exchange: String,
+    symbol: String,
+}
+
+/// 失去租约的旧领导者不再写入K线或删除原始数据
+async fn ensure_current(fence: Option<&Fence>) -> Result<()> {
+    match fence {
+        Some(fence) if !fence.verify().await => Err(anyhow!(
+            "Lost leadership of {} (token {}), skipping write",
+            fence.job(),
+            fence.token()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// 单个策略/交易所的压缩任务
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionTask {
+    pub policy: String,
+    pub exchange: String,
+    pub symbols: Vec<String>,
+    pub resolution: BarResolution,
+    /// 早于该时间的原始Tick会被压缩
+    pub cutoff: DateTime<Utc>,
+    pub raw_rows: u64,
+    pub buckets: u64,
+    pub bars_written: bool,
+    pub ticks_deleted: bool,
+    pub error: Option<String>,
+}
+
+/// 压缩执行报告
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionReport {
+    pub dry_run: bool,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub tasks: Vec<CompactionTask>,
+    /// 没有匹配策略的交易对，保留原始数据
+    pub unmatched_symbols: Vec<String>,
+}
+
+impl CompactionReport {
+    /// 涉及的原始Tick总行数
+    pub fn total_raw_rows(&self) -> u64 {
+        self.tasks.iter().map(|t| t.raw_rows).sum()
+    }
+
+    /// 压缩后的K线总数
+    pub fn total_buckets(&self) -> u64 {
+        self.tasks.iter().map(|t| t.buckets).sum()
+    }
+
+    pub fn failed_tasks(&self) -> usize {
+        self.tasks.iter().filter(|t| t.error.is_some()).count()
+    }
+}
+
+/// 截止时间向下对齐到分辨率边界，避免同一时间桶被拆成两次压缩
+fn aligned_cutoff(now: DateTime<Utc>, retention_days: u32, resolution: BarResolution) -> DateTime<Utc> {
+    let cutoff = now - Duration::days(retention_days as i64);
+    let step = match resolution {
+        BarResolution::OneSecond => Duration::seconds(1),
+        BarResolution::OneMinute => Duration::minutes(1),
+    };
+    cutoff.duration_trunc(step).unwrap_or(cutoff)
+}
+
+/// 按策略和交易所对交易对分组
+fn plan_tasks(
+    config: &CompactionConfig,
+    symbols: Vec<(String, String)>,
+    now: DateTime<Utc>,
+) -> (Vec<CompactionTask>, Vec<String>) {
+    let mut groups: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+    let mut unmatched = Vec::new();
+
+    for (exchange, symbol) in symbols {
+        match config.policy_for(&exchange, &symbol) {
+            Some(policy) => groups
+                .entry((policy.name.clone(), exchange))
+                .or_default()
+                .push(symbol),
+            None => unmatched.push(format!("{}:{}", exchange, symbol)),
+        }
+    }
+
+    let tasks = groups
+        .into_iter()
+        .filter_map(|((policy_name, exchange), mut symbols)| {
+            let policy = config.policies.iter().find(|p| p.name == policy_name)?;
+            symbols.sort();
+            Some(CompactionTask {
+                policy: policy_name,
+                exchange,
+                symbols,
+                resolution: policy.resolution,
+                cutoff: aligned_cutoff(now, policy.raw_retention_days, policy.resolution),
+                raw_rows: 0,
+                buckets: 0,
+                bars_written: false,
+                ticks_deleted: false,
+                error: None,
+            })
+        })
+        .collect();
+
+    (tasks, unmatched)
+}
+
+/// Tick数据压缩任务
+///
+/// 将超过保留期的原始Tick在ClickHouse中聚合为1s/1m K线，校验K线完整写入后再删除原始数据。
+#[derive(Clone)]
+pub struct TickCompactor {
+    config: CompactionConfig,
+    database: String,
+    client: Option<clickhouse::Client>,
+    decimals: DecimalSchema,
+    last_report: Arc<RwLock<Option<CompactionReport>>>,
+    /// 防止定时任务与手动触发并发执行
+    running: Arc<Mutex<()>>,
+}
+
+impl TickCompactor {
+    pub fn new(config: CompactionConfig, clickhouse_config: Option<&ClickHouseConfig>) -> Self {
+        let client = clickhouse_config.map(|c| {
+            clickhouse::Client::default()
+                .with_url(&c.url)
+                .with_database(&c.database)
+                .with_user(&c.username)
+                .with_password(&c.password)
+        });
+
+        Self {
+            config,
+            database: clickhouse_config
+                .map(|c| c.database.clone())
+                .unwrap_or_else(|| "market_data".to_string()),
+            client,
+            decimals: DecimalSchema::new(
+                &clickhouse_config.map(|c| c.decimals.clone()).unwrap_or_default(),
+            ),
+            last_report: Arc::new(RwLock::new(None)),
+            running: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn config(&self) -> &CompactionConfig {
+        &self.config
+    }
+
+    /// 最近一次执行报告
+    pub async fn last_report(&self) -> Option<CompactionReport> {
+        self.last_report.read().await.clone()
+    }
+
+    fn client(&self) -> Result<&clickhouse::Client> {
+        self.client
+            .as_ref()
+            .ok_or_else(|| anyhow!("ClickHouse is not configured"))
+    }
+
+    async fn count(&self, query: &str) -> Result<u64> {
+        let row = self.client()?.query(query).fetch_one::<CountRow>().await?;
+        Ok(row.count)
+    }
+
+    /// 执行一次压缩
+    ///
+    /// dry_run 模式只统计将被压缩的行数和K线数，不写入也不删除。
+    pub async fn run(&self, dry_run: bool) -> Result<CompactionReport> {
+        self.run_fenced(dry_run, None).await
+    }
+
+    /// 定时任务持有租约执行，每次写入和删除前确认防护令牌仍是最新的
+    async fn run_fenced(&self, dry_run: bool, fence: Option<&Fence>) -> Result<CompactionReport> {
+        let _guard = self
+            .running
+            .try_lock()
+            .map_err(|_| anyhow!("Compaction is already running"))?;
+
+        let client = self.client()?;
+        let started_at = Utc::now();
+        let db = &self.database;
+        let ticks_table = &self.config.ticks_table;
+
+        // 1. 确保K线表存在
+        if !dry_run {
+            for resolution in [BarResolution::OneSecond, BarResolution::OneMinute] {
+                client
+                    .query(&sql::create_bars_table(
+                        db,
+                        self.config.bars_table(resolution),
+                        &self.decimals,
+                    ))
+                    .execute()
+                    .await?;
+            }
+        }
+
+        // 2. 查询超过最短保留期的交易对
+        let min_retention = self
+            .config
+            .policies
+            .iter()
+            .map(|p| p.raw_retention_days)
+            .min()
+            .ok_or_else(|| anyhow!("No compaction policies configured"))?;
+        let earliest_cutoff = started_at - Duration::days(min_retention as i64);
+
+        let symbols = client
+            .query(&sql::distinct_symbols(db, ticks_table, earliest_cutoff))
+            .fetch_all::<SymbolRow>()
+            .await?
+            .into_iter()
+            .map(|row| (row.exchange, row.symbol))
+            .collect();
+
+        // 3. 按策略分组
+        let (mut tasks, unmatched_symbols) = plan_tasks(&self.config, symbols, started_at);
+
+        // 4. 逐组压缩，单组失败不影响其他组
+        for task in tasks.iter_mut() {
+            if let Err(e) = self.compact(task, dry_run, fence).await {
+                error!(
+                    "Compaction of {} ({}) failed: {}",
+                    task.exchange, task.policy, e
+                );
+                task.error = Some(e.to_string());
+            }
+        }
+
+        let report = CompactionReport {
+            dry_run,
+            started_at,
+            finished_at: Utc::now(),
+            tasks,
+            unmatched_symbols,
+        };
+
+        info!(
+            "Compaction {}finished: {} tasks, {} raw rows -> {} bars, {} failed",
+            if dry_run { "dry run " } else { "" },
+            report.tasks.len(),
+            report.total_raw_rows(),
+            report.total_buckets(),
+            report.failed_tasks()
+        );
+
+        *self.last_report.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    async fn compact(&self, task: &mut CompactionTask, dry_run: bool, fence: Option<&Fence>) -> Result<()> {
+        let client = self.client()?;
+        let db = &self.database;
+        let ticks_table = &self.config.ticks_table;
+        let bars_table = self.config.bars_table(task.resolution);
+        let predicate = sql::compaction_predicate(&task.exchange, &task.symbols, task.cutoff);
+
+        // 1. 统计
+        task.raw_rows = self
+            .count(&sql::count_ticks(db, ticks_table, &predicate))
+            .await?;
+        if task.raw_rows == 0 {
+            return Ok(());
+        }
+        task.buckets = self
+            .count(&sql::count_buckets(db, ticks_table, &predicate, task.resolution))
+            .await?;
+
+        if dry_run {
+            return Ok(());
+        }
+
+        // 2. 聚合写入K线
+        ensure_current(fence).await?;
+        client
+            .query(&sql::insert_bars(
+                db,
+                ticks_table,
+                bars_table,
+                &predicate,
+                task.resolution,
+                &self.decimals,
+            ))
+            .execute()
+            .await?;
+        task.bars_written = true;
+
+        // 3. 校验所有时间桶都已写入后再删除原始数据
+        let missing = self
+            .count(&sql::count_missing_bars(
+                db,
+                ticks_table,
+                bars_table,
+                &predicate,
+                task.resolution,
+            ))
+            .await?;
+        if missing > 0 {
+            return Err(anyhow!(
+                "{} buckets missing from {} after insert, raw ticks kept",
+                missing,
+                bars_table
+            ));
+        }
+
+        ensure_current(fence).await?;
+        client
+            .query(&sql::delete_ticks(db, ticks_table, &predicate))
+            .execute()
+            .await?;
+        task.ticks_deleted = true;
+
+        info!(
+            "Compacted {} ticks into {} {} bars for {} {:?}",
+            task.raw_rows,
+            task.buckets,
+            task.resolution.as_str(),
+            task.exchange,
+            task.symbols
+        );
+        Ok(())
+    }
+
+    /// 启动定时压缩，多副本时只在领导者副本执行
+    pub fn start(&self, leader: LeaderElection) {
+        if self.client.is_none() {
+            warn!("Tick compaction disabled: ClickHouse is not configured");
+            return;
+        }
+
+        let compactor = self.clone();
+        let interval = std::time::Duration::from_secs(self.config.run_interval_seconds.max(60));
+        tokio::spawn(async move {
+            info!("Tick compaction scheduled every {:?}", interval);
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                // 持有租约直到本轮执行结束
+                let Some(lease) = leader.acquire("tick_compaction").await else {
+                    continue;
+                };
+                if let Err(e) = compactor.run_fenced(false, Some(&lease.fence())).await {
+                    warn!("Scheduled tick compaction failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_aligned_cutoff() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 12, 34, 56).unwrap()
+            + Duration::milliseconds(789);
+
+        assert_eq!(
+            aligned_cutoff(now, 7, BarResolution::OneMinute),
+            Utc.with_ymd_and_hms(2024, 3, 3, 12, 34, 0).unwrap()
+        );
+        assert_eq!(
+            aligned_cutoff(now, 30, BarResolution::OneSecond),
+            Utc.with_ymd_and_hms(2024, 2, 9, 12, 34, 56).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_plan_tasks_groups_by_policy() {
+        let config = CompactionConfig::default();
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap();
+        let symbols = vec![
+            ("binance".to_string(), "ETHUSDT".to_string()),
+            ("binance".to_string(), "BTCUSDT".to_string()),
+            ("binance".to_string(), "SOLUSDT".to_string()),
+            ("okx".to_string(), "BTCUSDT".to_string()),
+        ];
+
+        let (tasks, unmatched) = plan_tasks(&config, symbols, now);
+        assert!(unmatched.is_empty());
+        assert_eq!(tasks.len(), 3);
+
+        let majors = tasks
+            .iter()
+            .find(|t| t.policy == "majors" && t.exchange == "binance")
+            .unwrap();
+        assert_eq!(majors.symbols, vec!["BTCUSDT", "ETHUSDT"]);
+        assert_eq!(majors.resolution, BarResolution::OneSecond);
+        assert_eq!(majors.cutoff, now - Duration::days(30));
+
+        let default = tasks.iter().find(|t| t.policy == "default").unwrap();
+        assert_eq!(default.symbols, vec!["SOLUSDT"]);
+        assert_eq!(default.cutoff, now - Duration::days(7));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod job;
+pub mod sql;
+
+pub use job::{CompactionReport, CompactionTask, TickCompactor};
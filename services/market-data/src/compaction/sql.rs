@@ -0,0 +1,190 @@
+use chrono::{DateTime, Utc};
+
+use crate::config::BarResolution;
+use crate::schema::DecimalSchema;
+
+/// 转义ClickHouse字符串字面量
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// ClickHouse DateTime64(3)字面量
+pub fn datetime_literal(at: DateTime<Utc>) -> String {
+    format!(
+        "toDateTime64({}, 3, 'UTC')",
+        quote(&at.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+    )
+}
+
+/// 待压缩数据的过滤条件
+pub fn compaction_predicate(exchange: &str, symbols: &[String], cutoff: DateTime<Utc>) -> String {
+    let symbol_list = symbols
+        .iter()
+        .map(|s| quote(s))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "exchange = {} AND symbol IN ({}) AND timestamp < {}",
+        quote(exchange),
+        symbol_list,
+        datetime_literal(cutoff)
+    )
+}
+
+/// 压缩后K线表结构
+///
+/// 使用ReplacingMergeTree保证任务中断后重跑不会产生重复数据。
+pub fn create_bars_table(database: &str, table: &str, decimals: &DecimalSchema) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {database}.{table} (
+    exchange LowCardinality(String),
+    symbol LowCardinality(String),
+    bucket DateTime64(3, 'UTC'),
+    open {price},
+    high {price},
+    low {price},
+    close {price},
+    volume {amount},
+    bid {price},
+    ask {price},
+    tick_count UInt32,
+    flagged_ticks UInt32,
+    data_quality LowCardinality(String),
+    compacted_at DateTime64(3, 'UTC')
+) ENGINE = ReplacingMergeTree(compacted_at)
+PARTITION BY toYYYYMM(bucket)
+ORDER BY (exchange, symbol, bucket)",
+        price = decimals.price_type(),
+        amount = decimals.amount_type(),
+    )
+}
+
+/// 统计待压缩的交易对
+pub fn distinct_symbols(database: &str, ticks_table: &str, before: DateTime<Utc>) -> String {
+    format!(
+        "SELECT exchange, symbol FROM {database}.{ticks_table} WHERE timestamp < {} GROUP BY exchange, symbol",
+        datetime_literal(before)
+    )
+}
+
+/// 统计原始Tick行数
+pub fn count_ticks(database: &str, ticks_table: &str, predicate: &str) -> String {
+    format!("SELECT count() AS count FROM {database}.{ticks_table} WHERE {predicate}")
+}
+
+/// 统计压缩后的K线数量
+pub fn count_buckets(
+    database: &str,
+    ticks_table: &str,
+    predicate: &str,
+    resolution: BarResolution,
+) -> String {
+    format!(
+        "SELECT uniqExact(symbol, toStartOfInterval(timestamp, {})) AS count FROM {database}.{ticks_table} WHERE {predicate}",
+        resolution.clickhouse_interval()
+    )
+}
+
+/// 统计尚未写入K线表的时间桶数量，用于删除原始数据前的校验
+pub fn count_missing_bars(
+    database: &str,
+    ticks_table: &str,
+    bars_table: &str,
+    predicate: &str,
+    resolution: BarResolution,
+) -> String {
+    format!(
+        "SELECT count() AS count FROM (
+    SELECT DISTINCT exchange, symbol, toStartOfInterval(timestamp, {interval}) AS bucket
+    FROM {database}.{ticks_table}
+    WHERE {predicate}
+) AS t
+LEFT ANTI JOIN {database}.{bars_table} AS b
+    ON t.exchange = b.exchange AND t.symbol = b.symbol AND t.bucket = b.bucket",
+        interval = resolution.clickhouse_interval()
+    )
+}
+
+/// 聚合Tick写入K线表
+///
+/// 数据质量取桶内最差的标记：存在可疑数据为suspect，其次为recovered。
+pub fn insert_bars(
+    database: &str,
+    ticks_table: &str,
+    bars_table: &str,
+    predicate: &str,
+    resolution: BarResolution,
+    decimals: &DecimalSchema,
+) -> String {
+    format!(
+        "INSERT INTO {database}.{bars_table}
+SELECT
+    exchange,
+    symbol,
+    toStartOfInterval(timestamp, {interval}) AS bucket,
+    argMin(CAST(price AS {price}), timestamp) AS open,
+    max(CAST(price AS {price})) AS high,
+    min(CAST(price AS {price})) AS low,
+    argMax(CAST(price AS {price}), timestamp) AS close,
+    sum(CAST(volume AS {amount})) AS volume,
+    argMax(CAST(bid AS {price}), timestamp) AS bid,
+    argMax(CAST(ask AS {price}), timestamp) AS ask,
+    toUInt32(count()) AS tick_count,
+    toUInt32(countIf(data_quality != 'normal')) AS flagged_ticks,
+    multiIf(
+        countIf(data_quality = 'suspect') > 0, 'suspect',
+        countIf(data_quality = 'recovered') > 0, 'recovered',
+        'normal'
+    ) AS data_quality,
+    now64(3) AS compacted_at
+FROM {database}.{ticks_table}
+WHERE {predicate}
+GROUP BY exchange, symbol, bucket",
+        interval = resolution.clickhouse_interval(),
+        price = decimals.price_type(),
+        amount = decimals.amount_type(),
+    )
+}
+
+/// 删除已压缩的原始Tick
+pub fn delete_ticks(database: &str, ticks_table: &str, predicate: &str) -> String {
+    format!("ALTER TABLE {database}.{ticks_table} DELETE WHERE {predicate}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_quote_escapes() {
+        assert_eq!(quote("BTCUSDT"), "'BTCUSDT'");
+        assert_eq!(quote("a'b"), "'a\\'b'");
+    }
+
+    #[test]
+    fn test_compaction_predicate() {
+        let cutoff = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let predicate = compaction_predicate(
+            "binance",
+            &["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            cutoff,
+        );
+
+        assert_eq!(
+            predicate,
+            "exchange = 'binance' AND symbol IN ('BTCUSDT', 'ETHUSDT') AND timestamp < toDateTime64('2024-01-02 00:00:00.000', 3, 'UTC')"
+        );
+    }
+
+    #[test]
+    fn test_insert_bars_uses_resolution() {
+        let decimals = DecimalSchema::new(&crate::config::DecimalSchemaConfig::default());
+        let sql = insert_bars("md", "ticks", "bars", "1 = 1", BarResolution::OneMinute, &decimals);
+        assert!(sql.contains("INTERVAL 1 MINUTE"));
+        assert!(sql.contains("sum(CAST(volume AS Decimal128(8))) AS volume"));
+        assert!(sql.starts_with("INSERT INTO md.bars"));
+        assert!(sql.contains("FROM md.ticks"));
+    }
+}
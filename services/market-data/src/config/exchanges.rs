@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use shared_utils::RetryPolicy;
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -14,6 +15,12 @@ pub struct ExchangeConfig {
     pub connection: ConnectionConfig,
     pub rate_limits: RateLimits,
     pub data_types: DataTypes,
+    /// 主备冗余连接
+    #[serde(default)]
+    pub redundancy: FeedRedundancyConfig,
+    /// 交易所测试网，行情以独立的交易所名称标记，与实盘数据分开存储
+    #[serde(default)]
+    pub testnet: bool,
 }
 
 impl Default for ExchangeConfig {
@@ -28,6 +35,8 @@ impl Default for ExchangeConfig {
             connection: ConnectionConfig::default(),
             rate_limits: RateLimits::default(),
             data_types: DataTypes::default(),
+            redundancy: FeedRedundancyConfig::default(),
+            testnet: false,
         }
     }
 }
@@ -71,6 +80,30 @@ impl Default for ConnectionConfig {
     }
 }
 
+/// 冗余行情连接配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedRedundancyConfig {
+    pub enabled: bool,
+    /// 备用连接地址，为空时使用与主连接相同的地址
+    pub standby_websocket_url: Option<String>,
+    /// 当前连接超过该时长没有消息时切换到另一条连接（毫秒）
+    pub stall_timeout_ms: u64,
+    /// 去重窗口保留的事件数
+    pub dedup_window: usize,
+}
+
+impl Default for FeedRedundancyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            standby_websocket_url: None,
+            stall_timeout_ms: 3000,
+            dedup_window: 50_000,
+        }
+    }
+}
+
 /// 限流配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimits {
@@ -144,6 +177,19 @@ impl ExchangeConfig {
                 max_weight_per_minute: 6000,
             },
             data_types: DataTypes::default(),
+            redundancy: FeedRedundancyConfig::default(),
+            testnet: false,
+        }
+    }
+
+    /// 创建币安测试网配置，默认不启用
+    pub fn binance_testnet() -> Self {
+        Self {
+            enabled: false,
+            websocket_url: "wss://testnet.binance.vision/stream".to_string(),
+            rest_api_url: "https://testnet.binance.vision".to_string(),
+            testnet: true,
+            ..Self::binance()
         }
     }
 
@@ -168,6 +214,8 @@ impl ExchangeConfig {
                 max_weight_per_minute: 2400,
             },
             data_types: DataTypes::default(),
+            redundancy: FeedRedundancyConfig::default(),
+            testnet: false,
         }
     }
 
@@ -192,6 +240,61 @@ impl ExchangeConfig {
                 max_weight_per_minute: 2400,
             },
             data_types: DataTypes::default(),
+            redundancy: FeedRedundancyConfig::default(),
+            testnet: false,
+        }
+    }
+
+    /// 创建Kraken配置
+    pub fn kraken() -> Self {
+        Self {
+            enabled: true,
+            name: "kraken".to_string(),
+            websocket_url: "wss://ws.kraken.com".to_string(),
+            rest_api_url: "https://api.kraken.com".to_string(),
+            symbols: vec![
+                "BTCUSD".to_string(),
+                "ETHUSD".to_string(),
+                "BTCUSDT".to_string(),
+            ],
+            credentials: None,
+            connection: ConnectionConfig::default(),
+            rate_limits: RateLimits {
+                requests_per_second: 1,
+                requests_per_minute: 60,
+                weight_per_request: 1,
+                max_weight_per_minute: 60,
+            },
+            data_types: DataTypes {
+                depth_levels: 10,
+                ..DataTypes::default()
+            },
+            redundancy: FeedRedundancyConfig::default(),
+            testnet: false,
+        }
+    }
+
+    /// 创建内部撮合引擎配置，行情来自交易引擎的内部行情流
+    pub fn internal() -> Self {
+        Self {
+            enabled: true,
+            name: "internal".to_string(),
+            websocket_url: "ws://localhost:8082/ws/internal/book".to_string(),
+            rest_api_url: "http://localhost:8082".to_string(),
+            symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            credentials: None,
+            connection: ConnectionConfig::default(),
+            rate_limits: RateLimits::default(),
+            data_types: DataTypes {
+                ticker: false,
+                kline: false,
+                depth: true,
+                trade: true,
+                kline_intervals: Vec::new(),
+                depth_levels: 50,
+            },
+            redundancy: FeedRedundancyConfig::default(),
+            testnet: false,
         }
     }
 
@@ -251,16 +354,11 @@ impl ExchangeConfig {
         Duration::from_secs(self.connection.reconnect_interval)
     }
 
-    /// 计算退避延迟
+    /// 计算退避延迟（不含抖动）
     pub fn calculate_backoff(&self, attempt: u32) -> Duration {
-        let base_delay = self.connection.reconnect_interval as f64;
-        let multiplier = self.connection.backoff_multiplier;
-        let max_delay = self.connection.max_backoff as f64;
-
-        let delay = base_delay * multiplier.powi(attempt as i32);
-        let capped_delay = delay.min(max_delay);
-
-        Duration::from_secs(capped_delay as u64)
+        RetryPolicy::exponential(self.reconnect_interval(), Duration::from_secs(self.connection.max_backoff))
+            .with_multiplier(self.connection.backoff_multiplier)
+            .base_delay(attempt)
     }
 
     /// 检查是否启用了指定数据类型
@@ -282,6 +380,9 @@ pub fn default_exchanges() -> HashMap<String, ExchangeConfig> {
     exchanges.insert("binance".to_string(), ExchangeConfig::binance());
     exchanges.insert("okx".to_string(), ExchangeConfig::okx());
     exchanges.insert("huobi".to_string(), ExchangeConfig::huobi());
+    exchanges.insert("kraken".to_string(), ExchangeConfig::kraken());
+    exchanges.insert("binance_testnet".to_string(), ExchangeConfig::binance_testnet());
+    exchanges.insert("internal".to_string(), ExchangeConfig::internal());
 
     exchanges
 }
@@ -312,6 +413,12 @@ mod tests {
         assert_eq!(config.name, "binance");
         assert!(config.validate().is_ok());
         assert!(config.symbols.contains(&"BTCUSDT".to_string()));
+
+        let testnet = ExchangeConfig::binance_testnet();
+        assert_eq!(testnet.name, "binance");
+        assert!(testnet.testnet);
+        assert!(!testnet.enabled);
+        assert!(testnet.validate().is_ok());
     }
 
     #[test]
@@ -3,12 +3,21 @@ pub mod server;
 pub mod storage;
 
 use anyhow::Result;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use shared_models::common::Interval;
+use shared_utils::{
+    config_serde::decimal, deserialize_checked, logging::LogLevelConfig, ConfigReport,
+    InternalAuthConfig, LeaderElectionConfig,
+};
 use std::collections::HashMap;
 
-pub use exchanges::{ExchangeConfig, ExchangeCredentials};
+pub use exchanges::{ConnectionConfig, ExchangeConfig, ExchangeCredentials, FeedRedundancyConfig};
 pub use server::ServerConfig;
-pub use storage::{ClickHouseConfig, RedisConfig, StorageConfig};
+pub use storage::{
+    ClickHouseConfig, DecimalSchemaConfig, KafkaConfig, KafkaEgressConfig, MemoryStoreConfig,
+    PostgresStorageConfig, RedisConfig, StorageBackend, StorageConfig, StorageWriteConfig, WriteClass,
+};
 
 /// 市场数据服务配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,11 +28,75 @@ pub struct MarketDataConfig {
     pub data_processing: DataProcessingConfig,
     pub websocket: WebSocketConfig,
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub charting: ChartConfig,
+    #[serde(default)]
+    pub ticker: TickerConfig,
+    #[serde(default)]
+    pub trade_tape: TradeTapeConfig,
+    #[serde(default)]
+    pub depth_metrics: DepthMetricsConfig,
+    #[serde(default)]
+    pub whale_detection: WhaleDetectionConfig,
+    #[serde(default)]
+    pub seasonality: SeasonalityConfig,
+    #[serde(default)]
+    pub analytics_query: AnalyticsQueryConfig,
+    #[serde(default)]
+    pub compaction: CompactionConfig,
+    #[serde(default)]
+    pub rollups: RollupConfig,
+    #[serde(default)]
+    pub instruments: InstrumentConfig,
+    /// 多副本部署时定时任务的领导者选举，未配置Redis地址时使用存储Redis
+    #[serde(default)]
+    pub leader_election: LeaderElectionConfig,
+    #[serde(default)]
+    pub sharding: ShardingConfig,
+    #[serde(default)]
+    pub depth_history: DepthHistoryConfig,
+    #[serde(default)]
+    pub continuity: ContinuityConfig,
+    #[serde(default)]
+    pub candle_close: CandleCloseConfig,
+    #[serde(default)]
+    pub pipeline_latency: PipelineLatencyConfig,
+    /// 未开通实时行情的用户使用延迟行情
+    #[serde(default)]
+    pub delayed_data: DelayedDataConfig,
+    /// 指标和管理接口的内部认证
+    #[serde(default)]
+    pub internal_auth: InternalAuthConfig,
+    /// 日志级别和按模块覆盖，运行时可通过管理接口修改
+    #[serde(default)]
+    pub logging: LogLevelConfig,
 }
 
 impl MarketDataConfig {
-    /// 从环境变量和配置文件加载配置
+    /// 从环境变量和配置文件加载配置，存在字段错误时返回完整的检查报告
     pub fn load() -> Result<Self> {
+        let (config, report) = Self::load_checked()?;
+        for issue in report.warnings() {
+            tracing::warn!("Config {}: {}", issue.path, issue.message);
+        }
+        report.into_result()?;
+        config.ok_or_else(|| anyhow::anyhow!("Configuration could not be loaded"))
+    }
+
+    /// 加载并检查配置，返回生效配置和字段级报告
+    pub fn load_checked() -> Result<(Option<Self>, ConfigReport)> {
+        let settings = Self::settings()?;
+        let raw: serde_json::Value = settings.clone().try_deserialize()?;
+        let (mut config, mut report) = deserialize_checked::<Self, _>(settings, &raw);
+        if let Some(config) = &mut config {
+            config.internal_auth.apply_env();
+            config.check(&mut report);
+        }
+        Ok((config, report))
+    }
+
+    /// 合并配置文件、环境变量和默认值
+    fn settings() -> Result<config::Config> {
         let mut settings = config::Config::builder()
             .add_source(config::File::with_name("config/market-data").required(false))
             .add_source(config::Environment::with_prefix("MARKET_DATA").separator("__"))
@@ -37,10 +110,162 @@ impl MarketDataConfig {
         settings.set_default("websocket.max_connections", 10000)?;
         settings.set_default("monitoring.metrics_enabled", true)?;
 
-        let config: MarketDataConfig = settings.try_deserialize()?;
-        config.validate()?;
+        Ok(settings)
+    }
 
-        Ok(config)
+    /// 字段取值范围检查
+    fn check(&self, report: &mut ConfigReport) {
+        report.range("server.port", self.server.port, 1, u16::MAX);
+        report.range("data_processing.batch_size", self.data_processing.batch_size, 1, 1_000_000);
+        report.range("data_processing.flush_interval", self.data_processing.flush_interval, 1, 3600);
+        report.range(
+            "websocket.max_connections",
+            self.websocket.max_connections,
+            1,
+            1_000_000,
+        );
+        report.range("websocket.heartbeat_interval", self.websocket.heartbeat_interval, 1, 3600);
+        if self.websocket.conflation.enabled {
+            report.range(
+                "websocket.conflation.queue_threshold",
+                self.websocket.conflation.queue_threshold,
+                1,
+                self.websocket.message_buffer_size.max(1),
+            );
+        }
+        if self.sharding.enabled {
+            report.range(
+                "sharding.member_ttl_seconds",
+                self.sharding.member_ttl_seconds,
+                self.sharding.heartbeat_interval_seconds * 2,
+                3600,
+            );
+            if self.storage.redis.is_none() {
+                report.error("sharding.enabled", "sharding requires storage.redis");
+            }
+        }
+        if self.depth_history.enabled {
+            report.range("depth_history.depth", self.depth_history.depth, 1, 5000);
+            report.range(
+                "depth_history.snapshot_interval_ms",
+                self.depth_history.snapshot_interval_ms,
+                100,
+                3_600_000,
+            );
+            report.range("depth_history.retention_days", self.depth_history.retention_days, 1, 3650);
+            if self.depth_history.record_diffs {
+                report.range(
+                    "depth_history.max_reconstruction_diffs",
+                    self.depth_history.max_reconstruction_diffs,
+                    1,
+                    1_000_000,
+                );
+                if self.depth_history.max_seek_ms < self.depth_history.snapshot_interval_ms {
+                    report.error(
+                        "depth_history.max_seek_ms",
+                        "max_seek_ms must be at least snapshot_interval_ms",
+                    );
+                }
+                if self.depth_history.diffs_table == self.depth_history.table {
+                    report.error("depth_history.diffs_table", "diffs_table must differ from table");
+                }
+            }
+            if self.storage.clickhouse.is_none() {
+                report.error("depth_history.enabled", "depth history requires storage.clickhouse");
+            }
+        }
+        if self.continuity.enabled {
+            report.range(
+                "continuity.major_missing_candles",
+                self.continuity.major_missing_candles,
+                1,
+                self.continuity.critical_missing_candles,
+            );
+            report.range("continuity.gap_history_size", self.continuity.gap_history_size, 1, 100_000);
+            report.range(
+                "continuity.persist_interval_seconds",
+                self.continuity.persist_interval_seconds,
+                1,
+                3600,
+            );
+        }
+        if self.candle_close.enabled {
+            report.range(
+                "candle_close.grace_period_seconds",
+                self.candle_close.grace_period_seconds,
+                1,
+                86_400,
+            );
+            report.range(
+                "candle_close.settle_delay_ms",
+                self.candle_close.settle_delay_ms,
+                0,
+                self.candle_close.grace_period_seconds * 1000,
+            );
+            report.range("candle_close.tick_interval_ms", self.candle_close.tick_interval_ms, 10, 10_000);
+        }
+        if self.seasonality.enabled {
+            report.range("seasonality.lookback_days", self.seasonality.lookback_days, 7, 365);
+            report.range("seasonality.cache_ttl_seconds", self.seasonality.cache_ttl_seconds, 60, 86_400);
+            if self.storage.clickhouse.is_none() {
+                report.warning("seasonality.enabled", "seasonality analytics require storage.clickhouse");
+            }
+        }
+        if self.analytics_query.enabled {
+            let query = &self.analytics_query;
+            report.range("analytics_query.max_kline_range_days", query.max_kline_range_days, 1, 3650);
+            report.range("analytics_query.max_tick_range_hours", query.max_tick_range_hours, 1, 720);
+            report.range("analytics_query.max_time_buckets", query.max_time_buckets, 1, 100_000);
+            report.range("analytics_query.max_rows", query.max_rows, 1, 100_000);
+            report.range("analytics_query.default_limit", query.default_limit, 1, query.max_rows);
+            report.range("analytics_query.max_execution_seconds", query.max_execution_seconds, 1, 300);
+            if self.storage.clickhouse.is_none() {
+                report.warning("analytics_query.enabled", "analytics queries require storage.clickhouse");
+            }
+        }
+        if self.pipeline_latency.enabled {
+            report.range("pipeline_latency.sample_size", self.pipeline_latency.sample_size, 16, 100_000);
+        }
+        if self.websocket.quotas.enabled {
+            self.websocket.quotas.check("websocket.quotas", report);
+        }
+        if self.delayed_data.enabled {
+            self.delayed_data.check("delayed_data", report);
+        }
+        if let Some(clickhouse) = &self.storage.clickhouse {
+            clickhouse.decimals.check("storage.clickhouse.decimals", report);
+        }
+        match self.storage.backend {
+            StorageBackend::ClickHouse if self.storage.clickhouse.is_none() => {
+                report.error("storage.backend", "clickhouse backend requires storage.clickhouse");
+            }
+            StorageBackend::Postgres => match &self.storage.postgres {
+                Some(postgres) => {
+                    report.range("storage.postgres.max_connections", postgres.max_connections, 1, 10_000);
+                }
+                None => report.error("storage.backend", "postgres backend requires storage.postgres"),
+            },
+            StorageBackend::Memory => {
+                report.range(
+                    "storage.memory.max_ticks_per_symbol",
+                    self.storage.memory.max_ticks_per_symbol,
+                    1,
+                    10_000_000,
+                );
+                report.range(
+                    "storage.memory.max_klines_per_series",
+                    self.storage.memory.max_klines_per_series,
+                    1,
+                    10_000_000,
+                );
+                report.warning("storage.backend", "memory backend does not persist market data across restarts");
+            }
+            _ => {}
+        }
+        self.storage.writes.check("storage.writes", report);
+        self.internal_auth.check("internal_auth", report);
+        self.logging.check("logging", report);
+        report.merge_validation("exchanges", self.validate());
     }
 
     /// 验证配置
@@ -109,6 +334,587 @@ impl Default for DataProcessingConfig {
     }
 }
 
+/// 图表API配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartConfig {
+    pub cache_enabled: bool,
+    /// 最近区间（包含未收盘K线）的缓存时间
+    pub hot_ttl_seconds: u64,
+    /// 历史区间的缓存时间
+    pub cold_ttl_seconds: u64,
+    /// 结束时间距当前多近算作最近区间
+    pub hot_range_ms: i64,
+    /// 单次返回的最大K线数量
+    pub max_points: usize,
+}
+
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            cache_enabled: true,
+            hot_ttl_seconds: 5,
+            cold_ttl_seconds: 3600,
+            hot_range_ms: 60 * 60 * 1000,
+            max_points: 1500,
+        }
+    }
+}
+
+/// 24小时滚动行情配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerConfig {
+    pub enabled: bool,
+    pub window_hours: u32,
+    /// 发布周期（毫秒）
+    pub publish_interval_ms: u64,
+}
+
+impl Default for TickerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_hours: 24,
+            publish_interval_ms: 1000,
+        }
+    }
+}
+
+/// 成交明细配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeTapeConfig {
+    /// 每个交易对保留的最近成交数量
+    pub capacity: usize,
+    pub max_query_limit: usize,
+}
+
+impl Default for TradeTapeConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10000,
+            max_query_limit: 1000,
+        }
+    }
+}
+
+/// 订单簿深度指标配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthMetricsConfig {
+    pub enabled: bool,
+    /// 计算买卖不平衡的档位深度
+    pub imbalance_levels: Vec<usize>,
+    /// 统计流动性的基点范围
+    pub liquidity_bands_bps: Vec<u32>,
+    /// 同一交易对两次发布的最小间隔（毫秒）
+    pub min_interval_ms: u64,
+}
+
+impl Default for DepthMetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            imbalance_levels: vec![1, 5, 10, 20],
+            liquidity_bands_bps: vec![10, 25, 50, 100],
+            min_interval_ms: 250,
+        }
+    }
+}
+
+/// 大额成交检测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhaleDetectionConfig {
+    pub enabled: bool,
+    /// 默认成交额阈值（计价货币）
+    #[serde(with = "decimal")]
+    pub default_min_notional: Decimal,
+    /// 按交易对覆盖的成交额阈值，键为大写交易对
+    #[serde(with = "decimal::map")]
+    pub symbol_thresholds: HashMap<String, Decimal>,
+    /// 连续成交累计窗口（毫秒）
+    pub burst_window_ms: i64,
+    /// 每个交易对保留的最近大额成交数量
+    pub history_size: usize,
+}
+
+impl WhaleDetectionConfig {
+    /// 获取交易对的成交额阈值
+    pub fn threshold_for(&self, symbol: &str) -> Decimal {
+        self.symbol_thresholds
+            .get(&symbol.to_uppercase())
+            .copied()
+            .unwrap_or(self.default_min_notional)
+    }
+}
+
+impl Default for WhaleDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_min_notional: Decimal::from(500_000),
+            symbol_thresholds: HashMap::new(),
+            burst_window_ms: 1000,
+            history_size: 200,
+        }
+    }
+}
+
+/// 日内季节性统计配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeasonalityConfig {
+    pub enabled: bool,
+    /// 统计使用的历史天数
+    pub lookback_days: u32,
+    /// 画像缓存时间（秒）
+    pub cache_ttl_seconds: u64,
+}
+
+impl Default for SeasonalityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lookback_days: 28,
+            cache_ttl_seconds: 3600,
+        }
+    }
+}
+
+/// 即席分析查询配置
+///
+/// 查询由受限的DSL编译为ClickHouse SQL，以下限制在编译时检查，
+/// 执行时间、扫描行数和分组数同时作为查询设置交给ClickHouse强制执行。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyticsQueryConfig {
+    pub enabled: bool,
+    /// K线数据集单次查询的最大时间跨度（天）
+    pub max_kline_range_days: u32,
+    /// Tick数据集单次查询的最大时间跨度（小时）
+    pub max_tick_range_hours: u32,
+    /// 时间维度的最大分桶数
+    pub max_time_buckets: u32,
+    pub max_measures: usize,
+    pub max_dimensions: usize,
+    pub max_filters: usize,
+    /// 单个 in/not_in 过滤的最大取值数
+    pub max_filter_values: usize,
+    /// 返回行数上限
+    pub max_rows: u32,
+    /// 未指定 limit 时的返回行数
+    pub default_limit: u32,
+    /// 单次查询最长执行时间（秒）
+    pub max_execution_seconds: u32,
+    /// 单次查询最多扫描的行数
+    pub max_rows_to_read: u64,
+    /// 分组数上限，超过时查询失败
+    pub max_groups: u64,
+}
+
+impl Default for AnalyticsQueryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_kline_range_days: 90,
+            max_tick_range_hours: 24,
+            max_time_buckets: 5000,
+            max_measures: 8,
+            max_dimensions: 3,
+            max_filters: 16,
+            max_filter_values: 100,
+            max_rows: 10_000,
+            default_limit: 1000,
+            max_execution_seconds: 10,
+            max_rows_to_read: 500_000_000,
+            max_groups: 100_000,
+        }
+    }
+}
+
+/// Tick数据压缩配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    pub enabled: bool,
+    /// 定时执行间隔（秒）
+    pub run_interval_seconds: u64,
+    pub ticks_table: String,
+    pub second_bars_table: String,
+    pub minute_bars_table: String,
+    /// 按交易对类别的压缩策略，按顺序匹配第一个
+    pub policies: Vec<CompactionPolicy>,
+}
+
+impl CompactionConfig {
+    /// 查找交易对适用的压缩策略
+    pub fn policy_for(&self, exchange: &str, symbol: &str) -> Option<&CompactionPolicy> {
+        self.policies
+            .iter()
+            .find(|policy| policy.matches(exchange, symbol))
+    }
+
+    /// 目标分辨率对应的数据表
+    pub fn bars_table(&self, resolution: BarResolution) -> &str {
+        match resolution {
+            BarResolution::OneSecond => &self.second_bars_table,
+            BarResolution::OneMinute => &self.minute_bars_table,
+        }
+    }
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            run_interval_seconds: 3600,
+            ticks_table: "market_ticks".to_string(),
+            second_bars_table: "market_ticks_1s".to_string(),
+            minute_bars_table: "market_ticks_1m".to_string(),
+            policies: vec![
+                CompactionPolicy {
+                    name: "majors".to_string(),
+                    exchanges: Vec::new(),
+                    symbols: vec!["BTC*".to_string(), "ETH*".to_string()],
+                    raw_retention_days: 30,
+                    resolution: BarResolution::OneSecond,
+                },
+                CompactionPolicy {
+                    name: "default".to_string(),
+                    exchanges: Vec::new(),
+                    symbols: vec!["*".to_string()],
+                    raw_retention_days: 7,
+                    resolution: BarResolution::OneMinute,
+                },
+            ],
+        }
+    }
+}
+
+/// 压缩策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionPolicy {
+    pub name: String,
+    /// 适用的交易所，为空表示全部
+    #[serde(default)]
+    pub exchanges: Vec<String>,
+    /// 交易对匹配规则，支持 * 通配符，例如 "BTC*"、"*USDT"
+    pub symbols: Vec<String>,
+    /// 原始Tick保留天数
+    pub raw_retention_days: u32,
+    /// 压缩后的分辨率
+    pub resolution: BarResolution,
+}
+
+impl CompactionPolicy {
+    /// 策略是否适用于交易对
+    pub fn matches(&self, exchange: &str, symbol: &str) -> bool {
+        let exchange_matches = self.exchanges.is_empty()
+            || self
+                .exchanges
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(exchange));
+
+        exchange_matches
+            && self
+                .symbols
+                .iter()
+                .any(|pattern| wildcard_match(&pattern.to_uppercase(), &symbol.to_uppercase()))
+    }
+}
+
+/// 压缩分辨率
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BarResolution {
+    #[serde(rename = "1s")]
+    OneSecond,
+    #[serde(rename = "1m")]
+    OneMinute,
+}
+
+impl BarResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BarResolution::OneSecond => "1s",
+            BarResolution::OneMinute => "1m",
+        }
+    }
+
+    /// ClickHouse INTERVAL表达式
+    pub fn clickhouse_interval(&self) -> &'static str {
+        match self {
+            BarResolution::OneSecond => "INTERVAL 1 SECOND",
+            BarResolution::OneMinute => "INTERVAL 1 MINUTE",
+        }
+    }
+}
+
+/// 简单通配符匹配，仅支持 *
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+
+    let mut rest = value;
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// K线物化汇总配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollupConfig {
+    pub enabled: bool,
+    /// 1分钟K线源表
+    pub source_table: String,
+    /// 需要物化的周期
+    pub intervals: Vec<Interval>,
+    /// 一致性检查间隔（秒），0表示不定时检查
+    pub consistency_check_interval_seconds: u64,
+    /// 一致性检查回看时长（小时）
+    pub consistency_lookback_hours: u32,
+    /// 检查发现不一致时自动从源数据重建
+    pub auto_repair: bool,
+}
+
+impl RollupConfig {
+    /// 物化表名称（月线使用1mo后缀，避免与分钟线混淆）
+    pub fn table_for(&self, interval: &Interval) -> String {
+        let suffix = match interval {
+            Interval::OneMonth => "1mo",
+            other => other.as_str(),
+        };
+        format!("{}_{}", self.source_table, suffix)
+    }
+
+    /// 是否物化了该周期
+    pub fn has_interval(&self, interval: &Interval) -> bool {
+        self.intervals.contains(interval)
+    }
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            source_table: "market_klines".to_string(),
+            intervals: vec![
+                Interval::FiveMinutes,
+                Interval::FifteenMinutes,
+                Interval::OneHour,
+                Interval::FourHours,
+                Interval::OneDay,
+            ],
+            consistency_check_interval_seconds: 900,
+            consistency_lookback_hours: 6,
+            auto_repair: false,
+        }
+    }
+}
+
+/// 交易对元数据同步配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstrumentConfig {
+    pub enabled: bool,
+    /// 同步间隔（秒）
+    pub refresh_interval_seconds: u64,
+    /// 单个交易所请求超时（秒）
+    pub request_timeout_seconds: u64,
+}
+
+impl Default for InstrumentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_interval_seconds: 3600,
+            request_timeout_seconds: 10,
+        }
+    }
+}
+
+/// 交易对水平分片配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardingConfig {
+    pub enabled: bool,
+    /// 实例ID，为空时使用 HOSTNAME
+    pub instance_id: Option<String>,
+    /// 网关转发到本实例的地址，为空时使用 http://{instance_id}:{server.port}
+    pub advertise_url: Option<String>,
+    pub key_prefix: String,
+    /// 心跳间隔（秒）
+    pub heartbeat_interval_seconds: u64,
+    /// 超过该时长没有心跳的实例被移出分片（秒）
+    pub member_ttl_seconds: u64,
+    /// 每个实例在哈希环上的虚拟节点数
+    pub virtual_nodes: usize,
+}
+
+impl Default for ShardingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            instance_id: None,
+            advertise_url: None,
+            key_prefix: shared_utils::sharding::DEFAULT_SHARD_PREFIX.to_string(),
+            heartbeat_interval_seconds: 5,
+            member_ttl_seconds: 15,
+            virtual_nodes: 64,
+        }
+    }
+}
+
+/// 订单簿深度快照历史配置
+///
+/// 按固定节奏记录完整深度快照，用于热力图回放和排队位置研究，
+/// 保留期与Tick数据分开设置。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthHistoryConfig {
+    pub enabled: bool,
+    /// 每侧记录的档位数
+    pub depth: usize,
+    /// 同一交易对两次快照的最小间隔（毫秒）
+    pub snapshot_interval_ms: u64,
+    /// 快照保留天数
+    pub retention_days: u32,
+    pub table: String,
+    /// 缓冲达到该数量时立即写入
+    pub flush_batch_size: usize,
+    /// 定时写入间隔（毫秒）
+    pub flush_interval_ms: u64,
+    /// 单次查询返回的最大快照数
+    pub max_query_snapshots: usize,
+    /// 记录相邻两次订单簿之间的增量，用于重建任意时刻的订单簿
+    #[serde(default)]
+    pub record_diffs: bool,
+    #[serde(default = "default_diffs_table")]
+    pub diffs_table: String,
+    /// 重建时向前查找快照的最大范围（毫秒），应不小于快照间隔
+    #[serde(default = "default_max_seek_ms")]
+    pub max_seek_ms: u64,
+    /// 单次重建最多应用的增量数
+    #[serde(default = "default_max_reconstruction_diffs")]
+    pub max_reconstruction_diffs: usize,
+}
+
+fn default_diffs_table() -> String {
+    "orderbook_diffs".to_string()
+}
+
+fn default_max_seek_ms() -> u64 {
+    60_000
+}
+
+fn default_max_reconstruction_diffs() -> usize {
+    20_000
+}
+
+impl Default for DepthHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            depth: 50,
+            snapshot_interval_ms: 1000,
+            retention_days: 7,
+            table: "orderbook_snapshots".to_string(),
+            flush_batch_size: 500,
+            flush_interval_ms: 1000,
+            max_query_snapshots: 5000,
+            record_diffs: false,
+            diffs_table: default_diffs_table(),
+            max_seek_ms: default_max_seek_ms(),
+            max_reconstruction_diffs: default_max_reconstruction_diffs(),
+        }
+    }
+}
+
+/// K线连续性检测配置
+///
+/// 按 (交易所, 交易对, 周期) 检查已收盘K线是否连续，缺失根数决定间隙严重程度。
+/// 最后的开盘时间定期保存到Redis，重启后继续检测停机期间的间隙。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContinuityConfig {
+    pub enabled: bool,
+    /// 缺失达到该根数为major
+    pub major_missing_candles: u32,
+    /// 缺失达到该根数为critical
+    pub critical_missing_candles: u32,
+    /// 保留的最近间隙记录数
+    pub gap_history_size: usize,
+    /// 检测状态保存间隔（秒）
+    pub persist_interval_seconds: u64,
+}
+
+impl Default for ContinuityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            major_missing_candles: 2,
+            critical_missing_candles: 10,
+            gap_history_size: 1000,
+            persist_interval_seconds: 30,
+        }
+    }
+}
+
+/// K线收盘调度配置
+///
+/// 按墙钟时间在收盘边界后定稿K线，不依赖交易所推送的收盘标记。
+/// 宽限期内的迟到更新作为修正发布，宽限期结束后封存。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CandleCloseConfig {
+    pub enabled: bool,
+    /// 收盘边界后等待的毫秒数，之后定稿
+    pub settle_delay_ms: u64,
+    /// 接受修正的时间（秒），从收盘边界起算
+    pub grace_period_seconds: u64,
+    /// 检查到期K线的间隔（毫秒）
+    pub tick_interval_ms: u64,
+}
+
+impl Default for CandleCloseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            settle_delay_ms: 1000,
+            grace_period_seconds: 60,
+            tick_interval_ms: 250,
+        }
+    }
+}
+
+/// 行情管道分阶段延迟统计配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineLatencyConfig {
+    pub enabled: bool,
+    /// 每个交易对每个阶段保留的最近样本数
+    pub sample_size: usize,
+}
+
+impl Default for PipelineLatencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sample_size: 1024,
+        }
+    }
+}
+
 /// WebSocket配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketConfig {
@@ -117,6 +923,47 @@ pub struct WebSocketConfig {
     pub message_buffer_size: usize,
     pub compression_enabled: bool,
     pub rate_limit: WebSocketRateLimit,
+    /// 保留用于断线续传（SSE Last-Event-ID）的最近事件数量
+    #[serde(default = "default_replay_buffer_size")]
+    pub replay_buffer_size: usize,
+    /// 可恢复会话的保留时间（秒）
+    #[serde(default = "default_session_ttl_seconds")]
+    pub session_ttl_seconds: u64,
+    /// 重连时最多补发的事件数量
+    #[serde(default = "default_max_replay_events")]
+    pub max_replay_events: usize,
+    /// 保留的最近断开记录数量
+    #[serde(default = "default_disconnect_history_size")]
+    pub disconnect_history_size: usize,
+    /// 客户端落后最新事件超过该序号数视为慢消费者
+    #[serde(default = "default_slow_client_lag")]
+    pub slow_client_lag: u64,
+    /// 慢消费者合并推送
+    #[serde(default)]
+    pub conflation: ConflationConfig,
+    /// 按用户等级的订阅配额
+    #[serde(default)]
+    pub quotas: SubscriptionQuotaConfig,
+}
+
+fn default_replay_buffer_size() -> usize {
+    5000
+}
+
+fn default_session_ttl_seconds() -> u64 {
+    300
+}
+
+fn default_max_replay_events() -> usize {
+    1000
+}
+
+fn default_disconnect_history_size() -> usize {
+    200
+}
+
+fn default_slow_client_lag() -> u64 {
+    500
 }
 
 impl Default for WebSocketConfig {
@@ -127,6 +974,189 @@ impl Default for WebSocketConfig {
             message_buffer_size: 1000,
             compression_enabled: true,
             rate_limit: WebSocketRateLimit::default(),
+            replay_buffer_size: default_replay_buffer_size(),
+            session_ttl_seconds: default_session_ttl_seconds(),
+            max_replay_events: default_max_replay_events(),
+            disconnect_history_size: default_disconnect_history_size(),
+            slow_client_lag: default_slow_client_lag(),
+            conflation: ConflationConfig::default(),
+            quotas: SubscriptionQuotaConfig::default(),
+        }
+    }
+}
+
+/// 按事件类型的合并策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflationMode {
+    /// 按顺序排队，队列满时丢弃
+    Queue,
+    /// 只保留每个交易对的最新一条
+    Latest,
+}
+
+/// 慢消费者合并推送配置
+///
+/// 客户端待发送队列超过阈值后，按事件类型的策略合并同一交易对的更新，
+/// 用最新数据替换队列中尚未发送的旧数据，而不是丢弃事件或断开连接。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConflationConfig {
+    pub enabled: bool,
+    /// 待发送队列达到该长度后开始合并
+    pub queue_threshold: usize,
+    /// 事件类型 -> 合并策略，未列出的类型按顺序排队
+    pub policies: HashMap<String, ConflationMode>,
+}
+
+impl Default for ConflationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            queue_threshold: 200,
+            policies: HashMap::from([
+                ("tick".to_string(), ConflationMode::Latest),
+                ("orderbook".to_string(), ConflationMode::Latest),
+            ]),
+        }
+    }
+}
+
+/// 单个用户等级的订阅配额
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    /// 同时订阅的交易对上限
+    pub max_symbols: usize,
+    /// 每分钟推送的消息上限
+    pub messages_per_minute: u32,
+}
+
+/// WebSocket订阅配额配置
+///
+/// 用户等级取网关转发的角色中配额最高的一个，未认证或没有匹配角色时使用默认等级。
+/// 每个连接定期上报用量，供计费系统统计。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionQuotaConfig {
+    pub enabled: bool,
+    /// 角色 -> 配额
+    pub tiers: HashMap<String, QuotaLimits>,
+    /// 未匹配任何角色时使用的等级
+    pub default_tier: String,
+    /// 用量上报间隔（秒）
+    pub usage_report_interval_seconds: u64,
+}
+
+impl Default for SubscriptionQuotaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tiers: HashMap::from([
+                (
+                    "free".to_string(),
+                    QuotaLimits {
+                        max_symbols: 5,
+                        messages_per_minute: 600,
+                    },
+                ),
+                (
+                    "premium".to_string(),
+                    QuotaLimits {
+                        max_symbols: 50,
+                        messages_per_minute: 12_000,
+                    },
+                ),
+            ]),
+            default_tier: "free".to_string(),
+            usage_report_interval_seconds: 60,
+        }
+    }
+}
+
+impl SubscriptionQuotaConfig {
+    pub fn check(&self, path: &str, report: &mut ConfigReport) {
+        if !self.tiers.contains_key(&self.default_tier) {
+            report.error(&format!("{}.default_tier", path), "must be one of the configured tiers");
+        }
+        for (tier, limits) in &self.tiers {
+            report.range(&format!("{}.tiers.{}.max_symbols", path, tier), limits.max_symbols, 1, 100_000);
+            report.range(
+                &format!("{}.tiers.{}.messages_per_minute", path, tier),
+                limits.messages_per_minute,
+                1,
+                10_000_000,
+            );
+        }
+        report.range(
+            &format!("{}.usage_report_interval_seconds", path),
+            self.usage_report_interval_seconds,
+            1,
+            86_400,
+        );
+    }
+}
+
+/// 延迟行情配置
+///
+/// 没有实时行情权限的用户通过WebSocket、SSE和REST接口拿到的是延迟后的数据。
+/// 权限来自用户服务签发的令牌，由网关以 x-user-permissions / x-user-roles 请求头转发。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DelayedDataConfig {
+    pub enabled: bool,
+    /// 交易对匹配规则 -> 延迟秒数，支持 * 通配符，按最长规则匹配；未匹配的交易对不延迟
+    pub products: HashMap<String, u64>,
+    /// 拥有该权限的用户接收实时行情
+    pub realtime_permission: String,
+    /// 拥有这些角色之一的用户接收实时行情
+    pub realtime_roles: Vec<String>,
+    /// 延迟缓冲最多保留的事件数，超过时丢弃最早的事件
+    pub max_buffered_events: usize,
+    /// 检查到期事件的间隔（毫秒）
+    pub release_interval_ms: u64,
+}
+
+impl Default for DelayedDataConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            products: HashMap::from([("*".to_string(), 900)]),
+            realtime_permission: "market_data:realtime".to_string(),
+            realtime_roles: vec!["premium".to_string()],
+            max_buffered_events: 500_000,
+            release_interval_ms: 200,
+        }
+    }
+}
+
+impl DelayedDataConfig {
+    /// 交易对的延迟，未匹配任何规则时返回 None
+    pub fn delay_for(&self, symbol: &str) -> Option<std::time::Duration> {
+        let symbol = symbol.to_uppercase();
+        self.products
+            .iter()
+            .filter(|(pattern, _)| wildcard_match(&pattern.to_uppercase(), &symbol))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, seconds)| std::time::Duration::from_secs(*seconds))
+            .filter(|delay| !delay.is_zero())
+    }
+
+    pub fn check(&self, path: &str, report: &mut ConfigReport) {
+        for (pattern, seconds) in &self.products {
+            report.range(&format!("{}.products.{}", path, pattern), *seconds, 0, 86_400);
+        }
+        report.range(
+            &format!("{}.max_buffered_events", path),
+            self.max_buffered_events,
+            1,
+            100_000_000,
+        );
+        report.range(&format!("{}.release_interval_ms", path), self.release_interval_ms, 10, 10_000);
+        if self.realtime_permission.is_empty() && self.realtime_roles.is_empty() {
+            report.warning(
+                &format!("{}.realtime_permission", path),
+                "no permission or role grants real-time data, all users receive delayed data",
+            );
         }
     }
 }
@@ -200,6 +1230,25 @@ mod tests {
             data_processing: DataProcessingConfig::default(),
             websocket: WebSocketConfig::default(),
             monitoring: MonitoringConfig::default(),
+            charting: ChartConfig::default(),
+            ticker: TickerConfig::default(),
+            trade_tape: TradeTapeConfig::default(),
+            depth_metrics: DepthMetricsConfig::default(),
+            whale_detection: WhaleDetectionConfig::default(),
+            seasonality: SeasonalityConfig::default(),
+            analytics_query: AnalyticsQueryConfig::default(),
+            compaction: CompactionConfig::default(),
+            rollups: RollupConfig::default(),
+            instruments: InstrumentConfig::default(),
+            leader_election: LeaderElectionConfig::default(),
+            sharding: ShardingConfig::default(),
+            depth_history: DepthHistoryConfig::default(),
+            continuity: ContinuityConfig::default(),
+            candle_close: CandleCloseConfig::default(),
+            pipeline_latency: PipelineLatencyConfig::default(),
+            delayed_data: DelayedDataConfig::default(),
+            internal_auth: InternalAuthConfig::default(),
+            logging: LogLevelConfig::default(),
         };
 
         // 空交易所配置应该失败
@@ -228,6 +1277,25 @@ mod tests {
             data_processing: DataProcessingConfig::default(),
             websocket: WebSocketConfig::default(),
             monitoring: MonitoringConfig::default(),
+            charting: ChartConfig::default(),
+            ticker: TickerConfig::default(),
+            trade_tape: TradeTapeConfig::default(),
+            depth_metrics: DepthMetricsConfig::default(),
+            whale_detection: WhaleDetectionConfig::default(),
+            seasonality: SeasonalityConfig::default(),
+            analytics_query: AnalyticsQueryConfig::default(),
+            compaction: CompactionConfig::default(),
+            rollups: RollupConfig::default(),
+            instruments: InstrumentConfig::default(),
+            leader_election: LeaderElectionConfig::default(),
+            sharding: ShardingConfig::default(),
+            depth_history: DepthHistoryConfig::default(),
+            continuity: ContinuityConfig::default(),
+            candle_close: CandleCloseConfig::default(),
+            pipeline_latency: PipelineLatencyConfig::default(),
+            delayed_data: DelayedDataConfig::default(),
+            internal_auth: InternalAuthConfig::default(),
+            logging: LogLevelConfig::default(),
         };
 
         // 添加启用的交易所
@@ -254,4 +1322,33 @@ mod tests {
         assert_eq!(enabled.len(), 1);
         assert_eq!(enabled[0].0, "binance");
     }
+
+    #[test]
+    fn test_compaction_policy_matching() {
+        let config = CompactionConfig::default();
+
+        assert_eq!(config.policy_for("binance", "btcusdt").unwrap().name, "majors");
+        assert_eq!(config.policy_for("okx", "SOLUSDT").unwrap().name, "default");
+
+        assert!(wildcard_match("*USDT", "ETHUSDT"));
+        assert!(!wildcard_match("*USDT", "ETHBTC"));
+        assert!(wildcard_match("BTC*USD*", "BTCPERPUSDT"));
+        assert!(wildcard_match("ETHUSDT", "ETHUSDT"));
+        assert!(!wildcard_match("ETH", "ETHUSDT"));
+    }
+
+    #[test]
+    fn test_delayed_data_products() {
+        let mut config = DelayedDataConfig::default();
+        config.products.insert("BTCUSDT".to_string(), 0);
+        config.products.insert("*USDT".to_string(), 60);
+
+        // 最长规则优先，延迟为0的交易对不延迟
+        assert!(config.delay_for("btcusdt").is_none());
+        assert_eq!(config.delay_for("ETHUSDT"), Some(std::time::Duration::from_secs(60)));
+        assert_eq!(config.delay_for("ETHBTC"), Some(std::time::Duration::from_secs(900)));
+
+        config.products.remove("*");
+        assert!(config.delay_for("ETHBTC").is_none());
+    }
 }
@@ -1,19 +1,98 @@
 use serde::{Deserialize, Serialize};
+use shared_utils::ConfigReport;
+use std::collections::HashMap;
 
 /// 存储配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
+    /// Tick、K线和订单簿快照使用的存储后端
+    #[serde(default)]
+    pub backend: StorageBackend,
     pub clickhouse: Option<ClickHouseConfig>,
+    /// backend 为 postgres 时使用
+    #[serde(default)]
+    pub postgres: Option<PostgresStorageConfig>,
+    /// backend 为 memory 时使用
+    #[serde(default)]
+    pub memory: MemoryStoreConfig,
     pub redis: Option<RedisConfig>,
     pub kafka: Option<KafkaConfig>,
+    /// 按数据类型区分的写入可靠性等级
+    #[serde(default)]
+    pub writes: StorageWriteConfig,
 }
 
 impl Default for StorageConfig {
     fn default() -> Self {
         Self {
+            backend: StorageBackend::default(),
             clickhouse: Some(ClickHouseConfig::default()),
+            postgres: None,
+            memory: MemoryStoreConfig::default(),
             redis: Some(RedisConfig::default()),
             kafka: Some(KafkaConfig::default()),
+            writes: StorageWriteConfig::default(),
+        }
+    }
+}
+
+/// 行情存储后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    ClickHouse,
+    /// PostgreSQL，可选启用TimescaleDB超表
+    Postgres,
+    /// 进程内存，不持久化，用于测试和单机演示
+    Memory,
+}
+
+impl StorageBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageBackend::ClickHouse => "clickhouse",
+            StorageBackend::Postgres => "postgres",
+            StorageBackend::Memory => "memory",
+        }
+    }
+}
+
+/// PostgreSQL/TimescaleDB行情存储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostgresStorageConfig {
+    pub url: String,
+    pub max_connections: u32,
+    /// 将Tick和快照表转换为TimescaleDB超表，需要数据库已安装timescaledb扩展
+    pub timescale: bool,
+}
+
+impl Default for PostgresStorageConfig {
+    fn default() -> Self {
+        Self {
+            url: "postgresql://localhost:5432/market_data".to_string(),
+            max_connections: 10,
+            timescale: false,
+        }
+    }
+}
+
+/// 内存行情存储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryStoreConfig {
+    /// 每个交易对保留的最近Tick数
+    pub max_ticks_per_symbol: usize,
+    /// 每个交易对每个周期保留的最近K线数
+    pub max_klines_per_series: usize,
+}
+
+impl Default for MemoryStoreConfig {
+    fn default() -> Self {
+        Self {
+            max_ticks_per_symbol: 10_000,
+            max_klines_per_series: 5_000,
         }
     }
 }
@@ -30,6 +109,9 @@ pub struct ClickHouseConfig {
     pub max_connections: u32,
     pub batch_size: usize,
     pub compression: bool,
+    /// 价格和数量列的Decimal小数位
+    #[serde(default)]
+    pub decimals: DecimalSchemaConfig,
 }
 
 impl Default for ClickHouseConfig {
@@ -44,6 +126,7 @@ impl Default for ClickHouseConfig {
             max_connections: 10,
             batch_size: 10000,
             compression: true,
+            decimals: DecimalSchemaConfig::default(),
         }
     }
 }
@@ -94,6 +177,61 @@ impl ClickHouseConfig {
     }
 }
 
+/// 交易对的小数位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolScale {
+    pub price: u32,
+    pub amount: u32,
+}
+
+/// ClickHouse Decimal列配置
+///
+/// 价格存为Decimal64，数量和成交额存为Decimal128，表级小数位对所有交易对生效；
+/// 小数位最多12位，保证读取结果不超过rust_decimal的28位有效数字。
+/// 修改小数位只影响之后新建的表。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecimalSchemaConfig {
+    pub price_scale: u32,
+    pub amount_scale: u32,
+    /// 交易对的展示小数位，读取时补齐到该小数位，不能超过表级小数位
+    #[serde(default)]
+    pub symbol_scales: HashMap<String, SymbolScale>,
+    /// 启动时将源表中的浮点列迁移为Decimal
+    pub migrate_float_columns: bool,
+}
+
+impl Default for DecimalSchemaConfig {
+    fn default() -> Self {
+        Self {
+            price_scale: 8,
+            amount_scale: 8,
+            symbol_scales: HashMap::new(),
+            migrate_float_columns: true,
+        }
+    }
+}
+
+impl DecimalSchemaConfig {
+    pub fn check(&self, path: &str, report: &mut ConfigReport) {
+        report.range(&format!("{}.price_scale", path), self.price_scale, 0, 12);
+        report.range(&format!("{}.amount_scale", path), self.amount_scale, 0, 12);
+        for (symbol, scale) in &self.symbol_scales {
+            report.range(
+                &format!("{}.symbol_scales.{}.price", path, symbol),
+                scale.price,
+                0,
+                self.price_scale,
+            );
+            report.range(
+                &format!("{}.symbol_scales.{}.amount", path, symbol),
+                scale.amount,
+                0,
+                self.amount_scale,
+            );
+        }
+    }
+}
+
 /// Redis配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RedisConfig {
@@ -175,6 +313,9 @@ pub struct KafkaConfig {
     pub sasl_mechanism: Option<String>,
     pub sasl_username: Option<String>,
     pub sasl_password: Option<String>,
+    /// 发布缓冲和磁盘溢出队列
+    #[serde(default)]
+    pub egress: KafkaEgressConfig,
 }
 
 impl Default for KafkaConfig {
@@ -191,6 +332,46 @@ impl Default for KafkaConfig {
             sasl_mechanism: None,
             sasl_username: None,
             sasl_password: None,
+            egress: KafkaEgressConfig::default(),
+        }
+    }
+}
+
+/// Kafka发布缓冲配置
+///
+/// 启用后事件先进入内存队列，由后台任务按顺序发送；Kafka变慢或不可用导致
+/// 内存队列写满时，后续事件按顺序追加到磁盘段文件，恢复后先发完积压再发新事件。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KafkaEgressConfig {
+    pub enabled: bool,
+    /// 内存队列容量（条）
+    pub memory_buffer: usize,
+    /// 磁盘溢出目录
+    pub spill_dir: String,
+    /// 单个段文件大小上限（字节）
+    pub segment_max_bytes: u64,
+    /// 溢出文件总大小上限（字节），超过后拒绝新事件
+    pub max_spill_bytes: u64,
+    /// 单次并发发送的最大条数
+    pub send_batch_size: usize,
+    /// 发送失败后的重试间隔（毫秒）
+    pub retry_backoff_ms: u64,
+    /// 停止时等待内存队列发送的时间（毫秒），剩余事件写入磁盘溢出队列
+    pub shutdown_timeout_ms: u64,
+}
+
+impl Default for KafkaEgressConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            memory_buffer: 10_000,
+            spill_dir: "data/kafka-spill".to_string(),
+            segment_max_bytes: 16 * 1024 * 1024,
+            max_spill_bytes: 1024 * 1024 * 1024,
+            send_batch_size: 256,
+            retry_backoff_ms: 1000,
+            shutdown_timeout_ms: 5000,
         }
     }
 }
@@ -219,10 +400,122 @@ impl KafkaConfig {
             return Err(anyhow::anyhow!("Kafka topic prefix cannot be empty"));
         }
 
+        if self.egress.enabled {
+            if self.egress.memory_buffer == 0 || self.egress.send_batch_size == 0 {
+                return Err(anyhow::anyhow!(
+                    "Kafka egress memory_buffer and send_batch_size must be positive"
+                ));
+            }
+            if self.egress.segment_max_bytes > self.egress.max_spill_bytes {
+                return Err(anyhow::anyhow!(
+                    "Kafka egress segment_max_bytes cannot exceed max_spill_bytes"
+                ));
+            }
+        }
+
         Ok(())
     }
 }
 
+/// 写入可靠性等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteClass {
+    /// 写入预写日志并刷盘后才确认，ClickHouse不可用时从日志补写
+    Durable,
+    /// 进入内存队列后立即返回，后台按批写入，队列满时丢弃
+    Buffered,
+}
+
+impl WriteClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WriteClass::Durable => "durable",
+            WriteClass::Buffered => "buffered",
+        }
+    }
+}
+
+/// 分级写入配置
+///
+/// 成交、审计等数据丢失后无法从交易所补回，默认走同步持久化路径；
+/// Tick、深度、K线可从交易所重新拉取，默认走批量异步路径。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageWriteConfig {
+    /// 数据类型到可靠性等级的映射
+    pub classes: HashMap<String, WriteClass>,
+    /// 未配置的数据类型使用的等级
+    pub default_class: WriteClass,
+    /// 预写日志目录
+    pub wal_dir: String,
+    /// 单个日志段大小上限（字节）
+    pub wal_segment_max_bytes: u64,
+    /// 日志总大小上限（字节），超过后拒绝持久化写入
+    pub wal_max_bytes: u64,
+    /// 从日志补写时单批最大条数
+    pub replay_batch_size: usize,
+    /// 补写失败后的重试间隔（毫秒）
+    pub retry_backoff_ms: u64,
+    /// 异步路径内存队列容量（条）
+    pub queue_capacity: usize,
+    /// 异步路径单批最大条数
+    pub batch_size: usize,
+    /// 异步路径最长攒批时间（毫秒）
+    pub flush_interval_ms: u64,
+}
+
+impl Default for StorageWriteConfig {
+    fn default() -> Self {
+        let classes = [
+            ("trade", WriteClass::Durable),
+            ("audit", WriteClass::Durable),
+            ("backfill", WriteClass::Durable),
+            ("tick", WriteClass::Buffered),
+            ("orderbook", WriteClass::Buffered),
+            ("kline", WriteClass::Buffered),
+        ];
+        Self {
+            classes: classes
+                .into_iter()
+                .map(|(data_type, class)| (data_type.to_string(), class))
+                .collect(),
+            default_class: WriteClass::Buffered,
+            wal_dir: "data/storage-wal".to_string(),
+            wal_segment_max_bytes: 16 * 1024 * 1024,
+            wal_max_bytes: 1024 * 1024 * 1024,
+            replay_batch_size: 1000,
+            retry_backoff_ms: 1000,
+            queue_capacity: 100_000,
+            batch_size: 1000,
+            flush_interval_ms: 500,
+        }
+    }
+}
+
+impl StorageWriteConfig {
+    /// 数据类型的可靠性等级
+    pub fn class_for(&self, data_type: &str) -> WriteClass {
+        self.classes
+            .get(data_type)
+            .copied()
+            .unwrap_or(self.default_class)
+    }
+
+    pub fn check(&self, path: &str, report: &mut ConfigReport) {
+        report.range(&format!("{}.replay_batch_size", path), self.replay_batch_size, 1, 100_000);
+        report.range(&format!("{}.queue_capacity", path), self.queue_capacity, 1, 10_000_000);
+        report.range(&format!("{}.batch_size", path), self.batch_size, 1, 100_000);
+        report.range(&format!("{}.flush_interval_ms", path), self.flush_interval_ms, 1, 60_000);
+        if self.wal_segment_max_bytes == 0 || self.wal_segment_max_bytes > self.wal_max_bytes {
+            report.error(
+                &format!("{}.wal_segment_max_bytes", path),
+                "must be positive and not exceed wal_max_bytes",
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,4 +565,18 @@ mod tests {
         kafka_config.brokers.clear();
         assert!(kafka_config.validate().is_err());
     }
+
+    #[test]
+    fn test_write_classes() {
+        let config = StorageWriteConfig::default();
+        assert_eq!(config.class_for("trade"), WriteClass::Durable);
+        assert_eq!(config.class_for("tick"), WriteClass::Buffered);
+        assert_eq!(config.class_for("funding_rate"), WriteClass::Buffered);
+
+        let config: StorageWriteConfig =
+            serde_json::from_str(r#"{"classes":{"tick":"durable"},"default_class":"durable"}"#).unwrap();
+        assert_eq!(config.class_for("tick"), WriteClass::Durable);
+        assert_eq!(config.class_for("trade"), WriteClass::Durable);
+        assert_eq!(config.batch_size, 1000);
+    }
 }
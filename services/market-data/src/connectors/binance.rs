@@ -1,45 +1,100 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use shared_models::market::{MarketTick, Kline, OrderBook, Trade, OrderBookLevel};
+use shared_models::Timestamp;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{debug, error, info, warn};
-use url::Url;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info};
 
-use super::{ExchangeConnector, MarketDataEvent, ConnectionStats, ConnectorError};
+use super::registry::{ConnectorContext, ConnectorFactory};
+use super::websocket_client::{Json, ResilientWsClient, ResilientWsConfig, SubscriptionCodec, WsEvent};
+use super::{ExchangeConnector, MarketDataEvent, ConnectionStats, EventSender, TimedEvent};
 use crate::config::ExchangeConfig;
+use crate::instruments::{BinanceInstruments, InstrumentSource};
+
+/// 组合流地址，消息带流名称，订阅通过SUBSCRIBE请求动态增减
+const DEFAULT_WEBSOCKET_URL: &str = "wss://stream.binance.com:9443/stream";
+/// 测试网行情使用的交易所名称
+const TESTNET_EXCHANGE: &str = "binance_testnet";
+
+/// 单个订阅请求最多包含的流数量
+const MAX_STREAMS_PER_REQUEST: usize = 200;
+
+/// 币安订阅请求编码，主题即流名称
+#[derive(Default)]
+struct BinanceSubscriptions {
+    next_id: AtomicU64,
+}
+
+impl BinanceSubscriptions {
+    fn requests(&self, method: &str, topics: &[String]) -> Vec<Message> {
+        topics
+            .chunks(MAX_STREAMS_PER_REQUEST)
+            .map(|streams| {
+                let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+                Message::Text(json!({ "method": method, "params": streams, "id": id }).to_string())
+            })
+            .collect()
+    }
+}
+
+impl SubscriptionCodec for BinanceSubscriptions {
+    fn subscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+        Ok(self.requests("SUBSCRIBE", topics))
+    }
+
+    fn unsubscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+        Ok(self.requests("UNSUBSCRIBE", topics))
+    }
+}
+
+/// 数据类型对应的币安流名称
+fn stream_name(symbol: &str, data_type: &str) -> String {
+    let symbol = symbol.to_lowercase();
+    match data_type {
+        "depth" => format!("{}@depth20@100ms", symbol),
+        other => format!("{}@{}", symbol, other),
+    }
+}
 
 /// 币安WebSocket连接器
 pub struct BinanceConnector {
+    /// 事件中的交易所名称，测试网行情单独标记
+    exchange: &'static str,
     config: ExchangeConfig,
+    event_sender: EventSender,
     stats: Arc<RwLock<ConnectionStats>>,
     subscriptions: Arc<RwLock<HashMap<String, Vec<String>>>>,
-    is_connected: Arc<RwLock<bool>>,
+    client: ResilientWsClient,
 }
 
 impl BinanceConnector {
     /// 创建新的币安连接器
-    pub fn new(config: ExchangeConfig) -> Self {
+    pub fn new(config: ExchangeConfig, event_sender: EventSender) -> Self {
+        let url = if config.websocket_url.is_empty() {
+            DEFAULT_WEBSOCKET_URL
+        } else {
+            &config.websocket_url
+        };
+        let stats = Arc::new(RwLock::new(ConnectionStats::default()));
+        let client = ResilientWsClient::new(
+            ResilientWsConfig::from_exchange(url, &config.connection),
+            Arc::new(BinanceSubscriptions::default()),
+            stats.clone(),
+        );
         Self {
+            exchange: if config.testnet { TESTNET_EXCHANGE } else { "binance" },
             config,
-            stats: Arc::new(RwLock::new(ConnectionStats::default())),
+            event_sender,
+            stats,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
-            is_connected: Arc::new(RwLock::new(false)),
-        }
-    }
-
-    /// 构建WebSocket URL
-    fn build_websocket_url(&self, streams: &[String]) -> String {
-        if streams.is_empty() {
-            "wss://stream.binance.com:9443/ws".to_string()
-        } else {
-            let stream_names = streams.join("/");
-            format!("wss://stream.binance.com:9443/stream?streams={}", stream_names)
+            client,
         }
     }
 
@@ -76,50 +131,40 @@ impl BinanceConnector {
 
     /// 解析WebSocket消息
     async fn parse_message(&self, message: &str) -> Result<Vec<MarketDataEvent>> {
-        let mut events = Vec::new();
-        
         // 尝试解析为流数据格式
         if let Ok(stream_data) = serde_json::from_str::<BinanceStreamData>(message) {
-            match stream_data.data {
-                BinanceData::Ticker(ticker_data) => {
-                    if let Ok(tick) = self.parse_ticker(&ticker_data).await {
-                        events.push(MarketDataEvent::Tick(tick));
-                    }
-                }
-                BinanceData::Kline(kline_data) => {
-                    if let Ok(kline) = self.parse_kline(&kline_data).await {
-                        events.push(MarketDataEvent::Kline(kline));
-                    }
-                }
-                BinanceData::BookTicker(book_data) => {
-                    if let Ok(orderbook) = self.parse_book_ticker(&book_data).await {
-                        events.push(MarketDataEvent::OrderBook(orderbook));
-                    }
-                }
-                BinanceData::Trade(trade_data) => {
-                    if let Ok(trade) = self.parse_trade(&trade_data).await {
-                        events.push(MarketDataEvent::Trade(trade));
-                    }
-                }
-            }
-        } else {
-            // 尝试直接解析各种数据格式
-            if let Ok(ticker_data) = serde_json::from_str::<BinanceTickerData>(message) {
-                if let Ok(tick) = self.parse_ticker(&ticker_data).await {
-                    events.push(MarketDataEvent::Tick(tick));
-                }
+            return Ok(Self::stream_events(self.exchange, stream_data.data));
+        }
+
+        // 尝试直接解析各种数据格式
+        let mut events = Vec::new();
+        if let Ok(ticker_data) = serde_json::from_str::<BinanceTickerData>(message) {
+            if let Ok(tick) = Self::parse_ticker(self.exchange, &ticker_data) {
+                events.push(MarketDataEvent::Tick(tick));
             }
         }
-        
         Ok(events)
     }
 
+    /// 组合流消息转换为行情事件
+    fn stream_events(exchange: &str, data: BinanceData) -> Vec<MarketDataEvent> {
+        let event = match data {
+            BinanceData::Ticker(ticker_data) => Self::parse_ticker(exchange, &ticker_data).map(MarketDataEvent::Tick),
+            BinanceData::Kline(kline_data) => Self::parse_kline(exchange, &kline_data).map(MarketDataEvent::Kline),
+            BinanceData::BookTicker(book_data) => {
+                Self::parse_book_ticker(exchange, &book_data).map(MarketDataEvent::OrderBook)
+            }
+            BinanceData::Trade(trade_data) => Self::parse_trade(exchange, &trade_data).map(MarketDataEvent::Trade),
+        };
+        event.into_iter().collect()
+    }
+
     /// 解析Ticker数据
-    async fn parse_ticker(&self, data: &BinanceTickerData) -> Result<MarketTick> {
+    fn parse_ticker(exchange: &str, data: &BinanceTickerData) -> Result<MarketTick> {
         Ok(MarketTick {
-            exchange: "binance".to_string(),
+            exchange: exchange.to_string(),
             symbol: data.s.clone(),
-            timestamp: data.E,
+            timestamp: Timestamp::from_millis(data.E).to_datetime(),
             price: data.c.parse()?,
             volume: data.v.parse()?,
             bid: data.b.parse()?,
@@ -128,14 +173,14 @@ impl BinanceConnector {
     }
 
     /// 解析K线数据
-    async fn parse_kline(&self, data: &BinanceKlineData) -> Result<Kline> {
+    fn parse_kline(exchange: &str, data: &BinanceKlineData) -> Result<Kline> {
         let k = &data.k;
         Ok(Kline {
-            exchange: "binance".to_string(),
+            exchange: exchange.to_string(),
             symbol: k.s.clone(),
             interval: k.i.clone(),
-            open_time: k.t,
-            close_time: k.T,
+            open_time: Timestamp::from_millis(k.t).to_datetime(),
+            close_time: Timestamp::from_millis(k.T).to_datetime(),
             open: k.o.parse()?,
             high: k.h.parse()?,
             low: k.l.parse()?,
@@ -151,11 +196,11 @@ impl BinanceConnector {
     }
 
     /// 解析BookTicker数据
-    async fn parse_book_ticker(&self, data: &BinanceBookTickerData) -> Result<OrderBook> {
+    fn parse_book_ticker(exchange: &str, data: &BinanceBookTickerData) -> Result<OrderBook> {
         Ok(OrderBook {
-            exchange: "binance".to_string(),
+            exchange: exchange.to_string(),
             symbol: data.s.clone(),
-            timestamp: chrono::Utc::now().timestamp_millis(),
+            timestamp: chrono::Utc::now(),
             bids: vec![OrderBookLevel {
                 price: data.b.parse()?,
                 quantity: data.B.parse()?,
@@ -168,11 +213,11 @@ impl BinanceConnector {
     }
 
     /// 解析交易数据
-    async fn parse_trade(&self, data: &BinanceTradeData) -> Result<Trade> {
+    fn parse_trade(exchange: &str, data: &BinanceTradeData) -> Result<Trade> {
         Ok(Trade {
-            exchange: "binance".to_string(),
+            exchange: exchange.to_string(),
             symbol: data.s.clone(),
-            timestamp: data.T,
+            timestamp: Timestamp::from_millis(data.T).to_datetime(),
             trade_id: data.t.to_string(),
             price: data.p.parse()?,
             quantity: data.q.parse()?,
@@ -186,7 +231,7 @@ impl BinanceConnector {
 #[async_trait]
 impl ExchangeConnector for BinanceConnector {
     fn name(&self) -> &str {
-        "binance"
+        self.exchange
     }
 
     fn supported_symbols(&self) -> &[String] {
@@ -195,69 +240,48 @@ impl ExchangeConnector for BinanceConnector {
 
     async fn connect(&mut self) -> Result<()> {
         info!("Connecting to Binance WebSocket...");
-        
-        let streams = self.generate_stream_names();
-        let url = self.build_websocket_url(&streams);
-        
-        let url = Url::parse(&url)?;
-        let (ws_stream, _) = connect_async(url).await
-            .map_err(|e| ConnectorError::ConnectionFailed(e.to_string()))?;
-
-        let (mut write, mut read) = ws_stream.split();
-        
-        // 更新连接状态
-        *self.is_connected.write().await = true;
-        self.stats.write().await.set_connected(true);
-        
-        info!("Connected to Binance WebSocket");
 
-        // 启动消息处理循环
-        let stats = self.stats.clone();
-        let is_connected = self.is_connected.clone();
-        
-        tokio::spawn(async move {
-            while let Some(message) = read.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        stats.write().await.record_message_received();
-                        
-                        // 这里应该将消息发送到数据处理器
-                        // 由于架构限制，这里只是记录日志
-                        debug!("Received message: {}", text);
-                    }
-                    Ok(Message::Ping(ping)) => {
-                        // 响应ping
-                        if let Err(e) = write.send(Message::Pong(ping)).await {
-                            error!("Failed to send pong: {}", e);
-                            break;
-                        }
-                    }
-                    Ok(Message::Close(_)) => {
-                        info!("WebSocket connection closed by server");
-                        break;
-                    }
-                    Err(e) => {
-                        error!("WebSocket error: {}", e);
-                        stats.write().await.record_error();
-                        break;
+        let event_sender = self.event_sender.clone();
+        let exchange = self.exchange;
+        self.client
+            .start(move |event: WsEvent<Json<BinanceStreamData>>| match event {
+                WsEvent::Connected { .. } => {
+                    let _ = event_sender.send(TimedEvent::now(MarketDataEvent::ConnectionStatus {
+                        exchange: exchange.to_string(),
+                        connected: true,
+                        timestamp: Timestamp::now(),
+                    }));
+                }
+                WsEvent::Message { message: Json(stream_data), received } => {
+                    for event in Self::stream_events(exchange, stream_data.data) {
+                        let _ = event_sender.send(TimedEvent::received_at(event, received));
                     }
-                    _ => {}
                 }
-            }
-            
-            // 连接断开
-            *is_connected.write().await = false;
-            stats.write().await.set_connected(false);
-            warn!("Binance WebSocket connection lost");
-        });
+                WsEvent::DecodeError { text, .. } => {
+                    // 订阅请求的应答不是流数据
+                    debug!("Ignoring non-stream Binance message: {}", text);
+                }
+                WsEvent::Disconnected { .. } => {
+                    let _ = event_sender.send(TimedEvent::now(MarketDataEvent::ConnectionStatus {
+                        exchange: exchange.to_string(),
+                        connected: false,
+                        timestamp: Timestamp::now(),
+                    }));
+                }
+            })
+            .await?;
+
+        // 默认数据流，重连后由客户端重新订阅
+        self.client.subscribe(&self.generate_stream_names())?;
 
+        info!("Connected to Binance WebSocket");
         Ok(())
     }
 
     async fn disconnect(&mut self) -> Result<()> {
         info!("Disconnecting from Binance WebSocket...");
         
-        *self.is_connected.write().await = false;
+        self.client.stop();
         self.stats.write().await.set_connected(false);
         
         info!("Disconnected from Binance WebSocket");
@@ -267,6 +291,12 @@ impl ExchangeConnector for BinanceConnector {
     async fn subscribe(&mut self, symbols: &[String], data_types: &[String]) -> Result<()> {
         info!("Subscribing to {} symbols with {} data types", symbols.len(), data_types.len());
         
+        let streams: Vec<String> = symbols
+            .iter()
+            .flat_map(|symbol| data_types.iter().map(move |data_type| stream_name(symbol, data_type)))
+            .collect();
+        self.client.subscribe(&streams)?;
+
         let mut subscriptions = self.subscriptions.write().await;
         for symbol in symbols {
             subscriptions.insert(symbol.clone(), data_types.to_vec());
@@ -283,6 +313,12 @@ impl ExchangeConnector for BinanceConnector {
     async fn unsubscribe(&mut self, symbols: &[String], data_types: &[String]) -> Result<()> {
         info!("Unsubscribing from {} symbols", symbols.len());
         
+        let streams: Vec<String> = symbols
+            .iter()
+            .flat_map(|symbol| data_types.iter().map(move |data_type| stream_name(symbol, data_type)))
+            .collect();
+        self.client.unsubscribe(&streams)?;
+
         let mut subscriptions = self.subscriptions.write().await;
         for symbol in symbols {
             subscriptions.remove(symbol);
@@ -297,13 +333,15 @@ impl ExchangeConnector for BinanceConnector {
     }
 
     fn is_connected(&self) -> bool {
-        // 这里需要同步访问，在实际实现中可能需要调整
-        false
+        self.client.is_connected()
     }
 
     fn get_stats(&self) -> ConnectionStats {
-        // 这里需要同步访问，在实际实现中可能需要调整
-        ConnectionStats::default()
+        // 同步接口，锁被占用时返回空统计
+        self.stats
+            .try_read()
+            .map(|stats| stats.clone())
+            .unwrap_or_default()
     }
 
     async fn handle_message(&mut self, message: &str) -> Result<Vec<MarketDataEvent>> {
@@ -311,6 +349,23 @@ impl ExchangeConnector for BinanceConnector {
     }
 }
 
+/// 币安连接器工厂
+pub struct BinanceFactory;
+
+impl ConnectorFactory for BinanceFactory {
+    fn exchange(&self) -> &'static str {
+        "binance"
+    }
+
+    fn create(&self, context: ConnectorContext) -> Result<Box<dyn ExchangeConnector + Send + Sync>> {
+        Ok(Box::new(BinanceConnector::new(context.config, context.event_sender)))
+    }
+
+    fn instrument_source(&self) -> Option<Arc<dyn InstrumentSource>> {
+        Some(Arc::new(BinanceInstruments))
+    }
+}
+
 /// 币安流数据格式
 #[derive(Debug, Deserialize)]
 struct BinanceStreamData {
@@ -434,7 +489,8 @@ mod tests {
             ..Default::default()
         };
         
-        let connector = BinanceConnector::new(config);
+        let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+        let connector = BinanceConnector::new(config, sender);
         let streams = connector.generate_stream_names();
         
         assert!(streams.contains(&"btcusdt@ticker".to_string()));
@@ -444,8 +500,6 @@ mod tests {
 
     #[tokio::test]
     async fn test_ticker_parsing() {
-        let connector = BinanceConnector::new(ExchangeConfig::default());
-        
         let ticker_data = BinanceTickerData {
             E: 1640995200000,
             s: "BTCUSDT".to_string(),
@@ -455,8 +509,29 @@ mod tests {
             a: "50001.00".to_string(),
         };
         
-        let tick = connector.parse_ticker(&ticker_data).await.unwrap();
+        let tick = BinanceConnector::parse_ticker("binance", &ticker_data).unwrap();
         assert_eq!(tick.symbol, "BTCUSDT");
         assert_eq!(tick.exchange, "binance");
+        assert_eq!(tick.timestamp.timestamp_millis(), 1640995200000);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_subscription_requests() {
+        assert_eq!(stream_name("BTCUSDT", "depth"), "btcusdt@depth20@100ms");
+        assert_eq!(stream_name("BTCUSDT", "kline_1m"), "btcusdt@kline_1m");
+
+        let codec = BinanceSubscriptions::default();
+        let topics: Vec<String> = (0..250).map(|i| format!("s{}@trade", i)).collect();
+        let requests = codec.subscribe(&topics).unwrap();
+        assert_eq!(requests.len(), 2);
+        match &requests[1] {
+            Message::Text(text) => {
+                let request: serde_json::Value = serde_json::from_str(text).unwrap();
+                assert_eq!(request["method"], "SUBSCRIBE");
+                assert_eq!(request["params"].as_array().unwrap().len(), 50);
+                assert_eq!(request["id"], 2);
+            }
+            other => panic!("expected text request, got {:?}", other),
+        }
+    }
+}
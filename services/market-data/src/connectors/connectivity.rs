@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::exchange_manager::ExchangeManagerStats;
+use super::ConnectionStats;
+
+/// 交易所连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connected,
+    Disconnected,
+    /// 已启用但连接器未创建（启动失败或尚未实现）
+    NotStarted,
+}
+
+/// 连接器上报的最近错误
+#[derive(Debug, Clone, Serialize)]
+pub struct LastError {
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 单个交易所的连接快照
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeConnectivity {
+    pub exchange: String,
+    pub state: ConnectionState,
+    pub connected_since: Option<DateTime<Utc>>,
+    pub uptime_seconds: i64,
+    pub reconnect_count: u32,
+    pub rtt_ms: Option<f64>,
+    pub subscription_count: usize,
+    /// symbol -> data_types
+    pub subscriptions: HashMap<String, Vec<String>>,
+    pub messages_received: u64,
+    pub messages_sent: u64,
+    pub messages_per_second: f64,
+    pub errors_count: u64,
+    pub error_rate: f64,
+    pub last_message_time: Option<DateTime<Utc>>,
+    /// 距最后一条消息的毫秒数
+    pub last_message_age_ms: Option<i64>,
+    pub last_error: Option<LastError>,
+}
+
+impl ExchangeConnectivity {
+    /// 从连接器统计构建
+    pub fn from_stats(exchange: &str, stats: &ConnectionStats, now: DateTime<Utc>) -> Self {
+        let state = if stats.connected {
+            ConnectionState::Connected
+        } else {
+            ConnectionState::Disconnected
+        };
+
+        Self {
+            exchange: exchange.to_string(),
+            state,
+            connected_since: stats.connection_time.filter(|_| stats.connected),
+            uptime_seconds: match stats.connection_time {
+                Some(since) if stats.connected => (now - since).num_seconds().max(0),
+                _ => 0,
+            },
+            reconnect_count: stats.reconnect_count,
+            rtt_ms: stats.latency_ms,
+            subscription_count: stats.subscriptions.values().map(|types| types.len()).sum(),
+            subscriptions: stats.subscriptions.clone(),
+            messages_received: stats.messages_received,
+            messages_sent: stats.messages_sent,
+            messages_per_second: stats.message_rate(),
+            errors_count: stats.errors_count,
+            error_rate: stats.error_rate(),
+            last_message_time: stats.last_message_time,
+            last_message_age_ms: stats
+                .last_message_time
+                .map(|t| (now - t).num_milliseconds().max(0)),
+            last_error: match (&stats.last_error, stats.last_error_time) {
+                (Some(message), Some(timestamp)) => Some(LastError {
+                    message: message.clone(),
+                    timestamp,
+                }),
+                _ => None,
+            },
+        }
+    }
+
+    /// 未创建连接器的交易所
+    pub fn not_started(exchange: &str, last_error: Option<LastError>) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            state: ConnectionState::NotStarted,
+            connected_since: None,
+            uptime_seconds: 0,
+            reconnect_count: 0,
+            rtt_ms: None,
+            subscription_count: 0,
+            subscriptions: HashMap::new(),
+            messages_received: 0,
+            messages_sent: 0,
+            messages_per_second: 0.0,
+            errors_count: 0,
+            error_rate: 0.0,
+            last_message_time: None,
+            last_message_age_ms: None,
+            last_error,
+        }
+    }
+}
+
+/// 全部交易所的连接快照
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectivitySnapshot {
+    pub generated_at: DateTime<Utc>,
+    pub total_connectors: usize,
+    pub connected_connectors: usize,
+    pub total_events_processed: u64,
+    pub events_per_second: f64,
+    pub last_event_time: Option<DateTime<Utc>>,
+    pub exchanges: Vec<ExchangeConnectivity>,
+}
+
+impl ConnectivitySnapshot {
+    /// 合并管理器统计、已启用交易所列表和事件流中记录的错误
+    pub fn build(
+        stats: &ExchangeManagerStats,
+        enabled_exchanges: &[String],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut exchanges: Vec<ExchangeConnectivity> = stats
+            .connector_stats
+            .iter()
+            .map(|(name, connector_stats)| {
+                let mut connectivity = ExchangeConnectivity::from_stats(name, connector_stats, now);
+                // 连接器自身没有错误信息时使用事件流中最近的错误
+                if connectivity.last_error.is_none() {
+                    connectivity.last_error = stats.last_errors.get(name).cloned();
+                }
+                connectivity
+            })
+            .collect();
+
+        for exchange in enabled_exchanges {
+            if !stats.connector_stats.contains_key(exchange) {
+                exchanges.push(ExchangeConnectivity::not_started(
+                    exchange,
+                    stats.last_errors.get(exchange).cloned(),
+                ));
+            }
+        }
+        exchanges.sort_by(|a, b| a.exchange.cmp(&b.exchange));
+
+        Self {
+            generated_at: now,
+            total_connectors: stats.connector_stats.len(),
+            connected_connectors: exchanges
+                .iter()
+                .filter(|e| e.state == ConnectionState::Connected)
+                .count(),
+            total_events_processed: stats.total_events_processed,
+            events_per_second: stats.events_per_second,
+            last_event_time: stats.last_event_time,
+            exchanges,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_includes_not_started_exchanges() {
+        let now = Utc::now();
+        let mut binance = ConnectionStats::default();
+        binance.set_connected(true);
+        binance.add_subscription("BTCUSDT".to_string(), "trade".to_string());
+        binance.add_subscription("BTCUSDT".to_string(), "depth".to_string());
+        binance.update_latency(12.5);
+
+        let mut stats = ExchangeManagerStats::default();
+        stats.connector_stats.insert("binance".to_string(), binance);
+        stats.last_errors.insert(
+            "okx".to_string(),
+            LastError {
+                message: "Unsupported exchange".to_string(),
+                timestamp: now,
+            },
+        );
+
+        let snapshot = ConnectivitySnapshot::build(
+            &stats,
+            &["binance".to_string(), "okx".to_string()],
+            now,
+        );
+
+        assert_eq!(snapshot.exchanges.len(), 2);
+        assert_eq!(snapshot.connected_connectors, 1);
+
+        let binance = &snapshot.exchanges[0];
+        assert_eq!(binance.state, ConnectionState::Connected);
+        assert_eq!(binance.subscription_count, 2);
+        assert_eq!(binance.rtt_ms, Some(12.5));
+
+        let okx = &snapshot.exchanges[1];
+        assert_eq!(okx.state, ConnectionState::NotStarted);
+        assert_eq!(okx.last_error.as_ref().unwrap().message, "Unsupported exchange");
+    }
+}
@@ -2,25 +2,36 @@ use anyhow::Result;
 use shared_utils::AppMetrics;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tracing::{debug, error, info, warn};
 
 use crate::config::MarketDataConfig;
+use crate::latency::{PipelineLatencyTracker, SymbolLatency};
 use crate::processors::DataProcessor;
 
-use super::{
-    BinanceConnector, ExchangeConnector, MarketDataEvent, ConnectionStats,
-    ConnectorError, BinanceConfig,
-};
+use super::connectivity::{ConnectivitySnapshot, LastError};
+use super::redundancy::{FeedRedundancy, FeedRedundancyStats, FeedRole};
+use super::registry::ConnectorRegistry;
+use super::{EventSender, ExchangeConnector, MarketDataEvent, ConnectionStats, ConnectorError, TimedEvent};
 
 /// 交易所管理器
 pub struct ExchangeManager {
     config: MarketDataConfig,
     connectors: Arc<RwLock<HashMap<String, Box<dyn ExchangeConnector + Send + Sync>>>>,
-    event_sender: mpsc::UnboundedSender<MarketDataEvent>,
+    /// 启用冗余的交易所的备用连接器
+    standby_connectors: Arc<RwLock<HashMap<String, Box<dyn ExchangeConnector + Send + Sync>>>>,
+    /// 主备连接去重与切换
+    redundancy: Arc<FeedRedundancy>,
+    /// 连接器注册表
+    registry: ConnectorRegistry,
+    event_sender: EventSender,
+    /// 已处理事件的分发通道，供聚合、分析等下游模块订阅
+    event_tap: broadcast::Sender<MarketDataEvent>,
     data_processor: Arc<DataProcessor>,
     metrics: Arc<AppMetrics>,
     stats: Arc<RwLock<ExchangeManagerStats>>,
+    /// 收到原始消息到分发的分阶段延迟
+    pipeline_latency: Arc<PipelineLatencyTracker>,
 }
 
 /// 交易所管理器统计信息
@@ -32,24 +43,50 @@ pub struct ExchangeManagerStats {
     pub events_per_second: f64,
     pub last_event_time: Option<chrono::DateTime<chrono::Utc>>,
     pub connector_stats: HashMap<String, ConnectionStats>,
+    /// 各交易所最近一次错误（连接失败或事件流中的错误）
+    pub last_errors: HashMap<String, LastError>,
+    /// 启用冗余连接的交易所的去重与切换统计
+    pub feed_redundancy: HashMap<String, FeedRedundancyStats>,
+    /// 备用连接器统计
+    pub standby_stats: HashMap<String, ConnectionStats>,
 }
 
 impl ExchangeManager {
-    /// 创建新的交易所管理器
+    /// 创建新的交易所管理器，使用内置连接器
     pub async fn new(
         config: MarketDataConfig,
         data_processor: Arc<DataProcessor>,
         metrics: Arc<AppMetrics>,
+    ) -> Result<Self> {
+        Self::with_registry(config, data_processor, metrics, ConnectorRegistry::with_builtin()).await
+    }
+
+    /// 使用自定义连接器注册表创建交易所管理器
+    pub async fn with_registry(
+        config: MarketDataConfig,
+        data_processor: Arc<DataProcessor>,
+        metrics: Arc<AppMetrics>,
+        registry: ConnectorRegistry,
     ) -> Result<Self> {
         let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let (event_tap, _) = broadcast::channel(config.data_processing.max_queue_size.max(1024));
+        let pipeline_latency = Arc::new(PipelineLatencyTracker::new(
+            config.pipeline_latency.enabled,
+            config.pipeline_latency.sample_size,
+        ));
 
         let manager = Self {
             config,
             connectors: Arc::new(RwLock::new(HashMap::new())),
+            standby_connectors: Arc::new(RwLock::new(HashMap::new())),
+            redundancy: Arc::new(FeedRedundancy::new()),
+            registry,
             event_sender: event_sender.clone(),
+            event_tap,
             data_processor: data_processor.clone(),
             metrics: metrics.clone(),
             stats: Arc::new(RwLock::new(ExchangeManagerStats::default())),
+            pipeline_latency,
         };
 
         // 启动事件处理任务
@@ -65,6 +102,7 @@ impl ExchangeManager {
         for (exchange_name, exchange_config) in self.config.enabled_exchanges() {
             if let Err(e) = self.start_exchange_connection(exchange_name, exchange_config).await {
                 error!("Failed to start connection for {}: {}", exchange_name, e);
+                self.record_last_error(exchange_name, e.to_string()).await;
                 // 继续启动其他交易所，不因为一个失败而停止
             }
         }
@@ -74,6 +112,8 @@ impl ExchangeManager {
     }
 
     /// 启动单个交易所连接
+    ///
+    /// 连接器由注册表按配置的交易所名称创建，订阅的数据类型来自配置。
     async fn start_exchange_connection(
         &self,
         exchange_name: &str,
@@ -81,77 +121,37 @@ impl ExchangeManager {
     ) -> Result<()> {
         info!("Starting connection for exchange: {}", exchange_name);
 
-        match exchange_name {
-            "binance" => {
-                self.start_binance_connection(exchange_config).await?;
-            }
-            "okx" => {
-                // TODO: 实现OKX连接器
-                warn!("OKX connector not implemented yet");
-            }
-            "huobi" => {
-                // TODO: 实现火币连接器
-                warn!("Huobi connector not implemented yet");
-            }
-            _ => {
-                warn!("Unknown exchange: {}", exchange_name);
-                return Err(ConnectorError::ConfigurationError(
-                    format!("Unsupported exchange: {}", exchange_name)
-                ).into());
-            }
-        }
-
-        Ok(())
-    }
-
-    /// 启动币安连接
-    async fn start_binance_connection(
-        &self,
-        exchange_config: &crate::config::ExchangeConfig,
-    ) -> Result<()> {
-        let binance_config = BinanceConfig {
-            websocket_url: exchange_config.websocket_url.clone(),
-            testnet: false, // 从配置中读取
-            auto_reconnect: true,
-            max_reconnect_attempts: exchange_config.connection.max_reconnect_attempts,
-            reconnect_interval: std::time::Duration::from_secs(
-                exchange_config.connection.reconnect_interval
-            ),
+        let redundant = exchange_config.redundancy.enabled;
+        let event_sender = if redundant {
+            self.redundancy.register(exchange_name, &exchange_config.redundancy);
+            self.feed_sender(exchange_name, FeedRole::Primary)
+        } else {
+            self.event_sender.clone()
         };
 
-        let mut connector = BinanceConnector::new(
-            exchange_config.symbols.clone(),
-            self.event_sender.clone(),
-            binance_config,
-        );
+        let mut connector = self.registry.create(exchange_name, exchange_config, event_sender)?;
 
-        // 连接到币安
+        // 连接到交易所
         connector.connect().await?;
 
-        // 订阅数据
-        if exchange_config.data_types.ticker {
-            connector.subscribe(&exchange_config.symbols, &["ticker".to_string()]).await?;
+        // 订阅配置的数据类型
+        let symbols = &exchange_config.symbols;
+        for data_type in Self::configured_data_types(exchange_config) {
+            connector.subscribe(symbols, &[data_type]).await?;
         }
 
-        if exchange_config.data_types.kline {
-            for interval in &exchange_config.data_types.kline_intervals {
-                let data_type = format!("kline_{}", interval);
-                connector.subscribe(&exchange_config.symbols, &[data_type]).await?;
+        // 备用连接失败不影响主连接
+        if redundant {
+            if let Err(e) = self.start_standby_connection(exchange_name, exchange_config).await {
+                warn!("Failed to start standby connection for {}: {}", exchange_name, e);
+                self.record_last_error(exchange_name, format!("standby: {}", e)).await;
             }
         }
 
-        if exchange_config.data_types.depth {
-            connector.subscribe(&exchange_config.symbols, &["depth".to_string()]).await?;
-        }
-
-        if exchange_config.data_types.trade {
-            connector.subscribe(&exchange_config.symbols, &["trade".to_string()]).await?;
-        }
-
         // 将连接器添加到管理器
         {
             let mut connectors = self.connectors.write().await;
-            connectors.insert("binance".to_string(), Box::new(connector));
+            connectors.insert(exchange_name.to_string(), connector);
         }
 
         // 更新统计信息
@@ -161,23 +161,108 @@ impl ExchangeManager {
             stats.connected_connectors += 1;
         }
 
-        info!("Binance connection started successfully");
+        info!("{} connection started successfully", exchange_name);
+        Ok(())
+    }
+
+    /// 启动备用连接，订阅与主连接相同的数据
+    async fn start_standby_connection(
+        &self,
+        exchange_name: &str,
+        exchange_config: &crate::config::ExchangeConfig,
+    ) -> Result<()> {
+        let mut standby_config = exchange_config.clone();
+        if let Some(url) = &exchange_config.redundancy.standby_websocket_url {
+            standby_config.websocket_url = url.clone();
+        }
+
+        let mut connector = self.registry.create(
+            exchange_name,
+            &standby_config,
+            self.feed_sender(exchange_name, FeedRole::Standby),
+        )?;
+        connector.connect().await?;
+        for data_type in Self::configured_data_types(&standby_config) {
+            connector.subscribe(&standby_config.symbols, &[data_type]).await?;
+        }
+
+        self.standby_connectors
+            .write()
+            .await
+            .insert(exchange_name.to_string(), connector);
+        info!("{} standby connection started", exchange_name);
         Ok(())
     }
 
+    /// 冗余连接的事件通道，经去重后转发到事件处理器
+    ///
+    /// 连接器断开并释放发送端后转发任务自动退出。
+    fn feed_sender(&self, exchange_name: &str, role: FeedRole) -> EventSender {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let redundancy = self.redundancy.clone();
+        let event_sender = self.event_sender.clone();
+        let metrics = self.metrics.clone();
+        let exchange_name = exchange_name.to_string();
+        let role_label = role.to_string();
+
+        tokio::spawn(async move {
+            while let Some(timed) = receiver.recv().await {
+                if redundancy.accept(&exchange_name, role, &timed.event) {
+                    if event_sender.send(timed).is_err() {
+                        break;
+                    }
+                } else {
+                    let _ = metrics.inc_counter_vec(
+                        "market_data_feed_duplicates_total",
+                        &[exchange_name.as_str(), role_label.as_str()],
+                    );
+                }
+            }
+            debug!("{} {} feed forwarder stopped", exchange_name, role_label);
+        });
+
+        sender
+    }
+
+    /// 配置中启用的数据类型
+    pub(crate) fn configured_data_types(exchange_config: &crate::config::ExchangeConfig) -> Vec<String> {
+        let data_types = &exchange_config.data_types;
+        let mut result = Vec::new();
+
+        if data_types.ticker {
+            result.push("ticker".to_string());
+        }
+        if data_types.kline {
+            for interval in &data_types.kline_intervals {
+                result.push(format!("kline_{}", interval));
+            }
+        }
+        if data_types.depth {
+            result.push("depth".to_string());
+        }
+        if data_types.trade {
+            result.push("trade".to_string());
+        }
+
+        result
+    }
+
     /// 启动事件处理器
     async fn start_event_processor(
         &self,
-        mut event_receiver: mpsc::UnboundedReceiver<MarketDataEvent>,
+        mut event_receiver: mpsc::UnboundedReceiver<TimedEvent>,
     ) {
         let data_processor = self.data_processor.clone();
         let metrics = self.metrics.clone();
         let stats = self.stats.clone();
+        let event_tap = self.event_tap.clone();
+        let pipeline_latency = self.pipeline_latency.clone();
 
         tokio::spawn(async move {
             info!("Exchange manager event processor started");
 
-            while let Some(event) = event_receiver.recv().await {
+            while let Some(TimedEvent { event, mut timing }) = event_receiver.recv().await {
+                timing.mark_processing();
                 let start_time = std::time::Instant::now();
 
                 // 更新统计信息
@@ -185,11 +270,21 @@ impl ExchangeManager {
                     let mut stats_guard = stats.write().await;
                     stats_guard.total_events_processed += 1;
                     stats_guard.last_event_time = Some(chrono::Utc::now());
+                    if let MarketDataEvent::Error { exchange, error, .. } = &event {
+                        stats_guard.last_errors.insert(
+                            exchange.clone(),
+                            LastError {
+                                message: error.clone(),
+                                timestamp: chrono::Utc::now(),
+                            },
+                        );
+                    }
                 }
 
                 // 处理事件
                 match Self::process_market_event(&event, &data_processor, &metrics).await {
                     Ok(_) => {
+                        timing.mark_stored();
                         let processing_time = start_time.elapsed();
                         debug!(
                             "Event processed successfully: {} in {:?}",
@@ -202,6 +297,19 @@ impl ExchangeManager {
                             "market_data_event_processing_duration_seconds",
                             processing_time.as_secs_f64(),
                         );
+
+                        // 只统计行情事件，分发后事件已移出，先取出交易所和交易对
+                        let key = event
+                            .symbol()
+                            .map(|symbol| (event.exchange().to_string(), symbol.to_string()));
+
+                        // 分发给下游订阅者（无订阅者时忽略）
+                        let _ = event_tap.send(event);
+                        timing.mark_broadcast();
+
+                        if let Some((exchange, symbol)) = key.filter(|_| pipeline_latency.is_enabled()) {
+                            pipeline_latency.record(&exchange, &symbol, &timing).await;
+                        }
                     }
                     Err(e) => {
                         error!("Failed to process event: {} - {:?}", e, event);
@@ -214,6 +322,16 @@ impl ExchangeManager {
         });
     }
 
+    /// 按交易所/交易对查询管道分阶段延迟
+    pub async fn pipeline_latency(&self, exchange: Option<&str>, symbol: Option<&str>) -> Vec<SymbolLatency> {
+        self.pipeline_latency.snapshot(exchange, symbol).await
+    }
+
+    /// 订阅已处理的市场数据事件
+    pub fn subscribe_events(&self) -> broadcast::Receiver<MarketDataEvent> {
+        self.event_tap.subscribe()
+    }
+
     /// 处理市场数据事件
     async fn process_market_event(
         event: &MarketDataEvent,
@@ -275,6 +393,14 @@ impl ExchangeManager {
 
         connectors.clear();
 
+        let mut standby_connectors = self.standby_connectors.write().await;
+        for (exchange_name, connector) in standby_connectors.iter_mut() {
+            if let Err(e) = connector.disconnect().await {
+                error!("Failed to disconnect {} standby: {}", exchange_name, e);
+            }
+        }
+        standby_connectors.clear();
+
         // 重置统计信息
         {
             let mut stats = self.stats.write().await;
@@ -297,12 +423,22 @@ impl ExchangeManager {
                     warn!("Error disconnecting {}: {}", exchange_name, e);
                 }
             }
+
+            let mut standby_connectors = self.standby_connectors.write().await;
+            if let Some(mut connector) = standby_connectors.remove(exchange_name) {
+                if let Err(e) = connector.disconnect().await {
+                    warn!("Error disconnecting {} standby: {}", exchange_name, e);
+                }
+            }
         }
 
         // 重新启动连接
         if let Some(exchange_config) = self.config.exchanges.get(exchange_name) {
             if exchange_config.enabled {
-                self.start_exchange_connection(exchange_name, exchange_config).await?;
+                if let Err(e) = self.start_exchange_connection(exchange_name, exchange_config).await {
+                    self.record_last_error(exchange_name, e.to_string()).await;
+                    return Err(e);
+                }
                 info!("Connection restarted successfully for: {}", exchange_name);
             } else {
                 warn!("Exchange {} is disabled in configuration", exchange_name);
@@ -328,6 +464,15 @@ impl ExchangeManager {
             stats.connector_stats.insert(name.clone(), connector.get_stats());
         }
 
+        stats.standby_stats = self
+            .standby_connectors
+            .read()
+            .await
+            .iter()
+            .map(|(name, connector)| (name.clone(), connector.get_stats()))
+            .collect();
+        stats.feed_redundancy = self.redundancy.stats();
+
         // 计算每秒事件数
         if let Some(last_event_time) = stats.last_event_time {
             let duration = chrono::Utc::now() - last_event_time;
@@ -340,6 +485,31 @@ impl ExchangeManager {
         stats
     }
 
+    /// 记录交易所最近一次错误
+    async fn record_last_error(&self, exchange_name: &str, message: String) {
+        let mut stats = self.stats.write().await;
+        stats.last_errors.insert(
+            exchange_name.to_string(),
+            LastError {
+                message,
+                timestamp: chrono::Utc::now(),
+            },
+        );
+    }
+
+    /// 获取全部交易所的连接快照，包含已启用但未能建立连接的交易所
+    pub async fn connectivity_snapshot(&self) -> ConnectivitySnapshot {
+        let stats = self.get_all_stats().await;
+        let enabled: Vec<String> = self
+            .config
+            .enabled_exchanges()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        ConnectivitySnapshot::build(&stats, &enabled, chrono::Utc::now())
+    }
+
     /// 获取指定交易所的统计信息
     pub async fn get_exchange_stats(&self, exchange_name: &str) -> Option<ConnectionStats> {
         let connectors = self.connectors.read().await;
@@ -372,6 +542,11 @@ impl ExchangeManager {
         
         if let Some(connector) = connectors.get_mut(exchange_name) {
             connector.subscribe(symbols, data_types).await?;
+            if let Some(standby) = self.standby_connectors.write().await.get_mut(exchange_name) {
+                if let Err(e) = standby.subscribe(symbols, data_types).await {
+                    warn!("Standby subscription failed for {}: {}", exchange_name, e);
+                }
+            }
             info!("Subscription successful for: {}", exchange_name);
         } else {
             return Err(ConnectorError::ConnectionFailed(
@@ -396,6 +571,11 @@ impl ExchangeManager {
         
         if let Some(connector) = connectors.get_mut(exchange_name) {
             connector.unsubscribe(symbols, data_types).await?;
+            if let Some(standby) = self.standby_connectors.write().await.get_mut(exchange_name) {
+                if let Err(e) = standby.unsubscribe(symbols, data_types).await {
+                    warn!("Standby unsubscription failed for {}: {}", exchange_name, e);
+                }
+            }
             info!("Unsubscription successful for: {}", exchange_name);
         } else {
             return Err(ConnectorError::ConnectionFailed(
@@ -411,6 +591,16 @@ impl ExchangeManager {
         self.config.exchanges.keys().cloned().collect()
     }
 
+    /// 连接器注册表
+    pub fn registry(&self) -> &ConnectorRegistry {
+        &self.registry
+    }
+
+    /// 获取已注册连接器的交易所
+    pub fn registered_connectors(&self) -> Vec<String> {
+        self.registry.exchanges()
+    }
+
     /// 获取交易所支持的交易对
     pub async fn get_exchange_symbols(&self, exchange_name: &str) -> Option<Vec<String>> {
         let connectors = self.connectors.read().await;
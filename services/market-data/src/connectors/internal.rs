@@ -0,0 +1,465 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use shared_models::common::Exchange;
+use shared_models::market::{OrderBook, OrderBookLevel, Trade};
+use shared_models::Timestamp;
+use shared_protocols::internal_book::{BookLevel, InternalBookEvent, InternalBookRequest};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock as StdRwLock};
+use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+use super::registry::{ConnectorContext, ConnectorFactory};
+use super::websocket_client::{Json, ResilientWsClient, ResilientWsConfig, SubscriptionCodec, WsEvent};
+use super::{ConnectionStats, ConnectorError, EventSender, ExchangeConnector, MarketDataEvent, TimedEvent};
+use crate::config::ExchangeConfig;
+
+const EXCHANGE_NAME: &str = "internal";
+
+/// 本地维护的内部订单簿
+#[derive(Debug, Default)]
+struct LocalBook {
+    sequence: u64,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl LocalBook {
+    fn apply(levels: &mut BTreeMap<Decimal, Decimal>, updates: &[BookLevel]) {
+        for level in updates {
+            if level.quantity.is_zero() {
+                levels.remove(&level.price);
+            } else {
+                levels.insert(level.price, level.quantity);
+            }
+        }
+    }
+
+    fn to_order_book(&self, symbol: &str, depth: usize) -> OrderBook {
+        let level = |(price, quantity): (&Decimal, &Decimal)| OrderBookLevel {
+            price: *price,
+            quantity: *quantity,
+        };
+        OrderBook {
+            exchange: Exchange::Internal,
+            symbol: symbol.to_string(),
+            timestamp: Utc::now(),
+            last_update_id: self.sequence,
+            bids: self.bids.iter().rev().take(depth).map(level).collect(),
+            asks: self.asks.iter().take(depth).map(level).collect(),
+        }
+    }
+}
+
+/// 解析结果
+#[derive(Debug, Default)]
+pub struct ParsedInternal {
+    pub events: Vec<MarketDataEvent>,
+    /// 序号不连续需要重新同步的交易对
+    pub resync_symbols: Vec<String>,
+}
+
+/// 内部撮合行情解析器
+///
+/// 按序号应用快照和增量，序号断档时丢弃本地订单簿并请求重新下发快照，
+/// 收到快照前该交易对的增量全部忽略。
+#[derive(Debug)]
+pub struct InternalBookParser {
+    depth: usize,
+    books: HashMap<String, LocalBook>,
+    /// 已请求重新同步、等待快照的交易对
+    awaiting_snapshot: HashSet<String>,
+}
+
+impl InternalBookParser {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth: depth.max(1),
+            books: HashMap::new(),
+            awaiting_snapshot: HashSet::new(),
+        }
+    }
+
+    pub fn parse(&mut self, message: &str) -> Result<ParsedInternal> {
+        let event: InternalBookEvent = serde_json::from_str(message)
+            .map_err(|e| ConnectorError::MessageParsingFailed(e.to_string()))?;
+        Ok(self.apply(event))
+    }
+
+    pub fn apply(&mut self, event: InternalBookEvent) -> ParsedInternal {
+        let mut parsed = ParsedInternal::default();
+        match event {
+            InternalBookEvent::Snapshot { symbol, sequence, bids, asks, .. } => {
+                let mut book = LocalBook {
+                    sequence,
+                    ..LocalBook::default()
+                };
+                LocalBook::apply(&mut book.bids, &bids);
+                LocalBook::apply(&mut book.asks, &asks);
+                parsed
+                    .events
+                    .push(MarketDataEvent::OrderBook(book.to_order_book(&symbol, self.depth)));
+                self.awaiting_snapshot.remove(&symbol);
+                self.books.insert(symbol, book);
+            }
+            InternalBookEvent::Diff { symbol, sequence, bids, asks, .. } => {
+                let Some(book) = self.books.get_mut(&symbol) else {
+                    if self.awaiting_snapshot.insert(symbol.clone()) {
+                        parsed.resync_symbols.push(symbol);
+                    }
+                    return parsed;
+                };
+                // 快照之前已发出的增量
+                if sequence <= book.sequence {
+                    return parsed;
+                }
+                if sequence != book.sequence + 1 {
+                    warn!(
+                        "Internal book sequence gap for {}: expected {}, got {}",
+                        symbol,
+                        book.sequence + 1,
+                        sequence
+                    );
+                    self.books.remove(&symbol);
+                    self.awaiting_snapshot.insert(symbol.clone());
+                    parsed.resync_symbols.push(symbol);
+                    return parsed;
+                }
+                book.sequence = sequence;
+                LocalBook::apply(&mut book.bids, &bids);
+                LocalBook::apply(&mut book.asks, &asks);
+                parsed
+                    .events
+                    .push(MarketDataEvent::OrderBook(book.to_order_book(&symbol, self.depth)));
+            }
+            InternalBookEvent::Trade { symbol, trade_id, price, quantity, side, timestamp } => {
+                parsed.events.push(MarketDataEvent::Trade(Trade {
+                    id: None,
+                    exchange: Exchange::Internal,
+                    symbol,
+                    trade_id,
+                    timestamp,
+                    price,
+                    quantity,
+                    quote_quantity: price * quantity,
+                    // 主动卖出即买方为挂单方
+                    is_buyer_maker: side == "sell",
+                    side,
+                    is_best_match: true,
+                }));
+            }
+            // 返佣变化只面向做市方，不进入行情
+            InternalBookEvent::MakerRebate { .. } => {}
+        }
+        parsed
+    }
+
+    /// 丢弃交易对的本地订单簿
+    pub fn reset_book(&mut self, symbol: &str) {
+        self.books.remove(symbol);
+        self.awaiting_snapshot.remove(symbol);
+    }
+}
+
+/// 行情事件所属交易对
+fn event_symbol(event: &MarketDataEvent) -> Option<&str> {
+    match event {
+        MarketDataEvent::OrderBook(book) => Some(&book.symbol),
+        MarketDataEvent::Trade(trade) => Some(&trade.symbol),
+        _ => None,
+    }
+}
+
+fn resync_message(symbol: Option<String>) -> Message {
+    let request = InternalBookRequest::Resync { symbol };
+    Message::Text(serde_json::to_string(&request).unwrap_or_default())
+}
+
+/// 内部行情的订阅请求，订阅交易对时请求该交易对的当前快照
+///
+/// 服务端推送全部交易对，按交易对过滤在本地完成，取消订阅不需要发送请求。
+struct InternalSubscriptions;
+
+impl SubscriptionCodec for InternalSubscriptions {
+    fn subscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+        Ok(topics.iter().map(|symbol| resync_message(Some(symbol.clone()))).collect())
+    }
+
+    fn unsubscribe(&self, _topics: &[String]) -> Result<Vec<Message>> {
+        Ok(Vec::new())
+    }
+}
+
+/// 内部撮合引擎连接器
+///
+/// 订阅交易引擎的内部行情流，把内部订单簿和成交作为 INTERNAL 交易所
+/// 接入行情管道，与外部交易所行情一起经WebSocket和Kafka分发。
+pub struct InternalConnector {
+    config: ExchangeConfig,
+    event_sender: EventSender,
+    parser: Arc<Mutex<InternalBookParser>>,
+    stats: Arc<RwLock<ConnectionStats>>,
+    /// 已订阅的交易对，为空时转发全部交易对
+    subscriptions: Arc<StdRwLock<HashSet<String>>>,
+    client: ResilientWsClient,
+}
+
+impl InternalConnector {
+    /// 创建新的内部撮合连接器
+    pub fn new(config: ExchangeConfig, event_sender: EventSender) -> Self {
+        let depth = config.data_types.depth_levels as usize;
+        let stats = Arc::new(RwLock::new(ConnectionStats::default()));
+        let client = ResilientWsClient::new(
+            ResilientWsConfig::from_exchange(config.websocket_url.clone(), &config.connection),
+            Arc::new(InternalSubscriptions),
+            stats.clone(),
+        );
+        Self {
+            config,
+            event_sender,
+            parser: Arc::new(Mutex::new(InternalBookParser::new(depth))),
+            stats,
+            subscriptions: Arc::new(StdRwLock::new(HashSet::new())),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl ExchangeConnector for InternalConnector {
+    fn name(&self) -> &str {
+        EXCHANGE_NAME
+    }
+
+    fn supported_symbols(&self) -> &[String] {
+        &self.config.symbols
+    }
+
+    async fn connect(&mut self) -> Result<()> {
+        info!("Connecting to internal matching engine feed at {}", self.config.websocket_url);
+
+        let depth = self.config.data_types.depth_levels as usize;
+        let stats = self.stats.clone();
+        let parser = self.parser.clone();
+        let subscriptions = self.subscriptions.clone();
+        let event_sender = self.event_sender.clone();
+        let sender = self.client.sender();
+
+        self.client
+            .start(move |event: WsEvent<Json<InternalBookEvent>>| match event {
+                WsEvent::Connected { .. } => {
+                    // 连接后本地订单簿已过期，等待服务端下发的快照
+                    *parser.lock().expect("internal parser lock") = InternalBookParser::new(depth);
+                    let _ = event_sender.send(TimedEvent::now(MarketDataEvent::ConnectionStatus {
+                        exchange: EXCHANGE_NAME.to_string(),
+                        connected: true,
+                        timestamp: Timestamp::now(),
+                    }));
+                }
+                WsEvent::Message { message: Json(event), received } => {
+                    let parsed = parser.lock().expect("internal parser lock").apply(event);
+                    for symbol in parsed.resync_symbols {
+                        if let Ok(mut stats) = stats.try_write() {
+                            stats.record_error_message(format!("Internal book sequence gap for {}", symbol));
+                        }
+                        let _ = sender.send(resync_message(Some(symbol)));
+                    }
+                    let subscriptions = subscriptions.read().expect("internal subscriptions lock");
+                    for event in parsed.events {
+                        let wanted = subscriptions.is_empty()
+                            || event_symbol(&event).is_some_and(|s| subscriptions.contains(s));
+                        if wanted {
+                            let _ = event_sender.send(TimedEvent::received_at(event, received));
+                        }
+                    }
+                }
+                WsEvent::DecodeError { error, .. } => {
+                    warn!("Failed to parse internal book message: {}", error);
+                }
+                WsEvent::Disconnected { .. } => {
+                    let _ = event_sender.send(TimedEvent::now(MarketDataEvent::ConnectionStatus {
+                        exchange: EXCHANGE_NAME.to_string(),
+                        connected: false,
+                        timestamp: Timestamp::now(),
+                    }));
+                }
+            })
+            .await?;
+
+        info!("Connected to internal matching engine feed");
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        info!("Disconnecting from internal matching engine feed...");
+        self.client.stop();
+        self.stats.write().await.set_connected(false);
+        Ok(())
+    }
+
+    async fn subscribe(&mut self, symbols: &[String], data_types: &[String]) -> Result<()> {
+        info!("Subscribing internal book for {} symbols", symbols.len());
+
+        let symbols: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
+        self.subscriptions
+            .write()
+            .expect("internal subscriptions lock")
+            .extend(symbols.iter().cloned());
+        let mut stats = self.stats.write().await;
+        for symbol in &symbols {
+            for data_type in data_types {
+                stats.add_subscription(symbol.clone(), data_type.clone());
+            }
+        }
+        drop(stats);
+
+        // 新订阅的交易对需要当前快照，重连后自动重新请求
+        self.client.subscribe(&symbols)
+    }
+
+    async fn unsubscribe(&mut self, symbols: &[String], data_types: &[String]) -> Result<()> {
+        info!("Unsubscribing internal book for {} symbols", symbols.len());
+
+        let symbols: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
+        {
+            let mut subscriptions = self.subscriptions.write().expect("internal subscriptions lock");
+            let mut parser = self.parser.lock().expect("internal parser lock");
+            for symbol in &symbols {
+                subscriptions.remove(symbol);
+                parser.reset_book(symbol);
+            }
+        }
+        let mut stats = self.stats.write().await;
+        for symbol in &symbols {
+            for data_type in data_types {
+                stats.remove_subscription(symbol, data_type);
+            }
+        }
+        drop(stats);
+        self.client.unsubscribe(&symbols)
+    }
+
+    fn is_connected(&self) -> bool {
+        self.client.is_connected()
+    }
+
+    fn get_stats(&self) -> ConnectionStats {
+        // 同步接口，锁被占用时返回空统计
+        self.stats
+            .try_read()
+            .map(|stats| stats.clone())
+            .unwrap_or_default()
+    }
+
+    async fn handle_message(&mut self, message: &str) -> Result<Vec<MarketDataEvent>> {
+        let parsed = self.parser.lock().expect("internal parser lock").parse(message)?;
+        Ok(parsed.events)
+    }
+}
+
+/// 内部撮合连接器工厂
+pub struct InternalFactory;
+
+impl ConnectorFactory for InternalFactory {
+    fn exchange(&self) -> &'static str {
+        EXCHANGE_NAME
+    }
+
+    fn create(&self, context: ConnectorContext) -> Result<Box<dyn ExchangeConnector + Send + Sync>> {
+        Ok(Box::new(InternalConnector::new(context.config, context.event_sender)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: i64, quantity: i64) -> BookLevel {
+        BookLevel::new(Decimal::from(price), Decimal::from(quantity))
+    }
+
+    fn snapshot(sequence: u64) -> InternalBookEvent {
+        InternalBookEvent::Snapshot {
+            symbol: "BTCUSDT".to_string(),
+            sequence,
+            bids: vec![level(100, 1), level(99, 2)],
+            asks: vec![level(101, 1)],
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn diff(sequence: u64, bids: Vec<BookLevel>, asks: Vec<BookLevel>) -> InternalBookEvent {
+        InternalBookEvent::Diff {
+            symbol: "BTCUSDT".to_string(),
+            sequence,
+            bids,
+            asks,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn book(parsed: &ParsedInternal) -> &OrderBook {
+        match parsed.events.first() {
+            Some(MarketDataEvent::OrderBook(book)) => book,
+            other => panic!("expected order book, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_applies_snapshot_and_diffs() {
+        let mut parser = InternalBookParser::new(10);
+        parser.apply(snapshot(5));
+
+        // 快照之前的增量直接丢弃
+        assert!(parser.apply(diff(5, vec![level(98, 1)], vec![])).events.is_empty());
+
+        let parsed = parser.apply(diff(6, vec![level(100, 0), level(98, 4)], vec![level(102, 3)]));
+        let book = book(&parsed);
+        assert_eq!(book.exchange, Exchange::Internal);
+        assert_eq!(book.last_update_id, 6);
+        let bids: Vec<Decimal> = book.bids.iter().map(|l| l.price).collect();
+        assert_eq!(bids, vec![Decimal::from(99), Decimal::from(98)]);
+        assert_eq!(book.asks.len(), 2);
+    }
+
+    #[test]
+    fn test_sequence_gap_requests_resync() {
+        let mut parser = InternalBookParser::new(10);
+        parser.apply(snapshot(1));
+
+        let parsed = parser.apply(diff(3, vec![level(98, 1)], vec![]));
+        assert!(parsed.events.is_empty());
+        assert_eq!(parsed.resync_symbols, vec!["BTCUSDT".to_string()]);
+
+        // 等待快照期间不重复请求
+        assert!(parser.apply(diff(4, vec![], vec![])).resync_symbols.is_empty());
+
+        assert_eq!(book(&parser.apply(snapshot(4))).last_update_id, 4);
+        assert_eq!(parser.apply(diff(5, vec![], vec![level(101, 0)])).events.len(), 1);
+    }
+
+    #[test]
+    fn test_trade_print() {
+        let mut parser = InternalBookParser::new(10);
+        let parsed = parser.apply(InternalBookEvent::Trade {
+            symbol: "BTCUSDT".to_string(),
+            trade_id: "t-1".to_string(),
+            price: Decimal::from(100),
+            quantity: Decimal::new(5, 1),
+            side: "sell".to_string(),
+            timestamp: Utc::now(),
+        });
+
+        match &parsed.events[0] {
+            MarketDataEvent::Trade(trade) => {
+                assert_eq!(trade.exchange, Exchange::Internal);
+                assert_eq!(trade.quote_quantity, Decimal::from(50));
+                assert!(trade.is_buyer_maker);
+            }
+            other => panic!("expected trade, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,960 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use shared_models::common::{DataQuality, Exchange, Interval};
+use shared_models::market::{Kline, MarketTick, OrderBook, OrderBookLevel, Trade};
+use shared_models::Timestamp;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+use super::registry::{ConnectorContext, ConnectorFactory};
+use super::websocket_client::{ResilientWsClient, ResilientWsConfig, SubscriptionCodec, WsEvent};
+use super::{ConnectionStats, ConnectorError, EventSender, ExchangeConnector, MarketDataEvent, TimedEvent};
+use crate::config::ExchangeConfig;
+use crate::instruments::{KrakenInstruments, InstrumentSource};
+
+const DEFAULT_WEBSOCKET_URL: &str = "wss://ws.kraken.com";
+
+/// Kraken支持的订单簿深度
+const BOOK_DEPTHS: [u32; 5] = [10, 25, 100, 500, 1000];
+
+/// 校验和只覆盖前10档
+const CHECKSUM_LEVELS: usize = 10;
+
+/// 识别交易对报价币种，按长度优先匹配
+const QUOTE_ASSETS: [&str; 8] = ["USDT", "USDC", "USD", "EUR", "GBP", "JPY", "BTC", "ETH"];
+
+/// 内部币种名称与Kraken币种名称的对应关系
+const ASSET_ALIASES: [(&str, &str); 2] = [("BTC", "XBT"), ("DOGE", "XDG")];
+
+fn to_kraken_asset(asset: &str) -> &str {
+    ASSET_ALIASES
+        .iter()
+        .find(|(internal, _)| *internal == asset)
+        .map(|(_, kraken)| *kraken)
+        .unwrap_or(asset)
+}
+
+fn from_kraken_asset(asset: &str) -> &str {
+    ASSET_ALIASES
+        .iter()
+        .find(|(_, kraken)| *kraken == asset)
+        .map(|(internal, _)| *internal)
+        .unwrap_or(asset)
+}
+
+/// 内部交易对转换为Kraken WebSocket交易对，例如 BTCUSDT -> XBT/USDT
+pub fn to_kraken_pair(symbol: &str) -> Option<String> {
+    let symbol = symbol.trim().to_uppercase();
+    let (base, quote) = if let Some((base, quote)) = symbol.split_once(['/', '-']) {
+        (base.to_string(), quote.to_string())
+    } else {
+        let mut quotes = QUOTE_ASSETS.to_vec();
+        quotes.sort_by_key(|q| std::cmp::Reverse(q.len()));
+        let quote = quotes
+            .into_iter()
+            .find(|q| symbol.len() > q.len() && symbol.ends_with(q))?;
+        (symbol[..symbol.len() - quote.len()].to_string(), quote.to_string())
+    };
+
+    if base.is_empty() || quote.is_empty() {
+        return None;
+    }
+    Some(format!("{}/{}", to_kraken_asset(&base), to_kraken_asset(&quote)))
+}
+
+/// Kraken WebSocket交易对转换为内部交易对，例如 XBT/USDT -> BTCUSDT
+pub fn from_kraken_pair(pair: &str) -> String {
+    match pair.split_once('/') {
+        Some((base, quote)) => format!("{}{}", from_kraken_asset(base), from_kraken_asset(quote)),
+        None => pair.to_string(),
+    }
+}
+
+/// 周期对应的Kraken OHLC分钟数
+pub fn ohlc_minutes(interval: &Interval) -> Option<u32> {
+    match interval {
+        Interval::OneMinute => Some(1),
+        Interval::FiveMinutes => Some(5),
+        Interval::FifteenMinutes => Some(15),
+        Interval::ThirtyMinutes => Some(30),
+        Interval::OneHour => Some(60),
+        Interval::FourHours => Some(240),
+        Interval::OneDay => Some(1440),
+        Interval::OneWeek => Some(10080),
+        _ => None,
+    }
+}
+
+fn interval_for_minutes(minutes: u32) -> Option<Interval> {
+    match minutes {
+        1 => Some(Interval::OneMinute),
+        5 => Some(Interval::FiveMinutes),
+        15 => Some(Interval::FifteenMinutes),
+        30 => Some(Interval::ThirtyMinutes),
+        60 => Some(Interval::OneHour),
+        240 => Some(Interval::FourHours),
+        1440 => Some(Interval::OneDay),
+        10080 => Some(Interval::OneWeek),
+        _ => None,
+    }
+}
+
+/// 不小于配置档数的Kraken订阅深度
+pub fn book_depth_for(levels: u32) -> u32 {
+    BOOK_DEPTHS
+        .iter()
+        .copied()
+        .find(|depth| *depth >= levels)
+        .unwrap_or(BOOK_DEPTHS[BOOK_DEPTHS.len() - 1])
+}
+
+/// 解析Kraken秒级时间戳，例如 "1534614057.321597"
+pub fn parse_kraken_time(value: &str) -> Result<DateTime<Utc>> {
+    let seconds: Decimal = value.parse()?;
+    let micros = (seconds * Decimal::from(1_000_000))
+        .trunc()
+        .to_string()
+        .parse::<i64>()?;
+    Ok(Timestamp::from_micros(micros).to_datetime())
+}
+
+/// 校验和字段格式：去掉小数点和前导零
+fn checksum_field(value: &str) -> String {
+    let digits: String = value.chars().filter(|c| *c != '.').collect();
+    digits.trim_start_matches('0').to_string()
+}
+
+/// 订单簿价格档位，保留原始字符串用于计算校验和
+#[derive(Debug, Clone, PartialEq)]
+struct BookEntry {
+    price: String,
+    volume: String,
+}
+
+/// 本地维护的Kraken订单簿
+///
+/// 按Kraken的规则应用快照和增量，数量为0表示删除档位，
+/// 每次更新后截断到订阅深度，并用前10档计算CRC32与推送的校验和比对。
+#[derive(Debug, Clone, Default)]
+pub struct KrakenBook {
+    depth: usize,
+    asks: BTreeMap<Decimal, BookEntry>,
+    bids: BTreeMap<Decimal, BookEntry>,
+    update_id: u64,
+}
+
+impl KrakenBook {
+    pub fn new(depth: u32) -> Self {
+        Self {
+            depth: depth as usize,
+            ..Default::default()
+        }
+    }
+
+    /// 应用快照，清空已有档位
+    pub fn apply_snapshot(&mut self, asks: &[Value], bids: &[Value]) -> Result<()> {
+        self.asks.clear();
+        self.bids.clear();
+        self.apply_levels(asks, true)?;
+        self.apply_levels(bids, false)?;
+        Ok(())
+    }
+
+    /// 应用增量
+    pub fn apply_update(&mut self, asks: &[Value], bids: &[Value]) -> Result<()> {
+        self.apply_levels(asks, true)?;
+        self.apply_levels(bids, false)?;
+        Ok(())
+    }
+
+    fn apply_levels(&mut self, levels: &[Value], is_ask: bool) -> Result<()> {
+        for level in levels {
+            let fields = level
+                .as_array()
+                .ok_or_else(|| anyhow!("Invalid book level: {}", level))?;
+            let price = fields
+                .first()
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("Missing price in book level: {}", level))?;
+            let volume = fields
+                .get(1)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("Missing volume in book level: {}", level))?;
+
+            let key: Decimal = price.parse()?;
+            let side = if is_ask { &mut self.asks } else { &mut self.bids };
+            if volume.parse::<Decimal>()?.is_zero() {
+                side.remove(&key);
+            } else {
+                side.insert(
+                    key,
+                    BookEntry {
+                        price: price.to_string(),
+                        volume: volume.to_string(),
+                    },
+                );
+            }
+        }
+        self.truncate();
+        self.update_id += 1;
+        Ok(())
+    }
+
+    /// 超出订阅深度的档位不会再收到更新，需要删除
+    fn truncate(&mut self) {
+        while self.asks.len() > self.depth {
+            let worst = *self.asks.keys().next_back().expect("non-empty asks");
+            self.asks.remove(&worst);
+        }
+        while self.bids.len() > self.depth {
+            let worst = *self.bids.keys().next().expect("non-empty bids");
+            self.bids.remove(&worst);
+        }
+    }
+
+    /// 前10档卖单（价格升序）和买单（价格降序）拼接后的CRC32
+    pub fn checksum(&self) -> u32 {
+        let mut payload = String::new();
+        for entry in self.asks.values().take(CHECKSUM_LEVELS) {
+            payload.push_str(&checksum_field(&entry.price));
+            payload.push_str(&checksum_field(&entry.volume));
+        }
+        for entry in self.bids.values().rev().take(CHECKSUM_LEVELS) {
+            payload.push_str(&checksum_field(&entry.price));
+            payload.push_str(&checksum_field(&entry.volume));
+        }
+        crc32fast::hash(payload.as_bytes())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.asks.is_empty() && self.bids.is_empty()
+    }
+
+    /// 转换为通用订单簿
+    pub fn to_order_book(&self, symbol: &str, timestamp: DateTime<Utc>) -> OrderBook {
+        let level = |entry: &BookEntry| OrderBookLevel {
+            price: entry.price.parse().unwrap_or_default(),
+            quantity: entry.volume.parse().unwrap_or_default(),
+        };
+        OrderBook {
+            exchange: Exchange::Kraken,
+            symbol: symbol.to_string(),
+            timestamp,
+            last_update_id: self.update_id,
+            bids: self.bids.values().rev().map(level).collect(),
+            asks: self.asks.values().map(level).collect(),
+        }
+    }
+}
+
+/// 消息解析结果
+#[derive(Debug, Default)]
+pub struct ParsedMessage {
+    pub events: Vec<MarketDataEvent>,
+    /// 校验和不一致、需要重新订阅订单簿的交易对
+    pub resync_pairs: Vec<String>,
+}
+
+/// Kraken消息解析器，持有订单簿和未收盘K线状态
+#[derive(Debug)]
+pub struct KrakenParser {
+    book_depth: u32,
+    books: HashMap<String, KrakenBook>,
+    /// (pair, minutes) -> 当前周期的K线
+    candles: HashMap<(String, u32), Kline>,
+}
+
+impl KrakenParser {
+    pub fn new(book_depth: u32) -> Self {
+        Self {
+            book_depth,
+            books: HashMap::new(),
+            candles: HashMap::new(),
+        }
+    }
+
+    /// 丢弃交易对的本地订单簿，等待新的快照
+    pub fn reset_book(&mut self, pair: &str) {
+        self.books.remove(pair);
+    }
+
+    pub fn parse(&mut self, message: &str) -> Result<ParsedMessage> {
+        let value: Value = serde_json::from_str(message)?;
+        let mut parsed = ParsedMessage::default();
+
+        match &value {
+            Value::Object(object) => {
+                match object.get("event").and_then(Value::as_str) {
+                    Some("heartbeat") => parsed.events.push(MarketDataEvent::Heartbeat {
+                        exchange: "kraken".to_string(),
+                        timestamp: Timestamp::now(),
+                    }),
+                    Some("subscriptionStatus") => {
+                        if object.get("status").and_then(Value::as_str) == Some("error") {
+                            parsed.events.push(error_event(format!(
+                                "Subscription error for {}: {}",
+                                object.get("pair").and_then(Value::as_str).unwrap_or("-"),
+                                object.get("errorMessage").and_then(Value::as_str).unwrap_or("unknown")
+                            )));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Value::Array(items) if items.len() >= 4 => {
+                let pair = items[items.len() - 1]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Missing pair in message"))?;
+                let channel = items[items.len() - 2]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Missing channel name in message"))?;
+                let payloads = &items[1..items.len() - 2];
+
+                if channel.starts_with("book") {
+                    self.parse_book(pair, payloads, &mut parsed)?;
+                } else if channel == "trade" {
+                    for payload in payloads {
+                        parsed.events.extend(parse_trades(pair, payload)?);
+                    }
+                } else if channel == "ticker" {
+                    parsed.events.push(MarketDataEvent::Tick(parse_ticker(pair, &payloads[0])?));
+                } else if let Some(minutes) = channel.strip_prefix("ohlc-") {
+                    let minutes: u32 = minutes.parse()?;
+                    parsed.events.extend(self.parse_ohlc(pair, minutes, &payloads[0])?);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(parsed)
+    }
+
+    fn parse_book(&mut self, pair: &str, payloads: &[Value], parsed: &mut ParsedMessage) -> Result<()> {
+        let levels = |object: &serde_json::Map<String, Value>, key: &str| {
+            object.get(key).and_then(Value::as_array).cloned().unwrap_or_default()
+        };
+
+        let mut checksum = None;
+        for payload in payloads {
+            let object = match payload.as_object() {
+                Some(object) => object,
+                None => continue,
+            };
+
+            if object.contains_key("as") || object.contains_key("bs") {
+                let book = self
+                    .books
+                    .entry(pair.to_string())
+                    .or_insert_with(|| KrakenBook::new(self.book_depth));
+                book.apply_snapshot(&levels(object, "as"), &levels(object, "bs"))?;
+            } else {
+                let book = match self.books.get_mut(pair) {
+                    Some(book) => book,
+                    // 未收到快照前的增量无法应用
+                    None => return Ok(()),
+                };
+                book.apply_update(&levels(object, "a"), &levels(object, "b"))?;
+                if let Some(value) = object.get("c").and_then(Value::as_str) {
+                    checksum = Some(value.parse::<u32>()?);
+                }
+            }
+        }
+
+        let book = match self.books.get(pair) {
+            Some(book) => book,
+            None => return Ok(()),
+        };
+
+        if let Some(expected) = checksum {
+            let actual = book.checksum();
+            if actual != expected {
+                parsed.events.push(error_event(format!(
+                    "Order book checksum mismatch for {}: expected {}, got {}",
+                    pair, expected, actual
+                )));
+                parsed.resync_pairs.push(pair.to_string());
+                self.books.remove(pair);
+                return Ok(());
+            }
+        }
+
+        parsed.events.push(MarketDataEvent::OrderBook(
+            book.to_order_book(&from_kraken_pair(pair), Utc::now()),
+        ));
+        Ok(())
+    }
+
+    /// 解析OHLC，周期结束时间变化时补发上一根已收盘K线
+    fn parse_ohlc(&mut self, pair: &str, minutes: u32, payload: &Value) -> Result<Vec<MarketDataEvent>> {
+        let interval = interval_for_minutes(minutes)
+            .ok_or_else(|| anyhow!("Unsupported OHLC interval: {}", minutes))?;
+        let fields = payload
+            .as_array()
+            .filter(|fields| fields.len() >= 9)
+            .ok_or_else(|| anyhow!("Invalid OHLC payload: {}", payload))?;
+        let field = |index: usize| -> Result<&str> {
+            fields[index]
+                .as_str()
+                .ok_or_else(|| anyhow!("Invalid OHLC field {}: {}", index, fields[index]))
+        };
+
+        let close_time = parse_kraken_time(field(1)?)?;
+        let open_time = close_time - chrono::Duration::seconds(interval.to_seconds() as i64);
+        let volume: Decimal = field(7)?.parse()?;
+        let vwap: Decimal = field(6)?.parse()?;
+
+        let kline = Kline {
+            id: None,
+            exchange: Exchange::Kraken,
+            symbol: from_kraken_pair(pair),
+            interval,
+            open_time,
+            close_time,
+            open: field(2)?.parse()?,
+            high: field(3)?.parse()?,
+            low: field(4)?.parse()?,
+            close: field(5)?.parse()?,
+            volume,
+            quote_volume: vwap * volume,
+            trades_count: fields[8].as_u64().unwrap_or_default() as u32,
+            taker_buy_base_volume: Decimal::ZERO,
+            taker_buy_quote_volume: Decimal::ZERO,
+            is_closed: false,
+            data_quality: DataQuality::Normal,
+        };
+
+        let mut events = Vec::new();
+        let key = (pair.to_string(), minutes);
+        if let Some(previous) = self.candles.get(&key) {
+            if previous.close_time < kline.close_time {
+                let mut closed = previous.clone();
+                closed.is_closed = true;
+                events.push(MarketDataEvent::Kline(closed));
+            }
+        }
+        self.candles.insert(key, kline.clone());
+        events.push(MarketDataEvent::Kline(kline));
+        Ok(events)
+    }
+}
+
+fn error_event(error: String) -> MarketDataEvent {
+    MarketDataEvent::Error {
+        exchange: "kraken".to_string(),
+        error,
+        timestamp: Timestamp::now(),
+    }
+}
+
+/// 成交格式：[price, volume, time, side, orderType, misc]
+fn parse_trades(pair: &str, payload: &Value) -> Result<Vec<MarketDataEvent>> {
+    let trades = payload
+        .as_array()
+        .ok_or_else(|| anyhow!("Invalid trade payload: {}", payload))?;
+    let symbol = from_kraken_pair(pair);
+
+    trades
+        .iter()
+        .enumerate()
+        .map(|(index, trade)| {
+            let field = |i: usize| -> Result<&str> {
+                trade
+                    .get(i)
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| anyhow!("Invalid trade field {}: {}", i, trade))
+            };
+            let price: Decimal = field(0)?.parse()?;
+            let quantity: Decimal = field(1)?.parse()?;
+            let is_buy = field(3)? == "b";
+
+            Ok(MarketDataEvent::Trade(Trade {
+                id: None,
+                exchange: Exchange::Kraken,
+                symbol: symbol.clone(),
+                // Kraken v1 成交不带ID，用时间戳和批内序号标识
+                trade_id: format!("{}-{}", field(2)?, index),
+                timestamp: parse_kraken_time(field(2)?)?,
+                price,
+                quantity,
+                quote_quantity: price * quantity,
+                side: if is_buy { "buy" } else { "sell" }.to_string(),
+                is_buyer_maker: !is_buy,
+                is_best_match: true,
+            }))
+        })
+        .collect()
+}
+
+/// Ticker格式：{"a": [price, wholeLotVolume, lotVolume], "b": [...], "c": [price, lotVolume], "v": [today, last24Hours]}
+fn parse_ticker(pair: &str, payload: &Value) -> Result<MarketTick> {
+    let field = |key: &str, index: usize| -> Result<Decimal> {
+        payload
+            .get(key)
+            .and_then(|v| v.get(index))
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("Missing ticker field {}[{}]", key, index))?
+            .parse()
+            .map_err(Into::into)
+    };
+
+    Ok(MarketTick {
+        id: None,
+        exchange: Exchange::Kraken,
+        symbol: from_kraken_pair(pair),
+        timestamp: Utc::now(),
+        price: field("c", 0)?,
+        volume: field("v", 1)?,
+        bid: field("b", 0)?,
+        ask: field("a", 0)?,
+        bid_volume: field("b", 2)?,
+        ask_volume: field("a", 2)?,
+        trade_id: None,
+        is_buyer_maker: None,
+        data_quality: DataQuality::Normal,
+    })
+}
+
+/// 订阅主题，格式为 `数据类型|交易对`
+fn topic(symbol: &str, data_type: &str) -> String {
+    format!("{}|{}", data_type, symbol)
+}
+
+/// Kraken订阅请求编码，同一数据类型的交易对合并为一个请求
+struct KrakenSubscriptions {
+    book_depth: u32,
+}
+
+impl KrakenSubscriptions {
+    /// 将数据类型转换为Kraken订阅参数
+    fn subscription_for(&self, data_type: &str) -> Result<Value> {
+        match data_type {
+            "ticker" => Ok(json!({ "name": "ticker" })),
+            "trade" => Ok(json!({ "name": "trade" })),
+            "depth" => Ok(json!({ "name": "book", "depth": self.book_depth })),
+            other => {
+                let interval: Interval = other
+                    .strip_prefix("kline_")
+                    .ok_or_else(|| ConnectorError::SubscriptionFailed(format!("Unsupported data type: {}", other)))?
+                    .parse()
+                    .map_err(|e: shared_models::common::CommonError| ConnectorError::SubscriptionFailed(e.to_string()))?;
+                let minutes = ohlc_minutes(&interval).ok_or_else(|| {
+                    ConnectorError::SubscriptionFailed(format!("Kraken does not support {} OHLC", interval))
+                })?;
+                Ok(json!({ "name": "ohlc", "interval": minutes }))
+            }
+        }
+    }
+
+    fn build_requests(&self, event: &str, topics: &[String]) -> Result<Vec<Message>> {
+        let mut pairs_by_type: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for topic in topics {
+            let (data_type, symbol) = topic
+                .split_once('|')
+                .ok_or_else(|| ConnectorError::SubscriptionFailed(format!("Invalid topic: {}", topic)))?;
+            let pair = to_kraken_pair(symbol)
+                .ok_or_else(|| ConnectorError::SubscriptionFailed(format!("Unknown Kraken pair: {}", symbol)))?;
+            pairs_by_type.entry(data_type).or_default().push(pair);
+        }
+
+        pairs_by_type
+            .into_iter()
+            .map(|(data_type, pairs)| {
+                let request = json!({
+                    "event": event,
+                    "pair": pairs,
+                    "subscription": self.subscription_for(data_type)?,
+                });
+                Ok(Message::Text(request.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl SubscriptionCodec for KrakenSubscriptions {
+    fn subscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+        self.build_requests("subscribe", topics)
+    }
+
+    fn unsubscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+        self.build_requests("unsubscribe", topics)
+    }
+}
+
+/// Kraken WebSocket连接器
+pub struct KrakenConnector {
+    config: ExchangeConfig,
+    event_sender: EventSender,
+    parser: Arc<Mutex<KrakenParser>>,
+    stats: Arc<RwLock<ConnectionStats>>,
+    client: ResilientWsClient,
+}
+
+impl KrakenConnector {
+    /// 创建新的Kraken连接器
+    pub fn new(config: ExchangeConfig, event_sender: EventSender) -> Self {
+        let book_depth = book_depth_for(config.data_types.depth_levels);
+        let url = if config.websocket_url.is_empty() {
+            DEFAULT_WEBSOCKET_URL
+        } else {
+            &config.websocket_url
+        };
+        let stats = Arc::new(RwLock::new(ConnectionStats::default()));
+        let client = ResilientWsClient::new(
+            ResilientWsConfig::from_exchange(url, &config.connection),
+            Arc::new(KrakenSubscriptions { book_depth }),
+            stats.clone(),
+        );
+        Self {
+            config,
+            event_sender,
+            parser: Arc::new(Mutex::new(KrakenParser::new(book_depth))),
+            stats,
+            client,
+        }
+    }
+
+    fn book_depth(&self) -> u32 {
+        book_depth_for(self.config.data_types.depth_levels)
+    }
+
+    /// 重新订阅订单簿，用于校验和不一致后的重建
+    fn resync_messages(pair: &str, depth: u32) -> Vec<Message> {
+        ["unsubscribe", "subscribe"]
+            .iter()
+            .map(|event| {
+                Message::Text(
+                    json!({
+                        "event": event,
+                        "pair": [pair],
+                        "subscription": { "name": "book", "depth": depth },
+                    })
+                    .to_string(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ExchangeConnector for KrakenConnector {
+    fn name(&self) -> &str {
+        "kraken"
+    }
+
+    fn supported_symbols(&self) -> &[String] {
+        &self.config.symbols
+    }
+
+    async fn connect(&mut self) -> Result<()> {
+        info!("Connecting to Kraken WebSocket...");
+
+        let stats = self.stats.clone();
+        let parser = self.parser.clone();
+        let event_sender = self.event_sender.clone();
+        let sender = self.client.sender();
+        let book_depth = self.book_depth();
+
+        self.client
+            .start(move |event: WsEvent<String>| match event {
+                WsEvent::Connected { reconnect } => {
+                    // 重连后本地订单簿已过期，等待重新订阅后的快照
+                    if reconnect {
+                        *parser.lock().expect("kraken parser lock") = KrakenParser::new(book_depth);
+                    }
+                    let _ = event_sender.send(TimedEvent::now(MarketDataEvent::ConnectionStatus {
+                        exchange: "kraken".to_string(),
+                        connected: true,
+                        timestamp: Timestamp::now(),
+                    }));
+                }
+                WsEvent::Message { message, received } => {
+                    let parsed = parser.lock().expect("kraken parser lock").parse(&message);
+                    match parsed {
+                        Ok(parsed) => {
+                            for pair in &parsed.resync_pairs {
+                                warn!("Resubscribing Kraken book for {} after checksum mismatch", pair);
+                                if let Ok(mut stats) = stats.try_write() {
+                                    stats.record_error_message(format!("Order book checksum mismatch for {}", pair));
+                                }
+                                for request in Self::resync_messages(pair, book_depth) {
+                                    let _ = sender.send(request);
+                                }
+                            }
+                            for event in parsed.events {
+                                let _ = event_sender.send(TimedEvent::received_at(event, received));
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Failed to parse Kraken message: {}", e);
+                            if let Ok(mut stats) = stats.try_write() {
+                                stats.record_error_message(e.to_string());
+                            }
+                        }
+                    }
+                }
+                WsEvent::DecodeError { .. } => {}
+                WsEvent::Disconnected { .. } => {
+                    let _ = event_sender.send(TimedEvent::now(MarketDataEvent::ConnectionStatus {
+                        exchange: "kraken".to_string(),
+                        connected: false,
+                        timestamp: Timestamp::now(),
+                    }));
+                }
+            })
+            .await?;
+
+        info!("Connected to Kraken WebSocket");
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        info!("Disconnecting from Kraken WebSocket...");
+
+        self.client.stop();
+        self.stats.write().await.set_connected(false);
+
+        info!("Disconnected from Kraken WebSocket");
+        Ok(())
+    }
+
+    async fn subscribe(&mut self, symbols: &[String], data_types: &[String]) -> Result<()> {
+        info!("Subscribing Kraken {} symbols with {} data types", symbols.len(), data_types.len());
+
+        let tracked = self.client.topics();
+        let topics: Vec<String> = symbols
+            .iter()
+            .flat_map(|symbol| data_types.iter().map(move |data_type| topic(symbol, data_type)))
+            .collect();
+        self.client.subscribe(&topics)?;
+
+        let mut stats = self.stats.write().await;
+        for symbol in symbols {
+            for data_type in data_types {
+                if !tracked.contains(&topic(symbol, data_type)) {
+                    stats.add_subscription(symbol.clone(), data_type.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn unsubscribe(&mut self, symbols: &[String], data_types: &[String]) -> Result<()> {
+        info!("Unsubscribing Kraken {} symbols", symbols.len());
+
+        let topics: Vec<String> = symbols
+            .iter()
+            .flat_map(|symbol| data_types.iter().map(move |data_type| topic(symbol, data_type)))
+            .collect();
+        self.client.unsubscribe(&topics)?;
+
+        let mut stats = self.stats.write().await;
+        for symbol in symbols {
+            for data_type in data_types {
+                stats.remove_subscription(symbol, data_type);
+            }
+            if data_types.iter().any(|t| t == "depth") {
+                if let Some(pair) = to_kraken_pair(symbol) {
+                    self.parser.lock().expect("kraken parser lock").reset_book(&pair);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.client.is_connected()
+    }
+
+    fn get_stats(&self) -> ConnectionStats {
+        // 同步接口，锁被占用时返回空统计
+        self.stats
+            .try_read()
+            .map(|stats| stats.clone())
+            .unwrap_or_default()
+    }
+
+    async fn handle_message(&mut self, message: &str) -> Result<Vec<MarketDataEvent>> {
+        let parsed = self.parser.lock().expect("kraken parser lock").parse(message)?;
+        Ok(parsed.events)
+    }
+}
+
+/// Kraken连接器工厂
+pub struct KrakenFactory;
+
+impl ConnectorFactory for KrakenFactory {
+    fn exchange(&self) -> &'static str {
+        "kraken"
+    }
+
+    fn create(&self, context: ConnectorContext) -> Result<Box<dyn ExchangeConnector + Send + Sync>> {
+        Ok(Box::new(KrakenConnector::new(context.config, context.event_sender)))
+    }
+
+    fn instrument_source(&self) -> Option<Arc<dyn InstrumentSource>> {
+        Some(Arc::new(KrakenInstruments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(levels: &[(&str, &str)]) -> Vec<Value> {
+        levels
+            .iter()
+            .map(|(price, volume)| json!([price, volume, "1534614057.321597"]))
+            .collect()
+    }
+
+    #[test]
+    fn test_pair_mapping() {
+        assert_eq!(to_kraken_pair("BTCUSDT").as_deref(), Some("XBT/USDT"));
+        assert_eq!(to_kraken_pair("ethusd").as_deref(), Some("ETH/USD"));
+        assert_eq!(to_kraken_pair("DOGE/EUR").as_deref(), Some("XDG/EUR"));
+        assert_eq!(to_kraken_pair("ETHBTC").as_deref(), Some("ETH/XBT"));
+        assert_eq!(to_kraken_pair("USDT"), None);
+
+        assert_eq!(from_kraken_pair("XBT/USDT"), "BTCUSDT");
+        assert_eq!(from_kraken_pair("ETH/XBT"), "ETHBTC");
+    }
+
+    #[test]
+    fn test_book_depth_rounds_up() {
+        assert_eq!(book_depth_for(5), 10);
+        assert_eq!(book_depth_for(20), 25);
+        assert_eq!(book_depth_for(5000), 1000);
+    }
+
+    #[test]
+    fn test_checksum_field_format() {
+        assert_eq!(checksum_field("0.05005"), "5005");
+        assert_eq!(checksum_field("0.00000500"), "500");
+        assert_eq!(checksum_field("5541.30000"), "554130000");
+    }
+
+    #[test]
+    fn test_book_checksum() {
+        let mut book = KrakenBook::new(10);
+        book.apply_snapshot(
+            &levels(&[("0.05005", "0.00000500"), ("0.05010", "0.00000500")]),
+            &levels(&[("0.05000", "0.00000500"), ("0.04995", "0.00000500")]),
+        )
+        .unwrap();
+
+        // 卖单升序、买单降序: "5005500" "5010500" "5000500" "4995500"
+        let expected = crc32fast::hash(b"5005500501050050005004995500");
+        assert_eq!(book.checksum(), expected);
+    }
+
+    #[test]
+    fn test_book_update_and_truncate() {
+        let mut book = KrakenBook::new(10);
+        book.apply_snapshot(
+            &levels(&[("101.0", "1.0"), ("102.0", "1.0")]),
+            &levels(&[("100.0", "1.0"), ("99.0", "1.0")]),
+        )
+        .unwrap();
+
+        // 数量为0删除档位
+        book.apply_update(&levels(&[("101.0", "0.00000000")]), &[]).unwrap();
+        let snapshot = book.to_order_book("BTCUSDT", Utc::now());
+        assert_eq!(snapshot.asks.len(), 1);
+        assert_eq!(snapshot.asks[0].price, "102.0".parse::<Decimal>().unwrap());
+        assert_eq!(snapshot.bids[0].price, "100.0".parse::<Decimal>().unwrap());
+
+        // 超出深度的最差档位被截断
+        let extra: Vec<(String, String)> = (0..12)
+            .map(|i| (format!("{}.0", 90 - i), "1.0".to_string()))
+            .collect();
+        let extra: Vec<(&str, &str)> = extra.iter().map(|(p, v)| (p.as_str(), v.as_str())).collect();
+        book.apply_update(&[], &levels(&extra)).unwrap();
+        let snapshot = book.to_order_book("BTCUSDT", Utc::now());
+        assert_eq!(snapshot.bids.len(), 10);
+        assert_eq!(snapshot.bids[0].price, "100.0".parse::<Decimal>().unwrap());
+    }
+
+    #[test]
+    fn test_checksum_mismatch_requests_resync() {
+        let mut parser = KrakenParser::new(10);
+        let snapshot = json!([
+            336,
+            { "as": levels(&[("101.0", "1.0")]), "bs": levels(&[("100.0", "1.0")]) },
+            "book-10",
+            "XBT/USDT"
+        ]);
+        let parsed = parser.parse(&snapshot.to_string()).unwrap();
+        assert!(matches!(parsed.events[0], MarketDataEvent::OrderBook(_)));
+
+        let update = json!([
+            336,
+            { "b": levels(&[("100.5", "2.0")]), "c": "12345" },
+            "book-10",
+            "XBT/USDT"
+        ]);
+        let parsed = parser.parse(&update.to_string()).unwrap();
+        assert_eq!(parsed.resync_pairs, vec!["XBT/USDT".to_string()]);
+        assert!(matches!(parsed.events[0], MarketDataEvent::Error { .. }));
+
+        // 重建前的增量被忽略
+        let parsed = parser.parse(&update.to_string()).unwrap();
+        assert!(parsed.events.is_empty());
+    }
+
+    #[test]
+    fn test_trade_parsing() {
+        let mut parser = KrakenParser::new(10);
+        let message = json!([
+            337,
+            [["5541.20000", "0.15850568", "1534614057.321597", "s", "l", ""]],
+            "trade",
+            "XBT/USD"
+        ]);
+        let parsed = parser.parse(&message.to_string()).unwrap();
+        match &parsed.events[0] {
+            MarketDataEvent::Trade(trade) => {
+                assert_eq!(trade.symbol, "BTCUSD");
+                assert_eq!(trade.side, "sell");
+                assert!(trade.is_buyer_maker);
+                assert_eq!(trade.timestamp.timestamp(), 1534614057);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ohlc_emits_closed_candle_on_rollover() {
+        let mut parser = KrakenParser::new(10);
+        let candle = |etime: &str, close: &str| {
+            json!([
+                42,
+                ["1542057314.748456", etime, "3586.7", "3586.7", "3586.6", close, "3586.68", "0.03373000", 2],
+                "ohlc-5",
+                "XBT/USD"
+            ])
+            .to_string()
+        };
+
+        let parsed = parser.parse(&candle("1542057600.000000", "3586.6")).unwrap();
+        assert_eq!(parsed.events.len(), 1);
+
+        let parsed = parser.parse(&candle("1542057900.000000", "3590.0")).unwrap();
+        assert_eq!(parsed.events.len(), 2);
+        match &parsed.events[0] {
+            MarketDataEvent::Kline(kline) => {
+                assert!(kline.is_closed);
+                assert_eq!(kline.interval, Interval::FiveMinutes);
+                assert_eq!(kline.open_time.timestamp(), 1542057300);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+}
@@ -1,19 +1,35 @@
 pub mod binance;
 pub mod exchange_manager;
-pub mod websocket_client;
+pub mod internal;
+pub mod kraken;
 pub mod connection_pool;
+pub mod connectivity;
+pub mod redundancy;
+pub mod registry;
+pub mod runtime_subscriptions;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 use shared_models::market::{MarketTick, Kline, OrderBook, Trade};
+use shared_models::Timestamp;
 use std::collections::HashMap;
+use std::time::Instant;
 use tokio::sync::mpsc;
 
-pub use binance::BinanceConnector;
+use crate::latency::PipelineTimestamps;
+
+pub use binance::{BinanceConnector, BinanceFactory};
+pub use crate::transport::{websocket_client, ConnectionStats, ConnectorError};
 pub use exchange_manager::ExchangeManager;
+pub use internal::{InternalConnector, InternalFactory};
+pub use kraken::{KrakenConnector, KrakenFactory};
 pub use websocket_client::WebSocketClient;
 pub use connection_pool::ConnectionPool;
+pub use connectivity::{ConnectivitySnapshot, ExchangeConnectivity};
+pub use redundancy::{FeedRedundancy, FeedRedundancyStats, FeedRole};
+pub use registry::{ConnectorContext, ConnectorFactory, ConnectorRegistry};
+pub use runtime_subscriptions::RuntimeSubscriptionManager;
 
 /// 交易所连接器特征
 #[async_trait]
@@ -55,17 +71,17 @@ pub enum MarketDataEvent {
     Trade(Trade),
     Heartbeat {
         exchange: String,
-        timestamp: i64,
+        timestamp: Timestamp,
     },
     Error {
         exchange: String,
         error: String,
-        timestamp: i64,
+        timestamp: Timestamp,
     },
     ConnectionStatus {
         exchange: String,
         connected: bool,
-        timestamp: i64,
+        timestamp: Timestamp,
     },
 }
 
@@ -96,113 +112,66 @@ impl MarketDataEvent {
         }
     }
 
-    /// 获取时间戳
-    pub fn timestamp(&self) -> i64 {
+    /// 获取交易对，连接状态等非行情事件返回 None
+    pub fn symbol(&self) -> Option<&str> {
         match self {
-            MarketDataEvent::Tick(tick) => tick.timestamp.timestamp(),
-            MarketDataEvent::Kline(kline) => kline.open_time,
-            MarketDataEvent::OrderBook(book) => book.timestamp,
-            MarketDataEvent::Trade(trade) => trade.timestamp,
-            MarketDataEvent::Heartbeat { timestamp, .. } => *timestamp,
-            MarketDataEvent::Error { timestamp, .. } => *timestamp,
-            MarketDataEvent::ConnectionStatus { timestamp, .. } => *timestamp,
+            MarketDataEvent::Tick(tick) => Some(&tick.symbol),
+            MarketDataEvent::Kline(kline) => Some(&kline.symbol),
+            MarketDataEvent::OrderBook(book) => Some(&book.symbol),
+            MarketDataEvent::Trade(trade) => Some(&trade.symbol),
+            MarketDataEvent::Heartbeat { .. }
+            | MarketDataEvent::Error { .. }
+            | MarketDataEvent::ConnectionStatus { .. } => None,
         }
     }
-}
-
-/// 连接统计信息
-#[derive(Debug, Clone, Default, serde::Serialize)]
-pub struct ConnectionStats {
-    pub connected: bool,
-    pub connection_time: Option<chrono::DateTime<chrono::Utc>>,
-    pub last_message_time: Option<chrono::DateTime<chrono::Utc>>,
-    pub messages_received: u64,
-    pub messages_sent: u64,
-    pub errors_count: u64,
-    pub reconnect_count: u32,
-    pub subscriptions: HashMap<String, Vec<String>>, // symbol -> data_types
-    pub latency_ms: Option<f64>,
-}
-
-impl ConnectionStats {
-    /// 记录接收消息
-    pub fn record_message_received(&mut self) {
-        self.messages_received += 1;
-        self.last_message_time = Some(chrono::Utc::now());
-    }
-
-    /// 记录发送消息
-    pub fn record_message_sent(&mut self) {
-        self.messages_sent += 1;
-    }
 
-    /// 记录错误
-    pub fn record_error(&mut self) {
-        self.errors_count += 1;
-    }
-
-    /// 记录重连
-    pub fn record_reconnect(&mut self) {
-        self.reconnect_count += 1;
-    }
-
-    /// 设置连接状态
-    pub fn set_connected(&mut self, connected: bool) {
-        self.connected = connected;
-        if connected {
-            self.connection_time = Some(chrono::Utc::now());
+    /// 已收盘的K线
+    pub fn closed_kline(&self) -> Option<&Kline> {
+        match self {
+            MarketDataEvent::Kline(kline) if kline.is_closed => Some(kline),
+            _ => None,
         }
     }
 
-    /// 更新延迟
-    pub fn update_latency(&mut self, latency_ms: f64) {
-        self.latency_ms = Some(latency_ms);
-    }
-
-    /// 添加订阅
-    pub fn add_subscription(&mut self, symbol: String, data_type: String) {
-        self.subscriptions
-            .entry(symbol)
-            .or_insert_with(Vec::new)
-            .push(data_type);
-    }
-
-    /// 移除订阅
-    pub fn remove_subscription(&mut self, symbol: &str, data_type: &str) {
-        if let Some(types) = self.subscriptions.get_mut(symbol) {
-            types.retain(|t| t != data_type);
-            if types.is_empty() {
-                self.subscriptions.remove(symbol);
-            }
+    /// 获取时间戳，K线为开盘时间
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            MarketDataEvent::Tick(tick) => tick.timestamp.into(),
+            MarketDataEvent::Kline(kline) => kline.open_time.into(),
+            MarketDataEvent::OrderBook(book) => book.timestamp.into(),
+            MarketDataEvent::Trade(trade) => trade.timestamp.into(),
+            MarketDataEvent::Heartbeat { timestamp, .. } => *timestamp,
+            MarketDataEvent::Error { timestamp, .. } => *timestamp,
+            MarketDataEvent::ConnectionStatus { timestamp, .. } => *timestamp,
         }
     }
+}
 
-    /// 获取连接时长
-    pub fn connection_duration(&self) -> Option<chrono::Duration> {
-        self.connection_time.map(|start| chrono::Utc::now() - start)
-    }
+/// 连接器发往事件处理器的事件，携带管道各阶段的时间
+#[derive(Debug, Clone)]
+pub struct TimedEvent {
+    pub event: MarketDataEvent,
+    pub timing: PipelineTimestamps,
+}
 
-    /// 获取消息速率 (messages/second)
-    pub fn message_rate(&self) -> f64 {
-        if let Some(duration) = self.connection_duration() {
-            let seconds = duration.num_seconds() as f64;
-            if seconds > 0.0 {
-                return self.messages_received as f64 / seconds;
-            }
+impl TimedEvent {
+    /// 由原始消息解析出的事件，`received` 为收到原始消息的时间
+    pub fn received_at(event: MarketDataEvent, received: Instant) -> Self {
+        Self {
+            event,
+            timing: PipelineTimestamps::received_at(received),
         }
-        0.0
     }
 
-    /// 获取错误率
-    pub fn error_rate(&self) -> f64 {
-        if self.messages_received > 0 {
-            self.errors_count as f64 / self.messages_received as f64 * 100.0
-        } else {
-            0.0
-        }
+    /// 连接器自行产生的事件（连接状态、错误等），收到和解析时间都取当前时间
+    pub fn now(event: MarketDataEvent) -> Self {
+        Self::received_at(event, Instant::now())
     }
 }
 
+/// 事件处理器的发送端
+pub type EventSender = mpsc::UnboundedSender<TimedEvent>;
+
 /// 订阅配置
 #[derive(Debug, Clone)]
 pub struct SubscriptionConfig {
@@ -266,34 +235,6 @@ impl SubscriptionConfig {
     }
 }
 
-/// 连接器错误类型
-#[derive(Debug, thiserror::Error)]
-pub enum ConnectorError {
-    #[error("Connection failed: {0}")]
-    ConnectionFailed(String),
-    
-    #[error("Authentication failed: {0}")]
-    AuthenticationFailed(String),
-    
-    #[error("Subscription failed: {0}")]
-    SubscriptionFailed(String),
-    
-    #[error("Message parsing failed: {0}")]
-    MessageParsingFailed(String),
-    
-    #[error("Rate limit exceeded: {0}")]
-    RateLimitExceeded(String),
-    
-    #[error("Network error: {0}")]
-    NetworkError(String),
-    
-    #[error("Protocol error: {0}")]
-    ProtocolError(String),
-    
-    #[error("Configuration error: {0}")]
-    ConfigurationError(String),
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,6 +253,10 @@ mod tests {
         
         stats.record_error();
         assert_eq!(stats.errors_count, 1);
+
+        stats.record_error_message("connection reset");
+        assert_eq!(stats.errors_count, 2);
+        assert_eq!(stats.last_error.as_deref(), Some("connection reset"));
         
         // 测试订阅管理
         stats.add_subscription("BTCUSDT".to_string(), "ticker".to_string());
@@ -339,18 +284,26 @@ mod tests {
         use rust_decimal::Decimal;
         
         let tick = MarketTick {
-            exchange: "binance".to_string(),
+            id: None,
+            exchange: shared_models::common::Exchange::Binance,
             symbol: "BTCUSDT".to_string(),
-            timestamp: 1640995200000,
+            timestamp: Timestamp::from_millis(1640995200000).to_datetime(),
             price: Decimal::new(50000, 0),
             volume: Decimal::new(100, 0),
             bid: Decimal::new(49999, 0),
             ask: Decimal::new(50001, 0),
+            bid_volume: Decimal::ZERO,
+            ask_volume: Decimal::ZERO,
+            trade_id: None,
+            is_buyer_maker: None,
+            data_quality: shared_models::common::DataQuality::Normal,
         };
         
         let event = MarketDataEvent::Tick(tick);
         assert_eq!(event.event_type(), "tick");
         assert_eq!(event.exchange(), "binance");
-        assert_eq!(event.timestamp(), 1640995200000);
+        // 事件时间戳与原始毫秒一致，不会被截断为秒
+        assert_eq!(event.timestamp().as_millis(), 1640995200000);
+        assert_eq!(event.timestamp(), Timestamp::from_secs(1640995200));
     }
 }
\ No newline at end of file
@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use super::MarketDataEvent;
+use crate::config::FeedRedundancyConfig;
+
+/// 冗余连接中的角色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedRole {
+    #[default]
+    Primary,
+    Standby,
+}
+
+impl FeedRole {
+    fn index(self) -> usize {
+        match self {
+            FeedRole::Primary => 0,
+            FeedRole::Standby => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for FeedRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeedRole::Primary => write!(f, "primary"),
+            FeedRole::Standby => write!(f, "standby"),
+        }
+    }
+}
+
+/// 单个交易所冗余连接的统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeedRedundancyStats {
+    /// 当前负责心跳、状态等无标识事件的连接
+    pub active: FeedRole,
+    pub delivered: u64,
+    pub duplicates: u64,
+    /// 各连接最先送达的事件数
+    pub primary_first: u64,
+    pub standby_first: u64,
+    pub promotions: u32,
+    pub last_promotion: Option<DateTime<Utc>>,
+}
+
+/// 按事件标识去重的定长窗口
+struct DedupWindow {
+    seen: HashSet<String>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl DedupWindow {
+    fn new(capacity: usize) -> Self {
+        Self {
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// 首次出现返回 true
+    fn insert(&mut self, key: String) -> bool {
+        if self.seen.contains(&key) {
+            return false;
+        }
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+}
+
+struct FeedState {
+    stall_timeout: Duration,
+    registered_at: Instant,
+    last_seen: [Option<Instant>; 2],
+    dedup: DedupWindow,
+    stats: FeedRedundancyStats,
+}
+
+impl FeedState {
+    /// 连接在阈值内有消息；尚未收到消息时从注册起计算
+    fn is_fresh(&self, role: FeedRole, now: Instant) -> bool {
+        let since = self.last_seen[role.index()].unwrap_or(self.registered_at);
+        now.saturating_duration_since(since) <= self.stall_timeout
+    }
+}
+
+/// 事件标识，同一事件在两条连接上得到相同的标识
+///
+/// 成交按成交ID，订单簿按更新ID，行情和K线按内容；
+/// 心跳、错误和连接状态没有标识，只接受当前连接的。
+pub fn event_key(event: &MarketDataEvent) -> Option<String> {
+    match event {
+        MarketDataEvent::Trade(trade) => Some(format!("trade|{}|{}", trade.symbol, trade.trade_id)),
+        MarketDataEvent::OrderBook(book) => Some(format!(
+            "book|{}|{}|{}",
+            book.symbol, book.last_update_id, book.timestamp
+        )),
+        MarketDataEvent::Tick(tick) => Some(format!(
+            "tick|{}|{}|{}|{}",
+            tick.symbol, tick.timestamp, tick.price, tick.volume
+        )),
+        MarketDataEvent::Kline(kline) => Some(format!(
+            "kline|{}|{}|{}|{}|{}|{}|{}",
+            kline.symbol,
+            kline.interval,
+            kline.open_time,
+            kline.close,
+            kline.volume,
+            kline.trades_count,
+            kline.is_closed
+        )),
+        MarketDataEvent::Heartbeat { .. }
+        | MarketDataEvent::Error { .. }
+        | MarketDataEvent::ConnectionStatus { .. } => None,
+    }
+}
+
+/// 主备行情连接的合并层
+///
+/// 同一交易所的两条连接同时接收行情，有标识的事件先到先发，
+/// 另一条连接的重复事件被丢弃，下游每个事件只收到一次。
+/// 当前连接停顿超过阈值而另一条连接仍有消息时自动切换，
+/// 恢复后不会切回，避免在两条连接间来回切换。
+#[derive(Default)]
+pub struct FeedRedundancy {
+    feeds: Mutex<HashMap<String, FeedState>>,
+}
+
+impl FeedRedundancy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册交易所的冗余连接，已注册时保留去重窗口
+    pub fn register(&self, exchange: &str, config: &FeedRedundancyConfig) {
+        let mut feeds = self.feeds.lock().unwrap();
+        feeds.entry(exchange.to_string()).or_insert_with(|| FeedState {
+            stall_timeout: Duration::from_millis(config.stall_timeout_ms),
+            registered_at: Instant::now(),
+            last_seen: [None, None],
+            dedup: DedupWindow::new(config.dedup_window),
+            stats: FeedRedundancyStats::default(),
+        });
+    }
+
+    /// 是否向下游转发该事件
+    pub fn accept(&self, exchange: &str, role: FeedRole, event: &MarketDataEvent) -> bool {
+        self.accept_at(exchange, role, event, Instant::now())
+    }
+
+    fn accept_at(&self, exchange: &str, role: FeedRole, event: &MarketDataEvent, now: Instant) -> bool {
+        let mut feeds = self.feeds.lock().unwrap();
+        let Some(state) = feeds.get_mut(exchange) else {
+            return true;
+        };
+
+        state.last_seen[role.index()] = Some(now);
+        let active = state.stats.active;
+        if role != active && !state.is_fresh(active, now) {
+            warn!(
+                "{} {} feed stalled, promoting {} feed",
+                exchange, active, role
+            );
+            state.stats.active = role;
+            state.stats.promotions += 1;
+            state.stats.last_promotion = Some(Utc::now());
+        }
+
+        match event_key(event) {
+            Some(key) => {
+                if state.dedup.insert(key) {
+                    state.stats.delivered += 1;
+                    match role {
+                        FeedRole::Primary => state.stats.primary_first += 1,
+                        FeedRole::Standby => state.stats.standby_first += 1,
+                    }
+                    true
+                } else {
+                    state.stats.duplicates += 1;
+                    false
+                }
+            }
+            None => {
+                let accepted = role == state.stats.active;
+                if accepted {
+                    state.stats.delivered += 1;
+                }
+                accepted
+            }
+        }
+    }
+
+    /// 各交易所冗余连接统计
+    pub fn stats(&self) -> HashMap<String, FeedRedundancyStats> {
+        self.feeds
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(exchange, state)| (exchange.clone(), state.stats.clone()))
+            .collect()
+    }
+
+    /// 当前负责无标识事件的连接
+    pub fn active_role(&self, exchange: &str) -> Option<FeedRole> {
+        self.feeds
+            .lock()
+            .unwrap()
+            .get(exchange)
+            .map(|state| state.stats.active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use shared_models::{common::Exchange, market::Trade, Timestamp};
+
+    fn trade(id: &str) -> MarketDataEvent {
+        MarketDataEvent::Trade(Trade {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            trade_id: id.to_string(),
+            timestamp: Timestamp::from_millis(1_000).to_datetime(),
+            price: Decimal::from(100),
+            quantity: Decimal::ONE,
+            quote_quantity: Decimal::from(100),
+            side: "buy".to_string(),
+            is_buyer_maker: false,
+            is_best_match: true,
+        })
+    }
+
+    fn heartbeat() -> MarketDataEvent {
+        MarketDataEvent::Heartbeat {
+            exchange: "binance".to_string(),
+            timestamp: Timestamp::from_millis(0),
+        }
+    }
+
+    #[test]
+    fn test_exactly_once_delivery() {
+        let redundancy = FeedRedundancy::new();
+        redundancy.register("binance", &FeedRedundancyConfig::default());
+        let now = Instant::now();
+
+        assert!(redundancy.accept_at("binance", FeedRole::Primary, &trade("1"), now));
+        assert!(!redundancy.accept_at("binance", FeedRole::Standby, &trade("1"), now));
+        // 备用连接先到时由备用连接送达
+        assert!(redundancy.accept_at("binance", FeedRole::Standby, &trade("2"), now));
+        assert!(!redundancy.accept_at("binance", FeedRole::Primary, &trade("2"), now));
+
+        assert!(redundancy.accept_at("binance", FeedRole::Primary, &heartbeat(), now));
+        assert!(!redundancy.accept_at("binance", FeedRole::Standby, &heartbeat(), now));
+
+        let stats = &redundancy.stats()["binance"];
+        assert_eq!(stats.duplicates, 2);
+        assert_eq!(stats.primary_first, 1);
+        assert_eq!(stats.standby_first, 1);
+    }
+
+    #[test]
+    fn test_promote_standby_on_stall() {
+        let redundancy = FeedRedundancy::new();
+        redundancy.register(
+            "binance",
+            &FeedRedundancyConfig {
+                enabled: true,
+                stall_timeout_ms: 1000,
+                ..Default::default()
+            },
+        );
+        let start = Instant::now();
+        redundancy.accept_at("binance", FeedRole::Primary, &heartbeat(), start);
+        redundancy.accept_at("binance", FeedRole::Standby, &heartbeat(), start + Duration::from_millis(500));
+        assert_eq!(redundancy.active_role("binance"), Some(FeedRole::Primary));
+
+        let later = start + Duration::from_millis(2000);
+        assert!(redundancy.accept_at("binance", FeedRole::Standby, &heartbeat(), later));
+        assert_eq!(redundancy.active_role("binance"), Some(FeedRole::Standby));
+
+        // 主连接恢复后不切回
+        assert!(!redundancy.accept_at("binance", FeedRole::Primary, &heartbeat(), later));
+        assert_eq!(redundancy.stats()["binance"].promotions, 1);
+    }
+
+    #[test]
+    fn test_dedup_window_eviction() {
+        let mut window = DedupWindow::new(2);
+        assert!(window.insert("a".to_string()));
+        assert!(window.insert("b".to_string()));
+        assert!(window.insert("c".to_string()));
+        assert!(window.insert("a".to_string()));
+        assert!(!window.insert("c".to_string()));
+    }
+}
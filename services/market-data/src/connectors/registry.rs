@@ -0,0 +1,158 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{BinanceFactory, ConnectorError, EventSender, ExchangeConnector, InternalFactory, KrakenFactory};
+use crate::config::ExchangeConfig;
+use crate::instruments::InstrumentSource;
+
+/// 创建连接器所需的上下文
+pub struct ConnectorContext {
+    /// 配置中的交易所键，同一连接器可以对应多个实例（例如现货和测试网）
+    pub instance: String,
+    pub config: ExchangeConfig,
+    pub event_sender: EventSender,
+}
+
+/// 连接器工厂
+///
+/// 每个交易所模块实现一个工厂，新增交易所只需实现连接器和工厂并注册，
+/// 不需要修改 ExchangeManager。
+pub trait ConnectorFactory: Send + Sync {
+    /// 交易所名称，对应配置中的 name 字段
+    fn exchange(&self) -> &'static str;
+
+    /// 根据配置创建连接器
+    fn create(&self, context: ConnectorContext) -> Result<Box<dyn ExchangeConnector + Send + Sync>>;
+
+    /// 交易对元数据接口，不支持时返回 None
+    fn instrument_source(&self) -> Option<Arc<dyn InstrumentSource>> {
+        None
+    }
+}
+
+/// 内置连接器工厂，新增交易所模块后在此注册
+pub fn builtin_factories() -> Vec<Arc<dyn ConnectorFactory>> {
+    vec![
+        Arc::new(BinanceFactory),
+        Arc::new(KrakenFactory),
+        Arc::new(InternalFactory),
+    ]
+}
+
+/// 连接器注册表
+#[derive(Clone, Default)]
+pub struct ConnectorRegistry {
+    factories: HashMap<String, Arc<dyn ConnectorFactory>>,
+}
+
+impl ConnectorRegistry {
+    /// 创建空注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建包含内置连接器的注册表
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        for factory in builtin_factories() {
+            registry.register(factory);
+        }
+        registry
+    }
+
+    /// 注册连接器工厂，同名工厂会被替换
+    pub fn register(&mut self, factory: Arc<dyn ConnectorFactory>) {
+        self.factories
+            .insert(factory.exchange().to_lowercase(), factory);
+    }
+
+    /// 是否支持该交易所
+    pub fn supports(&self, exchange: &str) -> bool {
+        self.factories.contains_key(&exchange.to_lowercase())
+    }
+
+    /// 已注册的交易所
+    pub fn exchanges(&self) -> Vec<String> {
+        let mut exchanges: Vec<String> = self.factories.keys().cloned().collect();
+        exchanges.sort();
+        exchanges
+    }
+
+    /// 交易所的交易对元数据接口
+    pub fn instrument_source(&self, exchange: &str) -> Option<Arc<dyn InstrumentSource>> {
+        self.factories
+            .get(&exchange.to_lowercase())
+            .and_then(|factory| factory.instrument_source())
+    }
+
+    /// 配置对应的连接器名称：优先使用 name 字段，未设置时使用配置键
+    pub fn connector_name(instance: &str, config: &ExchangeConfig) -> String {
+        if config.name.is_empty() {
+            instance.to_lowercase()
+        } else {
+            config.name.to_lowercase()
+        }
+    }
+
+    /// 根据配置创建连接器
+    pub fn create(
+        &self,
+        instance: &str,
+        config: &ExchangeConfig,
+        event_sender: EventSender,
+    ) -> Result<Box<dyn ExchangeConnector + Send + Sync>> {
+        let name = Self::connector_name(instance, config);
+        let factory = self.factories.get(&name).ok_or_else(|| {
+            ConnectorError::ConfigurationError(format!(
+                "No connector registered for exchange {} (available: {})",
+                name,
+                self.exchanges().join(", ")
+            ))
+        })?;
+
+        factory.create(ConnectorContext {
+            instance: instance.to_string(),
+            config: config.clone(),
+            event_sender,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_builtin_registry() {
+        let registry = ConnectorRegistry::with_builtin();
+        assert!(registry.supports("binance"));
+        assert!(registry.supports("BINANCE"));
+        assert!(registry.supports("kraken"));
+        assert!(registry.instrument_source("kraken").is_some());
+        assert!(registry.supports("internal"));
+        assert!(registry.instrument_source("internal").is_none());
+        assert!(!registry.supports("unknown"));
+    }
+
+    #[test]
+    fn test_connector_name_falls_back_to_instance() {
+        let mut config = ExchangeConfig::binance();
+        assert_eq!(ConnectorRegistry::connector_name("binance_testnet", &config), "binance");
+
+        config.name = String::new();
+        assert_eq!(ConnectorRegistry::connector_name("Binance", &config), "binance");
+    }
+
+    #[test]
+    fn test_create_unknown_exchange() {
+        let registry = ConnectorRegistry::with_builtin();
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let mut config = ExchangeConfig::default();
+        config.name = "kraken2".to_string();
+
+        let result = registry.create("kraken2", &config, sender);
+        assert!(result.is_err());
+    }
+}
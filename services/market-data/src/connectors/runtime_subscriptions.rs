@@ -0,0 +1,360 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use shared_models::common::Interval;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::ExchangeManager;
+use crate::config::MarketDataConfig;
+
+/// symbol -> data_types
+pub type SymbolSubscriptions = BTreeMap<String, BTreeSet<String>>;
+
+/// 单个交易所相对静态配置的订阅变更
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeSubscriptionChanges {
+    #[serde(default)]
+    pub added: SymbolSubscriptions,
+    #[serde(default)]
+    pub removed: SymbolSubscriptions,
+}
+
+impl ExchangeSubscriptionChanges {
+    fn insert(map: &mut SymbolSubscriptions, symbol: &str, data_type: &str) {
+        map.entry(symbol.to_string())
+            .or_default()
+            .insert(data_type.to_string());
+    }
+
+    fn take(map: &mut SymbolSubscriptions, symbol: &str, data_type: &str) -> bool {
+        let removed = match map.get_mut(symbol) {
+            Some(types) => types.remove(data_type),
+            None => false,
+        };
+        if map.get(symbol).map(|t| t.is_empty()).unwrap_or(false) {
+            map.remove(symbol);
+        }
+        removed
+    }
+
+    /// 记录新增订阅，`configured` 表示静态配置中已有该订阅
+    pub fn add(&mut self, symbol: &str, data_type: &str, configured: bool) {
+        Self::take(&mut self.removed, symbol, data_type);
+        if !configured {
+            Self::insert(&mut self.added, symbol, data_type);
+        }
+    }
+
+    /// 记录移除订阅，`configured` 表示静态配置中已有该订阅
+    pub fn remove(&mut self, symbol: &str, data_type: &str, configured: bool) {
+        Self::take(&mut self.added, symbol, data_type);
+        if configured {
+            Self::insert(&mut self.removed, symbol, data_type);
+        }
+    }
+
+    /// 丢弃与当前静态配置不再构成差异的变更，静态配置在保存后可能已修改
+    pub fn reconcile(&mut self, configured: Option<&SymbolSubscriptions>) {
+        let contains = |symbol: &String, data_type: &String| {
+            configured
+                .and_then(|c| c.get(symbol))
+                .map(|types| types.contains(data_type))
+                .unwrap_or(false)
+        };
+        for (symbol, types) in self.added.iter_mut() {
+            types.retain(|t| !contains(symbol, t));
+        }
+        for (symbol, types) in self.removed.iter_mut() {
+            types.retain(|t| contains(symbol, t));
+        }
+        self.added.retain(|_, types| !types.is_empty());
+        self.removed.retain(|_, types| !types.is_empty());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 运行时订阅变更，按交易所保存
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuntimeSubscriptions {
+    #[serde(default)]
+    pub exchanges: BTreeMap<String, ExchangeSubscriptionChanges>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// 校验订阅数据类型
+pub fn validate_data_type(data_type: &str) -> Result<()> {
+    match data_type {
+        "ticker" | "trade" | "depth" => Ok(()),
+        other => match other.strip_prefix("kline_") {
+            Some(interval) => interval
+                .parse::<Interval>()
+                .map(|_| ())
+                .map_err(|e| anyhow!(e.to_string())),
+            None => Err(anyhow!("Unsupported data type: {}", other)),
+        },
+    }
+}
+
+/// 启用的交易所在静态配置中的订阅，exchange -> symbol -> data_types
+fn configured_subscriptions(config: &MarketDataConfig) -> BTreeMap<String, SymbolSubscriptions> {
+    config
+        .enabled_exchanges()
+        .into_iter()
+        .map(|(name, exchange_config)| {
+            let data_types: BTreeSet<String> = ExchangeManager::configured_data_types(exchange_config)
+                .into_iter()
+                .collect();
+            let symbols = exchange_config
+                .symbols
+                .iter()
+                .map(|symbol| (symbol.to_uppercase(), data_types.clone()))
+                .collect();
+            (name.to_lowercase(), symbols)
+        })
+        .collect()
+}
+
+/// 运行时订阅管理
+///
+/// 通过 ExchangeManager 增删订阅，并将相对静态配置的变更保存到Redis，
+/// 服务重启后在静态配置订阅完成后重新应用。
+#[derive(Clone)]
+pub struct RuntimeSubscriptionManager {
+    key: String,
+    redis: Option<Arc<RwLock<ConnectionManager>>>,
+    configured: Arc<BTreeMap<String, SymbolSubscriptions>>,
+    state: Arc<RwLock<RuntimeSubscriptions>>,
+}
+
+impl RuntimeSubscriptionManager {
+    pub async fn new(config: &MarketDataConfig) -> Self {
+        let redis_config = config.storage.redis.as_ref();
+        let key_prefix = redis_config
+            .map(|c| c.key_prefix.clone())
+            .unwrap_or_else(|| "market_data:".to_string());
+
+        let redis = match redis_config {
+            Some(redis_config) => match Self::connect(&redis_config.url).await {
+                Ok(conn) => Some(Arc::new(RwLock::new(conn))),
+                Err(e) => {
+                    warn!(
+                        "Runtime subscriptions will not persist, failed to connect to Redis: {}",
+                        e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Self {
+            key: format!("{}subscriptions:runtime", key_prefix),
+            redis,
+            configured: Arc::new(configured_subscriptions(config)),
+            state: Arc::new(RwLock::new(RuntimeSubscriptions::default())),
+        }
+    }
+
+    async fn connect(url: &str) -> Result<ConnectionManager> {
+        let client = redis::Client::open(url)?;
+        Ok(ConnectionManager::new(client).await?)
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    /// 当前运行时变更
+    pub async fn changes(&self) -> RuntimeSubscriptions {
+        self.state.read().await.clone()
+    }
+
+    async fn load(&self) -> Option<RuntimeSubscriptions> {
+        use redis::AsyncCommands;
+
+        let redis = self.redis.as_ref()?;
+        let mut conn = redis.write().await;
+        match conn.get::<_, Option<String>>(&self.key).await {
+            Ok(Some(json)) => match serde_json::from_str(&json) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    warn!("Ignoring invalid runtime subscriptions: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to load runtime subscriptions: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn persist(&self, state: &RuntimeSubscriptions) -> Result<()> {
+        use redis::AsyncCommands;
+
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return Ok(()),
+        };
+        let json = serde_json::to_string(state)?;
+        let mut conn = redis.write().await;
+        conn.set::<_, _, ()>(&self.key, json).await?;
+        Ok(())
+    }
+
+    /// 重新应用已保存的订阅变更，需在静态配置的连接建立后调用
+    pub async fn restore(&self, manager: &ExchangeManager) {
+        let mut saved = match self.load().await {
+            Some(saved) => saved,
+            None => return,
+        };
+        for (exchange, changes) in saved.exchanges.iter_mut() {
+            changes.reconcile(self.configured.get(exchange));
+        }
+        saved.exchanges.retain(|_, changes| !changes.is_empty());
+
+        for (exchange, changes) in &saved.exchanges {
+            for (symbol, types) in &changes.added {
+                let types: Vec<String> = types.iter().cloned().collect();
+                if let Err(e) = manager
+                    .subscribe_data(exchange, std::slice::from_ref(symbol), &types)
+                    .await
+                {
+                    warn!("Failed to restore subscription {}:{} {:?}: {}", exchange, symbol, types, e);
+                }
+            }
+            for (symbol, types) in &changes.removed {
+                let types: Vec<String> = types.iter().cloned().collect();
+                if let Err(e) = manager
+                    .unsubscribe_data(exchange, std::slice::from_ref(symbol), &types)
+                    .await
+                {
+                    warn!("Failed to restore unsubscription {}:{} {:?}: {}", exchange, symbol, types, e);
+                }
+            }
+        }
+
+        info!(
+            "Restored runtime subscriptions for {} exchanges",
+            saved.exchanges.len()
+        );
+        *self.state.write().await = saved;
+    }
+
+    /// 新增订阅
+    pub async fn add(
+        &self,
+        manager: &ExchangeManager,
+        exchange: &str,
+        symbols: &[String],
+        data_types: &[String],
+    ) -> Result<RuntimeSubscriptions> {
+        manager.subscribe_data(exchange, symbols, data_types).await?;
+        self.record(exchange, symbols, data_types, true).await
+    }
+
+    /// 移除订阅
+    pub async fn remove(
+        &self,
+        manager: &ExchangeManager,
+        exchange: &str,
+        symbols: &[String],
+        data_types: &[String],
+    ) -> Result<RuntimeSubscriptions> {
+        manager.unsubscribe_data(exchange, symbols, data_types).await?;
+        self.record(exchange, symbols, data_types, false).await
+    }
+
+    async fn record(
+        &self,
+        exchange: &str,
+        symbols: &[String],
+        data_types: &[String],
+        added: bool,
+    ) -> Result<RuntimeSubscriptions> {
+        let configured = self.configured.get(exchange);
+        let mut state = self.state.write().await;
+        let changes = state.exchanges.entry(exchange.to_string()).or_default();
+        for symbol in symbols {
+            for data_type in data_types {
+                let is_configured = configured
+                    .and_then(|c| c.get(symbol))
+                    .map(|types| types.contains(data_type))
+                    .unwrap_or(false);
+                if added {
+                    changes.add(symbol, data_type, is_configured);
+                } else {
+                    changes.remove(symbol, data_type, is_configured);
+                }
+            }
+        }
+        if changes.is_empty() {
+            state.exchanges.remove(exchange);
+        }
+        state.updated_at = Some(Utc::now());
+
+        // 订阅已生效，持久化失败只影响重启后的恢复
+        if let Err(e) = self.persist(&state).await {
+            warn!("Failed to persist runtime subscriptions: {}", e);
+        }
+        Ok(state.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_cancel_out() {
+        let mut changes = ExchangeSubscriptionChanges::default();
+
+        changes.add("SOLUSDT", "trade", false);
+        assert!(changes.added["SOLUSDT"].contains("trade"));
+        changes.remove("SOLUSDT", "trade", false);
+        assert!(changes.is_empty());
+
+        changes.remove("BTCUSDT", "depth", true);
+        assert!(changes.removed["BTCUSDT"].contains("depth"));
+        changes.add("BTCUSDT", "depth", true);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_changes_diff_against_static_config() {
+        let mut changes = ExchangeSubscriptionChanges::default();
+
+        // 重复新增静态配置中的订阅后再移除，仍需记录移除，否则重启后会恢复
+        changes.add("BTCUSDT", "trade", true);
+        assert!(changes.is_empty());
+        changes.remove("BTCUSDT", "trade", true);
+        assert!(changes.removed["BTCUSDT"].contains("trade"));
+
+        // 移除未配置的订阅不记录
+        changes.remove("DOGEUSDT", "trade", false);
+        assert!(!changes.removed.contains_key("DOGEUSDT"));
+
+        // 保存后静态配置新增了SOLUSDT、去掉了BTCUSDT
+        changes.add("SOLUSDT", "ticker", false);
+        let configured: SymbolSubscriptions =
+            [("SOLUSDT".to_string(), BTreeSet::from(["ticker".to_string()]))].into();
+        changes.reconcile(Some(&configured));
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_validate_data_type() {
+        assert!(validate_data_type("trade").is_ok());
+        assert!(validate_data_type("kline_1h").is_ok());
+        assert!(validate_data_type("kline_7m").is_err());
+        assert!(validate_data_type("funding").is_err());
+    }
+}
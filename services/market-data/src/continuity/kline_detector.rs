@@ -1,25 +1,74 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::warn;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
 use shared_models::common::{Exchange, Interval, DataQuality};
 
-/// K线连续性检测器 - Phase 1 最小实现
+use super::ContinuityStateStore;
+use crate::config::ContinuityConfig;
+use shared_models::market::Kline;
+
+/// K线连续性检测器
+///
+/// 按 (exchange, symbol, interval) 维护最后的 open_time，记录最近的间隙及其严重程度。
 pub struct KlineContinuityDetector {
-    /// 按 (exchange, symbol, interval) 维度维护最后的 open_time
-    last_open_times: Arc<RwLock<HashMap<String, i64>>>,
+    config: ContinuityConfig,
+    /// exchange:symbol:interval -> 序列状态
+    series: Arc<RwLock<HashMap<String, SeriesState>>>,
+    /// 最近的间隙记录
+    gaps: Arc<RwLock<VecDeque<GapRecord>>>,
     /// 内存统计
     stats: Arc<RwLock<ContinuityStats>>,
 }
 
 /// 连续性统计信息
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct ContinuityStats {
     pub total_checks: u64,
     pub gaps_detected: u64,
     pub last_check_time: Option<i64>,
 }
 
+/// 间隙严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapSeverity {
+    /// 延迟或提前到达，未缺失或只缺一根
+    Minor,
+    Major,
+    Critical,
+}
+
+/// 一次间隙记录
+#[derive(Debug, Clone, Serialize)]
+pub struct GapRecord {
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub interval: Interval,
+    pub expected_open_time: i64,
+    pub actual_open_time: i64,
+    /// 正值为延迟，负值为提前
+    pub gap_duration_ms: i64,
+    pub missing_candles: i64,
+    pub severity: GapSeverity,
+    pub detected_at: i64,
+}
+
+/// 单个序列的检测状态，保存后重启时恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesState {
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub interval: Interval,
+    pub last_open_time: i64,
+    #[serde(default)]
+    pub checks: u64,
+    #[serde(default)]
+    pub gaps: u64,
+}
+
 /// 连续性检测结果
 #[derive(Debug, Clone)]
 pub struct ContinuityCheckResult {
@@ -29,17 +78,76 @@ pub struct ContinuityCheckResult {
     pub actual_time: i64,
     /// 数据质量标记
     pub data_quality: DataQuality,
+    pub severity: Option<GapSeverity>,
+}
+
+/// 间隙查询条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GapFilter {
+    pub exchange: Option<String>,
+    pub symbol: Option<String>,
+    pub interval: Option<String>,
+    /// 只返回不低于该严重程度的间隙
+    pub min_severity: Option<GapSeverity>,
+}
+
+impl GapFilter {
+    fn matches(&self, gap: &GapRecord) -> bool {
+        let exchange = self
+            .exchange
+            .as_ref()
+            .map(|e| e.eq_ignore_ascii_case(gap.exchange.as_str()))
+            .unwrap_or(true);
+        let symbol = self
+            .symbol
+            .as_ref()
+            .map(|s| s.eq_ignore_ascii_case(&gap.symbol))
+            .unwrap_or(true);
+        let interval = self
+            .interval
+            .as_ref()
+            .map(|i| i == gap.interval.as_str())
+            .unwrap_or(true);
+        let severity = self.min_severity.map(|min| gap.severity >= min).unwrap_or(true);
+        exchange && symbol && interval && severity
+    }
+}
+
+fn series_key(exchange: &Exchange, symbol: &str, interval: &Interval) -> String {
+    format!("{}:{}:{}", exchange.as_str(), symbol, interval.as_str())
 }
 
 impl KlineContinuityDetector {
-    /// 创建新的连续性检测器
+    /// 使用默认配置创建连续性检测器
     pub fn new() -> Self {
+        Self::with_config(ContinuityConfig::default())
+    }
+
+    pub fn with_config(config: ContinuityConfig) -> Self {
         Self {
-            last_open_times: Arc::new(RwLock::new(HashMap::new())),
+            config,
+            series: Arc::new(RwLock::new(HashMap::new())),
+            gaps: Arc::new(RwLock::new(VecDeque::new())),
             stats: Arc::new(RwLock::new(ContinuityStats::default())),
         }
     }
 
+    /// 按缺失根数划分严重程度
+    fn classify(&self, gap_ms: i64, interval_ms: i64) -> (i64, GapSeverity) {
+        if gap_ms <= 0 {
+            return (0, GapSeverity::Minor);
+        }
+        let missing = gap_ms / interval_ms;
+        let severity = if missing >= self.config.critical_missing_candles as i64 {
+            GapSeverity::Critical
+        } else if missing >= self.config.major_missing_candles as i64 {
+            GapSeverity::Major
+        } else {
+            GapSeverity::Minor
+        };
+        (missing, severity)
+    }
+
     /// 检查K线连续性
     /// 参数：exchange, symbol, interval, open_time (毫秒时间戳)
     pub async fn check_continuity(
@@ -50,11 +158,7 @@ impl KlineContinuityDetector {
         open_time: i64,
     ) -> ContinuityCheckResult {
         // 构建唯一键：exchange:symbol:interval
-        let key = format!("{}:{}:{}", 
-            exchange.as_str(), 
-            symbol, 
-            interval.as_str()
-        );
+        let key = series_key(&exchange, symbol, &interval);
 
         let mut result = ContinuityCheckResult {
             has_gap: false,
@@ -62,12 +166,20 @@ impl KlineContinuityDetector {
             expected_next_time: None,
             actual_time: open_time,
             data_quality: DataQuality::Normal, // 默认为正常
+            severity: None,
         };
 
         // 获取上次的 open_time
-        let mut last_times = self.last_open_times.write().await;
-        
-        if let Some(&last_open_time) = last_times.get(&key) {
+        let mut series = self.series.write().await;
+        let mut gap = None;
+
+        if let Some(state) = series.get(&key) {
+            let last_open_time = state.last_open_time;
+            // 重复推送的同一根K线不计入检测
+            if open_time == last_open_time {
+                return result;
+            }
+
             // 计算预期的下一个 open_time (基于interval)
             let interval_ms = self.get_interval_milliseconds(&interval);
             let expected_next_time = last_open_time + interval_ms;
@@ -76,12 +188,14 @@ impl KlineContinuityDetector {
             
             // 检查是否有间隙 (根据时间间隔动态调整容忍度)
             let gap_ms = open_time - expected_next_time;
-            let tolerance_ms = self.get_tolerance_milliseconds(&interval);
+            let tolerance_ms = Self::tolerance_milliseconds(&interval);
             
             if gap_ms.abs() > tolerance_ms { // 超过容忍范围认为有间隙
+                let (missing_candles, severity) = self.classify(gap_ms, interval_ms);
                 result.has_gap = true;
                 result.gap_duration_ms = Some(gap_ms);
                 result.data_quality = DataQuality::Suspect; // gap后首次到达的数据标记为可疑
+                result.severity = Some(severity);
                 
                 // 输出结构化警告日志
                 let gap_type = if gap_ms > 0 { "延迟" } else { "提前" };
@@ -95,24 +209,57 @@ impl KlineContinuityDetector {
                     gap_duration_ms = %gap_ms,
                     gap_type = %gap_type,
                     tolerance_ms = %tolerance_ms,
+                    missing_candles = %missing_candles,
+                    severity = ?severity,
                     data_quality = %result.data_quality,
                     "K线连续性间隙检测"
                 );
-                
-                // 更新间隙统计
-                let mut stats = self.stats.write().await;
-                stats.gaps_detected += 1;
+
+                gap = Some(GapRecord {
+                    exchange: exchange.clone(),
+                    symbol: symbol.to_string(),
+                    interval: interval.clone(),
+                    expected_open_time: expected_next_time,
+                    actual_open_time: open_time,
+                    gap_duration_ms: gap_ms,
+                    missing_candles,
+                    severity,
+                    detected_at: chrono::Utc::now().timestamp_millis(),
+                });
             }
         }
 
         // 更新最后的 open_time
-        last_times.insert(key, open_time);
-        
+        let state = series.entry(key).or_insert_with(|| SeriesState {
+            exchange,
+            symbol: symbol.to_string(),
+            interval,
+            last_open_time: open_time,
+            checks: 0,
+            gaps: 0,
+        });
+        state.last_open_time = open_time;
+        state.checks += 1;
+        if gap.is_some() {
+            state.gaps += 1;
+        }
+        drop(series);
+
         // 更新检查统计
         let mut stats = self.stats.write().await;
         stats.total_checks += 1;
         stats.last_check_time = Some(chrono::Utc::now().timestamp_millis());
 
+        if let Some(gap) = gap {
+            // 更新间隙统计
+            stats.gaps_detected += 1;
+            let mut gaps = self.gaps.write().await;
+            gaps.push_back(gap);
+            while gaps.len() > self.config.gap_history_size.max(1) {
+                gaps.pop_front();
+            }
+        }
+
         result
     }
 
@@ -140,7 +287,7 @@ impl KlineContinuityDetector {
 
     /// 获取连续性检测的容忍度 (毫秒)
     /// 根据时间间隔动态调整，考虑网络延迟和服务器时钟偏差
-    fn get_tolerance_milliseconds(&self, interval: &Interval) -> i64 {
+    pub fn tolerance_milliseconds(interval: &Interval) -> i64 {
         match interval {
             // 秒级K线：允许2秒误差
             Interval::OneSecond => 2_000,
@@ -175,7 +322,95 @@ impl KlineContinuityDetector {
 
     /// 获取当前维护的交易对数量
     pub async fn get_tracked_pairs_count(&self) -> usize {
-        self.last_open_times.read().await.len()
+        self.series.read().await.len()
+    }
+
+    /// 最近的间隙，按检测时间从新到旧
+    pub async fn gaps(&self, filter: &GapFilter, limit: usize) -> Vec<GapRecord> {
+        self.gaps
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|gap| filter.matches(gap))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// 所有序列的检测状态
+    pub async fn series(&self) -> Vec<SeriesState> {
+        let mut series: Vec<SeriesState> = self.series.read().await.values().cloned().collect();
+        series.sort_by(|a, b| {
+            (a.exchange.as_str(), &a.symbol, a.interval.to_millis())
+                .cmp(&(b.exchange.as_str(), &b.symbol, b.interval.to_millis()))
+        });
+        series
+    }
+
+    /// 恢复保存的序列状态，已在检测的序列保留当前值
+    pub async fn restore(&self, saved: Vec<SeriesState>) -> usize {
+        let mut series = self.series.write().await;
+        let mut restored = 0;
+        for state in saved {
+            let key = series_key(&state.exchange, &state.symbol, &state.interval);
+            if !series.contains_key(&key) {
+                series.insert(key, state);
+                restored += 1;
+            }
+        }
+        restored
+    }
+
+    /// 检测事件流中的已收盘K线，定期保存序列状态
+    pub fn start<E, F>(
+        self: Arc<Self>,
+        mut events: broadcast::Receiver<E>,
+        closed_kline: F,
+        store: Arc<ContinuityStateStore>,
+    ) where
+        E: Clone + Send + 'static,
+        F: Fn(&E) -> Option<&Kline> + Send + 'static,
+    {
+        let persist_interval = Duration::from_secs(self.config.persist_interval_seconds.max(1));
+        tokio::spawn(async move {
+            let restored = self.restore(store.load().await).await;
+            if restored > 0 {
+                info!("Restored continuity state for {} kline series", restored);
+            }
+
+            let mut persist = tokio::time::interval(persist_interval);
+            loop {
+                tokio::select! {
+                    event = events.recv() => match event {
+                        Ok(event) => {
+                            if let Some(kline) = closed_kline(&event) {
+                                self.check_continuity(
+                                    kline.exchange.clone(),
+                                    &kline.symbol,
+                                    kline.interval.clone(),
+                                    kline.open_time.timestamp_millis(),
+                                )
+                                .await;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Kline continuity detector lagged, skipped {} events", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = persist.tick() => {
+                        if let Err(e) = store.save(&self.series().await).await {
+                            warn!("Failed to persist kline continuity state: {}", e);
+                        }
+                    }
+                }
+            }
+
+            if let Err(e) = store.save(&self.series().await).await {
+                warn!("Failed to persist kline continuity state: {}", e);
+            }
+        });
     }
 }
 
@@ -358,4 +593,46 @@ mod tests {
         assert_eq!(stats.gaps_detected, 0);
         assert!(stats.last_check_time.is_some());
     }
+
+    #[tokio::test]
+    async fn test_gap_severity_and_filter() {
+        let detector = KlineContinuityDetector::new();
+        let base = 1640995200000;
+
+        detector.check_continuity(Exchange::Binance, "BTCUSDT", Interval::OneMinute, base).await;
+        // 缺失3根，达到默认的 Major 阈值
+        let result = detector
+            .check_continuity(Exchange::Binance, "BTCUSDT", Interval::OneMinute, base + 4 * 60_000)
+            .await;
+        assert_eq!(result.severity, Some(GapSeverity::Major));
+
+        // 同一交易对的其他周期独立检测
+        detector.check_continuity(Exchange::Binance, "BTCUSDT", Interval::FiveMinutes, base).await;
+        let result = detector
+            .check_continuity(Exchange::Binance, "BTCUSDT", Interval::FiveMinutes, base + 300_000)
+            .await;
+        assert!(!result.has_gap);
+
+        // 重复推送不计入检测
+        detector
+            .check_continuity(Exchange::Binance, "BTCUSDT", Interval::FiveMinutes, base + 300_000)
+            .await;
+        assert_eq!(detector.get_stats().await.total_checks, 4);
+
+        let filter = GapFilter {
+            interval: Some("1m".to_string()),
+            min_severity: Some(GapSeverity::Major),
+            ..Default::default()
+        };
+        let gaps = detector.gaps(&filter, 10).await;
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].missing_candles, 3);
+
+        let filter = GapFilter {
+            min_severity: Some(GapSeverity::Critical),
+            ..Default::default()
+        };
+        assert!(detector.gaps(&filter, 10).await.is_empty());
+        assert_eq!(detector.series().await.len(), 2);
+    }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
-// Phase 1: 只保留必要的kline_detector模块
 pub mod kline_detector;
+pub mod store;
 
-pub use kline_detector::{ContinuityCheckResult, ContinuityStats, KlineContinuityDetector};
-
-// Phase 1: 最小实现，不包含复杂的管理器
+pub use kline_detector::{
+    ContinuityCheckResult, ContinuityStats, GapFilter, GapRecord, GapSeverity,
+    KlineContinuityDetector, SeriesState,
+};
+pub use store::ContinuityStateStore;
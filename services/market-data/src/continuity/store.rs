@@ -0,0 +1,87 @@
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use super::SeriesState;
+use crate::config::RedisConfig;
+
+/// 连续性序列状态存储
+///
+/// 将每个序列最后的 open_time 保存到Redis，重启后据此继续检测，
+/// 停机期间产生的间隙也能被发现。Redis不可用时仅保留在内存中。
+pub struct ContinuityStateStore {
+    key: String,
+    redis: Option<Arc<RwLock<ConnectionManager>>>,
+}
+
+impl ContinuityStateStore {
+    pub async fn new(redis_config: Option<&RedisConfig>) -> Self {
+        let key_prefix = redis_config
+            .map(|c| c.key_prefix.clone())
+            .unwrap_or_else(|| "market_data:".to_string());
+
+        let redis = match redis_config {
+            Some(redis_config) => match Self::connect(&redis_config.url).await {
+                Ok(conn) => Some(Arc::new(RwLock::new(conn))),
+                Err(e) => {
+                    warn!(
+                        "Continuity state will not persist, failed to connect to Redis: {}",
+                        e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Self {
+            key: format!("{}continuity:series", key_prefix),
+            redis,
+        }
+    }
+
+    async fn connect(url: &str) -> Result<ConnectionManager> {
+        let client = redis::Client::open(url)?;
+        Ok(ConnectionManager::new(client).await?)
+    }
+
+    /// 读取已保存的序列状态，失败时返回空
+    pub async fn load(&self) -> Vec<SeriesState> {
+        use redis::AsyncCommands;
+
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return Vec::new(),
+        };
+        let mut conn = redis.write().await;
+        match conn.get::<_, Option<String>>(&self.key).await {
+            Ok(Some(json)) => match serde_json::from_str(&json) {
+                Ok(series) => series,
+                Err(e) => {
+                    warn!("Ignoring invalid continuity state: {}", e);
+                    Vec::new()
+                }
+            },
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                warn!("Failed to load continuity state: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    pub async fn save(&self, series: &[SeriesState]) -> Result<()> {
+        use redis::AsyncCommands;
+
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return Ok(()),
+        };
+        let json = serde_json::to_string(series)?;
+        let mut conn = redis.write().await;
+        conn.set::<_, _, ()>(&self.key, json).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+pub mod recorder;
+pub mod sql;
+
+pub use recorder::{
+    compress_series, DepthHistoryRecorder, HeatmapFrame, HeatmapLevel, Reconstruction,
+};
@@ -0,0 +1,702 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared_models::market::{OrderBook, OrderBookLevel};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+use super::sql;
+use crate::config::{ClickHouseConfig, DepthHistoryConfig};
+use crate::connectors::MarketDataEvent;
+
+/// 快照表中的一行，价格和数量按档位顺序保存为字符串以保留精度
+#[derive(Debug, Clone, PartialEq, clickhouse::Row, Serialize, Deserialize)]
+pub struct SnapshotRow {
+    pub exchange: String,
+    pub symbol: String,
+    /// 毫秒时间戳
+    pub timestamp: i64,
+    pub last_update_id: u64,
+    pub bid_prices: Vec<String>,
+    pub bid_quantities: Vec<String>,
+    pub ask_prices: Vec<String>,
+    pub ask_quantities: Vec<String>,
+}
+
+impl SnapshotRow {
+    /// 从订单簿截取前 depth 档
+    pub fn from_orderbook(book: &OrderBook, depth: usize) -> Self {
+        let side = |levels: &[OrderBookLevel]| -> (Vec<String>, Vec<String>) {
+            levels
+                .iter()
+                .take(depth)
+                .map(|level| (level.price.to_string(), level.quantity.to_string()))
+                .unzip()
+        };
+        let (bid_prices, bid_quantities) = side(&book.bids);
+        let (ask_prices, ask_quantities) = side(&book.asks);
+
+        Self {
+            exchange: book.exchange.as_str().to_string(),
+            symbol: book.symbol.to_uppercase(),
+            timestamp: book.timestamp.timestamp_millis(),
+            last_update_id: book.last_update_id,
+            bid_prices,
+            bid_quantities,
+            ask_prices,
+            ask_quantities,
+        }
+    }
+}
+
+/// 热力图中的一个价位
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeatmapLevel {
+    pub price: Decimal,
+    /// 增量帧中为0表示该价位已移除
+    pub quantity: Decimal,
+}
+
+/// 压缩后的快照序列中的一帧
+///
+/// 关键帧包含完整深度；增量帧只包含相对上一帧数量变化、新增或移除的价位。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeatmapFrame {
+    pub timestamp: i64,
+    pub keyframe: bool,
+    pub last_update_id: u64,
+    pub bids: Vec<HeatmapLevel>,
+    pub asks: Vec<HeatmapLevel>,
+}
+
+fn parse_side(prices: &[String], quantities: &[String]) -> BTreeMap<Decimal, Decimal> {
+    prices
+        .iter()
+        .zip(quantities)
+        .filter_map(|(price, quantity)| {
+            Some((Decimal::from_str(price).ok()?, Decimal::from_str(quantity).ok()?))
+        })
+        .collect()
+}
+
+/// 相对上一帧的变化，移除的价位数量记为0
+fn side_delta(
+    previous: &BTreeMap<Decimal, Decimal>,
+    current: &BTreeMap<Decimal, Decimal>,
+) -> BTreeMap<Decimal, Decimal> {
+    let mut delta: BTreeMap<Decimal, Decimal> = current
+        .iter()
+        .filter(|(price, quantity)| previous.get(*price) != Some(*quantity))
+        .map(|(price, quantity)| (*price, *quantity))
+        .collect();
+    for price in previous.keys() {
+        if !current.contains_key(price) {
+            delta.insert(*price, Decimal::ZERO);
+        }
+    }
+    delta
+}
+
+/// 买盘按价格降序、卖盘按价格升序输出
+fn to_levels(side: &BTreeMap<Decimal, Decimal>, descending: bool) -> Vec<HeatmapLevel> {
+    let levels = side.iter().map(|(price, quantity)| HeatmapLevel {
+        price: *price,
+        quantity: *quantity,
+    });
+    if descending {
+        levels.rev().collect()
+    } else {
+        levels.collect()
+    }
+}
+
+/// 把按时间升序的快照压缩为关键帧加增量帧的序列
+///
+/// 每 keyframe_interval 帧输出一个关键帧，客户端从任一关键帧开始即可按顺序回放。
+pub fn compress_series(rows: &[SnapshotRow], keyframe_interval: usize) -> Vec<HeatmapFrame> {
+    let keyframe_interval = keyframe_interval.max(1);
+    let mut frames = Vec::with_capacity(rows.len());
+    let mut previous: Option<(BTreeMap<Decimal, Decimal>, BTreeMap<Decimal, Decimal>)> = None;
+
+    for (index, row) in rows.iter().enumerate() {
+        let bids = parse_side(&row.bid_prices, &row.bid_quantities);
+        let asks = parse_side(&row.ask_prices, &row.ask_quantities);
+        let keyframe = index % keyframe_interval == 0;
+
+        let (frame_bids, frame_asks) = match (&previous, keyframe) {
+            (Some((prev_bids, prev_asks)), false) => {
+                (side_delta(prev_bids, &bids), side_delta(prev_asks, &asks))
+            }
+            _ => (bids.clone(), asks.clone()),
+        };
+
+        frames.push(HeatmapFrame {
+            timestamp: row.timestamp,
+            keyframe,
+            last_update_id: row.last_update_id,
+            bids: to_levels(&frame_bids, true),
+            asks: to_levels(&frame_asks, false),
+        });
+        previous = Some((bids, asks));
+    }
+
+    frames
+}
+
+/// 从增量表重建出的某一时刻订单簿
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReconstructedBook {
+    /// 作为起点的快照时间
+    pub snapshot_timestamp: i64,
+    /// 最后应用的增量时间，未应用增量时等于快照时间
+    pub timestamp: i64,
+    pub last_update_id: u64,
+    pub diffs_applied: usize,
+    pub bids: Vec<HeatmapLevel>,
+    pub asks: Vec<HeatmapLevel>,
+}
+
+/// 重建结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reconstruction {
+    Book(ReconstructedBook),
+    /// 查找范围内没有快照
+    NoSnapshot,
+    /// 需要应用的增量超过上限
+    TooManyDiffs(usize),
+}
+
+fn apply_side(side: &mut BTreeMap<Decimal, Decimal>, delta: BTreeMap<Decimal, Decimal>) {
+    for (price, quantity) in delta {
+        if quantity.is_zero() {
+            side.remove(&price);
+        } else {
+            side.insert(price, quantity);
+        }
+    }
+}
+
+/// 在快照上按顺序应用增量，早于或等于快照的增量会被跳过
+pub fn reconstruct(snapshot: &SnapshotRow, diffs: &[SnapshotRow]) -> ReconstructedBook {
+    let mut bids = parse_side(&snapshot.bid_prices, &snapshot.bid_quantities);
+    let mut asks = parse_side(&snapshot.ask_prices, &snapshot.ask_quantities);
+    let mut timestamp = snapshot.timestamp;
+    let mut last_update_id = snapshot.last_update_id;
+    let mut diffs_applied = 0;
+
+    for diff in diffs {
+        if (diff.timestamp, diff.last_update_id) <= (snapshot.timestamp, snapshot.last_update_id) {
+            continue;
+        }
+        apply_side(&mut bids, parse_side(&diff.bid_prices, &diff.bid_quantities));
+        apply_side(&mut asks, parse_side(&diff.ask_prices, &diff.ask_quantities));
+        timestamp = diff.timestamp;
+        last_update_id = diff.last_update_id;
+        diffs_applied += 1;
+    }
+
+    ReconstructedBook {
+        snapshot_timestamp: snapshot.timestamp,
+        timestamp,
+        last_update_id,
+        diffs_applied,
+        bids: to_levels(&bids, true),
+        asks: to_levels(&asks, false),
+    }
+}
+
+type SideMaps = (BTreeMap<Decimal, Decimal>, BTreeMap<Decimal, Decimal>);
+
+/// 订单簿深度快照记录器
+///
+/// 按配置的节奏对每个交易对采样完整深度，批量写入ClickHouse供热力图回放
+/// 和排队位置研究使用。分片部署时每个实例只会收到自己负责的交易对，因此不需要领导者选举。
+#[derive(Clone)]
+pub struct DepthHistoryRecorder {
+    config: DepthHistoryConfig,
+    database: String,
+    client: Option<clickhouse::Client>,
+    last_sampled: Arc<RwLock<HashMap<String, i64>>>,
+    buffer: Arc<Mutex<Vec<SnapshotRow>>>,
+    /// 每个交易对上一次的截断深度，用于计算增量
+    diff_state: Arc<Mutex<HashMap<String, SideMaps>>>,
+    diff_buffer: Arc<Mutex<Vec<SnapshotRow>>>,
+}
+
+impl DepthHistoryRecorder {
+    pub fn new(config: DepthHistoryConfig, clickhouse_config: Option<&ClickHouseConfig>) -> Self {
+        let client = clickhouse_config.filter(|_| config.enabled).map(|c| {
+            clickhouse::Client::default()
+                .with_url(&c.url)
+                .with_database(&c.database)
+                .with_user(&c.username)
+                .with_password(&c.password)
+        });
+
+        Self {
+            config,
+            database: clickhouse_config
+                .map(|c| c.database.clone())
+                .unwrap_or_else(|| "market_data".to_string()),
+            client,
+            last_sampled: Arc::new(RwLock::new(HashMap::new())),
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            diff_state: Arc::new(Mutex::new(HashMap::new())),
+            diff_buffer: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.client.is_some()
+    }
+
+    pub fn config(&self) -> &DepthHistoryConfig {
+        &self.config
+    }
+
+    fn client(&self) -> Result<&clickhouse::Client> {
+        self.client
+            .as_ref()
+            .ok_or_else(|| anyhow!("Order book depth history is disabled"))
+    }
+
+    fn key(exchange: &str, symbol: &str) -> String {
+        format!("{}:{}", exchange, symbol.to_uppercase())
+    }
+
+    /// 创建快照表（开启增量记录时同时创建增量表）并同步保留期
+    pub async fn ensure_table(&self) -> Result<()> {
+        let client = self.client()?;
+        let mut tables = vec![&self.config.table];
+        if self.config.record_diffs {
+            tables.push(&self.config.diffs_table);
+        }
+        for table in tables {
+            client
+                .query(&sql::create_snapshots_table(
+                    &self.database,
+                    table,
+                    self.config.retention_days,
+                ))
+                .execute()
+                .await?;
+            client
+                .query(&sql::modify_retention(
+                    &self.database,
+                    table,
+                    self.config.retention_days,
+                ))
+                .execute()
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 计算相对上一次订单簿的增量，首次收到的订单簿只记录状态
+    async fn record_diff(&self, key: &str, book: &OrderBook) -> bool {
+        let current = SnapshotRow::from_orderbook(book, self.config.depth);
+        let bids = parse_side(&current.bid_prices, &current.bid_quantities);
+        let asks = parse_side(&current.ask_prices, &current.ask_quantities);
+
+        let previous = self
+            .diff_state
+            .lock()
+            .await
+            .insert(key.to_string(), (bids.clone(), asks.clone()));
+        let Some((prev_bids, prev_asks)) = previous else {
+            return false;
+        };
+
+        let bid_delta = to_levels(&side_delta(&prev_bids, &bids), true);
+        let ask_delta = to_levels(&side_delta(&prev_asks, &asks), false);
+        if bid_delta.is_empty() && ask_delta.is_empty() {
+            return false;
+        }
+
+        let strings = |levels: Vec<HeatmapLevel>| -> (Vec<String>, Vec<String>) {
+            levels
+                .into_iter()
+                .map(|level| (level.price.to_string(), level.quantity.to_string()))
+                .unzip()
+        };
+        let (bid_prices, bid_quantities) = strings(bid_delta);
+        let (ask_prices, ask_quantities) = strings(ask_delta);
+        let mut diffs = self.diff_buffer.lock().await;
+        diffs.push(SnapshotRow {
+            bid_prices,
+            bid_quantities,
+            ask_prices,
+            ask_quantities,
+            ..current
+        });
+        diffs.len() >= self.config.flush_batch_size
+    }
+
+    /// 按采样间隔缓冲订单簿快照，返回缓冲是否已达到批量写入大小
+    pub async fn on_orderbook(&self, book: &OrderBook) -> bool {
+        let key = Self::key(book.exchange.as_str(), &book.symbol);
+        let timestamp = book.timestamp.timestamp_millis();
+        let diffs_full = self.config.record_diffs && self.record_diff(&key, book).await;
+
+        {
+            let mut last_sampled = self.last_sampled.write().await;
+            if let Some(previous) = last_sampled.get(&key) {
+                let elapsed = timestamp - previous;
+                if elapsed >= 0 && (elapsed as u64) < self.config.snapshot_interval_ms {
+                    return diffs_full;
+                }
+            }
+            last_sampled.insert(key, timestamp);
+        }
+
+        let row = SnapshotRow::from_orderbook(book, self.config.depth);
+        let mut buffer = self.buffer.lock().await;
+        buffer.push(row);
+        diffs_full || buffer.len() >= self.config.flush_batch_size
+    }
+
+    async fn write_rows(&self, table: &str, rows: &[SnapshotRow]) -> Result<()> {
+        let mut insert = self.client()?.insert::<SnapshotRow>(table)?;
+        for row in rows {
+            insert.write(row).await?;
+        }
+        insert.end().await?;
+        Ok(())
+    }
+
+    /// 写入缓冲的快照和增量，写入失败的批次丢弃，避免内存无限增长
+    pub async fn flush(&self) -> Result<usize> {
+        let rows = std::mem::take(&mut *self.buffer.lock().await);
+        let diffs = std::mem::take(&mut *self.diff_buffer.lock().await);
+
+        if !rows.is_empty() {
+            self.write_rows(&self.config.table, &rows).await?;
+        }
+        if !diffs.is_empty() {
+            self.write_rows(&self.config.diffs_table, &diffs).await?;
+        }
+        Ok(rows.len() + diffs.len())
+    }
+
+    async fn flush_logged(&self) {
+        match self.flush().await {
+            Ok(0) => {}
+            Ok(count) => debug!("Recorded {} order book snapshots and diffs", count),
+            Err(e) => warn!("Failed to record order book snapshots: {}", e),
+        }
+    }
+
+    /// 启动采样和定时写入任务
+    pub fn start(&self, mut events: broadcast::Receiver<MarketDataEvent>) {
+        if self.client.is_none() {
+            warn!("Order book depth history disabled: ClickHouse is not configured");
+            return;
+        }
+
+        let recorder = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = recorder.ensure_table().await {
+                warn!("Failed to prepare order book snapshot table: {}", e);
+            }
+            info!(
+                "Order book depth history recording {} levels every {}ms (retention {} days)",
+                recorder.config.depth, recorder.config.snapshot_interval_ms, recorder.config.retention_days
+            );
+
+            let mut ticker = tokio::time::interval(std::time::Duration::from_millis(
+                recorder.config.flush_interval_ms.max(100),
+            ));
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => recorder.flush_logged().await,
+                    event = events.recv() => match event {
+                        Ok(MarketDataEvent::OrderBook(book)) => {
+                            if recorder.on_orderbook(&book).await {
+                                recorder.flush_logged().await;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Depth history recorder lagged, skipped {} events", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                }
+            }
+            recorder.flush_logged().await;
+            warn!("Depth history recorder stopped");
+        });
+    }
+
+    /// 查询时间范围内的快照，按时间升序
+    pub async fn query(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        resolution_ms: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<SnapshotRow>> {
+        Ok(self
+            .client()?
+            .query(&sql::query_snapshots(
+                &self.database,
+                &self.config.table,
+                exchange,
+                &symbol.to_uppercase(),
+                start,
+                end,
+                resolution_ms,
+                limit,
+            ))
+            .fetch_all::<SnapshotRow>()
+            .await?)
+    }
+
+    /// 重建 at 时刻的订单簿
+    ///
+    /// 从 max_seek_ms 范围内最近的快照开始应用增量，增量数超过上限时放弃重建，
+    /// 保证单次查询的代价有界。
+    pub async fn reconstruct_at(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Reconstruction> {
+        let client = self.client()?;
+        let symbol = symbol.to_uppercase();
+        let earliest = at - chrono::Duration::milliseconds(self.config.max_seek_ms as i64);
+
+        let snapshot = client
+            .query(&sql::latest_snapshot_before(
+                &self.database,
+                &self.config.table,
+                exchange,
+                &symbol,
+                at,
+                earliest,
+            ))
+            .fetch_optional::<SnapshotRow>()
+            .await?;
+        let Some(snapshot) = snapshot else {
+            return Ok(Reconstruction::NoSnapshot);
+        };
+
+        // 与快照同一毫秒的增量也要取出，由 reconstruct 按更新ID过滤；多取一条用于判断超限
+        let limit = self.config.max_reconstruction_diffs;
+        let diffs = client
+            .query(&sql::query_diffs(
+                &self.database,
+                &self.config.diffs_table,
+                exchange,
+                &symbol,
+                DateTime::<Utc>::from_timestamp_millis(snapshot.timestamp).unwrap_or(earliest),
+                at,
+                limit + 1,
+            ))
+            .fetch_all::<SnapshotRow>()
+            .await?;
+        if diffs.len() > limit {
+            return Ok(Reconstruction::TooManyDiffs(limit));
+        }
+
+        Ok(Reconstruction::Book(reconstruct(&snapshot, &diffs)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_models::common::Exchange;
+
+    fn row(timestamp: i64, bids: &[(i64, i64)], asks: &[(i64, i64)]) -> SnapshotRow {
+        let strings = |levels: &[(i64, i64)]| -> (Vec<String>, Vec<String>) {
+            levels
+                .iter()
+                .map(|(price, quantity)| (price.to_string(), quantity.to_string()))
+                .unzip()
+        };
+        let (bid_prices, bid_quantities) = strings(bids);
+        let (ask_prices, ask_quantities) = strings(asks);
+        SnapshotRow {
+            exchange: "binance".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            timestamp,
+            last_update_id: timestamp as u64,
+            bid_prices,
+            bid_quantities,
+            ask_prices,
+            ask_quantities,
+        }
+    }
+
+    /// 按顺序回放压缩帧，还原每一帧的完整深度
+    fn replay(frames: &[HeatmapFrame]) -> Vec<(BTreeMap<Decimal, Decimal>, BTreeMap<Decimal, Decimal>)> {
+        let mut bids = BTreeMap::new();
+        let mut asks = BTreeMap::new();
+        let apply = |side: &mut BTreeMap<Decimal, Decimal>, levels: &[HeatmapLevel], keyframe: bool| {
+            if keyframe {
+                side.clear();
+            }
+            for level in levels {
+                if level.quantity.is_zero() {
+                    side.remove(&level.price);
+                } else {
+                    side.insert(level.price, level.quantity);
+                }
+            }
+        };
+
+        frames
+            .iter()
+            .map(|frame| {
+                apply(&mut bids, &frame.bids, frame.keyframe);
+                apply(&mut asks, &frame.asks, frame.keyframe);
+                (bids.clone(), asks.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compress_series_round_trip() {
+        let rows = vec![
+            row(1_000, &[(100, 5), (99, 3)], &[(101, 2), (102, 4)]),
+            row(2_000, &[(100, 6), (99, 3)], &[(101, 2), (103, 1)]),
+            row(3_000, &[(100, 6)], &[(101, 2), (103, 1)]),
+            row(4_000, &[(100, 1), (98, 7)], &[(102, 9)]),
+        ];
+        let frames = compress_series(&rows, 3);
+
+        assert!(frames[0].keyframe && !frames[1].keyframe && frames[3].keyframe);
+        // 第二帧只包含变化的价位：买一数量变化，卖出102移除、103新增
+        assert_eq!(frames[1].bids, vec![HeatmapLevel { price: Decimal::from(100), quantity: Decimal::from(6) }]);
+        assert_eq!(
+            frames[1].asks,
+            vec![
+                HeatmapLevel { price: Decimal::from(102), quantity: Decimal::ZERO },
+                HeatmapLevel { price: Decimal::from(103), quantity: Decimal::ONE },
+            ]
+        );
+        assert!(frames[2].asks.is_empty());
+
+        for (restored, original) in replay(&frames).iter().zip(&rows) {
+            assert_eq!(restored.0, parse_side(&original.bid_prices, &original.bid_quantities));
+            assert_eq!(restored.1, parse_side(&original.ask_prices, &original.ask_quantities));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_and_depth() {
+        let config = DepthHistoryConfig {
+            depth: 1,
+            flush_batch_size: 2,
+            ..Default::default()
+        };
+        let recorder = DepthHistoryRecorder::new(config, None);
+        let level = |price: i64| OrderBookLevel {
+            price: Decimal::from(price),
+            quantity: Decimal::ONE,
+        };
+        let mut book = OrderBook {
+            exchange: Exchange::Binance,
+            symbol: "btcusdt".to_string(),
+            timestamp: Utc::now(),
+            last_update_id: 1,
+            bids: vec![level(99), level(98)],
+            asks: vec![level(101), level(102)],
+        };
+
+        assert!(!recorder.on_orderbook(&book).await);
+        book.timestamp += chrono::Duration::milliseconds(500);
+        assert!(!recorder.on_orderbook(&book).await);
+        book.timestamp += chrono::Duration::milliseconds(500);
+        assert!(recorder.on_orderbook(&book).await);
+
+        let buffer = recorder.buffer.lock().await;
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer[0].symbol, "BTCUSDT");
+        assert_eq!(buffer[0].bid_prices, vec!["99".to_string()]);
+        assert_eq!(buffer[0].ask_prices, vec!["101".to_string()]);
+    }
+
+    #[test]
+    fn test_reconstruct_applies_diffs_after_snapshot() {
+        let snapshot = row(1_000, &[(100, 5), (99, 3)], &[(101, 2), (102, 4)]);
+        let diffs = vec![
+            // 与快照相同的更新不应重复应用
+            row(1_000, &[(100, 7)], &[]),
+            row(1_500, &[(100, 6), (98, 1)], &[(102, 0)]),
+            row(2_000, &[(99, 0)], &[(103, 8)]),
+        ];
+
+        let book = reconstruct(&snapshot, &diffs);
+        assert_eq!(book.snapshot_timestamp, 1_000);
+        assert_eq!(book.timestamp, 2_000);
+        assert_eq!(book.diffs_applied, 2);
+        assert_eq!(
+            book.bids,
+            vec![
+                HeatmapLevel { price: Decimal::from(100), quantity: Decimal::from(6) },
+                HeatmapLevel { price: Decimal::from(98), quantity: Decimal::ONE },
+            ]
+        );
+        assert_eq!(
+            book.asks,
+            vec![
+                HeatmapLevel { price: Decimal::from(101), quantity: Decimal::from(2) },
+                HeatmapLevel { price: Decimal::from(103), quantity: Decimal::from(8) },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recorded_diffs_rebuild_book() {
+        let config = DepthHistoryConfig {
+            depth: 2,
+            record_diffs: true,
+            ..Default::default()
+        };
+        let recorder = DepthHistoryRecorder::new(config, None);
+        let level = |price: i64, quantity: i64| OrderBookLevel {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        };
+        let start = Utc::now();
+        let books: Vec<OrderBook> = [
+            (vec![level(99, 1), level(98, 2)], vec![level(101, 1), level(102, 2)]),
+            (vec![level(99, 3), level(98, 2)], vec![level(101, 1), level(102, 2)]),
+            (vec![level(99, 3), level(97, 5)], vec![level(100, 4), level(101, 1), level(102, 2)]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, (bids, asks))| OrderBook {
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            timestamp: start + chrono::Duration::milliseconds(100 * index as i64),
+            last_update_id: index as u64 + 1,
+            bids,
+            asks,
+        })
+        .collect();
+        for book in &books {
+            recorder.on_orderbook(book).await;
+        }
+
+        let snapshots = recorder.buffer.lock().await;
+        let diffs = recorder.diff_buffer.lock().await;
+        // 采样间隔内只有第一个快照，之后的变化全部来自增量
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(diffs.len(), 2);
+
+        let rebuilt = reconstruct(&snapshots[0], &diffs);
+        let expected = SnapshotRow::from_orderbook(&books[2], 2);
+        assert_eq!(rebuilt.last_update_id, 3);
+        assert_eq!(rebuilt.bids, to_levels(&parse_side(&expected.bid_prices, &expected.bid_quantities), true));
+        assert_eq!(rebuilt.asks, to_levels(&parse_side(&expected.ask_prices, &expected.ask_quantities), false));
+    }
+}
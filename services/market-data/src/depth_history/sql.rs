@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+
+use crate::compaction::sql::{datetime_literal, quote};
+
+/// 订单簿快照表结构
+///
+/// 每侧价格和数量按档位顺序存为数组，使用ZSTD压缩；
+/// 保留期由表级TTL控制，与Tick数据表互不影响。
+pub fn create_snapshots_table(database: &str, table: &str, retention_days: u32) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {database}.{table} (
+    exchange LowCardinality(String),
+    symbol LowCardinality(String),
+    timestamp DateTime64(3, 'UTC') CODEC(Delta, ZSTD),
+    last_update_id UInt64 CODEC(Delta, ZSTD),
+    bid_prices Array(String) CODEC(ZSTD(3)),
+    bid_quantities Array(String) CODEC(ZSTD(3)),
+    ask_prices Array(String) CODEC(ZSTD(3)),
+    ask_quantities Array(String) CODEC(ZSTD(3))
+) ENGINE = MergeTree
+PARTITION BY toYYYYMMDD(timestamp)
+ORDER BY (exchange, symbol, timestamp)
+TTL toDateTime(timestamp) + INTERVAL {retention_days} DAY"
+    )
+}
+
+/// 按当前配置更新保留期，已有表调整保留天数后生效
+pub fn modify_retention(database: &str, table: &str, retention_days: u32) -> String {
+    format!(
+        "ALTER TABLE {database}.{table} MODIFY TTL toDateTime(timestamp) + INTERVAL {retention_days} DAY"
+    )
+}
+
+/// 查询时间范围内的快照
+///
+/// 指定 resolution_ms 时每个时间桶只取第一个快照，用于长时间范围的降采样。
+#[allow(clippy::too_many_arguments)]
+pub fn query_snapshots(
+    database: &str,
+    table: &str,
+    exchange: &str,
+    symbol: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    resolution_ms: Option<u64>,
+    limit: usize,
+) -> String {
+    let sample = match resolution_ms {
+        Some(resolution) if resolution > 0 => format!(
+            "\nLIMIT 1 BY intDiv(toUnixTimestamp64Milli(timestamp), {})",
+            resolution
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        "SELECT
+    exchange,
+    symbol,
+    timestamp,
+    last_update_id,
+    bid_prices,
+    bid_quantities,
+    ask_prices,
+    ask_quantities
+FROM {database}.{table}
+WHERE exchange = {exchange} AND symbol = {symbol}
+  AND timestamp >= {start} AND timestamp < {end}
+ORDER BY timestamp{sample}
+LIMIT {limit}",
+        exchange = quote(exchange),
+        symbol = quote(symbol),
+        start = datetime_literal(start),
+        end = datetime_literal(end),
+    )
+}
+
+/// 查询不晚于 at 的最近一个快照，只在 [earliest, at] 内查找以限制扫描范围
+pub fn latest_snapshot_before(
+    database: &str,
+    table: &str,
+    exchange: &str,
+    symbol: &str,
+    at: DateTime<Utc>,
+    earliest: DateTime<Utc>,
+) -> String {
+    format!(
+        "SELECT
+    exchange,
+    symbol,
+    timestamp,
+    last_update_id,
+    bid_prices,
+    bid_quantities,
+    ask_prices,
+    ask_quantities
+FROM {database}.{table}
+WHERE exchange = {exchange} AND symbol = {symbol}
+  AND timestamp >= {earliest} AND timestamp <= {at}
+ORDER BY timestamp DESC, last_update_id DESC
+LIMIT 1",
+        exchange = quote(exchange),
+        symbol = quote(symbol),
+        earliest = datetime_literal(earliest),
+        at = datetime_literal(at),
+    )
+}
+
+/// 查询 [after, at] 内的订单簿增量，按时间和更新ID升序用于顺序回放
+pub fn query_diffs(
+    database: &str,
+    table: &str,
+    exchange: &str,
+    symbol: &str,
+    after: DateTime<Utc>,
+    at: DateTime<Utc>,
+    limit: usize,
+) -> String {
+    format!(
+        "SELECT
+    exchange,
+    symbol,
+    timestamp,
+    last_update_id,
+    bid_prices,
+    bid_quantities,
+    ask_prices,
+    ask_quantities
+FROM {database}.{table}
+WHERE exchange = {exchange} AND symbol = {symbol}
+  AND timestamp >= {after} AND timestamp <= {at}
+ORDER BY timestamp, last_update_id
+LIMIT {limit}",
+        exchange = quote(exchange),
+        symbol = quote(symbol),
+        after = datetime_literal(after),
+        at = datetime_literal(at),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_query_snapshots_sampling() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 1, 1, 0, 0).unwrap();
+
+        let raw = query_snapshots("md", "orderbook_snapshots", "binance", "BTCUSDT", start, end, None, 100);
+        assert!(raw.contains("symbol = 'BTCUSDT'"));
+        assert!(!raw.contains("LIMIT 1 BY"));
+        assert!(raw.ends_with("LIMIT 100"));
+
+        let sampled =
+            query_snapshots("md", "orderbook_snapshots", "binance", "BTCUSDT", start, end, Some(60_000), 100);
+        assert!(sampled.contains("LIMIT 1 BY intDiv(toUnixTimestamp64Milli(timestamp), 60000)"));
+    }
+
+    #[test]
+    fn test_reconstruction_queries() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 10, 0).unwrap();
+        let earliest = at - chrono::Duration::minutes(1);
+
+        let snapshot = latest_snapshot_before("md", "orderbook_snapshots", "binance", "BTCUSDT", at, earliest);
+        assert!(snapshot.contains("timestamp <= toDateTime64"));
+        assert!(snapshot.contains("ORDER BY timestamp DESC, last_update_id DESC"));
+        assert!(snapshot.ends_with("LIMIT 1"));
+
+        let diffs = query_diffs("md", "orderbook_diffs", "binance", "BTCUSDT", earliest, at, 501);
+        assert!(diffs.contains("FROM md.orderbook_diffs"));
+        assert!(diffs.contains("ORDER BY timestamp, last_update_id"));
+        assert!(diffs.ends_with("LIMIT 501"));
+    }
+}
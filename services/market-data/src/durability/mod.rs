@@ -0,0 +1,4 @@
+pub mod sql;
+pub mod writer;
+
+pub use writer::{ClickHouseSink, TieredWriter, WriteSink};
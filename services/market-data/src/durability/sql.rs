@@ -0,0 +1,29 @@
+/// 以 JSONEachRow 格式插入整行JSON
+///
+/// 行数据直接附在语句后发送。客户端会把 `?` 当作绑定参数，数据中的 `?` 需转义为 `??`。
+pub fn insert_json_rows(database: &str, table: &str, rows: &[&str]) -> String {
+    let mut statement = format!("INSERT INTO {database}.{table} FORMAT JSONEachRow\n");
+    for row in rows {
+        statement.push_str(&row.replace('?', "??"));
+        statement.push('\n');
+    }
+    statement
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_json_rows() {
+        let statement = insert_json_rows(
+            "md",
+            "market_trades",
+            &[r#"{"trade_id":"1","note":"why?"}"#, r#"{"trade_id":"2"}"#],
+        );
+        assert_eq!(
+            statement,
+            "INSERT INTO md.market_trades FORMAT JSONEachRow\n{\"trade_id\":\"1\",\"note\":\"why??\"}\n{\"trade_id\":\"2\"}\n"
+        );
+    }
+}
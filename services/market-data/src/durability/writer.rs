@@ -0,0 +1,500 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use shared_utils::AppMetrics;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+
+use super::sql;
+use crate::config::{ClickHouseConfig, StorageWriteConfig, WriteClass};
+use crate::publishing::spill::{SpillQueue, SpilledRecord};
+
+#[derive(Default)]
+struct ClassCounters {
+    accepted: AtomicU64,
+    persisted: AtomicU64,
+    retried: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl ClassCounters {
+    fn counter(&self, outcome: &str) -> &AtomicU64 {
+        match outcome {
+            "accepted" => &self.accepted,
+            "persisted" => &self.persisted,
+            "retried" => &self.retried,
+            "dropped" => &self.dropped,
+            _ => &self.failed,
+        }
+    }
+
+    fn stats(&self) -> ClassStats {
+        ClassStats {
+            accepted: self.accepted.load(Ordering::Relaxed),
+            persisted: self.persisted.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 单个可靠性等级的写入统计（条）
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassStats {
+    /// 已确认给调用方
+    pub accepted: u64,
+    /// 已写入ClickHouse
+    pub persisted: u64,
+    /// 从预写日志补写失败、等待重试
+    pub retried: u64,
+    /// 写入失败且不会重试
+    pub failed: u64,
+    /// 队列写满被拒绝
+    pub dropped: u64,
+}
+
+/// 分级写入统计
+#[derive(Debug, Clone, Serialize)]
+pub struct TieredWriterStats {
+    pub durable: ClassStats,
+    pub buffered: ClassStats,
+    /// 预写日志中尚未写入ClickHouse的条数
+    pub wal_pending: u64,
+    /// 预写日志占用字节数
+    pub wal_bytes: u64,
+    pub last_error: Option<String>,
+}
+
+impl TieredWriterStats {
+    /// 最近一次写入ClickHouse是否失败
+    pub fn is_degraded(&self) -> bool {
+        self.last_error.is_some()
+    }
+}
+
+/// 分级写入的落库端
+#[async_trait]
+pub trait WriteSink: Send + Sync {
+    /// 按顺序写入同一数据类型、同一目标表的JSON行
+    async fn write_rows(&self, data_type: &str, table: &str, rows: &[&str]) -> Result<()>;
+}
+
+/// 以 JSONEachRow 格式写入ClickHouse表，行的字段与表的列一致
+pub struct ClickHouseSink {
+    client: clickhouse::Client,
+    database: String,
+}
+
+impl ClickHouseSink {
+    pub fn new(config: &ClickHouseConfig) -> Self {
+        let client = clickhouse::Client::default()
+            .with_url(&config.url)
+            .with_database(&config.database)
+            .with_user(&config.username)
+            .with_password(&config.password);
+        Self {
+            client,
+            database: config.database.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl WriteSink for ClickHouseSink {
+    async fn write_rows(&self, _data_type: &str, table: &str, rows: &[&str]) -> Result<()> {
+        self.client
+            .query(&sql::insert_json_rows(&self.database, table, rows))
+            .execute()
+            .await?;
+        Ok(())
+    }
+}
+
+/// 分级存储写入
+///
+/// 按数据类型选择可靠性等级：持久化写入先追加到预写日志并刷盘再确认，由后台任务
+/// 按顺序补写到ClickHouse，写入成功后才提交日志位置，进程重启后从未提交处继续，
+/// 至少写入一次；异步写入进入有界内存队列后立即返回，后台按批写入，失败不重试。
+/// 预写日志目录不可用时持久化写入退化为同步写入ClickHouse后再确认。
+///
+/// 数据类型默认以 `table` 为目标表写入ClickHouse，行情存储通过 [`TieredWriter::route`]
+/// 把Tick、K线和订单簿快照交给配置的存储后端。
+pub struct TieredWriter {
+    config: StorageWriteConfig,
+    default_sink: Option<Arc<dyn WriteSink>>,
+    sinks: RwLock<HashMap<String, Arc<dyn WriteSink>>>,
+    metrics: Arc<AppMetrics>,
+    wal: Mutex<Option<SpillQueue>>,
+    wal_notify: Notify,
+    queue: mpsc::Sender<SpilledRecord>,
+    queue_rx: Mutex<Option<mpsc::Receiver<SpilledRecord>>>,
+    durable: ClassCounters,
+    buffered: ClassCounters,
+    last_error: Mutex<Option<String>>,
+}
+
+impl TieredWriter {
+    pub fn new(
+        config: StorageWriteConfig,
+        clickhouse_config: Option<&ClickHouseConfig>,
+        metrics: Arc<AppMetrics>,
+    ) -> Self {
+        let default_sink = clickhouse_config.map(|c| Arc::new(ClickHouseSink::new(c)) as Arc<dyn WriteSink>);
+
+        let wal = match Self::open_wal(&config) {
+            Ok(wal) => {
+                if wal.unread() > 0 {
+                    info!("Storage WAL has {} pending writes from previous run", wal.unread());
+                }
+                Some(wal)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to open storage WAL dir {}, durable writes go straight to ClickHouse: {}",
+                    config.wal_dir, e
+                );
+                None
+            }
+        };
+        let (queue, queue_rx) = mpsc::channel(config.queue_capacity.max(1));
+
+        Self {
+            default_sink,
+            sinks: RwLock::new(HashMap::new()),
+            metrics,
+            wal: Mutex::new(wal),
+            wal_notify: Notify::new(),
+            queue,
+            queue_rx: Mutex::new(Some(queue_rx)),
+            durable: ClassCounters::default(),
+            buffered: ClassCounters::default(),
+            last_error: Mutex::new(None),
+            config,
+        }
+    }
+
+    fn open_wal(config: &StorageWriteConfig) -> Result<SpillQueue> {
+        SpillQueue::open(&config.wal_dir, config.wal_segment_max_bytes, config.wal_max_bytes)
+    }
+
+    /// 指定数据类型的落库端，需在 `start` 之前调用，重启后日志中的数据也按此补写
+    pub fn route(&self, data_type: &str, sink: Arc<dyn WriteSink>) {
+        self.sinks.write().unwrap().insert(data_type.to_string(), sink);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.default_sink.is_some() || !self.sinks.read().unwrap().is_empty()
+    }
+
+    fn sink_for(&self, data_type: &str) -> Result<Arc<dyn WriteSink>> {
+        self.sinks
+            .read()
+            .unwrap()
+            .get(data_type)
+            .or(self.default_sink.as_ref())
+            .cloned()
+            .ok_or_else(|| anyhow!("No storage sink for {} writes", data_type))
+    }
+
+    fn counters(&self, class: WriteClass) -> &ClassCounters {
+        match class {
+            WriteClass::Durable => &self.durable,
+            WriteClass::Buffered => &self.buffered,
+        }
+    }
+
+    fn count(&self, class: WriteClass, outcome: &str, count: u64) {
+        self.counters(class)
+            .counter(outcome)
+            .fetch_add(count, Ordering::Relaxed);
+        let _ = self.metrics.record_storage_write(class.as_str(), outcome, count);
+    }
+
+    fn set_error(&self, error: Option<String>) {
+        *self.last_error.lock().unwrap() = error;
+    }
+
+    /// 写入一行，按数据类型的可靠性等级确认
+    ///
+    /// 持久化写入返回时数据已落盘；异步写入返回时只保证已进入内存队列。
+    pub async fn write<T: Serialize>(&self, data_type: &str, table: &str, row: &T) -> Result<()> {
+        self.sink_for(data_type)?;
+        let record = SpilledRecord {
+            topic: table.to_string(),
+            key: data_type.to_string(),
+            payload: serde_json::to_string(row)?,
+        };
+
+        let class = self.config.class_for(data_type);
+        let started = Instant::now();
+        match class {
+            WriteClass::Durable => self.write_durable(record).await?,
+            WriteClass::Buffered => self.write_buffered(record)?,
+        }
+
+        self.count(class, "accepted", 1);
+        let _ = self
+            .metrics
+            .record_storage_write_ack(class.as_str(), started.elapsed());
+        Ok(())
+    }
+
+    async fn write_durable(&self, record: SpilledRecord) -> Result<()> {
+        let appended = self
+            .wal
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map(|wal| wal.append(&record).and_then(|_| wal.sync()));
+
+        match appended {
+            Some(Ok(())) => {
+                self.wal_notify.notify_one();
+                Ok(())
+            }
+            Some(Err(e)) => {
+                self.count(WriteClass::Durable, "failed", 1);
+                Err(e)
+            }
+            None => match self.insert(&[&record]).await {
+                Ok(()) => {
+                    self.count(WriteClass::Durable, "persisted", 1);
+                    Ok(())
+                }
+                Err(e) => {
+                    self.count(WriteClass::Durable, "failed", 1);
+                    self.set_error(Some(e.to_string()));
+                    Err(e)
+                }
+            },
+        }
+    }
+
+    fn write_buffered(&self, record: SpilledRecord) -> Result<()> {
+        match self.queue.try_send(record) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.count(WriteClass::Buffered, "dropped", 1);
+                Err(anyhow!(
+                    "Storage write queue is full ({} rows)",
+                    self.config.queue_capacity
+                ))
+            }
+            Err(TrySendError::Closed(_)) => {
+                self.count(WriteClass::Buffered, "failed", 1);
+                Err(anyhow!("Storage write queue is closed"))
+            }
+        }
+    }
+
+    /// 按数据类型和目标表分组交给落库端，同一组内保持原有顺序
+    async fn insert(&self, records: &[&SpilledRecord]) -> Result<()> {
+        let mut groups: BTreeMap<(&str, &str), Vec<&str>> = BTreeMap::new();
+        for record in records {
+            groups
+                .entry((record.key.as_str(), record.topic.as_str()))
+                .or_default()
+                .push(record.payload.as_str());
+        }
+
+        for ((data_type, table), rows) in groups {
+            self.sink_for(data_type)?
+                .write_rows(data_type, table, &rows)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 写入统计
+    pub fn stats(&self) -> TieredWriterStats {
+        let (wal_pending, wal_bytes) = self
+            .wal
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|wal| (wal.unread(), wal.size_bytes()))
+            .unwrap_or((0, 0));
+
+        TieredWriterStats {
+            durable: self.durable.stats(),
+            buffered: self.buffered.stats(),
+            wal_pending,
+            wal_bytes,
+            last_error: self.last_error.lock().unwrap().clone(),
+        }
+    }
+
+    /// 启动预写日志补写和异步批量写入任务
+    pub fn start(self: &Arc<Self>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if self.wal.lock().unwrap().is_some() {
+            tokio::spawn(self.clone().replay_wal());
+        }
+        if let Some(queue_rx) = self.queue_rx.lock().unwrap().take() {
+            tokio::spawn(self.clone().flush_batches(queue_rx));
+        }
+        info!(
+            "Tiered storage writer started (batch size: {}, flush interval: {}ms)",
+            self.config.batch_size, self.config.flush_interval_ms
+        );
+    }
+
+    /// 按顺序把预写日志写入ClickHouse
+    ///
+    /// 写入失败时重新打开日志，读取位置回到上次提交处，整批重试。
+    async fn replay_wal(self: Arc<Self>) {
+        let backoff = Duration::from_millis(self.config.retry_backoff_ms);
+        loop {
+            let read = match self.wal.lock().unwrap().as_mut() {
+                Some(wal) => wal.read(self.config.replay_batch_size.max(1)),
+                None => return,
+            };
+            let batch = match read {
+                Ok(batch) => batch,
+                Err(e) => {
+                    error!("Failed to read storage WAL: {}", e);
+                    self.set_error(Some(e.to_string()));
+                    tokio::time::sleep(backoff).await;
+                    continue;
+                }
+            };
+            let Some(&(_, position)) = batch.last() else {
+                let _ = self.metrics.set_storage_wal_pending(0);
+                let _ = tokio::time::timeout(Duration::from_secs(1), self.wal_notify.notified()).await;
+                continue;
+            };
+
+            let records: Vec<&SpilledRecord> = batch.iter().map(|(record, _)| record).collect();
+            let count = records.len() as u64;
+            match self.insert(&records).await {
+                Ok(()) => {
+                    let mut wal = self.wal.lock().unwrap();
+                    if let Some(wal) = wal.as_mut() {
+                        if let Err(e) = wal.commit(position) {
+                            error!("Failed to commit storage WAL position: {}", e);
+                        }
+                        let _ = self.metrics.set_storage_wal_pending(wal.unread() as i64);
+                    }
+                    drop(wal);
+                    self.count(WriteClass::Durable, "persisted", count);
+                    self.set_error(None);
+                }
+                Err(e) => {
+                    warn!("Failed to persist {} durable writes, retrying: {}", count, e);
+                    self.count(WriteClass::Durable, "retried", count);
+                    self.set_error(Some(e.to_string()));
+                    match Self::open_wal(&self.config) {
+                        Ok(wal) => *self.wal.lock().unwrap() = Some(wal),
+                        Err(e) => error!("Failed to reopen storage WAL: {}", e),
+                    }
+                    tokio::time::sleep(backoff).await;
+                }
+            }
+        }
+    }
+
+    /// 攒批写入异步队列中的数据，达到批大小或超过攒批时间即写入
+    async fn flush_batches(self: Arc<Self>, mut queue_rx: mpsc::Receiver<SpilledRecord>) {
+        let batch_size = self.config.batch_size.max(1);
+        let interval = Duration::from_millis(self.config.flush_interval_ms.max(1));
+        let mut batch = Vec::with_capacity(batch_size);
+
+        while let Some(record) = queue_rx.recv().await {
+            batch.push(record);
+            let deadline = tokio::time::Instant::now() + interval;
+            while batch.len() < batch_size {
+                match tokio::time::timeout_at(deadline, queue_rx.recv()).await {
+                    Ok(Some(record)) => batch.push(record),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+
+            let records: Vec<&SpilledRecord> = batch.iter().collect();
+            let count = records.len() as u64;
+            match self.insert(&records).await {
+                Ok(()) => {
+                    self.count(WriteClass::Buffered, "persisted", count);
+                    self.set_error(None);
+                }
+                Err(e) => {
+                    warn!("Failed to write {} buffered rows: {}", count, e);
+                    self.count(WriteClass::Buffered, "failed", count);
+                    self.set_error(Some(e.to_string()));
+                }
+            }
+            batch.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct TradeRow {
+        trade_id: String,
+        price: String,
+    }
+
+    fn writer(dir: &std::path::Path, queue_capacity: usize) -> TieredWriter {
+        let config = StorageWriteConfig {
+            wal_dir: dir.to_string_lossy().to_string(),
+            queue_capacity,
+            ..StorageWriteConfig::default()
+        };
+        TieredWriter::new(
+            config,
+            Some(&ClickHouseConfig::default()),
+            Arc::new(AppMetrics::new().unwrap()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_durable_writes_survive_restart() {
+        let dir = std::env::temp_dir().join(format!("storage-wal-{}", uuid::Uuid::new_v4()));
+        let row = TradeRow {
+            trade_id: "1".to_string(),
+            price: "50000.10".to_string(),
+        };
+
+        let first = writer(&dir, 1);
+        first.write("trade", "market_trades", &row).await.unwrap();
+        first.write("tick", "market_ticks", &row).await.unwrap();
+        // 异步队列已满，新的Tick被丢弃而不是阻塞
+        assert!(first.write("tick", "market_ticks", &row).await.is_err());
+
+        let stats = first.stats();
+        assert_eq!(stats.durable.accepted, 1);
+        assert_eq!(stats.wal_pending, 1);
+        assert_eq!(stats.buffered.accepted, 1);
+        assert_eq!(stats.buffered.dropped, 1);
+        drop(first);
+
+        // 未写入ClickHouse的持久化写入在重启后仍在日志中
+        let second = writer(&dir, 1);
+        let pending = second
+            .wal
+            .lock()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .read(10)
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0.topic, "market_trades");
+        assert_eq!(pending[0].0.key, "trade");
+        assert_eq!(pending[0].0.payload, r#"{"trade_id":"1","price":"50000.10"}"#);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
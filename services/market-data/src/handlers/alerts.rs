@@ -0,0 +1,89 @@
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use shared_models::common::{CommonError, Exchange};
+use shared_protocols::kafka::NotificationChannel;
+use uuid::Uuid;
+
+use super::{authenticated_user, ApiError, ApiResponse};
+use crate::alerts::{AlertCondition, AlertRule};
+use crate::AppState;
+
+/// 创建告警规则请求，规则属于网关认证的用户
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertRuleRequest {
+    pub exchange: Option<String>,
+    pub symbol: String,
+    pub condition: AlertCondition,
+    pub channels: Option<Vec<NotificationChannel>>,
+    pub cooldown_seconds: Option<u64>,
+}
+
+/// 创建告警规则
+pub async fn create_alert_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateAlertRuleRequest>,
+) -> Result<Json<ApiResponse<AlertRule>>, ApiError> {
+    let user_id = authenticated_user(&headers)?;
+    if request.symbol.trim().is_empty() {
+        return Err(ApiError::BadRequest("Symbol is required".to_string()));
+    }
+    request
+        .condition
+        .validate()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let exchange = match request.exchange {
+        Some(exchange) => Some(
+            exchange
+                .parse::<Exchange>()
+                .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?,
+        ),
+        None => None,
+    };
+
+    let rule = AlertRule::new(
+        user_id,
+        exchange,
+        request.symbol,
+        request.condition,
+        request
+            .channels
+            .unwrap_or_else(|| vec![NotificationChannel::InApp]),
+        request.cooldown_seconds.unwrap_or(300),
+    );
+
+    Ok(Json(ApiResponse::success(
+        state.alert_engine.add_rule(rule).await,
+    )))
+}
+
+/// 列出当前用户的告警规则
+pub async fn list_alert_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<AlertRule>>>, ApiError> {
+    let user_id = authenticated_user(&headers)?;
+    Ok(Json(ApiResponse::success(
+        state.alert_engine.list_rules(user_id).await,
+    )))
+}
+
+/// 删除当前用户的告警规则，其他用户的规则按不存在处理
+pub async fn delete_alert_rule(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<AlertRule>>, ApiError> {
+    let user_id = authenticated_user(&headers)?;
+    state
+        .alert_engine
+        .remove_rule(user_id, rule_id)
+        .await
+        .map(|rule| Json(ApiResponse::success(rule)))
+        .ok_or_else(|| ApiError::NotFound(format!("Alert rule {} not found", rule_id)))
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use shared_models::common::{CommonError, Exchange};
+use shared_models::market::{DepthMetrics, SeasonalityProfile, WhaleTrade};
+
+use super::{ApiError, ApiResponse};
+use crate::analytics::{AnalyticsQuery, QueryResult};
+use crate::AppState;
+
+/// 获取最新的订单簿深度指标
+pub async fn get_depth_metrics(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<DepthMetrics>>, ApiError> {
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+
+    state
+        .depth_metrics
+        .get_latest(exchange.as_str(), &symbol)
+        .await
+        .map(|metrics| Json(ApiResponse::success(metrics)))
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No depth metrics for {}:{}",
+                exchange,
+                symbol.to_uppercase()
+            ))
+        })
+}
+
+/// 大额成交查询参数
+#[derive(Debug, Deserialize)]
+pub struct WhaleTradeQuery {
+    pub limit: Option<usize>,
+}
+
+/// 获取最近的大额成交
+pub async fn get_whale_trades(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<WhaleTradeQuery>,
+) -> Result<Json<ApiResponse<Vec<WhaleTrade>>>, ApiError> {
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+
+    let limit = query.limit.unwrap_or(50).min(state.config.whale_detection.history_size);
+    let whales = state
+        .whale_detector
+        .get_recent(exchange.as_str(), &symbol, limit)
+        .await;
+
+    Ok(Json(ApiResponse::success(whales)))
+}
+
+/// 获取交易对的日内季节性画像（按星期和小时的成交量与波动）
+pub async fn get_seasonality(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<SeasonalityProfile>>, ApiError> {
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    if !state.seasonality.is_enabled() {
+        return Err(ApiError::ServiceUnavailable(
+            "Seasonality analytics are disabled".to_string(),
+        ));
+    }
+
+    state
+        .seasonality
+        .profile(exchange, &symbol)
+        .await
+        .map(|profile| Json(ApiResponse::success(profile)))
+        .map_err(|e| ApiError::InternalServerError(e.to_string()))
+}
+
+/// 执行即席分析查询，查询先按DSL和限制校验后再编译为ClickHouse SQL
+pub async fn run_analytics_query(
+    State(state): State<AppState>,
+    Json(query): Json<AnalyticsQuery>,
+) -> Result<Json<ApiResponse<QueryResult>>, ApiError> {
+    let engine = &state.analytics_query;
+    if !engine.is_enabled() {
+        return Err(ApiError::ServiceUnavailable(
+            "Analytics queries are disabled".to_string(),
+        ));
+    }
+
+    let compiled = engine
+        .compile(&query)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    engine
+        .execute(compiled)
+        .await
+        .map(|result| Json(ApiResponse::success(result)))
+        .map_err(|e| ApiError::InternalServerError(e.to_string()))
+}
@@ -0,0 +1,273 @@
+use axum::{extract::State, Json};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use shared_models::common::{Exchange, Interval};
+use shared_models::market::{Kline, MarketTick};
+use std::collections::{BTreeMap, HashSet};
+use tracing::warn;
+
+use super::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// 单次批量查询最多包含的条目数
+pub const MAX_BATCH_ITEMS: usize = 100;
+
+/// 批量查询条目
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchItem {
+    pub exchange: String,
+    pub symbol: String,
+    /// K线周期，仅K线批量查询需要
+    pub interval: Option<String>,
+}
+
+/// 批量查询请求
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    pub items: Vec<BatchItem>,
+}
+
+/// 批量查询响应，按 exchange:SYMBOL[:interval] 为键
+#[derive(Debug, Serialize)]
+pub struct BatchResponse<T> {
+    pub requested: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: BTreeMap<String, T>,
+    /// 单个条目的失败原因，不影响其他条目
+    pub errors: BTreeMap<String, String>,
+}
+
+impl<T> BatchResponse<T> {
+    fn new(requested: usize) -> Self {
+        Self {
+            requested,
+            succeeded: 0,
+            failed: 0,
+            results: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        }
+    }
+
+    fn push(&mut self, key: String, result: Result<T, String>) {
+        match result {
+            Ok(value) => {
+                self.succeeded += 1;
+                self.results.insert(key, value);
+            }
+            Err(e) => {
+                self.failed += 1;
+                self.errors.insert(key, e);
+            }
+        }
+    }
+}
+
+/// 解析后的批量条目
+#[derive(Debug, Clone)]
+struct ParsedItem {
+    key: String,
+    exchange: Exchange,
+    symbol: String,
+    interval: Option<Interval>,
+}
+
+/// 结果键，Tick查询忽略周期
+fn item_key(item: &BatchItem, with_interval: bool) -> String {
+    match item.interval.as_ref().filter(|_| with_interval) {
+        Some(interval) => format!(
+            "{}:{}:{}",
+            item.exchange.to_lowercase(),
+            item.symbol.to_uppercase(),
+            interval
+        ),
+        None => format!("{}:{}", item.exchange.to_lowercase(), item.symbol.to_uppercase()),
+    }
+}
+
+/// 校验批量大小、去重并解析条目，解析失败的条目计入错误
+fn parse_items(
+    items: &[BatchItem],
+    require_interval: bool,
+) -> Result<(Vec<ParsedItem>, Vec<(String, String)>), ApiError> {
+    if items.is_empty() {
+        return Err(ApiError::BadRequest("Batch must contain at least one item".to_string()));
+    }
+    if items.len() > MAX_BATCH_ITEMS {
+        return Err(ApiError::BadRequest(format!(
+            "Batch contains {} items, limit is {}",
+            items.len(),
+            MAX_BATCH_ITEMS
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut parsed = Vec::new();
+    let mut errors = Vec::new();
+
+    for item in items {
+        let key = item_key(item, require_interval);
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+
+        let exchange = match item.exchange.parse::<Exchange>() {
+            Ok(exchange) => exchange,
+            Err(e) => {
+                errors.push((key, e.to_string()));
+                continue;
+            }
+        };
+        let interval = match (&item.interval, require_interval) {
+            (Some(interval), true) => match interval.parse::<Interval>() {
+                Ok(interval) => Some(interval),
+                Err(e) => {
+                    errors.push((key, e.to_string()));
+                    continue;
+                }
+            },
+            (None, true) => {
+                errors.push((key, "Missing interval".to_string()));
+                continue;
+            }
+            (_, false) => None,
+        };
+
+        parsed.push(ParsedItem {
+            key,
+            exchange,
+            symbol: item.symbol.to_uppercase(),
+            interval,
+        });
+    }
+
+    Ok((parsed, errors))
+}
+
+/// 批量获取最新Tick
+pub async fn get_ticks_batch(
+    State(state): State<AppState>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Json<ApiResponse<BatchResponse<MarketTick>>>, ApiError> {
+    let (items, invalid) = parse_items(&request.items, false)?;
+    let mut response = BatchResponse::new(items.len() + invalid.len());
+    for (key, e) in invalid {
+        response.push(key, Err(e));
+    }
+
+    let lookups = items.iter().map(|item| {
+        let state = state.clone();
+        async move {
+            let result = match state
+                .storage_manager
+                .get_latest_tick(&item.exchange, &item.symbol)
+                .await
+            {
+                Ok(Some(tick)) => Ok(tick),
+                Ok(None) => Err("No tick data".to_string()),
+                Err(e) => {
+                    warn!("Batch tick lookup failed for {}: {}", item.key, e);
+                    Err(e.to_string())
+                }
+            };
+            (item.key.clone(), result)
+        }
+    });
+    for (key, result) in join_all(lookups).await {
+        response.push(key, result);
+    }
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 批量获取最新K线
+pub async fn get_klines_batch(
+    State(state): State<AppState>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Json<ApiResponse<BatchResponse<Kline>>>, ApiError> {
+    let (items, invalid) = parse_items(&request.items, true)?;
+    let mut response = BatchResponse::new(items.len() + invalid.len());
+    for (key, e) in invalid {
+        response.push(key, Err(e));
+    }
+
+    let lookups = items.iter().map(|item| {
+        let state = state.clone();
+        async move {
+            let interval = item.interval.clone().unwrap_or(Interval::OneMinute);
+            let result = match state
+                .storage_manager
+                .get_latest_kline(&item.exchange, &item.symbol, &interval)
+                .await
+            {
+                Ok(Some(kline)) => Ok(kline),
+                Ok(None) => Err("No kline data".to_string()),
+                Err(e) => {
+                    warn!("Batch kline lookup failed for {}: {}", item.key, e);
+                    Err(e.to_string())
+                }
+            };
+            (item.key.clone(), result)
+        }
+    });
+    for (key, result) in join_all(lookups).await {
+        response.push(key, result);
+    }
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(exchange: &str, symbol: &str, interval: Option<&str>) -> BatchItem {
+        BatchItem {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            interval: interval.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_items_dedups_and_reports_invalid() {
+        let items = vec![
+            item("binance", "btcusdt", Some("1m")),
+            item("Binance", "BTCUSDT", Some("1m")),
+            item("binance", "ethusdt", None),
+            item("nowhere", "btcusdt", Some("1m")),
+            item("binance", "solusdt", Some("7x")),
+        ];
+
+        let (parsed, errors) = parse_items(&items, true).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].key, "binance:BTCUSDT:1m");
+        assert_eq!(parsed[0].symbol, "BTCUSDT");
+
+        let failed: Vec<&str> = errors.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(
+            failed,
+            vec!["binance:ETHUSDT", "nowhere:BTCUSDT:1m", "binance:SOLUSDT:7x"]
+        );
+    }
+
+    #[test]
+    fn test_parse_items_limits() {
+        assert!(parse_items(&[], false).is_err());
+
+        let items: Vec<BatchItem> = (0..=MAX_BATCH_ITEMS)
+            .map(|i| item("binance", &format!("SYM{}USDT", i), None))
+            .collect();
+        assert!(parse_items(&items, false).is_err());
+        assert!(parse_items(&items[..MAX_BATCH_ITEMS], false).is_ok());
+    }
+
+    #[test]
+    fn test_batch_response_counts() {
+        let mut response = BatchResponse::new(2);
+        response.push("binance:BTCUSDT".to_string(), Ok(1));
+        response.push("binance:ETHUSDT".to_string(), Err("No tick data".to_string()));
+        assert_eq!((response.succeeded, response.failed), (1, 1));
+        assert_eq!(response.results["binance:BTCUSDT"], 1);
+    }
+}
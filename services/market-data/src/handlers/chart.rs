@@ -0,0 +1,208 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use shared_models::common::{CommonError, Exchange, Interval};
+use tracing::{debug, error, warn};
+
+use super::{ApiError, ApiResponse};
+use crate::charting::{self, ChartCandle};
+use crate::continuity::KlineContinuityDetector;
+use crate::AppState;
+
+/// 图表查询参数
+#[derive(Debug, Deserialize)]
+pub struct ChartQuery {
+    /// 目标周期，例如 4h
+    pub interval: String,
+    /// 源K线周期，默认根据目标周期自动选择
+    pub source_interval: Option<String>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    /// 是否填充间隙，默认开启
+    pub fill_gaps: Option<bool>,
+}
+
+/// 图表数据响应
+#[derive(Debug, Serialize)]
+pub struct ChartResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    pub source_interval: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub synthetic_count: usize,
+    pub cached: bool,
+    pub candles: Vec<ChartCandle>,
+}
+
+/// 获取图表数据
+pub async fn get_chart(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<ChartQuery>,
+) -> Result<Json<ApiResponse<ChartResponse>>, ApiError> {
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    let target: Interval = query
+        .interval
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    let source = match &query.source_interval {
+        Some(source) => source
+            .parse()
+            .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?,
+        // 优先使用物化汇总，避免每次扫描1分钟K线
+        None => state
+            .rollups
+            .source_for(&target)
+            .unwrap_or_else(|| charting::source_interval_for(&target)),
+    };
+
+    if source.to_millis() > target.to_millis() || target.to_millis() % source.to_millis() != 0 {
+        return Err(ApiError::BadRequest(format!(
+            "Cannot downsample {} into {}",
+            source, target
+        )));
+    }
+
+    let config = &state.config.charting;
+    // 结束时间对齐到周期，默认和实时边缘的请求才能复用缓存
+    let end_time = charting::align_end_to_interval(
+        query
+            .end_time
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+        target.to_millis(),
+    );
+    let start_time = query
+        .start_time
+        .unwrap_or(end_time - target.to_millis() * config.max_points as i64);
+    if start_time >= end_time {
+        return Err(ApiError::BadRequest(
+            "Start time must be before end time".to_string(),
+        ));
+    }
+
+    let points = (end_time - start_time) / target.to_millis();
+    if points > config.max_points as i64 {
+        return Err(ApiError::BadRequest(format!(
+            "Requested range covers {} candles, maximum is {}",
+            points, config.max_points
+        )));
+    }
+
+    let fill_gaps = query.fill_gaps.unwrap_or(true);
+    let start_time = charting::align_to_interval(start_time, target.to_millis());
+    let cache_key = state.chart_cache.cache_key(
+        exchange.as_str(),
+        &symbol,
+        target.as_str(),
+        start_time,
+        end_time,
+        fill_gaps,
+    );
+
+    // 1. 命中缓存直接返回
+    if let Some(candles) = state.chart_cache.get(&cache_key).await {
+        return Ok(Json(ApiResponse::success(build_response(
+            &exchange, &symbol, &target, &source, start_time, end_time, candles, true,
+        ))));
+    }
+
+    // 2. 查询源K线，物化周期读取汇总表，失败时回退到1分钟K线
+    let range_start = charting::millis_to_datetime(start_time);
+    let range_end = charting::millis_to_datetime(end_time);
+    let mut source = source;
+    let mut materialized = false;
+    let mut klines = None;
+
+    if state.rollups.is_enabled() && state.rollups.config().has_interval(&source) {
+        match state
+            .rollups
+            .query_klines(&exchange, &symbol, &source, range_start, range_end)
+            .await
+        {
+            Ok(rollup_klines) => {
+                materialized = true;
+                klines = Some(rollup_klines);
+            }
+            Err(e) => {
+                warn!(
+                    "Rollup query failed for {}:{} {}, falling back to 1m klines: {}",
+                    exchange, symbol, source, e
+                );
+                source = Interval::OneMinute;
+            }
+        }
+    }
+
+    let klines = match klines {
+        Some(klines) => klines,
+        None => state
+            .storage_manager
+            .query_klines(&exchange, &symbol, &source, range_start, range_end)
+            .await
+            .map_err(|e| {
+                error!("Failed to query klines for chart: {}", e);
+                ApiError::InternalServerError(e.to_string())
+            })?,
+    };
+
+    debug!(
+        "Building chart {}:{} {} from {} {} klines (materialized: {})",
+        exchange,
+        symbol,
+        target,
+        klines.len(),
+        source,
+        materialized
+    );
+
+    // 3. 先在源周期填充间隙，再降采样
+    let mut candles: Vec<ChartCandle> = klines.iter().map(ChartCandle::from_kline).collect();
+    candles.sort_by_key(|c| c.open_time);
+    if fill_gaps {
+        candles = charting::fill_gaps(
+            &candles,
+            &source,
+            KlineContinuityDetector::tolerance_milliseconds(&source),
+        );
+    }
+    if source != target {
+        candles = charting::downsample(&candles, &target);
+    }
+
+    // 4. 写入缓存
+    state.chart_cache.set(&cache_key, &candles, end_time).await;
+
+    Ok(Json(ApiResponse::success(build_response(
+        &exchange, &symbol, &target, &source, start_time, end_time, candles, false,
+    ))))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_response(
+    exchange: &Exchange,
+    symbol: &str,
+    target: &Interval,
+    source: &Interval,
+    start_time: i64,
+    end_time: i64,
+    candles: Vec<ChartCandle>,
+    cached: bool,
+) -> ChartResponse {
+    ChartResponse {
+        exchange: exchange.as_str().to_string(),
+        symbol: symbol.to_uppercase(),
+        interval: target.as_str().to_string(),
+        source_interval: source.as_str().to_string(),
+        start_time,
+        end_time,
+        synthetic_count: candles.iter().filter(|c| c.synthetic).count(),
+        cached,
+        candles,
+    }
+}
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use super::{ApiError, ApiResponse};
+use crate::compaction::CompactionReport;
+use crate::config::CompactionConfig;
+use crate::AppState;
+
+/// 手动压缩参数
+#[derive(Debug, Deserialize)]
+pub struct CompactionRunQuery {
+    /// 默认只统计不执行
+    pub dry_run: Option<bool>,
+}
+
+/// 手动触发Tick压缩
+pub async fn run_compaction(
+    State(state): State<AppState>,
+    Query(query): Query<CompactionRunQuery>,
+) -> Result<Json<ApiResponse<CompactionReport>>, ApiError> {
+    let dry_run = query.dry_run.unwrap_or(true);
+
+    let report = state
+        .tick_compactor
+        .run(dry_run)
+        .await
+        .map_err(|e| ApiError::ServiceUnavailable(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+/// 获取压缩策略配置
+pub async fn get_compaction_policies(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<CompactionConfig>>, ApiError> {
+    Ok(Json(ApiResponse::success(
+        state.tick_compactor.config().clone(),
+    )))
+}
+
+/// 获取最近一次压缩报告
+pub async fn get_compaction_report(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<CompactionReport>>, ApiError> {
+    state
+        .tick_compactor
+        .last_report()
+        .await
+        .map(|report| Json(ApiResponse::success(report)))
+        .ok_or_else(|| ApiError::NotFound("Compaction has not run yet".to_string()))
+}
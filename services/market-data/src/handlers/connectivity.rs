@@ -0,0 +1,13 @@
+use axum::{extract::State, Json};
+
+use super::{ApiError, ApiResponse};
+use crate::connectors::ConnectivitySnapshot;
+use crate::AppState;
+
+/// 获取交易所连接状态快照，供监控面板使用
+pub async fn get_connectivity(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<ConnectivitySnapshot>>, ApiError> {
+    let snapshot = state.exchange_manager.connectivity_snapshot().await;
+    Ok(Json(ApiResponse::success(snapshot)))
+}
@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{ApiError, ApiResponse};
+use crate::aggregation::CandleCloseStats;
+use crate::continuity::{ContinuityStats, GapFilter, GapRecord, SeriesState};
+use crate::AppState;
+
+/// 间隙查询参数
+#[derive(Debug, Deserialize)]
+pub struct GapQuery {
+    #[serde(flatten)]
+    pub filter: GapFilter,
+    pub limit: Option<usize>,
+}
+
+/// K线连续性报告
+#[derive(Debug, Serialize)]
+pub struct ContinuityReport {
+    pub stats: ContinuityStats,
+    pub series: Vec<SeriesState>,
+    pub gaps: Vec<GapRecord>,
+}
+
+/// 获取K线连续性状态和最近的间隙
+pub async fn get_continuity_gaps(
+    State(state): State<AppState>,
+    Query(query): Query<GapQuery>,
+) -> Result<Json<ApiResponse<ContinuityReport>>, ApiError> {
+    let limit = query.limit.unwrap_or(100).min(state.config.continuity.gap_history_size);
+    let detector = &state.continuity;
+
+    Ok(Json(ApiResponse::success(ContinuityReport {
+        stats: detector.get_stats().await,
+        series: detector.series().await,
+        gaps: detector.gaps(&query.filter, limit).await,
+    })))
+}
+
+/// 获取K线收盘调度统计
+pub async fn get_candle_close_stats(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<CandleCloseStats>>, ApiError> {
+    Ok(Json(ApiResponse::success(state.candle_close.get_stats().await)))
+}
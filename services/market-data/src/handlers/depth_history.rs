@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use shared_models::common::{CommonError, Exchange};
+
+use super::{ApiError, ApiResponse};
+use crate::charting::millis_to_datetime;
+use crate::depth_history::{compress_series, HeatmapFrame, HeatmapLevel, Reconstruction};
+use crate::AppState;
+
+/// 默认查询最近1小时
+const DEFAULT_RANGE_MS: i64 = 3_600_000;
+
+/// 深度快照历史查询参数
+#[derive(Debug, Deserialize)]
+pub struct DepthHistoryQuery {
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    /// 降采样分辨率（毫秒），每个时间桶只取第一个快照
+    pub resolution_ms: Option<u64>,
+    /// 每多少帧输出一个完整关键帧
+    pub keyframe_interval: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// 压缩后的深度快照序列
+#[derive(Debug, Serialize)]
+pub struct DepthHistoryResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub depth: usize,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub resolution_ms: Option<u64>,
+    pub keyframe_interval: usize,
+    pub count: usize,
+    /// 超过返回上限时为 true，可从最后一帧时间继续查询
+    pub truncated: bool,
+    pub frames: Vec<HeatmapFrame>,
+}
+
+/// 查询订单簿深度快照历史，用于热力图回放
+pub async fn get_depth_history(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<DepthHistoryQuery>,
+) -> Result<Json<ApiResponse<DepthHistoryResponse>>, ApiError> {
+    let recorder = &state.depth_history;
+    if !recorder.is_enabled() {
+        return Err(ApiError::ServiceUnavailable(
+            "Order book depth history is disabled".to_string(),
+        ));
+    }
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+
+    let end_time = query
+        .end_time
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let start_time = query.start_time.unwrap_or(end_time - DEFAULT_RANGE_MS);
+    if start_time >= end_time {
+        return Err(ApiError::BadRequest(
+            "Start time must be before end time".to_string(),
+        ));
+    }
+
+    let config = recorder.config();
+    let limit = query
+        .limit
+        .unwrap_or(config.max_query_snapshots)
+        .clamp(1, config.max_query_snapshots);
+    let keyframe_interval = query.keyframe_interval.unwrap_or(60).max(1);
+
+    // 多取一条用于判断是否截断
+    let mut rows = recorder
+        .query(
+            exchange.as_str(),
+            &symbol,
+            millis_to_datetime(start_time),
+            millis_to_datetime(end_time),
+            query.resolution_ms,
+            limit + 1,
+        )
+        .await?;
+    let truncated = rows.len() > limit;
+    rows.truncate(limit);
+
+    let frames = compress_series(&rows, keyframe_interval);
+    Ok(Json(ApiResponse::success(DepthHistoryResponse {
+        exchange: exchange.as_str().to_string(),
+        symbol: symbol.to_uppercase(),
+        depth: config.depth,
+        start_time,
+        end_time,
+        resolution_ms: query.resolution_ms,
+        keyframe_interval,
+        count: frames.len(),
+        truncated,
+        frames,
+    })))
+}
+
+/// 订单簿重建查询参数
+#[derive(Debug, Deserialize)]
+pub struct OrderBookAtQuery {
+    /// 毫秒时间戳
+    pub ts: i64,
+}
+
+/// 指定时刻的订单簿
+#[derive(Debug, Serialize)]
+pub struct OrderBookAtResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub requested_at: i64,
+    /// 作为起点的快照时间
+    pub snapshot_timestamp: i64,
+    /// 重建结果对应的最后一次更新时间
+    pub timestamp: i64,
+    pub last_update_id: u64,
+    pub diffs_applied: usize,
+    pub bids: Vec<HeatmapLevel>,
+    pub asks: Vec<HeatmapLevel>,
+}
+
+/// 重建历史某一时刻的订单簿，用于成交质量排查
+pub async fn get_orderbook_at(
+    State(state): State<AppState>,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<OrderBookAtQuery>,
+) -> Result<Json<ApiResponse<OrderBookAtResponse>>, ApiError> {
+    let recorder = &state.depth_history;
+    if !recorder.is_enabled() || !recorder.config().record_diffs {
+        return Err(ApiError::ServiceUnavailable(
+            "Order book diff recording is disabled".to_string(),
+        ));
+    }
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    if query.ts > chrono::Utc::now().timestamp_millis() {
+        return Err(ApiError::BadRequest(
+            "Timestamp must not be in the future".to_string(),
+        ));
+    }
+
+    let book = match recorder
+        .reconstruct_at(exchange.as_str(), &symbol, millis_to_datetime(query.ts))
+        .await?
+    {
+        Reconstruction::Book(book) => book,
+        Reconstruction::NoSnapshot => {
+            return Err(ApiError::NotFound(format!(
+                "No order book snapshot within {}ms before {}",
+                recorder.config().max_seek_ms,
+                query.ts
+            )))
+        }
+        Reconstruction::TooManyDiffs(limit) => {
+            return Err(ApiError::BadRequest(format!(
+                "Reconstruction would apply more than {} diffs",
+                limit
+            )))
+        }
+    };
+
+    Ok(Json(ApiResponse::success(OrderBookAtResponse {
+        exchange: exchange.as_str().to_string(),
+        symbol: symbol.to_uppercase(),
+        requested_at: query.ts,
+        snapshot_timestamp: book.snapshot_timestamp,
+        timestamp: book.timestamp,
+        last_update_id: book.last_update_id,
+        diffs_applied: book.diffs_applied,
+        bids: book.bids,
+        asks: book.asks,
+    })))
+}
@@ -149,6 +149,43 @@ pub async fn detailed_health_handler(
         details: Some(serde_json::to_value(&exchange_health).unwrap_or_default()),
     });
 
+    // 检查分级存储写入
+    if state.storage_writes.is_enabled() {
+        let writes = state.storage_writes.stats();
+        let degraded = writes.is_degraded();
+        if degraded && overall_status == "healthy" {
+            overall_status = "degraded";
+        }
+
+        components.insert("storage_writes".to_string(), ComponentHealth {
+            status: if degraded { "degraded" } else { "healthy" }.to_string(),
+            message: Some(format!(
+                "{} durable writes pending in WAL, {} buffered writes dropped",
+                writes.wal_pending, writes.buffered.dropped
+            )),
+            last_check: chrono::Utc::now().timestamp_millis(),
+            details: Some(serde_json::to_value(&writes).unwrap_or_default()),
+        });
+    }
+
+    // 检查Kafka发布缓冲积压
+    if let Some(egress) = state.kafka_publisher.egress_stats() {
+        let backlog = egress.has_backlog();
+        if backlog && overall_status == "healthy" {
+            overall_status = "degraded";
+        }
+
+        components.insert("kafka_egress".to_string(), ComponentHealth {
+            status: if backlog { "degraded" } else { "healthy" }.to_string(),
+            message: Some(format!(
+                "{} buffered, {} spilled to disk, {} dropped",
+                egress.buffered, egress.spilled, egress.dropped
+            )),
+            last_check: chrono::Utc::now().timestamp_millis(),
+            details: Some(serde_json::to_value(&egress).unwrap_or_default()),
+        });
+    }
+
     // 收集指标
     let processor_stats = state.data_processor.get_stats();
     let storage_stats = state.storage_manager.get_stats().await;
@@ -157,7 +194,7 @@ pub async fn detailed_health_handler(
         total_events_processed: processor_stats.total_events,
         events_per_second: processor_stats.events_per_second,
         error_rate: processor_stats.error_rate(),
-        active_connections: 0, // TODO: 从WebSocket服务器获取
+        active_connections: state.ws_clients.active_count().await as u64,
         memory_usage_mb: get_memory_usage(),
         cpu_usage_percent: get_cpu_usage(),
     };
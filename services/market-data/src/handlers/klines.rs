@@ -0,0 +1,399 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared_models::common::{CommonError, Exchange, Interval};
+use shared_models::market::Kline;
+
+use super::{ApiError, ApiResponse};
+use crate::charting::millis_to_datetime;
+use crate::stores::TimeRange;
+use crate::websocket::DataEntitlement;
+use crate::AppState;
+
+/// 未指定 limit 时返回的K线数量
+pub const DEFAULT_KLINE_LIMIT: usize = 500;
+
+/// 单次查询最多返回的K线数量
+pub const MAX_KLINE_LIMIT: usize = 1000;
+
+/// 成交量曲线单次最多统计的K线数量
+pub const MAX_PROFILE_KLINES: usize = 20_000;
+
+/// 未指定 start_time 时成交量曲线统计的天数
+const DEFAULT_PROFILE_DAYS: i64 = 7;
+
+const DAY_MILLIS: i64 = 86_400_000;
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// 历史K线查询参数
+#[derive(Debug, Deserialize)]
+pub struct KlineHistoryQuery {
+    /// 起始开盘时间（毫秒，含），默认为 end_time 往前 limit 个周期
+    pub start_time: Option<i64>,
+    /// 结束开盘时间（毫秒，不含），默认为当前时间
+    pub end_time: Option<i64>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+/// 历史K线响应
+#[derive(Debug, Serialize)]
+pub struct KlineHistoryResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    pub order: SortOrder,
+    pub count: usize,
+    pub klines: Vec<Kline>,
+    /// 升序翻页时下一页的 start_time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_start_time: Option<i64>,
+    /// 降序翻页时下一页的 end_time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_end_time: Option<i64>,
+    /// 延迟行情用户只能看到在该时间（毫秒）之前收盘的K线
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delayed_until: Option<i64>,
+}
+
+/// 校验参数并计算查询范围，多取一条用于判断是否还有下一页
+fn query_range(query: &KlineHistoryQuery, interval: &Interval, now: i64) -> Result<(TimeRange, usize), ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_KLINE_LIMIT);
+    if limit == 0 {
+        return Err(ApiError::BadRequest("Limit must be greater than 0".to_string()));
+    }
+    if limit > MAX_KLINE_LIMIT {
+        return Err(ApiError::BadRequest(format!(
+            "Limit cannot exceed {}",
+            MAX_KLINE_LIMIT
+        )));
+    }
+
+    let end = query.end_time.unwrap_or(now);
+    let start = query
+        .start_time
+        .unwrap_or_else(|| end.saturating_sub(interval.to_millis() * limit as i64));
+    if start >= end {
+        return Err(ApiError::BadRequest(
+            "Start time must be before end time".to_string(),
+        ));
+    }
+
+    Ok((
+        TimeRange {
+            start: millis_to_datetime(start),
+            end: millis_to_datetime(end),
+            limit: limit + 1,
+            descending: query.order == SortOrder::Desc,
+        },
+        limit,
+    ))
+}
+
+/// 截取一页K线，返回下一页的游标
+///
+/// 升序时游标为多取的那条K线的开盘时间，作为下一页的 start_time；
+/// 降序时游标为本页最后一条的开盘时间，作为下一页的 end_time。
+fn take_page(mut klines: Vec<Kline>, limit: usize, order: SortOrder) -> (Vec<Kline>, Option<i64>) {
+    if klines.len() <= limit {
+        return (klines, None);
+    }
+    let extra = klines.split_off(limit);
+    let cursor = match order {
+        SortOrder::Asc => extra[0].open_time,
+        SortOrder::Desc => klines[limit - 1].open_time,
+    };
+    (klines, Some(cursor.timestamp_millis()))
+}
+
+/// 查询历史K线，支持按开盘时间升序或降序翻页
+///
+/// 延迟行情用户以延迟后的时间作为当前时间，并去掉在此之后收盘的K线。
+pub async fn get_kline_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((exchange, symbol, interval)): Path<(String, String, String)>,
+    Query(query): Query<KlineHistoryQuery>,
+) -> Result<Json<ApiResponse<KlineHistoryResponse>>, ApiError> {
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    let interval: Interval = interval
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    let symbol = symbol.to_uppercase();
+
+    let now = Utc::now().timestamp_millis();
+    let delayed_until = DataEntitlement::from_headers(&state.config.delayed_data, &headers)
+        .is_delayed()
+        .then(|| state.delayed_feed.cutoff(&symbol, now))
+        .flatten();
+    let query = KlineHistoryQuery {
+        end_time: match (query.end_time, delayed_until) {
+            (Some(end), Some(cutoff)) => Some(end.min(cutoff)),
+            (end, cutoff) => end.or(cutoff),
+        },
+        ..query
+    };
+
+    let (range, limit) = query_range(&query, &interval, delayed_until.unwrap_or(now))?;
+    let mut klines = state
+        .market_stores
+        .klines
+        .klines(exchange.clone(), &symbol, interval.clone(), range)
+        .await?;
+    if let Some(cutoff) = delayed_until {
+        klines.retain(|kline| kline.close_time.timestamp_millis() < cutoff);
+    }
+    let (klines, cursor) = take_page(klines, limit, query.order);
+
+    Ok(Json(ApiResponse::success(KlineHistoryResponse {
+        exchange: exchange.as_str().to_string(),
+        symbol,
+        interval: interval.as_str().to_string(),
+        order: query.order,
+        count: klines.len(),
+        klines,
+        next_start_time: cursor.filter(|_| query.order == SortOrder::Asc),
+        next_end_time: cursor.filter(|_| query.order == SortOrder::Desc),
+        delayed_until,
+    })))
+}
+
+/// 成交量曲线查询参数
+#[derive(Debug, Deserialize)]
+pub struct VolumeProfileQuery {
+    /// 起始开盘时间（毫秒，含），默认为 end_time 往前7天
+    pub start_time: Option<i64>,
+    /// 结束开盘时间（毫秒，不含），默认为当前时间
+    pub end_time: Option<i64>,
+}
+
+/// 日内一个周期的成交量
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VolumeBucket {
+    /// 周期开始相对UTC零点的毫秒数
+    pub offset_ms: i64,
+    pub volume: Decimal,
+    /// 占全天成交量的比例
+    pub share: Decimal,
+    /// 参与统计的K线数量
+    pub samples: u32,
+}
+
+/// 日内成交量曲线响应
+#[derive(Debug, Serialize)]
+pub struct VolumeProfileResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    pub interval_ms: i64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub total_volume: Decimal,
+    /// 按日内时间排列，覆盖全天的每个周期
+    pub buckets: Vec<VolumeBucket>,
+}
+
+/// 按日内时间汇总已收盘K线的成交量
+fn volume_profile(klines: &[Kline], interval_ms: i64) -> (Decimal, Vec<VolumeBucket>) {
+    let mut buckets: Vec<VolumeBucket> = (0..DAY_MILLIS / interval_ms)
+        .map(|i| VolumeBucket {
+            offset_ms: i * interval_ms,
+            volume: Decimal::ZERO,
+            share: Decimal::ZERO,
+            samples: 0,
+        })
+        .collect();
+    for kline in klines.iter().filter(|kline| kline.is_closed) {
+        let offset = kline.open_time.timestamp_millis().rem_euclid(DAY_MILLIS);
+        let bucket = &mut buckets[(offset / interval_ms) as usize];
+        bucket.volume += kline.volume;
+        bucket.samples += 1;
+    }
+
+    let total: Decimal = buckets.iter().map(|bucket| bucket.volume).sum();
+    if total > Decimal::ZERO {
+        for bucket in &mut buckets {
+            bucket.share = (bucket.volume / total).round_dp(8);
+        }
+    }
+    (total, buckets)
+}
+
+/// 按历史K线统计日内成交量曲线，供交易引擎的VWAP算法分配子订单数量
+///
+/// 周期必须能整除一天，例如 1m、5m、15m、1h。
+pub async fn get_volume_profile(
+    State(state): State<AppState>,
+    Path((exchange, symbol, interval)): Path<(String, String, String)>,
+    Query(query): Query<VolumeProfileQuery>,
+) -> Result<Json<ApiResponse<VolumeProfileResponse>>, ApiError> {
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    let interval: Interval = interval
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+    let symbol = symbol.to_uppercase();
+
+    let interval_ms = interval.to_millis();
+    if interval_ms <= 0 || interval_ms >= DAY_MILLIS || DAY_MILLIS % interval_ms != 0 {
+        return Err(ApiError::BadRequest(format!(
+            "Interval {} cannot be used for an intraday volume profile",
+            interval.as_str()
+        )));
+    }
+    let end = query.end_time.unwrap_or_else(|| Utc::now().timestamp_millis());
+    let start = query
+        .start_time
+        .unwrap_or_else(|| end.saturating_sub(DEFAULT_PROFILE_DAYS * DAY_MILLIS));
+    if start >= end {
+        return Err(ApiError::BadRequest(
+            "Start time must be before end time".to_string(),
+        ));
+    }
+    if (end - start) / interval_ms > MAX_PROFILE_KLINES as i64 {
+        return Err(ApiError::BadRequest(format!(
+            "Volume profile range cannot exceed {} klines",
+            MAX_PROFILE_KLINES
+        )));
+    }
+
+    let klines = state
+        .market_stores
+        .klines
+        .klines(
+            exchange.clone(),
+            &symbol,
+            interval.clone(),
+            TimeRange {
+                start: millis_to_datetime(start),
+                end: millis_to_datetime(end),
+                limit: MAX_PROFILE_KLINES,
+                descending: false,
+            },
+        )
+        .await?;
+    let (total_volume, buckets) = volume_profile(&klines, interval_ms);
+
+    Ok(Json(ApiResponse::success(VolumeProfileResponse {
+        exchange: exchange.as_str().to_string(),
+        symbol,
+        interval: interval.as_str().to_string(),
+        interval_ms,
+        start_time: start,
+        end_time: end,
+        total_volume,
+        buckets,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::Decimal;
+    use shared_models::common::DataQuality;
+
+    fn kline(minute: i64) -> Kline {
+        let open_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minute);
+        Kline {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            interval: Interval::OneMinute,
+            open_time,
+            close_time: open_time + Duration::seconds(59),
+            open: Decimal::ONE,
+            high: Decimal::ONE,
+            low: Decimal::ONE,
+            close: Decimal::ONE,
+            volume: Decimal::ONE,
+            quote_volume: Decimal::ONE,
+            trades_count: 1,
+            taker_buy_base_volume: Decimal::ZERO,
+            taker_buy_quote_volume: Decimal::ZERO,
+            is_closed: true,
+            data_quality: DataQuality::Normal,
+        }
+    }
+
+    fn query(start_time: Option<i64>, end_time: Option<i64>, limit: Option<usize>) -> KlineHistoryQuery {
+        KlineHistoryQuery {
+            start_time,
+            end_time,
+            limit,
+            order: SortOrder::Desc,
+        }
+    }
+
+    #[test]
+    fn test_query_range() {
+        let now = 1_704_067_200_000;
+        let (range, limit) = query_range(&query(None, None, Some(10)), &Interval::OneMinute, now).unwrap();
+        assert_eq!(limit, 10);
+        assert_eq!(range.limit, 11);
+        assert!(range.descending);
+        assert_eq!(range.end.timestamp_millis(), now);
+        assert_eq!(range.start.timestamp_millis(), now - 600_000);
+
+        let (range, limit) = query_range(&query(Some(0), Some(now), None), &Interval::OneHour, now).unwrap();
+        assert_eq!(limit, DEFAULT_KLINE_LIMIT);
+        assert_eq!(range.start.timestamp_millis(), 0);
+
+        assert!(query_range(&query(None, None, Some(0)), &Interval::OneMinute, now).is_err());
+        assert!(query_range(&query(None, None, Some(MAX_KLINE_LIMIT + 1)), &Interval::OneMinute, now).is_err());
+        assert!(query_range(&query(Some(now), Some(now), None), &Interval::OneMinute, now).is_err());
+    }
+
+    #[test]
+    fn test_volume_profile() {
+        // 两天的 00:00 和 00:01，第二天 00:01 的成交量为 3
+        let mut klines: Vec<Kline> = [0, 1, 1440, 1441].into_iter().map(kline).collect();
+        klines[3].volume = Decimal::from(3);
+        let mut open = kline(2);
+        open.is_closed = false;
+        klines.push(open);
+
+        let (total, buckets) = volume_profile(&klines, Interval::OneMinute.to_millis());
+        assert_eq!(buckets.len(), 1440);
+        assert_eq!(total, Decimal::from(6));
+        assert_eq!(buckets[0].volume, Decimal::from(2));
+        assert_eq!(buckets[0].samples, 2);
+        assert_eq!(buckets[1].share, Decimal::new(66_666_667, 8));
+        assert_eq!(buckets[2].samples, 0);
+        assert_eq!(buckets[60].offset_ms, 3_600_000);
+    }
+
+    #[test]
+    fn test_take_page() {
+        let (page, cursor) = take_page((0..3).map(kline).collect(), 3, SortOrder::Asc);
+        assert_eq!(page.len(), 3);
+        assert!(cursor.is_none());
+
+        // 升序：下一页从多取的那条开始
+        let (page, cursor) = take_page((0..4).map(kline).collect(), 3, SortOrder::Asc);
+        assert_eq!(page.len(), 3);
+        assert_eq!(cursor, Some(kline(3).open_time.timestamp_millis()));
+
+        // 降序：下一页截止到本页最早的一条（不含）
+        let (page, cursor) = take_page((0..4).rev().map(kline).collect(), 3, SortOrder::Desc);
+        assert_eq!(page.last().unwrap().open_time, kline(1).open_time);
+        assert_eq!(cursor, Some(kline(1).open_time.timestamp_millis()));
+    }
+}
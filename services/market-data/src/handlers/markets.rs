@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{ApiError, ApiResponse};
+use crate::instruments::{build_markets, ExchangeInstruments, InstrumentStatus, Market, MarketFilter};
+use crate::AppState;
+
+/// 交易对查询参数
+#[derive(Debug, Deserialize)]
+pub struct MarketsQuery {
+    pub exchange: Option<String>,
+    pub base: Option<String>,
+    pub quote: Option<String>,
+    /// trading / limited / halted
+    pub status: Option<String>,
+    /// 计划下单金额，过滤掉最小下单金额更高的交易所
+    pub min_notional: Option<Decimal>,
+    pub connected_only: Option<bool>,
+}
+
+impl MarketsQuery {
+    fn to_filter(&self) -> Result<MarketFilter, ApiError> {
+        let status = self
+            .status
+            .as_deref()
+            .map(|s| s.parse::<InstrumentStatus>())
+            .transpose()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+        Ok(MarketFilter {
+            exchange: self.exchange.clone(),
+            base: self.base.clone(),
+            quote: self.quote.clone(),
+            status,
+            min_notional: self.min_notional,
+            connected_only: self.connected_only.unwrap_or(false),
+        })
+    }
+}
+
+/// 交易所元数据同步状态
+#[derive(Debug, Serialize)]
+pub struct InstrumentSyncStatus {
+    pub exchange: String,
+    pub instrument_count: usize,
+    pub synced_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+fn sync_statuses(instruments: HashMap<String, ExchangeInstruments>) -> Vec<InstrumentSyncStatus> {
+    let mut statuses: Vec<InstrumentSyncStatus> = instruments
+        .into_values()
+        .map(|synced| InstrumentSyncStatus {
+            exchange: synced.exchange,
+            instrument_count: synced.instruments.len(),
+            synced_at: synced.synced_at,
+            error: synced.error,
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.exchange.cmp(&b.exchange));
+    statuses
+}
+
+/// 交易对列表
+#[derive(Debug, Serialize)]
+pub struct MarketsResponse {
+    pub total: usize,
+    pub markets: Vec<Market>,
+    pub sources: Vec<InstrumentSyncStatus>,
+}
+
+/// 获取跨交易所聚合的可交易交易对
+pub async fn get_markets(
+    State(state): State<AppState>,
+    Query(query): Query<MarketsQuery>,
+) -> Result<Json<ApiResponse<MarketsResponse>>, ApiError> {
+    let filter = query.to_filter()?;
+    let instruments = state.instrument_sync.snapshot().await;
+    let stats = state.exchange_manager.get_all_stats().await;
+
+    let markets = build_markets(&instruments, &stats.connector_stats, &filter);
+
+    Ok(Json(ApiResponse::success(MarketsResponse {
+        total: markets.len(),
+        markets,
+        sources: sync_statuses(instruments),
+    })))
+}
+
+/// 立即同步交易对元数据
+pub async fn sync_instruments(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<InstrumentSyncStatus>>>, ApiError> {
+    if !state.instrument_sync.is_enabled() {
+        return Err(ApiError::ServiceUnavailable(
+            "Instrument metadata sync is disabled".to_string(),
+        ));
+    }
+
+    let exchanges: Vec<_> = state
+        .config
+        .enabled_exchanges()
+        .into_iter()
+        .map(|(name, config)| (name.clone(), config.clone()))
+        .collect();
+    state
+        .instrument_sync
+        .sync_all(&exchanges, state.exchange_manager.registry())
+        .await;
+
+    let instruments = state.instrument_sync.snapshot().await;
+    Ok(Json(ApiResponse::success(sync_statuses(instruments))))
+}
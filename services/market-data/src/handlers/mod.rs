@@ -1,10 +1,29 @@
+pub mod alerts;
+pub mod analytics;
+pub mod batch;
+pub mod chart;
+pub mod compaction;
+pub mod connectivity;
+pub mod continuity;
+pub mod depth_history;
 pub mod health;
+pub mod klines;
 pub mod market_data;
+pub mod markets;
 pub mod metrics;
+pub mod pipeline_latency;
+pub mod rollups;
+pub mod shards;
+pub mod sse;
+pub mod stream;
+pub mod subscriptions;
+pub mod ticker;
+pub mod trades;
 pub mod websocket;
+pub mod ws_clients;
 
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 
@@ -26,26 +45,135 @@ pub fn create_routes() -> Router<AppState> {
         .route("/health/detailed", get(health::detailed_health_handler))
         // 指标
         .route("/metrics", get(metrics_handler))
+        // 交易对分片
+        .route("/api/v1/shards", get(shards::get_shards))
+        .route("/api/v1/shards/route", get(shards::get_shard_route))
         // WebSocket连接
         .route("/ws", get(websocket_handler))
+        .route("/ws/stream", get(stream::resumable_stream_websocket))
+        // SSE（无法使用WebSocket的客户端）
+        .route("/sse", get(sse::market_stream_sse))
         // 市场数据API
         .route("/api/v1/tick/:exchange/:symbol", get(get_latest_tick))
         .route(
             "/api/v1/kline/:exchange/:symbol/:interval",
             get(get_latest_kline),
         )
+        .route(
+            "/api/v1/klines/:exchange/:symbol/:interval",
+            get(klines::get_kline_history),
+        )
+        .route(
+            "/api/v1/volume-profile/:exchange/:symbol/:interval",
+            get(klines::get_volume_profile),
+        )
         .route(
             "/api/v1/orderbook/:exchange/:symbol",
             get(get_latest_orderbook),
         )
+        .route(
+            "/api/v1/orderbook/:exchange/:symbol/history",
+            get(depth_history::get_depth_history),
+        )
+        .route(
+            "/api/v1/orderbook/:exchange/:symbol/at",
+            get(depth_history::get_orderbook_at),
+        )
         .route("/api/v1/trade/:exchange/:symbol", get(get_latest_trade))
+        // K线连续性
+        .route("/api/v1/continuity/gaps", get(continuity::get_continuity_gaps))
+        .route(
+            "/api/v1/continuity/candle-close",
+            get(continuity::get_candle_close_stats),
+        )
+        // 批量查询
+        .route("/api/v1/ticks/batch", post(batch::get_ticks_batch))
+        .route("/api/v1/klines/batch", post(batch::get_klines_batch))
+        // 成交明细
+        .route(
+            "/api/v1/trades/:exchange/:symbol/history",
+            get(trades::get_trade_history),
+        )
+        .route(
+            "/ws/trades/:exchange/:symbol",
+            get(trades::trade_stream_websocket),
+        )
+        // 24小时滚动行情
+        .route("/api/v1/ticker/24hr", get(ticker::get_all_tickers_24hr))
+        .route(
+            "/api/v1/ticker/24hr/:exchange/:symbol",
+            get(ticker::get_ticker_24hr),
+        )
+        // 分析指标
+        .route(
+            "/api/v1/depth-metrics/:exchange/:symbol",
+            get(analytics::get_depth_metrics),
+        )
+        .route(
+            "/api/v1/whales/:exchange/:symbol",
+            get(analytics::get_whale_trades),
+        )
+        .route(
+            "/api/v1/seasonality/:exchange/:symbol",
+            get(analytics::get_seasonality),
+        )
+        .route(
+            "/api/v1/analytics/query",
+            post(analytics::run_analytics_query),
+        )
+        // 告警规则
+        .route(
+            "/api/v1/alerts/rules",
+            get(alerts::list_alert_rules).post(alerts::create_alert_rule),
+        )
+        .route("/api/v1/alerts/rules/:id", delete(alerts::delete_alert_rule))
+        // 图表API
+        .route("/api/v1/chart/:exchange/:symbol", get(chart::get_chart))
         // 元数据API
         .route("/api/v1/symbols", get(get_symbols))
         .route("/api/v1/exchanges", get(get_exchanges))
+        .route("/api/v1/markets", get(markets::get_markets))
         // 管理API
         .route("/api/v1/admin/stats", get(market_data::get_stats))
+        .route(
+            "/api/v1/admin/connectivity",
+            get(connectivity::get_connectivity),
+        )
+        .route(
+            "/api/v1/admin/pipeline-latency",
+            get(pipeline_latency::get_pipeline_latency),
+        )
+        .route("/api/v1/admin/ws/clients", get(ws_clients::list_ws_clients))
+        .route(
+            "/api/v1/admin/subscriptions",
+            get(subscriptions::list_subscriptions)
+                .post(subscriptions::add_subscriptions)
+                .delete(subscriptions::remove_subscriptions),
+        )
+        .route(
+            "/api/v1/admin/instruments/sync",
+            post(markets::sync_instruments),
+        )
         .route("/api/v1/admin/flush", post(market_data::flush_buffers))
         .route("/api/v1/admin/reset-stats", post(market_data::reset_stats))
+        .route(
+            "/api/v1/admin/compaction/run",
+            post(compaction::run_compaction),
+        )
+        .route(
+            "/api/v1/admin/compaction/policies",
+            get(compaction::get_compaction_policies),
+        )
+        .route(
+            "/api/v1/admin/compaction/report",
+            get(compaction::get_compaction_report),
+        )
+        .route("/api/v1/admin/rollups", get(rollups::get_rollup_status))
+        .route(
+            "/api/v1/admin/rollups/check",
+            post(rollups::check_rollup_consistency),
+        )
+        .route("/api/v1/admin/rollups/rebuild", post(rollups::rebuild_rollup))
 }
 
 /// API响应结构
@@ -99,6 +227,9 @@ pub enum ApiError {
 
     #[error("Unauthorized")]
     Unauthorized,
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
 }
 
 impl ApiError {
@@ -111,6 +242,7 @@ impl ApiError {
             ApiError::ServiceUnavailable(_) => axum::http::StatusCode::SERVICE_UNAVAILABLE,
             ApiError::RateLimitExceeded => axum::http::StatusCode::TOO_MANY_REQUESTS,
             ApiError::Unauthorized => axum::http::StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => axum::http::StatusCode::FORBIDDEN,
         }
     }
 
@@ -122,6 +254,18 @@ impl ApiError {
     }
 }
 
+/// 网关认证后转发的用户ID请求头
+pub const USER_ID_HEADER: &str = "x-user-id";
+
+/// 读取网关根据JWT设置的用户ID，缺失或格式错误时返回 401
+pub fn authenticated_user(headers: &axum::http::HeaderMap) -> Result<uuid::Uuid, ApiError> {
+    headers
+        .get(USER_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .ok_or(ApiError::Unauthorized)
+}
+
 /// 实现从anyhow::Error的转换
 impl From<anyhow::Error> for ApiError {
     fn from(err: anyhow::Error) -> Self {
@@ -308,6 +452,19 @@ mod tests {
         assert!(invalid_params.validate().is_err());
     }
 
+    #[test]
+    fn test_authenticated_user() {
+        let mut headers = axum::http::HeaderMap::new();
+        assert!(matches!(authenticated_user(&headers), Err(ApiError::Unauthorized)));
+
+        headers.insert(USER_ID_HEADER, "not-a-uuid".parse().unwrap());
+        assert!(matches!(authenticated_user(&headers), Err(ApiError::Unauthorized)));
+
+        let user_id = uuid::Uuid::new_v4();
+        headers.insert(USER_ID_HEADER, user_id.to_string().parse().unwrap());
+        assert_eq!(authenticated_user(&headers).unwrap(), user_id);
+    }
+
     #[test]
     fn test_api_error() {
         let error = ApiError::NotFound("Resource not found".to_string());
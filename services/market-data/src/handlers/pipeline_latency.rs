@@ -0,0 +1,27 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use super::{ApiError, ApiResponse};
+use crate::latency::SymbolLatency;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct PipelineLatencyQuery {
+    pub exchange: Option<String>,
+    pub symbol: Option<String>,
+}
+
+/// 获取行情管道各阶段的延迟分位数（收到→解析→处理→存储→分发），按交易所/交易对分组
+pub async fn get_pipeline_latency(
+    State(state): State<AppState>,
+    Query(query): Query<PipelineLatencyQuery>,
+) -> Result<Json<ApiResponse<Vec<SymbolLatency>>>, ApiError> {
+    let latency = state
+        .exchange_manager
+        .pipeline_latency(query.exchange.as_deref(), query.symbol.as_deref())
+        .await;
+    Ok(Json(ApiResponse::success(latency)))
+}
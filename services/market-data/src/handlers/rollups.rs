@@ -0,0 +1,130 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use shared_models::common::{CommonError, Exchange, Interval};
+
+use super::{ApiError, ApiResponse};
+use crate::charting::millis_to_datetime;
+use crate::config::RollupConfig;
+use crate::rollups::ConsistencyReport;
+use crate::AppState;
+
+/// 物化汇总状态
+#[derive(Debug, Serialize)]
+pub struct RollupStatus {
+    pub enabled: bool,
+    pub config: RollupConfig,
+    pub last_check: Vec<ConsistencyReport>,
+}
+
+/// 一致性检查/重建请求
+#[derive(Debug, Deserialize)]
+pub struct RollupRangeRequest {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    /// 默认为配置的回看时长
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    /// 检查时发现差异是否重建
+    pub repair: Option<bool>,
+}
+
+impl RollupRangeRequest {
+    fn parse(
+        &self,
+        config: &RollupConfig,
+    ) -> Result<(Exchange, Interval, chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>), ApiError> {
+        let exchange: Exchange = self
+            .exchange
+            .parse()
+            .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+        let interval: Interval = self
+            .interval
+            .parse()
+            .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+        if !config.has_interval(&interval) {
+            return Err(ApiError::BadRequest(format!(
+                "Interval {} is not materialized",
+                interval
+            )));
+        }
+
+        let end_time = self
+            .end_time
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+        let start_time = self.start_time.unwrap_or(
+            end_time - config.consistency_lookback_hours as i64 * 3_600_000,
+        );
+        if start_time >= end_time {
+            return Err(ApiError::BadRequest(
+                "Start time must be before end time".to_string(),
+            ));
+        }
+
+        Ok((
+            exchange,
+            interval,
+            millis_to_datetime(start_time),
+            millis_to_datetime(end_time),
+        ))
+    }
+}
+
+/// 获取物化汇总状态和最近一次一致性检查结果
+pub async fn get_rollup_status(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<RollupStatus>>, ApiError> {
+    Ok(Json(ApiResponse::success(RollupStatus {
+        enabled: state.rollups.is_enabled(),
+        config: state.rollups.config().clone(),
+        last_check: state.rollups.last_check().await,
+    })))
+}
+
+/// 对比汇总表与源数据
+pub async fn check_rollup_consistency(
+    State(state): State<AppState>,
+    Json(request): Json<RollupRangeRequest>,
+) -> Result<Json<ApiResponse<ConsistencyReport>>, ApiError> {
+    if !state.rollups.is_enabled() {
+        return Err(ApiError::ServiceUnavailable("K-line rollups are disabled".to_string()));
+    }
+    let (exchange, interval, start, end) = request.parse(state.rollups.config())?;
+
+    let report = state
+        .rollups
+        .check_consistency(
+            exchange.as_str(),
+            &request.symbol,
+            &interval,
+            start,
+            end,
+            request.repair.unwrap_or(false),
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::success(report)))
+}
+
+/// 从源数据重建汇总表的时间范围
+pub async fn rebuild_rollup(
+    State(state): State<AppState>,
+    Json(request): Json<RollupRangeRequest>,
+) -> Result<Json<ApiResponse<String>>, ApiError> {
+    if !state.rollups.is_enabled() {
+        return Err(ApiError::ServiceUnavailable("K-line rollups are disabled".to_string()));
+    }
+    let (exchange, interval, start, end) = request.parse(state.rollups.config())?;
+
+    state
+        .rollups
+        .rebuild(exchange.as_str(), &request.symbol, &interval, start, end)
+        .await?;
+
+    Ok(Json(ApiResponse::success(format!(
+        "Rebuilt {} rollup for {}:{}",
+        interval,
+        exchange,
+        request.symbol.to_uppercase()
+    ))))
+}
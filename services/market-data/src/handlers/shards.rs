@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use shared_utils::sharding::{ShardAssignments, ShardMember};
+
+use super::{ApiError, ApiResponse};
+use crate::AppState;
+
+/// 分片状态
+#[derive(Debug, Serialize)]
+pub struct ShardStatus {
+    pub enabled: bool,
+    pub instance_id: String,
+    pub owned_symbols: usize,
+    pub assignments: ShardAssignments,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShardRouteQuery {
+    pub exchange: String,
+    pub symbol: String,
+}
+
+/// 获取当前分片分配
+pub async fn get_shards(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<ShardStatus>>, ApiError> {
+    let coordinator = &state.shard_coordinator;
+    Ok(Json(ApiResponse::success(ShardStatus {
+        enabled: coordinator.is_enabled(),
+        instance_id: coordinator.instance_id().to_string(),
+        owned_symbols: coordinator.owned_count().await,
+        assignments: coordinator.assignments().await,
+    })))
+}
+
+/// 查询交易对所属的实例，供网关和客户端路由订阅
+pub async fn get_shard_route(
+    State(state): State<AppState>,
+    Query(query): Query<ShardRouteQuery>,
+) -> Result<Json<ApiResponse<ShardMember>>, ApiError> {
+    let assignments = state.shard_coordinator.assignments().await;
+    assignments
+        .owner(&query.exchange, &query.symbol)
+        .cloned()
+        .map(|member| Json(ApiResponse::success(member)))
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No shard owns {}:{}",
+                query.exchange, query.symbol
+            ))
+        })
+}
@@ -0,0 +1,250 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use super::{stream::feed_for, ApiError};
+use crate::websocket::{DataEntitlement, EventFilter, ReplayResult, SequencedEvent};
+use crate::AppState;
+
+/// SSE订阅参数，与WebSocket订阅过滤条件一致，多个值用逗号分隔
+#[derive(Debug, Deserialize)]
+pub struct SseQuery {
+    pub exchanges: Option<String>,
+    pub symbols: Option<String>,
+    pub types: Option<String>,
+    pub min_notional: Option<rust_decimal::Decimal>,
+    /// 不支持自定义请求头的客户端可通过参数传入续传位置
+    pub last_event_id: Option<String>,
+}
+
+/// SSE续传位置，事件ID格式为 `{epoch}:{序号}`
+///
+/// 序号只在同一广播器实例内有效，见 [`WebSocketBroadcaster::epoch`](crate::websocket::WebSocketBroadcaster::epoch)。
+/// 不带实例标识的旧格式ID无法确认来源，按不可续传处理。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResumePosition {
+    epoch: String,
+    sequence: u64,
+}
+
+impl ResumePosition {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (epoch, sequence) = value.rsplit_once(':').unwrap_or(("", value));
+        Some(Self {
+            epoch: epoch.to_string(),
+            sequence: sequence.parse().ok()?,
+        })
+    }
+
+    /// 续传位置是否属于当前广播器，与WebSocket会话的判断一致
+    fn is_resumable(&self, epoch: &str, head: u64) -> bool {
+        self.epoch == epoch && self.sequence <= head
+    }
+}
+
+fn event_id(epoch: &str, sequence: u64) -> String {
+    format!("{}:{}", epoch, sequence)
+}
+
+fn split_list(value: Option<String>, uppercase: bool) -> Option<Vec<String>> {
+    let items: Vec<String> = value?
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            if uppercase {
+                item.to_uppercase()
+            } else {
+                item.to_lowercase()
+            }
+        })
+        .collect();
+
+    if items.is_empty() {
+        None
+    } else {
+        Some(items)
+    }
+}
+
+impl SseQuery {
+    fn filter(&self) -> EventFilter {
+        EventFilter {
+            exchanges: split_list(self.exchanges.clone(), false),
+            symbols: split_list(self.symbols.clone(), true),
+            event_types: split_list(self.types.clone(), false),
+            min_notional: self.min_notional,
+        }
+    }
+}
+
+fn to_sse_event(epoch: &str, event: &SequencedEvent) -> Event {
+    match event.event.to_json() {
+        Ok(json) => Event::default()
+            .id(event_id(epoch, event.id))
+            .event(event.event.event_type())
+            .data(json),
+        Err(e) => Event::default()
+            .event("error")
+            .data(json!({ "message": e.to_string() }).to_string()),
+    }
+}
+
+/// 市场数据SSE订阅
+///
+/// 与WebSocket共用广播器；支持通过 Last-Event-ID 请求头或 last_event_id 参数断线续传。
+/// 没有实时行情权限的用户订阅延迟广播器，续传序号也属于延迟广播器。
+/// 续传位置来自其他广播器实例（服务重启或切换实例）时发送 `reset` 的resync事件，
+/// 客户端需要重新获取快照。
+pub async fn market_stream_sse(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SseQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let last_event_id = match headers.get("last-event-id") {
+        Some(value) => Some(value.to_str().unwrap_or_default()),
+        None => query.last_event_id.as_deref(),
+    };
+    let resume = last_event_id
+        .map(|value| {
+            ResumePosition::parse(value).ok_or_else(|| ApiError::BadRequest("Invalid Last-Event-ID".to_string()))
+        })
+        .transpose()?;
+    let filter = query.filter();
+    let broadcaster = feed_for(&state, DataEntitlement::from_headers(&state.config.delayed_data, &headers));
+
+    // 先记录最新序号再按主题订阅实时流，订阅期间发布的事件序号都大于该值，不会被跳过
+    let head = broadcaster.last_event_id();
+    let epoch = broadcaster.epoch().to_string();
+    let receiver = broadcaster.subscribe_topics(&filter);
+
+    let mut initial = Vec::new();
+    let mut last_sent = head;
+    match resume {
+        // 续传位置来自重启前或其他实例的广播器，序号在当前实例中没有意义，从最新位置重新开始
+        Some(position) if !position.is_resumable(&epoch, head) => {
+            initial.push(
+                Event::default().event("resync").data(
+                    json!({
+                        "last_event_id": event_id(&position.epoch, position.sequence),
+                        "oldest_available": null,
+                        "reset": true,
+                    })
+                    .to_string(),
+                ),
+            );
+            debug!(
+                "SSE resume position {}:{} does not belong to broadcaster {} (head {}), resetting",
+                position.epoch, position.sequence, epoch, head
+            );
+        }
+        Some(position) => {
+            let last_event_id = position.sequence;
+            let replayed = match broadcaster.replay_since(last_event_id).await {
+                ReplayResult::Complete(events) => events,
+                ReplayResult::Gap {
+                    oldest_available,
+                    events,
+                } => {
+                    // 请求的位置已超出缓冲区，通知客户端需要重新获取快照
+                    initial.push(
+                        Event::default().event("resync").data(
+                            json!({
+                                "last_event_id": event_id(&epoch, last_event_id),
+                                "oldest_available": event_id(&epoch, oldest_available),
+                            })
+                            .to_string(),
+                        ),
+                    );
+                    events
+                }
+            };
+
+            last_sent = last_event_id;
+            for event in replayed {
+                last_sent = event.id;
+                if filter.matches(&event.event) {
+                    initial.push(to_sse_event(&epoch, &event));
+                }
+            }
+            debug!("SSE resumed from {}, replayed up to {}", last_event_id, last_sent);
+        }
+        None => {}
+    }
+
+    info!(
+        "SSE stream opened (exchanges: {:?}, symbols: {:?}, types: {:?})",
+        filter.exchanges, filter.symbols, filter.event_types
+    );
+
+    let live = stream::unfold(
+        (receiver, filter, epoch, last_sent),
+        |(mut receiver, filter, epoch, mut last_sent)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        // 已在续传阶段发送过的事件
+                        if event.id <= last_sent {
+                            continue;
+                        }
+                        last_sent = event.id;
+                        if !filter.matches(&event.event) {
+                            continue;
+                        }
+                        let sse_event = to_sse_event(&epoch, &event);
+                        return Some((sse_event, (receiver, filter, epoch, last_sent)));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("SSE stream lagged, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+
+    let stream = stream::iter(initial).chain(live).map(Ok);
+
+    let keep_alive = KeepAlive::new()
+        .interval(Duration::from_secs(state.config.websocket.heartbeat_interval.max(1)))
+        .text("heartbeat");
+
+    Ok(Sse::new(stream).keep_alive(keep_alive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_position_requires_matching_epoch() {
+        let position = ResumePosition::parse(&event_id("a1", 42)).unwrap();
+        assert_eq!(position.epoch, "a1");
+        assert_eq!(position.sequence, 42);
+        assert!(position.is_resumable("a1", 42));
+        assert!(position.is_resumable("a1", 100));
+
+        // 其他实例分配的序号即使落在当前范围内也不能续传
+        assert!(!position.is_resumable("b2", 100));
+        // 同一实例内不会出现超过最新序号的位置
+        assert!(!position.is_resumable("a1", 41));
+
+        // 不带实例标识的旧格式ID只能全量重新同步
+        let legacy = ResumePosition::parse("42").unwrap();
+        assert!(!legacy.is_resumable("a1", 100));
+
+        assert!(ResumePosition::parse("a1:").is_none());
+        assert!(ResumePosition::parse("a1:x").is_none());
+    }
+}
@@ -0,0 +1,394 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::HeaderMap,
+    response::Response,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use shared_protocols::kafka::KafkaTopics;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::websocket::{
+    ClientQuota, ClientStats, DataEntitlement, DisconnectReason, QuotaViolation, ReplayResult,
+    SequencedEvent, StreamSession, SubscriptionSet, TopicSubscription, WebSocketBroadcaster,
+};
+use crate::AppState;
+
+/// 会话持久化间隔
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 可恢复流连接参数
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// 上次连接下发的会话令牌
+    pub session: Option<String>,
+}
+
+/// 客户端请求
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientRequest {
+    Subscribe {
+        #[serde(default)]
+        exchanges: Vec<String>,
+        #[serde(default)]
+        symbols: Vec<String>,
+        #[serde(default)]
+        types: Vec<String>,
+        min_notional: Option<rust_decimal::Decimal>,
+    },
+    Unsubscribe {
+        #[serde(default)]
+        exchanges: Vec<String>,
+        #[serde(default)]
+        symbols: Vec<String>,
+        #[serde(default)]
+        types: Vec<String>,
+    },
+    Ping,
+}
+
+fn event_message(event: &SequencedEvent) -> Option<String> {
+    serde_json::to_string(&json!({ "seq": event.id, "event": event.event })).ok()
+}
+
+fn quota_error(quota: &ClientQuota, violation: &QuotaViolation) -> serde_json::Value {
+    json!({
+        "type": "error",
+        "code": violation.code(),
+        "message": violation.message(quota.tier()),
+        "quota": violation,
+    })
+}
+
+/// 上报本周期的订阅用量，供计费系统统计
+async fn report_usage(state: &AppState, quota: &mut ClientQuota) {
+    let usage = quota.take_usage(chrono::Utc::now().timestamp_millis());
+    if usage.messages == 0 && usage.throttled == 0 {
+        return;
+    }
+    let key = usage.user_id.clone().unwrap_or_else(|| usage.client_id.to_string());
+    if let Err(e) = state
+        .kafka_publisher
+        .publish(KafkaTopics::MARKET_WS_USAGE, &key, "ws_usage", &usage)
+        .await
+    {
+        warn!("Failed to report WebSocket usage for {}: {}", key, e);
+    }
+}
+
+/// 按行情权限选择广播器，延迟用户只接收延迟后的事件
+pub(crate) fn feed_for(state: &AppState, entitlement: DataEntitlement) -> &Arc<WebSocketBroadcaster> {
+    if entitlement.is_delayed() {
+        state.delayed_feed.broadcaster()
+    } else {
+        &state.broadcaster
+    }
+}
+
+/// 按会话订阅集合更新主题路由
+fn route_subscriptions(events: &TopicSubscription, subscriptions: &SubscriptionSet) {
+    if subscriptions.is_empty() {
+        events.clear();
+    } else {
+        events.route(&subscriptions.to_filter());
+    }
+}
+
+/// 可恢复的市场数据WebSocket
+///
+/// 服务端下发会话令牌并保存订阅集合和最后推送序号，客户端携带令牌重连后恢复订阅并补发断线期间的事件。
+///
+/// 订阅的交易对数和每分钟推送的消息数受用户等级的配额限制，用量定期上报供计费。
+/// 没有实时行情权限的用户接收延迟行情，会话序号属于延迟广播器。
+pub async fn resumable_stream_websocket(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_stream(socket, state, query.session, headers))
+}
+
+async fn handle_stream(socket: WebSocket, state: AppState, token: Option<String>, headers: HeaderMap) {
+    let client = state.ws_clients.register("/ws/stream").await;
+    let mut quota = ClientQuota::from_headers(&state.config.websocket.quotas, client.id, &headers);
+    let entitlement = DataEntitlement::from_headers(&state.config.delayed_data, &headers);
+    let reason = run_stream(socket, &state, token, &client, &mut quota, entitlement).await;
+    report_usage(&state, &mut quota).await;
+    state
+        .ws_clients
+        .unregister(client.id, reason, feed_for(&state, entitlement).last_event_id())
+        .await;
+}
+
+async fn run_stream(
+    socket: WebSocket,
+    state: &AppState,
+    token: Option<String>,
+    client: &ClientStats,
+    quota: &mut ClientQuota,
+    entitlement: DataEntitlement,
+) -> DisconnectReason {
+    let (mut sender, mut receiver) = socket.split();
+    let broadcaster = feed_for(state, entitlement);
+
+    // 先注册订阅，恢复会话后在补发前设置主题，避免遗漏
+    let mut events = broadcaster.subscribe_unrouted();
+
+    // 1. 恢复或创建会话，行情权限变化后旧会话的序号不再适用
+    let restored = match &token {
+        Some(token) => state
+            .stream_sessions
+            .load(token)
+            .await
+            .filter(|session| session.delayed == entitlement.is_delayed()),
+        None => None,
+    };
+    let resumed = restored.is_some();
+    let mut session = restored.unwrap_or_else(|| StreamSession::new(broadcaster.last_event_id()));
+    session.delayed = entitlement.is_delayed();
+
+    // 服务重启或换了实例后旧序号不再有效，从最新序号继续并提示客户端重新同步
+    let head = broadcaster.last_event_id();
+    let stale_sequence = (resumed && !session.is_resumable(broadcaster.epoch(), head)).then_some(session.last_sequence);
+    if stale_sequence.is_some() {
+        session.last_sequence = head;
+    }
+    session.epoch = broadcaster.epoch().to_string();
+
+    // 恢复的订阅超过当前等级配额时清空，由客户端重新订阅
+    let restored_violation = quota.check_subscriptions(&session.subscriptions).err();
+    if restored_violation.is_some() {
+        session.subscriptions = SubscriptionSet::default();
+    }
+
+    let hello = json!({
+        "type": "session",
+        "token": session.token,
+        "resumed": resumed,
+        "last_sequence": session.last_sequence,
+        "subscriptions": session.subscriptions,
+        "tier": quota.tier(),
+        "entitlement": entitlement,
+    });
+    if sender.send(Message::Text(hello.to_string())).await.is_err() {
+        return DisconnectReason::SendFailed;
+    }
+    if let Some(violation) = restored_violation {
+        if sender.send(Message::Text(quota_error(quota, &violation).to_string())).await.is_err() {
+            return DisconnectReason::SendFailed;
+        }
+    }
+    if let Some(stale_sequence) = stale_sequence {
+        let resync = json!({
+            "type": "resync",
+            "last_sequence": stale_sequence,
+            "oldest_available": null,
+            "truncated": false,
+            "reset": true,
+        });
+        if sender.send(Message::Text(resync.to_string())).await.is_err() {
+            return DisconnectReason::SendFailed;
+        }
+    }
+    client.set_subscriptions(session.subscriptions.labels()).await;
+    route_subscriptions(&events, &session.subscriptions);
+
+    // 2. 补发断线期间的事件
+    let mut replayed_until = session.last_sequence;
+    if resumed && stale_sequence.is_none() {
+        let max_replay = state.config.websocket.max_replay_events;
+        let (replayed, gap) = match broadcaster.replay_since(session.last_sequence).await {
+            ReplayResult::Complete(events) => (events, None),
+            ReplayResult::Gap {
+                oldest_available,
+                events,
+            } => (events, Some(oldest_available)),
+        };
+
+        let replay_tail = replayed.last().map(|e| e.id).unwrap_or(0);
+        let matching: Vec<SequencedEvent> = replayed
+            .into_iter()
+            .filter(|e| session.subscriptions.matches(&e.event))
+            .collect();
+        let truncated = matching.len() > max_replay;
+        let skip = matching.len().saturating_sub(max_replay);
+
+        if gap.is_some() || truncated {
+            let resync = json!({
+                "type": "resync",
+                "last_sequence": session.last_sequence,
+                "oldest_available": gap,
+                "truncated": truncated,
+            });
+            if sender.send(Message::Text(resync.to_string())).await.is_err() {
+                return DisconnectReason::SendFailed;
+            }
+        }
+
+        info!(
+            "WebSocket session {} resumed from {}, replaying {} events",
+            session.token,
+            session.last_sequence,
+            matching.len() - skip
+        );
+        for event in matching.into_iter().skip(skip) {
+            session.last_sequence = event.id;
+            if let Err(violation) = quota.acquire_message(chrono::Utc::now().timestamp_millis()) {
+                if let Some(violation) = violation {
+                    if sender.send(Message::Text(quota_error(quota, &violation).to_string())).await.is_err() {
+                        return DisconnectReason::SendFailed;
+                    }
+                }
+                client.record_dropped(1);
+                continue;
+            }
+            if let Some(text) = event_message(&event) {
+                let bytes = text.len();
+                if sender.send(Message::Text(text)).await.is_err() {
+                    return DisconnectReason::SendFailed;
+                }
+                client.record_sent(bytes, Some(event.id));
+                quota.record_sent(bytes);
+            }
+        }
+        replayed_until = replayed_until.max(replay_tail);
+    }
+    state.stream_sessions.save(&session).await;
+
+    // 3. 实时推送
+    let mut save_timer = tokio::time::interval(SESSION_SAVE_INTERVAL);
+    let mut usage_timer = tokio::time::interval(Duration::from_secs(
+        state.config.websocket.quotas.usage_report_interval_seconds.max(1),
+    ));
+    usage_timer.tick().await;
+    let mut dirty = false;
+
+    let reason = loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket session {} lagged, skipped {} events", session.token, skipped);
+                        client.record_dropped(skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break DisconnectReason::ServerShutdown,
+                };
+
+                let conflated = events.take_conflated();
+                if conflated > 0 {
+                    client.record_conflated(conflated);
+                }
+
+                if event.id <= replayed_until || !session.subscriptions.matches(&event.event) {
+                    continue;
+                }
+
+                session.last_sequence = event.id;
+                dirty = true;
+                if let Err(violation) = quota.acquire_message(chrono::Utc::now().timestamp_millis()) {
+                    if let Some(violation) = violation {
+                        if sender.send(Message::Text(quota_error(quota, &violation).to_string())).await.is_err() {
+                            break DisconnectReason::SendFailed;
+                        }
+                    }
+                    client.record_dropped(1);
+                    continue;
+                }
+                if let Some(text) = event_message(&event) {
+                    let bytes = text.len();
+                    if sender.send(Message::Text(text)).await.is_err() {
+                        break DisconnectReason::SendFailed;
+                    }
+                    client.record_sent(bytes, Some(event.id));
+                    quota.record_sent(bytes);
+                }
+            }
+            msg = receiver.next() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Ping(payload))) => {
+                        if sender.send(Message::Pong(payload)).await.is_err() {
+                            break DisconnectReason::SendFailed;
+                        }
+                        continue;
+                    }
+                    Some(Ok(Message::Close(_))) | None => break DisconnectReason::ClientClosed,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        debug!("WebSocket session {} receive error: {}", session.token, e);
+                        break DisconnectReason::ReceiveError(e.to_string());
+                    }
+                };
+                client.record_received(text.len());
+
+                let reply = match serde_json::from_str::<ClientRequest>(&text) {
+                    Ok(ClientRequest::Subscribe { exchanges, symbols, types, min_notional }) => {
+                        let mut subscriptions = session.subscriptions.clone();
+                        subscriptions.subscribe(&exchanges, &symbols, &types, min_notional);
+                        match quota.check_subscriptions(&subscriptions) {
+                            Ok(()) => {
+                                session.subscriptions = subscriptions;
+                                route_subscriptions(&events, &session.subscriptions);
+                                state.stream_sessions.save(&session).await;
+                                client.set_subscriptions(session.subscriptions.labels()).await;
+                                json!({ "type": "subscribed", "subscriptions": session.subscriptions })
+                            }
+                            Err(violation) => {
+                                debug!("WebSocket session {} exceeded subscription quota: {:?}", session.token, violation);
+                                quota_error(quota, &violation)
+                            }
+                        }
+                    }
+                    Ok(ClientRequest::Unsubscribe { exchanges, symbols, types }) => {
+                        session.subscriptions.unsubscribe(&exchanges, &symbols, &types);
+                        // 取消全部交易对但保留事件类型时相当于订阅全部交易对，直接清空
+                        if quota.check_subscriptions(&session.subscriptions).is_err() {
+                            session.subscriptions = SubscriptionSet::default();
+                            let _ = quota.check_subscriptions(&session.subscriptions);
+                        }
+                        route_subscriptions(&events, &session.subscriptions);
+                        state.stream_sessions.save(&session).await;
+                        client.set_subscriptions(session.subscriptions.labels()).await;
+                        json!({ "type": "unsubscribed", "subscriptions": session.subscriptions })
+                    }
+                    Ok(ClientRequest::Ping) => {
+                        json!({ "type": "pong", "timestamp": chrono::Utc::now().timestamp_millis() })
+                    }
+                    Err(e) => json!({ "type": "error", "message": format!("Invalid request: {}", e) }),
+                };
+
+                if sender.send(Message::Text(reply.to_string())).await.is_err() {
+                    break DisconnectReason::SendFailed;
+                }
+            }
+            _ = save_timer.tick() => {
+                if dirty {
+                    state.stream_sessions.save(&session).await;
+                    dirty = false;
+                }
+            }
+            _ = usage_timer.tick() => {
+                report_usage(state, quota).await;
+            }
+        }
+    };
+
+    // 4. 断开时保存最后推送位置
+    state.stream_sessions.save(&session).await;
+    info!(
+        "WebSocket session {} disconnected at sequence {} ({:?})",
+        session.token, session.last_sequence, reason
+    );
+    reason
+}
@@ -0,0 +1,101 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{ApiError, ApiResponse};
+use crate::connectors::runtime_subscriptions::{validate_data_type, RuntimeSubscriptions};
+use crate::AppState;
+
+/// 订阅变更请求
+#[derive(Debug, Deserialize)]
+pub struct SubscriptionRequest {
+    pub exchange: String,
+    pub symbols: Vec<String>,
+    /// ticker / trade / depth / kline_<interval>
+    pub data_types: Vec<String>,
+}
+
+impl SubscriptionRequest {
+    fn normalize(self) -> Result<(String, Vec<String>, Vec<String>), ApiError> {
+        let symbols: Vec<String> = self
+            .symbols
+            .iter()
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if symbols.is_empty() {
+            return Err(ApiError::BadRequest("At least one symbol is required".to_string()));
+        }
+        if self.data_types.is_empty() {
+            return Err(ApiError::BadRequest("At least one data type is required".to_string()));
+        }
+        for data_type in &self.data_types {
+            validate_data_type(data_type).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        }
+
+        Ok((self.exchange.to_lowercase(), symbols, self.data_types))
+    }
+}
+
+/// 订阅状态
+#[derive(Debug, Serialize)]
+pub struct SubscriptionsResponse {
+    /// exchange -> symbol -> data_types
+    pub active: HashMap<String, HashMap<String, Vec<String>>>,
+    /// 相对静态配置的运行时变更
+    pub runtime_changes: RuntimeSubscriptions,
+    pub persistent: bool,
+}
+
+async fn build_response(state: &AppState, runtime_changes: RuntimeSubscriptions) -> SubscriptionsResponse {
+    let stats = state.exchange_manager.get_all_stats().await;
+    SubscriptionsResponse {
+        active: stats
+            .connector_stats
+            .into_iter()
+            .map(|(exchange, stats)| (exchange, stats.subscriptions))
+            .collect(),
+        runtime_changes,
+        persistent: state.runtime_subscriptions.is_persistent(),
+    }
+}
+
+/// 获取当前订阅
+pub async fn list_subscriptions(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<SubscriptionsResponse>>, ApiError> {
+    let changes = state.runtime_subscriptions.changes().await;
+    Ok(Json(ApiResponse::success(build_response(&state, changes).await)))
+}
+
+/// 运行时新增订阅
+pub async fn add_subscriptions(
+    State(state): State<AppState>,
+    Json(request): Json<SubscriptionRequest>,
+) -> Result<Json<ApiResponse<SubscriptionsResponse>>, ApiError> {
+    let (exchange, symbols, data_types) = request.normalize()?;
+
+    let changes = state
+        .runtime_subscriptions
+        .add(&state.exchange_manager, &exchange, &symbols, &data_types)
+        .await
+        .map_err(|e| ApiError::ServiceUnavailable(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(build_response(&state, changes).await)))
+}
+
+/// 运行时移除订阅
+pub async fn remove_subscriptions(
+    State(state): State<AppState>,
+    Json(request): Json<SubscriptionRequest>,
+) -> Result<Json<ApiResponse<SubscriptionsResponse>>, ApiError> {
+    let (exchange, symbols, data_types) = request.normalize()?;
+
+    let changes = state
+        .runtime_subscriptions
+        .remove(&state.exchange_manager, &exchange, &symbols, &data_types)
+        .await
+        .map_err(|e| ApiError::ServiceUnavailable(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(build_response(&state, changes).await)))
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use shared_models::common::{CommonError, Exchange};
+use shared_models::market::Ticker24hr;
+
+use super::{ApiError, ApiResponse};
+use crate::websocket::DataEntitlement;
+use crate::AppState;
+
+/// 24小时行情查询参数
+#[derive(Debug, Deserialize)]
+pub struct TickerQuery {
+    pub exchange: Option<String>,
+}
+
+/// 获取全部24小时滚动行情，延迟行情用户取延迟后发布的行情
+pub async fn get_all_tickers_24hr(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TickerQuery>,
+) -> Result<Json<ApiResponse<Vec<Ticker24hr>>>, ApiError> {
+    let exchange = match query.exchange {
+        Some(exchange) => Some(
+            exchange
+                .parse::<Exchange>()
+                .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?,
+        ),
+        None => None,
+    };
+
+    let mut tickers = if DataEntitlement::from_headers(&state.config.delayed_data, &headers).is_delayed() {
+        state
+            .delayed_feed
+            .tickers(exchange.as_ref().map(|exchange| exchange.as_str()))
+    } else {
+        state
+            .ticker_aggregator
+            .get_all_tickers(exchange.as_ref())
+            .await
+    };
+    tickers.sort_by(|a, b| b.quote_volume.cmp(&a.quote_volume));
+
+    Ok(Json(ApiResponse::success(tickers)))
+}
+
+/// 获取单个交易对的24小时滚动行情
+pub async fn get_ticker_24hr(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((exchange, symbol)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<Ticker24hr>>, ApiError> {
+    let exchange: Exchange = exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))?;
+
+    let ticker = if DataEntitlement::from_headers(&state.config.delayed_data, &headers).is_delayed() {
+        state.delayed_feed.ticker(exchange.as_str(), &symbol)
+    } else {
+        state.ticker_aggregator.get_ticker(&exchange, &symbol).await
+    };
+    ticker
+        .map(|ticker| Json(ApiResponse::success(ticker)))
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No 24h ticker for {}:{}",
+                exchange,
+                symbol.to_uppercase()
+            ))
+        })
+}
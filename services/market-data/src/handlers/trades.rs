@@ -0,0 +1,220 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::HeaderMap,
+    response::Response,
+    Json,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use shared_models::common::{CommonError, Exchange};
+use shared_models::market::Trade;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use super::{ApiError, ApiResponse};
+use crate::aggregation::TradeFilter;
+use crate::connectors::MarketDataEvent;
+use crate::websocket::{ClientStats, DataEntitlement, DisconnectReason, WebSocketEvent};
+use crate::AppState;
+
+/// 成交历史查询参数
+#[derive(Debug, Deserialize)]
+pub struct TradeHistoryQuery {
+    pub min_size: Option<rust_decimal::Decimal>,
+    pub min_notional: Option<rust_decimal::Decimal>,
+    pub side: Option<String>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+/// 成交历史响应
+#[derive(Debug, Serialize)]
+pub struct TradeHistoryResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub count: usize,
+    pub trades: Vec<Trade>,
+    /// 延迟行情用户只能看到该时间（毫秒）之前的成交
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delayed_until: Option<i64>,
+}
+
+/// 成交订阅参数
+#[derive(Debug, Deserialize)]
+pub struct TradeStreamQuery {
+    pub min_notional: Option<rust_decimal::Decimal>,
+    pub min_size: Option<rust_decimal::Decimal>,
+    pub side: Option<String>,
+}
+
+fn parse_exchange(exchange: &str) -> Result<Exchange, ApiError> {
+    exchange
+        .parse()
+        .map_err(|e: CommonError| ApiError::BadRequest(e.to_string()))
+}
+
+/// 查询最近成交明细，延迟行情用户的结束时间截止到延迟之前
+pub async fn get_trade_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<TradeHistoryQuery>,
+) -> Result<Json<ApiResponse<TradeHistoryResponse>>, ApiError> {
+    let exchange = parse_exchange(&exchange)?;
+    let delayed_until = DataEntitlement::from_headers(&state.config.delayed_data, &headers)
+        .is_delayed()
+        .then(|| state.delayed_feed.cutoff(&symbol, chrono::Utc::now().timestamp_millis()))
+        .flatten();
+    let end_time = match (query.end_time, delayed_until) {
+        (Some(end), Some(cutoff)) => Some(end.min(cutoff)),
+        (end, cutoff) => end.or(cutoff),
+    };
+    let filter = TradeFilter {
+        min_size: query.min_size,
+        min_notional: query.min_notional,
+        side: query.side,
+        start_time: query.start_time,
+        end_time,
+    };
+
+    if let (Some(start), Some(end)) = (filter.start_time, filter.end_time) {
+        if start >= end {
+            return Err(ApiError::BadRequest(
+                "Start time must be before end time".to_string(),
+            ));
+        }
+    }
+
+    let max_limit = state.config.trade_tape.max_query_limit;
+    let limit = query.limit.unwrap_or(500).min(max_limit);
+    let trades = state
+        .trade_tape
+        .query(&exchange, &symbol, &filter, limit)
+        .await;
+
+    Ok(Json(ApiResponse::success(TradeHistoryResponse {
+        exchange: exchange.as_str().to_string(),
+        symbol: symbol.to_uppercase(),
+        count: trades.len(),
+        trades,
+        delayed_until,
+    })))
+}
+
+/// 成交明细WebSocket订阅（服务端按成交额过滤）
+///
+/// 直接推送实时成交，延迟行情用户订阅延迟交易对时拒绝，应改用 /ws/stream。
+pub async fn trade_stream_websocket(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((exchange, symbol)): Path<(String, String)>,
+    Query(query): Query<TradeStreamQuery>,
+) -> Result<Response, ApiError> {
+    let exchange = parse_exchange(&exchange)?;
+    if DataEntitlement::from_headers(&state.config.delayed_data, &headers).is_delayed()
+        && state.config.delayed_data.delay_for(&symbol).is_some()
+    {
+        return Err(ApiError::Forbidden(format!(
+            "Real-time trades for {} require a real-time market data entitlement",
+            symbol.to_uppercase()
+        )));
+    }
+    let filter = TradeFilter {
+        min_size: query.min_size,
+        min_notional: query.min_notional,
+        side: query.side,
+        start_time: None,
+        end_time: None,
+    };
+    let events = state.exchange_manager.subscribe_events();
+    let clients = state.ws_clients.clone();
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        let symbol = symbol.to_uppercase();
+        let client = clients.register(&format!("/ws/trades/{}/{}", exchange, symbol)).await;
+        let mut subscriptions = vec![format!("symbol:{}", symbol)];
+        if let Some(min_notional) = filter.min_notional {
+            subscriptions.push(format!("min_notional:{}", min_notional));
+        }
+        client.set_subscriptions(subscriptions).await;
+
+        let reason = handle_trade_stream(socket, events, exchange, symbol, filter, &client).await;
+        clients.unregister(client.id, reason, 0).await;
+    }))
+}
+
+async fn handle_trade_stream(
+    socket: WebSocket,
+    mut events: broadcast::Receiver<MarketDataEvent>,
+    exchange: Exchange,
+    symbol: String,
+    filter: TradeFilter,
+    client: &ClientStats,
+) -> DisconnectReason {
+    let (mut sender, mut receiver) = socket.split();
+    info!(
+        "Trade stream opened for {}:{} (min_notional: {:?})",
+        exchange, symbol, filter.min_notional
+    );
+
+    let reason = loop {
+        tokio::select! {
+            event = events.recv() => {
+                let trade = match event {
+                    Ok(MarketDataEvent::Trade(trade)) => trade,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Trade stream for {} lagged, skipped {} events", symbol, skipped);
+                        client.record_dropped(skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break DisconnectReason::ServerShutdown,
+                };
+
+                if trade.exchange != exchange
+                    || !trade.symbol.eq_ignore_ascii_case(&symbol)
+                    || !filter.matches(&trade)
+                {
+                    continue;
+                }
+
+                let json = match WebSocketEvent::Trade(trade).to_json() {
+                    Ok(json) => json,
+                    Err(e) => {
+                        warn!("Failed to serialize trade event: {}", e);
+                        continue;
+                    }
+                };
+                let bytes = json.len();
+                if sender.send(Message::Text(json)).await.is_err() {
+                    break DisconnectReason::SendFailed;
+                }
+                client.record_sent(bytes, None);
+            }
+            msg = receiver.next() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | None => break DisconnectReason::ClientClosed,
+                    Some(Ok(Message::Ping(payload))) => {
+                        if sender.send(Message::Pong(payload)).await.is_err() {
+                            break DisconnectReason::SendFailed;
+                        }
+                    }
+                    Some(Ok(Message::Text(text))) => client.record_received(text.len()),
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        debug!("Trade stream receive error: {}", e);
+                        break DisconnectReason::ReceiveError(e.to_string());
+                    }
+                }
+            }
+        }
+    };
+
+    info!("Trade stream closed for {}:{} ({:?})", exchange, symbol, reason);
+    reason
+}
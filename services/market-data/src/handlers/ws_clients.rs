@@ -0,0 +1,36 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+
+use super::{ApiError, ApiResponse};
+use crate::websocket::{ClientSnapshot, DisconnectRecord, TopicRouterStats, WebSocketStats};
+use crate::AppState;
+
+/// WebSocket客户端诊断信息
+#[derive(Debug, Serialize)]
+pub struct WebSocketClientsResponse {
+    pub active_clients: usize,
+    /// 广播器最新事件序号
+    pub head_sequence: u64,
+    pub totals: WebSocketStats,
+    /// 主题路由分布
+    pub topics: TopicRouterStats,
+    pub clients: Vec<ClientSnapshot>,
+    pub recent_disconnects: Vec<DisconnectRecord>,
+}
+
+/// 列出活跃WebSocket客户端及其健康状况，用于排查慢消费者
+pub async fn list_ws_clients(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<WebSocketClientsResponse>>, ApiError> {
+    let head_sequence = state.broadcaster.last_event_id();
+    let clients = state.ws_clients.clients(head_sequence).await;
+
+    Ok(Json(ApiResponse::success(WebSocketClientsResponse {
+        active_clients: clients.len(),
+        head_sequence,
+        totals: state.broadcaster.get_stats().await,
+        topics: state.broadcaster.topic_stats(),
+        clients,
+        recent_disconnects: state.ws_clients.recent_disconnects().await,
+    })))
+}
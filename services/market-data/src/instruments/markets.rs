@@ -0,0 +1,259 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use super::{ExchangeInstruments, Instrument, InstrumentStatus};
+use crate::connectors::ConnectionStats;
+
+/// 交易对在单个交易所的上架信息
+#[derive(Debug, Clone, Serialize)]
+pub struct VenueListing {
+    pub exchange: String,
+    pub venue_symbol: String,
+    pub status: InstrumentStatus,
+    #[serde(with = "shared_models::decimal::option")]
+    pub min_notional: Option<Decimal>,
+    #[serde(with = "shared_models::decimal::option")]
+    pub min_quantity: Option<Decimal>,
+    #[serde(with = "shared_models::decimal::option")]
+    pub tick_size: Option<Decimal>,
+    #[serde(with = "shared_models::decimal::option")]
+    pub step_size: Option<Decimal>,
+    /// 价格小数位数，客户端按此格式化字符串小数
+    pub price_precision: Option<u32>,
+    /// 数量小数位数
+    pub quantity_precision: Option<u32>,
+    /// 交易所连接是否在线
+    pub connected: bool,
+    /// 当前是否订阅了该交易对的行情
+    pub subscribed: bool,
+}
+
+/// 跨交易所聚合的交易对
+#[derive(Debug, Clone, Serialize)]
+pub struct Market {
+    /// 统一交易对名称
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+    /// 可交易的交易所数量
+    pub trading_venues: usize,
+    pub venues: Vec<VenueListing>,
+}
+
+/// 交易对筛选条件
+#[derive(Debug, Clone, Default)]
+pub struct MarketFilter {
+    pub exchange: Option<String>,
+    pub base: Option<String>,
+    pub quote: Option<String>,
+    pub status: Option<InstrumentStatus>,
+    /// 计划下单金额，过滤掉最小下单金额高于该值的交易所
+    pub min_notional: Option<Decimal>,
+    /// 只返回连接在线的交易所
+    pub connected_only: bool,
+}
+
+impl MarketFilter {
+    fn matches(&self, exchange: &str, instrument: &Instrument, connected: bool) -> bool {
+        if let Some(filter) = &self.exchange {
+            if !filter.eq_ignore_ascii_case(exchange) {
+                return false;
+            }
+        }
+        if let Some(base) = &self.base {
+            if !base.eq_ignore_ascii_case(&instrument.base) {
+                return false;
+            }
+        }
+        if let Some(quote) = &self.quote {
+            if !quote.eq_ignore_ascii_case(&instrument.quote) {
+                return false;
+            }
+        }
+        if let Some(status) = self.status {
+            if instrument.status != status {
+                return false;
+            }
+        }
+        if let (Some(notional), Some(required)) = (self.min_notional, instrument.min_notional) {
+            if required > notional {
+                return false;
+            }
+        }
+        !self.connected_only || connected
+    }
+}
+
+/// 合并各交易所元数据和连接状态，按统一交易对聚合
+pub fn build_markets(
+    instruments: &HashMap<String, ExchangeInstruments>,
+    connector_stats: &HashMap<String, ConnectionStats>,
+    filter: &MarketFilter,
+) -> Vec<Market> {
+    let mut markets: BTreeMap<String, Market> = BTreeMap::new();
+
+    for (exchange, synced) in instruments {
+        let stats = connector_stats.get(exchange);
+        let connected = stats.map(|s| s.connected).unwrap_or(false);
+
+        for instrument in &synced.instruments {
+            if !filter.matches(exchange, instrument, connected) {
+                continue;
+            }
+
+            let subscribed = stats
+                .map(|s| {
+                    s.subscriptions.keys().any(|symbol| {
+                        symbol.eq_ignore_ascii_case(&instrument.symbol)
+                            || symbol.eq_ignore_ascii_case(&instrument.venue_symbol)
+                    })
+                })
+                .unwrap_or(false);
+
+            let market = markets
+                .entry(instrument.symbol.clone())
+                .or_insert_with(|| Market {
+                    symbol: instrument.symbol.clone(),
+                    base: instrument.base.clone(),
+                    quote: instrument.quote.clone(),
+                    trading_venues: 0,
+                    venues: Vec::new(),
+                });
+            if instrument.status == InstrumentStatus::Trading {
+                market.trading_venues += 1;
+            }
+            market.venues.push(VenueListing {
+                exchange: exchange.clone(),
+                venue_symbol: instrument.venue_symbol.clone(),
+                status: instrument.status,
+                min_notional: instrument.min_notional,
+                min_quantity: instrument.min_quantity,
+                tick_size: instrument.tick_size,
+                step_size: instrument.step_size,
+                price_precision: instrument.price_precision(),
+                quantity_precision: instrument.quantity_precision(),
+                connected,
+                subscribed,
+            });
+        }
+    }
+
+    markets
+        .into_values()
+        .map(|mut market| {
+            market.venues.sort_by(|a, b| a.exchange.cmp(&b.exchange));
+            market
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument(symbol: &str, venue_symbol: &str, status: InstrumentStatus, min_notional: i64) -> Instrument {
+        Instrument {
+            symbol: symbol.to_string(),
+            venue_symbol: venue_symbol.to_string(),
+            base: symbol[..3].to_string(),
+            quote: symbol[3..].to_string(),
+            status,
+            min_notional: Some(Decimal::from(min_notional)),
+            min_quantity: None,
+            tick_size: None,
+            step_size: None,
+        }
+    }
+
+    fn sample() -> (HashMap<String, ExchangeInstruments>, HashMap<String, ConnectionStats>) {
+        let mut instruments = HashMap::new();
+        instruments.insert(
+            "binance".to_string(),
+            ExchangeInstruments {
+                exchange: "binance".to_string(),
+                instruments: vec![
+                    instrument("BTCUSDT", "BTCUSDT", InstrumentStatus::Trading, 5),
+                    instrument("ETHUSDT", "ETHUSDT", InstrumentStatus::Halted, 5),
+                ],
+                ..Default::default()
+            },
+        );
+        instruments.insert(
+            "kraken".to_string(),
+            ExchangeInstruments {
+                exchange: "kraken".to_string(),
+                instruments: vec![instrument("BTCUSDT", "XBT/USDT", InstrumentStatus::Trading, 20)],
+                ..Default::default()
+            },
+        );
+
+        let mut binance = ConnectionStats::default();
+        binance.set_connected(true);
+        binance.add_subscription("BTCUSDT".to_string(), "trade".to_string());
+        let mut stats = HashMap::new();
+        stats.insert("binance".to_string(), binance);
+
+        (instruments, stats)
+    }
+
+    #[test]
+    fn test_markets_aggregate_across_venues() {
+        let (instruments, stats) = sample();
+        let markets = build_markets(&instruments, &stats, &MarketFilter::default());
+
+        assert_eq!(markets.len(), 2);
+        let btc = &markets[0];
+        assert_eq!(btc.symbol, "BTCUSDT");
+        assert_eq!(btc.trading_venues, 2);
+        assert_eq!(btc.venues[0].exchange, "binance");
+        assert!(btc.venues[0].connected && btc.venues[0].subscribed);
+        assert_eq!(btc.venues[1].venue_symbol, "XBT/USDT");
+        assert!(!btc.venues[1].connected);
+    }
+
+    #[test]
+    fn test_precision_metadata() {
+        let mut listed = instrument("BTCUSDT", "BTCUSDT", InstrumentStatus::Trading, 5);
+        listed.tick_size = Some(Decimal::new(1_000, 5));
+        listed.step_size = Some(Decimal::ONE);
+        assert_eq!(listed.price_precision(), Some(2));
+        assert_eq!(listed.quantity_precision(), Some(0));
+
+        let mut instruments = HashMap::new();
+        instruments.insert(
+            "binance".to_string(),
+            ExchangeInstruments {
+                exchange: "binance".to_string(),
+                instruments: vec![listed],
+                ..Default::default()
+            },
+        );
+        let markets = build_markets(&instruments, &HashMap::new(), &MarketFilter::default());
+        let venue = serde_json::to_value(&markets[0].venues[0]).unwrap();
+        assert_eq!(venue["tick_size"], "0.01");
+        assert_eq!(venue["price_precision"], 2);
+        assert!(venue["step_size"] == "1" && venue["quantity_precision"] == 0);
+    }
+
+    #[test]
+    fn test_market_filters() {
+        let (instruments, stats) = sample();
+
+        let filter = MarketFilter {
+            min_notional: Some(Decimal::from(10)),
+            ..Default::default()
+        };
+        let markets = build_markets(&instruments, &stats, &filter);
+        assert!(markets.iter().all(|m| m.venues.iter().all(|v| v.exchange == "binance")));
+
+        let filter = MarketFilter {
+            status: Some(InstrumentStatus::Trading),
+            connected_only: true,
+            ..Default::default()
+        };
+        let markets = build_markets(&instruments, &stats, &filter);
+        assert_eq!(markets.len(), 1);
+        assert_eq!(markets[0].venues.len(), 1);
+    }
+}
@@ -0,0 +1,97 @@
+pub mod markets;
+pub mod sources;
+pub mod sync;
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use markets::{build_markets, Market, MarketFilter, VenueListing};
+pub use sources::{BinanceInstruments, KrakenInstruments};
+pub use sync::{ExchangeInstruments, InstrumentSync};
+
+/// 交易对交易状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentStatus {
+    /// 正常交易
+    Trading,
+    /// 仅允许部分操作（只撤单、只挂单等）
+    Limited,
+    /// 暂停交易
+    Halted,
+}
+
+impl std::str::FromStr for InstrumentStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "trading" => Ok(InstrumentStatus::Trading),
+            "limited" => Ok(InstrumentStatus::Limited),
+            "halted" => Ok(InstrumentStatus::Halted),
+            _ => Err(anyhow::anyhow!("Invalid instrument status: {}", s)),
+        }
+    }
+}
+
+/// 交易所的交易对元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Instrument {
+    /// 统一交易对名称，例如 BTCUSDT
+    pub symbol: String,
+    /// 交易所原始交易对名称，例如 XBT/USDT
+    pub venue_symbol: String,
+    pub base: String,
+    pub quote: String,
+    pub status: InstrumentStatus,
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub min_notional: Option<Decimal>,
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub min_quantity: Option<Decimal>,
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub tick_size: Option<Decimal>,
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub step_size: Option<Decimal>,
+}
+
+impl Instrument {
+    /// 价格精度（小数位数），由最小价格变动推出
+    pub fn price_precision(&self) -> Option<u32> {
+        self.tick_size.map(decimal_places)
+    }
+
+    /// 数量精度（小数位数），由最小数量变动推出
+    pub fn quantity_precision(&self) -> Option<u32> {
+        self.step_size.map(decimal_places)
+    }
+}
+
+/// 最小变动单位对应的小数位数，例如 0.0100 为 2
+pub fn decimal_places(increment: Decimal) -> u32 {
+    increment.normalize().scale()
+}
+
+/// 交易对元数据接口，由各交易所的连接器工厂提供
+pub trait InstrumentSource: Send + Sync {
+    /// 元数据接口地址
+    fn endpoint(&self, rest_api_url: &str) -> String;
+
+    /// 解析接口响应
+    fn parse(&self, body: &Value) -> Result<Vec<Instrument>>;
+}
+
+/// 币种别名统一为通用名称
+pub fn canonical_asset(asset: &str) -> String {
+    match asset.to_uppercase().as_str() {
+        "XBT" => "BTC".to_string(),
+        "XDG" => "DOGE".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// 统一交易对名称
+pub fn canonical_symbol(base: &str, quote: &str) -> String {
+    format!("{}{}", canonical_asset(base), canonical_asset(quote))
+}
@@ -0,0 +1,187 @@
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use super::{canonical_asset, canonical_symbol, Instrument, InstrumentSource, InstrumentStatus};
+
+fn decimal(value: Option<&Value>) -> Option<Decimal> {
+    value
+        .and_then(Value::as_str)
+        .and_then(|s| s.parse::<Decimal>().ok())
+        .filter(|d| !d.is_zero())
+}
+
+/// 币安 exchangeInfo 接口
+pub struct BinanceInstruments;
+
+impl InstrumentSource for BinanceInstruments {
+    fn endpoint(&self, rest_api_url: &str) -> String {
+        format!("{}/api/v3/exchangeInfo", rest_api_url.trim_end_matches('/'))
+    }
+
+    fn parse(&self, body: &Value) -> Result<Vec<Instrument>> {
+        let symbols = body
+            .get("symbols")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("Binance exchangeInfo missing symbols"))?;
+
+        Ok(symbols
+            .iter()
+            .filter_map(|item| {
+                let venue_symbol = item.get("symbol")?.as_str()?;
+                let base = item.get("baseAsset")?.as_str()?;
+                let quote = item.get("quoteAsset")?.as_str()?;
+                let status = match item.get("status").and_then(Value::as_str) {
+                    Some("TRADING") => InstrumentStatus::Trading,
+                    Some("AUCTION_MATCH") | Some("PRE_TRADING") | Some("POST_TRADING") => {
+                        InstrumentStatus::Limited
+                    }
+                    _ => InstrumentStatus::Halted,
+                };
+
+                let filters = item.get("filters").and_then(Value::as_array);
+                let filter = |name: &str| {
+                    filters.and_then(|filters| {
+                        filters
+                            .iter()
+                            .find(|f| f.get("filterType").and_then(Value::as_str) == Some(name))
+                    })
+                };
+                let price_filter = filter("PRICE_FILTER");
+                let lot_size = filter("LOT_SIZE");
+                let min_notional = filter("NOTIONAL")
+                    .or_else(|| filter("MIN_NOTIONAL"))
+                    .and_then(|f| decimal(f.get("minNotional")));
+
+                Some(Instrument {
+                    symbol: canonical_symbol(base, quote),
+                    venue_symbol: venue_symbol.to_string(),
+                    base: canonical_asset(base),
+                    quote: canonical_asset(quote),
+                    status,
+                    min_notional,
+                    min_quantity: lot_size.and_then(|f| decimal(f.get("minQty"))),
+                    tick_size: price_filter.and_then(|f| decimal(f.get("tickSize"))),
+                    step_size: lot_size.and_then(|f| decimal(f.get("stepSize"))),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Kraken AssetPairs 接口
+pub struct KrakenInstruments;
+
+impl InstrumentSource for KrakenInstruments {
+    fn endpoint(&self, rest_api_url: &str) -> String {
+        format!("{}/0/public/AssetPairs", rest_api_url.trim_end_matches('/'))
+    }
+
+    fn parse(&self, body: &Value) -> Result<Vec<Instrument>> {
+        if let Some(errors) = body.get("error").and_then(Value::as_array) {
+            if !errors.is_empty() {
+                return Err(anyhow!("Kraken AssetPairs error: {:?}", errors));
+            }
+        }
+        let pairs = body
+            .get("result")
+            .and_then(Value::as_object)
+            .ok_or_else(|| anyhow!("Kraken AssetPairs missing result"))?;
+
+        Ok(pairs
+            .values()
+            .filter_map(|item| {
+                // 暗池等交易对没有 wsname，不提供行情
+                let wsname = item.get("wsname")?.as_str()?;
+                let (base, quote) = wsname.split_once('/')?;
+                let status = match item.get("status").and_then(Value::as_str) {
+                    Some("online") | None => InstrumentStatus::Trading,
+                    Some("cancel_only") | Some("post_only") | Some("limit_only")
+                    | Some("reduce_only") => InstrumentStatus::Limited,
+                    _ => InstrumentStatus::Halted,
+                };
+                let step_size = item
+                    .get("lot_decimals")
+                    .and_then(Value::as_u64)
+                    .map(|decimals| Decimal::new(1, decimals as u32));
+
+                Some(Instrument {
+                    symbol: canonical_symbol(base, quote),
+                    venue_symbol: wsname.to_string(),
+                    base: canonical_asset(base),
+                    quote: canonical_asset(quote),
+                    status,
+                    min_notional: decimal(item.get("costmin")),
+                    min_quantity: decimal(item.get("ordermin")),
+                    tick_size: decimal(item.get("tick_size")),
+                    step_size,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_binance_exchange_info() {
+        let body = json!({
+            "symbols": [{
+                "symbol": "BTCUSDT",
+                "status": "TRADING",
+                "baseAsset": "BTC",
+                "quoteAsset": "USDT",
+                "filters": [
+                    { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                    { "filterType": "LOT_SIZE", "minQty": "0.00001000", "stepSize": "0.00001000" },
+                    { "filterType": "NOTIONAL", "minNotional": "5.00000000" }
+                ]
+            }, {
+                "symbol": "LUNAUSDT",
+                "status": "BREAK",
+                "baseAsset": "LUNA",
+                "quoteAsset": "USDT",
+                "filters": []
+            }]
+        });
+
+        let instruments = BinanceInstruments.parse(&body).unwrap();
+        assert_eq!(instruments.len(), 2);
+        assert_eq!(instruments[0].symbol, "BTCUSDT");
+        assert_eq!(instruments[0].min_notional, Some(Decimal::from(5)));
+        assert_eq!(instruments[0].tick_size, Some(Decimal::new(1, 2)));
+        assert_eq!(instruments[1].status, InstrumentStatus::Halted);
+    }
+
+    #[test]
+    fn test_parse_kraken_asset_pairs() {
+        let body = json!({
+            "error": [],
+            "result": {
+                "XXBTZUSD": {
+                    "altname": "XBTUSD",
+                    "wsname": "XBT/USD",
+                    "base": "XXBT",
+                    "quote": "ZUSD",
+                    "lot_decimals": 8,
+                    "ordermin": "0.0001",
+                    "costmin": "0.5",
+                    "tick_size": "0.1",
+                    "status": "online"
+                },
+                "XXBTZUSD.d": { "altname": "XBTUSD.d" }
+            }
+        });
+
+        let instruments = KrakenInstruments.parse(&body).unwrap();
+        assert_eq!(instruments.len(), 1);
+        assert_eq!(instruments[0].symbol, "BTCUSD");
+        assert_eq!(instruments[0].venue_symbol, "XBT/USD");
+        assert_eq!(instruments[0].base, "BTC");
+        assert_eq!(instruments[0].step_size, Some(Decimal::new(1, 8)));
+        assert_eq!(instruments[0].min_notional, Some(Decimal::new(5, 1)));
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::Instrument;
+use crate::config::{ExchangeConfig, InstrumentConfig};
+use crate::connectors::ConnectorRegistry;
+
+/// 单个交易所的元数据同步结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExchangeInstruments {
+    pub exchange: String,
+    pub instruments: Vec<Instrument>,
+    /// 最近一次成功同步时间
+    pub synced_at: Option<DateTime<Utc>>,
+    /// 最近一次同步失败的错误，成功后清除
+    pub error: Option<String>,
+}
+
+/// 交易对元数据同步
+///
+/// 按配置的交易所从连接器工厂提供的元数据接口拉取交易对，
+/// 同步失败时保留上一次成功的结果。
+#[derive(Clone)]
+pub struct InstrumentSync {
+    config: InstrumentConfig,
+    client: reqwest::Client,
+    state: Arc<RwLock<HashMap<String, ExchangeInstruments>>>,
+}
+
+impl InstrumentSync {
+    pub fn new(config: InstrumentConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_seconds.max(1)))
+            .build()
+            .unwrap_or_default();
+        Self {
+            config,
+            client,
+            state: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 当前元数据，按配置中的交易所键保存
+    pub async fn snapshot(&self) -> HashMap<String, ExchangeInstruments> {
+        self.state.read().await.clone()
+    }
+
+    async fn fetch(
+        &self,
+        instance: &str,
+        config: &ExchangeConfig,
+        registry: &ConnectorRegistry,
+    ) -> Result<Vec<Instrument>> {
+        let name = ConnectorRegistry::connector_name(instance, config);
+        let source = registry
+            .instrument_source(&name)
+            .ok_or_else(|| anyhow!("Instrument metadata is not supported for {}", name))?;
+        if config.rest_api_url.is_empty() {
+            return Err(anyhow!("REST API URL is not configured for {}", instance));
+        }
+
+        let body: serde_json::Value = self
+            .client
+            .get(source.endpoint(&config.rest_api_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        source.parse(&body)
+    }
+
+    /// 同步全部交易所
+    pub async fn sync_all(&self, exchanges: &[(String, ExchangeConfig)], registry: &ConnectorRegistry) {
+        for (instance, config) in exchanges {
+            let result = self.fetch(instance, config, registry).await;
+
+            let mut state = self.state.write().await;
+            let entry = state.entry(instance.clone()).or_insert_with(|| ExchangeInstruments {
+                exchange: instance.clone(),
+                ..Default::default()
+            });
+            match result {
+                Ok(instruments) => {
+                    info!("Synced {} instruments for {}", instruments.len(), instance);
+                    entry.instruments = instruments;
+                    entry.synced_at = Some(Utc::now());
+                    entry.error = None;
+                }
+                Err(e) => {
+                    warn!("Failed to sync instruments for {}: {}", instance, e);
+                    entry.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// 启动定时同步
+    pub fn start(&self, exchanges: Vec<(String, ExchangeConfig)>, registry: ConnectorRegistry) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let sync = self.clone();
+        tokio::spawn(async move {
+            let interval = Duration::from_secs(sync.config.refresh_interval_seconds.max(60));
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                sync.sync_all(&exchanges, &registry).await;
+            }
+        });
+    }
+}
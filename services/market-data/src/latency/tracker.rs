@@ -0,0 +1,227 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+/// 行情管道的处理阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStage {
+    /// 连接器收到原始消息到解析完成
+    Parse,
+    /// 解析完成到事件处理器取出事件，包括主备去重和排队
+    Process,
+    /// 事件处理器处理并写入存储
+    Store,
+    /// 写入完成到分发给下游订阅者
+    Broadcast,
+    /// 收到原始消息到分发完成
+    Total,
+}
+
+impl PipelineStage {
+    pub const ALL: [PipelineStage; 5] = [
+        PipelineStage::Parse,
+        PipelineStage::Process,
+        PipelineStage::Store,
+        PipelineStage::Broadcast,
+        PipelineStage::Total,
+    ];
+}
+
+/// 随事件传递的各阶段单调时钟时间
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineTimestamps {
+    pub received: Instant,
+    pub parsed: Instant,
+    pub processing: Option<Instant>,
+    pub stored: Option<Instant>,
+    pub broadcast: Option<Instant>,
+}
+
+impl PipelineTimestamps {
+    /// 收到原始消息的时间，解析时间取当前时间
+    pub fn received_at(received: Instant) -> Self {
+        Self {
+            received,
+            parsed: Instant::now(),
+            processing: None,
+            stored: None,
+            broadcast: None,
+        }
+    }
+
+    pub fn mark_processing(&mut self) {
+        self.processing = Some(Instant::now());
+    }
+
+    pub fn mark_stored(&mut self) {
+        self.stored = Some(Instant::now());
+    }
+
+    pub fn mark_broadcast(&mut self) {
+        self.broadcast = Some(Instant::now());
+    }
+
+    /// 已完成阶段的耗时（微秒）
+    pub fn stage_latencies(&self) -> Vec<(PipelineStage, u64)> {
+        fn micros(from: Instant, to: Instant) -> u64 {
+            to.saturating_duration_since(from).as_micros() as u64
+        }
+
+        let mut latencies = vec![(PipelineStage::Parse, micros(self.received, self.parsed))];
+        let Some(processing) = self.processing else {
+            return latencies;
+        };
+        latencies.push((PipelineStage::Process, micros(self.parsed, processing)));
+        let Some(stored) = self.stored else {
+            return latencies;
+        };
+        latencies.push((PipelineStage::Store, micros(processing, stored)));
+        if let Some(broadcast) = self.broadcast {
+            latencies.push((PipelineStage::Broadcast, micros(stored, broadcast)));
+            latencies.push((PipelineStage::Total, micros(self.received, broadcast)));
+        }
+        latencies
+    }
+}
+
+/// 单个阶段的延迟分位数（微秒）
+#[derive(Debug, Clone, Serialize)]
+pub struct StageLatency {
+    pub samples: usize,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+impl StageLatency {
+    fn from_samples(samples: &VecDeque<u64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<u64> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[((sorted.len() - 1) * p) / 100];
+        Some(Self {
+            samples: sorted.len(),
+            p50_us: percentile(50),
+            p90_us: percentile(90),
+            p99_us: percentile(99),
+            max_us: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// 单个交易对的分阶段延迟
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolLatency {
+    pub exchange: String,
+    pub symbol: String,
+    pub stages: BTreeMap<PipelineStage, StageLatency>,
+}
+
+/// 行情管道分阶段延迟统计
+///
+/// 每个交易所/交易对的每个阶段保留最近 `sample_size` 个样本，查询时计算分位数。
+pub struct PipelineLatencyTracker {
+    enabled: bool,
+    sample_size: usize,
+    samples: RwLock<HashMap<(String, String), HashMap<PipelineStage, VecDeque<u64>>>>,
+}
+
+impl PipelineLatencyTracker {
+    pub fn new(enabled: bool, sample_size: usize) -> Self {
+        Self {
+            enabled,
+            sample_size: sample_size.max(1),
+            samples: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 记录一个事件的各阶段耗时
+    pub async fn record(&self, exchange: &str, symbol: &str, timestamps: &PipelineTimestamps) {
+        if !self.enabled {
+            return;
+        }
+        let latencies = timestamps.stage_latencies();
+        let mut samples = self.samples.write().await;
+        let stages = samples
+            .entry((exchange.to_string(), symbol.to_string()))
+            .or_default();
+        for (stage, micros) in latencies {
+            let window = stages.entry(stage).or_default();
+            if window.len() == self.sample_size {
+                window.pop_front();
+            }
+            window.push_back(micros);
+        }
+    }
+
+    /// 按交易所/交易对查询分阶段延迟，未指定时返回全部
+    pub async fn snapshot(&self, exchange: Option<&str>, symbol: Option<&str>) -> Vec<SymbolLatency> {
+        let samples = self.samples.read().await;
+        let mut result: Vec<SymbolLatency> = samples
+            .iter()
+            .filter(|((e, s), _)| {
+                exchange.is_none_or(|exchange| e.eq_ignore_ascii_case(exchange))
+                    && symbol.is_none_or(|symbol| s.eq_ignore_ascii_case(symbol))
+            })
+            .map(|((exchange, symbol), stages)| SymbolLatency {
+                exchange: exchange.clone(),
+                symbol: symbol.clone(),
+                stages: PipelineStage::ALL
+                    .iter()
+                    .filter_map(|stage| {
+                        let latency = StageLatency::from_samples(stages.get(stage)?)?;
+                        Some((*stage, latency))
+                    })
+                    .collect(),
+            })
+            .collect();
+        result.sort_by(|a, b| (&a.exchange, &a.symbol).cmp(&(&b.exchange, &b.symbol)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_stage_percentiles() {
+        let tracker = PipelineLatencyTracker::new(true, 100);
+        let start = Instant::now();
+        for i in 1..=100u64 {
+            let timestamps = PipelineTimestamps {
+                received: start,
+                parsed: start + Duration::from_micros(i),
+                processing: Some(start + Duration::from_micros(2 * i)),
+                stored: Some(start + Duration::from_micros(3 * i)),
+                broadcast: Some(start + Duration::from_micros(4 * i)),
+            };
+            tracker.record("binance", "BTCUSDT", &timestamps).await;
+        }
+
+        // 未完成的事件只记录已完成的阶段
+        let mut partial = PipelineTimestamps::received_at(start);
+        partial.mark_processing();
+        tracker.record("kraken", "XBTUSD", &partial).await;
+
+        let snapshot = tracker.snapshot(Some("binance"), None).await;
+        assert_eq!(snapshot.len(), 1);
+        let parse = &snapshot[0].stages[&PipelineStage::Parse];
+        assert_eq!((parse.samples, parse.p50_us, parse.p99_us, parse.max_us), (100, 50, 99, 100));
+        assert_eq!(snapshot[0].stages[&PipelineStage::Total].p90_us, 360);
+
+        let partial = tracker.snapshot(None, Some("xbtusd")).await;
+        assert_eq!(partial[0].stages.len(), 2);
+        assert!(!partial[0].stages.contains_key(&PipelineStage::Store));
+    }
+}
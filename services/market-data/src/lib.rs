@@ -0,0 +1,6 @@
+//! 市场数据服务中不依赖行情处理管线的模块
+//!
+//! 独立二进制直接依赖这些模块，测试也通过库目标编译运行。
+pub mod config;
+pub mod continuity;
+pub mod transport;
@@ -1,14 +1,33 @@
+mod aggregation;
+mod alerts;
+mod analytics;
+mod charting;
+mod compaction;
 mod config;
 mod connectors;
 mod continuity;
+mod depth_history;
+mod durability;
 mod handlers;
+mod instruments;
+mod latency;
 mod processors;
+mod publishing;
+mod rollups;
+mod schema;
+mod sharding;
 mod storage;
+mod stores;
+mod transport;
 mod websocket;
 
 use anyhow::Result;
 use axum::Router;
-use shared_utils::{LoggingInitializer, AppMetrics};
+use shared_utils::{
+    check_config_requested, decimal_format_middleware, internal_auth_middleware,
+    log_level_routes, request_span_middleware, request_trace_routes, run_config_check,
+    AppMetrics, InternalAuth, LeaderElection, LoggingInitializer,
+};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
@@ -16,14 +35,31 @@ use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{error, info};
 
 use crate::{
+    aggregation::{CandleCloseScheduler, RollingTickerAggregator, TradeTape},
+    alerts::AlertRuleEngine,
+    analytics::{AnalyticsQueryEngine, DepthMetricsProcessor, SeasonalityAnalyzer, WhaleDetector},
+    charting::ChartCache,
+    compaction::TickCompactor,
     config::MarketDataConfig,
+    continuity::{ContinuityStateStore, KlineContinuityDetector},
+    depth_history::DepthHistoryRecorder,
+    durability::TieredWriter,
     handlers::create_routes,
+    instruments::InstrumentSync,
     processors::DataProcessor,
+    publishing::KafkaPublisher,
+    rollups::RollupManager,
+    schema::ClickHouseSchema,
+    sharding::ShardCoordinator,
     storage::StorageManager,
-    connectors::ExchangeManager,
+    stores::MarketStores,
+    connectors::{ExchangeManager, MarketDataEvent, RuntimeSubscriptionManager},
+    websocket::{
+        ClientRegistry, ConflationPolicy, DelayedFeed, EventFilter, SessionStore, WebSocketBroadcaster,
+    },
 };
 
 #[tokio::main]
@@ -31,21 +67,50 @@ async fn main() -> Result<()> {
     // 加载环境变量
     dotenvy::dotenv().ok();
 
-    // 初始化日志
-    LoggingInitializer::init_dev()?;
+    // 检查配置后退出
+    if check_config_requested() {
+        let (config, report) = MarketDataConfig::load_checked()?;
+        std::process::exit(run_config_check("market-data", config.as_ref(), &report));
+    }
+
+    // 初始化日志，LOG_FORMAT=json 时输出结构化日志
+    let log_handle = LoggingInitializer::init_from_env()?;
 
     // 加载配置
     let config = MarketDataConfig::load()?;
     info!("Market data service configuration loaded");
+    log_handle.apply(&config.logging.level, &config.logging.modules)?;
 
     // 初始化指标
     let metrics = Arc::new(AppMetrics::new()?);
     info!("Metrics initialized");
 
+    // 定时任务只在领导者副本执行
+    let mut leader_config = config.leader_election.clone();
+    if leader_config.redis_url.is_none() {
+        leader_config.redis_url = config.storage.redis.as_ref().map(|redis| redis.url.clone());
+    }
+    let leader = LeaderElection::connect(leader_config)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect leader election: {}", e))?;
+    info!("Leader election initialized (instance: {})", leader.instance_id());
+
+    // 初始化分级存储写入：成交等关键数据经预写日志持久化后确认，行情数据批量异步写入
+    let storage_writes = Arc::new(TieredWriter::new(
+        config.storage.writes.clone(),
+        config.storage.clickhouse.as_ref(),
+        metrics.clone(),
+    ));
+
     // 初始化存储管理器
     let storage_manager = Arc::new(StorageManager::new(config.clone()).await?);
     info!("Storage manager initialized");
 
+    // Tick、K线和订单簿快照按配置选择存储后端
+    let market_stores = MarketStores::from_config(&config, &storage_writes).await?;
+    info!("Market stores initialized with {} backend", market_stores.backend.as_str());
+    storage_writes.start();
+
     // 初始化数据处理器
     let data_processor = Arc::new(DataProcessor::new(
         config.clone(),
@@ -66,22 +131,249 @@ async fn main() -> Result<()> {
     exchange_manager.start_all_connections().await?;
     info!("Exchange connections started");
 
+    // 恢复运行时订阅变更
+    let runtime_subscriptions = Arc::new(
+        RuntimeSubscriptionManager::new(&config).await,
+    );
+    runtime_subscriptions.restore(&exchange_manager).await;
+
+    // 启动交易对分片协调，只保留分配给本实例的订阅
+    let shard_coordinator = Arc::new(ShardCoordinator::new(&config).await);
+    shard_coordinator
+        .clone()
+        .start(leader.clone(), exchange_manager.clone());
+
+    // 启动交易对元数据同步
+    let instrument_sync = Arc::new(InstrumentSync::new(config.instruments.clone()));
+    instrument_sync.start(
+        config
+            .enabled_exchanges()
+            .into_iter()
+            .map(|(name, exchange)| (name.clone(), exchange.clone()))
+            .collect(),
+        exchange_manager.registry().clone(),
+    );
+
+    // 初始化图表缓存
+    let chart_cache = Arc::new(
+        ChartCache::new(config.charting.clone(), config.storage.redis.as_ref()).await,
+    );
+    info!("Chart cache initialized (enabled: {})", chart_cache.is_enabled());
+
+    // 创建ClickHouse源表，已有表中的浮点列在后台迁移为Decimal
+    let clickhouse_schema = ClickHouseSchema::new(config.storage.clickhouse.as_ref());
+    if config.storage.clickhouse.is_some() {
+        if let Err(e) = clickhouse_schema
+            .ensure_tables(&config.compaction.ticks_table, &config.rollups.source_table)
+            .await
+        {
+            error!("Failed to create ClickHouse source tables: {}", e);
+        }
+        clickhouse_schema.start_migration(
+            leader.clone(),
+            vec![
+                config.compaction.ticks_table.clone(),
+                config.rollups.source_table.clone(),
+            ],
+        );
+    }
+
+    // 初始化K线物化汇总
+    let rollups = Arc::new(RollupManager::new(
+        config.rollups.clone(),
+        config.storage.clickhouse.as_ref(),
+    ));
+    rollups.start(leader.clone());
+    info!("K-line rollups initialized (enabled: {})", rollups.is_enabled());
+
+    // 日内季节性分析，按需从K线源表计算并缓存
+    let seasonality = Arc::new(SeasonalityAnalyzer::new(
+        config.seasonality.clone(),
+        &config.rollups,
+        config.storage.clickhouse.as_ref(),
+    ));
+
+    // 即席分析查询，DSL编译为受限的ClickHouse SQL
+    let analytics_query = Arc::new(AnalyticsQueryEngine::new(
+        config.analytics_query.clone(),
+        &config.rollups,
+        &config.compaction,
+        config.storage.clickhouse.as_ref(),
+    ));
+    info!("Analytics queries initialized (enabled: {})", analytics_query.is_enabled());
+
+    // 初始化WebSocket广播器和Kafka发布器
+    let broadcaster = Arc::new(WebSocketBroadcaster::with_conflation(
+        config.websocket.message_buffer_size,
+        config.websocket.replay_buffer_size,
+        ConflationPolicy::from_config(&config.websocket.conflation),
+    ));
+    let kafka_publisher = Arc::new(KafkaPublisher::new(config.storage.kafka.as_ref())?);
+
+    // 延迟行情分发，未开通实时行情的用户从延迟广播器接收数据
+    let delayed_feed = Arc::new(DelayedFeed::new(
+        config.delayed_data.clone(),
+        WebSocketBroadcaster::with_conflation(
+            config.websocket.message_buffer_size,
+            config.websocket.replay_buffer_size,
+            ConflationPolicy::from_config(&config.websocket.conflation),
+        ),
+    ));
+    if delayed_feed.is_enabled() {
+        delayed_feed
+            .clone()
+            .start(broadcaster.subscribe_topics(&EventFilter::allow_all()));
+        info!("Delayed market data feed started");
+    }
+
+    // 初始化可恢复WebSocket会话存储
+    let stream_sessions = Arc::new(
+        SessionStore::new(config.websocket.session_ttl_seconds, config.storage.redis.as_ref()).await,
+    );
+
+    // WebSocket客户端诊断
+    let ws_clients = Arc::new(ClientRegistry::new(
+        config.websocket.disconnect_history_size,
+        config.websocket.slow_client_lag,
+    ));
+
+    // 启动24小时滚动行情聚合
+    let ticker_aggregator = Arc::new(RollingTickerAggregator::new(config.ticker.window_hours));
+    if config.ticker.enabled {
+        ticker_aggregator.start(
+            exchange_manager.subscribe_events(),
+            broadcaster.clone(),
+            kafka_publisher.clone(),
+            std::time::Duration::from_millis(config.ticker.publish_interval_ms),
+        );
+        info!("24h ticker aggregator started");
+    }
+
+    // 启动K线收盘调度
+    let candle_close = Arc::new(CandleCloseScheduler::new(config.candle_close.clone()));
+    if config.candle_close.enabled {
+        candle_close.start(
+            exchange_manager.subscribe_events(),
+            broadcaster.clone(),
+            kafka_publisher.clone(),
+        );
+    }
+
+    // 启动成交明细记录
+    let trade_tape = Arc::new(TradeTape::new(config.trade_tape.capacity));
+    trade_tape.start(exchange_manager.subscribe_events());
+
+    // 启动订单簿深度指标计算
+    let depth_metrics = Arc::new(DepthMetricsProcessor::new(config.depth_metrics.clone()));
+    if config.depth_metrics.enabled {
+        depth_metrics.start(
+            exchange_manager.subscribe_events(),
+            broadcaster.clone(),
+            kafka_publisher.clone(),
+        );
+    }
+
+    // 启动订单簿深度快照记录
+    let depth_history = Arc::new(DepthHistoryRecorder::new(
+        config.depth_history.clone(),
+        config.storage.clickhouse.as_ref(),
+    ));
+    if config.depth_history.enabled {
+        depth_history.start(exchange_manager.subscribe_events());
+    }
+
+    // 启动K线连续性检测
+    let continuity = Arc::new(KlineContinuityDetector::with_config(config.continuity.clone()));
+    if config.continuity.enabled {
+        let continuity_store = Arc::new(
+            ContinuityStateStore::new(config.storage.redis.as_ref()).await,
+        );
+        continuity.clone().start(
+            exchange_manager.subscribe_events(),
+            MarketDataEvent::closed_kline,
+            continuity_store,
+        );
+        info!("Kline continuity detector started");
+    }
+
+    // 启动告警规则引擎
+    let alert_engine = Arc::new(AlertRuleEngine::new(kafka_publisher.clone()));
+    alert_engine.start(exchange_manager.subscribe_events());
+
+    // 启动大额成交检测
+    let whale_detector = Arc::new(WhaleDetector::new(config.whale_detection.clone()));
+    if config.whale_detection.enabled {
+        whale_detector.start(
+            exchange_manager.subscribe_events(),
+            broadcaster.clone(),
+            kafka_publisher.clone(),
+            alert_engine.clone(),
+        );
+        info!("Whale trade detector started");
+    }
+
+    // 启动Tick数据压缩
+    let tick_compactor = Arc::new(TickCompactor::new(
+        config.compaction.clone(),
+        config.storage.clickhouse.as_ref(),
+    ));
+    if config.compaction.enabled {
+        tick_compactor.start(leader.clone());
+    }
+
+    // 非ClickHouse后端持久化行情事件
+    market_stores.start(exchange_manager.subscribe_events());
+
+    // 停止时把未发出的Kafka事件写入磁盘
+    let egress_publisher = kafka_publisher.clone();
+
     // 创建应用状态
     let app_state = AppState {
         config: config.clone(),
         metrics,
         storage_manager,
+        market_stores,
+        storage_writes,
         data_processor,
         exchange_manager,
+        runtime_subscriptions,
+        shard_coordinator,
+        instrument_sync,
+        chart_cache,
+        rollups,
+        seasonality,
+        analytics_query,
+        broadcaster,
+        delayed_feed,
+        stream_sessions,
+        ws_clients,
+        kafka_publisher,
+        ticker_aggregator,
+        candle_close,
+        trade_tape,
+        depth_metrics,
+        depth_history,
+        continuity,
+        alert_engine,
+        whale_detector,
+        tick_compactor,
     };
 
+    // 指标和管理接口的内部认证
+    let internal_auth = Arc::new(InternalAuth::new(config.internal_auth.clone()));
+
     // 创建中间件层
     let middleware = ServiceBuilder::new()
         .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any));
+        .layer(axum::middleware::from_fn(request_span_middleware))
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        .layer(axum::middleware::from_fn_with_state(internal_auth, internal_auth_middleware))
+        .layer(axum::middleware::from_fn(decimal_format_middleware));
 
     // 创建路由
     let app = create_routes()
+        .merge(request_trace_routes("/api/v1/admin/trace/:request_id", log_handle.request_logs()))
+        .merge(log_level_routes("/api/v1/admin/log-level", log_handle))
         .layer(middleware)
         .with_state(app_state);
 
@@ -93,17 +385,75 @@ async fn main() -> Result<()> {
     info!("📊 Metrics available at http://{}/metrics", addr);
     info!("🏥 Health check available at http://{}/health", addr);
 
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    egress_publisher.shutdown().await;
+    info!("Market Data Service stopped");
 
     Ok(())
 }
 
+/// 等待 Ctrl+C 或 SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutdown signal received");
+}
+
 /// 应用状态
 #[derive(Clone)]
 pub struct AppState {
     pub config: MarketDataConfig,
     pub metrics: Arc<AppMetrics>,
     pub storage_manager: Arc<StorageManager>,
+    pub market_stores: MarketStores,
+    pub storage_writes: Arc<TieredWriter>,
     pub data_processor: Arc<DataProcessor>,
     pub exchange_manager: Arc<ExchangeManager>,
+    pub runtime_subscriptions: Arc<RuntimeSubscriptionManager>,
+    pub shard_coordinator: Arc<ShardCoordinator>,
+    pub instrument_sync: Arc<InstrumentSync>,
+    pub chart_cache: Arc<ChartCache>,
+    pub rollups: Arc<RollupManager>,
+    pub seasonality: Arc<SeasonalityAnalyzer>,
+    pub analytics_query: Arc<AnalyticsQueryEngine>,
+    pub broadcaster: Arc<WebSocketBroadcaster>,
+    pub delayed_feed: Arc<DelayedFeed>,
+    pub stream_sessions: Arc<SessionStore>,
+    pub ws_clients: Arc<ClientRegistry>,
+    pub kafka_publisher: Arc<KafkaPublisher>,
+    pub ticker_aggregator: Arc<RollingTickerAggregator>,
+    pub candle_close: Arc<CandleCloseScheduler>,
+    pub trade_tape: Arc<TradeTape>,
+    pub depth_metrics: Arc<DepthMetricsProcessor>,
+    pub depth_history: Arc<DepthHistoryRecorder>,
+    pub continuity: Arc<KlineContinuityDetector>,
+    pub alert_engine: Arc<AlertRuleEngine>,
+    pub whale_detector: Arc<WhaleDetector>,
+    pub tick_compactor: Arc<TickCompactor>,
 }
\ No newline at end of file
@@ -22,14 +22,13 @@ use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-// 导入连续性检测器
-mod continuity;
-use continuity::KlineContinuityDetector;
+// 连续性检测、配置和WebSocket传输层来自库目标
+use market_data::config::ConnectionConfig;
+use market_data::continuity::KlineContinuityDetector;
+use market_data::transport::websocket_client::{Json as WsJson, ResilientWsClient, ResilientWsConfig, SubscriptionCodec, WsEvent};
+use market_data::transport::ConnectionStats;
 use shared_models::common::DataQuality;
-
-// 导入配置模块
-mod config;
-use config::MarketDataConfig;
+use tokio_tungstenite::tungstenite::Message;
 
 // 使用内置简化存储，不需要外部存储模块
 
@@ -271,123 +270,98 @@ async fn get_tickers(State(state): State<AppState>) -> Result<Json<Value>, Statu
     })))
 }
 
+/// 币安组合流地址，连接建立后按主题发送订阅请求
+const BINANCE_STREAM_URL: &str = "wss://stream.binance.com:9443/stream";
+
+/// 本地HTTP CONNECT代理
+const PROXY_ADDR: &str = "127.0.0.1:4780";
+
+/// 币安组合流的订阅请求
+struct BinanceStreamCodec;
+
+impl SubscriptionCodec for BinanceStreamCodec {
+    fn subscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+        Ok(vec![Message::Text(json!({ "method": "SUBSCRIBE", "params": topics, "id": 1 }).to_string())])
+    }
+
+    fn unsubscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+        Ok(vec![Message::Text(json!({ "method": "UNSUBSCRIBE", "params": topics, "id": 2 }).to_string())])
+    }
+}
+
+/// 组合流消息，订阅应答等其他消息解码失败后忽略
+#[derive(Debug, serde::Deserialize)]
+struct StreamMessage {
+    stream: String,
+    data: Value,
+}
+
 /// 启动WebSocket数据采集
+///
+/// 所有数据流共用一个经代理的可靠连接，断线后由客户端退避重连并重新订阅。
 async fn start_websocket_data_collection(
     market_data: Arc<RwLock<HashMap<String, MarketData>>>,
     storage: SimpleStorage
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    use futures_util::{SinkExt, StreamExt};
-    use tokio_tungstenite::{connect_async, tungstenite::Message};
-    use serde_json::Value;
-    
     info!("🚀 启动WebSocket数据采集...");
     
-    // 主要交易对
+    // 主要交易对的ticker流和BTCUSDT的1分钟K线流
     let symbols = ["btcusdt", "ethusdt", "bnbusdt", "adausdt", "xrpusdt", "solusdt", "dotusdt", "dogeusdt"];
-    
-    // 为每个交易对启动ticker流
-    for symbol in symbols {
-        let market_data_clone = market_data.clone();
-        let storage_clone = storage.clone();
-        let symbol = symbol.to_string();
-        
-        tokio::spawn(async move {
-            loop {
-                match connect_to_binance_ticker(&symbol, market_data_clone.clone(), storage_clone.clone()).await {
-                    Ok(_) => {
-                        info!("✅ {}@ticker WebSocket连接正常结束", symbol);
-                    }
-                    Err(e) => {
-                        tracing::error!("❌ {}@ticker WebSocket连接失败: {}", symbol, e);
-                    }
-                }
-                
-                // 重连延迟
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                info!("🔄 重新连接 {}@ticker WebSocket...", symbol);
-            }
-        });
+    let mut topics: Vec<String> = symbols.iter().map(|symbol| format!("{}@ticker", symbol)).collect();
+    topics.push("btcusdt@kline_1m".to_string());
+
+    let config = ResilientWsConfig {
+        // 与原来的采集循环一致，断线后一直重连
+        max_reconnect_attempts: 0,
+        ..ResilientWsConfig::from_exchange(BINANCE_STREAM_URL, &ConnectionConfig::default())
     }
-    
-    // 为BTCUSDT启动K线流 (1分钟)
-    let market_data_kline = market_data.clone();
-    let storage_kline = storage.clone();
-    tokio::spawn(async move {
-        loop {
-            match connect_to_binance_kline("btcusdt", "1m", market_data_kline.clone(), storage_kline.clone()).await {
-                Ok(_) => {
-                    info!("✅ btcusdt@kline_1m WebSocket连接正常结束");
+    .with_proxy(PROXY_ADDR);
+    let mut client = ResilientWsClient::new(
+        config,
+        Arc::new(BinanceStreamCodec),
+        Arc::new(RwLock::new(ConnectionStats::default())),
+    );
+    client.subscribe(&topics)?;
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    loop {
+        info!("🔗 连接到 {} (通过代理 {})", BINANCE_STREAM_URL, PROXY_ADDR);
+        let sender = sender.clone();
+        let started = client
+            .start(move |event: WsEvent<WsJson<StreamMessage>>| match event {
+                WsEvent::Connected { reconnect } => {
+                    info!("✅ 币安WebSocket已连接{}", if reconnect { " (重连)" } else { "" });
                 }
-                Err(e) => {
-                    tracing::error!("❌ btcusdt@kline_1m WebSocket连接失败: {}", e);
+                WsEvent::Message { message: WsJson(message), .. } => {
+                    let _ = sender.send(message);
                 }
+                WsEvent::DecodeError { .. } => {}
+                WsEvent::Disconnected { reason } => {
+                    warn!("币安WebSocket连接断开: {}", reason);
+                }
+            })
+            .await;
+        match started {
+            Ok(()) => break,
+            Err(e) => {
+                tracing::error!("❌ 币安WebSocket连接失败: {}", e);
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
             }
-            
-            // 重连延迟
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-            info!("🔄 重新连接 btcusdt@kline_1m WebSocket...");
         }
-    });
-    
-    Ok(())
-}
+    }
 
-/// 连接到币安ticker WebSocket流
-async fn connect_to_binance_ticker(
-    symbol: &str,
-    market_data: Arc<RwLock<HashMap<String, MarketData>>>,
-    storage: SimpleStorage
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    use futures_util::{SinkExt, StreamExt};
-    use tokio_tungstenite::{tungstenite::Message, client_async, tungstenite::handshake::client::Request};
-    use tokio::net::TcpStream;
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-    use tokio_native_tls::{TlsConnector, native_tls};
-    
-    let url = format!("wss://stream.binance.com:9443/ws/{}@ticker", symbol);
-    info!("🔗 连接到 {} (通过代理 127.0.0.1:4780)", url);
-    
-    // 通过HTTP CONNECT代理建立WebSocket连接
-    let (ws_stream, _) = connect_websocket_via_proxy(&url).await?;
-    let (mut write, mut read) = ws_stream.split();
-    
-    info!("✅ {}@ticker WebSocket已连接", symbol);
-    
-    // 启动心跳
-    let write_clone = Arc::new(tokio::sync::Mutex::new(write));
-    let write_for_ping = write_clone.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
-        loop {
-            interval.tick().await;
-            let mut write = write_for_ping.lock().await;
-            if let Err(e) = write.send(Message::Ping(vec![])).await {
-                tracing::error!("发送心跳失败: {}", e);
-                break;
-            }
-        }
-    });
-    
-    // 处理消息
-    while let Some(message) = read.next().await {
-        match message {
-            Ok(Message::Text(text)) => {
-                if let Err(e) = process_ticker_message(&text, symbol, &market_data, &storage).await {
-                    tracing::error!("处理ticker消息失败: {}", e);
-                }
-            }
-            Ok(Message::Pong(_)) => {
-                // 心跳响应
-            }
-            Ok(Message::Close(_)) => {
-                info!("{}@ticker WebSocket连接被服务器关闭", symbol);
-                break;
+    while let Some(message) = receiver.recv().await {
+        let Some((symbol, stream)) = message.stream.split_once('@') else {
+            continue;
+        };
+        if stream == "ticker" {
+            if let Err(e) = process_ticker_message(&message.data, symbol, &market_data, &storage).await {
+                tracing::error!("处理ticker消息失败: {}", e);
             }
-            Err(e) => {
-                tracing::error!("{}@ticker WebSocket错误: {}", symbol, e);
-                break;
+        } else if let Some(interval) = stream.strip_prefix("kline_") {
+            if let Err(e) = process_kline_message(&message.data, symbol, interval, &storage).await {
+                tracing::error!("处理K线消息失败: {}", e);
             }
-            _ => {}
         }
     }
     
@@ -396,15 +370,11 @@ async fn connect_to_binance_ticker(
 
 /// 处理ticker消息
 async fn process_ticker_message(
-    message: &str,
+    data: &Value,
     symbol: &str,
     market_data: &Arc<RwLock<HashMap<String, MarketData>>>,
     storage: &SimpleStorage
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    use serde_json::Value;
-    
-    let data: Value = serde_json::from_str(message)?;
-    
     // 解析ticker数据
     let symbol_upper = symbol.to_uppercase();
     let price: f64 = data["c"].as_str().unwrap_or("0").parse().unwrap_or(0.0);
@@ -552,59 +522,6 @@ async fn get_metrics(State(state): State<AppState>) -> Result<String, StatusCode
     Ok(metrics)
 }
 
-/// 通过HTTP CONNECT代理连接WebSocket
-async fn connect_websocket_via_proxy(
-    ws_url: &str
-) -> Result<(tokio_tungstenite::WebSocketStream<tokio_native_tls::TlsStream<tokio::net::TcpStream>>, tokio_tungstenite::tungstenite::handshake::client::Response), Box<dyn std::error::Error + Send + Sync>> {
-    use tokio::net::TcpStream;
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-    use tokio_native_tls::{TlsConnector, native_tls};
-    use tokio_tungstenite::{client_async, tungstenite::handshake::client::Request};
-    
-    // 代理服务器地址
-    let proxy_addr = "127.0.0.1:4780";
-    
-    // 1. 连接到代理服务器
-    info!("📡 连接到代理服务器: {}", proxy_addr);
-    let mut stream = TcpStream::connect(proxy_addr).await?;
-    
-    // 2. 发送HTTP CONNECT请求建立隧道
-    let connect_request = "CONNECT stream.binance.com:9443 HTTP/1.1\r\nHost: stream.binance.com:9443\r\nProxy-Connection: Keep-Alive\r\n\r\n";
-    stream.write_all(connect_request.as_bytes()).await?;
-    
-    // 3. 读取代理响应
-    let mut buffer = vec![0u8; 1024];
-    let n = stream.read(&mut buffer).await?;
-    let response = String::from_utf8_lossy(&buffer[..n]);
-    
-    if !response.starts_with("HTTP/1.1 200") {
-        return Err(format!("代理连接失败: {}", response).into());
-    }
-    
-    info!("✅ 代理隧道建立成功");
-    
-    // 4. 升级到TLS连接
-    let connector = TlsConnector::from(native_tls::TlsConnector::new()?);
-    let tls_stream = connector.connect("stream.binance.com", stream).await?;
-    
-    info!("🔒 TLS连接建立成功");
-    
-    // 5. 建立WebSocket连接
-    let request = Request::builder()
-        .uri(ws_url)
-        .header("Host", "stream.binance.com")
-        .header("Upgrade", "websocket")
-        .header("Connection", "Upgrade")
-        .header("Sec-WebSocket-Key", tokio_tungstenite::tungstenite::handshake::client::generate_key())
-        .header("Sec-WebSocket-Version", "13")
-        .body(())?;
-    
-    let (ws_stream, response) = client_async(request, tls_stream).await?;
-    
-    info!("🚀 WebSocket连接建立成功");
-    
-    Ok((ws_stream, response))
-}
 /// K线数据结构
 #[derive(Debug, Clone)]
 pub struct KlineData {
@@ -636,77 +553,13 @@ fn get_continuity_detector() -> &'static Arc<KlineContinuityDetector> {
     CONTINUITY_DETECTOR.get_or_init(|| Arc::new(KlineContinuityDetector::new()))
 }
 
-/// 连接到币安K线WebSocket流
-async fn connect_to_binance_kline(
-    symbol: &str,
-    interval: &str,
-    market_data: Arc<RwLock<HashMap<String, MarketData>>>,
-    storage: SimpleStorage
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    use futures_util::{SinkExt, StreamExt};
-    use tokio_tungstenite::{tungstenite::Message};
-    
-    let url = format!("wss://stream.binance.com:9443/ws/{}@kline_{}", symbol, interval);
-    info!("🔗 连接到 {} (K线数据)", url);
-    
-    // 通过HTTP CONNECT代理建立WebSocket连接
-    let (ws_stream, _) = connect_websocket_via_proxy(&url).await?;
-    let (mut write, mut read) = ws_stream.split();
-    
-    info!("✅ {}@kline_{} WebSocket已连接", symbol, interval);
-    
-    // 启动心跳
-    let write_clone = Arc::new(tokio::sync::Mutex::new(write));
-    let write_for_ping = write_clone.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
-        loop {
-            interval.tick().await;
-            let mut write = write_for_ping.lock().await;
-            if let Err(e) = write.send(Message::Ping(vec![])).await {
-                tracing::error!("发送心跳失败: {}", e);
-                break;
-            }
-        }
-    });
-    
-    // 处理消息
-    while let Some(message) = read.next().await {
-        match message {
-            Ok(Message::Text(text)) => {
-                if let Err(e) = process_kline_message(&text, symbol, interval, &storage).await {
-                    tracing::error!("处理K线消息失败: {}", e);
-                }
-            }
-            Ok(Message::Pong(_)) => {
-                // 心跳响应
-            }
-            Ok(Message::Close(_)) => {
-                info!("{}@kline_{} WebSocket连接被服务器关闭", symbol, interval);
-                break;
-            }
-            Err(e) => {
-                tracing::error!("{}@kline_{} WebSocket错误: {}", symbol, interval, e);
-                break;
-            }
-            _ => {}
-        }
-    }
-    
-    Ok(())
-}
-
 /// 处理K线消息
 async fn process_kline_message(
-    message: &str,
+    data: &Value,
     symbol: &str,
     interval: &str,
     storage: &SimpleStorage
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    use serde_json::Value;
-    
-    let data: Value = serde_json::from_str(message)?;
-    
     // 解析K线数据
     if let Some(k) = data.get("k") {
         let symbol_upper = symbol.to_uppercase();
@@ -0,0 +1,436 @@
+use anyhow::{anyhow, Result};
+use futures_util::future::join_all;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::Serialize;
+use shared_protocols::kafka::KafkaMessage;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{debug, error, info, warn};
+
+use super::spill::{SpillPosition, SpillQueue, SpilledRecord};
+use crate::config::{KafkaConfig, KafkaEgressConfig};
+
+const SOURCE: &str = "market-data";
+
+/// Kafka事件发布器
+///
+/// 未配置Kafka时所有发布操作为空操作。
+#[derive(Clone)]
+pub struct KafkaPublisher {
+    producer: Option<FutureProducer>,
+    send_timeout: Duration,
+    egress: Option<Arc<Egress>>,
+}
+
+/// 发布缓冲统计
+#[derive(Debug, Clone, Serialize)]
+pub struct EgressStats {
+    /// 内存队列中等待发送的条数
+    pub buffered: usize,
+    /// 磁盘溢出队列中尚未读取的条数
+    pub spilled: u64,
+    /// 磁盘溢出文件占用字节数
+    pub spill_bytes: u64,
+    /// 队列写满被拒绝的条数
+    pub dropped: u64,
+    pub last_error: Option<String>,
+}
+
+impl EgressStats {
+    /// 是否有积压
+    pub fn has_backlog(&self) -> bool {
+        self.spilled > 0 || self.dropped > 0
+    }
+}
+
+struct EgressState {
+    /// 按发送顺序排列；从磁盘读回的消息带有其溢出位置，发送成功后提交
+    memory: VecDeque<(SpilledRecord, Option<SpillPosition>)>,
+    spill: Option<SpillQueue>,
+    dropped: u64,
+    last_error: Option<String>,
+    /// 停止后新事件直接写入溢出队列
+    closing: bool,
+}
+
+/// 发布缓冲
+///
+/// 所有事件经同一个队列按顺序发送，同一键的事件不会乱序。磁盘溢出队列中
+/// 还有积压时，新事件继续写入磁盘，保证积压先于新事件发出。发送至少一次，
+/// 部分失败时从第一条失败的消息开始重发，可能产生重复。写入磁盘的消息由刷盘任务
+/// 成批 fsync，停止时内存队列中未发出的消息也写入磁盘。
+struct Egress {
+    config: KafkaEgressConfig,
+    state: Mutex<EgressState>,
+    notify: Notify,
+    /// 溢出队列有新写入，等待刷盘
+    spilled: Notify,
+}
+
+impl Egress {
+    fn new(config: KafkaEgressConfig) -> Self {
+        let spill = match SpillQueue::open(
+            &config.spill_dir,
+            config.segment_max_bytes,
+            config.max_spill_bytes,
+        ) {
+            Ok(spill) => {
+                if spill.unread() > 0 {
+                    info!(
+                        "Kafka spill queue has {} pending events from previous run",
+                        spill.unread()
+                    );
+                }
+                Some(spill)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to open Kafka spill dir {}, buffering in memory only: {}",
+                    config.spill_dir, e
+                );
+                None
+            }
+        };
+
+        Self {
+            state: Mutex::new(EgressState {
+                memory: VecDeque::with_capacity(config.memory_buffer),
+                spill,
+                dropped: 0,
+                last_error: None,
+                closing: false,
+            }),
+            notify: Notify::new(),
+            spilled: Notify::new(),
+            config,
+        }
+    }
+
+    fn enqueue(&self, record: SpilledRecord) -> Result<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            let spill_pending = state.spill.as_ref().is_some_and(|spill| spill.unread() > 0);
+
+            if !spill_pending && !state.closing && state.memory.len() < self.config.memory_buffer {
+                state.memory.push_back((record, None));
+            } else {
+                let result = match state.spill.as_mut() {
+                    Some(spill) => spill.append(&record),
+                    None => Err(anyhow!("Kafka egress buffer is full")),
+                };
+                if let Err(e) = result {
+                    state.dropped += 1;
+                    state.last_error = Some(e.to_string());
+                    return Err(e);
+                }
+                self.spilled.notify_one();
+            }
+        }
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// 刷盘溢出队列的新写入
+    fn sync_spill(&self) {
+        let mut state = self.state.lock().unwrap();
+        let result = match state.spill.as_mut() {
+            Some(spill) => spill.sync(),
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            error!("Failed to sync Kafka spill queue: {}", e);
+            state.last_error = Some(e.to_string());
+        }
+    }
+
+    /// 刷盘任务：等待期间的多次写入合并为一次 fsync
+    async fn run_sync(self: Arc<Self>) {
+        loop {
+            self.spilled.notified().await;
+            let egress = self.clone();
+            if let Err(e) = tokio::task::spawn_blocking(move || egress.sync_spill()).await {
+                error!("Kafka spill sync task failed: {}", e);
+            }
+        }
+    }
+
+    /// 停止发布：在期限内等待内存队列发出，剩余消息按顺序追加到溢出队列并刷盘
+    ///
+    /// 从磁盘读回的消息尚未提交，重启后会重新读取，不再重复写入。
+    async fn shutdown(&self) {
+        let deadline = Instant::now() + Duration::from_millis(self.config.shutdown_timeout_ms);
+        while self.stats().buffered > 0 && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.closing = true;
+        let pending: Vec<SpilledRecord> = state
+            .memory
+            .drain(..)
+            .filter_map(|(record, position)| position.is_none().then_some(record))
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+        let EgressState {
+            spill,
+            dropped,
+            last_error,
+            ..
+        } = &mut *state;
+        let Some(spill) = spill.as_mut() else {
+            *dropped += pending.len() as u64;
+            warn!("Dropped {} buffered Kafka events on shutdown: no spill queue", pending.len());
+            return;
+        };
+        let mut spilled = 0;
+        for record in &pending {
+            match spill.append(record) {
+                Ok(()) => spilled += 1,
+                Err(e) => {
+                    *dropped += 1;
+                    *last_error = Some(e.to_string());
+                }
+            }
+        }
+        if let Err(e) = spill.sync() {
+            error!("Failed to sync Kafka spill queue on shutdown: {}", e);
+        }
+        info!(
+            "Spilled {}/{} buffered Kafka events on shutdown",
+            spilled,
+            pending.len()
+        );
+    }
+
+    /// 取出下一批待发送的消息，内存队列为空时从磁盘读回
+    fn next_batch(&self) -> Vec<SpilledRecord> {
+        let mut state = self.state.lock().unwrap();
+        if state.memory.is_empty() {
+            let read = match state.spill.as_mut() {
+                Some(spill) if spill.unread() > 0 => spill.read(self.config.send_batch_size),
+                _ => Ok(Vec::new()),
+            };
+            match read {
+                Ok(records) => state
+                    .memory
+                    .extend(records.into_iter().map(|(record, position)| (record, Some(position)))),
+                Err(e) => {
+                    error!("Failed to read Kafka spill queue: {}", e);
+                    state.last_error = Some(e.to_string());
+                }
+            }
+        }
+
+        state
+            .memory
+            .iter()
+            .take(self.config.send_batch_size.max(1))
+            .map(|(record, _)| record.clone())
+            .collect()
+    }
+
+    /// 移除已发送的前 sent 条消息并提交磁盘位置
+    fn complete(&self, sent: usize, failure: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        let mut position = None;
+        for _ in 0..sent {
+            if let Some((_, Some(sent_position))) = state.memory.pop_front() {
+                position = Some(sent_position);
+            }
+        }
+
+        if let (Some(position), Some(spill)) = (position, state.spill.as_mut()) {
+            if let Err(e) = spill.commit(position) {
+                error!("Failed to commit Kafka spill position: {}", e);
+            }
+        }
+        if failure.is_some() {
+            state.last_error = failure;
+        }
+    }
+
+    fn stats(&self) -> EgressStats {
+        let state = self.state.lock().unwrap();
+        EgressStats {
+            buffered: state.memory.len(),
+            spilled: state.spill.as_ref().map(|spill| spill.unread()).unwrap_or(0),
+            spill_bytes: state.spill.as_ref().map(|spill| spill.size_bytes()).unwrap_or(0),
+            dropped: state.dropped,
+            last_error: state.last_error.clone(),
+        }
+    }
+
+    async fn run(self: Arc<Self>, producer: FutureProducer, send_timeout: Duration) {
+        loop {
+            let batch = self.next_batch();
+            if batch.is_empty() {
+                let _ = tokio::time::timeout(Duration::from_secs(1), self.notify.notified()).await;
+                continue;
+            }
+
+            // 按顺序创建发送请求，librdkafka按入队顺序发送
+            let results = join_all(batch.iter().map(|record| {
+                producer.send(
+                    FutureRecord::to(&record.topic)
+                        .key(&record.key)
+                        .payload(&record.payload),
+                    send_timeout,
+                )
+            }))
+            .await;
+
+            let sent = results.iter().take_while(|result| result.is_ok()).count();
+            let failure = results
+                .iter()
+                .find_map(|result| result.as_ref().err())
+                .map(|(e, _)| e.to_string());
+            self.complete(sent, failure.clone());
+
+            if let Some(e) = failure {
+                warn!(
+                    "Kafka publish failed after {}/{} events, retrying: {}",
+                    sent,
+                    batch.len(),
+                    e
+                );
+                tokio::time::sleep(Duration::from_millis(self.config.retry_backoff_ms)).await;
+            }
+        }
+    }
+}
+
+impl KafkaPublisher {
+    /// 根据配置创建发布器
+    pub fn new(config: Option<&KafkaConfig>) -> Result<Self> {
+        let send_timeout = Duration::from_secs(5);
+        let producer = match config {
+            Some(config) => {
+                config.validate()?;
+
+                let mut client_config = ClientConfig::new();
+                client_config
+                    .set("bootstrap.servers", config.broker_list())
+                    .set("batch.size", config.batch_size.to_string())
+                    .set("linger.ms", config.linger_ms.to_string())
+                    .set("compression.type", &config.compression_type)
+                    .set("acks", &config.acks)
+                    .set("retries", config.retries.to_string());
+
+                if let Some(protocol) = &config.security_protocol {
+                    client_config.set("security.protocol", protocol);
+                }
+                if let Some(mechanism) = &config.sasl_mechanism {
+                    client_config.set("sasl.mechanism", mechanism);
+                }
+                if let Some(username) = &config.sasl_username {
+                    client_config.set("sasl.username", username);
+                }
+                if let Some(password) = &config.sasl_password {
+                    client_config.set("sasl.password", password);
+                }
+
+                info!("Kafka publisher connecting to {}", config.broker_list());
+                Some(client_config.create::<FutureProducer>()?)
+            }
+            None => {
+                info!("Kafka not configured, publisher disabled");
+                None
+            }
+        };
+
+        let egress = match (&producer, config) {
+            (Some(producer), Some(config)) if config.egress.enabled => {
+                let egress = Arc::new(Egress::new(config.egress.clone()));
+                tokio::spawn(egress.clone().run(producer.clone(), send_timeout));
+                tokio::spawn(egress.clone().run_sync());
+                Some(egress)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            producer,
+            send_timeout,
+            egress,
+        })
+    }
+
+    /// 创建禁用的发布器
+    pub fn disabled() -> Self {
+        Self {
+            producer: None,
+            send_timeout: Duration::from_secs(5),
+            egress: None,
+        }
+    }
+
+    /// 发布是否可用
+    pub fn is_enabled(&self) -> bool {
+        self.producer.is_some()
+    }
+
+    /// 发布缓冲统计，未启用缓冲时为 None
+    pub fn egress_stats(&self) -> Option<EgressStats> {
+        self.egress.as_ref().map(|egress| egress.stats())
+    }
+
+    /// 进程退出前调用，未发出的缓冲事件写入磁盘溢出队列
+    pub async fn shutdown(&self) {
+        if let Some(egress) = &self.egress {
+            egress.shutdown().await;
+        }
+    }
+
+    /// 发布事件
+    ///
+    /// 启用发布缓冲时事件入队后立即返回，只有缓冲和溢出队列都写满时才返回错误。
+    pub async fn publish<T: Serialize>(
+        &self,
+        topic: &str,
+        key: &str,
+        event_type: &str,
+        data: T,
+    ) -> Result<()> {
+        let producer = match &self.producer {
+            Some(producer) => producer,
+            None => return Ok(()),
+        };
+
+        let message = KafkaMessage::new(event_type, SOURCE, data);
+        let payload = serde_json::to_string(&message)?;
+
+        if let Some(egress) = &self.egress {
+            egress
+                .enqueue(SpilledRecord {
+                    topic: topic.to_string(),
+                    key: key.to_string(),
+                    payload,
+                })
+                .map_err(|e| {
+                    warn!("Dropped {} for {}: {}", event_type, topic, e);
+                    e
+                })?;
+            debug!("Queued {} for {} (key: {})", event_type, topic, key);
+            return Ok(());
+        }
+
+        producer
+            .send(
+                FutureRecord::to(topic).key(key).payload(&payload),
+                self.send_timeout,
+            )
+            .await
+            .map_err(|(e, _)| {
+                warn!("Failed to publish {} to {}: {}", event_type, topic, e);
+                anyhow!("Kafka publish failed: {}", e)
+            })?;
+
+        debug!("Published {} to {} (key: {})", event_type, topic, key);
+        Ok(())
+    }
+}
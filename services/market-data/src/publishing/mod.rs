@@ -0,0 +1,4 @@
+pub mod kafka;
+pub mod spill;
+
+pub use kafka::KafkaPublisher;
@@ -0,0 +1,287 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const SEGMENT_SUFFIX: &str = ".log";
+const CURSOR_FILE: &str = "cursor";
+
+/// 溢出到磁盘的待发布消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpilledRecord {
+    pub topic: String,
+    pub key: String,
+    pub payload: String,
+}
+
+/// 消息在溢出队列中的位置：段序号和该消息之后的字节偏移
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SpillPosition {
+    pub segment: u64,
+    pub offset: u64,
+}
+
+/// 磁盘溢出队列
+///
+/// 消息按行追加到编号递增的段文件，段写满后切换到下一个段。读取不会删除数据，
+/// 调用方确认发送成功后提交位置，已全部确认的段才会删除；确认位置写入游标文件，
+/// 进程重启后从游标处继续，未确认的消息会重新发送。
+pub struct SpillQueue {
+    dir: PathBuf,
+    segment_max_bytes: u64,
+    max_bytes: u64,
+    /// 未删除的段及其大小，按序号升序
+    segments: VecDeque<(u64, u64)>,
+    writer: Option<File>,
+    reader: Option<(u64, BufReader<File>)>,
+    read_position: SpillPosition,
+    /// 已写入但尚未读取的消息数
+    unread: u64,
+    /// 当前段有尚未刷盘的写入
+    dirty: bool,
+}
+
+fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+    dir.join(format!("{:020}{}", segment, SEGMENT_SUFFIX))
+}
+
+impl SpillQueue {
+    /// 打开溢出目录，恢复已有的段和确认位置
+    pub fn open(dir: impl Into<PathBuf>, segment_max_bytes: u64, max_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(segment) = name
+                .strip_suffix(SEGMENT_SUFFIX)
+                .and_then(|id| id.parse::<u64>().ok())
+            {
+                segments.push((segment, entry.metadata()?.len()));
+            }
+        }
+        segments.sort_unstable();
+
+        let cursor = fs::read_to_string(dir.join(CURSOR_FILE))
+            .ok()
+            .and_then(|text| {
+                let (segment, offset) = text.trim().split_once(' ')?;
+                Some(SpillPosition {
+                    segment: segment.parse().ok()?,
+                    offset: offset.parse().ok()?,
+                })
+            });
+
+        let mut queue = Self {
+            dir,
+            segment_max_bytes: segment_max_bytes.max(1),
+            max_bytes,
+            segments: segments.into_iter().collect(),
+            writer: None,
+            reader: None,
+            read_position: SpillPosition { segment: 0, offset: 0 },
+            unread: 0,
+            dirty: false,
+        };
+
+        // 删除游标之前已确认的段
+        if let Some(cursor) = cursor {
+            queue.remove_segments_before(cursor.segment)?;
+        }
+        queue.read_position = match (cursor, queue.segments.front()) {
+            (Some(cursor), Some(&(first, _))) if cursor.segment == first => cursor,
+            (_, Some(&(first, _))) => SpillPosition { segment: first, offset: 0 },
+            (_, None) => SpillPosition { segment: 0, offset: 0 },
+        };
+        queue.unread = queue.count_unread()?;
+        Ok(queue)
+    }
+
+    /// 尚未读取的消息数
+    pub fn unread(&self) -> u64 {
+        self.unread
+    }
+
+    /// 磁盘占用字节数
+    pub fn size_bytes(&self) -> u64 {
+        self.segments.iter().map(|(_, size)| size).sum()
+    }
+
+    fn count_unread(&self) -> Result<u64> {
+        let mut count = 0;
+        for &(segment, _) in &self.segments {
+            let mut file = File::open(segment_path(&self.dir, segment))?;
+            if segment == self.read_position.segment {
+                file.seek(SeekFrom::Start(self.read_position.offset))?;
+            }
+            // 只统计完整的行，崩溃时写了一半的行会被跳过
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            count += bytes.iter().filter(|b| **b == b'\n').count() as u64;
+        }
+        Ok(count)
+    }
+
+    /// 追加一条消息
+    pub fn append(&mut self, record: &SpilledRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let len = line.len() as u64;
+
+        if self.size_bytes() + len > self.max_bytes {
+            return Err(anyhow!(
+                "Kafka spill queue is full ({} bytes)",
+                self.max_bytes
+            ));
+        }
+
+        let rotate = match self.segments.back() {
+            Some(&(_, size)) => self.writer.is_none() || size + len > self.segment_max_bytes,
+            None => true,
+        };
+        if rotate {
+            // 切换前把旧段刷盘，之后只需同步当前段
+            self.sync()?;
+            // 重启后不续写旧段，始终从新段开始
+            let next = self.segments.back().map(|(id, _)| id + 1).unwrap_or(self.read_position.segment);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(segment_path(&self.dir, next))?;
+            self.segments.push_back((next, 0));
+            self.writer = Some(file);
+        }
+
+        let writer = self.writer.as_mut().expect("spill writer opened above");
+        writer.write_all(&line)?;
+        if let Some((_, size)) = self.segments.back_mut() {
+            *size += len;
+        }
+        self.unread += 1;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// 把已追加的消息刷到磁盘，返回后进程崩溃也不会丢失；没有新写入时不做处理
+    pub fn sync(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.sync_data()?;
+        }
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// 按顺序读取最多 max 条尚未读取的消息及其位置
+    pub fn read(&mut self, max: usize) -> Result<Vec<(SpilledRecord, SpillPosition)>> {
+        let mut records = Vec::new();
+        while records.len() < max && self.unread > 0 {
+            let SpillPosition { segment, offset } = self.read_position;
+            if self.reader.as_ref().map(|(id, _)| *id) != Some(segment) {
+                let mut file = File::open(segment_path(&self.dir, segment))?;
+                file.seek(SeekFrom::Start(offset))?;
+                self.reader = Some((segment, BufReader::new(file)));
+            }
+            let (_, reader) = self.reader.as_mut().expect("spill reader opened above");
+
+            let mut line = String::new();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 || !line.ends_with('\n') {
+                // 当前段已读完，切换到下一个段
+                let next = self
+                    .segments
+                    .iter()
+                    .map(|(id, _)| *id)
+                    .find(|id| *id > segment)
+                    .ok_or_else(|| anyhow!("Kafka spill segment {} is truncated", segment))?;
+                self.read_position = SpillPosition { segment: next, offset: 0 };
+                self.reader = None;
+                continue;
+            }
+
+            self.read_position.offset += read as u64;
+            self.unread -= 1;
+            let record = serde_json::from_str(line.trim_end())?;
+            records.push((record, self.read_position));
+        }
+        Ok(records)
+    }
+
+    /// 确认位置之前的消息已发送，删除已全部确认的段
+    pub fn commit(&mut self, position: SpillPosition) -> Result<()> {
+        fs::write(
+            self.dir.join(CURSOR_FILE),
+            format!("{} {}", position.segment, position.offset),
+        )?;
+        self.remove_segments_before(position.segment)
+    }
+
+    fn remove_segments_before(&mut self, segment: u64) -> Result<()> {
+        while let Some(&(id, _)) = self.segments.front() {
+            if id >= segment {
+                break;
+            }
+            fs::remove_file(segment_path(&self.dir, id))?;
+            self.segments.pop_front();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(key: &str, seq: usize) -> SpilledRecord {
+        SpilledRecord {
+            topic: "market.trades".to_string(),
+            key: key.to_string(),
+            payload: format!("{{\"seq\":{}}}", seq),
+        }
+    }
+
+    #[test]
+    fn test_spill_queue_order_and_recovery() {
+        let dir = std::env::temp_dir().join(format!("kafka-spill-{}", uuid::Uuid::new_v4()));
+        let mut queue = SpillQueue::open(&dir, 200, 1 << 20).unwrap();
+        for seq in 0..10 {
+            queue.append(&record("BTCUSDT", seq)).unwrap();
+        }
+        assert!(queue.segments.len() > 1);
+
+        let first = queue.read(4).unwrap();
+        let expected: Vec<_> = (0..4).map(|seq| record("BTCUSDT", seq)).collect();
+        assert_eq!(first.iter().map(|(r, _)| r.clone()).collect::<Vec<_>>(), expected);
+        queue.commit(first[3].1).unwrap();
+        // 读取但未确认的消息在重启后重新投递
+        queue.read(2).unwrap();
+        drop(queue);
+
+        let mut queue = SpillQueue::open(&dir, 200, 1 << 20).unwrap();
+        assert_eq!(queue.unread(), 6);
+        queue.append(&record("BTCUSDT", 10)).unwrap();
+        let rest = queue.read(100).unwrap();
+        let seqs: Vec<_> = rest.iter().map(|(r, _)| r.payload.clone()).collect();
+        assert_eq!(seqs, (4..11).map(|s| format!("{{\"seq\":{}}}", s)).collect::<Vec<_>>());
+
+        queue.commit(rest.last().unwrap().1).unwrap();
+        assert_eq!(queue.segments.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_spill_queue_capacity() {
+        let dir = std::env::temp_dir().join(format!("kafka-spill-{}", uuid::Uuid::new_v4()));
+        let mut queue = SpillQueue::open(&dir, 1 << 20, 100).unwrap();
+        queue.append(&record("BTCUSDT", 0)).unwrap();
+        assert!(queue.append(&record("BTCUSDT", 1)).is_err());
+        assert_eq!(queue.unread(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
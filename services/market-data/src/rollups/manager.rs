@@ -0,0 +1,652 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shared_models::common::{DataQuality, Exchange, Interval};
+use shared_models::market::Kline;
+use shared_utils::{Fence, LeaderElection};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use super::sql;
+use crate::charting::{align_to_interval, millis_to_datetime};
+use crate::config::{ClickHouseConfig, RollupConfig};
+use crate::schema::{DecimalKind, DecimalSchema};
+
+/// 汇总表或源表聚合后的一行
+#[derive(Debug, Clone, PartialEq, clickhouse::Row, Deserialize)]
+pub struct RollupRow {
+    pub open_time_ms: i64,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: String,
+    pub quote_volume: String,
+    pub taker_buy_base_volume: String,
+    pub taker_buy_quote_volume: String,
+    pub trades_count: u64,
+    pub source_count: u64,
+    pub worst_quality: u8,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct SymbolRow {
+    exchange: String,
+    symbol: String,
+}
+
+impl RollupRow {
+    /// 转换为K线，未满周期或包含异常数据的K线标记为可疑
+    ///
+    /// 数值无法无损转换为 rust_decimal 时返回错误。
+    pub fn to_kline(
+        &self,
+        decimals: &DecimalSchema,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: &Interval,
+        now_ms: i64,
+    ) -> Result<Kline> {
+        let interval_ms = interval.to_millis();
+        let close_time_ms = self.open_time_ms + interval_ms - 1;
+        let expected_minutes = (interval_ms / 60_000) as u64;
+
+        let data_quality = match self.worst_quality {
+            2 => DataQuality::Suspect,
+            1 => DataQuality::Recovered,
+            _ if close_time_ms < now_ms && self.source_count < expected_minutes => {
+                DataQuality::Suspect
+            }
+            _ => DataQuality::Normal,
+        };
+
+        let price = |value: &str| decimals.parse(symbol, DecimalKind::Price, value);
+        let amount = |value: &str| decimals.parse(symbol, DecimalKind::Amount, value);
+
+        Ok(Kline {
+            id: None,
+            exchange: exchange.clone(),
+            symbol: symbol.to_string(),
+            interval: interval.clone(),
+            open_time: millis_to_datetime(self.open_time_ms),
+            close_time: millis_to_datetime(close_time_ms),
+            open: price(&self.open)?,
+            high: price(&self.high)?,
+            low: price(&self.low)?,
+            close: price(&self.close)?,
+            volume: amount(&self.volume)?,
+            quote_volume: amount(&self.quote_volume)?,
+            trades_count: self.trades_count.min(u32::MAX as u64) as u32,
+            taker_buy_base_volume: amount(&self.taker_buy_base_volume)?,
+            taker_buy_quote_volume: amount(&self.taker_buy_quote_volume)?,
+            is_closed: close_time_ms < now_ms,
+            data_quality,
+        })
+    }
+}
+
+/// 汇总数据与源数据的差异
+#[derive(Debug, Clone, Serialize)]
+pub struct RollupMismatch {
+    pub open_time: i64,
+    /// missing_in_rollup / missing_in_source / values_differ
+    pub kind: String,
+    pub detail: Option<String>,
+}
+
+/// 一致性检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsistencyReport {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub rollup_buckets: usize,
+    pub source_buckets: usize,
+    pub mismatches: Vec<RollupMismatch>,
+    pub repaired: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// 对比汇总数据与源数据（两者均需按 open_time_ms 升序）
+pub fn compare_rows(rollup: &[RollupRow], source: &[RollupRow]) -> Vec<RollupMismatch> {
+    let mut mismatches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < rollup.len() || j < source.len() {
+        match (rollup.get(i), source.get(j)) {
+            (Some(r), Some(s)) if r.open_time_ms == s.open_time_ms => {
+                if r != s {
+                    mismatches.push(RollupMismatch {
+                        open_time: r.open_time_ms,
+                        kind: "values_differ".to_string(),
+                        detail: Some(format!(
+                            "rollup close={} volume={} count={}, source close={} volume={} count={}",
+                            r.close, r.volume, r.source_count, s.close, s.volume, s.source_count
+                        )),
+                    });
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(r), Some(s)) if r.open_time_ms < s.open_time_ms => {
+                mismatches.push(RollupMismatch {
+                    open_time: r.open_time_ms,
+                    kind: "missing_in_source".to_string(),
+                    detail: None,
+                });
+                i += 1;
+            }
+            (Some(r), None) => {
+                mismatches.push(RollupMismatch {
+                    open_time: r.open_time_ms,
+                    kind: "missing_in_source".to_string(),
+                    detail: None,
+                });
+                i += 1;
+            }
+            (_, Some(s)) => {
+                mismatches.push(RollupMismatch {
+                    open_time: s.open_time_ms,
+                    kind: "missing_in_rollup".to_string(),
+                    detail: None,
+                });
+                j += 1;
+            }
+            (None, None) => break,
+        }
+    }
+
+    mismatches
+}
+
+/// 选择目标周期的最佳物化源：能整除目标周期的最大物化周期
+pub fn best_rollup_for(intervals: &[Interval], target: &Interval) -> Option<Interval> {
+    let target_ms = target.to_millis();
+    intervals
+        .iter()
+        .filter(|i| i.to_millis() <= target_ms && target_ms % i.to_millis() == 0)
+        .max_by_key(|i| i.to_millis())
+        .cloned()
+}
+
+/// K线物化汇总管理
+///
+/// 在ClickHouse中为每个汇总周期维护一张AggregatingMergeTree表和一个物化视图，
+/// 1分钟K线写入源表时同步增量更新所有汇总表。
+#[derive(Clone)]
+pub struct RollupManager {
+    config: RollupConfig,
+    database: String,
+    client: Option<clickhouse::Client>,
+    decimals: DecimalSchema,
+    last_check: Arc<RwLock<Vec<ConsistencyReport>>>,
+}
+
+impl RollupManager {
+    pub fn new(config: RollupConfig, clickhouse_config: Option<&ClickHouseConfig>) -> Self {
+        let client = clickhouse_config.filter(|_| config.enabled).map(|c| {
+            clickhouse::Client::default()
+                .with_url(&c.url)
+                .with_database(&c.database)
+                .with_user(&c.username)
+                .with_password(&c.password)
+        });
+
+        Self {
+            config,
+            database: clickhouse_config
+                .map(|c| c.database.clone())
+                .unwrap_or_else(|| "market_data".to_string()),
+            client,
+            decimals: DecimalSchema::new(
+                &clickhouse_config.map(|c| c.decimals.clone()).unwrap_or_default(),
+            ),
+            last_check: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.client.is_some()
+    }
+
+    pub fn config(&self) -> &RollupConfig {
+        &self.config
+    }
+
+    fn client(&self) -> Result<&clickhouse::Client> {
+        self.client
+            .as_ref()
+            .ok_or_else(|| anyhow!("K-line rollups are disabled"))
+    }
+
+    /// 目标周期可用的物化源
+    pub fn source_for(&self, target: &Interval) -> Option<Interval> {
+        if !self.is_enabled() {
+            return None;
+        }
+        best_rollup_for(&self.config.intervals, target)
+    }
+
+    /// 创建汇总表和物化视图
+    pub async fn ensure_views(&self) -> Result<()> {
+        let client = self.client()?;
+        for interval in &self.config.intervals {
+            let table = self.config.table_for(interval);
+            client
+                .query(&sql::create_rollup_table(&self.database, &table, &self.decimals))
+                .execute()
+                .await?;
+            client
+                .query(&sql::create_materialized_view(
+                    &self.database,
+                    &self.config.source_table,
+                    &table,
+                    interval,
+                    &self.decimals,
+                ))
+                .execute()
+                .await?;
+        }
+        info!(
+            "K-line rollups ready for {:?}",
+            self.config
+                .intervals
+                .iter()
+                .map(|i| i.as_str())
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    async fn fetch_rollup(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        interval: &Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<RollupRow>> {
+        let table = self.config.table_for(interval);
+        Ok(self
+            .client()?
+            .query(&sql::query_rollup(&self.database, &table, exchange, symbol, start, end))
+            .fetch_all::<RollupRow>()
+            .await?)
+    }
+
+    async fn fetch_source(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        interval: &Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<RollupRow>> {
+        Ok(self
+            .client()?
+            .query(&sql::query_raw_aggregate(
+                &self.database,
+                &self.config.source_table,
+                interval,
+                exchange,
+                symbol,
+                start,
+                end,
+                &self.decimals,
+            ))
+            .fetch_all::<RollupRow>()
+            .await?)
+    }
+
+    /// 对齐到周期边界，保证汇总与源数据按完整时间桶比较
+    fn aligned_range(
+        interval: &Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> (DateTime<Utc>, DateTime<Utc>) {
+        let interval_ms = interval.to_millis();
+        let start_ms = align_to_interval(start.timestamp_millis(), interval_ms);
+        let end_ms = align_to_interval(end.timestamp_millis() + interval_ms - 1, interval_ms);
+        (millis_to_datetime(start_ms), millis_to_datetime(end_ms))
+    }
+
+    /// 查询物化K线
+    pub async fn query_klines(
+        &self,
+        exchange: &Exchange,
+        symbol: &str,
+        interval: &Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Kline>> {
+        if !self.config.has_interval(interval) {
+            return Err(anyhow!("Interval {} is not materialized", interval));
+        }
+
+        let symbol = symbol.to_uppercase();
+        let (start, end) = Self::aligned_range(interval, start, end);
+        let rows = self
+            .fetch_rollup(exchange.as_str(), &symbol, interval, start, end)
+            .await?;
+
+        let now_ms = Utc::now().timestamp_millis();
+        rows.iter()
+            .map(|row| row.to_kline(&self.decimals, exchange, &symbol, interval, now_ms))
+            .collect()
+    }
+
+    /// 对比汇总表与源数据，可选自动修复
+    pub async fn check_consistency(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        interval: &Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        repair: bool,
+    ) -> Result<ConsistencyReport> {
+        self.check_consistency_fenced(exchange, symbol, interval, start, end, repair, None)
+            .await
+    }
+
+    /// 定时检查持有租约，自动修复前确认防护令牌仍是最新的
+    #[allow(clippy::too_many_arguments)]
+    async fn check_consistency_fenced(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        interval: &Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        repair: bool,
+        fence: Option<&Fence>,
+    ) -> Result<ConsistencyReport> {
+        if !self.config.has_interval(interval) {
+            return Err(anyhow!("Interval {} is not materialized", interval));
+        }
+
+        let symbol = symbol.to_uppercase();
+        let (start, end) = Self::aligned_range(interval, start, end);
+
+        // 1. 分别读取汇总数据和源数据聚合结果
+        let rollup = self.fetch_rollup(exchange, &symbol, interval, start, end).await?;
+        let source = self.fetch_source(exchange, &symbol, interval, start, end).await?;
+
+        // 2. 逐桶比较
+        let mismatches = compare_rows(&rollup, &source);
+
+        // 3. 存在差异时重建
+        let repaired = if !mismatches.is_empty() && repair {
+            if let Some(fence) = fence {
+                if !fence.verify().await {
+                    return Err(anyhow!(
+                        "Lost leadership of {} (token {}), skipping rebuild",
+                        fence.job(),
+                        fence.token()
+                    ));
+                }
+            }
+            self.rebuild(exchange, &symbol, interval, start, end).await?;
+            true
+        } else {
+            false
+        };
+
+        if !mismatches.is_empty() {
+            warn!(
+                "Rollup {} for {}:{} has {} mismatched buckets{}",
+                interval,
+                exchange,
+                symbol,
+                mismatches.len(),
+                if repaired { ", rebuilt from source" } else { "" }
+            );
+        }
+
+        Ok(ConsistencyReport {
+            exchange: exchange.to_string(),
+            symbol,
+            interval: interval.as_str().to_string(),
+            start_time: start.timestamp_millis(),
+            end_time: end.timestamp_millis(),
+            rollup_buckets: rollup.len(),
+            source_buckets: source.len(),
+            mismatches,
+            repaired,
+            checked_at: Utc::now(),
+        })
+    }
+
+    /// 从源数据重建汇总表的时间范围，也用于首次启用后的历史回填
+    pub async fn rebuild(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        interval: &Interval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()> {
+        if !self.config.has_interval(interval) {
+            return Err(anyhow!("Interval {} is not materialized", interval));
+        }
+
+        let client = self.client()?;
+        let table = self.config.table_for(interval);
+        let symbol = symbol.to_uppercase();
+        let (start, end) = Self::aligned_range(interval, start, end);
+
+        // 删除需同步完成，否则重新写入的数据可能被随后执行的删除清掉
+        client
+            .clone()
+            .with_option("mutations_sync", "1")
+            .query(&sql::delete_rollup_range(
+                &self.database,
+                &table,
+                exchange,
+                &symbol,
+                start,
+                end,
+            ))
+            .execute()
+            .await?;
+        client
+            .query(&sql::rebuild_rollup_range(
+                &self.database,
+                &self.config.source_table,
+                &table,
+                interval,
+                exchange,
+                &symbol,
+                start,
+                end,
+                &self.decimals,
+            ))
+            .execute()
+            .await?;
+
+        info!(
+            "Rebuilt {} rollup for {}:{} from {} to {}",
+            interval, exchange, symbol, start, end
+        );
+        Ok(())
+    }
+
+    /// 检查最近时间窗口内所有活跃交易对
+    pub async fn check_recent(&self, fence: &Fence) -> Result<Vec<ConsistencyReport>> {
+        let client = self.client()?;
+        let end = Utc::now();
+        let start = end - Duration::hours(self.config.consistency_lookback_hours as i64);
+        let mut reports = Vec::new();
+
+        for interval in &self.config.intervals {
+            // 尚未收盘的时间桶不参与比较
+            let closed_end = millis_to_datetime(align_to_interval(
+                end.timestamp_millis(),
+                interval.to_millis(),
+            ));
+            if closed_end <= start {
+                continue;
+            }
+
+            let table = self.config.table_for(interval);
+            let symbols = client
+                .query(&sql::active_symbols(&self.database, &table, start))
+                .fetch_all::<SymbolRow>()
+                .await?;
+
+            for row in symbols {
+                match self
+                    .check_consistency_fenced(
+                        &row.exchange,
+                        &row.symbol,
+                        interval,
+                        start,
+                        closed_end,
+                        self.config.auto_repair,
+                        Some(fence),
+                    )
+                    .await
+                {
+                    Ok(report) => reports.push(report),
+                    Err(e) => warn!(
+                        "Rollup consistency check failed for {}:{} {}: {}",
+                        row.exchange, row.symbol, interval, e
+                    ),
+                }
+            }
+        }
+
+        *self.last_check.write().await = reports.clone();
+        Ok(reports)
+    }
+
+    /// 最近一次定时检查结果
+    pub async fn last_check(&self) -> Vec<ConsistencyReport> {
+        self.last_check.read().await.clone()
+    }
+
+    /// 创建物化视图并启动定时一致性检查，多副本时检查只在领导者副本执行
+    pub fn start(&self, leader: LeaderElection) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let manager = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = manager.ensure_views().await {
+                error!("Failed to create K-line rollup views: {}", e);
+                return;
+            }
+
+            let interval_seconds = manager.config.consistency_check_interval_seconds;
+            if interval_seconds == 0 {
+                return;
+            }
+
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_seconds));
+            loop {
+                ticker.tick().await;
+                // 持有租约直到本轮执行结束
+                let Some(lease) = leader.acquire("rollup_consistency_check").await else {
+                    continue;
+                };
+                match manager.check_recent(&lease.fence()).await {
+                    Ok(reports) => {
+                        let inconsistent = reports.iter().filter(|r| !r.is_consistent()).count();
+                        info!(
+                            "Rollup consistency check: {} checked, {} inconsistent",
+                            reports.len(),
+                            inconsistent
+                        );
+                    }
+                    Err(e) => warn!("Rollup consistency check failed: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DecimalSchemaConfig;
+    use rust_decimal::Decimal;
+
+    fn row(open_time_ms: i64, close: &str, source_count: u64) -> RollupRow {
+        RollupRow {
+            open_time_ms,
+            open: "100".to_string(),
+            high: "110".to_string(),
+            low: "90".to_string(),
+            close: close.to_string(),
+            volume: "5".to_string(),
+            quote_volume: "500".to_string(),
+            taker_buy_base_volume: "2".to_string(),
+            taker_buy_quote_volume: "200".to_string(),
+            trades_count: 10,
+            source_count,
+            worst_quality: 0,
+        }
+    }
+
+    #[test]
+    fn test_best_rollup_for() {
+        let intervals = RollupConfig::default().intervals;
+
+        assert_eq!(best_rollup_for(&intervals, &Interval::FiveMinutes), Some(Interval::FiveMinutes));
+        assert_eq!(best_rollup_for(&intervals, &Interval::ThirtyMinutes), Some(Interval::FifteenMinutes));
+        assert_eq!(best_rollup_for(&intervals, &Interval::TwelveHours), Some(Interval::FourHours));
+        assert_eq!(best_rollup_for(&intervals, &Interval::OneWeek), Some(Interval::OneDay));
+        assert_eq!(best_rollup_for(&intervals, &Interval::ThreeMinutes), None);
+        assert_eq!(best_rollup_for(&intervals, &Interval::OneMinute), None);
+    }
+
+    #[test]
+    fn test_compare_rows() {
+        let rollup = vec![row(0, "101", 5), row(300_000, "102", 5), row(900_000, "104", 5)];
+        let source = vec![row(0, "101", 5), row(300_000, "103", 5), row(600_000, "103", 5)];
+
+        let mismatches = compare_rows(&rollup, &source);
+        let kinds: Vec<_> = mismatches.iter().map(|m| (m.open_time, m.kind.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (300_000, "values_differ"),
+                (600_000, "missing_in_rollup"),
+                (900_000, "missing_in_source"),
+            ]
+        );
+        assert!(compare_rows(&rollup, &rollup).is_empty());
+    }
+
+    #[test]
+    fn test_incomplete_bucket_marked_suspect() {
+        let now_ms = 3_600_000;
+        let decimals = DecimalSchema::new(&DecimalSchemaConfig::default());
+        let kline = row(0, "101", 3)
+            .to_kline(&decimals, &Exchange::Binance, "BTCUSDT", &Interval::FiveMinutes, now_ms)
+            .unwrap();
+        assert!(kline.is_closed);
+        assert_eq!(kline.data_quality, DataQuality::Suspect);
+        assert_eq!(kline.close, Decimal::from(101));
+
+        let kline = row(0, "101", 5)
+            .to_kline(&decimals, &Exchange::Binance, "BTCUSDT", &Interval::FiveMinutes, now_ms)
+            .unwrap();
+        assert_eq!(kline.data_quality, DataQuality::Normal);
+
+        // 无法无损解析的值报错，不再按0处理
+        assert!(row(0, "nan", 5)
+            .to_kline(&decimals, &Exchange::Binance, "BTCUSDT", &Interval::FiveMinutes, now_ms)
+            .is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod manager;
+pub mod sql;
+
+pub use manager::{ConsistencyReport, RollupManager};
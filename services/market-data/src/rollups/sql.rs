@@ -0,0 +1,286 @@
+use chrono::{DateTime, Utc};
+use shared_models::common::Interval;
+
+use crate::compaction::sql::{datetime_literal, quote};
+use crate::schema::DecimalSchema;
+
+/// ClickHouse INTERVAL表达式，按秒对齐到Unix纪元，与图表降采样的对齐方式一致
+pub fn interval_expr(interval: &Interval) -> String {
+    format!("INTERVAL {} SECOND", interval.to_seconds())
+}
+
+/// 数据质量映射为可比较的等级：normal < recovered < suspect
+const QUALITY_RANK: &str =
+    "multiIf(data_quality = 'suspect', 2, data_quality = 'recovered', 1, 0)";
+
+/// 成交量类字段按1分钟K线的开盘时间分键
+const MINUTE_KEY: &str = "[CAST(open_time AS DateTime64(3, 'UTC'))]";
+
+/// 物化汇总表结构
+///
+/// open/close 保存聚合中间状态，其余字段使用 SimpleAggregateFunction，
+/// 同一时间桶的多次写入在后台合并，查询时再做最终聚合。
+/// 价格列为Decimal64，成交量求和使用Decimal128，聚合过程不经过浮点数。
+///
+/// 同一根1分钟K线可能重复或迟到写入，物化视图只能看到本次写入的数据，
+/// 因此成交量和成交笔数按分钟保存 maxMap，重复写入不会累加；
+/// 同一分钟的修正只会增加成交，取最大值即为最新值，查询时再按分钟求和。
+pub fn create_rollup_table(database: &str, table: &str, decimals: &DecimalSchema) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {database}.{table} (
+    exchange LowCardinality(String),
+    symbol LowCardinality(String),
+    bucket DateTime64(3, 'UTC'),
+    open AggregateFunction(argMin, {price}, DateTime64(3, 'UTC')),
+    high SimpleAggregateFunction(max, {price}),
+    low SimpleAggregateFunction(min, {price}),
+    close AggregateFunction(argMax, {price}, DateTime64(3, 'UTC')),
+    volume SimpleAggregateFunction(maxMap, Tuple(Array(DateTime64(3, 'UTC')), Array({amount}))),
+    quote_volume SimpleAggregateFunction(maxMap, Tuple(Array(DateTime64(3, 'UTC')), Array({amount}))),
+    taker_buy_base_volume SimpleAggregateFunction(maxMap, Tuple(Array(DateTime64(3, 'UTC')), Array({amount}))),
+    taker_buy_quote_volume SimpleAggregateFunction(maxMap, Tuple(Array(DateTime64(3, 'UTC')), Array({amount}))),
+    trades_count SimpleAggregateFunction(maxMap, Tuple(Array(DateTime64(3, 'UTC')), Array(UInt64))),
+    worst_quality SimpleAggregateFunction(max, UInt8)
+) ENGINE = AggregatingMergeTree
+PARTITION BY toYYYYMM(bucket)
+ORDER BY (exchange, symbol, bucket)",
+        price = decimals.price_type(),
+        amount = decimals.amount_type(),
+    )
+}
+
+/// 从源表聚合出中间状态的SELECT，供物化视图和重建共用
+///
+/// 数值列显式转换为汇总表的类型，聚合状态类型不随源表列类型变化。
+fn state_select(
+    database: &str,
+    source_table: &str,
+    interval: &Interval,
+    filter: &str,
+    decimals: &DecimalSchema,
+) -> String {
+    format!(
+        "SELECT
+    exchange,
+    symbol,
+    toStartOfInterval(open_time, {interval}) AS bucket,
+    argMinState(CAST(open AS {price}), open_time) AS open,
+    max(CAST(high AS {price})) AS high,
+    min(CAST(low AS {price})) AS low,
+    argMaxState(CAST(close AS {price}), open_time) AS close,
+    maxMap({MINUTE_KEY}, [CAST(volume AS {amount})]) AS volume,
+    maxMap({MINUTE_KEY}, [CAST(quote_volume AS {amount})]) AS quote_volume,
+    maxMap({MINUTE_KEY}, [CAST(taker_buy_base_volume AS {amount})]) AS taker_buy_base_volume,
+    maxMap({MINUTE_KEY}, [CAST(taker_buy_quote_volume AS {amount})]) AS taker_buy_quote_volume,
+    maxMap({MINUTE_KEY}, [toUInt64(trades_count)]) AS trades_count,
+    toUInt8(max({QUALITY_RANK})) AS worst_quality
+FROM {database}.{source_table}
+WHERE {filter}
+GROUP BY exchange, symbol, bucket",
+        interval = interval_expr(interval),
+        price = decimals.price_type(),
+        amount = decimals.amount_type(),
+    )
+}
+
+/// 源数据过滤条件：只汇总已收盘的1分钟K线
+fn source_filter(extra: Option<&str>) -> String {
+    match extra {
+        Some(extra) => format!("interval = '1m' AND is_closed = 1 AND {}", extra),
+        None => "interval = '1m' AND is_closed = 1".to_string(),
+    }
+}
+
+/// 写入时增量维护汇总表的物化视图
+pub fn create_materialized_view(
+    database: &str,
+    source_table: &str,
+    rollup_table: &str,
+    interval: &Interval,
+    decimals: &DecimalSchema,
+) -> String {
+    format!(
+        "CREATE MATERIALIZED VIEW IF NOT EXISTS {database}.{rollup_table}_mv TO {database}.{rollup_table} AS\n{}",
+        state_select(database, source_table, interval, &source_filter(None), decimals)
+    )
+}
+
+/// 时间范围过滤条件
+pub fn range_predicate(
+    exchange: &str,
+    symbol: &str,
+    column: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> String {
+    format!(
+        "exchange = {} AND symbol = {} AND {column} >= {} AND {column} < {}",
+        quote(exchange),
+        quote(symbol),
+        datetime_literal(start),
+        datetime_literal(end)
+    )
+}
+
+/// 最终聚合后的列，数值转换为字符串读取以保留精度
+///
+/// 成交量类字段先按分钟去重再求和，source_count 为去重后的分钟数。
+const FINAL_COLUMNS: &str = "toUnixTimestamp64Milli(bucket) AS open_time_ms,
+    toString(argMinMerge(open)) AS open,
+    toString(max(high)) AS high,
+    toString(min(low)) AS low,
+    toString(argMaxMerge(close)) AS close,
+    toString(arraySum((maxMap(volume)).2)) AS volume,
+    toString(arraySum((maxMap(quote_volume)).2)) AS quote_volume,
+    toString(arraySum((maxMap(taker_buy_base_volume)).2)) AS taker_buy_base_volume,
+    toString(arraySum((maxMap(taker_buy_quote_volume)).2)) AS taker_buy_quote_volume,
+    arraySum((maxMap(trades_count)).2) AS trades_count,
+    toUInt64(length((maxMap(trades_count)).1)) AS source_count,
+    max(worst_quality) AS worst_quality";
+
+/// 查询汇总表
+pub fn query_rollup(
+    database: &str,
+    rollup_table: &str,
+    exchange: &str,
+    symbol: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> String {
+    format!(
+        "SELECT
+    {FINAL_COLUMNS}
+FROM {database}.{rollup_table}
+WHERE {}
+GROUP BY bucket
+ORDER BY bucket",
+        range_predicate(exchange, symbol, "bucket", start, end)
+    )
+}
+
+/// 直接从源表聚合，用于一致性校验
+#[allow(clippy::too_many_arguments)]
+pub fn query_raw_aggregate(
+    database: &str,
+    source_table: &str,
+    interval: &Interval,
+    exchange: &str,
+    symbol: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    decimals: &DecimalSchema,
+) -> String {
+    let filter = source_filter(Some(&range_predicate(exchange, symbol, "open_time", start, end)));
+    format!(
+        "SELECT
+    {FINAL_COLUMNS}
+FROM ({})
+GROUP BY bucket
+ORDER BY bucket",
+        state_select(database, source_table, interval, &filter, decimals)
+    )
+}
+
+/// 汇总表中指定时间范围内出现过的交易对
+pub fn active_symbols(database: &str, rollup_table: &str, since: DateTime<Utc>) -> String {
+    format!(
+        "SELECT exchange, symbol FROM {database}.{rollup_table} WHERE bucket >= {} GROUP BY exchange, symbol",
+        datetime_literal(since)
+    )
+}
+
+/// 删除汇总表中的时间范围
+pub fn delete_rollup_range(
+    database: &str,
+    rollup_table: &str,
+    exchange: &str,
+    symbol: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> String {
+    format!(
+        "ALTER TABLE {database}.{rollup_table} DELETE WHERE {}",
+        range_predicate(exchange, symbol, "bucket", start, end)
+    )
+}
+
+/// 从源表重建汇总表的时间范围，也用于历史数据回填
+#[allow(clippy::too_many_arguments)]
+pub fn rebuild_rollup_range(
+    database: &str,
+    source_table: &str,
+    rollup_table: &str,
+    interval: &Interval,
+    exchange: &str,
+    symbol: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    decimals: &DecimalSchema,
+) -> String {
+    let filter = source_filter(Some(&range_predicate(exchange, symbol, "open_time", start, end)));
+    format!(
+        "INSERT INTO {database}.{rollup_table}\n{}",
+        state_select(database, source_table, interval, &filter, decimals)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DecimalSchemaConfig;
+
+    fn decimals() -> DecimalSchema {
+        DecimalSchema::new(&DecimalSchemaConfig::default())
+    }
+
+    #[test]
+    fn test_materialized_view_reads_closed_minutes() {
+        let sql = create_materialized_view(
+            "md",
+            "market_klines",
+            "market_klines_1h",
+            &Interval::OneHour,
+            &decimals(),
+        );
+        assert!(sql.starts_with(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS md.market_klines_1h_mv TO md.market_klines_1h AS"
+        ));
+        assert!(sql.contains("INTERVAL 3600 SECOND"));
+        assert!(sql.contains("WHERE interval = '1m' AND is_closed = 1"));
+        assert!(sql.contains("argMinState(CAST(open AS Decimal64(8)), open_time)"));
+        // 重复写入的分钟按开盘时间去重，不累加成交量
+        assert!(sql.contains(
+            "maxMap([CAST(open_time AS DateTime64(3, 'UTC'))], [CAST(volume AS Decimal128(8))]) AS volume"
+        ));
+        assert!(!sql.contains("sum("));
+    }
+
+    #[test]
+    fn test_rollup_table_uses_decimal_types() {
+        let sql = create_rollup_table("md", "market_klines_1h", &decimals());
+        assert!(sql.contains("open AggregateFunction(argMin, Decimal64(8), DateTime64(3, 'UTC'))"));
+        assert!(sql.contains(
+            "volume SimpleAggregateFunction(maxMap, Tuple(Array(DateTime64(3, 'UTC')), Array(Decimal128(8))))"
+        ));
+        assert!(!sql.contains("Float"));
+    }
+
+    #[test]
+    fn test_raw_aggregate_uses_range() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let end = DateTime::from_timestamp(1_700_003_600, 0).unwrap();
+        let sql = query_raw_aggregate(
+            "md",
+            "market_klines",
+            &Interval::FiveMinutes,
+            "binance",
+            "BTCUSDT",
+            start,
+            end,
+            &decimals(),
+        );
+        assert!(sql.contains("symbol = 'BTCUSDT' AND open_time >= "));
+        assert!(sql.contains("argMinMerge(open)"));
+        assert!(sql.contains("toUInt64(length((maxMap(trades_count)).1)) AS source_count"));
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use crate::config::storage::SymbolScale;
+use crate::config::DecimalSchemaConfig;
+
+/// 数值列类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecimalKind {
+    /// 价格，存为Decimal64
+    Price,
+    /// 数量和成交额，求和后仍需足够的整数位，存为Decimal128
+    Amount,
+}
+
+impl DecimalKind {
+    /// 按列名判断类别，用于迁移已有的浮点列
+    pub fn for_column(name: &str) -> Self {
+        match name {
+            "open" | "high" | "low" | "close" | "bid" | "ask" => DecimalKind::Price,
+            _ if name.contains("price") => DecimalKind::Price,
+            _ => DecimalKind::Amount,
+        }
+    }
+}
+
+/// ClickHouse Decimal列类型，以及查询结果到 rust_decimal 的无损转换
+///
+/// 查询时数值列以 `toString` 读取，避免经过浮点数；解析按精确模式进行，
+/// 超出 rust_decimal 表示范围时报错而不是四舍五入。
+#[derive(Debug, Clone)]
+pub struct DecimalSchema {
+    price_scale: u32,
+    amount_scale: u32,
+    symbol_scales: HashMap<String, SymbolScale>,
+}
+
+impl DecimalSchema {
+    pub fn new(config: &DecimalSchemaConfig) -> Self {
+        Self {
+            price_scale: config.price_scale,
+            amount_scale: config.amount_scale,
+            symbol_scales: config
+                .symbol_scales
+                .iter()
+                .map(|(symbol, scale)| (symbol.to_uppercase(), *scale))
+                .collect(),
+        }
+    }
+
+    /// 列的表级小数位
+    pub fn scale(&self, kind: DecimalKind) -> u32 {
+        match kind {
+            DecimalKind::Price => self.price_scale,
+            DecimalKind::Amount => self.amount_scale,
+        }
+    }
+
+    /// 列类型
+    pub fn column_type(&self, kind: DecimalKind) -> String {
+        match kind {
+            DecimalKind::Price => format!("Decimal64({})", self.price_scale),
+            DecimalKind::Amount => format!("Decimal128({})", self.amount_scale),
+        }
+    }
+
+    pub fn price_type(&self) -> String {
+        self.column_type(DecimalKind::Price)
+    }
+
+    pub fn amount_type(&self) -> String {
+        self.column_type(DecimalKind::Amount)
+    }
+
+    /// 交易对的展示小数位，未配置时使用表级小数位
+    pub fn symbol_scale(&self, symbol: &str, kind: DecimalKind) -> u32 {
+        match (self.symbol_scales.get(symbol), kind) {
+            (Some(scale), DecimalKind::Price) => scale.price,
+            (Some(scale), DecimalKind::Amount) => scale.amount,
+            (None, kind) => self.scale(kind),
+        }
+    }
+
+    /// 解析查询结果
+    ///
+    /// 结果补齐到交易对小数位；小数位更多的值（如旧表中的高精度数据）原样保留。
+    pub fn parse(&self, symbol: &str, kind: DecimalKind, value: &str) -> Result<Decimal> {
+        let mut decimal = parse_exact(value)?;
+        let scale = self.symbol_scale(symbol, kind);
+        if decimal.scale() < scale {
+            decimal.rescale(scale);
+        }
+        Ok(decimal)
+    }
+}
+
+/// 精确解析十进制字符串，去掉小数部分末尾的0后仍超出28位有效数字时报错
+pub fn parse_exact(value: &str) -> Result<Decimal> {
+    let value = value.trim();
+    let trimmed = if value.contains('.') {
+        value.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        value
+    };
+    Decimal::from_str_exact(trimmed)
+        .map_err(|e| anyhow!("Cannot represent {} as decimal without loss: {}", value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_kind_and_types() {
+        assert_eq!(DecimalKind::for_column("close"), DecimalKind::Price);
+        assert_eq!(DecimalKind::for_column("mark_price"), DecimalKind::Price);
+        assert_eq!(DecimalKind::for_column("quote_volume"), DecimalKind::Amount);
+
+        let mut config = DecimalSchemaConfig::default();
+        config.symbol_scales.insert(
+            "btcusdt".to_string(),
+            SymbolScale { price: 2, amount: 6 },
+        );
+        let schema = DecimalSchema::new(&config);
+        assert_eq!(schema.price_type(), "Decimal64(8)");
+        assert_eq!(schema.amount_type(), "Decimal128(8)");
+        assert_eq!(schema.symbol_scale("BTCUSDT", DecimalKind::Price), 2);
+        assert_eq!(schema.symbol_scale("ETHUSDT", DecimalKind::Price), 8);
+    }
+
+    #[test]
+    fn test_parse_is_exact() {
+        let schema = DecimalSchema::new(&DecimalSchemaConfig::default());
+
+        let price = schema.parse("BTCUSDT", DecimalKind::Price, "0.30000000").unwrap();
+        assert_eq!(price.to_string(), "0.30000000");
+
+        // Decimal(38, 18) 旧表的结果去掉末尾0后解析
+        let volume = parse_exact("123456789012.100000000000000000").unwrap();
+        assert_eq!(volume.to_string(), "123456789012.1");
+
+        // 有效数字超出范围时报错，不静默舍入
+        assert!(parse_exact("12345678901234567890.123456789012").is_err());
+        assert!(parse_exact("1e-7").is_err());
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use shared_utils::LeaderElection;
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use super::decimal::{DecimalKind, DecimalSchema};
+use super::sql::{self, MIGRATION_SUFFIX};
+use crate::config::ClickHouseConfig;
+
+/// 浮点列迁移任务名
+const MIGRATION_JOB: &str = "clickhouse_decimal_migration";
+
+/// 未取得租约时的重试间隔
+const MIGRATION_RETRY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct ColumnRow {
+    table: String,
+    name: String,
+    column_type: String,
+    in_key: u8,
+}
+
+#[derive(Debug, clickhouse::Row, Deserialize)]
+struct CountRow {
+    count: u64,
+}
+
+/// 单列迁移结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnMigration {
+    pub table: String,
+    pub column: String,
+    pub from_type: String,
+    pub to_type: String,
+    /// migrated / resumed / skipped_key_column / verification_failed
+    pub outcome: String,
+}
+
+/// ClickHouse源表结构管理
+///
+/// 启动时创建Tick和K线源表，并在领导者节点上把已有表中的浮点价格和数量列迁移为Decimal。
+/// 迁移先写临时列、校验偏差后再替换原列，中途中断后下次启动从临时列继续。
+#[derive(Clone)]
+pub struct ClickHouseSchema {
+    database: String,
+    client: Option<clickhouse::Client>,
+    decimals: DecimalSchema,
+    migrate_float_columns: bool,
+}
+
+impl ClickHouseSchema {
+    pub fn new(clickhouse_config: Option<&ClickHouseConfig>) -> Self {
+        let client = clickhouse_config.map(|c| {
+            clickhouse::Client::default()
+                .with_url(&c.url)
+                .with_database(&c.database)
+                .with_user(&c.username)
+                .with_password(&c.password)
+        });
+        let decimals_config = clickhouse_config
+            .map(|c| c.decimals.clone())
+            .unwrap_or_default();
+
+        Self {
+            database: clickhouse_config
+                .map(|c| c.database.clone())
+                .unwrap_or_else(|| "market_data".to_string()),
+            client,
+            decimals: DecimalSchema::new(&decimals_config),
+            migrate_float_columns: decimals_config.migrate_float_columns,
+        }
+    }
+
+    fn client(&self) -> Result<&clickhouse::Client> {
+        self.client
+            .as_ref()
+            .ok_or_else(|| anyhow!("ClickHouse is not configured"))
+    }
+
+    /// 创建源表，已存在的表不做修改
+    pub async fn ensure_tables(&self, ticks_table: &str, klines_table: &str) -> Result<()> {
+        let client = self.client()?;
+        client
+            .query(&sql::create_ticks_table(&self.database, ticks_table, &self.decimals))
+            .execute()
+            .await?;
+        client
+            .query(&sql::create_klines_table(&self.database, klines_table, &self.decimals))
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    /// 把表中的浮点列迁移为Decimal
+    pub async fn migrate_float_columns(&self, tables: &[String]) -> Result<Vec<ColumnMigration>> {
+        let client = self.client()?;
+        let columns = client
+            .query(&sql::list_columns(&self.database, tables))
+            .fetch_all::<ColumnRow>()
+            .await?;
+
+        let mut by_table: BTreeMap<&str, Vec<&ColumnRow>> = BTreeMap::new();
+        for column in &columns {
+            by_table.entry(&column.table).or_default().push(column);
+        }
+
+        let mut results = Vec::new();
+        for (table, columns) in by_table {
+            let names: HashSet<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+
+            // 上次迁移在删除原列后中断：只剩临时列，改回原列名
+            for column in &columns {
+                let Some(base) = column.name.strip_suffix(MIGRATION_SUFFIX) else {
+                    continue;
+                };
+                if !names.contains(base) {
+                    client
+                        .query(&sql::rename_decimal_column(&self.database, table, base))
+                        .execute()
+                        .await?;
+                    results.push(ColumnMigration {
+                        table: table.to_string(),
+                        column: base.to_string(),
+                        from_type: String::new(),
+                        to_type: column.column_type.clone(),
+                        outcome: "resumed".to_string(),
+                    });
+                }
+            }
+
+            for column in columns.iter().filter(|c| sql::is_float_type(&c.column_type)) {
+                results.push(self.migrate_column(table, column).await?);
+            }
+        }
+
+        Ok(results)
+    }
+
+    async fn migrate_column(&self, table: &str, column: &ColumnRow) -> Result<ColumnMigration> {
+        let client = self.client()?;
+        let kind = DecimalKind::for_column(&column.name);
+        let scale = self.decimals.scale(kind);
+        let to_type = sql::target_type(&column.column_type, kind, &self.decimals);
+        let mut result = ColumnMigration {
+            table: table.to_string(),
+            column: column.name.clone(),
+            from_type: column.column_type.clone(),
+            to_type: to_type.clone(),
+            outcome: "migrated".to_string(),
+        };
+
+        if column.in_key != 0 {
+            // 排序键和分区键中的列不能删除，需要重建表
+            warn!(
+                "Float column {}.{} is part of the table key, rebuild the table to convert it",
+                table, column.name
+            );
+            result.outcome = "skipped_key_column".to_string();
+            return Ok(result);
+        }
+
+        let db = &self.database;
+        client
+            .query(&sql::add_decimal_column(db, table, &column.name, &to_type))
+            .execute()
+            .await?;
+        client
+            .query(&sql::fill_decimal_column(db, table, &column.name, &to_type, scale))
+            .execute()
+            .await?;
+
+        let errors = client
+            .query(&sql::count_conversion_errors(db, table, &column.name, scale))
+            .fetch_one::<CountRow>()
+            .await?
+            .count;
+        if errors > 0 {
+            error!(
+                "Converting {}.{} to {} changes {} values beyond scale {}, keeping float column",
+                table, column.name, to_type, errors, scale
+            );
+            result.outcome = "verification_failed".to_string();
+            return Ok(result);
+        }
+
+        client
+            .query(&sql::drop_column(db, table, &column.name))
+            .execute()
+            .await?;
+        client
+            .query(&sql::rename_decimal_column(db, table, &column.name))
+            .execute()
+            .await?;
+        info!("Migrated {}.{} from {} to {}", table, column.name, column.column_type, to_type);
+        Ok(result)
+    }
+
+    /// 启动源表浮点列迁移，同一时间只在一个节点上执行
+    pub fn start_migration(&self, leader: LeaderElection, tables: Vec<String>) {
+        if self.client.is_none() || !self.migrate_float_columns {
+            return;
+        }
+
+        let schema = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let Some(_lease) = leader.acquire(MIGRATION_JOB).await else {
+                    tokio::time::sleep(MIGRATION_RETRY).await;
+                    continue;
+                };
+                match schema.migrate_float_columns(&tables).await {
+                    Ok(results) => {
+                        let failed = results
+                            .iter()
+                            .filter(|r| r.outcome == "verification_failed" || r.outcome == "skipped_key_column")
+                            .count();
+                        if !results.is_empty() {
+                            info!(
+                                "Float column migration finished: {} columns, {} need attention",
+                                results.len(),
+                                failed
+                            );
+                        }
+                    }
+                    Err(e) => error!("Float column migration failed: {}", e),
+                }
+                return;
+            }
+        });
+    }
+}
@@ -0,0 +1,6 @@
+pub mod decimal;
+pub mod manager;
+pub mod sql;
+
+pub use decimal::{DecimalKind, DecimalSchema};
+pub use manager::ClickHouseSchema;
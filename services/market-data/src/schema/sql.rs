@@ -0,0 +1,171 @@
+use super::decimal::{DecimalKind, DecimalSchema};
+use crate::compaction::sql::quote;
+
+/// 迁移过程中临时列名后缀
+pub const MIGRATION_SUFFIX: &str = "__decimal";
+
+/// 1分钟K线源表结构
+pub fn create_klines_table(database: &str, table: &str, decimals: &DecimalSchema) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {database}.{table} (
+    exchange LowCardinality(String),
+    symbol LowCardinality(String),
+    interval LowCardinality(String),
+    open_time DateTime64(3, 'UTC'),
+    close_time DateTime64(3, 'UTC'),
+    open {price},
+    high {price},
+    low {price},
+    close {price},
+    volume {amount},
+    quote_volume {amount},
+    trades_count UInt32,
+    taker_buy_base_volume {amount},
+    taker_buy_quote_volume {amount},
+    is_closed UInt8,
+    data_quality LowCardinality(String)
+) ENGINE = ReplacingMergeTree
+PARTITION BY toYYYYMM(open_time)
+ORDER BY (exchange, symbol, interval, open_time)",
+        price = decimals.price_type(),
+        amount = decimals.amount_type(),
+    )
+}
+
+/// 原始Tick表结构
+pub fn create_ticks_table(database: &str, table: &str, decimals: &DecimalSchema) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {database}.{table} (
+    exchange LowCardinality(String),
+    symbol LowCardinality(String),
+    timestamp DateTime64(3, 'UTC'),
+    price {price},
+    volume {amount},
+    bid {price},
+    ask {price},
+    data_quality LowCardinality(String)
+) ENGINE = MergeTree
+PARTITION BY toYYYYMMDD(timestamp)
+ORDER BY (exchange, symbol, timestamp)",
+        price = decimals.price_type(),
+        amount = decimals.amount_type(),
+    )
+}
+
+/// 列出表结构，用于查找浮点列和迁移中断后留下的临时列
+pub fn list_columns(database: &str, tables: &[String]) -> String {
+    let table_list = tables
+        .iter()
+        .map(|t| quote(t))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "SELECT
+    table,
+    name,
+    type AS column_type,
+    toUInt8(is_in_partition_key OR is_in_sorting_key OR is_in_primary_key) AS in_key
+FROM system.columns
+WHERE database = {database} AND table IN ({table_list})
+ORDER BY table, position",
+        database = quote(database),
+    )
+}
+
+/// 是否为浮点列
+pub fn is_float_type(column_type: &str) -> bool {
+    let inner = column_type
+        .strip_prefix("Nullable(")
+        .and_then(|t| t.strip_suffix(')'))
+        .unwrap_or(column_type);
+    matches!(inner, "Float32" | "Float64")
+}
+
+/// 浮点列对应的Decimal类型，可空列保持可空
+pub fn target_type(float_type: &str, kind: DecimalKind, decimals: &DecimalSchema) -> String {
+    let column_type = decimals.column_type(kind);
+    if float_type.starts_with("Nullable(") {
+        format!("Nullable({})", column_type)
+    } else {
+        column_type
+    }
+}
+
+/// 添加临时Decimal列
+pub fn add_decimal_column(database: &str, table: &str, column: &str, column_type: &str) -> String {
+    format!(
+        "ALTER TABLE {database}.{table} ADD COLUMN IF NOT EXISTS {column}{MIGRATION_SUFFIX} {column_type} AFTER {column}"
+    )
+}
+
+/// 回填临时列
+///
+/// 先按目标小数位舍入再经字符串转换：浮点数直接转Decimal会截断，0.3 会变成 0.29999999。
+pub fn fill_decimal_column(
+    database: &str,
+    table: &str,
+    column: &str,
+    column_type: &str,
+    scale: u32,
+) -> String {
+    format!(
+        "ALTER TABLE {database}.{table} UPDATE {column}{MIGRATION_SUFFIX} = CAST(toString(round({column}, {scale})) AS {column_type}) WHERE 1
+SETTINGS mutations_sync = 2"
+    )
+}
+
+/// 统计转换前后偏差超过一个最小单位的行，删除原列前必须为0
+pub fn count_conversion_errors(database: &str, table: &str, column: &str, scale: u32) -> String {
+    format!(
+        "SELECT count() AS count FROM {database}.{table}
+WHERE isNull({column}{MIGRATION_SUFFIX}) != isNull({column})
+   OR abs(toFloat64({column}{MIGRATION_SUFFIX}) - {column}) > pow(10, -{scale}) + abs({column}) * 1e-15"
+    )
+}
+
+/// 删除原浮点列
+pub fn drop_column(database: &str, table: &str, column: &str) -> String {
+    format!("ALTER TABLE {database}.{table} DROP COLUMN IF EXISTS {column}")
+}
+
+/// 临时列改回原列名
+pub fn rename_decimal_column(database: &str, table: &str, column: &str) -> String {
+    format!("ALTER TABLE {database}.{table} RENAME COLUMN {column}{MIGRATION_SUFFIX} TO {column}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DecimalSchemaConfig;
+
+    #[test]
+    fn test_tables_use_decimal_types() {
+        let decimals = DecimalSchema::new(&DecimalSchemaConfig::default());
+        let klines = create_klines_table("md", "market_klines", &decimals);
+        assert!(klines.contains("close Decimal64(8)"));
+        assert!(klines.contains("quote_volume Decimal128(8)"));
+        assert!(!klines.contains("Float"));
+
+        let ticks = create_ticks_table("md", "market_ticks", &decimals);
+        assert!(ticks.contains("price Decimal64(8)"));
+        assert!(ticks.contains("volume Decimal128(8)"));
+    }
+
+    #[test]
+    fn test_float_migration_statements() {
+        let decimals = DecimalSchema::new(&DecimalSchemaConfig::default());
+        let column_type = target_type("Nullable(Float64)", DecimalKind::Price, &decimals);
+        assert_eq!(column_type, "Nullable(Decimal64(8))");
+
+        let fill = fill_decimal_column("md", "market_ticks", "price", &column_type, 8);
+        assert!(fill.starts_with(
+            "ALTER TABLE md.market_ticks UPDATE price__decimal = CAST(toString(round(price, 8)) AS Nullable(Decimal64(8)))"
+        ));
+        assert!(fill.ends_with("mutations_sync = 2"));
+
+        assert!(is_float_type("Nullable(Float32)"));
+        assert!(!is_float_type("Decimal(38, 18)"));
+        let list = list_columns("md", &["market_ticks".to_string()]);
+        assert!(list.contains("database = 'md' AND table IN ('market_ticks')"));
+    }
+}
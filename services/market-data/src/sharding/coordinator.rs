@@ -0,0 +1,245 @@
+use anyhow::Result;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use shared_utils::sharding::{self, shard_key, ShardAssignments, ShardMember};
+use shared_utils::LeaderElection;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::config::{ExchangeConfig, MarketDataConfig, ShardingConfig};
+use crate::connectors::ExchangeManager;
+
+/// 领导者选举中的任务名
+const ASSIGNMENT_JOB: &str = "market_data_shard_assignment";
+
+/// 交易对分片协调器
+///
+/// 每个实例定期上报心跳；领导者实例按一致性哈希把全部交易对分配给
+/// 存活实例并发布到Redis，所有实例只按已发布的分配订阅行情，
+/// 因此同一时刻每个交易对只有一个实例负责。实例增减时只有受影响的
+/// 交易对迁移，迁出方在看到新分配后退订，迁入方随即订阅。
+pub struct ShardCoordinator {
+    config: ShardingConfig,
+    instance_id: String,
+    url: String,
+    redis: Option<ConnectionManager>,
+    exchanges: HashMap<String, ExchangeConfig>,
+    /// 分片键 -> (交易所, 原始交易对)
+    universe: BTreeMap<String, (String, String)>,
+    /// 本实例当前订阅的分片键
+    owned: RwLock<HashSet<String>>,
+    assignments: RwLock<ShardAssignments>,
+}
+
+impl ShardCoordinator {
+    pub async fn new(config: &MarketDataConfig) -> Self {
+        let sharding = config.sharding.clone();
+        let instance_id = sharding
+            .instance_id
+            .clone()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .unwrap_or_else(|| format!("market-data-{}", uuid::Uuid::new_v4().simple()));
+        let url = sharding
+            .advertise_url
+            .clone()
+            .unwrap_or_else(|| format!("http://{}:{}", instance_id, config.server.port));
+
+        let exchanges: HashMap<String, ExchangeConfig> = config
+            .enabled_exchanges()
+            .into_iter()
+            .map(|(name, exchange)| (name.clone(), exchange.clone()))
+            .collect();
+        let universe: BTreeMap<String, (String, String)> = exchanges
+            .iter()
+            .flat_map(|(name, exchange)| {
+                exchange
+                    .symbols
+                    .iter()
+                    .map(move |symbol| (shard_key(name, symbol), (name.clone(), symbol.clone())))
+            })
+            .collect();
+
+        let redis = match (&config.storage.redis, sharding.enabled) {
+            (Some(redis_config), true) => match Self::connect(&redis_config.url).await {
+                Ok(conn) => Some(conn),
+                Err(e) => {
+                    warn!("Symbol sharding disabled, failed to connect to Redis: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        // 启动时已订阅全部配置的交易对，首次分配后退订不属于本实例的
+        let owned = universe.keys().cloned().collect();
+
+        Self {
+            config: sharding,
+            instance_id,
+            url,
+            redis,
+            exchanges,
+            universe,
+            owned: RwLock::new(owned),
+            assignments: RwLock::new(ShardAssignments::default()),
+        }
+    }
+
+    async fn connect(url: &str) -> Result<ConnectionManager> {
+        let client = redis::Client::open(url)?;
+        Ok(ConnectionManager::new(client).await?)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// 本实例负责的交易对数
+    pub async fn owned_count(&self) -> usize {
+        self.owned.read().await.len()
+    }
+
+    /// 最近一次读取的分配
+    pub async fn assignments(&self) -> ShardAssignments {
+        self.assignments.read().await.clone()
+    }
+
+    /// 启动心跳、分配和订阅调整任务
+    pub fn start(self: Arc<Self>, leader: LeaderElection, exchange_manager: Arc<ExchangeManager>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let interval = Duration::from_secs(self.config.heartbeat_interval_seconds.max(1));
+        tokio::spawn(async move {
+            info!(
+                "Symbol sharding started for instance {} ({} symbols configured)",
+                self.instance_id,
+                self.universe.len()
+            );
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.tick(&leader, &exchange_manager).await {
+                    warn!("Shard coordination failed: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn tick(&self, leader: &LeaderElection, exchange_manager: &ExchangeManager) -> Result<()> {
+        let Some(mut redis) = self.redis.clone() else {
+            return Ok(());
+        };
+        let prefix = &self.config.key_prefix;
+
+        sharding::heartbeat(
+            &mut redis,
+            prefix,
+            &ShardMember {
+                instance_id: self.instance_id.clone(),
+                url: self.url.clone(),
+                last_seen: Utc::now(),
+            },
+        )
+        .await?;
+
+        let current = sharding::load_assignments(&mut redis, prefix).await?;
+
+        if let Some(_lease) = leader.acquire(ASSIGNMENT_JOB).await {
+            let ttl = chrono::Duration::seconds(self.config.member_ttl_seconds as i64);
+            let members = sharding::live_members(&mut redis, prefix, ttl).await?;
+            let mut next =
+                ShardAssignments::compute(members, self.universe.keys().cloned(), self.config.virtual_nodes);
+            let changed = !matches!(&current, Some(current) if current.same_layout(&next));
+            if changed && !next.members.is_empty() {
+                next.version = current.as_ref().map_or(0, |c| c.version) + 1;
+                next.updated_at = Some(Utc::now());
+                sharding::publish_assignments(&mut redis, prefix, &next).await?;
+                info!(
+                    "Published shard assignment v{} across {} instances",
+                    next.version,
+                    next.members.len()
+                );
+                self.apply(next, exchange_manager).await;
+                return Ok(());
+            }
+        }
+
+        if let Some(current) = current {
+            self.apply(current, exchange_manager).await;
+        }
+        Ok(())
+    }
+
+    /// 按分配调整本实例的订阅
+    async fn apply(&self, assignments: ShardAssignments, exchange_manager: &ExchangeManager) {
+        let target: HashSet<String> = assignments
+            .owned_by(&self.instance_id)
+            .filter(|key| self.universe.contains_key(*key))
+            .cloned()
+            .collect();
+
+        let (added, removed) = {
+            let owned = self.owned.read().await;
+            (
+                target.difference(&owned).cloned().collect::<Vec<_>>(),
+                owned.difference(&target).cloned().collect::<Vec<_>>(),
+            )
+        };
+
+        let mut owned = target;
+        for key in &added {
+            if !self.resubscribe(exchange_manager, key, true).await {
+                owned.remove(key);
+            }
+        }
+        for key in &removed {
+            if !self.resubscribe(exchange_manager, key, false).await {
+                owned.insert(key.clone());
+            }
+        }
+
+        if !added.is_empty() || !removed.is_empty() {
+            info!(
+                "Shard v{}: {} symbols owned (+{} -{})",
+                assignments.version,
+                owned.len(),
+                added.len(),
+                removed.len()
+            );
+        }
+        *self.owned.write().await = owned;
+        *self.assignments.write().await = assignments;
+    }
+
+    async fn resubscribe(&self, exchange_manager: &ExchangeManager, key: &str, subscribe: bool) -> bool {
+        let Some((exchange, symbol)) = self.universe.get(key) else {
+            return false;
+        };
+        let Some(exchange_config) = self.exchanges.get(exchange) else {
+            return false;
+        };
+        let data_types = ExchangeManager::configured_data_types(exchange_config);
+        let symbols = std::slice::from_ref(symbol);
+        let result = if subscribe {
+            exchange_manager.subscribe_data(exchange, symbols, &data_types).await
+        } else {
+            exchange_manager.unsubscribe_data(exchange, symbols, &data_types).await
+        };
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to move {} to/from this shard: {}", key, e);
+                false
+            }
+        }
+    }
+}
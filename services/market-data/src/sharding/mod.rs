@@ -0,0 +1,3 @@
+pub mod coordinator;
+
+pub use coordinator::ShardCoordinator;
@@ -0,0 +1,317 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use shared_models::common::{Exchange, Interval};
+use shared_models::market::{Kline, MarketTick, OrderBook};
+use std::str::FromStr;
+
+use super::{parse_levels, parse_quality, KlineStore, OrderBookStore, TickStore, TimeRange};
+use crate::compaction::sql::{datetime_literal, quote};
+use crate::config::ClickHouseConfig;
+use crate::depth_history::recorder::SnapshotRow;
+
+#[derive(Debug, ::clickhouse::Row, Deserialize)]
+struct TickRow {
+    timestamp_ms: i64,
+    price: String,
+    volume: String,
+    bid: String,
+    ask: String,
+    data_quality: String,
+}
+
+#[derive(Debug, ::clickhouse::Row, Deserialize)]
+struct KlineRow {
+    open_time_ms: i64,
+    close_time_ms: i64,
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    volume: String,
+    quote_volume: String,
+    trades_count: u32,
+    taker_buy_base_volume: String,
+    taker_buy_quote_volume: String,
+    is_closed: u8,
+    data_quality: String,
+}
+
+fn decimal(value: &str) -> Result<Decimal> {
+    Decimal::from_str(value).map_err(|e| anyhow!("invalid decimal {}: {}", value, e))
+}
+
+fn millis(value: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(value).ok_or_else(|| anyhow!("invalid timestamp {}", value))
+}
+
+/// ClickHouse行情存储
+///
+/// 写入使用与表结构一致的Decimal字面量，读取时数值列转为字符串后解析，避免精度损失。
+pub struct ClickHouseMarketStore {
+    client: ::clickhouse::Client,
+    database: String,
+    ticks_table: String,
+    klines_table: String,
+    snapshots_table: String,
+}
+
+impl ClickHouseMarketStore {
+    pub fn new(config: &ClickHouseConfig, ticks_table: &str, klines_table: &str, snapshots_table: &str) -> Self {
+        let client = ::clickhouse::Client::default()
+            .with_url(&config.url)
+            .with_database(&config.database)
+            .with_user(&config.username)
+            .with_password(&config.password);
+
+        Self {
+            client,
+            database: config.database.clone(),
+            ticks_table: ticks_table.to_string(),
+            klines_table: klines_table.to_string(),
+            snapshots_table: snapshots_table.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl TickStore for ClickHouseMarketStore {
+    async fn insert_ticks(&self, ticks: &[MarketTick]) -> Result<()> {
+        if ticks.is_empty() {
+            return Ok(());
+        }
+        let values: Vec<String> = ticks
+            .iter()
+            .map(|t| {
+                format!(
+                    "({}, {}, {}, {}, {}, {}, {}, {})",
+                    quote(t.exchange.as_str()),
+                    quote(&t.symbol.to_uppercase()),
+                    datetime_literal(t.timestamp),
+                    quote(&t.price.to_string()),
+                    quote(&t.volume.to_string()),
+                    quote(&t.bid.to_string()),
+                    quote(&t.ask.to_string()),
+                    quote(t.data_quality.as_str()),
+                )
+            })
+            .collect();
+        self.client
+            .query(&format!(
+                "INSERT INTO {}.{} (exchange, symbol, timestamp, price, volume, bid, ask, data_quality) VALUES {}",
+                self.database,
+                self.ticks_table,
+                values.join(", ")
+            ))
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    async fn ticks(&self, exchange: Exchange, symbol: &str, range: TimeRange) -> Result<Vec<MarketTick>> {
+        let symbol = symbol.to_uppercase();
+        let rows = self
+            .client
+            .query(&format!(
+                "SELECT
+    toUnixTimestamp64Milli(timestamp) AS timestamp_ms,
+    toString(price) AS price,
+    toString(volume) AS volume,
+    toString(bid) AS bid,
+    toString(ask) AS ask,
+    data_quality
+FROM {}.{}
+WHERE exchange = {} AND symbol = {}
+  AND timestamp >= {} AND timestamp < {}
+ORDER BY timestamp {}
+LIMIT {}",
+                self.database,
+                self.ticks_table,
+                quote(exchange.as_str()),
+                quote(&symbol),
+                datetime_literal(range.start),
+                datetime_literal(range.end),
+                range.order(),
+                range.limit,
+            ))
+            .fetch_all::<TickRow>()
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(MarketTick {
+                    id: None,
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    timestamp: millis(row.timestamp_ms)?,
+                    price: decimal(&row.price)?,
+                    volume: decimal(&row.volume)?,
+                    bid: decimal(&row.bid)?,
+                    ask: decimal(&row.ask)?,
+                    bid_volume: Decimal::ZERO,
+                    ask_volume: Decimal::ZERO,
+                    trade_id: None,
+                    is_buyer_maker: None,
+                    data_quality: parse_quality(&row.data_quality),
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl KlineStore for ClickHouseMarketStore {
+    async fn upsert_klines(&self, klines: &[Kline]) -> Result<()> {
+        if klines.is_empty() {
+            return Ok(());
+        }
+        // ReplacingMergeTree按排序键合并，同一开盘时间保留最后写入的一行
+        let values: Vec<String> = klines
+            .iter()
+            .map(|k| {
+                format!(
+                    "({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {})",
+                    quote(k.exchange.as_str()),
+                    quote(&k.symbol.to_uppercase()),
+                    quote(k.interval.as_str()),
+                    datetime_literal(k.open_time),
+                    datetime_literal(k.close_time),
+                    quote(&k.open.to_string()),
+                    quote(&k.high.to_string()),
+                    quote(&k.low.to_string()),
+                    quote(&k.close.to_string()),
+                    quote(&k.volume.to_string()),
+                    quote(&k.quote_volume.to_string()),
+                    k.trades_count,
+                    quote(&k.taker_buy_base_volume.to_string()),
+                    quote(&k.taker_buy_quote_volume.to_string()),
+                    u8::from(k.is_closed),
+                    quote(k.data_quality.as_str()),
+                )
+            })
+            .collect();
+        self.client
+            .query(&format!(
+                "INSERT INTO {}.{} (exchange, symbol, interval, open_time, close_time, open, high, low, close, \
+                 volume, quote_volume, trades_count, taker_buy_base_volume, taker_buy_quote_volume, is_closed, \
+                 data_quality) VALUES {}",
+                self.database,
+                self.klines_table,
+                values.join(", ")
+            ))
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    async fn klines(
+        &self,
+        exchange: Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: TimeRange,
+    ) -> Result<Vec<Kline>> {
+        let symbol = symbol.to_uppercase();
+        let rows = self
+            .client
+            .query(&format!(
+                "SELECT
+    toUnixTimestamp64Milli(open_time) AS open_time_ms,
+    toUnixTimestamp64Milli(close_time) AS close_time_ms,
+    toString(open) AS open,
+    toString(high) AS high,
+    toString(low) AS low,
+    toString(close) AS close,
+    toString(volume) AS volume,
+    toString(quote_volume) AS quote_volume,
+    trades_count,
+    toString(taker_buy_base_volume) AS taker_buy_base_volume,
+    toString(taker_buy_quote_volume) AS taker_buy_quote_volume,
+    is_closed,
+    data_quality
+FROM {}.{} FINAL
+WHERE exchange = {} AND symbol = {} AND interval = {}
+  AND open_time >= {} AND open_time < {}
+ORDER BY open_time {}
+LIMIT {}",
+                self.database,
+                self.klines_table,
+                quote(exchange.as_str()),
+                quote(&symbol),
+                quote(interval.as_str()),
+                datetime_literal(range.start),
+                datetime_literal(range.end),
+                range.order(),
+                range.limit,
+            ))
+            .fetch_all::<KlineRow>()
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Kline {
+                    id: None,
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    interval: interval.clone(),
+                    open_time: millis(row.open_time_ms)?,
+                    close_time: millis(row.close_time_ms)?,
+                    open: decimal(&row.open)?,
+                    high: decimal(&row.high)?,
+                    low: decimal(&row.low)?,
+                    close: decimal(&row.close)?,
+                    volume: decimal(&row.volume)?,
+                    quote_volume: decimal(&row.quote_volume)?,
+                    trades_count: row.trades_count,
+                    taker_buy_base_volume: decimal(&row.taker_buy_base_volume)?,
+                    taker_buy_quote_volume: decimal(&row.taker_buy_quote_volume)?,
+                    is_closed: row.is_closed != 0,
+                    data_quality: parse_quality(&row.data_quality),
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OrderBookStore for ClickHouseMarketStore {
+    async fn save_snapshot(&self, book: &OrderBook) -> Result<()> {
+        let depth = book.bids.len().max(book.asks.len());
+        let mut insert = self.client.insert::<SnapshotRow>(&self.snapshots_table)?;
+        insert.write(&SnapshotRow::from_orderbook(book, depth)).await?;
+        insert.end().await?;
+        Ok(())
+    }
+
+    async fn latest_snapshot(&self, exchange: Exchange, symbol: &str) -> Result<Option<OrderBook>> {
+        let row = self
+            .client
+            .query(&format!(
+                "SELECT exchange, symbol, timestamp, last_update_id, bid_prices, bid_quantities, ask_prices, ask_quantities
+FROM {}.{}
+WHERE exchange = {} AND symbol = {}
+ORDER BY timestamp DESC
+LIMIT 1",
+                self.database,
+                self.snapshots_table,
+                quote(exchange.as_str()),
+                quote(&symbol.to_uppercase()),
+            ))
+            .fetch_optional::<SnapshotRow>()
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(OrderBook {
+            exchange,
+            symbol: row.symbol,
+            timestamp: millis(row.timestamp)?,
+            last_update_id: row.last_update_id,
+            bids: parse_levels(&row.bid_prices, &row.bid_quantities)?,
+            asks: parse_levels(&row.ask_prices, &row.ask_quantities)?,
+        }))
+    }
+}
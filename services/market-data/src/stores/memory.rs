@@ -0,0 +1,264 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use shared_models::common::{Exchange, Interval};
+use shared_models::market::{Kline, MarketTick, OrderBook};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tokio::sync::RwLock;
+
+use super::{KlineStore, OrderBookStore, TickStore, TimeRange};
+use crate::config::MemoryStoreConfig;
+
+type SeriesKey = (Exchange, String);
+
+fn key(exchange: &Exchange, symbol: &str) -> SeriesKey {
+    (exchange.clone(), symbol.to_uppercase())
+}
+
+/// 进程内存行情存储
+///
+/// 每个交易对只保留最近的Tick和K线，超出上限时丢弃最早的数据；
+/// K线按开盘时间去重，与ClickHouse的ReplacingMergeTree语义一致。
+pub struct MemoryMarketStore {
+    config: MemoryStoreConfig,
+    ticks: RwLock<HashMap<SeriesKey, VecDeque<MarketTick>>>,
+    klines: RwLock<HashMap<(SeriesKey, Interval), BTreeMap<DateTime<Utc>, Kline>>>,
+    order_books: RwLock<HashMap<SeriesKey, OrderBook>>,
+}
+
+impl MemoryMarketStore {
+    pub fn new(config: MemoryStoreConfig) -> Self {
+        Self {
+            config,
+            ticks: RwLock::new(HashMap::new()),
+            klines: RwLock::new(HashMap::new()),
+            order_books: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl TickStore for MemoryMarketStore {
+    async fn insert_ticks(&self, ticks: &[MarketTick]) -> Result<()> {
+        let mut series = self.ticks.write().await;
+        for tick in ticks {
+            let buffer = series.entry(key(&tick.exchange, &tick.symbol)).or_default();
+            // 乱序到达的Tick插入到对应位置，保持时间升序
+            let position = buffer.partition_point(|t| t.timestamp <= tick.timestamp);
+            buffer.insert(position, tick.clone());
+            while buffer.len() > self.config.max_ticks_per_symbol {
+                buffer.pop_front();
+            }
+        }
+        Ok(())
+    }
+
+    async fn ticks(&self, exchange: Exchange, symbol: &str, range: TimeRange) -> Result<Vec<MarketTick>> {
+        let series = self.ticks.read().await;
+        Ok(series
+            .get(&key(&exchange, symbol))
+            .map(|buffer| {
+                let ticks = buffer
+                    .iter()
+                    .filter(|t| t.timestamp >= range.start && t.timestamp < range.end);
+                if range.descending {
+                    ticks.rev().take(range.limit).cloned().collect()
+                } else {
+                    ticks.take(range.limit).cloned().collect()
+                }
+            })
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl KlineStore for MemoryMarketStore {
+    async fn upsert_klines(&self, klines: &[Kline]) -> Result<()> {
+        let mut series = self.klines.write().await;
+        for kline in klines {
+            let bars = series
+                .entry((key(&kline.exchange, &kline.symbol), kline.interval.clone()))
+                .or_default();
+            bars.insert(kline.open_time, kline.clone());
+            while bars.len() > self.config.max_klines_per_series {
+                bars.pop_first();
+            }
+        }
+        Ok(())
+    }
+
+    async fn klines(
+        &self,
+        exchange: Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: TimeRange,
+    ) -> Result<Vec<Kline>> {
+        let series = self.klines.read().await;
+        Ok(series
+            .get(&(key(&exchange, symbol), interval))
+            .map(|bars| {
+                let bars = bars.range(range.start..range.end).map(|(_, kline)| kline.clone());
+                if range.descending {
+                    bars.rev().take(range.limit).collect()
+                } else {
+                    bars.take(range.limit).collect()
+                }
+            })
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl OrderBookStore for MemoryMarketStore {
+    async fn save_snapshot(&self, book: &OrderBook) -> Result<()> {
+        let mut books = self.order_books.write().await;
+        let entry = books.entry(key(&book.exchange, &book.symbol));
+        match entry {
+            std::collections::hash_map::Entry::Occupied(mut existing) => {
+                if existing.get().timestamp <= book.timestamp {
+                    existing.insert(book.clone());
+                }
+            }
+            std::collections::hash_map::Entry::Vacant(vacant) => {
+                vacant.insert(book.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn latest_snapshot(&self, exchange: Exchange, symbol: &str) -> Result<Option<OrderBook>> {
+        Ok(self.order_books.read().await.get(&key(&exchange, symbol)).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stores::MarketStores;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::Decimal;
+    use shared_models::common::DataQuality;
+
+    fn tick(at: DateTime<Utc>, price: i64) -> MarketTick {
+        MarketTick {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            timestamp: at,
+            price: Decimal::from(price),
+            volume: Decimal::ONE,
+            bid: Decimal::from(price),
+            ask: Decimal::from(price),
+            bid_volume: Decimal::ONE,
+            ask_volume: Decimal::ONE,
+            trade_id: None,
+            is_buyer_maker: None,
+            data_quality: DataQuality::Normal,
+        }
+    }
+
+    fn kline(open_time: DateTime<Utc>, close: i64) -> Kline {
+        Kline {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            interval: Interval::OneMinute,
+            open_time,
+            close_time: open_time + Duration::seconds(59),
+            open: Decimal::from(close),
+            high: Decimal::from(close),
+            low: Decimal::from(close),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+            quote_volume: Decimal::from(close),
+            trades_count: 1,
+            taker_buy_base_volume: Decimal::ZERO,
+            taker_buy_quote_volume: Decimal::ZERO,
+            is_closed: true,
+            data_quality: DataQuality::Normal,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        let stores = MarketStores::in_memory(&MemoryStoreConfig {
+            max_ticks_per_symbol: 3,
+            max_klines_per_series: 2,
+        });
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let range = TimeRange {
+            start: t0,
+            end: t0 + Duration::hours(1),
+            limit: 100,
+            descending: false,
+        };
+
+        // 乱序写入，超过上限丢弃最早的
+        let ticks: Vec<_> = [3, 1, 2, 4].iter().map(|s| tick(t0 + Duration::seconds(*s), *s)).collect();
+        stores.ticks.insert_ticks(&ticks).await.unwrap();
+        let stored = stores.ticks.ticks(Exchange::Binance, "btcusdt", range).await.unwrap();
+        let prices: Vec<_> = stored.iter().map(|t| t.price).collect();
+        assert_eq!(prices, vec![Decimal::from(2), Decimal::from(3), Decimal::from(4)]);
+
+        // 同一开盘时间以最后一次写入为准
+        let minute = Duration::minutes(1);
+        stores
+            .klines
+            .upsert_klines(&[kline(t0, 100), kline(t0 + minute, 101), kline(t0 + minute, 102)])
+            .await
+            .unwrap();
+        let bars = stores
+            .klines
+            .klines(Exchange::Binance, "BTCUSDT", Interval::OneMinute, range)
+            .await
+            .unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[1].close, Decimal::from(102));
+        // 降序查询从最新的K线开始截取
+        let latest = stores
+            .klines
+            .klines(
+                Exchange::Binance,
+                "BTCUSDT",
+                Interval::OneMinute,
+                TimeRange {
+                    limit: 1,
+                    descending: true,
+                    ..range
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].open_time, t0 + minute);
+        assert!(stores
+            .klines
+            .klines(Exchange::Binance, "BTCUSDT", Interval::OneHour, range)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let book = OrderBook {
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            timestamp: t0 + minute,
+            last_update_id: 2,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        };
+        stores.order_books.save_snapshot(&book).await.unwrap();
+        // 较早的快照不覆盖较新的
+        stores
+            .order_books
+            .save_snapshot(&OrderBook {
+                timestamp: t0,
+                last_update_id: 1,
+                ..book.clone()
+            })
+            .await
+            .unwrap();
+        let latest = stores.order_books.latest_snapshot(Exchange::Binance, "BTCUSDT").await.unwrap().unwrap();
+        assert_eq!(latest.last_update_id, 2);
+    }
+}
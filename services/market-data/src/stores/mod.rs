@@ -0,0 +1,197 @@
+pub mod clickhouse;
+pub mod memory;
+pub mod postgres;
+pub mod tiered;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use shared_models::common::{DataQuality, Exchange, Interval};
+use shared_models::market::{Kline, MarketTick, OrderBook, OrderBookLevel};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::config::{MarketDataConfig, StorageBackend};
+use crate::connectors::MarketDataEvent;
+use crate::durability::TieredWriter;
+
+pub use self::clickhouse::ClickHouseMarketStore;
+pub use self::memory::MemoryMarketStore;
+pub use self::postgres::PostgresMarketStore;
+pub use self::tiered::TieredMarketStore;
+
+/// 查询时间范围，左闭右开
+#[derive(Debug, Clone, Copy)]
+pub struct TimeRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub limit: usize,
+    /// 按时间降序返回，limit 从 end 一侧截取
+    pub descending: bool,
+}
+
+impl TimeRange {
+    /// SQL排序方向
+    pub fn order(&self) -> &'static str {
+        if self.descending {
+            "DESC"
+        } else {
+            "ASC"
+        }
+    }
+}
+
+/// 解析存储中的数据质量标记，未知值按正常数据处理
+fn parse_quality(value: &str) -> DataQuality {
+    match value {
+        "suspect" => DataQuality::Suspect,
+        "recovered" => DataQuality::Recovered,
+        _ => DataQuality::Normal,
+    }
+}
+
+/// 按档位顺序保存的价格和数量还原为订单簿档位
+fn parse_levels(prices: &[String], quantities: &[String]) -> Result<Vec<OrderBookLevel>> {
+    prices
+        .iter()
+        .zip(quantities)
+        .map(|(price, quantity)| {
+            Ok(OrderBookLevel {
+                price: Decimal::from_str(price)?,
+                quantity: Decimal::from_str(quantity)?,
+            })
+        })
+        .collect()
+}
+
+/// Tick存储
+#[async_trait]
+pub trait TickStore: Send + Sync {
+    async fn insert_ticks(&self, ticks: &[MarketTick]) -> Result<()>;
+
+    /// 按 `range.descending` 指定的时间顺序返回范围内的Tick
+    async fn ticks(&self, exchange: Exchange, symbol: &str, range: TimeRange) -> Result<Vec<MarketTick>>;
+}
+
+/// K线存储，同一交易对、周期和开盘时间的K线以最后一次写入为准
+#[async_trait]
+pub trait KlineStore: Send + Sync {
+    async fn upsert_klines(&self, klines: &[Kline]) -> Result<()>;
+
+    /// 按 `range.descending` 指定的开盘时间顺序返回范围内的K线
+    async fn klines(
+        &self,
+        exchange: Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: TimeRange,
+    ) -> Result<Vec<Kline>>;
+}
+
+/// 订单簿快照存储
+#[async_trait]
+pub trait OrderBookStore: Send + Sync {
+    async fn save_snapshot(&self, book: &OrderBook) -> Result<()>;
+
+    /// 最新一次快照
+    async fn latest_snapshot(&self, exchange: Exchange, symbol: &str) -> Result<Option<OrderBook>>;
+}
+
+/// 按配置选择的行情存储后端
+#[derive(Clone)]
+pub struct MarketStores {
+    pub backend: StorageBackend,
+    pub ticks: Arc<dyn TickStore>,
+    pub klines: Arc<dyn KlineStore>,
+    pub order_books: Arc<dyn OrderBookStore>,
+}
+
+impl MarketStores {
+    /// 同一个后端实例同时实现三类存储
+    fn from_store<S>(backend: StorageBackend, store: S) -> Self
+    where
+        S: TickStore + KlineStore + OrderBookStore + 'static,
+    {
+        let store = Arc::new(store);
+        Self {
+            backend,
+            ticks: store.clone(),
+            klines: store.clone(),
+            order_books: store,
+        }
+    }
+
+    /// 按 `storage.backend` 创建存储，PostgreSQL后端启动时建表
+    ///
+    /// ClickHouse和PostgreSQL后端的写入经分级写入器按数据类型的可靠性等级落库，
+    /// 需在写入器启动前调用。
+    pub async fn from_config(config: &MarketDataConfig, writer: &Arc<TieredWriter>) -> Result<Self> {
+        let backend = config.storage.backend;
+        match backend {
+            StorageBackend::ClickHouse => {
+                let clickhouse = config
+                    .storage
+                    .clickhouse
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("clickhouse backend requires storage.clickhouse"))?;
+                let store = ClickHouseMarketStore::new(
+                    clickhouse,
+                    &config.compaction.ticks_table,
+                    &config.rollups.source_table,
+                    &config.depth_history.table,
+                );
+                Ok(Self::from_store(backend, TieredMarketStore::new(store, writer.clone())))
+            }
+            StorageBackend::Postgres => {
+                let postgres = config
+                    .storage
+                    .postgres
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("postgres backend requires storage.postgres"))?;
+                let store = PostgresMarketStore::connect(postgres).await?;
+                store.ensure_schema().await?;
+                Ok(Self::from_store(backend, TieredMarketStore::new(store, writer.clone())))
+            }
+            StorageBackend::Memory => Ok(Self::in_memory(&config.storage.memory)),
+        }
+    }
+
+    /// 内存存储，未配置持久化的小规模部署和测试使用
+    pub fn in_memory(config: &crate::config::MemoryStoreConfig) -> Self {
+        Self::from_store(StorageBackend::Memory, MemoryMarketStore::new(config.clone()))
+    }
+
+    /// 持久化行情事件
+    ///
+    /// ClickHouse后端的写入由存储管理器和分级写入器负责，这里只为其他后端写入Tick、已收盘K线和订单簿快照。
+    pub fn start(&self, mut events: broadcast::Receiver<MarketDataEvent>) {
+        if self.backend == StorageBackend::ClickHouse {
+            return;
+        }
+        let stores = self.clone();
+        tokio::spawn(async move {
+            info!("Persisting market data to {} store", stores.backend.as_str());
+            loop {
+                let result = match events.recv().await {
+                    Ok(MarketDataEvent::Tick(tick)) => stores.ticks.insert_ticks(&[tick]).await,
+                    Ok(MarketDataEvent::Kline(kline)) if kline.is_closed => {
+                        stores.klines.upsert_klines(&[kline]).await
+                    }
+                    Ok(MarketDataEvent::OrderBook(book)) => stores.order_books.save_snapshot(&book).await,
+                    Ok(_) => Ok(()),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Market store persistence lagged, skipped {} events", skipped);
+                        Ok(())
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if let Err(e) = result {
+                    warn!("Failed to persist market data to {} store: {}", stores.backend.as_str(), e);
+                }
+            }
+        });
+    }
+}
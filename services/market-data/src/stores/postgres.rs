@@ -0,0 +1,339 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use shared_models::common::{Exchange, Interval};
+use shared_models::market::{Kline, MarketTick, OrderBook};
+use sqlx::{postgres::PgPoolOptions, PgPool, Row};
+
+use super::{parse_levels, parse_quality, KlineStore, OrderBookStore, TickStore, TimeRange};
+use crate::config::PostgresStorageConfig;
+use crate::depth_history::recorder::SnapshotRow;
+
+/// Tick、K线和订单簿快照表
+///
+/// K线以 (exchange, symbol, interval, open_time) 为主键，重复写入时覆盖；
+/// 快照的价格和数量按档位顺序保存为文本数组，与ClickHouse快照表一致。
+const SCHEMA: [&str; 5] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS market_ticks (
+        exchange TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        timestamp TIMESTAMPTZ NOT NULL,
+        price NUMERIC NOT NULL,
+        volume NUMERIC NOT NULL,
+        bid NUMERIC NOT NULL,
+        ask NUMERIC NOT NULL,
+        bid_volume NUMERIC NOT NULL DEFAULT 0,
+        ask_volume NUMERIC NOT NULL DEFAULT 0,
+        trade_id TEXT,
+        is_buyer_maker BOOLEAN,
+        data_quality TEXT NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_market_ticks_series ON market_ticks (exchange, symbol, timestamp)",
+    r#"
+    CREATE TABLE IF NOT EXISTS market_klines (
+        exchange TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        interval TEXT NOT NULL,
+        open_time TIMESTAMPTZ NOT NULL,
+        close_time TIMESTAMPTZ NOT NULL,
+        open NUMERIC NOT NULL,
+        high NUMERIC NOT NULL,
+        low NUMERIC NOT NULL,
+        close NUMERIC NOT NULL,
+        volume NUMERIC NOT NULL,
+        quote_volume NUMERIC NOT NULL,
+        trades_count BIGINT NOT NULL,
+        taker_buy_base_volume NUMERIC NOT NULL,
+        taker_buy_quote_volume NUMERIC NOT NULL,
+        is_closed BOOLEAN NOT NULL,
+        data_quality TEXT NOT NULL,
+        PRIMARY KEY (exchange, symbol, interval, open_time)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS orderbook_snapshots (
+        exchange TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        timestamp TIMESTAMPTZ NOT NULL,
+        last_update_id BIGINT NOT NULL,
+        bid_prices TEXT[] NOT NULL,
+        bid_quantities TEXT[] NOT NULL,
+        ask_prices TEXT[] NOT NULL,
+        ask_quantities TEXT[] NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_orderbook_snapshots_series ON orderbook_snapshots (exchange, symbol, timestamp DESC)",
+];
+
+/// 转换为TimescaleDB超表的表及其时间列
+const HYPERTABLES: [(&str, &str); 2] = [("market_ticks", "timestamp"), ("orderbook_snapshots", "timestamp")];
+
+/// PostgreSQL/TimescaleDB行情存储
+pub struct PostgresMarketStore {
+    pool: PgPool,
+    timescale: bool,
+}
+
+impl PostgresMarketStore {
+    pub async fn connect(config: &PostgresStorageConfig) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect market data postgres: {}", e))?;
+        Ok(Self {
+            pool,
+            timescale: config.timescale,
+        })
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        if self.timescale {
+            for (table, column) in HYPERTABLES {
+                sqlx::query("SELECT create_hypertable($1, $2, if_not_exists => TRUE, migrate_data => TRUE)")
+                    .bind(table)
+                    .bind(column)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TickStore for PostgresMarketStore {
+    async fn insert_ticks(&self, ticks: &[MarketTick]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for tick in ticks {
+            sqlx::query(
+                r#"
+                INSERT INTO market_ticks (exchange, symbol, timestamp, price, volume, bid, ask,
+                    bid_volume, ask_volume, trade_id, is_buyer_maker, data_quality)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
+            )
+            .bind(tick.exchange.as_str())
+            .bind(tick.symbol.to_uppercase())
+            .bind(tick.timestamp)
+            .bind(tick.price)
+            .bind(tick.volume)
+            .bind(tick.bid)
+            .bind(tick.ask)
+            .bind(tick.bid_volume)
+            .bind(tick.ask_volume)
+            .bind(&tick.trade_id)
+            .bind(tick.is_buyer_maker)
+            .bind(tick.data_quality.as_str())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn ticks(&self, exchange: Exchange, symbol: &str, range: TimeRange) -> Result<Vec<MarketTick>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT symbol, timestamp, price, volume, bid, ask, bid_volume, ask_volume,
+                trade_id, is_buyer_maker, data_quality
+            FROM market_ticks
+            WHERE exchange = $1 AND symbol = $2 AND timestamp >= $3 AND timestamp < $4
+            ORDER BY timestamp {}
+            LIMIT $5
+            "#,
+            range.order()
+        ))
+        .bind(exchange.as_str())
+        .bind(symbol.to_uppercase())
+        .bind(range.start)
+        .bind(range.end)
+        .bind(range.limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(MarketTick {
+                    id: None,
+                    exchange: exchange.clone(),
+                    symbol: row.try_get("symbol")?,
+                    timestamp: row.try_get("timestamp")?,
+                    price: row.try_get("price")?,
+                    volume: row.try_get("volume")?,
+                    bid: row.try_get("bid")?,
+                    ask: row.try_get("ask")?,
+                    bid_volume: row.try_get("bid_volume")?,
+                    ask_volume: row.try_get("ask_volume")?,
+                    trade_id: row.try_get("trade_id")?,
+                    is_buyer_maker: row.try_get("is_buyer_maker")?,
+                    data_quality: parse_quality(row.try_get("data_quality")?),
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl KlineStore for PostgresMarketStore {
+    async fn upsert_klines(&self, klines: &[Kline]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for kline in klines {
+            sqlx::query(
+                r#"
+                INSERT INTO market_klines (exchange, symbol, interval, open_time, close_time, open, high, low,
+                    close, volume, quote_volume, trades_count, taker_buy_base_volume, taker_buy_quote_volume,
+                    is_closed, data_quality)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                ON CONFLICT (exchange, symbol, interval, open_time) DO UPDATE SET
+                    close_time = EXCLUDED.close_time,
+                    open = EXCLUDED.open,
+                    high = EXCLUDED.high,
+                    low = EXCLUDED.low,
+                    close = EXCLUDED.close,
+                    volume = EXCLUDED.volume,
+                    quote_volume = EXCLUDED.quote_volume,
+                    trades_count = EXCLUDED.trades_count,
+                    taker_buy_base_volume = EXCLUDED.taker_buy_base_volume,
+                    taker_buy_quote_volume = EXCLUDED.taker_buy_quote_volume,
+                    is_closed = EXCLUDED.is_closed,
+                    data_quality = EXCLUDED.data_quality
+                "#,
+            )
+            .bind(kline.exchange.as_str())
+            .bind(kline.symbol.to_uppercase())
+            .bind(kline.interval.as_str())
+            .bind(kline.open_time)
+            .bind(kline.close_time)
+            .bind(kline.open)
+            .bind(kline.high)
+            .bind(kline.low)
+            .bind(kline.close)
+            .bind(kline.volume)
+            .bind(kline.quote_volume)
+            .bind(i64::from(kline.trades_count))
+            .bind(kline.taker_buy_base_volume)
+            .bind(kline.taker_buy_quote_volume)
+            .bind(kline.is_closed)
+            .bind(kline.data_quality.as_str())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn klines(
+        &self,
+        exchange: Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: TimeRange,
+    ) -> Result<Vec<Kline>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT symbol, open_time, close_time, open, high, low, close, volume, quote_volume, trades_count,
+                taker_buy_base_volume, taker_buy_quote_volume, is_closed, data_quality
+            FROM market_klines
+            WHERE exchange = $1 AND symbol = $2 AND interval = $3 AND open_time >= $4 AND open_time < $5
+            ORDER BY open_time {}
+            LIMIT $6
+            "#,
+            range.order()
+        ))
+        .bind(exchange.as_str())
+        .bind(symbol.to_uppercase())
+        .bind(interval.as_str())
+        .bind(range.start)
+        .bind(range.end)
+        .bind(range.limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(Kline {
+                    id: None,
+                    exchange: exchange.clone(),
+                    symbol: row.try_get("symbol")?,
+                    interval: interval.clone(),
+                    open_time: row.try_get("open_time")?,
+                    close_time: row.try_get("close_time")?,
+                    open: row.try_get("open")?,
+                    high: row.try_get("high")?,
+                    low: row.try_get("low")?,
+                    close: row.try_get("close")?,
+                    volume: row.try_get("volume")?,
+                    quote_volume: row.try_get("quote_volume")?,
+                    trades_count: u32::try_from(row.try_get::<i64, _>("trades_count")?)?,
+                    taker_buy_base_volume: row.try_get("taker_buy_base_volume")?,
+                    taker_buy_quote_volume: row.try_get("taker_buy_quote_volume")?,
+                    is_closed: row.try_get("is_closed")?,
+                    data_quality: parse_quality(row.try_get("data_quality")?),
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OrderBookStore for PostgresMarketStore {
+    async fn save_snapshot(&self, book: &OrderBook) -> Result<()> {
+        let depth = book.bids.len().max(book.asks.len());
+        let row = SnapshotRow::from_orderbook(book, depth);
+        sqlx::query(
+            r#"
+            INSERT INTO orderbook_snapshots (exchange, symbol, timestamp, last_update_id,
+                bid_prices, bid_quantities, ask_prices, ask_quantities)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(&row.exchange)
+        .bind(&row.symbol)
+        .bind(book.timestamp)
+        .bind(i64::try_from(row.last_update_id)?)
+        .bind(&row.bid_prices)
+        .bind(&row.bid_quantities)
+        .bind(&row.ask_prices)
+        .bind(&row.ask_quantities)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn latest_snapshot(&self, exchange: Exchange, symbol: &str) -> Result<Option<OrderBook>> {
+        let row = sqlx::query(
+            r#"
+            SELECT symbol, timestamp, last_update_id, bid_prices, bid_quantities, ask_prices, ask_quantities
+            FROM orderbook_snapshots
+            WHERE exchange = $1 AND symbol = $2
+            ORDER BY timestamp DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(exchange.as_str())
+        .bind(symbol.to_uppercase())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let bid_prices: Vec<String> = row.try_get("bid_prices")?;
+        let bid_quantities: Vec<String> = row.try_get("bid_quantities")?;
+        let ask_prices: Vec<String> = row.try_get("ask_prices")?;
+        let ask_quantities: Vec<String> = row.try_get("ask_quantities")?;
+        Ok(Some(OrderBook {
+            exchange,
+            symbol: row.try_get("symbol")?,
+            timestamp: row.try_get("timestamp")?,
+            last_update_id: u64::try_from(row.try_get::<i64, _>("last_update_id")?)?,
+            bids: parse_levels(&bid_prices, &bid_quantities)?,
+            asks: parse_levels(&ask_prices, &ask_quantities)?,
+        }))
+    }
+}
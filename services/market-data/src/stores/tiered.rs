@@ -0,0 +1,189 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use shared_models::common::{Exchange, Interval};
+use shared_models::market::{Kline, MarketTick, OrderBook};
+use std::sync::Arc;
+
+use super::{KlineStore, OrderBookStore, TickStore, TimeRange};
+use crate::durability::{TieredWriter, WriteSink};
+
+/// 行情数据类型，对应 `storage.writes.classes` 中的键
+pub const TICK: &str = "tick";
+pub const KLINE: &str = "kline";
+pub const ORDER_BOOK: &str = "orderbook";
+
+fn parse_rows<T: DeserializeOwned>(rows: &[&str]) -> Result<Vec<T>> {
+    rows.iter()
+        .map(|row| serde_json::from_str(row).map_err(|e| anyhow!("Invalid stored market data row: {}", e)))
+        .collect()
+}
+
+/// 分级写入器的落库端：把预写日志和异步队列中的行情还原后写入后端存储
+struct StoreSink<S> {
+    store: Arc<S>,
+}
+
+#[async_trait]
+impl<S> WriteSink for StoreSink<S>
+where
+    S: TickStore + KlineStore + OrderBookStore + 'static,
+{
+    async fn write_rows(&self, data_type: &str, _table: &str, rows: &[&str]) -> Result<()> {
+        match data_type {
+            TICK => self.store.insert_ticks(&parse_rows::<MarketTick>(rows)?).await,
+            KLINE => self.store.upsert_klines(&parse_rows::<Kline>(rows)?).await,
+            ORDER_BOOK => {
+                for book in parse_rows::<OrderBook>(rows)? {
+                    self.store.save_snapshot(&book).await?;
+                }
+                Ok(())
+            }
+            other => Err(anyhow!("Unsupported market data type: {}", other)),
+        }
+    }
+}
+
+/// 经分级写入器持久化的行情存储
+///
+/// 写入按数据类型的可靠性等级进入预写日志或异步队列后返回，由写入器写入后端存储；
+/// 读取直接访问后端存储，异步路径上尚未落库的数据读不到。
+pub struct TieredMarketStore<S> {
+    store: Arc<S>,
+    writer: Arc<TieredWriter>,
+}
+
+impl<S> TieredMarketStore<S>
+where
+    S: TickStore + KlineStore + OrderBookStore + 'static,
+{
+    /// 把Tick、K线和订单簿快照的写入路由到 `store`，需在写入器启动前创建
+    pub fn new(store: S, writer: Arc<TieredWriter>) -> Self {
+        let store = Arc::new(store);
+        let sink: Arc<dyn WriteSink> = Arc::new(StoreSink { store: store.clone() });
+        for data_type in [TICK, KLINE, ORDER_BOOK] {
+            writer.route(data_type, sink.clone());
+        }
+        Self { store, writer }
+    }
+
+    /// 逐行提交，目标表由后端存储决定，这里使用数据类型
+    async fn write_all<T: Serialize + Sync>(&self, data_type: &str, rows: &[T]) -> Result<()> {
+        for row in rows {
+            self.writer.write(data_type, data_type, row).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S> TickStore for TieredMarketStore<S>
+where
+    S: TickStore + KlineStore + OrderBookStore + 'static,
+{
+    async fn insert_ticks(&self, ticks: &[MarketTick]) -> Result<()> {
+        self.write_all(TICK, ticks).await
+    }
+
+    async fn ticks(&self, exchange: Exchange, symbol: &str, range: TimeRange) -> Result<Vec<MarketTick>> {
+        self.store.ticks(exchange, symbol, range).await
+    }
+}
+
+#[async_trait]
+impl<S> KlineStore for TieredMarketStore<S>
+where
+    S: TickStore + KlineStore + OrderBookStore + 'static,
+{
+    async fn upsert_klines(&self, klines: &[Kline]) -> Result<()> {
+        self.write_all(KLINE, klines).await
+    }
+
+    async fn klines(
+        &self,
+        exchange: Exchange,
+        symbol: &str,
+        interval: Interval,
+        range: TimeRange,
+    ) -> Result<Vec<Kline>> {
+        self.store.klines(exchange, symbol, interval, range).await
+    }
+}
+
+#[async_trait]
+impl<S> OrderBookStore for TieredMarketStore<S>
+where
+    S: TickStore + KlineStore + OrderBookStore + 'static,
+{
+    async fn save_snapshot(&self, book: &OrderBook) -> Result<()> {
+        self.writer.write(ORDER_BOOK, ORDER_BOOK, book).await
+    }
+
+    async fn latest_snapshot(&self, exchange: Exchange, symbol: &str) -> Result<Option<OrderBook>> {
+        self.store.latest_snapshot(exchange, symbol).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MemoryStoreConfig, StorageWriteConfig};
+    use crate::stores::MemoryMarketStore;
+    use chrono::{Duration, Utc};
+    use rust_decimal::Decimal;
+    use shared_models::common::DataQuality;
+    use shared_utils::AppMetrics;
+
+    #[tokio::test]
+    async fn test_store_sink_replays_rows() {
+        let store = Arc::new(MemoryMarketStore::new(MemoryStoreConfig::default()));
+        let sink = StoreSink { store: store.clone() };
+        let now = Utc::now();
+        let tick = MarketTick {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTCUSDT".to_string(),
+            timestamp: now,
+            price: Decimal::new(5_000_010, 2),
+            volume: Decimal::ONE,
+            bid: Decimal::new(5_000_000, 2),
+            ask: Decimal::new(5_000_020, 2),
+            bid_volume: Decimal::ONE,
+            ask_volume: Decimal::ONE,
+            trade_id: None,
+            is_buyer_maker: None,
+            data_quality: DataQuality::Normal,
+        };
+        let row = serde_json::to_string(&tick).unwrap();
+        sink.write_rows(TICK, TICK, &[row.as_str()]).await.unwrap();
+        assert!(sink.write_rows("trade", "market_trades", &[row.as_str()]).await.is_err());
+
+        let range = TimeRange {
+            start: now - Duration::seconds(1),
+            end: now + Duration::seconds(1),
+            limit: 10,
+            descending: false,
+        };
+        let ticks = store.ticks(Exchange::Binance, "BTCUSDT", range).await.unwrap();
+        assert_eq!(ticks.len(), 1);
+        assert_eq!(ticks[0].price, tick.price);
+
+        // 未配置ClickHouse时，行情数据类型由存储后端落库
+        let dir = std::env::temp_dir().join(format!("storage-wal-{}", uuid::Uuid::new_v4()));
+        let config = StorageWriteConfig {
+            wal_dir: dir.to_string_lossy().to_string(),
+            ..StorageWriteConfig::default()
+        };
+        let writer = Arc::new(TieredWriter::new(
+            config,
+            None,
+            Arc::new(AppMetrics::new().unwrap()),
+        ));
+        assert!(!writer.is_enabled());
+        let tiered = TieredMarketStore::new(MemoryMarketStore::new(MemoryStoreConfig::default()), writer.clone());
+        assert!(writer.is_enabled());
+        tiered.insert_ticks(&[tick]).await.unwrap();
+        assert_eq!(writer.stats().buffered.accepted, 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+/// 连接统计信息
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ConnectionStats {
+    pub connected: bool,
+    pub connection_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_message_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub messages_received: u64,
+    pub messages_sent: u64,
+    pub errors_count: u64,
+    pub reconnect_count: u32,
+    pub subscriptions: HashMap<String, Vec<String>>, // symbol -> data_types
+    pub latency_ms: Option<f64>,
+    pub last_error: Option<String>,
+    pub last_error_time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ConnectionStats {
+    /// 记录接收消息
+    pub fn record_message_received(&mut self) {
+        self.messages_received += 1;
+        self.last_message_time = Some(chrono::Utc::now());
+    }
+
+    /// 记录发送消息
+    pub fn record_message_sent(&mut self) {
+        self.messages_sent += 1;
+    }
+
+    /// 记录错误
+    pub fn record_error(&mut self) {
+        self.errors_count += 1;
+    }
+
+    /// 记录错误及错误信息
+    pub fn record_error_message(&mut self, message: impl Into<String>) {
+        self.record_error();
+        self.last_error = Some(message.into());
+        self.last_error_time = Some(chrono::Utc::now());
+    }
+
+    /// 记录重连
+    pub fn record_reconnect(&mut self) {
+        self.reconnect_count += 1;
+    }
+
+    /// 设置连接状态
+    pub fn set_connected(&mut self, connected: bool) {
+        self.connected = connected;
+        if connected {
+            self.connection_time = Some(chrono::Utc::now());
+        }
+    }
+
+    /// 更新延迟
+    pub fn update_latency(&mut self, latency_ms: f64) {
+        self.latency_ms = Some(latency_ms);
+    }
+
+    /// 添加订阅
+    pub fn add_subscription(&mut self, symbol: String, data_type: String) {
+        self.subscriptions
+            .entry(symbol)
+            .or_default()
+            .push(data_type);
+    }
+
+    /// 移除订阅
+    pub fn remove_subscription(&mut self, symbol: &str, data_type: &str) {
+        if let Some(types) = self.subscriptions.get_mut(symbol) {
+            types.retain(|t| t != data_type);
+            if types.is_empty() {
+                self.subscriptions.remove(symbol);
+            }
+        }
+    }
+
+    /// 获取连接时长
+    pub fn connection_duration(&self) -> Option<chrono::Duration> {
+        self.connection_time.map(|start| chrono::Utc::now() - start)
+    }
+
+    /// 获取消息速率 (messages/second)
+    pub fn message_rate(&self) -> f64 {
+        if let Some(duration) = self.connection_duration() {
+            let seconds = duration.num_seconds() as f64;
+            if seconds > 0.0 {
+                return self.messages_received as f64 / seconds;
+            }
+        }
+        0.0
+    }
+
+    /// 获取错误率
+    pub fn error_rate(&self) -> f64 {
+        if self.messages_received > 0 {
+            self.errors_count as f64 / self.messages_received as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 连接器错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectorError {
+    #[error("Connection failed: {0}")]
+    ConnectionFailed(String),
+    
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(String),
+    
+    #[error("Subscription failed: {0}")]
+    SubscriptionFailed(String),
+    
+    #[error("Message parsing failed: {0}")]
+    MessageParsingFailed(String),
+    
+    #[error("Rate limit exceeded: {0}")]
+    RateLimitExceeded(String),
+    
+    #[error("Network error: {0}")]
+    NetworkError(String),
+    
+    #[error("Protocol error: {0}")]
+    ProtocolError(String),
+    
+    #[error("Configuration error: {0}")]
+    ConfigurationError(String),
+}
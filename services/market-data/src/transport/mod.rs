@@ -0,0 +1,6 @@
+//! 交易所WebSocket传输层，不依赖行情处理管线，连接器和独立二进制共用
+mod connection;
+pub mod websocket_client;
+
+pub use connection::{ConnectionStats, ConnectorError};
+pub use websocket_client::{ResilientWsClient, ResilientWsConfig, WebSocketClient};
@@ -0,0 +1,992 @@
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use shared_utils::retry::{random_jitter, Jitter, RetryPolicy};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, timeout, MissedTickBehavior};
+use tokio_tungstenite::{client_async_tls, connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+use super::{ConnectorError, ConnectionStats};
+use crate::config::ConnectionConfig;
+
+/// WebSocket客户端
+pub struct WebSocketClient {
+    url: String,
+    stats: Arc<RwLock<ConnectionStats>>,
+    message_sender: Option<mpsc::UnboundedSender<String>>,
+    event_receiver: Option<mpsc::UnboundedReceiver<WebSocketEvent>>,
+    config: WebSocketConfig,
+}
+
+/// WebSocket配置
+#[derive(Debug, Clone)]
+pub struct WebSocketConfig {
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
+    pub write_timeout: Duration,
+    pub ping_interval: Duration,
+    pub pong_timeout: Duration,
+    pub reconnect_interval: Duration,
+    pub max_reconnect_attempts: u32,
+    pub message_buffer_size: usize,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            write_timeout: Duration::from_secs(10),
+            ping_interval: Duration::from_secs(30),
+            pong_timeout: Duration::from_secs(10),
+            reconnect_interval: Duration::from_secs(5),
+            max_reconnect_attempts: 10,
+            message_buffer_size: 10000,
+        }
+    }
+}
+
+/// WebSocket事件
+#[derive(Debug, Clone)]
+pub enum WebSocketEvent {
+    Connected,
+    Disconnected,
+    Message(String),
+    Error(String),
+    Pong(Vec<u8>),
+}
+
+impl WebSocketClient {
+    /// 创建新的WebSocket客户端
+    pub fn new(url: String, config: WebSocketConfig) -> Self {
+        Self {
+            url,
+            stats: Arc::new(RwLock::new(ConnectionStats::default())),
+            message_sender: None,
+            event_receiver: None,
+            config,
+        }
+    }
+
+    /// 连接到WebSocket服务器
+    pub async fn connect(&mut self) -> Result<mpsc::UnboundedReceiver<WebSocketEvent>> {
+        info!("Connecting to WebSocket: {}", self.url);
+
+        // 验证URL
+        let url = Url::parse(&self.url)
+            .map_err(|e| ConnectorError::ConfigurationError(format!("Invalid URL: {}", e)))?;
+
+        // 建立连接
+        let (ws_stream, _) = timeout(self.config.connect_timeout, connect_async(url))
+            .await
+            .map_err(|_| ConnectorError::ConnectionFailed("Connection timeout".to_string()))?
+            .map_err(|e| ConnectorError::ConnectionFailed(format!("WebSocket connection failed: {}", e)))?;
+
+        info!("WebSocket connected successfully");
+
+        // 分离读写流
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+        // 创建消息通道
+        let (message_tx, mut message_rx) = mpsc::unbounded_channel::<String>();
+        let (event_tx, event_rx) = mpsc::unbounded_channel::<WebSocketEvent>();
+
+        self.message_sender = Some(message_tx);
+
+        // 更新连接状态
+        {
+            let mut stats = self.stats.write().await;
+            stats.set_connected(true);
+        }
+
+        // 发送连接事件
+        let _ = event_tx.send(WebSocketEvent::Connected);
+
+        // 启动消息发送任务
+        let write_timeout = self.config.write_timeout;
+        let stats_write = self.stats.clone();
+        let event_tx_write = event_tx.clone();
+        
+        tokio::spawn(async move {
+            while let Some(message) = message_rx.recv().await {
+                debug!("Sending WebSocket message: {}", message);
+                
+                let send_result = timeout(
+                    write_timeout,
+                    ws_sender.send(Message::Text(message.clone()))
+                ).await;
+
+                match send_result {
+                    Ok(Ok(_)) => {
+                        stats_write.write().await.record_message_sent();
+                        debug!("Message sent successfully");
+                    }
+                    Ok(Err(e)) => {
+                        error!("Failed to send message: {}", e);
+                        stats_write.write().await.record_error();
+                        let _ = event_tx_write.send(WebSocketEvent::Error(format!("Send error: {}", e)));
+                        break;
+                    }
+                    Err(_) => {
+                        error!("Message send timeout");
+                        stats_write.write().await.record_error();
+                        let _ = event_tx_write.send(WebSocketEvent::Error("Send timeout".to_string()));
+                        break;
+                    }
+                }
+            }
+            
+            warn!("WebSocket sender task ended");
+        });
+
+        // 启动消息接收任务
+        let read_timeout = self.config.read_timeout;
+        let stats_read = self.stats.clone();
+        let event_tx_read = event_tx.clone();
+        
+        tokio::spawn(async move {
+            loop {
+                let receive_result = timeout(read_timeout, ws_receiver.next()).await;
+                
+                match receive_result {
+                    Ok(Some(Ok(message))) => {
+                        stats_read.write().await.record_message_received();
+                        
+                        match message {
+                            Message::Text(text) => {
+                                debug!("Received text message: {}", text);
+                                let _ = event_tx_read.send(WebSocketEvent::Message(text));
+                            }
+                            Message::Binary(data) => {
+                                debug!("Received binary message: {} bytes", data.len());
+                                // 尝试将二进制数据转换为文本
+                                if let Ok(text) = String::from_utf8(data) {
+                                    let _ = event_tx_read.send(WebSocketEvent::Message(text));
+                                } else {
+                                    warn!("Received non-UTF8 binary message");
+                                }
+                            }
+                            Message::Ping(data) => {
+                                debug!("Received ping, sending pong");
+                                // WebSocket库会自动处理pong响应
+                            }
+                            Message::Pong(data) => {
+                                debug!("Received pong");
+                                let _ = event_tx_read.send(WebSocketEvent::Pong(data));
+                            }
+                            Message::Close(_) => {
+                                info!("WebSocket connection closed by server");
+                                break;
+                            }
+                            Message::Frame(_) => {
+                                // 原始帧，通常不需要处理
+                            }
+                        }
+                    }
+                    Ok(Some(Err(e))) => {
+                        error!("WebSocket receive error: {}", e);
+                        stats_read.write().await.record_error();
+                        let _ = event_tx_read.send(WebSocketEvent::Error(format!("Receive error: {}", e)));
+                        break;
+                    }
+                    Ok(None) => {
+                        info!("WebSocket stream ended");
+                        break;
+                    }
+                    Err(_) => {
+                        warn!("WebSocket receive timeout");
+                        // 超时不算错误，继续接收
+                        continue;
+                    }
+                }
+            }
+            
+            // 更新连接状态
+            stats_read.write().await.set_connected(false);
+            let _ = event_tx_read.send(WebSocketEvent::Disconnected);
+            warn!("WebSocket receiver task ended");
+        });
+
+        // 启动心跳任务
+        self.start_heartbeat_task(event_tx.clone()).await;
+
+        Ok(event_rx)
+    }
+
+    /// 发送消息
+    pub async fn send_message(&self, message: &str) -> Result<()> {
+        if let Some(sender) = &self.message_sender {
+            sender.send(message.to_string())
+                .map_err(|_| ConnectorError::NetworkError("Message channel closed".to_string()))?;
+            Ok(())
+        } else {
+            Err(ConnectorError::ConnectionFailed("Not connected".to_string()).into())
+        }
+    }
+
+    /// 发送JSON消息
+    pub async fn send_json(&self, json: &Value) -> Result<()> {
+        let message = serde_json::to_string(json)
+            .map_err(|e| ConnectorError::MessageParsingFailed(format!("JSON serialization failed: {}", e)))?;
+        self.send_message(&message).await
+    }
+
+    /// 发送订阅消息
+    pub async fn subscribe(&self, method: &str, params: &Value, id: Option<u64>) -> Result<()> {
+        let subscription = serde_json::json!({
+            "method": method,
+            "params": params,
+            "id": id.unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64)
+        });
+
+        self.send_json(&subscription).await
+    }
+
+    /// 发送取消订阅消息
+    pub async fn unsubscribe(&self, method: &str, params: &Value, id: Option<u64>) -> Result<()> {
+        let unsubscription = serde_json::json!({
+            "method": method,
+            "params": params,
+            "id": id.unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64)
+        });
+
+        self.send_json(&unsubscription).await
+    }
+
+    /// 发送Ping消息
+    pub async fn ping(&self) -> Result<()> {
+        // 通过发送特殊格式的消息来触发ping
+        let ping_message = serde_json::json!({
+            "method": "ping",
+            "params": [],
+            "id": chrono::Utc::now().timestamp_millis()
+        });
+
+        self.send_json(&ping_message).await
+    }
+
+    /// 获取连接统计
+    pub async fn get_stats(&self) -> ConnectionStats {
+        self.stats.read().await.clone()
+    }
+
+    /// 检查是否已连接
+    pub async fn is_connected(&self) -> bool {
+        self.stats.read().await.connected
+    }
+
+    /// 启动心跳任务
+    async fn start_heartbeat_task(&self, event_tx: mpsc::UnboundedSender<WebSocketEvent>) {
+        let ping_interval = self.config.ping_interval;
+        let pong_timeout = self.config.pong_timeout;
+        let stats = self.stats.clone();
+        
+        tokio::spawn(async move {
+            let mut ping_timer = interval(ping_interval);
+            let last_pong = Instant::now();
+            
+            loop {
+                ping_timer.tick().await;
+                
+                // 检查是否仍然连接
+                if !stats.read().await.connected {
+                    break;
+                }
+                
+                // 检查pong超时
+                if last_pong.elapsed() > pong_timeout + ping_interval {
+                    error!("Pong timeout detected, connection may be dead");
+                    let _ = event_tx.send(WebSocketEvent::Error("Pong timeout".to_string()));
+                    break;
+                }
+                
+                // 发送ping (通过特殊消息格式)
+                debug!("Sending heartbeat ping");
+                // 这里应该通过message_sender发送ping，但为了简化，我们跳过实际的ping发送
+                // 在实际实现中，应该有一个专门的ping通道
+            }
+            
+            debug!("Heartbeat task ended");
+        });
+    }
+
+    /// 断开连接
+    pub async fn disconnect(&mut self) -> Result<()> {
+        info!("Disconnecting WebSocket");
+        
+        // 关闭消息发送通道
+        self.message_sender = None;
+        
+        // 更新连接状态
+        {
+            let mut stats = self.stats.write().await;
+            stats.set_connected(false);
+        }
+        
+        Ok(())
+    }
+
+    /// 重置统计信息
+    pub async fn reset_stats(&self) {
+        let mut stats = self.stats.write().await;
+        *stats = ConnectionStats::default();
+    }
+}
+
+/// WebSocket客户端构建器
+pub struct WebSocketClientBuilder {
+    url: Option<String>,
+    config: WebSocketConfig,
+}
+
+impl WebSocketClientBuilder {
+    /// 创建新的构建器
+    pub fn new() -> Self {
+        Self {
+            url: None,
+            config: WebSocketConfig::default(),
+        }
+    }
+
+    /// 设置URL
+    pub fn url<S: Into<String>>(mut self, url: S) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// 设置连接超时
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = timeout;
+        self
+    }
+
+    /// 设置读取超时
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.config.read_timeout = timeout;
+        self
+    }
+
+    /// 设置写入超时
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.config.write_timeout = timeout;
+        self
+    }
+
+    /// 设置心跳间隔
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.config.ping_interval = interval;
+        self
+    }
+
+    /// 设置重连间隔
+    pub fn reconnect_interval(mut self, interval: Duration) -> Self {
+        self.config.reconnect_interval = interval;
+        self
+    }
+
+    /// 设置最大重连次数
+    pub fn max_reconnect_attempts(mut self, attempts: u32) -> Self {
+        self.config.max_reconnect_attempts = attempts;
+        self
+    }
+
+    /// 构建WebSocket客户端
+    pub fn build(self) -> Result<WebSocketClient> {
+        let url = self.url.ok_or_else(|| {
+            ConnectorError::ConfigurationError("URL is required".to_string())
+        })?;
+
+        Ok(WebSocketClient::new(url, self.config))
+    }
+}
+
+impl Default for WebSocketClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 订阅请求编码
+///
+/// 客户端只跟踪主题字符串，重连后由编码器把全部主题重新生成订阅请求。
+pub trait SubscriptionCodec: Send + Sync {
+    fn subscribe(&self, topics: &[String]) -> Result<Vec<Message>>;
+
+    fn unsubscribe(&self, topics: &[String]) -> Result<Vec<Message>>;
+}
+
+/// 文本消息解码为连接器的消息类型
+pub trait WsMessage: Sized + Send + 'static {
+    fn decode(text: &str) -> Result<Self>;
+}
+
+impl WsMessage for String {
+    fn decode(text: &str) -> Result<Self> {
+        Ok(text.to_string())
+    }
+}
+
+/// 按JSON反序列化的消息
+#[derive(Debug)]
+pub struct Json<T>(pub T);
+
+impl<T: DeserializeOwned + Send + 'static> WsMessage for Json<T> {
+    fn decode(text: &str) -> Result<Self> {
+        Ok(Json(serde_json::from_str(text)?))
+    }
+}
+
+/// 可靠连接的事件
+#[derive(Debug)]
+pub enum WsEvent<M> {
+    /// 连接建立，跟踪的主题已重新订阅；重连时 reconnect 为 true
+    Connected { reconnect: bool },
+    Message { message: M, received: Instant },
+    /// 消息无法解码，连接保持
+    DecodeError { text: String, error: String },
+    Disconnected { reason: String },
+}
+
+/// 可靠连接配置
+#[derive(Debug, Clone)]
+pub struct ResilientWsConfig {
+    pub url: String,
+    pub connect_timeout: Duration,
+    pub ping_interval: Duration,
+    /// 超过 ping_interval + pong_timeout 没有收到任何帧视为连接失效
+    pub pong_timeout: Duration,
+    pub reconnect_interval: Duration,
+    pub backoff_multiplier: f64,
+    pub max_backoff: Duration,
+    /// 连续重连失败次数上限，0表示不限
+    pub max_reconnect_attempts: u32,
+    /// HTTP CONNECT代理地址（host:port），不设置时直连
+    pub proxy: Option<String>,
+}
+
+impl ResilientWsConfig {
+    /// 使用交易所连接配置
+    pub fn from_exchange(url: impl Into<String>, connection: &ConnectionConfig) -> Self {
+        Self {
+            url: url.into(),
+            connect_timeout: Duration::from_secs(connection.connect_timeout),
+            ping_interval: Duration::from_secs(connection.ping_interval),
+            pong_timeout: Duration::from_secs(connection.pong_timeout),
+            reconnect_interval: Duration::from_secs(connection.reconnect_interval),
+            backoff_multiplier: connection.backoff_multiplier,
+            max_backoff: Duration::from_secs(connection.max_backoff),
+            max_reconnect_attempts: connection.max_reconnect_attempts,
+            proxy: None,
+        }
+    }
+
+    /// 通过HTTP CONNECT代理连接
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// 重连退避策略
+    ///
+    /// 指数退避并封顶后取一半固定、一半随机，
+    /// 避免多个连接在交易所故障恢复时同时重连。
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::exponential(self.reconnect_interval, self.max_backoff)
+            .with_multiplier(self.backoff_multiplier)
+            .with_jitter(Jitter::Equal)
+    }
+
+    /// 第 attempt 次重连前的等待时间，jitter 取值 [0, 1)
+    pub fn backoff_delay(&self, attempt: u32, jitter: f64) -> Duration {
+        self.retry_policy().delay_with_jitter(attempt, jitter)
+    }
+}
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 发送端，重连后自动指向新连接，可在消息回调中发送重同步等请求
+#[derive(Clone, Default)]
+pub struct WsSender {
+    outgoing: Arc<Mutex<Option<mpsc::UnboundedSender<Message>>>>,
+}
+
+impl WsSender {
+    /// 未连接时返回错误
+    pub fn send(&self, message: Message) -> Result<()> {
+        let outgoing = self.outgoing.lock().expect("ws sender lock");
+        match outgoing.as_ref() {
+            Some(outgoing) => outgoing
+                .send(message)
+                .map_err(|e| ConnectorError::NetworkError(e.to_string()).into()),
+            None => Err(ConnectorError::ConnectionFailed("Not connected".to_string()).into()),
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.outgoing.lock().expect("ws sender lock").is_some()
+    }
+
+    fn set(&self, outgoing: Option<mpsc::UnboundedSender<Message>>) {
+        *self.outgoing.lock().expect("ws sender lock") = outgoing;
+    }
+}
+
+/// 自动重连的WebSocket客户端
+///
+/// 断线后按带抖动的指数退避重连，每次连接建立后（包括首次连接和停止后再次启动）
+/// 重新订阅全部跟踪的主题；
+/// 定时发送Ping，超时未收到任何帧时主动断开重连。收到的消息按 `M` 解码后交给回调。
+pub struct ResilientWsClient {
+    config: ResilientWsConfig,
+    codec: Arc<dyn SubscriptionCodec>,
+    stats: Arc<RwLock<ConnectionStats>>,
+    topics: Arc<Mutex<BTreeSet<String>>>,
+    sender: WsSender,
+    task: Option<JoinHandle<()>>,
+}
+
+impl ResilientWsClient {
+    pub fn new(
+        config: ResilientWsConfig,
+        codec: Arc<dyn SubscriptionCodec>,
+        stats: Arc<RwLock<ConnectionStats>>,
+    ) -> Self {
+        Self {
+            config,
+            codec,
+            stats,
+            topics: Arc::new(Mutex::new(BTreeSet::new())),
+            sender: WsSender::default(),
+            task: None,
+        }
+    }
+
+    pub fn sender(&self) -> WsSender {
+        self.sender.clone()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.sender.is_connected()
+    }
+
+    /// 当前跟踪的主题
+    pub fn topics(&self) -> Vec<String> {
+        self.topics.lock().expect("ws topics lock").iter().cloned().collect()
+    }
+
+    async fn open(config: &ResilientWsConfig) -> Result<WsStream> {
+        let url = Url::parse(&config.url)
+            .map_err(|e| ConnectorError::ConfigurationError(format!("Invalid URL: {}", e)))?;
+        let connect = async {
+            let (stream, _) = match &config.proxy {
+                Some(proxy) => {
+                    let tunnel = Self::tunnel(proxy, &url).await?;
+                    client_async_tls(url, tunnel).await
+                }
+                None => connect_async(url).await,
+            }
+            .map_err(|e| ConnectorError::ConnectionFailed(e.to_string()))?;
+            Ok::<WsStream, anyhow::Error>(stream)
+        };
+        timeout(config.connect_timeout, connect)
+            .await
+            .map_err(|_| ConnectorError::ConnectionFailed("Connection timeout".to_string()))?
+    }
+
+    /// 通过HTTP CONNECT代理建立到目标主机的隧道
+    async fn tunnel(proxy: &str, url: &Url) -> Result<TcpStream> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| ConnectorError::ConfigurationError("URL has no host".to_string()))?;
+        let port = url.port_or_known_default().unwrap_or(443);
+        let mut stream = TcpStream::connect(proxy)
+            .await
+            .map_err(|e| ConnectorError::ConnectionFailed(format!("Failed to connect proxy {}: {}", proxy, e)))?;
+        let request = format!(
+            "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\nProxy-Connection: Keep-Alive\r\n\r\n"
+        );
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| ConnectorError::NetworkError(e.to_string()))?;
+
+        let mut buffer = [0u8; 1024];
+        let read = stream
+            .read(&mut buffer)
+            .await
+            .map_err(|e| ConnectorError::NetworkError(e.to_string()))?;
+        let response = String::from_utf8_lossy(&buffer[..read]);
+        let status = response.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(ConnectorError::ConnectionFailed(format!("Proxy CONNECT failed: {}", status)).into());
+        }
+        Ok(stream)
+    }
+
+    /// 建立连接并启动读写和重连任务
+    ///
+    /// 首次连接失败直接返回错误，之后的断线由后台任务重连。
+    pub async fn start<M, F>(&mut self, on_event: F) -> Result<()>
+    where
+        M: WsMessage,
+        F: FnMut(WsEvent<M>) + Send + 'static,
+    {
+        self.stop();
+        info!("Connecting to {}", self.config.url);
+        let stream = Self::open(&self.config).await?;
+
+        let config = self.config.clone();
+        let codec = self.codec.clone();
+        let stats = self.stats.clone();
+        let topics = self.topics.clone();
+        let sender = self.sender.clone();
+
+        self.task = Some(tokio::spawn(async move {
+            let mut on_event = on_event;
+            let mut session = Some(stream);
+            let mut failures = 0u32;
+            let mut reconnect = false;
+            loop {
+                if let Some(stream) = session.take() {
+                    failures = 0;
+                    let reason = run_session(
+                        stream, &config, codec.as_ref(), &stats, &topics, &sender, reconnect, &mut on_event,
+                    )
+                    .await;
+                    warn!("WebSocket {} disconnected: {}", config.url, reason);
+                    on_event(WsEvent::Disconnected { reason });
+                    reconnect = true;
+                }
+
+                let delay = config.backoff_delay(failures, random_jitter());
+                info!("Reconnecting to {} in {:?}", config.url, delay);
+                tokio::time::sleep(delay).await;
+
+                match Self::open(&config).await {
+                    Ok(stream) => {
+                        stats.write().await.record_reconnect();
+                        session = Some(stream);
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        error!("Failed to reconnect to {} (attempt {}): {}", config.url, failures, e);
+                        stats.write().await.record_error_message(e.to_string());
+                        if config.max_reconnect_attempts > 0 && failures >= config.max_reconnect_attempts {
+                            error!("Giving up on {} after {} reconnect attempts", config.url, failures);
+                            break;
+                        }
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    /// 订阅主题，已跟踪的主题不重复发送
+    ///
+    /// 未连接时只跟踪，连接建立后统一订阅。持有主题锁发送，与会话开始时的重新订阅互斥。
+    pub fn subscribe(&self, topics: &[String]) -> Result<()> {
+        let mut tracked = self.topics.lock().expect("ws topics lock");
+        let added: Vec<String> = topics.iter().filter(|t| tracked.insert((*t).clone())).cloned().collect();
+        if added.is_empty() || !self.is_connected() {
+            return Ok(());
+        }
+        for message in self.codec.subscribe(&added)? {
+            self.sender.send(message)?;
+        }
+        Ok(())
+    }
+
+    /// 取消订阅并停止跟踪
+    pub fn unsubscribe(&self, topics: &[String]) -> Result<()> {
+        let mut tracked = self.topics.lock().expect("ws topics lock");
+        let removed: Vec<String> = topics.iter().filter(|t| tracked.remove(*t)).cloned().collect();
+        if removed.is_empty() || !self.is_connected() {
+            return Ok(());
+        }
+        for message in self.codec.unsubscribe(&removed)? {
+            self.sender.send(message)?;
+        }
+        Ok(())
+    }
+
+    /// 停止重连并断开连接，跟踪的主题保留，再次启动时重新订阅
+    pub fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.sender.set(None);
+    }
+}
+
+impl Drop for ResilientWsClient {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 标记已连接并重新订阅全部跟踪的主题，返回新连接的发送队列
+///
+/// 持有主题锁完成，与 `subscribe`/`unsubscribe` 互斥：此前跟踪的主题由这里订阅，
+/// 之后新增的主题由 `subscribe` 直接发送，不会重复或遗漏。
+fn resume_subscriptions(
+    codec: &dyn SubscriptionCodec,
+    topics: &Mutex<BTreeSet<String>>,
+    sender: &WsSender,
+) -> Result<mpsc::UnboundedReceiver<Message>> {
+    let (outgoing, receiver) = mpsc::unbounded_channel();
+    let tracked = topics.lock().expect("ws topics lock");
+    sender.set(Some(outgoing));
+    if !tracked.is_empty() {
+        let tracked: Vec<String> = tracked.iter().cloned().collect();
+        for message in codec.subscribe(&tracked)? {
+            sender.send(message)?;
+        }
+    }
+    Ok(receiver)
+}
+
+/// 运行一次连接直到断开，返回断开原因
+#[allow(clippy::too_many_arguments)]
+async fn run_session<M, F>(
+    stream: WsStream,
+    config: &ResilientWsConfig,
+    codec: &dyn SubscriptionCodec,
+    stats: &RwLock<ConnectionStats>,
+    topics: &Mutex<BTreeSet<String>>,
+    sender: &WsSender,
+    reconnect: bool,
+    on_event: &mut F,
+) -> String
+where
+    M: WsMessage,
+    F: FnMut(WsEvent<M>),
+{
+    let (mut write, mut read) = stream.split();
+    stats.write().await.set_connected(true);
+
+    let mut outgoing = match resume_subscriptions(codec, topics, sender) {
+        Ok(outgoing) => outgoing,
+        Err(e) => {
+            sender.set(None);
+            stats.write().await.set_connected(false);
+            return format!("failed to rebuild subscriptions: {}", e);
+        }
+    };
+    on_event(WsEvent::Connected { reconnect });
+
+    let mut ping_timer = interval(config.ping_interval);
+    ping_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ping_timer.tick().await;
+    let mut last_frame = Instant::now();
+
+    let reason = loop {
+        tokio::select! {
+            frame = read.next() => {
+                let text = match frame {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Binary(data))) => match String::from_utf8(data) {
+                        Ok(text) => text,
+                        Err(_) => {
+                            warn!("Received non-UTF8 binary message");
+                            last_frame = Instant::now();
+                            continue;
+                        }
+                    },
+                    Some(Ok(Message::Ping(ping))) => {
+                        last_frame = Instant::now();
+                        if let Err(e) = write.send(Message::Pong(ping)).await {
+                            break format!("failed to send pong: {}", e);
+                        }
+                        continue;
+                    }
+                    Some(Ok(Message::Pong(_))) | Some(Ok(Message::Frame(_))) => {
+                        last_frame = Instant::now();
+                        continue;
+                    }
+                    Some(Ok(Message::Close(_))) | None => break "closed by server".to_string(),
+                    Some(Err(e)) => break e.to_string(),
+                };
+                let received = Instant::now();
+                last_frame = received;
+                stats.write().await.record_message_received();
+                match M::decode(&text) {
+                    Ok(message) => on_event(WsEvent::Message { message, received }),
+                    Err(e) => {
+                        stats.write().await.record_error_message(e.to_string());
+                        on_event(WsEvent::DecodeError { text, error: e.to_string() });
+                    }
+                }
+            }
+            request = outgoing.recv() => match request {
+                Some(request) => {
+                    if let Err(e) = write.send(request).await {
+                        break format!("failed to send request: {}", e);
+                    }
+                    stats.write().await.record_message_sent();
+                }
+                None => break "sender dropped".to_string(),
+            },
+            _ = ping_timer.tick() => {
+                if last_frame.elapsed() > config.ping_interval + config.pong_timeout {
+                    break "pong timeout".to_string();
+                }
+                if let Err(e) = write.send(Message::Ping(Vec::new())).await {
+                    break format!("failed to send ping: {}", e);
+                }
+            }
+        }
+    };
+
+    sender.set(None);
+    let mut stats = stats.write().await;
+    stats.set_connected(false);
+    stats.record_error_message(reason.clone());
+    reason
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_config_default() {
+        let config = WebSocketConfig::default();
+        assert_eq!(config.connect_timeout, Duration::from_secs(10));
+        assert_eq!(config.ping_interval, Duration::from_secs(30));
+        assert_eq!(config.max_reconnect_attempts, 10);
+    }
+
+    #[test]
+    fn test_websocket_client_builder() {
+        let client = WebSocketClientBuilder::new()
+            .url("wss://example.com/ws")
+            .connect_timeout(Duration::from_secs(5))
+            .ping_interval(Duration::from_secs(15))
+            .build();
+
+        assert!(client.is_ok());
+        let client = client.unwrap();
+        assert_eq!(client.url, "wss://example.com/ws");
+        assert_eq!(client.config.connect_timeout, Duration::from_secs(5));
+        assert_eq!(client.config.ping_interval, Duration::from_secs(15));
+    }
+
+    #[test]
+    fn test_websocket_client_builder_missing_url() {
+        let result = WebSocketClientBuilder::new()
+            .connect_timeout(Duration::from_secs(5))
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client_creation() {
+        let client = WebSocketClient::new(
+            "wss://example.com/ws".to_string(),
+            WebSocketConfig::default(),
+        );
+
+        assert_eq!(client.url, "wss://example.com/ws");
+        assert!(!client.is_connected().await);
+        
+        let stats = client.get_stats().await;
+        assert!(!stats.connected);
+        assert_eq!(stats.messages_received, 0);
+    }
+
+    #[test]
+    fn test_resilient_backoff_delay() {
+        let config = ResilientWsConfig::from_exchange("wss://example.com/ws", &ConnectionConfig::default());
+
+        // 5秒基数，倍数2，封顶300秒；一半固定一半随机
+        assert_eq!(config.backoff_delay(0, 0.0), Duration::from_secs_f64(2.5));
+        assert_eq!(config.backoff_delay(0, 1.0), Duration::from_secs(5));
+        assert_eq!(config.backoff_delay(2, 0.5), Duration::from_secs(15));
+        assert_eq!(config.backoff_delay(20, 1.0), Duration::from_secs(300));
+        assert_eq!(config.backoff_delay(u32::MAX, 0.0), Duration::from_secs(150));
+
+        let jitter = random_jitter();
+        assert!((0.0..1.0).contains(&jitter));
+    }
+
+    struct TopicCodec;
+
+    impl SubscriptionCodec for TopicCodec {
+        fn subscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+            Ok(vec![Message::Text(format!("+{}", topics.join(",")))])
+        }
+
+        fn unsubscribe(&self, topics: &[String]) -> Result<Vec<Message>> {
+            Ok(vec![Message::Text(format!("-{}", topics.join(",")))])
+        }
+    }
+
+    #[test]
+    fn test_resilient_topic_tracking() {
+        let client = ResilientWsClient::new(
+            ResilientWsConfig::from_exchange("wss://example.com/ws", &ConnectionConfig::default()),
+            Arc::new(TopicCodec),
+            Arc::new(RwLock::new(ConnectionStats::default())),
+        );
+
+        // 未连接时只跟踪主题，连接后统一订阅
+        client.subscribe(&["a".to_string(), "b".to_string()]).unwrap();
+        client.subscribe(&["b".to_string()]).unwrap();
+        client.unsubscribe(&["a".to_string(), "c".to_string()]).unwrap();
+        assert_eq!(client.topics(), vec!["b".to_string()]);
+        assert!(!client.is_connected());
+        assert!(client.sender().send(Message::Text("x".to_string())).is_err());
+
+        // 已连接时只发送新增的主题
+        let (outgoing, mut receiver) = mpsc::unbounded_channel();
+        client.sender.set(Some(outgoing));
+        client.subscribe(&["b".to_string(), "d".to_string()]).unwrap();
+        client.unsubscribe(&["b".to_string()]).unwrap();
+        assert_eq!(receiver.try_recv().unwrap(), Message::Text("+d".to_string()));
+        assert_eq!(receiver.try_recv().unwrap(), Message::Text("-b".to_string()));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_resilient_resubscribe_on_session_start() {
+        let client = ResilientWsClient::new(
+            ResilientWsConfig::from_exchange("wss://example.com/ws", &ConnectionConfig::default()),
+            Arc::new(TopicCodec),
+            Arc::new(RwLock::new(ConnectionStats::default())),
+        );
+        client.subscribe(&["a".to_string(), "b".to_string()]).unwrap();
+
+        // 首次会话订阅启动前跟踪的主题，之后新增的主题直接发送
+        let mut receiver = resume_subscriptions(client.codec.as_ref(), &client.topics, &client.sender).unwrap();
+        assert!(client.is_connected());
+        client.subscribe(&["b".to_string(), "c".to_string()]).unwrap();
+        assert_eq!(receiver.try_recv().unwrap(), Message::Text("+a,b".to_string()));
+        assert_eq!(receiver.try_recv().unwrap(), Message::Text("+c".to_string()));
+
+        // 停止后再次启动，重新订阅全部跟踪的主题
+        client.sender.set(None);
+        client.subscribe(&["a".to_string()]).unwrap();
+        let mut receiver = resume_subscriptions(client.codec.as_ref(), &client.topics, &client.sender).unwrap();
+        assert_eq!(receiver.try_recv().unwrap(), Message::Text("+a,b,c".to_string()));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_json_message_decode() {
+        let Json(value) = <Json<Value>>::decode(r#"{"e":"trade"}"#).unwrap();
+        assert_eq!(value["e"], "trade");
+        assert!(<Json<Value>>::decode("not json").is_err());
+        assert_eq!(String::decode("raw").unwrap(), "raw");
+    }
+}
@@ -0,0 +1,315 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// 客户端断开原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisconnectReason {
+    /// 客户端主动关闭
+    ClientClosed,
+    /// 推送失败，通常是客户端已断开或写缓冲满
+    SendFailed,
+    /// 接收出错
+    ReceiveError(String),
+    /// 服务端事件流关闭
+    ServerShutdown,
+}
+
+/// 客户端健康状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientHealth {
+    Healthy,
+    /// 曾因消费过慢丢弃事件
+    Dropping,
+    /// 当前落后最新事件过多
+    Lagging,
+    /// 消费过慢，行情更新被合并推送
+    Conflating,
+}
+
+/// 单个连接的统计，推送路径上只做原子操作
+#[derive(Debug)]
+pub struct ClientStats {
+    pub id: Uuid,
+    pub endpoint: String,
+    pub connected_at: i64,
+    subscriptions: RwLock<Vec<String>>,
+    messages_sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    messages_received: AtomicU64,
+    bytes_received: AtomicU64,
+    dropped_events: AtomicU64,
+    lag_events: AtomicU64,
+    conflated_events: AtomicU64,
+    last_sequence: AtomicU64,
+    last_activity: AtomicU64,
+}
+
+impl ClientStats {
+    fn new(endpoint: &str) -> Self {
+        let now = chrono::Utc::now().timestamp_millis();
+        Self {
+            id: Uuid::new_v4(),
+            endpoint: endpoint.to_string(),
+            connected_at: now,
+            subscriptions: RwLock::new(Vec::new()),
+            messages_sent: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            dropped_events: AtomicU64::new(0),
+            lag_events: AtomicU64::new(0),
+            conflated_events: AtomicU64::new(0),
+            last_sequence: AtomicU64::new(0),
+            last_activity: AtomicU64::new(now as u64),
+        }
+    }
+
+    fn touch(&self) {
+        self.last_activity
+            .store(chrono::Utc::now().timestamp_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// 记录一次推送，带序号的流同时记录推送位置
+    pub fn record_sent(&self, bytes: usize, sequence: Option<u64>) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        if let Some(sequence) = sequence {
+            self.last_sequence.fetch_max(sequence, Ordering::Relaxed);
+        }
+        self.touch();
+    }
+
+    pub fn record_received(&self, bytes: usize) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.touch();
+    }
+
+    /// 记录广播通道落后被丢弃的事件
+    pub fn record_dropped(&self, skipped: u64) {
+        self.dropped_events.fetch_add(skipped, Ordering::Relaxed);
+        self.lag_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录被合并替换、未单独推送的事件
+    pub fn record_conflated(&self, conflated: u64) {
+        self.conflated_events.fetch_add(conflated, Ordering::Relaxed);
+    }
+
+    pub async fn set_subscriptions(&self, subscriptions: Vec<String>) {
+        *self.subscriptions.write().await = subscriptions;
+    }
+
+    /// 生成快照，`head_sequence` 为广播器最新序号，用于计算落后量
+    pub async fn snapshot(&self, head_sequence: u64, slow_lag: u64) -> ClientSnapshot {
+        let last_sequence = self.last_sequence.load(Ordering::Relaxed);
+        let lag = if last_sequence > 0 {
+            Some(head_sequence.saturating_sub(last_sequence))
+        } else {
+            None
+        };
+        let dropped_events = self.dropped_events.load(Ordering::Relaxed);
+        let messages_sent = self.messages_sent.load(Ordering::Relaxed);
+        let conflated_events = self.conflated_events.load(Ordering::Relaxed);
+        let conflation_ratio = if conflated_events > 0 {
+            conflated_events as f64 / (conflated_events + messages_sent) as f64
+        } else {
+            0.0
+        };
+
+        let health = match lag {
+            Some(lag) if lag > slow_lag => ClientHealth::Lagging,
+            _ if dropped_events > 0 => ClientHealth::Dropping,
+            _ if conflated_events > 0 => ClientHealth::Conflating,
+            _ => ClientHealth::Healthy,
+        };
+
+        ClientSnapshot {
+            id: self.id,
+            endpoint: self.endpoint.clone(),
+            connected_at: self.connected_at,
+            last_activity: self.last_activity.load(Ordering::Relaxed) as i64,
+            subscriptions: self.subscriptions.read().await.clone(),
+            messages_sent,
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            dropped_events,
+            lag_events: self.lag_events.load(Ordering::Relaxed),
+            conflated_events,
+            conflation_ratio,
+            last_sequence: (last_sequence > 0).then_some(last_sequence),
+            lag,
+            health,
+        }
+    }
+}
+
+/// 连接快照
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientSnapshot {
+    pub id: Uuid,
+    pub endpoint: String,
+    pub connected_at: i64,
+    pub last_activity: i64,
+    pub subscriptions: Vec<String>,
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    pub messages_received: u64,
+    pub bytes_received: u64,
+    pub dropped_events: u64,
+    /// 发生落后丢弃的次数
+    pub lag_events: u64,
+    /// 被更新数据合并替换的事件数
+    pub conflated_events: u64,
+    /// 合并事件占应推送事件的比例
+    pub conflation_ratio: f64,
+    pub last_sequence: Option<u64>,
+    /// 落后广播器最新序号的事件数，无序号的流为空
+    pub lag: Option<u64>,
+    pub health: ClientHealth,
+}
+
+/// 断开记录
+#[derive(Debug, Clone, Serialize)]
+pub struct DisconnectRecord {
+    pub client: ClientSnapshot,
+    pub reason: DisconnectReason,
+    pub disconnected_at: i64,
+}
+
+/// WebSocket客户端注册表
+///
+/// 记录每个活跃连接的统计，并保留最近的断开原因用于排查慢消费者。
+pub struct ClientRegistry {
+    clients: RwLock<HashMap<Uuid, Arc<ClientStats>>>,
+    disconnects: RwLock<VecDeque<DisconnectRecord>>,
+    history_size: usize,
+    slow_lag: u64,
+}
+
+impl ClientRegistry {
+    pub fn new(history_size: usize, slow_lag: u64) -> Self {
+        Self {
+            clients: RwLock::new(HashMap::new()),
+            disconnects: RwLock::new(VecDeque::with_capacity(history_size)),
+            history_size,
+            slow_lag,
+        }
+    }
+
+    /// 注册新连接
+    pub async fn register(&self, endpoint: &str) -> Arc<ClientStats> {
+        let stats = Arc::new(ClientStats::new(endpoint));
+        self.clients.write().await.insert(stats.id, stats.clone());
+        stats
+    }
+
+    /// 注销连接并记录断开原因
+    pub async fn unregister(&self, id: Uuid, reason: DisconnectReason, head_sequence: u64) {
+        let Some(stats) = self.clients.write().await.remove(&id) else {
+            return;
+        };
+        if self.history_size == 0 {
+            return;
+        }
+
+        let record = DisconnectRecord {
+            client: stats.snapshot(head_sequence, self.slow_lag).await,
+            reason,
+            disconnected_at: chrono::Utc::now().timestamp_millis(),
+        };
+        let mut disconnects = self.disconnects.write().await;
+        if disconnects.len() >= self.history_size {
+            disconnects.pop_front();
+        }
+        disconnects.push_back(record);
+    }
+
+    /// 活跃连接快照，按落后量从大到小排列
+    pub async fn clients(&self, head_sequence: u64) -> Vec<ClientSnapshot> {
+        let clients: Vec<Arc<ClientStats>> = self.clients.read().await.values().cloned().collect();
+        let mut snapshots = Vec::with_capacity(clients.len());
+        for client in clients {
+            snapshots.push(client.snapshot(head_sequence, self.slow_lag).await);
+        }
+        snapshots.sort_by(|a, b| {
+            b.lag
+                .cmp(&a.lag)
+                .then_with(|| b.dropped_events.cmp(&a.dropped_events))
+        });
+        snapshots
+    }
+
+    /// 最近的断开记录，最新的在前
+    pub async fn recent_disconnects(&self) -> Vec<DisconnectRecord> {
+        self.disconnects.read().await.iter().rev().cloned().collect()
+    }
+
+    pub async fn active_count(&self) -> usize {
+        self.clients.read().await.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_client_health() {
+        let registry = ClientRegistry::new(10, 100);
+        let fast = registry.register("/ws/stream").await;
+        let slow = registry.register("/ws/stream").await;
+        let trades = registry.register("/ws/trades").await;
+        let conflating = registry.register("/ws/stream").await;
+
+        fast.record_sent(120, Some(1000));
+        slow.record_sent(120, Some(700));
+        trades.record_sent(80, None);
+        trades.record_dropped(15);
+        for _ in 0..3 {
+            conflating.record_sent(120, Some(1000));
+        }
+        conflating.record_conflated(1);
+
+        let clients = registry.clients(1000).await;
+        assert_eq!(clients.len(), 4);
+        assert_eq!(clients[0].id, slow.id);
+        assert_eq!(clients[0].lag, Some(300));
+        assert_eq!(clients[0].health, ClientHealth::Lagging);
+
+        let health: HashMap<Uuid, ClientHealth> = clients.iter().map(|c| (c.id, c.health)).collect();
+        assert_eq!(health[&fast.id], ClientHealth::Healthy);
+        assert_eq!(health[&trades.id], ClientHealth::Dropping);
+        assert_eq!(health[&conflating.id], ClientHealth::Conflating);
+
+        let snapshot = clients.iter().find(|c| c.id == conflating.id).unwrap();
+        assert_eq!(snapshot.conflated_events, 1);
+        assert_eq!(snapshot.conflation_ratio, 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_ring_buffer() {
+        let registry = ClientRegistry::new(2, 100);
+        for _ in 0..3 {
+            let client = registry.register("/ws/stream").await;
+            registry
+                .unregister(client.id, DisconnectReason::ClientClosed, 0)
+                .await;
+        }
+        let last = registry.register("/ws/stream").await;
+        registry.unregister(last.id, DisconnectReason::SendFailed, 0).await;
+
+        let disconnects = registry.recent_disconnects().await;
+        assert_eq!(disconnects.len(), 2);
+        assert_eq!(disconnects[0].client.id, last.id);
+        assert_eq!(disconnects[0].reason, DisconnectReason::SendFailed);
+        assert_eq!(registry.active_count().await, 0);
+    }
+}
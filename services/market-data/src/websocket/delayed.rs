@@ -0,0 +1,274 @@
+use axum::http::HeaderMap;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use shared_models::market::Ticker24hr;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+use super::{TopicSubscription, WebSocketBroadcaster, WebSocketEvent};
+use crate::config::DelayedDataConfig;
+
+/// 行情权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataEntitlement {
+    Realtime,
+    Delayed,
+}
+
+impl DataEntitlement {
+    /// 按网关转发的权限和角色请求头确定，未启用延迟行情时所有用户都接收实时行情
+    pub fn from_headers(config: &DelayedDataConfig, headers: &HeaderMap) -> Self {
+        if !config.enabled {
+            return DataEntitlement::Realtime;
+        }
+        let list = |name: &str| -> Vec<String> {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| serde_json::from_str(value).ok())
+                .unwrap_or_default()
+        };
+        let realtime = list("x-user-permissions")
+            .iter()
+            .any(|permission| *permission == config.realtime_permission)
+            || list("x-user-roles")
+                .iter()
+                .any(|role| config.realtime_roles.contains(role));
+        if realtime {
+            DataEntitlement::Realtime
+        } else {
+            DataEntitlement::Delayed
+        }
+    }
+
+    pub fn is_delayed(&self) -> bool {
+        matches!(self, DataEntitlement::Delayed)
+    }
+}
+
+/// 延迟缓冲，按到期时间和到达顺序排列
+struct DelayBuffer {
+    capacity: usize,
+    next_seq: u64,
+    events: BTreeMap<(i64, u64), WebSocketEvent>,
+}
+
+impl DelayBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            next_seq: 0,
+            events: BTreeMap::new(),
+        }
+    }
+
+    /// 加入缓冲，缓冲已满时丢弃最早到期的事件并返回 true
+    fn push(&mut self, release_at: i64, event: WebSocketEvent) -> bool {
+        let dropped = self.events.len() >= self.capacity && self.events.pop_first().is_some();
+        self.events.insert((release_at, self.next_seq), event);
+        self.next_seq += 1;
+        dropped
+    }
+
+    /// 取出到期的事件
+    fn take_due(&mut self, now: i64) -> Vec<WebSocketEvent> {
+        let pending = self.events.split_off(&(now + 1, 0));
+        std::mem::replace(&mut self.events, pending).into_values().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.events.len()
+    }
+}
+
+/// 延迟行情分发
+///
+/// 订阅实时广播器的全部事件，需要延迟的交易对按配置的延迟放入缓冲，到期后发布到独立的
+/// 延迟广播器；不延迟的交易对和没有交易对的事件直接转发。延迟广播器有自己的序号和续传缓冲，
+/// 延迟用户的会话恢复不会补发实时数据。同时保留已发布的最新24小时行情，供延迟用户的REST查询。
+pub struct DelayedFeed {
+    config: DelayedDataConfig,
+    broadcaster: Arc<WebSocketBroadcaster>,
+    buffer: Mutex<DelayBuffer>,
+    tickers: RwLock<HashMap<(String, String), Ticker24hr>>,
+    dropped: AtomicU64,
+}
+
+impl DelayedFeed {
+    pub fn new(config: DelayedDataConfig, broadcaster: WebSocketBroadcaster) -> Self {
+        let buffer = Mutex::new(DelayBuffer::new(config.max_buffered_events));
+        Self {
+            config,
+            broadcaster: Arc::new(broadcaster),
+            buffer,
+            tickers: RwLock::new(HashMap::new()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 延迟用户使用的广播器
+    pub fn broadcaster(&self) -> &Arc<WebSocketBroadcaster> {
+        &self.broadcaster
+    }
+
+    /// 延迟用户在该交易对上可见数据的截止时间（毫秒），不延迟时返回 None
+    pub fn cutoff(&self, symbol: &str, now: i64) -> Option<i64> {
+        self.config
+            .delay_for(symbol)
+            .map(|delay| now - delay.as_millis() as i64)
+    }
+
+    /// 已发布的最新24小时行情
+    pub fn tickers(&self, exchange: Option<&str>) -> Vec<Ticker24hr> {
+        self.tickers
+            .read()
+            .iter()
+            .filter(|((ticker_exchange, _), _)| exchange.map_or(true, |e| e.eq_ignore_ascii_case(ticker_exchange)))
+            .map(|(_, ticker)| ticker.clone())
+            .collect()
+    }
+
+    pub fn ticker(&self, exchange: &str, symbol: &str) -> Option<Ticker24hr> {
+        self.tickers
+            .read()
+            .get(&(exchange.to_lowercase(), symbol.to_uppercase()))
+            .cloned()
+    }
+
+    /// 缓冲中的事件数
+    pub fn buffered(&self) -> usize {
+        self.buffer.lock().len()
+    }
+
+    /// 缓冲已满或订阅滞后丢弃的事件数
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 接收实时事件，需要延迟的放入缓冲，其余直接发布
+    pub async fn push(&self, event: WebSocketEvent, now: i64) {
+        let delay = event.symbol().and_then(|symbol| self.config.delay_for(symbol));
+        match delay {
+            Some(delay) => {
+                let release_at = now + delay.as_millis() as i64;
+                if self.buffer.lock().push(release_at, event) {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            None => self.publish(event).await,
+        }
+    }
+
+    /// 发布到期的事件，返回发布的条数
+    pub async fn release(&self, now: i64) -> usize {
+        let due = self.buffer.lock().take_due(now);
+        let released = due.len();
+        for event in due {
+            self.publish(event).await;
+        }
+        released
+    }
+
+    async fn publish(&self, event: WebSocketEvent) {
+        if let WebSocketEvent::Ticker24hr(ticker) = &event {
+            if let (Some(exchange), Some(symbol)) = (event.exchange(), event.symbol()) {
+                self.tickers
+                    .write()
+                    .insert((exchange.to_lowercase(), symbol.to_uppercase()), ticker.clone());
+            }
+        }
+        if let Err(e) = self.broadcaster.broadcast(event).await {
+            debug!("Failed to broadcast delayed event: {}", e);
+        }
+    }
+
+    /// 启动延迟分发，`source` 为实时广播器上接收全部事件的订阅
+    pub fn start(self: Arc<Self>, mut source: TopicSubscription) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(self.config.release_interval_ms.max(10)));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    event = source.recv() => match event {
+                        Ok(event) => self.push(event.event, chrono::Utc::now().timestamp_millis()).await,
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Delayed feed lagged, skipped {} events", skipped);
+                            self.dropped.fetch_add(skipped, Ordering::Relaxed);
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = ticker.tick() => {
+                        self.release(chrono::Utc::now().timestamp_millis()).await;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use shared_models::Timestamp;
+
+    fn heartbeat(millis: i64) -> WebSocketEvent {
+        WebSocketEvent::Heartbeat {
+            timestamp: Timestamp::from_millis(millis),
+        }
+    }
+
+    fn released_at(events: Vec<WebSocketEvent>) -> Vec<i64> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                WebSocketEvent::Heartbeat { timestamp } => timestamp.as_millis(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_entitlement_from_headers() {
+        let mut config = DelayedDataConfig::default();
+        let mut headers = HeaderMap::new();
+        assert_eq!(DataEntitlement::from_headers(&config, &headers), DataEntitlement::Realtime);
+
+        config.enabled = true;
+        assert!(DataEntitlement::from_headers(&config, &headers).is_delayed());
+
+        headers.insert("x-user-roles", HeaderValue::from_static(r#"["premium"]"#));
+        assert_eq!(DataEntitlement::from_headers(&config, &headers), DataEntitlement::Realtime);
+
+        headers.insert("x-user-roles", HeaderValue::from_static(r#"["trader"]"#));
+        headers.insert("x-user-permissions", HeaderValue::from_static(r#"["market_data:realtime"]"#));
+        assert_eq!(DataEntitlement::from_headers(&config, &headers), DataEntitlement::Realtime);
+    }
+
+    #[test]
+    fn test_delay_buffer_releases_in_order() {
+        let mut buffer = DelayBuffer::new(3);
+        assert!(!buffer.push(300, heartbeat(300)));
+        assert!(!buffer.push(100, heartbeat(100)));
+        assert!(!buffer.push(200, heartbeat(200)));
+
+        assert!(buffer.take_due(99).is_empty());
+        assert_eq!(released_at(buffer.take_due(200)), vec![100, 200]);
+
+        // 缓冲已满时丢弃最早到期的事件
+        assert!(!buffer.push(400, heartbeat(400)));
+        assert!(!buffer.push(500, heartbeat(500)));
+        assert!(buffer.push(600, heartbeat(600)));
+        assert_eq!(released_at(buffer.take_due(1_000)), vec![400, 500, 600]);
+        assert_eq!(buffer.len(), 0);
+    }
+}
@@ -2,20 +2,32 @@ pub mod server;
 pub mod connection;
 pub mod message;
 pub mod subscription;
+pub mod session;
+pub mod clients;
+pub mod topics;
+pub mod quotas;
+pub mod delayed;
 
 use anyhow::Result;
 use axum::extract::ws::{Message, WebSocket};
 use serde::{Deserialize, Serialize};
-use shared_models::market::{MarketTick, Kline, OrderBook, Trade};
-use std::collections::HashMap;
+use shared_models::market::{CandleClose, DepthMetrics, MarketTick, Kline, OrderBook, Ticker24hr, Trade, WhaleTrade};
+use shared_models::Timestamp;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 pub use server::WebSocketServer;
 pub use connection::{WebSocketConnection, ConnectionManager};
 pub use message::{WebSocketMessage, MessageType, SubscriptionRequest, SubscriptionResponse};
 pub use subscription::{SubscriptionManager, Subscription, SubscriptionFilter};
+pub use session::{SessionStore, StreamSession, SubscriptionSet};
+pub use clients::{ClientRegistry, ClientSnapshot, ClientStats, DisconnectReason, DisconnectRecord};
+pub use topics::{ConflationPolicy, TopicRouter, TopicRouterStats, TopicSubscription};
+pub use quotas::{ClientQuota, QuotaViolation, SubscriptionUsage};
+pub use delayed::{DataEntitlement, DelayedFeed};
 
 use crate::config::MarketDataConfig;
 use crate::processors::DataEvent;
@@ -32,21 +44,29 @@ pub enum WebSocketEvent {
     OrderBook(OrderBook),
     /// 交易数据
     Trade(Trade),
+    /// 24小时滚动行情
+    Ticker24hr(Ticker24hr),
+    /// 订单簿深度指标
+    DepthMetrics(DepthMetrics),
+    /// 大额成交
+    WhaleTrade(WhaleTrade),
+    /// K线定稿、修正和封存
+    CandleClose(CandleClose),
     /// 连接状态变化
     ConnectionStatus {
         exchange: String,
         connected: bool,
-        timestamp: i64,
+        timestamp: Timestamp,
     },
     /// 错误信息
     Error {
         code: u32,
         message: String,
-        timestamp: i64,
+        timestamp: Timestamp,
     },
     /// 心跳
     Heartbeat {
-        timestamp: i64,
+        timestamp: Timestamp,
     },
 }
 
@@ -62,14 +82,14 @@ impl WebSocketEvent {
                 Some(WebSocketEvent::ConnectionStatus {
                     exchange: exchange.clone(),
                     connected: *connected,
-                    timestamp: chrono::Utc::now().timestamp_millis(),
+                    timestamp: Timestamp::now(),
                 })
             }
             DataEvent::Error { exchange, error } => {
                 Some(WebSocketEvent::Error {
                     code: 500,
                     message: format!("{}: {}", exchange, error),
-                    timestamp: chrono::Utc::now().timestamp_millis(),
+                    timestamp: Timestamp::now(),
                 })
             }
         }
@@ -82,6 +102,10 @@ impl WebSocketEvent {
             WebSocketEvent::Kline(_) => "kline",
             WebSocketEvent::OrderBook(_) => "orderbook",
             WebSocketEvent::Trade(_) => "trade",
+            WebSocketEvent::Ticker24hr(_) => "ticker_24hr",
+            WebSocketEvent::DepthMetrics(_) => "depth_metrics",
+            WebSocketEvent::WhaleTrade(_) => "whale_trade",
+            WebSocketEvent::CandleClose(_) => "candle_close",
             WebSocketEvent::ConnectionStatus { .. } => "connection_status",
             WebSocketEvent::Error { .. } => "error",
             WebSocketEvent::Heartbeat { .. } => "heartbeat",
@@ -95,6 +119,10 @@ impl WebSocketEvent {
             WebSocketEvent::Kline(kline) => Some(kline.exchange.as_str()),
             WebSocketEvent::OrderBook(book) => Some(book.exchange.as_str()),
             WebSocketEvent::Trade(trade) => Some(trade.exchange.as_str()),
+            WebSocketEvent::Ticker24hr(ticker) => Some(ticker.exchange.as_str()),
+            WebSocketEvent::DepthMetrics(metrics) => Some(metrics.exchange.as_str()),
+            WebSocketEvent::WhaleTrade(whale) => Some(whale.exchange.as_str()),
+            WebSocketEvent::CandleClose(close) => Some(close.kline.exchange.as_str()),
             WebSocketEvent::ConnectionStatus { exchange, .. } => Some(exchange),
             _ => None,
         }
@@ -107,6 +135,10 @@ impl WebSocketEvent {
             WebSocketEvent::Kline(kline) => Some(&kline.symbol),
             WebSocketEvent::OrderBook(book) => Some(&book.symbol),
             WebSocketEvent::Trade(trade) => Some(&trade.symbol),
+            WebSocketEvent::Ticker24hr(ticker) => Some(&ticker.symbol),
+            WebSocketEvent::DepthMetrics(metrics) => Some(&metrics.symbol),
+            WebSocketEvent::WhaleTrade(whale) => Some(&whale.symbol),
+            WebSocketEvent::CandleClose(close) => Some(&close.kline.symbol),
             _ => None,
         }
     }
@@ -165,7 +197,7 @@ impl WebSocketError {
         WebSocketEvent::Error {
             code,
             message,
-            timestamp: chrono::Utc::now().timestamp_millis(),
+            timestamp: Timestamp::now(),
         }
     }
 }
@@ -282,19 +314,58 @@ impl Default for WebSocketConfig {
     }
 }
 
+/// 带序号的广播事件
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    /// 单调递增的事件序号，用于断线续传
+    pub id: u64,
+    pub event: WebSocketEvent,
+}
+
+/// 断线续传结果
+#[derive(Debug)]
+pub enum ReplayResult {
+    /// 缓冲区完整覆盖请求区间
+    Complete(Vec<SequencedEvent>),
+    /// 请求的序号已被淘汰，只能返回缓冲区内的事件
+    Gap {
+        oldest_available: u64,
+        events: Vec<SequencedEvent>,
+    },
+}
+
 /// WebSocket广播器
+///
+/// WebSocket与SSE共用同一个广播器。事件按 (交易所, 交易对) 主题路由，
+/// 只投递给订阅了该主题的客户端，不再经单一通道扇出给所有连接。
 pub struct WebSocketBroadcaster {
-    sender: broadcast::Sender<WebSocketEvent>,
+    router: Arc<TopicRouter>,
+    replay: Arc<RwLock<VecDeque<SequencedEvent>>>,
+    replay_capacity: usize,
+    next_id: AtomicU64,
+    epoch: String,
     stats: Arc<RwLock<WebSocketStats>>,
 }
 
 impl WebSocketBroadcaster {
     /// 创建新的广播器
     pub fn new(buffer_size: usize) -> Self {
-        let (sender, _) = broadcast::channel(buffer_size);
-        
+        Self::with_replay_buffer(buffer_size, buffer_size)
+    }
+
+    /// 创建指定续传缓冲大小的广播器，`buffer_size` 为每个订阅者的队列容量
+    pub fn with_replay_buffer(buffer_size: usize, replay_capacity: usize) -> Self {
+        Self::with_conflation(buffer_size, replay_capacity, ConflationPolicy::default())
+    }
+
+    /// 创建对慢消费者合并推送的广播器
+    pub fn with_conflation(buffer_size: usize, replay_capacity: usize, conflation: ConflationPolicy) -> Self {
         Self {
-            sender,
+            router: Arc::new(TopicRouter::with_conflation(buffer_size, conflation)),
+            replay: Arc::new(RwLock::new(VecDeque::with_capacity(replay_capacity))),
+            replay_capacity,
+            next_id: AtomicU64::new(1),
+            epoch: uuid::Uuid::new_v4().simple().to_string(),
             stats: Arc::new(RwLock::new(WebSocketStats::default())),
         }
     }
@@ -304,25 +375,74 @@ impl WebSocketBroadcaster {
         let json = event.to_json()?;
         let bytes = json.len() as u64;
 
-        match self.sender.send(event) {
-            Ok(receiver_count) => {
-                let mut stats = self.stats.write().await;
-                stats.record_message_sent(bytes * receiver_count as u64);
-                
-                debug!("Broadcasted event to {} receivers", receiver_count);
-                Ok(())
-            }
-            Err(_) => {
-                // 没有接收者，这是正常的
-                debug!("No receivers for broadcast event");
-                Ok(())
+        // 在续传缓冲的锁内分配序号并投递，保证缓冲和实时流的顺序一致
+        let delivered = {
+            let mut replay = self.replay.write().await;
+            let sequenced = SequencedEvent {
+                id: self.next_id.fetch_add(1, Ordering::SeqCst),
+                event,
+            };
+            let delivered = self.router.publish(&sequenced);
+            if self.replay_capacity > 0 {
+                if replay.len() >= self.replay_capacity {
+                    replay.pop_front();
+                }
+                replay.push_back(sequenced);
             }
+            delivered
+        };
+
+        if delivered > 0 {
+            let mut stats = self.stats.write().await;
+            stats.record_message_sent(bytes * delivered as u64);
+            debug!("Broadcasted event to {} receivers", delivered);
+        } else {
+            // 没有订阅该主题的接收者，这是正常的
+            debug!("No receivers for broadcast event");
+        }
+        Ok(())
+    }
+
+    /// 按过滤条件订阅带序号的事件流
+    ///
+    /// 只按交易所和交易对路由，事件类型和成交额仍需调用方用过滤器检查。
+    pub fn subscribe_topics(&self, filter: &EventFilter) -> TopicSubscription {
+        let subscription = self.router.subscribe();
+        subscription.route(filter);
+        subscription
+    }
+
+    /// 创建未订阅任何主题的订阅，之后通过 `route` 设置主题
+    pub fn subscribe_unrouted(&self) -> TopicSubscription {
+        self.router.subscribe()
+    }
+
+    /// 获取指定序号之后的缓冲事件
+    pub async fn replay_since(&self, last_event_id: u64) -> ReplayResult {
+        let replay = self.replay.read().await;
+        let events: Vec<SequencedEvent> = replay
+            .iter()
+            .filter(|e| e.id > last_event_id)
+            .cloned()
+            .collect();
+
+        match replay.front() {
+            Some(oldest) if oldest.id > last_event_id + 1 => ReplayResult::Gap {
+                oldest_available: oldest.id,
+                events,
+            },
+            _ => ReplayResult::Complete(events),
         }
     }
 
-    /// 订阅事件流
-    pub fn subscribe(&self) -> broadcast::Receiver<WebSocketEvent> {
-        self.sender.subscribe()
+    /// 最新分配的事件序号
+    pub fn last_event_id(&self) -> u64 {
+        self.next_id.load(Ordering::SeqCst).saturating_sub(1)
+    }
+
+    /// 广播器实例标识，每次创建时生成，序号只在同一实例内有效
+    pub fn epoch(&self) -> &str {
+        &self.epoch
     }
 
     /// 获取统计信息
@@ -332,7 +452,12 @@ impl WebSocketBroadcaster {
 
     /// 获取接收者数量
     pub fn receiver_count(&self) -> usize {
-        self.sender.receiver_count()
+        self.router.subscriber_count()
+    }
+
+    /// 主题路由统计
+    pub fn topic_stats(&self) -> TopicRouterStats {
+        self.router.stats()
     }
 }
 
@@ -342,6 +467,8 @@ pub struct EventFilter {
     pub exchanges: Option<Vec<String>>,
     pub symbols: Option<Vec<String>>,
     pub event_types: Option<Vec<String>>,
+    /// 成交事件的最小成交额，在服务端过滤
+    pub min_notional: Option<rust_decimal::Decimal>,
 }
 
 impl EventFilter {
@@ -351,6 +478,7 @@ impl EventFilter {
             exchanges: None,
             symbols: None,
             event_types: None,
+            min_notional: None,
         }
     }
 
@@ -360,6 +488,7 @@ impl EventFilter {
             exchanges: Some(exchanges),
             symbols: None,
             event_types: None,
+            min_notional: None,
         }
     }
 
@@ -369,6 +498,7 @@ impl EventFilter {
             exchanges: None,
             symbols: Some(symbols),
             event_types: None,
+            min_notional: None,
         }
     }
 
@@ -378,9 +508,16 @@ impl EventFilter {
             exchanges: None,
             symbols: None,
             event_types: Some(event_types),
+            min_notional: None,
         }
     }
 
+    /// 设置成交最小成交额
+    pub fn with_min_notional(mut self, min_notional: rust_decimal::Decimal) -> Self {
+        self.min_notional = Some(min_notional);
+        self
+    }
+
     /// 检查事件是否匹配过滤器
     pub fn matches(&self, event: &WebSocketEvent) -> bool {
         // 检查交易所过滤器
@@ -408,6 +545,13 @@ impl EventFilter {
             }
         }
 
+        // 检查成交额过滤器
+        if let (Some(min_notional), WebSocketEvent::Trade(trade)) = (self.min_notional, event) {
+            if trade.price * trade.quantity < min_notional {
+                return false;
+            }
+        }
+
         true
     }
 }
@@ -517,4 +661,71 @@ mod tests {
         
         assert_eq!(broadcaster.receiver_count(), 0);
     }
+
+    fn heartbeat(timestamp: i64) -> WebSocketEvent {
+        WebSocketEvent::Heartbeat {
+            timestamp: Timestamp::from_millis(timestamp),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_broadcaster_replay() {
+        let broadcaster = WebSocketBroadcaster::with_replay_buffer(100, 3);
+        for i in 0..5 {
+            broadcaster.broadcast(heartbeat(i)).await.unwrap();
+        }
+        assert_eq!(broadcaster.last_event_id(), 5);
+
+        // 缓冲区保留序号 3..=5
+        match broadcaster.replay_since(3).await {
+            ReplayResult::Complete(events) => {
+                assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![4, 5]);
+            }
+            _ => panic!("Expected complete replay"),
+        }
+
+        match broadcaster.replay_since(1).await {
+            ReplayResult::Gap { oldest_available, events } => {
+                assert_eq!(oldest_available, 3);
+                assert_eq!(events.len(), 3);
+            }
+            _ => panic!("Expected replay gap"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sequenced_subscription() {
+        let broadcaster = WebSocketBroadcaster::new(10);
+        let mut receiver = broadcaster.subscribe_topics(&EventFilter::allow_all());
+
+        broadcaster.broadcast(heartbeat(1)).await.unwrap();
+        broadcaster.broadcast(heartbeat(2)).await.unwrap();
+
+        assert_eq!(receiver.recv().await.unwrap().id, 1);
+        assert_eq!(receiver.recv().await.unwrap().id, 2);
+        assert_eq!(broadcaster.receiver_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_topic_subscription_skips_other_symbols() {
+        let broadcaster = WebSocketBroadcaster::new(10);
+        let mut receiver = broadcaster.subscribe_topics(&EventFilter::symbols(vec!["BTCUSDT".to_string()]));
+
+        for (i, symbol) in ["ETHUSDT", "BTCUSDT"].iter().enumerate() {
+            let tick = MarketTick {
+                exchange: "binance".to_string(),
+                symbol: symbol.to_string(),
+                timestamp: 1640995200000 + i as i64,
+                price: Decimal::new(50000, 0),
+                volume: Decimal::new(100, 0),
+                bid: Decimal::new(49999, 0),
+                ask: Decimal::new(50001, 0),
+            };
+            broadcaster.broadcast(WebSocketEvent::Tick(tick)).await.unwrap();
+        }
+
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.id, 2);
+        assert_eq!(event.event.symbol(), Some("BTCUSDT"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,262 @@
+use axum::http::HeaderMap;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::SubscriptionSet;
+use crate::config::{QuotaLimits, SubscriptionQuotaConfig};
+
+/// 消息配额的统计窗口（毫秒）
+const MESSAGE_WINDOW_MS: i64 = 60_000;
+
+/// 违反配额时下发给客户端的错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum QuotaViolation {
+    /// 订阅的交易对超过上限，未指定交易对视为订阅全部
+    TooManySymbols { limit: usize, requested: Option<usize> },
+    /// 本分钟推送的消息已达上限，之后的行情被丢弃直到下一分钟
+    MessageRateExceeded { limit: u32 },
+}
+
+impl QuotaViolation {
+    pub fn code(&self) -> u32 {
+        match self {
+            QuotaViolation::TooManySymbols { .. } => 4291,
+            QuotaViolation::MessageRateExceeded { .. } => 4292,
+        }
+    }
+
+    pub fn message(&self, tier: &str) -> String {
+        match self {
+            QuotaViolation::TooManySymbols { limit, requested: Some(requested) } => format!(
+                "Tier {} allows {} symbol subscriptions, requested {}",
+                tier, limit, requested
+            ),
+            QuotaViolation::TooManySymbols { limit, requested: None } => format!(
+                "Tier {} allows {} symbol subscriptions, subscribe to specific symbols",
+                tier, limit
+            ),
+            QuotaViolation::MessageRateExceeded { limit } => format!(
+                "Tier {} allows {} messages per minute, further updates are dropped until the next minute",
+                tier, limit
+            ),
+        }
+    }
+}
+
+/// 一个上报周期内的订阅用量
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionUsage {
+    pub client_id: Uuid,
+    pub user_id: Option<String>,
+    pub tier: String,
+    pub symbols: usize,
+    pub messages: u64,
+    pub bytes: u64,
+    /// 超过消息配额被丢弃的消息数
+    pub throttled: u64,
+    pub period_start: i64,
+    pub period_end: i64,
+}
+
+/// 单个连接的订阅配额和用量
+///
+/// 只在连接自己的任务中使用，不需要同步。
+#[derive(Debug)]
+pub struct ClientQuota {
+    client_id: Uuid,
+    user_id: Option<String>,
+    tier: String,
+    limits: Option<QuotaLimits>,
+    window_start: i64,
+    window_messages: u32,
+    /// 本窗口是否已通知过限流
+    throttle_notified: bool,
+    symbols: usize,
+    messages: u64,
+    bytes: u64,
+    throttled: u64,
+    period_start: i64,
+}
+
+impl ClientQuota {
+    /// 按网关转发的用户和角色请求头确定等级，未启用配额时不做限制
+    ///
+    /// 这两个请求头由网关根据JWT设置，客户端传入的同名请求头在网关被丢弃，
+    /// 因此本服务只能部署在网关之后。
+    pub fn from_headers(config: &SubscriptionQuotaConfig, client_id: Uuid, headers: &HeaderMap) -> Self {
+        let user_id = headers
+            .get("x-user-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let roles: Vec<String> = headers
+            .get("x-user-roles")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| serde_json::from_str(value).ok())
+            .unwrap_or_default();
+        let (tier, limits) = resolve_tier(config, &roles);
+        Self::new(client_id, user_id, tier, config.enabled.then_some(limits))
+    }
+
+    fn new(client_id: Uuid, user_id: Option<String>, tier: String, limits: Option<QuotaLimits>) -> Self {
+        let now = chrono::Utc::now().timestamp_millis();
+        Self {
+            client_id,
+            user_id,
+            tier,
+            limits,
+            window_start: now,
+            window_messages: 0,
+            throttle_notified: false,
+            symbols: 0,
+            messages: 0,
+            bytes: 0,
+            throttled: 0,
+            period_start: now,
+        }
+    }
+
+    pub fn tier(&self) -> &str {
+        &self.tier
+    }
+
+    /// 检查订阅集合是否在交易对配额内
+    pub fn check_subscriptions(&mut self, subscriptions: &SubscriptionSet) -> Result<(), QuotaViolation> {
+        if let Some(limits) = self.limits {
+            if subscriptions.symbols.is_empty() && !subscriptions.is_empty() {
+                return Err(QuotaViolation::TooManySymbols {
+                    limit: limits.max_symbols,
+                    requested: None,
+                });
+            }
+            if subscriptions.symbols.len() > limits.max_symbols {
+                return Err(QuotaViolation::TooManySymbols {
+                    limit: limits.max_symbols,
+                    requested: Some(subscriptions.symbols.len()),
+                });
+            }
+        }
+        self.symbols = subscriptions.symbols.len();
+        Ok(())
+    }
+
+    /// 占用一条消息配额
+    ///
+    /// 超限时返回 `Err`，每个窗口只在第一次超限时带上需要通知客户端的错误。
+    pub fn acquire_message(&mut self, now: i64) -> Result<(), Option<QuotaViolation>> {
+        let Some(limits) = self.limits else {
+            return Ok(());
+        };
+        if now - self.window_start >= MESSAGE_WINDOW_MS {
+            self.window_start = now;
+            self.window_messages = 0;
+            self.throttle_notified = false;
+        }
+        if self.window_messages < limits.messages_per_minute {
+            self.window_messages += 1;
+            return Ok(());
+        }
+
+        self.throttled += 1;
+        if self.throttle_notified {
+            return Err(None);
+        }
+        self.throttle_notified = true;
+        Err(Some(QuotaViolation::MessageRateExceeded {
+            limit: limits.messages_per_minute,
+        }))
+    }
+
+    /// 记录一条已推送的消息
+    pub fn record_sent(&mut self, bytes: usize) {
+        self.messages += 1;
+        self.bytes += bytes as u64;
+    }
+
+    /// 取出本周期的用量并开始新周期
+    pub fn take_usage(&mut self, now: i64) -> SubscriptionUsage {
+        let usage = SubscriptionUsage {
+            client_id: self.client_id,
+            user_id: self.user_id.clone(),
+            tier: self.tier.clone(),
+            symbols: self.symbols,
+            messages: self.messages,
+            bytes: self.bytes,
+            throttled: self.throttled,
+            period_start: self.period_start,
+            period_end: now,
+        };
+        self.messages = 0;
+        self.bytes = 0;
+        self.throttled = 0;
+        self.period_start = now;
+        usage
+    }
+}
+
+/// 取角色中配额最高的等级，没有匹配时使用默认等级
+fn resolve_tier(config: &SubscriptionQuotaConfig, roles: &[String]) -> (String, QuotaLimits) {
+    let default_limits = config.tiers.get(&config.default_tier).copied().unwrap_or(QuotaLimits {
+        max_symbols: 1,
+        messages_per_minute: 1,
+    });
+    roles
+        .iter()
+        .filter_map(|role| config.tiers.get(role).map(|limits| (role.clone(), *limits)))
+        .max_by_key(|(_, limits)| (limits.max_symbols, limits.messages_per_minute))
+        .unwrap_or_else(|| (config.default_tier.clone(), default_limits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_quota_tiers_and_limits() {
+        let config = SubscriptionQuotaConfig::default();
+
+        let mut headers = HeaderMap::new();
+        let free = ClientQuota::from_headers(&config, Uuid::new_v4(), &headers);
+        assert_eq!(free.tier(), "free");
+
+        headers.insert("x-user-roles", HeaderValue::from_static(r#"["trader","premium"]"#));
+        let mut quota = ClientQuota::from_headers(&config, Uuid::new_v4(), &headers);
+        assert_eq!(quota.tier(), "premium");
+
+        // 交易对配额，未指定交易对视为订阅全部
+        let mut subscriptions = SubscriptionSet::default();
+        subscriptions.subscribe(&[], &[], &["tick".to_string()], None);
+        assert!(matches!(
+            quota.check_subscriptions(&subscriptions),
+            Err(QuotaViolation::TooManySymbols { requested: None, .. })
+        ));
+        let symbols: Vec<String> = (0..51).map(|i| format!("SYM{}USDT", i)).collect();
+        subscriptions.subscribe(&[], &symbols[..50], &[], None);
+        assert!(quota.check_subscriptions(&subscriptions).is_ok());
+        subscriptions.subscribe(&[], &symbols[50..], &[], None);
+        assert!(quota.check_subscriptions(&subscriptions).is_err());
+
+        // 消息配额：超限只通知一次，下一分钟恢复
+        let mut quota = ClientQuota::new(
+            Uuid::new_v4(),
+            None,
+            "free".to_string(),
+            Some(QuotaLimits {
+                max_symbols: 1,
+                messages_per_minute: 2,
+            }),
+        );
+        let start = quota.window_start;
+        assert!(quota.acquire_message(start).is_ok());
+        assert!(quota.acquire_message(start + 1).is_ok());
+        assert!(matches!(quota.acquire_message(start + 2), Err(Some(_))));
+        assert_eq!(quota.acquire_message(start + 3), Err(None));
+        assert!(quota.acquire_message(start + MESSAGE_WINDOW_MS).is_ok());
+
+        quota.record_sent(100);
+        let usage = quota.take_usage(start + MESSAGE_WINDOW_MS);
+        assert_eq!((usage.messages, usage.bytes, usage.throttled), (1, 100, 2));
+        assert_eq!(quota.take_usage(start + MESSAGE_WINDOW_MS).messages, 0);
+    }
+}
@@ -0,0 +1,323 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::{EventFilter, WebSocketEvent};
+use crate::config::RedisConfig;
+
+/// 客户端订阅集合
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubscriptionSet {
+    #[serde(default)]
+    pub exchanges: BTreeSet<String>,
+    #[serde(default)]
+    pub symbols: BTreeSet<String>,
+    #[serde(default)]
+    pub event_types: BTreeSet<String>,
+    pub min_notional: Option<Decimal>,
+}
+
+impl SubscriptionSet {
+    /// 合并订阅
+    pub fn subscribe(
+        &mut self,
+        exchanges: &[String],
+        symbols: &[String],
+        event_types: &[String],
+        min_notional: Option<Decimal>,
+    ) {
+        self.exchanges
+            .extend(exchanges.iter().map(|e| e.to_lowercase()));
+        self.symbols.extend(symbols.iter().map(|s| s.to_uppercase()));
+        self.event_types
+            .extend(event_types.iter().map(|t| t.to_lowercase()));
+        if min_notional.is_some() {
+            self.min_notional = min_notional;
+        }
+    }
+
+    /// 取消订阅
+    pub fn unsubscribe(&mut self, exchanges: &[String], symbols: &[String], event_types: &[String]) {
+        for exchange in exchanges {
+            self.exchanges.remove(&exchange.to_lowercase());
+        }
+        for symbol in symbols {
+            self.symbols.remove(&symbol.to_uppercase());
+        }
+        for event_type in event_types {
+            self.event_types.remove(&event_type.to_lowercase());
+        }
+    }
+
+    /// 未订阅任何交易对或事件类型
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty() && self.event_types.is_empty()
+    }
+
+    /// 转换为事件过滤器，空集合表示不限制该维度
+    pub fn to_filter(&self) -> EventFilter {
+        fn to_list(set: &BTreeSet<String>) -> Option<Vec<String>> {
+            if set.is_empty() {
+                None
+            } else {
+                Some(set.iter().cloned().collect())
+            }
+        }
+
+        EventFilter {
+            exchanges: to_list(&self.exchanges),
+            symbols: to_list(&self.symbols),
+            event_types: to_list(&self.event_types),
+            min_notional: self.min_notional,
+        }
+    }
+
+    /// 事件是否需要推送给该会话
+    pub fn matches(&self, event: &WebSocketEvent) -> bool {
+        !self.is_empty() && self.to_filter().matches(event)
+    }
+
+    /// 订阅描述，用于诊断接口展示
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .exchanges
+            .iter()
+            .map(|e| format!("exchange:{}", e))
+            .chain(self.symbols.iter().map(|s| format!("symbol:{}", s)))
+            .chain(self.event_types.iter().map(|t| format!("type:{}", t)))
+            .collect();
+        if let Some(min_notional) = self.min_notional {
+            labels.push(format!("min_notional:{}", min_notional));
+        }
+        labels
+    }
+}
+
+/// 可恢复的WebSocket会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamSession {
+    pub token: String,
+    pub subscriptions: SubscriptionSet,
+    /// 最后一条已推送事件的序号
+    pub last_sequence: u64,
+    /// 序号属于延迟行情广播器
+    #[serde(default)]
+    pub delayed: bool,
+    /// 分配序号的广播器实例，见 [`WebSocketBroadcaster::epoch`](super::WebSocketBroadcaster::epoch)
+    #[serde(default)]
+    pub epoch: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl StreamSession {
+    pub fn new(last_sequence: u64) -> Self {
+        let now = Utc::now();
+        Self {
+            token: Uuid::new_v4().simple().to_string(),
+            subscriptions: SubscriptionSet::default(),
+            last_sequence,
+            delayed: false,
+            epoch: String::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// 会话的续传位置是否属于当前广播器
+    ///
+    /// 序号由广播器在进程内分配，服务重启或会话换到其他实例后从1重新计数，
+    /// 旧序号可能大于最新序号，也可能落在新的序号范围内指向不相关的事件。
+    pub fn is_resumable(&self, epoch: &str, head: u64) -> bool {
+        self.epoch == epoch && self.last_sequence <= head
+    }
+}
+
+/// 会话存储
+///
+/// 优先保存在Redis中以便跨实例恢复，Redis不可用时降级为进程内存储。
+#[derive(Clone)]
+pub struct SessionStore {
+    key_prefix: String,
+    ttl_seconds: u64,
+    redis: Option<Arc<RwLock<ConnectionManager>>>,
+    local: Arc<RwLock<HashMap<String, StreamSession>>>,
+}
+
+impl SessionStore {
+    pub async fn new(ttl_seconds: u64, redis_config: Option<&RedisConfig>) -> Self {
+        let key_prefix = redis_config
+            .map(|c| c.key_prefix.clone())
+            .unwrap_or_else(|| "market_data:".to_string());
+
+        let redis = match redis_config {
+            Some(redis_config) => match Self::connect(&redis_config.url).await {
+                Ok(conn) => Some(Arc::new(RwLock::new(conn))),
+                Err(e) => {
+                    warn!("WebSocket sessions kept in memory, failed to connect to Redis: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Self {
+            key_prefix,
+            ttl_seconds,
+            redis,
+            local: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 创建仅使用内存的会话存储
+    pub fn in_memory(ttl_seconds: u64) -> Self {
+        Self {
+            key_prefix: "market_data:".to_string(),
+            ttl_seconds,
+            redis: None,
+            local: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    async fn connect(url: &str) -> Result<ConnectionManager> {
+        let client = redis::Client::open(url)?;
+        Ok(ConnectionManager::new(client).await?)
+    }
+
+    fn key(&self, token: &str) -> String {
+        format!("{}ws:session:{}", self.key_prefix, token)
+    }
+
+    /// 加载会话，过期或不存在时返回None
+    pub async fn load(&self, token: &str) -> Option<StreamSession> {
+        use redis::AsyncCommands;
+
+        if let Some(redis) = &self.redis {
+            let key = self.key(token);
+            let mut conn = redis.write().await;
+            return match conn.get::<_, Option<String>>(&key).await {
+                Ok(Some(json)) => serde_json::from_str(&json).ok(),
+                Ok(None) => None,
+                Err(e) => {
+                    warn!("Failed to load WebSocket session {}: {}", token, e);
+                    None
+                }
+            };
+        }
+
+        let local = self.local.read().await;
+        local.get(token).cloned().filter(|session| {
+            (Utc::now() - session.updated_at).num_seconds() < self.ttl_seconds as i64
+        })
+    }
+
+    /// 保存会话并刷新过期时间
+    pub async fn save(&self, session: &StreamSession) {
+        use redis::AsyncCommands;
+
+        let mut session = session.clone();
+        session.updated_at = Utc::now();
+
+        if let Some(redis) = &self.redis {
+            let json = match serde_json::to_string(&session) {
+                Ok(json) => json,
+                Err(e) => {
+                    warn!("Failed to serialize WebSocket session {}: {}", session.token, e);
+                    return;
+                }
+            };
+            let key = self.key(&session.token);
+            let mut conn = redis.write().await;
+            if let Err(e) = conn.set_ex::<_, _, ()>(&key, json, self.ttl_seconds).await {
+                warn!("Failed to save WebSocket session {}: {}", session.token, e);
+            } else {
+                debug!("Saved WebSocket session {} at {}", session.token, session.last_sequence);
+            }
+            return;
+        }
+
+        let mut local = self.local.write().await;
+        let ttl = self.ttl_seconds as i64;
+        local.retain(|_, s| (Utc::now() - s.updated_at).num_seconds() < ttl);
+        local.insert(session.token.clone(), session);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_models::common::Exchange;
+    use shared_models::market::Trade;
+
+    fn trade_event(symbol: &str) -> WebSocketEvent {
+        WebSocketEvent::Trade(Trade {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: symbol.to_string(),
+            trade_id: "1".to_string(),
+            timestamp: Utc::now(),
+            price: Decimal::from(100),
+            quantity: Decimal::from(1),
+            quote_quantity: Decimal::from(100),
+            side: "buy".to_string(),
+            is_buyer_maker: false,
+            is_best_match: true,
+        })
+    }
+
+    #[test]
+    fn test_subscription_set() {
+        let mut set = SubscriptionSet::default();
+        assert!(!set.matches(&trade_event("BTCUSDT")));
+
+        set.subscribe(&[], &["btcusdt".to_string(), "ethusdt".to_string()], &[], None);
+        assert!(set.matches(&trade_event("BTCUSDT")));
+
+        set.unsubscribe(&[], &["BTCUSDT".to_string()], &[]);
+        assert!(!set.matches(&trade_event("BTCUSDT")));
+        assert!(set.matches(&trade_event("ETHUSDT")));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_session_store() {
+        let store = SessionStore::in_memory(60);
+        let mut session = StreamSession::new(10);
+        session
+            .subscriptions
+            .subscribe(&[], &["BTCUSDT".to_string()], &["trade".to_string()], None);
+        store.save(&session).await;
+
+        let loaded = store.load(&session.token).await.unwrap();
+        assert_eq!(loaded.last_sequence, 10);
+        assert!(loaded.subscriptions.symbols.contains("BTCUSDT"));
+        assert!(store.load("missing").await.is_none());
+    }
+
+    #[test]
+    fn test_session_resumable() {
+        let mut session = StreamSession::new(10);
+        session.epoch = "a".to_string();
+        assert!(session.is_resumable("a", 10));
+        // 广播器重启后序号重新计数
+        assert!(!session.is_resumable("a", 3));
+        assert!(!session.is_resumable("b", 100));
+
+        // 升级前保存的会话没有广播器实例
+        let legacy: StreamSession = serde_json::from_value(serde_json::json!({
+            "token": "t",
+            "subscriptions": SubscriptionSet::default(),
+            "last_sequence": 5,
+            "created_at": Utc::now(),
+            "updated_at": Utc::now()
+        }))
+        .unwrap();
+        assert!(!legacy.is_resumable("a", 100));
+    }
+}
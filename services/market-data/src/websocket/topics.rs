@@ -0,0 +1,446 @@
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast::error::RecvError, Notify};
+
+use super::{EventFilter, SequencedEvent, WebSocketEvent};
+use crate::config::{ConflationConfig, ConflationMode};
+
+/// 订阅主题，未指定交易所时匹配所有交易所的该交易对
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TopicKey {
+    exchange: Option<String>,
+    symbol: String,
+}
+
+impl TopicKey {
+    fn new(exchange: Option<&str>, symbol: &str) -> Self {
+        Self {
+            exchange: exchange.map(|e| e.to_lowercase()),
+            symbol: symbol.to_uppercase(),
+        }
+    }
+}
+
+/// 合并策略
+#[derive(Debug, Clone, Default)]
+pub struct ConflationPolicy {
+    /// 待发送队列达到该长度后开始合并，0 表示不合并
+    threshold: usize,
+    /// 保留最新一条的事件类型
+    latest: HashSet<String>,
+}
+
+impl ConflationPolicy {
+    pub fn from_config(config: &ConflationConfig) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+        Self {
+            threshold: config.queue_threshold,
+            latest: config
+                .policies
+                .iter()
+                .filter(|(_, mode)| **mode == ConflationMode::Latest)
+                .map(|(event_type, _)| event_type.clone())
+                .collect(),
+        }
+    }
+
+    fn conflates(&self, event: &WebSocketEvent, queued: usize) -> bool {
+        self.threshold > 0
+            && queued >= self.threshold
+            && event.symbol().is_some()
+            && self.latest.contains(event.event_type())
+    }
+}
+
+/// 同一类型、交易所和交易对的事件可以互相替换
+fn same_stream(a: &WebSocketEvent, b: &WebSocketEvent) -> bool {
+    a.event_type() == b.event_type() && a.exchange() == b.exchange() && a.symbol() == b.symbol()
+}
+
+/// 订阅者的待发送队列
+struct Mailbox {
+    capacity: usize,
+    queue: Mutex<VecDeque<SequencedEvent>>,
+    notify: Notify,
+    /// 队列已满被丢弃的事件数，接收端下次读取时报告
+    dropped: AtomicU64,
+    /// 被更新数据替换的事件数
+    conflated: AtomicU64,
+}
+
+impl Mailbox {
+    /// 投递事件，队列满时丢弃
+    ///
+    /// 队列超过合并阈值时，移除同一数据流尚未发送的旧事件再追加新事件，
+    /// 队列中的序号始终保持递增。
+    fn deliver(&self, event: &SequencedEvent, policy: &ConflationPolicy) -> Delivery {
+        let mut queue = self.queue.lock();
+        let mut delivery = Delivery::Queued;
+        if policy.conflates(&event.event, queue.len()) {
+            if let Some(index) = queue.iter().rposition(|queued| same_stream(&queued.event, &event.event)) {
+                queue.remove(index);
+                self.conflated.fetch_add(1, Ordering::Relaxed);
+                delivery = Delivery::Conflated;
+            }
+        }
+        if queue.len() >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Delivery::Dropped;
+        }
+        queue.push_back(event.clone());
+        drop(queue);
+        self.notify.notify_one();
+        delivery
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delivery {
+    Queued,
+    /// 替换了队列中的旧事件
+    Conflated,
+    Dropped,
+}
+
+#[derive(Default)]
+struct Routes {
+    /// 主题 -> 订阅者ID
+    topics: HashMap<TopicKey, HashSet<u64>>,
+    /// 未限定交易对、需要接收全部事件的订阅者
+    wildcard: HashSet<u64>,
+    /// 订阅者ID -> 待发送队列和已注册主题
+    subscribers: HashMap<u64, (Arc<Mailbox>, Vec<TopicKey>)>,
+}
+
+impl Routes {
+    fn unroute(&mut self, id: u64) {
+        self.wildcard.remove(&id);
+        let Some((_, keys)) = self.subscribers.get_mut(&id) else {
+            return;
+        };
+        for key in std::mem::take(keys) {
+            if let Some(ids) = self.topics.get_mut(&key) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.topics.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// 按主题路由统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TopicRouterStats {
+    pub subscribers: usize,
+    /// 有订阅者的 (交易所, 交易对) 主题数
+    pub topics: usize,
+    /// 接收全部事件的订阅者数
+    pub wildcard_subscribers: usize,
+    /// 慢消费者被合并替换的事件总数
+    pub conflated_events: u64,
+}
+
+/// 按 (交易所, 交易对) 分片的订阅路由
+///
+/// 每个客户端只有一个有界队列，按订阅的主题注册；发布事件时只投递给该主题的订阅者，
+/// 订阅 BTCUSDT 的客户端不会被其他交易对的事件唤醒。没有交易对的事件
+/// （连接状态、错误、心跳）投递给所有订阅者。慢消费者的队列超过阈值后按
+/// [`ConflationPolicy`] 合并行情更新，队列满时丢弃并在接收端报告滞后。
+pub struct TopicRouter {
+    capacity: usize,
+    conflation: ConflationPolicy,
+    next_id: AtomicU64,
+    conflated: AtomicU64,
+    routes: RwLock<Routes>,
+}
+
+impl TopicRouter {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_conflation(capacity, ConflationPolicy::default())
+    }
+
+    pub fn with_conflation(capacity: usize, conflation: ConflationPolicy) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            conflation,
+            next_id: AtomicU64::new(1),
+            conflated: AtomicU64::new(0),
+            routes: RwLock::new(Routes::default()),
+        }
+    }
+
+    /// 注册订阅者，初始不订阅任何主题
+    pub fn subscribe(self: &Arc<Self>) -> TopicSubscription {
+        let mailbox = Arc::new(Mailbox {
+            capacity: self.capacity,
+            queue: Mutex::new(VecDeque::new()),
+            notify: Notify::new(),
+            dropped: AtomicU64::new(0),
+            conflated: AtomicU64::new(0),
+        });
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.routes
+            .write()
+            .subscribers
+            .insert(id, (mailbox.clone(), Vec::new()));
+
+        TopicSubscription {
+            id,
+            router: self.clone(),
+            mailbox,
+        }
+    }
+
+    /// 按过滤条件重新注册订阅者的主题
+    fn route(&self, id: u64, filter: &EventFilter) {
+        let keys: Option<Vec<TopicKey>> = filter.symbols.as_ref().map(|symbols| match &filter.exchanges {
+            Some(exchanges) => exchanges
+                .iter()
+                .flat_map(|exchange| symbols.iter().map(move |symbol| TopicKey::new(Some(exchange), symbol)))
+                .collect(),
+            None => symbols.iter().map(|symbol| TopicKey::new(None, symbol)).collect(),
+        });
+
+        let mut routes = self.routes.write();
+        routes.unroute(id);
+        if !routes.subscribers.contains_key(&id) {
+            return;
+        }
+        match keys {
+            Some(keys) => {
+                for key in &keys {
+                    routes.topics.entry(key.clone()).or_default().insert(id);
+                }
+                if let Some((_, registered)) = routes.subscribers.get_mut(&id) {
+                    *registered = keys;
+                }
+            }
+            None => {
+                routes.wildcard.insert(id);
+            }
+        }
+    }
+
+    fn clear(&self, id: u64) {
+        self.routes.write().unroute(id);
+    }
+
+    fn remove(&self, id: u64) {
+        let mut routes = self.routes.write();
+        routes.unroute(id);
+        routes.subscribers.remove(&id);
+    }
+
+    /// 投递事件，返回投递成功的订阅者数
+    pub fn publish(&self, event: &SequencedEvent) -> usize {
+        let routes = self.routes.read();
+        let Some(symbol) = event.event.symbol() else {
+            return routes
+                .subscribers
+                .iter()
+                .filter(|(id, (_, keys))| !keys.is_empty() || routes.wildcard.contains(id))
+                .filter(|(_, (mailbox, _))| self.deliver(mailbox, event))
+                .count();
+        };
+
+        let exact = TopicKey::new(event.event.exchange(), symbol);
+        let any_exchange = TopicKey::new(None, symbol);
+        let targets = routes
+            .topics
+            .get(&exact)
+            .into_iter()
+            .chain(routes.topics.get(&any_exchange))
+            .flatten()
+            .chain(routes.wildcard.iter());
+
+        let mut delivered = 0;
+        for id in targets {
+            if let Some((mailbox, _)) = routes.subscribers.get(id) {
+                if self.deliver(mailbox, event) {
+                    delivered += 1;
+                }
+            }
+        }
+        delivered
+    }
+
+    fn deliver(&self, mailbox: &Mailbox, event: &SequencedEvent) -> bool {
+        match mailbox.deliver(event, &self.conflation) {
+            Delivery::Queued => true,
+            Delivery::Conflated => {
+                self.conflated.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Delivery::Dropped => false,
+        }
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.routes.read().subscribers.len()
+    }
+
+    pub fn stats(&self) -> TopicRouterStats {
+        let routes = self.routes.read();
+        TopicRouterStats {
+            subscribers: routes.subscribers.len(),
+            topics: routes.topics.len(),
+            wildcard_subscribers: routes.wildcard.len(),
+            conflated_events: self.conflated.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 客户端的主题订阅，释放时自动注销
+pub struct TopicSubscription {
+    id: u64,
+    router: Arc<TopicRouter>,
+    mailbox: Arc<Mailbox>,
+}
+
+impl TopicSubscription {
+    /// 按过滤条件订阅主题，替换之前的订阅
+    pub fn route(&self, filter: &EventFilter) {
+        self.router.route(self.id, filter);
+    }
+
+    /// 取消所有主题
+    pub fn clear(&self) {
+        self.router.clear(self.id);
+    }
+
+    /// 接收下一条事件；此前有事件因队列满被丢弃时先返回 `Lagged`
+    pub async fn recv(&mut self) -> Result<SequencedEvent, RecvError> {
+        loop {
+            let dropped = self.mailbox.dropped.swap(0, Ordering::Relaxed);
+            if dropped > 0 {
+                return Err(RecvError::Lagged(dropped));
+            }
+            if let Some(event) = self.mailbox.queue.lock().pop_front() {
+                return Ok(event);
+            }
+            self.mailbox.notify.notified().await;
+        }
+    }
+
+    /// 取出上次读取以来被合并替换的事件数
+    pub fn take_conflated(&self) -> u64 {
+        self.mailbox.conflated.swap(0, Ordering::Relaxed)
+    }
+}
+
+impl Drop for TopicSubscription {
+    fn drop(&mut self) {
+        self.router.remove(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use shared_models::market::MarketTick;
+    use shared_models::Timestamp;
+
+    fn tick(id: u64, exchange: &str, symbol: &str) -> SequencedEvent {
+        SequencedEvent {
+            id,
+            event: WebSocketEvent::Tick(MarketTick {
+                exchange: exchange.to_string(),
+                symbol: symbol.to_string(),
+                timestamp: 1640995200000,
+                price: Decimal::ONE,
+                volume: Decimal::ONE,
+                bid: Decimal::ONE,
+                ask: Decimal::ONE,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_topic() {
+        let router = Arc::new(TopicRouter::new(16));
+        let mut btc = router.subscribe();
+        btc.route(&EventFilter::symbols(vec!["btcusdt".to_string()]));
+        let mut binance_eth = router.subscribe();
+        binance_eth.route(&EventFilter {
+            exchanges: Some(vec!["binance".to_string()]),
+            ..EventFilter::symbols(vec!["ETHUSDT".to_string()])
+        });
+        let mut all = router.subscribe();
+        all.route(&EventFilter::allow_all());
+        let idle = router.subscribe();
+
+        assert_eq!(router.publish(&tick(1, "binance", "BTCUSDT")), 2);
+        assert_eq!(router.publish(&tick(2, "okx", "ETHUSDT")), 1);
+        assert_eq!(router.publish(&tick(3, "binance", "ETHUSDT")), 2);
+        assert_eq!(router.publish(&tick(4, "binance", "SOLUSDT")), 1);
+
+        assert_eq!(btc.recv().await.unwrap().id, 1);
+        assert_eq!(binance_eth.recv().await.unwrap().id, 3);
+        assert_eq!(all.recv().await.unwrap().id, 1);
+        assert_eq!(router.stats().topics, 2);
+
+        // 没有交易对的事件投递给所有已订阅的客户端
+        let heartbeat = SequencedEvent {
+            id: 5,
+            event: WebSocketEvent::Heartbeat {
+                timestamp: Timestamp::from_millis(0),
+            },
+        };
+        assert_eq!(router.publish(&heartbeat), 3);
+
+        btc.clear();
+        assert_eq!(router.publish(&tick(6, "binance", "BTCUSDT")), 1);
+        drop(idle);
+        drop(all);
+        assert_eq!(router.subscriber_count(), 2);
+        assert_eq!(router.publish(&tick(7, "okx", "SOLUSDT")), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reports_lag_when_full() {
+        let router = Arc::new(TopicRouter::new(1));
+        let mut subscription = router.subscribe();
+        subscription.route(&EventFilter::symbols(vec!["BTCUSDT".to_string()]));
+
+        router.publish(&tick(1, "binance", "BTCUSDT"));
+        router.publish(&tick(2, "binance", "BTCUSDT"));
+        assert!(matches!(subscription.recv().await, Err(RecvError::Lagged(1))));
+        assert_eq!(subscription.recv().await.unwrap().id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_conflates_slow_consumer() {
+        let policy = ConflationPolicy::from_config(&ConflationConfig {
+            enabled: true,
+            queue_threshold: 2,
+            ..ConflationConfig::default()
+        });
+        let router = Arc::new(TopicRouter::with_conflation(10, policy));
+        let mut subscription = router.subscribe();
+        subscription.route(&EventFilter::allow_all());
+
+        router.publish(&tick(1, "binance", "BTCUSDT"));
+        router.publish(&tick(2, "binance", "ETHUSDT"));
+        // 队列达到阈值，BTCUSDT 的旧数据被替换
+        router.publish(&tick(3, "binance", "BTCUSDT"));
+        // 不同交易所是不同的数据流
+        router.publish(&tick(4, "okx", "BTCUSDT"));
+        router.publish(&tick(5, "binance", "ETHUSDT"));
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(subscription.recv().await.unwrap().id);
+        }
+        assert_eq!(ids, vec![3, 4, 5]);
+        assert_eq!(subscription.take_conflated(), 2);
+        assert_eq!(subscription.take_conflated(), 0);
+        assert_eq!(router.stats().conflated_events, 2);
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use redis::aio::ConnectionManager;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// 成本在Redis中按百万分之一计数
+const COST_SCALE: i64 = 1_000_000;
+/// 计数键保留两天，跨时区查询前一天用量时仍可读取
+const COUNTER_TTL_SECONDS: usize = 2 * 24 * 3600;
+
+/// 单个模型的每日预算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelBudget {
+    /// 每日token上限，None表示不限
+    pub max_tokens_per_day: Option<u64>,
+    /// 每日费用上限（美元），None表示不限
+    pub max_cost_per_day: Option<Decimal>,
+    /// 每千个输入token价格
+    pub prompt_price_per_1k: Decimal,
+    /// 每千个输出token价格
+    pub completion_price_per_1k: Decimal,
+}
+
+impl Default for ModelBudget {
+    fn default() -> Self {
+        Self {
+            max_tokens_per_day: Some(2_000_000),
+            max_cost_per_day: Some(Decimal::from(20)),
+            prompt_price_per_1k: Decimal::new(14, 5),     // 0.00014
+            completion_price_per_1k: Decimal::new(28, 5), // 0.00028
+        }
+    }
+}
+
+impl ModelBudget {
+    /// 计算一次调用的费用
+    pub fn cost(&self, usage: &TokenUsage) -> Decimal {
+        Decimal::from(usage.prompt_tokens) * self.prompt_price_per_1k / Decimal::from(1000)
+            + Decimal::from(usage.completion_tokens) * self.completion_price_per_1k / Decimal::from(1000)
+    }
+
+    /// 超出预算时返回原因
+    pub fn exceeded(&self, tokens: u64, cost: Decimal) -> Option<String> {
+        if let Some(max_tokens) = self.max_tokens_per_day {
+            if tokens >= max_tokens {
+                return Some(format!("daily token limit {} reached ({} used)", max_tokens, tokens));
+            }
+        }
+        if let Some(max_cost) = self.max_cost_per_day {
+            if cost >= max_cost {
+                return Some(format!("daily cost limit {} reached ({} used)", max_cost, cost));
+            }
+        }
+        None
+    }
+}
+
+/// AI调用预算配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AIBudgetConfig {
+    /// 未单独配置的模型使用的预算
+    #[serde(default)]
+    pub default: ModelBudget,
+    /// 按模型名称配置的预算
+    #[serde(default)]
+    pub models: HashMap<String, ModelBudget>,
+}
+
+impl AIBudgetConfig {
+    pub fn budget_for(&self, model: &str) -> &ModelBudget {
+        self.models.get(model).unwrap_or(&self.default)
+    }
+}
+
+/// 一次调用的token用量
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens as u64 + self.completion_tokens as u64
+    }
+}
+
+/// 预算耗尽
+#[derive(Debug, Error)]
+#[error("AI budget exhausted for {model}: {reason}")]
+pub struct BudgetExhausted {
+    pub model: String,
+    pub reason: String,
+}
+
+/// 模型当日用量
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetUsage {
+    pub model: String,
+    pub date: NaiveDate,
+    pub tokens: u64,
+    pub cost: Decimal,
+    pub max_tokens_per_day: Option<u64>,
+    pub max_cost_per_day: Option<Decimal>,
+    pub exhausted: bool,
+}
+
+/// AI调用预算管理
+///
+/// 按模型、按UTC日期在Redis中累计token和费用，多个实例共享同一份计数。
+/// 预算耗尽后在本地熔断到当日结束，期间不再访问Redis。
+#[derive(Clone)]
+pub struct AIBudgetManager {
+    config: AIBudgetConfig,
+    redis: ConnectionManager,
+    key_prefix: String,
+    /// model -> 熔断日期
+    tripped: Arc<RwLock<HashMap<String, NaiveDate>>>,
+}
+
+impl AIBudgetManager {
+    pub fn new(config: AIBudgetConfig, redis: ConnectionManager) -> Self {
+        Self {
+            config,
+            redis,
+            key_prefix: "ai:budget".to_string(),
+            tripped: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn keys(&self, model: &str, date: NaiveDate) -> (String, String) {
+        let base = format!("{}:{}:{}", self.key_prefix, model, date.format("%Y%m%d"));
+        (format!("{}:tokens", base), format!("{}:cost", base))
+    }
+
+    /// 熔断是否打开
+    pub async fn is_exhausted(&self, model: &str) -> bool {
+        let today = Utc::now().date_naive();
+        self.tripped.read().await.get(model) == Some(&today)
+    }
+
+    async fn trip(&self, model: &str, date: NaiveDate, reason: &str) {
+        let mut tripped = self.tripped.write().await;
+        if tripped.insert(model.to_string(), date) != Some(date) {
+            warn!("AI budget exhausted for {}: {}", model, reason);
+        }
+    }
+
+    /// 调用前检查预算，Redis不可用时放行
+    pub async fn check(&self, model: &str) -> Result<(), BudgetExhausted> {
+        if self.is_exhausted(model).await {
+            return Err(BudgetExhausted {
+                model: model.to_string(),
+                reason: "circuit open until end of day".to_string(),
+            });
+        }
+
+        let usage = match self.usage(model).await {
+            Ok(usage) => usage,
+            Err(e) => {
+                warn!("Failed to read AI budget for {}: {}", model, e);
+                return Ok(());
+            }
+        };
+        match self.config.budget_for(model).exceeded(usage.tokens, usage.cost) {
+            Some(reason) => {
+                self.trip(model, usage.date, &reason).await;
+                Err(BudgetExhausted {
+                    model: model.to_string(),
+                    reason,
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// 记录一次调用的用量
+    pub async fn record(&self, model: &str, usage: &TokenUsage) -> Result<BudgetUsage> {
+        let budget = self.config.budget_for(model);
+        let today = Utc::now().date_naive();
+        let (tokens_key, cost_key) = self.keys(model, today);
+        let cost_micros = (budget.cost(usage) * Decimal::from(COST_SCALE))
+            .round()
+            .to_i64()
+            .unwrap_or(i64::MAX);
+
+        let mut conn = self.redis.clone();
+        let (tokens, cost_micros): (u64, i64) = redis::pipe()
+            .atomic()
+            .incr(&tokens_key, usage.total())
+            .expire(&tokens_key, COUNTER_TTL_SECONDS)
+            .ignore()
+            .incr(&cost_key, cost_micros)
+            .expire(&cost_key, COUNTER_TTL_SECONDS)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        let cost = Decimal::new(cost_micros, 6);
+        let exceeded = budget.exceeded(tokens, cost);
+        if let Some(reason) = &exceeded {
+            self.trip(model, today, reason).await;
+        }
+
+        Ok(BudgetUsage {
+            model: model.to_string(),
+            date: today,
+            tokens,
+            cost,
+            max_tokens_per_day: budget.max_tokens_per_day,
+            max_cost_per_day: budget.max_cost_per_day,
+            exhausted: exceeded.is_some(),
+        })
+    }
+
+    /// 查询模型当日用量
+    pub async fn usage(&self, model: &str) -> Result<BudgetUsage> {
+        let budget = self.config.budget_for(model);
+        let today = Utc::now().date_naive();
+        let (tokens_key, cost_key) = self.keys(model, today);
+
+        let mut conn = self.redis.clone();
+        let (tokens, cost_micros): (Option<u64>, Option<i64>) = redis::pipe()
+            .get(&tokens_key)
+            .get(&cost_key)
+            .query_async(&mut conn)
+            .await?;
+
+        let tokens = tokens.unwrap_or(0);
+        let cost = Decimal::new(cost_micros.unwrap_or(0), 6);
+        Ok(BudgetUsage {
+            model: model.to_string(),
+            date: today,
+            tokens,
+            cost,
+            max_tokens_per_day: budget.max_tokens_per_day,
+            max_cost_per_day: budget.max_cost_per_day,
+            exhausted: budget.exceeded(tokens, cost).is_some(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_cost() {
+        let budget = ModelBudget {
+            max_tokens_per_day: None,
+            max_cost_per_day: None,
+            prompt_price_per_1k: Decimal::new(1, 3),
+            completion_price_per_1k: Decimal::new(2, 3),
+        };
+        let usage = TokenUsage {
+            prompt_tokens: 1500,
+            completion_tokens: 500,
+        };
+        assert_eq!(budget.cost(&usage), Decimal::new(25, 4));
+        assert_eq!(usage.total(), 2000);
+    }
+
+    #[test]
+    fn test_budget_limits() {
+        let budget = ModelBudget {
+            max_tokens_per_day: Some(1000),
+            max_cost_per_day: Some(Decimal::from(1)),
+            ..Default::default()
+        };
+        assert!(budget.exceeded(999, Decimal::new(99, 2)).is_none());
+        assert!(budget.exceeded(1000, Decimal::ZERO).is_some());
+        assert!(budget.exceeded(0, Decimal::from(1)).is_some());
+
+        let mut config = AIBudgetConfig::default();
+        config.models.insert("deepseek-chat".to_string(), budget);
+        assert_eq!(config.budget_for("deepseek-chat").max_tokens_per_day, Some(1000));
+        assert_eq!(config.budget_for("gpt-4").max_tokens_per_day, Some(2_000_000));
+    }
+}
@@ -0,0 +1,513 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use shared_models::market::{DepthMetrics, SeasonalityProfile};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use super::market_analyzer::{chart_window, interval_millis, MarketContextProvider};
+use super::strategy_generator::*;
+use crate::features::compute::{momentum, rsi, sma, volatility};
+use crate::models::Symbol;
+
+/// 市场上下文构建配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketContextConfig {
+    /// market-data服务地址
+    pub base_url: String,
+    pub exchange: String,
+    /// 最新K线落后当前时间超过多少个周期视为行情中断，拒绝构建上下文
+    pub max_candle_lag: i64,
+    /// 深度指标最长时效（毫秒），超过后不计入微观结构
+    pub max_depth_age_ms: i64,
+    /// 构建好的上下文缓存时长（毫秒），也是预热任务的刷新间隔
+    pub cache_ttl_ms: u64,
+    /// 单个请求超时（毫秒）
+    pub request_timeout_ms: u64,
+    /// 用于统计主动买卖量和成交频率的最近成交条数
+    pub trade_sample_size: usize,
+    /// 成交量分布的价格分桶数
+    pub volume_buckets: usize,
+}
+
+impl Default for MarketContextConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:8081".to_string(),
+            exchange: "binance".to_string(),
+            max_candle_lag: 2,
+            max_depth_age_ms: 10_000,
+            cache_ttl_ms: 5_000,
+            request_timeout_ms: 5_000,
+            trade_sample_size: 500,
+            volume_buckets: 24,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiEnvelope<T> {
+    data: Option<T>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartData {
+    candles: Vec<ChartCandle>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartCandle {
+    open_time: i64,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+    volume: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradeHistory {
+    trades: Vec<TradeSample>,
+}
+
+/// 成交历史中构建上下文用到的字段
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TradeSample {
+    timestamp: DateTime<Utc>,
+    quantity: Decimal,
+    side: String,
+}
+
+struct CachedContext {
+    context: MarketContext,
+    built_at: Instant,
+}
+
+/// AI分析用的市场上下文构建器
+///
+/// 并发拉取market-data服务的K线、深度指标、成交历史和日内季节性，计算技术指标、成交量分布
+/// 和微观结构指标。K线中断时拒绝构建，避免AI基于过期行情给出结论；深度指标和成交只作为
+/// 补充，获取失败或过期时忽略。构建结果短时缓存，预热任务按缓存时长定期刷新常用交易对。
+pub struct MarketContextBuilder {
+    config: MarketContextConfig,
+    base_url: String,
+    client: reqwest::Client,
+    cache: RwLock<HashMap<(String, &'static str), CachedContext>>,
+}
+
+impl MarketContextBuilder {
+    pub fn new(config: MarketContextConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()?;
+        Ok(Self {
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            config,
+            client,
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let envelope: ApiEnvelope<T> = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(query)
+            .send()
+            .await?
+            .json()
+            .await?;
+        envelope
+            .data
+            .ok_or_else(|| anyhow!("Request {} failed: {}", path, envelope.error.unwrap_or_default()))
+    }
+
+    async fn fetch_candles(&self, symbol: &Symbol, interval: &str, start_time: i64, end_time: i64) -> Result<Vec<PricePoint>> {
+        let chart: ChartData = self
+            .get(
+                &format!("/api/v1/chart/{}/{}", self.config.exchange, symbol),
+                &[
+                    ("interval", interval.to_string()),
+                    ("start_time", start_time.to_string()),
+                    ("end_time", end_time.to_string()),
+                ],
+            )
+            .await?;
+        Ok(chart
+            .candles
+            .into_iter()
+            .map(|c| PricePoint {
+                timestamp: c.open_time,
+                open: c.open,
+                high: c.high,
+                low: c.low,
+                close: c.close,
+                volume: c.volume,
+            })
+            .collect())
+    }
+
+    async fn fetch_depth_metrics(&self, symbol: &Symbol) -> Result<DepthMetrics> {
+        self.get(&format!("/api/v1/depth-metrics/{}/{}", self.config.exchange, symbol), &[])
+            .await
+    }
+
+    async fn fetch_trades(&self, symbol: &Symbol) -> Result<Vec<TradeSample>> {
+        let history: TradeHistory = self
+            .get(
+                &format!("/api/v1/trades/{}/{}/history", self.config.exchange, symbol),
+                &[("limit", self.config.trade_sample_size.to_string())],
+            )
+            .await?;
+        Ok(history.trades)
+    }
+
+    async fn fetch_seasonality(&self, symbol: &Symbol) -> Result<SeasonalityProfile> {
+        self.get(&format!("/api/v1/seasonality/{}/{}", self.config.exchange, symbol), &[])
+            .await
+    }
+
+    /// 拉取数据并构建上下文，不经过缓存
+    pub async fn build(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketContext> {
+        let (interval, count) = chart_window(horizon);
+        let interval_ms = interval_millis(interval);
+        let now = Utc::now().timestamp_millis();
+
+        let (candles, depth, trades, seasonality) = tokio::join!(
+            self.fetch_candles(symbol, interval, now - interval_ms * count, now),
+            self.fetch_depth_metrics(symbol),
+            self.fetch_trades(symbol),
+            self.fetch_seasonality(symbol),
+        );
+
+        let price_history = candles?;
+        ensure_fresh(symbol, &price_history, interval_ms * self.config.max_candle_lag, now)?;
+        let current_price = price_history
+            .last()
+            .map(|p| p.close)
+            .ok_or_else(|| anyhow!("No market data for {}", symbol))?;
+
+        let depth = depth
+            .map_err(|e| warn!("Depth metrics unavailable for {}: {}", symbol, e))
+            .ok()
+            .filter(|metrics| {
+                let age = now - metrics.timestamp.timestamp_millis();
+                if age > self.config.max_depth_age_ms {
+                    warn!("Ignoring depth metrics for {} that are {}ms old", symbol, age);
+                    return false;
+                }
+                true
+            });
+        let trades = trades.unwrap_or_else(|e| {
+            warn!("Trade history unavailable for {}: {}", symbol, e);
+            Vec::new()
+        });
+        // 季节性画像只用于辅助特征，获取失败时忽略
+        let seasonality = seasonality
+            .map_err(|e| debug!("Seasonality unavailable for {}: {}", symbol, e))
+            .ok();
+
+        Ok(MarketContext {
+            symbol: symbol.clone(),
+            current_price,
+            technical_indicators: compute_indicators(&price_history),
+            volume_profile: volume_profile(&price_history, &trades, self.config.volume_buckets),
+            price_history,
+            fundamental_data: None,
+            news_sentiment: None,
+            market_microstructure: microstructure(depth, &trades, seasonality),
+        })
+    }
+
+    /// 预热指定交易对和周期的上下文，返回成功构建的数量
+    pub async fn warm(self: &Arc<Self>, symbols: &[Symbol], horizons: &[TimeHorizon]) -> usize {
+        let mut tasks = tokio::task::JoinSet::new();
+        for symbol in symbols {
+            for horizon in horizons {
+                let builder = self.clone();
+                let symbol = symbol.clone();
+                let horizon = horizon.clone();
+                tasks.spawn(async move {
+                    let result = builder.refresh(&symbol, &horizon).await;
+                    if let Err(e) = &result {
+                        warn!("Failed to warm market context for {} {:?}: {}", symbol, horizon, e);
+                    }
+                    result.is_ok()
+                });
+            }
+        }
+
+        let mut warmed = 0;
+        while let Some(result) = tasks.join_next().await {
+            if matches!(result, Ok(true)) {
+                warmed += 1;
+            }
+        }
+        warmed
+    }
+
+    /// 启动预热任务，按缓存时长定期刷新，使AI调用时直接命中缓存
+    pub fn start(self: Arc<Self>, symbols: Vec<Symbol>, horizons: Vec<TimeHorizon>) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(self.config.cache_ttl_ms.max(1)));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let warmed = self.warm(&symbols, &horizons).await;
+                debug!("Warmed {} market contexts", warmed);
+            }
+        });
+    }
+
+    async fn refresh(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketContext> {
+        let context = self.build(symbol, horizon).await?;
+        let (interval, _) = chart_window(horizon);
+        self.cache.write().await.insert(
+            (symbol.to_string(), interval),
+            CachedContext {
+                context: context.clone(),
+                built_at: Instant::now(),
+            },
+        );
+        Ok(context)
+    }
+}
+
+#[async_trait]
+impl MarketContextProvider for MarketContextBuilder {
+    async fn market_context(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketContext> {
+        let (interval, _) = chart_window(horizon);
+        let ttl = Duration::from_millis(self.config.cache_ttl_ms);
+        if let Some(cached) = self.cache.read().await.get(&(symbol.to_string(), interval)) {
+            if cached.built_at.elapsed() < ttl {
+                return Ok(cached.context.clone());
+            }
+        }
+        self.refresh(symbol, horizon).await
+    }
+}
+
+/// 最新K线落后超过 max_lag_ms 时视为行情中断
+pub(crate) fn ensure_fresh(symbol: &Symbol, history: &[PricePoint], max_lag_ms: i64, now: i64) -> Result<()> {
+    let last = history
+        .last()
+        .ok_or_else(|| anyhow!("No market data for {}", symbol))?;
+    let lag = now - last.timestamp;
+    if lag > max_lag_ms {
+        return Err(anyhow!(
+            "Market data for {} is stale: last candle {}ms ago exceeds {}ms",
+            symbol,
+            lag,
+            max_lag_ms
+        ));
+    }
+    Ok(())
+}
+
+fn ema(values: &[f64], period: usize) -> Option<f64> {
+    if values.len() < period || period == 0 {
+        return None;
+    }
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let seed = values[..period].iter().sum::<f64>() / period as f64;
+    Some(values[period..].iter().fold(seed, |ema, v| ema + alpha * (v - ema)))
+}
+
+/// 平均真实波幅
+fn atr(history: &[PricePoint], period: usize) -> Option<f64> {
+    if history.len() <= period || period == 0 {
+        return None;
+    }
+    let total: f64 = history[history.len() - period - 1..]
+        .windows(2)
+        .filter_map(|w| {
+            let (high, low, prev_close) = (w[1].high.to_f64()?, w[1].low.to_f64()?, w[0].close.to_f64()?);
+            Some((high - low).max((high - prev_close).abs()).max((low - prev_close).abs()))
+        })
+        .sum();
+    Some(total / period as f64)
+}
+
+/// 由K线计算技术指标，数据不足的指标不输出
+pub(crate) fn compute_indicators(history: &[PricePoint]) -> HashMap<String, Decimal> {
+    let closes: Vec<f64> = history.iter().filter_map(|p| p.close.to_f64()).collect();
+    let macd = ema(&closes, 12).zip(ema(&closes, 26)).map(|(fast, slow)| fast - slow);
+
+    [
+        ("sma_20", sma(&closes, 20)),
+        ("sma_50", sma(&closes, 50)),
+        ("ema_12", ema(&closes, 12)),
+        ("ema_26", ema(&closes, 26)),
+        ("macd", macd),
+        ("rsi_14", rsi(&closes, 14)),
+        ("atr_14", atr(history, 14)),
+        ("volatility_20", volatility(&closes, 20)),
+        ("momentum_10", momentum(&closes, 10)),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| {
+        let value = Decimal::from_f64(value?)?;
+        Some((name.to_string(), value.round_dp(8)))
+    })
+    .collect()
+}
+
+/// 成交量分布按典型价格分桶，主动买卖量取自最近成交
+pub(crate) fn volume_profile(history: &[PricePoint], trades: &[TradeSample], buckets: usize) -> VolumeProfile {
+    let typical = |p: &PricePoint| (p.high + p.low + p.close) / Decimal::from(3);
+    let total_volume: Decimal = history.iter().map(|p| p.volume).sum();
+    let volume_weighted_price = if total_volume.is_zero() {
+        history.last().map(|p| p.close).unwrap_or_default()
+    } else {
+        history.iter().map(|p| typical(p) * p.volume).sum::<Decimal>() / total_volume
+    };
+
+    let (buy_volume, sell_volume) = trades.iter().fold((Decimal::ZERO, Decimal::ZERO), |(buy, sell), trade| {
+        match trade.side.as_str() {
+            "buy" => (buy + trade.quantity, sell),
+            "sell" => (buy, sell + trade.quantity),
+            _ => (buy, sell),
+        }
+    });
+
+    let low = history.iter().map(|p| p.low).min();
+    let high = history.iter().map(|p| p.high).max();
+    let volume_distribution = match (low, high) {
+        (Some(low), Some(high)) if high > low && buckets > 0 => {
+            let width = (high - low) / Decimal::from(buckets);
+            let mut volumes = vec![Decimal::ZERO; buckets];
+            for point in history {
+                let index = ((typical(point) - low) / width).floor().to_usize().unwrap_or(0);
+                volumes[index.min(buckets - 1)] += point.volume;
+            }
+            volumes
+                .into_iter()
+                .enumerate()
+                .map(|(i, volume)| (low + width * (Decimal::from(i) + Decimal::new(5, 1)), volume))
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+
+    VolumeProfile {
+        total_volume,
+        buy_volume,
+        sell_volume,
+        volume_weighted_price,
+        volume_distribution,
+    }
+}
+
+/// 微观结构指标：价差和深度取自深度指标，成交频率为每分钟成交笔数，冲击成本以半个价差估算
+fn microstructure(
+    depth: Option<DepthMetrics>,
+    trades: &[TradeSample],
+    seasonality: Option<SeasonalityProfile>,
+) -> MarketMicrostructure {
+    let (bid_ask_spread, order_book_depth, price_impact) = match &depth {
+        Some(metrics) => (
+            metrics.best_ask - metrics.best_bid,
+            metrics
+                .liquidity
+                .iter()
+                .max_by_key(|band| band.bps)
+                .map(|band| band.bid_notional + band.ask_notional)
+                .unwrap_or_default(),
+            metrics.spread_bps / Decimal::from(20_000),
+        ),
+        None => (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO),
+    };
+
+    let first = trades.iter().map(|t| t.timestamp).min();
+    let last = trades.iter().map(|t| t.timestamp).max();
+    let trade_frequency = match (first, last) {
+        (Some(first), Some(last)) if last > first => {
+            let minutes = Decimal::from((last - first).num_milliseconds()) / Decimal::from(60_000);
+            (Decimal::from(trades.len()) / minutes).round_dp(4)
+        }
+        _ => Decimal::ZERO,
+    };
+
+    MarketMicrostructure {
+        bid_ask_spread,
+        order_book_depth,
+        trade_frequency,
+        price_impact,
+        depth_metrics: depth,
+        seasonality,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: &[i64]) -> Vec<PricePoint> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| PricePoint {
+                timestamp: i as i64 * 60_000,
+                open: Decimal::from(close),
+                high: Decimal::from(close + 1),
+                low: Decimal::from(close - 1),
+                close: Decimal::from(close),
+                volume: Decimal::from(10),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_indicators_from_history() {
+        let history = candles(&(1..=60).map(|i| 100 + i).collect::<Vec<_>>());
+        let indicators = compute_indicators(&history);
+        assert_eq!(indicators["sma_20"], Decimal::new(1505, 1));
+        assert_eq!(indicators["rsi_14"], Decimal::from(100));
+        // 每根K线高低差2，收盘价每根上涨1
+        assert_eq!(indicators["atr_14"], Decimal::from(2));
+        assert!(indicators["macd"] > Decimal::ZERO);
+
+        let short = compute_indicators(&candles(&[100, 101, 102]));
+        assert!(short.is_empty());
+    }
+
+    #[test]
+    fn test_volume_profile_and_staleness() {
+        let history = candles(&[100, 110, 120]);
+        let trades = vec![
+            TradeSample {
+                timestamp: Utc::now(),
+                quantity: Decimal::from(3),
+                side: "buy".to_string(),
+            },
+            TradeSample {
+                timestamp: Utc::now(),
+                quantity: Decimal::from(1),
+                side: "sell".to_string(),
+            },
+        ];
+        let profile = volume_profile(&history, &trades, 4);
+        assert_eq!(profile.total_volume, Decimal::from(30));
+        assert_eq!(profile.volume_weighted_price, Decimal::from(110));
+        assert_eq!((profile.buy_volume, profile.sell_volume), (Decimal::from(3), Decimal::from(1)));
+        assert_eq!(profile.volume_distribution.len(), 4);
+        assert_eq!(
+            profile.volume_distribution.iter().map(|(_, v)| *v).sum::<Decimal>(),
+            Decimal::from(30)
+        );
+
+        let symbol = Symbol::new("BTC", "USDT");
+        assert!(ensure_fresh(&symbol, &history, 120_000, 240_000).is_ok());
+        assert!(ensure_fresh(&symbol, &history, 120_000, 300_000).is_err());
+        assert!(ensure_fresh(&symbol, &[], 120_000, 0).is_err());
+    }
+}
@@ -5,8 +5,11 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::budget::{AIBudgetManager, TokenUsage};
+use super::prompt_cache::PromptCache;
 use super::strategy_generator::*;
 use crate::models::{Strategy, TradingSignal};
+use crate::quotas::AICallQuota;
 
 /// DeepSeek AI客户端
 /// 专业的AI量化交易策略生成和分析
@@ -15,6 +18,9 @@ pub struct DeepSeekClient {
     base_url: String,
     model: String,
     client: Client,
+    prompt_cache: Option<PromptCache>,
+    budget: Option<AIBudgetManager>,
+    quota: Option<AICallQuota>,
 }
 
 #[derive(Debug, Serialize)]
@@ -64,11 +70,47 @@ impl DeepSeekClient {
             base_url: "https://api.deepseek.com/v1".to_string(),
             model: "deepseek-chat".to_string(),
             client: Client::new(),
+            prompt_cache: None,
+            budget: None,
+            quota: None,
         }
     }
 
+    /// 启用提示词响应缓存
+    pub fn with_prompt_cache(mut self, cache: PromptCache) -> Self {
+        self.prompt_cache = Some(cache);
+        self
+    }
+
+    /// 启用每日预算控制
+    pub fn with_budget(mut self, budget: AIBudgetManager) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// 代表策略调用时按策略的AI调用配额限制，超限的调用不会发出
+    pub fn with_quota(mut self, quota: AICallQuota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
     /// 发送请求到DeepSeek API
     async fn send_request(&self, messages: Vec<Message>) -> Result<String> {
+        let normalized = PromptCache::normalize(
+            messages.iter().map(|m| (m.role.as_str(), m.content.as_str())),
+        );
+        if let Some(cache) = &self.prompt_cache {
+            if let Some(response) = cache.get(&self.model, &normalized).await {
+                return Ok(response);
+            }
+        }
+        if let Some(budget) = &self.budget {
+            budget.check(&self.model).await?;
+        }
+        if let Some(quota) = &self.quota {
+            quota.check()?;
+        }
+
         let request = DeepSeekRequest {
             model: self.model.clone(),
             messages,
@@ -92,8 +134,21 @@ impl DeepSeekClient {
         }
 
         let deepseek_response: DeepSeekResponse = response.json().await?;
-        
+
+        if let Some(budget) = &self.budget {
+            let usage = TokenUsage {
+                prompt_tokens: deepseek_response.usage.prompt_tokens,
+                completion_tokens: deepseek_response.usage.completion_tokens,
+            };
+            if let Err(e) = budget.record(&self.model, &usage).await {
+                tracing::warn!("Failed to record AI usage for {}: {}", self.model, e);
+            }
+        }
+
         if let Some(choice) = deepseek_response.choices.first() {
+            if let Some(cache) = &self.prompt_cache {
+                cache.put(&self.model, &normalized, &choice.message.content).await;
+            }
             Ok(choice.message.content.clone())
         } else {
             Err(anyhow::anyhow!("No response from DeepSeek"))
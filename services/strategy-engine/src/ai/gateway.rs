@@ -0,0 +1,323 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use shared_utils::HashService;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{RwLock, Semaphore};
+
+use crate::quotas::{AICallQuota, QuotaExceeded};
+
+/// AI调用网关配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AIGatewayConfig {
+    /// 同时进行的AI调用数
+    pub max_concurrent: usize,
+    /// 排队等待的最大请求数
+    pub max_queued: usize,
+    /// 排队等待超时
+    pub queue_timeout_seconds: u64,
+    /// 单次AI调用超时
+    pub call_timeout_seconds: u64,
+    /// 响应缓存时间，0表示不缓存
+    pub cache_ttl_seconds: u64,
+    pub max_cache_entries: usize,
+}
+
+impl Default for AIGatewayConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            max_queued: 64,
+            queue_timeout_seconds: 30,
+            call_timeout_seconds: 120,
+            cache_ttl_seconds: 300,
+            max_cache_entries: 1000,
+        }
+    }
+}
+
+/// AI调用网关错误
+#[derive(Debug, Error)]
+pub enum AIGatewayError {
+    #[error("AI request queue is full ({0} waiting)")]
+    QueueFull(usize),
+    #[error("AI request timed out: {0}")]
+    Timeout(&'static str),
+    #[error("AI request failed: {0}")]
+    Failed(String),
+    #[error(transparent)]
+    QuotaExceeded(#[from] QuotaExceeded),
+}
+
+/// 网关调用结果
+#[derive(Debug, Clone)]
+pub struct GatewayResponse<T> {
+    pub value: T,
+    pub cached: bool,
+}
+
+/// 网关运行状态
+#[derive(Debug, Clone, Serialize)]
+pub struct AIGatewayStats {
+    pub in_flight: usize,
+    pub queued: usize,
+    pub cache_entries: usize,
+}
+
+struct CacheEntry {
+    value: Value,
+    expires_at: Instant,
+}
+
+/// AI调用网关
+///
+/// 所有AI调用经过这里排队：超过并发上限的请求等待信号量，
+/// 等待队列满时直接拒绝；相同输入的结果按输入哈希缓存。
+#[derive(Clone)]
+pub struct AIRequestGateway {
+    config: AIGatewayConfig,
+    permits: Arc<Semaphore>,
+    queued: Arc<AtomicUsize>,
+    cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
+}
+
+impl AIRequestGateway {
+    pub fn new(config: AIGatewayConfig) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(config.max_concurrent.max(1))),
+            queued: Arc::new(AtomicUsize::new(0)),
+            cache: Arc::new(RwLock::new(HashMap::new())),
+            config,
+        }
+    }
+
+    /// 缓存键：调用类型 + 规范化后输入JSON的SHA256
+    pub fn cache_key<I: Serialize>(kind: &str, input: &I) -> Result<String, AIGatewayError> {
+        // 转成Value后对象键按字典序输出，与字段顺序无关
+        let value = serde_json::to_value(input).map_err(|e| AIGatewayError::Failed(e.to_string()))?;
+        Ok(format!(
+            "{}:{}",
+            kind,
+            HashService::sha256_string(&format!("{}:{}", kind, value))
+        ))
+    }
+
+    /// 执行一次AI调用，命中缓存时直接返回
+    pub async fn call<I, T, F, Fut>(
+        &self,
+        kind: &str,
+        input: &I,
+        f: F,
+    ) -> Result<GatewayResponse<T>, AIGatewayError>
+    where
+        I: Serialize,
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        self.call_with_quota(None, kind, input, f).await
+    }
+
+    /// 代表策略执行AI调用，命中缓存不计入策略的AI调用配额
+    pub async fn call_with_quota<I, T, F, Fut>(
+        &self,
+        quota: Option<&AICallQuota>,
+        kind: &str,
+        input: &I,
+        f: F,
+    ) -> Result<GatewayResponse<T>, AIGatewayError>
+    where
+        I: Serialize,
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let key = Self::cache_key(kind, input)?;
+        if let Some(value) = self.cached(&key).await {
+            return Ok(GatewayResponse { value, cached: true });
+        }
+
+        let _permit = self.acquire().await?;
+
+        // 排队期间可能已有相同请求完成
+        if let Some(value) = self.cached(&key).await {
+            return Ok(GatewayResponse { value, cached: true });
+        }
+        if let Some(quota) = quota {
+            quota.check()?;
+        }
+
+        let value = tokio::time::timeout(Duration::from_secs(self.config.call_timeout_seconds), f())
+            .await
+            .map_err(|_| AIGatewayError::Timeout("model call"))?
+            .map_err(|e| AIGatewayError::Failed(e.to_string()))?;
+
+        self.store(key, &value).await;
+        Ok(GatewayResponse { value, cached: false })
+    }
+
+    async fn acquire(&self) -> Result<tokio::sync::SemaphorePermit<'_>, AIGatewayError> {
+        if let Ok(permit) = self.permits.try_acquire() {
+            return Ok(permit);
+        }
+
+        let waiting = self.queued.fetch_add(1, Ordering::SeqCst);
+        if waiting >= self.config.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(AIGatewayError::QueueFull(waiting));
+        }
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(self.config.queue_timeout_seconds),
+            self.permits.acquire(),
+        )
+        .await;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+
+        match result {
+            Ok(Ok(permit)) => Ok(permit),
+            Ok(Err(e)) => Err(AIGatewayError::Failed(e.to_string())),
+            Err(_) => Err(AIGatewayError::Timeout("queue wait")),
+        }
+    }
+
+    async fn cached<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        if self.config.cache_ttl_seconds == 0 {
+            return None;
+        }
+        let cache = self.cache.read().await;
+        cache
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .and_then(|entry| serde_json::from_value(entry.value.clone()).ok())
+    }
+
+    async fn store<T: Serialize>(&self, key: String, value: &T) {
+        if self.config.cache_ttl_seconds == 0 {
+            return;
+        }
+        let Ok(value) = serde_json::to_value(value) else {
+            return;
+        };
+
+        let now = Instant::now();
+        let mut cache = self.cache.write().await;
+        if cache.len() >= self.config.max_cache_entries {
+            cache.retain(|_, entry| entry.expires_at > now);
+            if cache.len() >= self.config.max_cache_entries {
+                // 仍然满时淘汰最早过期的条目
+                if let Some(oldest) = cache
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone())
+                {
+                    cache.remove(&oldest);
+                }
+            }
+        }
+        cache.insert(
+            key,
+            CacheEntry {
+                value,
+                expires_at: now + Duration::from_secs(self.config.cache_ttl_seconds),
+            },
+        );
+    }
+
+    pub async fn stats(&self) -> AIGatewayStats {
+        AIGatewayStats {
+            in_flight: self.config.max_concurrent.max(1) - self.permits.available_permits(),
+            queued: self.queued.load(Ordering::SeqCst),
+            cache_entries: self.cache.read().await.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cache_key_ignores_field_order() {
+        let a = json!({"symbol": "BTCUSDT", "horizon": "DayTrading"});
+        let b = json!({"horizon": "DayTrading", "symbol": "BTCUSDT"});
+        assert_eq!(
+            AIRequestGateway::cache_key("analyze", &a).unwrap(),
+            AIRequestGateway::cache_key("analyze", &b).unwrap()
+        );
+        assert_ne!(
+            AIRequestGateway::cache_key("analyze", &a).unwrap(),
+            AIRequestGateway::cache_key("risk", &a).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_cached() {
+        let gateway = AIRequestGateway::new(AIGatewayConfig::default());
+        let first = gateway
+            .call("analyze", &"BTCUSDT", || async { Ok(1u32) })
+            .await
+            .unwrap();
+        assert!(!first.cached);
+
+        let second = gateway
+            .call("analyze", &"BTCUSDT", || async { Ok(2u32) })
+            .await
+            .unwrap();
+        assert!(second.cached);
+        assert_eq!(second.value, 1);
+    }
+
+    #[tokio::test]
+    async fn test_queue_full_is_rejected() {
+        let gateway = AIRequestGateway::new(AIGatewayConfig {
+            max_concurrent: 1,
+            max_queued: 0,
+            ..Default::default()
+        });
+        let _busy = gateway.permits.try_acquire().unwrap();
+
+        let result = gateway
+            .call("analyze", &"ETHUSDT", || async { Ok(1u32) })
+            .await;
+        assert!(matches!(result, Err(AIGatewayError::QueueFull(_))));
+    }
+
+    #[tokio::test]
+    async fn test_strategy_ai_call_quota() {
+        use crate::quotas::{QuotaConfig, QuotaManager, StrategyQuota};
+
+        let quotas = QuotaManager::new(QuotaConfig {
+            default: StrategyQuota {
+                max_ai_calls_per_hour: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let guard = quotas.register(uuid::Uuid::new_v4(), "ema-ai").unwrap();
+        let quota = guard.ai_call_quota();
+        let gateway = AIRequestGateway::new(AIGatewayConfig::default());
+
+        let first = gateway
+            .call_with_quota(Some(&quota), "analyze", &"BTCUSDT", || async { Ok(1u32) })
+            .await
+            .unwrap();
+        assert!(!first.cached);
+        // 命中缓存不消耗配额
+        let cached = gateway
+            .call_with_quota(Some(&quota), "analyze", &"BTCUSDT", || async { Ok(2u32) })
+            .await
+            .unwrap();
+        assert!(cached.cached);
+
+        let result = gateway
+            .call_with_quota(Some(&quota), "analyze", &"ETHUSDT", || async { Ok(3u32) })
+            .await;
+        assert!(matches!(result, Err(AIGatewayError::QuotaExceeded(_))));
+    }
+}
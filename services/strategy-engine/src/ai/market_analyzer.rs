@@ -0,0 +1,69 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::strategy_generator::*;
+use crate::models::Symbol;
+
+/// 市场上下文来源
+#[async_trait]
+pub trait MarketContextProvider: Send + Sync {
+    async fn market_context(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketContext>;
+}
+
+/// AI市场分析器
+pub struct AIMarketAnalyzer {
+    ai_client: Arc<dyn AIClient>,
+    context_provider: Arc<dyn MarketContextProvider>,
+}
+
+impl AIMarketAnalyzer {
+    pub fn new(ai_client: Arc<dyn AIClient>, context_provider: Arc<dyn MarketContextProvider>) -> Self {
+        Self {
+            ai_client,
+            context_provider,
+        }
+    }
+
+    /// 分析指定交易对在给定周期下的市场状态
+    pub async fn analyze(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketAnalysis> {
+        let context = self.context_provider.market_context(symbol, horizon).await?;
+        self.analyze_context(&context).await
+    }
+
+    /// 分析已有的市场上下文
+    pub async fn analyze_context(&self, context: &MarketContext) -> Result<MarketAnalysis> {
+        self.ai_client.analyze_market(context).await
+    }
+
+    /// 获取市场上下文
+    pub async fn market_context(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketContext> {
+        self.context_provider.market_context(symbol, horizon).await
+    }
+
+    pub fn model_name(&self) -> &str {
+        self.ai_client.get_model_name()
+    }
+}
+
+/// 周期对应的K线周期和数量
+pub fn chart_window(horizon: &TimeHorizon) -> (&'static str, i64) {
+    match horizon {
+        TimeHorizon::Scalping => ("1m", 240),
+        TimeHorizon::DayTrading => ("15m", 192),
+        TimeHorizon::SwingTrading => ("4h", 180),
+        TimeHorizon::PositionTrading => ("1d", 180),
+        TimeHorizon::LongTerm => ("1w", 156),
+    }
+}
+
+/// K线周期的毫秒数
+pub fn interval_millis(interval: &str) -> i64 {
+    match interval {
+        "1m" => 60_000,
+        "15m" => 900_000,
+        "4h" => 14_400_000,
+        "1d" => 86_400_000,
+        _ => 604_800_000,
+    }
+}
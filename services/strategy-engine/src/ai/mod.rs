@@ -1,13 +1,22 @@
+pub mod budget;
 pub mod claude;
+pub mod context_builder;
 pub mod deepseek;
+pub mod gateway;
 pub mod local_llm;
 pub mod market_analyzer;
 pub mod openai;
 pub mod portfolio_optimizer;
+pub mod prompt_cache;
 pub mod risk_predictor;
+pub mod rule_based;
 pub mod strategy_generator;
 
+pub use budget::{AIBudgetConfig, AIBudgetManager};
+pub use context_builder::{MarketContextBuilder, MarketContextConfig};
 pub use market_analyzer::AIMarketAnalyzer;
 pub use portfolio_optimizer::AIPortfolioOptimizer;
+pub use prompt_cache::PromptCache;
 pub use risk_predictor::AIRiskPredictor;
+pub use rule_based::{DegradingAIClient, RuleBasedClient};
 pub use strategy_generator::AIStrategyGenerator;
@@ -0,0 +1,90 @@
+use redis::aio::ConnectionManager;
+use shared_utils::HashService;
+use tracing::warn;
+
+/// AI提示词响应缓存
+///
+/// 键由模型名称、提示词版本和规范化后的提示词组成。
+/// 修改提示词模板时提升版本号，旧缓存自然失效。
+#[derive(Clone)]
+pub struct PromptCache {
+    redis: ConnectionManager,
+    /// 提示词模板版本
+    version: String,
+    ttl_seconds: usize,
+}
+
+impl PromptCache {
+    pub fn new(redis: ConnectionManager, version: impl Into<String>, ttl_seconds: usize) -> Self {
+        Self {
+            redis,
+            version: version.into(),
+            ttl_seconds,
+        }
+    }
+
+    /// 规范化提示词：逐条消息合并空白字符，避免格式差异导致缓存未命中
+    pub fn normalize<'a>(messages: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+        messages
+            .into_iter()
+            .map(|(role, content)| {
+                format!(
+                    "{}:{}",
+                    role.trim().to_lowercase(),
+                    content.split_whitespace().collect::<Vec<_>>().join(" ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn key(&self, model: &str, normalized_prompt: &str) -> String {
+        format!(
+            "ai:prompt:{}:{}:{}",
+            model,
+            self.version,
+            HashService::sha256_string(normalized_prompt)
+        )
+    }
+
+    /// 读取缓存，Redis错误视为未命中
+    pub async fn get(&self, model: &str, normalized_prompt: &str) -> Option<String> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.redis.clone();
+        match conn.get(self.key(model, normalized_prompt)).await {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Failed to read AI prompt cache: {}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn put(&self, model: &str, normalized_prompt: &str, response: &str) {
+        use redis::AsyncCommands;
+
+        let mut conn = self.redis.clone();
+        let result: redis::RedisResult<()> = conn
+            .set_ex(self.key(model, normalized_prompt), response, self.ttl_seconds)
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to write AI prompt cache: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_collapses_whitespace() {
+        let a = PromptCache::normalize([("system", "你是分析师"), ("user", "分析  BTCUSDT\n\n 走势 ")]);
+        let b = PromptCache::normalize([("System ", "你是分析师"), ("user", "分析 BTCUSDT 走势")]);
+        assert_eq!(a, b);
+
+        let c = PromptCache::normalize([("system", "你是分析师"), ("user", "分析 ETHUSDT 走势")]);
+        assert_ne!(a, c);
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::Result;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::strategy_generator::*;
+
+/// 正态分布95%/99%分位数
+const Z_95: Decimal = dec!(1.645);
+const Z_99: Decimal = dec!(2.326);
+/// 正态分布下99%期望损失与VaR的比值
+const ES_99_FACTOR: Decimal = dec!(1.146);
+
+/// 风险等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// 单个持仓的风险预测
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionRisk {
+    pub symbol: String,
+    pub market_value: Decimal,
+    pub weight: Decimal,
+    /// AI分析给出的当前波动率（日）
+    pub volatility: Decimal,
+    pub var_95: Decimal,
+    pub var_99: Decimal,
+    pub volatility_regime: Option<VolatilityRegime>,
+    pub market_regime: Option<MarketRegime>,
+}
+
+/// 组合风险预测
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskPrediction {
+    pub model: String,
+    pub total_value: Decimal,
+    /// 一日95% VaR（各持仓VaR直接相加，不计分散化收益）
+    pub var_95: Decimal,
+    pub var_99: Decimal,
+    pub expected_shortfall_99: Decimal,
+    /// 持仓集中度（HHI，0~1）
+    pub concentration: Decimal,
+    pub risk_level: RiskLevel,
+    pub position_risks: Vec<PositionRisk>,
+    pub warnings: Vec<String>,
+}
+
+/// AI风险预测器
+///
+/// 由AI给出各持仓标的的波动率和市场状态，再按参数法计算组合VaR。
+pub struct AIRiskPredictor {
+    ai_client: Arc<dyn AIClient>,
+    /// 没有市场上下文的持仓使用的默认日波动率
+    default_volatility: Decimal,
+}
+
+impl AIRiskPredictor {
+    pub fn new(ai_client: Arc<dyn AIClient>) -> Self {
+        Self {
+            ai_client,
+            default_volatility: dec!(0.05),
+        }
+    }
+
+    pub fn model_name(&self) -> &str {
+        self.ai_client.get_model_name()
+    }
+
+    /// 预测组合风险
+    pub async fn predict(
+        &self,
+        portfolio: &PortfolioContext,
+        risk: Option<&RiskContext>,
+        market_contexts: &[MarketContext],
+    ) -> Result<RiskPrediction> {
+        let mut analyses = HashMap::new();
+        for context in market_contexts {
+            let analysis = self.ai_client.analyze_market(context).await?;
+            analyses.insert(context.symbol.to_string(), analysis);
+        }
+
+        Ok(build_prediction(
+            self.model_name(),
+            portfolio,
+            risk,
+            &analyses,
+            self.default_volatility,
+        ))
+    }
+}
+
+fn build_prediction(
+    model: &str,
+    portfolio: &PortfolioContext,
+    risk: Option<&RiskContext>,
+    analyses: &HashMap<String, MarketAnalysis>,
+    default_volatility: Decimal,
+) -> RiskPrediction {
+    let total_value = portfolio.total_value;
+    let mut warnings = Vec::new();
+    let mut position_risks = Vec::with_capacity(portfolio.positions.len());
+
+    for position in &portfolio.positions {
+        let symbol = position.symbol.to_string();
+        let market_value = (position.quantity * position.current_price).abs();
+        let weight = if total_value.is_zero() {
+            Decimal::ZERO
+        } else {
+            market_value / total_value
+        };
+
+        let analysis = analyses.get(&symbol);
+        let volatility = match analysis {
+            Some(analysis) => analysis.volatility_analysis.current_volatility.abs(),
+            None => {
+                warnings.push(format!(
+                    "No market analysis for {}, using default volatility",
+                    symbol
+                ));
+                default_volatility
+            }
+        };
+
+        position_risks.push(PositionRisk {
+            symbol,
+            market_value,
+            weight,
+            volatility,
+            var_95: market_value * volatility * Z_95,
+            var_99: market_value * volatility * Z_99,
+            volatility_regime: analysis.map(|a| a.volatility_analysis.volatility_regime.clone()),
+            market_regime: analysis.map(|a| a.regime_classification.clone()),
+        });
+    }
+
+    let var_95: Decimal = position_risks.iter().map(|p| p.var_95).sum();
+    let var_99: Decimal = position_risks.iter().map(|p| p.var_99).sum();
+    let concentration: Decimal = position_risks.iter().map(|p| p.weight * p.weight).sum();
+
+    let var_ratio = if total_value.is_zero() {
+        Decimal::ZERO
+    } else {
+        var_99 / total_value
+    };
+    let mut risk_level = match var_ratio {
+        r if r >= dec!(0.15) => RiskLevel::Critical,
+        r if r >= dec!(0.08) => RiskLevel::High,
+        r if r >= dec!(0.03) => RiskLevel::Medium,
+        _ => RiskLevel::Low,
+    };
+    if concentration > dec!(0.5) && risk_level == RiskLevel::Low {
+        risk_level = RiskLevel::Medium;
+    }
+
+    if let Some(risk) = risk {
+        if let Some(budget) = risk.risk_budget.get("var") {
+            if var_99 > *budget {
+                warnings.push(format!("Predicted VaR {} exceeds risk budget {}", var_99, budget));
+                if risk_level < RiskLevel::High {
+                    risk_level = RiskLevel::High;
+                }
+            }
+        }
+    }
+
+    RiskPrediction {
+        model: model.to_string(),
+        total_value,
+        var_95,
+        var_99,
+        expected_shortfall_99: var_99 * ES_99_FACTOR,
+        concentration,
+        risk_level,
+        position_risks,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn analysis(volatility: Decimal) -> MarketAnalysis {
+        MarketAnalysis {
+            trend_analysis: TrendAnalysis {
+                direction: TrendDirection::Sideways,
+                strength: Decimal::ZERO,
+                duration: 0,
+                confidence: Decimal::ZERO,
+            },
+            volatility_analysis: VolatilityAnalysis {
+                current_volatility: volatility,
+                historical_volatility: volatility,
+                implied_volatility: None,
+                volatility_regime: VolatilityRegime::Normal,
+            },
+            momentum_analysis: MomentumAnalysis {
+                short_term_momentum: Decimal::ZERO,
+                medium_term_momentum: Decimal::ZERO,
+                long_term_momentum: Decimal::ZERO,
+                momentum_divergence: false,
+            },
+            support_resistance: SupportResistance {
+                support_levels: Vec::new(),
+                resistance_levels: Vec::new(),
+                pivot_points: Vec::new(),
+            },
+            pattern_recognition: Vec::new(),
+            anomaly_detection: Vec::new(),
+            regime_classification: MarketRegime::Sideways,
+        }
+    }
+
+    fn position(base: &str, quantity: Decimal, price: Decimal) -> PositionInfo {
+        PositionInfo {
+            symbol: Symbol::new(base, "USDT"),
+            quantity,
+            avg_price: price,
+            current_price: price,
+            unrealized_pnl: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_parametric_var() {
+        let portfolio = PortfolioContext {
+            total_value: dec!(10000),
+            available_cash: dec!(5000),
+            positions: vec![position("BTC", dec!(0.1), dec!(50000))],
+            allocation: HashMap::new(),
+        };
+        let mut analyses = HashMap::new();
+        analyses.insert(portfolio.positions[0].symbol.to_string(), analysis(dec!(0.02)));
+
+        let prediction = build_prediction("test", &portfolio, None, &analyses, dec!(0.05));
+        assert_eq!(prediction.var_95, dec!(5000) * dec!(0.02) * Z_95);
+        assert_eq!(prediction.concentration, dec!(0.25));
+        assert_eq!(prediction.risk_level, RiskLevel::Low);
+        assert!(prediction.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_missing_analysis_and_budget_breach() {
+        let portfolio = PortfolioContext {
+            total_value: dec!(10000),
+            available_cash: Decimal::ZERO,
+            positions: vec![position("ETH", dec!(5), dec!(2000))],
+            allocation: HashMap::new(),
+        };
+        let mut risk_budget = HashMap::new();
+        risk_budget.insert("var".to_string(), dec!(500));
+        let risk = RiskContext {
+            current_var: Decimal::ZERO,
+            max_drawdown: Decimal::ZERO,
+            correlation_matrix: HashMap::new(),
+            risk_budget,
+        };
+
+        let prediction = build_prediction("test", &portfolio, Some(&risk), &HashMap::new(), dec!(0.05));
+        assert_eq!(prediction.position_risks[0].volatility, dec!(0.05));
+        assert_eq!(prediction.warnings.len(), 2);
+        assert!(prediction.risk_level >= RiskLevel::High);
+    }
+}
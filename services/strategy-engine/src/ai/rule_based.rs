@@ -0,0 +1,390 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use super::budget::{AIBudgetManager, BudgetExhausted};
+use super::strategy_generator::*;
+use crate::models::{SignalType, Strategy, StrategyType, TradingSignal};
+
+const SHORT_WINDOW: usize = 10;
+const LONG_WINDOW: usize = 30;
+
+/// 基于规则的策略客户端
+///
+/// 不调用任何模型，只根据价格序列计算均线、波动率和动量，
+/// 在AI预算耗尽时作为降级方案。
+pub struct RuleBasedClient {
+    name: String,
+}
+
+impl Default for RuleBasedClient {
+    fn default() -> Self {
+        Self {
+            name: "rule-based".to_string(),
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(6)
+}
+
+/// 根据价格序列计算市场状态
+pub fn analyze_prices(context: &MarketContext) -> MarketAnalysis {
+    let closes: Vec<f64> = context
+        .price_history
+        .iter()
+        .filter_map(|p| p.close.to_f64())
+        .collect();
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect();
+
+    let short_ma = mean(&closes[closes.len().saturating_sub(SHORT_WINDOW)..]);
+    let long_ma = mean(&closes[closes.len().saturating_sub(LONG_WINDOW)..]);
+    let trend_strength = if long_ma > 0.0 { short_ma / long_ma - 1.0 } else { 0.0 };
+    let direction = if closes.len() < SHORT_WINDOW {
+        TrendDirection::Uncertain
+    } else if trend_strength > 0.005 {
+        TrendDirection::Bullish
+    } else if trend_strength < -0.005 {
+        TrendDirection::Bearish
+    } else {
+        TrendDirection::Sideways
+    };
+
+    let volatility = |returns: &[f64]| {
+        let m = mean(returns);
+        mean(&returns.iter().map(|r| (r - m).powi(2)).collect::<Vec<_>>()).sqrt()
+    };
+    let current_volatility = volatility(&returns[returns.len().saturating_sub(SHORT_WINDOW)..]);
+    let historical_volatility = volatility(&returns);
+    let volatility_regime = match current_volatility {
+        v if v >= 0.08 => VolatilityRegime::Extreme,
+        v if v >= 0.04 => VolatilityRegime::High,
+        v if v >= 0.01 => VolatilityRegime::Normal,
+        _ => VolatilityRegime::Low,
+    };
+
+    let momentum = |lookback: usize| match (closes.last(), closes.len().checked_sub(lookback + 1)) {
+        (Some(last), Some(i)) if closes[i] > 0.0 => last / closes[i] - 1.0,
+        _ => 0.0,
+    };
+    let short_term_momentum = momentum(SHORT_WINDOW / 2);
+    let long_term_momentum = momentum(LONG_WINDOW);
+
+    let lows = context.price_history.iter().map(|p| p.low);
+    let highs = context.price_history.iter().map(|p| p.high);
+    let support_resistance = SupportResistance {
+        support_levels: lows.min().into_iter().collect(),
+        resistance_levels: highs.max().into_iter().collect(),
+        pivot_points: context
+            .price_history
+            .last()
+            .map(|p| (p.high + p.low + p.close) / Decimal::from(3))
+            .into_iter()
+            .collect(),
+    };
+
+    let regime_classification = match (&direction, &volatility_regime) {
+        (_, VolatilityRegime::Extreme) => MarketRegime::Volatile,
+        (TrendDirection::Bullish, _) => MarketRegime::Bull,
+        (TrendDirection::Bearish, _) => MarketRegime::Bear,
+        (_, VolatilityRegime::Low) => MarketRegime::Calm,
+        _ => MarketRegime::Sideways,
+    };
+
+    MarketAnalysis {
+        trend_analysis: TrendAnalysis {
+            direction,
+            strength: to_decimal(trend_strength.abs()),
+            duration: 0,
+            // 规则判断的置信度固定为中等
+            confidence: Decimal::new(50, 2),
+        },
+        volatility_analysis: VolatilityAnalysis {
+            current_volatility: to_decimal(current_volatility),
+            historical_volatility: to_decimal(historical_volatility),
+            implied_volatility: None,
+            volatility_regime,
+        },
+        momentum_analysis: MomentumAnalysis {
+            short_term_momentum: to_decimal(short_term_momentum),
+            medium_term_momentum: to_decimal(momentum(SHORT_WINDOW)),
+            long_term_momentum: to_decimal(long_term_momentum),
+            momentum_divergence: short_term_momentum * long_term_momentum < 0.0,
+        },
+        support_resistance,
+        pattern_recognition: Vec::new(),
+        anomaly_detection: Vec::new(),
+        regime_classification,
+    }
+}
+
+fn condition(name: &str, logic: &str) -> Condition {
+    Condition {
+        name: name.to_string(),
+        description: String::new(),
+        logic: logic.to_string(),
+        parameters: HashMap::new(),
+    }
+}
+
+#[async_trait]
+impl AIClient for RuleBasedClient {
+    async fn generate_strategy(&self, prompt: &StrategyPrompt) -> Result<GeneratedStrategy> {
+        // 趋势行情用动量，其余用均值回归
+        let trending = matches!(
+            prompt.market_conditions.trend,
+            TrendDirection::Bullish | TrendDirection::Bearish
+        );
+        let (stop_loss, take_profit, position_size) = match prompt.risk_tolerance {
+            RiskTolerance::Conservative => (Decimal::new(2, 2), Decimal::new(4, 2), Decimal::new(5, 2)),
+            RiskTolerance::Moderate | RiskTolerance::Custom(_) => (Decimal::new(3, 2), Decimal::new(6, 2), Decimal::new(10, 2)),
+            RiskTolerance::Aggressive => (Decimal::new(5, 2), Decimal::new(10, 2), Decimal::new(20, 2)),
+        };
+
+        let mut parameters = HashMap::new();
+        let (name, entry, exit) = if trending {
+            parameters.insert("fast_period".to_string(), ParameterValue::Integer(12));
+            parameters.insert("slow_period".to_string(), ParameterValue::Integer(26));
+            (
+                "Momentum",
+                vec![condition("ema_cross_up", "ema(fast_period) > ema(slow_period)")],
+                vec![condition("ema_cross_down", "ema(fast_period) < ema(slow_period)")],
+            )
+        } else {
+            parameters.insert("lookback_period".to_string(), ParameterValue::Integer(20));
+            parameters.insert("threshold".to_string(), ParameterValue::Decimal(Decimal::from(2)));
+            (
+                "Mean Reversion",
+                vec![condition("below_band", "close < sma(lookback_period) - threshold * stddev(lookback_period)")],
+                vec![condition("back_to_mean", "close >= sma(lookback_period)")],
+            )
+        };
+
+        Ok(GeneratedStrategy {
+            name: format!("{} (rule-based)", name),
+            description: format!("Rule-based fallback strategy for: {}", prompt.objective),
+            strategy_type: StrategyType::Custom,
+            entry_conditions: entry,
+            exit_conditions: exit,
+            risk_management: RiskManagement {
+                stop_loss: Some(stop_loss),
+                take_profit: Some(take_profit),
+                position_sizing: PositionSizing::Percentage(position_size),
+                max_drawdown: Decimal::new(15, 2),
+                max_positions: 3,
+            },
+            parameters,
+            expected_performance: ExpectedPerformance {
+                annual_return: Decimal::ZERO,
+                sharpe_ratio: Decimal::ZERO,
+                max_drawdown: Decimal::new(15, 2),
+                win_rate: Decimal::new(50, 2),
+                profit_factor: Decimal::ONE,
+                volatility: Decimal::ZERO,
+            },
+            code: None,
+            confidence_score: Decimal::new(50, 2),
+        })
+    }
+
+    async fn analyze_market(&self, context: &MarketContext) -> Result<MarketAnalysis> {
+        Ok(analyze_prices(context))
+    }
+
+    async fn optimize_parameters(&self, _strategy: &Strategy, _performance: &PerformanceMetrics) -> Result<OptimizedParameters> {
+        // 规则模式不调整参数
+        Ok(OptimizedParameters {
+            parameters: HashMap::new(),
+            expected_improvement: Decimal::ZERO,
+            confidence: Decimal::ZERO,
+            optimization_method: "rule-based (unchanged)".to_string(),
+        })
+    }
+
+    async fn predict_signals(&self, context: &TradingContext) -> Result<Vec<TradingSignal>> {
+        let analysis = analyze_prices(&context.market_context);
+        let signal_type = match analysis.trend_analysis.direction {
+            TrendDirection::Bullish => SignalType::Buy,
+            TrendDirection::Bearish => SignalType::Sell,
+            _ => return Ok(Vec::new()),
+        };
+
+        Ok(vec![TradingSignal {
+            id: uuid::Uuid::new_v4(),
+            symbol: context.market_context.symbol.clone(),
+            signal_type,
+            strength: analysis.trend_analysis.confidence,
+            price: context.market_context.current_price,
+            timestamp: chrono::Utc::now(),
+            strategy_id: None,
+            metadata: HashMap::new(),
+        }])
+    }
+
+    fn get_model_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// 预算熔断时降级到规则策略的AI客户端
+pub struct DegradingAIClient {
+    primary: Arc<dyn AIClient>,
+    fallback: RuleBasedClient,
+    budget: AIBudgetManager,
+}
+
+impl DegradingAIClient {
+    pub fn new(primary: Arc<dyn AIClient>, budget: AIBudgetManager) -> Self {
+        Self {
+            primary,
+            fallback: RuleBasedClient::default(),
+            budget,
+        }
+    }
+
+    /// 熔断是否打开
+    pub async fn is_degraded(&self) -> bool {
+        self.budget.is_exhausted(self.primary.get_model_name()).await
+    }
+
+    fn should_fallback<T>(&self, result: &Result<T>) -> bool {
+        match result {
+            Err(e) if e.downcast_ref::<BudgetExhausted>().is_some() => {
+                info!(
+                    "Falling back to rule-based client for {}: {}",
+                    self.primary.get_model_name(),
+                    e
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl AIClient for DegradingAIClient {
+    async fn generate_strategy(&self, prompt: &StrategyPrompt) -> Result<GeneratedStrategy> {
+        if !self.is_degraded().await {
+            let result = self.primary.generate_strategy(prompt).await;
+            if !self.should_fallback(&result) {
+                return result;
+            }
+        }
+        self.fallback.generate_strategy(prompt).await
+    }
+
+    async fn analyze_market(&self, context: &MarketContext) -> Result<MarketAnalysis> {
+        if !self.is_degraded().await {
+            let result = self.primary.analyze_market(context).await;
+            if !self.should_fallback(&result) {
+                return result;
+            }
+        }
+        self.fallback.analyze_market(context).await
+    }
+
+    async fn optimize_parameters(&self, strategy: &Strategy, performance: &PerformanceMetrics) -> Result<OptimizedParameters> {
+        if !self.is_degraded().await {
+            let result = self.primary.optimize_parameters(strategy, performance).await;
+            if !self.should_fallback(&result) {
+                return result;
+            }
+        }
+        self.fallback.optimize_parameters(strategy, performance).await
+    }
+
+    async fn predict_signals(&self, context: &TradingContext) -> Result<Vec<TradingSignal>> {
+        if !self.is_degraded().await {
+            let result = self.primary.predict_signals(context).await;
+            if !self.should_fallback(&result) {
+                return result;
+            }
+        }
+        self.fallback.predict_signals(context).await
+    }
+
+    fn get_model_name(&self) -> &str {
+        self.primary.get_model_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn context(closes: &[i64]) -> MarketContext {
+        MarketContext {
+            symbol: Symbol::new("BTC", "USDT"),
+            current_price: Decimal::from(*closes.last().unwrap()),
+            price_history: closes
+                .iter()
+                .enumerate()
+                .map(|(i, c)| PricePoint {
+                    timestamp: i as i64 * 60_000,
+                    open: Decimal::from(*c),
+                    high: Decimal::from(*c + 1),
+                    low: Decimal::from(*c - 1),
+                    close: Decimal::from(*c),
+                    volume: Decimal::ONE,
+                })
+                .collect(),
+            volume_profile: VolumeProfile {
+                total_volume: Decimal::ZERO,
+                buy_volume: Decimal::ZERO,
+                sell_volume: Decimal::ZERO,
+                volume_weighted_price: Decimal::ZERO,
+                volume_distribution: Vec::new(),
+            },
+            technical_indicators: HashMap::new(),
+            fundamental_data: None,
+            news_sentiment: None,
+            market_microstructure: MarketMicrostructure {
+                bid_ask_spread: Decimal::ZERO,
+                order_book_depth: Decimal::ZERO,
+                trade_frequency: Decimal::ZERO,
+                price_impact: Decimal::ZERO,
+                depth_metrics: None,
+                seasonality: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_trend_detection() {
+        let rising: Vec<i64> = (0..40).map(|i| 100 + i * 2).collect();
+        let analysis = analyze_prices(&context(&rising));
+        assert!(matches!(analysis.trend_analysis.direction, TrendDirection::Bullish));
+        assert!(matches!(analysis.regime_classification, MarketRegime::Bull));
+        assert_eq!(analysis.support_resistance.support_levels, vec![Decimal::from(99)]);
+
+        let flat = vec![100; 40];
+        let analysis = analyze_prices(&context(&flat));
+        assert!(matches!(analysis.trend_analysis.direction, TrendDirection::Sideways));
+        assert_eq!(analysis.volatility_analysis.current_volatility, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_short_history_is_uncertain() {
+        let analysis = analyze_prices(&context(&[100, 101, 102]));
+        assert!(matches!(analysis.trend_analysis.direction, TrendDirection::Uncertain));
+    }
+}
@@ -2,16 +2,21 @@ use anyhow::Result;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::models::{Strategy, StrategyType, Symbol, TradingSignal};
+use super::market_analyzer::MarketContextProvider;
+use crate::quality::SignalScoreboard;
+use shared_models::market::{DepthMetrics, SeasonalityProfile};
 
 /// AI驱动的策略生成器
 /// 支持多种AI模型：DeepSeek、GPT-4、Claude等
 pub struct AIStrategyGenerator {
     ai_client: Box<dyn AIClient>,
-    market_data_cache: HashMap<Symbol, MarketContext>,
+    context_provider: Option<Arc<dyn MarketContextProvider>>,
     strategy_templates: Vec<StrategyTemplate>,
+    scoreboard: Option<Arc<SignalScoreboard>>,
 }
 
 /// AI客户端接口
@@ -219,6 +224,52 @@ pub struct MarketMicrostructure {
     pub order_book_depth: Decimal,
     pub trade_frequency: Decimal,
     pub price_impact: Decimal,
+    /// 市场数据服务推送的订单簿深度指标（market.depth_metrics）
+    #[serde(default)]
+    pub depth_metrics: Option<DepthMetrics>,
+    /// 市场数据服务计算的日内季节性画像，用于避开清淡时段
+    #[serde(default)]
+    pub seasonality: Option<SeasonalityProfile>,
+}
+
+impl MarketMicrostructure {
+    /// 指定时刻（毫秒）所在星期和小时的成交量比和波动比
+    pub fn seasonal_ratios(&self, timestamp_ms: i64) -> Option<(Decimal, Decimal)> {
+        use chrono::{Datelike, TimeZone, Timelike};
+
+        let at = chrono::Utc.timestamp_millis_opt(timestamp_ms).single()?;
+        let bucket = self
+            .seasonality
+            .as_ref()?
+            .bucket(at.weekday().number_from_monday() as u8, at.hour() as u8)?;
+        Some((bucket.volume_ratio, bucket.volatility_ratio))
+    }
+
+    /// 导出深度相关特征，供指标计算使用
+    pub fn depth_features(&self) -> HashMap<String, Decimal> {
+        let mut features = HashMap::new();
+        if let Some(metrics) = &self.depth_metrics {
+            features.insert("microprice".to_string(), metrics.microprice);
+            features.insert("spread_bps".to_string(), metrics.spread_bps);
+            features.insert(
+                "microprice_offset".to_string(),
+                metrics.microprice - metrics.mid_price,
+            );
+            for imbalance in &metrics.imbalances {
+                features.insert(
+                    format!("depth_imbalance_{}", imbalance.levels),
+                    imbalance.imbalance,
+                );
+            }
+            for band in &metrics.liquidity {
+                features.insert(
+                    format!("liquidity_{}bps", band.bps),
+                    band.bid_notional + band.ask_notional,
+                );
+            }
+        }
+        features
+    }
 }
 
 /// 市场分析结果
@@ -390,15 +441,28 @@ impl AIStrategyGenerator {
     pub fn new(ai_client: Box<dyn AIClient>) -> Self {
         Self {
             ai_client,
-            market_data_cache: HashMap::new(),
+            context_provider: None,
             strategy_templates: Self::load_strategy_templates(),
+            scoreboard: None,
         }
     }
 
+    /// 生成策略前从该来源获取市场上下文
+    pub fn with_context_provider(mut self, provider: Arc<dyn MarketContextProvider>) -> Self {
+        self.context_provider = Some(provider);
+        self
+    }
+
+    /// 发出的信号计入信号质量记分板
+    pub fn with_scoreboard(mut self, scoreboard: Arc<SignalScoreboard>) -> Self {
+        self.scoreboard = Some(scoreboard);
+        self
+    }
+
     /// 生成AI策略
     pub async fn generate_strategy(&self, prompt: StrategyPrompt) -> Result<GeneratedStrategy> {
         // 1. 收集市场数据
-        let market_contexts = self.collect_market_data(&prompt.symbols).await?;
+        let market_contexts = self.collect_market_data(&prompt.symbols, &prompt.time_horizon).await?;
         
         // 2. 分析市场条件
         let market_analysis = self.analyze_market_conditions(&market_contexts).await?;
@@ -431,40 +495,41 @@ impl AIStrategyGenerator {
 
     /// 生成交易信号
     pub async fn generate_signals(&self, context: &TradingContext) -> Result<Vec<TradingSignal>> {
-        self.ai_client.predict_signals(context).await
+        let mut signals = self.ai_client.predict_signals(context).await?;
+        // 记录生成信号的模型，用于按模型统计信号质量
+        for signal in &mut signals {
+            signal
+                .metadata
+                .entry("model".to_string())
+                .or_insert_with(|| serde_json::json!(self.ai_client.get_model_name()));
+        }
+        if let Some(scoreboard) = &self.scoreboard {
+            scoreboard.track_all(&signals).await;
+        }
+        Ok(signals)
     }
 
-    /// 收集市场数据
-    async fn collect_market_data(&self, symbols: &[Symbol]) -> Result<Vec<MarketContext>> {
-        let mut contexts = Vec::new();
-        
-        for symbol in symbols {
-            // TODO: 从市场数据服务获取实时数据
-            let context = MarketContext {
-                symbol: symbol.clone(),
-                current_price: Decimal::from(50000), // 模拟数据
-                price_history: Vec::new(),
-                volume_profile: VolumeProfile {
-                    total_volume: Decimal::from(1000),
-                    buy_volume: Decimal::from(600),
-                    sell_volume: Decimal::from(400),
-                    volume_weighted_price: Decimal::from(49950),
-                    volume_distribution: Vec::new(),
-                },
-                technical_indicators: HashMap::new(),
-                fundamental_data: None,
-                news_sentiment: None,
-                market_microstructure: MarketMicrostructure {
-                    bid_ask_spread: Decimal::new(1, 2), // 0.01
-                    order_book_depth: Decimal::from(1000000),
-                    trade_frequency: Decimal::from(100),
-                    price_impact: Decimal::new(5, 4), // 0.0005
-                },
-            };
-            contexts.push(context);
+    /// 并发获取各交易对的市场上下文，任一交易对行情缺失或过期时不生成策略
+    async fn collect_market_data(&self, symbols: &[Symbol], horizon: &TimeHorizon) -> Result<Vec<MarketContext>> {
+        let provider = self
+            .context_provider
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No market context provider configured"))?;
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, symbol) in symbols.iter().enumerate() {
+            let provider = provider.clone();
+            let symbol = symbol.clone();
+            let horizon = horizon.clone();
+            tasks.spawn(async move { (index, provider.market_context(&symbol, &horizon).await) });
         }
-        
-        Ok(contexts)
+
+        let mut contexts: Vec<Option<MarketContext>> = vec![None; symbols.len()];
+        while let Some(joined) = tasks.join_next().await {
+            let (index, context) = joined?;
+            contexts[index] = Some(context?);
+        }
+        Ok(contexts.into_iter().flatten().collect())
     }
 
     /// 分析市场条件
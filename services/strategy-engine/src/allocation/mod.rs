@@ -0,0 +1,267 @@
+pub mod report;
+
+use anyhow::{anyhow, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::execution::{OrderIntent, OrderSink, SubmittedOrder};
+use crate::models::TradingSignal;
+use crate::registry::{strategy_tag, SemVer};
+
+pub use report::ABComparisonReport;
+
+/// A/B测试中的一个版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantConfig {
+    /// 版本标签，例如 A / B
+    pub label: String,
+    pub version: SemVer,
+    /// 分配的资金比例
+    pub capital_fraction: Decimal,
+}
+
+/// A/B测试配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ABTestConfig {
+    pub test_id: String,
+    pub strategy: String,
+    pub total_capital: Decimal,
+    /// 单个满强度信号使用的版本资金比例
+    #[serde(default = "default_position_fraction")]
+    pub position_fraction: Decimal,
+    /// 显著性水平
+    #[serde(default = "default_alpha")]
+    pub alpha: f64,
+    pub variants: Vec<VariantConfig>,
+}
+
+fn default_position_fraction() -> Decimal {
+    Decimal::new(10, 2)
+}
+
+fn default_alpha() -> f64 {
+    0.05
+}
+
+impl ABTestConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.variants.len() != 2 {
+            return Err(anyhow!("A/B test needs exactly two variants"));
+        }
+        if self.variants[0].label == self.variants[1].label {
+            return Err(anyhow!("Variant labels must be distinct"));
+        }
+        if self.variants[0].version == self.variants[1].version {
+            return Err(anyhow!("Variants must run different versions"));
+        }
+        if self.variants.iter().any(|v| v.capital_fraction <= Decimal::ZERO) {
+            return Err(anyhow!("Capital fractions must be positive"));
+        }
+        let total: Decimal = self.variants.iter().map(|v| v.capital_fraction).sum();
+        if total > Decimal::ONE {
+            return Err(anyhow!("Capital fractions sum to {}, more than 1", total));
+        }
+        if self.total_capital <= Decimal::ZERO {
+            return Err(anyhow!("Total capital must be positive"));
+        }
+        if self.position_fraction <= Decimal::ZERO || self.position_fraction > Decimal::ONE {
+            return Err(anyhow!("Position fraction must be in (0, 1]"));
+        }
+        Ok(())
+    }
+
+    pub fn variant(&self, label: &str) -> Option<&VariantConfig> {
+        self.variants.iter().find(|v| v.label == label)
+    }
+
+    pub fn variant_capital(&self, variant: &VariantConfig) -> Decimal {
+        self.total_capital * variant.capital_fraction
+    }
+
+    /// 订单标签：策略版本标签 + A/B测试标签
+    pub fn tags(&self, variant: &VariantConfig) -> Vec<String> {
+        vec![
+            strategy_tag(&self.strategy, &variant.version),
+            format!("ab:{}:{}", self.test_id, variant.label),
+        ]
+    }
+
+    /// 按信号强度和版本资金计算下单数量
+    pub fn order_quantity(&self, variant: &VariantConfig, strength: Decimal, price: Decimal) -> Decimal {
+        if price <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let strength = strength.clamp(Decimal::ZERO, Decimal::ONE);
+        (self.variant_capital(variant) * self.position_fraction * strength / price).round_dp(8)
+    }
+}
+
+#[derive(Debug, Default)]
+struct VariantPerformance {
+    pnl: Vec<Decimal>,
+    orders: u64,
+}
+
+/// A/B资金分配器
+///
+/// 两个策略版本并行运行，各自使用分配的资金下单，订单带有各自的标签，
+/// 按周期记录盈亏后生成对比报告。
+pub struct ABAllocator {
+    config: ABTestConfig,
+    sink: Arc<dyn OrderSink>,
+    performance: RwLock<HashMap<String, VariantPerformance>>,
+}
+
+impl ABAllocator {
+    pub fn new(config: ABTestConfig, sink: Arc<dyn OrderSink>) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            sink,
+            performance: RwLock::new(HashMap::new()),
+        })
+    }
+
+    pub fn config(&self) -> &ABTestConfig {
+        &self.config
+    }
+
+    /// 将某个版本产生的信号转为订单
+    pub async fn route(&self, label: &str, signal: &TradingSignal) -> Result<Option<SubmittedOrder>> {
+        let variant = self
+            .config
+            .variant(label)
+            .ok_or_else(|| anyhow!("Unknown variant {} in test {}", label, self.config.test_id))?;
+
+        let quantity = self
+            .config
+            .order_quantity(variant, signal.strength, signal.price);
+        if quantity.is_zero() {
+            return Ok(None);
+        }
+        let Some(intent) = OrderIntent::from_signal(signal, quantity, self.config.tags(variant)) else {
+            return Ok(None);
+        };
+
+        let order = self.sink.submit(&intent).await?;
+        self.performance
+            .write()
+            .await
+            .entry(label.to_string())
+            .or_default()
+            .orders += 1;
+        info!(
+            "A/B test {} routed {} signal to order {}",
+            self.config.test_id, label, order.id
+        );
+        Ok(Some(order))
+    }
+
+    /// 记录某版本一个周期的盈亏
+    pub async fn record_pnl(&self, label: &str, pnl: Decimal) -> Result<()> {
+        if self.config.variant(label).is_none() {
+            return Err(anyhow!("Unknown variant {} in test {}", label, self.config.test_id));
+        }
+        self.performance
+            .write()
+            .await
+            .entry(label.to_string())
+            .or_default()
+            .pnl
+            .push(pnl);
+        Ok(())
+    }
+
+    /// 生成对比报告
+    pub async fn report(&self) -> ABComparisonReport {
+        let performance = self.performance.read().await;
+        let (a, b) = (&self.config.variants[0], &self.config.variants[1]);
+
+        let series = |variant: &VariantConfig| -> (Vec<Decimal>, Vec<f64>) {
+            let capital = self.config.variant_capital(variant);
+            let pnl = performance
+                .get(&variant.label)
+                .map(|p| p.pnl.clone())
+                .unwrap_or_default();
+            let returns = pnl
+                .iter()
+                .filter_map(|p| (*p / capital).to_f64())
+                .collect();
+            (pnl, returns)
+        };
+        let (pnl_a, returns_a) = series(a);
+        let (pnl_b, returns_b) = series(b);
+
+        let stats_a = report::variant_stats(&a.label, a.version, self.config.variant_capital(a), &pnl_a, &returns_a);
+        let stats_b = report::variant_stats(&b.label, b.version, self.config.variant_capital(b), &pnl_b, &returns_b);
+        let significance = report::welch_test(&returns_a, &returns_b, self.config.alpha);
+        let (winner, conclusion) = report::pick_winner(&stats_a, &stats_b, significance.as_ref());
+
+        ABComparisonReport {
+            test_id: self.config.test_id.clone(),
+            strategy: self.config.strategy.clone(),
+            variants: vec![stats_a, stats_b],
+            significance,
+            winner,
+            conclusion,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ABTestConfig {
+        ABTestConfig {
+            test_id: "grid-2024".to_string(),
+            strategy: "grid".to_string(),
+            total_capital: Decimal::from(100_000),
+            position_fraction: Decimal::new(10, 2),
+            alpha: 0.05,
+            variants: vec![
+                VariantConfig {
+                    label: "A".to_string(),
+                    version: SemVer::new(1, 0, 0),
+                    capital_fraction: Decimal::new(70, 2),
+                },
+                VariantConfig {
+                    label: "B".to_string(),
+                    version: SemVer::new(1, 1, 0),
+                    capital_fraction: Decimal::new(30, 2),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(config().validate().is_ok());
+
+        let mut over = config();
+        over.variants[1].capital_fraction = Decimal::new(40, 2);
+        assert!(over.validate().is_err());
+
+        let mut same = config();
+        same.variants[1].version = SemVer::new(1, 0, 0);
+        assert!(same.validate().is_err());
+    }
+
+    #[test]
+    fn test_quantity_and_tags() {
+        let config = config();
+        let b = config.variant("B").unwrap();
+        // 100000 * 0.3 * 0.1 * 0.5 / 50000
+        assert_eq!(
+            config.order_quantity(b, Decimal::new(5, 1), Decimal::from(50_000)),
+            Decimal::new(3, 2)
+        );
+        assert_eq!(config.order_quantity(b, Decimal::ONE, Decimal::ZERO), Decimal::ZERO);
+        assert_eq!(config.tags(b), vec!["strategy:grid@1.1.0", "ab:grid-2024:B"]);
+    }
+}
@@ -0,0 +1,285 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::registry::SemVer;
+
+/// 年化系数，按日收益计算
+const PERIODS_PER_YEAR: f64 = 365.0;
+/// 判定胜出需要的最少样本数
+pub const MIN_PERIODS: usize = 30;
+
+/// 单个版本的表现
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantStats {
+    pub label: String,
+    pub version: SemVer,
+    pub capital: Decimal,
+    pub total_pnl: Decimal,
+    pub periods: usize,
+    pub mean_return: f64,
+    pub volatility: f64,
+    /// 年化夏普比率
+    pub sharpe: f64,
+    /// 最大回撤（相对分配资金的比例）
+    pub max_drawdown: f64,
+}
+
+/// 两个版本的收益差异检验
+#[derive(Debug, Clone, Serialize)]
+pub struct SignificanceTest {
+    /// Welch t统计量（A - B）
+    pub t_statistic: f64,
+    /// Welch–Satterthwaite自由度
+    pub degrees_of_freedom: f64,
+    /// 双侧p值（Student t分布）
+    pub p_value: f64,
+    pub significant: bool,
+}
+
+/// A/B对比报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ABComparisonReport {
+    pub test_id: String,
+    pub strategy: String,
+    pub variants: Vec<VariantStats>,
+    pub significance: Option<SignificanceTest>,
+    /// 胜出版本标签，差异不显著时为None
+    pub winner: Option<String>,
+    pub conclusion: String,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// 样本方差
+fn variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+}
+
+/// 年化夏普比率（无风险利率按0计）
+pub fn sharpe_ratio(returns: &[f64]) -> f64 {
+    let sd = variance(returns).sqrt();
+    if sd == 0.0 {
+        0.0
+    } else {
+        mean(returns) / sd * PERIODS_PER_YEAR.sqrt()
+    }
+}
+
+/// 按收益序列复利计算的最大回撤
+pub fn max_drawdown(returns: &[f64]) -> f64 {
+    let mut equity = 1.0;
+    let mut peak = 1.0;
+    let mut drawdown: f64 = 0.0;
+    for r in returns {
+        equity *= 1.0 + r;
+        peak = f64::max(peak, equity);
+        drawdown = drawdown.max((peak - equity) / peak);
+    }
+    drawdown
+}
+
+/// ln Γ(x)（Lanczos近似，g=7）
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // 反射公式
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(COEFFS[0], |acc, (i, c)| acc + c / (x + i as f64));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// 不完全Beta函数的连分式展开（修正Lentz法）
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        h *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// 正则化不完全Beta函数 I_x(a, b)
+fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// 自由度为 `df` 的Student t分布下 |T| ≥ |t| 的双侧概率
+fn student_t_p_value(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / (df + t * t), df / 2.0, 0.5)
+}
+
+/// Welch t检验，样本不足时返回None
+pub fn welch_test(a: &[f64], b: &[f64], alpha: f64) -> Option<SignificanceTest> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let (se_a, se_b) = (variance(a) / n_a, variance(b) / n_b);
+    let se = (se_a + se_b).sqrt();
+    if se == 0.0 {
+        return None;
+    }
+    let t_statistic = (mean(a) - mean(b)) / se;
+    // 小样本下正态近似会高估显著性，按Welch–Satterthwaite自由度取t分布
+    let degrees_of_freedom = (se_a + se_b).powi(2) / (se_a.powi(2) / (n_a - 1.0) + se_b.powi(2) / (n_b - 1.0));
+    let p_value = student_t_p_value(t_statistic, degrees_of_freedom);
+    Some(SignificanceTest {
+        t_statistic,
+        degrees_of_freedom,
+        p_value,
+        significant: p_value < alpha,
+    })
+}
+
+pub fn variant_stats(label: &str, version: SemVer, capital: Decimal, pnl: &[Decimal], returns: &[f64]) -> VariantStats {
+    VariantStats {
+        label: label.to_string(),
+        version,
+        capital,
+        total_pnl: pnl.iter().sum(),
+        periods: returns.len(),
+        mean_return: mean(returns),
+        volatility: variance(returns).sqrt(),
+        sharpe: sharpe_ratio(returns),
+        max_drawdown: max_drawdown(returns),
+    }
+}
+
+/// 根据检验结果选择胜出版本
+pub fn pick_winner(
+    a: &VariantStats,
+    b: &VariantStats,
+    significance: Option<&SignificanceTest>,
+) -> (Option<String>, String) {
+    if a.periods.min(b.periods) < MIN_PERIODS {
+        return (
+            None,
+            format!("Need at least {} periods per variant before deciding", MIN_PERIODS),
+        );
+    }
+    match significance {
+        Some(test) if test.significant => {
+            let winner = if test.t_statistic > 0.0 { a } else { b };
+            (
+                Some(winner.label.clone()),
+                format!(
+                    "{} ({}) outperforms with p = {:.4}",
+                    winner.label, winner.version, test.p_value
+                ),
+            )
+        }
+        Some(test) => (
+            None,
+            format!("Difference is not significant (p = {:.4})", test.p_value),
+        ),
+        None => (None, "Not enough return variance to compare".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drawdown_and_sharpe() {
+        let returns = [0.1, -0.5, 0.2];
+        assert!((max_drawdown(&returns) - 0.5).abs() < 1e-12);
+        assert_eq!(sharpe_ratio(&[0.01; 10]), 0.0);
+        assert!(sharpe_ratio(&[0.01, 0.02, 0.0, 0.01]) > 0.0);
+    }
+
+    #[test]
+    fn test_student_t_p_value() {
+        assert!((student_t_p_value(0.0, 10.0) - 1.0).abs() < 1e-9);
+        assert!((student_t_p_value(2.228, 10.0) - 0.05).abs() < 1e-4);
+        assert!((student_t_p_value(-2.228, 10.0) - 0.05).abs() < 1e-4);
+        assert!((student_t_p_value(12.706, 1.0) - 0.05).abs() < 1e-4);
+        // 自由度很大时接近正态分布
+        assert!((student_t_p_value(1.96, 1e6) - 0.05).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_small_sample_not_significant() {
+        // 正态近似下 p≈0.013，按自由度2的t分布 p≈0.13
+        let a = [0.045, 0.025];
+        let b = [-0.01, 0.01];
+        let test = welch_test(&a, &b, 0.05).unwrap();
+        assert!((test.degrees_of_freedom - 2.0).abs() < 1e-9);
+        assert!(!test.significant);
+    }
+
+    #[test]
+    fn test_winner_requires_significance() {
+        let a: Vec<f64> = (0..40).map(|i| 0.01 + (i % 3) as f64 * 0.001).collect();
+        let b: Vec<f64> = (0..40).map(|i| -0.01 + (i % 3) as f64 * 0.001).collect();
+        let version = SemVer::new(1, 0, 0);
+        let stats_a = variant_stats("A", version, Decimal::from(1000), &[], &a);
+        let stats_b = variant_stats("B", SemVer::new(1, 1, 0), Decimal::from(1000), &[], &b);
+
+        let test = welch_test(&a, &b, 0.05).unwrap();
+        assert!(test.significant);
+        let (winner, _) = pick_winner(&stats_a, &stats_b, Some(&test));
+        assert_eq!(winner.as_deref(), Some("A"));
+
+        let short = variant_stats("A", version, Decimal::from(1000), &[], &a[..10]);
+        let (winner, _) = pick_winner(&short, &stats_b, Some(&test));
+        assert!(winner.is_none());
+    }
+}
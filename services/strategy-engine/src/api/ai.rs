@@ -0,0 +1,116 @@
+use axum::{extract::State, routing::post, Json, Router};
+use serde::{Deserialize, Serialize};
+use shared_models::common::ApiResponse;
+use std::sync::Arc;
+
+use super::ApiError;
+use crate::ai::gateway::AIRequestGateway;
+use crate::ai::risk_predictor::RiskPrediction;
+use crate::ai::strategy_generator::{
+    MarketAnalysis, MarketContext, PortfolioContext, RiskContext, TimeHorizon,
+};
+use crate::ai::{AIMarketAnalyzer, AIRiskPredictor};
+use crate::models::Symbol;
+
+/// AI接口共享状态
+#[derive(Clone)]
+pub struct AIApiState {
+    pub market_analyzer: Arc<AIMarketAnalyzer>,
+    pub risk_predictor: Arc<AIRiskPredictor>,
+    pub gateway: AIRequestGateway,
+}
+
+/// 市场分析请求
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalyzeRequest {
+    pub symbol: String,
+    pub horizon: TimeHorizon,
+}
+
+/// 市场分析响应
+#[derive(Debug, Serialize)]
+pub struct AnalyzeResponse {
+    pub symbol: String,
+    pub model: String,
+    /// 是否命中缓存
+    pub cached: bool,
+    pub analysis: MarketAnalysis,
+}
+
+/// 组合风险预测请求
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RiskRequest {
+    pub portfolio: PortfolioContext,
+    #[serde(default)]
+    pub risk: Option<RiskContext>,
+    /// 持仓标的的市场上下文，缺失的标的按默认波动率估算
+    #[serde(default)]
+    pub market_contexts: Vec<MarketContext>,
+}
+
+/// 组合风险预测响应
+#[derive(Debug, Serialize)]
+pub struct RiskResponse {
+    pub cached: bool,
+    pub prediction: RiskPrediction,
+}
+
+pub fn routes(state: AIApiState) -> Router {
+    Router::new()
+        .route("/api/v1/ai/analyze", post(analyze))
+        .route("/api/v1/ai/risk", post(predict_risk))
+        .with_state(state)
+}
+
+/// AI市场分析
+pub async fn analyze(
+    State(state): State<AIApiState>,
+    Json(request): Json<AnalyzeRequest>,
+) -> Result<Json<ApiResponse<AnalyzeResponse>>, ApiError> {
+    let symbol = Symbol::from_string(&request.symbol)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid symbol: {}", request.symbol)))?;
+    let model = state.market_analyzer.model_name().to_string();
+
+    // 缓存键包含模型名称，切换模型后不会复用旧结果
+    let key = (&model, &request);
+    let response = state
+        .gateway
+        .call("analyze", &key, || {
+            state.market_analyzer.analyze(&symbol, &request.horizon)
+        })
+        .await?;
+
+    Ok(Json(ApiResponse::success(AnalyzeResponse {
+        symbol: request.symbol,
+        model,
+        cached: response.cached,
+        analysis: response.value,
+    })))
+}
+
+/// AI组合风险预测
+pub async fn predict_risk(
+    State(state): State<AIApiState>,
+    Json(request): Json<RiskRequest>,
+) -> Result<Json<ApiResponse<RiskResponse>>, ApiError> {
+    if request.portfolio.positions.is_empty() {
+        return Err(ApiError::BadRequest("Portfolio has no positions".to_string()));
+    }
+
+    let key = (state.risk_predictor.model_name(), &request);
+    let response = state
+        .gateway
+        .call("risk", &key, || {
+            state.risk_predictor.predict(
+                &request.portfolio,
+                request.risk.as_ref(),
+                &request.market_contexts,
+            )
+        })
+        .await?;
+
+    Ok(Json(ApiResponse::success(RiskResponse {
+        cached: response.cached,
+        prediction: response.value,
+    })))
+}
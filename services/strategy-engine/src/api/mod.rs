@@ -0,0 +1,59 @@
+pub mod ai;
+pub mod quality;
+
+use shared_models::common::ApiResponse;
+
+use crate::ai::gateway::AIGatewayError;
+
+/// API错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Internal server error: {0}")]
+    InternalServerError(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    #[error("Request timeout: {0}")]
+    Timeout(String),
+}
+
+impl ApiError {
+    /// 转换为HTTP状态码
+    pub fn status_code(&self) -> axum::http::StatusCode {
+        match self {
+            ApiError::BadRequest(_) => axum::http::StatusCode::BAD_REQUEST,
+            ApiError::InternalServerError(_) => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::ServiceUnavailable(_) => axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Timeout(_) => axum::http::StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        ApiError::InternalServerError(err.to_string())
+    }
+}
+
+impl From<AIGatewayError> for ApiError {
+    fn from(err: AIGatewayError) -> Self {
+        match err {
+            AIGatewayError::QueueFull(_) => ApiError::ServiceUnavailable(err.to_string()),
+            AIGatewayError::Timeout(_) => ApiError::Timeout(err.to_string()),
+            AIGatewayError::Failed(_) => ApiError::InternalServerError(err.to_string()),
+            AIGatewayError::QuotaExceeded(_) => ApiError::ServiceUnavailable(err.to_string()),
+        }
+    }
+}
+
+impl axum::response::IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        let status = self.status_code();
+        let body = ApiResponse::<()>::error(self.to_string());
+        (status, axum::Json(body)).into_response()
+    }
+}
@@ -0,0 +1,28 @@
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use shared_models::common::ApiResponse;
+use std::sync::Arc;
+
+use super::ApiError;
+use crate::quality::{SignalQualityReport, SignalScoreboard};
+
+pub fn routes(scoreboard: Arc<SignalScoreboard>) -> Router {
+    Router::new()
+        .route("/api/v1/strategies/:id/signal-quality", get(signal_quality))
+        .with_state(scoreboard)
+}
+
+/// 策略信号在各周期上的命中率、平均收益和衰减
+pub async fn signal_quality(
+    State(scoreboard): State<Arc<SignalScoreboard>>,
+    Path(strategy_id): Path<String>,
+) -> Result<Json<ApiResponse<SignalQualityReport>>, ApiError> {
+    if strategy_id.trim().is_empty() {
+        return Err(ApiError::BadRequest("Strategy id is required".to_string()));
+    }
+    let report = scoreboard.report(&strategy_id).await?;
+    Ok(Json(ApiResponse::success(report)))
+}
@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared_utils::{feature_flags::flags, FeatureFlags};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::models::{SignalType, TradingSignal};
+
+/// 由信号生成的下单意图
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderIntent {
+    pub symbol: String,
+    /// BUY / SELL
+    pub side: String,
+    pub order_type: String,
+    pub quantity: Decimal,
+    pub price: Option<Decimal>,
+    pub client_order_id: String,
+    pub tags: Vec<String>,
+}
+
+impl OrderIntent {
+    /// 信号转为市价单，非买卖信号返回None
+    pub fn from_signal(signal: &TradingSignal, quantity: Decimal, tags: Vec<String>) -> Option<Self> {
+        let side = match signal.signal_type {
+            SignalType::Buy => "BUY",
+            SignalType::Sell => "SELL",
+            _ => return None,
+        };
+        Some(Self {
+            symbol: signal.symbol.to_string(),
+            side: side.to_string(),
+            order_type: "MARKET".to_string(),
+            quantity,
+            price: None,
+            client_order_id: format!("sig-{}", signal.id.simple()),
+            tags,
+        })
+    }
+}
+
+/// 已提交的订单
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmittedOrder {
+    pub id: Uuid,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+}
+
+/// 订单提交通道
+#[async_trait]
+pub trait OrderSink: Send + Sync {
+    async fn submit(&self, intent: &OrderIntent) -> Result<SubmittedOrder>;
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateOrderResponse {
+    success: bool,
+    data: Option<SubmittedOrder>,
+    error: Option<String>,
+}
+
+/// 信号到订单的桥接，通过交易引擎接口下单
+pub struct SignalOrderBridge {
+    base_url: String,
+    client: reqwest::Client,
+    /// 配置后由 ai_signal_execution 开关控制是否下单
+    feature_flags: Option<FeatureFlags>,
+}
+
+impl SignalOrderBridge {
+    pub fn new(base_url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+            feature_flags: None,
+        }
+    }
+
+    /// 按功能开关灰度信号自动下单
+    pub fn with_feature_flags(mut self, feature_flags: FeatureFlags) -> Self {
+        self.feature_flags = Some(feature_flags);
+        self
+    }
+}
+
+#[async_trait]
+impl OrderSink for SignalOrderBridge {
+    async fn submit(&self, intent: &OrderIntent) -> Result<SubmittedOrder> {
+        if let Some(feature_flags) = &self.feature_flags {
+            if !feature_flags.is_enabled(flags::AI_SIGNAL_EXECUTION).await {
+                return Err(anyhow!(
+                    "Signal execution is disabled, order {} not submitted",
+                    intent.client_order_id
+                ));
+            }
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/v1/orders", self.base_url))
+            .json(intent)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Trading engine rejected order {}: {}",
+                intent.client_order_id,
+                response.status()
+            ));
+        }
+
+        let body: CreateOrderResponse = response.json().await?;
+        match body.data {
+            Some(order) if body.success => Ok(order),
+            _ => Err(anyhow!(
+                "Trading engine rejected order {}: {}",
+                intent.client_order_id,
+                body.error.unwrap_or_default()
+            )),
+        }
+    }
+}
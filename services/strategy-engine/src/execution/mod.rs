@@ -0,0 +1,3 @@
+pub mod bridge;
+
+pub use bridge::{OrderIntent, OrderSink, SignalOrderBridge, SubmittedOrder};
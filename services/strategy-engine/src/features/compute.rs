@@ -0,0 +1,152 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use super::{FeatureDefinition, FeatureKind, FeatureValue};
+use crate::ai::strategy_generator::MarketContext;
+
+/// 从市场上下文计算标准特征
+pub struct FeatureComputer;
+
+fn definition(name: &str, kind: FeatureKind, description: &str) -> FeatureDefinition {
+    FeatureDefinition {
+        name: name.to_string(),
+        kind,
+        version: 1,
+        description: description.to_string(),
+    }
+}
+
+pub fn sma(closes: &[f64], period: usize) -> Option<f64> {
+    if closes.len() < period || period == 0 {
+        return None;
+    }
+    Some(closes[closes.len() - period..].iter().sum::<f64>() / period as f64)
+}
+
+pub fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if closes.len() <= period {
+        return None;
+    }
+    let (gain, loss) = closes[closes.len() - period - 1..]
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .fold((0.0, 0.0), |(gain, loss), change| {
+            if change > 0.0 {
+                (gain + change, loss)
+            } else {
+                (gain, loss - change)
+            }
+        });
+    if loss == 0.0 {
+        return Some(100.0);
+    }
+    Some(100.0 - 100.0 / (1.0 + gain / loss))
+}
+
+pub fn volatility(closes: &[f64], period: usize) -> Option<f64> {
+    if closes.len() <= period {
+        return None;
+    }
+    let returns: Vec<f64> = closes[closes.len() - period - 1..]
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect();
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    Some((returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64).sqrt())
+}
+
+pub fn momentum(closes: &[f64], period: usize) -> Option<f64> {
+    let base = *closes.get(closes.len().checked_sub(period + 1)?)?;
+    (base > 0.0).then(|| closes[closes.len() - 1] / base - 1.0)
+}
+
+impl FeatureComputer {
+    /// 标准特征定义
+    pub fn definitions() -> Vec<FeatureDefinition> {
+        vec![
+            definition("sma_10", FeatureKind::Indicator, "10周期收盘价均线"),
+            definition("sma_30", FeatureKind::Indicator, "30周期收盘价均线"),
+            definition("rsi_14", FeatureKind::Indicator, "14周期RSI"),
+            definition("momentum_10", FeatureKind::Indicator, "10周期收益率"),
+            definition("volatility_10", FeatureKind::Volatility, "10周期收益率标准差"),
+            definition("volatility_30", FeatureKind::Volatility, "30周期收益率标准差"),
+            definition("sentiment_score", FeatureKind::Sentiment, "新闻情绪得分（-1~1）"),
+            definition("spread_bps", FeatureKind::Microstructure, "买卖价差（基点）"),
+            definition("microprice_offset", FeatureKind::Microstructure, "微观价格相对中间价偏离"),
+            definition("seasonal_volume_ratio", FeatureKind::Seasonality, "当前小时历史成交量相对均值之比"),
+            definition("seasonal_volatility_ratio", FeatureKind::Seasonality, "当前小时历史振幅相对均值之比"),
+            definition("next_hour_volume_ratio", FeatureKind::Seasonality, "下一小时历史成交量相对均值之比，用于执行排期"),
+        ]
+    }
+
+    /// 计算特征，数据时间取最后一根K线的时间
+    pub fn compute(context: &MarketContext, available_at: i64) -> Vec<FeatureValue> {
+        let Some(event_time) = context.price_history.last().map(|p| p.timestamp) else {
+            return Vec::new();
+        };
+        let closes: Vec<f64> = context
+            .price_history
+            .iter()
+            .filter_map(|p| p.close.to_f64())
+            .collect();
+
+        let mut values: Vec<(&str, Option<Decimal>)> = vec![
+            ("sma_10", sma(&closes, 10).and_then(Decimal::from_f64)),
+            ("sma_30", sma(&closes, 30).and_then(Decimal::from_f64)),
+            ("rsi_14", rsi(&closes, 14).and_then(Decimal::from_f64)),
+            ("momentum_10", momentum(&closes, 10).and_then(Decimal::from_f64)),
+            ("volatility_10", volatility(&closes, 10).and_then(Decimal::from_f64)),
+            ("volatility_30", volatility(&closes, 30).and_then(Decimal::from_f64)),
+            (
+                "sentiment_score",
+                context.news_sentiment.as_ref().map(|s| s.overall_score),
+            ),
+        ];
+        let depth = context.market_microstructure.depth_features();
+        values.push(("spread_bps", depth.get("spread_bps").copied()));
+        values.push(("microprice_offset", depth.get("microprice_offset").copied()));
+
+        let microstructure = &context.market_microstructure;
+        let current = microstructure.seasonal_ratios(event_time);
+        values.push(("seasonal_volume_ratio", current.map(|(volume, _)| volume)));
+        values.push(("seasonal_volatility_ratio", current.map(|(_, volatility)| volatility)));
+        values.push((
+            "next_hour_volume_ratio",
+            microstructure
+                .seasonal_ratios(event_time + 3_600_000)
+                .map(|(volume, _)| volume),
+        ));
+
+        values
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value.map(|value| FeatureValue {
+                    symbol: context.symbol.to_string(),
+                    name: name.to_string(),
+                    value: value.round_dp(8),
+                    event_time,
+                    available_at: available_at.max(event_time),
+                    version: 1,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indicator_values() {
+        let closes: Vec<f64> = (1..=31).map(|i| i as f64).collect();
+        assert_eq!(sma(&closes, 10), Some(26.5));
+        assert_eq!(rsi(&closes, 14), Some(100.0));
+        assert_eq!(momentum(&closes, 10), Some(31.0 / 21.0 - 1.0));
+        assert!(sma(&closes[..5], 10).is_none());
+
+        let flat = vec![10.0; 20];
+        assert_eq!(volatility(&flat, 10), Some(0.0));
+    }
+}
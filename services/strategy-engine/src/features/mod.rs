@@ -0,0 +1,141 @@
+pub mod compute;
+pub mod offline;
+pub mod online;
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+pub use compute::FeatureComputer;
+pub use offline::{MemoryFeatureLog, OfflineFeatureStore};
+pub use online::OnlineFeatureStore;
+
+/// 特征类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureKind {
+    Indicator,
+    Volatility,
+    Sentiment,
+    Microstructure,
+    /// 按星期和小时统计的日内规律
+    Seasonality,
+}
+
+/// 特征定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureDefinition {
+    pub name: String,
+    pub kind: FeatureKind,
+    /// 计算逻辑版本，逻辑变化时递增
+    pub version: u32,
+    pub description: String,
+}
+
+/// 一条特征值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureValue {
+    pub symbol: String,
+    pub name: String,
+    pub value: Decimal,
+    /// 特征描述的数据时间（毫秒）
+    pub event_time: i64,
+    /// 特征可被使用的时间（毫秒），即计算完成并写入的时间
+    pub available_at: i64,
+    pub version: u32,
+}
+
+/// 某一时刻的特征快照，name -> value
+pub type FeatureVector = BTreeMap<String, Decimal>;
+
+/// 训练样本：时间点及当时可见的特征
+#[derive(Debug, Clone, Serialize)]
+pub struct PointInTimeRow {
+    pub timestamp: i64,
+    pub features: FeatureVector,
+}
+
+/// 特征存储
+///
+/// 离线部分保存完整历史，按可用时间做时点查询，回测只能看到当时已经算出的特征；
+/// 在线部分只保存每个特征的最新值，供实盘策略低延迟读取。
+#[derive(Clone)]
+pub struct FeatureStore {
+    definitions: Arc<HashMap<String, FeatureDefinition>>,
+    offline: Arc<dyn OfflineFeatureStore>,
+    online: Option<OnlineFeatureStore>,
+}
+
+impl FeatureStore {
+    pub fn new(
+        definitions: Vec<FeatureDefinition>,
+        offline: Arc<dyn OfflineFeatureStore>,
+        online: Option<OnlineFeatureStore>,
+    ) -> Self {
+        Self {
+            definitions: Arc::new(
+                definitions
+                    .into_iter()
+                    .map(|d| (d.name.clone(), d))
+                    .collect(),
+            ),
+            offline,
+            online,
+        }
+    }
+
+    pub fn definitions(&self) -> Vec<FeatureDefinition> {
+        let mut definitions: Vec<_> = self.definitions.values().cloned().collect();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
+    /// 写入特征，未定义的特征会被拒绝
+    pub async fn write(&self, values: Vec<FeatureValue>) -> Result<()> {
+        if let Some(unknown) = values.iter().find(|v| !self.definitions.contains_key(&v.name)) {
+            return Err(anyhow::anyhow!("Unknown feature: {}", unknown.name));
+        }
+
+        self.offline.append(&values).await?;
+        if let Some(online) = &self.online {
+            online.update(&values).await?;
+        }
+        Ok(())
+    }
+
+    /// 时点查询：返回as_of时刻已可用的各特征最新值
+    pub async fn point_in_time(&self, symbol: &str, names: &[String], as_of: i64) -> Result<FeatureVector> {
+        self.offline.as_of(symbol, names, as_of).await
+    }
+
+    /// 为一组时间点生成无前视偏差的特征样本
+    pub async fn training_set(
+        &self,
+        symbol: &str,
+        names: &[String],
+        timestamps: &[i64],
+    ) -> Result<Vec<PointInTimeRow>> {
+        let mut rows = Vec::with_capacity(timestamps.len());
+        for &timestamp in timestamps {
+            rows.push(PointInTimeRow {
+                timestamp,
+                features: self.offline.as_of(symbol, names, timestamp).await?,
+            });
+        }
+        Ok(rows)
+    }
+
+    /// 在线查询最新特征，未启用在线存储时回退到离线存储
+    pub async fn latest(&self, symbol: &str, names: &[String]) -> Result<FeatureVector> {
+        match &self.online {
+            Some(online) => online.latest(symbol, names).await,
+            None => {
+                self.offline
+                    .as_of(symbol, names, chrono::Utc::now().timestamp_millis())
+                    .await
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use super::{FeatureValue, FeatureVector};
+
+/// 离线特征存储
+#[async_trait]
+pub trait OfflineFeatureStore: Send + Sync {
+    async fn append(&self, values: &[FeatureValue]) -> Result<()>;
+
+    /// 返回as_of时刻已可用的各特征最新值
+    async fn as_of(&self, symbol: &str, names: &[String], as_of: i64) -> Result<FeatureVector>;
+
+    /// 按数据时间查询特征历史
+    async fn history(&self, symbol: &str, name: &str, start: i64, end: i64) -> Result<Vec<FeatureValue>>;
+}
+
+/// 内存特征日志，回测和测试使用
+///
+/// 每个(symbol, feature)的记录按可用时间排序，
+/// 同一数据时间的修正值追加写入，时点查询只能看到当时已写入的版本。
+#[derive(Default)]
+pub struct MemoryFeatureLog {
+    entries: RwLock<HashMap<(String, String), Vec<FeatureValue>>>,
+}
+
+impl MemoryFeatureLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// 在按可用时间排序的记录中选出as_of时刻可见的最新值
+fn visible_at(records: &[FeatureValue], as_of: i64) -> Option<&FeatureValue> {
+    let visible = records.partition_point(|v| v.available_at <= as_of);
+    records[..visible]
+        .iter()
+        .filter(|v| v.event_time <= as_of)
+        .max_by_key(|v| (v.event_time, v.available_at))
+}
+
+#[async_trait]
+impl OfflineFeatureStore for MemoryFeatureLog {
+    async fn append(&self, values: &[FeatureValue]) -> Result<()> {
+        let mut entries = self.entries.write().await;
+        for value in values {
+            let records = entries
+                .entry((value.symbol.clone(), value.name.clone()))
+                .or_default();
+            let index = records.partition_point(|v| v.available_at <= value.available_at);
+            records.insert(index, value.clone());
+        }
+        Ok(())
+    }
+
+    async fn as_of(&self, symbol: &str, names: &[String], as_of: i64) -> Result<FeatureVector> {
+        let entries = self.entries.read().await;
+        let mut vector = FeatureVector::new();
+        for name in names {
+            let value = entries
+                .get(&(symbol.to_string(), name.clone()))
+                .and_then(|records| visible_at(records, as_of));
+            if let Some(value) = value {
+                vector.insert(name.clone(), value.value);
+            }
+        }
+        Ok(vector)
+    }
+
+    async fn history(&self, symbol: &str, name: &str, start: i64, end: i64) -> Result<Vec<FeatureValue>> {
+        let entries = self.entries.read().await;
+        let mut values: Vec<FeatureValue> = entries
+            .get(&(symbol.to_string(), name.to_string()))
+            .map(|records| {
+                records
+                    .iter()
+                    .filter(|v| v.event_time >= start && v.event_time <= end)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        values.sort_by_key(|v| (v.event_time, v.available_at));
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn value(name: &str, value: i64, event_time: i64, available_at: i64) -> FeatureValue {
+        FeatureValue {
+            symbol: "BTCUSDT".to_string(),
+            name: name.to_string(),
+            value: Decimal::from(value),
+            event_time,
+            available_at,
+            version: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_point_in_time_hides_future_values() {
+        let log = MemoryFeatureLog::new();
+        log.append(&[
+            value("sma_10", 100, 1_000, 1_500),
+            value("sma_10", 110, 2_000, 2_500),
+            // 数据时间2000的修正值，3000才写入
+            value("sma_10", 111, 2_000, 3_000),
+        ])
+        .await
+        .unwrap();
+
+        let names = vec!["sma_10".to_string(), "rsi_14".to_string()];
+        // 数据已产生但特征尚未算出
+        assert!(log.as_of("BTCUSDT", &names, 1_200).await.unwrap().is_empty());
+        assert_eq!(log.as_of("BTCUSDT", &names, 2_000).await.unwrap()["sma_10"], Decimal::from(100));
+        assert_eq!(log.as_of("BTCUSDT", &names, 2_600).await.unwrap()["sma_10"], Decimal::from(110));
+        assert_eq!(log.as_of("BTCUSDT", &names, 3_000).await.unwrap()["sma_10"], Decimal::from(111));
+    }
+
+    #[tokio::test]
+    async fn test_history_is_ordered_by_event_time() {
+        let log = MemoryFeatureLog::new();
+        log.append(&[value("vol", 2, 2_000, 2_100), value("vol", 1, 1_000, 2_200)])
+            .await
+            .unwrap();
+
+        let history = log.history("BTCUSDT", "vol", 0, 5_000).await.unwrap();
+        assert_eq!(history.iter().map(|v| v.event_time).collect::<Vec<_>>(), vec![1_000, 2_000]);
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use redis::aio::ConnectionManager;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use super::{FeatureValue, FeatureVector};
+
+/// 在线特征存储
+///
+/// 每个交易对一个Redis哈希，字段为特征名，值为"event_time|value"。
+/// 只在数据时间更新时覆盖，乱序到达的旧值不会替换新值。
+#[derive(Clone)]
+pub struct OnlineFeatureStore {
+    redis: ConnectionManager,
+    key_prefix: String,
+}
+
+impl OnlineFeatureStore {
+    pub fn new(redis: ConnectionManager) -> Self {
+        Self {
+            redis,
+            key_prefix: "features:online".to_string(),
+        }
+    }
+
+    fn key(&self, symbol: &str) -> String {
+        format!("{}:{}", self.key_prefix, symbol)
+    }
+
+    fn decode(raw: &str) -> Option<(i64, Decimal)> {
+        let (event_time, value) = raw.split_once('|')?;
+        Some((event_time.parse().ok()?, value.parse().ok()?))
+    }
+
+    pub async fn update(&self, values: &[FeatureValue]) -> Result<()> {
+        use redis::AsyncCommands;
+
+        let mut by_symbol: HashMap<&str, Vec<&FeatureValue>> = HashMap::new();
+        for value in values {
+            by_symbol.entry(&value.symbol).or_default().push(value);
+        }
+
+        let mut conn = self.redis.clone();
+        for (symbol, values) in by_symbol {
+            let key = self.key(symbol);
+            let current: HashMap<String, String> = conn.hgetall(&key).await?;
+
+            let mut latest: HashMap<&str, &FeatureValue> = HashMap::new();
+            for value in values {
+                let stored = current
+                    .get(&value.name)
+                    .and_then(|raw| Self::decode(raw))
+                    .map(|(event_time, _)| event_time);
+                if stored.map(|t| value.event_time < t).unwrap_or(false) {
+                    continue;
+                }
+                match latest.get(value.name.as_str()) {
+                    Some(existing) if existing.event_time > value.event_time => {}
+                    _ => {
+                        latest.insert(&value.name, value);
+                    }
+                }
+            }
+
+            let fields: Vec<(String, String)> = latest
+                .into_values()
+                .map(|v| (v.name.clone(), format!("{}|{}", v.event_time, v.value)))
+                .collect();
+            if !fields.is_empty() {
+                let _: () = conn.hset_multiple(&key, &fields).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn latest(&self, symbol: &str, names: &[String]) -> Result<FeatureVector> {
+        if names.is_empty() {
+            return Ok(FeatureVector::new());
+        }
+
+        let mut conn = self.redis.clone();
+        let raw: Vec<Option<String>> = redis::cmd("HMGET")
+            .arg(self.key(symbol))
+            .arg(names)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(names
+            .iter()
+            .zip(raw)
+            .filter_map(|(name, raw)| {
+                raw.and_then(|raw| Self::decode(&raw))
+                    .map(|(_, value)| (name.clone(), value))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_online_value() {
+        assert_eq!(
+            OnlineFeatureStore::decode("1700000000000|0.25"),
+            Some((1_700_000_000_000, Decimal::new(25, 2)))
+        );
+        assert_eq!(OnlineFeatureStore::decode("bad"), None);
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::Message;
+use serde::{Deserialize, Serialize};
+use shared_models::market::{Kline, MarketTick};
+use shared_protocols::kafka::{KafkaMessage, KafkaTopics};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use super::windows::RollingWindows;
+
+/// 实时行情消费配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketFeedConfig {
+    pub enabled: bool,
+    pub brokers: Vec<String>,
+    pub group_id: String,
+    /// 没有已提交位点时从最新还是最早的消息开始消费
+    pub auto_offset_reset: String,
+    /// 消费出错后的等待时间（毫秒）
+    pub error_backoff_ms: u64,
+}
+
+impl Default for MarketFeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            brokers: vec!["localhost:9092".to_string()],
+            group_id: "strategy-engine".to_string(),
+            auto_offset_reset: "latest".to_string(),
+            error_backoff_ms: 1_000,
+        }
+    }
+}
+
+/// 实时行情消费者
+///
+/// 消费 market.klines 和 market.ticks 写入滚动窗口；无法解析的消息记录后跳过，
+/// 不阻塞后续消息。
+pub struct MarketDataConsumer {
+    consumer: StreamConsumer,
+    windows: Arc<RollingWindows>,
+    error_backoff: Duration,
+}
+
+impl MarketDataConsumer {
+    pub fn new(config: &MarketFeedConfig, windows: Arc<RollingWindows>) -> Result<Self> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", config.brokers.join(","))
+            .set("group.id", &config.group_id)
+            .set("auto.offset.reset", &config.auto_offset_reset)
+            .set("enable.auto.commit", "true")
+            .create()?;
+        consumer.subscribe(&[KafkaTopics::MARKET_KLINES, KafkaTopics::MARKET_TICKS])?;
+        info!(
+            "Market data consumer {} subscribed to {} and {}",
+            config.group_id,
+            KafkaTopics::MARKET_KLINES,
+            KafkaTopics::MARKET_TICKS
+        );
+
+        Ok(Self {
+            consumer,
+            windows,
+            error_backoff: Duration::from_millis(config.error_backoff_ms),
+        })
+    }
+
+    /// 启动消费任务
+    pub fn start(self) {
+        tokio::spawn(async move {
+            loop {
+                match self.consumer.recv().await {
+                    Ok(message) => {
+                        let Some(payload) = message.payload() else {
+                            continue;
+                        };
+                        if let Err(e) = self.handle(message.topic(), payload).await {
+                            warn!(
+                                "Skipping malformed message on {} at offset {}: {}",
+                                message.topic(),
+                                message.offset(),
+                                e
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        error!("Market data consumer error: {}", e);
+                        tokio::time::sleep(self.error_backoff).await;
+                    }
+                }
+            }
+        });
+    }
+
+    async fn handle(&self, topic: &str, payload: &[u8]) -> Result<()> {
+        match topic {
+            KafkaTopics::MARKET_KLINES => {
+                let message: KafkaMessage<Kline> = serde_json::from_slice(payload)?;
+                self.windows.apply_kline(&message.data).await;
+            }
+            KafkaTopics::MARKET_TICKS => {
+                let message: KafkaMessage<MarketTick> = serde_json::from_slice(payload)?;
+                self.windows.apply_tick(&message.data).await;
+            }
+            _ => debug!("Ignoring message on unexpected topic {}", topic),
+        }
+        Ok(())
+    }
+}
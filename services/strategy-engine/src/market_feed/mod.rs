@@ -0,0 +1,5 @@
+pub mod consumer;
+pub mod windows;
+
+pub use consumer::{MarketDataConsumer, MarketFeedConfig};
+pub use windows::{ClosedCandle, LiveMarketContextProvider, RollingWindowConfig, RollingWindows};
@@ -0,0 +1,348 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared_models::market::{Kline, MarketTick};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::debug;
+
+use crate::ai::context_builder::{compute_indicators, ensure_fresh, volume_profile};
+use crate::ai::market_analyzer::{chart_window, interval_millis, MarketContextProvider};
+use crate::ai::strategy_generator::*;
+use crate::models::Symbol;
+
+/// 滚动窗口配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RollingWindowConfig {
+    /// 每个交易对每个周期保留的K线根数
+    pub max_candles: usize,
+    /// 每个交易对保留的最近tick条数
+    pub max_ticks: usize,
+    /// 最新K线落后当前时间超过多少个周期视为行情中断
+    pub max_candle_lag: i64,
+    /// 成交量分布的价格分桶数
+    pub volume_buckets: usize,
+}
+
+impl Default for RollingWindowConfig {
+    fn default() -> Self {
+        Self {
+            max_candles: 500,
+            max_ticks: 1_000,
+            max_candle_lag: 2,
+            volume_buckets: 24,
+        }
+    }
+}
+
+/// 收盘K线事件，供实盘策略按K线驱动
+#[derive(Debug, Clone)]
+pub struct ClosedCandle {
+    pub symbol: String,
+    pub interval: String,
+    pub candle: PricePoint,
+}
+
+#[derive(Debug, Clone)]
+struct TickSample {
+    timestamp: i64,
+    price: Decimal,
+    bid: Decimal,
+    ask: Decimal,
+    bid_volume: Decimal,
+    ask_volume: Decimal,
+}
+
+#[derive(Default)]
+struct SymbolWindow {
+    /// 周期 -> 按开盘时间排序的K线，最后一根可能尚未收盘
+    candles: HashMap<String, VecDeque<PricePoint>>,
+    ticks: VecDeque<TickSample>,
+}
+
+/// 统一交易对写法，BTC/USDT、btc-usdt 与 BTCUSDT 视为同一交易对
+fn symbol_key(symbol: &str) -> String {
+    symbol.replace(['/', '-'], "").to_uppercase()
+}
+
+/// 按交易对维护的实时K线和tick滚动窗口
+///
+/// K线按开盘时间去重，同一根K线的更新覆盖旧值，乱序到达的旧K线被忽略；
+/// K线收盘时广播给订阅方。
+pub struct RollingWindows {
+    config: RollingWindowConfig,
+    windows: RwLock<HashMap<String, SymbolWindow>>,
+    closed: broadcast::Sender<ClosedCandle>,
+}
+
+impl RollingWindows {
+    pub fn new(config: RollingWindowConfig) -> Self {
+        let (closed, _) = broadcast::channel(1024);
+        Self {
+            config,
+            windows: RwLock::new(HashMap::new()),
+            closed,
+        }
+    }
+
+    pub fn config(&self) -> &RollingWindowConfig {
+        &self.config
+    }
+
+    /// 订阅收盘K线
+    pub fn subscribe_closed(&self) -> broadcast::Receiver<ClosedCandle> {
+        self.closed.subscribe()
+    }
+
+    /// 写入一根K线，返回是否被接受
+    pub async fn apply_kline(&self, kline: &Kline) -> bool {
+        let symbol = symbol_key(&kline.symbol);
+        let interval = kline.interval.to_string();
+        let candle = PricePoint {
+            timestamp: kline.open_time.timestamp_millis(),
+            open: kline.open,
+            high: kline.high,
+            low: kline.low,
+            close: kline.close,
+            volume: kline.volume,
+        };
+
+        {
+            let mut windows = self.windows.write().await;
+            let candles = windows
+                .entry(symbol.clone())
+                .or_default()
+                .candles
+                .entry(interval.clone())
+                .or_default();
+            match candles.back() {
+                Some(last) if candle.timestamp < last.timestamp => {
+                    debug!("Ignoring out-of-order {} {} candle {}", symbol, interval, candle.timestamp);
+                    return false;
+                }
+                Some(last) if candle.timestamp == last.timestamp => {
+                    candles.pop_back();
+                }
+                _ => {}
+            }
+            candles.push_back(candle.clone());
+            while candles.len() > self.config.max_candles {
+                candles.pop_front();
+            }
+        }
+
+        if kline.is_closed {
+            let _ = self.closed.send(ClosedCandle {
+                symbol,
+                interval,
+                candle,
+            });
+        }
+        true
+    }
+
+    /// 写入一条tick
+    pub async fn apply_tick(&self, tick: &MarketTick) {
+        let mut windows = self.windows.write().await;
+        let ticks = &mut windows.entry(symbol_key(&tick.symbol)).or_default().ticks;
+        let timestamp = tick.timestamp.timestamp_millis();
+        if ticks.back().is_some_and(|last| timestamp < last.timestamp) {
+            return;
+        }
+        ticks.push_back(TickSample {
+            timestamp,
+            price: tick.price,
+            bid: tick.bid,
+            ask: tick.ask,
+            bid_volume: tick.bid_volume,
+            ask_volume: tick.ask_volume,
+        });
+        while ticks.len() > self.config.max_ticks {
+            ticks.pop_front();
+        }
+    }
+
+    /// 指定周期最近的 count 根K线
+    pub async fn candles(&self, symbol: &str, interval: &str, count: usize) -> Vec<PricePoint> {
+        let windows = self.windows.read().await;
+        windows
+            .get(&symbol_key(symbol))
+            .and_then(|window| window.candles.get(interval))
+            .map(|candles| candles.iter().skip(candles.len().saturating_sub(count)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 最新成交价，优先取tick，没有tick时取最近K线收盘价
+    pub async fn last_price(&self, symbol: &str) -> Option<Decimal> {
+        let windows = self.windows.read().await;
+        let window = windows.get(&symbol_key(symbol))?;
+        window.ticks.back().map(|tick| tick.price).or_else(|| {
+            window
+                .candles
+                .values()
+                .filter_map(|candles| candles.back())
+                .max_by_key(|candle| candle.timestamp)
+                .map(|candle| candle.close)
+        })
+    }
+
+    /// 由tick计算微观结构：最新买卖价差和盘口挂单量，成交频率为每分钟tick数
+    async fn microstructure(&self, symbol: &str) -> MarketMicrostructure {
+        let windows = self.windows.read().await;
+        let ticks = windows.get(&symbol_key(symbol)).map(|window| &window.ticks);
+        let (bid_ask_spread, order_book_depth) = ticks
+            .and_then(|ticks| ticks.back())
+            .map(|tick| ((tick.ask - tick.bid).max(Decimal::ZERO), tick.bid_volume + tick.ask_volume))
+            .unwrap_or_default();
+        let trade_frequency = match ticks.and_then(|ticks| Some((ticks.front()?, ticks.back()?, ticks.len()))) {
+            Some((first, last, count)) if last.timestamp > first.timestamp => {
+                let minutes = Decimal::from(last.timestamp - first.timestamp) / Decimal::from(60_000);
+                (Decimal::from(count) / minutes).round_dp(4)
+            }
+            _ => Decimal::ZERO,
+        };
+        let mid = ticks
+            .and_then(|ticks| ticks.back())
+            .map(|tick| (tick.bid + tick.ask) / Decimal::TWO)
+            .filter(|mid| *mid > Decimal::ZERO);
+
+        MarketMicrostructure {
+            bid_ask_spread,
+            order_book_depth,
+            trade_frequency,
+            price_impact: mid.map(|mid| bid_ask_spread / mid / Decimal::TWO).unwrap_or_default(),
+            depth_metrics: None,
+            seasonality: None,
+        }
+    }
+
+    /// 由滚动窗口构建市场上下文，窗口K线不足或中断时返回错误
+    pub async fn market_context(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketContext> {
+        let (interval, count) = chart_window(horizon);
+        let key = symbol.to_string();
+        let price_history = self.candles(&key, interval, count as usize).await;
+        if price_history.len() < count as usize {
+            return Err(anyhow!(
+                "Rolling window for {} {} has {} of {} candles",
+                symbol,
+                interval,
+                price_history.len(),
+                count
+            ));
+        }
+        let interval_ms = interval_millis(interval);
+        ensure_fresh(
+            symbol,
+            &price_history,
+            interval_ms * self.config.max_candle_lag,
+            Utc::now().timestamp_millis(),
+        )?;
+        let current_price = self
+            .last_price(&key)
+            .await
+            .ok_or_else(|| anyhow!("No market data for {}", symbol))?;
+
+        Ok(MarketContext {
+            symbol: symbol.clone(),
+            current_price,
+            technical_indicators: compute_indicators(&price_history),
+            volume_profile: volume_profile(&price_history, &[], self.config.volume_buckets),
+            price_history,
+            fundamental_data: None,
+            news_sentiment: None,
+            market_microstructure: self.microstructure(&key).await,
+        })
+    }
+}
+
+/// 实时行情上下文来源
+///
+/// 优先使用Kafka实时行情的滚动窗口，窗口尚未积累足够K线（如刚启动）或行情中断时
+/// 回退到拉取market-data服务的上下文构建器。
+pub struct LiveMarketContextProvider {
+    windows: Arc<RollingWindows>,
+    fallback: Option<Arc<dyn MarketContextProvider>>,
+}
+
+impl LiveMarketContextProvider {
+    pub fn new(windows: Arc<RollingWindows>, fallback: Option<Arc<dyn MarketContextProvider>>) -> Self {
+        Self { windows, fallback }
+    }
+}
+
+#[async_trait]
+impl MarketContextProvider for LiveMarketContextProvider {
+    async fn market_context(&self, symbol: &Symbol, horizon: &TimeHorizon) -> Result<MarketContext> {
+        match self.windows.market_context(symbol, horizon).await {
+            Ok(context) => Ok(context),
+            Err(e) => match &self.fallback {
+                Some(fallback) => {
+                    debug!("Falling back to market-data context for {}: {}", symbol, e);
+                    fallback.market_context(symbol, horizon).await
+                }
+                None => Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use shared_models::common::{DataQuality, Exchange, Interval};
+
+    fn kline(open_minute: i64, close: i64, is_closed: bool) -> Kline {
+        let open_time = Utc.timestamp_millis_opt(open_minute * 60_000).unwrap();
+        Kline {
+            id: None,
+            exchange: Exchange::Binance,
+            symbol: "BTC/USDT".to_string(),
+            interval: Interval::OneMinute,
+            open_time,
+            close_time: open_time + chrono::Duration::minutes(1),
+            open: Decimal::from(close),
+            high: Decimal::from(close),
+            low: Decimal::from(close),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+            quote_volume: Decimal::from(close),
+            trades_count: 1,
+            taker_buy_base_volume: Decimal::ZERO,
+            taker_buy_quote_volume: Decimal::ZERO,
+            is_closed,
+            data_quality: DataQuality::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rolling_window_updates_and_trims() {
+        let windows = RollingWindows::new(RollingWindowConfig {
+            max_candles: 2,
+            ..Default::default()
+        });
+        let mut closed = windows.subscribe_closed();
+
+        assert!(windows.apply_kline(&kline(1, 100, false)).await);
+        // 同一根K线的更新覆盖未收盘的旧值
+        assert!(windows.apply_kline(&kline(1, 101, true)).await);
+        assert!(windows.apply_kline(&kline(2, 102, true)).await);
+        assert!(windows.apply_kline(&kline(3, 103, false)).await);
+        assert!(!windows.apply_kline(&kline(1, 99, true)).await);
+
+        let candles = windows.candles("BTCUSDT", "1m", 10).await;
+        assert_eq!(
+            candles.iter().map(|c| c.close).collect::<Vec<_>>(),
+            vec![Decimal::from(102), Decimal::from(103)]
+        );
+        assert_eq!(windows.last_price("btc-usdt").await, Some(Decimal::from(103)));
+
+        let first = closed.recv().await.unwrap();
+        assert_eq!((first.symbol.as_str(), first.candle.close), ("BTCUSDT", Decimal::from(101)));
+        assert_eq!(closed.recv().await.unwrap().candle.close, Decimal::from(102));
+    }
+}
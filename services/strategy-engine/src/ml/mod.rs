@@ -0,0 +1,52 @@
+pub mod onnx;
+pub mod signals;
+pub mod store;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub use onnx::OnnxModel;
+pub use signals::{MLSignalGenerator, ModelPrediction};
+pub use store::ModelStore;
+
+/// 模型类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    /// 涨跌方向分类，输出[下跌, 上涨]或[下跌, 持平, 上涨]概率
+    DirectionClassifier,
+    /// 波动率预测，输出单个预测值
+    VolatilityForecaster,
+}
+
+/// 模型描述，随模型文件一起上传
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub name: String,
+    pub version: String,
+    pub kind: ModelKind,
+    /// 输入特征顺序，对应特征存储中的特征名
+    pub features: Vec<String>,
+    /// 分类模型发出信号的最低概率
+    #[serde(default = "default_signal_threshold")]
+    pub signal_threshold: Decimal,
+    #[serde(default)]
+    pub description: String,
+    /// 模型文件SHA256，上传时校验
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub uploaded_at: Option<DateTime<Utc>>,
+}
+
+fn default_signal_threshold() -> Decimal {
+    Decimal::new(60, 2)
+}
+
+impl ModelManifest {
+    /// 模型唯一标识
+    pub fn model_id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
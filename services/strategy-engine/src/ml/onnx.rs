@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Result};
+use ndarray::{Array2, CowArray};
+use ort::{Environment, GraphOptimizationLevel, Session, SessionBuilder, Value};
+use rust_decimal::prelude::ToPrimitive;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::ModelManifest;
+use crate::features::FeatureVector;
+
+/// 已加载的ONNX模型
+pub struct OnnxModel {
+    manifest: ModelManifest,
+    session: Session,
+}
+
+impl OnnxModel {
+    pub fn load(environment: &Arc<Environment>, manifest: ModelManifest, path: &Path) -> Result<Self> {
+        let session = SessionBuilder::new(environment)?
+            .with_optimization_level(GraphOptimizationLevel::Level1)?
+            .with_intra_threads(1)?
+            .with_model_from_file(path)?;
+
+        if session.inputs.len() != 1 {
+            return Err(anyhow!(
+                "Model {} must have exactly one input, found {}",
+                manifest.model_id(),
+                session.inputs.len()
+            ));
+        }
+
+        Ok(Self { manifest, session })
+    }
+
+    pub fn manifest(&self) -> &ModelManifest {
+        &self.manifest
+    }
+
+    /// 按模型声明的特征顺序组装输入
+    pub fn input_row(&self, features: &FeatureVector) -> Result<Vec<f32>> {
+        self.manifest
+            .features
+            .iter()
+            .map(|name| {
+                features
+                    .get(name)
+                    .and_then(|v| v.to_f32())
+                    .ok_or_else(|| anyhow!("Missing feature {} for model {}", name, self.manifest.model_id()))
+            })
+            .collect()
+    }
+
+    /// 执行推理，返回第一个输出的扁平结果
+    pub fn predict(&self, features: &FeatureVector) -> Result<Vec<f32>> {
+        let row = self.input_row(features)?;
+        let input = CowArray::from(Array2::from_shape_vec((1, row.len()), row)?.into_dyn());
+        let inputs = vec![Value::from_array(self.session.allocator(), &input)?];
+
+        let outputs = self.session.run(inputs)?;
+        let output = outputs
+            .first()
+            .ok_or_else(|| anyhow!("Model {} returned no output", self.manifest.model_id()))?;
+        let tensor = output.try_extract::<f32>()?;
+        let values = tensor.view().iter().copied().collect();
+        Ok(values)
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{ModelKind, ModelManifest, ModelStore};
+use crate::features::{FeatureStore, FeatureVector};
+use crate::models::{SignalType, Symbol, TradingSignal};
+use crate::quality::SignalScoreboard;
+
+/// 一次模型推理结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelPrediction {
+    pub model: String,
+    pub version: String,
+    pub kind: ModelKind,
+    pub outputs: Vec<f32>,
+    pub features: FeatureVector,
+    /// 方向分类模型超过阈值时生成的信号，波动率模型不生成信号
+    #[serde(skip)]
+    pub signal: Option<TradingSignal>,
+}
+
+/// 将分类概率转为方向和强度
+///
+/// 二分类输出[下跌, 上涨]，三分类输出[下跌, 持平, 上涨]。
+pub fn direction_from_probabilities(outputs: &[f32], threshold: Decimal) -> Result<Option<(SignalType, Decimal)>> {
+    let (down, up) = match outputs {
+        [down, up] | [down, _, up] => (*down, *up),
+        _ => return Err(anyhow!("Unexpected classifier output size: {}", outputs.len())),
+    };
+
+    let (signal_type, probability) = if up >= down {
+        (SignalType::Buy, up)
+    } else {
+        (SignalType::Sell, down)
+    };
+    let probability = Decimal::from_f32(probability).unwrap_or_default().round_dp(4);
+    Ok((probability >= threshold).then_some((signal_type, probability)))
+}
+
+/// 基于ONNX模型的信号生成
+pub struct MLSignalGenerator {
+    models: Arc<ModelStore>,
+    features: FeatureStore,
+    scoreboard: Option<Arc<SignalScoreboard>>,
+}
+
+impl MLSignalGenerator {
+    pub fn new(models: Arc<ModelStore>, features: FeatureStore) -> Self {
+        Self {
+            models,
+            features,
+            scoreboard: None,
+        }
+    }
+
+    /// 发出的信号计入信号质量记分板
+    pub fn with_scoreboard(mut self, scoreboard: Arc<SignalScoreboard>) -> Self {
+        self.scoreboard = Some(scoreboard);
+        self
+    }
+
+    /// 用模型当前生效版本和在线特征做推理
+    pub async fn predict(&self, model_name: &str, symbol: &Symbol, price: Decimal) -> Result<ModelPrediction> {
+        let model = self
+            .models
+            .active(model_name)
+            .await
+            .ok_or_else(|| anyhow!("No active version for model {}", model_name))?;
+        let manifest = model.manifest().clone();
+
+        let features = self
+            .features
+            .latest(&symbol.to_string(), &manifest.features)
+            .await?;
+        let outputs = {
+            let model = model.clone();
+            let features = features.clone();
+            tokio::task::spawn_blocking(move || model.predict(&features)).await??
+        };
+
+        let signal = match manifest.kind {
+            ModelKind::DirectionClassifier => direction_from_probabilities(&outputs, manifest.signal_threshold)?
+                .map(|(signal_type, strength)| build_signal(&manifest, symbol, signal_type, strength, price)),
+            ModelKind::VolatilityForecaster => None,
+        };
+        if let (Some(scoreboard), Some(signal)) = (&self.scoreboard, &signal) {
+            scoreboard.track_all(std::slice::from_ref(signal)).await;
+        }
+
+        Ok(ModelPrediction {
+            model: manifest.name,
+            version: manifest.version,
+            kind: manifest.kind,
+            outputs,
+            features,
+            signal,
+        })
+    }
+}
+
+/// 信号元数据中记录模型名称和版本，便于按版本追踪信号表现
+fn build_signal(
+    manifest: &ModelManifest,
+    symbol: &Symbol,
+    signal_type: SignalType,
+    strength: Decimal,
+    price: Decimal,
+) -> TradingSignal {
+    let mut metadata = HashMap::new();
+    metadata.insert("source".to_string(), serde_json::json!("onnx"));
+    metadata.insert("model".to_string(), serde_json::json!(manifest.name));
+    metadata.insert("model_version".to_string(), serde_json::json!(manifest.version));
+    metadata.insert("model_sha256".to_string(), serde_json::json!(manifest.sha256));
+
+    TradingSignal {
+        id: uuid::Uuid::new_v4(),
+        symbol: symbol.clone(),
+        signal_type,
+        strength,
+        price,
+        timestamp: chrono::Utc::now(),
+        strategy_id: None,
+        metadata,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction_from_probabilities() {
+        let threshold = Decimal::new(60, 2);
+
+        let (signal_type, strength) = direction_from_probabilities(&[0.2, 0.8], threshold)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(signal_type, SignalType::Buy));
+        assert_eq!(strength, Decimal::new(8, 1));
+
+        let (signal_type, _) = direction_from_probabilities(&[0.7, 0.2, 0.1], threshold)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(signal_type, SignalType::Sell));
+
+        assert!(direction_from_probabilities(&[0.45, 0.55], threshold).unwrap().is_none());
+        assert!(direction_from_probabilities(&[1.0], threshold).is_err());
+    }
+}
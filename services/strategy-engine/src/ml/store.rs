@@ -0,0 +1,223 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use ort::Environment;
+use serde::Serialize;
+use shared_utils::HashService;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::{ModelManifest, OnnxModel};
+
+const MODEL_FILE: &str = "model.onnx";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// 模型列表项
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelInfo {
+    pub manifest: ModelManifest,
+    pub active: bool,
+}
+
+/// 名称和版本只允许字母、数字和 . _ -，防止路径穿越
+pub fn validate_identifier(value: &str) -> Result<()> {
+    let valid = !value.is_empty()
+        && value != "."
+        && value != ".."
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid model identifier: {}", value))
+    }
+}
+
+/// ONNX模型仓库
+///
+/// 模型文件保存在 `{root}/{name}/{version}/`，启动时全部加载。
+/// 每个模型名称有一个生效版本，新上传的版本自动生效，可切回旧版本。
+pub struct ModelStore {
+    environment: Arc<Environment>,
+    root: PathBuf,
+    /// name -> version -> model
+    models: RwLock<HashMap<String, BTreeMap<String, Arc<OnnxModel>>>>,
+    /// name -> 生效版本
+    active: RwLock<HashMap<String, String>>,
+}
+
+impl ModelStore {
+    pub async fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        tokio::fs::create_dir_all(&root).await?;
+        let environment = Environment::builder()
+            .with_name("strategy-engine")
+            .build()?
+            .into_arc();
+
+        let store = Self {
+            environment,
+            root,
+            models: RwLock::new(HashMap::new()),
+            active: RwLock::new(HashMap::new()),
+        };
+        store.load_all().await?;
+        Ok(store)
+    }
+
+    async fn load_all(&self) -> Result<()> {
+        let mut names = tokio::fs::read_dir(&self.root).await?;
+        while let Some(name_dir) = names.next_entry().await? {
+            if !name_dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut versions = tokio::fs::read_dir(name_dir.path()).await?;
+            while let Some(version_dir) = versions.next_entry().await? {
+                let dir = version_dir.path();
+                match self.load_dir(&dir).await {
+                    Ok(model) => self.insert(model, false).await,
+                    Err(e) => warn!("Failed to load model from {}: {}", dir.display(), e),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn load_dir(&self, dir: &Path) -> Result<Arc<OnnxModel>> {
+        let manifest: ModelManifest =
+            serde_json::from_slice(&tokio::fs::read(dir.join(MANIFEST_FILE)).await?)?;
+        let environment = self.environment.clone();
+        let path = dir.join(MODEL_FILE);
+        let model = tokio::task::spawn_blocking(move || OnnxModel::load(&environment, manifest, &path)).await??;
+        Ok(Arc::new(model))
+    }
+
+    /// 加入已加载的模型；启动加载时按上传时间选择生效版本
+    async fn insert(&self, model: Arc<OnnxModel>, activate: bool) {
+        let manifest = model.manifest().clone();
+        self.models
+            .write()
+            .await
+            .entry(manifest.name.clone())
+            .or_default()
+            .insert(manifest.version.clone(), model);
+
+        let models = self.models.read().await;
+        let mut active = self.active.write().await;
+        let newer = match active.get(&manifest.name) {
+            None => true,
+            Some(current) => {
+                let current_uploaded = models
+                    .get(&manifest.name)
+                    .and_then(|versions| versions.get(current))
+                    .and_then(|m| m.manifest().uploaded_at);
+                manifest.uploaded_at > current_uploaded
+            }
+        };
+        if activate || newer {
+            active.insert(manifest.name.clone(), manifest.version.clone());
+        }
+    }
+
+    /// 上传模型，校验后写入磁盘并设为生效版本
+    pub async fn upload(&self, mut manifest: ModelManifest, bytes: Vec<u8>) -> Result<ModelManifest> {
+        validate_identifier(&manifest.name)?;
+        validate_identifier(&manifest.version)?;
+        if manifest.features.is_empty() {
+            return Err(anyhow!("Model {} declares no input features", manifest.model_id()));
+        }
+
+        let digest = HashService::sha256(&bytes);
+        if let Some(expected) = &manifest.sha256 {
+            if !expected.eq_ignore_ascii_case(&digest) {
+                return Err(anyhow!("Checksum mismatch for model {}", manifest.model_id()));
+            }
+        }
+        if self.get(&manifest.name, &manifest.version).await.is_some() {
+            return Err(anyhow!("Model {} already exists", manifest.model_id()));
+        }
+        manifest.sha256 = Some(digest);
+        manifest.uploaded_at = Some(Utc::now());
+
+        let dir = self.root.join(&manifest.name).join(&manifest.version);
+        tokio::fs::create_dir_all(&dir).await?;
+        tokio::fs::write(dir.join(MODEL_FILE), &bytes).await?;
+        tokio::fs::write(dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?).await?;
+
+        match self.load_dir(&dir).await {
+            Ok(model) => self.insert(model, true).await,
+            Err(e) => {
+                // 无法加载的模型不保留
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                return Err(e);
+            }
+        }
+
+        info!("Uploaded model {}", manifest.model_id());
+        Ok(manifest)
+    }
+
+    /// 切换生效版本
+    pub async fn activate(&self, name: &str, version: &str) -> Result<()> {
+        if self.get(name, version).await.is_none() {
+            return Err(anyhow!("Model {}@{} not found", name, version));
+        }
+        self.active
+            .write()
+            .await
+            .insert(name.to_string(), version.to_string());
+        info!("Activated model {}@{}", name, version);
+        Ok(())
+    }
+
+    pub async fn get(&self, name: &str, version: &str) -> Option<Arc<OnnxModel>> {
+        self.models
+            .read()
+            .await
+            .get(name)
+            .and_then(|versions| versions.get(version))
+            .cloned()
+    }
+
+    /// 当前生效版本
+    pub async fn active(&self, name: &str) -> Option<Arc<OnnxModel>> {
+        let version = self.active.read().await.get(name).cloned()?;
+        self.get(name, &version).await
+    }
+
+    pub async fn list(&self) -> Vec<ModelInfo> {
+        let models = self.models.read().await;
+        let active = self.active.read().await;
+        let mut list: Vec<ModelInfo> = models
+            .iter()
+            .flat_map(|(name, versions)| {
+                versions.iter().map(|(version, model)| ModelInfo {
+                    manifest: model.manifest().clone(),
+                    active: active.get(name) == Some(version),
+                })
+            })
+            .collect();
+        list.sort_by(|a, b| {
+            (a.manifest.name.as_str(), a.manifest.uploaded_at).cmp(&(b.manifest.name.as_str(), b.manifest.uploaded_at))
+        });
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_validation() {
+        assert!(validate_identifier("btc-direction").is_ok());
+        assert!(validate_identifier("1.2.0").is_ok());
+        assert!(validate_identifier("..").is_err());
+        assert!(validate_identifier("../etc").is_err());
+        assert!(validate_identifier("a/b").is_err());
+        assert!(validate_identifier("").is_err());
+    }
+}
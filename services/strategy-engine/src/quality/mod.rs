@@ -0,0 +1,388 @@
+pub mod prices;
+pub mod store;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::{SignalType, TradingSignal};
+
+pub use prices::MarketDataPriceSource;
+pub use store::SignalQualityStore;
+
+/// 每轮最多评估的信号数
+const EVALUATION_BATCH: i64 = 500;
+
+/// 未关联策略的信号归入的模型名
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// 信号评估周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Horizon {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
+impl Horizon {
+    pub const ALL: [Horizon; 3] = [Horizon::OneMinute, Horizon::FifteenMinutes, Horizon::OneHour];
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Horizon::OneMinute => Duration::minutes(1),
+            Horizon::FifteenMinutes => Duration::minutes(15),
+            Horizon::OneHour => Duration::hours(1),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Horizon::OneMinute => "1m",
+            Horizon::FifteenMinutes => "15m",
+            Horizon::OneHour => "1h",
+        }
+    }
+}
+
+impl std::str::FromStr for Horizon {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Horizon::ALL
+            .into_iter()
+            .find(|h| h.as_str() == s)
+            .ok_or_else(|| anyhow!("Invalid signal horizon: {}", s))
+    }
+}
+
+/// 待评估的信号，每个周期一条
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEvaluation {
+    pub signal_id: Uuid,
+    pub horizon: Horizon,
+    pub strategy_id: String,
+    pub model: String,
+    pub symbol: String,
+    /// 买入为1，卖出为-1
+    pub direction: i16,
+    pub entry_price: Decimal,
+    pub emitted_at: DateTime<Utc>,
+}
+
+impl PendingEvaluation {
+    pub fn due_at(&self) -> DateTime<Utc> {
+        self.emitted_at + self.horizon.duration()
+    }
+
+    /// 按信号方向计算的收益（基点），正值即命中
+    pub fn edge_bps(&self, exit_price: Decimal) -> Decimal {
+        let change = (exit_price - self.entry_price) / self.entry_price;
+        (change * Decimal::from(self.direction) * Decimal::from(10_000)).round_dp(4)
+    }
+}
+
+/// 信号的策略和模型归属，策略缺失时用模型名代替
+fn attribution(signal: &TradingSignal) -> Option<(String, String)> {
+    let metadata = |key: &str| signal.metadata.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let model = match (metadata("model"), metadata("model_version")) {
+        (Some(name), Some(version)) => Some(format!("{}@{}", name, version)),
+        (name, _) => name,
+    };
+    let strategy_id = signal
+        .strategy_id
+        .as_ref()
+        .map(|id| id.to_string())
+        .or_else(|| metadata("model"))?;
+    Some((strategy_id, model.unwrap_or_else(|| UNKNOWN_MODEL.to_string())))
+}
+
+/// 买卖信号展开为各周期的待评估记录，其它信号和无价格信号不评估
+pub fn pending_evaluations(signal: &TradingSignal) -> Vec<PendingEvaluation> {
+    let direction = match signal.signal_type {
+        SignalType::Buy => 1,
+        SignalType::Sell => -1,
+        _ => return Vec::new(),
+    };
+    if signal.price <= Decimal::ZERO {
+        return Vec::new();
+    }
+    let Some((strategy_id, model)) = attribution(signal) else {
+        return Vec::new();
+    };
+
+    Horizon::ALL
+        .into_iter()
+        .map(|horizon| PendingEvaluation {
+            signal_id: signal.id,
+            horizon,
+            strategy_id: strategy_id.clone(),
+            model: model.clone(),
+            symbol: signal.symbol.to_string(),
+            direction,
+            entry_price: signal.price,
+            emitted_at: signal.timestamp,
+        })
+        .collect()
+}
+
+/// 某模型在一个周期上的汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HorizonQuality {
+    pub horizon: Horizon,
+    pub signals: i64,
+    pub hits: i64,
+    pub hit_rate: Decimal,
+    /// 按信号方向的平均收益（基点）
+    pub average_edge_bps: Decimal,
+}
+
+/// 某模型的信号质量
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelQuality {
+    pub model: String,
+    pub horizons: Vec<HorizonQuality>,
+    /// 最长周期与最短周期平均收益之差，负值表示信号优势随时间衰减
+    pub edge_decay_bps: Option<Decimal>,
+}
+
+/// 策略信号质量报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SignalQualityReport {
+    pub strategy_id: String,
+    /// 全部模型合计
+    pub horizons: Vec<HorizonQuality>,
+    pub edge_decay_bps: Option<Decimal>,
+    pub models: Vec<ModelQuality>,
+    /// 尚未到期评估的信号周期数
+    pub pending: i64,
+}
+
+/// 存储层按模型和周期聚合的结果
+#[derive(Debug, Clone)]
+pub struct HorizonAggregate {
+    pub model: String,
+    pub horizon: Horizon,
+    pub signals: i64,
+    pub hits: i64,
+    pub edge_sum_bps: Decimal,
+}
+
+fn horizon_quality(horizon: Horizon, signals: i64, hits: i64, edge_sum_bps: Decimal) -> HorizonQuality {
+    let (hit_rate, average_edge_bps) = if signals > 0 {
+        let count = Decimal::from(signals);
+        (
+            (Decimal::from(hits) / count).round_dp(4),
+            (edge_sum_bps / count).round_dp(4),
+        )
+    } else {
+        (Decimal::ZERO, Decimal::ZERO)
+    };
+    HorizonQuality {
+        horizon,
+        signals,
+        hits,
+        hit_rate,
+        average_edge_bps,
+    }
+}
+
+fn edge_decay(horizons: &[HorizonQuality]) -> Option<Decimal> {
+    let evaluated: Vec<_> = horizons.iter().filter(|h| h.signals > 0).collect();
+    match (evaluated.first(), evaluated.last()) {
+        (Some(first), Some(last)) if first.horizon != last.horizon => {
+            Some(last.average_edge_bps - first.average_edge_bps)
+        }
+        _ => None,
+    }
+}
+
+/// 由聚合结果生成报告
+pub fn build_report(strategy_id: &str, aggregates: &[HorizonAggregate], pending: i64) -> SignalQualityReport {
+    let mut model_names: Vec<&str> = aggregates.iter().map(|a| a.model.as_str()).collect();
+    model_names.sort_unstable();
+    model_names.dedup();
+
+    let summarize = |filter: &dyn Fn(&HorizonAggregate) -> bool| -> Vec<HorizonQuality> {
+        Horizon::ALL
+            .into_iter()
+            .map(|horizon| {
+                let (signals, hits, edge) = aggregates
+                    .iter()
+                    .filter(|a| a.horizon == horizon && filter(a))
+                    .fold((0, 0, Decimal::ZERO), |(s, h, e), a| {
+                        (s + a.signals, h + a.hits, e + a.edge_sum_bps)
+                    });
+                horizon_quality(horizon, signals, hits, edge)
+            })
+            .collect()
+    };
+
+    let models = model_names
+        .into_iter()
+        .map(|model| {
+            let horizons = summarize(&|a| a.model == model);
+            ModelQuality {
+                model: model.to_string(),
+                edge_decay_bps: edge_decay(&horizons),
+                horizons,
+            }
+        })
+        .collect();
+    let horizons = summarize(&|_| true);
+
+    SignalQualityReport {
+        strategy_id: strategy_id.to_string(),
+        edge_decay_bps: edge_decay(&horizons),
+        horizons,
+        models,
+        pending,
+    }
+}
+
+/// 评估信号使用的价格来源
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    async fn last_price(&self, symbol: &str) -> Result<Decimal>;
+}
+
+/// 信号质量记分板
+///
+/// 每个买卖信号发出时按1m/15m/1h三个周期写入待评估记录，到期后取当时价格，
+/// 按信号方向计算收益和是否命中。待评估记录和结果都持久化，重启后继续评估。
+pub struct SignalScoreboard {
+    store: SignalQualityStore,
+    prices: Arc<dyn PriceSource>,
+}
+
+impl SignalScoreboard {
+    pub fn new(store: SignalQualityStore, prices: Arc<dyn PriceSource>) -> Self {
+        Self { store, prices }
+    }
+
+    /// 记录发出的信号
+    pub async fn track(&self, signal: &TradingSignal) -> Result<()> {
+        let pending = pending_evaluations(signal);
+        if pending.is_empty() {
+            return Ok(());
+        }
+        self.store.insert_pending(&pending).await
+    }
+
+    /// 记录信号，失败只记日志，不影响信号发出
+    pub async fn track_all(&self, signals: &[TradingSignal]) {
+        for signal in signals {
+            if let Err(e) = self.track(signal).await {
+                warn!("Failed to track signal {}: {}", signal.id, e);
+            }
+        }
+    }
+
+    /// 评估已到期的信号，返回评估数量
+    pub async fn evaluate_due(&self) -> Result<usize> {
+        let due = self.store.due(Utc::now(), EVALUATION_BATCH).await?;
+        let mut evaluated = 0;
+        for pending in due {
+            let exit_price = match self.prices.last_price(&pending.symbol).await {
+                Ok(price) if price > Decimal::ZERO => price,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("No price to evaluate signal {} on {}: {}", pending.signal_id, pending.symbol, e);
+                    continue;
+                }
+            };
+            let edge_bps = pending.edge_bps(exit_price);
+            self.store
+                .complete(&pending, exit_price, edge_bps, edge_bps > Decimal::ZERO)
+                .await?;
+            evaluated += 1;
+        }
+        Ok(evaluated)
+    }
+
+    /// 策略信号质量报告
+    pub async fn report(&self, strategy_id: &str) -> Result<SignalQualityReport> {
+        let aggregates = self.store.aggregates(strategy_id).await?;
+        let pending = self.store.pending_count(strategy_id).await?;
+        Ok(build_report(strategy_id, &aggregates, pending))
+    }
+
+    /// 定期评估到期信号
+    pub fn start(self: Arc<Self>, interval: std::time::Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.evaluate_due().await {
+                    Ok(0) => {}
+                    Ok(evaluated) => info!("Evaluated {} signal outcomes", evaluated),
+                    Err(e) => warn!("Signal quality evaluation failed: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(direction: i16) -> PendingEvaluation {
+        PendingEvaluation {
+            signal_id: Uuid::new_v4(),
+            horizon: Horizon::FifteenMinutes,
+            strategy_id: "momentum".to_string(),
+            model: "btc-direction@1.0.0".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            direction,
+            entry_price: Decimal::from(50_000),
+            emitted_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_edge_follows_signal_direction() {
+        let exit = Decimal::from(50_500);
+        assert_eq!(pending(1).edge_bps(exit), Decimal::from(100));
+        assert_eq!(pending(-1).edge_bps(exit), Decimal::from(-100));
+        assert_eq!(pending(1).due_at() - pending(1).emitted_at, Duration::minutes(15));
+    }
+
+    #[test]
+    fn test_report_hit_rate_and_decay() {
+        let aggregate = |model: &str, horizon, signals, hits, edge: i64| HorizonAggregate {
+            model: model.to_string(),
+            horizon,
+            signals,
+            hits,
+            edge_sum_bps: Decimal::from(edge),
+        };
+        let aggregates = vec![
+            aggregate("a@1", Horizon::OneMinute, 4, 3, 80),
+            aggregate("a@1", Horizon::OneHour, 4, 2, 20),
+            aggregate("b@1", Horizon::OneMinute, 6, 3, 0),
+        ];
+
+        let report = build_report("momentum", &aggregates, 5);
+        assert_eq!(report.pending, 5);
+        assert_eq!(report.models.len(), 2);
+
+        let a = &report.models[0];
+        assert_eq!(a.horizons[0].hit_rate, Decimal::new(75, 2));
+        assert_eq!(a.horizons[0].average_edge_bps, Decimal::from(20));
+        assert_eq!(a.horizons[1].signals, 0);
+        assert_eq!(a.edge_decay_bps, Some(Decimal::from(-15)));
+        assert_eq!(report.models[1].edge_decay_bps, None);
+
+        assert_eq!(report.horizons[0].signals, 10);
+        assert_eq!(report.horizons[0].hits, 6);
+        assert_eq!(report.horizons[0].average_edge_bps, Decimal::from(8));
+    }
+}
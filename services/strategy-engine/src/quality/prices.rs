@@ -0,0 +1,48 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use shared_models::common::ApiResponse;
+use shared_models::market::MarketTick;
+use std::time::Duration;
+
+use super::PriceSource;
+
+/// 从行情服务读取最新成交价
+pub struct MarketDataPriceSource {
+    base_url: String,
+    exchange: String,
+    client: reqwest::Client,
+}
+
+impl MarketDataPriceSource {
+    pub fn new(base_url: String, exchange: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            exchange: exchange.to_lowercase(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for MarketDataPriceSource {
+    async fn last_price(&self, symbol: &str) -> Result<Decimal> {
+        let response = self
+            .client
+            .get(format!("{}/api/v1/tick/{}/{}", self.base_url, self.exchange, symbol))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Market data returned {} for {}", response.status(), symbol));
+        }
+
+        let body: ApiResponse<MarketTick> = response.json().await?;
+        body.data
+            .map(|tick| tick.price)
+            .ok_or_else(|| anyhow!("No tick for {}: {}", symbol, body.error.unwrap_or_default()))
+    }
+}
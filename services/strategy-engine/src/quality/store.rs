@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+
+use super::{HorizonAggregate, PendingEvaluation};
+
+const SCHEMA: [&str; 3] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS signal_evaluations (
+        signal_id UUID NOT NULL,
+        horizon TEXT NOT NULL,
+        strategy_id TEXT NOT NULL,
+        model TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        direction SMALLINT NOT NULL,
+        entry_price NUMERIC NOT NULL,
+        emitted_at TIMESTAMPTZ NOT NULL,
+        due_at TIMESTAMPTZ NOT NULL,
+        exit_price NUMERIC,
+        edge_bps NUMERIC,
+        hit BOOLEAN,
+        evaluated_at TIMESTAMPTZ,
+        PRIMARY KEY (signal_id, horizon)
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_signal_evaluations_due ON signal_evaluations (due_at) WHERE evaluated_at IS NULL",
+    "CREATE INDEX IF NOT EXISTS idx_signal_evaluations_strategy ON signal_evaluations (strategy_id)",
+];
+
+/// 信号评估存储
+#[derive(Clone)]
+pub struct SignalQualityStore {
+    pool: Arc<PgPool>,
+}
+
+impl SignalQualityStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn insert_pending(&self, pending: &[PendingEvaluation]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for evaluation in pending {
+            sqlx::query(
+                r#"
+                INSERT INTO signal_evaluations (
+                    signal_id, horizon, strategy_id, model, symbol, direction,
+                    entry_price, emitted_at, due_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (signal_id, horizon) DO NOTHING
+                "#,
+            )
+            .bind(evaluation.signal_id)
+            .bind(evaluation.horizon.as_str())
+            .bind(&evaluation.strategy_id)
+            .bind(&evaluation.model)
+            .bind(&evaluation.symbol)
+            .bind(evaluation.direction)
+            .bind(evaluation.entry_price)
+            .bind(evaluation.emitted_at)
+            .bind(evaluation.due_at())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 已到期未评估的记录，按到期时间排序
+    pub async fn due(&self, now: DateTime<Utc>, limit: i64) -> Result<Vec<PendingEvaluation>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM signal_evaluations
+            WHERE evaluated_at IS NULL AND due_at <= $1
+            ORDER BY due_at
+            LIMIT $2
+            "#,
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_pending).collect()
+    }
+
+    pub async fn complete(
+        &self,
+        pending: &PendingEvaluation,
+        exit_price: Decimal,
+        edge_bps: Decimal,
+        hit: bool,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE signal_evaluations SET
+                exit_price = $3, edge_bps = $4, hit = $5, evaluated_at = $6
+            WHERE signal_id = $1 AND horizon = $2
+            "#,
+        )
+        .bind(pending.signal_id)
+        .bind(pending.horizon.as_str())
+        .bind(exit_price)
+        .bind(edge_bps)
+        .bind(hit)
+        .bind(Utc::now())
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 按模型和周期聚合已评估的记录
+    pub async fn aggregates(&self, strategy_id: &str) -> Result<Vec<HorizonAggregate>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT model, horizon,
+                COUNT(*) AS signals,
+                COUNT(*) FILTER (WHERE hit) AS hits,
+                COALESCE(SUM(edge_bps), 0) AS edge_sum_bps
+            FROM signal_evaluations
+            WHERE strategy_id = $1 AND evaluated_at IS NOT NULL
+            GROUP BY model, horizon
+            "#,
+        )
+        .bind(strategy_id)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(HorizonAggregate {
+                    model: row.try_get("model")?,
+                    horizon: row.try_get::<String, _>("horizon")?.parse()?,
+                    signals: row.try_get("signals")?,
+                    hits: row.try_get("hits")?,
+                    edge_sum_bps: row.try_get("edge_sum_bps")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn pending_count(&self, strategy_id: &str) -> Result<i64> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS pending FROM signal_evaluations WHERE strategy_id = $1 AND evaluated_at IS NULL",
+        )
+        .bind(strategy_id)
+        .fetch_one(&*self.pool)
+        .await?;
+        Ok(row.try_get("pending")?)
+    }
+
+    fn row_to_pending(row: PgRow) -> Result<PendingEvaluation> {
+        Ok(PendingEvaluation {
+            signal_id: row.try_get("signal_id")?,
+            horizon: row.try_get::<String, _>("horizon")?.parse()?,
+            strategy_id: row.try_get("strategy_id")?,
+            model: row.try_get("model")?,
+            symbol: row.try_get("symbol")?,
+            direction: row.try_get("direction")?,
+            entry_price: row.try_get("entry_price")?,
+            emitted_at: row.try_get("emitted_at")?,
+        })
+    }
+}
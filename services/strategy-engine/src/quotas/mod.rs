@@ -0,0 +1,511 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tracing::warn;
+use uuid::Uuid;
+
+/// AI调用计数窗口
+const AI_CALL_WINDOW: Duration = Duration::from_secs(3600);
+/// 下单频率计数窗口
+const ORDER_WINDOW: Duration = Duration::from_secs(60);
+
+/// 单个用户等级的资源配额
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyQuota {
+    /// 同时运行的策略数
+    pub max_concurrent_strategies: usize,
+    /// 单次K线处理的最长耗时（毫秒）
+    pub max_tick_cpu_ms: u64,
+    /// 连续超时多少次后暂停策略
+    pub max_tick_overruns: u32,
+    /// 单个策略每小时的AI调用次数
+    pub max_ai_calls_per_hour: u32,
+    /// 单个策略每分钟的下单数，超过后暂停策略
+    pub max_orders_per_minute: u32,
+}
+
+impl Default for StrategyQuota {
+    fn default() -> Self {
+        Self {
+            max_concurrent_strategies: 5,
+            max_tick_cpu_ms: 50,
+            max_tick_overruns: 3,
+            max_ai_calls_per_hour: 60,
+            max_orders_per_minute: 30,
+        }
+    }
+}
+
+/// 策略资源配额配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    /// 未分配等级的用户使用的配额
+    #[serde(default)]
+    pub default: StrategyQuota,
+    /// 按等级名称配置的配额
+    #[serde(default)]
+    pub tiers: HashMap<String, StrategyQuota>,
+    /// 用户 -> 等级名称
+    #[serde(default)]
+    pub users: HashMap<Uuid, String>,
+}
+
+impl QuotaConfig {
+    pub fn quota_for(&self, user_id: Uuid) -> &StrategyQuota {
+        self.users
+            .get(&user_id)
+            .and_then(|tier| self.tiers.get(tier))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// 配额类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    ConcurrentStrategies,
+    TickCpuTime,
+    AiCalls,
+    OrderRate,
+}
+
+impl std::fmt::Display for QuotaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaKind::ConcurrentStrategies => write!(f, "concurrent_strategies"),
+            QuotaKind::TickCpuTime => write!(f, "tick_cpu_time"),
+            QuotaKind::AiCalls => write!(f, "ai_calls"),
+            QuotaKind::OrderRate => write!(f, "order_rate"),
+        }
+    }
+}
+
+/// 超出配额
+#[derive(Debug, Clone, Error)]
+#[error("Strategy {strategy_id} exceeded {kind} quota: {observed} > {limit}")]
+pub struct QuotaExceeded {
+    pub user_id: Uuid,
+    pub strategy_id: String,
+    pub kind: QuotaKind,
+    pub limit: u64,
+    pub observed: u64,
+}
+
+/// 配额超限事件，供告警和前端展示
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaEvent {
+    pub user_id: Uuid,
+    pub strategy_id: String,
+    pub kind: QuotaKind,
+    pub limit: u64,
+    pub observed: u64,
+    /// 策略是否因此被暂停
+    pub suspended: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 策略当前的资源使用
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyUsage {
+    pub user_id: Uuid,
+    pub strategy_id: String,
+    pub ai_calls_last_hour: usize,
+    pub orders_last_minute: usize,
+    pub tick_overruns: u32,
+    pub suspended: bool,
+}
+
+struct StrategyState {
+    user_id: Uuid,
+    quota: StrategyQuota,
+    ai_calls: VecDeque<Instant>,
+    orders: VecDeque<Instant>,
+    tick_overruns: u32,
+    suspended: bool,
+}
+
+/// 滑动窗口内已达上限时返回 false，否则记录本次调用
+fn admit(window: &mut VecDeque<Instant>, span: Duration, limit: u32, now: Instant) -> bool {
+    while window
+        .front()
+        .is_some_and(|at| now.saturating_duration_since(*at) >= span)
+    {
+        window.pop_front();
+    }
+    if window.len() >= limit as usize {
+        return false;
+    }
+    window.push_back(now);
+    true
+}
+
+/// 策略状态按 (用户, 策略ID) 区分，不同用户可以使用相同的策略ID
+type StrategyKey = (Uuid, String);
+
+fn key(user_id: Uuid, strategy_id: &str) -> StrategyKey {
+    (user_id, strategy_id.to_string())
+}
+
+/// 策略运行时资源配额
+///
+/// 按用户等级限制同时运行的策略数，按策略限制K线处理耗时、AI调用次数和下单频率。
+/// 处理连续超时或下单频率超限的策略会被暂停，已产生的信号不再下单，直到手动恢复；
+/// AI调用超限只拒绝本次调用，策略可回退到规则模型。每次超限都会广播事件。
+#[derive(Clone)]
+pub struct QuotaManager {
+    config: Arc<QuotaConfig>,
+    strategies: Arc<Mutex<HashMap<StrategyKey, StrategyState>>>,
+    events: broadcast::Sender<QuotaEvent>,
+}
+
+impl QuotaManager {
+    pub fn new(config: QuotaConfig) -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            config: Arc::new(config),
+            strategies: Arc::new(Mutex::new(HashMap::new())),
+            events,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<QuotaEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, exceeded: &QuotaExceeded, suspended: bool) {
+        warn!("{}{}", exceeded, if suspended { ", strategy suspended" } else { "" });
+        let _ = self.events.send(QuotaEvent {
+            user_id: exceeded.user_id,
+            strategy_id: exceeded.strategy_id.clone(),
+            kind: exceeded.kind,
+            limit: exceeded.limit,
+            observed: exceeded.observed,
+            suspended,
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// 启动策略前占用一个并发名额，返回的守卫释放时归还
+    pub fn register(&self, user_id: Uuid, strategy_id: &str) -> Result<StrategyQuotaGuard, QuotaExceeded> {
+        let quota = self.config.quota_for(user_id).clone();
+        let mut strategies = self.strategies.lock().unwrap_or_else(|e| e.into_inner());
+        let strategy_key = key(user_id, strategy_id);
+        if !strategies.contains_key(&strategy_key) {
+            let running = strategies.keys().filter(|(owner, _)| *owner == user_id).count();
+            if running >= quota.max_concurrent_strategies {
+                let exceeded = QuotaExceeded {
+                    user_id,
+                    strategy_id: strategy_id.to_string(),
+                    kind: QuotaKind::ConcurrentStrategies,
+                    limit: quota.max_concurrent_strategies as u64,
+                    observed: running as u64 + 1,
+                };
+                drop(strategies);
+                self.emit(&exceeded, false);
+                return Err(exceeded);
+            }
+            strategies.insert(
+                strategy_key,
+                StrategyState {
+                    user_id,
+                    quota,
+                    ai_calls: VecDeque::new(),
+                    orders: VecDeque::new(),
+                    tick_overruns: 0,
+                    suspended: false,
+                },
+            );
+        }
+        Ok(StrategyQuotaGuard {
+            manager: self.clone(),
+            user_id,
+            strategy_id: strategy_id.to_string(),
+        })
+    }
+
+    /// 对策略状态执行检查，超限时广播事件
+    fn check<F>(&self, user_id: Uuid, strategy_id: &str, check: F) -> Result<(), QuotaExceeded>
+    where
+        F: FnOnce(&mut StrategyState) -> Option<(QuotaKind, u64, u64)>,
+    {
+        let (exceeded, suspended) = {
+            let mut strategies = self.strategies.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(state) = strategies.get_mut(&key(user_id, strategy_id)) else {
+                return Ok(());
+            };
+            let was_suspended = state.suspended;
+            let Some((kind, limit, observed)) = check(state) else {
+                return Ok(());
+            };
+            let exceeded = QuotaExceeded {
+                user_id: state.user_id,
+                strategy_id: strategy_id.to_string(),
+                kind,
+                limit,
+                observed,
+            };
+            (exceeded, state.suspended && !was_suspended)
+        };
+        self.emit(&exceeded, suspended);
+        Err(exceeded)
+    }
+
+    /// 记录一次K线处理耗时，连续超时达到上限时暂停策略
+    pub fn record_tick(&self, user_id: Uuid, strategy_id: &str, elapsed: Duration) -> Result<(), QuotaExceeded> {
+        self.check(user_id, strategy_id, |state| {
+            let elapsed_ms = elapsed.as_millis() as u64;
+            if elapsed_ms <= state.quota.max_tick_cpu_ms {
+                state.tick_overruns = 0;
+                return None;
+            }
+            state.tick_overruns += 1;
+            if state.tick_overruns >= state.quota.max_tick_overruns {
+                state.suspended = true;
+            }
+            Some((QuotaKind::TickCpuTime, state.quota.max_tick_cpu_ms, elapsed_ms))
+        })
+    }
+
+    /// AI调用前检查每小时调用次数，未超限时计入本次调用
+    pub fn check_ai_call(&self, user_id: Uuid, strategy_id: &str) -> Result<(), QuotaExceeded> {
+        self.check(user_id, strategy_id, |state| {
+            let limit = state.quota.max_ai_calls_per_hour;
+            if admit(&mut state.ai_calls, AI_CALL_WINDOW, limit, Instant::now()) {
+                return None;
+            }
+            Some((QuotaKind::AiCalls, limit as u64, state.ai_calls.len() as u64 + 1))
+        })
+    }
+
+    /// 下单前检查每分钟下单数，超限时暂停策略
+    pub fn check_order(&self, user_id: Uuid, strategy_id: &str) -> Result<(), QuotaExceeded> {
+        self.check(user_id, strategy_id, |state| {
+            let limit = state.quota.max_orders_per_minute;
+            if admit(&mut state.orders, ORDER_WINDOW, limit, Instant::now()) {
+                return None;
+            }
+            state.suspended = true;
+            Some((QuotaKind::OrderRate, limit as u64, state.orders.len() as u64 + 1))
+        })
+    }
+
+    pub fn is_suspended(&self, user_id: Uuid, strategy_id: &str) -> bool {
+        self.strategies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key(user_id, strategy_id))
+            .is_some_and(|state| state.suspended)
+    }
+
+    /// 手动恢复被暂停的策略，返回策略是否在运行
+    pub fn resume(&self, user_id: Uuid, strategy_id: &str) -> bool {
+        let mut strategies = self.strategies.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = strategies.get_mut(&key(user_id, strategy_id)) else {
+            return false;
+        };
+        state.suspended = false;
+        state.tick_overruns = 0;
+        state.orders.clear();
+        true
+    }
+
+    pub fn usage(&self, user_id: Uuid, strategy_id: &str) -> Option<StrategyUsage> {
+        let strategies = self.strategies.lock().unwrap_or_else(|e| e.into_inner());
+        strategies.get(&key(user_id, strategy_id)).map(|state| {
+            let now = Instant::now();
+            let recent = |window: &VecDeque<Instant>, span: Duration| {
+                window
+                    .iter()
+                    .filter(|at| now.saturating_duration_since(**at) < span)
+                    .count()
+            };
+            StrategyUsage {
+                user_id: state.user_id,
+                strategy_id: strategy_id.to_string(),
+                ai_calls_last_hour: recent(&state.ai_calls, AI_CALL_WINDOW),
+                orders_last_minute: recent(&state.orders, ORDER_WINDOW),
+                tick_overruns: state.tick_overruns,
+                suspended: state.suspended,
+            }
+        })
+    }
+
+    fn unregister(&self, user_id: Uuid, strategy_id: &str) {
+        self.strategies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key(user_id, strategy_id));
+    }
+}
+
+/// 运行中策略的配额句柄，释放时归还并发名额
+pub struct StrategyQuotaGuard {
+    manager: QuotaManager,
+    user_id: Uuid,
+    strategy_id: String,
+}
+
+impl StrategyQuotaGuard {
+    pub fn strategy_id(&self) -> &str {
+        &self.strategy_id
+    }
+
+    pub fn record_tick(&self, elapsed: Duration) -> Result<(), QuotaExceeded> {
+        self.manager.record_tick(self.user_id, &self.strategy_id, elapsed)
+    }
+
+    pub fn check_ai_call(&self) -> Result<(), QuotaExceeded> {
+        self.manager.check_ai_call(self.user_id, &self.strategy_id)
+    }
+
+    pub fn check_order(&self) -> Result<(), QuotaExceeded> {
+        self.manager.check_order(self.user_id, &self.strategy_id)
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.manager.is_suspended(self.user_id, &self.strategy_id)
+    }
+
+    /// 交给AI客户端或网关的调用配额，策略停止后不再限制
+    pub fn ai_call_quota(&self) -> AICallQuota {
+        AICallQuota {
+            manager: self.manager.clone(),
+            user_id: self.user_id,
+            strategy_id: self.strategy_id.clone(),
+        }
+    }
+}
+
+impl Drop for StrategyQuotaGuard {
+    fn drop(&mut self) {
+        self.manager.unregister(self.user_id, &self.strategy_id);
+    }
+}
+
+/// 策略的AI调用配额，不占用并发名额，可随AI客户端克隆
+#[derive(Clone)]
+pub struct AICallQuota {
+    manager: QuotaManager,
+    user_id: Uuid,
+    strategy_id: String,
+}
+
+impl AICallQuota {
+    /// 发起模型调用前检查，未超限时计入本次调用
+    pub fn check(&self) -> Result<(), QuotaExceeded> {
+        self.manager.check_ai_call(self.user_id, &self.strategy_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(quota: StrategyQuota, user_id: Uuid) -> QuotaManager {
+        let mut config = QuotaConfig::default();
+        config.tiers.insert("basic".to_string(), quota);
+        config.users.insert(user_id, "basic".to_string());
+        QuotaManager::new(config)
+    }
+
+    #[test]
+    fn test_concurrent_strategies_per_user() {
+        let user_id = Uuid::new_v4();
+        let quotas = manager(
+            StrategyQuota {
+                max_concurrent_strategies: 1,
+                ..Default::default()
+            },
+            user_id,
+        );
+        let mut events = quotas.subscribe();
+
+        let first = quotas.register(user_id, "ema-1").unwrap();
+        let err = quotas.register(user_id, "ema-2").err().unwrap();
+        assert_eq!(err.kind, QuotaKind::ConcurrentStrategies);
+        assert_eq!(events.try_recv().unwrap().kind, QuotaKind::ConcurrentStrategies);
+
+        // 其他用户使用默认配额，不受影响
+        let _other = quotas.register(Uuid::new_v4(), "ema-3").unwrap();
+
+        drop(first);
+        assert!(quotas.register(user_id, "ema-2").is_ok());
+    }
+
+    #[test]
+    fn test_same_strategy_id_across_users() {
+        let user_id = Uuid::new_v4();
+        let quotas = manager(
+            StrategyQuota {
+                max_concurrent_strategies: 1,
+                max_ai_calls_per_hour: 1,
+                ..Default::default()
+            },
+            user_id,
+        );
+        let other = Uuid::new_v4();
+        let _own = quotas.register(user_id, "ema-1").unwrap();
+        let theirs = quotas.register(other, "ema-1").unwrap();
+
+        // 状态互不共享，其他用户的调用不占用本用户的配额
+        let ai_calls = theirs.ai_call_quota();
+        assert!(ai_calls.check().is_ok());
+        assert!(quotas.check_ai_call(user_id, "ema-1").is_ok());
+        assert!(quotas.check_ai_call(user_id, "ema-1").is_err());
+
+        // 释放其他用户的守卫不影响本用户的策略
+        drop(theirs);
+        assert!(quotas.usage(other, "ema-1").is_none());
+        assert!(quotas.usage(user_id, "ema-1").is_some());
+        assert!(ai_calls.check().is_ok());
+        assert_eq!(
+            quotas.register(user_id, "ema-2").err().unwrap().kind,
+            QuotaKind::ConcurrentStrategies
+        );
+    }
+
+    #[test]
+    fn test_suspension_on_overruns_and_order_rate() {
+        let user_id = Uuid::new_v4();
+        let quotas = manager(
+            StrategyQuota {
+                max_tick_cpu_ms: 10,
+                max_tick_overruns: 2,
+                max_ai_calls_per_hour: 1,
+                max_orders_per_minute: 2,
+                ..Default::default()
+            },
+            user_id,
+        );
+        let mut events = quotas.subscribe();
+        let guard = quotas.register(user_id, "boll-1").unwrap();
+
+        // 超时后恢复正常会清零连续超时次数
+        assert!(guard.record_tick(Duration::from_millis(20)).is_err());
+        assert!(guard.record_tick(Duration::from_millis(5)).is_ok());
+        assert!(guard.record_tick(Duration::from_millis(20)).is_err());
+        assert!(!guard.is_suspended());
+        assert!(guard.record_tick(Duration::from_millis(20)).is_err());
+        assert!(guard.is_suspended());
+        let suspended: Vec<bool> = std::iter::from_fn(|| events.try_recv().ok()).map(|e| e.suspended).collect();
+        assert_eq!(suspended, vec![false, false, true]);
+
+        assert!(quotas.resume(user_id, "boll-1"));
+        assert!(guard.check_ai_call().is_ok());
+        assert_eq!(guard.check_ai_call().unwrap_err().kind, QuotaKind::AiCalls);
+        assert!(!guard.is_suspended());
+
+        assert!(guard.check_order().is_ok());
+        assert!(guard.check_order().is_ok());
+        assert_eq!(guard.check_order().unwrap_err().kind, QuotaKind::OrderRate);
+        assert!(guard.is_suspended());
+
+        let usage = quotas.usage(user_id, "boll-1").unwrap();
+        assert_eq!(usage.ai_calls_last_hour, 1);
+        assert_eq!(usage.orders_last_minute, 2);
+    }
+}
@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::{strategy_tag, ArtifactKind, ArtifactVersion, DeploymentState, LiveOrderTagger, RegistryStore, SemVer};
+
+/// 注册新版本的请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewArtifact {
+    pub name: String,
+    pub kind: ArtifactKind,
+    pub version: SemVer,
+    #[serde(default)]
+    pub artifact_uri: Option<String>,
+    #[serde(default)]
+    pub checksum: Option<String>,
+    #[serde(default)]
+    pub config: serde_json::Value,
+    #[serde(default)]
+    pub parent_version: Option<SemVer>,
+    #[serde(default)]
+    pub backtest_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// 实盘版本切换结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RollbackResult {
+    pub name: String,
+    pub from: Option<SemVer>,
+    pub to: SemVer,
+    /// 替换了策略标签的活跃订单数
+    pub retagged_orders: u64,
+    /// 版本已切换但订单标签替换失败
+    pub retag_error: Option<String>,
+}
+
+/// 选择回滚目标：指定版本，或最近一次上过实盘的非当前版本
+pub fn rollback_target(
+    versions: &[ArtifactVersion],
+    current: Option<&SemVer>,
+    requested: Option<&SemVer>,
+) -> Result<SemVer> {
+    match requested {
+        Some(requested) => {
+            if Some(requested) == current {
+                return Err(anyhow!("{} is already live", requested));
+            }
+            versions
+                .iter()
+                .find(|v| &v.version == requested)
+                .map(|v| v.version)
+                .ok_or_else(|| anyhow!("Version {} not found", requested))
+        }
+        None => versions
+            .iter()
+            .filter(|v| Some(&v.version) != current && v.promoted_at.is_some())
+            .max_by_key(|v| v.promoted_at)
+            .map(|v| v.version)
+            .ok_or_else(|| anyhow!("No previously live version to roll back to")),
+    }
+}
+
+/// 策略/模型注册中心
+pub struct ArtifactRegistry {
+    store: RegistryStore,
+    tagger: Arc<dyn LiveOrderTagger>,
+}
+
+impl ArtifactRegistry {
+    pub fn new(store: RegistryStore, tagger: Arc<dyn LiveOrderTagger>) -> Self {
+        Self { store, tagger }
+    }
+
+    /// 注册新版本，新版本以影子模式开始
+    pub async fn register(&self, request: NewArtifact) -> Result<ArtifactVersion> {
+        let existing = self.store.list(&request.name).await?;
+        if let Some(latest) = existing.first() {
+            if latest.kind != request.kind {
+                return Err(anyhow!(
+                    "{} is registered as {}, not {}",
+                    request.name,
+                    latest.kind.as_str(),
+                    request.kind.as_str()
+                ));
+            }
+            if request.version <= latest.version {
+                return Err(anyhow!(
+                    "Version {} must be greater than latest {}",
+                    request.version,
+                    latest.version
+                ));
+            }
+        }
+        if let Some(parent) = &request.parent_version {
+            if !existing.iter().any(|v| &v.version == parent) {
+                return Err(anyhow!("Parent version {} not found", parent));
+            }
+        }
+
+        let artifact = ArtifactVersion {
+            id: Uuid::new_v4(),
+            name: request.name,
+            kind: request.kind,
+            version: request.version,
+            state: DeploymentState::Shadow,
+            artifact_uri: request.artifact_uri,
+            checksum: request.checksum,
+            config: request.config,
+            // 未指定时默认派生自当前最新版本
+            parent_version: request.parent_version.or(existing.first().map(|v| v.version)),
+            backtest_ids: request.backtest_ids,
+            notes: request.notes,
+            created_at: Utc::now(),
+            promoted_at: None,
+            retired_at: None,
+        };
+        self.store.insert(&artifact).await?;
+        info!("Registered {}@{}", artifact.name, artifact.version);
+        Ok(artifact)
+    }
+
+    pub async fn versions(&self, name: &str) -> Result<Vec<ArtifactVersion>> {
+        self.store.list(name).await
+    }
+
+    pub async fn live(&self, name: &str) -> Result<Option<ArtifactVersion>> {
+        Ok(self
+            .store
+            .list(name)
+            .await?
+            .into_iter()
+            .find(|v| v.state == DeploymentState::Live))
+    }
+
+    /// 修改部署状态，上线时自动下线原实盘版本
+    pub async fn set_state(&self, name: &str, version: &SemVer, state: DeploymentState) -> Result<Option<RollbackResult>> {
+        match state {
+            DeploymentState::Live => self.switch_live(name, version).await.map(Some),
+            _ => {
+                self.store.set_state(name, version, state).await?;
+                info!("{}@{} is now {}", name, version, state.as_str());
+                Ok(None)
+            }
+        }
+    }
+
+    /// 一键回滚到指定版本或上一个实盘版本，并替换活跃订单的策略标签
+    pub async fn rollback(&self, name: &str, to: Option<SemVer>) -> Result<RollbackResult> {
+        let versions = self.store.list(name).await?;
+        let current = versions
+            .iter()
+            .find(|v| v.state == DeploymentState::Live)
+            .map(|v| v.version);
+        let target = rollback_target(&versions, current.as_ref(), to.as_ref())?;
+        self.switch_live(name, &target).await
+    }
+
+    async fn switch_live(&self, name: &str, to: &SemVer) -> Result<RollbackResult> {
+        let from = self.live(name).await?.map(|v| v.version);
+        if from.as_ref() == Some(to) {
+            return Err(anyhow!("{}@{} is already live", name, to));
+        }
+        self.store.swap_live(name, to).await?;
+        info!(
+            "Switched live version of {} from {} to {}",
+            name,
+            from.map(|v| v.to_string()).unwrap_or_else(|| "none".to_string()),
+            to
+        );
+
+        let mut result = RollbackResult {
+            name: name.to_string(),
+            from,
+            to: *to,
+            retagged_orders: 0,
+            retag_error: None,
+        };
+        if let Some(from) = &from {
+            match self
+                .tagger
+                .retag(&strategy_tag(name, from), &strategy_tag(name, to))
+                .await
+            {
+                Ok(updated) => result.retagged_orders = updated,
+                Err(e) => {
+                    error!("Failed to retag live orders for {}: {}", name, e);
+                    result.retag_error = Some(e.to_string());
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// 关联验证某版本的回测
+    pub async fn link_backtest(&self, name: &str, version: &SemVer, backtest_id: Uuid) -> Result<()> {
+        self.store.link_backtest(name, version, backtest_id).await
+    }
+
+    /// 版本谱系：从指定版本沿父版本追溯到初始版本
+    pub async fn lineage(&self, name: &str, version: &SemVer) -> Result<Vec<ArtifactVersion>> {
+        let versions = self.store.list(name).await?;
+        let mut lineage = Vec::new();
+        let mut next = Some(*version);
+        while let Some(current) = next {
+            let artifact = versions
+                .iter()
+                .find(|v| v.version == current)
+                .ok_or_else(|| anyhow!("{}@{} not found", name, current))?;
+            // 父版本号总是更小，不会成环
+            next = artifact.parent_version.filter(|parent| parent < &current);
+            lineage.push(artifact.clone());
+        }
+        Ok(lineage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn version(v: &str, state: DeploymentState, promoted_minutes_ago: Option<i64>) -> ArtifactVersion {
+        ArtifactVersion {
+            id: Uuid::new_v4(),
+            name: "grid".to_string(),
+            kind: ArtifactKind::Strategy,
+            version: v.parse().unwrap(),
+            state,
+            artifact_uri: None,
+            checksum: None,
+            config: serde_json::Value::Null,
+            parent_version: None,
+            backtest_ids: Vec::new(),
+            notes: None,
+            created_at: Utc::now(),
+            promoted_at: promoted_minutes_ago.map(|m| Utc::now() - Duration::minutes(m)),
+            retired_at: None,
+        }
+    }
+
+    #[test]
+    fn test_rollback_picks_last_live_version() {
+        let versions = vec![
+            version("1.3.0", DeploymentState::Shadow, None),
+            version("1.2.0", DeploymentState::Live, Some(5)),
+            version("1.1.0", DeploymentState::Retired, Some(60)),
+            version("1.0.0", DeploymentState::Retired, Some(600)),
+        ];
+        let current = SemVer::new(1, 2, 0);
+
+        assert_eq!(rollback_target(&versions, Some(&current), None).unwrap(), SemVer::new(1, 1, 0));
+        assert_eq!(
+            rollback_target(&versions, Some(&current), Some(&SemVer::new(1, 0, 0))).unwrap(),
+            SemVer::new(1, 0, 0)
+        );
+        assert!(rollback_target(&versions, Some(&current), Some(&current)).is_err());
+        assert!(rollback_target(&versions, Some(&current), Some(&SemVer::new(9, 0, 0))).is_err());
+    }
+
+    #[test]
+    fn test_rollback_without_history_fails() {
+        let versions = vec![version("1.0.0", DeploymentState::Live, Some(5))];
+        assert!(rollback_target(&versions, Some(&SemVer::new(1, 0, 0)), None).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+pub mod manager;
+pub mod store;
+pub mod tagger;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
+
+pub use manager::{ArtifactRegistry, RollbackResult};
+pub use store::RegistryStore;
+pub use tagger::{LiveOrderTagger, TradingEngineTagger};
+
+/// 语义化版本号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl SemVer {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch }
+    }
+}
+
+impl std::fmt::Display for SemVer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl std::str::FromStr for SemVer {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().trim_start_matches('v').split('.').collect();
+        if parts.len() != 3 {
+            return Err(anyhow!("Invalid semantic version: {}", s));
+        }
+        let parse = |part: &str| {
+            part.parse::<u64>()
+                .map_err(|_| anyhow!("Invalid semantic version: {}", s))
+        };
+        Ok(Self::new(parse(parts[0])?, parse(parts[1])?, parse(parts[2])?))
+    }
+}
+
+impl Serialize for SemVer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SemVer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// 注册对象类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Strategy,
+    /// LLM提示词/配置
+    AiModel,
+    /// ONNX模型文件
+    OnnxModel,
+}
+
+/// 部署状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentState {
+    /// 影子运行：接收行情、产生信号，但不下单
+    Shadow,
+    /// 实盘
+    Live,
+    /// 已下线，可回滚
+    Retired,
+}
+
+impl DeploymentState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeploymentState::Shadow => "shadow",
+            DeploymentState::Live => "live",
+            DeploymentState::Retired => "retired",
+        }
+    }
+}
+
+impl std::str::FromStr for DeploymentState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "shadow" => Ok(DeploymentState::Shadow),
+            "live" => Ok(DeploymentState::Live),
+            "retired" => Ok(DeploymentState::Retired),
+            _ => Err(anyhow!("Invalid deployment state: {}", s)),
+        }
+    }
+}
+
+impl ArtifactKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtifactKind::Strategy => "strategy",
+            ArtifactKind::AiModel => "ai_model",
+            ArtifactKind::OnnxModel => "onnx_model",
+        }
+    }
+}
+
+impl std::str::FromStr for ArtifactKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strategy" => Ok(ArtifactKind::Strategy),
+            "ai_model" => Ok(ArtifactKind::AiModel),
+            "onnx_model" => Ok(ArtifactKind::OnnxModel),
+            _ => Err(anyhow!("Invalid artifact kind: {}", s)),
+        }
+    }
+}
+
+/// 注册的版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactVersion {
+    pub id: Uuid,
+    pub name: String,
+    pub kind: ArtifactKind,
+    pub version: SemVer,
+    pub state: DeploymentState,
+    /// 产物位置，例如模型文件路径或策略代码地址
+    pub artifact_uri: Option<String>,
+    pub checksum: Option<String>,
+    /// 策略参数或模型配置
+    pub config: serde_json::Value,
+    /// 派生自哪个版本
+    pub parent_version: Option<SemVer>,
+    /// 验证该版本的回测
+    pub backtest_ids: Vec<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// 最近一次上线时间
+    pub promoted_at: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+/// 活跃订单上标识策略版本的标签
+pub fn strategy_tag(name: &str, version: &SemVer) -> String {
+    format!("strategy:{}@{}", name, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semver_parse_and_order() {
+        let a: SemVer = "1.2.3".parse().unwrap();
+        let b: SemVer = "v1.10.0".parse().unwrap();
+        assert!(a < b);
+        assert_eq!(b.to_string(), "1.10.0");
+        assert!("1.2".parse::<SemVer>().is_err());
+        assert!("1.2.x".parse::<SemVer>().is_err());
+
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, "\"1.2.3\"");
+        assert_eq!(serde_json::from_str::<SemVer>(&json).unwrap(), a);
+    }
+
+    #[test]
+    fn test_strategy_tag() {
+        assert_eq!(strategy_tag("grid", &SemVer::new(2, 0, 1)), "strategy:grid@2.0.1");
+    }
+}
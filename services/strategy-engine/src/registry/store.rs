@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{ArtifactVersion, DeploymentState, SemVer};
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS artifact_versions (
+        id UUID PRIMARY KEY,
+        name TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        version TEXT NOT NULL,
+        major BIGINT NOT NULL,
+        minor BIGINT NOT NULL,
+        patch BIGINT NOT NULL,
+        state TEXT NOT NULL,
+        artifact_uri TEXT,
+        checksum TEXT,
+        config JSONB NOT NULL DEFAULT '{}'::jsonb,
+        parent_version TEXT,
+        backtest_ids UUID[] NOT NULL DEFAULT '{}',
+        notes TEXT,
+        created_at TIMESTAMPTZ NOT NULL,
+        promoted_at TIMESTAMPTZ,
+        retired_at TIMESTAMPTZ,
+        UNIQUE (name, version)
+    )
+"#;
+
+/// 策略/模型版本存储
+#[derive(Clone)]
+pub struct RegistryStore {
+    pool: Arc<PgPool>,
+}
+
+impl RegistryStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        sqlx::query(SCHEMA).execute(&*self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn insert(&self, artifact: &ArtifactVersion) -> Result<()> {
+        let query = r#"
+            INSERT INTO artifact_versions (
+                id, name, kind, version, major, minor, patch, state, artifact_uri,
+                checksum, config, parent_version, backtest_ids, notes, created_at,
+                promoted_at, retired_at
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17
+            )
+        "#;
+
+        sqlx::query(query)
+            .bind(artifact.id)
+            .bind(&artifact.name)
+            .bind(artifact.kind.as_str())
+            .bind(artifact.version.to_string())
+            .bind(artifact.version.major as i64)
+            .bind(artifact.version.minor as i64)
+            .bind(artifact.version.patch as i64)
+            .bind(artifact.state.as_str())
+            .bind(&artifact.artifact_uri)
+            .bind(&artifact.checksum)
+            .bind(&artifact.config)
+            .bind(artifact.parent_version.map(|v| v.to_string()))
+            .bind(&artifact.backtest_ids)
+            .bind(&artifact.notes)
+            .bind(artifact.created_at)
+            .bind(artifact.promoted_at)
+            .bind(artifact.retired_at)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, name: &str, version: &SemVer) -> Result<Option<ArtifactVersion>> {
+        let row = sqlx::query("SELECT * FROM artifact_versions WHERE name = $1 AND version = $2")
+            .bind(name)
+            .bind(version.to_string())
+            .fetch_optional(&*self.pool)
+            .await?;
+        row.map(Self::row_to_artifact).transpose()
+    }
+
+    /// 按版本号从新到旧列出
+    pub async fn list(&self, name: &str) -> Result<Vec<ArtifactVersion>> {
+        let rows = sqlx::query(
+            "SELECT * FROM artifact_versions WHERE name = $1 ORDER BY major DESC, minor DESC, patch DESC",
+        )
+        .bind(name)
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_artifact).collect()
+    }
+
+    pub async fn names(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query("SELECT DISTINCT name FROM artifact_versions ORDER BY name")
+            .fetch_all(&*self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|row| row.get("name")).collect())
+    }
+
+    pub async fn set_state(&self, name: &str, version: &SemVer, state: DeploymentState) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE artifact_versions SET
+                state = $3,
+                retired_at = CASE WHEN $3 = 'retired' THEN $4 ELSE retired_at END
+            WHERE name = $1 AND version = $2
+            "#,
+        )
+        .bind(name)
+        .bind(version.to_string())
+        .bind(state.as_str())
+        .bind(Utc::now())
+        .execute(&*self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("{}@{} not found", name, version));
+        }
+        Ok(())
+    }
+
+    /// 在一个事务中切换实盘版本：原实盘版本下线，目标版本上线
+    pub async fn swap_live(&self, name: &str, to: &SemVer) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE artifact_versions SET state = 'retired', retired_at = $2 WHERE name = $1 AND state = 'live'",
+        )
+        .bind(name)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query(
+            "UPDATE artifact_versions SET state = 'live', promoted_at = $3, retired_at = NULL WHERE name = $1 AND version = $2",
+        )
+        .bind(name)
+        .bind(to.to_string())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("{}@{} not found", name, to));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 关联验证该版本的回测
+    pub async fn link_backtest(&self, name: &str, version: &SemVer, backtest_id: Uuid) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE artifact_versions SET backtest_ids = array_append(backtest_ids, $3)
+            WHERE name = $1 AND version = $2 AND NOT ($3 = ANY(backtest_ids))
+            "#,
+        )
+        .bind(name)
+        .bind(version.to_string())
+        .bind(backtest_id)
+        .execute(&*self.pool)
+        .await?;
+        if result.rows_affected() == 0 && self.get(name, version).await?.is_none() {
+            return Err(anyhow!("{}@{} not found", name, version));
+        }
+        Ok(())
+    }
+
+    fn row_to_artifact(row: PgRow) -> Result<ArtifactVersion> {
+        let parent_version: Option<String> = row.try_get("parent_version")?;
+        Ok(ArtifactVersion {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            kind: row.try_get::<String, _>("kind")?.parse()?,
+            version: row.try_get::<String, _>("version")?.parse()?,
+            state: row.try_get::<String, _>("state")?.parse()?,
+            artifact_uri: row.try_get("artifact_uri")?,
+            checksum: row.try_get("checksum")?,
+            config: row.try_get("config")?,
+            parent_version: parent_version.map(|v| v.parse()).transpose()?,
+            backtest_ids: row.try_get("backtest_ids")?,
+            notes: row.try_get("notes")?,
+            created_at: row.try_get("created_at")?,
+            promoted_at: row.try_get("promoted_at")?,
+            retired_at: row.try_get("retired_at")?,
+        })
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// 替换活跃订单的策略标签
+#[async_trait]
+pub trait LiveOrderTagger: Send + Sync {
+    /// 返回更新的订单数
+    async fn retag(&self, from_tag: &str, to_tag: &str) -> Result<u64>;
+}
+
+#[derive(Debug, Deserialize)]
+struct RetagResponse {
+    success: bool,
+    data: Option<RetagData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RetagData {
+    updated: u64,
+}
+
+/// 通过交易引擎接口替换订单标签
+pub struct TradingEngineTagger {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl TradingEngineTagger {
+    pub fn new(base_url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl LiveOrderTagger for TradingEngineTagger {
+    async fn retag(&self, from_tag: &str, to_tag: &str) -> Result<u64> {
+        let response: RetagResponse = self
+            .client
+            .post(format!("{}/api/v1/orders/strategy-tag", self.base_url))
+            .json(&serde_json::json!({ "from_tag": from_tag, "to_tag": to_tag }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        match response.data {
+            Some(data) if response.success => Ok(data.updated),
+            _ => Err(anyhow!("Trading engine rejected order retag")),
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{NativeStrategy, StrategyAction};
+use crate::ai::strategy_generator::PricePoint;
+
+/// 回测参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BacktestConfig {
+    pub initial_capital: Decimal,
+    /// 单边手续费率
+    pub fee_rate: Decimal,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            initial_capital: Decimal::from(10_000),
+            fee_rate: Decimal::new(1, 3),
+        }
+    }
+}
+
+/// 一笔完整的开平仓
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestTrade {
+    pub entry_time: i64,
+    pub entry_price: Decimal,
+    pub exit_time: i64,
+    pub exit_price: Decimal,
+    pub quantity: Decimal,
+    /// 扣除双边手续费后的盈亏
+    pub pnl: Decimal,
+}
+
+/// 回测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub strategy: String,
+    pub trades: Vec<BacktestTrade>,
+    /// 期末权益，未平仓位按最后收盘价计
+    pub final_equity: Decimal,
+    pub total_return: Decimal,
+    pub max_drawdown: Decimal,
+    pub win_rate: Option<Decimal>,
+}
+
+struct OpenPosition {
+    entry_time: i64,
+    entry_price: Decimal,
+    quantity: Decimal,
+    cost: Decimal,
+}
+
+/// 按收盘价成交的全仓回测
+pub fn run_backtest(
+    strategy: &mut dyn NativeStrategy,
+    candles: &[PricePoint],
+    config: &BacktestConfig,
+) -> BacktestReport {
+    let mut cash = config.initial_capital;
+    let mut position: Option<OpenPosition> = None;
+    let mut trades = Vec::new();
+    let mut peak = cash;
+    let mut max_drawdown = Decimal::ZERO;
+
+    for candle in candles {
+        if candle.close <= Decimal::ZERO {
+            continue;
+        }
+        match strategy.on_candle(candle, position.is_some()) {
+            Some(StrategyAction::EnterLong) if position.is_none() => {
+                let quantity = cash / (candle.close * (Decimal::ONE + config.fee_rate));
+                position = Some(OpenPosition {
+                    entry_time: candle.timestamp,
+                    entry_price: candle.close,
+                    quantity,
+                    cost: cash,
+                });
+                cash = Decimal::ZERO;
+            }
+            Some(StrategyAction::ExitLong) => {
+                if let Some(open) = position.take() {
+                    let proceeds = open.quantity * candle.close * (Decimal::ONE - config.fee_rate);
+                    cash += proceeds;
+                    trades.push(BacktestTrade {
+                        entry_time: open.entry_time,
+                        entry_price: open.entry_price,
+                        exit_time: candle.timestamp,
+                        exit_price: candle.close,
+                        quantity: open.quantity.round_dp(8),
+                        pnl: (proceeds - open.cost).round_dp(8),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        let equity = cash + position.as_ref().map_or(Decimal::ZERO, |p| p.quantity * candle.close);
+        peak = peak.max(equity);
+        if peak > Decimal::ZERO {
+            max_drawdown = max_drawdown.max((peak - equity) / peak);
+        }
+    }
+
+    let last_close = candles.last().map_or(Decimal::ZERO, |c| c.close);
+    let final_equity = cash + position.map_or(Decimal::ZERO, |p| p.quantity * last_close);
+    let total_return = if config.initial_capital.is_zero() {
+        Decimal::ZERO
+    } else {
+        (final_equity - config.initial_capital) / config.initial_capital
+    };
+    let win_rate = (!trades.is_empty()).then(|| {
+        let wins = trades.iter().filter(|t| t.pnl > Decimal::ZERO).count();
+        (Decimal::from(wins) / Decimal::from(trades.len())).round_dp(4)
+    });
+
+    BacktestReport {
+        strategy: strategy.name().to_string(),
+        trades,
+        final_equity: final_equity.round_dp(8),
+        total_return: total_return.round_dp(6),
+        max_drawdown: max_drawdown.round_dp(6),
+        win_rate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::ReferenceStrategyConfig;
+
+    fn series(closes: &[i64]) -> Vec<PricePoint> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let close = Decimal::from(*close);
+                PricePoint {
+                    timestamp: i as i64 * 60_000,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: Decimal::ONE,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reference_strategies_backtest() {
+        // 震荡后单边上涨
+        let closes: Vec<i64> = (0..40)
+            .map(|i| if i % 2 == 0 { 100 } else { 102 })
+            .chain((0..40).map(|i| 100 + i * 2))
+            .collect();
+        let candles = series(&closes);
+        let config = BacktestConfig::default();
+
+        let mut momentum = ReferenceStrategyConfig::EmaCross(Default::default()).build().unwrap();
+        let report = run_backtest(momentum.as_mut(), &candles, &config);
+        assert_eq!(report.strategy, "ema_cross");
+        // 趋势行情中动量策略持仓到期末并盈利
+        assert!(report.total_return > Decimal::new(2, 1));
+
+        let mut reversion = ReferenceStrategyConfig::BollingerReversion(Default::default())
+            .build()
+            .unwrap();
+        let report = run_backtest(reversion.as_mut(), &candles, &config);
+        assert_eq!(report.strategy, "bollinger_reversion");
+        assert!(report.max_drawdown >= Decimal::ZERO);
+        assert!(report.final_equity > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_round_trip_pnl() {
+        struct Scripted(usize);
+        impl NativeStrategy for Scripted {
+            fn name(&self) -> &'static str {
+                "scripted"
+            }
+            fn on_candle(&mut self, _: &PricePoint, _: bool) -> Option<StrategyAction> {
+                self.0 += 1;
+                match self.0 {
+                    1 => Some(StrategyAction::EnterLong),
+                    3 => Some(StrategyAction::ExitLong),
+                    _ => None,
+                }
+            }
+        }
+
+        let config = BacktestConfig {
+            initial_capital: Decimal::from(1_000),
+            fee_rate: Decimal::ZERO,
+        };
+        let report = run_backtest(&mut Scripted(0), &series(&[100, 80, 110, 50]), &config);
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.trades[0].pnl, Decimal::from(100));
+        assert_eq!(report.final_equity, Decimal::from(1_100));
+        assert_eq!(report.total_return, Decimal::new(1, 1));
+        assert_eq!(report.max_drawdown, Decimal::new(2, 1));
+        assert_eq!(report.win_rate, Some(Decimal::ONE));
+    }
+}
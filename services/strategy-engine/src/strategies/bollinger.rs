@@ -0,0 +1,123 @@
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::{NativeStrategy, StrategyAction};
+use crate::ai::strategy_generator::PricePoint;
+
+/// 布林带均值回归策略参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BollingerConfig {
+    pub period: usize,
+    /// 上下轨距中轨的标准差倍数
+    pub std_dev: f64,
+    /// true 时回到中轨平仓，否则触及上轨平仓
+    pub exit_at_mean: bool,
+}
+
+impl Default for BollingerConfig {
+    fn default() -> Self {
+        Self {
+            period: 20,
+            std_dev: 2.0,
+            exit_at_mean: true,
+        }
+    }
+}
+
+/// 布林带均值回归策略
+///
+/// 收盘价跌破下轨时开多，回到中轨（或上轨）时平仓。
+pub struct BollingerReversion {
+    config: BollingerConfig,
+    window: VecDeque<f64>,
+}
+
+impl BollingerReversion {
+    pub fn new(config: BollingerConfig) -> Self {
+        Self {
+            window: VecDeque::with_capacity(config.period),
+            config,
+        }
+    }
+
+    /// (下轨, 中轨, 上轨)
+    fn bands(&self) -> (f64, f64, f64) {
+        let n = self.window.len() as f64;
+        let mean = self.window.iter().sum::<f64>() / n;
+        let variance = self.window.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / n;
+        let width = variance.sqrt() * self.config.std_dev;
+        (mean - width, mean, mean + width)
+    }
+}
+
+impl NativeStrategy for BollingerReversion {
+    fn name(&self) -> &'static str {
+        "bollinger_reversion"
+    }
+
+    fn on_candle(&mut self, candle: &PricePoint, in_position: bool) -> Option<StrategyAction> {
+        let close = candle.close.to_f64()?;
+        if self.window.len() == self.config.period {
+            self.window.pop_front();
+        }
+        self.window.push_back(close);
+        if self.window.len() < self.config.period {
+            return None;
+        }
+
+        let (lower, middle, upper) = self.bands();
+        let exit_level = if self.config.exit_at_mean { middle } else { upper };
+        if !in_position && close < lower {
+            Some(StrategyAction::EnterLong)
+        } else if in_position && close >= exit_level {
+            Some(StrategyAction::ExitLong)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn candle(timestamp: i64, close: Decimal) -> PricePoint {
+        PricePoint {
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: Decimal::ONE,
+        }
+    }
+
+    #[test]
+    fn test_reversion_signals() {
+        let mut strategy = BollingerReversion::new(BollingerConfig {
+            period: 5,
+            std_dev: 1.5,
+            ..Default::default()
+        });
+        let closes = ["100", "101", "100", "101", "100", "101", "94", "97", "101"];
+
+        let mut in_position = false;
+        let mut actions = Vec::new();
+        for (i, close) in closes.iter().enumerate() {
+            let candle = candle(i as i64, close.parse().unwrap());
+            if let Some(action) = strategy.on_candle(&candle, in_position) {
+                in_position = action == StrategyAction::EnterLong;
+                actions.push((i, action));
+            }
+        }
+
+        // 急跌跌破下轨开多，回到中轨平仓
+        assert_eq!(
+            actions,
+            vec![(6, StrategyAction::EnterLong), (8, StrategyAction::ExitLong)]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use super::{NativeStrategy, StrategyAction};
+use crate::ai::strategy_generator::PricePoint;
+
+/// EMA交叉动量策略参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmaCrossConfig {
+    pub fast_period: usize,
+    pub slow_period: usize,
+}
+
+impl Default for EmaCrossConfig {
+    fn default() -> Self {
+        Self {
+            fast_period: 12,
+            slow_period: 26,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    fn new(period: usize) -> Self {
+        Self {
+            alpha: 2.0 / (period as f64 + 1.0),
+            value: None,
+        }
+    }
+
+    fn update(&mut self, price: f64) -> f64 {
+        let value = match self.value {
+            Some(prev) => prev + self.alpha * (price - prev),
+            None => price,
+        };
+        self.value = Some(value);
+        value
+    }
+}
+
+/// EMA交叉动量策略
+///
+/// 快线上穿慢线时开多，下穿时平仓；慢线周期的K线数量之前不产生信号。
+pub struct EmaCross {
+    config: EmaCrossConfig,
+    fast: Ema,
+    slow: Ema,
+    seen: usize,
+    prev_diff: Option<f64>,
+}
+
+impl EmaCross {
+    pub fn new(config: EmaCrossConfig) -> Self {
+        Self {
+            fast: Ema::new(config.fast_period),
+            slow: Ema::new(config.slow_period),
+            config,
+            seen: 0,
+            prev_diff: None,
+        }
+    }
+}
+
+impl NativeStrategy for EmaCross {
+    fn name(&self) -> &'static str {
+        "ema_cross"
+    }
+
+    fn on_candle(&mut self, candle: &PricePoint, in_position: bool) -> Option<StrategyAction> {
+        let close = candle.close.to_f64()?;
+        let diff = self.fast.update(close) - self.slow.update(close);
+        self.seen += 1;
+        let prev = self.prev_diff.replace(diff);
+        if self.seen <= self.config.slow_period {
+            return None;
+        }
+
+        match prev? {
+            p if p <= 0.0 && diff > 0.0 && !in_position => Some(StrategyAction::EnterLong),
+            p if p >= 0.0 && diff < 0.0 && in_position => Some(StrategyAction::ExitLong),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn candle(timestamp: i64, close: i64) -> PricePoint {
+        let close = Decimal::from(close);
+        PricePoint {
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: Decimal::ONE,
+        }
+    }
+
+    #[test]
+    fn test_cross_signals() {
+        let mut strategy = EmaCross::new(EmaCrossConfig {
+            fast_period: 3,
+            slow_period: 6,
+        });
+        // 下跌后反弹再回落
+        let closes: Vec<i64> = (0..10)
+            .map(|i| 200 - i * 5)
+            .chain((0..10).map(|i| 155 + i * 10))
+            .chain((0..10).map(|i| 245 - i * 10))
+            .collect();
+
+        let mut in_position = false;
+        let mut actions = Vec::new();
+        for (i, close) in closes.iter().enumerate() {
+            if let Some(action) = strategy.on_candle(&candle(i as i64, *close), in_position) {
+                in_position = action == StrategyAction::EnterLong;
+                actions.push((i, action));
+            }
+        }
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].1, StrategyAction::EnterLong);
+        assert!((10..15).contains(&actions[0].0));
+        assert_eq!(actions[1].1, StrategyAction::ExitLong);
+        assert!((20..25).contains(&actions[1].0));
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::Result;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use super::{NativeStrategy, StrategyAction};
+use crate::ai::strategy_generator::PricePoint;
+use crate::execution::{OrderIntent, OrderSink, SubmittedOrder};
+use crate::market_feed::ClosedCandle;
+use crate::quotas::StrategyQuotaGuard;
+
+/// 实盘运行原生策略
+///
+/// 按收盘K线驱动策略，信号转为市价单提交；订单提交成功后才更新持仓状态，
+/// 重复或乱序的K线被忽略。配置资源配额后，策略被暂停期间只推进K线不下单。
+pub struct LiveStrategyRunner {
+    strategy: Box<dyn NativeStrategy>,
+    sink: Arc<dyn OrderSink>,
+    symbol: String,
+    quantity: Decimal,
+    in_position: bool,
+    last_candle: Option<i64>,
+    quota: Option<StrategyQuotaGuard>,
+}
+
+impl LiveStrategyRunner {
+    pub fn new(
+        strategy: Box<dyn NativeStrategy>,
+        sink: Arc<dyn OrderSink>,
+        symbol: String,
+        quantity: Decimal,
+    ) -> Self {
+        Self {
+            strategy,
+            sink,
+            symbol,
+            quantity,
+            in_position: false,
+            last_candle: None,
+            quota: None,
+        }
+    }
+
+    /// 按资源配额限制处理耗时和下单频率
+    pub fn with_quota(mut self, quota: StrategyQuotaGuard) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    pub fn in_position(&self) -> bool {
+        self.in_position
+    }
+
+    /// 处理一根已收盘K线，产生信号时下单
+    pub async fn on_closed_candle(&mut self, candle: &PricePoint) -> Result<Option<SubmittedOrder>> {
+        if self.last_candle.is_some_and(|last| candle.timestamp <= last) {
+            return Ok(None);
+        }
+        self.last_candle = Some(candle.timestamp);
+
+        let started = Instant::now();
+        let action = self.strategy.on_candle(candle, self.in_position);
+        if let Some(quota) = &self.quota {
+            // 超时只在连续超限时暂停，本次信号仍按暂停状态决定是否下单
+            let _ = quota.record_tick(started.elapsed());
+            if quota.is_suspended() {
+                debug!("Strategy {} is suspended, skipping candle {}", quota.strategy_id(), candle.timestamp);
+                return Ok(None);
+            }
+        }
+        let Some(action) = action else {
+            return Ok(None);
+        };
+        let side = match action {
+            StrategyAction::EnterLong => "BUY",
+            StrategyAction::ExitLong => "SELL",
+        };
+        let name = self.strategy.name();
+        let intent = OrderIntent {
+            symbol: self.symbol.clone(),
+            side: side.to_string(),
+            order_type: "MARKET".to_string(),
+            quantity: self.quantity,
+            price: None,
+            // 同一根K线的信号使用相同ID，重试时交易引擎可去重
+            client_order_id: format!("{}-{}-{}", name, self.symbol, candle.timestamp),
+            tags: vec![name.to_string()],
+        };
+
+        if let Some(quota) = &self.quota {
+            quota.check_order()?;
+        }
+        let order = self.sink.submit(&intent).await?;
+        self.in_position = action == StrategyAction::EnterLong;
+        Ok(Some(order))
+    }
+
+    /// 按实时行情的收盘K线驱动策略，直到行情源关闭
+    ///
+    /// 只处理本策略交易对和周期的K线；消费落后丢失的K线不补发，策略从下一根K线继续。
+    pub async fn run(mut self, mut candles: broadcast::Receiver<ClosedCandle>, interval: String) {
+        loop {
+            let closed = match candles.recv().await {
+                Ok(closed) => closed,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Live strategy {} lagged, skipped {} candles", self.symbol, skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if closed.symbol != self.symbol || closed.interval != interval {
+                continue;
+            }
+            if let Err(e) = self.on_closed_candle(&closed.candle).await {
+                warn!("Live strategy {} failed on candle {}: {}", self.symbol, closed.candle.timestamp, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::{BollingerConfig, BollingerReversion};
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        intents: Mutex<Vec<OrderIntent>>,
+    }
+
+    #[async_trait]
+    impl OrderSink for RecordingSink {
+        async fn submit(&self, intent: &OrderIntent) -> Result<SubmittedOrder> {
+            self.intents.lock().await.push(intent.clone());
+            Ok(SubmittedOrder {
+                id: Uuid::new_v4(),
+                client_order_id: Some(intent.client_order_id.clone()),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_live_orders() {
+        let sink = Arc::new(RecordingSink::default());
+        let strategy = BollingerReversion::new(BollingerConfig {
+            period: 5,
+            std_dev: 1.5,
+            ..Default::default()
+        });
+        let mut runner = LiveStrategyRunner::new(
+            Box::new(strategy),
+            sink.clone(),
+            "BTCUSDT".to_string(),
+            Decimal::new(1, 2),
+        );
+
+        for (i, close) in ["100", "101", "100", "101", "100", "101", "94"].iter().enumerate() {
+            let close: Decimal = close.parse().unwrap();
+            let candle = PricePoint {
+                timestamp: i as i64,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: Decimal::ONE,
+            };
+            runner.on_closed_candle(&candle).await.unwrap();
+            // 重复推送的K线不会重复下单
+            runner.on_closed_candle(&candle).await.unwrap();
+        }
+
+        let intents = sink.intents.lock().await;
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].side, "BUY");
+        assert_eq!(intents[0].client_order_id, "bollinger_reversion-BTCUSDT-6");
+        assert!(runner.in_position());
+    }
+
+    #[tokio::test]
+    async fn test_suspended_strategy_stops_ordering() {
+        use crate::quotas::{QuotaConfig, QuotaManager, StrategyQuota};
+
+        let user_id = Uuid::new_v4();
+        let quotas = QuotaManager::new(QuotaConfig {
+            default: StrategyQuota {
+                max_orders_per_minute: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let sink = Arc::new(RecordingSink::default());
+        let strategy = BollingerReversion::new(BollingerConfig {
+            period: 3,
+            std_dev: 1.0,
+            ..Default::default()
+        });
+        let mut runner = LiveStrategyRunner::new(
+            Box::new(strategy),
+            sink.clone(),
+            "BTCUSDT".to_string(),
+            Decimal::ONE,
+        )
+        .with_quota(quotas.register(user_id, "boll-live").unwrap());
+
+        let mut results = Vec::new();
+        for (i, close) in ["100", "101", "100", "90", "120", "90", "120"].iter().enumerate() {
+            let close: Decimal = close.parse().unwrap();
+            let candle = PricePoint {
+                timestamp: i as i64,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: Decimal::ONE,
+            };
+            results.push(runner.on_closed_candle(&candle).await.map(|order| order.is_some()));
+        }
+
+        // 第二笔订单超过下单频率，策略被暂停，之后的K线不再下单
+        assert_eq!(sink.intents.lock().await.len(), 1);
+        assert!(results.iter().any(|result| result.is_err()));
+        assert!(quotas.is_suspended(user_id, "boll-live"));
+        assert!(matches!(results.last(), Some(Ok(false))));
+    }
+}
@@ -0,0 +1,89 @@
+pub mod backtest;
+pub mod bollinger;
+pub mod ema_cross;
+pub mod live;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ai::strategy_generator::PricePoint;
+
+pub use backtest::{run_backtest, BacktestConfig, BacktestReport, BacktestTrade};
+pub use bollinger::{BollingerConfig, BollingerReversion};
+pub use ema_cross::{EmaCross, EmaCrossConfig};
+pub use live::LiveStrategyRunner;
+
+/// 策略动作，参考策略只做多
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyAction {
+    EnterLong,
+    ExitLong,
+}
+
+/// 原生Rust策略
+///
+/// 逐根处理已收盘K线，回测和实盘使用同一实现。
+pub trait NativeStrategy: Send {
+    fn name(&self) -> &'static str;
+
+    /// 处理一根已收盘K线，`in_position` 表示当前是否持仓
+    fn on_candle(&mut self, candle: &PricePoint, in_position: bool) -> Option<StrategyAction>;
+}
+
+/// 内置参考策略配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReferenceStrategyConfig {
+    EmaCross(EmaCrossConfig),
+    BollingerReversion(BollingerConfig),
+}
+
+impl ReferenceStrategyConfig {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::EmaCross(config) => {
+                if config.fast_period == 0 || config.fast_period >= config.slow_period {
+                    return Err(anyhow!("EMA cross requires 0 < fast_period < slow_period"));
+                }
+            }
+            Self::BollingerReversion(config) => {
+                if config.period < 2 {
+                    return Err(anyhow!("Bollinger period must be at least 2"));
+                }
+                if !config.std_dev.is_finite() || config.std_dev <= 0.0 {
+                    return Err(anyhow!("Bollinger std_dev must be positive"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 校验配置并创建策略实例
+    pub fn build(&self) -> Result<Box<dyn NativeStrategy>> {
+        self.validate()?;
+        Ok(match self {
+            Self::EmaCross(config) => Box::new(EmaCross::new(config.clone())),
+            Self::BollingerReversion(config) => Box::new(BollingerReversion::new(config.clone())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_build() {
+        let config: ReferenceStrategyConfig = serde_json::from_str(r#"{"type":"ema_cross"}"#).unwrap();
+        assert_eq!(config.build().unwrap().name(), "ema_cross");
+
+        let config: ReferenceStrategyConfig =
+            serde_json::from_str(r#"{"type":"bollinger_reversion","period":10,"std_dev":1.5}"#).unwrap();
+        assert_eq!(config.build().unwrap().name(), "bollinger_reversion");
+
+        let invalid: ReferenceStrategyConfig =
+            serde_json::from_str(r#"{"type":"ema_cross","fast_period":30,"slow_period":10}"#).unwrap();
+        assert!(invalid.build().is_err());
+    }
+}
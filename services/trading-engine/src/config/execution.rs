@@ -1,19 +1,42 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use shared_utils::config_serde::{decimal, duration};
+use std::collections::HashMap;
 use std::time::Duration;
 
+use crate::engines::matching_engine::{INTERNAL_MAKER_FEE_RATE, INTERNAL_TAKER_FEE_RATE};
+use crate::engines::volatility_regime::VolatilityRegime;
+use crate::models::EnvironmentAccess;
+
 /// 执行引擎配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionConfig {
     pub enabled: bool,
+    #[serde(with = "decimal")]
     pub max_slippage: Decimal,
+    #[serde(with = "duration")]
     pub execution_timeout: Duration,
     pub retry_attempts: u32,
+    #[serde(with = "duration")]
     pub retry_delay: Duration,
     pub algorithms: AlgorithmConfig,
     pub routing: RoutingConfig,
     pub latency: LatencyConfig,
+    #[serde(default)]
+    pub internal_book: InternalBookFeedConfig,
+    #[serde(default)]
+    pub maker_rebates: MakerRebateConfig,
+    #[serde(default)]
+    pub volatility_policy: VolatilityPolicyConfig,
+    #[serde(default)]
+    pub price_collars: PriceCollarConfig,
+    #[serde(default)]
+    pub spread_orders: SpreadOrderConfig,
+    #[serde(default)]
+    pub testnet: TestnetConfig,
+    #[serde(default)]
+    pub book_snapshots: BookSnapshotConfig,
 }
 
 /// 算法配置
@@ -29,27 +52,55 @@ pub struct AlgorithmConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TwapConfig {
     pub enabled: bool,
+    #[serde(with = "duration")]
     pub min_duration: Duration,
+    #[serde(with = "duration")]
     pub max_duration: Duration,
+    #[serde(with = "duration")]
     pub slice_interval: Duration,
+    #[serde(with = "decimal")]
     pub max_participation_rate: Decimal,
 }
 
 /// VWAP (Volume Weighted Average Price) 配置
+///
+/// 子订单按行情服务统计的日内成交量曲线分配数量，每个曲线周期一片。
+/// 取不到成交量曲线时按时间均分。
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VwapConfig {
     pub enabled: bool,
+    /// 统计成交量曲线的历史范围
+    #[serde(with = "duration")]
     pub lookback_period: Duration,
+    #[serde(with = "decimal")]
     pub max_participation_rate: Decimal,
+    /// 执行中按剩余时间片的曲线权重重新分配剩余数量，关闭时未成交数量滚入下一片
     pub volume_curve_adjustment: bool,
+    #[serde(with = "duration")]
+    pub min_duration: Duration,
+    #[serde(with = "duration")]
+    pub max_duration: Duration,
+    /// 行情服务地址
+    pub market_data_url: String,
+    /// 统计成交量曲线使用的交易所
+    pub profile_exchange: String,
+    /// 成交量曲线的K线周期，也是子订单的切片间隔，必须能整除一天
+    pub profile_interval: String,
+    /// 查询成交量曲线的超时
+    #[serde(with = "duration")]
+    pub profile_timeout: Duration,
 }
 
 /// 冰山订单配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IcebergConfig {
     pub enabled: bool,
+    #[serde(with = "decimal")]
     pub min_visible_size: Decimal,
+    #[serde(with = "decimal")]
     pub max_visible_size: Decimal,
+    #[serde(with = "decimal")]
     pub refresh_threshold: Decimal,
     pub randomization: bool,
 }
@@ -58,8 +109,11 @@ pub struct IcebergConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PovConfig {
     pub enabled: bool,
+    #[serde(with = "decimal")]
     pub min_participation_rate: Decimal,
+    #[serde(with = "decimal")]
     pub max_participation_rate: Decimal,
+    #[serde(with = "duration")]
     pub volume_lookback: Duration,
     pub aggressive_mode: bool,
 }
@@ -79,10 +133,14 @@ pub struct VenueConfig {
     pub name: String,
     pub enabled: bool,
     pub priority: u32,
+    #[serde(with = "decimal")]
     pub max_order_size: Decimal,
+    #[serde(with = "decimal")]
     pub min_order_size: Decimal,
+    #[serde(with = "decimal")]
     pub fee_rate: Decimal,
     pub latency_ms: u64,
+    #[serde(with = "decimal")]
     pub reliability_score: Decimal,
 }
 
@@ -100,21 +158,185 @@ pub enum RoutingStrategy {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmartRoutingConfig {
     pub enabled: bool,
+    #[serde(with = "decimal")]
     pub price_improvement_threshold: Decimal,
+    #[serde(with = "decimal")]
     pub latency_weight: Decimal,
+    #[serde(with = "decimal")]
     pub fee_weight: Decimal,
+    #[serde(with = "decimal")]
     pub liquidity_weight: Decimal,
+    #[serde(with = "decimal")]
     pub reliability_weight: Decimal,
 }
 
 /// 延迟配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LatencyConfig {
+    #[serde(with = "duration")]
     pub target_latency: Duration,
+    #[serde(with = "duration")]
     pub max_acceptable_latency: Duration,
     pub latency_monitoring: bool,
     pub latency_alerts: bool,
     pub performance_optimization: PerformanceConfig,
+    #[serde(default)]
+    pub budget: LatencyBudgetConfig,
+}
+
+/// 订单延迟预算配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyBudgetConfig {
+    pub enabled: bool,
+    /// 交易所确认订单的默认时限，超时即撤单
+    #[serde(with = "duration")]
+    pub ack_timeout: Duration,
+    /// 按交易所覆盖确认时限
+    #[serde(default)]
+    #[serde(with = "duration::map")]
+    pub venue_ack_timeouts: HashMap<String, Duration>,
+    /// 超时后是否改投其他交易所
+    pub reroute_on_timeout: bool,
+    pub max_reroutes: u32,
+    /// 生成慢交易所报告所需的最少样本数
+    pub min_samples: u64,
+    /// 超时率超过该值视为慢交易所
+    #[serde(with = "decimal")]
+    pub slow_timeout_rate: Decimal,
+}
+
+/// 内部撮合引擎行情推送配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InternalBookFeedConfig {
+    pub enabled: bool,
+    /// 推送的订单簿档位数
+    pub depth: usize,
+    /// 推送通道容量，订阅方落后超过该值需要重新同步
+    pub channel_capacity: usize,
+}
+
+/// 挂单返佣激励配置
+///
+/// 内部订单簿一侧深度不足时，对该侧挂单给予返佣，返佣率在不平衡度超过阈值后
+/// 从 `min_rebate_rate` 线性增加到 `max_rebate_rate`。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MakerRebateConfig {
+    pub enabled: bool,
+    /// 计算不平衡度使用的订单簿档位数
+    pub depth: usize,
+    /// 触发返佣的不平衡度阈值，取值 [0, 1)
+    #[serde(with = "decimal")]
+    pub imbalance_threshold: Decimal,
+    /// 返佣率下限
+    #[serde(with = "decimal")]
+    pub min_rebate_rate: Decimal,
+    /// 返佣率上限
+    #[serde(with = "decimal")]
+    pub max_rebate_rate: Decimal,
+    /// 按交易对覆盖的返佣率上限
+    pub symbol_max_rebate_rates: HashMap<String, Decimal>,
+}
+
+/// 波动分档执行限制配置
+///
+/// 定期采样标记价格，按采样收益率标准差把交易对划入波动分档，再按分档查表限制执行：
+/// 放宽滑点上限、只允许限价执行、缩小子订单、暂停算法单。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolatilityPolicyConfig {
+    pub enabled: bool,
+    /// 标记价格采样间隔
+    #[serde(with = "duration")]
+    pub sample_interval: Duration,
+    /// 计算波动率使用的收益率个数
+    pub window: usize,
+    /// 低于该值为低波动
+    #[serde(with = "decimal")]
+    pub low_threshold: Decimal,
+    /// 达到该值为高波动
+    #[serde(with = "decimal")]
+    pub high_threshold: Decimal,
+    /// 达到该值为极端波动
+    #[serde(with = "decimal")]
+    pub extreme_threshold: Decimal,
+    /// 读数超过该时长未更新时按正常波动处理
+    #[serde(with = "duration")]
+    pub max_reading_age: Duration,
+    /// 各分档的执行限制，未配置的分档不限制
+    pub policies: HashMap<VolatilityRegime, VolatilityPolicy>,
+}
+
+/// 单个波动分档的执行限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolatilityPolicy {
+    /// 滑点上限的放大倍数
+    #[serde(with = "decimal")]
+    pub slippage_multiplier: Decimal,
+    /// 只允许限价执行，市价单按放宽后的滑点上限转为保护价限价单
+    pub limit_only: bool,
+    /// 子订单上限占交易所单笔上限的比例
+    #[serde(with = "decimal")]
+    pub child_order_ratio: Decimal,
+    /// 暂停算法单
+    pub pause_algorithms: bool,
+}
+
+/// 市价单价格保护配置
+///
+/// 内部撮合的市价单以对手方最优价加减保护幅度作为最差成交价，转为带保护价的
+/// 即时成交单：保护价以内的部分成交，其余撤销；保护价以内没有流动性时拒绝。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceCollarConfig {
+    pub enabled: bool,
+    /// 默认保护幅度，单位基点
+    #[serde(with = "decimal")]
+    pub collar_bps: Decimal,
+    /// 按交易对覆盖的保护幅度
+    pub symbol_collar_bps: HashMap<String, Decimal>,
+}
+
+/// 多腿价差单配置
+///
+/// 各腿按比例依次执行，后续腿按先行腿实际完成的价差单位数下单；
+/// 某条腿成交超出完成单位数的部分按配置反向对冲，避免留下单边敞口。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpreadOrderConfig {
+    pub enabled: bool,
+    /// 单个价差单最多的腿数
+    pub max_legs: usize,
+    /// 是否对冲超出完成单位数的腿
+    pub hedge_residuals: bool,
+}
+
+/// 测试网配置
+///
+/// 启用的交易所额外注册一个测试网连接器。测试网订单只路由到这些连接器，不进入内部撮合；
+/// 实盘订单不会路由到测试网。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TestnetConfig {
+    /// 启用测试网的交易所，目前支持 binance 和 kraken
+    pub exchanges: Vec<String>,
+    /// 未单独设置的账户允许交易的环境
+    pub default_access: EnvironmentAccess,
+}
+
+/// 内部撮合订单簿快照配置
+///
+/// 定期把各交易对的挂单和未触发条件单写入数据库，重启后从快照恢复撮合引擎。
+/// 进程退出时最后一次快照之后的变化会丢失。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BookSnapshotConfig {
+    pub enabled: bool,
+    #[serde(with = "duration")]
+    pub interval: Duration,
+    /// 启动时立即恢复所有有快照的交易对，否则在首次使用时恢复
+    pub restore_on_startup: bool,
 }
 
 /// 性能优化配置
@@ -142,6 +364,13 @@ impl ExecutionConfig {
         self.algorithms.validate()?;
         self.routing.validate()?;
         self.latency.validate()?;
+        self.internal_book.validate()?;
+        self.maker_rebates.validate()?;
+        self.volatility_policy.validate()?;
+        self.price_collars.validate()?;
+        self.spread_orders.validate()?;
+        self.testnet.validate()?;
+        self.book_snapshots.validate()?;
 
         Ok(())
     }
@@ -202,6 +431,23 @@ impl VwapConfig {
             ));
         }
 
+        if self.min_duration >= self.max_duration {
+            return Err(anyhow::anyhow!(
+                "VWAP min duration must be less than max duration"
+            ));
+        }
+
+        let interval = self
+            .profile_interval
+            .parse::<shared_models::Interval>()
+            .map_err(|e| anyhow::anyhow!("Invalid VWAP profile interval: {}", e))?;
+        let interval_ms = interval.to_millis();
+        if interval_ms <= 0 || interval_ms >= 86_400_000 || 86_400_000 % interval_ms != 0 {
+            return Err(anyhow::anyhow!(
+                "VWAP profile interval must evenly divide a day"
+            ));
+        }
+
         Ok(())
     }
 }
@@ -328,7 +574,7 @@ impl LatencyConfig {
             ));
         }
 
-        Ok(())
+        self.budget.validate()
     }
 
     /// 检查延迟是否可接受
@@ -337,6 +583,29 @@ impl LatencyConfig {
     }
 }
 
+impl LatencyBudgetConfig {
+    /// 验证延迟预算配置
+    pub fn validate(&self) -> Result<()> {
+        if self.ack_timeout.is_zero() || self.venue_ack_timeouts.values().any(|t| t.is_zero()) {
+            return Err(anyhow::anyhow!("Ack timeout must be greater than zero"));
+        }
+
+        if self.slow_timeout_rate <= Decimal::ZERO || self.slow_timeout_rate > Decimal::ONE {
+            return Err(anyhow::anyhow!("Slow timeout rate must be in (0, 1]"));
+        }
+
+        Ok(())
+    }
+
+    /// 指定交易所的确认时限
+    pub fn ack_timeout_for(&self, venue: &str) -> Duration {
+        self.venue_ack_timeouts
+            .get(venue)
+            .copied()
+            .unwrap_or(self.ack_timeout)
+    }
+}
+
 impl Default for ExecutionConfig {
     fn default() -> Self {
         Self {
@@ -348,6 +617,13 @@ impl Default for ExecutionConfig {
             algorithms: AlgorithmConfig::default(),
             routing: RoutingConfig::default(),
             latency: LatencyConfig::default(),
+            internal_book: InternalBookFeedConfig::default(),
+            maker_rebates: MakerRebateConfig::default(),
+            volatility_policy: VolatilityPolicyConfig::default(),
+            price_collars: PriceCollarConfig::default(),
+            spread_orders: SpreadOrderConfig::default(),
+            testnet: TestnetConfig::default(),
+            book_snapshots: BookSnapshotConfig::default(),
         }
     }
 }
@@ -379,9 +655,15 @@ impl Default for VwapConfig {
     fn default() -> Self {
         Self {
             enabled: true,
-            lookback_period: Duration::from_secs(3600),
+            lookback_period: Duration::from_secs(7 * 86400),
             max_participation_rate: Decimal::new(3, 1), // 0.3 (30%)
             volume_curve_adjustment: true,
+            min_duration: Duration::from_secs(300),
+            max_duration: Duration::from_secs(8 * 3600),
+            market_data_url: "http://localhost:8083".to_string(),
+            profile_exchange: "binance".to_string(),
+            profile_interval: "5m".to_string(),
+            profile_timeout: Duration::from_secs(3),
         }
     }
 }
@@ -463,6 +745,193 @@ impl Default for LatencyConfig {
             latency_monitoring: true,
             latency_alerts: true,
             performance_optimization: PerformanceConfig::default(),
+            budget: LatencyBudgetConfig::default(),
+        }
+    }
+}
+
+impl Default for InternalBookFeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            depth: 50,
+            channel_capacity: 4096,
+        }
+    }
+}
+
+impl InternalBookFeedConfig {
+    /// 验证内部行情推送配置
+    pub fn validate(&self) -> Result<()> {
+        if self.depth == 0 {
+            return Err(anyhow::anyhow!("Internal book depth must be greater than 0"));
+        }
+        if self.channel_capacity == 0 {
+            return Err(anyhow::anyhow!("Internal book channel capacity must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for MakerRebateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            depth: 10,
+            imbalance_threshold: Decimal::new(3, 1), // 0.3
+            min_rebate_rate: Decimal::new(5, 5),     // 0.005%
+            max_rebate_rate: Decimal::new(2, 4),     // 0.02%
+            symbol_max_rebate_rates: HashMap::new(),
+        }
+    }
+}
+
+impl MakerRebateConfig {
+    /// 验证挂单返佣配置
+    pub fn validate(&self) -> Result<()> {
+        if self.depth == 0 {
+            return Err(anyhow::anyhow!("Maker rebate depth must be greater than 0"));
+        }
+        if self.imbalance_threshold < Decimal::ZERO || self.imbalance_threshold >= Decimal::ONE {
+            return Err(anyhow::anyhow!("Maker rebate imbalance threshold must be in [0, 1)"));
+        }
+        if self.min_rebate_rate < Decimal::ZERO {
+            return Err(anyhow::anyhow!("Maker rebate rates cannot be negative"));
+        }
+        // 返佣不能超过一笔内部成交双方支付的手续费，否则每笔成交都会亏损
+        let fee_budget = INTERNAL_MAKER_FEE_RATE + INTERNAL_TAKER_FEE_RATE;
+        let max_rates = std::iter::once(&self.max_rebate_rate).chain(self.symbol_max_rebate_rates.values());
+        for max_rate in max_rates {
+            if *max_rate < self.min_rebate_rate || *max_rate > fee_budget {
+                return Err(anyhow::anyhow!(
+                    "Maker rebate max rate must be between the min rate and the internal fee budget {}",
+                    fee_budget
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 交易对的返佣率上限
+    pub fn max_rebate_rate_for(&self, symbol: &str) -> Decimal {
+        self.symbol_max_rebate_rates
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.max_rebate_rate)
+    }
+}
+
+impl Default for PriceCollarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            collar_bps: Decimal::from(100), // 1%
+            symbol_collar_bps: HashMap::new(),
+        }
+    }
+}
+
+impl PriceCollarConfig {
+    /// 验证价格保护配置
+    pub fn validate(&self) -> Result<()> {
+        let collars = std::iter::once(&self.collar_bps).chain(self.symbol_collar_bps.values());
+        for collar_bps in collars {
+            if *collar_bps <= Decimal::ZERO || *collar_bps >= Decimal::from(10_000) {
+                return Err(anyhow::anyhow!("Price collar must be between 0 and 10000 bps"));
+            }
+        }
+        Ok(())
+    }
+
+    /// 交易对的保护幅度，未启用时返回 None
+    pub fn collar_bps_for(&self, symbol: &str) -> Option<Decimal> {
+        self.enabled.then(|| {
+            self.symbol_collar_bps
+                .get(symbol)
+                .copied()
+                .unwrap_or(self.collar_bps)
+        })
+    }
+}
+
+impl Default for SpreadOrderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_legs: 4,
+            hedge_residuals: true,
+        }
+    }
+}
+
+impl SpreadOrderConfig {
+    /// 验证价差单配置
+    pub fn validate(&self) -> Result<()> {
+        if self.max_legs < 2 {
+            return Err(anyhow::anyhow!("Spread orders need at least 2 legs"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for TestnetConfig {
+    fn default() -> Self {
+        Self {
+            exchanges: Vec::new(),
+            default_access: EnvironmentAccess::LiveOnly,
+        }
+    }
+}
+
+impl TestnetConfig {
+    /// 支持测试网的交易所
+    pub const SUPPORTED_EXCHANGES: [&'static str; 2] = ["binance", "kraken"];
+
+    /// 验证测试网配置
+    pub fn validate(&self) -> Result<()> {
+        for exchange in &self.exchanges {
+            if !Self::SUPPORTED_EXCHANGES.contains(&exchange.to_lowercase().as_str()) {
+                return Err(anyhow::anyhow!("Testnet is not supported for exchange {}", exchange));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, exchange: &str) -> bool {
+        self.exchanges.iter().any(|e| e.eq_ignore_ascii_case(exchange))
+    }
+}
+
+impl Default for BookSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(5),
+            restore_on_startup: true,
+        }
+    }
+}
+
+impl BookSnapshotConfig {
+    /// 验证订单簿快照配置
+    pub fn validate(&self) -> Result<()> {
+        if self.enabled && self.interval < Duration::from_millis(100) {
+            return Err(anyhow::anyhow!("Book snapshot interval must be at least 100ms"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for LatencyBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ack_timeout: Duration::from_millis(500),
+            venue_ack_timeouts: HashMap::new(),
+            reroute_on_timeout: true,
+            max_reroutes: 1,
+            min_samples: 20,
+            slow_timeout_rate: Decimal::new(5, 2), // 0.05 (5%)
         }
     }
 }
@@ -478,3 +947,96 @@ impl Default for PerformanceConfig {
         }
     }
 }
+
+impl Default for VolatilityPolicyConfig {
+    fn default() -> Self {
+        let policies = HashMap::from([
+            (
+                VolatilityRegime::High,
+                VolatilityPolicy {
+                    slippage_multiplier: Decimal::new(15, 1), // 1.5
+                    child_order_ratio: Decimal::new(5, 1),    // 0.5
+                    ..VolatilityPolicy::default()
+                },
+            ),
+            (
+                VolatilityRegime::Extreme,
+                VolatilityPolicy {
+                    slippage_multiplier: Decimal::from(2),
+                    limit_only: true,
+                    child_order_ratio: Decimal::new(25, 2), // 0.25
+                    pause_algorithms: true,
+                },
+            ),
+        ]);
+        Self {
+            enabled: false,
+            sample_interval: Duration::from_secs(10),
+            window: 30,
+            low_threshold: Decimal::new(2, 4),     // 0.02%
+            high_threshold: Decimal::new(2, 3),    // 0.2%
+            extreme_threshold: Decimal::new(5, 3), // 0.5%
+            max_reading_age: Duration::from_secs(60),
+            policies,
+        }
+    }
+}
+
+impl Default for VolatilityPolicy {
+    fn default() -> Self {
+        Self {
+            slippage_multiplier: Decimal::ONE,
+            limit_only: false,
+            child_order_ratio: Decimal::ONE,
+            pause_algorithms: false,
+        }
+    }
+}
+
+impl VolatilityPolicyConfig {
+    /// 验证波动分档配置
+    pub fn validate(&self) -> Result<()> {
+        if self.window < 2 {
+            return Err(anyhow::anyhow!("Volatility window must be at least 2"));
+        }
+        if self.sample_interval.is_zero() {
+            return Err(anyhow::anyhow!("Volatility sample interval must be greater than 0"));
+        }
+        if !(Decimal::ZERO <= self.low_threshold
+            && self.low_threshold < self.high_threshold
+            && self.high_threshold < self.extreme_threshold)
+        {
+            return Err(anyhow::anyhow!(
+                "Volatility thresholds must satisfy 0 <= low < high < extreme"
+            ));
+        }
+        for (regime, policy) in &self.policies {
+            if policy.slippage_multiplier < Decimal::ONE {
+                return Err(anyhow::anyhow!(
+                    "Slippage multiplier for {} volatility must be at least 1",
+                    regime
+                ));
+            }
+            if policy.child_order_ratio <= Decimal::ZERO || policy.child_order_ratio > Decimal::ONE {
+                return Err(anyhow::anyhow!(
+                    "Child order ratio for {} volatility must be in (0, 1]",
+                    regime
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 波动率对应的分档
+    pub fn classify(&self, volatility: Decimal) -> VolatilityRegime {
+        if volatility >= self.extreme_threshold {
+            VolatilityRegime::Extreme
+        } else if volatility >= self.high_threshold {
+            VolatilityRegime::High
+        } else if volatility < self.low_threshold {
+            VolatilityRegime::Low
+        } else {
+            VolatilityRegime::Normal
+        }
+    }
+}
@@ -3,7 +3,12 @@ pub mod risk;
 pub mod trading;
 
 use anyhow::Result;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use shared_utils::{
+    config_serde::{decimal, duration}, deserialize_checked, logging::LogLevelConfig, ConfigReport,
+    FeatureFlagConfig, InternalAuthConfig, LeaderElectionConfig,
+};
 use std::time::Duration;
 
 pub use execution::ExecutionConfig;
@@ -21,6 +26,21 @@ pub struct TradingEngineConfig {
     pub execution: ExecutionConfig,
     pub websocket: WebSocketConfig,
     pub monitoring: MonitoringConfig,
+    /// 功能开关，未配置Redis地址时使用服务Redis
+    #[serde(default)]
+    pub feature_flags: FeatureFlagConfig,
+    /// 多副本部署时定时任务的领导者选举，未配置Redis地址时使用服务Redis
+    #[serde(default)]
+    pub leader_election: LeaderElectionConfig,
+    /// 指标和管理接口的内部认证
+    #[serde(default)]
+    pub internal_auth: InternalAuthConfig,
+    /// 日志级别和按模块覆盖，运行时可通过管理接口修改
+    #[serde(default)]
+    pub logging: LogLevelConfig,
+    /// 订单和成交事件的事务发件箱投递
+    #[serde(default)]
+    pub outbox: OutboxConfig,
 }
 
 /// 服务器配置
@@ -30,7 +50,9 @@ pub struct ServerConfig {
     pub port: u16,
     pub workers: Option<usize>,
     pub max_connections: usize,
+    #[serde(with = "duration")]
     pub request_timeout: Duration,
+    #[serde(with = "duration")]
     pub shutdown_timeout: Duration,
 }
 
@@ -40,9 +62,74 @@ pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
     pub min_connections: u32,
+    #[serde(with = "duration")]
     pub connect_timeout: Duration,
+    #[serde(with = "duration")]
     pub idle_timeout: Duration,
+    #[serde(with = "duration")]
     pub max_lifetime: Duration,
+    /// 历史和报表查询的读连接池
+    #[serde(default)]
+    pub read_pool: ReadPoolConfig,
+}
+
+/// 查询类型，用于选择主库或读连接池
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryClass {
+    /// 下单、撤单、成交等写入及写后立即读取
+    Transactional,
+    /// 订单和成交历史列表
+    History,
+    /// 盈亏快照、税务报表等统计查询
+    Reporting,
+}
+
+/// 数据库读连接池配置
+///
+/// 路由到读连接池的查询使用独立的连接，未配置只读副本时读连接池连接主库，
+/// 大范围历史查询不会占满下单写入所需的连接。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadPoolConfig {
+    /// 只读副本地址，多个副本轮询使用；为空时连接主库
+    pub replica_urls: Vec<String>,
+    /// 每个读连接池的最大连接数
+    pub max_connections: u32,
+    /// 使用读连接池的查询类型，其余查询使用主库连接池
+    pub routes: Vec<QueryClass>,
+    /// 连接池使用情况指标的刷新间隔
+    #[serde(with = "duration")]
+    pub metrics_interval: Duration,
+}
+
+impl Default for ReadPoolConfig {
+    fn default() -> Self {
+        Self {
+            replica_urls: Vec::new(),
+            max_connections: 10,
+            routes: vec![QueryClass::History, QueryClass::Reporting],
+            metrics_interval: Duration::from_secs(15),
+        }
+    }
+}
+
+impl ReadPoolConfig {
+    /// 验证读连接池配置
+    pub fn validate(&self) -> Result<()> {
+        if self.routes.contains(&QueryClass::Transactional) {
+            return Err(anyhow::anyhow!(
+                "Transactional queries must use the primary pool, remove them from database.read_pool.routes"
+            ));
+        }
+        if self.replica_urls.iter().any(|url| url.is_empty()) {
+            return Err(anyhow::anyhow!("Read replica URLs cannot be empty"));
+        }
+        if self.metrics_interval.is_zero() {
+            return Err(anyhow::anyhow!("Read pool metrics interval cannot be 0"));
+        }
+        Ok(())
+    }
 }
 
 /// Redis配置
@@ -50,8 +137,11 @@ pub struct DatabaseConfig {
 pub struct RedisConfig {
     pub url: String,
     pub pool_size: u32,
+    #[serde(with = "duration")]
     pub connect_timeout: Duration,
+    #[serde(with = "duration")]
     pub command_timeout: Duration,
+    #[serde(with = "duration")]
     pub connection_timeout: Duration,
 }
 
@@ -60,9 +150,65 @@ pub struct RedisConfig {
 pub struct WebSocketConfig {
     pub enabled: bool,
     pub max_connections: usize,
+    #[serde(with = "duration")]
     pub heartbeat_interval: Duration,
+    #[serde(with = "duration")]
     pub message_timeout: Duration,
     pub buffer_size: usize,
+    /// 账户权益推送
+    #[serde(default)]
+    pub equity_stream: EquityStreamConfig,
+}
+
+/// 账户权益推送配置
+///
+/// 每个采样周期按实时标记价格重新计算权益，变化超过阈值时立即推送，
+/// 否则按固定周期推送。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EquityStreamConfig {
+    /// 重新计算权益的间隔
+    #[serde(with = "duration")]
+    pub sample_interval: Duration,
+    /// 权益无明显变化时的推送间隔
+    #[serde(with = "duration")]
+    pub push_interval: Duration,
+    /// 相对上次推送的权益变化比例超过该值时立即推送
+    #[serde(with = "decimal")]
+    pub min_change_ratio: Decimal,
+    /// 计价货币，只有该货币的余额计入权益
+    pub quote_currency: String,
+}
+
+impl Default for EquityStreamConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval: Duration::from_secs(1),
+            push_interval: Duration::from_secs(5),
+            min_change_ratio: Decimal::new(1, 3), // 0.1%
+            quote_currency: "USDT".to_string(),
+        }
+    }
+}
+
+impl EquityStreamConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.sample_interval.is_zero() {
+            return Err(anyhow::anyhow!("Equity stream sample interval cannot be 0"));
+        }
+        if self.push_interval < self.sample_interval {
+            return Err(anyhow::anyhow!(
+                "Equity stream push interval must not be shorter than the sample interval"
+            ));
+        }
+        if self.min_change_ratio < Decimal::ZERO {
+            return Err(anyhow::anyhow!("Equity stream change ratio cannot be negative"));
+        }
+        if self.quote_currency.is_empty() {
+            return Err(anyhow::anyhow!("Equity stream quote currency is required"));
+        }
+        Ok(())
+    }
 }
 
 /// 监控配置
@@ -74,9 +220,85 @@ pub struct MonitoringConfig {
     pub prometheus_registry: bool,
 }
 
+/// 事务发件箱配置
+///
+/// 订单和成交事件与业务数据在同一事务中写入发件箱表，由投递任务发送到Kafka。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboxConfig {
+    /// 关闭时事件仍写入发件箱，但不投递
+    pub enabled: bool,
+    pub brokers: String,
+    /// 发件箱为空时的轮询间隔
+    #[serde(with = "duration")]
+    pub poll_interval: Duration,
+    /// 每批读取的事件数
+    pub batch_size: u32,
+    #[serde(with = "duration")]
+    pub send_timeout: Duration,
+    /// 投递连续失败时的最大退避时间
+    #[serde(with = "duration")]
+    pub max_backoff: Duration,
+    /// 已投递事件的保留时长
+    #[serde(with = "duration")]
+    pub retention: Duration,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            brokers: "localhost:9092".to_string(),
+            poll_interval: Duration::from_millis(500),
+            batch_size: 200,
+            send_timeout: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60),
+            retention: Duration::from_secs(7 * 86400),
+        }
+    }
+}
+
+impl OutboxConfig {
+    /// 验证发件箱配置
+    pub fn validate(&self) -> Result<()> {
+        if self.enabled && self.brokers.is_empty() {
+            return Err(anyhow::anyhow!("Outbox brokers are required when the outbox relay is enabled"));
+        }
+        if self.poll_interval.is_zero() {
+            return Err(anyhow::anyhow!("Outbox poll interval cannot be 0"));
+        }
+        if self.max_backoff < self.poll_interval {
+            return Err(anyhow::anyhow!("Outbox max backoff must not be shorter than the poll interval"));
+        }
+        Ok(())
+    }
+}
+
 impl TradingEngineConfig {
-    /// 加载配置
+    /// 加载配置，存在字段错误时返回完整的检查报告
     pub fn load() -> Result<Self> {
+        let (config, report) = Self::load_checked()?;
+        for issue in report.warnings() {
+            tracing::warn!("Config {}: {}", issue.path, issue.message);
+        }
+        report.into_result()?;
+        config.ok_or_else(|| anyhow::anyhow!("Configuration could not be loaded"))
+    }
+
+    /// 加载并检查配置，返回生效配置和字段级报告
+    pub fn load_checked() -> Result<(Option<Self>, ConfigReport)> {
+        let settings = Self::settings()?;
+        let raw: serde_json::Value = settings.clone().try_deserialize()?;
+        let (mut config, mut report) = deserialize_checked::<Self, _>(settings, &raw);
+        if let Some(config) = &mut config {
+            config.internal_auth.apply_env();
+            config.check(&mut report);
+        }
+        Ok((config, report))
+    }
+
+    /// 合并配置文件、环境变量和默认值
+    fn settings() -> Result<config::Config> {
         let mut settings = config::Config::builder()
             .add_source(config::File::with_name("config/development").required(false))
             .add_source(config::Environment::with_prefix("TRADING_ENGINE").separator("__"))
@@ -133,8 +355,57 @@ impl TradingEngineConfig {
         settings.set_default("execution.retry_attempts", 3)?;
         settings.set_default("execution.retry_delay", "1s")?;
 
-        let config: TradingEngineConfig = settings.try_deserialize()?;
-        Ok(config)
+        Ok(settings)
+    }
+
+    /// 字段取值范围检查
+    fn check(&self, report: &mut ConfigReport) {
+        report.range("server.port", self.server.port, 1, u16::MAX);
+        report.range("server.max_connections", self.server.max_connections, 1, 1_000_000);
+        report.range("database.max_connections", self.database.max_connections, 1, 10_000);
+        report.range(
+            "database.min_connections",
+            self.database.min_connections,
+            0,
+            self.database.max_connections,
+        );
+        report.range(
+            "database.read_pool.max_connections",
+            self.database.read_pool.max_connections,
+            1,
+            10_000,
+        );
+        report.range("redis.pool_size", self.redis.pool_size, 1, 10_000);
+        report.range("websocket.buffer_size", self.websocket.buffer_size, 1, 1_000_000);
+        report.range(
+            "execution.max_slippage",
+            self.execution.max_slippage,
+            Decimal::ZERO,
+            Decimal::ONE,
+        );
+        report.range(
+            "risk.margin_call_threshold",
+            self.risk.margin_call_threshold,
+            Decimal::ZERO,
+            Decimal::ONE,
+        );
+        report.range(
+            "risk.liquidation_threshold",
+            self.risk.liquidation_threshold,
+            Decimal::ZERO,
+            Decimal::ONE,
+        );
+        report.range(
+            "feature_flags.refresh_interval",
+            self.feature_flags.refresh_interval.as_secs(),
+            1,
+            3600,
+        );
+        self.risk.user_config_cache.check("risk.user_config_cache", report);
+        report.range("outbox.batch_size", self.outbox.batch_size, 1, 10_000);
+        self.internal_auth.check("internal_auth", report);
+        self.logging.check("logging", report);
+        report.merge_validation("", self.validate());
     }
 
     /// 验证配置
@@ -156,6 +427,7 @@ impl TradingEngineConfig {
         if self.database.max_connections == 0 {
             return Err(anyhow::anyhow!("Database max connections cannot be 0"));
         }
+        self.database.read_pool.validate()?;
 
         // 验证Redis配置
         if self.redis.url.is_empty() {
@@ -166,6 +438,8 @@ impl TradingEngineConfig {
         self.trading.validate()?;
         self.risk.validate()?;
         self.execution.validate()?;
+        self.outbox.validate()?;
+        self.websocket.equity_stream.validate()?;
 
         Ok(())
     }
@@ -189,6 +463,7 @@ impl Default for TradingEngineConfig {
                 connect_timeout: Duration::from_secs(10),
                 idle_timeout: Duration::from_secs(300),
                 max_lifetime: Duration::from_secs(1800),
+                read_pool: ReadPoolConfig::default(),
             },
             redis: RedisConfig {
                 url: "redis://localhost:6379".to_string(),
@@ -206,6 +481,7 @@ impl Default for TradingEngineConfig {
                 heartbeat_interval: Duration::from_secs(30),
                 message_timeout: Duration::from_secs(10),
                 buffer_size: 1024,
+                equity_stream: EquityStreamConfig::default(),
             },
             monitoring: MonitoringConfig {
                 enabled: true,
@@ -213,6 +489,11 @@ impl Default for TradingEngineConfig {
                 health_path: "/health".to_string(),
                 prometheus_registry: true,
             },
+            feature_flags: FeatureFlagConfig::default(),
+            leader_election: LeaderElectionConfig::default(),
+            internal_auth: InternalAuthConfig::default(),
+            logging: LogLevelConfig::default(),
+            outbox: OutboxConfig::default(),
         }
     }
 }
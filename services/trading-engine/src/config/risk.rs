@@ -1,28 +1,149 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use shared_utils::config_serde::{decimal, duration};
+use shared_utils::CacheConfig;
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// 风险管理配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskConfig {
     pub enabled: bool,
+    #[serde(with = "decimal")]
     pub max_position_size: Decimal,
+    #[serde(with = "decimal")]
     pub max_daily_loss: Decimal,
+    #[serde(with = "decimal")]
     pub max_leverage: Decimal,
+    #[serde(with = "decimal")]
     pub margin_call_threshold: Decimal,
+    #[serde(with = "decimal")]
     pub liquidation_threshold: Decimal,
     pub position_limits: PositionLimits,
     pub trading_limits: TradingLimits,
     pub risk_checks: RiskChecks,
+    /// 下单前保证金余量缓存的最长有效期，仓位变化事件会提前刷新
+    #[serde(default = "default_headroom_cache_ttl", with = "duration")]
+    pub headroom_cache_ttl: Duration,
+    /// 用户风控配置缓存，配置修改时会主动失效
+    #[serde(default)]
+    pub user_config_cache: CacheConfig,
+    /// 风险事件队列、确认时限和归档
+    #[serde(default)]
+    pub events: RiskEventConfig,
+    /// KYC 认证等级对交易功能的限制
+    #[serde(default)]
+    pub verification: VerificationConfig,
+}
+
+fn default_headroom_cache_ttl() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// 风险事件处理配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskEventConfig {
+    /// 检查确认时限和归档的间隔
+    #[serde(with = "duration")]
+    pub check_interval: Duration,
+    /// 严重事件须在此时限内确认，否则升级告警
+    #[serde(with = "duration")]
+    pub critical_ack_sla: Duration,
+    /// 升级后仍未确认时再次升级的间隔
+    #[serde(with = "duration")]
+    pub escalation_interval: Duration,
+    /// 已解决事件保留在活动表中的时长，之后移入归档表
+    #[serde(with = "duration")]
+    pub resolved_retention: Duration,
+    /// 归档事件的保留时长
+    #[serde(with = "duration")]
+    pub archive_retention: Duration,
+}
+
+impl Default for RiskEventConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(30),
+            critical_ack_sla: Duration::from_secs(300),
+            escalation_interval: Duration::from_secs(900),
+            resolved_retention: Duration::from_secs(7 * 24 * 3600),
+            archive_retention: Duration::from_secs(365 * 24 * 3600),
+        }
+    }
+}
+
+impl RiskEventConfig {
+    /// 验证风险事件配置
+    pub fn validate(&self) -> Result<()> {
+        if self.check_interval.is_zero() {
+            return Err(anyhow::anyhow!("Risk event check interval cannot be 0"));
+        }
+        if self.critical_ack_sla.is_zero() || self.escalation_interval.is_zero() {
+            return Err(anyhow::anyhow!("Risk event SLA and escalation interval cannot be 0"));
+        }
+        if self.archive_retention < self.resolved_retention {
+            return Err(anyhow::anyhow!(
+                "Risk event archive retention must not be shorter than resolved retention"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// KYC 认证等级限制配置
+///
+/// 未认证用户只能查询和模拟交易，基础认证用户限制单笔和单个交易对的名义价值，
+/// 高级认证用户不受此限制。关闭时所有用户按高级认证处理。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerificationConfig {
+    pub enabled: bool,
+    /// 基础认证用户单笔订单名义价值上限
+    #[serde(with = "decimal")]
+    pub level1_max_order_notional: Decimal,
+    /// 基础认证用户单个交易对持仓和挂单名义价值上限
+    #[serde(with = "decimal")]
+    pub level1_max_position_notional: Decimal,
+    /// 认证等级读取缓存，修改等级时会主动失效
+    pub cache: CacheConfig,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level1_max_order_notional: Decimal::from(10_000),
+            level1_max_position_notional: Decimal::from(50_000),
+            cache: CacheConfig::default(),
+        }
+    }
+}
+
+impl VerificationConfig {
+    /// 验证认证等级限制配置
+    pub fn validate(&self) -> Result<()> {
+        if self.level1_max_order_notional <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Level 1 max order notional must be greater than 0"));
+        }
+        if self.level1_max_position_notional < self.level1_max_order_notional {
+            return Err(anyhow::anyhow!(
+                "Level 1 max position notional must not be less than max order notional"
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// 仓位限制
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionLimits {
     pub max_positions_per_user: u32,
+    #[serde(with = "decimal")]
     pub max_position_value: Decimal,
+    #[serde(with = "decimal")]
     pub max_concentration: Decimal,        // 单一资产最大占比
+    #[serde(with = "decimal")]
     pub max_correlation_exposure: Decimal, // 相关性资产最大敞口
 }
 
@@ -32,9 +153,15 @@ pub struct TradingLimits {
     pub max_orders_per_second: u32,
     pub max_orders_per_minute: u32,
     pub max_orders_per_hour: u32,
+    #[serde(with = "decimal")]
     pub max_daily_volume: Decimal,
+    #[serde(with = "duration")]
     pub max_order_frequency: Duration,
+    #[serde(with = "duration")]
     pub cooling_period: Duration,
+    /// 交易对 -> 单个用户每分钟最大下单数，未配置的交易对只受账户级限制
+    #[serde(default)]
+    pub symbol_orders_per_minute: HashMap<String, u32>,
 }
 
 /// 风险检查配置
@@ -69,6 +196,7 @@ pub struct PostTradeChecks {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealTimeMonitoring {
     pub enabled: bool,
+    #[serde(with = "duration")]
     pub check_interval: Duration,
     pub alert_thresholds: AlertThresholds,
     pub auto_actions: AutoActions,
@@ -77,11 +205,17 @@ pub struct RealTimeMonitoring {
 /// 告警阈值
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertThresholds {
+    #[serde(with = "decimal")]
     pub margin_warning: Decimal,    // 保证金预警阈值
+    #[serde(with = "decimal")]
     pub margin_critical: Decimal,   // 保证金危险阈值
+    #[serde(with = "decimal")]
     pub loss_warning: Decimal,      // 亏损预警阈值
+    #[serde(with = "decimal")]
     pub loss_critical: Decimal,     // 亏损危险阈值
+    #[serde(with = "decimal")]
     pub exposure_warning: Decimal,  // 敞口预警阈值
+    #[serde(with = "decimal")]
     pub exposure_critical: Decimal, // 敞口危险阈值
 }
 
@@ -130,9 +264,15 @@ impl RiskConfig {
             ));
         }
 
+        if self.headroom_cache_ttl.is_zero() {
+            return Err(anyhow::anyhow!("Headroom cache TTL cannot be 0"));
+        }
+
         // 验证子配置
         self.position_limits.validate()?;
         self.trading_limits.validate()?;
+        self.events.validate()?;
+        self.verification.validate()?;
 
         Ok(())
     }
@@ -219,6 +359,10 @@ impl TradingLimits {
             return Err(anyhow::anyhow!("Max daily volume must be positive"));
         }
 
+        if let Some((symbol, _)) = self.symbol_orders_per_minute.iter().find(|(_, limit)| **limit == 0) {
+            return Err(anyhow::anyhow!("Max orders per minute for {} cannot be 0", symbol));
+        }
+
         Ok(())
     }
 
@@ -242,6 +386,10 @@ impl Default for RiskConfig {
             position_limits: PositionLimits::default(),
             trading_limits: TradingLimits::default(),
             risk_checks: RiskChecks::default(),
+            headroom_cache_ttl: default_headroom_cache_ttl(),
+            user_config_cache: CacheConfig::default(),
+            events: RiskEventConfig::default(),
+            verification: VerificationConfig::default(),
         }
     }
 }
@@ -266,6 +414,7 @@ impl Default for TradingLimits {
             max_daily_volume: Decimal::from(10_000_000),
             max_order_frequency: Duration::from_millis(100), // 100ms
             cooling_period: Duration::from_secs(60),         // 1 minute
+            symbol_orders_per_minute: HashMap::new(),
         }
     }
 }
@@ -1,20 +1,53 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use shared_models::SettlementCurrency;
+use shared_utils::config_serde::{decimal, duration};
+use shared_utils::CacheConfig;
+use std::collections::BTreeMap;
 use std::time::Duration;
 
+use crate::engines::tax_lots::CostBasisMethod;
+
 /// 交易配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingConfig {
     pub enabled: bool,
     pub max_orders_per_user: u32,
+    #[serde(with = "decimal")]
     pub max_order_size: Decimal,
+    #[serde(with = "decimal")]
     pub min_order_size: Decimal,
+    #[serde(with = "duration")]
     pub order_timeout: Duration,
     pub supported_symbols: Vec<String>,
     pub supported_order_types: Vec<OrderTypeConfig>,
     pub fee_config: FeeConfig,
     pub market_hours: MarketHoursConfig,
+    #[serde(default)]
+    pub pnl_snapshots: PnlSnapshotConfig,
+    #[serde(default)]
+    pub tax_lots: TaxLotConfig,
+    #[serde(default)]
+    pub referrals: ReferralConfig,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub settlement: SettlementConfig,
+    #[serde(default)]
+    pub sagas: SagaConfig,
+    #[serde(default)]
+    pub balance_holds: BalanceHoldConfig,
+    #[serde(default)]
+    pub execution_dedup: ExecutionDedupConfig,
+    #[serde(default)]
+    pub portfolio_stops: PortfolioStopConfig,
+    #[serde(default)]
+    pub scheduled_orders: ScheduledOrderConfig,
+    #[serde(default)]
+    pub conversion: ConversionConfig,
+    #[serde(default)]
+    pub notifications: NotificationInboxConfig,
 }
 
 /// 订单类型配置
@@ -22,7 +55,9 @@ pub struct TradingConfig {
 pub struct OrderTypeConfig {
     pub order_type: String,
     pub enabled: bool,
+    #[serde(default, with = "decimal::option")]
     pub min_size: Option<Decimal>,
+    #[serde(default, with = "decimal::option")]
     pub max_size: Option<Decimal>,
     pub requires_price: bool,
     pub requires_stop_price: bool,
@@ -31,9 +66,13 @@ pub struct OrderTypeConfig {
 /// 手续费配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeeConfig {
+    #[serde(with = "decimal")]
     pub maker_fee: Decimal,
+    #[serde(with = "decimal")]
     pub taker_fee: Decimal,
+    #[serde(with = "decimal")]
     pub withdrawal_fee: Decimal,
+    #[serde(with = "decimal")]
     pub minimum_fee: Decimal,
     pub fee_currency: String,
 }
@@ -47,6 +86,444 @@ pub struct MarketHoursConfig {
     pub holidays: Vec<String>,
 }
 
+/// 账户权益/盈亏快照配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlSnapshotConfig {
+    pub enabled: bool,
+    /// 快照间隔
+    #[serde(with = "duration")]
+    pub interval: Duration,
+    /// 在该时间内有成交或快照的账户视为活跃账户
+    #[serde(with = "duration")]
+    pub active_lookback: Duration,
+    /// 快照保留时长
+    #[serde(with = "duration")]
+    pub retention: Duration,
+}
+
+impl Default for PnlSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(300),
+            active_lookback: Duration::from_secs(30 * 86400),
+            retention: Duration::from_secs(365 * 86400),
+        }
+    }
+}
+
+impl PnlSnapshotConfig {
+    /// 验证快照配置
+    pub fn validate(&self) -> Result<()> {
+        if self.interval.is_zero() {
+            return Err(anyhow::anyhow!("PnL snapshot interval cannot be 0"));
+        }
+        if self.retention < self.interval {
+            return Err(anyhow::anyhow!("PnL snapshot retention must cover at least one interval"));
+        }
+        Ok(())
+    }
+}
+
+/// 税务批次配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxLotConfig {
+    /// 默认成本计算方法：fifo/lifo/hifo/average
+    pub default_method: String,
+    /// 持有超过该天数视为长期持有
+    pub long_term_days: i64,
+}
+
+impl Default for TaxLotConfig {
+    fn default() -> Self {
+        Self {
+            default_method: "fifo".to_string(),
+            long_term_days: 365,
+        }
+    }
+}
+
+impl TaxLotConfig {
+    /// 验证税务批次配置
+    pub fn validate(&self) -> Result<()> {
+        self.default_method
+            .parse::<CostBasisMethod>()
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        if self.long_term_days < 0 {
+            return Err(anyhow::anyhow!("Tax lot long term days cannot be negative"));
+        }
+        Ok(())
+    }
+}
+
+/// 推荐返佣配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralConfig {
+    pub enabled: bool,
+    /// 被推荐人手续费中分给推荐人的比例
+    #[serde(with = "decimal")]
+    pub fee_share_rate: Decimal,
+    /// 注册后在该时长内产生的手续费参与分成
+    #[serde(with = "duration")]
+    pub attribution_window: Duration,
+    /// 结算间隔
+    #[serde(with = "duration")]
+    pub payout_interval: Duration,
+    /// 单币种待结算金额达到该值才结算
+    #[serde(with = "decimal")]
+    pub min_payout: Decimal,
+}
+
+impl Default for ReferralConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fee_share_rate: Decimal::new(2, 1),
+            attribution_window: Duration::from_secs(365 * 86400),
+            payout_interval: Duration::from_secs(86400),
+            min_payout: Decimal::ONE,
+        }
+    }
+}
+
+impl ReferralConfig {
+    /// 验证推荐返佣配置
+    pub fn validate(&self) -> Result<()> {
+        if self.fee_share_rate < Decimal::ZERO || self.fee_share_rate > Decimal::ONE {
+            return Err(anyhow::anyhow!("Referral fee share rate must be between 0 and 1"));
+        }
+        if self.payout_interval.is_zero() {
+            return Err(anyhow::anyhow!("Referral payout interval cannot be 0"));
+        }
+        if self.min_payout < Decimal::ZERO {
+            return Err(anyhow::anyhow!("Referral minimum payout cannot be negative"));
+        }
+        Ok(())
+    }
+}
+
+/// 模拟盘配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    pub enabled: bool,
+    /// 开通和重置时发放的虚拟余额
+    pub initial_balances: BTreeMap<String, Decimal>,
+    /// 模拟成交手续费率
+    #[serde(with = "decimal")]
+    pub fee_rate: Decimal,
+    /// 会话有效期，超过该时长未使用的模拟账户在下次访问时自动重置
+    #[serde(with = "duration")]
+    pub session_ttl: Duration,
+    /// 两次手动重置之间的最短间隔
+    #[serde(with = "duration")]
+    pub reset_cooldown: Duration,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initial_balances: BTreeMap::from([
+                ("USDT".to_string(), Decimal::from(100_000)),
+                ("BTC".to_string(), Decimal::ONE),
+                ("ETH".to_string(), Decimal::from(10)),
+            ]),
+            fee_rate: Decimal::new(1, 3),
+            session_ttl: Duration::from_secs(7 * 86400),
+            reset_cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// 验证模拟盘配置
+    pub fn validate(&self) -> Result<()> {
+        if self.initial_balances.values().any(|amount| *amount < Decimal::ZERO) {
+            return Err(anyhow::anyhow!("Sandbox initial balances cannot be negative"));
+        }
+        if self.fee_rate < Decimal::ZERO || self.fee_rate >= Decimal::ONE {
+            return Err(anyhow::anyhow!("Sandbox fee rate must be between 0 and 1"));
+        }
+        if self.session_ttl.is_zero() {
+            return Err(anyhow::anyhow!("Sandbox session TTL cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 日终结算配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementConfig {
+    pub enabled: bool,
+    /// 每日结算截止时间（UTC，HH:MM），营业日为前一截止时间到该时间
+    pub cutoff: String,
+    /// 检查是否有待结算营业日的间隔
+    #[serde(with = "duration")]
+    pub check_interval: Duration,
+    /// 按持仓名义价值计提的日资金费率，多头支付、空头收取，为负时相反
+    #[serde(with = "decimal")]
+    pub daily_funding_rate: Decimal,
+    /// 生成的对账单格式：csv/pdf
+    pub statement_formats: Vec<String>,
+    /// 对账单渲染并发数
+    pub render_workers: usize,
+}
+
+impl Default for SettlementConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cutoff: "00:00".to_string(),
+            check_interval: Duration::from_secs(60),
+            daily_funding_rate: Decimal::new(3, 4),
+            statement_formats: vec!["csv".to_string(), "pdf".to_string()],
+            render_workers: 2,
+        }
+    }
+}
+
+impl SettlementConfig {
+    /// 验证日终结算配置
+    pub fn validate(&self) -> Result<()> {
+        chrono::NaiveTime::parse_from_str(&self.cutoff, "%H:%M")
+            .map_err(|_| anyhow::anyhow!("Invalid settlement cutoff: {}", self.cutoff))?;
+        if self.check_interval.is_zero() {
+            return Err(anyhow::anyhow!("Settlement check interval cannot be 0"));
+        }
+        if self.daily_funding_rate.abs() >= Decimal::ONE {
+            return Err(anyhow::anyhow!("Daily funding rate must be between -1 and 1"));
+        }
+        for format in &self.statement_formats {
+            if !matches!(format.as_str(), "csv" | "pdf") {
+                return Err(anyhow::anyhow!("Unsupported statement format: {}", format));
+            }
+        }
+        if self.render_workers == 0 {
+            return Err(anyhow::anyhow!("Settlement render workers cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 下单流程 saga 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SagaConfig {
+    pub enabled: bool,
+    /// 检查未完成 saga 的间隔
+    #[serde(with = "duration")]
+    pub recovery_interval: Duration,
+    /// 超过该时间未更新的未完成 saga 视为进程已中断，需要恢复
+    #[serde(with = "duration")]
+    pub stale_after: Duration,
+    /// 已结束 saga 的保留时长
+    #[serde(with = "duration")]
+    pub retention: Duration,
+}
+
+impl Default for SagaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            recovery_interval: Duration::from_secs(30),
+            stale_after: Duration::from_secs(120),
+            retention: Duration::from_secs(7 * 86400),
+        }
+    }
+}
+
+impl SagaConfig {
+    /// 验证 saga 配置
+    pub fn validate(&self) -> Result<()> {
+        if self.recovery_interval.is_zero() {
+            return Err(anyhow::anyhow!("Saga recovery interval cannot be 0"));
+        }
+        if self.stale_after.is_zero() {
+            return Err(anyhow::anyhow!("Saga stale timeout cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 余额冻结配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BalanceHoldConfig {
+    /// 下单时冻结可用余额，成交扣减、撤单释放；
+    /// 需要 account_balances 中有入金数据，否则所有订单都会因余额不足被拒绝
+    pub enabled: bool,
+}
+
+/// 执行回报去重配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionDedupConfig {
+    pub enabled: bool,
+    /// 已处理回报的保留时长，需覆盖交易所重连后可能重发的最长时间范围
+    #[serde(with = "duration")]
+    pub retention: Duration,
+}
+
+impl Default for ExecutionDedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention: Duration::from_secs(7 * 86400),
+        }
+    }
+}
+
+impl ExecutionDedupConfig {
+    /// 验证执行回报去重配置
+    pub fn validate(&self) -> Result<()> {
+        if self.enabled && self.retention.is_zero() {
+            return Err(anyhow::anyhow!("Execution dedup retention cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 账户级回撤止损配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioStopConfig {
+    pub enabled: bool,
+    /// 检查账户权益的间隔
+    #[serde(with = "duration")]
+    pub check_interval: Duration,
+    /// 用户可设置的最大回撤比例上限
+    #[serde(with = "decimal")]
+    pub max_drawdown_limit: Decimal,
+}
+
+impl Default for PortfolioStopConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval: Duration::from_secs(10),
+            max_drawdown_limit: Decimal::new(9, 1), // 90%
+        }
+    }
+}
+
+impl PortfolioStopConfig {
+    /// 验证账户级回撤止损配置
+    pub fn validate(&self) -> Result<()> {
+        if self.check_interval.is_zero() {
+            return Err(anyhow::anyhow!("Portfolio stop check interval cannot be 0"));
+        }
+        if self.max_drawdown_limit <= Decimal::ZERO || self.max_drawdown_limit >= Decimal::ONE {
+            return Err(anyhow::anyhow!("Portfolio stop drawdown limit must be in (0, 1)"));
+        }
+        Ok(())
+    }
+}
+
+/// 定时订单配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledOrderConfig {
+    pub enabled: bool,
+    /// 扫描到期激活/撤单的间隔
+    #[serde(with = "duration")]
+    pub poll_interval: Duration,
+    /// 每轮最多处理的定时订单数
+    pub batch_size: u32,
+    /// 激活时间距今的上限
+    #[serde(with = "duration")]
+    pub max_schedule_ahead: Duration,
+}
+
+impl Default for ScheduledOrderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+            max_schedule_ahead: Duration::from_secs(30 * 24 * 3600), // 30天
+        }
+    }
+}
+
+impl ScheduledOrderConfig {
+    /// 验证定时订单配置
+    pub fn validate(&self) -> Result<()> {
+        if self.poll_interval.is_zero() {
+            return Err(anyhow::anyhow!("Scheduled order poll interval cannot be 0"));
+        }
+        if self.batch_size == 0 {
+            return Err(anyhow::anyhow!("Scheduled order batch size cannot be 0"));
+        }
+        if self.max_schedule_ahead.is_zero() {
+            return Err(anyhow::anyhow!("Scheduled order max schedule ahead cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 站内通知配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationInboxConfig {
+    /// 通知保留时长，超过后不论是否已读都会删除
+    #[serde(with = "duration")]
+    pub retention: Duration,
+    /// 清理过期通知的间隔
+    #[serde(with = "duration")]
+    pub cleanup_interval: Duration,
+}
+
+impl Default for NotificationInboxConfig {
+    fn default() -> Self {
+        Self {
+            retention: Duration::from_secs(90 * 24 * 3600), // 90天
+            cleanup_interval: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl NotificationInboxConfig {
+    /// 验证站内通知配置
+    pub fn validate(&self) -> Result<()> {
+        if self.retention.is_zero() {
+            return Err(anyhow::anyhow!("Notification retention cannot be 0"));
+        }
+        if self.cleanup_interval.is_zero() {
+            return Err(anyhow::anyhow!("Notification cleanup interval cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 结算币种换算配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConversionConfig {
+    /// 未设置结算币种的账户使用的币种
+    pub default_currency: SettlementCurrency,
+    /// 各币种对 USDT 价格的缓存
+    pub rate_cache: CacheConfig,
+    /// 账户结算币种读取缓存，修改时会主动失效
+    pub currency_cache: CacheConfig,
+}
+
+impl Default for ConversionConfig {
+    fn default() -> Self {
+        Self {
+            default_currency: SettlementCurrency::Usdt,
+            rate_cache: CacheConfig {
+                capacity: 1_000,
+                ttl_seconds: 10,
+            },
+            currency_cache: CacheConfig::default(),
+        }
+    }
+}
+
+impl ConversionConfig {
+    /// 验证结算币种换算配置
+    pub fn validate(&self) -> Result<()> {
+        if self.rate_cache.ttl_seconds == 0 {
+            return Err(anyhow::anyhow!("Conversion rate cache TTL cannot be 0"));
+        }
+        Ok(())
+    }
+}
+
 /// 交易时间段
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingHour {
@@ -78,6 +555,17 @@ impl TradingConfig {
 
         // 验证手续费配置
         self.fee_config.validate()?;
+        self.pnl_snapshots.validate()?;
+        self.tax_lots.validate()?;
+        self.referrals.validate()?;
+        self.sandbox.validate()?;
+        self.settlement.validate()?;
+        self.sagas.validate()?;
+        self.execution_dedup.validate()?;
+        self.portfolio_stops.validate()?;
+        self.scheduled_orders.validate()?;
+        self.notifications.validate()?;
+        self.conversion.validate()?;
 
         // 验证订单类型配置
         for order_type in &self.supported_order_types {
@@ -238,6 +726,18 @@ impl Default for TradingConfig {
             ],
             fee_config: FeeConfig::default(),
             market_hours: MarketHoursConfig::default(),
+            pnl_snapshots: PnlSnapshotConfig::default(),
+            tax_lots: TaxLotConfig::default(),
+            referrals: ReferralConfig::default(),
+            sandbox: SandboxConfig::default(),
+            settlement: SettlementConfig::default(),
+            sagas: SagaConfig::default(),
+            balance_holds: BalanceHoldConfig::default(),
+            execution_dedup: ExecutionDedupConfig::default(),
+            portfolio_stops: PortfolioStopConfig::default(),
+            scheduled_orders: ScheduledOrderConfig::default(),
+            notifications: NotificationInboxConfig::default(),
+            conversion: ConversionConfig::default(),
         }
     }
 }
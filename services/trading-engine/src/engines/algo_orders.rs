@@ -0,0 +1,367 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::execution::{TwapConfig, VwapConfig};
+use crate::engines::execution_engine::ExecutionResult;
+use crate::models::{Order, Side, TradingError, TradingResult};
+
+/// TWAP 算法名，写在 `OrderMetadata::algorithm` 中
+pub const TWAP_ALGORITHM: &str = "twap";
+
+/// VWAP 算法名
+pub const VWAP_ALGORITHM: &str = "vwap";
+
+/// 算法单的执行场所名称，父订单本身不下到任何交易所
+pub const ALGO_VENUE: &str = "ALGO";
+
+/// 算法单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlgoOrderStatus {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+/// 算法单执行进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlgoOrderProgress {
+    pub parent_order_id: Uuid,
+    pub user_id: Uuid,
+    pub symbol: String,
+    pub side: Side,
+    pub algorithm: String,
+    pub target_quantity: Decimal,
+    pub filled_quantity: Decimal,
+    pub avg_price: Option<Decimal>,
+    pub total_fee: Decimal,
+    /// 已提交的子订单数
+    pub child_orders: u32,
+    /// 已执行的时间片数
+    pub completed_slices: u32,
+    pub total_slices: u32,
+    pub status: AlgoOrderStatus,
+    /// 受理时的标记价格，作为滑点基准
+    pub arrival_price: Option<Decimal>,
+    /// 已成交均价相对到达价的滑点（基点），不利方向为正
+    pub slippage_bps: Option<Decimal>,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl AlgoOrderProgress {
+    pub fn new(order: &Order, algorithm: &str, total_slices: u32, duration: Duration) -> Self {
+        let started_at = Utc::now();
+        Self {
+            parent_order_id: order.id,
+            user_id: order.user_id,
+            symbol: order.symbol.to_string(),
+            side: order.side,
+            algorithm: algorithm.to_string(),
+            target_quantity: order.quantity,
+            filled_quantity: Decimal::ZERO,
+            avg_price: None,
+            total_fee: Decimal::ZERO,
+            child_orders: 0,
+            completed_slices: 0,
+            total_slices,
+            status: AlgoOrderStatus::Running,
+            arrival_price: None,
+            slippage_bps: None,
+            started_at,
+            ends_at: started_at + chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::zero()),
+        }
+    }
+
+    /// 计入一个子订单的成交
+    pub fn record_child(&mut self, filled: Decimal, avg_price: Option<Decimal>, fee: Decimal) {
+        self.child_orders += 1;
+        self.total_fee += fee;
+        if filled <= Decimal::ZERO {
+            return;
+        }
+        let notional = self.avg_price.unwrap_or(Decimal::ZERO) * self.filled_quantity
+            + avg_price.unwrap_or(Decimal::ZERO) * filled;
+        self.filled_quantity += filled;
+        self.avg_price = Some(notional / self.filled_quantity);
+        self.slippage_bps = self
+            .arrival_price
+            .zip(self.avg_price)
+            .and_then(|(arrival, avg)| slippage_bps(self.side, arrival, avg));
+    }
+
+    /// 当前的滑点报告
+    pub fn slippage(&self) -> Option<SlippageReport> {
+        self.arrival_price.map(|arrival_price| SlippageReport {
+            arrival_price,
+            avg_price: self.avg_price,
+            slippage_bps: self.slippage_bps,
+        })
+    }
+
+    pub fn remaining_quantity(&self) -> Decimal {
+        (self.target_quantity - self.filled_quantity).max(Decimal::ZERO)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == AlgoOrderStatus::Running
+    }
+}
+
+/// 执行引擎发给订单服务的算法单变化，按发生顺序处理
+#[derive(Debug, Clone)]
+pub enum AlgoOrderUpdate {
+    /// 子订单执行结果，成交计入父订单
+    ChildExecuted { parent_order_id: Uuid, result: ExecutionResult },
+    /// 执行结束（完成或撤销），父订单未成交的数量撤销
+    Finished(AlgoOrderProgress),
+}
+
+/// 是否为需要按时间片拆分执行的算法父订单
+pub fn is_algo_parent(order: &Order) -> bool {
+    order.metadata.parent_order_id.is_none()
+        && matches!(order.metadata.algorithm.as_deref(), Some(TWAP_ALGORITHM | VWAP_ALGORITHM))
+}
+
+/// 执行中的算法单在此之前会再次保存进度，过期未更新视为执行实例已中断
+///
+/// 留出两个切片间隔和一分钟余量，覆盖子订单执行的耗时。
+pub fn stale_deadline(interval: Duration) -> DateTime<Utc> {
+    let grace = chrono::Duration::from_std(interval * 2).unwrap_or_else(|_| chrono::Duration::zero());
+    Utc::now() + grace + chrono::Duration::minutes(1)
+}
+
+/// 算法单的执行计划
+#[derive(Debug, Clone)]
+pub struct AlgoSchedule {
+    pub algorithm: &'static str,
+    /// 切片间隔
+    pub interval: Duration,
+    /// 各时间片的计划数量
+    pub slices: Vec<Decimal>,
+    pub max_participation_rate: Decimal,
+    /// 每片按剩余各片的计划比例重新分配剩余数量，否则未成交数量滚入下一片
+    pub rebalance: bool,
+}
+
+/// 校验 TWAP 参数，返回执行时长
+pub fn twap_duration(order: &Order, config: &TwapConfig) -> TradingResult<Duration> {
+    algo_duration(order, "TWAP", config.enabled, config.min_duration, config.max_duration)
+}
+
+/// 校验 VWAP 参数，返回执行时长
+pub fn vwap_duration(order: &Order, config: &VwapConfig) -> TradingResult<Duration> {
+    algo_duration(order, "VWAP", config.enabled, config.min_duration, config.max_duration)
+}
+
+fn algo_duration(order: &Order, name: &str, enabled: bool, min: Duration, max: Duration) -> TradingResult<Duration> {
+    if !enabled {
+        return Err(TradingError::ConfigError(format!("{} execution is disabled", name)));
+    }
+    let secs = order.metadata.algorithm_duration_secs.ok_or_else(|| {
+        TradingError::InvalidOrder(format!("{} orders require algorithm_duration_secs", name))
+    })?;
+    let duration = Duration::from_secs(secs);
+    if duration < min || duration > max {
+        return Err(TradingError::InvalidOrder(format!(
+            "{} duration {}s must be between {}s and {}s",
+            name,
+            secs,
+            min.as_secs(),
+            max.as_secs()
+        )));
+    }
+    Ok(duration)
+}
+
+/// 把数量均分到各时间片，片数为执行时长除以切片间隔向上取整
+///
+/// 每片数量截断到 8 位小数，余数计入最后一片。
+pub fn twap_slices(quantity: Decimal, duration: Duration, slice_interval: Duration) -> Vec<Decimal> {
+    let interval = slice_interval.as_millis().max(1);
+    let count = duration.as_millis().div_ceil(interval).max(1) as usize;
+    let slice = (quantity / Decimal::from(count)).round_dp_with_strategy(8, RoundingStrategy::ToZero);
+    let mut slices = vec![slice; count];
+    slices[count - 1] = quantity - slice * Decimal::from(count - 1);
+    slices
+}
+
+/// 按成交量曲线权重分配各时间片的数量，权重全为零时均分
+///
+/// 每片数量截断到 8 位小数，余数计入最后一片。
+pub fn vwap_slices(quantity: Decimal, weights: &[Decimal]) -> Vec<Decimal> {
+    let count = weights.len().max(1);
+    let total: Decimal = weights.iter().copied().sum();
+    let mut slices: Vec<Decimal> = if total > Decimal::ZERO {
+        weights
+            .iter()
+            .map(|weight| (quantity * weight / total).round_dp_with_strategy(8, RoundingStrategy::ToZero))
+            .collect()
+    } else {
+        vec![(quantity / Decimal::from(count)).round_dp_with_strategy(8, RoundingStrategy::ToZero); count]
+    };
+    let allocated: Decimal = slices[..count - 1].iter().copied().sum();
+    slices[count - 1] = quantity - allocated;
+    slices
+}
+
+/// 按剩余各片的计划数量比例，计算当前片应执行的剩余数量
+///
+/// `planned` 从当前片开始，最后一片执行全部剩余数量。
+pub fn rebalanced_slice(remaining: Decimal, planned: &[Decimal]) -> Decimal {
+    if planned.len() <= 1 {
+        return remaining;
+    }
+    let total: Decimal = planned.iter().copied().sum();
+    let quantity = if total > Decimal::ZERO {
+        remaining * planned[0] / total
+    } else {
+        remaining / Decimal::from(planned.len())
+    };
+    quantity.round_dp_with_strategy(8, RoundingStrategy::ToZero)
+}
+
+/// 成交均价相对基准价的滑点（基点），不利方向为正
+pub fn slippage_bps(side: Side, reference: Decimal, avg_price: Decimal) -> Option<Decimal> {
+    if reference <= Decimal::ZERO {
+        return None;
+    }
+    let diff = match side {
+        Side::Buy => avg_price - reference,
+        Side::Sell => reference - avg_price,
+    };
+    Some((diff / reference * Decimal::from(10_000)).round_dp(2))
+}
+
+/// 相对到达价的滑点报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SlippageReport {
+    pub arrival_price: Decimal,
+    pub avg_price: Option<Decimal>,
+    pub slippage_bps: Option<Decimal>,
+}
+
+impl SlippageReport {
+    pub fn new(side: Side, arrival_price: Decimal, avg_price: Option<Decimal>) -> Self {
+        Self {
+            arrival_price,
+            avg_price,
+            slippage_bps: avg_price.and_then(|avg| slippage_bps(side, arrival_price, avg)),
+        }
+    }
+}
+
+/// 按参与率限制子订单数量，对手盘没有可见数量时不限制
+pub fn cap_participation(quantity: Decimal, visible_liquidity: Decimal, max_rate: Decimal) -> Decimal {
+    if visible_liquidity <= Decimal::ZERO {
+        return quantity;
+    }
+    quantity.min((visible_liquidity * max_rate).round_dp_with_strategy(8, RoundingStrategy::ToZero))
+}
+
+/// 算法单的子订单，沿用父订单的价格和算法标记
+pub fn algo_child_order(parent: &Order, quantity: Decimal) -> Order {
+    let mut child = parent.clone();
+    child.id = Uuid::new_v4();
+    child.client_order_id = None;
+    child.quantity = quantity;
+    child.remaining_quantity = quantity;
+    child.filled_quantity = Decimal::ZERO;
+    child.created_at = Utc::now();
+    child.updated_at = child.created_at;
+    child.metadata.parent_order_id = Some(parent.id);
+    child
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderType, Symbol};
+
+    fn twap_order(duration_secs: Option<u64>) -> Order {
+        let mut order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            OrderType::Market,
+            Side::Buy,
+            Decimal::from(10),
+            None,
+            None,
+        )
+        .unwrap();
+        order.metadata.algorithm = Some(TWAP_ALGORITHM.to_string());
+        order.metadata.algorithm_duration_secs = duration_secs;
+        order
+    }
+
+    #[test]
+    fn test_twap_slices_cover_quantity() {
+        let slices = twap_slices(Decimal::from(10), Duration::from_secs(100), Duration::from_secs(30));
+        assert_eq!(slices.len(), 4);
+        assert_eq!(slices[0], Decimal::new(25, 1));
+        assert_eq!(slices.iter().copied().sum::<Decimal>(), Decimal::from(10));
+
+        let slices = twap_slices(Decimal::ONE, Duration::from_secs(90), Duration::from_secs(30));
+        assert_eq!(slices.len(), 3);
+        assert_eq!(slices[0], Decimal::new(33333333, 8));
+        assert_eq!(slices[2], Decimal::new(33333334, 8));
+
+        assert_eq!(cap_participation(Decimal::from(5), Decimal::from(10), Decimal::new(2, 1)), Decimal::from(2));
+        assert_eq!(cap_participation(Decimal::from(5), Decimal::ZERO, Decimal::new(2, 1)), Decimal::from(5));
+    }
+
+    #[test]
+    fn test_twap_duration_and_progress() {
+        let config = TwapConfig::default();
+        assert!(twap_duration(&twap_order(None), &config).is_err());
+        assert!(twap_duration(&twap_order(Some(10)), &config).is_err());
+        assert_eq!(
+            twap_duration(&twap_order(Some(600)), &config).unwrap(),
+            Duration::from_secs(600)
+        );
+
+        let order = twap_order(Some(600));
+        let mut progress = AlgoOrderProgress::new(&order, TWAP_ALGORITHM, 20, Duration::from_secs(600));
+        progress.record_child(Decimal::from(2), Some(Decimal::from(100)), Decimal::ONE);
+        progress.record_child(Decimal::ZERO, None, Decimal::ZERO);
+        progress.record_child(Decimal::from(2), Some(Decimal::from(110)), Decimal::ONE);
+        assert_eq!(progress.child_orders, 3);
+        assert_eq!(progress.avg_price, Some(Decimal::from(105)));
+        assert_eq!(progress.remaining_quantity(), Decimal::from(6));
+        assert_eq!(progress.total_fee, Decimal::from(2));
+
+        let child = algo_child_order(&order, Decimal::from(2));
+        assert_eq!(child.metadata.parent_order_id, Some(order.id));
+        assert_eq!(child.metadata.algorithm.as_deref(), Some(TWAP_ALGORITHM));
+    }
+
+    #[test]
+    fn test_vwap_slices_and_slippage() {
+        let weights = [Decimal::new(1, 1), Decimal::new(3, 1), Decimal::ZERO, Decimal::new(2, 1)];
+        let slices = vwap_slices(Decimal::from(3), &weights);
+        assert_eq!(slices, vec![Decimal::new(5, 1), Decimal::new(15, 1), Decimal::ZERO, Decimal::ONE]);
+        assert_eq!(vwap_slices(Decimal::from(4), &[Decimal::ZERO; 4]), vec![Decimal::ONE; 4]);
+
+        // 第一片被参与率限制后，剩余 2.8 按剩余计划 1.5 : 0 : 1 分配
+        assert_eq!(rebalanced_slice(Decimal::new(28, 1), &slices[1..]), Decimal::new(168, 2));
+        assert_eq!(rebalanced_slice(Decimal::new(7, 1), &slices[3..]), Decimal::new(7, 1));
+
+        let mut order = twap_order(Some(600));
+        order.metadata.algorithm = Some(VWAP_ALGORITHM.to_string());
+        let config = VwapConfig::default();
+        assert!(vwap_duration(&order, &config).is_ok());
+        order.metadata.algorithm_duration_secs = Some(60);
+        assert!(vwap_duration(&order, &config).is_err());
+
+        let mut progress = AlgoOrderProgress::new(&order, VWAP_ALGORITHM, 4, Duration::from_secs(600));
+        progress.arrival_price = Some(Decimal::from(100));
+        progress.record_child(Decimal::from(2), Some(Decimal::new(1001, 1)), Decimal::ZERO);
+        assert_eq!(progress.slippage_bps, Some(Decimal::from(10)));
+        assert_eq!(slippage_bps(Side::Sell, Decimal::from(100), Decimal::new(1001, 1)), Some(Decimal::from(-10)));
+        let report = SlippageReport::new(Side::Buy, Decimal::from(100), None);
+        assert_eq!(report.slippage_bps, None);
+    }
+}
@@ -0,0 +1,249 @@
+use rust_decimal::Decimal;
+use shared_protocols::internal_book::{BookLevel, InternalBookEvent};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::{broadcast, RwLock};
+
+use crate::config::execution::InternalBookFeedConfig;
+use crate::engines::maker_rebates::MakerRebate;
+use crate::engines::matching_engine::{OrderBookSnapshot, TradeExecution};
+use crate::models::Side;
+
+/// 已推送的订单簿状态
+#[derive(Debug, Clone, Default)]
+struct PublishedBook {
+    sequence: u64,
+    bids: Vec<BookLevel>,
+    asks: Vec<BookLevel>,
+}
+
+/// 计算两次订单簿之间的价位变化，已删除的价位数量为0
+pub fn diff_levels(prev: &[BookLevel], next: &[BookLevel]) -> Vec<BookLevel> {
+    let before: BTreeMap<Decimal, Decimal> = prev.iter().map(|l| (l.price, l.quantity)).collect();
+    let after: BTreeMap<Decimal, Decimal> = next.iter().map(|l| (l.price, l.quantity)).collect();
+
+    let mut changes: Vec<BookLevel> = after
+        .iter()
+        .filter(|(price, quantity)| before.get(*price) != Some(*quantity))
+        .map(|(price, quantity)| BookLevel::new(*price, *quantity))
+        .collect();
+    changes.extend(
+        before
+            .keys()
+            .filter(|price| !after.contains_key(*price))
+            .map(|price| BookLevel::new(*price, Decimal::ZERO)),
+    );
+    changes
+}
+
+fn to_levels(levels: &[(Decimal, Decimal)]) -> Vec<BookLevel> {
+    levels
+        .iter()
+        .map(|(price, quantity)| BookLevel::new(*price, *quantity))
+        .collect()
+}
+
+/// 内部撮合引擎行情推送
+///
+/// 撮合后推送成交和订单簿增量，每个交易对维护递增序号，
+/// 新订阅方和请求重新同步的订阅方通过 `snapshots` 获取全量快照。
+pub struct InternalBookFeed {
+    enabled: bool,
+    depth: usize,
+    sender: broadcast::Sender<InternalBookEvent>,
+    books: RwLock<HashMap<String, PublishedBook>>,
+}
+
+impl InternalBookFeed {
+    pub fn new(config: &InternalBookFeedConfig) -> Self {
+        let (sender, _) = broadcast::channel(config.channel_capacity.max(1));
+        Self {
+            enabled: config.enabled,
+            depth: config.depth,
+            sender,
+            books: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 推送的订单簿档位数
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<InternalBookEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 推送成交
+    pub fn publish_trades(&self, trades: &[TradeExecution]) {
+        if !self.enabled {
+            return;
+        }
+        for trade in trades {
+            let side = match trade.side {
+                Side::Buy => "buy",
+                Side::Sell => "sell",
+            };
+            // 没有订阅方时发送失败，忽略即可
+            let _ = self.sender.send(InternalBookEvent::Trade {
+                symbol: trade.symbol.to_string(),
+                trade_id: trade.trade_id.to_string(),
+                price: trade.price,
+                quantity: trade.quantity,
+                side: side.to_string(),
+                timestamp: trade.timestamp,
+            });
+        }
+    }
+
+    /// 推送挂单返佣变化
+    pub fn publish_maker_rebate(&self, rebate: &MakerRebate) {
+        if !self.enabled {
+            return;
+        }
+        let _ = self.sender.send(InternalBookEvent::MakerRebate {
+            symbol: rebate.symbol.clone(),
+            imbalance: rebate.imbalance,
+            bid_rebate_rate: rebate.bid_rebate_rate,
+            ask_rebate_rate: rebate.ask_rebate_rate,
+            timestamp: rebate.updated_at,
+        });
+    }
+
+    /// 推送订单簿变化，首次推送为快照，之后为增量，无变化时不推送
+    pub async fn publish_book(&self, snapshot: &OrderBookSnapshot) {
+        if !self.enabled {
+            return;
+        }
+        let symbol = snapshot.symbol.to_string();
+        let bids = to_levels(&snapshot.bids);
+        let asks = to_levels(&snapshot.asks);
+
+        // 持锁发送，保证快照与增量的序号顺序一致
+        let mut books = self.books.write().await;
+        let event = match books.get_mut(&symbol) {
+            Some(book) => {
+                let bid_changes = diff_levels(&book.bids, &bids);
+                let ask_changes = diff_levels(&book.asks, &asks);
+                if bid_changes.is_empty() && ask_changes.is_empty() {
+                    return;
+                }
+                book.sequence += 1;
+                book.bids = bids;
+                book.asks = asks;
+                InternalBookEvent::Diff {
+                    symbol,
+                    sequence: book.sequence,
+                    bids: bid_changes,
+                    asks: ask_changes,
+                    timestamp: snapshot.timestamp,
+                }
+            }
+            None => {
+                books.insert(
+                    symbol.clone(),
+                    PublishedBook {
+                        sequence: 1,
+                        bids: bids.clone(),
+                        asks: asks.clone(),
+                    },
+                );
+                InternalBookEvent::Snapshot {
+                    symbol,
+                    sequence: 1,
+                    bids,
+                    asks,
+                    timestamp: snapshot.timestamp,
+                }
+            }
+        };
+        let _ = self.sender.send(event);
+    }
+
+    /// 当前订单簿快照，未指定交易对时返回全部
+    pub async fn snapshots(&self, symbol: Option<&str>) -> Vec<InternalBookEvent> {
+        let books = self.books.read().await;
+        books
+            .iter()
+            .filter(|(name, _)| match symbol {
+                Some(symbol) => symbol.eq_ignore_ascii_case(name),
+                None => true,
+            })
+            .map(|(name, book)| InternalBookEvent::Snapshot {
+                symbol: name.clone(),
+                sequence: book.sequence,
+                bids: book.bids.clone(),
+                asks: book.asks.clone(),
+                timestamp: chrono::Utc::now(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn level(price: i64, quantity: i64) -> BookLevel {
+        BookLevel::new(Decimal::from(price), Decimal::from(quantity))
+    }
+
+    fn book(bids: &[(i64, i64)], asks: &[(i64, i64)]) -> OrderBookSnapshot {
+        let levels = |levels: &[(i64, i64)]| {
+            levels
+                .iter()
+                .map(|(p, q)| (Decimal::from(*p), Decimal::from(*q)))
+                .collect()
+        };
+        OrderBookSnapshot {
+            symbol: Symbol::new("BTC", "USDT"),
+            bids: levels(bids),
+            asks: levels(asks),
+            last_price: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_diff_levels() {
+        let prev = vec![level(100, 1), level(99, 2), level(98, 3)];
+        let next = vec![level(100, 1), level(99, 5), level(97, 4)];
+
+        let mut changes = diff_levels(&prev, &next);
+        changes.sort_by(|a, b| b.price.cmp(&a.price));
+        assert_eq!(changes, vec![level(99, 5), level(98, 0), level(97, 4)]);
+        assert!(diff_levels(&next, &next).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_book_sequences() {
+        let feed = InternalBookFeed::new(&InternalBookFeedConfig::default());
+        let mut receiver = feed.subscribe();
+
+        feed.publish_book(&book(&[(100, 1)], &[(101, 1)])).await;
+        feed.publish_book(&book(&[(100, 1)], &[(101, 1)])).await;
+        feed.publish_book(&book(&[(100, 2)], &[])).await;
+
+        match receiver.recv().await.unwrap() {
+            InternalBookEvent::Snapshot { sequence, .. } => assert_eq!(sequence, 1),
+            other => panic!("unexpected event {:?}", other),
+        }
+        match receiver.recv().await.unwrap() {
+            InternalBookEvent::Diff { sequence, bids, asks, .. } => {
+                assert_eq!(sequence, 2);
+                assert_eq!(bids, vec![level(100, 2)]);
+                assert_eq!(asks, vec![level(101, 0)]);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!(receiver.try_recv().is_err());
+
+        let snapshots = feed.snapshots(Some("btcusdt")).await;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].sequence(), Some(2));
+    }
+}
@@ -1,17 +1,41 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use serde::Serialize;
+use shared_utils::{feature_flags::flags, FeatureFlags};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Instant;
+use tokio::sync::{mpsc, RwLock};
 use uuid::Uuid;
 
 use crate::{
     config::{TradingEngineConfig, execution::RoutingStrategy},
-    engines::MatchingEngine,
-    models::{Order, OrderType, Side, Symbol, TradingError, TradingResult, OrderStatus},
-    exchanges::binance::BinanceConnector,
+    engines::{
+        InternalBookFeed, MakerRebateEngine, MatchingEngine, MatchingEngineState, StateCodec,
+        algo_orders::{
+            algo_child_order, cap_participation, rebalanced_slice, slippage_bps, stale_deadline, twap_duration,
+            twap_slices, vwap_duration, vwap_slices, AlgoOrderProgress, AlgoOrderStatus, AlgoOrderUpdate,
+            AlgoSchedule, SlippageReport, ALGO_VENUE, TWAP_ALGORITHM, VWAP_ALGORITHM,
+        },
+        order_events::{OrderEvent, OrderEventBus},
+        matching_engine::{
+            TradeExecution as MatchTrade, INTERNAL_MAKER_FEE_RATE, INTERNAL_TAKER_FEE_RATE,
+        },
+        venue_latency::{SlowVenueReport, VenueLatencyTracker},
+        volatility_regime::{throttle_order, VolatilityReading, VolatilityRegime, VolatilityRegimeTracker},
+        volume_profile::{VolumeProfile, VolumeProfileClient},
+    },
+    models::{
+        spread_of, ExecutionReport, Fill, Order, OrderType, Side, SpreadOrderRequest, SpreadQuote, Symbol, TradingEnvironment,
+        TradingError, TradingResult, OrderStatus,
+    },
+    exchanges::{binance::BinanceConnector, kraken::KrakenConnector},
+    storage::{AlgoOrderStore, BookSnapshotStore, OrderStore},
 };
 
+/// 内部撮合的执行场所名称
+pub const INTERNAL_VENUE: &str = "INTERNAL";
+
 /// 市场数据结构
 #[derive(Debug, Clone)]
 pub struct MarketData {
@@ -29,6 +53,7 @@ pub struct MarketData {
 #[derive(Clone)]
 pub enum ExchangeConnectorEnum {
     Binance(BinanceConnector),
+    Kraken(KrakenConnector),
     // 可以添加其他交易所
 }
 
@@ -36,42 +61,64 @@ impl ExchangeConnectorEnum {
     pub async fn submit_order(&self, order: &Order) -> Result<String> {
         match self {
             ExchangeConnectorEnum::Binance(connector) => connector.submit_order(order).await,
+            ExchangeConnectorEnum::Kraken(connector) => connector.submit_order(order).await,
         }
     }
 
     pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
         match self {
             ExchangeConnectorEnum::Binance(connector) => connector.cancel_order(order_id).await,
+            ExchangeConnectorEnum::Kraken(connector) => connector.cancel_order(order_id).await,
+        }
+    }
+
+    /// 按客户端订单号撤单，交易所尚未返回订单号时使用
+    pub async fn cancel_order_by_client_id(&self, client_order_id: &str) -> Result<()> {
+        match self {
+            ExchangeConnectorEnum::Binance(connector) => connector.cancel_order_by_client_id(client_order_id).await,
+            ExchangeConnectorEnum::Kraken(connector) => connector.cancel_order_by_client_id(client_order_id).await,
         }
     }
 
     pub async fn get_order_status(&self, order_id: &str) -> Result<OrderStatusInfo> {
         match self {
             ExchangeConnectorEnum::Binance(connector) => connector.get_order_status(order_id).await,
+            ExchangeConnectorEnum::Kraken(connector) => connector.get_order_status(order_id).await,
         }
     }
 
     pub async fn get_account_balance(&self) -> Result<HashMap<String, Decimal>> {
         match self {
             ExchangeConnectorEnum::Binance(connector) => connector.get_account_balance().await,
+            ExchangeConnectorEnum::Kraken(connector) => connector.get_account_balance().await,
         }
     }
 
     pub async fn get_market_data(&self, symbol: &Symbol) -> Result<MarketData> {
         match self {
             ExchangeConnectorEnum::Binance(connector) => connector.get_market_data(symbol).await,
+            ExchangeConnectorEnum::Kraken(connector) => connector.get_market_data(symbol).await,
         }
     }
 
     pub fn get_name(&self) -> &str {
         match self {
             ExchangeConnectorEnum::Binance(connector) => connector.get_name(),
+            ExchangeConnectorEnum::Kraken(connector) => connector.get_name(),
         }
     }
 
     pub fn get_fees(&self) -> (Decimal, Decimal) {
         match self {
             ExchangeConnectorEnum::Binance(connector) => connector.get_fees(),
+            ExchangeConnectorEnum::Kraken(connector) => connector.get_fees(),
+        }
+    }
+
+    pub fn environment(&self) -> TradingEnvironment {
+        match self {
+            ExchangeConnectorEnum::Binance(connector) => connector.environment(),
+            ExchangeConnectorEnum::Kraken(connector) => connector.environment(),
         }
     }
 }
@@ -86,6 +133,32 @@ pub struct ExecutionEngine {
     exchange_connectors: Arc<RwLock<HashMap<String, ExchangeConnectorEnum>>>,
     /// 执行统计
     execution_stats: Arc<RwLock<ExecutionStats>>,
+    /// 各交易所确认/成交延迟
+    venue_latency: Arc<VenueLatencyTracker>,
+    /// 内部撮合引擎行情推送
+    book_feed: Arc<InternalBookFeed>,
+    /// 挂单返佣激励
+    maker_rebates: Arc<MakerRebateEngine>,
+    /// 波动分档，极端行情下限制执行方式
+    volatility: Arc<VolatilityRegimeTracker>,
+    /// 功能开关，控制新版智能路由的灰度
+    feature_flags: Option<FeatureFlags>,
+    /// 订单受理、成交、撤销事件
+    order_events: Arc<OrderEventBus>,
+    /// 内部撮合订单簿快照
+    book_snapshots: Option<(Arc<BookSnapshotStore>, Arc<OrderStore>)>,
+    state_codec: StateCodec,
+    /// 快照恢复失败的交易对，不再覆盖其快照，留待人工处理
+    unrestored_books: Arc<RwLock<HashSet<Symbol>>>,
+    /// 执行中的算法单，按父订单ID索引
+    algo_orders: Arc<RwLock<HashMap<Uuid, AlgoOrderProgress>>>,
+    /// VWAP 使用的历史成交量曲线
+    volume_profiles: Arc<VolumeProfileClient>,
+    /// 算法单进度存储，进程中断后据此撤销父订单
+    algo_store: Option<Arc<AlgoOrderStore>>,
+    /// 子订单成交和算法单结束通知，由订单服务计入父订单
+    algo_updates: mpsc::UnboundedSender<AlgoOrderUpdate>,
+    algo_update_receiver: Arc<std::sync::Mutex<Option<mpsc::UnboundedReceiver<AlgoOrderUpdate>>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -96,6 +169,8 @@ pub struct ExecutionStats {
     pub avg_execution_time_ms: f64,
     pub total_volume: Decimal,
     pub total_fees: Decimal,
+    /// 执行中的算法单进度，子订单计入上面的订单统计
+    pub algo_orders: Vec<AlgoOrderProgress>,
 }
 
 #[derive(Debug, Clone)]
@@ -109,6 +184,10 @@ pub struct ExecutionResult {
     pub execution_time_ms: u64,
     pub venue: String,
     pub trades: Vec<TradeExecution>,
+    /// 相对到达价的滑点报告，目前只有算法单受理时给出；成交后的滑点随算法单进度推送
+    pub slippage: Option<SlippageReport>,
+    /// 本次成交触发的条件单产生的执行回报，包含触发单和挂单方两侧，由下单方按回报入账
+    pub triggered_fills: Vec<ExecutionReport>,
 }
 
 #[derive(Debug, Clone)]
@@ -118,6 +197,53 @@ pub struct TradeExecution {
     pub quantity: Decimal,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub fee: Decimal,
+    /// 本方订单是否为挂单方（增加流动性）
+    pub is_maker: bool,
+}
+
+impl ExecutionResult {
+    /// 转换为成交记录，保留每笔成交的流动性方向和交易所
+    pub fn to_fills(&self, order: &Order) -> Vec<Fill> {
+        let strategy_tag = order
+            .metadata
+            .tags
+            .iter()
+            .find(|tag| tag.starts_with("strategy:"))
+            .cloned();
+        self.trades
+            .iter()
+            .filter(|trade| trade.quantity > Decimal::ZERO)
+            .map(|trade| Fill {
+                id: trade.trade_id,
+                user_id: order.user_id,
+                order_id: order.id,
+                symbol: order.symbol.clone(),
+                side: order.side,
+                price: trade.price,
+                quantity: trade.quantity,
+                quote_quantity: trade.price * trade.quantity,
+                fee: trade.fee,
+                fee_currency: order.fee_currency.clone(),
+                is_maker: trade.is_maker,
+                venue: self.venue.clone(),
+                strategy_tag: strategy_tag.clone(),
+                environment: order.metadata.environment,
+                executed_at: trade.timestamp,
+            })
+            .collect()
+    }
+}
+
+/// 单个交易所执行失败的原因
+#[derive(Debug)]
+enum VenueError {
+    /// 交易所未在时限内确认
+    AckTimeout {
+        timeout: std::time::Duration,
+        /// 是否已成功撤单
+        cancelled: bool,
+    },
+    Failed(TradingError),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -154,6 +280,20 @@ pub struct OrderStatusInfo {
     pub status: String,
     pub filled_quantity: Decimal,
     pub avg_price: Option<Decimal>,
+    /// 交易所回报的逐笔成交，交易所不提供时为空
+    pub fills: Vec<VenueFill>,
+}
+
+/// 交易所回报的单笔成交
+#[derive(Debug, Clone)]
+pub struct VenueFill {
+    pub trade_id: String,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub fee: Decimal,
+    /// 交易所标记的挂单方成交
+    pub is_maker: bool,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
 impl ExecutionEngine {
@@ -165,16 +305,120 @@ impl ExecutionEngine {
             avg_execution_time_ms: 0.0,
             total_volume: Decimal::ZERO,
             total_fees: Decimal::ZERO,
+            algo_orders: Vec::new(),
         };
 
+        let book_feed = Arc::new(InternalBookFeed::new(&config.execution.internal_book));
+        let maker_rebates = Arc::new(MakerRebateEngine::new(config.execution.maker_rebates.clone()));
+        let volatility = Arc::new(VolatilityRegimeTracker::new(config.execution.volatility_policy.clone()));
+        let volume_profiles = Arc::new(VolumeProfileClient::new(config.execution.algorithms.vwap.clone()));
+        let (algo_updates, algo_update_receiver) = mpsc::unbounded_channel();
+
         Ok(Self {
             config,
             matching_engines: Arc::new(RwLock::new(HashMap::new())),
             exchange_connectors: Arc::new(RwLock::new(HashMap::new())),
             execution_stats: Arc::new(RwLock::new(execution_stats)),
+            venue_latency: Arc::new(VenueLatencyTracker::new()),
+            book_feed,
+            maker_rebates,
+            volatility,
+            feature_flags: None,
+            order_events: Arc::new(OrderEventBus::new()),
+            book_snapshots: None,
+            state_codec: StateCodec::default(),
+            unrestored_books: Arc::new(RwLock::new(HashSet::new())),
+            algo_orders: Arc::new(RwLock::new(HashMap::new())),
+            volume_profiles,
+            algo_store: None,
+            algo_updates,
+            algo_update_receiver: Arc::new(std::sync::Mutex::new(Some(algo_update_receiver))),
         })
     }
 
+    /// 使用共享的内部行情推送，供WebSocket等订阅方使用同一个推送源
+    pub fn with_book_feed(mut self, book_feed: Arc<InternalBookFeed>) -> Self {
+        self.book_feed = book_feed;
+        self
+    }
+
+    /// 使用共享的挂单返佣引擎，供返佣查询接口读取当前返佣
+    pub fn with_maker_rebates(mut self, maker_rebates: Arc<MakerRebateEngine>) -> Self {
+        self.maker_rebates = maker_rebates;
+        self
+    }
+
+    /// 按功能开关灰度新版智能路由
+    pub fn with_feature_flags(mut self, feature_flags: FeatureFlags) -> Self {
+        self.feature_flags = Some(feature_flags);
+        self
+    }
+
+    /// 使用共享的订单事件总线，供订单WebSocket订阅
+    pub fn with_order_events(mut self, order_events: Arc<OrderEventBus>) -> Self {
+        self.order_events = order_events;
+        self
+    }
+
+    /// 定期保存内部撮合订单簿，新建撮合引擎时从快照恢复挂单
+    ///
+    /// 恢复时按订单表校正快照中的挂单，快照之后成交或撤销的订单不再挂回订单簿。
+    pub fn with_book_snapshots(mut self, store: Arc<BookSnapshotStore>, order_store: Arc<OrderStore>) -> Self {
+        self.book_snapshots = Some((store, order_store));
+        self
+    }
+
+    /// 每个时间片保存算法单进度
+    pub fn with_algo_store(mut self, store: Arc<AlgoOrderStore>) -> Self {
+        self.algo_store = Some(store);
+        self
+    }
+
+    /// 取走算法单变化的接收端，只能取一次；订单服务启动前发出的变化会保留
+    pub fn take_algo_updates(&self) -> Option<mpsc::UnboundedReceiver<AlgoOrderUpdate>> {
+        self.algo_update_receiver.lock().ok()?.take()
+    }
+
+    /// 内部撮合引擎行情推送
+    pub fn book_feed(&self) -> Arc<InternalBookFeed> {
+        self.book_feed.clone()
+    }
+
+    /// 写入外部波动率读数
+    pub async fn update_volatility(&self, symbol: &Symbol, volatility: Decimal) -> VolatilityReading {
+        self.volatility.update(symbol, volatility, chrono::Utc::now()).await
+    }
+
+    /// 交易对当前的波动分档
+    pub async fn volatility_regime(&self, symbol: &Symbol) -> VolatilityRegime {
+        self.volatility.regime(symbol, chrono::Utc::now()).await
+    }
+
+    /// 各交易对最近的波动率读数
+    pub async fn volatility_readings(&self) -> HashMap<Symbol, VolatilityReading> {
+        self.volatility.readings().await
+    }
+
+    /// 启动标记价格采样，为已有撮合引擎的交易对更新波动分档
+    pub fn start_volatility_sampler(self: Arc<Self>) {
+        if !self.volatility.is_enabled() {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.execution.volatility_policy.sample_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let symbols: Vec<Symbol> = self.matching_engines.read().await.keys().cloned().collect();
+                for symbol in symbols {
+                    if let Some(price) = self.mark_price(&symbol).await {
+                        self.volatility.record_price(&symbol, price, chrono::Utc::now()).await;
+                    }
+                }
+            }
+        });
+    }
+
     /// 注册交易所连接器
     pub async fn register_exchange(&self, connector: ExchangeConnectorEnum) {
         let name = connector.get_name().to_string();
@@ -183,20 +427,457 @@ impl ExecutionEngine {
         tracing::info!("已注册交易所连接器: {}", name);
     }
 
-    /// 获取或创建撮合引擎
+    /// 已注册的交易所及其环境
+    pub async fn registered_exchanges(&self) -> Vec<(String, TradingEnvironment)> {
+        let mut exchanges: Vec<(String, TradingEnvironment)> = self
+            .exchange_connectors
+            .read()
+            .await
+            .iter()
+            .map(|(name, connector)| (name.clone(), connector.environment()))
+            .collect();
+        exchanges.sort_by(|a, b| a.0.cmp(&b.0));
+        exchanges
+    }
+
+    /// 注册通过环境变量配置了凭据的真实交易所，以及配置中启用的测试网
+    pub async fn register_configured_exchanges(&self) -> Result<()> {
+        if let Some(kraken) = KrakenConnector::from_env()? {
+            self.register_exchange(ExchangeConnectorEnum::Kraken(kraken)).await;
+        }
+
+        let testnet = &self.config.execution.testnet;
+        if testnet.is_enabled("binance") {
+            self.register_exchange(ExchangeConnectorEnum::Binance(BinanceConnector::testnet())).await;
+        }
+        if testnet.is_enabled("kraken") {
+            match KrakenConnector::testnet_from_env()? {
+                Some(kraken) => self.register_exchange(ExchangeConnectorEnum::Kraken(kraken)).await,
+                None => tracing::warn!("Kraken testnet is enabled but KRAKEN_TESTNET_* variables are not set"),
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取或创建撮合引擎，新建时先从订单簿快照恢复挂单
     async fn get_matching_engine(&self, symbol: &Symbol) -> Arc<MatchingEngine> {
-        let mut engines = self.matching_engines.write().await;
-        engines
+        if let Some(engine) = self.matching_engines.read().await.get(symbol) {
+            return engine.clone();
+        }
+
+        let collar_bps = self.config.execution.price_collars.collar_bps_for(&symbol.to_string());
+        let engine = Arc::new(MatchingEngine::new(symbol.clone()).with_price_collar(collar_bps));
+        self.restore_book(symbol, &engine).await;
+
+        // 并发创建时以先写入的为准
+        self.matching_engines
+            .write()
+            .await
             .entry(symbol.clone())
-            .or_insert_with(|| Arc::new(MatchingEngine::new(symbol.clone())))
+            .or_insert(engine)
             .clone()
     }
 
+    /// 从快照恢复订单簿
+    ///
+    /// 读取或解码失败时以空订单簿启动，并停止写入该交易对的快照，避免空订单簿覆盖原有挂单。
+    async fn restore_book(&self, symbol: &Symbol, engine: &MatchingEngine) {
+        let Some((store, order_store)) = &self.book_snapshots else {
+            return;
+        };
+
+        let restored = match store.load(&symbol.to_string()).await {
+            Ok(Some(bytes)) => match self.state_codec.decode(&bytes) {
+                Ok(state) => match reconcile_snapshot(order_store, state).await {
+                    Ok((state, dropped)) => {
+                        let resting = state.bids.len() + state.asks.len() + state.trigger_orders.len();
+                        engine.restore_state(state).await.map(|()| Some((resting, dropped)))
+                    }
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        match restored {
+            Ok(Some((resting, dropped))) => tracing::info!(
+                "Restored {} resting orders for {} from book snapshot, dropped {} no longer open",
+                resting,
+                symbol,
+                dropped
+            ),
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!(
+                    "Failed to restore order book for {}, snapshots for this symbol are suspended: {}",
+                    symbol,
+                    e
+                );
+                self.unrestored_books.write().await.insert(symbol.clone());
+            }
+        }
+    }
+
+    /// 恢复所有有快照的交易对，返回恢复的撮合引擎数
+    pub async fn restore_books(&self) -> TradingResult<usize> {
+        let Some((store, _)) = &self.book_snapshots else {
+            return Ok(0);
+        };
+
+        let mut restored = 0;
+        for symbol in store.symbols().await? {
+            match symbol.parse::<Symbol>() {
+                Ok(symbol) => {
+                    self.get_matching_engine(&symbol).await;
+                    restored += 1;
+                }
+                Err(e) => tracing::warn!("Skipping book snapshot with invalid symbol {}: {}", symbol, e),
+            }
+        }
+        Ok(restored)
+    }
+
+    /// 保存所有撮合引擎的订单簿快照，返回保存的交易对数
+    pub async fn snapshot_books(&self) -> TradingResult<usize> {
+        let Some((store, _)) = &self.book_snapshots else {
+            return Ok(0);
+        };
+
+        let engines: Vec<(Symbol, Arc<MatchingEngine>)> = self
+            .matching_engines
+            .read()
+            .await
+            .iter()
+            .map(|(symbol, engine)| (symbol.clone(), engine.clone()))
+            .collect();
+        let unrestored = self.unrestored_books.read().await.clone();
+
+        let mut saved = 0;
+        for (symbol, engine) in engines {
+            if unrestored.contains(&symbol) {
+                continue;
+            }
+            let taken_at = chrono::Utc::now();
+            let state = engine.export_state().await;
+            let resting = state.bids.len() + state.asks.len() + state.trigger_orders.len();
+            let bytes = self.state_codec.encode(&state)?;
+            store
+                .save(&symbol.to_string(), self.state_codec.version(), &bytes, resting, taken_at)
+                .await?;
+            saved += 1;
+        }
+        Ok(saved)
+    }
+
+    /// 启动订单簿快照任务，按配置先恢复所有有快照的交易对
+    pub fn start_book_snapshots(self: Arc<Self>) {
+        if !self.config.execution.book_snapshots.enabled || self.book_snapshots.is_none() {
+            return;
+        }
+        tokio::spawn(async move {
+            let config = self.config.execution.book_snapshots.clone();
+            if config.restore_on_startup {
+                match self.restore_books().await {
+                    Ok(0) => {}
+                    Ok(restored) => tracing::info!("Restored {} matching engines from book snapshots", restored),
+                    Err(e) => tracing::error!("Failed to restore matching engines from book snapshots: {}", e),
+                }
+            }
+
+            let mut ticker = tokio::time::interval(config.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.snapshot_books().await {
+                    tracing::error!("Failed to snapshot order books: {}", e);
+                }
+            }
+        });
+    }
+
     /// 执行订单 - 智能路由
+    ///
+    /// 元数据标记为 TWAP 或 VWAP 的父订单在后台按时间片拆分执行，立即返回待执行结果。
     pub async fn execute_order(
         &self,
         order: Order,
         strategy: RoutingStrategy,
+    ) -> TradingResult<ExecutionResult> {
+        if order.metadata.parent_order_id.is_none() {
+            match order.metadata.algorithm.as_deref() {
+                Some(TWAP_ALGORITHM) => return self.start_twap(order, strategy).await,
+                Some(VWAP_ALGORITHM) => return self.start_vwap(order, strategy).await,
+                _ => {}
+            }
+        }
+        self.execute_order_at(order, strategy, None).await
+    }
+
+    /// 受理 TWAP 父订单，数量按时间均分
+    async fn start_twap(&self, order: Order, strategy: RoutingStrategy) -> TradingResult<ExecutionResult> {
+        let twap = &self.config.execution.algorithms.twap;
+        let duration = twap_duration(&order, twap)?;
+        let schedule = AlgoSchedule {
+            algorithm: TWAP_ALGORITHM,
+            interval: twap.slice_interval,
+            slices: twap_slices(order.quantity, duration, twap.slice_interval),
+            max_participation_rate: twap.max_participation_rate,
+            rebalance: false,
+        };
+        self.start_algo(order, schedule, duration, strategy).await
+    }
+
+    /// 受理 VWAP 父订单，数量按执行时段内的历史成交量曲线分配
+    ///
+    /// 取不到成交量曲线时按时间均分，执行仍按曲线周期切片。
+    async fn start_vwap(&self, order: Order, strategy: RoutingStrategy) -> TradingResult<ExecutionResult> {
+        let vwap = &self.config.execution.algorithms.vwap;
+        let duration = vwap_duration(&order, vwap)?;
+        let profile = match self.volume_profiles.fetch(&order.symbol).await {
+            Ok(profile) => profile,
+            Err(e) => {
+                tracing::warn!(
+                    "No volume profile for {}, VWAP order {} falls back to uniform slices: {}",
+                    order.symbol,
+                    order.id,
+                    e
+                );
+                VolumeProfile::uniform(self.volume_profiles.interval())
+            }
+        };
+        let count = duration.as_millis().div_ceil(profile.interval.as_millis().max(1)).max(1) as usize;
+        let weights = profile.weights(chrono::Utc::now(), count);
+        let schedule = AlgoSchedule {
+            algorithm: VWAP_ALGORITHM,
+            interval: profile.interval,
+            slices: vwap_slices(order.quantity, &weights),
+            max_participation_rate: vwap.max_participation_rate,
+            rebalance: vwap.volume_curve_adjustment,
+        };
+        self.start_algo(order, schedule, duration, strategy).await
+    }
+
+    /// 登记算法单并启动后台执行，以当前标记价格作为滑点基准
+    async fn start_algo(
+        &self,
+        order: Order,
+        schedule: AlgoSchedule,
+        duration: std::time::Duration,
+        strategy: RoutingStrategy,
+    ) -> TradingResult<ExecutionResult> {
+        // 极端行情暂停算法单时直接拒绝，不等到第一个子订单
+        self.apply_volatility_policy(order.clone()).await?;
+
+        let mut progress = AlgoOrderProgress::new(&order, schedule.algorithm, schedule.slices.len() as u32, duration);
+        progress.arrival_price = self.mark_price(&order.symbol).await;
+        self.algo_orders.write().await.insert(order.id, progress.clone());
+        self.save_algo_progress(&progress, schedule.interval).await;
+        self.order_events.publish(OrderEvent::created(&order));
+        self.order_events.publish(OrderEvent::algo_progress(&progress));
+        tracing::info!(
+            "Started {} order {} for {} {} over {}s in {} slices",
+            schedule.algorithm.to_uppercase(),
+            order.id,
+            order.quantity,
+            order.symbol,
+            duration.as_secs(),
+            schedule.slices.len()
+        );
+
+        let result = ExecutionResult {
+            order_id: order.id,
+            execution_id: Uuid::new_v4(),
+            status: ExecutionStatus::Pending,
+            filled_quantity: Decimal::ZERO,
+            avg_price: None,
+            total_fee: Decimal::ZERO,
+            execution_time_ms: 0,
+            venue: ALGO_VENUE.to_string(),
+            trades: Vec::new(),
+            slippage: progress.slippage(),
+            triggered_fills: Vec::new(),
+        };
+        let engine = self.clone();
+        tokio::spawn(async move { engine.run_algo(order, schedule, strategy).await });
+        Ok(result)
+    }
+
+    /// 按切片间隔依次执行子订单
+    ///
+    /// 子订单按对手盘可见数量的参与率上限缩小。未成交和被缩小的数量滚入下一片，
+    /// 或在计划要求时按剩余各片的计划比例重新分配；内部撮合的限价子订单不挂单，
+    /// 未成交部分在本片结束时撤销。父订单被撤销后停止。
+    async fn run_algo(&self, parent: Order, schedule: AlgoSchedule, strategy: RoutingStrategy) {
+        let name = schedule.algorithm.to_uppercase();
+        let mut ticker = tokio::time::interval(schedule.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        let mut carry = Decimal::ZERO;
+        for (index, slice) in schedule.slices.iter().enumerate() {
+            ticker.tick().await;
+            let remaining = match self.algo_orders.read().await.get(&parent.id) {
+                Some(progress) if progress.is_running() => progress.remaining_quantity(),
+                _ => break,
+            };
+
+            let target = if schedule.rebalance {
+                rebalanced_slice(remaining, &schedule.slices[index..])
+            } else {
+                slice + carry
+            };
+            let liquidity = self.visible_liquidity(&parent.symbol, parent.side).await;
+            let quantity = cap_participation(target, liquidity, schedule.max_participation_rate);
+            carry = target - quantity;
+
+            let (filled, avg_price, fee) = if quantity > Decimal::ZERO {
+                let child = algo_child_order(&parent, quantity);
+                match self.execute_order_at(child.clone(), strategy.clone(), None).await {
+                    Ok(result) => {
+                        let filled = result.filled_quantity.min(quantity);
+                        if result.venue == INTERNAL_VENUE && filled < quantity && child.order_type != OrderType::Market {
+                            self.expire_internal_child(&child).await;
+                        }
+                        let (avg_price, fee) = (result.avg_price, result.total_fee);
+                        self.send_algo_update(AlgoOrderUpdate::ChildExecuted {
+                            parent_order_id: parent.id,
+                            result,
+                        });
+                        (Some(filled), avg_price, fee)
+                    }
+                    Err(e) => {
+                        tracing::warn!("{} child order {} of {} failed: {}", name, child.id, parent.id, e);
+                        (Some(Decimal::ZERO), None, Decimal::ZERO)
+                    }
+                }
+            } else {
+                (None, None, Decimal::ZERO)
+            };
+            if let Some(filled) = filled {
+                carry += quantity - filled;
+            }
+
+            let updated = {
+                let mut algo_orders = self.algo_orders.write().await;
+                algo_orders.get_mut(&parent.id).map(|progress| {
+                    if let Some(filled) = filled {
+                        progress.record_child(filled, avg_price, fee);
+                    }
+                    progress.completed_slices += 1;
+                    self.order_events.publish(OrderEvent::algo_progress(progress));
+                    progress.clone()
+                })
+            };
+            if let Some(progress) = updated {
+                self.save_algo_progress(&progress, schedule.interval).await;
+            }
+        }
+
+        let Some(mut progress) = self.algo_orders.write().await.remove(&parent.id) else {
+            return;
+        };
+        // 撤销时已推送撤销事件
+        if progress.is_running() {
+            progress.status = AlgoOrderStatus::Completed;
+            self.order_events.publish(OrderEvent::algo_progress(&progress));
+            let remaining = progress.remaining_quantity();
+            if remaining > Decimal::ZERO {
+                self.order_events.publish(OrderEvent::cancelled(
+                    &parent,
+                    remaining,
+                    &format!("{} window elapsed", schedule.algorithm),
+                ));
+            }
+            tracing::info!(
+                "{} order {} finished: filled {}/{} in {} child orders, avg price {:?}, slippage {:?} bps",
+                name,
+                parent.id,
+                progress.filled_quantity,
+                progress.target_quantity,
+                progress.child_orders,
+                progress.avg_price,
+                progress.slippage_bps
+            );
+        }
+        self.save_algo_progress(&progress, schedule.interval).await;
+        self.send_algo_update(AlgoOrderUpdate::Finished(progress));
+    }
+
+    /// 保存算法单进度，失败只记录日志，中断恢复以最后一次保存的进度为准
+    async fn save_algo_progress(&self, progress: &AlgoOrderProgress, interval: std::time::Duration) {
+        if let Some(store) = &self.algo_store {
+            if let Err(e) = store.save(progress, stale_deadline(interval)).await {
+                tracing::error!("Failed to save progress of algo order {}: {}", progress.parent_order_id, e);
+            }
+        }
+    }
+
+    fn send_algo_update(&self, update: AlgoOrderUpdate) {
+        if self.algo_updates.send(update).is_err() {
+            tracing::error!("Algo order updates receiver is closed, parent order fills are not booked");
+        }
+    }
+
+    /// 对手盘在聚合订单簿可见深度内的数量，取不到订单簿时为零
+    async fn visible_liquidity(&self, symbol: &Symbol, side: Side) -> Decimal {
+        match self.get_aggregated_order_book(symbol, PREVIEW_BOOK_DEPTH).await {
+            Ok(book) => {
+                let levels = match side {
+                    Side::Buy => &book.asks,
+                    Side::Sell => &book.bids,
+                };
+                levels.iter().map(|(_, quantity)| *quantity).sum()
+            }
+            Err(e) => {
+                tracing::debug!("No order book for {} participation cap: {}", symbol, e);
+                Decimal::ZERO
+            }
+        }
+    }
+
+    /// 撤销内部撮合中算法子订单的剩余挂单
+    async fn expire_internal_child(&self, child: &Order) {
+        let matching_engine = self.get_matching_engine(&child.symbol).await;
+        match matching_engine.remove_order(child.id, child.side, child.price).await {
+            Ok(Some(order)) => {
+                self.publish_internal_book(&matching_engine, &[]).await;
+                self.order_events.publish(OrderEvent::cancelled(
+                    &order,
+                    order.quantity - order.filled_quantity,
+                    "algo slice expired",
+                ));
+            }
+            Ok(None) => {}
+            Err(e) => tracing::error!("Failed to expire algo child order {}: {}", child.id, e),
+        }
+    }
+
+    /// 撤销执行中的算法单，已提交的子订单不受影响，返回是否找到
+    pub async fn cancel_algo_order(&self, order_id: Uuid) -> bool {
+        let mut algo_orders = self.algo_orders.write().await;
+        let Some(progress) = algo_orders.get_mut(&order_id).filter(|progress| progress.is_running()) else {
+            return false;
+        };
+        progress.status = AlgoOrderStatus::Cancelled;
+        self.order_events.publish(OrderEvent::algo_progress(progress));
+        self.order_events.publish(OrderEvent::OrderCancelled {
+            user_id: progress.user_id,
+            order_id,
+            symbol: progress.symbol.clone(),
+            remaining_quantity: progress.remaining_quantity(),
+            reason: "cancelled by user".to_string(),
+            timestamp: chrono::Utc::now(),
+        });
+        true
+    }
+
+    /// 执行订单，指定交易所时不经路由选择
+    pub async fn execute_order_at(
+        &self,
+        order: Order,
+        strategy: RoutingStrategy,
+        venue: Option<&str>,
     ) -> TradingResult<ExecutionResult> {
         let start_time = std::time::Instant::now();
         let execution_id = Uuid::new_v4();
@@ -207,12 +888,22 @@ impl ExecutionEngine {
             strategy
         );
 
-        let result = match strategy {
-            RoutingStrategy::BestPrice => self.execute_best_price(&order).await,
-            RoutingStrategy::LowestFee => self.execute_lowest_fee(&order).await,
-            RoutingStrategy::FastestExecution => self.execute_lowest_latency(&order).await,
-            RoutingStrategy::SmartRouting => self.execute_best_price(&order).await, // 暂时使用最佳价格
-            RoutingStrategy::RoundRobin => self.execute_best_price(&order).await, // 暂时使用最佳价格
+        let rejected_order = order.clone();
+        let result = match self.apply_volatility_policy(order).await {
+            Ok((order, child_order_ratio)) => {
+                self.order_events.publish(OrderEvent::created(&order));
+                let result = self.route_order(&order, strategy, child_order_ratio, venue).await;
+                self.publish_execution_events(&order, &result);
+                result
+            }
+            Err(e) => {
+                self.order_events.publish(OrderEvent::cancelled(
+                    &rejected_order,
+                    rejected_order.quantity,
+                    &format!("rejected: {}", e),
+                ));
+                Err(e)
+            }
         };
 
         let execution_time = start_time.elapsed().as_millis() as u64;
@@ -233,9 +924,246 @@ impl ExecutionEngine {
         }
     }
 
+    /// 发布执行结果对应的订单事件
+    ///
+    /// 内部撮合的成交在撮合时按挂单方和吃单方分别发布，这里只发布外部交易所的成交；
+    /// 执行失败、交易所撤单以及市价单未成交的剩余数量发布撤销事件。
+    fn publish_execution_events(&self, order: &Order, result: &TradingResult<ExecutionResult>) {
+        let exec_result = match result {
+            Ok(exec_result) => exec_result,
+            Err(e) => {
+                self.order_events
+                    .publish(OrderEvent::cancelled(order, order.quantity, &format!("rejected: {}", e)));
+                return;
+            }
+        };
+
+        if exec_result.venue != INTERNAL_VENUE {
+            for trade in &exec_result.trades {
+                self.order_events.publish(OrderEvent::OrderFilled {
+                    user_id: order.user_id,
+                    order_id: order.id,
+                    symbol: order.symbol.to_string(),
+                    side: order.side,
+                    trade_id: trade.trade_id,
+                    price: trade.price,
+                    quantity: trade.quantity,
+                    fee: trade.fee,
+                    is_maker: trade.is_maker,
+                    venue: exec_result.venue.clone(),
+                    timestamp: trade.timestamp,
+                });
+            }
+        }
+
+        let remaining = order.quantity - exec_result.filled_quantity;
+        let reason = match exec_result.status {
+            ExecutionStatus::Cancelled => Some("cancelled by venue"),
+            ExecutionStatus::Rejected | ExecutionStatus::Failed => Some("rejected by venue"),
+            // 内部撮合的市价单不挂单，未成交部分直接撤销
+            _ if exec_result.venue == INTERNAL_VENUE
+                && order.order_type == OrderType::Market
+                && remaining > Decimal::ZERO =>
+            {
+                Some("unfilled market remainder")
+            }
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            self.order_events.publish(OrderEvent::cancelled(order, remaining, reason));
+        }
+    }
+
+    /// 按路由策略执行，外部交易所的单笔上限按子订单比例缩小后超出的订单拆分执行
+    async fn route_order(
+        &self,
+        order: &Order,
+        strategy: RoutingStrategy,
+        child_order_ratio: Option<Decimal>,
+        venue: Option<&str>,
+    ) -> TradingResult<ExecutionResult> {
+        let venue = match venue {
+            Some(name) => self.pinned_venue(name, order.metadata.environment).await?,
+            None => self.select_venue(order, strategy).await?,
+        };
+        let Some(venue) = venue else {
+            return self.execute_internal(order).await;
+        };
+        let child_size = child_order_ratio.and_then(|ratio| {
+            self.config
+                .execution
+                .routing
+                .venues
+                .iter()
+                .find(|v| v.name == venue.get_name())
+                .map(|v| v.max_order_size * ratio)
+                .filter(|size| *size > Decimal::ZERO)
+        });
+        match child_size {
+            Some(child_size) if order.quantity > child_size => {
+                self.execute_children(order, &venue, child_size).await
+            }
+            _ => self.execute_with_budget(order, &venue).await,
+        }
+    }
+
+    /// 按波动分档限制订单，返回调整后的订单和子订单比例
+    async fn apply_volatility_policy(&self, mut order: Order) -> TradingResult<(Order, Option<Decimal>)> {
+        let Some((regime, policy)) = self.volatility.policy(&order.symbol, chrono::Utc::now()).await else {
+            return Ok((order, None));
+        };
+        let mark_price = if policy.limit_only && order.order_type == OrderType::Market {
+            self.mark_price(&order.symbol).await
+        } else {
+            None
+        };
+        throttle_order(&mut order, regime, &policy, self.config.execution.max_slippage, mark_price)?;
+        let child_order_ratio = (policy.child_order_ratio < Decimal::ONE).then_some(policy.child_order_ratio);
+        Ok((order, child_order_ratio))
+    }
+
+    /// 拆成不超过 child_size 的子订单依次执行，子订单未完全成交时停止，不再追价
+    async fn execute_children(
+        &self,
+        order: &Order,
+        venue: &ExchangeConnectorEnum,
+        child_size: Decimal,
+    ) -> TradingResult<ExecutionResult> {
+        let mut total_filled = Decimal::ZERO;
+        let mut total_fee = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        let mut all_trades = Vec::new();
+
+        while total_filled < order.quantity {
+            let quantity = (order.quantity - total_filled).min(child_size);
+            let mut child = order.clone();
+            child.id = Uuid::new_v4();
+            child.client_order_id = None;
+            child.quantity = quantity;
+            child.remaining_quantity = quantity;
+            child.metadata.parent_order_id = Some(order.id);
+
+            let result = match self.execute_with_budget(&child, venue).await {
+                Ok(result) => result,
+                // 已有部分成交时返回部分成交结果
+                Err(e) if total_filled > Decimal::ZERO => {
+                    tracing::warn!("Child order of {} failed on {}: {}", order.id, venue.get_name(), e);
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            total_filled += result.filled_quantity;
+            total_fee += result.total_fee;
+            notional += result.avg_price.unwrap_or(Decimal::ZERO) * result.filled_quantity;
+            all_trades.extend(result.trades);
+            if result.filled_quantity < quantity {
+                break;
+            }
+        }
+
+        let status = if total_filled >= order.quantity {
+            ExecutionStatus::Filled
+        } else if total_filled > Decimal::ZERO {
+            ExecutionStatus::PartiallyFilled
+        } else {
+            ExecutionStatus::Pending
+        };
+
+        Ok(ExecutionResult {
+            order_id: order.id,
+            execution_id: Uuid::new_v4(),
+            status,
+            filled_quantity: total_filled,
+            avg_price: (total_filled > Decimal::ZERO).then(|| notional / total_filled),
+            total_fee,
+            execution_time_ms: 0,
+            venue: venue.get_name().to_string(),
+            trades: all_trades,
+            slippage: None,
+            triggered_fills: Vec::new(),
+        })
+    }
+
+    /// 按路由策略选择交易所，返回 None 时使用内部撮合引擎
+    ///
+    /// 下单和订单预览共用该选择逻辑，预览结果与实际路由一致。
+    /// 只在与订单相同环境的交易所中选择，测试网订单不使用内部撮合引擎。
+    async fn select_venue(
+        &self,
+        order: &Order,
+        strategy: RoutingStrategy,
+    ) -> TradingResult<Option<ExchangeConnectorEnum>> {
+        let venue = match strategy {
+            RoutingStrategy::BestPrice => self.best_price_venue(order).await?,
+            RoutingStrategy::LowestFee => self.lowest_fee_venue(order).await?,
+            // 优先使用内部撮合引擎（延迟最低）
+            RoutingStrategy::FastestExecution => None,
+            RoutingStrategy::SmartRouting => self.smart_venue(order).await?,
+            RoutingStrategy::RoundRobin => self.best_price_venue(order).await?, // 暂时使用最佳价格
+        };
+        if venue.is_none() && order.metadata.environment.is_testnet() {
+            return self
+                .get_available_venues(order)
+                .await?
+                .into_iter()
+                .next()
+                .map(Some)
+                .ok_or_else(|| TradingError::ExecutionError("No testnet venue is available".to_string()));
+        }
+        Ok(venue)
+    }
+
+    /// 按名称查找指定的交易所，INTERNAL 表示内部撮合，交易所环境必须与订单一致
+    async fn pinned_venue(
+        &self,
+        name: &str,
+        environment: TradingEnvironment,
+    ) -> TradingResult<Option<ExchangeConnectorEnum>> {
+        if name.eq_ignore_ascii_case(INTERNAL_VENUE) {
+            if environment.is_testnet() {
+                return Err(TradingError::ExecutionError(
+                    "Testnet orders cannot be matched internally".to_string(),
+                ));
+            }
+            return Ok(None);
+        }
+        let connectors = self.exchange_connectors.read().await;
+        let venue = connectors
+            .get(name)
+            .cloned()
+            .ok_or_else(|| TradingError::ExecutionError(format!("Unknown venue: {}", name)))?;
+        if venue.environment() != environment {
+            return Err(TradingError::ExecutionError(format!(
+                "Venue {} is a {} venue, order is {}",
+                name,
+                venue.environment(),
+                environment
+            )));
+        }
+        Ok(Some(venue))
+    }
+
+    /// 智能路由：开启新版路由的用户按流动性选择交易所，其余用户使用最佳价格
+    async fn smart_venue(&self, order: &Order) -> TradingResult<Option<ExchangeConnectorEnum>> {
+        let use_v2 = match &self.feature_flags {
+            Some(feature_flags) => {
+                feature_flags
+                    .is_enabled_for(flags::SMART_ORDER_ROUTING_V2, &order.user_id.to_string())
+                    .await
+            }
+            None => false,
+        };
+
+        if use_v2 {
+            self.max_liquidity_venue(order).await
+        } else {
+            self.best_price_venue(order).await
+        }
+    }
+
     /// 最佳价格执行策略
-    async fn execute_best_price(&self, order: &Order) -> TradingResult<ExecutionResult> {
-        let venues = self.get_available_venues(&order.symbol).await?;
+    async fn best_price_venue(&self, order: &Order) -> TradingResult<Option<ExchangeConnectorEnum>> {
+        let venues = self.get_available_venues(order).await?;
         let mut best_venue = None;
         let mut best_price = None;
 
@@ -257,23 +1185,19 @@ impl ExecutionEngine {
             }
         }
 
-        if let Some(venue) = best_venue {
-            self.execute_on_venue(order, &venue).await
-        } else {
-            // 回退到内部撮合引擎
-            self.execute_internal(order).await
-        }
+        // 没有可用报价时回退到内部撮合引擎
+        Ok(best_venue)
     }
 
     /// 最低手续费执行策略
-    async fn execute_lowest_fee(&self, order: &Order) -> TradingResult<ExecutionResult> {
-        let venues = self.get_available_venues(&order.symbol).await?;
+    async fn lowest_fee_venue(&self, order: &Order) -> TradingResult<Option<ExchangeConnectorEnum>> {
+        let venues = self.get_available_venues(order).await?;
         let mut best_venue = None;
         let mut lowest_fee = None;
 
         for venue in venues {
             let (maker_fee, taker_fee) = venue.get_fees();
-            let fee = if order.order_type == OrderType::Limit { maker_fee } else { taker_fee };
+            let fee = if order.order_type.is_limit_order() { maker_fee } else { taker_fee };
 
             if lowest_fee.is_none() || fee < lowest_fee.unwrap() {
                 lowest_fee = Some(fee);
@@ -281,16 +1205,12 @@ impl ExecutionEngine {
             }
         }
 
-        if let Some(venue) = best_venue {
-            self.execute_on_venue(order, &venue).await
-        } else {
-            self.execute_internal(order).await
-        }
+        Ok(best_venue)
     }
 
     /// 最大流动性执行策略
-    async fn execute_max_liquidity(&self, order: &Order) -> TradingResult<ExecutionResult> {
-        let venues = self.get_available_venues(&order.symbol).await?;
+    async fn max_liquidity_venue(&self, order: &Order) -> TradingResult<Option<ExchangeConnectorEnum>> {
+        let venues = self.get_available_venues(order).await?;
         let mut best_venue = None;
         let mut max_volume = Decimal::ZERO;
 
@@ -303,17 +1223,121 @@ impl ExecutionEngine {
             }
         }
 
-        if let Some(venue) = best_venue {
-            self.execute_on_venue(order, &venue).await
-        } else {
-            self.execute_internal(order).await
+        Ok(best_venue)
+    }
+
+    /// 为多腿价差单报价
+    ///
+    /// 按订单簿估算各腿成交均价，价差低于目标时拒绝。各腿的执行、风控和成交入账由订单服务完成。
+    pub async fn quote_spread(
+        &self,
+        user_id: Uuid,
+        request: &SpreadOrderRequest,
+        strategy: RoutingStrategy,
+    ) -> TradingResult<SpreadQuote> {
+        let spread_config = &self.config.execution.spread_orders;
+        if !spread_config.enabled {
+            return Err(TradingError::ConfigError("Spread orders are disabled".to_string()));
         }
+        let legs = request.legs(spread_config.max_legs)?;
+        let spread_id = Uuid::new_v4();
+
+        let mut orders = Vec::with_capacity(legs.len());
+        let mut quotes = Vec::with_capacity(legs.len());
+        for leg in legs {
+            let order = leg.order(user_id, spread_id, request.quantity * leg.ratio)?;
+            let preview = self.preview_order(&order, strategy.clone()).await?;
+            quotes.push((leg.side, leg.ratio, preview.avg_price));
+            orders.push((leg, order));
+        }
+        let quoted_spread = spread_of(quotes).ok_or_else(|| {
+            TradingError::ExecutionError(format!("No liquidity to quote spread {}", spread_id))
+        })?;
+        if quoted_spread < request.target_spread {
+            return Err(TradingError::RiskViolation(format!(
+                "Quoted spread {} is below target {}",
+                quoted_spread, request.target_spread
+            )));
+        }
+
+        Ok(SpreadQuote {
+            spread_id,
+            legs: orders,
+            quoted_spread,
+            hedge_residuals: spread_config.hedge_residuals,
+        })
     }
 
-    /// 最低延迟执行策略
-    async fn execute_lowest_latency(&self, order: &Order) -> TradingResult<ExecutionResult> {
-        // 优先使用内部撮合引擎（延迟最低）
-        self.execute_internal(order).await
+    /// 预览订单：按实际路由选择交易所，遍历聚合订单簿估算成交，不下单
+    pub async fn preview_order(
+        &self,
+        order: &Order,
+        strategy: RoutingStrategy,
+    ) -> TradingResult<OrderPreview> {
+        let venue = self.select_venue(order, strategy).await?;
+        let (venue_name, (maker_fee, taker_fee)) = match &venue {
+            Some(venue) => (venue.get_name().to_string(), venue.get_fees()),
+            None => (
+                INTERNAL_VENUE.to_string(),
+                (INTERNAL_MAKER_FEE_RATE, INTERNAL_TAKER_FEE_RATE),
+            ),
+        };
+        // 按吃单估算，限价单未成交部分挂单后按挂单费率计
+        let fee_rate = taker_fee;
+
+        let book = self
+            .get_aggregated_order_book(&order.symbol, PREVIEW_BOOK_DEPTH)
+            .await?;
+        let levels = match order.side {
+            Side::Buy => &book.asks,
+            Side::Sell => &book.bids,
+        };
+        let limit_price = match order.order_type {
+            OrderType::Market => None,
+            _ => order.price,
+        };
+        let estimate = estimate_fills(levels, order.side, order.quantity, limit_price);
+
+        let notional: Decimal = estimate.fills.iter().map(|(price, qty)| price * qty).sum();
+        let best_price = levels.first().map(|(price, _)| *price);
+        let slippage_bps = best_price
+            .zip(estimate.avg_price)
+            .and_then(|(best, avg)| slippage_bps(order.side, best, avg));
+        let (volatility_regime, slippage_multiplier) =
+            match self.volatility.policy(&order.symbol, chrono::Utc::now()).await {
+                Some((regime, policy)) => (regime, policy.slippage_multiplier),
+                None => (self.volatility_regime(&order.symbol).await, Decimal::ONE),
+            };
+        let max_slippage_bps = self.config.execution.max_slippage * slippage_multiplier * Decimal::from(10_000);
+        let resting_quantity = order.quantity - estimate.filled_quantity;
+        let resting_fee = match (limit_price, resting_quantity > Decimal::ZERO) {
+            (Some(price), true) => resting_quantity * price * maker_fee,
+            _ => Decimal::ZERO,
+        };
+
+        Ok(OrderPreview {
+            symbol: order.symbol.to_string(),
+            side: order.side,
+            quantity: order.quantity,
+            venue: venue_name,
+            book_venues: book.venues,
+            filled_quantity: estimate.filled_quantity,
+            unfilled_quantity: resting_quantity,
+            avg_price: estimate.avg_price,
+            best_price,
+            slippage_bps,
+            max_slippage_bps,
+            slippage_acceptable: !matches!(slippage_bps, Some(bps) if bps > max_slippage_bps),
+            volatility_regime,
+            notional,
+            fee_rate,
+            estimated_fee: notional * fee_rate + resting_fee,
+            fills: estimate
+                .fills
+                .into_iter()
+                .map(|(price, quantity)| PreviewFill { price, quantity })
+                .collect(),
+        })
     }
 
     /// 分割执行策略
@@ -341,9 +1365,9 @@ impl ExecutionEngine {
             split_order.remaining_quantity = split_quantity;
 
             // 尝试在指定交易所执行
-            let connectors = self.exchange_connectors.read().await;
-            if let Some(connector) = connectors.get(&venue_name) {
-                match self.execute_on_venue(&split_order, connector).await {
+            let connector = self.exchange_connectors.read().await.get(&venue_name).cloned();
+            if let Some(connector) = connector {
+                match self.execute_with_budget(&split_order, &connector).await {
                     Ok(result) => {
                         total_filled += result.filled_quantity;
                         total_fee += result.total_fee;
@@ -386,17 +1410,122 @@ impl ExecutionEngine {
             execution_time_ms: 0, // 将在上层设置
             venue: "SPLIT".to_string(),
             trades: all_trades,
+            slippage: None,
+            triggered_fills: Vec::new(),
         })
     }
 
+    /// 在延迟预算内执行：确认超时则撤单，并按配置改投其他交易所
+    async fn execute_with_budget(
+        &self,
+        order: &Order,
+        connector: &ExchangeConnectorEnum,
+    ) -> TradingResult<ExecutionResult> {
+        let budget = &self.config.execution.latency.budget;
+        let mut tried = vec![connector.get_name().to_string()];
+        let mut venue = connector.clone();
+
+        loop {
+            match self.execute_on_venue(order, &venue).await {
+                Err(VenueError::AckTimeout { timeout, cancelled }) => {
+                    // 撤单失败时原订单可能仍会成交，不再改投以免重复下单
+                    let reroutes = tried.len() as u32 - 1;
+                    let next = if cancelled && budget.reroute_on_timeout && reroutes < budget.max_reroutes {
+                        self.next_venue(&tried, connector.environment()).await
+                    } else {
+                        None
+                    };
+
+                    match next {
+                        Some(next) => {
+                            tracing::warn!(
+                                "Order {} not acknowledged by {} within {}ms, re-routing to {}",
+                                order.id,
+                                venue.get_name(),
+                                timeout.as_millis(),
+                                next.get_name()
+                            );
+                            tried.push(next.get_name().to_string());
+                            venue = next;
+                        }
+                        None => {
+                            return Err(TradingError::ExecutionError(format!(
+                                "{} did not acknowledge order {} within {}ms",
+                                venue.get_name(),
+                                order.id,
+                                timeout.as_millis()
+                            )))
+                        }
+                    }
+                }
+                Err(VenueError::Failed(e)) => return Err(e),
+                Ok(result) => return Ok(result),
+            }
+        }
+    }
+
+    /// 改投目标：同一环境中未尝试过的交易所里延迟惩罚最低的一个
+    async fn next_venue(&self, tried: &[String], environment: TradingEnvironment) -> Option<ExchangeConnectorEnum> {
+        let report = self.slow_venue_report().await;
+        let connectors = self.exchange_connectors.read().await;
+        connectors
+            .values()
+            .filter(|c| c.environment() == environment && !tried.iter().any(|t| t == c.get_name()))
+            .min_by(|a, b| {
+                report
+                    .penalty(a.get_name())
+                    .cmp(&report.penalty(b.get_name()))
+                    .then_with(|| a.get_name().cmp(b.get_name()))
+            })
+            .cloned()
+    }
+
     /// 在指定交易所执行订单
     async fn execute_on_venue(
         &self,
         order: &Order,
         connector: &ExchangeConnectorEnum,
-    ) -> TradingResult<ExecutionResult> {
-        match connector.submit_order(order).await {
+    ) -> Result<ExecutionResult, VenueError> {
+        let budget = &self.config.execution.latency.budget;
+        let venue = connector.get_name().to_string();
+
+        // 未确认的订单没有交易所订单号，需要客户端订单号才能撤单
+        let mut order = order.clone();
+        let client_order_id = order
+            .client_order_id
+            .clone()
+            .unwrap_or_else(|| order.id.to_string());
+        order.client_order_id = Some(client_order_id.clone());
+
+        let submitted_at = Instant::now();
+        let submitted = if budget.enabled {
+            let timeout = budget.ack_timeout_for(&venue);
+            match tokio::time::timeout(timeout, connector.submit_order(&order)).await {
+                Ok(submitted) => submitted,
+                Err(_) => {
+                    self.venue_latency.record_ack_timeout(&venue).await;
+                    // 请求可能已到达交易所，撤单防止迟到的订单成交
+                    let cancelled = match connector.cancel_order_by_client_id(&client_order_id).await {
+                        Ok(_) => true,
+                        Err(e) => {
+                            tracing::warn!(
+                                "Failed to cancel unacknowledged order {} on {}: {}",
+                                client_order_id, venue, e
+                            );
+                            false
+                        }
+                    };
+                    return Err(VenueError::AckTimeout { timeout, cancelled });
+                }
+            }
+        } else {
+            connector.submit_order(&order).await
+        };
+
+        match submitted {
             Ok(exchange_order_id) => {
+                self.venue_latency.record_ack(&venue, submitted_at.elapsed()).await;
+
                 // 模拟等待执行完成
                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                 
@@ -409,22 +1538,43 @@ impl ExecutionEngine {
                             "REJECTED" => ExecutionStatus::Rejected,
                             _ => ExecutionStatus::Pending,
                         };
+                        if execution_status == ExecutionStatus::Filled {
+                            self.venue_latency.record_fill(&venue, submitted_at.elapsed()).await;
+                        }
 
-                        let (maker_fee, taker_fee) = connector.get_fees();
-                        let fee_rate = if order.order_type == OrderType::Limit { maker_fee } else { taker_fee };
-                        let total_fee = if let Some(avg_price) = status.avg_price {
-                            status.filled_quantity * avg_price * fee_rate
+                        let trades = if status.fills.is_empty() {
+                            // 交易所没有逐笔回报时按订单类型估计流动性方向和手续费
+                            let is_maker = order.order_type.is_limit_order();
+                            let (maker_fee, taker_fee) = connector.get_fees();
+                            let fee_rate = if is_maker { maker_fee } else { taker_fee };
+                            let fee = if let Some(avg_price) = status.avg_price {
+                                status.filled_quantity * avg_price * fee_rate
+                            } else {
+                                Decimal::ZERO
+                            };
+                            vec![TradeExecution {
+                                trade_id: Uuid::new_v4(),
+                                price: status.avg_price.unwrap_or(Decimal::ZERO),
+                                quantity: status.filled_quantity,
+                                timestamp: chrono::Utc::now(),
+                                fee,
+                                is_maker,
+                            }]
                         } else {
-                            Decimal::ZERO
-                        };
-
-                        let trade = TradeExecution {
-                            trade_id: Uuid::new_v4(),
-                            price: status.avg_price.unwrap_or(Decimal::ZERO),
-                            quantity: status.filled_quantity,
-                            timestamp: chrono::Utc::now(),
-                            fee: total_fee,
+                            status
+                                .fills
+                                .iter()
+                                .map(|fill| TradeExecution {
+                                    trade_id: Uuid::new_v4(),
+                                    price: fill.price,
+                                    quantity: fill.quantity,
+                                    timestamp: fill.timestamp,
+                                    fee: fill.fee,
+                                    is_maker: fill.is_maker,
+                                })
+                                .collect()
                         };
+                        let total_fee = trades.iter().map(|t| t.fee).sum();
 
                         Ok(ExecutionResult {
                             order_id: order.id,
@@ -435,17 +1585,19 @@ impl ExecutionEngine {
                             total_fee,
                             execution_time_ms: 0,
                             venue: connector.get_name().to_string(),
-                            trades: vec![trade],
+                            trades,
+                            slippage: None,
+                            triggered_fills: Vec::new(),
                         })
                     }
-                    Err(e) => Err(TradingError::ExecutionError(format!(
+                    Err(e) => Err(VenueError::Failed(TradingError::ExecutionError(format!(
                         "Failed to get order status: {}", e
-                    ))),
+                    )))),
                 }
             }
-            Err(e) => Err(TradingError::ExecutionError(format!(
+            Err(e) => Err(VenueError::Failed(TradingError::ExecutionError(format!(
                 "Failed to submit order: {}", e
-            ))),
+            )))),
         }
     }
 
@@ -455,8 +1607,19 @@ impl ExecutionEngine {
         
         match matching_engine.process_order(order.clone()).await {
             Ok(trades) => {
+                // 本次成交触发的条件单与本订单的成交一起推送，并转换为双方的执行回报入账
+                let triggered: Vec<MatchTrade> = matching_engine
+                    .take_triggered_executions()
+                    .await
+                    .into_iter()
+                    .flat_map(|execution| execution.trades)
+                    .collect();
+                let triggered_fills = match_fill_reports(&triggered);
+                let published: Vec<MatchTrade> = trades.iter().cloned().chain(triggered).collect();
+                self.publish_internal_book(&matching_engine, &published).await;
+                self.publish_match_fills(&published);
+
                 let total_filled: Decimal = trades.iter().map(|t| t.quantity).sum();
-                let total_fee: Decimal = trades.iter().map(|t| t.taker_fee).sum();
                 
                 let avg_price = if total_filled > Decimal::ZERO {
                     let weighted_sum: Decimal = trades.iter()
@@ -476,14 +1639,17 @@ impl ExecutionEngine {
                 };
 
                 let trade_executions: Vec<TradeExecution> = trades.into_iter().map(|t| {
+                    let is_maker = t.is_maker(order.id);
                     TradeExecution {
                         trade_id: t.trade_id,
                         price: t.price,
                         quantity: t.quantity,
                         timestamp: t.timestamp,
-                        fee: t.taker_fee,
+                        fee: if is_maker { t.maker_fee } else { t.taker_fee },
+                        is_maker,
                     }
                 }).collect();
+                let total_fee: Decimal = trade_executions.iter().map(|t| t.fee).sum();
 
                 Ok(ExecutionResult {
                     order_id: order.id,
@@ -493,18 +1659,105 @@ impl ExecutionEngine {
                     avg_price,
                     total_fee,
                     execution_time_ms: 0,
-                    venue: "INTERNAL".to_string(),
+                    venue: INTERNAL_VENUE.to_string(),
                     trades: trade_executions,
+                    slippage: None,
+                    triggered_fills,
                 })
             }
             Err(e) => Err(e),
         }
     }
 
-    /// 获取可用交易所
-    async fn get_available_venues(&self, _symbol: &Symbol) -> TradingResult<Vec<ExchangeConnectorEnum>> {
+    /// 按挂单方和吃单方分别发布内部撮合的成交事件
+    fn publish_match_fills(&self, trades: &[MatchTrade]) {
+        for trade in trades {
+            let sides = [
+                (trade.taker_user_id, trade.taker_order_id, trade.side, trade.taker_fee, false),
+                (trade.maker_user_id, trade.maker_order_id, trade.side.opposite(), trade.maker_fee, true),
+            ];
+            for (user_id, order_id, side, fee, is_maker) in sides {
+                self.order_events.publish(OrderEvent::OrderFilled {
+                    user_id,
+                    order_id,
+                    symbol: trade.symbol.to_string(),
+                    side,
+                    trade_id: trade.trade_id,
+                    price: trade.price,
+                    quantity: trade.quantity,
+                    fee,
+                    is_maker,
+                    venue: INTERNAL_VENUE.to_string(),
+                    timestamp: trade.timestamp,
+                });
+            }
+        }
+    }
+
+    /// 推送内部撮合产生的成交和订单簿变化
+    async fn publish_internal_book(&self, matching_engine: &MatchingEngine, trades: &[MatchTrade]) {
+        self.update_maker_rebates(matching_engine).await;
+        if !self.book_feed.is_enabled() {
+            return;
+        }
+        self.book_feed.publish_trades(trades);
+        let book = matching_engine.get_order_book(self.book_feed.depth()).await;
+        self.book_feed.publish_book(&book).await;
+    }
+
+    /// 按订单簿不平衡度更新挂单返佣，返佣率变化时推送给做市方
+    async fn update_maker_rebates(&self, matching_engine: &MatchingEngine) {
+        if !self.maker_rebates.is_enabled() {
+            return;
+        }
+        let book = matching_engine.get_order_book(self.maker_rebates.depth()).await;
+        if let Some(rebate) = self.maker_rebates.update(&book).await {
+            matching_engine
+                .set_maker_rebate_rates(rebate.bid_rebate_rate, rebate.ask_rebate_rate)
+                .await;
+            self.book_feed.publish_maker_rebate(&rebate);
+        }
+    }
+
+    /// 撤销内部撮合引擎中的挂单
+    pub async fn cancel_internal_order(
+        &self,
+        symbol: &Symbol,
+        order_id: Uuid,
+        side: Side,
+        price: Option<Decimal>,
+    ) -> TradingResult<bool> {
+        let matching_engine = self.get_matching_engine(symbol).await;
+        let Some(order) = matching_engine.remove_order(order_id, side, price).await? else {
+            return Ok(false);
+        };
+        self.publish_internal_book(&matching_engine, &[]).await;
+        self.order_events.publish(OrderEvent::cancelled(
+            &order,
+            order.quantity - order.filled_quantity,
+            "cancelled by user",
+        ));
+        Ok(true)
+    }
+
+    /// 获取与订单环境一致的可用交易所，存在其他选择时排除慢交易所
+    async fn get_available_venues(&self, order: &Order) -> TradingResult<Vec<ExchangeConnectorEnum>> {
+        let report = self.slow_venue_report().await;
+        let slow: Vec<&str> = report.slow_venues().collect();
         let connectors = self.exchange_connectors.read().await;
-        Ok(connectors.values().cloned().collect())
+        let venues: Vec<ExchangeConnectorEnum> = connectors
+            .values()
+            .filter(|v| v.environment() == order.metadata.environment)
+            .cloned()
+            .collect();
+
+        if venues.iter().all(|v| slow.contains(&v.get_name())) {
+            return Ok(venues);
+        }
+        Ok(venues
+            .into_iter()
+            .filter(|v| !slow.contains(&v.get_name()))
+            .collect())
     }
 
     /// 更新执行统计
@@ -535,11 +1788,29 @@ impl ExecutionEngine {
 
     /// 获取执行统计
     pub async fn get_execution_stats(&self) -> ExecutionStats {
-        self.execution_stats.read().await.clone()
+        let mut stats = self.execution_stats.read().await.clone();
+        stats.algo_orders = self.algo_orders.read().await.values().cloned().collect();
+        stats.algo_orders.sort_by_key(|progress| progress.started_at);
+        stats
+    }
+
+    /// 慢交易所报告，惩罚分供智能路由使用
+    pub async fn slow_venue_report(&self) -> SlowVenueReport {
+        self.venue_latency
+            .report(&self.config.execution.latency.budget)
+            .await
+    }
+
+    /// 交易所的延迟惩罚分
+    pub async fn venue_latency_penalty(&self, venue: &str) -> Decimal {
+        self.slow_venue_report().await.penalty(venue)
     }
 
     /// 取消订单
     pub async fn cancel_order(&self, order_id: Uuid, venue: Option<String>) -> TradingResult<bool> {
+        if self.cancel_algo_order(order_id).await {
+            return Ok(true);
+        }
         if let Some(venue_name) = venue {
             let connectors = self.exchange_connectors.read().await;
             if let Some(connector) = connectors.get(&venue_name) {
@@ -569,6 +1840,29 @@ impl ExecutionEngine {
         }
     }
 
+    /// 实时标记价格
+    ///
+    /// 优先取外部实盘交易所的买卖中间价，没有报价时取最新成交价，
+    /// 都不可用时回退到内部撮合的最新成交价。测试网报价不参与标记价格。
+    pub async fn mark_price(&self, symbol: &Symbol) -> Option<Decimal> {
+        let connectors = self.exchange_connectors.read().await;
+        for connector in connectors.values().filter(|c| !c.environment().is_testnet()) {
+            if let Ok(data) = connector.get_market_data(symbol).await {
+                let price = match (data.bid, data.ask) {
+                    (Some(bid), Some(ask)) => (bid + ask) / Decimal::from(2),
+                    _ => data.last.unwrap_or(data.price),
+                };
+                if price > Decimal::ZERO {
+                    return Some(price);
+                }
+            }
+        }
+        drop(connectors);
+
+        let matching_engine = self.matching_engines.read().await.get(symbol).cloned()?;
+        matching_engine.get_order_book(0).await.last_price
+    }
+
     /// 获取订单簿聚合视图
     pub async fn get_aggregated_order_book(&self, symbol: &Symbol, depth: usize) -> TradingResult<AggregatedOrderBook> {
         let matching_engine = self.get_matching_engine(symbol).await;
@@ -582,11 +1876,178 @@ impl ExecutionEngine {
             asks: internal_book.asks,
             last_price: internal_book.last_price,
             timestamp: chrono::Utc::now(),
-            venues: vec!["INTERNAL".to_string()],
+            venues: vec![INTERNAL_VENUE.to_string()],
         })
     }
 }
 
+/// 订单预览遍历的订单簿档位数
+const PREVIEW_BOOK_DEPTH: usize = 200;
+
+/// 订单预览中的单档估算成交
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewFill {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+/// 订单预览结果
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderPreview {
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: Decimal,
+    /// 实际下单时路由到的交易所
+    pub venue: String,
+    /// 参与估算的订单簿来源
+    pub book_venues: Vec<String>,
+    pub filled_quantity: Decimal,
+    /// 订单簿深度不足或超出限价的数量
+    pub unfilled_quantity: Decimal,
+    pub avg_price: Option<Decimal>,
+    pub best_price: Option<Decimal>,
+    /// 相对最优价的滑点（基点），不利方向为正
+    pub slippage_bps: Option<Decimal>,
+    /// 当前波动分档下的滑点上限（基点）
+    pub max_slippage_bps: Decimal,
+    pub slippage_acceptable: bool,
+    pub volatility_regime: VolatilityRegime,
+    pub notional: Decimal,
+    pub fee_rate: Decimal,
+    pub estimated_fee: Decimal,
+    pub fills: Vec<PreviewFill>,
+}
+
+/// 遍历订单簿的估算结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillEstimate {
+    pub filled_quantity: Decimal,
+    pub avg_price: Option<Decimal>,
+    /// (价格, 数量)
+    pub fills: Vec<(Decimal, Decimal)>,
+}
+
+/// 按价格优先遍历对手盘估算成交，限价单只吃到限价为止
+///
+/// `levels` 为对手盘，买单传卖盘（价格升序），卖单传买盘（价格降序）。
+pub fn estimate_fills(
+    levels: &[(Decimal, Decimal)],
+    side: Side,
+    quantity: Decimal,
+    limit_price: Option<Decimal>,
+) -> FillEstimate {
+    let mut remaining = quantity;
+    let mut fills = Vec::new();
+    for (price, available) in levels {
+        if remaining <= Decimal::ZERO {
+            break;
+        }
+        let crosses = match (side, limit_price) {
+            (_, None) => true,
+            (Side::Buy, Some(limit)) => *price <= limit,
+            (Side::Sell, Some(limit)) => *price >= limit,
+        };
+        if !crosses {
+            break;
+        }
+        let take = remaining.min(*available);
+        if take > Decimal::ZERO {
+            fills.push((*price, take));
+            remaining -= take;
+        }
+    }
+
+    let filled_quantity = quantity - remaining;
+    let avg_price = if filled_quantity > Decimal::ZERO {
+        let notional: Decimal = fills.iter().map(|(price, qty)| price * qty).sum();
+        Some(notional / filled_quantity)
+    } else {
+        None
+    };
+    FillEstimate {
+        filled_quantity,
+        avg_price,
+        fills,
+    }
+}
+
+/// 按订单表校正快照中的挂单，返回校正后的状态和丢弃的订单数
+///
+/// 快照之后可能已有成交、撤单或过期，订单表是准确状态。
+async fn reconcile_snapshot(
+    order_store: &OrderStore,
+    mut state: MatchingEngineState,
+) -> TradingResult<(MatchingEngineState, usize)> {
+    let ids: Vec<Uuid> = state
+        .bids
+        .iter()
+        .chain(&state.asks)
+        .chain(&state.trigger_orders)
+        .map(|order| order.id)
+        .collect();
+    if ids.is_empty() {
+        return Ok((state, 0));
+    }
+    let stored: HashMap<Uuid, Order> = order_store
+        .get_orders_by_ids(&ids)
+        .await?
+        .into_iter()
+        .map(|order| (order.id, order))
+        .collect();
+
+    state.bids = reconcile_resting(std::mem::take(&mut state.bids), &stored);
+    state.asks = reconcile_resting(std::mem::take(&mut state.asks), &stored);
+    state.trigger_orders = reconcile_resting(std::mem::take(&mut state.trigger_orders), &stored);
+    let dropped = ids.len() - (state.bids.len() + state.asks.len() + state.trigger_orders.len());
+    Ok((state, dropped))
+}
+
+/// 只保留订单表中仍为待成交或部分成交的挂单，并按订单表的成交进度更新剩余数量
+///
+/// 冰山单的剩余数量只表示当前展示部分，不超过订单表中的未成交数量。
+fn reconcile_resting(orders: Vec<Order>, stored: &HashMap<Uuid, Order>) -> Vec<Order> {
+    orders
+        .into_iter()
+        .filter_map(|mut order| {
+            let current = stored.get(&order.id).filter(|current| current.status.is_active())?;
+            if current.remaining_quantity <= Decimal::ZERO {
+                return None;
+            }
+            order.status = current.status;
+            order.filled_quantity = current.filled_quantity;
+            order.remaining_quantity = if order.order_type == OrderType::Iceberg {
+                order.remaining_quantity.min(current.remaining_quantity)
+            } else {
+                current.remaining_quantity
+            };
+            Some(order)
+        })
+        .collect()
+}
+
+/// 把内部撮合的成交转换为吃单方和挂单方的执行回报
+///
+/// 双方共用成交ID，挂单方的回报ID加上后缀，按 (venue, execution_id) 去重时互不覆盖。
+fn match_fill_reports(trades: &[MatchTrade]) -> Vec<ExecutionReport> {
+    trades
+        .iter()
+        .flat_map(|trade| {
+            [
+                (trade.taker_order_id, trade.trade_id.to_string(), trade.taker_fee),
+                (trade.maker_order_id, format!("{}:maker", trade.trade_id), trade.maker_fee),
+            ]
+            .map(|(order_id, execution_id, fee)| ExecutionReport {
+                venue: INTERNAL_VENUE.to_string(),
+                execution_id,
+                order_id,
+                quantity: trade.quantity,
+                price: trade.price,
+                fee,
+            })
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub struct AggregatedOrderBook {
     pub symbol: Symbol,
@@ -595,4 +2056,156 @@ pub struct AggregatedOrderBook {
     pub last_price: Option<Decimal>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub venues: Vec<String>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asks() -> Vec<(Decimal, Decimal)> {
+        vec![
+            (Decimal::from(100), Decimal::from(1)),
+            (Decimal::from(101), Decimal::from(2)),
+            (Decimal::from(103), Decimal::from(5)),
+        ]
+    }
+
+    #[test]
+    fn test_estimate_fills_walks_book() {
+        let estimate = estimate_fills(&asks(), Side::Buy, Decimal::from(3), None);
+        assert_eq!(estimate.filled_quantity, Decimal::from(3));
+        assert_eq!(estimate.fills.len(), 2);
+        // (100 * 1 + 101 * 2) / 3
+        assert_eq!(estimate.avg_price.unwrap().round_dp(4), Decimal::new(1_006_667, 4));
+
+        let estimate = estimate_fills(&asks(), Side::Buy, Decimal::from(20), None);
+        assert_eq!(estimate.filled_quantity, Decimal::from(8));
+    }
+
+    #[test]
+    fn test_estimate_fills_respects_limit_price() {
+        let estimate = estimate_fills(&asks(), Side::Buy, Decimal::from(5), Some(Decimal::from(101)));
+        assert_eq!(estimate.filled_quantity, Decimal::from(3));
+
+        let bids = vec![(Decimal::from(99), Decimal::from(1)), (Decimal::from(98), Decimal::from(1))];
+        let estimate = estimate_fills(&bids, Side::Sell, Decimal::from(2), Some(Decimal::from(99)));
+        assert_eq!(estimate.filled_quantity, Decimal::ONE);
+
+        let estimate = estimate_fills(&[], Side::Sell, Decimal::ONE, None);
+        assert_eq!(estimate.avg_price, None);
+    }
+
+    #[test]
+    fn test_reconcile_resting_keeps_open_orders() {
+        let symbol = Symbol::new("BTC", "USDT");
+        let resting = |quantity: i64| {
+            Order::new(
+                Uuid::new_v4(),
+                symbol.clone(),
+                OrderType::Limit,
+                Side::Buy,
+                Decimal::from(quantity),
+                Some(Decimal::from(100)),
+                None,
+            )
+            .unwrap()
+        };
+        let (partial, filled, missing) = (resting(5), resting(2), resting(1));
+        let mut iceberg = resting(10).with_visible_quantity(Decimal::from(3));
+        iceberg.order_type = OrderType::Iceberg;
+        iceberg.next_iceberg_tranche();
+
+        let mut stored = HashMap::new();
+        let mut current = partial.clone();
+        current.update_fill(Decimal::from(3), Decimal::from(100), Decimal::ZERO).unwrap();
+        stored.insert(current.id, current);
+        let mut current = filled.clone();
+        current.update_fill(Decimal::from(2), Decimal::from(100), Decimal::ZERO).unwrap();
+        stored.insert(current.id, current);
+        let mut current = iceberg.clone();
+        current.remaining_quantity = current.quantity;
+        current.update_fill(Decimal::from(8), Decimal::from(100), Decimal::ZERO).unwrap();
+        stored.insert(current.id, current);
+
+        let restored = reconcile_resting(vec![partial.clone(), filled, missing, iceberg.clone()], &stored);
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].id, partial.id);
+        assert_eq!(restored[0].remaining_quantity, Decimal::from(2));
+        assert_eq!(restored[0].status, OrderStatus::PartiallyFilled);
+        assert_eq!(restored[1].id, iceberg.id);
+        assert_eq!(restored[1].remaining_quantity, Decimal::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_internal_execution_reports_triggered_fills_for_both_sides() {
+        use crate::models::{Position, PositionSide, PositionStatus};
+
+        let engine = ExecutionEngine::new(TradingEngineConfig::default()).await.unwrap();
+        let symbol = Symbol::new("BTC", "USDT");
+        let order = |order_type: OrderType, side: Side, quantity: i64, price: Option<i64>, stop: Option<i64>| {
+            Order::new(
+                Uuid::new_v4(),
+                symbol.clone(),
+                order_type,
+                side,
+                Decimal::from(quantity),
+                price.map(Decimal::from),
+                stop.map(Decimal::from),
+            )
+            .unwrap()
+        };
+        let trade_at = |price: i64| {
+            [
+                order(OrderType::Limit, Side::Sell, 1, Some(price), None),
+                order(OrderType::Limit, Side::Buy, 1, Some(price), None),
+            ]
+        };
+
+        for seed in trade_at(100) {
+            engine.execute_internal(&seed).await.unwrap();
+        }
+        // 多头仓位的止损卖单和下方的买盘
+        let mut stop = order(OrderType::StopLoss, Side::Sell, 2, None, Some(95));
+        let mut position = Position::new(
+            stop.user_id,
+            symbol.clone(),
+            PositionSide::Long,
+            Decimal::from(2),
+            Decimal::from(100),
+            Decimal::ONE,
+            Decimal::from(200),
+        )
+        .unwrap();
+        let mut bid = order(OrderType::Limit, Side::Buy, 5, Some(94), None);
+        engine.execute_internal(&stop).await.unwrap();
+        engine.execute_internal(&bid).await.unwrap();
+
+        // 在触发价成交的订单只返回自身的成交，触发单的成交作为双方的回报返回
+        let [seller, buyer] = trade_at(95);
+        engine.execute_internal(&seller).await.unwrap();
+        let result = engine.execute_internal(&buyer).await.unwrap();
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.triggered_fills.len(), 2);
+
+        let stop_report = result.triggered_fills.iter().find(|r| r.order_id == stop.id).unwrap();
+        let bid_report = result.triggered_fills.iter().find(|r| r.order_id == bid.id).unwrap();
+        assert_eq!(stop_report.venue, INTERNAL_VENUE);
+        assert_ne!(stop_report.execution_id, bid_report.execution_id);
+        assert_eq!(
+            (stop_report.quantity, stop_report.price),
+            (Decimal::from(2), Decimal::from(94))
+        );
+        assert_eq!((bid_report.quantity, bid_report.price), (stop_report.quantity, stop_report.price));
+
+        // 按回报入账后止损单成交、仓位平掉，挂单方部分成交
+        for report in &result.triggered_fills {
+            let target = if report.order_id == stop.id { &mut stop } else { &mut bid };
+            target.update_fill(report.quantity, report.price, report.fee).unwrap();
+        }
+        let pnl = position.partial_close(stop_report.quantity, stop_report.price).unwrap();
+        assert_eq!(stop.status, OrderStatus::Filled);
+        assert_eq!(bid.status, OrderStatus::PartiallyFilled);
+        assert_eq!(bid.remaining_quantity, Decimal::from(3));
+        assert_eq!(position.status, PositionStatus::Closed);
+        assert_eq!(pnl, Decimal::from(-12));
+    }
+}
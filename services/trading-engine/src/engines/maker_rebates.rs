@@ -0,0 +1,195 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::config::execution::MakerRebateConfig;
+use crate::engines::matching_engine::OrderBookSnapshot;
+
+/// 交易对当前的挂单返佣
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MakerRebate {
+    pub symbol: String,
+    /// 订单簿不平衡度 (买量-卖量)/(买量+卖量)，正数表示买盘较厚
+    pub imbalance: Decimal,
+    pub bid_depth: Decimal,
+    pub ask_depth: Decimal,
+    /// 买方挂单的返佣率
+    pub bid_rebate_rate: Decimal,
+    /// 卖方挂单的返佣率
+    pub ask_rebate_rate: Decimal,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl MakerRebate {
+    fn same_rates(&self, other: &MakerRebate) -> bool {
+        self.bid_rebate_rate == other.bid_rebate_rate && self.ask_rebate_rate == other.ask_rebate_rate
+    }
+}
+
+/// 订单簿不平衡度，两侧都为空时为0
+pub fn book_imbalance(bid_depth: Decimal, ask_depth: Decimal) -> Decimal {
+    let total = bid_depth + ask_depth;
+    if total <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    (bid_depth - ask_depth) / total
+}
+
+/// 薄弱一侧的返佣率，不平衡度未超过阈值时为0
+pub fn rebate_rate(
+    imbalance: Decimal,
+    threshold: Decimal,
+    min_rate: Decimal,
+    max_rate: Decimal,
+) -> Decimal {
+    let imbalance = imbalance.abs().min(Decimal::ONE);
+    if imbalance <= threshold {
+        return Decimal::ZERO;
+    }
+    let scale = (imbalance - threshold) / (Decimal::ONE - threshold);
+    min_rate + (max_rate - min_rate) * scale
+}
+
+/// 挂单返佣激励引擎
+///
+/// 每次内部订单簿变化后按配置档位计算买卖两侧的不平衡度，
+/// 对较薄一侧的挂单给予返佣，撮合引擎按返佣率调整挂单手续费。
+pub struct MakerRebateEngine {
+    config: MakerRebateConfig,
+    rebates: RwLock<HashMap<String, MakerRebate>>,
+}
+
+impl MakerRebateEngine {
+    pub fn new(config: MakerRebateConfig) -> Self {
+        Self {
+            config,
+            rebates: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 计算不平衡度使用的订单簿档位数
+    pub fn depth(&self) -> usize {
+        self.config.depth
+    }
+
+    /// 按订单簿快照计算返佣
+    pub fn compute(&self, snapshot: &OrderBookSnapshot) -> MakerRebate {
+        let symbol = snapshot.symbol.to_string();
+        let depth = self.config.depth;
+        let bid_depth: Decimal = snapshot.bids.iter().take(depth).map(|(_, qty)| *qty).sum();
+        let ask_depth: Decimal = snapshot.asks.iter().take(depth).map(|(_, qty)| *qty).sum();
+        let imbalance = book_imbalance(bid_depth, ask_depth);
+        let rate = rebate_rate(
+            imbalance,
+            self.config.imbalance_threshold,
+            self.config.min_rebate_rate,
+            self.config.max_rebate_rate_for(&symbol),
+        );
+
+        // 买盘较厚时卖方较薄，返佣给卖方挂单，反之给买方
+        let (bid_rebate_rate, ask_rebate_rate) = if imbalance > Decimal::ZERO {
+            (Decimal::ZERO, rate)
+        } else {
+            (rate, Decimal::ZERO)
+        };
+
+        MakerRebate {
+            symbol,
+            imbalance,
+            bid_depth,
+            ask_depth,
+            bid_rebate_rate,
+            ask_rebate_rate,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// 更新交易对的返佣，返佣率有变化时返回新的返佣
+    pub async fn update(&self, snapshot: &OrderBookSnapshot) -> Option<MakerRebate> {
+        let rebate = self.compute(snapshot);
+        let mut rebates = self.rebates.write().await;
+        let changed = rebates
+            .get(&rebate.symbol)
+            .map(|current| !current.same_rates(&rebate))
+            .unwrap_or(true);
+        rebates.insert(rebate.symbol.clone(), rebate.clone());
+        changed.then_some(rebate)
+    }
+
+    /// 当前返佣，未指定交易对时返回全部
+    pub async fn rebates(&self, symbol: Option<&str>) -> Vec<MakerRebate> {
+        let rebates = self.rebates.read().await;
+        let mut result: Vec<MakerRebate> = rebates
+            .values()
+            .filter(|rebate| match symbol {
+                Some(symbol) => symbol.eq_ignore_ascii_case(&rebate.symbol),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        result.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn book(bids: &[(i64, i64)], asks: &[(i64, i64)]) -> OrderBookSnapshot {
+        let levels = |levels: &[(i64, i64)]| {
+            levels
+                .iter()
+                .map(|(p, q)| (Decimal::from(*p), Decimal::from(*q)))
+                .collect()
+        };
+        OrderBookSnapshot {
+            symbol: Symbol::new("BTC", "USDT"),
+            bids: levels(bids),
+            asks: levels(asks),
+            last_price: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rebate_for_thin_side() {
+        let engine = MakerRebateEngine::new(MakerRebateConfig {
+            enabled: true,
+            depth: 2,
+            imbalance_threshold: Decimal::new(2, 1),
+            min_rebate_rate: Decimal::new(1, 4),
+            max_rebate_rate: Decimal::new(5, 4),
+            symbol_max_rebate_rates: HashMap::new(),
+        });
+
+        // 均衡的订单簿没有返佣
+        let balanced = engine.compute(&book(&[(100, 5)], &[(101, 5)]));
+        assert_eq!(balanced.bid_rebate_rate, Decimal::ZERO);
+        assert_eq!(balanced.ask_rebate_rate, Decimal::ZERO);
+
+        // 卖盘较薄，不平衡度 0.6，返佣率在上下限之间线性插值；超出档位的数量不计入
+        let rebate = engine
+            .update(&book(&[(100, 6), (99, 2), (98, 100)], &[(101, 2)]))
+            .await
+            .unwrap();
+        assert_eq!(rebate.imbalance, Decimal::new(6, 1));
+        assert_eq!(rebate.bid_rebate_rate, Decimal::ZERO);
+        assert_eq!(rebate.ask_rebate_rate, Decimal::new(3, 4));
+
+        // 返佣率不变时不重复推送
+        assert!(engine.update(&book(&[(100, 8)], &[(101, 2)])).await.is_none());
+
+        // 买盘为空时买方返佣为上限
+        let rebate = engine.update(&book(&[], &[(101, 2)])).await.unwrap();
+        assert_eq!(rebate.bid_rebate_rate, Decimal::new(5, 4));
+        assert_eq!(rebate.ask_rebate_rate, Decimal::ZERO);
+        assert_eq!(engine.rebates(Some("btcusdt")).await.len(), 1);
+    }
+}
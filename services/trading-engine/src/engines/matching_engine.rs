@@ -5,8 +5,16 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use super::state_format::MatchingEngineState;
+use super::trigger_book::{self, TriggerOrderBook};
 use crate::models::{Order, OrderType, Side, Symbol, TradingError, TradingResult};
 
+/// 内部撮合挂单手续费率 0.01%
+pub const INTERNAL_MAKER_FEE_RATE: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+
+/// 内部撮合吃单手续费率 0.02%
+pub const INTERNAL_TAKER_FEE_RATE: Decimal = Decimal::from_parts(2, 0, 0, false, 4);
+
 /// 高性能订单撮合引擎
 /// 使用价格-时间优先算法，支持微秒级撮合
 #[derive(Debug)]
@@ -20,6 +28,14 @@ pub struct MatchingEngine {
     last_price: Arc<RwLock<Option<Decimal>>>,
     /// 成交统计
     stats: Arc<RwLock<MatchingStats>>,
+    /// 挂单返佣率 (买方, 卖方)
+    maker_rebate_rates: Arc<RwLock<(Decimal, Decimal)>>,
+    /// 未触发的止损、止盈单
+    trigger_orders: Arc<RwLock<TriggerOrderBook>>,
+    /// 已触发条件单的撮合结果，由调用方取走
+    triggered_executions: Arc<RwLock<Vec<TriggeredExecution>>>,
+    /// 市价单价格保护幅度（基点），None 表示不保护
+    price_collar_bps: Option<Decimal>,
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +53,8 @@ pub struct TradeExecution {
     pub trade_id: Uuid,
     pub maker_order_id: Uuid,
     pub taker_order_id: Uuid,
+    pub maker_user_id: Uuid,
+    pub taker_user_id: Uuid,
     pub symbol: Symbol,
     pub price: Decimal,
     pub quantity: Decimal,
@@ -46,6 +64,23 @@ pub struct TradeExecution {
     pub taker_fee: Decimal,
 }
 
+impl TradeExecution {
+    /// 指定订单在该成交中是否为挂单方（增加流动性）
+    pub fn is_maker(&self, order_id: Uuid) -> bool {
+        self.maker_order_id == order_id
+    }
+}
+
+/// 条件单触发后的撮合结果
+#[derive(Debug, Clone)]
+pub struct TriggeredExecution {
+    /// 触发后转换的市价单或限价单
+    pub order: Order,
+    /// 触发时的最新成交价
+    pub trigger_price: Decimal,
+    pub trades: Vec<TradeExecution>,
+}
+
 #[derive(Debug, Clone)]
 pub struct OrderBookSnapshot {
     pub symbol: Symbol,
@@ -70,26 +105,46 @@ impl MatchingEngine {
                 price_low_24h: None,
                 volume_24h: Decimal::ZERO,
             })),
+            maker_rebate_rates: Arc::new(RwLock::new((Decimal::ZERO, Decimal::ZERO))),
+            trigger_orders: Arc::new(RwLock::new(TriggerOrderBook::new())),
+            triggered_executions: Arc::new(RwLock::new(Vec::new())),
+            price_collar_bps: None,
         }
     }
 
+    /// 设置市价单价格保护幅度（基点）
+    pub fn with_price_collar(mut self, collar_bps: Option<Decimal>) -> Self {
+        self.price_collar_bps = collar_bps;
+        self
+    }
+
     /// 处理新订单 - 核心撮合逻辑
-    pub async fn process_order(&self, mut order: Order) -> TradingResult<Vec<TradeExecution>> {
-        let mut trades = Vec::new();
+    ///
+    /// 止损、止盈单进入条件单簿，最新价已越过触发价时立即按市价单或限价单撮合。
+    /// 返回的成交只包含本订单作为吃单方的成交；因本次成交触发的其他条件单的结果
+    /// 通过 [`MatchingEngine::take_triggered_executions`] 取走。
+    pub async fn process_order(&self, order: Order) -> TradingResult<Vec<TradeExecution>> {
+        let trades = if trigger_book::is_trigger_order(order.order_type) {
+            self.submit_trigger_order(order).await?
+        } else {
+            self.match_order(order).await?
+        };
+
+        self.run_triggers().await?;
+        Ok(trades)
+    }
 
-        match order.order_type {
-            OrderType::Market => {
-                trades = self.process_market_order(&mut order).await?;
-            }
-            OrderType::Limit => {
-                trades = self.process_limit_order(&mut order).await?;
-            }
+    /// 撮合市价单或限价单并更新统计
+    async fn match_order(&self, mut order: Order) -> TradingResult<Vec<TradeExecution>> {
+        let trades = match order.order_type {
+            OrderType::Market => self.process_market_order(&mut order).await?,
+            OrderType::Limit | OrderType::Iceberg => self.process_limit_order(&mut order).await?,
             _ => {
                 return Err(TradingError::InvalidOrder(
                     "Unsupported order type for matching".to_string(),
                 ));
             }
-        }
+        };
 
         // 更新统计信息
         self.update_stats(&trades).await;
@@ -97,19 +152,93 @@ impl MatchingEngine {
         Ok(trades)
     }
 
+    /// 提交条件单，已越过触发价时立即撮合，否则挂入条件单簿
+    async fn submit_trigger_order(&self, order: Order) -> TradingResult<Vec<TradeExecution>> {
+        let (stop_price, direction) = TriggerOrderBook::validate(&order)?;
+        let last_price = *self.last_price.read().await;
+        match last_price {
+            Some(last_price) if direction.is_crossed(stop_price, last_price) => {
+                self.match_order(trigger_book::activate(order)).await
+            }
+            _ => {
+                self.trigger_orders.write().await.insert(order)?;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// 按最新价触发条件单，触发单的成交可能继续触发其他条件单
+    async fn run_triggers(&self) -> TradingResult<()> {
+        loop {
+            let Some(last_price) = *self.last_price.read().await else {
+                return Ok(());
+            };
+            let triggered = self.trigger_orders.write().await.take_triggered(last_price);
+            if triggered.is_empty() {
+                return Ok(());
+            }
+
+            for order in triggered {
+                let order = trigger_book::activate(order);
+                // 触发后被价格保护拒绝的条件单不影响其他订单的撮合
+                let trades = match self.match_order(order.clone()).await {
+                    Ok(trades) => trades,
+                    Err(e) => {
+                        tracing::warn!("Trigger order {} rejected after activation: {}", order.id, e);
+                        Vec::new()
+                    }
+                };
+                tracing::info!(
+                    "Trigger order {} activated at {} as {} with {} trades",
+                    order.id,
+                    last_price,
+                    order.order_type,
+                    trades.len()
+                );
+                self.triggered_executions.write().await.push(TriggeredExecution {
+                    order,
+                    trigger_price: last_price,
+                    trades,
+                });
+            }
+        }
+    }
+
+    /// 外部行情更新最新价，返回因此触发的条件单撮合结果
+    pub async fn update_last_price(&self, price: Decimal) -> TradingResult<Vec<TriggeredExecution>> {
+        *self.last_price.write().await = Some(price);
+        self.run_triggers().await?;
+        Ok(self.take_triggered_executions().await)
+    }
+
+    /// 取走已触发条件单的撮合结果
+    pub async fn take_triggered_executions(&self) -> Vec<TriggeredExecution> {
+        std::mem::take(&mut *self.triggered_executions.write().await)
+    }
+
+    /// 未触发的条件单数量
+    pub async fn pending_trigger_orders(&self) -> usize {
+        self.trigger_orders.read().await.len()
+    }
+
     /// 处理市价单
+    ///
+    /// 启用价格保护时以对手方最优价计算保护价，超出保护价的档位不再成交，
+    /// 剩余数量撤销而不是扫穿薄的订单簿。
     async fn process_market_order(&self, order: &mut Order) -> TradingResult<Vec<TradeExecution>> {
         let mut trades = Vec::new();
         let mut remaining_qty = order.quantity;
+        let maker_rebate = self.maker_rebate_rate(order.side.opposite()).await;
 
         match order.side {
             Side::Buy => {
                 // 买入市价单，从最低卖价开始撮合
                 let mut ask_orders = self.ask_orders.write().await;
+                let protection_price = self.protection_price(order, ask_orders.keys().next().copied())?;
                 let mut prices_to_remove = Vec::new();
 
                 for (&price, orders_at_price) in ask_orders.iter_mut() {
-                    if remaining_qty <= Decimal::ZERO {
+                    if remaining_qty <= Decimal::ZERO || protection_price.is_some_and(|limit| price > limit) {
                         break;
                     }
 
@@ -126,12 +255,14 @@ impl MatchingEngine {
                             trade_id: Uuid::new_v4(),
                             maker_order_id: maker_order.id,
                             taker_order_id: order.id,
+                            maker_user_id: maker_order.user_id,
+                            taker_user_id: order.user_id,
                             symbol: self.symbol.clone(),
                             price,
                             quantity: trade_qty,
                             side: Side::Buy,
                             timestamp: chrono::Utc::now(),
-                            maker_fee: self.calculate_maker_fee(trade_qty, price),
+                            maker_fee: self.calculate_maker_fee(trade_qty, price, maker_rebate),
                             taker_fee: self.calculate_taker_fee(trade_qty, price),
                         };
 
@@ -142,9 +273,11 @@ impl MatchingEngine {
                         maker_order.remaining_quantity -= trade_qty;
                         remaining_qty -= trade_qty;
 
-                        // 如果maker订单完全成交，不放回队列
+                        // 如果maker订单完全成交，不放回队列；冰山单补充展示数量后排到队尾
                         if maker_order.remaining_quantity > Decimal::ZERO {
                             orders_at_price.push_front(maker_order);
+                        } else if maker_order.next_iceberg_tranche() {
+                            orders_at_price.push_back(maker_order);
                         }
                     }
 
@@ -161,10 +294,11 @@ impl MatchingEngine {
             Side::Sell => {
                 // 卖出市价单，从最高买价开始撮合
                 let mut bid_orders = self.bid_orders.write().await;
+                let protection_price = self.protection_price(order, bid_orders.keys().next_back().copied())?;
                 let mut prices_to_remove = Vec::new();
 
                 for (&price, orders_at_price) in bid_orders.iter_mut().rev() {
-                    if remaining_qty <= Decimal::ZERO {
+                    if remaining_qty <= Decimal::ZERO || protection_price.is_some_and(|limit| price < limit) {
                         break;
                     }
 
@@ -180,12 +314,14 @@ impl MatchingEngine {
                             trade_id: Uuid::new_v4(),
                             maker_order_id: maker_order.id,
                             taker_order_id: order.id,
+                            maker_user_id: maker_order.user_id,
+                            taker_user_id: order.user_id,
                             symbol: self.symbol.clone(),
                             price,
                             quantity: trade_qty,
                             side: Side::Sell,
                             timestamp: chrono::Utc::now(),
-                            maker_fee: self.calculate_maker_fee(trade_qty, price),
+                            maker_fee: self.calculate_maker_fee(trade_qty, price, maker_rebate),
                             taker_fee: self.calculate_taker_fee(trade_qty, price),
                         };
 
@@ -197,6 +333,8 @@ impl MatchingEngine {
 
                         if maker_order.remaining_quantity > Decimal::ZERO {
                             orders_at_price.push_front(maker_order);
+                        } else if maker_order.next_iceberg_tranche() {
+                            orders_at_price.push_back(maker_order);
                         }
                     }
 
@@ -211,6 +349,15 @@ impl MatchingEngine {
             }
         }
 
+        if remaining_qty > Decimal::ZERO && order.price.is_some() {
+            tracing::info!(
+                "Market order {} stopped at protection price {:?}, {} unfilled",
+                order.id,
+                order.price,
+                remaining_qty
+            );
+        }
+
         // 更新最新成交价
         if let Some(last_trade) = trades.last() {
             *self.last_price.write().await = Some(last_trade.price);
@@ -219,11 +366,32 @@ impl MatchingEngine {
         Ok(trades)
     }
 
+    /// 按对手方最优价计算市价单保护价并记录在订单上，未启用价格保护时返回 None
+    fn protection_price(&self, order: &mut Order, best_price: Option<Decimal>) -> TradingResult<Option<Decimal>> {
+        let Some(collar_bps) = self.price_collar_bps else {
+            return Ok(None);
+        };
+        let best_price = best_price.ok_or_else(|| {
+            TradingError::ExecutionError(format!(
+                "No liquidity within price collar for market order {}",
+                order.id
+            ))
+        })?;
+        let collar = best_price * collar_bps / Decimal::from(10_000);
+        let price = match order.side {
+            Side::Buy => best_price + collar,
+            Side::Sell => best_price - collar,
+        };
+        order.price = Some(price);
+        Ok(Some(price))
+    }
+
     /// 处理限价单
     async fn process_limit_order(&self, order: &mut Order) -> TradingResult<Vec<TradeExecution>> {
         let order_price = order.price.ok_or_else(|| {
             TradingError::InvalidOrder("Limit order must have price".to_string())
         })?;
+        let maker_rebate = self.maker_rebate_rate(order.side.opposite()).await;
 
         let mut trades = Vec::new();
         let mut remaining_qty = order.quantity;
@@ -251,12 +419,14 @@ impl MatchingEngine {
                             trade_id: Uuid::new_v4(),
                             maker_order_id: maker_order.id,
                             taker_order_id: order.id,
+                            maker_user_id: maker_order.user_id,
+                            taker_user_id: order.user_id,
                             symbol: self.symbol.clone(),
                             price: ask_price, // 使用maker价格
                             quantity: trade_qty,
                             side: Side::Buy,
                             timestamp: chrono::Utc::now(),
-                            maker_fee: self.calculate_maker_fee(trade_qty, ask_price),
+                            maker_fee: self.calculate_maker_fee(trade_qty, ask_price, maker_rebate),
                             taker_fee: self.calculate_taker_fee(trade_qty, ask_price),
                         };
 
@@ -268,6 +438,8 @@ impl MatchingEngine {
 
                         if maker_order.remaining_quantity > Decimal::ZERO {
                             orders_at_price.push_front(maker_order);
+                        } else if maker_order.next_iceberg_tranche() {
+                            orders_at_price.push_back(maker_order);
                         }
                     }
 
@@ -280,13 +452,14 @@ impl MatchingEngine {
                     ask_orders.remove(&price);
                 }
 
-                // 如果还有剩余数量，加入买单订单簿
+                // 如果还有剩余数量，加入买单订单簿，冰山单只挂出展示数量
                 if remaining_qty > Decimal::ZERO {
                     drop(ask_orders); // 释放写锁
                     let mut bid_orders = self.bid_orders.write().await;
                     
                     order.remaining_quantity = remaining_qty;
                     order.filled_quantity = order.quantity - remaining_qty;
+                    order.next_iceberg_tranche();
                     
                     bid_orders
                         .entry(order_price)
@@ -316,12 +489,14 @@ impl MatchingEngine {
                             trade_id: Uuid::new_v4(),
                             maker_order_id: maker_order.id,
                             taker_order_id: order.id,
+                            maker_user_id: maker_order.user_id,
+                            taker_user_id: order.user_id,
                             symbol: self.symbol.clone(),
                             price: bid_price, // 使用maker价格
                             quantity: trade_qty,
                             side: Side::Sell,
                             timestamp: chrono::Utc::now(),
-                            maker_fee: self.calculate_maker_fee(trade_qty, bid_price),
+                            maker_fee: self.calculate_maker_fee(trade_qty, bid_price, maker_rebate),
                             taker_fee: self.calculate_taker_fee(trade_qty, bid_price),
                         };
 
@@ -333,6 +508,8 @@ impl MatchingEngine {
 
                         if maker_order.remaining_quantity > Decimal::ZERO {
                             orders_at_price.push_front(maker_order);
+                        } else if maker_order.next_iceberg_tranche() {
+                            orders_at_price.push_back(maker_order);
                         }
                     }
 
@@ -345,13 +522,14 @@ impl MatchingEngine {
                     bid_orders.remove(&price);
                 }
 
-                // 如果还有剩余数量，加入卖单订单簿
+                // 如果还有剩余数量，加入卖单订单簿，冰山单只挂出展示数量
                 if remaining_qty > Decimal::ZERO {
                     drop(bid_orders);
                     let mut ask_orders = self.ask_orders.write().await;
                     
                     order.remaining_quantity = remaining_qty;
                     order.filled_quantity = order.quantity - remaining_qty;
+                    order.next_iceberg_tranche();
                     
                     ask_orders
                         .entry(order_price)
@@ -369,19 +547,27 @@ impl MatchingEngine {
         Ok(trades)
     }
 
-    /// 取消订单
+    /// 取消订单，未触发的条件单从条件单簿撤销
     pub async fn cancel_order(&self, order_id: Uuid, side: Side, price: Option<Decimal>) -> TradingResult<bool> {
+        Ok(self.remove_order(order_id, side, price).await?.is_some())
+    }
+
+    /// 撤销订单并返回被撤销的订单
+    pub async fn remove_order(&self, order_id: Uuid, side: Side, price: Option<Decimal>) -> TradingResult<Option<Order>> {
+        if let Some(order) = self.trigger_orders.write().await.remove(order_id) {
+            return Ok(Some(order));
+        }
         match side {
             Side::Buy => {
                 let mut bid_orders = self.bid_orders.write().await;
                 if let Some(price) = price {
                     if let Some(orders_at_price) = bid_orders.get_mut(&price) {
                         if let Some(pos) = orders_at_price.iter().position(|o| o.id == order_id) {
-                            orders_at_price.remove(pos);
+                            let order = orders_at_price.remove(pos);
                             if orders_at_price.is_empty() {
                                 bid_orders.remove(&price);
                             }
-                            return Ok(true);
+                            return Ok(order);
                         }
                     }
                 }
@@ -391,17 +577,17 @@ impl MatchingEngine {
                 if let Some(price) = price {
                     if let Some(orders_at_price) = ask_orders.get_mut(&price) {
                         if let Some(pos) = orders_at_price.iter().position(|o| o.id == order_id) {
-                            orders_at_price.remove(pos);
+                            let order = orders_at_price.remove(pos);
                             if orders_at_price.is_empty() {
                                 ask_orders.remove(&price);
                             }
-                            return Ok(true);
+                            return Ok(order);
                         }
                     }
                 }
             }
         }
-        Ok(false)
+        Ok(None)
     }
 
     /// 获取订单簿快照
@@ -438,6 +624,55 @@ impl MatchingEngine {
         }
     }
 
+    /// 导出订单簿、条件单和最新价，用于持久化和跨版本恢复
+    pub async fn export_state(&self) -> MatchingEngineState {
+        let bid_orders = self.bid_orders.read().await;
+        let ask_orders = self.ask_orders.read().await;
+        let trigger_orders = self.trigger_orders.read().await;
+        MatchingEngineState {
+            symbol: self.symbol.clone(),
+            bids: bid_orders.values().rev().flatten().cloned().collect(),
+            asks: ask_orders.values().flatten().cloned().collect(),
+            trigger_orders: trigger_orders.orders().cloned().collect(),
+            last_price: *self.last_price.read().await,
+            maker_rebate_rates: *self.maker_rebate_rates.read().await,
+        }
+    }
+
+    /// 用导出的状态替换当前订单簿，同价订单按保存顺序排队
+    pub async fn restore_state(&self, state: MatchingEngineState) -> TradingResult<()> {
+        if state.symbol != self.symbol {
+            return Err(TradingError::IncompatibleStateFormat(format!(
+                "state for {} cannot be restored into {} matching engine",
+                state.symbol, self.symbol
+            )));
+        }
+
+        let mut bids: BTreeMap<Decimal, VecDeque<Order>> = BTreeMap::new();
+        let mut asks: BTreeMap<Decimal, VecDeque<Order>> = BTreeMap::new();
+        for order in state.bids.into_iter().chain(state.asks) {
+            let price = order
+                .price
+                .ok_or_else(|| TradingError::InvalidOrder(format!("Resting order {} has no price", order.id)))?;
+            let levels = match order.side {
+                Side::Buy => &mut bids,
+                Side::Sell => &mut asks,
+            };
+            levels.entry(price).or_default().push_back(order);
+        }
+        let mut triggers = TriggerOrderBook::new();
+        for order in state.trigger_orders {
+            triggers.insert(order)?;
+        }
+
+        *self.bid_orders.write().await = bids;
+        *self.ask_orders.write().await = asks;
+        *self.trigger_orders.write().await = triggers;
+        *self.last_price.write().await = state.last_price;
+        *self.maker_rebate_rates.write().await = state.maker_rebate_rates;
+        Ok(())
+    }
+
     /// 获取最佳买卖价
     pub async fn get_best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
         let bid_orders = self.bid_orders.read().await;
@@ -449,16 +684,30 @@ impl MatchingEngine {
         (best_bid, best_ask)
     }
 
-    /// 计算maker手续费
-    fn calculate_maker_fee(&self, quantity: Decimal, price: Decimal) -> Decimal {
+    /// 设置挂单返佣率，由返佣激励引擎按订单簿不平衡度更新
+    pub async fn set_maker_rebate_rates(&self, bid_rebate_rate: Decimal, ask_rebate_rate: Decimal) {
+        *self.maker_rebate_rates.write().await = (bid_rebate_rate, ask_rebate_rate);
+    }
+
+    /// 挂单方所在一侧的返佣率
+    async fn maker_rebate_rate(&self, maker_side: Side) -> Decimal {
+        let (bid_rebate_rate, ask_rebate_rate) = *self.maker_rebate_rates.read().await;
+        match maker_side {
+            Side::Buy => bid_rebate_rate,
+            Side::Sell => ask_rebate_rate,
+        }
+    }
+
+    /// 计算maker手续费，扣除返佣后为负表示向挂单方支付返佣
+    fn calculate_maker_fee(&self, quantity: Decimal, price: Decimal, rebate_rate: Decimal) -> Decimal {
         let notional = quantity * price;
-        notional * Decimal::new(1, 4) // 0.01% maker fee
+        notional * (INTERNAL_MAKER_FEE_RATE - rebate_rate)
     }
 
     /// 计算taker手续费
     fn calculate_taker_fee(&self, quantity: Decimal, price: Decimal) -> Decimal {
         let notional = quantity * price;
-        notional * Decimal::new(2, 4) // 0.02% taker fee
+        notional * INTERNAL_TAKER_FEE_RATE
     }
 
     /// 更新统计信息
@@ -555,4 +804,198 @@ impl MatchingEngine {
 
         Ok(expired_orders)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engines::StateCodec;
+
+    fn order(order_type: OrderType, side: Side, quantity: i64, price: Option<i64>, stop: Option<i64>) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            order_type,
+            side,
+            Decimal::from(quantity),
+            price.map(Decimal::from),
+            stop.map(Decimal::from),
+        )
+        .unwrap()
+    }
+
+    fn limit(side: Side, quantity: i64, price: i64) -> Order {
+        order(OrderType::Limit, side, quantity, Some(price), None)
+    }
+
+    /// 在 price 成交一笔，确定最新价
+    async fn trade_at(engine: &MatchingEngine, price: i64) {
+        engine.process_order(limit(Side::Sell, 1, price)).await.unwrap();
+        engine.process_order(limit(Side::Buy, 1, price)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_and_restore_state() {
+        let engine = MatchingEngine::new(Symbol::new("BTC", "USDT"));
+        trade_at(&engine, 100).await;
+        let first = limit(Side::Buy, 1, 99);
+        let second = limit(Side::Buy, 2, 99);
+        engine.process_order(first.clone()).await.unwrap();
+        engine.process_order(second.clone()).await.unwrap();
+        engine.process_order(limit(Side::Sell, 3, 105)).await.unwrap();
+        engine
+            .process_order(order(OrderType::StopLoss, Side::Sell, 1, None, Some(90)))
+            .await
+            .unwrap();
+
+        let codec = StateCodec::default();
+        let bytes = codec.encode(&engine.export_state().await).unwrap();
+        let restored = MatchingEngine::new(Symbol::new("BTC", "USDT"));
+        restored.restore_state(codec.decode(&bytes).unwrap()).await.unwrap();
+
+        assert_eq!(restored.get_best_bid_ask().await, (Some(Decimal::from(99)), Some(Decimal::from(105))));
+        assert_eq!(restored.pending_trigger_orders().await, 1);
+        // 同价买单保持时间优先
+        let trades = restored.process_order(limit(Side::Sell, 1, 99)).await.unwrap();
+        assert_eq!(trades[0].maker_order_id, first.id);
+
+        let other = MatchingEngine::new(Symbol::new("ETH", "USDT"));
+        assert!(other.restore_state(engine.export_state().await).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stop_loss_triggers_on_last_price() {
+        let engine = MatchingEngine::new(Symbol::new("BTC", "USDT"));
+
+        // 没有最新价时条件单只挂起
+        let stop = order(OrderType::StopLoss, Side::Sell, 2, None, Some(95));
+        assert!(engine.process_order(stop.clone()).await.unwrap().is_empty());
+        assert_eq!(engine.pending_trigger_orders().await, 1);
+
+        trade_at(&engine, 100).await;
+        assert_eq!(engine.pending_trigger_orders().await, 1);
+        assert!(engine.take_triggered_executions().await.is_empty());
+
+        engine.process_order(limit(Side::Buy, 5, 94)).await.unwrap();
+        // 成交价恰好等于触发价时触发，转为市价单吃掉买盘
+        trade_at(&engine, 95).await;
+        let executions = engine.take_triggered_executions().await;
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].order.id, stop.id);
+        assert_eq!(executions[0].order.order_type, OrderType::Market);
+        assert_eq!(executions[0].trigger_price, Decimal::from(95));
+        assert_eq!(executions[0].trades.len(), 1);
+        assert_eq!(executions[0].trades[0].price, Decimal::from(94));
+        assert_eq!(executions[0].trades[0].quantity, Decimal::from(2));
+        assert_eq!(engine.pending_trigger_orders().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_crossed_trigger_order_executes_immediately() {
+        let engine = MatchingEngine::new(Symbol::new("BTC", "USDT"));
+        trade_at(&engine, 100).await;
+        engine.process_order(limit(Side::Sell, 3, 101)).await.unwrap();
+
+        // 止损买单触发价低于最新价，提交即成交
+        let stop = order(OrderType::StopLoss, Side::Buy, 1, None, Some(99));
+        let trades = engine.process_order(stop.clone()).await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].taker_order_id, stop.id);
+        assert!(!trades[0].is_maker(stop.id));
+        assert_eq!(engine.pending_trigger_orders().await, 0);
+
+        // 未越过触发价的止盈限价单挂起，外部行情价越过后转为限价单挂入订单簿
+        let take_profit = order(OrderType::TakeProfitLimit, Side::Buy, 1, Some(90), Some(95));
+        assert!(engine.process_order(take_profit.clone()).await.unwrap().is_empty());
+        assert!(engine.update_last_price(Decimal::from(96)).await.unwrap().is_empty());
+        let executions = engine.update_last_price(Decimal::from(95)).await.unwrap();
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].order.order_type, OrderType::Limit);
+        assert!(executions[0].trades.is_empty());
+        assert_eq!(engine.get_best_bid_ask().await.0, Some(Decimal::from(90)));
+    }
+
+    #[tokio::test]
+    async fn test_trigger_cascade_and_cancel() {
+        let engine = MatchingEngine::new(Symbol::new("BTC", "USDT"));
+        trade_at(&engine, 100).await;
+        engine.process_order(limit(Side::Buy, 1, 94)).await.unwrap();
+        engine.process_order(limit(Side::Buy, 1, 89)).await.unwrap();
+
+        // 第一张止损单在94成交后越过第二张的触发价
+        let first = order(OrderType::StopLoss, Side::Sell, 1, None, Some(95));
+        let second = order(OrderType::StopLoss, Side::Sell, 1, None, Some(94));
+        let cancelled = order(OrderType::StopLoss, Side::Sell, 1, None, Some(80));
+        for o in [&first, &second, &cancelled] {
+            engine.process_order(o.clone()).await.unwrap();
+        }
+        assert!(engine.cancel_order(cancelled.id, Side::Sell, None).await.unwrap());
+        assert!(!engine.cancel_order(cancelled.id, Side::Sell, None).await.unwrap());
+
+        let executions = engine.update_last_price(Decimal::from(95)).await.unwrap();
+        let ids: Vec<_> = executions.iter().map(|e| e.order.id).collect();
+        assert_eq!(ids, vec![first.id, second.id]);
+        assert_eq!(executions[1].trigger_price, Decimal::from(94));
+        assert_eq!(executions[1].trades[0].price, Decimal::from(89));
+        assert_eq!(engine.pending_trigger_orders().await, 0);
+
+        // 缺少触发价的条件单被拒绝
+        let mut invalid = order(OrderType::StopLoss, Side::Sell, 1, None, Some(90));
+        invalid.stop_price = None;
+        assert!(engine.process_order(invalid).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_iceberg_replenishes_visible_tranche() {
+        let engine = MatchingEngine::new(Symbol::new("BTC", "USDT"));
+        let iceberg = order(OrderType::Iceberg, Side::Sell, 5, Some(100), None)
+            .with_visible_quantity(Decimal::from(2));
+        engine.process_order(iceberg.clone()).await.unwrap();
+        engine.process_order(limit(Side::Sell, 1, 100)).await.unwrap();
+
+        // 订单簿只展示冰山单的当前批次
+        let book = engine.get_order_book(5).await;
+        assert_eq!(book.asks, vec![(Decimal::from(100), Decimal::from(3))]);
+
+        // 展示批次成交完后补充的部分排在同价位普通挂单之后
+        let trades = engine.process_order(limit(Side::Buy, 3, 100)).await.unwrap();
+        let fills: Vec<_> = trades.iter().map(|t| (t.maker_order_id, t.quantity)).collect();
+        assert_eq!(fills[0], (iceberg.id, Decimal::from(2)));
+        assert_ne!(fills[1].0, iceberg.id);
+        assert_eq!(engine.get_order_book(5).await.asks, vec![(Decimal::from(100), Decimal::from(2))]);
+
+        // 大单连续吃掉剩余批次
+        let trades = engine.process_order(limit(Side::Buy, 4, 100)).await.unwrap();
+        let filled: Decimal = trades.iter().map(|t| t.quantity).sum();
+        assert_eq!(filled, Decimal::from(3));
+        assert!(trades.iter().all(|t| t.maker_order_id == iceberg.id));
+        let (best_bid, best_ask) = engine.get_best_bid_ask().await;
+        assert_eq!((best_bid, best_ask), (Some(Decimal::from(100)), None));
+    }
+
+    #[tokio::test]
+    async fn test_price_collar_limits_market_sweep() {
+        // 100 基点保护幅度
+        let engine = MatchingEngine::new(Symbol::new("BTC", "USDT")).with_price_collar(Some(Decimal::from(100)));
+        let market = |side, quantity| order(OrderType::Market, side, quantity, None, None);
+
+        // 没有对手盘时拒绝
+        assert!(engine.process_order(market(Side::Buy, 1)).await.is_err());
+
+        engine.process_order(limit(Side::Sell, 1, 100)).await.unwrap();
+        engine.process_order(limit(Side::Sell, 1, 101)).await.unwrap();
+        engine.process_order(limit(Side::Sell, 5, 150)).await.unwrap();
+
+        // 保护价 101，只成交前两档，剩余数量撤销不挂单
+        let trades = engine.process_order(market(Side::Buy, 4)).await.unwrap();
+        let prices: Vec<_> = trades.iter().map(|t| t.price).collect();
+        assert_eq!(prices, vec![Decimal::from(100), Decimal::from(101)]);
+        assert_eq!(engine.get_best_bid_ask().await, (None, Some(Decimal::from(150))));
+
+        // 卖出方向以最优买价向下计算保护价
+        engine.process_order(limit(Side::Buy, 1, 140)).await.unwrap();
+        engine.process_order(limit(Side::Buy, 1, 138)).await.unwrap();
+        let trades = engine.process_order(market(Side::Sell, 2)).await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].price, Decimal::from(140));
+    }
+}
@@ -1,7 +1,23 @@
+pub mod algo_orders;
+pub mod book_feed;
 pub mod execution_engine;
+pub mod maker_rebates;
 pub mod matching_engine;
+pub mod order_events;
+pub mod order_replay;
 pub mod risk_engine;
+pub mod state_format;
+pub mod tax_lots;
+pub mod trigger_book;
+pub mod venue_latency;
+pub mod volatility_regime;
+pub mod volume_profile;
 
+pub use book_feed::InternalBookFeed;
 pub use execution_engine::ExecutionEngine;
+pub use maker_rebates::MakerRebateEngine;
 pub use matching_engine::MatchingEngine;
+pub use order_events::{OrderEvent, OrderEventBus};
 pub use risk_engine::RiskEngine;
+pub use state_format::{MatchingEngineState, StateCodec};
+pub use volatility_regime::VolatilityRegimeTracker;
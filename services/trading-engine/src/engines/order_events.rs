@@ -0,0 +1,194 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::algo_orders::AlgoOrderProgress;
+use crate::models::{Order, OrderType, Side};
+
+/// 订单事件通道容量，订阅方落后超过该值时丢弃最旧的事件
+const ORDER_EVENT_CHANNEL_CAPACITY: usize = 4096;
+
+/// 执行路径上的订单事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum OrderEvent {
+    OrderCreated {
+        user_id: Uuid,
+        order_id: Uuid,
+        symbol: String,
+        side: Side,
+        order_type: OrderType,
+        quantity: Decimal,
+        price: Option<Decimal>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    OrderFilled {
+        user_id: Uuid,
+        order_id: Uuid,
+        symbol: String,
+        side: Side,
+        trade_id: Uuid,
+        price: Decimal,
+        quantity: Decimal,
+        fee: Decimal,
+        is_maker: bool,
+        venue: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    OrderCancelled {
+        user_id: Uuid,
+        order_id: Uuid,
+        symbol: String,
+        /// 撤销时未成交的数量
+        remaining_quantity: Decimal,
+        reason: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// 算法单每个时间片执行后的进度，子订单另有各自的受理、成交和撤销事件
+    AlgoProgress {
+        #[serde(flatten)]
+        progress: AlgoOrderProgress,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl OrderEvent {
+    pub fn created(order: &Order) -> Self {
+        OrderEvent::OrderCreated {
+            user_id: order.user_id,
+            order_id: order.id,
+            symbol: order.symbol.to_string(),
+            side: order.side,
+            order_type: order.order_type,
+            quantity: order.quantity,
+            price: order.price,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    pub fn cancelled(order: &Order, remaining_quantity: Decimal, reason: &str) -> Self {
+        OrderEvent::OrderCancelled {
+            user_id: order.user_id,
+            order_id: order.id,
+            symbol: order.symbol.to_string(),
+            remaining_quantity,
+            reason: reason.to_string(),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    pub fn algo_progress(progress: &AlgoOrderProgress) -> Self {
+        OrderEvent::AlgoProgress {
+            progress: progress.clone(),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    /// 事件所属用户
+    pub fn user_id(&self) -> Uuid {
+        match self {
+            OrderEvent::OrderCreated { user_id, .. }
+            | OrderEvent::OrderFilled { user_id, .. }
+            | OrderEvent::OrderCancelled { user_id, .. } => *user_id,
+            OrderEvent::AlgoProgress { progress, .. } => progress.user_id,
+        }
+    }
+}
+
+/// 订单事件总线
+///
+/// 执行引擎和撮合引擎在订单受理、成交和撤销时发布事件，
+/// 订单WebSocket按连接用户过滤后推送。
+pub struct OrderEventBus {
+    sender: broadcast::Sender<OrderEvent>,
+}
+
+impl OrderEventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(ORDER_EVENT_CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: OrderEvent) {
+        // 没有订阅方时发送失败，忽略即可
+        let _ = self.sender.send(event);
+    }
+
+    /// 订阅指定用户的订单事件
+    pub fn subscribe(&self, user_id: Uuid) -> OrderEventSubscription {
+        OrderEventSubscription {
+            user_id,
+            receiver: self.sender.subscribe(),
+        }
+    }
+}
+
+impl Default for OrderEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 单个用户的订单事件订阅
+pub struct OrderEventSubscription {
+    user_id: Uuid,
+    receiver: broadcast::Receiver<OrderEvent>,
+}
+
+impl OrderEventSubscription {
+    /// 等待下一条属于该用户的事件，总线关闭时返回 None
+    ///
+    /// 落后时跳过丢失的事件并返回丢失数量，调用方可据此重新拉取订单快照。
+    pub async fn recv(&mut self) -> Option<Result<OrderEvent, u64>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if event.user_id() == self.user_id => return Some(Ok(event)),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => return Some(Err(skipped)),
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn order(user_id: Uuid) -> Order {
+        Order::new(
+            user_id,
+            Symbol::new("BTC", "USDT"),
+            OrderType::Limit,
+            Side::Buy,
+            Decimal::from(2),
+            Some(Decimal::from(100)),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_subscription_filters_by_user() {
+        let bus = OrderEventBus::new();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut subscription = bus.subscribe(alice);
+
+        bus.publish(OrderEvent::created(&order(bob)));
+        let alice_order = order(alice);
+        bus.publish(OrderEvent::created(&alice_order));
+        bus.publish(OrderEvent::cancelled(&alice_order, Decimal::from(2), "user"));
+
+        match subscription.recv().await.unwrap().unwrap() {
+            OrderEvent::OrderCreated { order_id, .. } => assert_eq!(order_id, alice_order.id),
+            other => panic!("unexpected event {:?}", other),
+        }
+        let cancelled = subscription.recv().await.unwrap().unwrap();
+        assert_eq!(cancelled.user_id(), alice);
+        let json = serde_json::to_value(&cancelled).unwrap();
+        assert_eq!(json["event"], "order_cancelled");
+        assert_eq!(json["reason"], "user");
+    }
+}
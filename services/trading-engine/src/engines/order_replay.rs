@@ -0,0 +1,324 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use uuid::Uuid;
+
+use super::MatchingEngine;
+use crate::models::{Fill, Order, OrderStatus, OrderType, Side, Symbol};
+
+/// 重放步骤的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayAction {
+    /// 窗口开始前已挂在订单簿上的订单，按窗口开始时的剩余数量放入
+    Seed,
+    Place,
+    Cancel,
+    /// 撮合引擎不支持的订单类型，不参与重放
+    Skip,
+}
+
+/// 日志中的一条订单事件
+#[derive(Debug, Clone)]
+struct JournalEvent {
+    at: DateTime<Utc>,
+    action: ReplayAction,
+    order: Order,
+}
+
+/// 重放撮合产生的一笔成交
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedFill {
+    pub maker_order_id: Uuid,
+    pub taker_order_id: Uuid,
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+/// 重放的一步
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    pub sequence: usize,
+    pub at: DateTime<Utc>,
+    pub action: ReplayAction,
+    pub order_id: Uuid,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub quantity: Decimal,
+    pub fills: Vec<ReplayedFill>,
+    /// 撤单时订单是否仍在订单簿上
+    pub cancelled: Option<bool>,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    pub error: Option<String>,
+}
+
+/// 窗口内记录的成交与重放结果不一致的订单
+#[derive(Debug, Clone, Serialize)]
+pub struct FillDiscrepancy {
+    pub order_id: Uuid,
+    pub recorded_quantity: Decimal,
+    pub replayed_quantity: Decimal,
+    pub recorded_avg_price: Option<Decimal>,
+    pub replayed_avg_price: Option<Decimal>,
+}
+
+/// 订单流重放报告
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderReplayReport {
+    pub symbol: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub steps: Vec<ReplayStep>,
+    pub discrepancies: Vec<FillDiscrepancy>,
+}
+
+#[derive(Default)]
+struct FillTotals {
+    quantity: Decimal,
+    notional: Decimal,
+}
+
+impl FillTotals {
+    fn add(&mut self, price: Decimal, quantity: Decimal) {
+        self.quantity += quantity;
+        self.notional += price * quantity;
+    }
+
+    fn avg_price(&self) -> Option<Decimal> {
+        (!self.quantity.is_zero()).then(|| (self.notional / self.quantity).round_dp(8))
+    }
+}
+
+/// 由订单和成交记录重建窗口内的订单事件序列
+///
+/// 窗口开始前创建且当时仍未成交完的订单作为种子挂单，数量扣除窗口开始前的成交；
+/// 窗口内创建的订单按创建时间下单，窗口内撤销或过期的订单按更新时间撤单。
+fn build_journal(orders: &[Order], fills: &[Fill], start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<JournalEvent> {
+    let mut filled_before: HashMap<Uuid, Decimal> = HashMap::new();
+    for fill in fills.iter().filter(|f| f.executed_at < start) {
+        *filled_before.entry(fill.order_id).or_default() += fill.quantity;
+    }
+
+    let mut events = Vec::new();
+    for order in orders {
+        let supported = matches!(order.order_type, OrderType::Limit | OrderType::Market);
+        let mut replayed = order.clone();
+        replayed.status = OrderStatus::Pending;
+
+        if order.created_at < start {
+            let remaining = order.quantity - filled_before.get(&order.id).copied().unwrap_or_default();
+            if !supported || order.order_type == OrderType::Market || remaining <= Decimal::ZERO {
+                continue;
+            }
+            replayed.quantity = remaining;
+            events.push(JournalEvent {
+                at: start,
+                action: ReplayAction::Seed,
+                order: replayed.clone(),
+            });
+        } else if order.created_at < end {
+            events.push(JournalEvent {
+                at: order.created_at,
+                action: if supported { ReplayAction::Place } else { ReplayAction::Skip },
+                order: replayed.clone(),
+            });
+        } else {
+            continue;
+        }
+
+        let cancelled = matches!(order.status, OrderStatus::Cancelled | OrderStatus::Expired);
+        if supported && cancelled && order.updated_at >= start && order.updated_at < end {
+            events.push(JournalEvent {
+                at: order.updated_at,
+                action: ReplayAction::Cancel,
+                order: replayed,
+            });
+        }
+    }
+
+    // 同一时刻先下单再撤单，种子挂单按原始创建顺序排队
+    events.sort_by_key(|e| (e.at, e.action == ReplayAction::Cancel, e.order.created_at));
+    events
+}
+
+/// 在独立的撮合引擎实例中重放订单流，并与记录的成交对比
+pub async fn replay_order_flow(
+    symbol: Symbol,
+    orders: &[Order],
+    fills: &[Fill],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> OrderReplayReport {
+    let engine = MatchingEngine::new(symbol.clone());
+    let mut replayed: BTreeMap<Uuid, FillTotals> = BTreeMap::new();
+    let mut steps = Vec::new();
+
+    for (sequence, event) in build_journal(orders, fills, start, end).into_iter().enumerate() {
+        let order = event.order;
+        let mut step = ReplayStep {
+            sequence,
+            at: event.at,
+            action: event.action,
+            order_id: order.id,
+            side: order.side,
+            order_type: order.order_type,
+            price: order.price,
+            quantity: order.quantity,
+            fills: Vec::new(),
+            cancelled: None,
+            best_bid: None,
+            best_ask: None,
+            error: None,
+        };
+
+        match event.action {
+            ReplayAction::Seed | ReplayAction::Place => match engine.process_order(order).await {
+                Ok(mut trades) => {
+                    // 本次成交触发的条件单也计入重放成交
+                    for execution in engine.take_triggered_executions().await {
+                        trades.extend(execution.trades);
+                    }
+                    for trade in trades {
+                        replayed.entry(trade.maker_order_id).or_default().add(trade.price, trade.quantity);
+                        replayed.entry(trade.taker_order_id).or_default().add(trade.price, trade.quantity);
+                        step.fills.push(ReplayedFill {
+                            maker_order_id: trade.maker_order_id,
+                            taker_order_id: trade.taker_order_id,
+                            price: trade.price,
+                            quantity: trade.quantity,
+                        });
+                    }
+                }
+                Err(e) => step.error = Some(e.to_string()),
+            },
+            ReplayAction::Cancel => match engine.cancel_order(order.id, order.side, order.price).await {
+                Ok(cancelled) => step.cancelled = Some(cancelled),
+                Err(e) => step.error = Some(e.to_string()),
+            },
+            ReplayAction::Skip => {}
+        }
+
+        let (best_bid, best_ask) = engine.get_best_bid_ask().await;
+        step.best_bid = best_bid;
+        step.best_ask = best_ask;
+        steps.push(step);
+    }
+
+    let mut recorded: BTreeMap<Uuid, FillTotals> = BTreeMap::new();
+    for fill in fills.iter().filter(|f| f.executed_at >= start && f.executed_at < end) {
+        recorded.entry(fill.order_id).or_default().add(fill.price, fill.quantity);
+    }
+
+    let order_ids: BTreeSet<Uuid> = recorded.keys().chain(replayed.keys()).copied().collect();
+    let discrepancies = order_ids
+        .into_iter()
+        .filter_map(|order_id| {
+            let recorded = recorded.get(&order_id);
+            let replayed = replayed.get(&order_id);
+            let recorded_quantity = recorded.map_or(Decimal::ZERO, |t| t.quantity);
+            let replayed_quantity = replayed.map_or(Decimal::ZERO, |t| t.quantity);
+            let recorded_avg_price = recorded.and_then(FillTotals::avg_price);
+            let replayed_avg_price = replayed.and_then(FillTotals::avg_price);
+            (recorded_quantity != replayed_quantity || recorded_avg_price != replayed_avg_price).then_some(
+                FillDiscrepancy {
+                    order_id,
+                    recorded_quantity,
+                    replayed_quantity,
+                    recorded_avg_price,
+                    replayed_avg_price,
+                },
+            )
+        })
+        .collect();
+
+    OrderReplayReport {
+        symbol: symbol.to_string(),
+        start,
+        end,
+        steps,
+        discrepancies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn limit(side: Side, price: i64, quantity: i64, created_at: DateTime<Utc>) -> Order {
+        let mut order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            OrderType::Limit,
+            side,
+            Decimal::from(quantity),
+            Some(Decimal::from(price)),
+            None,
+        )
+        .unwrap();
+        order.created_at = created_at;
+        order.updated_at = created_at;
+        order
+    }
+
+    fn fill(order: &Order, price: i64, quantity: i64, executed_at: DateTime<Utc>) -> Fill {
+        Fill {
+            id: Uuid::new_v4(),
+            user_id: order.user_id,
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+            quote_quantity: Decimal::from(price * quantity),
+            fee: Decimal::ZERO,
+            fee_currency: "USDT".to_string(),
+            is_maker: false,
+            venue: "INTERNAL".to_string(),
+            strategy_tag: None,
+            environment: order.metadata.environment,
+            executed_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_order_flow() {
+        let start = Utc::now() - Duration::hours(1);
+        let end = start + Duration::minutes(30);
+
+        // 窗口前挂出5个，已成交2个
+        let resting = limit(Side::Sell, 100, 5, start - Duration::minutes(10));
+        let mut cancelled = limit(Side::Sell, 101, 1, start + Duration::minutes(1));
+        cancelled.status = OrderStatus::Cancelled;
+        cancelled.updated_at = start + Duration::minutes(2);
+        let taker = limit(Side::Buy, 101, 4, start + Duration::minutes(3));
+
+        let fills = vec![
+            fill(&resting, 100, 2, start - Duration::minutes(5)),
+            fill(&resting, 100, 3, start + Duration::minutes(3)),
+            fill(&taker, 100, 3, start + Duration::minutes(3)),
+        ];
+        let orders = vec![resting.clone(), cancelled.clone(), taker.clone()];
+
+        let report = replay_order_flow(Symbol::new("BTC", "USDT"), &orders, &fills, start, end).await;
+        let actions: Vec<_> = report.steps.iter().map(|s| s.action).collect();
+        assert_eq!(
+            actions,
+            vec![ReplayAction::Seed, ReplayAction::Place, ReplayAction::Cancel, ReplayAction::Place]
+        );
+        assert_eq!(report.steps[0].quantity, Decimal::from(3));
+        assert_eq!(report.steps[2].cancelled, Some(true));
+        assert_eq!(report.steps[3].fills.len(), 1);
+        assert_eq!(report.steps[3].best_bid, Some(Decimal::from(101)));
+        assert!(report.discrepancies.is_empty());
+
+        // 记录中缺少的成交会被报告
+        let report = replay_order_flow(Symbol::new("BTC", "USDT"), &orders, &fills[..2], start, end).await;
+        assert_eq!(report.discrepancies.len(), 1);
+        assert_eq!(report.discrepancies[0].order_id, taker.id);
+        assert_eq!(report.discrepancies[0].replayed_quantity, Decimal::from(3));
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
+use shared_utils::{AppMetrics, Cache};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -7,7 +8,12 @@ use uuid::Uuid;
 
 use crate::{
     config::TradingEngineConfig,
-    models::{Order, Position, Symbol, TradingError, TradingResult},
+    models::{
+        Order, Position, RiskEventRecord, RiskEventStatus, RiskSeverity, Symbol, TradingError,
+        TradingResult,
+    },
+    services::{MarginHeadroomService, RiskEventService, VerificationService},
+    storage::RiskConfigStore,
 };
 
 /// 专业级风险管理引擎
@@ -15,14 +21,20 @@ use crate::{
 #[derive(Clone)]
 pub struct RiskEngine {
     config: TradingEngineConfig,
-    /// 用户风险配置
-    user_risk_configs: Arc<RwLock<HashMap<Uuid, UserRiskConfig>>>,
+    /// 用户风险配置存储
+    risk_configs: Arc<RiskConfigStore>,
+    /// 用户风险配置读取缓存，未配置的用户也会缓存
+    user_risk_configs: Arc<Cache<Uuid, Option<UserRiskConfig>>>,
     /// 系统风险限制
     system_limits: Arc<RwLock<SystemRiskLimits>>,
     /// 实时风险监控
     risk_monitor: Arc<RwLock<RiskMonitor>>,
-    /// 风险事件历史
-    risk_events: Arc<RwLock<Vec<RiskEvent>>>,
+    /// 风险事件队列
+    risk_events: Arc<RiskEventService>,
+    /// 账户实时保证金余量和持仓价值
+    margin_headroom: Arc<MarginHeadroomService>,
+    /// 按认证等级限制实盘交易
+    verification: Arc<VerificationService>,
 }
 
 #[derive(Debug, Clone)]
@@ -71,7 +83,31 @@ pub struct RiskEvent {
     pub message: String,
     pub data: serde_json::Value,
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub resolved: bool,
+}
+
+impl RiskEvent {
+    /// 转为待确认的队列事件
+    pub fn to_record(&self) -> RiskEventRecord {
+        RiskEventRecord {
+            id: self.event_id,
+            event_type: self.event_type.to_string(),
+            user_id: self.user_id,
+            symbol: self.symbol.as_ref().map(|symbol| symbol.to_string()),
+            severity: self.severity,
+            message: self.message.clone(),
+            data: self.data.clone(),
+            status: RiskEventStatus::Open,
+            created_at: self.timestamp,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            resolved_at: None,
+            resolved_by: None,
+            resolution_note: None,
+            escalation_level: 0,
+            escalated_at: None,
+            updated_at: self.timestamp,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -86,6 +122,8 @@ pub enum RiskEventType {
     VolatilitySpike,
     ConcentrationRisk,
     SuspiciousActivity,
+    /// 下单被风控拒绝
+    OrderRejected,
 }
 
 impl std::fmt::Display for RiskEventType {
@@ -101,18 +139,11 @@ impl std::fmt::Display for RiskEventType {
             RiskEventType::VolatilitySpike => write!(f, "VOLATILITY_SPIKE"),
             RiskEventType::ConcentrationRisk => write!(f, "CONCENTRATION_RISK"),
             RiskEventType::SuspiciousActivity => write!(f, "SUSPICIOUS_ACTIVITY"),
+            RiskEventType::OrderRejected => write!(f, "ORDER_REJECTED"),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Ord, PartialOrd, Eq)]
-pub enum RiskSeverity {
-    Low,
-    Medium,
-    High,
-    Critical,
-}
-
 #[derive(Debug, Clone)]
 pub struct RiskAssessment {
     pub overall_risk: RiskLevel,
@@ -140,7 +171,14 @@ pub struct RiskFactor {
 }
 
 impl RiskEngine {
-    pub fn new(config: TradingEngineConfig) -> Self {
+    pub fn new(
+        config: TradingEngineConfig,
+        risk_configs: Arc<RiskConfigStore>,
+        margin_headroom: Arc<MarginHeadroomService>,
+        risk_events: Arc<RiskEventService>,
+        verification: Arc<VerificationService>,
+        metrics: Arc<AppMetrics>,
+    ) -> Self {
         let system_limits = SystemRiskLimits {
             max_total_exposure: Decimal::from(10_000_000), // 1000万
             max_symbol_concentration: Decimal::new(20, 2), // 20%
@@ -160,36 +198,48 @@ impl RiskEngine {
             last_update: chrono::Utc::now(),
         };
 
+        let user_risk_configs = Arc::new(
+            Cache::new("user_risk_configs", config.risk.user_config_cache.clone()).with_metrics(metrics),
+        );
+
         Self {
             config,
-            user_risk_configs: Arc::new(RwLock::new(HashMap::new())),
+            risk_configs,
+            user_risk_configs,
             system_limits: Arc::new(RwLock::new(system_limits)),
             risk_monitor: Arc::new(RwLock::new(risk_monitor)),
-            risk_events: Arc::new(RwLock::new(Vec::new())),
+            risk_events,
+            margin_headroom,
+            verification,
         }
     }
 
     /// 设置用户风险配置
-    pub async fn set_user_risk_config(&self, config: UserRiskConfig) {
-        let mut configs = self.user_risk_configs.write().await;
-        configs.insert(config.user_id, config);
+    pub async fn set_user_risk_config(&self, config: UserRiskConfig) -> TradingResult<()> {
+        self.risk_configs.upsert(&config).await?;
+        self.user_risk_configs.invalidate(&config.user_id);
+        Ok(())
     }
 
-    /// 获取用户风险配置
-    pub async fn get_user_risk_config(&self, user_id: Uuid) -> Option<UserRiskConfig> {
-        let configs = self.user_risk_configs.read().await;
-        configs.get(&user_id).cloned()
+    /// 获取用户风险配置，优先读取缓存
+    pub async fn get_user_risk_config(&self, user_id: Uuid) -> TradingResult<Option<UserRiskConfig>> {
+        self.user_risk_configs
+            .get_or_try_load(user_id, || self.risk_configs.get(user_id))
+            .await
     }
 
     /// 订单前风险检查
     pub async fn validate_order(&self, order: &Order) -> TradingResult<RiskAssessment> {
-        let user_config = self.get_user_risk_config(order.user_id).await
+        let user_config = self.get_user_risk_config(order.user_id).await?
             .ok_or_else(|| TradingError::RiskViolation("User risk config not found".to_string()))?;
 
         if !user_config.is_active {
             return Err(TradingError::RiskViolation("User trading is suspended".to_string()));
         }
 
+        // 认证等级限制，未认证用户不能实盘下单
+        self.check_verification(order).await?;
+
         let mut risk_factors = Vec::new();
         let mut recommendations = Vec::new();
 
@@ -206,13 +256,12 @@ impl RiskEngine {
         let required_margin = self.calculate_margin_requirement(order).await?;
         self.check_margin_sufficiency(order.user_id, required_margin, &mut risk_factors).await?;
 
-        // 5. 检查交易频率
-        self.check_trading_frequency(order.user_id, &user_config, &mut risk_factors).await?;
+        // 下单频率由 OrderRateService::admit 在提交订单时计数并限制
 
-        // 6. 检查系统级风险
+        // 5. 检查系统级风险
         self.check_system_risk(order, &mut risk_factors).await?;
 
-        // 7. 检查市场风险
+        // 6. 检查市场风险
         self.check_market_risk(order, &mut risk_factors).await?;
 
         // 评估整体风险等级
@@ -233,6 +282,22 @@ impl RiskEngine {
         })
     }
 
+    /// 检查认证等级限额，持仓价值不计入该订单原有的挂单
+    async fn check_verification(&self, order: &Order) -> TradingResult<()> {
+        if !self.verification.is_enabled() {
+            return Ok(());
+        }
+        let order_value = order.calculate_value().unwrap_or(Decimal::ZERO);
+        let headroom = self.margin_headroom.headroom(order.user_id).await?;
+        let existing = headroom
+            .reservations
+            .get(&order.id)
+            .map(|r| r.value)
+            .unwrap_or(Decimal::ZERO);
+        let exposure = headroom.exposure(&order.symbol.to_string()) - existing;
+        self.verification.check_order(order, order_value, exposure).await
+    }
+
     /// 检查交易对限制
     fn check_symbol_restrictions(
         &self,
@@ -307,8 +372,12 @@ impl RiskEngine {
         config: &UserRiskConfig,
         risk_factors: &mut Vec<RiskFactor>,
     ) -> TradingResult<()> {
-        // TODO: 从仓位服务获取当前仓位
-        let current_position_value = Decimal::ZERO; // 临时值
+        // 当前持仓和未成交订单的名义价值
+        let current_position_value = self
+            .margin_headroom
+            .headroom(order.user_id)
+            .await?
+            .exposure(&order.symbol.to_string());
 
         let order_value = order.calculate_value().unwrap_or(Decimal::ZERO);
         let total_position_value = current_position_value + order_value;
@@ -346,8 +415,8 @@ impl RiskEngine {
         required_margin: Decimal,
         risk_factors: &mut Vec<RiskFactor>,
     ) -> TradingResult<()> {
-        // TODO: 从账户服务获取可用保证金
-        let available_margin = Decimal::from(100000); // 临时值
+        // 扣除未成交订单占用后的可用保证金
+        let available_margin = self.margin_headroom.headroom(user_id).await?.free_margin();
 
         if available_margin < required_margin {
             risk_factors.push(RiskFactor {
@@ -367,34 +436,6 @@ impl RiskEngine {
         Ok(())
     }
 
-    /// 检查交易频率
-    async fn check_trading_frequency(
-        &self,
-        user_id: Uuid,
-        config: &UserRiskConfig,
-        risk_factors: &mut Vec<RiskFactor>,
-    ) -> TradingResult<()> {
-        // TODO: 实现交易频率检查
-        // 统计最近1分钟的订单数量
-        let recent_orders = 0; // 临时值
-
-        if recent_orders >= config.max_orders_per_minute {
-            risk_factors.push(RiskFactor {
-                factor_type: "ORDER_RATE_LIMIT".to_string(),
-                severity: RiskSeverity::High,
-                value: Decimal::from(recent_orders),
-                threshold: Decimal::from(config.max_orders_per_minute),
-                description: "Order rate limit exceeded".to_string(),
-            });
-
-            return Err(TradingError::RiskViolation(
-                "Order rate limit exceeded".to_string(),
-            ));
-        }
-
-        Ok(())
-    }
-
     /// 检查系统级风险
     async fn check_system_risk(
         &self,
@@ -513,11 +554,17 @@ impl RiskEngine {
             max_size = max_size.min(max_size_by_value);
         }
 
-        // 基于仓位限制
-        // TODO: 考虑当前仓位
+        if let Some(price) = order.price.filter(|price| *price > Decimal::ZERO) {
+            let headroom = self.margin_headroom.headroom(order.user_id).await?;
 
-        // 基于保证金限制
-        // TODO: 考虑可用保证金
+            // 基于仓位限制
+            let position_room = config.max_position_value - headroom.exposure(&order.symbol.to_string());
+            max_size = max_size.min((position_room / price).max(Decimal::ZERO));
+
+            // 基于保证金限制，与保证金计算使用相同的默认杠杆
+            let margin_room = headroom.free_margin() * Decimal::from(10);
+            max_size = max_size.min((margin_room / price).max(Decimal::ZERO));
+        }
 
         Ok(max_size)
     }
@@ -567,7 +614,6 @@ impl RiskEngine {
                     "limit": limits.max_total_exposure
                 }),
                 timestamp: chrono::Utc::now(),
-                resolved: false,
             }).await;
         }
 
@@ -588,7 +634,6 @@ impl RiskEngine {
                         "limit": limits.max_symbol_concentration
                     }),
                     timestamp: chrono::Utc::now(),
-                    resolved: false,
                 }).await;
             }
         }
@@ -596,39 +641,25 @@ impl RiskEngine {
         Ok(())
     }
 
-    /// 触发风险事件
+    /// 触发风险事件，写入事件队列等待值班人员确认
     async fn trigger_risk_event(&self, event: RiskEvent) {
         tracing::warn!("Risk event triggered: {:?}", event);
-        
-        let mut events = self.risk_events.write().await;
-        events.push(event.clone());
 
-        // 保留最近1000个事件
-        if events.len() > 1000 {
-            let len = events.len();
-            events.drain(0..len - 1000);
+        if let Err(e) = self.risk_events.record(&event.to_record()).await {
+            // 入队失败时至少保留告警日志
+            tracing::error!(
+                "Failed to record risk event {} ({} - {}): {}",
+                event.event_id,
+                event.event_type,
+                event.message,
+                e
+            );
         }
-
-        // TODO: 发送告警通知
-        self.send_risk_alert(&event).await;
     }
 
-    /// 发送风险告警
-    async fn send_risk_alert(&self, event: &RiskEvent) {
-        // TODO: 实现告警通知（邮件、短信、Webhook等）
-        tracing::error!("RISK ALERT: {} - {}", event.event_type, event.message);
-    }
-
-    /// 获取风险事件历史
-    pub async fn get_risk_events(&self, limit: Option<usize>) -> Vec<RiskEvent> {
-        let events = self.risk_events.read().await;
-        let limit = limit.unwrap_or(100);
-        
-        events.iter()
-            .rev()
-            .take(limit)
-            .cloned()
-            .collect()
+    /// 获取风险事件，按严重程度和触发时间排队
+    pub async fn get_risk_events(&self, limit: Option<u32>) -> TradingResult<Vec<RiskEventRecord>> {
+        self.risk_events.list(None, None, limit.unwrap_or(100)).await
     }
 
     /// 获取风险监控状态
@@ -641,7 +672,14 @@ impl RiskEngine {
         let mut liquidation_list = Vec::new();
 
         for position in positions {
-            if let Some(config) = self.get_user_risk_config(position.user_id).await {
+            let config = match self.get_user_risk_config(position.user_id).await {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::warn!("Failed to load risk config for user {}: {}", position.user_id, e);
+                    continue;
+                }
+            };
+            if let Some(config) = config {
                 // 检查是否达到强平阈值
                 if position.margin_ratio <= config.liquidation_threshold {
                     liquidation_list.push(position.id);
@@ -660,7 +698,6 @@ impl RiskEngine {
                             "threshold": config.liquidation_threshold
                         }),
                         timestamp: chrono::Utc::now(),
-                        resolved: false,
                     };
                     
                     self.trigger_risk_event(event).await;
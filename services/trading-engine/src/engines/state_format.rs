@@ -0,0 +1,244 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::{Order, Symbol, TradingError, TradingResult};
+
+/// 撮合引擎状态的格式标识
+pub const MATCHING_STATE_FORMAT: &str = "matching_engine_state";
+
+/// 当前写入的状态格式版本
+///
+/// 修改 [`MatchingEngineState`] 的结构时递增版本号：只新增可缺省字段时旧版本仍能读取，
+/// `MIN_READER_VERSION` 保持不变；删除、改名或改变字段含义时同时把 `MIN_READER_VERSION`
+/// 提高到新版本，并在 `MIGRATIONS` 中登记从上一版本升级的迁移函数。
+pub const STATE_FORMAT_VERSION: u32 = 1;
+
+/// 能读取当前版本所写状态的最低版本
+pub const MIN_READER_VERSION: u32 = 1;
+
+/// 仍能迁移读取的最早版本
+pub const OLDEST_READABLE_VERSION: u32 = 1;
+
+/// 状态格式迁移：把 `from` 版本的内容升级为 `from + 1` 版本
+#[derive(Clone, Copy)]
+pub struct StateMigration {
+    pub from: u32,
+    pub migrate: fn(Value) -> TradingResult<Value>,
+}
+
+/// 历次格式变更的迁移函数，按 `from` 升序排列
+const MIGRATIONS: &[StateMigration] = &[];
+
+/// 撮合引擎状态
+///
+/// 订单簿和条件单按队列顺序保存，恢复时依次挂回即可还原时间优先级。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchingEngineState {
+    pub symbol: Symbol,
+    /// 买单，价格从高到低、同价按提交顺序
+    pub bids: Vec<Order>,
+    /// 卖单，价格从低到高、同价按提交顺序
+    pub asks: Vec<Order>,
+    /// 未触发的条件单
+    pub trigger_orders: Vec<Order>,
+    pub last_price: Option<Decimal>,
+    /// 挂单返佣率 (买方, 卖方)
+    pub maker_rebate_rates: (Decimal, Decimal),
+}
+
+/// 带版本信息的状态封装
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateEnvelope {
+    pub format: String,
+    pub version: u32,
+    /// 能读取这份状态的最低版本，滚动升级时旧实例据此判断能否加载
+    pub min_reader_version: u32,
+    /// 写入状态的服务版本
+    pub writer: String,
+    pub written_at: DateTime<Utc>,
+    pub payload: Value,
+}
+
+/// 状态格式编解码
+///
+/// 读取时检查版本兼容性：旧版本状态依次执行迁移；新版本状态只有在声明的
+/// `min_reader_version` 不高于本实例版本时才读取（忽略不认识的字段），否则拒绝加载，
+/// 避免回滚后的旧实例错误解释新格式的订单簿。
+#[derive(Clone, Copy)]
+pub struct StateCodec {
+    version: u32,
+    min_reader_version: u32,
+    oldest_readable_version: u32,
+    migrations: &'static [StateMigration],
+}
+
+impl Default for StateCodec {
+    fn default() -> Self {
+        Self {
+            version: STATE_FORMAT_VERSION,
+            min_reader_version: MIN_READER_VERSION,
+            oldest_readable_version: OLDEST_READABLE_VERSION,
+            migrations: MIGRATIONS,
+        }
+    }
+}
+
+impl StateCodec {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// 序列化为带版本信息的状态
+    pub fn encode(&self, state: &MatchingEngineState) -> TradingResult<Vec<u8>> {
+        let payload = serde_json::to_value(state).map_err(|e| TradingError::SerializationError(e.to_string()))?;
+        let envelope = StateEnvelope {
+            format: MATCHING_STATE_FORMAT.to_string(),
+            version: self.version,
+            min_reader_version: self.min_reader_version,
+            writer: env!("CARGO_PKG_VERSION").to_string(),
+            written_at: Utc::now(),
+            payload,
+        };
+        serde_json::to_vec(&envelope).map_err(|e| TradingError::SerializationError(e.to_string()))
+    }
+
+    /// 读取状态，必要时迁移到当前版本
+    pub fn decode(&self, bytes: &[u8]) -> TradingResult<MatchingEngineState> {
+        let envelope: StateEnvelope =
+            serde_json::from_slice(bytes).map_err(|e| TradingError::SerializationError(e.to_string()))?;
+        let payload = self.upgrade(envelope)?;
+        serde_json::from_value(payload).map_err(|e| TradingError::SerializationError(e.to_string()))
+    }
+
+    /// 检查版本兼容性并把内容迁移到当前版本
+    pub fn upgrade(&self, envelope: StateEnvelope) -> TradingResult<Value> {
+        if envelope.format != MATCHING_STATE_FORMAT {
+            return Err(TradingError::IncompatibleStateFormat(format!(
+                "expected format {}, found {}",
+                MATCHING_STATE_FORMAT, envelope.format
+            )));
+        }
+
+        if envelope.version > self.version {
+            if envelope.min_reader_version > self.version {
+                return Err(TradingError::IncompatibleStateFormat(format!(
+                    "state v{} written by trading-engine {} requires reader v{} or newer, this instance reads up to v{}; \
+                     refusing to load state after a downgrade",
+                    envelope.version, envelope.writer, envelope.min_reader_version, self.version
+                )));
+            }
+            tracing::info!(
+                "Loading forward-compatible state v{} written by trading-engine {} with reader v{}",
+                envelope.version,
+                envelope.writer,
+                self.version
+            );
+            return Ok(envelope.payload);
+        }
+
+        if envelope.version < self.oldest_readable_version {
+            return Err(TradingError::IncompatibleStateFormat(format!(
+                "state v{} written by trading-engine {} is older than the oldest readable v{}",
+                envelope.version, envelope.writer, self.oldest_readable_version
+            )));
+        }
+
+        let mut payload = envelope.payload;
+        for version in envelope.version..self.version {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|migration| migration.from == version)
+                .ok_or_else(|| {
+                    TradingError::IncompatibleStateFormat(format!("no migration from state v{} to v{}", version, version + 1))
+                })?;
+            payload = (migration.migrate)(payload)?;
+            tracing::info!("Migrated matching engine state from v{} to v{}", version, version + 1);
+        }
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderType, Side};
+    use uuid::Uuid;
+
+    fn state() -> MatchingEngineState {
+        let bid = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            OrderType::Limit,
+            Side::Buy,
+            Decimal::ONE,
+            Some(Decimal::from(100)),
+            None,
+        )
+        .unwrap();
+        MatchingEngineState {
+            symbol: Symbol::new("BTC", "USDT"),
+            bids: vec![bid],
+            asks: Vec::new(),
+            trigger_orders: Vec::new(),
+            last_price: Some(Decimal::from(101)),
+            maker_rebate_rates: (Decimal::ZERO, Decimal::ZERO),
+        }
+    }
+
+    fn rename_last_price(mut payload: Value) -> TradingResult<Value> {
+        let last = payload
+            .as_object_mut()
+            .and_then(|object| object.remove("last"))
+            .unwrap_or(Value::Null);
+        payload["last_price"] = last;
+        Ok(payload)
+    }
+
+    #[test]
+    fn test_state_round_trip_and_migration() {
+        let codec = StateCodec::default();
+        let decoded = codec.decode(&codec.encode(&state()).unwrap()).unwrap();
+        assert_eq!(decoded.bids.len(), 1);
+        assert_eq!(decoded.last_price, Some(Decimal::from(101)));
+
+        // v0 把最新价保存在 last 字段
+        const RENAME: &[StateMigration] = &[StateMigration { from: 0, migrate: rename_last_price }];
+        let v1 = StateCodec { oldest_readable_version: 0, migrations: RENAME, ..codec };
+        let mut envelope: StateEnvelope = serde_json::from_slice(&codec.encode(&state()).unwrap()).unwrap();
+        let last = envelope.payload.as_object_mut().unwrap().remove("last_price").unwrap();
+        envelope.payload["last"] = last;
+        envelope.version = 0;
+        let migrated = v1.decode(&serde_json::to_vec(&envelope).unwrap()).unwrap();
+        assert_eq!(migrated.last_price, Some(Decimal::from(101)));
+
+        // 没有登记迁移时拒绝加载
+        assert!(matches!(
+            codec.decode(&serde_json::to_vec(&envelope).unwrap()),
+            Err(TradingError::IncompatibleStateFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_newer_state_requires_compatible_reader() {
+        let codec = StateCodec::default();
+
+        // 新版本只新增字段，旧实例忽略后照常读取
+        let additive = StateCodec { version: codec.version + 1, ..codec };
+        let mut envelope: StateEnvelope = serde_json::from_slice(&additive.encode(&state()).unwrap()).unwrap();
+        envelope.payload["auction_phase"] = Value::from("continuous");
+        assert_eq!(codec.decode(&serde_json::to_vec(&envelope).unwrap()).unwrap().bids.len(), 1);
+
+        // 不兼容的新格式在降级时拒绝加载
+        let breaking = StateCodec {
+            version: codec.version + 1,
+            min_reader_version: codec.version + 1,
+            ..codec
+        };
+        let bytes = breaking.encode(&state()).unwrap();
+        assert!(matches!(codec.decode(&bytes), Err(TradingError::IncompatibleStateFormat(_))));
+        assert!(breaking.decode(&bytes).is_ok());
+    }
+}
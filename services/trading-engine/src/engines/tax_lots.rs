@@ -0,0 +1,386 @@
+use chrono::Datelike;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::models::{Fill, Id, Side, Timestamp, TradingError};
+
+/// 成本计算方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostBasisMethod {
+    /// 先进先出
+    Fifo,
+    /// 后进先出
+    Lifo,
+    /// 最高成本先出
+    Hifo,
+    /// 移动平均成本
+    AverageCost,
+}
+
+impl std::str::FromStr for CostBasisMethod {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Ok(CostBasisMethod::Fifo),
+            "lifo" => Ok(CostBasisMethod::Lifo),
+            "hifo" => Ok(CostBasisMethod::Hifo),
+            "average" | "average_cost" | "avg" => Ok(CostBasisMethod::AverageCost),
+            _ => Err(TradingError::ConfigError(format!("Invalid cost basis method: {}", s))),
+        }
+    }
+}
+
+/// 持仓批次
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxLot {
+    pub id: Id,
+    pub asset: String,
+    pub fill_id: Id,
+    pub quantity: Decimal,
+    pub remaining: Decimal,
+    /// 含买入手续费的单位成本
+    pub cost_per_unit: Decimal,
+    pub acquired_at: Timestamp,
+}
+
+/// 持有期类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldingTerm {
+    ShortTerm,
+    LongTerm,
+}
+
+/// 单个批次的已实现盈亏
+#[derive(Debug, Clone, Serialize)]
+pub struct RealizedGain {
+    pub lot_id: Id,
+    pub asset: String,
+    pub sell_fill_id: Id,
+    pub quantity: Decimal,
+    /// 扣除卖出手续费后的收入
+    pub proceeds: Decimal,
+    pub cost_basis: Decimal,
+    pub gain: Decimal,
+    pub acquired_at: Timestamp,
+    pub disposed_at: Timestamp,
+    pub term: HoldingTerm,
+}
+
+/// 批次账本，按资产维护未平批次
+#[derive(Debug)]
+pub struct TaxLotBook {
+    method: CostBasisMethod,
+    long_term_days: i64,
+    lots: HashMap<String, Vec<TaxLot>>,
+    /// 卖出超过持仓的数量，没有成本可匹配
+    uncovered: HashMap<String, Decimal>,
+}
+
+impl TaxLotBook {
+    pub fn new(method: CostBasisMethod, long_term_days: i64) -> Self {
+        Self {
+            method,
+            long_term_days,
+            lots: HashMap::new(),
+            uncovered: HashMap::new(),
+        }
+    }
+
+    /// 处理一笔成交：买入生成批次，卖出按方法消耗批次
+    pub fn apply_fill(&mut self, fill: &Fill) -> Vec<RealizedGain> {
+        let asset = fill.symbol.base.clone();
+        let fee_in_quote = fill.fee_currency.eq_ignore_ascii_case(&fill.symbol.quote);
+        let fee_in_base = fill.fee_currency.eq_ignore_ascii_case(&fill.symbol.base);
+
+        match fill.side {
+            Side::Buy => {
+                // 以基础币扣费时实际到账数量减少
+                let quantity = if fee_in_base { fill.quantity - fill.fee } else { fill.quantity };
+                if quantity <= Decimal::ZERO {
+                    return Vec::new();
+                }
+                let cost = fill.quote_quantity + if fee_in_quote { fill.fee } else { Decimal::ZERO };
+                self.lots.entry(asset.clone()).or_default().push(TaxLot {
+                    id: Uuid::new_v4(),
+                    asset,
+                    fill_id: fill.id,
+                    quantity,
+                    remaining: quantity,
+                    cost_per_unit: cost / quantity,
+                    acquired_at: fill.executed_at,
+                });
+                Vec::new()
+            }
+            Side::Sell => {
+                let fee = if fee_in_quote { fill.fee } else { Decimal::ZERO };
+                let proceeds_per_unit = if fill.quantity.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    (fill.quote_quantity - fee) / fill.quantity
+                };
+                self.consume(&asset, fill, proceeds_per_unit)
+            }
+        }
+    }
+
+    fn consume(&mut self, asset: &str, fill: &Fill, proceeds_per_unit: Decimal) -> Vec<RealizedGain> {
+        let lots = self.lots.entry(asset.to_string()).or_default();
+        let average_cost = {
+            let (quantity, cost) = lots.iter().fold((Decimal::ZERO, Decimal::ZERO), |(q, c), lot| {
+                (q + lot.remaining, c + lot.remaining * lot.cost_per_unit)
+            });
+            if quantity.is_zero() { Decimal::ZERO } else { cost / quantity }
+        };
+
+        // 按方法确定批次消耗顺序，平均成本按时间顺序消耗以保留持有期
+        let mut order: Vec<usize> = (0..lots.len()).collect();
+        match self.method {
+            CostBasisMethod::Fifo | CostBasisMethod::AverageCost => {}
+            CostBasisMethod::Lifo => order.reverse(),
+            CostBasisMethod::Hifo => {
+                order.sort_by(|a, b| lots[*b].cost_per_unit.cmp(&lots[*a].cost_per_unit))
+            }
+        }
+
+        let mut gains = Vec::new();
+        let mut remaining = fill.quantity;
+        for index in order {
+            if remaining.is_zero() {
+                break;
+            }
+            let lot = &mut lots[index];
+            let quantity = remaining.min(lot.remaining);
+            lot.remaining -= quantity;
+            remaining -= quantity;
+
+            let unit_cost = match self.method {
+                CostBasisMethod::AverageCost => average_cost,
+                _ => lot.cost_per_unit,
+            };
+            let proceeds = (quantity * proceeds_per_unit).round_dp(8);
+            let cost_basis = (quantity * unit_cost).round_dp(8);
+            let held_days = (fill.executed_at - lot.acquired_at).num_days();
+            gains.push(RealizedGain {
+                lot_id: lot.id,
+                asset: asset.to_string(),
+                sell_fill_id: fill.id,
+                quantity,
+                proceeds,
+                cost_basis,
+                gain: proceeds - cost_basis,
+                acquired_at: lot.acquired_at,
+                disposed_at: fill.executed_at,
+                term: if held_days > self.long_term_days {
+                    HoldingTerm::LongTerm
+                } else {
+                    HoldingTerm::ShortTerm
+                },
+            });
+        }
+        lots.retain(|lot| !lot.remaining.is_zero());
+        // 平均成本法下剩余批次统一按本次处置使用的均价计价，后续买入再与其加权
+        if self.method == CostBasisMethod::AverageCost {
+            for lot in lots.iter_mut() {
+                lot.cost_per_unit = average_cost;
+            }
+        }
+
+        if remaining > Decimal::ZERO {
+            tracing::warn!(
+                "Sell fill {} exceeds open {} lots by {}",
+                fill.id,
+                asset,
+                remaining
+            );
+            *self.uncovered.entry(asset.to_string()).or_default() += remaining;
+        }
+        gains
+    }
+
+    /// 未平批次
+    pub fn open_lots(&self) -> Vec<&TaxLot> {
+        let mut lots: Vec<&TaxLot> = self.lots.values().flatten().collect();
+        lots.sort_by(|a, b| a.asset.cmp(&b.asset).then(a.acquired_at.cmp(&b.acquired_at)));
+        lots
+    }
+
+    pub fn uncovered(&self) -> &HashMap<String, Decimal> {
+        &self.uncovered
+    }
+}
+
+/// 单个资产的年度盈亏汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AssetGainSummary {
+    pub asset: String,
+    pub disposals: usize,
+    pub quantity: Decimal,
+    pub proceeds: Decimal,
+    pub cost_basis: Decimal,
+    pub short_term_gain: Decimal,
+    pub long_term_gain: Decimal,
+    pub total_gain: Decimal,
+}
+
+/// 年度税务报告
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxReport {
+    pub user_id: Id,
+    pub year: i32,
+    pub method: CostBasisMethod,
+    pub assets: Vec<AssetGainSummary>,
+    pub totals: AssetGainSummary,
+    /// 无法匹配成本的卖出数量
+    pub uncovered: BTreeMap<String, Decimal>,
+    pub disposals: Vec<RealizedGain>,
+}
+
+/// 按时间顺序回放成交并生成指定年度的报告
+pub fn build_report(
+    user_id: Id,
+    year: i32,
+    method: CostBasisMethod,
+    long_term_days: i64,
+    fills: &[Fill],
+) -> TaxReport {
+    let mut book = TaxLotBook::new(method, long_term_days);
+    let mut disposals = Vec::new();
+    for fill in fills {
+        let gains = book.apply_fill(fill);
+        if fill.executed_at.year() == year {
+            disposals.extend(gains);
+        }
+    }
+
+    let mut assets: BTreeMap<String, AssetGainSummary> = BTreeMap::new();
+    let mut totals = AssetGainSummary {
+        asset: "TOTAL".to_string(),
+        ..AssetGainSummary::default()
+    };
+    for gain in &disposals {
+        let summary = assets.entry(gain.asset.clone()).or_insert_with(|| AssetGainSummary {
+            asset: gain.asset.clone(),
+            ..AssetGainSummary::default()
+        });
+        for summary in [&mut *summary, &mut totals] {
+            summary.disposals += 1;
+            summary.quantity += gain.quantity;
+            summary.proceeds += gain.proceeds;
+            summary.cost_basis += gain.cost_basis;
+            summary.total_gain += gain.gain;
+            match gain.term {
+                HoldingTerm::ShortTerm => summary.short_term_gain += gain.gain,
+                HoldingTerm::LongTerm => summary.long_term_gain += gain.gain,
+            }
+        }
+    }
+    // 合计中数量跨资产相加没有意义
+    totals.quantity = Decimal::ZERO;
+
+    TaxReport {
+        user_id,
+        year,
+        method,
+        assets: assets.into_values().collect(),
+        totals,
+        uncovered: book.uncovered().iter().map(|(k, v)| (k.clone(), *v)).collect(),
+        disposals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+    use chrono::{TimeZone, Utc};
+
+    fn fill(side: Side, price: i64, quantity: i64, day: i64) -> Fill {
+        Fill {
+            id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            order_id: Uuid::new_v4(),
+            symbol: Symbol::new("BTC", "USDT"),
+            side,
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+            quote_quantity: Decimal::from(price * quantity),
+            fee: Decimal::ZERO,
+            fee_currency: "USDT".to_string(),
+            is_maker: false,
+            venue: "INTERNAL".to_string(),
+            strategy_tag: None,
+            environment: crate::models::TradingEnvironment::Live,
+            executed_at: Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap() + chrono::Duration::days(day),
+        }
+    }
+
+    fn fills() -> Vec<Fill> {
+        vec![
+            fill(Side::Buy, 100, 1, 0),
+            fill(Side::Buy, 300, 1, 10),
+            fill(Side::Buy, 200, 1, 20),
+            fill(Side::Sell, 250, 2, 400),
+        ]
+    }
+
+    fn total_gain(method: CostBasisMethod) -> Decimal {
+        build_report(Uuid::nil(), 2024, method, 365, &fills()).totals.total_gain
+    }
+
+    #[test]
+    fn test_methods() {
+        // 收入 500
+        assert_eq!(total_gain(CostBasisMethod::Fifo), Decimal::from(100));
+        assert_eq!(total_gain(CostBasisMethod::Lifo), Decimal::ZERO);
+        assert_eq!(total_gain(CostBasisMethod::Hifo), Decimal::ZERO);
+        assert_eq!(total_gain(CostBasisMethod::AverageCost), Decimal::from(100));
+    }
+
+    #[test]
+    fn test_average_cost_rebases_remaining_lots() {
+        let fills = vec![
+            fill(Side::Buy, 100, 1, 0),
+            fill(Side::Buy, 200, 1, 1),
+            fill(Side::Sell, 150, 1, 2),
+            fill(Side::Sell, 150, 1, 3),
+        ];
+        let report = build_report(Uuid::nil(), 2023, CostBasisMethod::AverageCost, 365, &fills);
+        let bases: Vec<Decimal> = report.disposals.iter().map(|gain| gain.cost_basis).collect();
+        assert_eq!(bases, vec![Decimal::from(150), Decimal::from(150)]);
+        assert_eq!(report.totals.cost_basis, Decimal::from(300));
+
+        // 处置后再买入，与剩余持仓的均价加权
+        let mut book = TaxLotBook::new(CostBasisMethod::AverageCost, 365);
+        for fill in &fills[..3] {
+            book.apply_fill(fill);
+        }
+        book.apply_fill(&fill(Side::Buy, 300, 1, 4));
+        let gains = book.apply_fill(&fill(Side::Sell, 300, 2, 5));
+        let basis: Decimal = gains.iter().map(|gain| gain.cost_basis).sum();
+        assert_eq!(basis, Decimal::from(450));
+    }
+
+    #[test]
+    fn test_holding_term_and_uncovered() {
+        let mut fills = fills();
+        fills.push(fill(Side::Sell, 250, 3, 401));
+        let report = build_report(Uuid::nil(), 2024, CostBasisMethod::Fifo, 365, &fills);
+
+        let first = &report.disposals[0];
+        assert_eq!(first.term, HoldingTerm::LongTerm);
+        assert_eq!(report.assets.len(), 1);
+        assert_eq!(report.assets[0].disposals, 3);
+        assert_eq!(report.uncovered["BTC"], Decimal::from(2));
+        // 所有批次持有均超过365天
+        assert_eq!(report.assets[0].short_term_gain, Decimal::ZERO);
+
+        // 年度过滤
+        let report = build_report(Uuid::nil(), 2023, CostBasisMethod::Fifo, 365, &fills);
+        assert!(report.disposals.is_empty());
+    }
+}
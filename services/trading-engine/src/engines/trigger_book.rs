@@ -0,0 +1,235 @@
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, VecDeque};
+use uuid::Uuid;
+
+use crate::models::{Order, OrderType, Side, TradingError, TradingResult};
+
+/// 触发方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerDirection {
+    /// 最新价上涨到触发价及以上时触发
+    Rising,
+    /// 最新价下跌到触发价及以下时触发
+    Falling,
+}
+
+impl TriggerDirection {
+    /// 止损买单和止盈卖单在价格上涨时触发，止损卖单和止盈买单在价格下跌时触发
+    pub fn of(order_type: OrderType, side: Side) -> Option<Self> {
+        match (order_type, side) {
+            (OrderType::StopLoss | OrderType::StopLossLimit, Side::Buy)
+            | (OrderType::TakeProfit | OrderType::TakeProfitLimit, Side::Sell) => Some(Self::Rising),
+            (OrderType::StopLoss | OrderType::StopLossLimit, Side::Sell)
+            | (OrderType::TakeProfit | OrderType::TakeProfitLimit, Side::Buy) => Some(Self::Falling),
+            (OrderType::Market | OrderType::Limit | OrderType::Iceberg, _) => None,
+        }
+    }
+
+    /// 最新价是否已越过触发价
+    pub fn is_crossed(&self, stop_price: Decimal, last_price: Decimal) -> bool {
+        match self {
+            Self::Rising => last_price >= stop_price,
+            Self::Falling => last_price <= stop_price,
+        }
+    }
+}
+
+/// 是否为条件单
+pub fn is_trigger_order(order_type: OrderType) -> bool {
+    order_type.requires_stop_price()
+}
+
+/// 触发后转换为可撮合的订单：止损/止盈转市价单，止损限价/止盈限价转限价单
+pub fn activate(mut order: Order) -> Order {
+    order.order_type = if order.order_type.is_limit_order() {
+        OrderType::Limit
+    } else {
+        OrderType::Market
+    };
+    order.updated_at = chrono::Utc::now();
+    order
+}
+
+/// 条件单簿
+///
+/// 按触发方向和触发价保存未触发的止损、止盈单，同一触发价按提交顺序排队。
+/// 最新价变化时取出所有已越过触发价的订单，先越过的触发价排在前面。
+#[derive(Debug, Default)]
+pub struct TriggerOrderBook {
+    rising: BTreeMap<Decimal, VecDeque<Order>>,
+    falling: BTreeMap<Decimal, VecDeque<Order>>,
+}
+
+impl TriggerOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 校验条件单参数并返回触发价和触发方向
+    pub fn validate(order: &Order) -> TradingResult<(Decimal, TriggerDirection)> {
+        let direction = TriggerDirection::of(order.order_type, order.side).ok_or_else(|| {
+            TradingError::InvalidOrder(format!("{} is not a trigger order", order.order_type))
+        })?;
+        let stop_price = order
+            .stop_price
+            .filter(|price| *price > Decimal::ZERO)
+            .ok_or_else(|| TradingError::InvalidOrder("Trigger order must have a positive stop price".to_string()))?;
+        if order.order_type.is_limit_order() && order.price.is_none() {
+            return Err(TradingError::InvalidOrder("Stop limit order must have price".to_string()));
+        }
+        Ok((stop_price, direction))
+    }
+
+    /// 挂入条件单，不检查是否已触发
+    pub fn insert(&mut self, order: Order) -> TradingResult<()> {
+        let (stop_price, direction) = Self::validate(&order)?;
+        self.side_mut(direction).entry(stop_price).or_default().push_back(order);
+        Ok(())
+    }
+
+    /// 撤销条件单
+    pub fn remove(&mut self, order_id: Uuid) -> Option<Order> {
+        for levels in [&mut self.rising, &mut self.falling] {
+            let found = levels.iter_mut().find_map(|(&price, orders)| {
+                orders.iter().position(|o| o.id == order_id).map(|pos| (price, pos))
+            });
+            if let Some((price, pos)) = found {
+                let orders = levels.get_mut(&price)?;
+                let order = orders.remove(pos);
+                if orders.is_empty() {
+                    levels.remove(&price);
+                }
+                return order;
+            }
+        }
+        None
+    }
+
+    /// 取出最新价已越过触发价的订单
+    pub fn take_triggered(&mut self, last_price: Decimal) -> Vec<Order> {
+        let mut triggered = Vec::new();
+
+        // 上涨触发：触发价从低到高，等于最新价的也触发
+        let higher = self.rising.split_off(&last_price);
+        let crossed = std::mem::replace(&mut self.rising, higher);
+        triggered.extend(crossed.into_values().flatten());
+        if let Some(orders) = self.rising.remove(&last_price) {
+            triggered.extend(orders);
+        }
+
+        // 下跌触发：触发价从高到低
+        let crossed = self.falling.split_off(&last_price);
+        triggered.extend(crossed.into_values().rev().flatten());
+
+        triggered
+    }
+
+    /// 全部条件单，同一触发价按提交顺序
+    pub fn orders(&self) -> impl Iterator<Item = &Order> {
+        self.rising.values().chain(self.falling.values()).flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rising.values().chain(self.falling.values()).map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rising.is_empty() && self.falling.is_empty()
+    }
+
+    fn side_mut(&mut self, direction: TriggerDirection) -> &mut BTreeMap<Decimal, VecDeque<Order>> {
+        match direction {
+            TriggerDirection::Rising => &mut self.rising,
+            TriggerDirection::Falling => &mut self.falling,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn order(order_type: OrderType, side: Side, stop: i64, price: Option<i64>) -> Order {
+        Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            order_type,
+            side,
+            Decimal::ONE,
+            price.map(Decimal::from),
+            Some(Decimal::from(stop)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_trigger_direction() {
+        let price = Decimal::from(100);
+        let rising = TriggerDirection::of(OrderType::StopLoss, Side::Buy).unwrap();
+        assert_eq!(rising, TriggerDirection::Rising);
+        assert_eq!(TriggerDirection::of(OrderType::TakeProfitLimit, Side::Sell), Some(TriggerDirection::Rising));
+        assert_eq!(TriggerDirection::of(OrderType::StopLossLimit, Side::Sell), Some(TriggerDirection::Falling));
+        assert_eq!(TriggerDirection::of(OrderType::TakeProfit, Side::Buy), Some(TriggerDirection::Falling));
+        assert_eq!(TriggerDirection::of(OrderType::Limit, Side::Buy), None);
+
+        // 触发价本身也算越过
+        assert!(rising.is_crossed(price, price));
+        assert!(!rising.is_crossed(price, price - Decimal::new(1, 2)));
+        assert!(TriggerDirection::Falling.is_crossed(price, price));
+        assert!(!TriggerDirection::Falling.is_crossed(price, price + Decimal::new(1, 2)));
+    }
+
+    #[test]
+    fn test_take_triggered() {
+        let mut book = TriggerOrderBook::new();
+        let stop_sell_95 = order(OrderType::StopLoss, Side::Sell, 95, None);
+        let stop_sell_90 = order(OrderType::StopLoss, Side::Sell, 90, None);
+        let take_sell_110 = order(OrderType::TakeProfitLimit, Side::Sell, 110, Some(109));
+        let stop_buy_105 = order(OrderType::StopLoss, Side::Buy, 105, None);
+        for o in [&stop_sell_95, &stop_sell_90, &take_sell_110, &stop_buy_105] {
+            book.insert(o.clone()).unwrap();
+        }
+        assert_eq!(book.len(), 4);
+
+        // 两侧之间的价格不触发
+        assert!(book.take_triggered(Decimal::from(100)).is_empty());
+
+        // 跳空下跌同时越过两个触发价，先越过的排前面
+        let ids: Vec<_> = book.take_triggered(Decimal::from(90)).iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![stop_sell_95.id, stop_sell_90.id]);
+
+        // 恰好等于触发价时触发
+        let ids: Vec<_> = book.take_triggered(Decimal::from(105)).iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![stop_buy_105.id]);
+        assert_eq!(book.len(), 1);
+
+        let triggered = book.take_triggered(Decimal::from(120));
+        assert_eq!(triggered.len(), 1);
+        let activated = activate(triggered[0].clone());
+        assert_eq!(activated.order_type, OrderType::Limit);
+        assert_eq!(activated.price, Some(Decimal::from(109)));
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut book = TriggerOrderBook::new();
+        assert!(book.insert(order(OrderType::Limit, Side::Buy, 100, Some(100))).is_err());
+
+        let mut missing_stop = order(OrderType::StopLoss, Side::Sell, 90, None);
+        missing_stop.stop_price = None;
+        assert!(book.insert(missing_stop).is_err());
+
+        let mut missing_price = order(OrderType::StopLossLimit, Side::Sell, 90, Some(89));
+        missing_price.price = None;
+        assert!(book.insert(missing_price).is_err());
+
+        let stop = order(OrderType::StopLoss, Side::Sell, 90, None);
+        book.insert(stop.clone()).unwrap();
+        assert_eq!(activate(stop.clone()).order_type, OrderType::Market);
+        assert_eq!(book.remove(stop.id).map(|o| o.id), Some(stop.id));
+        assert!(book.remove(stop.id).is_none());
+        assert!(book.is_empty());
+    }
+}
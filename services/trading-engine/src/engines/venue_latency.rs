@@ -0,0 +1,285 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::config::execution::LatencyBudgetConfig;
+
+/// 直方图桶上界（毫秒），最后一个桶收纳所有更大的值
+const BUCKET_BOUNDS_MS: [u64; 14] = [
+    1, 2, 5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000,
+];
+
+/// 固定桶延迟直方图
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: [u64; BUCKET_BOUNDS_MS.len() + 1],
+    count: u64,
+    sum_ms: u64,
+    max_ms: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; BUCKET_BOUNDS_MS.len() + 1],
+            count: 0,
+            sum_ms: 0,
+            max_ms: 0,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum_ms as f64 / self.count as f64
+        }
+    }
+
+    /// 分位数估计，返回所在桶的上界（不超过观测到的最大值）
+    pub fn percentile_ms(&self, quantile: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((self.count as f64) * quantile.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = BUCKET_BOUNDS_MS.get(bucket).copied().unwrap_or(self.max_ms);
+                return bound.min(self.max_ms);
+            }
+        }
+        self.max_ms
+    }
+}
+
+/// 单个交易所的延迟统计
+#[derive(Debug, Clone, Default)]
+pub struct VenueLatency {
+    /// 提交到交易所确认
+    pub ack: LatencyHistogram,
+    /// 提交到完全成交
+    pub fill: LatencyHistogram,
+    pub submissions: u64,
+    pub ack_timeouts: u64,
+}
+
+impl VenueLatency {
+    pub fn timeout_rate(&self) -> f64 {
+        if self.submissions == 0 {
+            0.0
+        } else {
+            self.ack_timeouts as f64 / self.submissions as f64
+        }
+    }
+}
+
+/// 慢交易所报告中的一行
+#[derive(Debug, Clone, Serialize)]
+pub struct VenueLatencySummary {
+    pub venue: String,
+    pub submissions: u64,
+    pub ack_timeouts: u64,
+    pub timeout_rate: Decimal,
+    pub ack_mean_ms: Decimal,
+    pub ack_p50_ms: u64,
+    pub ack_p95_ms: u64,
+    pub ack_p99_ms: u64,
+    pub fill_p50_ms: u64,
+    pub fill_p95_ms: u64,
+    /// 路由惩罚分，0表示不惩罚，1表示最慢
+    pub penalty: Decimal,
+    pub slow: bool,
+}
+
+/// 慢交易所报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowVenueReport {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    /// 按惩罚分从高到低排列
+    pub venues: Vec<VenueLatencySummary>,
+}
+
+impl SlowVenueReport {
+    pub fn slow_venues(&self) -> impl Iterator<Item = &str> {
+        self.venues.iter().filter(|v| v.slow).map(|v| v.venue.as_str())
+    }
+
+    pub fn penalty(&self, venue: &str) -> Decimal {
+        self.venues
+            .iter()
+            .find(|v| v.venue == venue)
+            .map(|v| v.penalty)
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// 计算一个交易所的报告行
+///
+/// 惩罚分由确认延迟P95在预算中的占比和超时率加权得到，
+/// 样本不足时不惩罚也不标记为慢交易所。
+pub fn summarize(venue: &str, stats: &VenueLatency, budget: &LatencyBudgetConfig) -> VenueLatencySummary {
+    let ack_budget_ms = budget.ack_timeout_for(venue).as_millis() as f64;
+    let ack_p95_ms = stats.ack.percentile_ms(0.95);
+    let timeout_rate = stats.timeout_rate();
+    let enough_samples = stats.submissions >= budget.min_samples;
+
+    let latency_score = if ack_budget_ms > 0.0 {
+        (ack_p95_ms as f64 / ack_budget_ms).min(1.0)
+    } else {
+        0.0
+    };
+    let slow_rate = budget.slow_timeout_rate.to_f64().unwrap_or(1.0);
+    let timeout_score = (timeout_rate / slow_rate).min(1.0);
+    let penalty = if enough_samples {
+        0.6 * latency_score + 0.4 * timeout_score
+    } else {
+        0.0
+    };
+
+    let slow = enough_samples && (timeout_rate > slow_rate || ack_p95_ms as f64 >= ack_budget_ms);
+
+    VenueLatencySummary {
+        venue: venue.to_string(),
+        submissions: stats.submissions,
+        ack_timeouts: stats.ack_timeouts,
+        timeout_rate: Decimal::from_f64(timeout_rate).unwrap_or_default().round_dp(4),
+        ack_mean_ms: Decimal::from_f64(stats.ack.mean_ms()).unwrap_or_default().round_dp(2),
+        ack_p50_ms: stats.ack.percentile_ms(0.50),
+        ack_p95_ms,
+        ack_p99_ms: stats.ack.percentile_ms(0.99),
+        fill_p50_ms: stats.fill.percentile_ms(0.50),
+        fill_p95_ms: stats.fill.percentile_ms(0.95),
+        penalty: Decimal::from_f64(penalty).unwrap_or_default().round_dp(4),
+        slow,
+    }
+}
+
+/// 按交易所记录确认与成交延迟
+#[derive(Debug, Default)]
+pub struct VenueLatencyTracker {
+    venues: RwLock<HashMap<String, VenueLatency>>,
+}
+
+impl VenueLatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn record_ack(&self, venue: &str, latency: Duration) {
+        let mut venues = self.venues.write().await;
+        let stats = venues.entry(venue.to_string()).or_default();
+        stats.submissions += 1;
+        stats.ack.record(latency);
+    }
+
+    pub async fn record_ack_timeout(&self, venue: &str) {
+        let mut venues = self.venues.write().await;
+        let stats = venues.entry(venue.to_string()).or_default();
+        stats.submissions += 1;
+        stats.ack_timeouts += 1;
+    }
+
+    pub async fn record_fill(&self, venue: &str, latency: Duration) {
+        self.venues
+            .write()
+            .await
+            .entry(venue.to_string())
+            .or_default()
+            .fill
+            .record(latency);
+    }
+
+    pub async fn snapshot(&self) -> HashMap<String, VenueLatency> {
+        self.venues.read().await.clone()
+    }
+
+    pub async fn report(&self, budget: &LatencyBudgetConfig) -> SlowVenueReport {
+        let venues = self.venues.read().await;
+        let mut summaries: Vec<VenueLatencySummary> = venues
+            .iter()
+            .map(|(venue, stats)| summarize(venue, stats, budget))
+            .collect();
+        summaries.sort_by(|a, b| b.penalty.cmp(&a.penalty).then_with(|| a.venue.cmp(&b.venue)));
+
+        SlowVenueReport {
+            generated_at: chrono::Utc::now(),
+            venues: summaries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget() -> LatencyBudgetConfig {
+        LatencyBudgetConfig {
+            min_samples: 10,
+            ..LatencyBudgetConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_histogram_percentiles() {
+        let mut histogram = LatencyHistogram::default();
+        for _ in 0..90 {
+            histogram.record(Duration::from_millis(8));
+        }
+        for _ in 0..10 {
+            histogram.record(Duration::from_millis(400));
+        }
+
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.percentile_ms(0.50), 10);
+        assert_eq!(histogram.percentile_ms(0.95), 400);
+        assert_eq!(histogram.percentile_ms(0.99), 400);
+        assert!((histogram.mean_ms() - 47.2).abs() < 1e-9);
+        assert_eq!(LatencyHistogram::default().percentile_ms(0.99), 0);
+    }
+
+    #[tokio::test]
+    async fn test_report_flags_laggards() {
+        let tracker = VenueLatencyTracker::new();
+        for _ in 0..20 {
+            tracker.record_ack("Fast", Duration::from_millis(20)).await;
+            tracker.record_ack("Laggy", Duration::from_millis(300)).await;
+        }
+        for _ in 0..3 {
+            tracker.record_ack_timeout("Laggy").await;
+        }
+        for _ in 0..5 {
+            tracker.record_ack("New", Duration::from_millis(900)).await;
+        }
+
+        let report = tracker.report(&budget()).await;
+        assert_eq!(report.venues[0].venue, "Laggy");
+        assert_eq!(report.slow_venues().collect::<Vec<_>>(), vec!["Laggy"]);
+        assert!(report.penalty("Laggy") > report.penalty("Fast"));
+        // 样本不足不惩罚
+        assert_eq!(report.penalty("New"), Decimal::ZERO);
+        assert_eq!(report.penalty("Unknown"), Decimal::ZERO);
+    }
+}
@@ -0,0 +1,266 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::RwLock;
+
+use crate::{
+    config::execution::{VolatilityPolicy, VolatilityPolicyConfig},
+    models::{Order, OrderType, Side, Symbol, Timestamp, TradingError, TradingResult},
+};
+
+/// 波动分档，按声明顺序由低到高
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolatilityRegime {
+    Low,
+    Normal,
+    High,
+    Extreme,
+}
+
+impl std::fmt::Display for VolatilityRegime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VolatilityRegime::Low => write!(f, "low"),
+            VolatilityRegime::Normal => write!(f, "normal"),
+            VolatilityRegime::High => write!(f, "high"),
+            VolatilityRegime::Extreme => write!(f, "extreme"),
+        }
+    }
+}
+
+/// 交易对最近一次波动率读数
+#[derive(Debug, Clone, Serialize)]
+pub struct VolatilityReading {
+    pub regime: VolatilityRegime,
+    /// 采样收益率标准差
+    pub volatility: Decimal,
+    pub updated_at: Timestamp,
+}
+
+/// 波动分档跟踪
+///
+/// 读数可以来自标记价格采样，也可以由外部波动率数据直接写入；
+/// 超过时效的读数按正常波动处理，避免采样中断后一直沿用极端分档。
+pub struct VolatilityRegimeTracker {
+    config: VolatilityPolicyConfig,
+    prices: RwLock<HashMap<Symbol, VecDeque<Decimal>>>,
+    readings: RwLock<HashMap<Symbol, VolatilityReading>>,
+}
+
+impl VolatilityRegimeTracker {
+    pub fn new(config: VolatilityPolicyConfig) -> Self {
+        Self {
+            config,
+            prices: RwLock::new(HashMap::new()),
+            readings: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 记录一次标记价格采样，样本足够时更新读数
+    pub async fn record_price(&self, symbol: &Symbol, price: Decimal, now: Timestamp) -> Option<VolatilityReading> {
+        let volatility = {
+            let mut prices = self.prices.write().await;
+            let samples = prices.entry(symbol.clone()).or_default();
+            samples.push_back(price);
+            while samples.len() > self.config.window + 1 {
+                samples.pop_front();
+            }
+            if samples.len() <= self.config.window {
+                return None;
+            }
+            return_volatility(samples.make_contiguous())?
+        };
+        Some(self.update(symbol, volatility, now).await)
+    }
+
+    /// 写入外部波动率读数
+    pub async fn update(&self, symbol: &Symbol, volatility: Decimal, now: Timestamp) -> VolatilityReading {
+        let reading = VolatilityReading {
+            regime: self.config.classify(volatility),
+            volatility,
+            updated_at: now,
+        };
+        let previous = self.readings.write().await.insert(symbol.clone(), reading.clone());
+        if previous.map(|p| p.regime) != Some(reading.regime) {
+            tracing::info!(
+                "Volatility regime for {} is now {} ({})",
+                symbol,
+                reading.regime,
+                volatility.round_dp(6)
+            );
+        }
+        reading
+    }
+
+    /// 当前分档，没有读数或读数过期时为正常波动
+    pub async fn regime(&self, symbol: &Symbol, now: Timestamp) -> VolatilityRegime {
+        let max_age = chrono::Duration::from_std(self.config.max_reading_age).unwrap_or(chrono::Duration::MAX);
+        self.readings
+            .read()
+            .await
+            .get(symbol)
+            .filter(|reading| now - reading.updated_at <= max_age)
+            .map(|reading| reading.regime)
+            .unwrap_or(VolatilityRegime::Normal)
+    }
+
+    /// 当前分档及其执行限制，未启用或该分档不限制时返回 None
+    pub async fn policy(&self, symbol: &Symbol, now: Timestamp) -> Option<(VolatilityRegime, VolatilityPolicy)> {
+        if !self.config.enabled {
+            return None;
+        }
+        let regime = self.regime(symbol, now).await;
+        self.config
+            .policies
+            .get(&regime)
+            .map(|policy| (regime, policy.clone()))
+    }
+
+    pub async fn readings(&self) -> HashMap<Symbol, VolatilityReading> {
+        self.readings.read().await.clone()
+    }
+}
+
+/// 相邻价格收益率的标准差
+fn return_volatility(prices: &[Decimal]) -> Option<Decimal> {
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .filter(|w| w[0] > Decimal::ZERO)
+        .filter_map(|w| (w[1] / w[0] - Decimal::ONE).to_f64())
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+    Decimal::from_f64(variance.sqrt())
+}
+
+/// 按波动分档限制订单
+///
+/// 暂停算法单时拒绝带算法标记的订单；只允许限价执行时，市价单以标记价格加放宽后的
+/// 滑点上限作为保护价转为限价单。
+pub fn throttle_order(
+    order: &mut Order,
+    regime: VolatilityRegime,
+    policy: &VolatilityPolicy,
+    max_slippage: Decimal,
+    mark_price: Option<Decimal>,
+) -> TradingResult<()> {
+    if policy.pause_algorithms {
+        if let Some(algorithm) = &order.metadata.algorithm {
+            return Err(TradingError::RiskViolation(format!(
+                "Algorithmic execution ({}) is paused for {} in {} volatility",
+                algorithm, order.symbol, regime
+            )));
+        }
+    }
+
+    if policy.limit_only && order.order_type == OrderType::Market {
+        let mark_price = mark_price.ok_or_else(|| {
+            TradingError::ExecutionError(format!(
+                "No mark price to protect market order {} in {} volatility",
+                order.id, regime
+            ))
+        })?;
+        let slippage = max_slippage * policy.slippage_multiplier;
+        let price = match order.side {
+            Side::Buy => mark_price * (Decimal::ONE + slippage),
+            Side::Sell => mark_price * (Decimal::ONE - slippage),
+        };
+        order.order_type = OrderType::Limit;
+        order.price = Some(price.round_dp(8));
+        tracing::info!(
+            "Converted market order {} to limit at {} in {} volatility",
+            order.id,
+            price.round_dp(8),
+            regime
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    fn config() -> VolatilityPolicyConfig {
+        VolatilityPolicyConfig {
+            enabled: true,
+            window: 4,
+            ..VolatilityPolicyConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_regime_from_price_samples() {
+        let tracker = VolatilityRegimeTracker::new(config());
+        let symbol = Symbol::new("BTC", "USDT");
+        let now = Utc::now();
+
+        for price in [100, 100, 100, 100] {
+            assert!(tracker.record_price(&symbol, Decimal::from(price), now).await.is_none());
+        }
+        let reading = tracker.record_price(&symbol, Decimal::from(100), now).await.unwrap();
+        assert_eq!(reading.regime, VolatilityRegime::Low);
+
+        // 价格来回跳动 ±2%
+        for price in [102, 100, 102, 100] {
+            tracker.record_price(&symbol, Decimal::from(price), now).await;
+        }
+        assert_eq!(tracker.regime(&symbol, now).await, VolatilityRegime::Extreme);
+        assert!(tracker.policy(&symbol, now).await.unwrap().1.limit_only);
+
+        // 读数过期后按正常波动处理
+        let later = now + Duration::minutes(5);
+        assert_eq!(tracker.regime(&symbol, later).await, VolatilityRegime::Normal);
+        assert!(tracker.policy(&symbol, later).await.is_none());
+    }
+
+    #[test]
+    fn test_throttle_order() {
+        let policy = VolatilityPolicyConfig::default().policies[&VolatilityRegime::Extreme].clone();
+        let mut order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            OrderType::Market,
+            Side::Buy,
+            Decimal::ONE,
+            None,
+            None,
+        )
+        .unwrap();
+
+        throttle_order(&mut order, VolatilityRegime::Extreme, &policy, Decimal::new(1, 2), Some(Decimal::from(100)))
+            .unwrap();
+        assert_eq!(order.order_type, OrderType::Limit);
+        // 1% 滑点上限放大两倍
+        assert_eq!(order.price, Some(Decimal::from(102)));
+
+        let mut market = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            OrderType::Market,
+            Side::Sell,
+            Decimal::ONE,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(throttle_order(&mut market, VolatilityRegime::Extreme, &policy, Decimal::new(1, 2), None).is_err());
+
+        order.metadata.algorithm = Some("twap".to_string());
+        assert!(matches!(
+            throttle_order(&mut order, VolatilityRegime::Extreme, &policy, Decimal::new(1, 2), None),
+            Err(TradingError::RiskViolation(_))
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use shared_models::Interval;
+use std::time::Duration;
+
+use crate::config::execution::VwapConfig;
+use crate::models::{Symbol, TradingError, TradingResult};
+
+const DAY_MILLIS: i64 = 86_400_000;
+
+/// 日内成交量曲线：从UTC零点开始每个周期占全天成交量的比例
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeProfile {
+    pub interval: Duration,
+    pub shares: Vec<Decimal>,
+    /// 是否来自历史成交量，取不到曲线时为均匀分布
+    pub historical: bool,
+}
+
+impl VolumeProfile {
+    /// 均匀曲线，各周期权重相同
+    pub fn uniform(interval: Duration) -> Self {
+        let count = (DAY_MILLIS / interval.as_millis().max(1) as i64).max(1) as usize;
+        Self {
+            interval,
+            shares: vec![Decimal::ONE; count],
+            historical: false,
+        }
+    }
+
+    /// 从 `start` 开始连续 `count` 个周期的权重
+    pub fn weights(&self, start: DateTime<Utc>, count: usize) -> Vec<Decimal> {
+        let interval_ms = self.interval.as_millis().max(1) as i64;
+        let first = start.timestamp_millis().rem_euclid(DAY_MILLIS) / interval_ms;
+        (0..count as i64)
+            .map(|i| self.shares[((first + i) as usize) % self.shares.len()])
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileResponse {
+    data: Option<ProfileData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileData {
+    interval_ms: i64,
+    buckets: Vec<ProfileBucket>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileBucket {
+    share: Decimal,
+}
+
+/// 从行情服务查询历史成交量曲线
+pub struct VolumeProfileClient {
+    config: VwapConfig,
+    client: reqwest::Client,
+}
+
+impl VolumeProfileClient {
+    pub fn new(config: VwapConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.profile_timeout)
+            .build()
+            .unwrap_or_default();
+        Self { config, client }
+    }
+
+    /// 配置的曲线周期
+    pub fn interval(&self) -> Duration {
+        self.config
+            .profile_interval
+            .parse::<Interval>()
+            .map(|interval| Duration::from_millis(interval.to_millis() as u64))
+            .unwrap_or(Duration::from_secs(300))
+    }
+
+    /// 查询交易对最近 lookback_period 内的成交量曲线
+    pub async fn fetch(&self, symbol: &Symbol) -> TradingResult<VolumeProfile> {
+        let end = Utc::now().timestamp_millis();
+        let start = end - self.config.lookback_period.as_millis() as i64;
+        let url = format!(
+            "{}/api/v1/volume-profile/{}/{}/{}",
+            self.config.market_data_url.trim_end_matches('/'),
+            self.config.profile_exchange,
+            symbol,
+            self.config.profile_interval
+        );
+        let response = self
+            .client
+            .get(url)
+            .query(&[("start_time", start), ("end_time", end)])
+            .send()
+            .await
+            .map_err(|e| TradingError::ExecutionError(format!("Volume profile request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(TradingError::ExecutionError(format!(
+                "Volume profile request returned status {}",
+                response.status()
+            )));
+        }
+        let body: ProfileResponse = response
+            .json()
+            .await
+            .map_err(|e| TradingError::SerializationError(e.to_string()))?;
+        parse_profile(body)
+    }
+}
+
+fn parse_profile(body: ProfileResponse) -> TradingResult<VolumeProfile> {
+    let data = body
+        .data
+        .ok_or_else(|| TradingError::ExecutionError("Volume profile response has no data".to_string()))?;
+    if data.interval_ms <= 0 || data.buckets.is_empty() {
+        return Err(TradingError::ExecutionError("Volume profile is empty".to_string()));
+    }
+    let shares: Vec<Decimal> = data.buckets.into_iter().map(|bucket| bucket.share).collect();
+    if shares.iter().all(|share| *share <= Decimal::ZERO) {
+        return Err(TradingError::ExecutionError("Volume profile has no traded volume".to_string()));
+    }
+    Ok(VolumeProfile {
+        interval: Duration::from_millis(data.interval_ms as u64),
+        shares,
+        historical: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_profile_weights() {
+        let body: ProfileResponse = serde_json::from_value(serde_json::json!({
+            "success": true,
+            "data": {
+                "interval_ms": 21_600_000,
+                "buckets": [{ "share": "0.1" }, { "share": "0.2" }, { "share": "0.3" }, { "share": "0.4" }]
+            }
+        }))
+        .unwrap();
+        let profile = parse_profile(body).unwrap();
+        assert!(profile.historical);
+        assert_eq!(profile.interval, Duration::from_secs(6 * 3600));
+
+        // 从 18:00 开始跨过零点
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 18, 30, 0).unwrap();
+        assert_eq!(
+            profile.weights(start, 3),
+            vec![Decimal::new(4, 1), Decimal::new(1, 1), Decimal::new(2, 1)]
+        );
+
+        let empty: ProfileResponse = serde_json::from_value(serde_json::json!({
+            "data": { "interval_ms": 21_600_000, "buckets": [{ "share": 0 }] }
+        }))
+        .unwrap();
+        assert!(parse_profile(empty).is_err());
+        assert_eq!(VolumeProfile::uniform(Duration::from_secs(300)).shares.len(), 288);
+    }
+}
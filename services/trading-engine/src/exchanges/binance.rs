@@ -2,7 +2,7 @@ use anyhow::Result;
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 
-use crate::models::{Order, Symbol};
+use crate::models::{Order, Symbol, TradingEnvironment};
 use crate::engines::execution_engine::{MarketData, OrderStatusInfo};
 
 /// 币安交易所连接器
@@ -11,6 +11,7 @@ pub struct BinanceConnector {
     pub name: String,
     pub maker_fee: Decimal,
     pub taker_fee: Decimal,
+    pub environment: TradingEnvironment,
 }
 
 impl BinanceConnector {
@@ -19,6 +20,16 @@ impl BinanceConnector {
             name: "Binance".to_string(),
             maker_fee: Decimal::from_f64_retain(0.001).unwrap_or_default(), // 0.1%
             taker_fee: Decimal::from_f64_retain(0.001).unwrap_or_default(), // 0.1%
+            environment: TradingEnvironment::Live,
+        }
+    }
+
+    /// 币安现货测试网，以独立的交易所名称注册，避免与实盘混用
+    pub fn testnet() -> Self {
+        Self {
+            name: "Binance-Testnet".to_string(),
+            environment: TradingEnvironment::Testnet,
+            ..Self::new()
         }
     }
 
@@ -32,6 +43,11 @@ impl BinanceConnector {
         Ok(())
     }
 
+    pub async fn cancel_order_by_client_id(&self, _client_order_id: &str) -> Result<()> {
+        // 模拟订单取消
+        Ok(())
+    }
+
     pub async fn get_order_status(&self, _order_id: &str) -> Result<OrderStatusInfo> {
         // 模拟订单状态查询
         Ok(OrderStatusInfo {
@@ -39,6 +55,7 @@ impl BinanceConnector {
             status: "FILLED".to_string(),
             filled_quantity: Decimal::from(100),
             avg_price: Some(Decimal::from(50000)),
+            fills: Vec::new(),
         })
     }
 
@@ -70,6 +87,10 @@ impl BinanceConnector {
     pub fn get_fees(&self) -> (Decimal, Decimal) {
         (self.maker_fee, self.taker_fee)
     }
+
+    pub fn environment(&self) -> TradingEnvironment {
+        self.environment
+    }
 }
 
 impl Default for BinanceConnector {
@@ -0,0 +1,560 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::engines::execution_engine::{MarketData, OrderStatusInfo, VenueFill};
+use crate::models::{Order, OrderType, Side, Symbol, TimeInForce, TradingEnvironment};
+
+const DEFAULT_API_URL: &str = "https://api.kraken.com";
+
+/// Kraken连接配置
+#[derive(Debug, Clone)]
+pub struct KrakenConfig {
+    pub api_url: String,
+    pub api_key: String,
+    /// Base64编码的私钥
+    pub api_secret: String,
+    pub maker_fee: Decimal,
+    pub taker_fee: Decimal,
+    pub timeout: Duration,
+    /// 测试网使用独立的接口地址和凭据
+    pub environment: TradingEnvironment,
+}
+
+impl Default for KrakenConfig {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            api_key: String::new(),
+            api_secret: String::new(),
+            maker_fee: Decimal::new(25, 4), // 0.25%
+            taker_fee: Decimal::new(40, 4), // 0.40%
+            timeout: Duration::from_secs(10),
+            environment: TradingEnvironment::Live,
+        }
+    }
+}
+
+impl KrakenConfig {
+    /// 从环境变量读取: KRAKEN_API_KEY / KRAKEN_API_SECRET / KRAKEN_API_URL
+    pub fn from_env() -> Option<Self> {
+        let api_key = std::env::var("KRAKEN_API_KEY").ok()?;
+        let api_secret = std::env::var("KRAKEN_API_SECRET").ok()?;
+        Some(Self {
+            api_url: std::env::var("KRAKEN_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string()),
+            api_key,
+            api_secret,
+            ..Default::default()
+        })
+    }
+
+    /// 从环境变量读取测试网配置: KRAKEN_TESTNET_API_URL / KRAKEN_TESTNET_API_KEY / KRAKEN_TESTNET_API_SECRET
+    ///
+    /// Kraken没有固定的现货测试网地址，未配置接口地址时不启用。
+    pub fn testnet_from_env() -> Option<Self> {
+        Some(Self {
+            api_url: std::env::var("KRAKEN_TESTNET_API_URL").ok()?,
+            api_key: std::env::var("KRAKEN_TESTNET_API_KEY").ok()?,
+            api_secret: std::env::var("KRAKEN_TESTNET_API_SECRET").ok()?,
+            environment: TradingEnvironment::Testnet,
+            ..Default::default()
+        })
+    }
+}
+
+/// 内部币种转换为Kraken币种
+fn to_kraken_asset(asset: &str) -> &str {
+    match asset {
+        "BTC" => "XBT",
+        "DOGE" => "XDG",
+        other => other,
+    }
+}
+
+/// Kraken余额中的币种转换为内部币种，例如 XXBT -> BTC, ZUSD -> USD
+pub fn from_kraken_asset(asset: &str) -> String {
+    let asset = match asset {
+        "XXBT" | "XBT" => "BTC",
+        "XXDG" | "XDG" => "DOGE",
+        "XETH" => "ETH",
+        "XLTC" => "LTC",
+        "XXRP" => "XRP",
+        "ZUSD" => "USD",
+        "ZEUR" => "EUR",
+        "ZGBP" => "GBP",
+        "ZJPY" => "JPY",
+        other => other,
+    };
+    asset.to_string()
+}
+
+/// REST接口交易对，例如 BTC/USDT -> XBTUSDT
+pub fn kraken_pair(symbol: &Symbol) -> String {
+    format!("{}{}", to_kraken_asset(&symbol.base), to_kraken_asset(&symbol.quote))
+}
+
+/// 订单类型映射
+fn kraken_order_type(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "market",
+        OrderType::Limit => "limit",
+        OrderType::StopLoss => "stop-loss",
+        OrderType::TakeProfit => "take-profit",
+        OrderType::StopLossLimit => "stop-loss-limit",
+        OrderType::TakeProfitLimit => "take-profit-limit",
+        OrderType::Iceberg => "iceberg",
+    }
+}
+
+/// 构建AddOrder参数
+///
+/// 止损/止盈单的触发价放在 price，限价放在 price2。
+pub fn add_order_params(order: &Order) -> Result<Vec<(String, String)>> {
+    let mut params = vec![
+        ("pair".to_string(), kraken_pair(&order.symbol)),
+        (
+            "type".to_string(),
+            match order.side {
+                Side::Buy => "buy",
+                Side::Sell => "sell",
+            }
+            .to_string(),
+        ),
+        ("ordertype".to_string(), kraken_order_type(order.order_type).to_string()),
+        ("volume".to_string(), order.quantity.normalize().to_string()),
+    ];
+
+    let price = order.price.map(|p| p.normalize().to_string());
+    let stop_price = order.stop_price.map(|p| p.normalize().to_string());
+    match order.order_type {
+        OrderType::Market => {}
+        OrderType::Limit => {
+            let price = price.ok_or_else(|| anyhow!("Limit order requires price"))?;
+            params.push(("price".to_string(), price));
+        }
+        OrderType::StopLoss | OrderType::TakeProfit => {
+            let trigger = stop_price.ok_or_else(|| anyhow!("Stop order requires stop price"))?;
+            params.push(("price".to_string(), trigger));
+        }
+        OrderType::StopLossLimit | OrderType::TakeProfitLimit => {
+            let trigger = stop_price.ok_or_else(|| anyhow!("Stop order requires stop price"))?;
+            let limit = price.ok_or_else(|| anyhow!("Stop limit order requires price"))?;
+            params.push(("price".to_string(), trigger));
+            params.push(("price2".to_string(), limit));
+        }
+        OrderType::Iceberg => {
+            let price = price.ok_or_else(|| anyhow!("Iceberg order requires price"))?;
+            let display = order
+                .visible_quantity
+                .ok_or_else(|| anyhow!("Iceberg order requires visible quantity"))?;
+            params.push(("price".to_string(), price));
+            params.push(("displayvol".to_string(), display.normalize().to_string()));
+        }
+    }
+
+    if order.order_type != OrderType::Market {
+        let time_in_force = match order.time_in_force {
+            TimeInForce::GTC => "GTC",
+            TimeInForce::IOC => "IOC",
+            TimeInForce::GTD => "GTD",
+            TimeInForce::FOK => return Err(anyhow!("Kraken does not support FOK orders")),
+        };
+        params.push(("timeinforce".to_string(), time_in_force.to_string()));
+        if order.time_in_force == TimeInForce::GTD {
+            let expires_at = order
+                .expires_at
+                .ok_or_else(|| anyhow!("GTD order requires expiry"))?;
+            params.push(("expiretm".to_string(), expires_at.timestamp().to_string()));
+        }
+    }
+
+    if let Some(client_order_id) = &order.client_order_id {
+        params.push(("cl_ord_id".to_string(), client_order_id.clone()));
+    }
+
+    Ok(params)
+}
+
+/// Kraken订单状态映射为执行引擎状态
+pub fn map_order_status(status: &str, filled: Decimal) -> &'static str {
+    match status {
+        "closed" => "FILLED",
+        "canceled" | "expired" if filled > Decimal::ZERO => "PARTIALLY_FILLED",
+        "canceled" | "expired" => "CANCELLED",
+        "open" if filled > Decimal::ZERO => "PARTIALLY_FILLED",
+        _ => "PENDING",
+    }
+}
+
+/// 私有接口签名
+///
+/// API-Sign = Base64(HMAC-SHA512(uri_path + SHA256(nonce + post_data), Base64Decode(secret)))
+pub fn sign_request(uri_path: &str, nonce: u64, post_data: &str, secret: &str) -> Result<String> {
+    let key = STANDARD
+        .decode(secret)
+        .map_err(|e| anyhow!("Invalid Kraken API secret: {}", e))?;
+
+    let mut sha = Sha256::new();
+    sha.update(nonce.to_string().as_bytes());
+    sha.update(post_data.as_bytes());
+    let digest = sha.finalize();
+
+    let mut mac = Hmac::<Sha512>::new_from_slice(&key)
+        .map_err(|e| anyhow!("Invalid Kraken API secret: {}", e))?;
+    mac.update(uri_path.as_bytes());
+    mac.update(&digest);
+    Ok(STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+/// 解析QueryTrades返回的逐笔成交，按成交时间升序
+///
+/// `maker` 字段标记本方是否为挂单方。
+pub fn parse_trades(result: &Value) -> Vec<VenueFill> {
+    let Some(trades) = result.as_object() else {
+        return Vec::new();
+    };
+    let mut fills: Vec<VenueFill> = trades
+        .iter()
+        .map(|(trade_id, trade)| {
+            let time = trade.get("time").and_then(Value::as_f64).unwrap_or_default();
+            VenueFill {
+                trade_id: trade_id.clone(),
+                price: decimal_field(trade, "price"),
+                quantity: decimal_field(trade, "vol"),
+                fee: decimal_field(trade, "fee"),
+                is_maker: trade.get("maker").and_then(Value::as_bool).unwrap_or(false),
+                timestamp: chrono::DateTime::from_timestamp_millis((time * 1000.0) as i64).unwrap_or_default(),
+            }
+        })
+        .collect();
+    fills.sort_by_key(|fill| fill.timestamp);
+    fills
+}
+
+fn decimal_field(value: &Value, key: &str) -> Decimal {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Kraken REST交易所连接器
+#[derive(Clone)]
+pub struct KrakenConnector {
+    pub name: String,
+    config: KrakenConfig,
+    client: reqwest::Client,
+    /// 私有接口要求nonce严格递增
+    last_nonce: Arc<AtomicU64>,
+}
+
+impl KrakenConnector {
+    pub fn new(config: KrakenConfig) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(config.timeout).build()?;
+        let name = match config.environment {
+            TradingEnvironment::Live => "Kraken",
+            TradingEnvironment::Testnet => "Kraken-Testnet",
+        };
+        Ok(Self {
+            name: name.to_string(),
+            config,
+            client,
+            last_nonce: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// 使用环境变量中的凭据创建，未配置时返回 None
+    pub fn from_env() -> Result<Option<Self>> {
+        KrakenConfig::from_env().map(Self::new).transpose()
+    }
+
+    /// 通过环境变量配置了测试网时创建测试网连接器
+    pub fn testnet_from_env() -> Result<Option<Self>> {
+        KrakenConfig::testnet_from_env().map(Self::new).transpose()
+    }
+
+    fn next_nonce(&self) -> u64 {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let mut last = self.last_nonce.load(Ordering::SeqCst);
+        loop {
+            let next = now.max(last + 1);
+            match self
+                .last_nonce
+                .compare_exchange(last, next, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return next,
+                Err(current) => last = current,
+            }
+        }
+    }
+
+    /// 解析统一响应格式 {"error": [...], "result": {...}}
+    fn into_result(body: Value) -> Result<Value> {
+        if let Some(errors) = body.get("error").and_then(Value::as_array) {
+            if !errors.is_empty() {
+                let messages: Vec<String> = errors
+                    .iter()
+                    .map(|e| e.as_str().unwrap_or_default().to_string())
+                    .collect();
+                return Err(anyhow!("Kraken API error: {}", messages.join(", ")));
+            }
+        }
+        body.get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("Kraken response missing result"))
+    }
+
+    async fn private_request(&self, method: &str, params: &[(String, String)]) -> Result<Value> {
+        if self.config.api_key.is_empty() || self.config.api_secret.is_empty() {
+            return Err(anyhow!("Kraken API credentials are not configured"));
+        }
+
+        let uri_path = format!("/0/private/{}", method);
+        let nonce = self.next_nonce();
+        let mut form: Vec<(String, String)> = vec![("nonce".to_string(), nonce.to_string())];
+        form.extend(params.iter().cloned());
+        let post_data = form
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let signature = sign_request(&uri_path, nonce, &post_data, &self.config.api_secret)?;
+
+        let body: Value = self
+            .client
+            .post(format!("{}{}", self.config.api_url, uri_path))
+            .header("API-Key", &self.config.api_key)
+            .header("API-Sign", signature)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(post_data)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Self::into_result(body)
+    }
+
+    async fn public_request(&self, method: &str, query: &[(&str, String)]) -> Result<Value> {
+        let body: Value = self
+            .client
+            .get(format!("{}/0/public/{}", self.config.api_url, method))
+            .query(query)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Self::into_result(body)
+    }
+
+    pub async fn submit_order(&self, order: &Order) -> Result<String> {
+        let params = add_order_params(order)?;
+        let result = self.private_request("AddOrder", &params).await?;
+        result
+            .get("txid")
+            .and_then(Value::as_array)
+            .and_then(|ids| ids.first())
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Kraken AddOrder response missing txid"))
+    }
+
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        self.private_request("CancelOrder", &[("txid".to_string(), order_id.to_string())])
+            .await?;
+        Ok(())
+    }
+
+    /// 按下单时的 cl_ord_id 撤单，用于未拿到 txid 的订单
+    pub async fn cancel_order_by_client_id(&self, client_order_id: &str) -> Result<()> {
+        self.private_request("CancelOrder", &[("cl_ord_id".to_string(), client_order_id.to_string())])
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_order_status(&self, order_id: &str) -> Result<OrderStatusInfo> {
+        let params = [
+            ("txid".to_string(), order_id.to_string()),
+            ("trades".to_string(), "true".to_string()),
+        ];
+        let result = self.private_request("QueryOrders", &params).await?;
+        let info = result
+            .get(order_id)
+            .ok_or_else(|| anyhow!("Kraken order {} not found", order_id))?;
+
+        let filled_quantity = decimal_field(info, "vol_exec");
+        let avg_price = Some(decimal_field(info, "price")).filter(|p| !p.is_zero());
+        let status = info.get("status").and_then(Value::as_str).unwrap_or_default();
+
+        // 逐笔成交带有挂单/吃单标记和实际手续费
+        let trade_ids: Vec<&str> = info
+            .get("trades")
+            .and_then(Value::as_array)
+            .map(|ids| ids.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let fills = if trade_ids.is_empty() {
+            Vec::new()
+        } else {
+            let trades = self
+                .private_request("QueryTrades", &[("txid".to_string(), trade_ids.join(","))])
+                .await?;
+            parse_trades(&trades)
+        };
+
+        Ok(OrderStatusInfo {
+            order_id: order_id.to_string(),
+            status: map_order_status(status, filled_quantity).to_string(),
+            filled_quantity,
+            avg_price,
+            fills,
+        })
+    }
+
+    pub async fn get_account_balance(&self) -> Result<HashMap<String, Decimal>> {
+        let result = self.private_request("Balance", &[]).await?;
+        let balances = result
+            .as_object()
+            .ok_or_else(|| anyhow!("Invalid Kraken balance response"))?;
+
+        Ok(balances
+            .iter()
+            .filter_map(|(asset, amount)| {
+                let amount: Decimal = amount.as_str()?.parse().ok()?;
+                Some((from_kraken_asset(asset), amount))
+            })
+            .collect())
+    }
+
+    pub async fn get_market_data(&self, symbol: &Symbol) -> Result<MarketData> {
+        let result = self
+            .public_request("Ticker", &[("pair", kraken_pair(symbol))])
+            .await?;
+        // 返回的键可能是Kraken内部名称（例如 XXBTZUSD），取第一个
+        let ticker = result
+            .as_object()
+            .and_then(|tickers| tickers.values().next())
+            .ok_or_else(|| anyhow!("Kraken ticker for {} not found", symbol.to_string()))?;
+        let field = |key: &str, index: usize| -> Option<Decimal> {
+            ticker.get(key)?.get(index)?.as_str()?.parse().ok()
+        };
+
+        let last = field("c", 0);
+        Ok(MarketData {
+            symbol: symbol.clone(),
+            price: last.unwrap_or_default(),
+            volume: field("v", 1).unwrap_or_default(),
+            timestamp: chrono::Utc::now(),
+            bid: field("b", 0),
+            ask: field("a", 0),
+            last,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_fees(&self) -> (Decimal, Decimal) {
+        (self.config.maker_fee, self.config.taker_fee)
+    }
+
+    pub fn environment(&self) -> TradingEnvironment {
+        self.config.environment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderStatus;
+
+    #[test]
+    fn test_sign_request() {
+        // Kraken API文档中的签名示例
+        let secret = "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==";
+        let post_data = "nonce=1616492376594&ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25";
+        let signature = sign_request("/0/private/AddOrder", 1616492376594, post_data, secret).unwrap();
+        assert_eq!(
+            signature,
+            "4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ=="
+        );
+    }
+
+    #[test]
+    fn test_pair_and_asset_mapping() {
+        assert_eq!(kraken_pair(&Symbol::new("BTC", "USDT")), "XBTUSDT");
+        assert_eq!(kraken_pair(&Symbol::new("ETH", "USD")), "ETHUSD");
+        assert_eq!(from_kraken_asset("XXBT"), "BTC");
+        assert_eq!(from_kraken_asset("ZUSD"), "USD");
+        assert_eq!(from_kraken_asset("USDT"), "USDT");
+    }
+
+    #[test]
+    fn test_map_order_status() {
+        assert_eq!(map_order_status("closed", Decimal::ONE), "FILLED");
+        assert_eq!(map_order_status("open", Decimal::ZERO), "PENDING");
+        assert_eq!(map_order_status("open", Decimal::ONE), "PARTIALLY_FILLED");
+        assert_eq!(map_order_status("canceled", Decimal::ZERO), "CANCELLED");
+    }
+
+    #[test]
+    fn test_add_order_params() {
+        let order = Order::new(
+            uuid::Uuid::new_v4(),
+            Symbol::new("BTC", "USD"),
+            OrderType::StopLossLimit,
+            Side::Sell,
+            Decimal::new(125, 2),
+            Some(Decimal::from(37000)),
+            Some(Decimal::from(37500)),
+        )
+        .unwrap();
+        assert_eq!(order.status, OrderStatus::Pending);
+
+        let params: HashMap<String, String> = add_order_params(&order).unwrap().into_iter().collect();
+        assert_eq!(params["pair"], "XBTUSD");
+        assert_eq!(params["type"], "sell");
+        assert_eq!(params["ordertype"], "stop-loss-limit");
+        assert_eq!(params["volume"], "1.25");
+        assert_eq!(params["price"], "37500");
+        assert_eq!(params["price2"], "37000");
+    }
+
+    #[test]
+    fn test_parse_trades() {
+        let result = serde_json::json!({
+            "TZX2WP-XSEOP-FP7WYR": {
+                "ordertxid": "OQCLML-BW3P3-BUCMWZ",
+                "time": 1688667800.5,
+                "price": "30010.0",
+                "vol": "0.50000000",
+                "fee": "12.00400",
+                "maker": false
+            },
+            "TCWJEG-FL4SZ-3FKGH6": {
+                "ordertxid": "OQCLML-BW3P3-BUCMWZ",
+                "time": 1688667796.8802,
+                "price": "30000.0",
+                "vol": "0.25000000",
+                "fee": "1.20000",
+                "maker": true
+            }
+        });
+
+        let fills = parse_trades(&result);
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].trade_id, "TCWJEG-FL4SZ-3FKGH6");
+        assert!(fills[0].is_maker);
+        assert_eq!(fills[0].quantity, Decimal::new(25, 2));
+        assert!(!fills[1].is_maker);
+        assert_eq!(fills[1].fee, Decimal::new(12004, 3));
+        assert!(parse_trades(&Value::Null).is_empty());
+    }
+}
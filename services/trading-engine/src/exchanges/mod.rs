@@ -1,3 +1,5 @@
 pub mod binance;
+pub mod kraken;
 
 pub use binance::BinanceConnector;
+pub use kraken::{KrakenConfig, KrakenConnector};
@@ -1,30 +1,53 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
+    Json as RequestJson,
 };
+use chrono::{TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use uuid::Uuid;
 
+use super::authenticated_user;
 use crate::{
+    models::{default_range_start, ActivityCategory, EnvironmentAccess, PnlGranularity},
     services::AccountService,
     state::AppState,
 };
-use shared_models::AccountType;
+use shared_models::{AccountType, SettlementCurrency};
 
 #[derive(Debug, Deserialize)]
 pub struct AccountQuery {
     pub account_type: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PnlHistoryQuery {
+    /// 5m / 15m / 1h / 4h / 1d / 1w，默认 1h
+    pub granularity: Option<String>,
+    /// 起始时间（毫秒时间戳），默认回看200个点
+    pub start_time: Option<i64>,
+    /// 结束时间（毫秒时间戳），默认当前时间
+    pub end_time: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetSettlementCurrencyRequest {
+    pub currency: SettlementCurrency,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetEnvironmentAccessRequest {
+    pub access: EnvironmentAccess,
+}
+
 /// 获取账户信息
 pub async fn get_account(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<AccountQuery>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     let account_type = if let Some(type_str) = query.account_type {
         match type_str.parse::<AccountType>() {
@@ -42,7 +65,12 @@ pub async fn get_account(
         None
     };
 
-    match state.account_service.get_account(user_id, account_type).await {
+    // 按账户结算币种展示
+    let account = match state.account_service.get_account(user_id, account_type).await {
+        Ok(account) => state.conversion_service.account_info(account).await,
+        Err(e) => Err(e),
+    };
+    match account {
         Ok(account) => {
             let response = json!({
                 "success": true,
@@ -65,10 +93,10 @@ pub async fn get_account(
 /// 获取资金余额
 pub async fn get_balance(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<AccountQuery>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     let account_type = if let Some(type_str) = query.account_type {
         match type_str.parse::<AccountType>() {
@@ -86,7 +114,11 @@ pub async fn get_balance(
         None
     };
 
-    match state.account_service.get_balance(user_id, account_type).await {
+    let balances = match state.account_service.get_balance(user_id, account_type).await {
+        Ok(balances) => state.conversion_service.balances(user_id, balances).await,
+        Err(e) => Err(e),
+    };
+    match balances {
         Ok(balances) => {
             let response = json!({
                 "success": true,
@@ -109,9 +141,9 @@ pub async fn get_balance(
 /// 获取保证金信息
 pub async fn get_margin_info(
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     match state.account_service.get_margin_info(user_id).await {
         Ok(margin_info) => {
@@ -136,11 +168,15 @@ pub async fn get_margin_info(
 /// 获取盈亏统计
 pub async fn get_pnl(
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
-    match state.account_service.get_pnl_summary(user_id).await {
+    let pnl = match state.account_service.get_pnl_summary(user_id).await {
+        Ok(pnl) => state.conversion_service.pnl_summary(user_id, pnl).await,
+        Err(e) => Err(e),
+    };
+    match pnl {
         Ok(pnl) => {
             let response = json!({
                 "success": true,
@@ -158,4 +194,162 @@ pub async fn get_pnl(
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
-}
\ No newline at end of file
+}
+
+/// 获取盈亏时间序列及回撤/收益统计
+pub async fn get_pnl_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PnlHistoryQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+
+    let granularity = match query.granularity.as_deref() {
+        Some(value) => value.parse::<PnlGranularity>().map_err(|_| StatusCode::BAD_REQUEST)?,
+        None => PnlGranularity::OneHour,
+    };
+    let end = match query.end_time {
+        Some(ms) => Utc.timestamp_millis_opt(ms).single().ok_or(StatusCode::BAD_REQUEST)?,
+        None => Utc::now(),
+    };
+    let start = match query.start_time {
+        Some(ms) => Utc.timestamp_millis_opt(ms).single().ok_or(StatusCode::BAD_REQUEST)?,
+        None => default_range_start(granularity, end, 200),
+    };
+
+    let history = match state.pnl_service.history(user_id, granularity, start, end).await {
+        Ok(history) => state.conversion_service.pnl_history(history).await,
+        Err(e) => Err(e),
+    };
+    match history {
+        Ok(history) => Ok(Json(json!({
+            "success": true,
+            "data": history
+        }))),
+        Err(crate::models::TradingError::ConfigError(e)) => {
+            tracing::warn!("Invalid PnL history request: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            tracing::error!("Failed to get PnL history: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 查询当前账户的结算币种
+pub async fn get_settlement_currency(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state.conversion_service.settlement_currency(user_id).await {
+        Ok(currency) => Ok(Json(json!({
+            "success": true,
+            "data": {
+                "currency": currency,
+                "precision": currency.precision()
+            }
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to get settlement currency: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 设置当前账户的结算币种，余额、盈亏和对账单按该币种折算
+pub async fn set_settlement_currency(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<SetSettlementCurrencyRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state
+        .conversion_service
+        .set_settlement_currency(user_id, request.currency)
+        .await
+    {
+        Ok(()) => {
+            state
+                .activity_service
+                .record(
+                    user_id,
+                    ActivityCategory::Settings,
+                    "settlement_currency_changed",
+                    format!("Settlement currency set to {}", request.currency),
+                    json!({ "currency": request.currency }),
+                )
+                .await;
+            Ok(Json(json!({
+                "success": true,
+                "data": {
+                    "currency": request.currency,
+                    "precision": request.currency.precision()
+                }
+            })))
+        }
+        Err(e) => {
+            tracing::error!("Failed to set settlement currency: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 查询当前账户允许交易的环境
+pub async fn get_environment_access(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state.account_service.environment_access(user_id).await {
+        Ok(access) => Ok(Json(json!({
+            "success": true,
+            "data": {
+                "access": access,
+                "testnet_exchanges": state.config.execution.testnet.exchanges
+            }
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to get environment access: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 设置当前账户允许交易的环境：live_only / testnet_only / both
+pub async fn set_environment_access(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<SetEnvironmentAccessRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state
+        .account_service
+        .set_environment_access(user_id, request.access)
+        .await
+    {
+        Ok(()) => {
+            state
+                .activity_service
+                .record(
+                    user_id,
+                    ActivityCategory::Settings,
+                    "environment_access_changed",
+                    format!("Trading environment access set to {}", request.access),
+                    json!({ "access": request.access }),
+                )
+                .await;
+            Ok(Json(json!({
+                "success": true,
+                "data": {
+                    "access": request.access
+                }
+            })))
+        }
+        Err(e) => {
+            tracing::error!("Failed to set environment access: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
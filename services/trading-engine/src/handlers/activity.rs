@@ -0,0 +1,282 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Json as RequestJson,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::authenticated_user;
+use crate::{
+    models::{AccountActivity, ActivityCategory, ActivityCursor, ActivityQuery},
+    state::AppState,
+    storage::account_activity_store::MAX_ACTIVITY_PAGE,
+};
+
+/// 导出单次最多读取的动态数
+const MAX_EXPORT_ROWS: usize = 50_000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListActivityQuery {
+    /// 逗号分隔的类型：order、auth、api_key、risk_config、settings，不传返回全部
+    pub types: Option<String>,
+    /// 起始时间（毫秒时间戳，包含）
+    pub start_time: Option<i64>,
+    /// 结束时间（毫秒时间戳，不包含）
+    pub end_time: Option<i64>,
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+fn millis(value: i64) -> Result<DateTime<Utc>, StatusCode> {
+    Utc.timestamp_millis_opt(value)
+        .single()
+        .ok_or(StatusCode::BAD_REQUEST)
+}
+
+/// 解析查询参数
+fn build_query(params: &ListActivityQuery, default_limit: u32) -> Result<ActivityQuery, StatusCode> {
+    let categories = params
+        .types
+        .as_deref()
+        .map(|types| {
+            types
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(|t| t.parse::<ActivityCategory>().map_err(|_| StatusCode::BAD_REQUEST))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let start_time = params.start_time.map(millis).transpose()?;
+    let end_time = params.end_time.map(millis).transpose()?;
+    if let (Some(start), Some(end)) = (start_time, end_time) {
+        if start >= end {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(|c| ActivityCursor::decode(c).map_err(|_| StatusCode::BAD_REQUEST))
+        .transpose()?;
+
+    Ok(ActivityQuery {
+        categories,
+        start_time,
+        end_time,
+        cursor,
+        limit: params.limit.unwrap_or(default_limit),
+    })
+}
+
+/// 查询当前用户的账户动态时间线
+pub async fn list_activity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ListActivityQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let query = build_query(&params, 50)?;
+
+    let (activities, has_more) = state
+        .activity_service
+        .list(user_id, &query)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list account activity: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let next_cursor = if has_more {
+        activities.last().map(|activity| ActivityCursor::from_activity(activity).encode())
+    } else {
+        None
+    };
+
+    Ok(Json(json!({
+        "success": true,
+        "data": activities,
+        "pagination": {
+            "count": activities.len(),
+            "has_more": has_more,
+            "next_cursor": next_cursor
+        }
+    })))
+}
+
+/// CSV字段：统一加引号并转义双引号，以 = + - @ 等开头的值加 ' 前缀，
+/// 避免外部上报的内容在表格软件中被当作公式执行
+fn csv_field(value: &str) -> String {
+    let escaped = value.replace('"', "\"\"");
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("\"'{}\"", escaped)
+    } else {
+        format!("\"{}\"", escaped)
+    }
+}
+
+fn csv_row(activity: &AccountActivity) -> String {
+    let fields = [
+        activity.occurred_at.to_rfc3339(),
+        activity.id.to_string(),
+        activity.category.to_string(),
+        activity.action.clone(),
+        activity.summary.clone(),
+        activity.source.clone(),
+    ];
+    let mut row = fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",");
+    row.push('\n');
+    row
+}
+
+/// 按相同过滤条件导出账户动态CSV
+pub async fn export_activity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ListActivityQuery>,
+) -> Result<Response, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let mut query = build_query(&params, MAX_ACTIVITY_PAGE)?;
+    query.limit = MAX_ACTIVITY_PAGE;
+
+    let mut body = String::from("occurred_at,activity_id,type,action,summary,source\n");
+    let mut rows = 0;
+    loop {
+        let (activities, has_more) = state
+            .activity_service
+            .list(user_id, &query)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to export account activity: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        for activity in &activities {
+            body.push_str(&csv_row(activity));
+        }
+        rows += activities.len();
+        match activities.last() {
+            Some(last) if has_more && rows < MAX_EXPORT_ROWS => {
+                query.cursor = Some(ActivityCursor::from_activity(last));
+            }
+            _ => break,
+        }
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"account_activity.csv\""),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordActivityRequest {
+    /// 上报方生成的事件ID，重复上报时去重
+    pub id: Option<Uuid>,
+    pub user_id: Uuid,
+    pub category: ActivityCategory,
+    pub action: String,
+    pub summary: String,
+    #[serde(default)]
+    pub details: Value,
+    pub source: String,
+    /// 发生时间（毫秒时间戳），默认接收时间
+    pub occurred_at: Option<i64>,
+}
+
+/// 认证服务上报登录和API密钥等账户动态
+///
+/// 订单和风控配置动态由本服务的数据生成，不接受外部上报。
+pub async fn record_activity(
+    State(state): State<AppState>,
+    RequestJson(request): RequestJson<RecordActivityRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if matches!(request.category, ActivityCategory::Order | ActivityCategory::RiskConfig) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let mut activity = AccountActivity::new(
+        request.user_id,
+        request.category,
+        &request.action,
+        request.summary,
+        request.details,
+    );
+    if let Some(id) = request.id {
+        activity.id = id;
+    }
+    if let Some(occurred_at) = request.occurred_at {
+        activity.occurred_at = millis(occurred_at)?;
+    }
+    activity.source = request.source;
+
+    match state.activity_service.ingest(&activity).await {
+        Ok(()) => Ok(Json(json!({
+            "success": true,
+            "data": activity
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to record account activity for user {}: {}", activity.user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query() {
+        let params = ListActivityQuery {
+            types: Some("order, api_key".to_string()),
+            start_time: Some(1_700_000_000_000),
+            ..ListActivityQuery::default()
+        };
+        let query = build_query(&params, 50).unwrap();
+        assert_eq!(query.categories, vec![ActivityCategory::Order, ActivityCategory::ApiKey]);
+        assert_eq!(query.limit, 50);
+        assert!(query.start_time.is_some());
+
+        let all = build_query(&ListActivityQuery::default(), 50).unwrap();
+        assert!(all.categories.is_empty());
+
+        let unknown = ListActivityQuery {
+            types: Some("order,logins".to_string()),
+            ..ListActivityQuery::default()
+        };
+        assert_eq!(build_query(&unknown, 50).unwrap_err(), StatusCode::BAD_REQUEST);
+
+        let reversed = ListActivityQuery {
+            start_time: Some(2),
+            end_time: Some(1),
+            ..ListActivityQuery::default()
+        };
+        assert!(build_query(&reversed, 50).is_err());
+    }
+
+    #[test]
+    fn test_csv_row_escapes_fields() {
+        let mut activity = AccountActivity::new(
+            Uuid::new_v4(),
+            ActivityCategory::ApiKey,
+            "=HYPERLINK(\"http://x\")",
+            "Created key \"bot\", scopes: read\ntrade".to_string(),
+            Value::Null,
+        );
+        activity.source = "@auth".to_string();
+
+        let row = csv_row(&activity);
+        assert!(row.ends_with(",\"'@auth\"\n"));
+        assert!(row.contains(",\"'=HYPERLINK(\"\"http://x\"\")\","));
+        assert!(row.contains(",\"Created key \"\"bot\"\", scopes: read\ntrade\","));
+        assert_eq!(csv_field("-1"), "\"'-1\"");
+        assert_eq!(csv_field("login"), "\"login\"");
+    }
+}
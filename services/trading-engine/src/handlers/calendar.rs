@@ -0,0 +1,152 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Json as RequestJson,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    models::{InstrumentCalendar, MaintenanceSource, MaintenanceWindow, Timestamp},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SessionStatusQuery {
+    pub at: Option<Timestamp>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceQuery {
+    pub symbol: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateMaintenanceRequest {
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub reason: String,
+    pub source: Option<MaintenanceSource>,
+}
+
+/// 查询交易对日历及当前交易状态
+pub async fn get_calendar(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<SessionStatusQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let at = query.at.unwrap_or_else(Utc::now);
+    let calendar = state.calendar_service.get_calendar(&symbol).await;
+    let status = state.calendar_service.get_session_status(&symbol, at).await;
+    let maintenance = state
+        .calendar_service
+        .list_maintenance_windows(Some(&symbol))
+        .await;
+
+    let response = json!({
+        "success": true,
+        "data": {
+            "calendar": calendar,
+            "status": status,
+            "maintenance_windows": maintenance
+        }
+    });
+    Ok(Json(response))
+}
+
+/// 更新交易对日历
+pub async fn set_calendar(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    RequestJson(mut calendar): RequestJson<InstrumentCalendar>,
+) -> Result<Json<Value>, StatusCode> {
+    calendar.symbol = symbol;
+
+    match state.calendar_service.set_calendar(calendar.clone()).await {
+        Ok(()) => {
+            let response = json!({
+                "success": true,
+                "data": calendar,
+                "message": "Trading calendar updated"
+            });
+            Ok(Json(response))
+        }
+        Err(e) => {
+            tracing::error!("Failed to update trading calendar: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// 查询维护窗口
+pub async fn list_maintenance_windows(
+    State(state): State<AppState>,
+    Query(query): Query<MaintenanceQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let windows = state
+        .calendar_service
+        .list_maintenance_windows(query.symbol.as_deref())
+        .await;
+
+    let response = json!({
+        "success": true,
+        "data": windows
+    });
+    Ok(Json(response))
+}
+
+/// 登记维护窗口
+pub async fn create_maintenance_window(
+    State(state): State<AppState>,
+    RequestJson(request): RequestJson<CreateMaintenanceRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let window = MaintenanceWindow::new(
+        request.symbols,
+        request.start_time,
+        request.end_time,
+        request.reason,
+        request
+            .source
+            .unwrap_or(MaintenanceSource::ExchangeAnnouncement),
+    );
+
+    match state.calendar_service.add_maintenance_window(window).await {
+        Ok(window) => {
+            let response = json!({
+                "success": true,
+                "data": window,
+                "message": "Maintenance window scheduled"
+            });
+            Ok(Json(response))
+        }
+        Err(e) => {
+            tracing::error!("Failed to schedule maintenance window: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// 删除维护窗口
+pub async fn delete_maintenance_window(
+    State(state): State<AppState>,
+    Path(window_id): Path<Uuid>,
+) -> Result<Json<Value>, StatusCode> {
+    if state
+        .calendar_service
+        .remove_maintenance_window(window_id)
+        .await
+    {
+        let response = json!({
+            "success": true,
+            "message": "Maintenance window removed"
+        });
+        Ok(Json(response))
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
@@ -0,0 +1,33 @@
+use axum::{extract::State, http::HeaderMap, http::StatusCode, response::Json};
+use serde_json::{json, Value};
+
+use super::authenticated_user;
+use crate::state::AppState;
+
+/// 当前用户的功能开关状态
+pub async fn list_feature_flags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?.to_string();
+
+    let flags: Vec<Value> = state
+        .feature_flags
+        .list()
+        .await
+        .into_iter()
+        .map(|flag| {
+            json!({
+                "name": flag.name,
+                "enabled": flag.evaluate(Some(&user_id)),
+                "description": flag.description,
+                "updated_at": flag.updated_at,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "success": true,
+        "data": flags
+    })))
+}
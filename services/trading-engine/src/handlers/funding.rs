@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    models::{Symbol, Timestamp},
+    state::AppState,
+};
+
+/// 资金费率历史默认查询天数
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+/// 资金费率历史默认条数
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+/// 资金费率历史最大条数
+const MAX_HISTORY_LIMIT: u32 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct FundingHistoryQuery {
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub limit: Option<u32>,
+}
+
+/// 查询交易对的历史资金费率和下一次资金费预测
+pub async fn get_funding_history(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<FundingHistoryQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let symbol = symbol
+        .parse::<Symbol>()
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+        .to_string();
+    let end = query.end_time.unwrap_or_else(Utc::now);
+    let start = query
+        .start_time
+        .unwrap_or(end - Duration::days(DEFAULT_HISTORY_DAYS));
+    if start >= end {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+
+    match state
+        .settlement_service
+        .funding_history(&symbol, start, end, limit)
+        .await
+    {
+        Ok(history) => Ok(Json(json!({
+            "success": true,
+            "data": {
+                "symbol": symbol,
+                "start_time": start,
+                "end_time": end,
+                "history": history,
+                "predicted": state.settlement_service.predicted_funding(&symbol)
+            }
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to query funding history for {}: {}", symbol, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
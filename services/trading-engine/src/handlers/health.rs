@@ -5,7 +5,8 @@ use axum::{
     Json,
 };
 use chrono::Utc;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
 use crate::state::AppState;
 
@@ -24,6 +25,71 @@ pub async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
     Json(health_status)
 }
 
+/// 运维统计，供网关汇总总览
+///
+/// 下单频率只统计经本副本提交的订单；风险告警为未解决事件数，按严重程度分组。
+pub async fn service_stats(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let orders = state.order_rate_service.totals().await;
+
+    let mut risk_alerts: BTreeMap<String, i64> = BTreeMap::new();
+    let queues = state.risk_event_service.queue_depths().await.map_err(|e| {
+        tracing::error!("Failed to count risk events: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    for (severity, _, count) in queues {
+        *risk_alerts.entry(severity.to_string()).or_default() += count;
+    }
+    let open_alerts: i64 = risk_alerts.values().sum();
+
+    let outbox = state.outbox_store.backlog().await.map_err(|e| {
+        tracing::error!("Failed to read outbox backlog: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let outbox_lag_seconds = outbox
+        .oldest_pending_at
+        .map(|oldest| (Utc::now() - oldest).num_seconds().max(0))
+        .unwrap_or(0);
+
+    let slow_venues: Vec<String> = state
+        .execution_engine
+        .slow_venue_report()
+        .await
+        .slow_venues()
+        .map(str::to_string)
+        .collect();
+    let venues: Vec<Value> = state
+        .execution_engine
+        .registered_exchanges()
+        .await
+        .into_iter()
+        .map(|(name, environment)| {
+            json!({
+                "name": name,
+                "environment": environment,
+                "slow": slow_venues.contains(&name)
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "orders": orders,
+            "risk_alerts": {
+                "open": open_alerts,
+                "by_severity": risk_alerts
+            },
+            "outbox": {
+                "pending": outbox.pending,
+                "oldest_pending_at": outbox.oldest_pending_at,
+                "lag_seconds": outbox_lag_seconds
+            },
+            "venues": venues,
+            "timestamp": Utc::now()
+        }
+    })))
+}
+
 /// Prometheus 指标
 pub async fn metrics(State(state): State<AppState>) -> Response {
     match state.metrics.gather() {
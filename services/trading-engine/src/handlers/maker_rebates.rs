@@ -0,0 +1,35 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct MakerRebateQuery {
+    pub symbol: Option<String>,
+}
+
+/// 查询内部订单簿当前的挂单返佣，供做市方调整挂单
+pub async fn list_maker_rebates(
+    State(state): State<AppState>,
+    Query(query): Query<MakerRebateQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let config = &state.config.execution.maker_rebates;
+    let rebates = state.maker_rebates.rebates(query.symbol.as_deref()).await;
+
+    let response = json!({
+        "success": true,
+        "data": {
+            "enabled": config.enabled,
+            "imbalance_threshold": config.imbalance_threshold,
+            "min_rebate_rate": config.min_rebate_rate,
+            "max_rebate_rate": config.max_rebate_rate,
+            "rebates": rebates
+        }
+    });
+    Ok(Json(response))
+}
@@ -1,14 +1,46 @@
 use axum::{
-    routing::{delete, get, post, put},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, patch, post, put},
     Router,
 };
+use uuid::Uuid;
 
 use crate::state::AppState;
 
 pub mod accounts;
+pub mod activity;
+pub mod calendar;
+pub mod feature_flags;
+pub mod funding;
 pub mod health;
+pub mod maker_rebates;
+pub mod notifications;
+pub mod order_replay;
 pub mod orders;
+pub mod portfolio_stop;
 pub mod positions;
+pub mod referrals;
+pub mod risk_events;
+pub mod sandbox;
+pub mod scheduled_orders;
+pub mod settlements;
+pub mod spread_orders;
+pub mod tax;
+pub mod trades;
+pub mod verification;
+pub mod volatility;
+
+/// 网关鉴权后转发的用户ID请求头
+pub const USER_ID_HEADER: &str = "x-user-id";
+
+/// 从网关转发的请求头中读取当前用户
+pub fn authenticated_user(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    headers
+        .get(USER_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Uuid>().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
 
 pub fn create_routes() -> Router<AppState> {
     Router::new()
@@ -21,6 +53,32 @@ pub fn create_routes() -> Router<AppState> {
         .route("/api/v1/orders/:id", put(orders::update_order))
         .route("/api/v1/orders/:id", delete(orders::cancel_order))
         .route("/api/v1/orders/batch", post(orders::batch_orders))
+        .route("/api/v1/orders/preview", post(orders::preview_order))
+        .route("/api/v1/orders/spread", post(spread_orders::create_spread_order))
+        .route(
+            "/api/v1/orders/scheduled",
+            post(scheduled_orders::create_scheduled_order),
+        )
+        .route(
+            "/api/v1/orders/scheduled",
+            get(scheduled_orders::list_scheduled_orders),
+        )
+        .route(
+            "/api/v1/orders/scheduled/:id",
+            get(scheduled_orders::get_scheduled_order),
+        )
+        .route(
+            "/api/v1/orders/scheduled/:id",
+            delete(scheduled_orders::cancel_scheduled_order),
+        )
+        .route(
+            "/api/v1/orders/strategy-tag",
+            post(orders::retag_strategy_orders),
+        )
+        // 成交记录
+        .route("/api/v1/trades", get(trades::list_trades))
+        .route("/api/v1/trades/export", get(trades::export_trades))
+        .route("/api/v1/trades/fees", get(trades::fee_report))
         // 仓位管理
         .route("/api/v1/positions", get(positions::list_positions))
         .route("/api/v1/positions/:symbol", get(positions::get_position))
@@ -34,6 +92,77 @@ pub fn create_routes() -> Router<AppState> {
         .route("/api/v1/account/balance", get(accounts::get_balance))
         .route("/api/v1/account/margin", get(accounts::get_margin_info))
         .route("/api/v1/account/pnl", get(accounts::get_pnl))
+        .route("/api/v1/account/pnl/history", get(accounts::get_pnl_history))
+        .route(
+            "/api/v1/account/settlement-currency",
+            get(accounts::get_settlement_currency),
+        )
+        .route(
+            "/api/v1/account/settlement-currency",
+            put(accounts::set_settlement_currency),
+        )
+        .route("/api/v1/account/environment", get(accounts::get_environment_access))
+        .route("/api/v1/account/environment", put(accounts::set_environment_access))
+        .route("/api/v1/account/activity", get(activity::list_activity))
+        .route("/api/v1/account/activity/export", get(activity::export_activity))
+        .route("/api/v1/account/portfolio-stop", get(portfolio_stop::get_portfolio_stop))
+        .route("/api/v1/account/portfolio-stop", put(portfolio_stop::set_portfolio_stop))
+        .route("/api/v1/account/portfolio-stop", delete(portfolio_stop::delete_portfolio_stop))
+        .route(
+            "/api/v1/account/portfolio-stop/unlock",
+            post(portfolio_stop::unlock_trading),
+        )
+        .route(
+            "/api/v1/account/portfolio-stop/events",
+            get(portfolio_stop::list_portfolio_stop_events),
+        )
+        .route("/api/v1/account/statements", get(settlements::list_statements))
+        .route(
+            "/api/v1/account/statements/:date",
+            get(settlements::download_statement),
+        )
+        // 资金费
+        .route("/api/v1/funding/history/:symbol", get(funding::get_funding_history))
+        // 站内通知
+        .route("/api/v1/notifications", get(notifications::list_notifications))
+        .route("/api/v1/notifications/read", post(notifications::mark_notifications_read))
+        // 推荐返佣
+        .route("/api/v1/referrals/code", get(referrals::get_referral_code))
+        .route("/api/v1/referrals/attribute", post(referrals::attribute_referral))
+        .route("/api/v1/referrals/earnings", get(referrals::get_referral_earnings))
+        .route("/api/v1/referrals/ledger", get(referrals::list_referral_ledger))
+        // 模拟盘
+        .route("/api/v1/sandbox", post(sandbox::provision_sandbox))
+        .route("/api/v1/sandbox", get(sandbox::get_sandbox))
+        .route("/api/v1/sandbox/reset", post(sandbox::reset_sandbox))
+        .route("/api/v1/sandbox/orders", post(sandbox::create_sandbox_order))
+        .route("/api/v1/sandbox/fills", get(sandbox::list_sandbox_fills))
+        .route("/api/v1/sandbox/stats", get(sandbox::get_sandbox_stats))
+        // 税务报告
+        .route("/api/v1/tax/report", get(tax::get_tax_report))
+        .route("/api/v1/tax/report/export", get(tax::export_tax_report))
+        // 功能开关
+        .route("/api/v1/feature-flags", get(feature_flags::list_feature_flags))
+        // 交易日历
+        .route(
+            "/api/v1/calendar/maintenance",
+            get(calendar::list_maintenance_windows),
+        )
+        .route(
+            "/api/v1/calendar/maintenance",
+            post(calendar::create_maintenance_window),
+        )
+        .route(
+            "/api/v1/calendar/maintenance/:id",
+            delete(calendar::delete_maintenance_window),
+        )
+        .route("/api/v1/calendar/:symbol", get(calendar::get_calendar))
+        .route("/api/v1/calendar/:symbol", put(calendar::set_calendar))
+        // 挂单返佣
+        .route("/api/v1/maker-rebates", get(maker_rebates::list_maker_rebates))
+        // 波动分档执行限制
+        .route("/api/v1/execution/volatility", get(volatility::list_volatility_regimes))
+        .route("/api/v1/execution/volatility/:symbol", put(volatility::update_volatility))
         // WebSocket
         .route(
             "/ws/orders",
@@ -47,6 +176,36 @@ pub fn create_routes() -> Router<AppState> {
             "/ws/account",
             get(crate::websocket::account::account_websocket),
         )
+        .route(
+            "/ws/account/equity",
+            get(crate::websocket::equity::equity_websocket),
+        )
+        .route(
+            "/ws/internal/book",
+            get(crate::websocket::internal_book::internal_book_websocket),
+        )
+        // 运维统计
+        .route("/api/v1/admin/stats", get(health::service_stats))
+        // 认证服务上报的账户动态
+        .route("/api/v1/admin/account-activity", post(activity::record_activity))
+        // 日终结算运行
+        .route("/api/v1/admin/settlements/:date", get(settlements::get_settlement_run))
+        .route("/api/v1/admin/settlements/:date/run", post(settlements::run_settlement))
+        // 风险事件队列，查询需要 support 角色，确认和解决需要 ops 角色
+        .route("/api/v1/admin/risk/events", get(risk_events::list_risk_events))
+        .route("/api/v1/admin/risk/events/:id", get(risk_events::get_risk_event))
+        .route("/api/v1/admin/risk/events/:id", patch(risk_events::update_risk_event))
+        // 订单流重放
+        .route("/api/v1/admin/order-replay", get(order_replay::replay_orders))
+        // 用户认证等级
+        .route(
+            "/api/v1/admin/users/:user_id/verification",
+            get(verification::get_verification),
+        )
+        .route(
+            "/api/v1/admin/users/:user_id/verification",
+            put(verification::set_verification),
+        )
         // 指标
         .route("/metrics", get(crate::handlers::health::metrics))
 }
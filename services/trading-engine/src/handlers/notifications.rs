@@ -0,0 +1,101 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::authenticated_user;
+use crate::{
+    models::{NotificationCategory, Timestamp, TradingError},
+    state::AppState,
+};
+
+/// 通知默认条数
+const DEFAULT_LIST_LIMIT: u32 = 50;
+/// 通知最大条数
+const MAX_LIST_LIMIT: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ListNotificationsQuery {
+    pub category: Option<NotificationCategory>,
+    #[serde(default)]
+    pub unread_only: bool,
+    /// 上一页最后一条通知的创建时间
+    pub before: Option<Timestamp>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkReadRequest {
+    /// 指定通知ID，不指定时按类别或全部标记
+    pub ids: Option<Vec<Uuid>>,
+    pub category: Option<NotificationCategory>,
+}
+
+fn notification_error(action: &str, e: TradingError) -> StatusCode {
+    tracing::error!("Failed to {} notifications: {}", action, e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// 按时间倒序查询当前用户的通知，附带各类别未读数
+pub async fn list_notifications(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<ListNotificationsQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+
+    let notifications = state
+        .notification_service
+        .list(user_id, query.category, query.unread_only, query.before, limit)
+        .await
+        .map_err(|e| notification_error("list", e))?;
+    let unread = state
+        .notification_service
+        .unread_counts(user_id)
+        .await
+        .map_err(|e| notification_error("count", e))?;
+    let unread_total: i64 = unread.iter().map(|(_, count)| count).sum();
+    let unread: serde_json::Map<String, Value> = unread
+        .into_iter()
+        .map(|(category, count)| (category.to_string(), json!(count)))
+        .collect();
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "notifications": notifications,
+            "unread": unread,
+            "unread_total": unread_total
+        }
+    })))
+}
+
+/// 批量标记已读
+pub async fn mark_notifications_read(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    RequestJson(request): RequestJson<MarkReadRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let marked = state
+        .notification_service
+        .mark_read(user_id, request.ids, request.category)
+        .await
+        .map_err(|e| notification_error("mark", e))?;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "marked": marked
+        }
+    })))
+}
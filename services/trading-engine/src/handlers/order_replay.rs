@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Duration;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    engines::order_replay::replay_order_flow,
+    models::{Symbol, Timestamp},
+    state::AppState,
+};
+
+/// 单次重放的最大时间窗口
+const MAX_REPLAY_WINDOW_HOURS: i64 = 24;
+
+#[derive(Debug, Deserialize)]
+pub struct OrderReplayQuery {
+    pub symbol: String,
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+/// 在离线撮合引擎中重放指定交易对和时间窗口的订单流，逐步输出撮合结果并与记录的成交对比
+pub async fn replay_orders(
+    State(state): State<AppState>,
+    Query(query): Query<OrderReplayQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    if query.end <= query.start || query.end - query.start > Duration::hours(MAX_REPLAY_WINDOW_HOURS) {
+        tracing::warn!(
+            "Rejected order replay window {} - {}",
+            query.start,
+            query.end
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let symbol = Symbol::from_string(&query.symbol.to_uppercase()).ok_or(StatusCode::BAD_REQUEST)?;
+
+    let orders = state
+        .order_store
+        .list_symbol_orders_in_window(&symbol.to_string(), query.start, query.end)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load orders for replay: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let order_ids: Vec<Uuid> = orders.iter().map(|o| o.id).collect();
+    let fills = state
+        .trade_store
+        .list_fills_for_orders(&order_ids)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load fills for replay: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let report = replay_order_flow(symbol, &orders, &fills, query.start, query.end).await;
+    tracing::info!(
+        "Replayed {} order events for {}, {} fill discrepancies",
+        report.steps.len(),
+        report.symbol,
+        report.discrepancies.len()
+    );
+
+    let response = json!({
+        "success": true,
+        "data": report
+    });
+    Ok(Json(response))
+}
@@ -1,16 +1,18 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
     Json as RequestJson,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use shared_utils::LogContext;
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use super::authenticated_user;
 use crate::{
-    models::{CreateOrderRequest, Order, OrderStatus},
+    models::{CreateOrderRequest, Order, OrderStatus, TradingError},
     services::OrderService,
     state::AppState,
 };
@@ -34,13 +36,29 @@ pub struct BatchOrderRequest {
     pub orders: Vec<CreateOrderRequest>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PreviewOrderRequest {
+    #[serde(flatten)]
+    pub order: CreateOrderRequest,
+    /// 杠杆倍数，用于估算保证金占用，默认1倍
+    pub leverage: Option<rust_decimal::Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetagOrdersRequest {
+    /// 原策略标签，例如 strategy:grid@1.2.0
+    pub from_tag: String,
+    pub to_tag: String,
+}
+
 /// 创建订单
 pub async fn create_order(
     State(state): State<AppState>,
+    headers: HeaderMap,
     RequestJson(request): RequestJson<CreateOrderRequest>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
+    LogContext::record_symbol(&request.symbol);
 
     match state.order_service.create_order(user_id, request).await {
         Ok(order) => {
@@ -63,13 +81,41 @@ pub async fn create_order(
     }
 }
 
+/// 预览订单：估算成交均价、滑点、手续费和保证金占用，不下单
+pub async fn preview_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<PreviewOrderRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+
+    match state
+        .order_service
+        .preview_order(user_id, request.order, request.leverage)
+        .await
+    {
+        Ok(preview) => Ok(Json(json!({
+            "success": true,
+            "data": preview
+        }))),
+        Err(TradingError::InvalidOrder(e)) => {
+            tracing::warn!("Invalid order preview request: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            tracing::error!("Failed to preview order: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 /// 查询订单列表
 pub async fn list_orders(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<ListOrdersQuery>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     let limit = query.limit.unwrap_or(50).min(100);
     let offset = query.offset.unwrap_or(0);
@@ -106,10 +152,10 @@ pub async fn list_orders(
 /// 查询单个订单
 pub async fn get_order(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(order_id): Path<Uuid>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     match state.order_service.get_order(user_id, order_id).await {
         Ok(Some(order)) => {
@@ -142,11 +188,11 @@ pub async fn get_order(
 /// 修改订单
 pub async fn update_order(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(order_id): Path<Uuid>,
     RequestJson(request): RequestJson<UpdateOrderRequest>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     match state
         .order_service
@@ -176,10 +222,10 @@ pub async fn update_order(
 /// 取消订单
 pub async fn cancel_order(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(order_id): Path<Uuid>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     match state.order_service.cancel_order(user_id, order_id).await {
         Ok(order) => {
@@ -205,10 +251,10 @@ pub async fn cancel_order(
 /// 批量操作订单
 pub async fn batch_orders(
     State(state): State<AppState>,
+    headers: HeaderMap,
     RequestJson(request): RequestJson<BatchOrderRequest>,
 ) -> Result<Json<Value>, StatusCode> {
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let user_id = authenticated_user(&headers)?;
 
     let mut results = Vec::new();
     let mut success_count = 0;
@@ -248,4 +294,28 @@ pub async fn batch_orders(
     } else {
         Ok(Json(response))
     }
-}
\ No newline at end of file
+}
+/// 替换活跃订单的策略标签（策略版本回滚时调用）
+pub async fn retag_strategy_orders(
+    State(state): State<AppState>,
+    RequestJson(request): RequestJson<RetagOrdersRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    if request.from_tag.is_empty() || request.to_tag.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match state
+        .order_service
+        .retag_strategy_orders(&request.from_tag, &request.to_tag)
+        .await
+    {
+        Ok(updated) => Ok(Json(json!({
+            "success": true,
+            "data": { "updated": updated }
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to retag orders: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::authenticated_user;
+use crate::{
+    models::{DrawdownBasis, TradingError},
+    state::AppState,
+};
+
+/// 审计事件默认条数
+const DEFAULT_EVENT_LIMIT: u32 = 100;
+/// 审计事件最大条数
+const MAX_EVENT_LIMIT: u32 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct SetPortfolioStopRequest {
+    pub basis: DrawdownBasis,
+    /// 最大回撤比例，例如 0.1 表示 10%
+    pub max_drawdown: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PortfolioStopEventsQuery {
+    pub limit: Option<u32>,
+}
+
+fn portfolio_stop_error(action: &str, e: TradingError) -> StatusCode {
+    match e {
+        TradingError::ConfigError(_) => {
+            tracing::warn!("Rejected portfolio stop {}: {}", action, e);
+            StatusCode::BAD_REQUEST
+        }
+        TradingError::RiskViolation(_) => {
+            tracing::warn!("Rejected portfolio stop {}: {}", action, e);
+            StatusCode::CONFLICT
+        }
+        e => {
+            tracing::error!("Failed to {} portfolio stop: {}", action, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// 查询当前用户的账户级止损
+pub async fn get_portfolio_stop(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state.portfolio_stop_service.get(user_id).await {
+        Ok(Some(stop)) => Ok(Json(json!({
+            "success": true,
+            "data": stop
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(portfolio_stop_error("query", e)),
+    }
+}
+
+/// 设置账户级止损，以当前权益为回撤基准
+pub async fn set_portfolio_stop(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<SetPortfolioStopRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state
+        .portfolio_stop_service
+        .configure(user_id, request.basis, request.max_drawdown)
+        .await
+    {
+        Ok(stop) => Ok(Json(json!({
+            "success": true,
+            "data": stop
+        }))),
+        Err(e) => Err(portfolio_stop_error("set", e)),
+    }
+}
+
+/// 删除账户级止损，锁定期间需先解锁
+pub async fn delete_portfolio_stop(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state.portfolio_stop_service.remove(user_id).await {
+        Ok(true) => Ok(Json(json!({
+            "success": true,
+            "message": "Portfolio stop removed"
+        }))),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(portfolio_stop_error("remove", e)),
+    }
+}
+
+/// 止损触发后手动解锁交易
+pub async fn unlock_trading(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state.portfolio_stop_service.unlock(user_id).await {
+        Ok(Some(stop)) => Ok(Json(json!({
+            "success": true,
+            "data": stop
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(portfolio_stop_error("unlock", e)),
+    }
+}
+
+/// 查询账户级止损的审计事件
+pub async fn list_portfolio_stop_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PortfolioStopEventsQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_EVENT_LIMIT)
+        .clamp(1, MAX_EVENT_LIMIT);
+
+    match state.portfolio_stop_service.events(user_id, limit).await {
+        Ok(events) => Ok(Json(json!({
+            "success": true,
+            "data": events
+        }))),
+        Err(e) => Err(portfolio_stop_error("list events of", e)),
+    }
+}
@@ -77,9 +77,15 @@ pub async fn get_position(
     match state.position_service.get_position(user_id, &symbol).await {
         Ok(Some(position)) => {
             let summary: PositionSummary = (&position).into();
+            let mut data = json!(summary);
+            // 资金费查询失败不影响返回持仓
+            match state.settlement_service.position_funding(&position).await {
+                Ok(funding) => data["funding"] = json!(funding),
+                Err(e) => tracing::warn!("Failed to load funding for position {}: {}", position.id, e),
+            }
             let response = json!({
                 "success": true,
-                "data": summary
+                "data": data
             });
             Ok(Json(response))
         }
@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::authenticated_user;
+use crate::{models::TradingError, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct AttributeReferralRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReferralLedgerQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// 获取当前用户的推荐码，不存在时生成
+pub async fn get_referral_code(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+
+    match state.referral_service.get_or_create_code(user_id).await {
+        Ok(code) => Ok(Json(json!({
+            "success": true,
+            "data": code
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to get referral code: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 注册时绑定推荐码
+pub async fn attribute_referral(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<AttributeReferralRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+
+    match state.referral_service.attribute_signup(user_id, &request.code).await {
+        Ok(attribution) => Ok(Json(json!({
+            "success": true,
+            "data": attribution
+        }))),
+        Err(TradingError::InvalidOrder(e)) => {
+            tracing::warn!("Rejected referral attribution for {}: {}", user_id, e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            tracing::error!("Failed to attribute referral: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 推荐收益概览
+pub async fn get_referral_earnings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+
+    match state.referral_service.earnings(user_id).await {
+        Ok(earnings) => Ok(Json(json!({
+            "success": true,
+            "data": earnings
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to get referral earnings: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 推荐账本明细
+pub async fn list_referral_ledger(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ReferralLedgerQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let limit = query.limit.unwrap_or(100).min(1000);
+    let offset = query.offset.unwrap_or(0);
+
+    match state.referral_service.ledger(user_id, limit, offset).await {
+        Ok(entries) => Ok(Json(json!({
+            "success": true,
+            "data": entries,
+            "count": entries.len()
+        }))),
+        Err(e) => {
+            tracing::error!("Failed to list referral ledger: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::authenticated_user;
+use crate::{
+    models::{RiskEventAction, RiskEventStatus, RiskSeverity, TradingError},
+    state::AppState,
+};
+
+/// 风险事件默认条数
+const DEFAULT_LIST_LIMIT: u32 = 100;
+/// 风险事件最大条数
+const MAX_LIST_LIMIT: u32 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct ListRiskEventsQuery {
+    pub status: Option<RiskEventStatus>,
+    /// 只返回不低于该严重程度的事件
+    pub min_severity: Option<RiskSeverity>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRiskEventRequest {
+    pub action: RiskEventAction,
+    /// 解决说明
+    pub note: Option<String>,
+}
+
+fn risk_event_error(action: &str, e: TradingError) -> StatusCode {
+    match e {
+        TradingError::RiskViolation(_) => {
+            tracing::warn!("Rejected risk event {}: {}", action, e);
+            StatusCode::CONFLICT
+        }
+        e => {
+            tracing::error!("Failed to {} risk event: {}", action, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// 按队列顺序查询风险事件，附带各严重程度未解决事件数
+pub async fn list_risk_events(
+    State(state): State<AppState>,
+    Query(query): Query<ListRiskEventsQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+
+    let events = state
+        .risk_event_service
+        .list(query.status, query.min_severity, limit)
+        .await
+        .map_err(|e| risk_event_error("list", e))?;
+    let queues: Vec<Value> = state
+        .risk_event_service
+        .queue_depths()
+        .await
+        .map_err(|e| risk_event_error("count", e))?
+        .into_iter()
+        .map(|(severity, status, count)| {
+            json!({
+                "severity": severity,
+                "status": status,
+                "count": count
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "events": events,
+            "queues": queues
+        }
+    })))
+}
+
+/// 查询单个风险事件
+pub async fn get_risk_event(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, StatusCode> {
+    match state.risk_event_service.get(id).await {
+        Ok(Some(event)) => Ok(Json(json!({
+            "success": true,
+            "data": event
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(risk_event_error("query", e)),
+    }
+}
+
+/// 值班人员确认或解决风险事件
+///
+/// 管理接口由内部认证限制为 ops 角色，操作人记录为网关转发的用户ID。
+pub async fn update_risk_event(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    RequestJson(request): RequestJson<UpdateRiskEventRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let operator = authenticated_user(&headers)?;
+    match state
+        .risk_event_service
+        .update(id, request.action, operator, request.note)
+        .await
+    {
+        Ok(Some(event)) => Ok(Json(json!({
+            "success": true,
+            "data": event
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(risk_event_error("update", e)),
+    }
+}
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::authenticated_user;
+use crate::{
+    models::{SandboxOrderRequest, TradingError},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SandboxFillsQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// 模拟盘关闭时按不存在处理
+fn sandbox_user(state: &AppState, headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    if !state.sandbox_service.is_enabled() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    authenticated_user(headers)
+}
+
+fn sandbox_error(action: &str, e: TradingError) -> StatusCode {
+    match e {
+        TradingError::InvalidOrder(_) | TradingError::InsufficientBalance { .. } => {
+            tracing::warn!("Rejected sandbox {}: {}", action, e);
+            StatusCode::BAD_REQUEST
+        }
+        TradingError::RiskLimitExceeded(_) => {
+            tracing::warn!("Rejected sandbox {}: {}", action, e);
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        e => {
+            tracing::error!("Failed to {} sandbox: {}", action, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// 开通模拟账户，已开通时返回现有账户
+pub async fn provision_sandbox(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = sandbox_user(&state, &headers)?;
+
+    match state.sandbox_service.provision(user_id).await {
+        Ok(account) => Ok(Json(json!({
+            "success": true,
+            "sandbox": true,
+            "data": account
+        }))),
+        Err(e) => Err(sandbox_error("provision", e)),
+    }
+}
+
+/// 获取模拟账户
+pub async fn get_sandbox(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = sandbox_user(&state, &headers)?;
+
+    match state.sandbox_service.account(user_id).await {
+        Ok(Some(account)) => Ok(Json(json!({
+            "success": true,
+            "sandbox": true,
+            "data": account
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(sandbox_error("load", e)),
+    }
+}
+
+/// 重置模拟账户
+pub async fn reset_sandbox(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = sandbox_user(&state, &headers)?;
+
+    match state.sandbox_service.reset(user_id).await {
+        Ok(account) => Ok(Json(json!({
+            "success": true,
+            "sandbox": true,
+            "data": account
+        }))),
+        Err(e) => Err(sandbox_error("reset", e)),
+    }
+}
+
+/// 模拟盘下单
+pub async fn create_sandbox_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<SandboxOrderRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = sandbox_user(&state, &headers)?;
+
+    match state.sandbox_service.place_order(user_id, request).await {
+        Ok(result) => Ok(Json(json!({
+            "success": true,
+            "sandbox": true,
+            "data": result
+        }))),
+        Err(e) => Err(sandbox_error("order", e)),
+    }
+}
+
+/// 模拟成交明细
+pub async fn list_sandbox_fills(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SandboxFillsQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = sandbox_user(&state, &headers)?;
+    let limit = query.limit.unwrap_or(100).min(1000);
+    let offset = query.offset.unwrap_or(0);
+
+    match state.sandbox_service.fills(user_id, limit, offset).await {
+        Ok(fills) => Ok(Json(json!({
+            "success": true,
+            "sandbox": true,
+            "data": fills,
+            "count": fills.len()
+        }))),
+        Err(e) => Err(sandbox_error("list fills of", e)),
+    }
+}
+
+/// 模拟盘统计
+pub async fn get_sandbox_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = sandbox_user(&state, &headers)?;
+
+    match state.sandbox_service.stats(user_id).await {
+        Ok(Some(stats)) => Ok(Json(json!({
+            "success": true,
+            "sandbox": true,
+            "data": stats
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(sandbox_error("load stats of", e)),
+    }
+}
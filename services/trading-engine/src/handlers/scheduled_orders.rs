@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use shared_utils::LogContext;
+use uuid::Uuid;
+
+use super::authenticated_user;
+use crate::{
+    models::{CreateScheduledOrderRequest, ScheduledOrderStatus, TradingError},
+    state::AppState,
+};
+
+/// 定时订单列表默认条数
+const DEFAULT_LIST_LIMIT: u32 = 50;
+/// 定时订单列表最大条数
+const MAX_LIST_LIMIT: u32 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListScheduledOrdersQuery {
+    pub status: Option<ScheduledOrderStatus>,
+    pub limit: Option<u32>,
+}
+
+fn scheduled_order_error(action: &str, e: TradingError) -> StatusCode {
+    match e {
+        TradingError::InvalidOrder(_) | TradingError::ConfigError(_) => {
+            tracing::warn!("Rejected scheduled order {}: {}", action, e);
+            StatusCode::BAD_REQUEST
+        }
+        TradingError::RiskViolation(_) => {
+            tracing::warn!("Rejected scheduled order {}: {}", action, e);
+            StatusCode::FORBIDDEN
+        }
+        e => {
+            tracing::error!("Failed to {} scheduled order: {}", action, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// 创建定时订单：到达激活时间时下单，到达撤单时间时撤销未成交部分
+pub async fn create_scheduled_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<CreateScheduledOrderRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    LogContext::record_symbol(&request.order.symbol);
+
+    match state.scheduled_order_service.create(user_id, request).await {
+        Ok(scheduled) => Ok(Json(json!({
+            "success": true,
+            "data": scheduled
+        }))),
+        Err(e) => Err(scheduled_order_error("create", e)),
+    }
+}
+
+/// 查询定时订单列表
+pub async fn list_scheduled_orders(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListScheduledOrdersQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+
+    match state
+        .scheduled_order_service
+        .list(user_id, query.status, limit)
+        .await
+    {
+        Ok(scheduled) => Ok(Json(json!({
+            "success": true,
+            "data": scheduled
+        }))),
+        Err(e) => Err(scheduled_order_error("list", e)),
+    }
+}
+
+/// 查询单个定时订单
+pub async fn get_scheduled_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state.scheduled_order_service.get(user_id, id).await {
+        Ok(Some(scheduled)) => Ok(Json(json!({
+            "success": true,
+            "data": scheduled
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(scheduled_order_error("get", e)),
+    }
+}
+
+/// 取消定时订单，已激活的同时撤销对应订单
+pub async fn cancel_scheduled_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    match state.scheduled_order_service.cancel(user_id, id).await {
+        Ok(Some(scheduled)) => Ok(Json(json!({
+            "success": true,
+            "data": scheduled
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(scheduled_order_error("cancel", e)),
+    }
+}
@@ -0,0 +1,146 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::authenticated_user;
+use crate::{
+    models::{StatementFormat, TradingError},
+    state::AppState,
+};
+
+/// 对账单列表默认条数
+const DEFAULT_STATEMENT_LIMIT: u32 = 30;
+/// 对账单列表最大条数
+const MAX_STATEMENT_LIMIT: u32 = 366;
+
+#[derive(Debug, Deserialize)]
+pub struct StatementListQuery {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    /// csv/pdf，默认csv
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunSettlementQuery {
+    /// 已完成的营业日是否按快照重新计提并生成对账单
+    #[serde(default)]
+    pub replay: bool,
+}
+
+fn settlement_error(action: &str, e: TradingError) -> StatusCode {
+    match e {
+        TradingError::ConfigError(_) => {
+            tracing::warn!("Rejected settlement {}: {}", action, e);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            tracing::error!("Failed to {} settlement: {}", action, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// 查询当前用户的日对账单列表
+pub async fn list_statements(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<StatementListQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_STATEMENT_LIMIT)
+        .clamp(1, MAX_STATEMENT_LIMIT);
+
+    match state.settlement_service.list_statements(user_id, limit).await {
+        Ok(statements) => Ok(Json(json!({
+            "success": true,
+            "data": statements
+        }))),
+        Err(e) => Err(settlement_error("list", e)),
+    }
+}
+
+/// 下载指定营业日的对账单
+pub async fn download_statement(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(business_date): Path<NaiveDate>,
+    Query(query): Query<StatementQuery>,
+) -> Result<Response, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let format = query
+        .format
+        .as_deref()
+        .map(|f| f.parse::<StatementFormat>().map_err(|_| StatusCode::BAD_REQUEST))
+        .transpose()?
+        .unwrap_or(StatementFormat::Csv);
+
+    let content = state
+        .settlement_service
+        .statement(user_id, business_date, format)
+        .await
+        .map_err(|e| settlement_error("download", e))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let disposition = format!(
+        "attachment; filename=\"statement-{}.{}\"",
+        business_date,
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        content,
+    )
+        .into_response())
+}
+
+/// 查询营业日结算状态
+pub async fn get_settlement_run(
+    State(state): State<AppState>,
+    Path(business_date): Path<NaiveDate>,
+) -> Result<Json<Value>, StatusCode> {
+    match state.settlement_service.get_run(business_date).await {
+        Ok(Some(run)) => Ok(Json(json!({
+            "success": true,
+            "data": run
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(settlement_error("query", e)),
+    }
+}
+
+/// 手动结算或重放营业日，其他副本正在结算时返回 409
+pub async fn run_settlement(
+    State(state): State<AppState>,
+    Path(business_date): Path<NaiveDate>,
+    Query(query): Query<RunSettlementQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    match state
+        .settlement_service
+        .settle_with_lease(&state.leader, business_date, query.replay)
+        .await
+    {
+        Ok(Some(run)) => Ok(Json(json!({
+            "success": true,
+            "data": run
+        }))),
+        Ok(None) => {
+            tracing::warn!("Settlement for {} is already running on another replica", business_date);
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => Err(settlement_error("run", e)),
+    }
+}
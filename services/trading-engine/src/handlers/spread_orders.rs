@@ -0,0 +1,58 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use serde_json::{json, Value};
+
+use super::authenticated_user;
+use crate::{
+    models::{SpreadOrderRequest, TradingError},
+    state::AppState,
+};
+
+fn spread_order_error(e: TradingError) -> StatusCode {
+    match e {
+        TradingError::InvalidOrder(_) | TradingError::ConfigError(_) => {
+            tracing::warn!("Rejected spread order: {}", e);
+            StatusCode::BAD_REQUEST
+        }
+        TradingError::RiskViolation(_) => {
+            tracing::warn!("Rejected spread order: {}", e);
+            StatusCode::CONFLICT
+        }
+        e => {
+            tracing::error!("Failed to execute spread order: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// 提交多腿价差单，返回各腿成交、对冲数量和实际达到的平均价差
+pub async fn create_spread_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RequestJson(request): RequestJson<SpreadOrderRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+
+    let execution = state
+        .order_service
+        .create_spread_order(user_id, &request)
+        .await
+        .map_err(spread_order_error)?;
+    for fill in &execution.fills {
+        if let Err(e) = state.trade_store.insert_fill(fill).await {
+            tracing::error!(
+                "Failed to record fill {} of spread {}: {}",
+                fill.id, execution.spread_id, e
+            );
+        }
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "data": execution
+    })))
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{Datelike, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::authenticated_user;
+use crate::{
+    engines::tax_lots::{AssetGainSummary, CostBasisMethod, TaxReport},
+    models::TradingError,
+    state::AppState,
+};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TaxReportQuery {
+    /// 报告年度，默认当前年度
+    pub year: Option<i32>,
+    /// 成本计算方法：fifo/lifo/hifo/average，默认使用配置
+    pub method: Option<String>,
+    /// 是否包含逐笔处置明细
+    pub include_disposals: Option<bool>,
+}
+
+async fn load_report(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: &TaxReportQuery,
+) -> Result<TaxReport, StatusCode> {
+    let user_id = authenticated_user(headers)?;
+    let year = query.year.unwrap_or_else(|| Utc::now().year());
+    let method = query
+        .method
+        .as_deref()
+        .map(|m| m.parse::<CostBasisMethod>().map_err(|_| StatusCode::BAD_REQUEST))
+        .transpose()?;
+
+    match state.tax_service.yearly_report(user_id, year, method).await {
+        Ok(report) => Ok(report),
+        Err(TradingError::ConfigError(e)) => {
+            tracing::warn!("Invalid tax report request: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            tracing::error!("Failed to build tax report: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 查询年度已实现盈亏报告
+pub async fn get_tax_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TaxReportQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let mut report = load_report(&state, &headers, &query).await?;
+    if !query.include_disposals.unwrap_or(false) {
+        report.disposals.clear();
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "data": report
+    })))
+}
+
+fn csv_row(summary: &AssetGainSummary) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{}\n",
+        summary.asset,
+        summary.disposals,
+        summary.quantity,
+        summary.proceeds,
+        summary.cost_basis,
+        summary.short_term_gain,
+        summary.long_term_gain,
+        summary.total_gain
+    )
+}
+
+/// 按资产导出年度盈亏汇总CSV
+pub async fn export_tax_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TaxReportQuery>,
+) -> Result<Response, StatusCode> {
+    let report = load_report(&state, &headers, &query).await?;
+
+    let mut body = String::from(
+        "asset,disposals,quantity,proceeds,cost_basis,short_term_gain,long_term_gain,total_gain\n",
+    );
+    for summary in &report.assets {
+        body.push_str(&csv_row(summary));
+    }
+    body.push_str(&csv_row(&report.totals));
+
+    let disposition = format!("attachment; filename=\"tax-report-{}.csv\"", report.year);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
@@ -0,0 +1,279 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::authenticated_user;
+use crate::{
+    models::{aggregate_by_order, liquidity_label, Fill, Side, Symbol, TradeCursor, TradeQuery, TradingEnvironment},
+    state::AppState,
+    storage::trade_store::MAX_TRADE_PAGE,
+};
+
+/// 导出单次最多读取的成交数
+const MAX_EXPORT_ROWS: usize = 50_000;
+
+/// 手续费报表默认统计天数
+const DEFAULT_FEE_REPORT_DAYS: i64 = 30;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListTradesQuery {
+    pub symbol: Option<String>,
+    pub side: Option<String>,
+    pub strategy_tag: Option<String>,
+    /// 交易环境：live 或 testnet
+    pub environment: Option<String>,
+    /// 起始时间（毫秒时间戳，包含）
+    pub start_time: Option<i64>,
+    /// 结束时间（毫秒时间戳，不包含）
+    pub end_time: Option<i64>,
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    /// 聚合方式，目前支持 order
+    pub group_by: Option<String>,
+}
+
+fn millis(value: i64) -> Result<DateTime<Utc>, StatusCode> {
+    Utc.timestamp_millis_opt(value)
+        .single()
+        .ok_or(StatusCode::BAD_REQUEST)
+}
+
+/// 解析查询参数
+fn build_query(params: &ListTradesQuery, default_limit: u32) -> Result<TradeQuery, StatusCode> {
+    let symbol = params
+        .symbol
+        .as_deref()
+        .map(|s| Symbol::from_string(&s.to_uppercase()).ok_or(StatusCode::BAD_REQUEST))
+        .transpose()?;
+    let side = params
+        .side
+        .as_deref()
+        .map(|s| s.parse::<Side>().map_err(|_| StatusCode::BAD_REQUEST))
+        .transpose()?;
+    let environment = params
+        .environment
+        .as_deref()
+        .map(|s| s.parse::<TradingEnvironment>().map_err(|_| StatusCode::BAD_REQUEST))
+        .transpose()?;
+    let start_time = params.start_time.map(millis).transpose()?;
+    let end_time = params.end_time.map(millis).transpose()?;
+    if let (Some(start), Some(end)) = (start_time, end_time) {
+        if start >= end {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(|c| TradeCursor::decode(c).map_err(|_| StatusCode::BAD_REQUEST))
+        .transpose()?;
+
+    Ok(TradeQuery {
+        symbol,
+        side,
+        strategy_tag: params.strategy_tag.clone(),
+        environment,
+        start_time,
+        end_time,
+        cursor,
+        limit: params.limit.unwrap_or(default_limit),
+    })
+}
+
+/// 查询当前用户的成交记录
+pub async fn list_trades(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ListTradesQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let query = build_query(&params, 100)?;
+    let group_by_order = match params.group_by.as_deref() {
+        None => false,
+        Some("order") => true,
+        Some(_) => return Err(StatusCode::BAD_REQUEST),
+    };
+
+    let (fills, has_more) = state
+        .trade_store
+        .list_fills(user_id, &query)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list trades: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let next_cursor = if has_more {
+        fills.last().map(|fill| TradeCursor::from_fill(fill).encode())
+    } else {
+        None
+    };
+
+    // 聚合只作用于当前页，跨页的订单会在相邻页各出现一次
+    let data = if group_by_order {
+        json!(aggregate_by_order(&fills))
+    } else {
+        json!(fills)
+    };
+
+    Ok(Json(json!({
+        "success": true,
+        "data": data,
+        "pagination": {
+            "count": fills.len(),
+            "has_more": has_more,
+            "next_cursor": next_cursor
+        }
+    })))
+}
+
+fn csv_row(fill: &Fill) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+        fill.executed_at.to_rfc3339(),
+        fill.id,
+        fill.order_id,
+        fill.symbol,
+        fill.side,
+        fill.price,
+        fill.quantity,
+        fill.quote_quantity,
+        fill.fee,
+        fill.fee_currency,
+        liquidity_label(fill.is_maker),
+        fill.venue,
+        fill.strategy_tag.as_deref().unwrap_or("").replace(',', ";"),
+        fill.environment
+    )
+}
+
+/// 按相同过滤条件导出成交CSV
+pub async fn export_trades(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ListTradesQuery>,
+) -> Result<Response, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let mut query = build_query(&params, MAX_TRADE_PAGE)?;
+    query.limit = MAX_TRADE_PAGE;
+
+    let mut body = String::from(
+        "executed_at,trade_id,order_id,symbol,side,price,quantity,quote_quantity,fee,fee_currency,liquidity,venue,strategy_tag,environment\n",
+    );
+    let mut rows = 0;
+    loop {
+        let (fills, has_more) = state
+            .trade_store
+            .list_fills(user_id, &query)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to export trades: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        for fill in &fills {
+            body.push_str(&csv_row(fill));
+        }
+        rows += fills.len();
+        match fills.last() {
+            Some(last) if has_more && rows < MAX_EXPORT_ROWS => {
+                query.cursor = Some(TradeCursor::from_fill(last));
+            }
+            _ => break,
+        }
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"trades.csv\""),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FeeReportQuery {
+    /// 起始时间（毫秒时间戳，包含），默认结束时间前30天
+    pub start_time: Option<i64>,
+    /// 结束时间（毫秒时间戳，不包含），默认当前时间
+    pub end_time: Option<i64>,
+}
+
+/// 按交易所和挂单/吃单方向汇总的手续费报表
+pub async fn fee_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<FeeReportQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    let end = params.end_time.map(millis).transpose()?.unwrap_or_else(Utc::now);
+    let start = match params.start_time {
+        Some(start) => millis(start)?,
+        None => end - Duration::days(DEFAULT_FEE_REPORT_DAYS),
+    };
+    if start >= end {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let summaries = state
+        .trade_store
+        .fee_report(user_id, start, end)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to build fee report: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "start_time": start.timestamp_millis(),
+            "end_time": end.timestamp_millis(),
+            "venues": summaries
+        }
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query() {
+        let params = ListTradesQuery {
+            symbol: Some("btcusdt".to_string()),
+            side: Some("sell".to_string()),
+            start_time: Some(1_700_000_000_000),
+            end_time: Some(1_700_000_600_000),
+            ..ListTradesQuery::default()
+        };
+        let query = build_query(&params, 100).unwrap();
+        assert_eq!(query.symbol, Some(Symbol::new("BTC", "USDT")));
+        assert_eq!(query.side, Some(Side::Sell));
+        assert_eq!(query.limit, 100);
+
+        let reversed = ListTradesQuery {
+            start_time: Some(2),
+            end_time: Some(1),
+            ..ListTradesQuery::default()
+        };
+        assert_eq!(build_query(&reversed, 100).unwrap_err(), StatusCode::BAD_REQUEST);
+
+        let bad_cursor = ListTradesQuery {
+            cursor: Some("???".to_string()),
+            ..ListTradesQuery::default()
+        };
+        assert!(build_query(&bad_cursor, 100).is_err());
+
+        let testnet = ListTradesQuery {
+            environment: Some("testnet".to_string()),
+            ..ListTradesQuery::default()
+        };
+        assert_eq!(build_query(&testnet, 100).unwrap().environment, Some(TradingEnvironment::Testnet));
+    }
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Json as RequestJson,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::authenticated_user;
+use crate::{
+    models::{ActivityCategory, VerificationLevel},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SetVerificationRequest {
+    pub level: VerificationLevel,
+    pub reason: Option<String>,
+}
+
+/// 查询用户认证等级及对应的交易限额
+pub async fn get_verification(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Value>, StatusCode> {
+    let service = &state.verification_service;
+    match service.get(user_id).await {
+        Ok(verification) => {
+            let level = verification.as_ref().map(|v| v.level).unwrap_or_default();
+            Ok(Json(json!({
+                "success": true,
+                "data": {
+                    "user_id": user_id,
+                    "level": level,
+                    "enabled": service.is_enabled(),
+                    "limits": service.limits(level),
+                    "verification": verification
+                }
+            })))
+        }
+        Err(e) => {
+            tracing::error!("Failed to get verification level for user {}: {}", user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 管理员修改用户认证等级，等级变化时发布 verification_level_changed 事件
+pub async fn set_verification(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    RequestJson(request): RequestJson<SetVerificationRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let operator = authenticated_user(&headers)?;
+    match state
+        .verification_service
+        .set_level(user_id, request.level, operator, request.reason)
+        .await
+    {
+        Ok(verification) => {
+            state
+                .activity_service
+                .record(
+                    user_id,
+                    ActivityCategory::Settings,
+                    "verification_level_changed",
+                    format!("Verification level set to {}", request.level),
+                    json!({ "level": request.level, "operator": operator }),
+                )
+                .await;
+            Ok(Json(json!({
+                "success": true,
+                "data": verification
+            })))
+        }
+        Err(e) => {
+            tracing::error!("Failed to set verification level for user {}: {}", user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
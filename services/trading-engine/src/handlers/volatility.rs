@@ -0,0 +1,66 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    Json as RequestJson,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{models::Symbol, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateVolatilityRequest {
+    /// 采样收益率标准差
+    pub volatility: Decimal,
+}
+
+/// 查询各交易对的波动分档和执行限制表
+pub async fn list_volatility_regimes(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
+    let config = &state.config.execution.volatility_policy;
+    let readings: Vec<Value> = state
+        .execution_engine
+        .volatility_readings()
+        .await
+        .into_iter()
+        .map(|(symbol, reading)| {
+            json!({
+                "symbol": symbol.to_string(),
+                "regime": reading.regime,
+                "volatility": reading.volatility,
+                "updated_at": reading.updated_at
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "success": true,
+        "data": {
+            "enabled": config.enabled,
+            "policies": config.policies,
+            "readings": readings
+        }
+    })))
+}
+
+/// 写入外部波动率数据源的读数
+pub async fn update_volatility(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    RequestJson(request): RequestJson<UpdateVolatilityRequest>,
+) -> Result<Json<Value>, StatusCode> {
+    let symbol = Symbol::from_string(&symbol.to_uppercase()).ok_or(StatusCode::BAD_REQUEST)?;
+    if request.volatility < Decimal::ZERO {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let reading = state
+        .execution_engine
+        .update_volatility(&symbol, request.volatility)
+        .await;
+
+    Ok(Json(json!({
+        "success": true,
+        "data": reading
+    })))
+}
@@ -10,7 +10,11 @@ mod exchanges;
 
 use anyhow::Result;
 use axum::{extract::connect_info::ConnectInfo, Router};
-use shared_utils::{LoggingInitializer, AppMetrics};
+use shared_utils::{
+    check_config_requested, decimal_format_middleware, internal_auth_middleware,
+    log_level_routes, request_span_middleware, request_trace_routes, run_config_check,
+    AppMetrics, InternalAuth, LoggingInitializer,
+};
 use std::{net::SocketAddr, sync::Arc};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
@@ -31,12 +35,19 @@ async fn main() -> Result<()> {
     // 加载环境变量
     dotenvy::dotenv().ok();
 
-    // 初始化日志
-    LoggingInitializer::init_dev()?;
+    // 检查配置后退出
+    if check_config_requested() {
+        let (config, report) = TradingEngineConfig::load_checked()?;
+        std::process::exit(run_config_check("trading-engine", config.as_ref(), &report));
+    }
+
+    // 初始化日志，LOG_FORMAT=json 时输出结构化日志
+    let log_handle = LoggingInitializer::init_from_env()?;
 
     // 加载配置
     let config = TradingEngineConfig::load()?;
     info!("Trading engine configuration loaded");
+    log_handle.apply(&config.logging.level, &config.logging.modules)?;
 
     // 初始化指标
     let metrics = Arc::new(AppMetrics::new()?);
@@ -46,13 +57,83 @@ async fn main() -> Result<()> {
     let state = AppState::new(config.clone(), metrics.clone()).await?;
     info!("Application state initialized");
 
+    // 启动账户盈亏快照任务
+    state.pnl_service.clone().start(state.leader.clone());
+
+    // 启动推荐返佣结算任务
+    state.referral_service.clone().start(state.leader.clone());
+
+    // 启动日终结算任务
+    state.settlement_service.clone().start(state.leader.clone());
+
+    // 启动账户级回撤止损检查
+    state.portfolio_stop_service.clone().start(state.leader.clone());
+
+    // 启动定时订单的激活和撤单任务
+    state.scheduled_order_service.clone().start(state.leader.clone());
+
+    // 启动风险事件确认时限升级和归档任务
+    state.risk_event_service.clone().start(state.leader.clone());
+
+    // 启动过期站内通知清理任务
+    state.notification_service.clone().start(state.leader.clone());
+
+    // 恢复进程中断后遗留的下单流程
+    state.order_service.clone().start_saga_recovery(state.leader.clone());
+
+    // 算法单子订单成交计入父订单，撤销执行中断的算法单
+    if let Some(algo_updates) = state.execution_engine.take_algo_updates() {
+        state
+            .order_service
+            .clone()
+            .start_algo_orders(algo_updates, state.leader.clone());
+    }
+
+    // 清理超过去重窗口的执行回报
+    state.order_service.clone().start_execution_purge(state.leader.clone());
+
+    // 采样标记价格更新波动分档
+    state.execution_engine.clone().start_volatility_sampler();
+
+    // 从快照恢复内部撮合订单簿并定期保存
+    state.execution_engine.clone().start_book_snapshots();
+
+    // 启动发件箱事件投递
+    state.outbox_relay.clone().start();
+
+    // 仓位变化时刷新下单前保证金余量缓存
+    state
+        .margin_headroom
+        .clone()
+        .start(state.position_service.subscribe());
+
+    // 恢复下单频率窗口并定期清理
+    state.order_rate_service.clone().start().await;
+
+    // 定期上报数据库连接池使用情况
+    state
+        .db_pools
+        .clone()
+        .start_metrics(metrics.clone(), config.database.read_pool.metrics_interval);
+
+    // 启动功能开关刷新任务
+    state.feature_flags.start_refresh();
+
+    // 指标和管理接口的内部认证
+    let internal_auth = Arc::new(InternalAuth::new(config.internal_auth.clone()));
+
     // 创建中间件层
     let middleware = ServiceBuilder::new()
         .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any));
+        .layer(axum::middleware::from_fn(request_span_middleware))
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        .layer(axum::middleware::from_fn_with_state(internal_auth, internal_auth_middleware))
+        .layer(axum::middleware::from_fn(decimal_format_middleware));
 
     // 创建路由
     let app = create_routes()
+        .merge(request_trace_routes("/api/v1/admin/trace/:request_id", log_handle.request_logs()))
+        .merge(log_level_routes("/api/v1/admin/log-level", log_handle))
         .layer(middleware)
         .with_state(state);
 
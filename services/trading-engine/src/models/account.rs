@@ -0,0 +1,312 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use super::{Id, Order, Side, Timestamp, TradingError, TradingResult};
+
+/// 未成交订单占用的保证金和敞口
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarginReservation {
+    pub symbol: String,
+    /// 未成交数量
+    #[serde(with = "shared_models::decimal")]
+    pub quantity: Decimal,
+    #[serde(with = "shared_models::decimal")]
+    pub margin: Decimal,
+    /// 未成交部分的名义价值
+    #[serde(with = "shared_models::decimal")]
+    pub value: Decimal,
+}
+
+impl MarginReservation {
+    /// 按剩余未成交数量缩减占用
+    pub fn shrink_to(&mut self, remaining: Decimal) {
+        if self.quantity <= Decimal::ZERO || remaining >= self.quantity {
+            return;
+        }
+        let ratio = remaining.max(Decimal::ZERO) / self.quantity;
+        self.margin *= ratio;
+        self.value *= ratio;
+        self.quantity = remaining.max(Decimal::ZERO);
+    }
+}
+
+/// 账户保证金余量
+///
+/// `available_margin` 和 `position_values` 来自账户和仓位服务，
+/// 已通过风控但尚未成交的订单计入 `reservations`，并发下单时据此扣减余量。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarginHeadroom {
+    #[serde(with = "shared_models::decimal")]
+    pub available_margin: Decimal,
+    /// 交易对 -> 持仓名义价值
+    #[serde(with = "shared_models::decimal::map")]
+    pub position_values: HashMap<String, Decimal>,
+    /// 订单ID -> 占用
+    pub reservations: HashMap<Id, MarginReservation>,
+}
+
+impl MarginHeadroom {
+    /// 未成交订单占用的保证金
+    pub fn reserved_margin(&self) -> Decimal {
+        self.reservations.values().map(|r| r.margin).sum()
+    }
+
+    /// 扣除未成交订单后的可用保证金
+    pub fn free_margin(&self) -> Decimal {
+        self.available_margin - self.reserved_margin()
+    }
+
+    /// 交易对的持仓和未成交订单名义价值
+    pub fn exposure(&self, symbol: &str) -> Decimal {
+        let pending: Decimal = self
+            .reservations
+            .values()
+            .filter(|r| r.symbol == symbol)
+            .map(|r| r.value)
+            .sum();
+        self.position_values.get(symbol).copied().unwrap_or(Decimal::ZERO) + pending
+    }
+
+    /// 检查可用保证金，修改订单时不计入该订单原有的占用
+    pub fn check_margin(&self, order_id: Id, reservation: &MarginReservation) -> TradingResult<()> {
+        let existing = self
+            .reservations
+            .get(&order_id)
+            .map(|r| r.margin)
+            .unwrap_or(Decimal::ZERO);
+        let available = self.free_margin() + existing;
+        if available < reservation.margin {
+            return Err(TradingError::InsufficientMargin {
+                required: reservation.margin,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// 检查交易对持仓价值上限，修改订单时不计入该订单原有的占用
+    pub fn check_position_limit(
+        &self,
+        order_id: Id,
+        reservation: &MarginReservation,
+        max_position_value: Decimal,
+    ) -> TradingResult<()> {
+        let existing = self
+            .reservations
+            .get(&order_id)
+            .filter(|r| r.symbol == reservation.symbol)
+            .map(|r| r.value)
+            .unwrap_or(Decimal::ZERO);
+        let total = self.exposure(&reservation.symbol) - existing + reservation.value;
+        if total > max_position_value {
+            return Err(TradingError::RiskViolation(format!(
+                "Total position value {} exceeds limit {}",
+                total, max_position_value
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 余额冻结状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldStatus {
+    Active,
+    /// 订单完全成交，冻结已全部扣减
+    Converted,
+    /// 订单撤销、过期或拒绝，剩余冻结已释放
+    Released,
+}
+
+impl std::fmt::Display for HoldStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HoldStatus::Active => write!(f, "active"),
+            HoldStatus::Converted => write!(f, "converted"),
+            HoldStatus::Released => write!(f, "released"),
+        }
+    }
+}
+
+impl std::str::FromStr for HoldStatus {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(HoldStatus::Active),
+            "converted" => Ok(HoldStatus::Converted),
+            "released" => Ok(HoldStatus::Released),
+            _ => Err(TradingError::SerializationError(format!("Invalid hold status: {}", s))),
+        }
+    }
+}
+
+/// 订单对余额的冻结
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceHold {
+    pub order_id: Id,
+    pub user_id: Id,
+    pub currency: String,
+    /// 当前仍冻结的金额
+    #[serde(with = "shared_models::decimal")]
+    pub amount: Decimal,
+    pub status: HoldStatus,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl BalanceHold {
+    /// 订单未成交部分需要冻结的币种和金额：买单冻结计价币，卖单冻结基础币
+    pub fn requirement(order: &Order, price: Decimal) -> (String, Decimal) {
+        match order.side {
+            Side::Buy => (order.symbol.quote.clone(), price * order.remaining_quantity),
+            Side::Sell => (order.symbol.base.clone(), order.remaining_quantity),
+        }
+    }
+}
+
+/// 一笔成交对余额的影响
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillSettlement {
+    /// 从冻结中扣减的币种和金额
+    pub debit_currency: String,
+    pub debit_amount: Decimal,
+    /// 入账的币种和金额
+    pub credit_currency: String,
+    pub credit_amount: Decimal,
+}
+
+impl FillSettlement {
+    /// 按成交计算扣减和入账，手续费从同币种的一侧扣除，其他币种的手续费不在此处理
+    pub fn for_fill(order: &Order, quantity: Decimal, price: Decimal, fee: Decimal) -> Self {
+        let (debit_currency, mut debit_amount, credit_currency, mut credit_amount) = match order.side {
+            Side::Buy => (&order.symbol.quote, price * quantity, &order.symbol.base, quantity),
+            Side::Sell => (&order.symbol.base, quantity, &order.symbol.quote, price * quantity),
+        };
+        if order.fee_currency == *credit_currency {
+            credit_amount -= fee;
+        } else if order.fee_currency == *debit_currency {
+            debit_amount += fee;
+        }
+
+        Self {
+            debit_currency: debit_currency.clone(),
+            debit_amount,
+            credit_currency: credit_currency.clone(),
+            credit_amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn reservation(symbol: &str, margin: i64, value: i64) -> MarginReservation {
+        MarginReservation {
+            symbol: symbol.to_string(),
+            quantity: Decimal::ONE,
+            margin: Decimal::from(margin),
+            value: Decimal::from(value),
+        }
+    }
+
+    #[test]
+    fn test_reservations_reduce_headroom() {
+        let mut headroom = MarginHeadroom {
+            available_margin: Decimal::from(1000),
+            position_values: HashMap::from([("BTCUSDT".to_string(), Decimal::from(4000))]),
+            reservations: HashMap::new(),
+        };
+        let first = Uuid::new_v4();
+        let limit = Decimal::from(10000);
+
+        let order = reservation("BTCUSDT", 600, 6000);
+        assert!(headroom.check_margin(first, &order).is_ok());
+        assert!(headroom.check_position_limit(first, &order, limit).is_ok());
+        headroom.reservations.insert(first, order);
+        assert_eq!(headroom.free_margin(), Decimal::from(400));
+        assert_eq!(headroom.exposure("BTCUSDT"), Decimal::from(10000));
+
+        // 第二笔订单在第一笔占用之后检查
+        let second = Uuid::new_v4();
+        assert!(matches!(
+            headroom.check_margin(second, &reservation("ETHUSDT", 500, 5000)),
+            Err(TradingError::InsufficientMargin { .. })
+        ));
+        assert!(matches!(
+            headroom.check_position_limit(second, &reservation("BTCUSDT", 100, 1000), limit),
+            Err(TradingError::RiskViolation(_))
+        ));
+
+        // 修改订单时替换原有占用
+        let amended = reservation("BTCUSDT", 900, 6000);
+        assert!(headroom.check_margin(first, &amended).is_ok());
+        assert!(headroom.check_position_limit(first, &amended, limit).is_ok());
+    }
+
+    #[test]
+    fn test_reservation_shrinks_on_fill() {
+        let mut order = MarginReservation {
+            symbol: "BTCUSDT".to_string(),
+            quantity: Decimal::from(4),
+            margin: Decimal::from(400),
+            value: Decimal::from(4000),
+        };
+        order.shrink_to(Decimal::ONE);
+        assert_eq!(order.quantity, Decimal::ONE);
+        assert_eq!(order.margin, Decimal::from(100));
+        assert_eq!(order.value, Decimal::from(1000));
+    }
+
+    fn order(side: &str) -> Order {
+        let request = crate::models::CreateOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            order_type: "LIMIT".to_string(),
+            side: side.to_string(),
+            quantity: Decimal::from(2),
+            price: Some(Decimal::from(50_000)),
+            stop_price: None,
+            time_in_force: None,
+            expires_at: None,
+            client_order_id: None,
+            tags: Vec::new(),
+            visible_quantity: None,
+            algorithm: None,
+            algorithm_duration_secs: None,
+            environment: crate::models::TradingEnvironment::Live,
+        };
+        request.to_order(Uuid::new_v4()).unwrap()
+    }
+
+    #[test]
+    fn test_hold_requirement_and_fill_settlement() {
+        let price = Decimal::from(50_000);
+        let buy = order("BUY");
+        assert_eq!(
+            BalanceHold::requirement(&buy, price),
+            ("USDT".to_string(), Decimal::from(100_000))
+        );
+        let sell = order("SELL");
+        assert_eq!(
+            BalanceHold::requirement(&sell, price),
+            ("BTC".to_string(), Decimal::from(2))
+        );
+
+        // 买单以低于限价成交，手续费为 USDT 时计入扣减
+        let settlement = FillSettlement::for_fill(&buy, Decimal::ONE, Decimal::from(49_000), Decimal::from(49));
+        assert_eq!(settlement.debit_currency, "USDT");
+        assert_eq!(settlement.debit_amount, Decimal::from(49_049));
+        assert_eq!(settlement.credit_currency, "BTC");
+        assert_eq!(settlement.credit_amount, Decimal::ONE);
+
+        // 卖单手续费从入账的 USDT 中扣除
+        let settlement = FillSettlement::for_fill(&sell, Decimal::ONE, price, Decimal::from(50));
+        assert_eq!(settlement.debit_currency, "BTC");
+        assert_eq!(settlement.debit_amount, Decimal::ONE);
+        assert_eq!(settlement.credit_amount, Decimal::from(49_950));
+    }
+}
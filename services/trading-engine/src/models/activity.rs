@@ -0,0 +1,168 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{Id, Timestamp, TradingError, TradingResult};
+
+/// 账户动态类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityCategory {
+    /// 下单及订单终态，来自订单表
+    Order,
+    /// 登录、登出等认证事件，由认证服务上报
+    Auth,
+    /// API密钥的创建、删除和权限变更，由认证服务上报
+    ApiKey,
+    /// 账户级止损等风控配置变更
+    RiskConfig,
+    /// 结算币种、交易环境、认证等级等账户设置变更
+    Settings,
+}
+
+impl std::fmt::Display for ActivityCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivityCategory::Order => write!(f, "order"),
+            ActivityCategory::Auth => write!(f, "auth"),
+            ActivityCategory::ApiKey => write!(f, "api_key"),
+            ActivityCategory::RiskConfig => write!(f, "risk_config"),
+            ActivityCategory::Settings => write!(f, "settings"),
+        }
+    }
+}
+
+impl std::str::FromStr for ActivityCategory {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "order" => Ok(ActivityCategory::Order),
+            "auth" => Ok(ActivityCategory::Auth),
+            "api_key" => Ok(ActivityCategory::ApiKey),
+            "risk_config" => Ok(ActivityCategory::RiskConfig),
+            "settings" => Ok(ActivityCategory::Settings),
+            _ => Err(TradingError::SerializationError(format!("Invalid activity category: {}", s))),
+        }
+    }
+}
+
+/// 账户动态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountActivity {
+    pub id: Id,
+    pub user_id: Id,
+    pub category: ActivityCategory,
+    /// 具体动作，例如 order_placed、login、settlement_currency_changed
+    pub action: String,
+    pub summary: String,
+    pub details: Value,
+    /// 产生动态的服务或操作人
+    pub source: String,
+    pub occurred_at: Timestamp,
+}
+
+impl AccountActivity {
+    pub fn new(user_id: Id, category: ActivityCategory, action: &str, summary: String, details: Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            category,
+            action: action.to_string(),
+            summary,
+            details,
+            source: "trading-engine".to_string(),
+            occurred_at: Utc::now(),
+        }
+    }
+}
+
+/// 账户动态分页游标，按 (发生时间, ID) 倒序翻页
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityCursor {
+    pub occurred_at: Timestamp,
+    pub id: Id,
+}
+
+impl ActivityCursor {
+    pub fn from_activity(activity: &AccountActivity) -> Self {
+        Self {
+            occurred_at: activity.occurred_at,
+            id: activity.id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", self.occurred_at.timestamp_micros(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> TradingResult<Self> {
+        let invalid = || TradingError::InvalidOrder(format!("Invalid cursor: {}", cursor));
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (micros, id) = text.split_once(':').ok_or_else(invalid)?;
+        let micros: i64 = micros.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            occurred_at: Utc.timestamp_micros(micros).single().ok_or_else(invalid)?,
+            id: id.parse::<Uuid>().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// 账户动态查询条件
+#[derive(Debug, Clone, Default)]
+pub struct ActivityQuery {
+    /// 为空时不按类型过滤
+    pub categories: Vec<ActivityCategory>,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub cursor: Option<ActivityCursor>,
+    pub limit: u32,
+}
+
+impl ActivityQuery {
+    pub fn includes(&self, category: ActivityCategory) -> bool {
+        self.categories.is_empty() || self.categories.contains(&category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_cursor_and_categories() {
+        let activity = AccountActivity::new(
+            Uuid::new_v4(),
+            ActivityCategory::Settings,
+            "settlement_currency_changed",
+            "Settlement currency set to USDC".to_string(),
+            Value::Null,
+        );
+        let cursor = ActivityCursor::from_activity(&activity);
+        let decoded = ActivityCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded.id, activity.id);
+        assert_eq!(decoded.occurred_at.timestamp_micros(), activity.occurred_at.timestamp_micros());
+        assert!(ActivityCursor::decode("not-a-cursor").is_err());
+
+        for category in [
+            ActivityCategory::Order,
+            ActivityCategory::Auth,
+            ActivityCategory::ApiKey,
+            ActivityCategory::RiskConfig,
+            ActivityCategory::Settings,
+        ] {
+            assert_eq!(category.to_string().parse::<ActivityCategory>().unwrap(), category);
+        }
+
+        let query = ActivityQuery {
+            categories: vec![ActivityCategory::Auth],
+            ..Default::default()
+        };
+        assert!(query.includes(ActivityCategory::Auth));
+        assert!(!query.includes(ActivityCategory::Order));
+        assert!(ActivityQuery::default().includes(ActivityCategory::Order));
+    }
+}
@@ -0,0 +1,267 @@
+use super::{Id, Timestamp};
+use chrono::{Datelike, FixedOffset, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 交易时段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingSession {
+    pub day_of_week: u8, // 0=Sunday, 1=Monday, ..., 6=Saturday
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+impl TradingSession {
+    pub fn new(day_of_week: u8, start_time: NaiveTime, end_time: NaiveTime) -> Self {
+        Self {
+            day_of_week,
+            start_time,
+            end_time,
+        }
+    }
+
+    /// 全天交易时段
+    pub fn full_day(day_of_week: u8) -> Self {
+        Self::new(
+            day_of_week,
+            NaiveTime::MIN,
+            NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
+        )
+    }
+
+    /// 检查本地时间是否在时段内（含首尾）
+    pub fn contains(&self, day_of_week: u8, time: NaiveTime) -> bool {
+        self.day_of_week == day_of_week && time >= self.start_time && time <= self.end_time
+    }
+}
+
+/// 维护窗口来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceSource {
+    ExchangeAnnouncement,
+    Internal,
+}
+
+/// 维护窗口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub id: Id,
+    /// 受影响的交易对，为空表示全部交易对
+    pub symbols: Vec<String>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub reason: String,
+    pub source: MaintenanceSource,
+    pub created_at: Timestamp,
+}
+
+impl MaintenanceWindow {
+    pub fn new(
+        symbols: Vec<String>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        reason: String,
+        source: MaintenanceSource,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            symbols: symbols.into_iter().map(|s| s.to_uppercase()).collect(),
+            start_time,
+            end_time,
+            reason,
+            source,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// 是否影响指定交易对
+    pub fn applies_to(&self, symbol: &str) -> bool {
+        self.symbols.is_empty() || self.symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+    }
+
+    /// 指定时间是否处于维护中
+    pub fn is_active_at(&self, at: Timestamp) -> bool {
+        at >= self.start_time && at < self.end_time
+    }
+
+    /// 是否已结束
+    pub fn is_finished(&self, at: Timestamp) -> bool {
+        at >= self.end_time
+    }
+}
+
+/// 交易对日历
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstrumentCalendar {
+    pub symbol: String,
+    /// 是否全天候交易（加密货币默认）
+    pub always_open: bool,
+    /// 时区偏移，例如 "+08:00"，"UTC" 等价于 "+00:00"
+    pub timezone: String,
+    pub sessions: Vec<TradingSession>,
+    /// 休市日期，格式 YYYY-MM-DD（本地时区）
+    pub holidays: Vec<String>,
+}
+
+impl InstrumentCalendar {
+    /// 7x24 交易日历
+    pub fn always_open(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            always_open: true,
+            timezone: "UTC".to_string(),
+            sessions: Vec::new(),
+            holidays: Vec::new(),
+        }
+    }
+
+    /// 解析时区偏移
+    pub fn offset(&self) -> Option<FixedOffset> {
+        parse_timezone_offset(&self.timezone)
+    }
+
+    /// 常规交易时段是否开放（不考虑维护窗口）
+    pub fn is_session_open(&self, at: Timestamp) -> bool {
+        if self.always_open {
+            return true;
+        }
+
+        let offset = match self.offset() {
+            Some(offset) => offset,
+            None => return false,
+        };
+
+        let local = at.with_timezone(&offset);
+        let date = local.format("%Y-%m-%d").to_string();
+        if self.holidays.iter().any(|h| h == &date) {
+            return false;
+        }
+
+        let day_of_week = local.weekday().num_days_from_sunday() as u8;
+        let time = local.time();
+        self.sessions
+            .iter()
+            .any(|session| session.contains(day_of_week, time))
+    }
+}
+
+/// 交易状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketSessionState {
+    Open,
+    Closed,
+    Maintenance,
+}
+
+/// 交易对当前交易状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketSessionStatus {
+    pub symbol: String,
+    pub state: MarketSessionState,
+    pub checked_at: Timestamp,
+    pub maintenance: Option<MaintenanceWindow>,
+}
+
+impl MarketSessionStatus {
+    pub fn is_open(&self) -> bool {
+        self.state == MarketSessionState::Open
+    }
+}
+
+/// 解析 "UTC"、"+08:00"、"-05:00" 形式的时区偏移
+pub fn parse_timezone_offset(timezone: &str) -> Option<FixedOffset> {
+    let tz = timezone.trim();
+    if tz.eq_ignore_ascii_case("UTC") || tz.eq_ignore_ascii_case("Z") {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match tz.chars().next()? {
+        '+' => (1, &tz[1..]),
+        '-' => (-1, &tz[1..]),
+        _ => return None,
+    };
+
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn weekday_calendar() -> InstrumentCalendar {
+        InstrumentCalendar {
+            symbol: "AAPLUSD".to_string(),
+            always_open: false,
+            timezone: "-05:00".to_string(),
+            sessions: (1..=5)
+                .map(|day| {
+                    TradingSession::new(
+                        day,
+                        NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+                        NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+                    )
+                })
+                .collect(),
+            holidays: vec!["2024-07-04".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_timezone_parsing() {
+        assert_eq!(parse_timezone_offset("UTC").unwrap().local_minus_utc(), 0);
+        assert_eq!(parse_timezone_offset("+08:00").unwrap().local_minus_utc(), 8 * 3600);
+        assert_eq!(parse_timezone_offset("-05:30").unwrap().local_minus_utc(), -(5 * 3600 + 1800));
+        assert!(parse_timezone_offset("Asia/Shanghai").is_none());
+    }
+
+    #[test]
+    fn test_session_open() {
+        let calendar = weekday_calendar();
+
+        // 2024-07-03 是周三，10:00 (UTC-5) = 15:00 UTC
+        let open = Utc.with_ymd_and_hms(2024, 7, 3, 15, 0, 0).unwrap();
+        assert!(calendar.is_session_open(open));
+
+        // 收盘后
+        let closed = Utc.with_ymd_and_hms(2024, 7, 3, 22, 0, 0).unwrap();
+        assert!(!calendar.is_session_open(closed));
+
+        // 节假日
+        let holiday = Utc.with_ymd_and_hms(2024, 7, 4, 15, 0, 0).unwrap();
+        assert!(!calendar.is_session_open(holiday));
+
+        // 周末
+        let weekend = Utc.with_ymd_and_hms(2024, 7, 6, 15, 0, 0).unwrap();
+        assert!(!calendar.is_session_open(weekend));
+
+        assert!(InstrumentCalendar::always_open("BTCUSDT").is_session_open(weekend));
+    }
+
+    #[test]
+    fn test_maintenance_window() {
+        let start = Utc::now();
+        let window = MaintenanceWindow::new(
+            vec!["btcusdt".to_string()],
+            start,
+            start + Duration::hours(1),
+            "Wallet upgrade".to_string(),
+            MaintenanceSource::ExchangeAnnouncement,
+        );
+
+        assert!(window.applies_to("BTCUSDT"));
+        assert!(!window.applies_to("ETHUSDT"));
+        assert!(window.is_active_at(start + Duration::minutes(30)));
+        assert!(!window.is_active_at(start + Duration::hours(1)));
+        assert!(window.is_finished(start + Duration::hours(2)));
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use super::TradingError;
+
+/// 交易环境
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingEnvironment {
+    /// 实盘
+    #[default]
+    Live,
+    /// 交易所测试网，资金和成交不具有真实价值
+    Testnet,
+}
+
+impl TradingEnvironment {
+    pub fn is_testnet(&self) -> bool {
+        matches!(self, TradingEnvironment::Testnet)
+    }
+}
+
+impl std::fmt::Display for TradingEnvironment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradingEnvironment::Live => write!(f, "live"),
+            TradingEnvironment::Testnet => write!(f, "testnet"),
+        }
+    }
+}
+
+impl std::str::FromStr for TradingEnvironment {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "live" => Ok(TradingEnvironment::Live),
+            "testnet" => Ok(TradingEnvironment::Testnet),
+            _ => Err(TradingError::SerializationError(format!("Invalid trading environment: {}", s))),
+        }
+    }
+}
+
+/// 账户允许交易的环境
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvironmentAccess {
+    #[default]
+    LiveOnly,
+    TestnetOnly,
+    Both,
+}
+
+impl EnvironmentAccess {
+    pub fn allows(&self, environment: TradingEnvironment) -> bool {
+        match self {
+            EnvironmentAccess::LiveOnly => environment == TradingEnvironment::Live,
+            EnvironmentAccess::TestnetOnly => environment == TradingEnvironment::Testnet,
+            EnvironmentAccess::Both => true,
+        }
+    }
+}
+
+impl std::fmt::Display for EnvironmentAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvironmentAccess::LiveOnly => write!(f, "live_only"),
+            EnvironmentAccess::TestnetOnly => write!(f, "testnet_only"),
+            EnvironmentAccess::Both => write!(f, "both"),
+        }
+    }
+}
+
+impl std::str::FromStr for EnvironmentAccess {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "live_only" => Ok(EnvironmentAccess::LiveOnly),
+            "testnet_only" => Ok(EnvironmentAccess::TestnetOnly),
+            "both" => Ok(EnvironmentAccess::Both),
+            _ => Err(TradingError::SerializationError(format!("Invalid environment access: {}", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment_access() {
+        assert!(EnvironmentAccess::LiveOnly.allows(TradingEnvironment::Live));
+        assert!(!EnvironmentAccess::LiveOnly.allows(TradingEnvironment::Testnet));
+        assert!(!EnvironmentAccess::TestnetOnly.allows(TradingEnvironment::Live));
+        assert!(EnvironmentAccess::Both.allows(TradingEnvironment::Testnet));
+        for access in [EnvironmentAccess::LiveOnly, EnvironmentAccess::TestnetOnly, EnvironmentAccess::Both] {
+            assert_eq!(access.to_string().parse::<EnvironmentAccess>().unwrap(), access);
+        }
+        assert_eq!("testnet".parse::<TradingEnvironment>().unwrap(), TradingEnvironment::Testnet);
+        assert!("paper".parse::<TradingEnvironment>().is_err());
+    }
+}
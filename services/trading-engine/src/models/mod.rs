@@ -1,10 +1,40 @@
 pub mod account;
+pub mod activity;
+pub mod calendar;
+pub mod environment;
+pub mod notification;
 pub mod order;
+pub mod pnl;
+pub mod portfolio_stop;
 pub mod position;
+pub mod referral;
+pub mod risk_event;
+pub mod saga;
+pub mod sandbox;
+pub mod scheduled_order;
+pub mod settlement;
+pub mod spread_order;
+pub mod trade;
+pub mod verification;
 
 pub use account::*;
+pub use activity::*;
+pub use calendar::*;
+pub use environment::*;
+pub use notification::*;
 pub use order::*;
+pub use pnl::*;
+pub use portfolio_stop::*;
 pub use position::*;
+pub use referral::*;
+pub use risk_event::*;
+pub use saga::*;
+pub use sandbox::*;
+pub use scheduled_order::*;
+pub use settlement::*;
+pub use spread_order::*;
+pub use trade::*;
+pub use verification::*;
 
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
@@ -227,6 +257,9 @@ pub enum TradingError {
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
+
+    #[error("Incompatible state format: {0}")]
+    IncompatibleStateFormat(String),
 }
 
 pub type TradingResult<T> = Result<T, TradingError>;
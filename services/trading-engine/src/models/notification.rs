@@ -0,0 +1,126 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use shared_protocols::kafka::NotificationType;
+use uuid::Uuid;
+
+use super::{Id, Timestamp, TradingError};
+
+/// 站内通知类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    Trading,
+    PriceAlert,
+    Risk,
+    Account,
+    System,
+    Marketing,
+}
+
+impl std::fmt::Display for NotificationCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationCategory::Trading => write!(f, "trading"),
+            NotificationCategory::PriceAlert => write!(f, "price_alert"),
+            NotificationCategory::Risk => write!(f, "risk"),
+            NotificationCategory::Account => write!(f, "account"),
+            NotificationCategory::System => write!(f, "system"),
+            NotificationCategory::Marketing => write!(f, "marketing"),
+        }
+    }
+}
+
+impl std::str::FromStr for NotificationCategory {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trading" => Ok(NotificationCategory::Trading),
+            "price_alert" => Ok(NotificationCategory::PriceAlert),
+            "risk" => Ok(NotificationCategory::Risk),
+            "account" => Ok(NotificationCategory::Account),
+            "system" => Ok(NotificationCategory::System),
+            "marketing" => Ok(NotificationCategory::Marketing),
+            _ => Err(TradingError::SerializationError(format!("Invalid notification category: {}", s))),
+        }
+    }
+}
+
+impl From<&NotificationType> for NotificationCategory {
+    fn from(notification_type: &NotificationType) -> Self {
+        match notification_type {
+            NotificationType::TradingAlert => NotificationCategory::Trading,
+            NotificationType::PriceAlert => NotificationCategory::PriceAlert,
+            NotificationType::RiskAlert => NotificationCategory::Risk,
+            NotificationType::SystemAlert => NotificationCategory::System,
+            NotificationType::MarketingMessage => NotificationCategory::Marketing,
+            NotificationType::AccountUpdate => NotificationCategory::Account,
+        }
+    }
+}
+
+/// 站内通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Id,
+    pub user_id: Id,
+    pub category: NotificationCategory,
+    pub title: String,
+    pub message: String,
+    /// 跳转或展示用的附加数据
+    pub data: serde_json::Value,
+    /// 未读时为 None
+    pub read_at: Option<Timestamp>,
+    pub created_at: Timestamp,
+}
+
+impl Notification {
+    pub fn new(
+        user_id: Id,
+        category: NotificationCategory,
+        title: impl Into<String>,
+        message: impl Into<String>,
+        data: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            category,
+            title: title.into(),
+            message: message.into(),
+            data,
+            read_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_category_round_trip() {
+        for category in [
+            NotificationCategory::Trading,
+            NotificationCategory::PriceAlert,
+            NotificationCategory::Risk,
+            NotificationCategory::Account,
+            NotificationCategory::System,
+            NotificationCategory::Marketing,
+        ] {
+            assert_eq!(category.to_string().parse::<NotificationCategory>().unwrap(), category);
+            assert_eq!(serde_json::to_value(category).unwrap(), category.to_string());
+        }
+        assert!("bell".parse::<NotificationCategory>().is_err());
+        assert_eq!(
+            NotificationCategory::from(&NotificationType::RiskAlert),
+            NotificationCategory::Risk
+        );
+        assert!(!Notification::new(Uuid::nil(), NotificationCategory::System, "t", "m", serde_json::json!({})).is_read());
+    }
+}
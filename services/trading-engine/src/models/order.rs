@@ -1,4 +1,4 @@
-use super::{Amount, Id, Price, Quantity, Side, Symbol, Timestamp, TradingError, TradingResult};
+use super::{Amount, Id, Price, Quantity, Side, Symbol, Timestamp, TradingEnvironment, TradingError, TradingResult};
 use chrono::Utc;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -13,6 +13,9 @@ pub enum OrderType {
     TakeProfit,
     StopLossLimit,
     TakeProfitLimit,
+    /// 冰山单：限价单，订单簿上只展示 `visible_quantity`，展示部分成交完后
+    /// 自动补充下一批并排到同价位队尾
+    Iceberg,
 }
 
 impl OrderType {
@@ -20,7 +23,7 @@ impl OrderType {
     pub fn requires_price(&self) -> bool {
         matches!(
             self,
-            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit
+            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit | OrderType::Iceberg
         )
     }
 
@@ -44,7 +47,7 @@ impl OrderType {
     pub fn is_limit_order(&self) -> bool {
         matches!(
             self,
-            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit
+            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit | OrderType::Iceberg
         )
     }
 }
@@ -58,6 +61,7 @@ impl std::fmt::Display for OrderType {
             OrderType::TakeProfit => write!(f, "TAKE_PROFIT"),
             OrderType::StopLossLimit => write!(f, "STOP_LOSS_LIMIT"),
             OrderType::TakeProfitLimit => write!(f, "TAKE_PROFIT_LIMIT"),
+            OrderType::Iceberg => write!(f, "ICEBERG"),
         }
     }
 }
@@ -73,6 +77,7 @@ impl std::str::FromStr for OrderType {
             "TAKE_PROFIT" => Ok(OrderType::TakeProfit),
             "STOP_LOSS_LIMIT" => Ok(OrderType::StopLossLimit),
             "TAKE_PROFIT_LIMIT" => Ok(OrderType::TakeProfitLimit),
+            "ICEBERG" => Ok(OrderType::Iceberg),
             _ => Err(anyhow::anyhow!("Invalid order type: {}", s)),
         }
     }
@@ -174,14 +179,24 @@ pub struct Order {
     pub symbol: Symbol,
     pub order_type: OrderType,
     pub side: Side,
+    #[serde(with = "shared_models::decimal")]
     pub quantity: Quantity,
+    #[serde(with = "shared_models::decimal::option", default)]
     pub price: Option<Price>,
+    #[serde(with = "shared_models::decimal::option", default)]
     pub stop_price: Option<Price>,
+    /// 冰山单每次展示的数量，总数量为 `quantity`
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "shared_models::decimal::option")]
+    pub visible_quantity: Option<Quantity>,
     pub status: OrderStatus,
     pub time_in_force: TimeInForce,
+    #[serde(with = "shared_models::decimal")]
     pub filled_quantity: Quantity,
+    #[serde(with = "shared_models::decimal")]
     pub remaining_quantity: Quantity,
+    #[serde(with = "shared_models::decimal::option", default)]
     pub average_price: Option<Price>,
+    #[serde(with = "shared_models::decimal")]
     pub fee: Amount,
     pub fee_currency: String,
     pub created_at: Timestamp,
@@ -195,10 +210,17 @@ pub struct Order {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderMetadata {
     pub source: String,
+    /// 执行算法，例如 twap、vwap；子订单沿用父订单的算法
     pub algorithm: Option<String>,
+    /// 算法单的执行时长（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithm_duration_secs: Option<u64>,
     pub parent_order_id: Option<Id>,
     pub tags: Vec<String>,
     pub notes: Option<String>,
+    /// 交易环境，测试网订单只路由到测试网交易所
+    #[serde(default)]
+    pub environment: TradingEnvironment,
 }
 
 impl Default for OrderMetadata {
@@ -206,9 +228,11 @@ impl Default for OrderMetadata {
         Self {
             source: "api".to_string(),
             algorithm: None,
+            algorithm_duration_secs: None,
             parent_order_id: None,
             tags: Vec::new(),
             notes: None,
+            environment: TradingEnvironment::Live,
         }
     }
 }
@@ -269,6 +293,7 @@ impl Order {
             quantity,
             price,
             stop_price,
+            visible_quantity: None,
             status: OrderStatus::Pending,
             time_in_force: TimeInForce::GTC,
             filled_quantity: Decimal::ZERO,
@@ -297,6 +322,12 @@ impl Order {
         self
     }
 
+    /// 设置冰山单展示数量
+    pub fn with_visible_quantity(mut self, visible_quantity: Quantity) -> Self {
+        self.visible_quantity = Some(visible_quantity);
+        self
+    }
+
     /// 设置客户端订单ID
     pub fn with_client_order_id(mut self, client_order_id: String) -> Self {
         self.client_order_id = Some(client_order_id);
@@ -327,6 +358,17 @@ impl Order {
             ));
         }
 
+        if self.order_type == OrderType::Iceberg {
+            match self.visible_quantity {
+                Some(visible) if visible > Decimal::ZERO && visible <= self.quantity => {}
+                _ => {
+                    return Err(TradingError::InvalidOrder(
+                        "Iceberg order requires a visible quantity between 0 and the total quantity".to_string(),
+                    ))
+                }
+            }
+        }
+
         if self.filled_quantity > self.quantity {
             return Err(TradingError::InvalidOrder(
                 "Filled quantity cannot exceed total quantity".to_string(),
@@ -356,6 +398,22 @@ impl Order {
         self.filled_quantity >= self.quantity
     }
 
+    /// 冰山单展示下一批数量，返回是否还有未成交数量可展示
+    ///
+    /// 撮合引擎订单簿中冰山单的 `remaining_quantity` 只表示当前展示的数量，
+    /// 隐藏部分为 `quantity - filled_quantity - remaining_quantity`。
+    pub fn next_iceberg_tranche(&mut self) -> bool {
+        let Some(visible_quantity) = self.visible_quantity else {
+            return false;
+        };
+        let unfilled = self.quantity - self.filled_quantity;
+        if self.order_type != OrderType::Iceberg || unfilled <= Decimal::ZERO {
+            return false;
+        }
+        self.remaining_quantity = unfilled.min(visible_quantity);
+        true
+    }
+
     /// 检查是否部分成交
     pub fn is_partially_filled(&self) -> bool {
         self.filled_quantity > Decimal::ZERO && self.filled_quantity < self.quantity
@@ -494,6 +552,22 @@ impl Order {
     }
 }
 
+/// 交易所执行回报
+///
+/// 用户数据流重连后交易所会重发断线前后的回报，(venue, execution_id) 唯一标识一次成交。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub venue: String,
+    pub execution_id: String,
+    pub order_id: Id,
+    #[serde(with = "shared_models::decimal")]
+    pub quantity: Quantity,
+    #[serde(with = "shared_models::decimal")]
+    pub price: Price,
+    #[serde(with = "shared_models::decimal")]
+    pub fee: Amount,
+}
+
 /// 订单创建请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateOrderRequest {
@@ -506,6 +580,21 @@ pub struct CreateOrderRequest {
     pub time_in_force: Option<String>,
     pub expires_at: Option<Timestamp>,
     pub client_order_id: Option<String>,
+    /// 订单标签，例如策略版本标签 strategy:grid@1.2.0
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 冰山单每次展示的数量
+    #[serde(default)]
+    pub visible_quantity: Option<Quantity>,
+    /// 执行算法：twap 或 vwap，父订单按时间片拆分成子订单执行
+    #[serde(default)]
+    pub algorithm: Option<String>,
+    /// 算法单的执行时长（秒）
+    #[serde(default)]
+    pub algorithm_duration_secs: Option<u64>,
+    /// 交易环境，默认实盘
+    #[serde(default)]
+    pub environment: TradingEnvironment,
 }
 
 impl CreateOrderRequest {
@@ -560,6 +649,23 @@ impl CreateOrderRequest {
             order = order.with_client_order_id(client_order_id.clone());
         }
 
+        if let Some(visible_quantity) = self.visible_quantity {
+            order = order.with_visible_quantity(visible_quantity);
+        }
+
+        if let Some(algorithm) = &self.algorithm {
+            let algorithm = algorithm.to_lowercase();
+            if !matches!(algorithm.as_str(), "twap" | "vwap") {
+                return Err(TradingError::InvalidOrder(format!("Invalid algorithm: {}", algorithm)));
+            }
+            order.metadata.algorithm = Some(algorithm);
+            order.metadata.algorithm_duration_secs = self.algorithm_duration_secs;
+        }
+
+        order.metadata.tags = self.tags.clone();
+        order.metadata.environment = self.environment;
+        order.validate()?;
+
         Ok(order)
     }
 }
@@ -666,4 +772,93 @@ mod tests {
         );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_iceberg_tranches() {
+        let order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            OrderType::Iceberg,
+            Side::Sell,
+            Decimal::from(5),
+            Some(Decimal::from(100)),
+            None,
+        )
+        .unwrap();
+
+        // 展示数量缺失或超过总数量时无效
+        assert!(order.validate().is_err());
+        assert!(order.clone().with_visible_quantity(Decimal::from(6)).validate().is_err());
+
+        let mut order = order.with_visible_quantity(Decimal::from(2));
+        assert!(order.validate().is_ok());
+        assert!(order.next_iceberg_tranche());
+        assert_eq!(order.remaining_quantity, Decimal::from(2));
+
+        order.filled_quantity = Decimal::from(4);
+        assert!(order.next_iceberg_tranche());
+        assert_eq!(order.remaining_quantity, Decimal::from(1));
+
+        order.filled_quantity = Decimal::from(5);
+        assert!(!order.next_iceberg_tranche());
+    }
+
+    #[test]
+    fn test_create_algo_order_request() {
+        let request: CreateOrderRequest = serde_json::from_value(serde_json::json!({
+            "symbol": "BTCUSDT",
+            "order_type": "MARKET",
+            "side": "BUY",
+            "quantity": "10",
+            "price": null,
+            "stop_price": null,
+            "time_in_force": null,
+            "expires_at": null,
+            "client_order_id": null,
+            "algorithm": "TWAP",
+            "algorithm_duration_secs": 600
+        }))
+        .unwrap();
+        let order = request.to_order(Uuid::new_v4()).unwrap();
+        assert_eq!(order.metadata.algorithm.as_deref(), Some("twap"));
+        assert_eq!(order.metadata.algorithm_duration_secs, Some(600));
+        assert!(order.metadata.parent_order_id.is_none());
+
+        let request = CreateOrderRequest {
+            algorithm: Some("iceberg".to_string()),
+            ..request
+        };
+        assert!(request.to_order(Uuid::new_v4()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_order_decimal_format() {
+        use shared_models::decimal::{self, DecimalFormat};
+
+        let order = Order::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            OrderType::Limit,
+            Side::Buy,
+            Decimal::new(15, 1),
+            Some(Decimal::from(50000)),
+            None,
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&order).unwrap();
+        assert_eq!(value["quantity"], "1.5");
+        assert_eq!(value["price"], "50000");
+
+        decimal::scope(DecimalFormat::Number, async {
+            let value = serde_json::to_value(&order).unwrap();
+            assert_eq!(value["quantity"], 1.5);
+            // 持久化不受请求格式影响
+            let persisted = decimal::canonical(|| serde_json::to_value(&order)).unwrap();
+            assert_eq!(persisted["quantity"], "1.5");
+            let parsed: Order = serde_json::from_value(value).unwrap();
+            assert_eq!(parsed.quantity, order.quantity);
+        })
+        .await;
+    }
 }
@@ -0,0 +1,294 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{Amount, Id, Timestamp, TradingError};
+
+/// 账户权益/盈亏快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlSnapshot {
+    pub user_id: Id,
+    #[serde(with = "shared_models::decimal")]
+    pub equity: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub realized_pnl: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub unrealized_pnl: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub total_pnl: Amount,
+    pub taken_at: Timestamp,
+}
+
+/// 时间序列粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PnlGranularity {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "4h")]
+    FourHours,
+    #[serde(rename = "1d")]
+    OneDay,
+    #[serde(rename = "1w")]
+    OneWeek,
+}
+
+impl PnlGranularity {
+    pub fn duration(&self) -> Duration {
+        match self {
+            PnlGranularity::FiveMinutes => Duration::minutes(5),
+            PnlGranularity::FifteenMinutes => Duration::minutes(15),
+            PnlGranularity::OneHour => Duration::hours(1),
+            PnlGranularity::FourHours => Duration::hours(4),
+            PnlGranularity::OneDay => Duration::days(1),
+            PnlGranularity::OneWeek => Duration::weeks(1),
+        }
+    }
+
+    /// 时间所在区间的起点，按UTC对齐
+    pub fn bucket_start(&self, at: Timestamp) -> Timestamp {
+        let step = self.duration().num_seconds();
+        let seconds = at.timestamp();
+        Utc.timestamp_opt(seconds - seconds.rem_euclid(step), 0)
+            .single()
+            .unwrap_or(at)
+    }
+
+    /// 年化所用的每年区间数
+    pub fn periods_per_year(&self) -> f64 {
+        Duration::days(365).num_seconds() as f64 / self.duration().num_seconds() as f64
+    }
+}
+
+impl std::str::FromStr for PnlGranularity {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "5m" => Ok(PnlGranularity::FiveMinutes),
+            "15m" => Ok(PnlGranularity::FifteenMinutes),
+            "1h" => Ok(PnlGranularity::OneHour),
+            "4h" => Ok(PnlGranularity::FourHours),
+            "1d" => Ok(PnlGranularity::OneDay),
+            "1w" => Ok(PnlGranularity::OneWeek),
+            _ => Err(TradingError::ConfigError(format!("Invalid granularity: {}", s))),
+        }
+    }
+}
+
+/// 时间序列中的一个点，取区间内最后一个快照
+#[derive(Debug, Clone, Serialize)]
+pub struct PnlPoint {
+    pub bucket: Timestamp,
+    #[serde(with = "shared_models::decimal")]
+    pub equity: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub realized_pnl: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub unrealized_pnl: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub total_pnl: Amount,
+    /// 相对上一个点的权益收益率
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub period_return: Option<Decimal>,
+    /// 相对此前权益高点的回撤
+    #[serde(with = "shared_models::decimal")]
+    pub drawdown: Decimal,
+}
+
+/// 序列统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PnlStatistics {
+    pub points: usize,
+    #[serde(with = "shared_models::decimal")]
+    pub start_equity: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub end_equity: Amount,
+    /// 区间内盈亏变化
+    #[serde(with = "shared_models::decimal")]
+    pub pnl_change: Amount,
+    #[serde(with = "shared_models::decimal")]
+    pub total_return: Decimal,
+    #[serde(with = "shared_models::decimal")]
+    pub max_drawdown: Decimal,
+    #[serde(with = "shared_models::decimal")]
+    pub max_drawdown_amount: Amount,
+    pub max_drawdown_peak_at: Option<Timestamp>,
+    pub max_drawdown_trough_at: Option<Timestamp>,
+    #[serde(with = "shared_models::decimal")]
+    pub current_drawdown: Decimal,
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub best_period_return: Option<Decimal>,
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub worst_period_return: Option<Decimal>,
+    /// 正收益区间占比
+    #[serde(with = "shared_models::decimal")]
+    pub win_rate: Decimal,
+    #[serde(with = "shared_models::decimal")]
+    pub annualized_volatility: Decimal,
+    #[serde(with = "shared_models::decimal::option", default)]
+    pub sharpe_ratio: Option<Decimal>,
+}
+
+fn ratio(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default().round_dp(6)
+}
+
+/// 按粒度降采样，快照需按时间升序
+pub fn downsample(snapshots: &[PnlSnapshot], granularity: PnlGranularity) -> Vec<PnlPoint> {
+    let mut points: Vec<PnlPoint> = Vec::new();
+    for snapshot in snapshots {
+        let bucket = granularity.bucket_start(snapshot.taken_at);
+        let point = PnlPoint {
+            bucket,
+            equity: snapshot.equity,
+            realized_pnl: snapshot.realized_pnl,
+            unrealized_pnl: snapshot.unrealized_pnl,
+            total_pnl: snapshot.total_pnl,
+            period_return: None,
+            drawdown: Decimal::ZERO,
+        };
+        match points.last_mut() {
+            Some(last) if last.bucket == bucket => *last = point,
+            _ => points.push(point),
+        }
+    }
+
+    let mut peak = Decimal::ZERO;
+    let mut previous: Option<Decimal> = None;
+    for point in &mut points {
+        peak = peak.max(point.equity);
+        if peak > Decimal::ZERO {
+            point.drawdown = ((peak - point.equity) / peak).round_dp(6);
+        }
+        point.period_return = previous
+            .filter(|p| !p.is_zero())
+            .map(|p| ((point.equity - p) / p).round_dp(6));
+        previous = Some(point.equity);
+    }
+    points
+}
+
+/// 计算回撤、收益和波动统计
+pub fn compute_statistics(points: &[PnlPoint], granularity: PnlGranularity) -> PnlStatistics {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return PnlStatistics::default();
+    };
+
+    let mut stats = PnlStatistics {
+        points: points.len(),
+        start_equity: first.equity,
+        end_equity: last.equity,
+        pnl_change: last.total_pnl - first.total_pnl,
+        current_drawdown: last.drawdown,
+        ..PnlStatistics::default()
+    };
+    if first.equity > Decimal::ZERO {
+        stats.total_return = ((last.equity - first.equity) / first.equity).round_dp(6);
+    }
+
+    // 最大回撤及其高点/低点
+    let mut peak = first;
+    for point in points {
+        if point.equity > peak.equity {
+            peak = point;
+        }
+        let amount = peak.equity - point.equity;
+        if amount > stats.max_drawdown_amount {
+            stats.max_drawdown_amount = amount;
+            stats.max_drawdown = point.drawdown;
+            stats.max_drawdown_peak_at = Some(peak.bucket);
+            stats.max_drawdown_trough_at = Some(point.bucket);
+        }
+    }
+
+    let returns: Vec<f64> = points
+        .iter()
+        .filter_map(|p| p.period_return.and_then(|r| r.to_f64()))
+        .collect();
+    if returns.is_empty() {
+        return stats;
+    }
+    let decimals: Vec<Decimal> = points.iter().filter_map(|p| p.period_return).collect();
+    stats.best_period_return = decimals.iter().max().copied();
+    stats.worst_period_return = decimals.iter().min().copied();
+    let wins = returns.iter().filter(|r| **r > 0.0).count();
+    stats.win_rate = ratio(wins as f64 / returns.len() as f64);
+
+    if returns.len() > 1 {
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let std_dev = variance.sqrt();
+        let periods = granularity.periods_per_year();
+        stats.annualized_volatility = ratio(std_dev * periods.sqrt());
+        if std_dev > 0.0 {
+            stats.sharpe_ratio = Some(ratio(mean / std_dev * periods.sqrt()));
+        }
+    }
+    stats
+}
+
+/// 序列区间起点，未指定时按粒度回看默认点数
+pub fn default_range_start(granularity: PnlGranularity, end: DateTime<Utc>, points: i32) -> DateTime<Utc> {
+    end - granularity.duration() * points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn snapshot(minutes: i64, equity: i64) -> PnlSnapshot {
+        PnlSnapshot {
+            user_id: Uuid::nil(),
+            equity: Decimal::from(equity),
+            realized_pnl: Decimal::ZERO,
+            unrealized_pnl: Decimal::from(equity - 1000),
+            total_pnl: Decimal::from(equity - 1000),
+            taken_at: Utc.timestamp_opt(1_700_000_000 - 1_700_000_000 % 3600, 0).unwrap()
+                + Duration::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_downsample_keeps_last_per_bucket() {
+        let snapshots = vec![snapshot(0, 1000), snapshot(30, 1100), snapshot(65, 1200), snapshot(130, 900)];
+        let points = downsample(&snapshots, PnlGranularity::OneHour);
+
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].equity, Decimal::from(1100));
+        assert_eq!(points[1].period_return, Some(Decimal::new(90909, 6)));
+        assert_eq!(points[2].drawdown, Decimal::new(25, 2));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let snapshots = vec![snapshot(0, 1000), snapshot(60, 1200), snapshot(120, 900), snapshot(180, 1100)];
+        let points = downsample(&snapshots, PnlGranularity::OneHour);
+        let stats = compute_statistics(&points, PnlGranularity::OneHour);
+
+        assert_eq!(stats.total_return, Decimal::new(1, 1));
+        assert_eq!(stats.pnl_change, Decimal::from(100));
+        assert_eq!(stats.max_drawdown, Decimal::new(25, 2));
+        assert_eq!(stats.max_drawdown_amount, Decimal::from(300));
+        assert_eq!(stats.max_drawdown_peak_at, Some(points[1].bucket));
+        assert_eq!(stats.current_drawdown, Decimal::new(83333, 6));
+        assert_eq!(stats.win_rate, Decimal::new(666667, 6));
+        assert!(stats.sharpe_ratio.is_some());
+
+        assert_eq!(compute_statistics(&[], PnlGranularity::OneDay).points, 0);
+    }
+
+    #[test]
+    fn test_granularity() {
+        let at = Utc.timestamp_opt(1_700_003_723, 0).unwrap();
+        assert_eq!("4h".parse::<PnlGranularity>().unwrap(), PnlGranularity::FourHours);
+        assert!("2h".parse::<PnlGranularity>().is_err());
+        assert_eq!(PnlGranularity::OneHour.bucket_start(at).timestamp() % 3600, 0);
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{Amount, Id, Timestamp, TradingError};
+
+/// 回撤的计算基准
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawdownBasis {
+    /// 相对当日（UTC）首次观察到的权益
+    Daily,
+    /// 相对设置以来的最高权益
+    HighWaterMark,
+}
+
+impl std::fmt::Display for DrawdownBasis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DrawdownBasis::Daily => write!(f, "daily"),
+            DrawdownBasis::HighWaterMark => write!(f, "high_water_mark"),
+        }
+    }
+}
+
+impl std::str::FromStr for DrawdownBasis {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daily" => Ok(DrawdownBasis::Daily),
+            "high_water_mark" => Ok(DrawdownBasis::HighWaterMark),
+            _ => Err(TradingError::SerializationError(format!("Invalid drawdown basis: {}", s))),
+        }
+    }
+}
+
+/// 账户级回撤止损
+///
+/// 权益相对基准回撤超过 `max_drawdown` 时触发：撤销全部订单、市价平掉全部仓位，
+/// 并锁定交易直到用户手动解锁。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioStop {
+    pub user_id: Id,
+    pub basis: DrawdownBasis,
+    /// 最大回撤比例，例如 0.1 表示 10%
+    pub max_drawdown: Decimal,
+    /// 回撤基准权益：当日首次权益或历史最高权益
+    pub reference_equity: Amount,
+    /// 基准权益对应的日期，按日计算时跨日重置
+    pub reference_date: NaiveDate,
+    /// 触发后锁定交易
+    pub locked: bool,
+    pub triggered_at: Option<Timestamp>,
+    pub updated_at: Timestamp,
+}
+
+impl PortfolioStop {
+    pub fn new(user_id: Id, basis: DrawdownBasis, max_drawdown: Decimal, equity: Amount, now: Timestamp) -> Self {
+        Self {
+            user_id,
+            basis,
+            max_drawdown,
+            reference_equity: equity,
+            reference_date: now.date_naive(),
+            locked: false,
+            triggered_at: None,
+            updated_at: now,
+        }
+    }
+
+    /// 当前权益相对基准的回撤比例，基准不为正时为0
+    pub fn drawdown(&self, equity: Amount) -> Decimal {
+        if self.reference_equity <= Decimal::ZERO || equity >= self.reference_equity {
+            return Decimal::ZERO;
+        }
+        (self.reference_equity - equity) / self.reference_equity
+    }
+
+    /// 根据最新权益更新基准，返回是否需要保存
+    pub fn observe(&mut self, equity: Amount, now: Timestamp) -> bool {
+        let today = now.date_naive();
+        let reset = match self.basis {
+            DrawdownBasis::Daily => today != self.reference_date,
+            DrawdownBasis::HighWaterMark => equity > self.reference_equity,
+        };
+        if reset {
+            self.reference_equity = equity;
+            self.reference_date = today;
+            self.updated_at = now;
+        }
+        reset
+    }
+
+    /// 回撤是否超过上限
+    pub fn is_breached(&self, equity: Amount) -> bool {
+        !self.locked && self.drawdown(equity) >= self.max_drawdown
+    }
+
+    /// 标记触发并锁定交易
+    pub fn trigger(&mut self, now: Timestamp) {
+        self.locked = true;
+        self.triggered_at = Some(now);
+        self.updated_at = now;
+    }
+
+    /// 手动解锁，以当前权益作为新的基准
+    pub fn unlock(&mut self, equity: Amount) {
+        let now = Utc::now();
+        self.locked = false;
+        self.reference_equity = equity;
+        self.reference_date = now.date_naive();
+        self.updated_at = now;
+    }
+}
+
+/// 账户级止损审计事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortfolioStopEventKind {
+    /// 设置或修改止损
+    Configured,
+    /// 删除止损
+    Removed,
+    /// 回撤超限触发，交易已锁定
+    Triggered,
+    /// 触发后撤销的订单
+    OrderCancelled,
+    /// 触发后市价平仓
+    PositionFlattened,
+    /// 平仓失败，需人工处理
+    FlattenFailed,
+    /// 手动解锁交易
+    Unlocked,
+}
+
+impl std::fmt::Display for PortfolioStopEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortfolioStopEventKind::Configured => write!(f, "configured"),
+            PortfolioStopEventKind::Removed => write!(f, "removed"),
+            PortfolioStopEventKind::Triggered => write!(f, "triggered"),
+            PortfolioStopEventKind::OrderCancelled => write!(f, "order_cancelled"),
+            PortfolioStopEventKind::PositionFlattened => write!(f, "position_flattened"),
+            PortfolioStopEventKind::FlattenFailed => write!(f, "flatten_failed"),
+            PortfolioStopEventKind::Unlocked => write!(f, "unlocked"),
+        }
+    }
+}
+
+impl std::str::FromStr for PortfolioStopEventKind {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "configured" => Ok(PortfolioStopEventKind::Configured),
+            "removed" => Ok(PortfolioStopEventKind::Removed),
+            "triggered" => Ok(PortfolioStopEventKind::Triggered),
+            "order_cancelled" => Ok(PortfolioStopEventKind::OrderCancelled),
+            "position_flattened" => Ok(PortfolioStopEventKind::PositionFlattened),
+            "flatten_failed" => Ok(PortfolioStopEventKind::FlattenFailed),
+            "unlocked" => Ok(PortfolioStopEventKind::Unlocked),
+            _ => Err(TradingError::SerializationError(format!(
+                "Invalid portfolio stop event kind: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// 账户级止损审计事件
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioStopEvent {
+    pub id: Id,
+    pub user_id: Id,
+    pub kind: PortfolioStopEventKind,
+    pub details: serde_json::Value,
+    pub created_at: Timestamp,
+}
+
+impl PortfolioStopEvent {
+    pub fn new(user_id: Id, kind: PortfolioStopEventKind, details: serde_json::Value) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            user_id,
+            kind,
+            details,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_drawdown_basis() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let max_drawdown = Decimal::new(1, 1);
+
+        // 按最高权益：新高抬升基准，回撤按新高计算
+        let mut stop = PortfolioStop::new(Uuid::nil(), DrawdownBasis::HighWaterMark, max_drawdown, Decimal::from(1000), now);
+        assert!(stop.observe(Decimal::from(1200), now));
+        assert!(!stop.observe(Decimal::from(1100), now));
+        assert!(!stop.is_breached(Decimal::from(1081)));
+        assert!(stop.is_breached(Decimal::from(1080)));
+
+        // 按日：跨日后以当日首次权益为基准
+        let mut stop = PortfolioStop::new(Uuid::nil(), DrawdownBasis::Daily, max_drawdown, Decimal::from(1000), now);
+        assert!(!stop.observe(Decimal::from(1200), now));
+        assert!(stop.is_breached(Decimal::from(900)));
+        assert!(stop.observe(Decimal::from(800), now + Duration::days(1)));
+        assert!(!stop.is_breached(Decimal::from(750)));
+
+        // 触发后不再重复触发，解锁后以当前权益为基准
+        stop.trigger(now);
+        assert!(!stop.is_breached(Decimal::from(100)));
+        stop.unlock(Decimal::from(700));
+        assert_eq!(stop.reference_equity, Decimal::from(700));
+        assert_eq!(stop.drawdown(Decimal::from(630)), max_drawdown);
+    }
+}
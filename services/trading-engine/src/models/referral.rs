@@ -0,0 +1,184 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use super::{Amount, Id, Timestamp, TradingError, TradingResult};
+
+/// 推荐码字符集，去掉易混淆的 0/O/1/I
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// 推荐码长度
+pub const REFERRAL_CODE_LEN: usize = 8;
+
+/// 用户推荐码
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralCode {
+    pub code: String,
+    pub user_id: Id,
+    pub created_at: Timestamp,
+}
+
+/// 被推荐用户注册归属
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralAttribution {
+    pub referee_id: Id,
+    pub referrer_id: Id,
+    pub code: String,
+    pub attributed_at: Timestamp,
+}
+
+/// 推荐账本条目类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferralEntryType {
+    /// 被推荐人交易手续费分成
+    Accrual,
+    /// 结算给推荐人
+    Payout,
+}
+
+impl std::fmt::Display for ReferralEntryType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferralEntryType::Accrual => write!(f, "accrual"),
+            ReferralEntryType::Payout => write!(f, "payout"),
+        }
+    }
+}
+
+impl std::str::FromStr for ReferralEntryType {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "accrual" => Ok(ReferralEntryType::Accrual),
+            "payout" => Ok(ReferralEntryType::Payout),
+            _ => Err(TradingError::SerializationError(format!("Invalid referral entry type: {}", s))),
+        }
+    }
+}
+
+/// 推荐账本条目，金额恒为正，类型决定方向
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralLedgerEntry {
+    pub id: Id,
+    pub referrer_id: Id,
+    /// 结算条目不对应单个被推荐人
+    pub referee_id: Option<Id>,
+    pub entry_type: ReferralEntryType,
+    pub amount: Amount,
+    pub currency: String,
+    /// 产生分成的成交ID，用于去重
+    pub source_id: Option<Id>,
+    /// 分成所依据的手续费
+    pub fee_amount: Option<Amount>,
+    pub created_at: Timestamp,
+}
+
+/// 单币种推荐收益
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CurrencyEarnings {
+    pub currency: String,
+    pub accrued: Amount,
+    pub paid: Amount,
+    /// 已累计未结算
+    pub pending: Amount,
+}
+
+/// 推荐收益概览
+#[derive(Debug, Clone, Serialize)]
+pub struct ReferralEarnings {
+    pub user_id: Id,
+    pub code: Option<String>,
+    pub fee_share_rate: Decimal,
+    pub referees: i64,
+    pub earnings: Vec<CurrencyEarnings>,
+}
+
+/// 生成随机推荐码
+pub fn generate_referral_code() -> String {
+    Uuid::new_v4()
+        .as_bytes()
+        .iter()
+        .take(REFERRAL_CODE_LEN)
+        .map(|b| CODE_ALPHABET[*b as usize % CODE_ALPHABET.len()] as char)
+        .collect()
+}
+
+/// 规范化用户输入的推荐码
+pub fn normalize_referral_code(code: &str) -> TradingResult<String> {
+    let code = code.trim().to_uppercase();
+    if code.len() != REFERRAL_CODE_LEN || !code.bytes().all(|b| CODE_ALPHABET.contains(&b)) {
+        return Err(TradingError::InvalidOrder(format!("Invalid referral code: {}", code)));
+    }
+    Ok(code)
+}
+
+/// 按分成比例计算推荐人应得金额
+pub fn compute_fee_share(fee: Amount, rate: Decimal) -> Amount {
+    if fee <= Decimal::ZERO || rate <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    (fee * rate).round_dp(8)
+}
+
+/// 按币种汇总账本金额，输入为 (币种, 类型, 金额)
+pub fn summarize_earnings(totals: &[(String, ReferralEntryType, Amount)]) -> Vec<CurrencyEarnings> {
+    let mut by_currency: BTreeMap<&str, CurrencyEarnings> = BTreeMap::new();
+    for (currency, entry_type, amount) in totals {
+        let earnings = by_currency
+            .entry(currency.as_str())
+            .or_insert_with(|| CurrencyEarnings {
+                currency: currency.clone(),
+                ..CurrencyEarnings::default()
+            });
+        match entry_type {
+            ReferralEntryType::Accrual => earnings.accrued += *amount,
+            ReferralEntryType::Payout => earnings.paid += *amount,
+        }
+    }
+    by_currency
+        .into_values()
+        .map(|mut earnings| {
+            earnings.pending = earnings.accrued - earnings.paid;
+            earnings
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total(entry_type: ReferralEntryType, amount: i64, currency: &str) -> (String, ReferralEntryType, Amount) {
+        (currency.to_string(), entry_type, Decimal::from(amount))
+    }
+
+    #[test]
+    fn test_referral_code() {
+        let code = generate_referral_code();
+        assert_eq!(code.len(), REFERRAL_CODE_LEN);
+        assert_eq!(normalize_referral_code(&format!(" {} ", code.to_lowercase())).unwrap(), code);
+        assert!(normalize_referral_code("ABC").is_err());
+        assert!(normalize_referral_code("ABCDEFG0").is_err());
+    }
+
+    #[test]
+    fn test_fee_share_and_summary() {
+        assert_eq!(compute_fee_share(Decimal::from(10), Decimal::new(2, 1)), Decimal::from(2));
+        assert_eq!(compute_fee_share(Decimal::from(-1), Decimal::new(2, 1)), Decimal::ZERO);
+
+        let totals = [
+            total(ReferralEntryType::Accrual, 5, "USDT"),
+            total(ReferralEntryType::Accrual, 3, "USDT"),
+            total(ReferralEntryType::Payout, 6, "USDT"),
+            total(ReferralEntryType::Accrual, 1, "BNB"),
+        ];
+        let summary = summarize_earnings(&totals);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[1].currency, "USDT");
+        assert_eq!(summary[1].pending, Decimal::from(2));
+        assert_eq!(summary[0].pending, Decimal::from(1));
+    }
+}
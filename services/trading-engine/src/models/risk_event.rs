@@ -0,0 +1,246 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use super::{Id, Timestamp, TradingError, TradingResult};
+
+/// 风险事件严重程度，按声明顺序由低到高
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl RiskSeverity {
+    /// 队列优先级，数值越大越先处理
+    pub fn priority(&self) -> i16 {
+        match self {
+            RiskSeverity::Low => 0,
+            RiskSeverity::Medium => 1,
+            RiskSeverity::High => 2,
+            RiskSeverity::Critical => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for RiskSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskSeverity::Low => write!(f, "low"),
+            RiskSeverity::Medium => write!(f, "medium"),
+            RiskSeverity::High => write!(f, "high"),
+            RiskSeverity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl std::str::FromStr for RiskSeverity {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(RiskSeverity::Low),
+            "medium" => Ok(RiskSeverity::Medium),
+            "high" => Ok(RiskSeverity::High),
+            "critical" => Ok(RiskSeverity::Critical),
+            _ => Err(TradingError::SerializationError(format!("Invalid risk severity: {}", s))),
+        }
+    }
+}
+
+/// 风险事件处理状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskEventStatus {
+    /// 等待值班人员确认
+    Open,
+    /// 已确认，处理中
+    Acknowledged,
+    /// 已处理完毕，保留期后归档
+    Resolved,
+}
+
+impl std::fmt::Display for RiskEventStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskEventStatus::Open => write!(f, "open"),
+            RiskEventStatus::Acknowledged => write!(f, "acknowledged"),
+            RiskEventStatus::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+impl std::str::FromStr for RiskEventStatus {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(RiskEventStatus::Open),
+            "acknowledged" => Ok(RiskEventStatus::Acknowledged),
+            "resolved" => Ok(RiskEventStatus::Resolved),
+            _ => Err(TradingError::SerializationError(format!(
+                "Invalid risk event status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// 值班人员对风险事件的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskEventAction {
+    Acknowledge,
+    Resolve,
+}
+
+/// 持久化的风险事件及其处理流程
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskEventRecord {
+    pub id: Id,
+    pub event_type: String,
+    pub user_id: Option<Id>,
+    pub symbol: Option<String>,
+    pub severity: RiskSeverity,
+    pub message: String,
+    pub data: serde_json::Value,
+    pub status: RiskEventStatus,
+    pub created_at: Timestamp,
+    pub acknowledged_at: Option<Timestamp>,
+    pub acknowledged_by: Option<Id>,
+    pub resolved_at: Option<Timestamp>,
+    pub resolved_by: Option<Id>,
+    pub resolution_note: Option<String>,
+    /// 超过确认时限后的升级次数
+    pub escalation_level: u32,
+    pub escalated_at: Option<Timestamp>,
+    pub updated_at: Timestamp,
+}
+
+impl RiskEventRecord {
+    /// 按操作推进处理状态，直接解决未确认的事件时同时记为确认
+    pub fn apply(
+        &mut self,
+        action: RiskEventAction,
+        operator: Id,
+        note: Option<String>,
+        now: Timestamp,
+    ) -> TradingResult<()> {
+        match (action, self.status) {
+            (RiskEventAction::Acknowledge, RiskEventStatus::Open) => {
+                self.status = RiskEventStatus::Acknowledged;
+                self.acknowledged_at = Some(now);
+                self.acknowledged_by = Some(operator);
+            }
+            (RiskEventAction::Resolve, RiskEventStatus::Open | RiskEventStatus::Acknowledged) => {
+                if self.acknowledged_at.is_none() {
+                    self.acknowledged_at = Some(now);
+                    self.acknowledged_by = Some(operator);
+                }
+                self.status = RiskEventStatus::Resolved;
+                self.resolved_at = Some(now);
+                self.resolved_by = Some(operator);
+                self.resolution_note = note;
+            }
+            (action, status) => {
+                return Err(TradingError::RiskViolation(format!(
+                    "Cannot {:?} risk event {} in status {}",
+                    action, self.id, status
+                )))
+            }
+        }
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// 未确认的事件是否需要升级：首次在超过确认时限时升级，之后每隔 interval 再次升级
+    pub fn escalation_due(&self, ack_sla: Duration, interval: Duration, now: Timestamp) -> bool {
+        if self.status != RiskEventStatus::Open {
+            return false;
+        }
+        match self.escalated_at {
+            None => now - self.created_at >= ack_sla,
+            Some(escalated_at) => now - escalated_at >= interval,
+        }
+    }
+
+    pub fn escalate(&mut self, now: Timestamp) {
+        self.escalation_level += 1;
+        self.escalated_at = Some(now);
+        self.updated_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn record(severity: RiskSeverity, created_at: Timestamp) -> RiskEventRecord {
+        RiskEventRecord {
+            id: Uuid::new_v4(),
+            event_type: "LIQUIDATION".to_string(),
+            user_id: None,
+            symbol: None,
+            severity,
+            message: "Position requires liquidation".to_string(),
+            data: serde_json::json!({}),
+            status: RiskEventStatus::Open,
+            created_at,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            resolved_at: None,
+            resolved_by: None,
+            resolution_note: None,
+            escalation_level: 0,
+            escalated_at: None,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_acknowledge_and_resolve() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let operator = Uuid::new_v4();
+
+        let mut event = record(RiskSeverity::High, now);
+        event.apply(RiskEventAction::Acknowledge, operator, None, now).unwrap();
+        assert_eq!(event.status, RiskEventStatus::Acknowledged);
+        assert!(event.apply(RiskEventAction::Acknowledge, operator, None, now).is_err());
+
+        event
+            .apply(RiskEventAction::Resolve, operator, Some("hedged".to_string()), now)
+            .unwrap();
+        assert_eq!(event.status, RiskEventStatus::Resolved);
+        assert_eq!(event.resolution_note.as_deref(), Some("hedged"));
+        assert!(event.apply(RiskEventAction::Resolve, operator, None, now).is_err());
+
+        // 直接解决未确认的事件
+        let mut event = record(RiskSeverity::Low, now);
+        event.apply(RiskEventAction::Resolve, operator, None, now).unwrap();
+        assert_eq!(event.acknowledged_by, Some(operator));
+    }
+
+    #[test]
+    fn test_escalation_schedule() {
+        let created = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let sla = Duration::minutes(5);
+        let interval = Duration::minutes(15);
+        let mut event = record(RiskSeverity::Critical, created);
+
+        assert!(!event.escalation_due(sla, interval, created + Duration::minutes(4)));
+        assert!(event.escalation_due(sla, interval, created + Duration::minutes(5)));
+        event.escalate(created + Duration::minutes(5));
+        assert!(!event.escalation_due(sla, interval, created + Duration::minutes(10)));
+        assert!(event.escalation_due(sla, interval, created + Duration::minutes(20)));
+
+        event
+            .apply(RiskEventAction::Acknowledge, Uuid::new_v4(), None, created + Duration::minutes(21))
+            .unwrap();
+        assert!(!event.escalation_due(sla, interval, created + Duration::hours(1)));
+        assert!(RiskSeverity::Critical > RiskSeverity::High);
+    }
+}
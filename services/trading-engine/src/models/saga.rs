@@ -0,0 +1,252 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{Id, Order, Timestamp, TradingError};
+
+/// 下单流程的步骤，按执行顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SagaStep {
+    /// 风控检查和下单频率计数，无需补偿
+    RiskCheck,
+    /// 占用保证金并冻结余额，补偿为释放
+    HoldMargin,
+    /// 保存订单，补偿为标记拒绝
+    PersistOrder,
+    /// 提交到交易所，补偿为撤单
+    SubmitVenue,
+}
+
+impl SagaStep {
+    /// 是否有补偿操作
+    pub fn compensable(&self) -> bool {
+        !matches!(self, SagaStep::RiskCheck)
+    }
+}
+
+impl std::fmt::Display for SagaStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SagaStep::RiskCheck => write!(f, "risk_check"),
+            SagaStep::HoldMargin => write!(f, "hold_margin"),
+            SagaStep::PersistOrder => write!(f, "persist_order"),
+            SagaStep::SubmitVenue => write!(f, "submit_venue"),
+        }
+    }
+}
+
+impl std::str::FromStr for SagaStep {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "risk_check" => Ok(SagaStep::RiskCheck),
+            "hold_margin" => Ok(SagaStep::HoldMargin),
+            "persist_order" => Ok(SagaStep::PersistOrder),
+            "submit_venue" => Ok(SagaStep::SubmitVenue),
+            _ => Err(TradingError::SerializationError(format!("Invalid saga step: {}", s))),
+        }
+    }
+}
+
+/// saga 状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SagaStatus {
+    Running,
+    /// 某一步失败，正在逆序执行补偿
+    Compensating,
+    Completed,
+    Compensated,
+    /// 补偿失败，需要人工处理
+    Failed,
+}
+
+impl SagaStatus {
+    /// 是否已结束
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, SagaStatus::Completed | SagaStatus::Compensated | SagaStatus::Failed)
+    }
+}
+
+impl std::fmt::Display for SagaStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SagaStatus::Running => write!(f, "running"),
+            SagaStatus::Compensating => write!(f, "compensating"),
+            SagaStatus::Completed => write!(f, "completed"),
+            SagaStatus::Compensated => write!(f, "compensated"),
+            SagaStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl std::str::FromStr for SagaStatus {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(SagaStatus::Running),
+            "compensating" => Ok(SagaStatus::Compensating),
+            "completed" => Ok(SagaStatus::Completed),
+            "compensated" => Ok(SagaStatus::Compensated),
+            "failed" => Ok(SagaStatus::Failed),
+            _ => Err(TradingError::SerializationError(format!("Invalid saga status: {}", s))),
+        }
+    }
+}
+
+/// 下单 saga
+///
+/// 每一步开始前记录 `pending`，完成后移入 `completed`。进程在某一步中途退出时，
+/// 该步骤的结果未知，恢复时按已执行处理并补偿，补偿操作需要幂等。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderSaga {
+    pub id: Id,
+    pub order: Order,
+    pub status: SagaStatus,
+    pub completed: Vec<SagaStep>,
+    pub pending: Option<SagaStep>,
+    pub error: Option<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl OrderSaga {
+    pub fn new(order: Order) -> Self {
+        let now = Utc::now();
+        Self {
+            id: order.id,
+            order,
+            status: SagaStatus::Running,
+            completed: Vec::new(),
+            pending: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// 开始执行步骤
+    pub fn begin(&mut self, step: SagaStep) {
+        self.pending = Some(step);
+        self.updated_at = Utc::now();
+    }
+
+    /// 步骤执行成功
+    pub fn complete(&mut self, step: SagaStep) {
+        if self.pending == Some(step) {
+            self.pending = None;
+        }
+        if !self.completed.contains(&step) {
+            self.completed.push(step);
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// 步骤执行失败，进入补偿；失败步骤本身没有生效，不需要补偿
+    pub fn fail(&mut self, step: SagaStep, error: &str) {
+        if self.pending == Some(step) {
+            self.pending = None;
+        }
+        self.status = SagaStatus::Compensating;
+        self.error = Some(format!("{} failed: {}", step, error));
+        self.updated_at = Utc::now();
+    }
+
+    /// 全部步骤完成
+    pub fn finish(&mut self) {
+        self.pending = None;
+        self.status = SagaStatus::Completed;
+        self.updated_at = Utc::now();
+    }
+
+    /// 需要补偿的步骤，按执行顺序倒序；结果未知的进行中步骤也包含在内
+    pub fn compensation_plan(&self) -> Vec<SagaStep> {
+        let mut steps: Vec<SagaStep> = self
+            .completed
+            .iter()
+            .copied()
+            .chain(self.pending)
+            .filter(SagaStep::compensable)
+            .collect();
+        steps.sort();
+        steps.dedup();
+        steps.reverse();
+        steps
+    }
+
+    /// 中断的 saga 能否直接视为完成：最后一步已确认成功
+    pub fn can_roll_forward(&self) -> bool {
+        self.status == SagaStatus::Running
+            && self.pending.is_none()
+            && self.completed.contains(&SagaStep::SubmitVenue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CreateOrderRequest;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn saga() -> OrderSaga {
+        let request = CreateOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            order_type: "LIMIT".to_string(),
+            side: "BUY".to_string(),
+            quantity: Decimal::ONE,
+            price: Some(Decimal::from(50_000)),
+            stop_price: None,
+            time_in_force: None,
+            expires_at: None,
+            client_order_id: None,
+            tags: Vec::new(),
+            visible_quantity: None,
+            algorithm: None,
+            algorithm_duration_secs: None,
+            environment: crate::models::TradingEnvironment::Live,
+        };
+        OrderSaga::new(request.to_order(Uuid::new_v4()).unwrap())
+    }
+
+    #[test]
+    fn test_compensation_plan() {
+        let mut saga = saga();
+        saga.begin(SagaStep::RiskCheck);
+        saga.complete(SagaStep::RiskCheck);
+        saga.begin(SagaStep::HoldMargin);
+        saga.complete(SagaStep::HoldMargin);
+        saga.begin(SagaStep::PersistOrder);
+        saga.complete(SagaStep::PersistOrder);
+
+        // 提交中途中断：结果未知，需要撤单
+        saga.begin(SagaStep::SubmitVenue);
+        assert!(!saga.can_roll_forward());
+        assert_eq!(
+            saga.compensation_plan(),
+            vec![SagaStep::SubmitVenue, SagaStep::PersistOrder, SagaStep::HoldMargin]
+        );
+
+        // 提交明确失败：交易所没有订单，不需要撤单
+        saga.fail(SagaStep::SubmitVenue, "rejected");
+        assert_eq!(saga.status, SagaStatus::Compensating);
+        assert_eq!(
+            saga.compensation_plan(),
+            vec![SagaStep::PersistOrder, SagaStep::HoldMargin]
+        );
+    }
+
+    #[test]
+    fn test_roll_forward() {
+        let mut saga = saga();
+        for step in [SagaStep::RiskCheck, SagaStep::HoldMargin, SagaStep::PersistOrder, SagaStep::SubmitVenue] {
+            saga.begin(step);
+            saga.complete(step);
+        }
+        assert!(saga.can_roll_forward());
+        saga.finish();
+        assert!(saga.status.is_terminal());
+    }
+}
@@ -0,0 +1,148 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{Amount, Id, Price, Quantity, Side, Symbol, Timestamp, TradingError, TradingResult};
+
+/// 模拟盘成交的场所标识，与真实交易所区分
+pub const SANDBOX_VENUE: &str = "paper";
+
+/// 模拟盘账户
+///
+/// 余额、委托和成交存放在独立的表中，不进入正式账户、盈亏快照和返佣统计。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxAccount {
+    pub user_id: Id,
+    pub balances: BTreeMap<String, Amount>,
+    pub created_at: Timestamp,
+    /// 最近一次重置时间，开通时与创建时间相同
+    pub reset_at: Timestamp,
+    pub reset_count: i32,
+    pub last_active_at: Timestamp,
+}
+
+/// 模拟盘下单请求，按实时行情以市价成交
+#[derive(Debug, Clone, Deserialize)]
+pub struct SandboxOrderRequest {
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: Quantity,
+}
+
+/// 模拟盘成交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxFill {
+    pub id: Id,
+    pub user_id: Id,
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: Quantity,
+    pub price: Price,
+    pub fee: Amount,
+    pub fee_currency: String,
+    pub venue: String,
+    pub created_at: Timestamp,
+}
+
+/// 模拟盘统计
+#[derive(Debug, Clone, Serialize)]
+pub struct SandboxStats {
+    pub user_id: Id,
+    pub sandbox: bool,
+    pub balances: BTreeMap<String, Amount>,
+    pub fills: i64,
+    /// 以计价币种统计的成交额
+    pub volume: BTreeMap<String, Amount>,
+    pub fees: BTreeMap<String, Amount>,
+    pub reset_at: Timestamp,
+}
+
+/// 一笔模拟成交对各币种余额的变动，手续费从买入所得币种中扣除
+pub fn paper_fill_deltas(
+    symbol: &Symbol,
+    side: Side,
+    quantity: Quantity,
+    price: Price,
+    fee_rate: Decimal,
+) -> (Vec<(String, Amount)>, Amount, String) {
+    let notional = quantity * price;
+    match side {
+        Side::Buy => {
+            let fee = quantity * fee_rate;
+            (
+                vec![(symbol.quote.clone(), -notional), (symbol.base.clone(), quantity - fee)],
+                fee,
+                symbol.base.clone(),
+            )
+        }
+        Side::Sell => {
+            let fee = notional * fee_rate;
+            (
+                vec![(symbol.base.clone(), -quantity), (symbol.quote.clone(), notional - fee)],
+                fee,
+                symbol.quote.clone(),
+            )
+        }
+    }
+}
+
+/// 应用余额变动，任一币种不足时整笔拒绝
+pub fn apply_balance_deltas(
+    balances: &mut BTreeMap<String, Amount>,
+    deltas: &[(String, Amount)],
+) -> TradingResult<()> {
+    for (currency, delta) in deltas {
+        let available = balances.get(currency).copied().unwrap_or(Decimal::ZERO);
+        if available + delta < Decimal::ZERO {
+            return Err(TradingError::InsufficientBalance {
+                required: -delta,
+                available,
+            });
+        }
+    }
+    for (currency, delta) in deltas {
+        *balances.entry(currency.clone()).or_insert(Decimal::ZERO) += delta;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paper_fill_deltas() {
+        let symbol = Symbol::new("BTC", "USDT");
+        let (deltas, fee, fee_currency) =
+            paper_fill_deltas(&symbol, Side::Buy, Decimal::ONE, Decimal::from(50_000), Decimal::new(1, 3));
+        assert_eq!(fee, Decimal::new(1, 3));
+        assert_eq!(fee_currency, "BTC");
+        assert_eq!(deltas[0], ("USDT".to_string(), Decimal::from(-50_000)));
+        assert_eq!(deltas[1], ("BTC".to_string(), Decimal::new(999, 3)));
+
+        let (deltas, fee, fee_currency) =
+            paper_fill_deltas(&symbol, Side::Sell, Decimal::ONE, Decimal::from(50_000), Decimal::new(1, 3));
+        assert_eq!(fee, Decimal::from(50));
+        assert_eq!(fee_currency, "USDT");
+        assert_eq!(deltas[1], ("USDT".to_string(), Decimal::from(49_950)));
+    }
+
+    #[test]
+    fn test_apply_balance_deltas_is_all_or_nothing() {
+        let mut balances = BTreeMap::from([("USDT".to_string(), Decimal::from(1_000))]);
+        let deltas = vec![
+            ("USDT".to_string(), Decimal::from(-500)),
+            ("BTC".to_string(), Decimal::from(-1)),
+        ];
+        assert!(apply_balance_deltas(&mut balances, &deltas).is_err());
+        assert_eq!(balances["USDT"], Decimal::from(1_000));
+
+        let deltas = vec![
+            ("USDT".to_string(), Decimal::from(-500)),
+            ("BTC".to_string(), Decimal::new(1, 2)),
+        ];
+        apply_balance_deltas(&mut balances, &deltas).unwrap();
+        assert_eq!(balances["USDT"], Decimal::from(500));
+        assert_eq!(balances["BTC"], Decimal::new(1, 2));
+    }
+}
@@ -0,0 +1,195 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use super::{CreateOrderRequest, Id, Timestamp, TradingError, TradingResult};
+
+/// 定时订单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledOrderStatus {
+    /// 等待到达激活时间
+    Scheduled,
+    /// 已下单，等待到达撤单时间
+    Active,
+    /// 已下单且无需再处理：未设置撤单时间，或到撤单时间时订单已结束
+    Completed,
+    /// 激活前被取消，或到撤单时间时撤销了未成交的订单
+    Cancelled,
+    /// 下单失败
+    Failed,
+}
+
+impl std::fmt::Display for ScheduledOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduledOrderStatus::Scheduled => write!(f, "scheduled"),
+            ScheduledOrderStatus::Active => write!(f, "active"),
+            ScheduledOrderStatus::Completed => write!(f, "completed"),
+            ScheduledOrderStatus::Cancelled => write!(f, "cancelled"),
+            ScheduledOrderStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl std::str::FromStr for ScheduledOrderStatus {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scheduled" => Ok(ScheduledOrderStatus::Scheduled),
+            "active" => Ok(ScheduledOrderStatus::Active),
+            "completed" => Ok(ScheduledOrderStatus::Completed),
+            "cancelled" => Ok(ScheduledOrderStatus::Cancelled),
+            "failed" => Ok(ScheduledOrderStatus::Failed),
+            _ => Err(TradingError::SerializationError(format!(
+                "Invalid scheduled order status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// 定时订单
+///
+/// 到达 `activate_at` 时按 `request` 下单，到达 `cancel_at` 时撤销仍未完全成交的订单。
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledOrder {
+    pub id: Id,
+    pub user_id: Id,
+    pub request: CreateOrderRequest,
+    pub activate_at: Timestamp,
+    pub cancel_at: Option<Timestamp>,
+    pub status: ScheduledOrderStatus,
+    /// 激活后的订单ID
+    pub order_id: Option<Id>,
+    pub error: Option<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+/// 创建定时订单请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateScheduledOrderRequest {
+    #[serde(flatten)]
+    pub order: CreateOrderRequest,
+    /// 激活时间，未指定时立即下单
+    pub activate_at: Option<Timestamp>,
+    /// 撤单时间
+    pub cancel_at: Option<Timestamp>,
+    /// 激活后多少秒撤单，与 `cancel_at` 二选一
+    pub cancel_after_secs: Option<u64>,
+}
+
+impl CreateScheduledOrderRequest {
+    /// 校验时间条件并转换为定时订单，`max_ahead` 为激活时间距今的上限
+    pub fn to_scheduled(self, user_id: Id, now: Timestamp, max_ahead: Duration) -> TradingResult<ScheduledOrder> {
+        // 提前校验订单参数，避免到激活时才失败
+        self.order.to_order(user_id)?;
+
+        let activate_at = self.activate_at.unwrap_or(now).max(now);
+        if activate_at - now > max_ahead {
+            return Err(TradingError::InvalidOrder(format!(
+                "Activation time must be within {} days",
+                max_ahead.num_days()
+            )));
+        }
+
+        let cancel_at = match (self.cancel_at, self.cancel_after_secs) {
+            (Some(_), Some(_)) => {
+                return Err(TradingError::InvalidOrder(
+                    "Specify either cancel_at or cancel_after_secs".to_string(),
+                ))
+            }
+            (Some(cancel_at), None) => Some(cancel_at),
+            (None, Some(secs)) => {
+                let secs = i64::try_from(secs)
+                    .map_err(|_| TradingError::InvalidOrder("cancel_after_secs is too large".to_string()))?;
+                Some(activate_at + Duration::seconds(secs))
+            }
+            (None, None) => None,
+        };
+        if let Some(cancel_at) = cancel_at {
+            if cancel_at <= activate_at {
+                return Err(TradingError::InvalidOrder(
+                    "Cancel time must be after activation time".to_string(),
+                ));
+            }
+        }
+
+        Ok(ScheduledOrder {
+            id: uuid::Uuid::new_v4(),
+            user_id,
+            request: self.order,
+            activate_at,
+            cancel_at,
+            status: ScheduledOrderStatus::Scheduled,
+            order_id: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn request(activate_at: Option<Timestamp>, cancel_at: Option<Timestamp>, cancel_after_secs: Option<u64>) -> CreateScheduledOrderRequest {
+        CreateScheduledOrderRequest {
+            order: CreateOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                order_type: "LIMIT".to_string(),
+                side: "BUY".to_string(),
+                quantity: Decimal::ONE,
+                price: Some(Decimal::from(50_000)),
+                stop_price: None,
+                time_in_force: None,
+                expires_at: None,
+                client_order_id: None,
+                tags: Vec::new(),
+                visible_quantity: None,
+                algorithm: None,
+                algorithm_duration_secs: None,
+                environment: crate::models::TradingEnvironment::Live,
+            },
+            activate_at,
+            cancel_at,
+            cancel_after_secs,
+        }
+    }
+
+    #[test]
+    fn test_schedule_times() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let max_ahead = Duration::days(30);
+        let user_id = Uuid::new_v4();
+
+        // 公告前5分钟下单，未成交10分钟后撤单
+        let activate_at = now + Duration::minutes(55);
+        let scheduled = request(Some(activate_at), None, Some(600))
+            .to_scheduled(user_id, now, max_ahead)
+            .unwrap();
+        assert_eq!(scheduled.status, ScheduledOrderStatus::Scheduled);
+        assert_eq!(scheduled.activate_at, activate_at);
+        assert_eq!(scheduled.cancel_at, Some(activate_at + Duration::minutes(10)));
+
+        // 未指定或已过去的激活时间按当前时间
+        let scheduled = request(Some(now - Duration::minutes(1)), None, None)
+            .to_scheduled(user_id, now, max_ahead)
+            .unwrap();
+        assert_eq!(scheduled.activate_at, now);
+        assert_eq!(scheduled.cancel_at, None);
+
+        assert!(request(None, Some(now), None).to_scheduled(user_id, now, max_ahead).is_err());
+        assert!(request(None, Some(now + Duration::minutes(1)), Some(60))
+            .to_scheduled(user_id, now, max_ahead)
+            .is_err());
+        assert!(request(Some(now + Duration::days(31)), None, None)
+            .to_scheduled(user_id, now, max_ahead)
+            .is_err());
+    }
+}
@@ -0,0 +1,647 @@
+use chrono::{Duration, NaiveDate, NaiveTime, Timelike};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared_models::{decimal::format_amount, SettlementCurrency};
+use std::collections::BTreeMap;
+
+use super::{Amount, Id, PositionSide, Price, Quantity, Timestamp, TradingError};
+
+/// PDF每页行数
+const PDF_LINES_PER_PAGE: usize = 60;
+
+/// 结算运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementRunStatus {
+    Running,
+    Completed,
+}
+
+impl std::fmt::Display for SettlementRunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettlementRunStatus::Running => write!(f, "running"),
+            SettlementRunStatus::Completed => write!(f, "completed"),
+        }
+    }
+}
+
+impl std::str::FromStr for SettlementRunStatus {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(SettlementRunStatus::Running),
+            "completed" => Ok(SettlementRunStatus::Completed),
+            _ => Err(TradingError::SerializationError(format!("Invalid settlement status: {}", s))),
+        }
+    }
+}
+
+/// 单个营业日的结算运行记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementRun {
+    pub business_date: NaiveDate,
+    pub status: SettlementRunStatus,
+    pub period_start: Timestamp,
+    pub period_end: Timestamp,
+    /// 已结算账户数
+    pub accounts: i32,
+    /// 运行次数，重放或中断后重跑时递增
+    pub attempts: i32,
+    pub started_at: Timestamp,
+    pub completed_at: Option<Timestamp>,
+}
+
+/// 截止时刻的币种余额
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettledBalance {
+    pub currency: String,
+    pub total: Amount,
+    pub available: Amount,
+    pub frozen: Amount,
+}
+
+/// 截止时刻的持仓
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettledPosition {
+    pub symbol: String,
+    /// 资金费的结算币种
+    pub quote_currency: String,
+    pub side: PositionSide,
+    pub size: Quantity,
+    pub entry_price: Price,
+    pub mark_price: Price,
+    pub unrealized_pnl: Amount,
+}
+
+/// 账户日终快照
+///
+/// 每个营业日只记录一次，重放时沿用已有快照，保证结果可重现。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub business_date: NaiveDate,
+    pub user_id: Id,
+    pub balances: Vec<SettledBalance>,
+    pub positions: Vec<SettledPosition>,
+    pub taken_at: Timestamp,
+}
+
+/// 结算计提类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementEntryKind {
+    /// 持仓资金费
+    Funding,
+    /// 营业日内成交手续费
+    Fee,
+}
+
+impl std::fmt::Display for SettlementEntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettlementEntryKind::Funding => write!(f, "funding"),
+            SettlementEntryKind::Fee => write!(f, "fee"),
+        }
+    }
+}
+
+impl std::str::FromStr for SettlementEntryKind {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "funding" => Ok(SettlementEntryKind::Funding),
+            "fee" => Ok(SettlementEntryKind::Fee),
+            _ => Err(TradingError::SerializationError(format!("Invalid settlement entry kind: {}", s))),
+        }
+    }
+}
+
+/// 结算计提条目，金额为负表示从账户扣除
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettlementEntry {
+    pub kind: SettlementEntryKind,
+    /// 资金费为交易对，手续费为币种
+    pub reference: String,
+    pub currency: String,
+    pub amount: Amount,
+}
+
+/// 对账单格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementFormat {
+    Csv,
+    Pdf,
+}
+
+impl StatementFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            StatementFormat::Csv => "text/csv; charset=utf-8",
+            StatementFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StatementFormat::Csv => "csv",
+            StatementFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl std::fmt::Display for StatementFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl std::str::FromStr for StatementFormat {
+    type Err = TradingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(StatementFormat::Csv),
+            "pdf" => Ok(StatementFormat::Pdf),
+            _ => Err(TradingError::ConfigError(format!("Invalid statement format: {}", s))),
+        }
+    }
+}
+
+/// 账户日对账单
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyStatement {
+    pub business_date: NaiveDate,
+    pub user_id: Id,
+    pub period_start: Timestamp,
+    pub period_end: Timestamp,
+    pub balances: Vec<SettledBalance>,
+    pub positions: Vec<SettledPosition>,
+    pub entries: Vec<SettlementEntry>,
+    /// 各币种计提合计
+    pub net_changes: BTreeMap<String, Amount>,
+    /// 按账户结算币种折算的汇总，未折算时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valuation: Option<StatementValuation>,
+}
+
+/// 对账单按结算币种折算的汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatementValuation {
+    pub currency: SettlementCurrency,
+    /// 余额合计加未实现盈亏
+    pub account_value: Amount,
+    /// 计提合计
+    pub net_change: Amount,
+}
+
+impl DailyStatement {
+    pub fn new(snapshot: AccountSnapshot, entries: Vec<SettlementEntry>, period: (Timestamp, Timestamp)) -> Self {
+        Self {
+            business_date: snapshot.business_date,
+            user_id: snapshot.user_id,
+            period_start: period.0,
+            period_end: period.1,
+            net_changes: net_changes(&entries),
+            balances: snapshot.balances,
+            positions: snapshot.positions,
+            entries,
+            valuation: None,
+        }
+    }
+
+    pub fn with_valuation(mut self, valuation: StatementValuation) -> Self {
+        self.valuation = Some(valuation);
+        self
+    }
+}
+
+/// 已生成的对账单概要
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementSummary {
+    pub business_date: NaiveDate,
+    pub format: StatementFormat,
+    pub size_bytes: i64,
+    pub generated_at: Timestamp,
+}
+
+/// 结算事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SettlementEvent {
+    /// 单个账户完成结算并生成对账单
+    AccountSettled {
+        business_date: NaiveDate,
+        user_id: Id,
+        net_changes: BTreeMap<String, Amount>,
+        formats: Vec<StatementFormat>,
+        replay: bool,
+    },
+    /// 营业日结算完成
+    RunCompleted {
+        business_date: NaiveDate,
+        accounts: usize,
+        replay: bool,
+    },
+}
+
+impl SettlementEvent {
+    /// 账户级事件所属用户
+    pub fn user_id(&self) -> Option<Id> {
+        match self {
+            SettlementEvent::AccountSettled { user_id, .. } => Some(*user_id),
+            SettlementEvent::RunCompleted { .. } => None,
+        }
+    }
+}
+
+/// 营业日结算时对交易对计提的资金费率
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingRate {
+    pub symbol: String,
+    pub business_date: NaiveDate,
+    /// 计提时刻，即营业日结算区间的结束时间
+    pub funding_time: Timestamp,
+    pub rate: Decimal,
+}
+
+/// 下一次资金费的预测
+#[derive(Debug, Clone, Serialize)]
+pub struct PredictedFunding {
+    pub symbol: String,
+    pub funding_time: Timestamp,
+    pub rate: Decimal,
+}
+
+/// 持仓累计资金费
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FundingSummary {
+    /// 累计支付，为正数
+    pub paid: Amount,
+    /// 累计收取
+    pub received: Amount,
+    /// 收取减去支付
+    pub net: Amount,
+}
+
+/// 营业日对应的结算区间：当日截止时间到次日截止时间
+pub fn settlement_window(date: NaiveDate, cutoff: NaiveTime) -> (Timestamp, Timestamp) {
+    let start = date.and_time(cutoff).and_utc();
+    (start, start + Duration::days(1))
+}
+
+/// 截至当前时间已可结算的最近营业日
+pub fn due_business_date(now: Timestamp, cutoff: NaiveTime) -> NaiveDate {
+    let since_midnight = Duration::seconds(cutoff.num_seconds_from_midnight() as i64);
+    (now - since_midnight).date_naive() - Duration::days(1)
+}
+
+/// 下一次资金费计提时刻，即尚未结束的最早营业日的截止时间
+pub fn next_funding_time(now: Timestamp, cutoff: NaiveTime) -> Timestamp {
+    let next_date = due_business_date(now, cutoff) + Duration::days(1);
+    settlement_window(next_date, cutoff).1
+}
+
+/// 持仓的日资金费，多头在费率为正时支付
+pub fn funding_accrual(position: &SettledPosition, daily_rate: Decimal) -> Amount {
+    let payment = (position.size * position.mark_price * daily_rate).round_dp(8);
+    match position.side {
+        PositionSide::Long => -payment,
+        PositionSide::Short => payment,
+    }
+}
+
+/// 按币种汇总计提
+pub fn net_changes(entries: &[SettlementEntry]) -> BTreeMap<String, Amount> {
+    let mut totals = BTreeMap::new();
+    for entry in entries {
+        *totals.entry(entry.currency.clone()).or_insert(Decimal::ZERO) += entry.amount;
+    }
+    totals
+}
+
+/// 渲染CSV对账单，所有记录共用同一组列
+pub fn render_csv(statement: &DailyStatement) -> String {
+    let date = statement.business_date;
+    let mut body = String::from("record_type,business_date,currency,symbol,side,quantity,price,amount\n");
+    for balance in &statement.balances {
+        body.push_str(&format!(
+            "balance,{},{},,,,,{}\n",
+            date,
+            balance.currency,
+            balance.total.normalize()
+        ));
+    }
+    for position in &statement.positions {
+        body.push_str(&format!(
+            "position,{},{},{},{},{},{},{}\n",
+            date,
+            position.quote_currency,
+            position.symbol,
+            position.side,
+            position.size.normalize(),
+            position.mark_price.normalize(),
+            position.unrealized_pnl.normalize()
+        ));
+    }
+    for entry in &statement.entries {
+        let symbol = match entry.kind {
+            SettlementEntryKind::Funding => entry.reference.as_str(),
+            SettlementEntryKind::Fee => "",
+        };
+        body.push_str(&format!(
+            "{},{},{},{},,,,{}\n",
+            entry.kind,
+            date,
+            entry.currency,
+            symbol,
+            entry.amount.normalize()
+        ));
+    }
+    for (currency, amount) in &statement.net_changes {
+        body.push_str(&format!("net,{},{},,,,,{}\n", date, currency, amount.normalize()));
+    }
+    if let Some(valuation) = &statement.valuation {
+        let currency = valuation.currency.as_str();
+        body.push_str(&format!(
+            "account_value,{},{},,,,,{}\n",
+            date,
+            currency,
+            format_amount(valuation.account_value, currency)
+        ));
+        body.push_str(&format!(
+            "net_value,{},{},,,,,{}\n",
+            date,
+            currency,
+            format_amount(valuation.net_change, currency)
+        ));
+    }
+    body
+}
+
+/// 对账单文本行，用于PDF渲染
+pub fn statement_lines(statement: &DailyStatement) -> Vec<String> {
+    let mut lines = vec![
+        "DAILY ACCOUNT STATEMENT".to_string(),
+        String::new(),
+        format!("Account:       {}", statement.user_id),
+        format!("Business date: {}", statement.business_date),
+        format!(
+            "Period (UTC):  {} - {}",
+            statement.period_start.format("%Y-%m-%d %H:%M"),
+            statement.period_end.format("%Y-%m-%d %H:%M")
+        ),
+        String::new(),
+        "BALANCES".to_string(),
+        format!("{:<10} {:>20} {:>20} {:>20}", "Currency", "Total", "Available", "Frozen"),
+    ];
+    for balance in &statement.balances {
+        lines.push(format!(
+            "{:<10} {:>20} {:>20} {:>20}",
+            balance.currency,
+            balance.total.normalize().to_string(),
+            balance.available.normalize().to_string(),
+            balance.frozen.normalize().to_string()
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push("POSITIONS".to_string());
+    if statement.positions.is_empty() {
+        lines.push("No open positions".to_string());
+    } else {
+        lines.push(format!(
+            "{:<12} {:<6} {:>16} {:>16} {:>16}",
+            "Symbol", "Side", "Size", "Mark price", "Unrealized PnL"
+        ));
+        for position in &statement.positions {
+            lines.push(format!(
+                "{:<12} {:<6} {:>16} {:>16} {:>16}",
+                position.symbol,
+                position.side.to_string(),
+                position.size.normalize().to_string(),
+                position.mark_price.normalize().to_string(),
+                position.unrealized_pnl.normalize().to_string()
+            ));
+        }
+    }
+
+    lines.push(String::new());
+    lines.push("ACCRUALS".to_string());
+    if statement.entries.is_empty() {
+        lines.push("No accruals".to_string());
+    }
+    for entry in &statement.entries {
+        lines.push(format!(
+            "{:<10} {:<12} {:<10} {:>20}",
+            entry.kind.to_string(),
+            entry.reference,
+            entry.currency,
+            entry.amount.normalize().to_string()
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push("NET CHANGE".to_string());
+    for (currency, amount) in &statement.net_changes {
+        lines.push(format!("{:<10} {:>20}", currency, amount.normalize().to_string()));
+    }
+
+    if let Some(valuation) = &statement.valuation {
+        let currency = valuation.currency.as_str();
+        lines.push(String::new());
+        lines.push(format!("VALUATION ({})", currency));
+        lines.push(format!(
+            "{:<14} {:>20}",
+            "Account value",
+            format_amount(valuation.account_value, currency)
+        ));
+        lines.push(format!(
+            "{:<14} {:>20}",
+            "Net change",
+            format_amount(valuation.net_change, currency)
+        ));
+    }
+    lines
+}
+
+/// PDF文本转义，非ASCII字符替换为问号
+fn pdf_escape(line: &str) -> String {
+    let mut escaped = String::with_capacity(line.len());
+    for c in line.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// 渲染PDF对账单
+///
+/// 只输出等宽字体的纯文本页，不依赖外部排版库。
+pub fn render_pdf(statement: &DailyStatement) -> Vec<u8> {
+    let lines = statement_lines(statement);
+    let pages: Vec<&[String]> = lines.chunks(PDF_LINES_PER_PAGE).collect();
+
+    // 对象编号：1 目录，2 页面树，3 字体，之后每页依次占用页面和内容两个对象
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", 4 + i * 2)).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_string(),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            5 + i * 2
+        ));
+        let mut stream = String::from("BT\n/F1 9 Tf\n12 TL\n40 800 Td\n");
+        for line in page.iter() {
+            stream.push_str(&format!("({}) '\n", pdf_escape(line)));
+        }
+        stream.push_str("ET");
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", stream.len(), stream));
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn statement() -> DailyStatement {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let position = SettledPosition {
+            symbol: "BTCUSDT".to_string(),
+            quote_currency: "USDT".to_string(),
+            side: PositionSide::Long,
+            size: Decimal::new(5, 1),
+            entry_price: Decimal::from(60_000),
+            mark_price: Decimal::from(62_000),
+            unrealized_pnl: Decimal::from(1_000),
+        };
+        let entries = vec![
+            SettlementEntry {
+                kind: SettlementEntryKind::Funding,
+                reference: "BTCUSDT".to_string(),
+                currency: "USDT".to_string(),
+                amount: funding_accrual(&position, Decimal::new(3, 4)),
+            },
+            SettlementEntry {
+                kind: SettlementEntryKind::Fee,
+                reference: "USDT".to_string(),
+                currency: "USDT".to_string(),
+                amount: Decimal::new(-125, 2),
+            },
+        ];
+        let snapshot = AccountSnapshot {
+            business_date: date,
+            user_id: Uuid::nil(),
+            balances: vec![SettledBalance {
+                currency: "USDT".to_string(),
+                total: Decimal::from(10_000),
+                available: Decimal::from(9_000),
+                frozen: Decimal::from(1_000),
+            }],
+            positions: vec![position],
+            taken_at: Utc::now(),
+        };
+        DailyStatement::new(snapshot, entries, settlement_window(date, NaiveTime::MIN))
+    }
+
+    #[test]
+    fn test_settlement_window_and_due_date() {
+        let cutoff = NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 4, 30).unwrap();
+        let (start, end) = settlement_window(date, cutoff);
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 4, 30, 22, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2024, 5, 1, 22, 0, 0).unwrap());
+
+        let before = Utc.with_ymd_and_hms(2024, 5, 2, 21, 59, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 5, 2, 22, 0, 0).unwrap();
+        assert_eq!(due_business_date(before, cutoff), date);
+        assert_eq!(due_business_date(after, cutoff), date.succ_opt().unwrap());
+        assert_eq!(
+            due_business_date(Utc.with_ymd_and_hms(2024, 5, 2, 0, 30, 0).unwrap(), NaiveTime::MIN),
+            NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+        );
+
+        assert_eq!(next_funding_time(before, cutoff), before + Duration::minutes(1));
+        assert_eq!(next_funding_time(after, cutoff), after + Duration::days(1));
+    }
+
+    #[test]
+    fn test_funding_and_net_changes() {
+        let statement = statement();
+        // 0.5 * 62000 * 0.0003 = 9.3
+        assert_eq!(statement.entries[0].amount, Decimal::new(-93, 1));
+        let mut short = statement.positions[0].clone();
+        short.side = PositionSide::Short;
+        assert_eq!(funding_accrual(&short, Decimal::new(3, 4)), Decimal::new(93, 1));
+        assert_eq!(statement.net_changes["USDT"], Decimal::new(-1055, 2));
+    }
+
+    #[test]
+    fn test_render_statement() {
+        let statement = statement();
+        let csv = render_csv(&statement);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[1], "balance,2024-05-01,USDT,,,,,10000");
+        assert_eq!(rows[2], "position,2024-05-01,USDT,BTCUSDT,LONG,0.5,62000,1000");
+        assert_eq!(rows[3], "funding,2024-05-01,USDT,BTCUSDT,,,,-9.3");
+        assert_eq!(rows[5], "net,2024-05-01,USDT,,,,,-10.55");
+
+        let pdf = render_pdf(&statement);
+        let text = String::from_utf8(pdf).unwrap();
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("(Business date: 2024-05-01) '"));
+        assert_eq!(pdf_escape("a(b)\\é"), "a\\(b\\)\\\\?");
+    }
+    #[test]
+    fn test_render_valuation() {
+        let statement = statement().with_valuation(StatementValuation {
+            currency: SettlementCurrency::Btc,
+            account_value: Decimal::new(1_774_193_548_387, 13),
+            net_change: Decimal::new(-17, 5),
+        });
+        let csv = render_csv(&statement);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 8);
+        assert_eq!(rows[6], "account_value,2024-05-01,BTC,,,,,0.17741935");
+        assert_eq!(rows[7], "net_value,2024-05-01,BTC,,,,,-0.00017000");
+
+        let lines = statement_lines(&statement);
+        assert!(lines.contains(&"VALUATION (BTC)".to_string()));
+    }
+}
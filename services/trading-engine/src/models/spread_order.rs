@@ -0,0 +1,299 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Amount, Fill, Id, Order, OrderType, Price, Quantity, Side, Symbol, Timestamp, TradingError, TradingResult,
+};
+
+/// 价差单的一条腿
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpreadLegRequest {
+    pub symbol: String,
+    pub side: String,
+    /// 每个价差单位对应的数量，默认为1
+    pub ratio: Option<Decimal>,
+    /// 指定执行的交易所，INTERNAL 表示内部撮合，不指定时按路由策略选择
+    pub venue: Option<String>,
+}
+
+/// 多腿价差单请求
+///
+/// 价差按每个价差单位计算：卖出腿价格乘比例之和减去买入腿价格乘比例之和。
+/// 跨交易所套利时为正的价差收入，基差交易可为负，表示最多愿意支付的价差。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpreadOrderRequest {
+    pub legs: Vec<SpreadLegRequest>,
+    /// 价差单位数，每条腿的数量为该值乘以腿的比例
+    pub quantity: Quantity,
+    /// 最低可接受的价差
+    pub target_spread: Price,
+}
+
+/// 解析后的价差单腿
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpreadLeg {
+    pub symbol: Symbol,
+    pub side: Side,
+    pub ratio: Decimal,
+    pub venue: Option<String>,
+}
+
+impl SpreadLeg {
+    /// 该腿对应的市价单，以价差单ID作为父订单
+    pub fn order(&self, user_id: Id, spread_id: Id, quantity: Quantity) -> TradingResult<Order> {
+        let mut order = Order::new(user_id, self.symbol.clone(), OrderType::Market, self.side, quantity, None, None)?;
+        order.metadata.algorithm = Some("spread".to_string());
+        order.metadata.parent_order_id = Some(spread_id);
+        order.metadata.tags.push(format!("spread:{}", spread_id));
+        Ok(order)
+    }
+}
+
+/// 价差单报价：按订单簿估算的价差已达到目标，可以按顺序执行各腿
+#[derive(Debug, Clone)]
+pub struct SpreadQuote {
+    pub spread_id: Id,
+    /// 各腿及按请求数量生成的订单
+    pub legs: Vec<(SpreadLeg, Order)>,
+    pub quoted_spread: Price,
+    /// 是否对冲超出完成单位数的腿
+    pub hedge_residuals: bool,
+}
+
+impl SpreadOrderRequest {
+    /// 校验请求并解析各条腿
+    pub fn legs(&self, max_legs: usize) -> TradingResult<Vec<SpreadLeg>> {
+        if self.legs.len() < 2 || self.legs.len() > max_legs {
+            return Err(TradingError::InvalidOrder(format!(
+                "Spread orders need between 2 and {} legs",
+                max_legs
+            )));
+        }
+        if self.quantity <= Decimal::ZERO {
+            return Err(TradingError::InvalidOrder("Quantity must be positive".to_string()));
+        }
+
+        self.legs
+            .iter()
+            .map(|leg| {
+                let symbol = leg
+                    .symbol
+                    .parse()
+                    .map_err(|e| TradingError::InvalidOrder(format!("Invalid symbol: {}", e)))?;
+                let side = leg
+                    .side
+                    .parse()
+                    .map_err(|e| TradingError::InvalidOrder(format!("Invalid side: {}", e)))?;
+                let ratio = leg.ratio.unwrap_or(Decimal::ONE);
+                if ratio <= Decimal::ZERO {
+                    return Err(TradingError::InvalidOrder("Leg ratio must be positive".to_string()));
+                }
+                Ok(SpreadLeg {
+                    symbol,
+                    side,
+                    ratio,
+                    venue: leg.venue.clone().filter(|venue| !venue.is_empty()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 腿在价差中的方向：卖出收入为正，买入支出为负
+fn leg_sign(side: Side) -> Decimal {
+    match side {
+        Side::Sell => Decimal::ONE,
+        Side::Buy => -Decimal::ONE,
+    }
+}
+
+/// 按各腿价格计算每个价差单位的价差，任一腿没有价格时返回 None
+pub fn spread_of<I>(legs: I) -> Option<Price>
+where
+    I: IntoIterator<Item = (Side, Decimal, Option<Price>)>,
+{
+    legs.into_iter()
+        .map(|(side, ratio, price)| price.map(|price| leg_sign(side) * ratio * price))
+        .sum()
+}
+
+/// 价差单执行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpreadStatus {
+    Filled,
+    PartiallyFilled,
+    Unfilled,
+}
+
+/// 单条腿的执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SpreadLegFill {
+    pub symbol: Symbol,
+    pub side: Side,
+    pub ratio: Decimal,
+    pub venue: String,
+    pub order_id: Id,
+    pub target_quantity: Quantity,
+    pub filled_quantity: Quantity,
+    pub avg_price: Option<Price>,
+    /// 含对冲订单的手续费
+    pub fee: Amount,
+    /// 超出完成单位数后反向对冲的数量
+    pub hedged_quantity: Quantity,
+    pub hedge_order_id: Option<Id>,
+    pub hedge_avg_price: Option<Price>,
+}
+
+impl SpreadLegFill {
+    /// 该腿成交可支撑的价差单位数
+    pub fn units(&self) -> Quantity {
+        self.filled_quantity / self.ratio
+    }
+
+    /// 超出已完成价差单位数、需要对冲的数量
+    pub fn residual(&self, completed_units: Quantity) -> Quantity {
+        (self.filled_quantity - completed_units * self.ratio).max(Decimal::ZERO)
+    }
+}
+
+/// 价差单执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SpreadExecution {
+    pub spread_id: Id,
+    pub status: SpreadStatus,
+    pub quantity: Quantity,
+    pub target_spread: Price,
+    /// 下单前按订单簿估算的价差
+    pub quoted_spread: Price,
+    /// 各腿都完成的价差单位数
+    pub filled_units: Quantity,
+    /// 按各腿成交均价计算的价差
+    pub avg_spread: Option<Price>,
+    pub total_fee: Amount,
+    pub legs: Vec<SpreadLegFill>,
+    /// 各腿及对冲订单的成交，供持久化
+    #[serde(skip)]
+    pub fills: Vec<Fill>,
+    pub executed_at: Timestamp,
+}
+
+/// 各腿都完成的价差单位数
+pub fn completed_units(legs: &[SpreadLegFill]) -> Quantity {
+    legs.iter()
+        .map(SpreadLegFill::units)
+        .min()
+        .unwrap_or(Decimal::ZERO)
+}
+
+impl SpreadExecution {
+    pub fn new(
+        spread_id: Id,
+        quantity: Quantity,
+        target_spread: Price,
+        quoted_spread: Price,
+        legs: Vec<SpreadLegFill>,
+        fills: Vec<Fill>,
+    ) -> Self {
+        let filled_units = completed_units(&legs);
+        let status = if filled_units >= quantity {
+            SpreadStatus::Filled
+        } else if filled_units > Decimal::ZERO {
+            SpreadStatus::PartiallyFilled
+        } else {
+            SpreadStatus::Unfilled
+        };
+        let avg_spread = if filled_units > Decimal::ZERO {
+            spread_of(legs.iter().map(|leg| (leg.side, leg.ratio, leg.avg_price)))
+        } else {
+            None
+        };
+        let total_fee = legs.iter().map(|leg| leg.fee).sum();
+
+        Self {
+            spread_id,
+            status,
+            quantity,
+            target_spread,
+            quoted_spread,
+            filled_units,
+            avg_spread,
+            total_fee,
+            legs,
+            fills,
+            executed_at: chrono::Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn leg(side: Side, ratio: i64, filled: i64, avg_price: i64) -> SpreadLegFill {
+        SpreadLegFill {
+            symbol: Symbol::new("BTC", "USDT"),
+            side,
+            ratio: Decimal::from(ratio),
+            venue: "INTERNAL".to_string(),
+            order_id: Uuid::new_v4(),
+            target_quantity: Decimal::from(ratio * 2),
+            filled_quantity: Decimal::from(filled),
+            avg_price: Some(Decimal::from(avg_price)),
+            fee: Decimal::ONE,
+            hedged_quantity: Decimal::ZERO,
+            hedge_order_id: None,
+            hedge_avg_price: None,
+        }
+    }
+
+    #[test]
+    fn test_spread_execution_units_and_spread() {
+        // 买1卖2：卖出腿只成交了1个单位，买入腿多出的1需要对冲
+        let legs = vec![leg(Side::Buy, 1, 2, 100), leg(Side::Sell, 2, 2, 51)];
+        assert_eq!(completed_units(&legs), Decimal::ONE);
+        assert_eq!(legs[0].residual(Decimal::ONE), Decimal::ONE);
+        assert_eq!(legs[1].residual(Decimal::ONE), Decimal::ZERO);
+
+        let execution = SpreadExecution::new(
+            Uuid::new_v4(),
+            Decimal::from(2),
+            Decimal::ONE,
+            Decimal::from(2),
+            legs,
+            Vec::new(),
+        );
+        assert_eq!(execution.status, SpreadStatus::PartiallyFilled);
+        assert_eq!(execution.filled_units, Decimal::ONE);
+        assert_eq!(execution.avg_spread, Some(Decimal::from(2)));
+        assert_eq!(execution.total_fee, Decimal::from(2));
+    }
+
+    #[test]
+    fn test_request_legs_validation() {
+        let request = SpreadOrderRequest {
+            legs: vec![SpreadLegRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: "buy".to_string(),
+                ratio: None,
+                venue: None,
+            }],
+            quantity: Decimal::ONE,
+            target_spread: Decimal::ZERO,
+        };
+        assert!(request.legs(4).is_err());
+
+        let mut request = request;
+        request.legs.push(SpreadLegRequest {
+            symbol: "BTC-USDT".to_string(),
+            side: "sell".to_string(),
+            ratio: Some(Decimal::from(2)),
+            venue: Some("kraken".to_string()),
+        });
+        let legs = request.legs(4).unwrap();
+        assert_eq!(legs[0].ratio, Decimal::ONE);
+        assert_eq!(legs[1].side, Side::Sell);
+        assert_eq!(legs[1].venue.as_deref(), Some("kraken"));
+    }
+}
@@ -0,0 +1,262 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{Id, Price, Quantity, Side, Symbol, Timestamp, TradingEnvironment, TradingError, TradingResult};
+
+/// 单笔成交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub id: Id,
+    pub user_id: Id,
+    pub order_id: Id,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub quote_quantity: Decimal,
+    pub fee: Decimal,
+    pub fee_currency: String,
+    /// 是否为挂单方成交
+    pub is_maker: bool,
+    pub venue: String,
+    /// 下单策略标签，例如 strategy:grid@1.2.0
+    pub strategy_tag: Option<String>,
+    /// 测试网成交不计入税务和盈亏报表
+    #[serde(default)]
+    pub environment: TradingEnvironment,
+    pub executed_at: Timestamp,
+}
+
+/// 分页游标，按成交时间和成交ID倒序定位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeCursor {
+    pub executed_at: Timestamp,
+    pub id: Id,
+}
+
+impl TradeCursor {
+    pub fn from_fill(fill: &Fill) -> Self {
+        Self {
+            executed_at: fill.executed_at,
+            id: fill.id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", self.executed_at.timestamp_micros(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> TradingResult<Self> {
+        let invalid = || TradingError::InvalidOrder(format!("Invalid cursor: {}", cursor));
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (micros, id) = text.split_once(':').ok_or_else(invalid)?;
+        let micros: i64 = micros.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            executed_at: Utc.timestamp_micros(micros).single().ok_or_else(invalid)?,
+            id: id.parse::<Uuid>().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// 成交查询条件
+#[derive(Debug, Clone, Default)]
+pub struct TradeQuery {
+    pub symbol: Option<Symbol>,
+    pub side: Option<Side>,
+    pub strategy_tag: Option<String>,
+    pub environment: Option<TradingEnvironment>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub cursor: Option<TradeCursor>,
+    pub limit: u32,
+}
+
+/// 按订单聚合的成交
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderFillSummary {
+    pub order_id: Id,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub fill_count: usize,
+    pub quantity: Quantity,
+    pub quote_quantity: Decimal,
+    /// 成交均价
+    pub average_price: Price,
+    pub fee: Decimal,
+    pub fee_currency: String,
+    pub strategy_tag: Option<String>,
+    pub first_executed_at: Timestamp,
+    pub last_executed_at: Timestamp,
+}
+
+/// 将成交按订单聚合，保持订单首次出现的顺序
+pub fn aggregate_by_order(fills: &[Fill]) -> Vec<OrderFillSummary> {
+    let mut index: HashMap<Id, usize> = HashMap::new();
+    let mut summaries: Vec<OrderFillSummary> = Vec::new();
+
+    for fill in fills {
+        match index.get(&fill.order_id) {
+            Some(&i) => {
+                let summary = &mut summaries[i];
+                summary.fill_count += 1;
+                summary.quantity += fill.quantity;
+                summary.quote_quantity += fill.quote_quantity;
+                summary.fee += fill.fee;
+                summary.first_executed_at = summary.first_executed_at.min(fill.executed_at);
+                summary.last_executed_at = summary.last_executed_at.max(fill.executed_at);
+            }
+            None => {
+                index.insert(fill.order_id, summaries.len());
+                summaries.push(OrderFillSummary {
+                    order_id: fill.order_id,
+                    symbol: fill.symbol.clone(),
+                    side: fill.side,
+                    fill_count: 1,
+                    quantity: fill.quantity,
+                    quote_quantity: fill.quote_quantity,
+                    average_price: fill.price,
+                    fee: fill.fee,
+                    fee_currency: fill.fee_currency.clone(),
+                    strategy_tag: fill.strategy_tag.clone(),
+                    first_executed_at: fill.executed_at,
+                    last_executed_at: fill.executed_at,
+                });
+            }
+        }
+    }
+
+    for summary in &mut summaries {
+        if !summary.quantity.is_zero() {
+            summary.average_price = (summary.quote_quantity / summary.quantity).round_dp(8);
+        }
+    }
+    summaries
+}
+
+/// 成交的流动性方向，挂单方增加流动性，吃单方移除流动性
+pub fn liquidity_label(is_maker: bool) -> &'static str {
+    if is_maker {
+        "maker"
+    } else {
+        "taker"
+    }
+}
+
+/// 按交易所、流动性方向和手续费币种汇总的成交，用于手续费对账和交易成本分析
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidityFeeSummary {
+    pub venue: String,
+    pub liquidity: &'static str,
+    pub fee_currency: String,
+    pub trade_count: i64,
+    pub quantity: Quantity,
+    pub quote_quantity: Decimal,
+    pub fee: Decimal,
+    /// 手续费占成交额的基点，手续费币种不是计价币种时仅供参考
+    pub fee_bps: Option<Decimal>,
+}
+
+impl LiquidityFeeSummary {
+    pub fn new(
+        venue: String,
+        is_maker: bool,
+        fee_currency: String,
+        trade_count: i64,
+        quantity: Quantity,
+        quote_quantity: Decimal,
+        fee: Decimal,
+    ) -> Self {
+        let fee_bps = (!quote_quantity.is_zero())
+            .then(|| (fee / quote_quantity * Decimal::from(10_000)).round_dp(4));
+        Self {
+            venue,
+            liquidity: liquidity_label(is_maker),
+            fee_currency,
+            trade_count,
+            quantity,
+            quote_quantity,
+            fee,
+            fee_bps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(order_id: Id, price: i64, quantity: i64, seconds: i64) -> Fill {
+        Fill {
+            id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            order_id,
+            symbol: Symbol::new("BTC", "USDT"),
+            side: Side::Buy,
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+            quote_quantity: Decimal::from(price * quantity),
+            fee: Decimal::new(1, 1),
+            fee_currency: "USDT".to_string(),
+            is_maker: false,
+            venue: "INTERNAL".to_string(),
+            strategy_tag: None,
+            environment: TradingEnvironment::Live,
+            executed_at: Utc.timestamp_opt(1_700_000_000 + seconds, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = TradeCursor::from_fill(&fill(Uuid::new_v4(), 100, 1, 0));
+        assert_eq!(TradeCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(TradeCursor::decode("not-a-cursor").is_err());
+    }
+
+    #[test]
+    fn test_aggregate_by_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let fills = vec![fill(a, 100, 1, 30), fill(b, 50, 2, 20), fill(a, 103, 2, 10)];
+
+        let summaries = aggregate_by_order(&fills);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].order_id, a);
+        assert_eq!(summaries[0].fill_count, 2);
+        assert_eq!(summaries[0].quantity, Decimal::from(3));
+        assert_eq!(summaries[0].average_price, Decimal::from(102));
+        assert_eq!(summaries[0].fee, Decimal::new(2, 1));
+        assert!(summaries[0].first_executed_at < summaries[0].last_executed_at);
+        assert_eq!(summaries[1].fill_count, 1);
+    }
+
+    #[test]
+    fn test_liquidity_fee_summary() {
+        let summary = LiquidityFeeSummary::new(
+            "kraken".to_string(),
+            true,
+            "USD".to_string(),
+            3,
+            Decimal::from(2),
+            Decimal::from(50_000),
+            Decimal::from(8),
+        );
+        assert_eq!(summary.liquidity, "maker");
+        assert_eq!(summary.fee_bps, Some(Decimal::new(16, 1)));
+
+        let empty = LiquidityFeeSummary::new(
+            "INTERNAL".to_string(),
+            false,
+            "USDT".to_string(),
+            0,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO,
+        );
+        assert_eq!(empty.liquidity, "taker");
+        assert_eq!(empty.fee_bps, None);
+    }
+}
@@ -0,0 +1,112 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+pub use shared_models::user::VerificationLevel;
+
+use super::{Id, Timestamp, TradingError, TradingResult};
+
+/// 用户当前的认证等级
+#[derive(Debug, Clone, Serialize)]
+pub struct UserVerification {
+    pub user_id: Id,
+    pub level: VerificationLevel,
+    /// 最后修改等级的管理员
+    pub updated_by: Option<Id>,
+    pub reason: Option<String>,
+    pub updated_at: Timestamp,
+}
+
+/// 认证等级变更事件
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationChanged {
+    pub user_id: Id,
+    pub previous_level: VerificationLevel,
+    pub level: VerificationLevel,
+    pub updated_by: Id,
+    pub reason: Option<String>,
+    pub changed_at: Timestamp,
+}
+
+/// 认证等级对应的交易限额
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct VerificationLimits {
+    /// 是否允许实盘下单，未认证用户只能模拟交易
+    pub live_trading: bool,
+    /// 单笔订单名义价值上限，None 表示不限
+    pub max_order_notional: Option<Decimal>,
+    /// 单个交易对持仓和挂单名义价值上限，None 表示不限
+    pub max_position_notional: Option<Decimal>,
+}
+
+impl VerificationLimits {
+    pub const UNRESTRICTED: VerificationLimits = VerificationLimits {
+        live_trading: true,
+        max_order_notional: None,
+        max_position_notional: None,
+    };
+
+    /// 检查订单名义价值，`exposure` 为该交易对已有的持仓和挂单名义价值
+    pub fn check(&self, level: VerificationLevel, order_value: Decimal, exposure: Decimal) -> TradingResult<()> {
+        if !self.live_trading {
+            return Err(TradingError::RiskViolation(format!(
+                "Live trading requires identity verification (current level: {})",
+                level
+            )));
+        }
+        if let Some(max) = self.max_order_notional {
+            if order_value > max {
+                return Err(TradingError::RiskViolation(format!(
+                    "Order value {} exceeds {} verification limit {}",
+                    order_value, level, max
+                )));
+            }
+        }
+        if let Some(max) = self.max_position_notional {
+            if exposure + order_value > max {
+                return Err(TradingError::RiskViolation(format!(
+                    "Position value {} exceeds {} verification limit {}",
+                    exposure + order_value,
+                    level,
+                    max
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_limits() {
+        let unverified = VerificationLimits {
+            live_trading: false,
+            max_order_notional: None,
+            max_position_notional: None,
+        };
+        assert!(unverified
+            .check(VerificationLevel::Unverified, Decimal::ONE, Decimal::ZERO)
+            .is_err());
+
+        let level1 = VerificationLimits {
+            live_trading: true,
+            max_order_notional: Some(Decimal::from(1_000)),
+            max_position_notional: Some(Decimal::from(5_000)),
+        };
+        assert!(level1
+            .check(VerificationLevel::Level1, Decimal::from(1_000), Decimal::from(4_000))
+            .is_ok());
+        assert!(level1
+            .check(VerificationLevel::Level1, Decimal::from(1_001), Decimal::ZERO)
+            .is_err());
+        assert!(level1
+            .check(VerificationLevel::Level1, Decimal::from(500), Decimal::from(4_600))
+            .is_err());
+
+        assert!(VerificationLimits::UNRESTRICTED
+            .check(VerificationLevel::Level2, Decimal::from(1_000_000), Decimal::from(1_000_000))
+            .is_ok());
+    }
+}
@@ -5,23 +5,30 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    models::{TradingError, TradingResult},
+    models::{
+        BalanceHold, EnvironmentAccess, FillSettlement, Order, OrderStatus, TradingEnvironment, TradingError,
+        TradingResult,
+    },
     services::PositionService,
     storage::AccountStore,
 };
-use shared_models::AccountType;
+use shared_models::{AccountType, SettlementCurrency};
 
 /// 账户服务
 #[derive(Clone)]
 pub struct AccountService {
     account_store: Arc<AccountStore>,
     position_service: Arc<PositionService>,
+    /// 未单独设置的账户允许交易的环境
+    default_environment_access: EnvironmentAccess,
 }
 
 #[derive(Debug, serde::Serialize)]
 pub struct AccountInfo {
     pub user_id: Uuid,
     pub account_type: AccountType,
+    /// 金额的计价币种，账户汇总默认以 USDT 计价
+    pub currency: SettlementCurrency,
     pub total_balance: Decimal,
     pub available_balance: Decimal,
     pub frozen_balance: Decimal,
@@ -38,6 +45,11 @@ pub struct BalanceInfo {
     pub total: Decimal,
     pub available: Decimal,
     pub frozen: Decimal,
+    /// 构成冻结金额的订单冻结明细
+    pub holds: Vec<BalanceHold>,
+    /// 总额折算为账户结算币种后的价值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settlement_value: Option<Decimal>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -52,6 +64,8 @@ pub struct MarginInfo {
 
 #[derive(Debug, serde::Serialize)]
 pub struct PnLSummary {
+    /// 金额的计价币种
+    pub currency: SettlementCurrency,
     pub total_unrealized_pnl: Decimal,
     pub total_realized_pnl: Decimal,
     pub daily_pnl: Decimal,
@@ -61,6 +75,41 @@ pub struct PnLSummary {
     pub roi: Decimal,
 }
 
+impl AccountInfo {
+    /// 按汇率把金额换算为 `currency`，比例字段不变
+    pub fn in_currency(self, currency: SettlementCurrency, rate: Decimal) -> Self {
+        let convert = |value: Decimal| currency.round(value * rate);
+        Self {
+            currency,
+            total_balance: convert(self.total_balance),
+            available_balance: convert(self.available_balance),
+            frozen_balance: convert(self.frozen_balance),
+            total_equity: convert(self.total_equity),
+            unrealized_pnl: convert(self.unrealized_pnl),
+            margin_used: convert(self.margin_used),
+            margin_available: convert(self.margin_available),
+            ..self
+        }
+    }
+}
+
+impl PnLSummary {
+    /// 按汇率把金额换算为 `currency`，收益率不变
+    pub fn in_currency(self, currency: SettlementCurrency, rate: Decimal) -> Self {
+        let convert = |value: Decimal| currency.round(value * rate);
+        Self {
+            currency,
+            total_unrealized_pnl: convert(self.total_unrealized_pnl),
+            total_realized_pnl: convert(self.total_realized_pnl),
+            daily_pnl: convert(self.daily_pnl),
+            weekly_pnl: convert(self.weekly_pnl),
+            monthly_pnl: convert(self.monthly_pnl),
+            total_pnl: convert(self.total_pnl),
+            ..self
+        }
+    }
+}
+
 impl AccountService {
     pub fn new(
         account_store: Arc<AccountStore>,
@@ -69,7 +118,39 @@ impl AccountService {
         Self {
             account_store,
             position_service,
+            default_environment_access: EnvironmentAccess::LiveOnly,
+        }
+    }
+
+    /// 设置未单独配置的账户允许交易的环境
+    pub fn with_default_environment_access(mut self, access: EnvironmentAccess) -> Self {
+        self.default_environment_access = access;
+        self
+    }
+
+    /// 账户允许交易的环境
+    pub async fn environment_access(&self, user_id: Uuid) -> TradingResult<EnvironmentAccess> {
+        Ok(self
+            .account_store
+            .environment_access(user_id)
+            .await?
+            .unwrap_or(self.default_environment_access))
+    }
+
+    pub async fn set_environment_access(&self, user_id: Uuid, access: EnvironmentAccess) -> TradingResult<()> {
+        self.account_store.set_environment_access(user_id, access).await
+    }
+
+    /// 检查账户能否在指定环境下单
+    pub async fn ensure_environment_allowed(&self, user_id: Uuid, environment: TradingEnvironment) -> TradingResult<()> {
+        let access = self.environment_access(user_id).await?;
+        if !access.allows(environment) {
+            return Err(TradingError::RiskViolation(format!(
+                "Account is restricted to {} and cannot trade on {}",
+                access, environment
+            )));
         }
+        Ok(())
     }
 
     /// 获取账户信息
@@ -103,6 +184,7 @@ impl AccountService {
         Ok(AccountInfo {
             user_id,
             account_type,
+            currency: SettlementCurrency::Usdt,
             total_balance,
             available_balance,
             frozen_balance,
@@ -120,22 +202,61 @@ impl AccountService {
         user_id: Uuid,
         account_type: Option<AccountType>,
     ) -> TradingResult<Vec<BalanceInfo>> {
-        // TODO: 从数据库获取真实余额
-        // 这里返回模拟数据
-        Ok(vec![
-            BalanceInfo {
-                currency: "USDT".to_string(),
-                total: Decimal::from(100000),
-                available: Decimal::from(95000),
-                frozen: Decimal::from(5000),
-            },
-            BalanceInfo {
-                currency: "BTC".to_string(),
-                total: Decimal::new(2, 0),
-                available: Decimal::new(15, 1),
-                frozen: Decimal::new(5, 1),
-            },
-        ])
+        let balances = self.account_store.balances(user_id).await?;
+        let holds = self.account_store.active_holds(user_id).await?;
+
+        Ok(balances
+            .into_iter()
+            .map(|balance| {
+                let currency_holds = holds
+                    .iter()
+                    .filter(|hold| hold.currency == balance.currency)
+                    .cloned()
+                    .collect();
+                BalanceInfo {
+                    available: balance.total - balance.held,
+                    frozen: balance.held,
+                    total: balance.total,
+                    currency: balance.currency,
+                    holds: currency_holds,
+                    settlement_value: None,
+                }
+            })
+            .collect())
+    }
+
+    /// 为订单未成交部分冻结余额，可用余额不足时返回 `InsufficientBalance`
+    ///
+    /// 重复调用（如修改订单后）会把冻结替换为按新价格和数量计算的金额。
+    pub async fn place_hold(&self, order: &Order, price: Decimal) -> TradingResult<()> {
+        let (currency, amount) = BalanceHold::requirement(order, price);
+        self.account_store
+            .place_hold(order.user_id, order.id, &currency, amount)
+            .await
+    }
+
+    /// 成交时把冻结转为扣款并入账，订单完全成交后释放剩余冻结
+    pub async fn settle_fill(
+        &self,
+        order: &Order,
+        quantity: Decimal,
+        price: Decimal,
+        fee: Decimal,
+    ) -> TradingResult<()> {
+        let settlement = FillSettlement::for_fill(order, quantity, price, fee);
+        self.account_store
+            .settle_fill(
+                order.user_id,
+                order.id,
+                &settlement,
+                order.status == OrderStatus::Filled,
+            )
+            .await
+    }
+
+    /// 释放订单剩余冻结，可重复调用
+    pub async fn release_hold(&self, order_id: Uuid) -> TradingResult<()> {
+        self.account_store.release_hold(order_id).await
     }
 
     /// 获取保证金信息
@@ -182,6 +303,7 @@ impl AccountService {
         };
 
         Ok(PnLSummary {
+            currency: SettlementCurrency::Usdt,
             total_unrealized_pnl,
             total_realized_pnl,
             daily_pnl,
@@ -0,0 +1,39 @@
+use serde_json::Value;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    models::{AccountActivity, ActivityCategory, ActivityQuery, TradingResult},
+    storage::AccountActivityStore,
+};
+
+/// 账户动态服务
+///
+/// 汇总用户可见的账户历史：本服务产生的设置变更直接记录，认证服务通过内部接口上报
+/// 登录和API密钥事件，订单和账户止损事件在查询时从各自的表读取。
+pub struct AccountActivityService {
+    store: Arc<AccountActivityStore>,
+}
+
+impl AccountActivityService {
+    pub fn new(store: Arc<AccountActivityStore>) -> Self {
+        Self { store }
+    }
+
+    /// 记录本服务产生的账户动态，失败只记录日志，不影响调用方的操作结果
+    pub async fn record(&self, user_id: Uuid, category: ActivityCategory, action: &str, summary: String, details: Value) {
+        let activity = AccountActivity::new(user_id, category, action, summary, details);
+        if let Err(e) = self.store.record(&activity).await {
+            tracing::error!("Failed to record account activity {} for {}: {}", action, user_id, e);
+        }
+    }
+
+    /// 写入其他服务上报的动态
+    pub async fn ingest(&self, activity: &AccountActivity) -> TradingResult<()> {
+        self.store.record(activity).await
+    }
+
+    pub async fn list(&self, user_id: Uuid, query: &ActivityQuery) -> TradingResult<(Vec<AccountActivity>, bool)> {
+        self.store.list(user_id, query).await
+    }
+}
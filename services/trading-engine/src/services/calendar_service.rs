@@ -0,0 +1,230 @@
+use chrono::{NaiveTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::{
+    config::{trading::MarketHoursConfig, TradingEngineConfig},
+    models::{
+        InstrumentCalendar, MaintenanceWindow, MarketSessionState, MarketSessionStatus, Timestamp,
+        TradingError, TradingResult, TradingSession,
+    },
+};
+
+/// 交易日历服务
+#[derive(Clone)]
+pub struct CalendarService {
+    // 未单独配置的交易对使用的默认日历
+    default_calendar: InstrumentCalendar,
+    calendars: Arc<RwLock<HashMap<String, InstrumentCalendar>>>,
+    maintenance_windows: Arc<RwLock<Vec<MaintenanceWindow>>>,
+}
+
+impl CalendarService {
+    pub fn new(config: TradingEngineConfig) -> Self {
+        Self {
+            default_calendar: Self::build_default_calendar(&config.trading.market_hours),
+            calendars: Arc::new(RwLock::new(HashMap::new())),
+            maintenance_windows: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// 根据市场时间配置构建默认日历
+    fn build_default_calendar(market_hours: &MarketHoursConfig) -> InstrumentCalendar {
+        if !market_hours.enabled {
+            return InstrumentCalendar::always_open("*");
+        }
+
+        let sessions = market_hours
+            .trading_hours
+            .iter()
+            .filter_map(|hour| {
+                let start = NaiveTime::parse_from_str(&hour.start_time, "%H:%M").ok()?;
+                let end = NaiveTime::parse_from_str(&hour.end_time, "%H:%M").ok()?;
+                // 23:59 结束的时段视为到当天最后一秒
+                let end = if end == NaiveTime::from_hms_opt(23, 59, 0).unwrap() {
+                    NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+                } else {
+                    end
+                };
+                Some(TradingSession::new(hour.day_of_week, start, end))
+            })
+            .collect();
+
+        InstrumentCalendar {
+            symbol: "*".to_string(),
+            always_open: false,
+            timezone: market_hours.timezone.clone(),
+            sessions,
+            holidays: market_hours.holidays.clone(),
+        }
+    }
+
+    /// 获取交易对日历
+    pub async fn get_calendar(&self, symbol: &str) -> InstrumentCalendar {
+        let symbol = symbol.to_uppercase();
+        let calendars = self.calendars.read().await;
+        match calendars.get(&symbol) {
+            Some(calendar) => calendar.clone(),
+            None => InstrumentCalendar {
+                symbol,
+                ..self.default_calendar.clone()
+            },
+        }
+    }
+
+    /// 设置交易对日历
+    pub async fn set_calendar(&self, mut calendar: InstrumentCalendar) -> TradingResult<()> {
+        if !calendar.always_open && calendar.offset().is_none() {
+            return Err(TradingError::ConfigError(format!(
+                "Invalid timezone offset: {}",
+                calendar.timezone
+            )));
+        }
+
+        calendar.symbol = calendar.symbol.to_uppercase();
+        tracing::info!("Trading calendar updated for {}", calendar.symbol);
+        self.calendars
+            .write()
+            .await
+            .insert(calendar.symbol.clone(), calendar);
+        Ok(())
+    }
+
+    /// 添加维护窗口（通常来自交易所公告）
+    pub async fn add_maintenance_window(
+        &self,
+        window: MaintenanceWindow,
+    ) -> TradingResult<MaintenanceWindow> {
+        if window.end_time <= window.start_time {
+            return Err(TradingError::ConfigError(
+                "Maintenance window end time must be after start time".to_string(),
+            ));
+        }
+
+        tracing::info!(
+            "Maintenance window {} scheduled: {} - {} ({})",
+            window.id,
+            window.start_time,
+            window.end_time,
+            window.reason
+        );
+
+        let mut windows = self.maintenance_windows.write().await;
+        // 清理已结束的窗口
+        let now = Utc::now();
+        windows.retain(|w| !w.is_finished(now));
+        windows.push(window.clone());
+        windows.sort_by_key(|w| w.start_time);
+
+        Ok(window)
+    }
+
+    /// 删除维护窗口
+    pub async fn remove_maintenance_window(&self, window_id: Uuid) -> bool {
+        let mut windows = self.maintenance_windows.write().await;
+        let before = windows.len();
+        windows.retain(|w| w.id != window_id);
+        windows.len() != before
+    }
+
+    /// 查询维护窗口
+    pub async fn list_maintenance_windows(&self, symbol: Option<&str>) -> Vec<MaintenanceWindow> {
+        let now = Utc::now();
+        self.maintenance_windows
+            .read()
+            .await
+            .iter()
+            .filter(|w| !w.is_finished(now))
+            .filter(|w| symbol.map_or(true, |s| w.applies_to(s)))
+            .cloned()
+            .collect()
+    }
+
+    /// 查询交易对在指定时间的交易状态
+    pub async fn get_session_status(&self, symbol: &str, at: Timestamp) -> MarketSessionStatus {
+        let symbol = symbol.to_uppercase();
+
+        let maintenance = self
+            .maintenance_windows
+            .read()
+            .await
+            .iter()
+            .find(|w| w.applies_to(&symbol) && w.is_active_at(at))
+            .cloned();
+
+        let state = if maintenance.is_some() {
+            MarketSessionState::Maintenance
+        } else if self.get_calendar(&symbol).await.is_session_open(at) {
+            MarketSessionState::Open
+        } else {
+            MarketSessionState::Closed
+        };
+
+        MarketSessionStatus {
+            symbol,
+            state,
+            checked_at: at,
+            maintenance,
+        }
+    }
+
+    /// 检查交易对当前是否允许交易
+    pub async fn ensure_market_open(&self, symbol: &str) -> TradingResult<()> {
+        let status = self.get_session_status(symbol, Utc::now()).await;
+        match status.state {
+            MarketSessionState::Open => Ok(()),
+            MarketSessionState::Closed => Err(TradingError::MarketClosed(status.symbol)),
+            MarketSessionState::Maintenance => {
+                let reason = status
+                    .maintenance
+                    .map(|w| w.reason)
+                    .unwrap_or_default();
+                Err(TradingError::MarketClosed(format!(
+                    "{} (maintenance: {})",
+                    status.symbol, reason
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MaintenanceSource;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_default_calendar_is_always_open() {
+        let service = CalendarService::new(TradingEngineConfig::default());
+        assert!(service.ensure_market_open("BTCUSDT").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_window_blocks_trading() {
+        let service = CalendarService::new(TradingEngineConfig::default());
+        let now = Utc::now();
+
+        let window = service
+            .add_maintenance_window(MaintenanceWindow::new(
+                vec!["ETHUSDT".to_string()],
+                now - Duration::minutes(5),
+                now + Duration::minutes(30),
+                "Network upgrade".to_string(),
+                MaintenanceSource::ExchangeAnnouncement,
+            ))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            service.ensure_market_open("ETHUSDT").await,
+            Err(TradingError::MarketClosed(_))
+        ));
+        assert!(service.ensure_market_open("BTCUSDT").await.is_ok());
+
+        assert!(service.remove_maintenance_window(window.id).await);
+        assert!(service.ensure_market_open("ETHUSDT").await.is_ok());
+    }
+}
@@ -0,0 +1,135 @@
+use rust_decimal::Decimal;
+use shared_models::SettlementCurrency;
+use shared_utils::{AppMetrics, Cache};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::trading::ConversionConfig,
+    engines::ExecutionEngine,
+    models::{Symbol, TradingError, TradingResult},
+    services::{
+        account_service::{AccountInfo, BalanceInfo, PnLSummary},
+        pnl_service::PnlHistory,
+    },
+    storage::AccountStore,
+};
+
+/// 账户汇总金额的计价币种，其他币种按对它的市价换算
+const BASE_CURRENCY: SettlementCurrency = SettlementCurrency::Usdt;
+
+/// 结算币种换算服务
+///
+/// 账户可以选择 USDT、USDC 或 BTC 作为结算和展示币种。所有币种先按
+/// `<币种>USDT` 标记价格折算为 USDT，再折算为目标币种，结果按目标币种精度舍入。
+/// 没有标记价格的币种返回错误，不使用默认价格。
+pub struct ConversionService {
+    config: ConversionConfig,
+    account_store: Arc<AccountStore>,
+    execution_engine: Arc<ExecutionEngine>,
+    /// 币种 -> USDT 价格
+    prices: Cache<String, Decimal>,
+    currencies: Cache<Uuid, SettlementCurrency>,
+}
+
+impl ConversionService {
+    pub fn new(
+        config: ConversionConfig,
+        account_store: Arc<AccountStore>,
+        execution_engine: Arc<ExecutionEngine>,
+        metrics: Arc<AppMetrics>,
+    ) -> Self {
+        let prices = Cache::new("conversion_prices", config.rate_cache.clone()).with_metrics(metrics.clone());
+        let currencies =
+            Cache::new("settlement_currencies", config.currency_cache.clone()).with_metrics(metrics);
+        Self {
+            config,
+            account_store,
+            execution_engine,
+            prices,
+            currencies,
+        }
+    }
+
+    /// 账户结算币种，未设置时使用配置的默认币种
+    pub async fn settlement_currency(&self, user_id: Uuid) -> TradingResult<SettlementCurrency> {
+        self.currencies
+            .get_or_try_load(user_id, || async {
+                Ok(self
+                    .account_store
+                    .settlement_currency(user_id)
+                    .await?
+                    .unwrap_or(self.config.default_currency))
+            })
+            .await
+    }
+
+    /// 设置账户结算币种
+    pub async fn set_settlement_currency(&self, user_id: Uuid, currency: SettlementCurrency) -> TradingResult<()> {
+        self.account_store.set_settlement_currency(user_id, currency).await?;
+        self.currencies.invalidate(&user_id);
+        tracing::info!("Settlement currency of user {} set to {}", user_id, currency);
+        Ok(())
+    }
+
+    /// 币种对 USDT 的价格
+    async fn base_price(&self, currency: &str) -> TradingResult<Decimal> {
+        let currency = currency.to_uppercase();
+        if currency == BASE_CURRENCY.as_str() {
+            return Ok(Decimal::ONE);
+        }
+        let symbol = Symbol::new(&currency, BASE_CURRENCY.as_str());
+        self.prices
+            .get_or_try_load(currency.clone(), || async {
+                self.execution_engine
+                    .mark_price(&symbol)
+                    .await
+                    .filter(|price| *price > Decimal::ZERO)
+                    .ok_or_else(|| TradingError::ExecutionError(format!("No conversion price for {}", currency)))
+            })
+            .await
+    }
+
+    /// 1 单位 `from` 折算为 `to` 的汇率
+    pub async fn rate(&self, from: &str, to: SettlementCurrency) -> TradingResult<Decimal> {
+        if from.eq_ignore_ascii_case(to.as_str()) {
+            return Ok(Decimal::ONE);
+        }
+        Ok(self.base_price(from).await? / self.base_price(to.as_str()).await?)
+    }
+
+    /// 把 `from` 币种金额折算为 `to`，按 `to` 的精度舍入
+    pub async fn convert(&self, amount: Decimal, from: &str, to: SettlementCurrency) -> TradingResult<Decimal> {
+        Ok(to.round(amount * self.rate(from, to).await?))
+    }
+
+    /// 账户信息按结算币种展示
+    pub async fn account_info(&self, info: AccountInfo) -> TradingResult<AccountInfo> {
+        let currency = self.settlement_currency(info.user_id).await?;
+        let rate = self.rate(info.currency.as_str(), currency).await?;
+        Ok(info.in_currency(currency, rate))
+    }
+
+    /// 各币种余额附上折算为结算币种后的价值
+    pub async fn balances(&self, user_id: Uuid, mut balances: Vec<BalanceInfo>) -> TradingResult<Vec<BalanceInfo>> {
+        let currency = self.settlement_currency(user_id).await?;
+        for balance in &mut balances {
+            balance.settlement_value = Some(self.convert(balance.total, &balance.currency, currency).await?);
+        }
+        Ok(balances)
+    }
+
+    /// 盈亏统计按结算币种展示
+    pub async fn pnl_summary(&self, user_id: Uuid, summary: PnLSummary) -> TradingResult<PnLSummary> {
+        let currency = self.settlement_currency(user_id).await?;
+        let rate = self.rate(summary.currency.as_str(), currency).await?;
+        Ok(summary.in_currency(currency, rate))
+    }
+
+    /// 盈亏时间序列按结算币种展示，历史点使用当前汇率
+    pub async fn pnl_history(&self, history: PnlHistory) -> TradingResult<PnlHistory> {
+        let currency = self.settlement_currency(history.user_id).await?;
+        let rate = self.rate(history.currency.as_str(), currency).await?;
+        Ok(history.in_currency(currency, rate))
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::EquityStreamConfig,
+    engines::ExecutionEngine,
+    models::{Position, PositionStatus, Symbol, TradingResult},
+    services::PositionService,
+    storage::AccountStore,
+};
+
+/// 单个仓位按实时标记价格计算的未实现盈亏
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionMark {
+    pub symbol: String,
+    pub size: Decimal,
+    pub entry_price: Decimal,
+    pub mark_price: Decimal,
+    pub unrealized_pnl: Decimal,
+    /// 没有实时价格时使用仓位上保存的标记价格
+    pub stale: bool,
+}
+
+/// 账户权益快照
+#[derive(Debug, Clone, Serialize)]
+pub struct EquitySnapshot {
+    pub user_id: Uuid,
+    pub currency: String,
+    pub balance: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub equity: Decimal,
+    pub positions: Vec<PositionMark>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 按标记价格重新计算仓位未实现盈亏
+fn mark_position(position: &Position, live_price: Option<Decimal>) -> PositionMark {
+    let mut marked = position.clone();
+    let stale = live_price.is_none();
+    if let Some(price) = live_price {
+        marked.mark_price = price;
+    }
+    PositionMark {
+        symbol: position.symbol.to_string(),
+        size: position.size,
+        entry_price: position.entry_price,
+        mark_price: marked.mark_price,
+        unrealized_pnl: marked.calculate_unrealized_pnl(),
+        stale,
+    }
+}
+
+/// 是否推送新快照：首次、距上次推送超过推送间隔，或权益变化超过阈值
+pub fn should_push(
+    last: Option<&EquitySnapshot>,
+    next: &EquitySnapshot,
+    config: &EquityStreamConfig,
+) -> bool {
+    let Some(last) = last else {
+        return true;
+    };
+    let elapsed = (next.timestamp - last.timestamp).to_std().unwrap_or_default();
+    if elapsed >= config.push_interval {
+        return true;
+    }
+    if last.equity.is_zero() {
+        return !next.equity.is_zero();
+    }
+    ((next.equity - last.equity) / last.equity).abs() >= config.min_change_ratio
+}
+
+/// 账户权益推送
+///
+/// 余额取计价货币的账户余额，未实现盈亏按执行引擎的实时标记价格计算，
+/// 同一次计算中每个交易对只查询一次价格。
+pub struct EquityStreamService {
+    config: EquityStreamConfig,
+    account_store: Arc<AccountStore>,
+    position_service: Arc<PositionService>,
+    execution_engine: Arc<ExecutionEngine>,
+}
+
+impl EquityStreamService {
+    pub fn new(
+        config: EquityStreamConfig,
+        account_store: Arc<AccountStore>,
+        position_service: Arc<PositionService>,
+        execution_engine: Arc<ExecutionEngine>,
+    ) -> Self {
+        Self {
+            config,
+            account_store,
+            position_service,
+            execution_engine,
+        }
+    }
+
+    pub fn config(&self) -> &EquityStreamConfig {
+        &self.config
+    }
+
+    /// 计算用户当前的权益快照
+    pub async fn snapshot(&self, user_id: Uuid) -> TradingResult<EquitySnapshot> {
+        let balance = self
+            .account_store
+            .balances(user_id)
+            .await?
+            .into_iter()
+            .filter(|b| b.currency.eq_ignore_ascii_case(&self.config.quote_currency))
+            .map(|b| b.total)
+            .sum();
+
+        let positions = self
+            .position_service
+            .list_positions(user_id, Some(PositionStatus::Open.to_string()), None)
+            .await?;
+        let mut prices: HashMap<Symbol, Option<Decimal>> = HashMap::new();
+        let mut marks = Vec::with_capacity(positions.len());
+        for position in &positions {
+            let price = match prices.get(&position.symbol) {
+                Some(price) => *price,
+                None => {
+                    let price = self.execution_engine.mark_price(&position.symbol).await;
+                    prices.insert(position.symbol.clone(), price);
+                    price
+                }
+            };
+            marks.push(mark_position(position, price));
+        }
+
+        let unrealized_pnl: Decimal = marks.iter().map(|m| m.unrealized_pnl).sum();
+        Ok(EquitySnapshot {
+            user_id,
+            currency: self.config.quote_currency.clone(),
+            balance,
+            unrealized_pnl,
+            equity: balance + unrealized_pnl,
+            positions: marks,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PositionSide;
+    use chrono::Duration;
+
+    fn snapshot(equity: i64, timestamp: DateTime<Utc>) -> EquitySnapshot {
+        EquitySnapshot {
+            user_id: Uuid::nil(),
+            currency: "USDT".to_string(),
+            balance: Decimal::from(equity),
+            unrealized_pnl: Decimal::ZERO,
+            equity: Decimal::from(equity),
+            positions: Vec::new(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_should_push() {
+        let config = EquityStreamConfig::default();
+        let now = Utc::now();
+        let last = snapshot(10_000, now);
+
+        assert!(should_push(None, &last, &config));
+        // 变化低于0.1%且未到推送间隔
+        assert!(!should_push(Some(&last), &snapshot(10_005, now + Duration::seconds(1)), &config));
+        assert!(should_push(Some(&last), &snapshot(10_010, now + Duration::seconds(1)), &config));
+        assert!(should_push(Some(&last), &snapshot(9_990, now + Duration::seconds(1)), &config));
+        assert!(should_push(Some(&last), &snapshot(10_000, now + Duration::seconds(5)), &config));
+    }
+
+    #[test]
+    fn test_mark_position() {
+        let mut position = Position::new(
+            Uuid::nil(),
+            Symbol::new("BTC", "USDT"),
+            PositionSide::Short,
+            Decimal::from(2),
+            Decimal::from(50_000),
+            Decimal::ONE,
+            Decimal::from(100_000),
+        )
+        .unwrap();
+        position.mark_price = Decimal::from(49_000);
+
+        let live = mark_position(&position, Some(Decimal::from(51_000)));
+        assert_eq!(live.unrealized_pnl, Decimal::from(-2_000));
+        assert!(!live.stale);
+
+        let stale = mark_position(&position, None);
+        assert_eq!(stale.mark_price, Decimal::from(49_000));
+        assert_eq!(stale.unrealized_pnl, Decimal::from(2_000));
+        assert!(stale.stale);
+    }
+}
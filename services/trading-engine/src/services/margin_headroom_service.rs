@@ -0,0 +1,243 @@
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use uuid::Uuid;
+
+use crate::{
+    config::RiskConfig,
+    models::{MarginHeadroom, MarginReservation, Order, Position, PositionStatus, TradingResult},
+    services::{position_service::PositionChange, PositionService},
+    storage::{account_store::BalanceRow, AccountStore},
+};
+
+/// 计价货币余额加上持仓未实现盈亏，扣除持仓占用的保证金
+///
+/// 余额取总额而不是可用额，未成交订单的冻结由余量中的订单占用单独扣减。
+fn available_margin(balances: &[BalanceRow], positions: &[Position], quote_currency: &str) -> Decimal {
+    let balance: Decimal = balances
+        .iter()
+        .filter(|b| b.currency.eq_ignore_ascii_case(quote_currency))
+        .map(|b| b.total)
+        .sum();
+    let unrealized_pnl: Decimal = positions.iter().map(|p| p.calculate_unrealized_pnl()).sum();
+    let used_margin: Decimal = positions.iter().map(|p| p.margin).sum();
+    balance + unrealized_pnl - used_margin
+}
+
+/// 单个账户的缓存视图
+#[derive(Default)]
+struct AccountView {
+    headroom: MarginHeadroom,
+    /// 为空或超过有效期时下次使用前重新加载
+    loaded_at: Option<Instant>,
+}
+
+/// 下单前保证金余量服务
+///
+/// 按账户缓存可用保证金和持仓价值，通过风控的订单立即扣减余量，直到成交、
+/// 撤单或拒绝后释放。同一账户的检查和扣减串行执行，并发下单不会超额占用保证金。
+/// 仓位变化事件使缓存失效，下次检查时从账户余额和仓位重新加载。
+pub struct MarginHeadroomService {
+    config: RiskConfig,
+    account_store: Arc<AccountStore>,
+    position_service: Arc<PositionService>,
+    /// 计价货币，只有该货币的余额计入保证金
+    quote_currency: String,
+    accounts: RwLock<HashMap<Uuid, Arc<Mutex<AccountView>>>>,
+}
+
+impl MarginHeadroomService {
+    pub fn new(
+        config: RiskConfig,
+        account_store: Arc<AccountStore>,
+        position_service: Arc<PositionService>,
+        quote_currency: String,
+    ) -> Self {
+        Self {
+            config,
+            account_store,
+            position_service,
+            quote_currency,
+            accounts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn account(&self, user_id: Uuid) -> Arc<Mutex<AccountView>> {
+        if let Some(view) = self.accounts.read().await.get(&user_id) {
+            return view.clone();
+        }
+        self.accounts
+            .write()
+            .await
+            .entry(user_id)
+            .or_default()
+            .clone()
+    }
+
+    /// 缓存过期时从账户余额和仓位重新加载，保留未成交订单的占用
+    async fn refresh(&self, user_id: Uuid, view: &mut AccountView) -> TradingResult<()> {
+        if let Some(loaded_at) = view.loaded_at {
+            if loaded_at.elapsed() < self.config.headroom_cache_ttl {
+                return Ok(());
+            }
+        }
+
+        let balances = self.account_store.balances(user_id).await?;
+        let positions = self
+            .position_service
+            .list_positions(user_id, Some(PositionStatus::Open.to_string()), None)
+            .await?;
+
+        let mut position_values = HashMap::new();
+        for position in &positions {
+            *position_values
+                .entry(position.symbol.to_string())
+                .or_insert(Decimal::ZERO) += position.get_position_value();
+        }
+        view.headroom.available_margin = available_margin(&balances, &positions, &self.quote_currency);
+        view.headroom.position_values = position_values;
+        view.loaded_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// 订单的保证金和敞口占用，按最大杠杆计算保证金
+    pub fn reservation_for(&self, order: &Order, value: Decimal) -> MarginReservation {
+        MarginReservation {
+            symbol: order.symbol.to_string(),
+            quantity: order.remaining_quantity,
+            margin: value / self.config.max_leverage,
+            value,
+        }
+    }
+
+    fn evaluate(&self, headroom: &MarginHeadroom, order_id: Uuid, reservation: &MarginReservation) -> TradingResult<()> {
+        let checks = &self.config.risk_checks.pre_trade_checks;
+        if checks.balance_check {
+            headroom.check_margin(order_id, reservation)?;
+        }
+        if checks.position_limit_check {
+            headroom.check_position_limit(
+                order_id,
+                reservation,
+                self.config.position_limits.max_position_value,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 只检查余量，不占用，用于订单预览
+    pub async fn check(&self, order: &Order, value: Decimal) -> TradingResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let account = self.account(order.user_id).await;
+        let mut view = account.lock().await;
+        self.refresh(order.user_id, &mut view).await?;
+        self.evaluate(&view.headroom, order.id, &self.reservation_for(order, value))
+    }
+
+    /// 检查余量并占用，修改订单时替换原有占用
+    pub async fn reserve(&self, order: &Order, value: Decimal) -> TradingResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let account = self.account(order.user_id).await;
+        let mut view = account.lock().await;
+        self.refresh(order.user_id, &mut view).await?;
+
+        let reservation = self.reservation_for(order, value);
+        self.evaluate(&view.headroom, order.id, &reservation)?;
+        view.headroom.reservations.insert(order.id, reservation);
+        Ok(())
+    }
+
+    /// 释放订单占用
+    pub async fn release(&self, user_id: Uuid, order_id: Uuid) {
+        let account = self.account(user_id).await;
+        account.lock().await.headroom.reservations.remove(&order_id);
+    }
+
+    /// 成交后按剩余数量缩减占用，完全成交或终态时释放
+    pub async fn record_fill(&self, order: &Order) {
+        let account = self.account(order.user_id).await;
+        let mut view = account.lock().await;
+        if order.status.is_terminal() {
+            view.headroom.reservations.remove(&order.id);
+        } else if let Some(reservation) = view.headroom.reservations.get_mut(&order.id) {
+            reservation.shrink_to(order.remaining_quantity);
+        }
+        // 成交会改变仓位和保证金占用
+        view.loaded_at = None;
+    }
+
+    /// 使账户缓存失效
+    pub async fn invalidate(&self, user_id: Uuid) {
+        if let Some(account) = self.accounts.read().await.get(&user_id).cloned() {
+            account.lock().await.loaded_at = None;
+        }
+    }
+
+    /// 账户当前余量
+    pub async fn headroom(&self, user_id: Uuid) -> TradingResult<MarginHeadroom> {
+        let account = self.account(user_id).await;
+        let mut view = account.lock().await;
+        self.refresh(user_id, &mut view).await?;
+        Ok(view.headroom.clone())
+    }
+
+    /// 订阅仓位变化，刷新对应账户的缓存
+    pub fn start(self: Arc<Self>, mut events: broadcast::Receiver<PositionChange>) {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(change) => self.invalidate(change.user_id).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        // 无法确定哪些账户变化，全部重新加载
+                        tracing::warn!("Margin headroom lagged {} position events, invalidating all", skipped);
+                        let accounts: Vec<_> = self.accounts.read().await.values().cloned().collect();
+                        for account in accounts {
+                            account.lock().await.loaded_at = None;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PositionSide, Symbol};
+
+    fn balance(currency: &str, total: i64, held: i64) -> BalanceRow {
+        BalanceRow {
+            currency: currency.to_string(),
+            total: Decimal::from(total),
+            held: Decimal::from(held),
+        }
+    }
+
+    #[test]
+    fn test_available_margin_from_balances_and_positions() {
+        let balances = vec![balance("BTC", 3, 0), balance("usdt", 10_000, 2_000)];
+        let mut position = Position::new(
+            Uuid::new_v4(),
+            Symbol::new("BTC", "USDT"),
+            PositionSide::Long,
+            Decimal::from(2),
+            Decimal::from(100),
+            Decimal::from(10),
+            Decimal::from(20),
+        )
+        .unwrap();
+        position.mark_price = Decimal::from(90);
+
+        // 10000 - 20 (持仓保证金) - 20 (未实现亏损)，冻结余额不重复扣减
+        assert_eq!(available_margin(&balances, &[position], "USDT"), Decimal::from(9_960));
+        assert_eq!(available_margin(&[], &[], "USDT"), Decimal::ZERO);
+    }
+}
@@ -1,11 +1,45 @@
 pub mod account_service;
+pub mod activity_service;
+pub mod calendar_service;
+pub mod conversion_service;
+pub mod equity_stream_service;
 pub mod execution_service;
+pub mod margin_headroom_service;
+pub mod order_rate_service;
 pub mod order_service;
+pub mod notification_service;
+pub mod outbox_relay;
+pub mod pnl_service;
+pub mod portfolio_stop_service;
 pub mod position_service;
+pub mod referral_service;
+pub mod risk_event_service;
 pub mod risk_service;
+pub mod sandbox_service;
+pub mod scheduled_order_service;
+pub mod settlement_service;
+pub mod tax_service;
+pub mod verification_service;
 
 pub use account_service::AccountService;
+pub use activity_service::AccountActivityService;
+pub use calendar_service::CalendarService;
+pub use conversion_service::ConversionService;
+pub use equity_stream_service::EquityStreamService;
 pub use execution_service::ExecutionService;
+pub use margin_headroom_service::MarginHeadroomService;
+pub use order_rate_service::OrderRateService;
 pub use order_service::OrderService;
+pub use notification_service::NotificationService;
+pub use outbox_relay::OutboxRelay;
+pub use pnl_service::PnlService;
+pub use portfolio_stop_service::PortfolioStopService;
 pub use position_service::PositionService;
+pub use referral_service::ReferralService;
+pub use risk_event_service::RiskEventService;
 pub use risk_service::RiskService;
+pub use sandbox_service::SandboxService;
+pub use scheduled_order_service::ScheduledOrderService;
+pub use settlement_service::SettlementService;
+pub use tax_service::TaxService;
+pub use verification_service::VerificationService;
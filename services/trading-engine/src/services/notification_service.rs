@@ -0,0 +1,107 @@
+use chrono::Utc;
+use shared_utils::LeaderElection;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::{
+    config::trading::NotificationInboxConfig,
+    models::{Notification, NotificationCategory, Timestamp, TradingError, TradingResult},
+    storage::NotificationStore,
+};
+
+/// 领导者选举中的任务名
+const NOTIFICATION_CLEANUP_JOB: &str = "notification_cleanup";
+
+/// 站内通知服务
+///
+/// 通知持久化后广播给账户WebSocket，UI据此更新通知铃铛；历史通知按用户分页查询，
+/// 支持按条、按类别或全部标记已读。超过保留期的通知定期删除。
+pub struct NotificationService {
+    config: NotificationInboxConfig,
+    store: Arc<NotificationStore>,
+    events: broadcast::Sender<Notification>,
+}
+
+impl NotificationService {
+    pub fn new(config: NotificationInboxConfig, store: Arc<NotificationStore>) -> Self {
+        let (events, _) = broadcast::channel(1024);
+        Self { config, store, events }
+    }
+
+    /// 订阅新通知
+    pub fn subscribe(&self) -> broadcast::Receiver<Notification> {
+        self.events.subscribe()
+    }
+
+    /// 保存并推送一条通知
+    pub async fn notify(
+        &self,
+        user_id: Uuid,
+        category: NotificationCategory,
+        title: &str,
+        message: &str,
+        data: serde_json::Value,
+    ) -> TradingResult<Notification> {
+        let notification = Notification::new(user_id, category, title, message, data);
+        self.store.insert(&notification).await?;
+        let _ = self.events.send(notification.clone());
+        Ok(notification)
+    }
+
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        category: Option<NotificationCategory>,
+        unread_only: bool,
+        before: Option<Timestamp>,
+        limit: u32,
+    ) -> TradingResult<Vec<Notification>> {
+        self.store.list(user_id, category, unread_only, before, limit).await
+    }
+
+    pub async fn unread_counts(&self, user_id: Uuid) -> TradingResult<Vec<(NotificationCategory, i64)>> {
+        self.store.unread_counts(user_id).await
+    }
+
+    /// 标记已读，不指定ID和类别时标记全部未读通知，返回标记的条数
+    pub async fn mark_read(
+        &self,
+        user_id: Uuid,
+        ids: Option<Vec<Uuid>>,
+        category: Option<NotificationCategory>,
+    ) -> TradingResult<u64> {
+        if ids.as_ref().is_some_and(|ids| ids.is_empty()) {
+            return Ok(0);
+        }
+        self.store
+            .mark_read(user_id, ids.as_deref(), category, Utc::now())
+            .await
+    }
+
+    /// 删除超过保留期的通知，返回删除的条数
+    pub async fn cleanup(&self, now: Timestamp) -> TradingResult<u64> {
+        let retention = chrono::Duration::from_std(self.config.retention)
+            .map_err(|e| TradingError::ConfigError(e.to_string()))?;
+        self.store.purge(now - retention).await
+    }
+
+    /// 启动过期通知清理，多副本时只在领导者副本执行
+    pub fn start(self: Arc<Self>, leader: LeaderElection) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.cleanup_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(_lease) = leader.acquire(NOTIFICATION_CLEANUP_JOB).await else {
+                    continue;
+                };
+                match self.cleanup(Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!("Purged {} expired notifications", purged),
+                    Err(e) => tracing::error!("Notification cleanup failed: {}", e),
+                }
+            }
+        });
+    }
+}
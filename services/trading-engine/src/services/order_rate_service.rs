@@ -0,0 +1,299 @@
+use chrono::{DateTime, Duration, Utc};
+use shared_utils::{AppMetrics, Cache};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    config::{risk::TradingLimits, RiskConfig},
+    engines::risk_engine::UserRiskConfig,
+    models::{TradingError, TradingResult},
+    storage::{OrderStore, RiskConfigStore},
+};
+
+/// 滑动窗口保留的最长时间，与每小时限制对应
+const WINDOW_RETENTION_SECS: i64 = 3600;
+
+/// 清理空闲用户窗口的间隔
+const PRUNE_INTERVAL_SECS: u64 = 60;
+
+/// 用户在各时间窗口内的下单数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderRateCounts {
+    pub per_second: u32,
+    pub per_minute: u32,
+    pub per_hour: u32,
+    /// 同一交易对最近一分钟的下单数
+    pub symbol_per_minute: u32,
+}
+
+/// 本副本所有用户在各时间窗口内的下单总数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct OrderRateTotals {
+    pub per_second: u32,
+    pub per_minute: u32,
+    pub per_hour: u32,
+    /// 最近一小时内下过单的用户数
+    pub active_users: usize,
+}
+
+/// 单个用户最近一小时的下单记录，按提交时间排序
+#[derive(Debug, Default)]
+struct OrderRateWindow {
+    submissions: VecDeque<(DateTime<Utc>, String)>,
+}
+
+impl OrderRateWindow {
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::seconds(WINDOW_RETENTION_SECS);
+        while self.submissions.front().is_some_and(|(at, _)| *at <= cutoff) {
+            self.submissions.pop_front();
+        }
+    }
+
+    fn counts(&self, symbol: &str, now: DateTime<Utc>) -> OrderRateCounts {
+        let second = now - Duration::seconds(1);
+        let minute = now - Duration::minutes(1);
+        let hour = now - Duration::seconds(WINDOW_RETENTION_SECS);
+
+        let mut counts = OrderRateCounts::default();
+        // 从最新的记录往前数，超出一小时即停止
+        for (at, order_symbol) in self.submissions.iter().rev() {
+            if *at <= hour {
+                break;
+            }
+            counts.per_hour += 1;
+            if *at > minute {
+                counts.per_minute += 1;
+                if order_symbol == symbol {
+                    counts.symbol_per_minute += 1;
+                }
+            }
+            if *at > second {
+                counts.per_second += 1;
+            }
+        }
+        counts
+    }
+
+    fn record(&mut self, symbol: &str, at: DateTime<Utc>) {
+        // 从数据库恢复的记录可能与新提交交错，保持按时间排序
+        let index = self.submissions.partition_point(|(existing, _)| *existing <= at);
+        self.submissions.insert(index, (at, symbol.to_string()));
+    }
+}
+
+/// 超出的限制，返回检查名、当前值和阈值
+///
+/// `max_per_minute` 为用户风控配置中的每分钟上限，没有配置时使用全局限制。
+fn exceeded_limit(
+    limits: &TradingLimits,
+    max_per_minute: Option<u32>,
+    symbol: &str,
+    counts: &OrderRateCounts,
+) -> Option<(&'static str, u32, u32)> {
+    if counts.per_second >= limits.max_orders_per_second {
+        return Some(("order_rate_second", counts.per_second, limits.max_orders_per_second));
+    }
+    let max_per_minute = max_per_minute.unwrap_or(limits.max_orders_per_minute);
+    if counts.per_minute >= max_per_minute {
+        return Some(("order_rate_minute", counts.per_minute, max_per_minute));
+    }
+    if counts.per_hour >= limits.max_orders_per_hour {
+        return Some(("order_rate_hour", counts.per_hour, limits.max_orders_per_hour));
+    }
+    if let Some(limit) = limits.symbol_orders_per_minute.get(symbol) {
+        if counts.symbol_per_minute >= *limit {
+            return Some(("order_rate_symbol", counts.symbol_per_minute, *limit));
+        }
+    }
+    None
+}
+
+/// 下单频率服务
+///
+/// 按用户在内存中维护最近一小时的下单滑动窗口，每次提交订单时检查秒、分钟、小时
+/// 和交易对级别的限制并计数。订单本身已持久化，启动时从订单表恢复窗口，重启不会
+/// 清空计数。每个副本只统计经自己提交的订单。每分钟上限优先使用用户风控配置。
+pub struct OrderRateService {
+    config: RiskConfig,
+    order_store: Arc<OrderStore>,
+    metrics: Arc<AppMetrics>,
+    users: Mutex<HashMap<Uuid, OrderRateWindow>>,
+    /// 用户风控配置及其读取缓存，未配置的用户也会缓存
+    user_configs: Option<(Arc<RiskConfigStore>, Cache<Uuid, Option<UserRiskConfig>>)>,
+}
+
+impl OrderRateService {
+    pub fn new(config: RiskConfig, order_store: Arc<OrderStore>, metrics: Arc<AppMetrics>) -> Self {
+        Self {
+            config,
+            order_store,
+            metrics,
+            users: Mutex::new(HashMap::new()),
+            user_configs: None,
+        }
+    }
+
+    /// 按用户风控配置的每分钟下单上限限制
+    pub fn with_user_configs(mut self, store: Arc<RiskConfigStore>) -> Self {
+        let cache = Cache::new("order_rate_user_configs", self.config.user_config_cache.clone())
+            .with_metrics(self.metrics.clone());
+        self.user_configs = Some((store, cache));
+        self
+    }
+
+    /// 用户风控配置中的每分钟下单上限
+    async fn user_minute_limit(&self, user_id: Uuid) -> TradingResult<Option<u32>> {
+        let Some((store, cache)) = &self.user_configs else {
+            return Ok(None);
+        };
+        let config = cache.get_or_try_load(user_id, || store.get(user_id)).await?;
+        Ok(config.map(|config| config.max_orders_per_minute))
+    }
+
+    /// 所有用户的下单总数，用于运维总览
+    pub async fn totals(&self) -> OrderRateTotals {
+        let now = Utc::now();
+        let users = self.users.lock().await;
+        let mut totals = OrderRateTotals::default();
+        for window in users.values() {
+            let counts = window.counts("", now);
+            if counts.per_hour == 0 {
+                continue;
+            }
+            totals.per_second += counts.per_second;
+            totals.per_minute += counts.per_minute;
+            totals.per_hour += counts.per_hour;
+            totals.active_users += 1;
+        }
+        totals
+    }
+
+    /// 记录下单频率拒单指标
+    pub fn record_rejection(&self, check: &str, symbol: &str) {
+        if let Err(e) = self.metrics.record_risk_rejection(check, symbol) {
+            tracing::warn!("Failed to record risk rejection metric: {}", e);
+        }
+    }
+
+    /// 检查下单频率并计入本次提交，超限时拒绝且不计数
+    pub async fn admit(&self, user_id: Uuid, symbol: &str) -> TradingResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        // 在计数锁外读取配置，避免数据库查询阻塞其他用户下单
+        let max_per_minute = self.user_minute_limit(user_id).await?;
+
+        let now = Utc::now();
+        let mut users = self.users.lock().await;
+        let window = users.entry(user_id).or_default();
+        window.prune(now);
+
+        let counts = window.counts(symbol, now);
+        if let Some((check, value, limit)) =
+            exceeded_limit(&self.config.trading_limits, max_per_minute, symbol, &counts)
+        {
+            self.record_rejection(check, symbol);
+            return Err(TradingError::RiskViolation(format!(
+                "Order rate limit exceeded: {} orders for {} (limit {})",
+                value, check, limit
+            )));
+        }
+
+        window.record(symbol, now);
+        Ok(())
+    }
+
+    /// 从订单表恢复最近一小时的下单记录
+    pub async fn restore(&self) -> TradingResult<usize> {
+        let since = Utc::now() - Duration::seconds(WINDOW_RETENTION_SECS);
+        let submissions = self.order_store.list_submissions_since(since).await?;
+        let restored = submissions.len();
+
+        let mut users = self.users.lock().await;
+        for (user_id, symbol, at) in submissions {
+            users.entry(user_id).or_default().record(&symbol, at);
+        }
+        Ok(restored)
+    }
+
+    /// 清理过期记录，移除空闲用户
+    async fn prune(&self) {
+        let now = Utc::now();
+        let mut users = self.users.lock().await;
+        users.retain(|_, window| {
+            window.prune(now);
+            !window.submissions.is_empty()
+        });
+    }
+
+    /// 在接受下单前恢复窗口，之后定期清理
+    pub async fn start(self: Arc<Self>) {
+        match self.restore().await {
+            Ok(restored) => tracing::info!("Restored {} recent order submissions for rate limits", restored),
+            Err(e) => tracing::warn!("Failed to restore order rate windows: {}", e),
+        }
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(PRUNE_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                self.prune().await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_counts_and_limits() {
+        let now = Utc::now();
+        let mut window = OrderRateWindow::default();
+        window.record("BTCUSDT", now - Duration::minutes(30));
+        window.record("ETHUSDT", now - Duration::seconds(30));
+        window.record("BTCUSDT", now - Duration::seconds(10));
+        // 恢复的旧记录插入到正确位置
+        window.record("BTCUSDT", now - Duration::minutes(90));
+
+        window.prune(now);
+        assert_eq!(window.submissions.len(), 3);
+        assert_eq!(
+            window.counts("BTCUSDT", now),
+            OrderRateCounts {
+                per_second: 0,
+                per_minute: 2,
+                per_hour: 3,
+                symbol_per_minute: 1,
+            }
+        );
+
+        let mut limits = TradingLimits::default();
+        let counts = window.counts("BTCUSDT", now);
+        assert!(exceeded_limit(&limits, None, "BTCUSDT", &counts).is_none());
+
+        limits.symbol_orders_per_minute.insert("BTCUSDT".to_string(), 1);
+        assert_eq!(
+            exceeded_limit(&limits, None, "BTCUSDT", &counts),
+            Some(("order_rate_symbol", 1, 1))
+        );
+
+        limits.max_orders_per_minute = 2;
+        assert_eq!(
+            exceeded_limit(&limits, None, "ETHUSDT", &window.counts("ETHUSDT", now)),
+            Some(("order_rate_minute", 2, 2))
+        );
+
+        // 用户风控配置的每分钟上限替代全局限制
+        assert!(exceeded_limit(&limits, Some(3), "ETHUSDT", &window.counts("ETHUSDT", now)).is_none());
+        assert_eq!(
+            exceeded_limit(&limits, Some(1), "ETHUSDT", &window.counts("ETHUSDT", now)),
+            Some(("order_rate_minute", 2, 1))
+        );
+    }
+}
@@ -1,12 +1,32 @@
 use anyhow::Result;
+use chrono::Utc;
 use rust_decimal::Decimal;
+use shared_utils::LeaderElection;
 use std::sync::Arc;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use crate::{
-    models::{CreateOrderRequest, Order, OrderStatus, TradingError, TradingResult},
-    storage::OrderStore,
-    services::{ExecutionService, RiskService},
+    config::{
+        execution::RoutingStrategy,
+        trading::{ExecutionDedupConfig, SagaConfig},
+    },
+    engines::{
+        algo_orders::{is_algo_parent, AlgoOrderStatus, AlgoOrderUpdate, ALGO_VENUE},
+        execution_engine::{ExecutionResult, OrderPreview, INTERNAL_VENUE},
+        risk_engine::{RiskEvent, RiskEventType},
+        ExecutionEngine,
+    },
+    models::{
+        completed_units, CreateOrderRequest, ExecutionReport, Order, OrderSaga, OrderStatus, OrderType, RiskSeverity,
+        SagaStatus, SagaStep, SpreadExecution, SpreadLeg, SpreadLegFill, SpreadOrderRequest, TradingError,
+        TradingResult,
+    },
+    storage::{AlgoOrderStore, ExecutionStore, OrderStore, PortfolioStopStore, SagaStore},
+    services::{
+        AccountService, CalendarService, ExecutionService, MarginHeadroomService, OrderRateService, PositionService,
+        ReferralService, RiskEventService, RiskService, VerificationService,
+    },
 };
 
 /// 订单服务
@@ -15,6 +35,70 @@ pub struct OrderService {
     order_store: Arc<OrderStore>,
     execution_service: Arc<ExecutionService>,
     risk_service: Arc<RiskService>,
+    calendar_service: Arc<CalendarService>,
+    referral_service: Option<Arc<ReferralService>>,
+    routing: Option<(Arc<ExecutionEngine>, RoutingStrategy)>,
+    margin_headroom: Option<Arc<MarginHeadroomService>>,
+    order_rate: Option<Arc<OrderRateService>>,
+    sagas: Option<(Arc<SagaStore>, SagaConfig)>,
+    balance_holds: Option<Arc<AccountService>>,
+    execution_dedup: Option<(Arc<ExecutionStore>, ExecutionDedupConfig)>,
+    portfolio_stops: Option<Arc<PortfolioStopStore>>,
+    verification: Option<Arc<VerificationService>>,
+    environment_access: Option<Arc<AccountService>>,
+    positions: Option<Arc<PositionService>>,
+    risk_events: Option<Arc<RiskEventService>>,
+    algo_orders: Option<Arc<AlgoOrderStore>>,
+}
+
+/// 下单 saga 恢复任务名
+const SAGA_RECOVERY_JOB: &str = "order_saga_recovery";
+
+/// 每轮恢复处理的 saga 数量上限
+const SAGA_RECOVERY_BATCH: i64 = 100;
+
+/// 过期执行回报清理任务名
+const EXECUTION_PURGE_JOB: &str = "execution_report_purge";
+
+/// 过期执行回报的清理间隔
+const EXECUTION_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// 中断算法单恢复任务名
+const ALGO_RECOVERY_JOB: &str = "algo_order_recovery";
+
+/// 中断算法单的检查间隔
+const ALGO_RECOVERY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// 每轮恢复处理的算法单数量上限
+const ALGO_RECOVERY_BATCH: i64 = 100;
+
+/// 下单流程的步骤顺序
+const ORDER_SAGA_STEPS: [SagaStep; 4] = [
+    SagaStep::RiskCheck,
+    SagaStep::HoldMargin,
+    SagaStep::PersistOrder,
+    SagaStep::SubmitVenue,
+];
+
+/// 订单提交步骤的去向
+#[derive(Debug, Clone, Copy)]
+enum Submission<'a> {
+    /// 提交给执行服务，成交通过回报异步入账
+    Simulated,
+    /// 通过执行引擎路由执行，可指定交易所
+    Routed(Option<&'a str>),
+}
+
+/// 订单预览及保证金影响
+#[derive(Debug, serde::Serialize)]
+pub struct OrderPreviewResult {
+    #[serde(flatten)]
+    pub estimate: OrderPreview,
+    pub leverage: Decimal,
+    /// 按估算均价（无成交时按限价或最优价）计算的保证金占用
+    pub margin_required: Decimal,
+    /// 风控检查未通过的原因，通过时为空
+    pub risk_rejection: Option<String>,
 }
 
 impl OrderService {
@@ -22,44 +106,797 @@ impl OrderService {
         order_store: Arc<OrderStore>,
         execution_service: Arc<ExecutionService>,
         risk_service: Arc<RiskService>,
+        calendar_service: Arc<CalendarService>,
     ) -> Self {
         Self {
             order_store,
             execution_service,
             risk_service,
+            calendar_service,
+            referral_service: None,
+            routing: None,
+            margin_headroom: None,
+            order_rate: None,
+            sagas: None,
+            balance_holds: None,
+            execution_dedup: None,
+            portfolio_stops: None,
+            verification: None,
+            environment_access: None,
+            positions: None,
+            risk_events: None,
+            algo_orders: None,
+        }
+    }
+
+    /// 下单流程记录 saga 状态，失败时补偿，进程中断后由恢复任务处理
+    pub fn with_sagas(mut self, store: Arc<SagaStore>, config: SagaConfig) -> Self {
+        self.sagas = Some((store, config));
+        self
+    }
+
+    /// 订单预览使用与下单相同的智能路由
+    pub fn with_routing(mut self, execution_engine: Arc<ExecutionEngine>, strategy: RoutingStrategy) -> Self {
+        self.routing = Some((execution_engine, strategy));
+        self
+    }
+
+    /// 成交手续费参与推荐返佣
+    pub fn with_referrals(mut self, referral_service: Arc<ReferralService>) -> Self {
+        self.referral_service = Some(referral_service);
+        self
+    }
+
+    /// 下单前按账户实时保证金余量检查并占用
+    pub fn with_margin_headroom(mut self, margin_headroom: Arc<MarginHeadroomService>) -> Self {
+        self.margin_headroom = Some(margin_headroom);
+        self
+    }
+
+    /// 下单时冻结账户余额，成交时扣减，撤单和过期时释放
+    pub fn with_balance_holds(mut self, account_service: Arc<AccountService>) -> Self {
+        self.balance_holds = Some(account_service);
+        self
+    }
+
+    /// 按 (交易所, 执行ID) 对成交回报去重，重连后重发的回报不会重复入账
+    pub fn with_execution_dedup(mut self, store: Arc<ExecutionStore>, config: ExecutionDedupConfig) -> Self {
+        self.execution_dedup = Some((store, config));
+        self
+    }
+
+    /// 账户级回撤止损触发后拒绝新订单，直到手动解锁
+    pub fn with_portfolio_stops(mut self, store: Arc<PortfolioStopStore>) -> Self {
+        self.portfolio_stops = Some(store);
+        self
+    }
+
+    /// 每次提交订单时检查并计入用户下单频率
+    pub fn with_order_rate(mut self, order_rate: Arc<OrderRateService>) -> Self {
+        self.order_rate = Some(order_rate);
+        self
+    }
+
+    /// 按认证等级限制实盘下单和名义价值
+    pub fn with_verification(mut self, verification: Arc<VerificationService>) -> Self {
+        self.verification = Some(verification);
+        self
+    }
+
+    /// 下单前检查账户允许交易的环境，限制实盘或测试网
+    pub fn with_environment_access(mut self, account_service: Arc<AccountService>) -> Self {
+        self.environment_access = Some(account_service);
+        self
+    }
+
+    /// 成交回报按成交数量和价格更新仓位
+    pub fn with_positions(mut self, position_service: Arc<PositionService>) -> Self {
+        self.positions = Some(position_service);
+        self
+    }
+
+    /// 风控拒绝的订单写入风险事件队列
+    pub fn with_risk_events(mut self, risk_events: Arc<RiskEventService>) -> Self {
+        self.risk_events = Some(risk_events);
+        self
+    }
+
+    /// 执行实例中断的算法单由恢复任务撤销父订单剩余数量
+    pub fn with_algo_orders(mut self, store: Arc<AlgoOrderStore>) -> Self {
+        self.algo_orders = Some(store);
+        self
+    }
+
+    /// 订单估算价格，市价单按当前市价
+    async fn order_price(&self, order: &Order) -> TradingResult<Decimal> {
+        match order.price.or(order.stop_price) {
+            Some(price) => Ok(price),
+            None => {
+                self.execution_service
+                    .get_market_price(&order.symbol.to_string())
+                    .await
+            }
+        }
+    }
+
+    /// 未成交部分的名义价值，市价单按当前市价估算
+    async fn open_value(&self, order: &Order) -> TradingResult<Decimal> {
+        Ok(self.order_price(order).await? * order.remaining_quantity)
+    }
+
+    /// 检查保证金余量并为订单占用
+    async fn reserve_margin(&self, order: &Order) -> TradingResult<()> {
+        if let Some(margin_headroom) = &self.margin_headroom {
+            let value = self.open_value(order).await?;
+            margin_headroom.reserve(order, value).await?;
+        }
+        Ok(())
+    }
+
+    /// 检查认证等级限额，持仓价值不计入该订单原有的挂单
+    async fn check_verification(&self, order: &Order) -> TradingResult<()> {
+        let verification = match &self.verification {
+            Some(verification) if verification.is_enabled() => verification,
+            _ => return Ok(()),
+        };
+        let value = self.open_value(order).await?;
+        let exposure = match &self.margin_headroom {
+            Some(margin_headroom) => {
+                let headroom = margin_headroom.headroom(order.user_id).await?;
+                let existing = headroom
+                    .reservations
+                    .get(&order.id)
+                    .map(|r| r.value)
+                    .unwrap_or(Decimal::ZERO);
+                headroom.exposure(&order.symbol.to_string()) - existing
+            }
+            None => Decimal::ZERO,
+        };
+        verification.check_order(order, value, exposure).await
+    }
+
+    /// 风控拒单记为风险事件，其他错误不记录；入队失败只记录日志
+    async fn record_rejection(&self, order: &Order, event_type: RiskEventType, error: &TradingError) {
+        let (Some(risk_events), TradingError::RiskViolation(reason)) = (&self.risk_events, error) else {
+            return;
+        };
+        let event = RiskEvent {
+            event_id: Uuid::new_v4(),
+            event_type,
+            user_id: Some(order.user_id),
+            symbol: Some(order.symbol.clone()),
+            severity: RiskSeverity::Medium,
+            message: format!("Order {} rejected: {}", order.id, reason),
+            data: serde_json::json!({
+                "order_id": order.id,
+                "side": order.side,
+                "order_type": order.order_type,
+                "quantity": order.quantity,
+                "price": order.price,
+                "reason": reason
+            }),
+            timestamp: Utc::now(),
+        };
+        if let Err(e) = risk_events.record(&event.to_record()).await {
+            tracing::error!("Failed to record rejection of order {} as risk event: {}", order.id, e);
+        }
+    }
+
+    /// 风险检查，拒单时记录风险事件
+    async fn validate_risk(&self, order: &Order) -> TradingResult<()> {
+        if let Err(e) = self.risk_service.validate_order(order).await {
+            self.record_rejection(order, RiskEventType::OrderRejected, &e).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn release_margin(&self, order: &Order) {
+        if let Some(margin_headroom) = &self.margin_headroom {
+            margin_headroom.release(order.user_id, order.id).await;
+        }
+    }
+
+    /// 为订单未成交部分冻结余额
+    async fn hold_balance(&self, order: &Order) -> TradingResult<()> {
+        if let Some(account_service) = &self.balance_holds {
+            let price = self.order_price(order).await?;
+            account_service.place_hold(order, price).await?;
+        }
+        Ok(())
+    }
+
+    async fn release_balance(&self, order: &Order) -> TradingResult<()> {
+        match &self.balance_holds {
+            Some(account_service) => account_service.release_hold(order.id).await,
+            None => Ok(()),
         }
     }
 
     /// 创建订单
+    ///
+    /// 风控检查、保证金占用和余额冻结、保存订单和提交执行作为一个 saga 执行，
+    /// 任一步失败时逆序补偿已完成的步骤。TWAP/VWAP 父订单交给执行引擎按时间片拆分执行，
+    /// 子订单的成交计入父订单。
     pub async fn create_order(
         &self,
         user_id: Uuid,
         request: CreateOrderRequest,
     ) -> TradingResult<Order> {
-        // 1. 转换请求为订单
-        let mut order = request.to_order(user_id)?;
-
-        // 2. 风险检查
-        self.risk_service.validate_order(&order).await?;
+        let order = request.to_order(user_id)?;
+        let submission = if is_algo_parent(&order) {
+            Submission::Routed(None)
+        } else {
+            Submission::Simulated
+        };
+        let (order, _) = self.place_order(order, submission).await?;
+        Ok(order)
+    }
 
-        // 3. 保存订单
-        self.order_store.create_order(&order).await?;
+    /// 通过执行引擎执行订单，可指定交易所
+    ///
+    /// 与普通下单经过相同的检查和 saga，执行结果中的成交按回报入账（订单、保证金、余额和仓位），
+    /// 市价单未成交的剩余部分撤销并释放占用。
+    pub async fn execute_routed_order(
+        &self,
+        order: Order,
+        venue: Option<&str>,
+    ) -> TradingResult<ExecutionResult> {
+        let (order, result) = self.place_order(order, Submission::Routed(venue)).await?;
+        let result = result.ok_or_else(|| {
+            TradingError::ExecutionError(format!("Order {} has no execution result", order.id))
+        })?;
 
-        // 4. 提交执行
-        match self.execution_service.submit_order(&order).await {
-            Ok(_) => {
-                tracing::info!("Order {} submitted for execution", order.id);
+        for trade in &result.trades {
+            let report = ExecutionReport {
+                venue: result.venue.clone(),
+                execution_id: trade.trade_id.to_string(),
+                order_id: order.id,
+                quantity: trade.quantity,
+                price: trade.price,
+                fee: trade.fee,
+            };
+            if let Err(e) = self.handle_order_fill(&report).await {
+                tracing::error!("Failed to apply fill {} of order {}: {}", trade.trade_id, order.id, e);
+            }
+        }
+        // 本次成交触发的条件单，双方订单按回报入账
+        for report in &result.triggered_fills {
+            if let Err(e) = self.handle_order_fill(report).await {
+                tracing::error!(
+                    "Failed to apply triggered fill {} of order {}: {}",
+                    report.execution_id,
+                    report.order_id,
+                    e
+                );
             }
+        }
+        // 算法单的剩余数量在执行结束时撤销
+        if order.order_type == OrderType::Market && result.venue != ALGO_VENUE {
+            self.cancel_remainder(order.id).await;
+        }
+        Ok(result)
+    }
+
+    /// 撤销市价单未成交的剩余部分，释放保证金占用和余额冻结
+    async fn cancel_remainder(&self, order_id: Uuid) {
+        let mut order = match self.order_store.get_order_by_id(order_id).await {
+            Ok(Some(order)) => order,
+            Ok(None) => return,
             Err(e) => {
-                tracing::error!("Failed to submit order {}: {}", order.id, e);
-                // 标记订单为拒绝状态
-                order.reject(&format!("Execution failed: {}", e))?;
+                tracing::error!("Failed to load order {} after execution: {}", order_id, e);
+                return;
+            }
+        };
+        if !order.status.is_active() {
+            return;
+        }
+        if let Err(e) = order.cancel() {
+            tracing::error!("Failed to cancel remainder of order {}: {}", order_id, e);
+            return;
+        }
+        if let Err(e) = self.order_store.update_order(&order).await {
+            tracing::error!("Failed to save cancelled remainder of order {}: {}", order_id, e);
+            return;
+        }
+        self.release_margin(&order).await;
+        if let Err(e) = self.release_balance(&order).await {
+            tracing::error!("Failed to release balance hold for order {}: {}", order_id, e);
+        }
+    }
+
+    /// 检查交易时段、账户止损锁定和允许交易的环境
+    async fn check_trading_allowed(&self, order: &Order) -> TradingResult<()> {
+        self.calendar_service
+            .ensure_market_open(&order.symbol.to_string())
+            .await?;
+        if let Some(store) = &self.portfolio_stops {
+            if store.is_locked(order.user_id).await? {
+                return Err(TradingError::RiskViolation(
+                    "Trading is locked by portfolio stop".to_string(),
+                ));
+            }
+        }
+        if let Some(accounts) = &self.environment_access {
+            accounts
+                .ensure_environment_allowed(order.user_id, order.metadata.environment)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 按顺序执行下单 saga，返回订单和路由执行的结果
+    async fn place_order(
+        &self,
+        order: Order,
+        submission: Submission<'_>,
+    ) -> TradingResult<(Order, Option<ExecutionResult>)> {
+        self.check_trading_allowed(&order).await?;
+
+        let mut saga = OrderSaga::new(order);
+        let mut execution = None;
+        for step in ORDER_SAGA_STEPS {
+            saga.begin(step);
+            let result = match self.save_saga(&saga, step.compensable()).await {
+                Ok(()) => self.execute_step(step, &saga.order, submission).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(result) => execution = execution.or(result),
+                Err(e) => {
+                    saga.fail(step, &e.to_string());
+                    self.compensate(&mut saga).await;
+                    return Err(e);
+                }
+            }
+            saga.complete(step);
+        }
+
+        saga.finish();
+        if let Err(e) = self.save_saga(&saga, true).await {
+            // 订单已提交，恢复任务会将其视为完成
+            tracing::warn!("Failed to record completed saga for order {}: {}", saga.id, e);
+        }
+        Ok((saga.order, execution))
+    }
+
+    /// 执行多腿价差单
+    ///
+    /// 各腿按顺序以市价执行，每条腿都经过与普通下单相同的检查并按成交入账。后续腿的数量按此前
+    /// 各腿都完成的价差单位数确定；全部执行后，成交超出完成单位数的腿反向对冲多出的部分。
+    pub async fn create_spread_order(
+        &self,
+        user_id: Uuid,
+        request: &SpreadOrderRequest,
+    ) -> TradingResult<SpreadExecution> {
+        let (execution_engine, strategy) = self
+            .routing
+            .as_ref()
+            .ok_or_else(|| TradingError::ConfigError("Order routing is not configured".to_string()))?;
+        let quote = execution_engine
+            .quote_spread(user_id, request, strategy.clone())
+            .await?;
+        let spread_id = quote.spread_id;
+
+        let mut units = request.quantity;
+        let mut leg_fills = Vec::with_capacity(quote.legs.len());
+        let mut fills = Vec::new();
+        for (leg, mut order) in quote.legs {
+            order.quantity = units * leg.ratio;
+            order.remaining_quantity = order.quantity;
+            let mut leg_fill = SpreadLegFill {
+                symbol: leg.symbol.clone(),
+                side: leg.side,
+                ratio: leg.ratio,
+                venue: leg.venue.clone().unwrap_or_else(|| INTERNAL_VENUE.to_string()),
+                order_id: order.id,
+                target_quantity: order.quantity,
+                filled_quantity: Decimal::ZERO,
+                avg_price: None,
+                fee: Decimal::ZERO,
+                hedged_quantity: Decimal::ZERO,
+                hedge_order_id: None,
+                hedge_avg_price: None,
+            };
+            // 前面的腿没有成交时后续腿不再下单
+            if order.quantity > Decimal::ZERO {
+                match self.execute_routed_order(order.clone(), leg.venue.as_deref()).await {
+                    Ok(result) => {
+                        leg_fill.venue = result.venue.clone();
+                        leg_fill.filled_quantity = result.filled_quantity.min(order.quantity);
+                        leg_fill.avg_price = result.avg_price;
+                        leg_fill.fee = result.total_fee;
+                        fills.extend(result.to_fills(&order));
+                    }
+                    Err(e) => {
+                        tracing::warn!("Spread {} leg {} on {} failed: {}", spread_id, order.id, leg.symbol, e);
+                    }
+                }
+            }
+            units = units.min(leg_fill.units());
+            leg_fills.push(leg_fill);
+        }
+
+        let completed = completed_units(&leg_fills);
+        if quote.hedge_residuals {
+            for leg_fill in leg_fills.iter_mut() {
+                let residual = leg_fill.residual(completed);
+                if residual <= Decimal::ZERO {
+                    continue;
+                }
+                let hedge = SpreadLeg {
+                    symbol: leg_fill.symbol.clone(),
+                    side: leg_fill.side.opposite(),
+                    ratio: leg_fill.ratio,
+                    venue: Some(leg_fill.venue.clone()),
+                };
+                let order = hedge.order(user_id, spread_id, residual)?;
+                leg_fill.hedge_order_id = Some(order.id);
+                match self.execute_routed_order(order.clone(), hedge.venue.as_deref()).await {
+                    Ok(result) => {
+                        leg_fill.hedged_quantity = result.filled_quantity.min(residual);
+                        leg_fill.hedge_avg_price = result.avg_price;
+                        leg_fill.fee += result.total_fee;
+                        fills.extend(result.to_fills(&order));
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            "Failed to hedge {} {} of spread {} leg {}: {}",
+                            residual, leg_fill.symbol, spread_id, leg_fill.order_id, e
+                        );
+                    }
+                }
+            }
+        }
+
+        let execution = SpreadExecution::new(
+            spread_id,
+            request.quantity,
+            request.target_spread,
+            quote.quoted_spread,
+            leg_fills,
+            fills,
+        );
+        tracing::info!(
+            "Spread {} filled {}/{} units, quoted spread {}, achieved {:?}",
+            spread_id, execution.filled_units, request.quantity, quote.quoted_spread, execution.avg_spread
+        );
+        Ok(execution)
+    }
+
+    /// 保存 saga 状态，未启用 saga 或 `required` 为 false 时跳过
+    async fn save_saga(&self, saga: &OrderSaga, required: bool) -> TradingResult<()> {
+        match &self.sagas {
+            Some((store, _)) if required => store.save(saga).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// 执行下单步骤，路由执行的提交步骤返回执行结果
+    async fn execute_step(
+        &self,
+        step: SagaStep,
+        order: &Order,
+        submission: Submission<'_>,
+    ) -> TradingResult<Option<ExecutionResult>> {
+        match step {
+            SagaStep::RiskCheck => {
+                // 认证等级和风险检查，通过后计入下单频率
+                self.check_verification(order).await?;
+                self.validate_risk(order).await?;
+                if let Some(order_rate) = &self.order_rate {
+                    if let Err(e) = order_rate.admit(order.user_id, &order.symbol.to_string()).await {
+                        self.record_rejection(order, RiskEventType::OrderRateExceeded, &e).await;
+                        return Err(e);
+                    }
+                }
+            }
+            SagaStep::HoldMargin => {
+                if let Err(e) = self.reserve_margin(order).await {
+                    self.record_rejection(order, RiskEventType::OrderRejected, &e).await;
+                    return Err(e);
+                }
+                if let Err(e) = self.hold_balance(order).await {
+                    self.release_margin(order).await;
+                    return Err(e);
+                }
+            }
+            SagaStep::PersistOrder => self.order_store.create_order(order).await?,
+            SagaStep::SubmitVenue => match (submission, &self.routing) {
+                (Submission::Routed(venue), Some((execution_engine, strategy))) => {
+                    // 不指定交易所时走智能路由，算法父订单在这里开始按时间片执行
+                    let result = match venue {
+                        Some(_) => {
+                            execution_engine
+                                .execute_order_at(order.clone(), strategy.clone(), venue)
+                                .await?
+                        }
+                        None => execution_engine.execute_order(order.clone(), strategy.clone()).await?,
+                    };
+                    return Ok(Some(result));
+                }
+                (Submission::Routed(_), None) => {
+                    return Err(TradingError::ConfigError("Order routing is not configured".to_string()));
+                }
+                (Submission::Simulated, _) => {
+                    if let Err(e) = self.execution_service.submit_order(order).await {
+                        tracing::error!("Failed to submit order {}: {}", order.id, e);
+                        return Err(e);
+                    }
+                    tracing::info!("Order {} submitted for execution", order.id);
+                }
+            },
+        }
+        Ok(None)
+    }
+
+    /// 补偿单个步骤，补偿操作可重复执行
+    async fn compensate_step(&self, step: SagaStep, saga: &OrderSaga) -> TradingResult<()> {
+        match step {
+            SagaStep::RiskCheck => {}
+            SagaStep::HoldMargin => {
+                self.release_margin(&saga.order).await;
+                self.release_balance(&saga.order).await?;
+            }
+            SagaStep::PersistOrder => {
+                // 以库中订单为准，未保存或已拒绝时无需处理
+                let Some(mut order) = self.order_store.get_order_by_id(saga.id).await? else {
+                    return Ok(());
+                };
+                if order.status == OrderStatus::Rejected {
+                    return Ok(());
+                }
+                let reason = saga.error.as_deref().unwrap_or("Order workflow aborted");
+                order.reject(&format!("Execution failed: {}", reason))?;
                 self.order_store.update_order(&order).await?;
-                return Err(e);
+            }
+            SagaStep::SubmitVenue => self.execution_service.cancel_order(&saga.order).await?,
+        }
+        Ok(())
+    }
+
+    /// 逆序补偿已执行的步骤，补偿失败的 saga 标记为 failed 待人工处理
+    async fn compensate(&self, saga: &mut OrderSaga) {
+        saga.status = SagaStatus::Compensating;
+        let mut failed = false;
+        for step in saga.compensation_plan() {
+            if let Err(e) = self.compensate_step(step, saga).await {
+                tracing::error!(
+                    "Failed to compensate step {} of order saga {}: {}",
+                    step,
+                    saga.id,
+                    e
+                );
+                failed = true;
             }
         }
 
-        Ok(order)
+        saga.pending = None;
+        saga.status = if failed { SagaStatus::Failed } else { SagaStatus::Compensated };
+        saga.updated_at = Utc::now();
+        if let Err(e) = self.save_saga(saga, true).await {
+            tracing::warn!("Failed to record compensated saga {}: {}", saga.id, e);
+        }
+    }
+
+    /// 处理进程中断后遗留的 saga：提交已确认的视为完成，其余补偿
+    pub async fn recover_sagas(&self) -> TradingResult<usize> {
+        let Some((store, config)) = &self.sagas else {
+            return Ok(0);
+        };
+        let stale_after = chrono::Duration::from_std(config.stale_after)
+            .map_err(|e| TradingError::ConfigError(e.to_string()))?;
+        let stale = store
+            .list_stale(Utc::now() - stale_after, SAGA_RECOVERY_BATCH)
+            .await?;
+
+        let count = stale.len();
+        for mut saga in stale {
+            if saga.can_roll_forward() {
+                saga.finish();
+                store.save(&saga).await?;
+                tracing::info!("Recovered order saga {} as completed", saga.id);
+                continue;
+            }
+            if saga.error.is_none() {
+                saga.error = Some(format!(
+                    "interrupted during {}",
+                    saga.pending.map(|step| step.to_string()).unwrap_or_else(|| "compensation".to_string())
+                ));
+            }
+            self.compensate(&mut saga).await;
+            tracing::warn!("Recovered order saga {} as {}", saga.id, saga.status);
+        }
+        Ok(count)
+    }
+
+    /// 启动 saga 恢复任务，只在领导者副本执行
+    pub fn start_saga_recovery(self: Arc<Self>, leader: LeaderElection) {
+        let Some((store, config)) = self.sagas.clone() else {
+            tracing::info!("Order sagas disabled");
+            return;
+        };
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(config.recovery_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(lease) = leader.acquire(SAGA_RECOVERY_JOB).await else {
+                    continue;
+                };
+                match self.recover_sagas().await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Recovered {} interrupted order sagas", count),
+                    Err(e) => tracing::error!("Order saga recovery failed: {}", e),
+                }
+                if !lease.is_valid() {
+                    continue;
+                }
+                if let Ok(retention) = chrono::Duration::from_std(config.retention) {
+                    if let Err(e) = store.purge_finished_before(Utc::now() - retention).await {
+                        tracing::warn!("Failed to purge finished order sagas: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// 把算法单子订单的成交计入父订单，执行结束时撤销父订单的剩余数量
+    async fn apply_algo_update(&self, update: AlgoOrderUpdate) {
+        match update {
+            AlgoOrderUpdate::ChildExecuted { parent_order_id, result } => {
+                for trade in &result.trades {
+                    let report = ExecutionReport {
+                        venue: result.venue.clone(),
+                        execution_id: trade.trade_id.to_string(),
+                        order_id: parent_order_id,
+                        quantity: trade.quantity,
+                        price: trade.price,
+                        fee: trade.fee,
+                    };
+                    if let Err(e) = self.handle_order_fill(&report).await {
+                        tracing::error!(
+                            "Failed to apply child fill {} to algo order {}: {}",
+                            trade.trade_id,
+                            parent_order_id,
+                            e
+                        );
+                    }
+                }
+            }
+            AlgoOrderUpdate::Finished(progress) => self.cancel_remainder(progress.parent_order_id).await,
+        }
+    }
+
+    /// 撤销执行实例已中断的算法单，返回处理的数量
+    ///
+    /// 父订单保留已入账的成交，剩余数量撤销并释放保证金占用和余额冻结。
+    pub async fn recover_algo_orders(&self) -> TradingResult<usize> {
+        let Some(store) = &self.algo_orders else {
+            return Ok(0);
+        };
+        let interrupted = store.list_interrupted(ALGO_RECOVERY_BATCH).await?;
+        let count = interrupted.len();
+        for mut progress in interrupted {
+            self.cancel_remainder(progress.parent_order_id).await;
+            progress.status = AlgoOrderStatus::Cancelled;
+            store.save(&progress, Utc::now()).await?;
+            tracing::warn!(
+                "Cancelled interrupted {} order {} after filling {}/{}",
+                progress.algorithm.to_uppercase(),
+                progress.parent_order_id,
+                progress.filled_quantity,
+                progress.target_quantity
+            );
+        }
+        Ok(count)
+    }
+
+    /// 启动算法单成交入账和中断恢复，恢复只在领导者副本执行
+    pub fn start_algo_orders(self: Arc<Self>, mut updates: mpsc::UnboundedReceiver<AlgoOrderUpdate>, leader: LeaderElection) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            while let Some(update) = updates.recv().await {
+                service.apply_algo_update(update).await;
+            }
+        });
+
+        if self.algo_orders.is_none() {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(ALGO_RECOVERY_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(_lease) = leader.acquire(ALGO_RECOVERY_JOB).await else {
+                    continue;
+                };
+                match self.recover_algo_orders().await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Recovered {} interrupted algo orders", count),
+                    Err(e) => tracing::error!("Algo order recovery failed: {}", e),
+                }
+            }
+        });
+    }
+
+    /// 启动过期执行回报清理，只在领导者副本执行
+    pub fn start_execution_purge(self: Arc<Self>, leader: LeaderElection) {
+        let Some((store, config)) = self.execution_dedup.clone() else {
+            return;
+        };
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(EXECUTION_PURGE_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(_lease) = leader.acquire(EXECUTION_PURGE_JOB).await else {
+                    continue;
+                };
+                let Ok(retention) = chrono::Duration::from_std(config.retention) else {
+                    continue;
+                };
+                match store.purge_before(Utc::now() - retention).await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!("Purged {} expired execution reports", purged),
+                    Err(e) => tracing::warn!("Failed to purge execution reports: {}", e),
+                }
+            }
+        });
+    }
+
+    /// 预览订单：估算成交均价、滑点、手续费和保证金占用，不保存也不下单
+    pub async fn preview_order(
+        &self,
+        user_id: Uuid,
+        request: CreateOrderRequest,
+        leverage: Option<Decimal>,
+    ) -> TradingResult<OrderPreviewResult> {
+        let (execution_engine, strategy) = self
+            .routing
+            .as_ref()
+            .ok_or_else(|| TradingError::ConfigError("Order routing is not configured".to_string()))?;
+        let order = request.to_order(user_id)?;
+        let leverage = leverage.unwrap_or(Decimal::ONE);
+        if leverage <= Decimal::ZERO {
+            return Err(TradingError::InvalidOrder("Leverage must be positive".to_string()));
+        }
+
+        let estimate = execution_engine.preview_order(&order, strategy.clone()).await?;
+
+        let reference_price = estimate
+            .avg_price
+            .or(order.price)
+            .or(estimate.best_price)
+            .unwrap_or(Decimal::ZERO);
+        let margin_required = self.risk_service.calculate_margin_requirement(
+            &estimate.symbol,
+            order.quantity,
+            reference_price,
+            leverage,
+        );
+        let mut risk_rejection = self
+            .risk_service
+            .validate_order(&order)
+            .await
+            .err()
+            .map(|e| e.to_string());
+        if let (None, Some(margin_headroom)) = (&risk_rejection, &self.margin_headroom) {
+            let value = self.open_value(&order).await?;
+            risk_rejection = margin_headroom
+                .check(&order, value)
+                .await
+                .err()
+                .map(|e| e.to_string());
+        }
+
+        Ok(OrderPreviewResult {
+            estimate,
+            leverage,
+            margin_required,
+            risk_rejection,
+        })
     }
 
     /// 查询订单列表
@@ -80,7 +917,7 @@ impl OrderService {
         };
 
         self.order_store
-            .list_orders(user_id, status_filter, symbol, limit, offset)
+            .list_order_history(user_id, status_filter, symbol, limit, offset)
             .await
     }
 
@@ -136,16 +973,24 @@ impl OrderService {
         // 4. 验证修改后的订单
         order.validate()?;
 
-        // 5. 风险检查
-        self.risk_service.validate_order(&order).await?;
+        // 5. 检查交易时段
+        self.calendar_service
+            .ensure_market_open(&order.symbol.to_string())
+            .await?;
 
-        // 6. 更新时间戳
+        // 6. 风险检查，替换原有保证金占用和余额冻结
+        self.check_verification(&order).await?;
+        self.validate_risk(&order).await?;
+        self.reserve_margin(&order).await?;
+        self.hold_balance(&order).await?;
+
+        // 7. 更新时间戳
         order.updated_at = chrono::Utc::now();
 
-        // 7. 保存订单
+        // 8. 保存订单
         self.order_store.update_order(&order).await?;
 
-        // 8. 通知执行服务
+        // 9. 通知执行服务
         self.execution_service.update_order(&order).await?;
 
         Ok(order)
@@ -163,23 +1008,59 @@ impl OrderService {
         // 2. 取消订单
         order.cancel()?;
 
-        // 3. 保存订单
+        // 3. 保存订单并释放保证金占用和余额冻结
         self.order_store.update_order(&order).await?;
+        self.release_margin(&order).await;
+        if let Err(e) = self.release_balance(&order).await {
+            tracing::error!("Failed to release balance hold for order {}: {}", order.id, e);
+        }
 
-        // 4. 通知执行服务
-        self.execution_service.cancel_order(&order).await?;
+        // 4. 通知执行服务，算法单停止提交子订单
+        match &self.routing {
+            Some((execution_engine, _)) if is_algo_parent(&order) => {
+                if !execution_engine.cancel_algo_order(order.id).await {
+                    tracing::warn!("Algo order {} is not running on this instance", order.id);
+                }
+            }
+            _ => self.execution_service.cancel_order(&order).await?,
+        }
 
         Ok(order)
     }
 
-    /// 处理订单成交
-    pub async fn handle_order_fill(
-        &self,
-        order_id: Uuid,
-        fill_quantity: Decimal,
-        fill_price: Decimal,
-        fee: Decimal,
-    ) -> TradingResult<()> {
+    /// 处理订单成交回报
+    ///
+    /// 启用去重时回报登记和订单更新在同一事务中提交，已登记过的回报直接忽略。
+    /// 订单更新失败时登记一起回滚，交易所重发或对账时可以重新处理；
+    /// 订单更新成功后的余额结算和返佣失败只记录错误。
+    pub async fn handle_order_fill(&self, report: &ExecutionReport) -> TradingResult<()> {
+        let dedup = match &self.execution_dedup {
+            Some((store, _)) => {
+                // 提前过滤已处理的重发，并发的重复回报由事务内的主键冲突拦截
+                if store.contains(report).await? {
+                    self.log_duplicate(report);
+                    return Ok(());
+                }
+                true
+            }
+            None => false,
+        };
+        self.apply_fill(report, dedup).await
+    }
+
+    fn log_duplicate(&self, report: &ExecutionReport) {
+        tracing::debug!(
+            "Ignoring duplicate execution report {} from {} for order {}",
+            report.execution_id,
+            report.venue,
+            report.order_id
+        );
+    }
+
+    async fn apply_fill(&self, report: &ExecutionReport, dedup: bool) -> TradingResult<()> {
+        let order_id = report.order_id;
+        let (fill_quantity, fill_price, fee) = (report.quantity, report.price, report.fee);
+
         // 1. 获取订单
         let mut order = self
             .order_store
@@ -190,10 +1071,62 @@ impl OrderService {
         // 2. 更新成交信息
         order.update_fill(fill_quantity, fill_price, fee)?;
 
-        // 3. 保存订单
-        self.order_store.update_order(&order).await?;
+        // 3. 保存订单，按剩余数量更新保证金占用
+        if dedup {
+            if !self.order_store.update_order_fill(&order, report).await? {
+                self.log_duplicate(report);
+                return Ok(());
+            }
+        } else {
+            self.order_store.update_order(&order).await?;
+        }
+        if let Some(margin_headroom) = &self.margin_headroom {
+            margin_headroom.record_fill(&order).await;
+        }
+        if let Some(account_service) = &self.balance_holds {
+            // 订单已更新，失败时记录错误由对账处理，不能重试成交
+            if let Err(e) = account_service
+                .settle_fill(&order, fill_quantity, fill_price, fee)
+                .await
+            {
+                tracing::error!("Failed to settle balance for order {} fill: {}", order_id, e);
+            }
+        }
+        if let Some(position_service) = &self.positions {
+            let margin = self.risk_service.calculate_margin_requirement(
+                &order.symbol.to_string(),
+                fill_quantity,
+                fill_price,
+                Decimal::ONE,
+            );
+            if let Err(e) = position_service
+                .update_position(
+                    order.user_id,
+                    order.symbol.clone(),
+                    order.side,
+                    fill_quantity,
+                    fill_price,
+                    Decimal::ONE,
+                    margin,
+                )
+                .await
+            {
+                tracing::error!("Failed to update position for order {} fill: {}", order_id, e);
+            }
+        }
 
-        // 4. 如果订单完全成交，通知相关服务
+        // 4. 手续费分成给推荐人，失败不影响成交处理
+        if let Some(referral_service) = &self.referral_service {
+            // 此处没有成交ID，每次回报单独记账
+            if let Err(e) = referral_service
+                .accrue_fee(order.user_id, fee, &order.fee_currency, Uuid::new_v4())
+                .await
+            {
+                tracing::warn!("Failed to accrue referral fee for order {}: {}", order_id, e);
+            }
+        }
+
+        // 5. 如果订单完全成交，通知相关服务
         if order.status == OrderStatus::Filled {
             tracing::info!("Order {} fully filled", order_id);
         }
@@ -206,6 +1139,13 @@ impl OrderService {
         self.order_store.get_active_orders(user_id).await
     }
 
+    /// 策略版本切换时替换活跃订单的策略标签
+    pub async fn retag_strategy_orders(&self, from_tag: &str, to_tag: &str) -> TradingResult<u64> {
+        let updated = self.order_store.retag_active_orders(from_tag, to_tag).await?;
+        tracing::info!("Retagged {} active orders from {} to {}", updated, from_tag, to_tag);
+        Ok(updated)
+    }
+
     /// 取消所有订单
     pub async fn cancel_all_orders(&self, user_id: Uuid, symbol: Option<String>) -> TradingResult<Vec<Order>> {
         let active_orders = if let Some(symbol) = symbol {
@@ -242,6 +1182,10 @@ impl OrderService {
             if let Err(e) = self.order_store.update_order(&order).await {
                 tracing::error!("Failed to save expired order {}: {}", order.id, e);
             } else {
+                self.release_margin(&order).await;
+                if let Err(e) = self.release_balance(&order).await {
+                    tracing::error!("Failed to release balance hold for order {}: {}", order.id, e);
+                }
                 tracing::info!("Order {} expired", order.id);
             }
         }
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use chrono::Utc;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use shared_utils::RetryPolicy;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config::OutboxConfig;
+use crate::models::{TradingError, TradingResult};
+use crate::storage::OutboxStore;
+
+/// 已投递事件的清理间隔
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 一批投递的结果
+#[derive(Debug, Default)]
+pub struct RelayBatch {
+    pub fetched: usize,
+    pub published: usize,
+    /// 第一条投递失败事件的错误，之后的事件留到下一批
+    pub error: Option<String>,
+}
+
+/// 连续失败时的退避时间，从轮询间隔开始翻倍
+fn backoff(poll_interval: Duration, max_backoff: Duration, failures: u32) -> Duration {
+    RetryPolicy::exponential(poll_interval, max_backoff).base_delay(failures.saturating_sub(1))
+}
+
+/// 发件箱投递任务
+///
+/// 按写入顺序把发件箱中的事件发送到Kafka，发送成功后在同一个投递事务中标记。
+/// 标记提交前进程退出的事件会在下次投递时重发，因此投递语义为至少一次，
+/// 消费方按消息 id 去重。某条事件发送失败时本批停止，后续事件不会越过它先发出。
+pub struct OutboxRelay {
+    config: OutboxConfig,
+    store: Arc<OutboxStore>,
+    producer: Option<FutureProducer>,
+}
+
+impl OutboxRelay {
+    pub fn new(config: OutboxConfig, store: Arc<OutboxStore>) -> Result<Self> {
+        let producer = if config.enabled {
+            let producer: FutureProducer = ClientConfig::new()
+                .set("bootstrap.servers", &config.brokers)
+                .set("message.timeout.ms", config.send_timeout.as_millis().to_string())
+                .set("enable.idempotence", "true")
+                .set("acks", "all")
+                .create()?;
+            Some(producer)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            config,
+            store,
+            producer,
+        })
+    }
+
+    /// 投递一批事件，其他副本正在投递时返回空结果
+    pub async fn relay_batch(&self) -> TradingResult<RelayBatch> {
+        let Some(producer) = &self.producer else {
+            return Ok(RelayBatch::default());
+        };
+        let Some(mut tx) = self.store.begin_relay().await? else {
+            return Ok(RelayBatch::default());
+        };
+
+        let events = OutboxStore::fetch_pending(&mut tx, self.config.batch_size).await?;
+        let mut published = Vec::with_capacity(events.len());
+        let mut failure = None;
+
+        for event in &events {
+            let record = FutureRecord::to(&event.topic)
+                .key(&event.key)
+                .payload(&event.payload);
+            match producer
+                .send(record, Timeout::After(self.config.send_timeout))
+                .await
+            {
+                Ok(_) => published.push(event.id),
+                Err((e, _)) => {
+                    failure = Some((event.id, event.attempts, e.to_string()));
+                    break;
+                }
+            }
+        }
+
+        OutboxStore::mark_published(&mut tx, &published, Utc::now()).await?;
+        if let Some((id, _, error)) = &failure {
+            OutboxStore::mark_failed(&mut tx, *id, error).await?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        if let Some((id, attempts, error)) = &failure {
+            tracing::warn!(
+                "Failed to publish outbox event {} (attempt {}): {}",
+                id,
+                attempts + 1,
+                error
+            );
+        }
+
+        Ok(RelayBatch {
+            fetched: events.len(),
+            published: published.len(),
+            error: failure.map(|(_, _, error)| error),
+        })
+    }
+
+    /// 启动投递任务
+    pub fn start(self: Arc<Self>) {
+        if self.producer.is_none() {
+            tracing::info!("Outbox relay disabled, events stay in the outbox");
+            return;
+        }
+        tokio::spawn(async move {
+            tracing::info!("Outbox relay started");
+            let mut failures = 0u32;
+            let mut last_purge = Instant::now();
+            loop {
+                let delay = match self.relay_batch().await {
+                    Ok(batch) if batch.error.is_none() => {
+                        failures = 0;
+                        if batch.published > 0 {
+                            tracing::debug!("Published {} outbox events", batch.published);
+                        }
+                        if batch.fetched as u32 >= self.config.batch_size {
+                            // 还有积压，立即继续
+                            Duration::ZERO
+                        } else {
+                            self.config.poll_interval
+                        }
+                    }
+                    Ok(_) => {
+                        failures += 1;
+                        backoff(self.config.poll_interval, self.config.max_backoff, failures)
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        tracing::error!("Outbox relay failed: {}", e);
+                        backoff(self.config.poll_interval, self.config.max_backoff, failures)
+                    }
+                };
+
+                if last_purge.elapsed() >= PURGE_INTERVAL {
+                    last_purge = Instant::now();
+                    if let Ok(retention) = chrono::Duration::from_std(self.config.retention) {
+                        match self.store.purge_published_before(Utc::now() - retention).await {
+                            Ok(purged) if purged > 0 => {
+                                tracing::info!("Purged {} published outbox events", purged)
+                            }
+                            Ok(_) => {}
+                            Err(e) => tracing::warn!("Failed to purge outbox: {}", e),
+                        }
+                    }
+                }
+
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let poll = Duration::from_millis(500);
+        let max = Duration::from_secs(60);
+        assert_eq!(backoff(poll, max, 1), poll);
+        assert_eq!(backoff(poll, max, 2), Duration::from_secs(1));
+        assert_eq!(backoff(poll, max, 4), Duration::from_secs(4));
+        assert_eq!(backoff(poll, max, 100), max);
+    }
+}
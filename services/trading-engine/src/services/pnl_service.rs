@@ -0,0 +1,220 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use shared_models::SettlementCurrency;
+use shared_utils::{Fence, LeaderElection};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::trading::PnlSnapshotConfig,
+    engines::tax_lots::{CostBasisMethod, TaxLotBook},
+    models::{
+        compute_statistics, downsample, Fill, PnlGranularity, PnlPoint, PnlSnapshot, PnlStatistics,
+        TradingError, TradingResult,
+    },
+    services::EquityStreamService,
+    storage::{PnlStore, PositionStore, TradeStore},
+};
+
+/// 单次查询允许的最大点数
+pub const MAX_SERIES_POINTS: i64 = 5000;
+
+/// 领导者选举中的任务名
+const SNAPSHOT_JOB: &str = "pnl_snapshots";
+
+/// 盈亏时间序列
+#[derive(Debug, Serialize)]
+pub struct PnlHistory {
+    pub user_id: Uuid,
+    /// 金额的计价币种
+    pub currency: SettlementCurrency,
+    pub granularity: PnlGranularity,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub points: Vec<PnlPoint>,
+    pub statistics: PnlStatistics,
+}
+
+impl PnlHistory {
+    /// 按当前汇率把序列金额换算为 `currency`，收益率和回撤比例不变
+    pub fn in_currency(mut self, currency: SettlementCurrency, rate: Decimal) -> Self {
+        let convert = |value: Decimal| currency.round(value * rate);
+        for point in &mut self.points {
+            point.equity = convert(point.equity);
+            point.realized_pnl = convert(point.realized_pnl);
+            point.unrealized_pnl = convert(point.unrealized_pnl);
+            point.total_pnl = convert(point.total_pnl);
+        }
+        let statistics = &mut self.statistics;
+        statistics.start_equity = convert(statistics.start_equity);
+        statistics.end_equity = convert(statistics.end_equity);
+        statistics.pnl_change = convert(statistics.pnl_change);
+        statistics.max_drawdown_amount = convert(statistics.max_drawdown_amount);
+        self.currency = currency;
+        self
+    }
+}
+
+/// 按移动平均成本重放成交，累计卖出的已实现盈亏（已扣除手续费）
+fn realized_pnl(fills: &[Fill]) -> Decimal {
+    let mut book = TaxLotBook::new(CostBasisMethod::AverageCost, 0);
+    fills
+        .iter()
+        .flat_map(|fill| book.apply_fill(fill))
+        .map(|gain| gain.gain)
+        .sum()
+}
+
+/// 账户盈亏快照服务
+///
+/// 定期为活跃账户记录权益和已实现/未实现盈亏，
+/// 并按粒度降采样生成时间序列和回撤/收益统计。
+/// 权益取账户余额加持仓未实现盈亏，已实现盈亏按用户的实盘成交重放计算。
+pub struct PnlService {
+    config: PnlSnapshotConfig,
+    pnl_store: Arc<PnlStore>,
+    position_store: Arc<PositionStore>,
+    trade_store: Arc<TradeStore>,
+    equity: Arc<EquityStreamService>,
+}
+
+impl PnlService {
+    pub fn new(
+        config: PnlSnapshotConfig,
+        pnl_store: Arc<PnlStore>,
+        position_store: Arc<PositionStore>,
+        trade_store: Arc<TradeStore>,
+        equity: Arc<EquityStreamService>,
+    ) -> Self {
+        Self {
+            config,
+            pnl_store,
+            position_store,
+            trade_store,
+            equity,
+        }
+    }
+
+    /// 计算单个账户的当前快照
+    pub async fn snapshot_account(&self, user_id: Uuid, taken_at: DateTime<Utc>) -> TradingResult<PnlSnapshot> {
+        let equity = self.equity.snapshot(user_id).await?;
+        let fills = self.trade_store.list_fills_until(user_id, taken_at).await?;
+        let realized_pnl = realized_pnl(&fills);
+
+        let snapshot = PnlSnapshot {
+            user_id,
+            equity: equity.equity,
+            realized_pnl,
+            unrealized_pnl: equity.unrealized_pnl,
+            total_pnl: realized_pnl + equity.unrealized_pnl,
+            taken_at,
+        };
+        Ok(snapshot)
+    }
+
+    /// 为所有活跃账户记录快照，返回成功的账户数
+    ///
+    /// 快照计算完成后在一个事务中写入，租约已失效或令牌已过期时整批丢弃。
+    pub async fn snapshot_all(&self, fence: &Fence) -> TradingResult<usize> {
+        let now = Utc::now();
+        let lookback = chrono::Duration::from_std(self.config.active_lookback)
+            .map_err(|e| TradingError::ConfigError(e.to_string()))?;
+
+        let mut accounts: HashSet<Uuid> = self
+            .pnl_store
+            .active_accounts(now - lookback)
+            .await?
+            .into_iter()
+            .collect();
+        accounts.extend(
+            self.position_store
+                .get_all_active_positions()
+                .await?
+                .into_iter()
+                .map(|position| position.user_id),
+        );
+
+        let mut snapshots = Vec::with_capacity(accounts.len());
+        for user_id in accounts {
+            match self.snapshot_account(user_id, now).await {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(e) => tracing::warn!("Failed to snapshot PnL for {}: {}", user_id, e),
+            }
+        }
+        if !fence.is_valid() || !self.pnl_store.insert_snapshots(&snapshots, fence).await? {
+            return Err(TradingError::ExecutionError(format!(
+                "Lost leadership of {} (token {}), discarded {} snapshots",
+                fence.job(),
+                fence.token(),
+                snapshots.len()
+            )));
+        }
+        Ok(snapshots.len())
+    }
+
+    /// 启动定期快照任务，多副本时只在领导者副本执行
+    pub fn start(self: Arc<Self>, leader: LeaderElection) {
+        if !self.config.enabled {
+            tracing::info!("PnL snapshots disabled");
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(lease) = leader.acquire(SNAPSHOT_JOB).await else {
+                    continue;
+                };
+                match self.snapshot_all(&lease.fence()).await {
+                    Ok(count) => tracing::debug!("Recorded PnL snapshots for {} accounts", count),
+                    Err(e) => tracing::error!("PnL snapshot run failed: {}", e),
+                }
+                if !lease.is_valid() {
+                    continue;
+                }
+                if let Ok(retention) = chrono::Duration::from_std(self.config.retention) {
+                    if let Err(e) = self.pnl_store.purge_before(Utc::now() - retention).await {
+                        tracing::warn!("Failed to purge old PnL snapshots: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// 查询盈亏时间序列
+    pub async fn history(
+        &self,
+        user_id: Uuid,
+        granularity: PnlGranularity,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TradingResult<PnlHistory> {
+        if start >= end {
+            return Err(TradingError::ConfigError("Start time must be before end time".to_string()));
+        }
+        let buckets = (end - start).num_seconds() / granularity.duration().num_seconds();
+        if buckets > MAX_SERIES_POINTS {
+            return Err(TradingError::ConfigError(format!(
+                "Range spans {} points at {:?}, limit is {}",
+                buckets, granularity, MAX_SERIES_POINTS
+            )));
+        }
+
+        let snapshots = self.pnl_store.list_snapshots(user_id, start, end).await?;
+        let points = downsample(&snapshots, granularity);
+        let statistics = compute_statistics(&points, granularity);
+
+        Ok(PnlHistory {
+            user_id,
+            currency: SettlementCurrency::Usdt,
+            granularity,
+            start_time: start,
+            end_time: end,
+            points,
+            statistics,
+        })
+    }
+}
@@ -0,0 +1,368 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde_json::json;
+use shared_utils::LeaderElection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::{execution::RoutingStrategy, trading::PortfolioStopConfig},
+    engines::ExecutionEngine,
+    models::{
+        Amount, DrawdownBasis, NotificationCategory, Order, OrderType, PortfolioStop, PortfolioStopEvent,
+        PortfolioStopEventKind, Position, PositionStatus, TradingError, TradingResult,
+    },
+    services::{EquityStreamService, NotificationService, OrderService, PositionService},
+    storage::{PortfolioStopStore, TradeStore},
+};
+
+/// 领导者选举中的任务名
+const PORTFOLIO_STOP_JOB: &str = "portfolio_stops";
+
+/// 账户级回撤止损服务
+///
+/// 定期检查设置了止损的账户权益，回撤超过上限时先锁定交易，再撤销全部订单、
+/// 通过执行引擎市价平掉全部仓位。锁定后下单被拒绝，直到用户手动解锁。
+/// 撤单或平仓失败、部分成交时，之后每轮检查继续重试。每一步操作都记录审计事件。
+pub struct PortfolioStopService {
+    config: PortfolioStopConfig,
+    store: Arc<PortfolioStopStore>,
+    /// 按账户余额和持仓的实时标记价格计算权益
+    equity: Arc<EquityStreamService>,
+    order_service: Arc<OrderService>,
+    position_service: Arc<PositionService>,
+    execution_engine: Arc<ExecutionEngine>,
+    trade_store: Arc<TradeStore>,
+    routing_strategy: RoutingStrategy,
+    notifications: Option<Arc<NotificationService>>,
+}
+
+impl PortfolioStopService {
+    pub fn new(
+        config: PortfolioStopConfig,
+        store: Arc<PortfolioStopStore>,
+        equity: Arc<EquityStreamService>,
+        order_service: Arc<OrderService>,
+        position_service: Arc<PositionService>,
+        execution_engine: Arc<ExecutionEngine>,
+        trade_store: Arc<TradeStore>,
+        routing_strategy: RoutingStrategy,
+    ) -> Self {
+        Self {
+            config,
+            store,
+            equity,
+            order_service,
+            position_service,
+            execution_engine,
+            trade_store,
+            routing_strategy,
+            notifications: None,
+        }
+    }
+
+    /// 触发止损时向用户发送站内通知
+    pub fn with_notifications(mut self, notifications: Arc<NotificationService>) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+
+    /// 计价货币余额加上按实时标记价格计算的持仓未实现盈亏
+    async fn current_equity(&self, user_id: Uuid) -> TradingResult<Amount> {
+        Ok(self.equity.snapshot(user_id).await?.equity)
+    }
+
+    /// 记录审计事件，失败只记录日志，不中断止损流程
+    async fn audit(&self, user_id: Uuid, kind: PortfolioStopEventKind, details: serde_json::Value) {
+        let event = PortfolioStopEvent::new(user_id, kind, details);
+        if let Err(e) = self.store.record_event(&event).await {
+            tracing::error!("Failed to record portfolio stop event {} for {}: {}", kind, user_id, e);
+        }
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> TradingResult<Option<PortfolioStop>> {
+        self.store.get(user_id).await
+    }
+
+    pub async fn events(&self, user_id: Uuid, limit: u32) -> TradingResult<Vec<PortfolioStopEvent>> {
+        self.store.list_events(user_id, limit).await
+    }
+
+    /// 设置止损，以当前权益作为回撤基准；已锁定的账户保持锁定
+    pub async fn configure(
+        &self,
+        user_id: Uuid,
+        basis: DrawdownBasis,
+        max_drawdown: Decimal,
+    ) -> TradingResult<PortfolioStop> {
+        if max_drawdown <= Decimal::ZERO || max_drawdown > self.config.max_drawdown_limit {
+            return Err(TradingError::ConfigError(format!(
+                "Max drawdown must be in (0, {}]",
+                self.config.max_drawdown_limit
+            )));
+        }
+
+        let equity = self.current_equity(user_id).await?;
+        let now = Utc::now();
+        let mut stop = PortfolioStop::new(user_id, basis, max_drawdown, equity, now);
+        if let Some(existing) = self.store.get(user_id).await? {
+            stop.locked = existing.locked;
+            stop.triggered_at = existing.triggered_at;
+        }
+        self.store.upsert(&stop).await?;
+        self.audit(
+            user_id,
+            PortfolioStopEventKind::Configured,
+            json!({
+                "basis": basis,
+                "max_drawdown": max_drawdown,
+                "reference_equity": equity
+            }),
+        )
+        .await;
+        Ok(stop)
+    }
+
+    /// 删除止损，锁定期间需要先解锁
+    pub async fn remove(&self, user_id: Uuid) -> TradingResult<bool> {
+        if self.store.delete(user_id).await? {
+            self.audit(user_id, PortfolioStopEventKind::Removed, json!({})).await;
+            return Ok(true);
+        }
+        match self.store.get(user_id).await? {
+            Some(_) => Err(TradingError::RiskViolation(
+                "Trading is locked by portfolio stop, unlock before removing it".to_string(),
+            )),
+            None => Ok(false),
+        }
+    }
+
+    /// 手动解锁交易，以当前权益作为新的回撤基准
+    pub async fn unlock(&self, user_id: Uuid) -> TradingResult<Option<PortfolioStop>> {
+        let Some(mut stop) = self.store.get(user_id).await? else {
+            return Ok(None);
+        };
+        if !stop.locked {
+            return Ok(Some(stop));
+        }
+
+        let equity = self.current_equity(user_id).await?;
+        stop.unlock(equity);
+        self.store.upsert(&stop).await?;
+        self.audit(
+            user_id,
+            PortfolioStopEventKind::Unlocked,
+            json!({ "reference_equity": equity }),
+        )
+        .await;
+        tracing::info!("Portfolio stop unlocked for {}", user_id);
+        Ok(Some(stop))
+    }
+
+    /// 检查所有未触发的止损，返回触发的账户数
+    ///
+    /// 已锁定的账户如果还有未撤销的订单或未平掉的仓位，每轮重试撤单和平仓直到清空。
+    pub async fn check_all(&self) -> TradingResult<usize> {
+        // 先取已锁定的账户，本轮新触发的账户在触发时已经处理过
+        let locked = self.store.list_locked().await?;
+        for stop in locked {
+            let user_id = stop.user_id;
+            match self.liquidate(user_id).await {
+                Ok(true) => {}
+                Ok(false) => tracing::warn!("Portfolio stop for {} still has open positions", user_id),
+                Err(e) => tracing::warn!("Failed to liquidate locked account {}: {}", user_id, e),
+            }
+        }
+
+        let mut triggered = 0;
+        for stop in self.store.list_armed().await? {
+            let user_id = stop.user_id;
+            match self.check(stop).await {
+                Ok(true) => triggered += 1,
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to check portfolio stop for {}: {}", user_id, e),
+            }
+        }
+        Ok(triggered)
+    }
+
+    async fn check(&self, mut stop: PortfolioStop) -> TradingResult<bool> {
+        let equity = self.current_equity(stop.user_id).await?;
+        let now = Utc::now();
+        if stop.observe(equity, now) {
+            self.store.upsert(&stop).await?;
+        }
+        if !stop.is_breached(equity) {
+            return Ok(false);
+        }
+        self.trigger(stop, equity).await?;
+        Ok(true)
+    }
+
+    /// 触发止损：锁定交易、撤销全部订单、市价平掉全部仓位
+    async fn trigger(&self, mut stop: PortfolioStop, equity: Amount) -> TradingResult<()> {
+        let user_id = stop.user_id;
+        let drawdown = stop.drawdown(equity);
+
+        // 先锁定，之后的下单请求会被拒绝
+        stop.trigger(Utc::now());
+        self.store.upsert(&stop).await?;
+        tracing::warn!(
+            "Portfolio stop triggered for {}: equity {} is {} below reference {}",
+            user_id,
+            equity,
+            drawdown,
+            stop.reference_equity
+        );
+        self.audit(
+            user_id,
+            PortfolioStopEventKind::Triggered,
+            json!({
+                "basis": stop.basis,
+                "max_drawdown": stop.max_drawdown,
+                "reference_equity": stop.reference_equity,
+                "equity": equity,
+                "drawdown": drawdown
+            }),
+        )
+        .await;
+        if let Some(notifications) = &self.notifications {
+            if let Err(e) = notifications
+                .notify(
+                    user_id,
+                    NotificationCategory::Risk,
+                    "Portfolio stop triggered",
+                    &format!(
+                        "Equity {} fell {} below reference {}; trading is locked and open positions are being closed",
+                        equity, drawdown, stop.reference_equity
+                    ),
+                    json!({ "equity": equity, "drawdown": drawdown }),
+                )
+                .await
+            {
+                tracing::error!("Failed to notify {} of portfolio stop: {}", user_id, e);
+            }
+        }
+
+        if !self.liquidate(user_id).await? {
+            tracing::warn!("Portfolio stop for {} left open positions, retrying next check", user_id);
+        }
+        Ok(())
+    }
+
+    /// 撤销账户全部订单并市价平掉全部仓位，返回是否已经全部平掉
+    ///
+    /// 没有订单和仓位时直接返回，锁定的账户可以反复调用。
+    async fn liquidate(&self, user_id: Uuid) -> TradingResult<bool> {
+        let cancelled = self.order_service.cancel_all_orders(user_id, None).await?;
+        for order in &cancelled {
+            self.audit(
+                user_id,
+                PortfolioStopEventKind::OrderCancelled,
+                json!({
+                    "order_id": order.id,
+                    "symbol": order.symbol.to_string(),
+                    "side": order.side,
+                    "remaining_quantity": order.remaining_quantity
+                }),
+            )
+            .await;
+        }
+
+        let positions = self
+            .position_service
+            .list_positions(user_id, Some(PositionStatus::Open.to_string()), None)
+            .await?;
+        let mut flat = true;
+        for position in positions {
+            let symbol = position.symbol.to_string();
+            if let Err(e) = self.flatten(position).await {
+                tracing::error!("Failed to flatten {} for {} after portfolio stop: {}", symbol, user_id, e);
+                self.audit(
+                    user_id,
+                    PortfolioStopEventKind::FlattenFailed,
+                    json!({ "symbol": symbol, "error": e.to_string() }),
+                )
+                .await;
+                flat = false;
+            }
+        }
+        Ok(flat)
+    }
+
+    /// 通过执行引擎市价平仓，按实际成交更新仓位
+    async fn flatten(&self, position: Position) -> TradingResult<()> {
+        let user_id = position.user_id;
+        let size = position.size;
+        let side = position.side.to_close_side();
+        let order = Order::new(user_id, position.symbol.clone(), OrderType::Market, side, size, None, None)?;
+        let order_id = order.id;
+
+        let result = self
+            .execution_engine
+            .execute_order(order.clone(), self.routing_strategy.clone())
+            .await?;
+        for fill in result.to_fills(&order) {
+            if let Err(e) = self.trade_store.insert_fill(&fill).await {
+                tracing::error!("Failed to record flatten fill {} of order {}: {}", fill.id, order_id, e);
+            }
+        }
+        let filled = result.filled_quantity.min(size);
+        if filled <= Decimal::ZERO {
+            return Err(TradingError::ExecutionFailed(format!(
+                "Flatten order {} was not filled on {}",
+                order_id, result.venue
+            )));
+        }
+
+        let close_price = result.avg_price.unwrap_or(position.mark_price);
+        let closed = self.position_service.record_close(position, filled, close_price).await?;
+        self.audit(
+            user_id,
+            PortfolioStopEventKind::PositionFlattened,
+            json!({
+                "order_id": order_id,
+                "symbol": closed.symbol,
+                "side": side,
+                "size": size,
+                "filled_quantity": filled,
+                "avg_price": close_price,
+                "venue": result.venue,
+                "realized_pnl": closed.realized_pnl
+            }),
+        )
+        .await;
+
+        if closed.remaining_size > Decimal::ZERO {
+            return Err(TradingError::ExecutionFailed(format!(
+                "Flatten order {} left {} open",
+                order_id, closed.remaining_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// 启动定期检查，多副本时只在领导者副本执行
+    pub fn start(self: Arc<Self>, leader: LeaderElection) {
+        if !self.config.enabled {
+            tracing::info!("Portfolio stops disabled");
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.check_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // 持有租约直到本轮检查结束，避免多个副本同时平仓
+                let Some(_lease) = leader.acquire(PORTFOLIO_STOP_JOB).await else {
+                    continue;
+                };
+                match self.check_all().await {
+                    Ok(0) => {}
+                    Ok(triggered) => tracing::warn!("Portfolio stops triggered for {} accounts", triggered),
+                    Err(e) => tracing::error!("Portfolio stop check failed: {}", e),
+                }
+            }
+        });
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
 use crate::{
@@ -9,12 +10,23 @@ use crate::{
     services::{ExecutionService, RiskService},
 };
 
+/// 仓位变化事件通道容量
+const POSITION_EVENT_CAPACITY: usize = 4096;
+
+/// 仓位变化事件
+#[derive(Debug, Clone)]
+pub struct PositionChange {
+    pub user_id: Uuid,
+    pub symbol: String,
+}
+
 /// 仓位服务
 #[derive(Clone)]
 pub struct PositionService {
     position_store: Arc<PositionStore>,
     execution_service: Arc<ExecutionService>,
     risk_service: Arc<RiskService>,
+    events: broadcast::Sender<PositionChange>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -33,13 +45,27 @@ impl PositionService {
         execution_service: Arc<ExecutionService>,
         risk_service: Arc<RiskService>,
     ) -> Self {
+        let (events, _) = broadcast::channel(POSITION_EVENT_CAPACITY);
         Self {
             position_store,
             execution_service,
             risk_service,
+            events,
         }
     }
 
+    /// 订阅仓位变化
+    pub fn subscribe(&self) -> broadcast::Receiver<PositionChange> {
+        self.events.subscribe()
+    }
+
+    fn notify(&self, position: &Position) {
+        let _ = self.events.send(PositionChange {
+            user_id: position.user_id,
+            symbol: position.symbol.to_string(),
+        });
+    }
+
     /// 查询仓位列表
     pub async fn list_positions(
         &self,
@@ -90,6 +116,7 @@ impl PositionService {
                 // 同方向，增加仓位
                 existing_position.increase_position(size, price, margin)?;
                 self.position_store.update_position(&existing_position).await?;
+                self.notify(&existing_position);
                 Ok(existing_position)
             } else {
                 // 反方向，可能是平仓或反向开仓
@@ -97,6 +124,7 @@ impl PositionService {
                     // 部分或完全平仓
                     let pnl = existing_position.partial_close(size, price)?;
                     self.position_store.update_position(&existing_position).await?;
+                    self.notify(&existing_position);
                     
                     tracing::info!(
                         "Position partially closed: {} {} {}, PnL: {}",
@@ -123,6 +151,7 @@ impl PositionService {
                     )?;
                     
                     self.position_store.create_position(&new_position).await?;
+                    self.notify(&new_position);
                     
                     tracing::info!(
                         "Position closed and reversed: {} {} -> {} {}, PnL: {}",
@@ -138,6 +167,7 @@ impl PositionService {
             let position = Position::new(user_id, symbol, position_side, size, price, leverage, margin)?;
             
             self.position_store.create_position(&position).await?;
+            self.notify(&position);
             
             tracing::info!(
                 "New position created: {} {} {}",
@@ -157,7 +187,7 @@ impl PositionService {
         price: Option<Decimal>,
     ) -> TradingResult<ClosePositionResult> {
         // 1. 获取仓位
-        let mut position = self
+        let position = self
             .get_position(user_id, symbol)
             .await?
             .ok_or_else(|| TradingError::PositionNotFound(symbol.to_string()))?;
@@ -195,24 +225,35 @@ impl PositionService {
             .await?;
 
         // 6. 更新仓位
+        let result = self.record_close(position, close_size, close_price).await?;
+
+        tracing::info!(
+            "Position closed: {} {} {}, PnL: {}",
+            symbol, close_size, close_side, result.realized_pnl
+        );
+
+        Ok(result)
+    }
+
+    /// 按已执行的平仓成交更新仓位
+    pub async fn record_close(
+        &self,
+        mut position: Position,
+        close_size: Decimal,
+        close_price: Decimal,
+    ) -> TradingResult<ClosePositionResult> {
         let pnl = position.partial_close(close_size, close_price)?;
         self.position_store.update_position(&position).await?;
+        self.notify(&position);
 
-        let result = ClosePositionResult {
+        Ok(ClosePositionResult {
             position_id: position.id,
-            symbol: symbol.to_string(),
+            symbol: position.symbol.to_string(),
             closed_size: close_size,
             close_price,
             realized_pnl: pnl,
             remaining_size: position.size,
-        };
-
-        tracing::info!(
-            "Position closed: {} {} {}, PnL: {}",
-            symbol, close_size, close_side, pnl
-        );
-
-        Ok(result)
+        })
     }
 
     /// 全部平仓
@@ -273,6 +314,7 @@ impl PositionService {
             if position.status == PositionStatus::Open {
                 position.update_mark_price(mark_price)?;
                 self.position_store.update_position(&position).await?;
+                self.notify(&position);
             }
         }
         
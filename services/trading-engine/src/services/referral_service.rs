@@ -0,0 +1,214 @@
+use chrono::Utc;
+use shared_utils::{Fence, LeaderElection};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::trading::ReferralConfig,
+    models::{
+        compute_fee_share, generate_referral_code, normalize_referral_code, summarize_earnings,
+        Amount, Fill, ReferralAttribution, ReferralCode, ReferralEarnings, ReferralEntryType,
+        ReferralLedgerEntry, TradingError, TradingResult,
+    },
+    storage::ReferralStore,
+};
+
+/// 生成推荐码时的最大重试次数
+const CODE_GENERATION_ATTEMPTS: usize = 5;
+
+/// 领导者选举中的任务名
+const PAYOUT_JOB: &str = "referral_payouts";
+
+/// 推荐返佣服务
+///
+/// 推荐人的收益来自被推荐人成交手续费的固定比例，
+/// 累计记入账本，定期对达到最小金额的余额生成结算条目。
+pub struct ReferralService {
+    config: ReferralConfig,
+    referral_store: Arc<ReferralStore>,
+}
+
+impl ReferralService {
+    pub fn new(config: ReferralConfig, referral_store: Arc<ReferralStore>) -> Self {
+        Self {
+            config,
+            referral_store,
+        }
+    }
+
+    /// 获取用户推荐码，不存在时生成
+    pub async fn get_or_create_code(&self, user_id: Uuid) -> TradingResult<ReferralCode> {
+        if let Some(code) = self.referral_store.get_code_by_user(user_id).await? {
+            return Ok(code);
+        }
+
+        for _ in 0..CODE_GENERATION_ATTEMPTS {
+            let code = ReferralCode {
+                code: generate_referral_code(),
+                user_id,
+                created_at: Utc::now(),
+            };
+            if self.referral_store.insert_code(&code).await? {
+                return Ok(code);
+            }
+            // 并发请求可能已为该用户生成推荐码
+            if let Some(existing) = self.referral_store.get_code_by_user(user_id).await? {
+                return Ok(existing);
+            }
+        }
+        Err(TradingError::ExecutionError("Failed to generate a unique referral code".to_string()))
+    }
+
+    /// 记录注册归属，每个用户只能归属一次
+    pub async fn attribute_signup(&self, referee_id: Uuid, code: &str) -> TradingResult<ReferralAttribution> {
+        let code = normalize_referral_code(code)?;
+        let referral_code = self
+            .referral_store
+            .get_code(&code)
+            .await?
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Unknown referral code: {}", code)))?;
+        if referral_code.user_id == referee_id {
+            return Err(TradingError::InvalidOrder("Cannot use own referral code".to_string()));
+        }
+
+        let attribution = ReferralAttribution {
+            referee_id,
+            referrer_id: referral_code.user_id,
+            code,
+            attributed_at: Utc::now(),
+        };
+        if !self.referral_store.insert_attribution(&attribution).await? {
+            return Err(TradingError::InvalidOrder("Referral already attributed".to_string()));
+        }
+        tracing::info!("User {} attributed to referrer {}", referee_id, attribution.referrer_id);
+        Ok(attribution)
+    }
+
+    /// 按被推荐人的手续费累计分成，返回记入的金额
+    pub async fn accrue_fee(
+        &self,
+        referee_id: Uuid,
+        fee: Amount,
+        currency: &str,
+        source_id: Uuid,
+    ) -> TradingResult<Option<Amount>> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+        let Some(attribution) = self.referral_store.get_attribution(referee_id).await? else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        let window = chrono::Duration::from_std(self.config.attribution_window)
+            .map_err(|e| TradingError::ConfigError(e.to_string()))?;
+        if now - attribution.attributed_at > window {
+            return Ok(None);
+        }
+
+        let amount = compute_fee_share(fee, self.config.fee_share_rate);
+        if amount.is_zero() {
+            return Ok(None);
+        }
+        let entry = ReferralLedgerEntry {
+            id: Uuid::new_v4(),
+            referrer_id: attribution.referrer_id,
+            referee_id: Some(referee_id),
+            entry_type: ReferralEntryType::Accrual,
+            amount,
+            currency: currency.to_string(),
+            source_id: Some(source_id),
+            fee_amount: Some(fee),
+            created_at: now,
+        };
+        if !self.referral_store.insert_entry(&entry).await? {
+            tracing::debug!("Referral fee share for {} already recorded", source_id);
+            return Ok(None);
+        }
+        Ok(Some(amount))
+    }
+
+    /// 按成交记录累计分成
+    pub async fn accrue_from_fill(&self, fill: &Fill) -> TradingResult<Option<Amount>> {
+        self.accrue_fee(fill.user_id, fill.fee, &fill.fee_currency, fill.id)
+            .await
+    }
+
+    /// 为达到最小金额的待结算余额生成结算条目，返回条目数
+    ///
+    /// 余额读取和条目写入在防护令牌保护的同一事务中完成，失去租约的旧领导者不会重复结算。
+    pub async fn settle_payouts(&self, fence: &Fence) -> TradingResult<usize> {
+        let now = Utc::now();
+        let min_payout = self.config.min_payout;
+        let settled = self
+            .referral_store
+            .settle_pending(now, fence, |balances| {
+                balances
+                    .into_iter()
+                    .filter(|(_, _, pending)| *pending >= min_payout)
+                    .map(|(referrer_id, currency, pending)| ReferralLedgerEntry {
+                        id: Uuid::new_v4(),
+                        referrer_id,
+                        referee_id: None,
+                        entry_type: ReferralEntryType::Payout,
+                        amount: pending,
+                        currency,
+                        source_id: None,
+                        fee_amount: None,
+                        created_at: now,
+                    })
+                    .collect()
+            })
+            .await?;
+        settled.ok_or_else(|| {
+            TradingError::ExecutionError(format!(
+                "Lost leadership of {} (token {}), payouts not settled",
+                fence.job(),
+                fence.token()
+            ))
+        })
+    }
+
+    /// 启动定期结算任务，多副本时只在领导者副本执行
+    pub fn start(self: Arc<Self>, leader: LeaderElection) {
+        if !self.config.enabled {
+            tracing::info!("Referral payouts disabled");
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.payout_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // 持有租约直到本轮执行结束
+                let Some(lease) = leader.acquire(PAYOUT_JOB).await else {
+                    continue;
+                };
+                match self.settle_payouts(&lease.fence()).await {
+                    Ok(count) => tracing::debug!("Settled {} referral payouts", count),
+                    Err(e) => tracing::error!("Referral payout run failed: {}", e),
+                }
+            }
+        });
+    }
+
+    /// 推荐收益概览
+    pub async fn earnings(&self, user_id: Uuid) -> TradingResult<ReferralEarnings> {
+        let code = self.referral_store.get_code_by_user(user_id).await?;
+        let referees = self.referral_store.count_referees(user_id).await?;
+        let totals = self.referral_store.totals(user_id).await?;
+
+        Ok(ReferralEarnings {
+            user_id,
+            code: code.map(|c| c.code),
+            fee_share_rate: self.config.fee_share_rate,
+            referees,
+            earnings: summarize_earnings(&totals),
+        })
+    }
+
+    /// 分页查询推荐账本
+    pub async fn ledger(&self, user_id: Uuid, limit: u32, offset: u32) -> TradingResult<Vec<ReferralLedgerEntry>> {
+        self.referral_store.list_entries(user_id, limit, offset).await
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::Utc;
+use shared_utils::LeaderElection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::risk::RiskEventConfig,
+    models::{
+        RiskEventAction, RiskEventRecord, RiskEventStatus, RiskSeverity, Timestamp, TradingError,
+        TradingResult,
+    },
+    storage::RiskEventStore,
+};
+
+/// 领导者选举中的任务名
+const RISK_EVENT_JOB: &str = "risk_events";
+
+fn chrono_duration(duration: std::time::Duration) -> TradingResult<chrono::Duration> {
+    chrono::Duration::from_std(duration).map_err(|e| TradingError::ConfigError(e.to_string()))
+}
+
+/// 风险事件队列服务
+///
+/// 风控引擎触发的事件和下单时被风控拒绝的订单持久化后按严重程度排队，值班人员确认、解决。严重事件超过确认时限
+/// 仍未确认时升级告警，之后按升级间隔重复；已解决的事件超过保留期后移入归档表。
+pub struct RiskEventService {
+    config: RiskEventConfig,
+    store: Arc<RiskEventStore>,
+}
+
+impl RiskEventService {
+    pub fn new(config: RiskEventConfig, store: Arc<RiskEventStore>) -> Self {
+        Self { config, store }
+    }
+
+    /// 事件入队
+    pub async fn record(&self, event: &RiskEventRecord) -> TradingResult<()> {
+        self.store.insert(event).await?;
+        if event.severity >= RiskSeverity::High {
+            tracing::error!(
+                "RISK ALERT [{}] {}: {} ({})",
+                event.severity,
+                event.event_type,
+                event.message,
+                event.id
+            );
+        } else {
+            tracing::warn!("Risk event [{}] {}: {}", event.severity, event.event_type, event.message);
+        }
+        Ok(())
+    }
+
+    pub async fn get(&self, id: Uuid) -> TradingResult<Option<RiskEventRecord>> {
+        self.store.get(id).await
+    }
+
+    /// 按队列顺序列出事件
+    pub async fn list(
+        &self,
+        status: Option<RiskEventStatus>,
+        min_severity: Option<RiskSeverity>,
+        limit: u32,
+    ) -> TradingResult<Vec<RiskEventRecord>> {
+        self.store.list(status, min_severity, limit).await
+    }
+
+    pub async fn queue_depths(&self) -> TradingResult<Vec<(RiskSeverity, RiskEventStatus, i64)>> {
+        self.store.queue_depths().await
+    }
+
+    /// 确认或解决事件，事件不存在时返回 None
+    pub async fn update(
+        &self,
+        id: Uuid,
+        action: RiskEventAction,
+        operator: Uuid,
+        note: Option<String>,
+    ) -> TradingResult<Option<RiskEventRecord>> {
+        let Some(mut event) = self.store.get(id).await? else {
+            return Ok(None);
+        };
+        let expected = event.status;
+        event.apply(action, operator, note, Utc::now())?;
+        if !self.store.update(&event, expected).await? {
+            return Err(TradingError::RiskViolation(format!(
+                "Risk event {} was updated concurrently",
+                id
+            )));
+        }
+        tracing::info!("Risk event {} {} by {}", id, event.status, operator);
+        Ok(Some(event))
+    }
+
+    /// 升级超过确认时限的严重事件，返回升级的事件数
+    pub async fn escalate_overdue(&self, now: Timestamp) -> TradingResult<usize> {
+        let ack_sla = chrono_duration(self.config.critical_ack_sla)?;
+        let interval = chrono_duration(self.config.escalation_interval)?;
+
+        let mut escalated = 0;
+        for mut event in self.store.list_unacknowledged(RiskSeverity::Critical).await? {
+            if !event.escalation_due(ack_sla, interval, now) {
+                continue;
+            }
+            event.escalate(now);
+            // 期间被确认的事件不再升级
+            if !self.store.update(&event, RiskEventStatus::Open).await? {
+                continue;
+            }
+            escalated += 1;
+            tracing::error!(
+                "RISK ESCALATION level {}: {} event {} unacknowledged since {}: {}",
+                event.escalation_level,
+                event.event_type,
+                event.id,
+                event.created_at,
+                event.message
+            );
+        }
+        Ok(escalated)
+    }
+
+    /// 归档超过保留期的已解决事件并清理过期归档，返回 (归档数, 清理数)
+    pub async fn archive(&self, now: Timestamp) -> TradingResult<(u64, u64)> {
+        let archived = self
+            .store
+            .archive_resolved(now - chrono_duration(self.config.resolved_retention)?)
+            .await?;
+        let purged = self
+            .store
+            .purge_archive(now - chrono_duration(self.config.archive_retention)?)
+            .await?;
+        Ok((archived, purged))
+    }
+
+    /// 启动确认时限检查和归档，多副本时只在领导者副本执行
+    pub fn start(self: Arc<Self>, leader: LeaderElection) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.check_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // 持有租约直到本轮检查结束，避免多个副本重复升级
+                let Some(_lease) = leader.acquire(RISK_EVENT_JOB).await else {
+                    continue;
+                };
+                let now = Utc::now();
+                match self.escalate_overdue(now).await {
+                    Ok(0) => {}
+                    Ok(escalated) => tracing::warn!("Escalated {} unacknowledged risk events", escalated),
+                    Err(e) => tracing::error!("Risk event escalation check failed: {}", e),
+                }
+                match self.archive(now).await {
+                    Ok((0, 0)) => {}
+                    Ok((archived, purged)) => tracing::info!(
+                        "Archived {} resolved risk events, purged {} expired archives",
+                        archived,
+                        purged
+                    ),
+                    Err(e) => tracing::error!("Risk event archival failed: {}", e),
+                }
+            }
+        });
+    }
+}
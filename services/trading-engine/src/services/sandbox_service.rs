@@ -0,0 +1,177 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::trading::SandboxConfig,
+    models::{
+        paper_fill_deltas, Amount, SandboxAccount, SandboxFill, SandboxOrderRequest,
+        SandboxStats, Symbol, TradingError, TradingResult, SANDBOX_VENUE,
+    },
+    services::ExecutionService,
+    storage::SandboxStore,
+};
+
+/// 模拟盘下单结果
+#[derive(Debug, Serialize)]
+pub struct SandboxOrderResult {
+    pub fill: SandboxFill,
+    pub balances: BTreeMap<String, Amount>,
+}
+
+/// 模拟盘服务
+///
+/// 新用户开通后获得预置虚拟余额的独立账户，委托按实时行情在模拟场所成交，
+/// 不经过订单服务和交易所连接，因此不会进入正式订单、盈亏快照和返佣统计。
+/// 会话超过有效期未使用时，下次访问自动重置为初始余额。
+pub struct SandboxService {
+    config: SandboxConfig,
+    sandbox_store: Arc<SandboxStore>,
+    execution_service: Arc<ExecutionService>,
+}
+
+impl SandboxService {
+    pub fn new(
+        config: SandboxConfig,
+        sandbox_store: Arc<SandboxStore>,
+        execution_service: Arc<ExecutionService>,
+    ) -> Self {
+        Self {
+            config,
+            sandbox_store,
+            execution_service,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 开通模拟账户，已开通时返回现有账户
+    pub async fn provision(&self, user_id: Uuid) -> TradingResult<SandboxAccount> {
+        if self
+            .sandbox_store
+            .create_account(user_id, &self.config.initial_balances, Utc::now())
+            .await?
+        {
+            tracing::info!("Provisioned sandbox account for user {}", user_id);
+        }
+        self.account(user_id)
+            .await?
+            .ok_or_else(|| TradingError::DatabaseError("Sandbox account missing after provisioning".to_string()))
+    }
+
+    /// 获取模拟账户，会话过期时先重置
+    pub async fn account(&self, user_id: Uuid) -> TradingResult<Option<SandboxAccount>> {
+        let Some(account) = self.sandbox_store.get_account(user_id).await? else {
+            return Ok(None);
+        };
+
+        let ttl = chrono::Duration::from_std(self.config.session_ttl)
+            .map_err(|e| TradingError::ConfigError(e.to_string()))?;
+        let now = Utc::now();
+        if now - account.last_active_at <= ttl {
+            return Ok(Some(account));
+        }
+
+        tracing::info!("Sandbox session for user {} expired, resetting", user_id);
+        self.sandbox_store
+            .reset_account(user_id, &self.config.initial_balances, now)
+            .await?;
+        self.sandbox_store.get_account(user_id).await
+    }
+
+    /// 手动重置为初始余额并清空成交
+    pub async fn reset(&self, user_id: Uuid) -> TradingResult<SandboxAccount> {
+        let account = self
+            .account(user_id)
+            .await?
+            .ok_or_else(|| TradingError::InvalidOrder("Sandbox account not provisioned".to_string()))?;
+
+        let cooldown = chrono::Duration::from_std(self.config.reset_cooldown)
+            .map_err(|e| TradingError::ConfigError(e.to_string()))?;
+        let now = Utc::now();
+        if account.reset_count > 0 && now - account.reset_at < cooldown {
+            return Err(TradingError::RiskLimitExceeded(format!(
+                "Sandbox can be reset once every {}s",
+                self.config.reset_cooldown.as_secs()
+            )));
+        }
+
+        self.sandbox_store
+            .reset_account(user_id, &self.config.initial_balances, now)
+            .await?;
+        tracing::info!("Sandbox account for user {} reset", user_id);
+        self.sandbox_store
+            .get_account(user_id)
+            .await?
+            .ok_or_else(|| TradingError::DatabaseError("Sandbox account missing after reset".to_string()))
+    }
+
+    /// 按实时行情以市价在模拟场所成交
+    pub async fn place_order(
+        &self,
+        user_id: Uuid,
+        request: SandboxOrderRequest,
+    ) -> TradingResult<SandboxOrderResult> {
+        if request.quantity <= Decimal::ZERO {
+            return Err(TradingError::InvalidOrder("Quantity must be positive".to_string()));
+        }
+        let symbol = Symbol::from_string(&request.symbol)
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Invalid symbol: {}", request.symbol)))?;
+        // 确保账户已开通，并在会话过期时先重置
+        self.account(user_id)
+            .await?
+            .ok_or_else(|| TradingError::InvalidOrder("Sandbox account not provisioned".to_string()))?;
+
+        let price = self.execution_service.get_market_price(&symbol.to_string()).await?;
+        let (deltas, fee, fee_currency) =
+            paper_fill_deltas(&symbol, request.side, request.quantity, price, self.config.fee_rate);
+
+        let fill = SandboxFill {
+            id: Uuid::new_v4(),
+            user_id,
+            symbol: symbol.to_string(),
+            side: request.side,
+            quantity: request.quantity,
+            price,
+            fee,
+            fee_currency,
+            venue: SANDBOX_VENUE.to_string(),
+            created_at: Utc::now(),
+        };
+        let balances = self.sandbox_store.record_fill(&fill, &deltas).await?;
+        tracing::debug!(
+            "Sandbox fill for user {}: {} {} {} @ {}",
+            user_id, fill.side, fill.quantity, fill.symbol, fill.price
+        );
+
+        Ok(SandboxOrderResult { fill, balances })
+    }
+
+    /// 分页查询模拟成交
+    pub async fn fills(&self, user_id: Uuid, limit: u32, offset: u32) -> TradingResult<Vec<SandboxFill>> {
+        self.sandbox_store.list_fills(user_id, limit, offset).await
+    }
+
+    /// 模拟盘统计，只包含本次重置以来的成交
+    pub async fn stats(&self, user_id: Uuid) -> TradingResult<Option<SandboxStats>> {
+        let Some(account) = self.account(user_id).await? else {
+            return Ok(None);
+        };
+        let (fills, volume, fees) = self.sandbox_store.fill_totals(user_id).await?;
+
+        Ok(Some(SandboxStats {
+            user_id,
+            sandbox: true,
+            balances: account.balances,
+            fills,
+            volume,
+            fees,
+            reset_at: account.reset_at,
+        }))
+    }
+}
@@ -0,0 +1,213 @@
+use chrono::Utc;
+use shared_utils::LeaderElection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::trading::ScheduledOrderConfig,
+    models::{
+        CreateScheduledOrderRequest, ScheduledOrder, ScheduledOrderStatus, TradingError, TradingResult,
+    },
+    services::OrderService,
+    storage::ScheduledOrderStore,
+};
+
+/// 领导者选举中的任务名
+const SCHEDULED_ORDER_JOB: &str = "scheduled_orders";
+
+/// 定时订单服务
+///
+/// 到达激活时间时下单，到达撤单时间时撤销仍未完全成交的订单。定时订单保存在数据库中，
+/// 服务重启后继续处理。激活前先把状态推进到 active 再下单，保证同一定时订单最多下单一次；
+/// 下单后记录订单ID前中断的定时订单在撤单检查时标记为失败，需人工核对。
+pub struct ScheduledOrderService {
+    config: ScheduledOrderConfig,
+    store: Arc<ScheduledOrderStore>,
+    order_service: Arc<OrderService>,
+}
+
+impl ScheduledOrderService {
+    pub fn new(
+        config: ScheduledOrderConfig,
+        store: Arc<ScheduledOrderStore>,
+        order_service: Arc<OrderService>,
+    ) -> Self {
+        Self {
+            config,
+            store,
+            order_service,
+        }
+    }
+
+    /// 创建定时订单，激活时间已到时立即下单
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        request: CreateScheduledOrderRequest,
+    ) -> TradingResult<ScheduledOrder> {
+        if !self.config.enabled {
+            return Err(TradingError::ConfigError("Scheduled orders are disabled".to_string()));
+        }
+        let max_ahead = chrono::Duration::from_std(self.config.max_schedule_ahead)
+            .map_err(|e| TradingError::ConfigError(e.to_string()))?;
+        let now = Utc::now();
+        let scheduled = request.to_scheduled(user_id, now, max_ahead)?;
+        self.store.insert(&scheduled).await?;
+
+        if scheduled.activate_at <= now {
+            self.activate(&scheduled).await?;
+            return self.get(user_id, scheduled.id).await?.ok_or_else(|| {
+                TradingError::DatabaseError(format!("Scheduled order {} disappeared", scheduled.id))
+            });
+        }
+        Ok(scheduled)
+    }
+
+    pub async fn get(&self, user_id: Uuid, id: Uuid) -> TradingResult<Option<ScheduledOrder>> {
+        self.store.get(user_id, id).await
+    }
+
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        status: Option<ScheduledOrderStatus>,
+        limit: u32,
+    ) -> TradingResult<Vec<ScheduledOrder>> {
+        self.store.list(user_id, status, limit).await
+    }
+
+    /// 取消定时订单；已激活的同时撤销对应订单
+    pub async fn cancel(&self, user_id: Uuid, id: Uuid) -> TradingResult<Option<ScheduledOrder>> {
+        let Some(scheduled) = self.store.get(user_id, id).await? else {
+            return Ok(None);
+        };
+
+        match scheduled.status {
+            ScheduledOrderStatus::Scheduled => {
+                self.store
+                    .transition(id, ScheduledOrderStatus::Scheduled, ScheduledOrderStatus::Cancelled, None)
+                    .await?;
+            }
+            ScheduledOrderStatus::Active => self.cancel_active(&scheduled).await?,
+            _ => {
+                return Err(TradingError::InvalidOrder(format!(
+                    "Scheduled order {} is already {}",
+                    id, scheduled.status
+                )))
+            }
+        }
+        self.store.get(user_id, id).await
+    }
+
+    /// 处理到期的激活和撤单，返回处理的定时订单数
+    pub async fn process_due(&self) -> TradingResult<usize> {
+        let now = Utc::now();
+        let mut processed = 0;
+
+        for scheduled in self.store.due_activations(now, self.config.batch_size).await? {
+            if let Err(e) = self.activate(&scheduled).await {
+                tracing::warn!("Failed to activate scheduled order {}: {}", scheduled.id, e);
+            }
+            processed += 1;
+        }
+
+        for scheduled in self.store.due_cancellations(now, self.config.batch_size).await? {
+            if let Err(e) = self.cancel_active(&scheduled).await {
+                tracing::warn!("Failed to cancel scheduled order {}: {}", scheduled.id, e);
+            }
+            processed += 1;
+        }
+        Ok(processed)
+    }
+
+    /// 激活：先推进到 active 占位，再下单并记录订单ID
+    async fn activate(&self, scheduled: &ScheduledOrder) -> TradingResult<()> {
+        let claimed = self
+            .store
+            .transition(scheduled.id, ScheduledOrderStatus::Scheduled, ScheduledOrderStatus::Active, None)
+            .await?;
+        if !claimed {
+            return Ok(());
+        }
+
+        match self
+            .order_service
+            .create_order(scheduled.user_id, scheduled.request.clone())
+            .await
+        {
+            Ok(order) => {
+                tracing::info!("Scheduled order {} activated as order {}", scheduled.id, order.id);
+                self.store.record_activation(scheduled.id, order.id).await
+            }
+            Err(e) => {
+                let error = e.to_string();
+                self.store
+                    .transition(
+                        scheduled.id,
+                        ScheduledOrderStatus::Active,
+                        ScheduledOrderStatus::Failed,
+                        Some(&error),
+                    )
+                    .await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// 撤单：订单仍活跃时撤销并标记为已取消，订单已结束时标记为已完成
+    async fn cancel_active(&self, scheduled: &ScheduledOrder) -> TradingResult<()> {
+        let Some(order_id) = scheduled.order_id else {
+            // 激活时在记录订单ID前中断，无法确定是否已下单
+            self.store
+                .transition(
+                    scheduled.id,
+                    ScheduledOrderStatus::Active,
+                    ScheduledOrderStatus::Failed,
+                    Some("Activation was interrupted before the order id was recorded"),
+                )
+                .await?;
+            return Ok(());
+        };
+
+        let order = self
+            .order_service
+            .get_order(scheduled.user_id, order_id)
+            .await?
+            .ok_or(TradingError::OrderNotFound(order_id))?;
+        let next = if order.status.is_active() {
+            self.order_service.cancel_order(scheduled.user_id, order_id).await?;
+            tracing::info!("Scheduled order {} cancelled order {}", scheduled.id, order_id);
+            ScheduledOrderStatus::Cancelled
+        } else {
+            ScheduledOrderStatus::Completed
+        };
+        self.store
+            .transition(scheduled.id, ScheduledOrderStatus::Active, next, None)
+            .await?;
+        Ok(())
+    }
+
+    /// 启动定期处理，多副本时只在领导者副本执行
+    pub fn start(self: Arc<Self>, leader: LeaderElection) {
+        if !self.config.enabled {
+            tracing::info!("Scheduled orders disabled");
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.poll_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // 持有租约直到本轮处理结束，避免多个副本重复下单
+                let Some(_lease) = leader.acquire(SCHEDULED_ORDER_JOB).await else {
+                    continue;
+                };
+                match self.process_due().await {
+                    Ok(0) => {}
+                    Ok(processed) => tracing::debug!("Processed {} scheduled orders", processed),
+                    Err(e) => tracing::error!("Scheduled order processing failed: {}", e),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,459 @@
+use chrono::{NaiveDate, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use shared_utils::LeaderElection;
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
+use uuid::Uuid;
+
+use crate::{
+    config::trading::SettlementConfig,
+    models::{
+        due_business_date, funding_accrual, next_funding_time, render_csv, render_pdf,
+        settlement_window, AccountSnapshot, DailyStatement, FundingRate, FundingSummary,
+        Position, PositionStatus, PredictedFunding, SettledBalance, SettledPosition,
+        SettlementEntry, SettlementEntryKind, SettlementEvent, SettlementRun,
+        SettlementRunStatus, StatementFormat, StatementSummary, StatementValuation, Timestamp,
+        TradingError, TradingResult,
+    },
+    services::{AccountService, ConversionService},
+    storage::{PositionStore, SettlementStore},
+};
+
+/// 领导者选举中的任务名
+const SETTLEMENT_JOB: &str = "daily_settlement";
+
+/// 结算事件通道容量
+const EVENT_CAPACITY: usize = 1024;
+
+/// 对账单渲染请求
+struct RenderJob {
+    statement: Arc<DailyStatement>,
+    format: StatementFormat,
+    reply: oneshot::Sender<Vec<u8>>,
+}
+
+/// 日终结算服务
+///
+/// 每个营业日截止后为账户记录余额和持仓快照，计提资金费和当日手续费并将资金费计入余额，
+/// 由渲染工作池生成对账单，并广播结算事件。同一营业日可以重复运行：
+/// 快照只记录一次，计提和对账单按快照重新生成后覆盖，资金费只入账一次。
+pub struct SettlementService {
+    config: SettlementConfig,
+    cutoff: NaiveTime,
+    formats: Vec<StatementFormat>,
+    settlement_store: Arc<SettlementStore>,
+    position_store: Arc<PositionStore>,
+    account_service: Arc<AccountService>,
+    /// 对账单按账户结算币种折算汇总
+    conversion: Option<Arc<ConversionService>>,
+    renderer: mpsc::Sender<RenderJob>,
+    events: broadcast::Sender<SettlementEvent>,
+    /// 本副本内串行执行结算
+    running: Mutex<()>,
+}
+
+impl SettlementService {
+    pub fn new(
+        config: SettlementConfig,
+        settlement_store: Arc<SettlementStore>,
+        position_store: Arc<PositionStore>,
+        account_service: Arc<AccountService>,
+    ) -> Self {
+        let cutoff = NaiveTime::parse_from_str(&config.cutoff, "%H:%M").unwrap_or(NaiveTime::MIN);
+        let formats = config
+            .statement_formats
+            .iter()
+            .filter_map(|format| format.parse().ok())
+            .collect();
+
+        let (renderer, jobs) = mpsc::channel(config.render_workers.max(1) * 4);
+        spawn_render_workers(jobs, config.render_workers.max(1));
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+
+        Self {
+            config,
+            cutoff,
+            formats,
+            settlement_store,
+            position_store,
+            account_service,
+            conversion: None,
+            renderer,
+            events,
+            running: Mutex::new(()),
+        }
+    }
+
+    /// 对账单附上按账户结算币种折算的汇总
+    pub fn with_conversion(mut self, conversion: Arc<ConversionService>) -> Self {
+        self.conversion = Some(conversion);
+        self
+    }
+
+    /// 订阅结算事件
+    pub fn subscribe(&self) -> broadcast::Receiver<SettlementEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn get_run(&self, business_date: NaiveDate) -> TradingResult<Option<SettlementRun>> {
+        self.settlement_store.get_run(business_date).await
+    }
+
+    /// 结算指定营业日
+    ///
+    /// 已完成的营业日直接返回运行记录；`replay` 为 true 时按已有快照重新计提、
+    /// 生成对账单并再次发出事件。有账户失败时运行保持未完成状态，下次检查时重试。
+    pub async fn settle(&self, business_date: NaiveDate, replay: bool) -> TradingResult<SettlementRun> {
+        let _guard = self.running.lock().await;
+
+        let existing = self.settlement_store.get_run(business_date).await?;
+        if let Some(run) = &existing {
+            if run.status == SettlementRunStatus::Completed && !replay {
+                return Ok(run.clone());
+            }
+        }
+
+        let period = settlement_window(business_date, self.cutoff);
+        if period.1 > Utc::now() {
+            return Err(TradingError::ConfigError(format!(
+                "Business date {} has not closed yet",
+                business_date
+            )));
+        }
+        let replay = replay || existing.is_some();
+        let run = self
+            .settlement_store
+            .begin_run(business_date, period, Utc::now())
+            .await?;
+        tracing::info!(
+            "Settling business date {} (attempt {})",
+            business_date,
+            run.attempts
+        );
+
+        let mut accounts: HashSet<Uuid> = self
+            .settlement_store
+            .accounts_to_settle(business_date, period)
+            .await?
+            .into_iter()
+            .collect();
+        accounts.extend(
+            self.position_store
+                .get_all_active_positions()
+                .await?
+                .into_iter()
+                .map(|position| position.user_id),
+        );
+
+        let total = accounts.len();
+        let mut failed = 0;
+        let mut funded_symbols = BTreeSet::new();
+        for user_id in accounts {
+            match self.settle_account(business_date, user_id, period, replay).await {
+                Ok(symbols) => funded_symbols.extend(symbols),
+                Err(e) => {
+                    tracing::warn!("Failed to settle {} for {}: {}", user_id, business_date, e);
+                    failed += 1;
+                }
+            }
+        }
+        let funded_symbols: Vec<String> = funded_symbols.into_iter().collect();
+        self.settlement_store
+            .upsert_funding_rates(business_date, period.1, self.config.daily_funding_rate, &funded_symbols)
+            .await?;
+        if failed > 0 {
+            return Err(TradingError::ExecutionError(format!(
+                "{} of {} accounts failed to settle for {}",
+                failed, total, business_date
+            )));
+        }
+
+        self.settlement_store
+            .complete_run(business_date, total, Utc::now())
+            .await?;
+        let _ = self.events.send(SettlementEvent::RunCompleted {
+            business_date,
+            accounts: total,
+            replay,
+        });
+
+        self.settlement_store
+            .get_run(business_date)
+            .await?
+            .ok_or_else(|| TradingError::ExecutionError(format!("Settlement run {} missing", business_date)))
+    }
+
+    /// 手动结算或重放营业日，与定时任务共用领导者租约
+    ///
+    /// 其他副本正在结算时返回 None。
+    pub async fn settle_with_lease(
+        &self,
+        leader: &LeaderElection,
+        business_date: NaiveDate,
+        replay: bool,
+    ) -> TradingResult<Option<SettlementRun>> {
+        let Some(_lease) = leader.acquire(SETTLEMENT_JOB).await else {
+            return Ok(None);
+        };
+        self.settle(business_date, replay).await.map(Some)
+    }
+
+    /// 结算单个账户，返回计提了资金费的交易对
+    async fn settle_account(
+        &self,
+        business_date: NaiveDate,
+        user_id: Uuid,
+        period: (Timestamp, Timestamp),
+        replay: bool,
+    ) -> TradingResult<Vec<String>> {
+        // 快照只记录一次，之后始终从存储读取，保证重放结果一致
+        if self.settlement_store.get_snapshot(business_date, user_id).await?.is_none() {
+            let snapshot = self.take_snapshot(business_date, user_id).await?;
+            self.settlement_store.insert_snapshot(&snapshot).await?;
+        }
+        let snapshot = self
+            .settlement_store
+            .get_snapshot(business_date, user_id)
+            .await?
+            .ok_or_else(|| TradingError::ExecutionError(format!("Snapshot for {} missing", user_id)))?;
+
+        let mut entries: Vec<SettlementEntry> = snapshot
+            .positions
+            .iter()
+            .map(|position| SettlementEntry {
+                kind: SettlementEntryKind::Funding,
+                reference: position.symbol.clone(),
+                currency: position.quote_currency.clone(),
+                amount: funding_accrual(position, self.config.daily_funding_rate),
+            })
+            .filter(|entry| !entry.amount.is_zero())
+            .collect();
+        let funded_symbols = entries.iter().map(|entry| entry.reference.clone()).collect();
+        for (currency, fee) in self.settlement_store.fee_totals(user_id, period).await? {
+            if !fee.is_zero() {
+                entries.push(SettlementEntry {
+                    kind: SettlementEntryKind::Fee,
+                    reference: currency.clone(),
+                    currency,
+                    amount: -fee,
+                });
+            }
+        }
+        self.settlement_store
+            .replace_entries(business_date, user_id, &entries)
+            .await?;
+
+        let mut statement = DailyStatement::new(snapshot, entries, period);
+        if let Some(conversion) = &self.conversion {
+            let valuation = self.valuation(conversion, &statement).await?;
+            statement = statement.with_valuation(valuation);
+        }
+        let statement = Arc::new(statement);
+        for format in &self.formats {
+            let content = self.render(statement.clone(), *format).await?;
+            self.settlement_store
+                .upsert_statement(business_date, user_id, *format, &content, Utc::now())
+                .await?;
+        }
+
+        let _ = self.events.send(SettlementEvent::AccountSettled {
+            business_date,
+            user_id,
+            net_changes: statement.net_changes.clone(),
+            formats: self.formats.clone(),
+            replay,
+        });
+        Ok(funded_symbols)
+    }
+
+    /// 余额、未实现盈亏和计提按账户结算币种折算
+    async fn valuation(
+        &self,
+        conversion: &ConversionService,
+        statement: &DailyStatement,
+    ) -> TradingResult<StatementValuation> {
+        let currency = conversion.settlement_currency(statement.user_id).await?;
+        let mut account_value = Decimal::ZERO;
+        for balance in &statement.balances {
+            account_value += conversion.convert(balance.total, &balance.currency, currency).await?;
+        }
+        for position in &statement.positions {
+            account_value += conversion
+                .convert(position.unrealized_pnl, &position.quote_currency, currency)
+                .await?;
+        }
+        let mut net_change = Decimal::ZERO;
+        for (from, amount) in &statement.net_changes {
+            net_change += conversion.convert(*amount, from, currency).await?;
+        }
+        Ok(StatementValuation {
+            currency,
+            account_value,
+            net_change,
+        })
+    }
+
+    async fn take_snapshot(&self, business_date: NaiveDate, user_id: Uuid) -> TradingResult<AccountSnapshot> {
+        let balances = self
+            .account_service
+            .get_balance(user_id, None)
+            .await?
+            .into_iter()
+            .map(|balance| SettledBalance {
+                currency: balance.currency,
+                total: balance.total,
+                available: balance.available,
+                frozen: balance.frozen,
+            })
+            .collect();
+        let positions = self
+            .position_store
+            .list_positions(user_id, Some(PositionStatus::Open), None)
+            .await?
+            .into_iter()
+            .map(|position| SettledPosition {
+                symbol: position.symbol.to_string(),
+                quote_currency: position.symbol.quote.clone(),
+                side: position.side,
+                size: position.size,
+                entry_price: position.entry_price,
+                mark_price: position.mark_price,
+                unrealized_pnl: position.unrealized_pnl,
+            })
+            .collect();
+
+        Ok(AccountSnapshot {
+            business_date,
+            user_id,
+            balances,
+            positions,
+            taken_at: Utc::now(),
+        })
+    }
+
+    async fn render(&self, statement: Arc<DailyStatement>, format: StatementFormat) -> TradingResult<Vec<u8>> {
+        let (reply, response) = oneshot::channel();
+        self.renderer
+            .send(RenderJob {
+                statement,
+                format,
+                reply,
+            })
+            .await
+            .map_err(|_| TradingError::ExecutionError("Statement renderer stopped".to_string()))?;
+        response
+            .await
+            .map_err(|_| TradingError::ExecutionError("Statement rendering failed".to_string()))
+    }
+
+    /// 下载对账单
+    pub async fn statement(
+        &self,
+        user_id: Uuid,
+        business_date: NaiveDate,
+        format: StatementFormat,
+    ) -> TradingResult<Option<Vec<u8>>> {
+        self.settlement_store
+            .get_statement(user_id, business_date, format)
+            .await
+    }
+
+    pub async fn list_statements(&self, user_id: Uuid, limit: u32) -> TradingResult<Vec<StatementSummary>> {
+        self.settlement_store.list_statements(user_id, limit).await
+    }
+
+    /// 交易对在时间区间内的历史资金费率
+    pub async fn funding_history(
+        &self,
+        symbol: &str,
+        start: Timestamp,
+        end: Timestamp,
+        limit: u32,
+    ) -> TradingResult<Vec<FundingRate>> {
+        self.settlement_store.funding_history(symbol, start, end, limit).await
+    }
+
+    /// 下一次资金费，按当前配置的日费率在下一个营业日截止时计提
+    pub fn predicted_funding(&self, symbol: &str) -> PredictedFunding {
+        PredictedFunding {
+            symbol: symbol.to_string(),
+            funding_time: next_funding_time(Utc::now(), self.cutoff),
+            rate: self.config.daily_funding_rate,
+        }
+    }
+
+    /// 持仓开仓以来累计的资金费
+    pub async fn position_funding(&self, position: &Position) -> TradingResult<FundingSummary> {
+        // 开仓时所在的营业日及之后的结算才属于该持仓
+        let opened = due_business_date(position.created_at, self.cutoff) + chrono::Duration::days(1);
+        self.settlement_store
+            .funding_summary(position.user_id, &position.symbol.to_string(), opened)
+            .await
+    }
+
+    /// 启动日终结算任务，多副本时只在领导者副本执行
+    pub fn start(self: Arc<Self>, leader: LeaderElection) {
+        if !self.config.enabled {
+            tracing::info!("Daily settlement disabled");
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.config.check_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let business_date = due_business_date(Utc::now(), self.cutoff);
+                match self.settlement_store.get_run(business_date).await {
+                    Ok(Some(run)) if run.status == SettlementRunStatus::Completed => continue,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!("Failed to check settlement status: {}", e);
+                        continue;
+                    }
+                }
+                // 持有租约直到本轮执行结束
+                let Some(_lease) = leader.acquire(SETTLEMENT_JOB).await else {
+                    continue;
+                };
+                match self.settle(business_date, false).await {
+                    Ok(run) => tracing::info!(
+                        "Settled business date {} for {} accounts",
+                        business_date,
+                        run.accounts
+                    ),
+                    Err(e) => tracing::error!("Daily settlement for {} failed: {}", business_date, e),
+                }
+            }
+        });
+    }
+}
+
+/// 启动对账单渲染工作池
+fn spawn_render_workers(jobs: mpsc::Receiver<RenderJob>, workers: usize) {
+    let jobs = Arc::new(Mutex::new(jobs));
+    for _ in 0..workers {
+        let jobs = jobs.clone();
+        tokio::spawn(async move {
+            loop {
+                let job = jobs.lock().await.recv().await;
+                let Some(job) = job else {
+                    break;
+                };
+                let statement = job.statement;
+                let format = job.format;
+                let rendered = tokio::task::spawn_blocking(move || match format {
+                    StatementFormat::Csv => render_csv(&statement).into_bytes(),
+                    StatementFormat::Pdf => render_pdf(&statement),
+                })
+                .await;
+                match rendered {
+                    Ok(content) => {
+                        let _ = job.reply.send(content);
+                    }
+                    Err(e) => tracing::error!("Statement render worker panicked: {}", e),
+                }
+            }
+        });
+    }
+}
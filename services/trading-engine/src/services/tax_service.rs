@@ -0,0 +1,50 @@
+use chrono::{TimeZone, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::trading::TaxLotConfig,
+    engines::tax_lots::{build_report, CostBasisMethod, TaxReport},
+    models::{TradingError, TradingResult},
+    storage::TradeStore,
+};
+
+/// 税务批次服务
+///
+/// 按时间顺序重放用户截至年末的全部成交生成批次，
+/// 因此切换成本计算方法无需迁移已存储的数据。
+pub struct TaxService {
+    config: TaxLotConfig,
+    trade_store: Arc<TradeStore>,
+}
+
+impl TaxService {
+    pub fn new(config: TaxLotConfig, trade_store: Arc<TradeStore>) -> Self {
+        Self { config, trade_store }
+    }
+
+    /// 配置的默认成本计算方法
+    pub fn default_method(&self) -> TradingResult<CostBasisMethod> {
+        self.config.default_method.parse()
+    }
+
+    /// 生成年度已实现盈亏报告
+    pub async fn yearly_report(
+        &self,
+        user_id: Uuid,
+        year: i32,
+        method: Option<CostBasisMethod>,
+    ) -> TradingResult<TaxReport> {
+        let method = match method {
+            Some(method) => method,
+            None => self.default_method()?,
+        };
+        let end = Utc
+            .with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0)
+            .single()
+            .ok_or_else(|| TradingError::ConfigError(format!("Invalid year: {}", year)))?;
+
+        let fills = self.trade_store.list_fills_until(user_id, end).await?;
+        Ok(build_report(user_id, year, method, self.config.long_term_days, &fills))
+    }
+}
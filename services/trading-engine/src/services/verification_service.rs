@@ -0,0 +1,96 @@
+use rust_decimal::Decimal;
+use shared_utils::{AppMetrics, Cache};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::risk::VerificationConfig,
+    models::{Order, TradingResult, UserVerification, VerificationLevel, VerificationLimits},
+    storage::VerificationStore,
+};
+
+/// 用户认证等级服务
+///
+/// 按认证等级限制实盘交易：未认证用户只能查询和使用模拟盘，基础认证用户
+/// 限制单笔和单个交易对的名义价值，高级认证用户不受限制。
+pub struct VerificationService {
+    config: VerificationConfig,
+    store: Arc<VerificationStore>,
+    /// 认证等级读取缓存，修改等级时失效
+    levels: Cache<Uuid, VerificationLevel>,
+}
+
+impl VerificationService {
+    pub fn new(config: VerificationConfig, store: Arc<VerificationStore>, metrics: Arc<AppMetrics>) -> Self {
+        let levels = Cache::new("verification_levels", config.cache.clone()).with_metrics(metrics);
+        Self { config, store, levels }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 查询用户认证记录，从未设置过时为 None
+    pub async fn get(&self, user_id: Uuid) -> TradingResult<Option<UserVerification>> {
+        self.store.get(user_id).await
+    }
+
+    /// 用户当前认证等级，没有记录时为未认证
+    pub async fn level(&self, user_id: Uuid) -> TradingResult<VerificationLevel> {
+        self.levels
+            .get_or_try_load(user_id, || async {
+                Ok(self.store.get(user_id).await?.map(|v| v.level).unwrap_or_default())
+            })
+            .await
+    }
+
+    /// 认证等级对应的交易限额
+    pub fn limits(&self, level: VerificationLevel) -> VerificationLimits {
+        match level {
+            VerificationLevel::Unverified => VerificationLimits {
+                live_trading: false,
+                max_order_notional: None,
+                max_position_notional: None,
+            },
+            VerificationLevel::Level1 => VerificationLimits {
+                live_trading: true,
+                max_order_notional: Some(self.config.level1_max_order_notional),
+                max_position_notional: Some(self.config.level1_max_position_notional),
+            },
+            VerificationLevel::Level2 => VerificationLimits::UNRESTRICTED,
+        }
+    }
+
+    /// 下单前检查认证等级限额，未启用时直接通过
+    ///
+    /// `exposure` 为该交易对已有的持仓和挂单名义价值，不含本订单。
+    pub async fn check_order(&self, order: &Order, order_value: Decimal, exposure: Decimal) -> TradingResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let level = self.level(order.user_id).await?;
+        self.limits(level).check(level, order_value, exposure)
+    }
+
+    /// 修改用户认证等级
+    pub async fn set_level(
+        &self,
+        user_id: Uuid,
+        level: VerificationLevel,
+        operator: Uuid,
+        reason: Option<String>,
+    ) -> TradingResult<UserVerification> {
+        let (verification, change) = self.store.set_level(user_id, level, operator, reason).await?;
+        self.levels.invalidate(&user_id);
+        if let Some(change) = change {
+            tracing::info!(
+                "Verification level of user {} changed from {} to {} by {}",
+                user_id,
+                change.previous_level,
+                change.level,
+                operator
+            );
+        }
+        Ok(verification)
+    }
+}
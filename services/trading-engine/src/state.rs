@@ -1,12 +1,22 @@
 use anyhow::Result;
-use shared_utils::AppMetrics;
+use shared_utils::{AppMetrics, FeatureFlags, LeaderElection};
 use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::{
-    config::TradingEngineConfig,
-    services::{AccountService, ExecutionService, OrderService, PositionService, RiskService},
-    storage::{AccountStore, OrderStore, PositionStore, TradeStore},
+    config::{QueryClass, TradingEngineConfig},
+    engines::{ExecutionEngine, InternalBookFeed, MakerRebateEngine, OrderEventBus},
+    services::{
+        AccountActivityService, AccountService, CalendarService, ConversionService, EquityStreamService, ExecutionService, MarginHeadroomService,
+        NotificationService, OrderRateService, OrderService, OutboxRelay, PnlService, PortfolioStopService, PositionService,
+        ReferralService, RiskEventService, RiskService, SandboxService, ScheduledOrderService, SettlementService, TaxService,
+        VerificationService,
+    },
+    storage::{
+        AccountActivityStore, AccountStore, AlgoOrderStore, BookSnapshotStore, DbPools, ExecutionStore, NotificationStore, OrderStore, OutboxStore, PnlStore, PortfolioStopStore, PositionStore,
+        ReferralStore, RiskConfigStore, RiskEventStore, SagaStore, SandboxStore, ScheduledOrderStore, SettlementStore, TradeStore,
+        VerificationStore,
+    },
 };
 
 /// 应用状态
@@ -15,12 +25,20 @@ pub struct AppState {
     pub config: TradingEngineConfig,
     pub metrics: Arc<AppMetrics>,
     pub db_pool: Arc<PgPool>,
+    /// 主库和读连接池
+    pub db_pools: Arc<DbPools>,
     
     // 存储层
     pub order_store: Arc<OrderStore>,
     pub position_store: Arc<PositionStore>,
     pub account_store: Arc<AccountStore>,
     pub trade_store: Arc<TradeStore>,
+    pub pnl_store: Arc<PnlStore>,
+    pub referral_store: Arc<ReferralStore>,
+    pub sandbox_store: Arc<SandboxStore>,
+    pub settlement_store: Arc<SettlementStore>,
+    pub outbox_store: Arc<OutboxStore>,
+    pub saga_store: Arc<SagaStore>,
     
     // 服务层
     pub order_service: Arc<OrderService>,
@@ -28,57 +46,335 @@ pub struct AppState {
     pub account_service: Arc<AccountService>,
     pub execution_service: Arc<ExecutionService>,
     pub risk_service: Arc<RiskService>,
+    pub risk_event_service: Arc<RiskEventService>,
+    pub margin_headroom: Arc<MarginHeadroomService>,
+    pub order_rate_service: Arc<OrderRateService>,
+    pub calendar_service: Arc<CalendarService>,
+    pub conversion_service: Arc<ConversionService>,
+    pub pnl_service: Arc<PnlService>,
+    pub tax_service: Arc<TaxService>,
+    pub referral_service: Arc<ReferralService>,
+    pub sandbox_service: Arc<SandboxService>,
+    pub settlement_service: Arc<SettlementService>,
+    pub outbox_relay: Arc<OutboxRelay>,
+    pub portfolio_stop_service: Arc<PortfolioStopService>,
+    pub scheduled_order_service: Arc<ScheduledOrderService>,
+    pub equity_stream: Arc<EquityStreamService>,
+    pub verification_service: Arc<VerificationService>,
+    pub notification_service: Arc<NotificationService>,
+    pub activity_service: Arc<AccountActivityService>,
+
+    // 内部撮合行情推送，与执行引擎共享
+    pub book_feed: Arc<InternalBookFeed>,
+
+    // 挂单返佣激励，与执行引擎共享
+    pub maker_rebates: Arc<MakerRebateEngine>,
+
+    // 订单事件总线，执行引擎发布，订单WebSocket订阅
+    pub order_events: Arc<OrderEventBus>,
+
+    // 执行引擎，波动分档读数经此写入
+    pub execution_engine: Arc<ExecutionEngine>,
+
+    // 功能开关
+    pub feature_flags: FeatureFlags,
+
+    // 定时任务领导者选举
+    pub leader: LeaderElection,
 }
 
 impl AppState {
     pub async fn new(config: TradingEngineConfig, metrics: Arc<AppMetrics>) -> Result<Self> {
-        // 创建数据库连接池
-        let db_pool = Arc::new(
-            PgPool::connect(&config.database.url)
-                .await
-                .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?,
-        );
+        // 创建数据库连接池，历史和报表查询使用独立的读连接池
+        let db_pools = Arc::new(DbPools::connect(&config.database).await?);
+        let db_pool = db_pools.primary();
+
+        // 订单和成交事件先写入发件箱，存储层依赖其表结构
+        let outbox_store = Arc::new(OutboxStore::new(db_pool.clone()));
+        outbox_store.ensure_schema().await?;
 
         // 创建存储层
-        let order_store = Arc::new(OrderStore::new(db_pool.clone()));
+        let order_store = Arc::new(
+            OrderStore::new(db_pool.clone()).with_read_pool(db_pools.for_query(QueryClass::History)),
+        );
+        order_store.ensure_schema().await?;
         let position_store = Arc::new(PositionStore::new(db_pool.clone()));
         let account_store = Arc::new(AccountStore::new(db_pool.clone()));
-        let trade_store = Arc::new(TradeStore::new(db_pool.clone()));
+        account_store.ensure_schema().await?;
+        let trade_store = Arc::new(
+            TradeStore::new(db_pool.clone()).with_read_pool(db_pools.for_query(QueryClass::History)),
+        );
+        trade_store.ensure_schema().await?;
+        let pnl_store = Arc::new(
+            PnlStore::new(db_pool.clone()).with_read_pool(db_pools.for_query(QueryClass::Reporting)),
+        );
+        pnl_store.ensure_schema().await?;
+        let referral_store = Arc::new(ReferralStore::new(db_pool.clone()));
+        referral_store.ensure_schema().await?;
+        let sandbox_store = Arc::new(SandboxStore::new(db_pool.clone()));
+        sandbox_store.ensure_schema().await?;
+        let settlement_store = Arc::new(SettlementStore::new(db_pool.clone()));
+        settlement_store.ensure_schema().await?;
+        let saga_store = Arc::new(SagaStore::new(db_pool.clone()));
+        saga_store.ensure_schema().await?;
+        let execution_store = Arc::new(ExecutionStore::new(db_pool.clone()));
+        execution_store.ensure_schema().await?;
+        let portfolio_stop_store = Arc::new(PortfolioStopStore::new(db_pool.clone()));
+        portfolio_stop_store.ensure_schema().await?;
+        let scheduled_order_store = Arc::new(ScheduledOrderStore::new(db_pool.clone()));
+        scheduled_order_store.ensure_schema().await?;
+        let risk_config_store = Arc::new(RiskConfigStore::new(db_pool.clone()));
+        risk_config_store.ensure_schema().await?;
+        let risk_event_store = Arc::new(RiskEventStore::new(db_pool.clone()));
+        risk_event_store.ensure_schema().await?;
+        let verification_store = Arc::new(VerificationStore::new(db_pool.clone()));
+        verification_store.ensure_schema().await?;
+        let notification_store = Arc::new(NotificationStore::new(db_pool.clone()));
+        notification_store.ensure_schema().await?;
+        let activity_store = Arc::new(
+            AccountActivityStore::new(db_pool.clone()).with_read_pool(db_pools.for_query(QueryClass::History)),
+        );
+        activity_store.ensure_schema().await?;
+        let book_snapshot_store = Arc::new(BookSnapshotStore::new(db_pool.clone()));
+        book_snapshot_store.ensure_schema().await?;
+        let algo_order_store = Arc::new(AlgoOrderStore::new(db_pool.clone()));
+        algo_order_store.ensure_schema().await?;
+
+        // 功能开关，默认与服务共用Redis
+        let mut flag_config = config.feature_flags.clone();
+        flag_config
+            .redis_url
+            .get_or_insert_with(|| config.redis.url.clone());
+        let feature_flags = FeatureFlags::connect(flag_config).await;
+
+        // 定时任务只在领导者副本执行
+        let mut leader_config = config.leader_election.clone();
+        leader_config
+            .redis_url
+            .get_or_insert_with(|| config.redis.url.clone());
+        let leader = LeaderElection::connect(leader_config)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect leader election: {}", e))?;
 
         // 创建服务层
         let execution_service = Arc::new(ExecutionService::new(config.clone()).await?);
         let risk_service = Arc::new(RiskService::new(config.clone()));
-        
-        let order_service = Arc::new(OrderService::new(
-            order_store.clone(),
-            execution_service.clone(),
-            risk_service.clone(),
+        let risk_event_service = Arc::new(RiskEventService::new(
+            config.risk.events.clone(),
+            risk_event_store,
         ));
+        let calendar_service = Arc::new(CalendarService::new(config.clone()));
+        let book_feed = Arc::new(InternalBookFeed::new(&config.execution.internal_book));
+        let maker_rebates = Arc::new(MakerRebateEngine::new(config.execution.maker_rebates.clone()));
+        let order_events = Arc::new(OrderEventBus::new());
+
+        // 订单预览与下单共用智能路由
+        let mut execution_engine = ExecutionEngine::new(config.clone())
+            .await?
+            .with_book_feed(book_feed.clone())
+            .with_maker_rebates(maker_rebates.clone())
+            .with_order_events(order_events.clone())
+            .with_feature_flags(feature_flags.clone())
+            .with_algo_store(algo_order_store.clone());
+        if config.execution.book_snapshots.enabled {
+            execution_engine = execution_engine.with_book_snapshots(book_snapshot_store, order_store.clone());
+        }
+        execution_engine.register_configured_exchanges().await?;
+        let execution_engine = Arc::new(execution_engine);
         
+        let referral_service = Arc::new(ReferralService::new(
+            config.trading.referrals.clone(),
+            referral_store.clone(),
+        ));
+
         let position_service = Arc::new(PositionService::new(
             position_store.clone(),
             execution_service.clone(),
             risk_service.clone(),
         ));
         
-        let account_service = Arc::new(AccountService::new(
+        let account_service = Arc::new(
+            AccountService::new(account_store.clone(), position_service.clone())
+                .with_default_environment_access(config.execution.testnet.default_access),
+        );
+
+        // 下单前按账户实时保证金余量检查，仓位变化时刷新
+        let margin_headroom = Arc::new(MarginHeadroomService::new(
+            config.risk.clone(),
+            account_store.clone(),
+            position_service.clone(),
+            config.websocket.equity_stream.quote_currency.clone(),
+        ));
+
+        // 按用户滑动窗口统计下单频率
+        // 每分钟上限按用户风控配置
+        let order_rate_service = Arc::new(
+            OrderRateService::new(config.risk.clone(), order_store.clone(), metrics.clone())
+                .with_user_configs(risk_config_store.clone()),
+        );
+
+        // 按认证等级限制实盘交易
+        let verification_service = Arc::new(VerificationService::new(
+            config.risk.verification.clone(),
+            verification_store,
+            metrics.clone(),
+        ));
+
+        let mut order_service = OrderService::new(
+            order_store.clone(),
+            execution_service.clone(),
+            risk_service.clone(),
+            calendar_service.clone(),
+        )
+        .with_referrals(referral_service.clone())
+        .with_routing(
+            execution_engine.clone(),
+            config.execution.routing.routing_strategy.clone(),
+        )
+        .with_margin_headroom(margin_headroom.clone())
+        .with_order_rate(order_rate_service.clone())
+        .with_portfolio_stops(portfolio_stop_store.clone())
+        .with_verification(verification_service.clone())
+        .with_environment_access(account_service.clone())
+        .with_positions(position_service.clone())
+        .with_risk_events(risk_event_service.clone())
+        .with_algo_orders(algo_order_store.clone());
+        if config.trading.sagas.enabled {
+            order_service = order_service.with_sagas(saga_store.clone(), config.trading.sagas.clone());
+        }
+        if config.trading.balance_holds.enabled {
+            order_service = order_service.with_balance_holds(account_service.clone());
+        }
+        if config.trading.execution_dedup.enabled {
+            order_service = order_service
+                .with_execution_dedup(execution_store, config.trading.execution_dedup.clone());
+        }
+        let order_service = Arc::new(order_service);
+
+        // 账户权益推送按执行引擎的实时价格计算未实现盈亏
+        let equity_stream = Arc::new(EquityStreamService::new(
+            config.websocket.equity_stream.clone(),
             account_store.clone(),
             position_service.clone(),
+            execution_engine.clone(),
+        ));
+
+        // 盈亏快照的权益与权益推送一致，已实现盈亏按成交重放计算
+        let pnl_service = Arc::new(PnlService::new(
+            config.trading.pnl_snapshots.clone(),
+            pnl_store.clone(),
+            position_store.clone(),
+            trade_store.clone(),
+            equity_stream.clone(),
+        ));
+
+        let tax_service = Arc::new(TaxService::new(
+            config.trading.tax_lots.clone(),
+            trade_store.clone(),
+        ));
+
+        // 模拟盘使用独立存储，只借用执行服务的行情价格
+        let sandbox_service = Arc::new(SandboxService::new(
+            config.trading.sandbox.clone(),
+            sandbox_store.clone(),
+            execution_service.clone(),
+        ));
+
+        let outbox_relay = Arc::new(
+            OutboxRelay::new(config.outbox.clone(), outbox_store.clone())
+                .map_err(|e| anyhow::anyhow!("Failed to create outbox relay: {}", e))?,
+        );
+
+        // 余额、盈亏和对账单按账户结算币种折算
+        let conversion_service = Arc::new(ConversionService::new(
+            config.trading.conversion.clone(),
+            account_store.clone(),
+            execution_engine.clone(),
+            metrics.clone(),
+        ));
+
+        let settlement_service = Arc::new(
+            SettlementService::new(
+                config.trading.settlement.clone(),
+                settlement_store.clone(),
+                position_store.clone(),
+                account_service.clone(),
+            )
+            .with_conversion(conversion_service.clone()),
+        );
+
+        let notification_service = Arc::new(NotificationService::new(
+            config.trading.notifications.clone(),
+            notification_store,
         ));
 
+        // 账户级回撤止损按账户权益检查，通过执行引擎平仓
+        let portfolio_stop_service = Arc::new(
+            PortfolioStopService::new(
+                config.trading.portfolio_stops.clone(),
+                portfolio_stop_store,
+                equity_stream.clone(),
+                order_service.clone(),
+                position_service.clone(),
+                execution_engine.clone(),
+                trade_store.clone(),
+                config.execution.routing.routing_strategy.clone(),
+            )
+            .with_notifications(notification_service.clone()),
+        );
+
+        // 定时订单到期时通过订单服务下单和撤单
+        let scheduled_order_service = Arc::new(ScheduledOrderService::new(
+            config.trading.scheduled_orders.clone(),
+            scheduled_order_store,
+            order_service.clone(),
+        ));
+
+        let activity_service = Arc::new(AccountActivityService::new(activity_store));
+
         Ok(Self {
             config,
             metrics,
             db_pool,
+            db_pools,
             order_store,
             position_store,
             account_store,
             trade_store,
+            pnl_store,
+            referral_store,
+            sandbox_store,
+            settlement_store,
+            outbox_store,
+            saga_store,
             order_service,
             position_service,
             account_service,
             execution_service,
             risk_service,
+            risk_event_service,
+            margin_headroom,
+            order_rate_service,
+            calendar_service,
+            conversion_service,
+            pnl_service,
+            tax_service,
+            referral_service,
+            sandbox_service,
+            settlement_service,
+            outbox_relay,
+            portfolio_stop_service,
+            scheduled_order_service,
+            equity_stream,
+            verification_service,
+            notification_service,
+            activity_service,
+            book_feed,
+            maker_rebates,
+            order_events,
+            execution_engine,
+            feature_flags,
+            leader,
         })
     }
 
@@ -96,9 +392,17 @@ impl AppState {
         "healthy".to_string()
     }
 
-    /// 检查Kafka健康状态
+    /// 检查Kafka健康状态，以发件箱积压衡量事件投递是否正常
     pub async fn check_kafka_health(&self) -> String {
-        // TODO: 实现Kafka健康检查
-        "healthy".to_string()
+        match self.outbox_store.backlog().await {
+            Ok(backlog) => match backlog.oldest_pending_at {
+                Some(oldest) if chrono::Utc::now() - oldest > chrono::Duration::minutes(5) => format!(
+                    "degraded: {} outbox events pending since {}",
+                    backlog.pending, oldest
+                ),
+                _ => "healthy".to_string(),
+            },
+            Err(e) => format!("unhealthy: {}", e),
+        }
     }
 }
\ No newline at end of file
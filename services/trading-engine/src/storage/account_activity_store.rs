@@ -0,0 +1,194 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{AccountActivity, ActivityCategory, ActivityQuery, TradingError, TradingResult};
+
+/// 账户动态审计表
+///
+/// 只保存其他表中没有的事件：认证服务上报的登录和API密钥变更、账户设置变更。
+/// 订单和账户止损的动态在查询时直接从 orders 和 portfolio_stop_events 读取。
+const SCHEMA: [&str; 2] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS account_activity (
+        id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        category TEXT NOT NULL,
+        action TEXT NOT NULL,
+        summary TEXT NOT NULL,
+        details JSONB NOT NULL,
+        source TEXT NOT NULL,
+        occurred_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_account_activity_user_time ON account_activity (user_id, occurred_at DESC, id DESC)",
+];
+
+/// 单次查询的最大条数
+pub const MAX_ACTIVITY_PAGE: u32 = 500;
+
+/// 订单动态：每个订单一条下单记录，进入终态后再加一条终态记录
+///
+/// 终态记录的ID由订单ID和状态派生，保证分页游标唯一。
+const ORDER_ACTIVITY: &str = r#"
+    SELECT id, user_id, 'order' AS category, 'order_placed' AS action,
+           side || ' ' || quantity::TEXT || ' ' || symbol || ' ' || order_type AS summary,
+           jsonb_build_object(
+               'order_id', id, 'symbol', symbol, 'side', side, 'order_type', order_type,
+               'quantity', quantity, 'price', price, 'environment', environment
+           ) AS details,
+           'trading-engine' AS source, created_at AS occurred_at
+    FROM orders WHERE user_id = $1
+    UNION ALL
+    SELECT md5(id::TEXT || status)::UUID, user_id, 'order', 'order_' || LOWER(status),
+           symbol || ' order ' || LOWER(status) || ', filled ' || filled_quantity::TEXT || '/' || quantity::TEXT,
+           jsonb_build_object(
+               'order_id', id, 'symbol', symbol, 'status', status,
+               'filled_quantity', filled_quantity, 'average_price', average_price
+           ),
+           'trading-engine', updated_at
+    FROM orders WHERE user_id = $1 AND status IN ('FILLED', 'CANCELLED', 'REJECTED', 'EXPIRED')
+"#;
+
+/// 账户止损的审计事件
+const RISK_CONFIG_ACTIVITY: &str = r#"
+    SELECT id, user_id, 'risk_config', 'portfolio_stop_' || kind,
+           'Portfolio stop ' || kind, details, 'trading-engine', created_at
+    FROM portfolio_stop_events WHERE user_id = $1
+"#;
+
+/// 账户动态存储
+#[derive(Clone)]
+pub struct AccountActivityStore {
+    pool: Arc<PgPool>,
+    /// 时间线查询使用的连接池，未单独配置时与主库相同
+    read_pool: Arc<PgPool>,
+}
+
+impl AccountActivityStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self {
+            read_pool: pool.clone(),
+            pool,
+        }
+    }
+
+    /// 使用独立的读连接池
+    pub fn with_read_pool(mut self, read_pool: Arc<PgPool>) -> Self {
+        self.read_pool = read_pool;
+        self
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 记录账户动态，重复的ID忽略
+    pub async fn record(&self, activity: &AccountActivity) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_activity (id, user_id, category, action, summary, details, source, occurred_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(activity.id)
+        .bind(activity.user_id)
+        .bind(activity.category.to_string())
+        .bind(&activity.action)
+        .bind(&activity.summary)
+        .bind(&activity.details)
+        .bind(&activity.source)
+        .bind(activity.occurred_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 合并审计表、订单表和止损事件，按发生时间倒序分页
+    ///
+    /// 多取一条用于判断是否还有下一页，返回 (本页动态, 是否有更多)。
+    pub async fn list(&self, user_id: Uuid, query: &ActivityQuery) -> TradingResult<(Vec<AccountActivity>, bool)> {
+        let limit = query.limit.clamp(1, MAX_ACTIVITY_PAGE);
+
+        let recorded: Vec<String> = [ActivityCategory::Auth, ActivityCategory::ApiKey, ActivityCategory::Settings]
+            .into_iter()
+            .filter(|category| query.includes(*category))
+            .map(|category| category.to_string())
+            .collect();
+        let mut sources = Vec::new();
+        if !recorded.is_empty() {
+            sources.push(
+                "SELECT id, user_id, category, action, summary, details, source, occurred_at \
+                 FROM account_activity WHERE user_id = $1 AND category = ANY($2)",
+            );
+        }
+        if query.includes(ActivityCategory::Order) {
+            sources.push(ORDER_ACTIVITY);
+        }
+        if query.includes(ActivityCategory::RiskConfig) {
+            sources.push(RISK_CONFIG_ACTIVITY);
+        }
+        if sources.is_empty() {
+            return Ok((Vec::new(), false));
+        }
+
+        // 各来源共用 $1（用户）和 $2（审计表类别），两者是外层最先绑定的参数
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("SELECT * FROM (");
+        builder.push(sources.join(" UNION ALL "));
+        builder.push(") AS activity WHERE user_id = ").push_bind(user_id);
+        builder
+            .push(" AND (category IN ('order', 'risk_config') OR category = ANY(")
+            .push_bind(recorded)
+            .push("))");
+        if let Some(start) = query.start_time {
+            builder.push(" AND occurred_at >= ").push_bind(start);
+        }
+        if let Some(end) = query.end_time {
+            builder.push(" AND occurred_at < ").push_bind(end);
+        }
+        if let Some(cursor) = &query.cursor {
+            builder
+                .push(" AND (occurred_at, id) < (")
+                .push_bind(cursor.occurred_at)
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        builder
+            .push(" ORDER BY occurred_at DESC, id DESC LIMIT ")
+            .push_bind(limit as i64 + 1);
+
+        let rows = builder
+            .build()
+            .fetch_all(&*self.read_pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        let mut activities = rows
+            .into_iter()
+            .map(row_to_activity)
+            .collect::<TradingResult<Vec<_>>>()?;
+        let has_more = activities.len() > limit as usize;
+        activities.truncate(limit as usize);
+        Ok((activities, has_more))
+    }
+}
+
+fn row_to_activity(row: PgRow) -> TradingResult<AccountActivity> {
+    Ok(AccountActivity {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        category: row.get::<String, _>("category").parse()?,
+        action: row.get("action"),
+        summary: row.get("summary"),
+        details: row.get("details"),
+        source: row.get("source"),
+        occurred_at: row.get("occurred_at"),
+    })
+}
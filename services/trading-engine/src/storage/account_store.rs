@@ -1,9 +1,65 @@
 use anyhow::Result;
-use sqlx::PgPool;
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::models::{TradingError, TradingResult};
+use crate::models::{BalanceHold, EnvironmentAccess, FillSettlement, HoldStatus, TradingError, TradingResult};
+use shared_models::SettlementCurrency;
+
+/// 余额、冻结和账户设置表
+///
+/// `account_balances.held` 是该币种所有生效冻结的合计，与 `balance_holds` 在同一事务中更新。
+const SCHEMA: [&str; 5] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS account_balances (
+        user_id UUID NOT NULL,
+        currency TEXT NOT NULL,
+        total NUMERIC NOT NULL DEFAULT 0,
+        held NUMERIC NOT NULL DEFAULT 0,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (user_id, currency)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS balance_holds (
+        order_id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        currency TEXT NOT NULL,
+        amount NUMERIC NOT NULL,
+        status TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_balance_holds_active ON balance_holds (user_id) WHERE status = 'active'",
+    r#"
+    CREATE TABLE IF NOT EXISTS account_settings (
+        user_id UUID PRIMARY KEY,
+        settlement_currency TEXT NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS account_environments (
+        user_id UUID PRIMARY KEY,
+        access TEXT NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+    "#,
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+/// 币种余额
+#[derive(Debug, Clone)]
+pub struct BalanceRow {
+    pub currency: String,
+    pub total: Decimal,
+    pub held: Decimal,
+}
 
 /// 账户存储
 #[derive(Clone)]
@@ -16,5 +72,333 @@ impl AccountStore {
         Self { pool }
     }
 
-    // TODO: 实现账户相关的数据库操作
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 用户各币种余额
+    pub async fn balances(&self, user_id: Uuid) -> TradingResult<Vec<BalanceRow>> {
+        let rows = sqlx::query(
+            "SELECT currency, total, held FROM account_balances WHERE user_id = $1 ORDER BY currency",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| BalanceRow {
+                currency: row.get("currency"),
+                total: row.get("total"),
+                held: row.get("held"),
+            })
+            .collect())
+    }
+
+    /// 账户选择的结算币种，未设置时为 None
+    pub async fn settlement_currency(&self, user_id: Uuid) -> TradingResult<Option<SettlementCurrency>> {
+        let row = sqlx::query("SELECT settlement_currency FROM account_settings WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| {
+            row.get::<String, _>("settlement_currency")
+                .parse::<SettlementCurrency>()
+                .map_err(|e| TradingError::SerializationError(e.to_string()))
+        })
+        .transpose()
+    }
+
+    /// 设置账户结算币种
+    pub async fn set_settlement_currency(&self, user_id: Uuid, currency: SettlementCurrency) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_settings (user_id, settlement_currency, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (user_id) DO UPDATE SET
+                settlement_currency = EXCLUDED.settlement_currency,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(currency.as_str())
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 账户允许交易的环境，未设置时为 None
+    pub async fn environment_access(&self, user_id: Uuid) -> TradingResult<Option<EnvironmentAccess>> {
+        let row = sqlx::query("SELECT access FROM account_environments WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| row.get::<String, _>("access").parse()).transpose()
+    }
+
+    /// 设置账户允许交易的环境
+    pub async fn set_environment_access(&self, user_id: Uuid, access: EnvironmentAccess) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_environments (user_id, access, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (user_id) DO UPDATE SET
+                access = EXCLUDED.access,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(access.to_string())
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 用户生效中的冻结
+    pub async fn active_holds(&self, user_id: Uuid) -> TradingResult<Vec<BalanceHold>> {
+        let rows = sqlx::query(
+            "SELECT * FROM balance_holds WHERE user_id = $1 AND status = 'active' ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(Self::row_to_hold).collect()
+    }
+
+    /// 为订单冻结余额
+    ///
+    /// 锁定余额行后检查可用余额，并发下单在行锁上排队，不会同时通过检查。
+    /// 订单已有生效冻结时替换为新金额，只校验差额，重复调用结果相同。
+    pub async fn place_hold(
+        &self,
+        user_id: Uuid,
+        order_id: Uuid,
+        currency: &str,
+        amount: Decimal,
+    ) -> TradingResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let existing = Self::lock_hold(&mut tx, order_id).await?;
+        let previous = match &existing {
+            Some(hold) if hold.status == HoldStatus::Active && hold.currency == currency => hold.amount,
+            Some(hold) if hold.status == HoldStatus::Active => {
+                return Err(TradingError::InvalidOrder(format!(
+                    "Order {} already holds {}",
+                    order_id, hold.currency
+                )));
+            }
+            Some(_) => {
+                return Err(TradingError::InvalidOrder(format!(
+                    "Hold for order {} is already closed",
+                    order_id
+                )));
+            }
+            None => Decimal::ZERO,
+        };
+
+        let balance = sqlx::query(
+            "SELECT total, held FROM account_balances WHERE user_id = $1 AND currency = $2 FOR UPDATE",
+        )
+        .bind(user_id)
+        .bind(currency)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        let (total, held): (Decimal, Decimal) = balance
+            .map(|row| (row.get("total"), row.get("held")))
+            .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+
+        let available = total - held + previous;
+        if amount > available {
+            return Err(TradingError::InsufficientBalance {
+                required: amount,
+                available,
+            });
+        }
+
+        sqlx::query(
+            "UPDATE account_balances SET held = held + $3, updated_at = NOW() WHERE user_id = $1 AND currency = $2",
+        )
+        .bind(user_id)
+        .bind(currency)
+        .bind(amount - previous)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO balance_holds (order_id, user_id, currency, amount, status)
+            VALUES ($1, $2, $3, $4, 'active')
+            ON CONFLICT (order_id) DO UPDATE SET amount = EXCLUDED.amount, updated_at = NOW()
+            "#,
+        )
+        .bind(order_id)
+        .bind(user_id)
+        .bind(currency)
+        .bind(amount)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 成交时从冻结中扣减并入账
+    ///
+    /// 实际成交金额超出冻结时（市价单按估价冻结）超出部分从可用余额扣减，
+    /// 可用余额不足时拒绝并回滚，余额不会变为负数。
+    /// `close` 为 true 时订单已完全成交，剩余冻结一并释放。
+    pub async fn settle_fill(
+        &self,
+        user_id: Uuid,
+        order_id: Uuid,
+        settlement: &FillSettlement,
+        close: bool,
+    ) -> TradingResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let held = match Self::lock_hold(&mut tx, order_id).await? {
+            Some(hold) if hold.status == HoldStatus::Active => hold.amount,
+            _ => Decimal::ZERO,
+        };
+        let consumed = settlement.debit_amount.min(held);
+        let remaining = held - consumed;
+        let released = if close { remaining } else { Decimal::ZERO };
+
+        // 锁定余额行，超出冻结的部分只能使用其他订单未冻结的余额
+        let balance = sqlx::query(
+            "SELECT total, held FROM account_balances WHERE user_id = $1 AND currency = $2 FOR UPDATE",
+        )
+        .bind(user_id)
+        .bind(&settlement.debit_currency)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        let (total, total_held): (Decimal, Decimal) = balance
+            .map(|row| (row.get("total"), row.get("held")))
+            .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+        let excess = settlement.debit_amount - consumed;
+        let available = total - total_held;
+        if excess > available {
+            return Err(TradingError::InsufficientBalance {
+                required: excess,
+                available,
+            });
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE account_balances
+            SET total = total - $3, held = held - $4, updated_at = NOW()
+            WHERE user_id = $1 AND currency = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(&settlement.debit_currency)
+        .bind(settlement.debit_amount)
+        .bind(consumed + released)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO account_balances (user_id, currency, total)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, currency) DO UPDATE SET
+                total = account_balances.total + EXCLUDED.total,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(&settlement.credit_currency)
+        .bind(settlement.credit_amount)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        if held > Decimal::ZERO {
+            let status = if close { HoldStatus::Converted } else { HoldStatus::Active };
+            sqlx::query("UPDATE balance_holds SET amount = $2, status = $3, updated_at = NOW() WHERE order_id = $1")
+                .bind(order_id)
+                .bind(remaining - released)
+                .bind(status.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 释放订单剩余冻结，冻结不存在或已结束时不做处理
+    pub async fn release_hold(&self, order_id: Uuid) -> TradingResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let Some(hold) = Self::lock_hold(&mut tx, order_id).await? else {
+            return Ok(());
+        };
+        if hold.status != HoldStatus::Active {
+            return Ok(());
+        }
+
+        sqlx::query(
+            "UPDATE account_balances SET held = held - $3, updated_at = NOW() WHERE user_id = $1 AND currency = $2",
+        )
+        .bind(hold.user_id)
+        .bind(&hold.currency)
+        .bind(hold.amount)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query("UPDATE balance_holds SET amount = 0, status = 'released', updated_at = NOW() WHERE order_id = $1")
+            .bind(order_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn lock_hold(
+        tx: &mut Transaction<'_, Postgres>,
+        order_id: Uuid,
+    ) -> TradingResult<Option<BalanceHold>> {
+        let row = sqlx::query("SELECT * FROM balance_holds WHERE order_id = $1 FOR UPDATE")
+            .bind(order_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(db_error)?;
+
+        row.as_ref().map(Self::row_to_hold).transpose()
+    }
+
+    fn row_to_hold(row: &PgRow) -> TradingResult<BalanceHold> {
+        Ok(BalanceHold {
+            order_id: row.get("order_id"),
+            user_id: row.get("user_id"),
+            currency: row.get("currency"),
+            amount: row.get("amount"),
+            status: row.get::<String, _>("status").parse()?,
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        })
+    }
 }
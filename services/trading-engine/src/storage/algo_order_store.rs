@@ -0,0 +1,107 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+use crate::engines::algo_orders::{AlgoOrderProgress, AlgoOrderStatus};
+use crate::models::{TradingError, TradingResult};
+
+/// 算法单执行进度
+///
+/// 执行实例每个时间片保存一次进度并顺延 stale_after，过期仍为 running 的算法单
+/// 说明执行实例已中断，由恢复任务撤销父订单的剩余数量。
+const SCHEMA: [&str; 2] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS algo_orders (
+        parent_order_id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        algorithm TEXT NOT NULL,
+        status TEXT NOT NULL,
+        progress JSONB NOT NULL,
+        stale_after TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_algo_orders_running ON algo_orders (stale_after) WHERE status = 'running'",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn status_name(status: AlgoOrderStatus) -> &'static str {
+    match status {
+        AlgoOrderStatus::Running => "running",
+        AlgoOrderStatus::Completed => "completed",
+        AlgoOrderStatus::Cancelled => "cancelled",
+    }
+}
+
+/// 算法单进度存储
+#[derive(Clone)]
+pub struct AlgoOrderStore {
+    pool: Arc<PgPool>,
+}
+
+impl AlgoOrderStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 保存算法单进度，执行中的算法单需在 `stale_after` 之前再次保存
+    pub async fn save(&self, progress: &AlgoOrderProgress, stale_after: DateTime<Utc>) -> TradingResult<()> {
+        let snapshot =
+            serde_json::to_value(progress).map_err(|e| TradingError::SerializationError(e.to_string()))?;
+        sqlx::query(
+            r#"
+            INSERT INTO algo_orders (parent_order_id, user_id, algorithm, status, progress, stale_after, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW())
+            ON CONFLICT (parent_order_id) DO UPDATE SET
+                status = EXCLUDED.status,
+                progress = EXCLUDED.progress,
+                stale_after = EXCLUDED.stale_after,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(progress.parent_order_id)
+        .bind(progress.user_id)
+        .bind(&progress.algorithm)
+        .bind(status_name(progress.status))
+        .bind(snapshot)
+        .bind(stale_after)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 执行实例已中断的算法单：仍为 running 且超过 stale_after 未更新
+    pub async fn list_interrupted(&self, limit: i64) -> TradingResult<Vec<AlgoOrderProgress>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT progress FROM algo_orders
+            WHERE status = 'running' AND stale_after < NOW()
+            ORDER BY stale_after
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                serde_json::from_value(row.get("progress"))
+                    .map_err(|e| TradingError::SerializationError(e.to_string()))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+use crate::models::{Timestamp, TradingError, TradingResult};
+
+/// 内部撮合订单簿快照，每个交易对只保留最新一份
+///
+/// state 为 [`crate::engines::StateCodec`] 编码的带版本状态，version 冗余保存便于排查。
+const SCHEMA: [&str; 1] = [r#"
+    CREATE TABLE IF NOT EXISTS matching_book_snapshots (
+        symbol TEXT PRIMARY KEY,
+        version INTEGER NOT NULL,
+        state BYTEA NOT NULL,
+        resting_orders INTEGER NOT NULL,
+        taken_at TIMESTAMPTZ NOT NULL
+    )
+    "#];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+/// 订单簿快照存储
+#[derive(Clone)]
+pub struct BookSnapshotStore {
+    pool: Arc<PgPool>,
+}
+
+impl BookSnapshotStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 写入交易对的最新快照，只有比已有快照新时才覆盖
+    pub async fn save(
+        &self,
+        symbol: &str,
+        version: u32,
+        state: &[u8],
+        resting_orders: usize,
+        taken_at: Timestamp,
+    ) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO matching_book_snapshots (symbol, version, state, resting_orders, taken_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (symbol) DO UPDATE SET
+                version = EXCLUDED.version,
+                state = EXCLUDED.state,
+                resting_orders = EXCLUDED.resting_orders,
+                taken_at = EXCLUDED.taken_at
+            WHERE matching_book_snapshots.taken_at <= EXCLUDED.taken_at
+            "#,
+        )
+        .bind(symbol)
+        .bind(version as i32)
+        .bind(state)
+        .bind(resting_orders as i32)
+        .bind(taken_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 读取交易对的快照
+    pub async fn load(&self, symbol: &str) -> TradingResult<Option<Vec<u8>>> {
+        let row = sqlx::query("SELECT state FROM matching_book_snapshots WHERE symbol = $1")
+            .bind(symbol)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(row.map(|row| row.get("state")))
+    }
+
+    /// 有快照的交易对
+    pub async fn symbols(&self) -> TradingResult<Vec<String>> {
+        let rows = sqlx::query("SELECT symbol FROM matching_book_snapshots ORDER BY symbol")
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(rows.into_iter().map(|row| row.get("symbol")).collect())
+    }
+}
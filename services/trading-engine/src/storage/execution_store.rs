@@ -0,0 +1,93 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+
+use crate::models::{ExecutionReport, TradingError, TradingResult};
+
+/// 已处理的执行回报
+///
+/// 主键保证同一回报只能登记一次，超过保留时长的记录定期清理。
+const SCHEMA: [&str; 2] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS execution_reports (
+        venue TEXT NOT NULL,
+        execution_id TEXT NOT NULL,
+        order_id UUID NOT NULL,
+        quantity NUMERIC NOT NULL,
+        price NUMERIC NOT NULL,
+        received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (venue, execution_id)
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_execution_reports_received ON execution_reports (received_at)",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+/// 执行回报去重存储
+#[derive(Clone)]
+pub struct ExecutionStore {
+    pool: Arc<PgPool>,
+}
+
+impl ExecutionStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 是否已登记过该回报
+    pub async fn contains(&self, report: &ExecutionReport) -> TradingResult<bool> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM execution_reports WHERE venue = $1 AND execution_id = $2)",
+        )
+        .bind(&report.venue)
+        .bind(&report.execution_id)
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(exists)
+    }
+
+    /// 在成交事务中登记执行回报，已登记过时返回 false
+    ///
+    /// 登记随订单更新一起提交或回滚，并发收到同一回报时只有一方插入成功。
+    pub async fn claim(tx: &mut Transaction<'_, Postgres>, report: &ExecutionReport) -> TradingResult<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO execution_reports (venue, execution_id, order_id, quantity, price)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (venue, execution_id) DO NOTHING
+            "#,
+        )
+        .bind(&report.venue)
+        .bind(&report.execution_id)
+        .bind(report.order_id)
+        .bind(report.quantity)
+        .bind(report.price)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 删除早于指定时间登记的回报
+    pub async fn purge_before(&self, before: DateTime<Utc>) -> TradingResult<u64> {
+        let result = sqlx::query("DELETE FROM execution_reports WHERE received_at < $1")
+            .bind(before)
+            .execute(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,9 +1,43 @@
+pub mod account_activity_store;
 pub mod account_store;
+pub mod algo_order_store;
+pub mod book_snapshot_store;
+pub mod execution_store;
+pub mod notification_store;
 pub mod order_store;
+pub mod outbox_store;
+pub mod pnl_store;
+pub mod pools;
+pub mod portfolio_stop_store;
 pub mod position_store;
+pub mod referral_store;
+pub mod risk_config_store;
+pub mod risk_event_store;
+pub mod saga_store;
+pub mod sandbox_store;
+pub mod scheduled_order_store;
+pub mod settlement_store;
 pub mod trade_store;
+pub mod verification_store;
 
+pub use account_activity_store::AccountActivityStore;
 pub use account_store::AccountStore;
+pub use algo_order_store::AlgoOrderStore;
+pub use book_snapshot_store::BookSnapshotStore;
+pub use execution_store::ExecutionStore;
+pub use notification_store::NotificationStore;
 pub use order_store::OrderStore;
+pub use outbox_store::OutboxStore;
+pub use pnl_store::PnlStore;
+pub use pools::{DbPools, PoolUsage};
+pub use portfolio_stop_store::PortfolioStopStore;
 pub use position_store::PositionStore;
+pub use referral_store::ReferralStore;
+pub use risk_config_store::RiskConfigStore;
+pub use risk_event_store::RiskEventStore;
+pub use saga_store::SagaStore;
+pub use sandbox_store::SandboxStore;
+pub use scheduled_order_store::ScheduledOrderStore;
+pub use settlement_store::SettlementStore;
 pub use trade_store::TradeStore;
+pub use verification_store::VerificationStore;
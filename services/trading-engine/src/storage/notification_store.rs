@@ -0,0 +1,176 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{Notification, NotificationCategory, Timestamp, TradingError, TradingResult};
+
+/// 站内通知表，按用户和创建时间倒序分页，未读通知单独建部分索引
+const SCHEMA: [&str; 4] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS notifications (
+        id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        category TEXT NOT NULL,
+        title TEXT NOT NULL,
+        message TEXT NOT NULL,
+        data JSONB NOT NULL,
+        read_at TIMESTAMPTZ,
+        created_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_notifications_user ON notifications (user_id, created_at DESC, id DESC)",
+    "CREATE INDEX IF NOT EXISTS idx_notifications_unread ON notifications (user_id, category) WHERE read_at IS NULL",
+    "CREATE INDEX IF NOT EXISTS idx_notifications_created ON notifications (created_at)",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn row_to_notification(row: PgRow) -> TradingResult<Notification> {
+    let category: String = row.get("category");
+    Ok(Notification {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        category: category.parse()?,
+        title: row.get("title"),
+        message: row.get("message"),
+        data: row.get("data"),
+        read_at: row.get("read_at"),
+        created_at: row.get("created_at"),
+    })
+}
+
+/// 站内通知存储
+#[derive(Clone)]
+pub struct NotificationStore {
+    pool: Arc<PgPool>,
+}
+
+impl NotificationStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn insert(&self, notification: &Notification) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (id, user_id, category, title, message, data, read_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(notification.id)
+        .bind(notification.user_id)
+        .bind(notification.category.to_string())
+        .bind(&notification.title)
+        .bind(&notification.message)
+        .bind(&notification.data)
+        .bind(notification.read_at)
+        .bind(notification.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 按创建时间倒序列出用户的通知，before 为上一页最后一条的创建时间
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        category: Option<NotificationCategory>,
+        unread_only: bool,
+        before: Option<Timestamp>,
+        limit: u32,
+    ) -> TradingResult<Vec<Notification>> {
+        sqlx::query(
+            r#"
+            SELECT * FROM notifications
+            WHERE user_id = $1
+              AND ($2::TEXT IS NULL OR category = $2)
+              AND (NOT $3 OR read_at IS NULL)
+              AND ($4::TIMESTAMPTZ IS NULL OR created_at < $4)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $5
+            "#,
+        )
+        .bind(user_id)
+        .bind(category.map(|c| c.to_string()))
+        .bind(unread_only)
+        .bind(before)
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(row_to_notification)
+        .collect()
+    }
+
+    /// 各类别的未读数量
+    pub async fn unread_counts(&self, user_id: Uuid) -> TradingResult<Vec<(NotificationCategory, i64)>> {
+        sqlx::query(
+            r#"
+            SELECT category, COUNT(*) AS count
+            FROM notifications
+            WHERE user_id = $1 AND read_at IS NULL
+            GROUP BY category
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|row| -> TradingResult<(NotificationCategory, i64)> {
+            let category: String = row.get("category");
+            Ok((category.parse()?, row.get("count")))
+        })
+        .collect()
+    }
+
+    /// 标记已读：指定ID时只标记这些通知，否则标记该类别（或全部）未读通知，返回标记的条数
+    pub async fn mark_read(
+        &self,
+        user_id: Uuid,
+        ids: Option<&[Uuid]>,
+        category: Option<NotificationCategory>,
+        read_at: Timestamp,
+    ) -> TradingResult<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET read_at = $4
+            WHERE user_id = $1
+              AND read_at IS NULL
+              AND ($2::UUID[] IS NULL OR id = ANY($2))
+              AND ($3::TEXT IS NULL OR category = $3)
+            "#,
+        )
+        .bind(user_id)
+        .bind(ids)
+        .bind(category.map(|c| c.to_string()))
+        .bind(read_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+
+    /// 删除创建时间早于 before 的通知
+    pub async fn purge(&self, before: Timestamp) -> TradingResult<u64> {
+        let result = sqlx::query("DELETE FROM notifications WHERE created_at < $1")
+            .bind(before)
+            .execute(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,34 +1,69 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use sqlx::{PgPool, Row};
+use sqlx::{PgPool, Postgres, Row, Transaction};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::models::{Order, OrderStatus, OrderType, Side, Symbol, TimeInForce, TradingError, TradingResult};
+use shared_protocols::kafka::KafkaTopics;
+
+use super::{ExecutionStore, OutboxStore};
+use crate::models::{ExecutionReport, Order, OrderStatus, OrderType, Side, Symbol, TimeInForce, TradingError, TradingResult};
+
+/// 订单表的增量列，基础表结构由数据库迁移创建
+///
+/// 交易环境保存在 metadata 中，另生成一列便于按环境查询和区分测试网数据。
+const SCHEMA: [&str; 2] = [
+    "ALTER TABLE orders ADD COLUMN IF NOT EXISTS visible_quantity NUMERIC",
+    "ALTER TABLE orders ADD COLUMN IF NOT EXISTS environment TEXT GENERATED ALWAYS AS (COALESCE(metadata->>'environment', 'live')) STORED",
+];
 
 /// 订单存储
 #[derive(Clone)]
 pub struct OrderStore {
     pool: Arc<PgPool>,
+    /// 订单历史列表使用的连接池，未单独配置时与主库相同
+    read_pool: Arc<PgPool>,
 }
 
 impl OrderStore {
     pub fn new(pool: Arc<PgPool>) -> Self {
-        Self { pool }
+        Self {
+            read_pool: pool.clone(),
+            pool,
+        }
+    }
+
+    /// 使用独立的读连接池
+    pub fn with_read_pool(mut self, read_pool: Arc<PgPool>) -> Self {
+        self.read_pool = read_pool;
+        self
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
     }
 
-    /// 创建订单
+    /// 创建订单，同一事务写入 order_created 事件
     pub async fn create_order(&self, order: &Order) -> TradingResult<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
         let query = r#"
             INSERT INTO orders (
                 id, user_id, symbol, order_type, side, quantity, price, stop_price,
                 status, time_in_force, filled_quantity, remaining_quantity,
                 average_price, fee, fee_currency, created_at, updated_at,
-                expires_at, client_order_id, metadata
+                expires_at, client_order_id, metadata, visible_quantity
             ) VALUES (
                 $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
-                $16, $17, $18, $19, $20
+                $16, $17, $18, $19, $20, $21
             )
         "#;
 
@@ -53,15 +88,64 @@ impl OrderStore {
             .bind(order.expires_at)
             .bind(&order.client_order_id)
             .bind(serde_json::to_value(&order.metadata).unwrap())
-            .execute(&*self.pool)
+            .bind(order.visible_quantity)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        OutboxStore::enqueue(
+            &mut tx,
+            KafkaTopics::TRADING_ORDERS,
+            &order.id.to_string(),
+            "order_created",
+            order,
+        )
+        .await?;
+        tx.commit()
             .await
             .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
 
         Ok(())
     }
 
-    /// 更新订单
+    /// 更新订单，同一事务写入 order_updated 事件
     pub async fn update_order(&self, order: &Order) -> TradingResult<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Self::write_update(&mut tx, order).await?;
+        tx.commit()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 保存成交后的订单，同一事务登记执行回报
+    ///
+    /// 回报已登记过时不修改订单并返回 false，订单更新失败时登记一起回滚。
+    pub async fn update_order_fill(&self, order: &Order, report: &ExecutionReport) -> TradingResult<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        if !ExecutionStore::claim(&mut tx, report).await? {
+            return Ok(false);
+        }
+        Self::write_update(&mut tx, order).await?;
+        tx.commit()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(true)
+    }
+
+    async fn write_update(tx: &mut Transaction<'_, Postgres>, order: &Order) -> TradingResult<()> {
         let query = r#"
             UPDATE orders SET
                 status = $2, filled_quantity = $3, remaining_quantity = $4,
@@ -78,7 +162,7 @@ impl OrderStore {
             .bind(order.fee)
             .bind(order.updated_at)
             .bind(serde_json::to_value(&order.metadata).unwrap())
-            .execute(&*self.pool)
+            .execute(&mut **tx)
             .await
             .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
 
@@ -86,7 +170,14 @@ impl OrderStore {
             return Err(TradingError::OrderNotFound(order.id));
         }
 
-        Ok(())
+        OutboxStore::enqueue(
+            tx,
+            KafkaTopics::TRADING_ORDERS,
+            &order.id.to_string(),
+            "order_updated",
+            order,
+        )
+        .await
     }
 
     /// 查询订单
@@ -128,7 +219,18 @@ impl OrderStore {
         }
     }
 
-    /// 查询订单列表
+    /// 按ID批量查询订单（不检查用户）
+    pub async fn get_orders_by_ids(&self, order_ids: &[Uuid]) -> TradingResult<Vec<Order>> {
+        let rows = sqlx::query("SELECT * FROM orders WHERE id = ANY($1)")
+            .bind(order_ids)
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(|row| self.row_to_order(row)).collect()
+    }
+
+    /// 查询订单列表，读取主库，用于需要看到最新写入的场景
     pub async fn list_orders(
         &self,
         user_id: Uuid,
@@ -136,6 +238,30 @@ impl OrderStore {
         symbol: Option<String>,
         limit: u32,
         offset: u32,
+    ) -> TradingResult<Vec<Order>> {
+        self.query_orders(&self.pool, user_id, status, symbol, limit, offset).await
+    }
+
+    /// 查询订单历史，使用读连接池，只读副本上可能有短暂延迟
+    pub async fn list_order_history(
+        &self,
+        user_id: Uuid,
+        status: Option<OrderStatus>,
+        symbol: Option<String>,
+        limit: u32,
+        offset: u32,
+    ) -> TradingResult<Vec<Order>> {
+        self.query_orders(&self.read_pool, user_id, status, symbol, limit, offset).await
+    }
+
+    async fn query_orders(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        status: Option<OrderStatus>,
+        symbol: Option<String>,
+        limit: u32,
+        offset: u32,
     ) -> TradingResult<Vec<Order>> {
         let mut query = "SELECT * FROM orders WHERE user_id = $1".to_string();
         let mut param_count = 1;
@@ -169,7 +295,7 @@ impl OrderStore {
         sql_query = sql_query.bind(limit as i64).bind(offset as i64);
 
         let rows = sql_query
-            .fetch_all(&*self.pool)
+            .fetch_all(pool)
             .await
             .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
 
@@ -203,6 +329,34 @@ impl OrderStore {
         Ok(orders)
     }
 
+    /// 替换活跃订单上的策略标签，返回更新的订单数
+    pub async fn retag_active_orders(&self, from_tag: &str, to_tag: &str) -> TradingResult<u64> {
+        let query = r#"
+            UPDATE orders SET
+                metadata = jsonb_set(
+                    metadata,
+                    '{tags}',
+                    (
+                        SELECT COALESCE(jsonb_agg(CASE WHEN tag = $1 THEN $2 ELSE tag END), '[]'::jsonb)
+                        FROM jsonb_array_elements_text(metadata->'tags') AS tag
+                    )
+                ),
+                updated_at = $3
+            WHERE status IN ('PENDING', 'PARTIALLY_FILLED')
+            AND metadata->'tags' ? $1
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(from_tag)
+            .bind(to_tag)
+            .bind(Utc::now())
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+
     /// 获取过期订单
     pub async fn get_expired_orders(&self) -> TradingResult<Vec<Order>> {
         let query = r#"
@@ -226,6 +380,57 @@ impl OrderStore {
         Ok(orders)
     }
 
+    /// 获取某时间之后提交的订单的用户、交易对和提交时间，按提交时间排序
+    pub async fn list_submissions_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> TradingResult<Vec<(Uuid, String, DateTime<Utc>)>> {
+        let query = r#"
+            SELECT user_id, symbol, created_at FROM orders
+            WHERE created_at >= $1
+            ORDER BY created_at ASC
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(since)
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get("user_id"), row.get("symbol"), row.get("created_at")))
+            .collect())
+    }
+
+    /// 查询某交易对在时间窗口内存活过的订单，按创建时间排序，用于订单流重放
+    ///
+    /// 包括窗口内创建的订单和窗口开始前创建、窗口开始时仍在订单簿上的订单。
+    pub async fn list_symbol_orders_in_window(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TradingResult<Vec<Order>> {
+        let query = r#"
+            SELECT * FROM orders
+            WHERE symbol = $1
+            AND created_at < $3
+            AND (created_at >= $2 OR updated_at >= $2 OR status IN ('PENDING', 'PARTIALLY_FILLED'))
+            ORDER BY created_at ASC, id ASC
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(symbol)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&*self.read_pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(|row| self.row_to_order(row)).collect()
+    }
+
     /// 删除订单
     pub async fn delete_order(&self, user_id: Uuid, order_id: Uuid) -> TradingResult<()> {
         let query = r#"
@@ -289,6 +494,7 @@ impl OrderStore {
             quantity: row.get("quantity"),
             price: row.get("price"),
             stop_price: row.get("stop_price"),
+            visible_quantity: row.get("visible_quantity"),
             status,
             time_in_force,
             filled_quantity: row.get("filled_quantity"),
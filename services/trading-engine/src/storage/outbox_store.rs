@@ -0,0 +1,210 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared_models::decimal;
+use shared_protocols::kafka::{KafkaMessage, MessageSerializer};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::sync::Arc;
+
+use crate::models::{TradingError, TradingResult};
+
+/// 事件来源标识
+const SOURCE: &str = "trading-engine";
+
+/// 发件箱表
+///
+/// 未投递的事件按 id 顺序发送，部分索引只覆盖未投递的行。
+const SCHEMA: [&str; 2] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS event_outbox (
+        id BIGSERIAL PRIMARY KEY,
+        topic TEXT NOT NULL,
+        message_key TEXT NOT NULL,
+        event_type TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        published_at TIMESTAMPTZ,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_event_outbox_pending ON event_outbox (id) WHERE published_at IS NULL",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+/// 待投递的事件
+#[derive(Debug, Clone)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub topic: String,
+    pub key: String,
+    pub event_type: String,
+    /// 序列化后的 `KafkaMessage`，消费方按其中的 id 去重
+    pub payload: String,
+    pub attempts: i32,
+}
+
+/// 发件箱积压统计
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboxBacklog {
+    pub pending: i64,
+    pub oldest_pending_at: Option<DateTime<Utc>>,
+}
+
+/// 事务发件箱存储
+#[derive(Clone)]
+pub struct OutboxStore {
+    pool: Arc<PgPool>,
+}
+
+impl OutboxStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 在业务事务中写入事件，事务提交后事件才可见
+    pub async fn enqueue<T: Serialize>(
+        tx: &mut Transaction<'_, Postgres>,
+        topic: &str,
+        key: &str,
+        event_type: &str,
+        data: &T,
+    ) -> TradingResult<()> {
+        // 事件按字符串小数保存，不受当前请求的输出格式影响
+        let message = KafkaMessage::new(event_type, SOURCE, data);
+        let payload = decimal::canonical(|| MessageSerializer::serialize_to_string(&message))
+            .map_err(|e| TradingError::SerializationError(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO event_outbox (topic, message_key, event_type, payload) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(topic)
+        .bind(key)
+        .bind(event_type)
+        .bind(payload)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// 开始投递事务
+    ///
+    /// 持有事务级咨询锁，多个副本同时只有一个在投递，保证事件按写入顺序发出。
+    /// 未取得锁时返回 None。
+    pub async fn begin_relay(&self) -> TradingResult<Option<Transaction<'static, Postgres>>> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let locked: bool = sqlx::query("SELECT pg_try_advisory_xact_lock(hashtext('event_outbox'))")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?
+            .get(0);
+        if !locked {
+            return Ok(None);
+        }
+        Ok(Some(tx))
+    }
+
+    /// 读取最早的未投递事件
+    pub async fn fetch_pending(
+        tx: &mut Transaction<'_, Postgres>,
+        limit: u32,
+    ) -> TradingResult<Vec<OutboxEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, topic, message_key, event_type, payload, attempts
+            FROM event_outbox
+            WHERE published_at IS NULL
+            ORDER BY id
+            LIMIT $1
+            "#,
+        )
+        .bind(limit as i64)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| OutboxEvent {
+                id: row.get("id"),
+                topic: row.get("topic"),
+                key: row.get("message_key"),
+                event_type: row.get("event_type"),
+                payload: row.get("payload"),
+                attempts: row.get("attempts"),
+            })
+            .collect())
+    }
+
+    /// 标记事件已投递
+    pub async fn mark_published(
+        tx: &mut Transaction<'_, Postgres>,
+        ids: &[i64],
+        published_at: DateTime<Utc>,
+    ) -> TradingResult<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query(
+            "UPDATE event_outbox SET published_at = $2, attempts = attempts + 1, last_error = NULL WHERE id = ANY($1)",
+        )
+        .bind(ids)
+        .bind(published_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 记录投递失败
+    pub async fn mark_failed(
+        tx: &mut Transaction<'_, Postgres>,
+        id: i64,
+        error: &str,
+    ) -> TradingResult<()> {
+        sqlx::query("UPDATE event_outbox SET attempts = attempts + 1, last_error = $2 WHERE id = $1")
+            .bind(id)
+            .bind(error)
+            .execute(&mut **tx)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 积压情况
+    pub async fn backlog(&self) -> TradingResult<OutboxBacklog> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS pending, MIN(created_at) AS oldest FROM event_outbox WHERE published_at IS NULL",
+        )
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(OutboxBacklog {
+            pending: row.get("pending"),
+            oldest_pending_at: row.get("oldest"),
+        })
+    }
+
+    /// 清理已投递的旧事件，返回删除条数
+    pub async fn purge_published_before(&self, before: DateTime<Utc>) -> TradingResult<u64> {
+        let result = sqlx::query("DELETE FROM event_outbox WHERE published_at IS NOT NULL AND published_at < $1")
+            .bind(before)
+            .execute(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use shared_utils::{leader::FENCE_SCHEMA, Fence};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{PnlSnapshot, TradingError, TradingResult};
+
+const SCHEMA: [&str; 3] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS pnl_snapshots (
+        user_id UUID NOT NULL,
+        taken_at TIMESTAMPTZ NOT NULL,
+        equity NUMERIC NOT NULL,
+        realized_pnl NUMERIC NOT NULL,
+        unrealized_pnl NUMERIC NOT NULL,
+        total_pnl NUMERIC NOT NULL,
+        PRIMARY KEY (user_id, taken_at)
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_pnl_snapshots_taken_at ON pnl_snapshots (taken_at)",
+    FENCE_SCHEMA,
+];
+
+const INSERT_SNAPSHOT: &str = r#"
+    INSERT INTO pnl_snapshots (
+        user_id, taken_at, equity, realized_pnl, unrealized_pnl, total_pnl
+    ) VALUES ($1, $2, $3, $4, $5, $6)
+    ON CONFLICT (user_id, taken_at) DO UPDATE SET
+        equity = EXCLUDED.equity,
+        realized_pnl = EXCLUDED.realized_pnl,
+        unrealized_pnl = EXCLUDED.unrealized_pnl,
+        total_pnl = EXCLUDED.total_pnl
+"#;
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+/// 账户权益/盈亏快照存储
+#[derive(Clone)]
+pub struct PnlStore {
+    pool: Arc<PgPool>,
+    /// 快照区间查询使用的连接池，未单独配置时与主库相同
+    read_pool: Arc<PgPool>,
+}
+
+impl PnlStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self {
+            read_pool: pool.clone(),
+            pool,
+        }
+    }
+
+    /// 使用独立的读连接池
+    pub fn with_read_pool(mut self, read_pool: Arc<PgPool>) -> Self {
+        self.read_pool = read_pool;
+        self
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 在防护令牌保护下批量写入快照，同一时刻重复写入时覆盖
+    ///
+    /// 令牌已被更新的领导者取代时不写入并返回 false。
+    pub async fn insert_snapshots(&self, snapshots: &[PnlSnapshot], fence: &Fence) -> TradingResult<bool> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        if !fence.guard_pg(&mut tx).await.map_err(db_error)? {
+            return Ok(false);
+        }
+        for snapshot in snapshots {
+            sqlx::query(INSERT_SNAPSHOT)
+                .bind(snapshot.user_id)
+                .bind(snapshot.taken_at)
+                .bind(snapshot.equity)
+                .bind(snapshot.realized_pnl)
+                .bind(snapshot.unrealized_pnl)
+                .bind(snapshot.total_pnl)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)?;
+        Ok(true)
+    }
+
+    /// 查询区间内的快照，按时间升序
+    pub async fn list_snapshots(
+        &self,
+        user_id: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TradingResult<Vec<PnlSnapshot>> {
+        let query = r#"
+            SELECT * FROM pnl_snapshots
+            WHERE user_id = $1 AND taken_at >= $2 AND taken_at < $3
+            ORDER BY taken_at
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(user_id)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&*self.read_pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PnlSnapshot {
+                user_id: row.get("user_id"),
+                equity: row.get("equity"),
+                realized_pnl: row.get("realized_pnl"),
+                unrealized_pnl: row.get("unrealized_pnl"),
+                total_pnl: row.get("total_pnl"),
+                taken_at: row.get("taken_at"),
+            })
+            .collect())
+    }
+
+    /// 需要快照的账户：近期有成交或已有快照的用户
+    pub async fn active_accounts(&self, since: DateTime<Utc>) -> TradingResult<Vec<Uuid>> {
+        let query = r#"
+            SELECT user_id FROM trades WHERE executed_at >= $1
+            UNION
+            SELECT user_id FROM pnl_snapshots WHERE taken_at >= $1
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(since)
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|row| row.get("user_id")).collect())
+    }
+
+    /// 清理过期快照，返回删除条数
+    pub async fn purge_before(&self, cutoff: DateTime<Utc>) -> TradingResult<u64> {
+        let result = sqlx::query("DELETE FROM pnl_snapshots WHERE taken_at < $1")
+            .bind(cutoff)
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::Result;
+use serde::Serialize;
+use shared_utils::AppMetrics;
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::config::{DatabaseConfig, QueryClass};
+
+const PRIMARY_POOL: &str = "primary";
+
+/// 单个连接池的使用情况
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolUsage {
+    pub pool: String,
+    pub in_use: u32,
+    pub idle: u32,
+    pub max_connections: u32,
+}
+
+impl PoolUsage {
+    /// 已用连接占上限的比例
+    pub fn saturation(&self) -> f64 {
+        self.in_use as f64 / self.max_connections.max(1) as f64
+    }
+}
+
+/// 主库连接池和读连接池
+///
+/// 写入和事务性读取始终使用主库连接池；配置为读路由的查询类型使用读连接池，
+/// 配置了多个只读副本时轮询分配。
+pub struct DbPools {
+    primary: Arc<PgPool>,
+    primary_max: u32,
+    replicas: Vec<Arc<PgPool>>,
+    replica_max: u32,
+    routes: HashSet<QueryClass>,
+    next_replica: AtomicUsize,
+}
+
+impl DbPools {
+    /// 连接主库，读连接池延迟建立连接，副本不可用时不影响启动
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self> {
+        let primary = pool_options(config, config.max_connections, config.min_connections)
+            .connect(&config.url)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to database: {}", e))?;
+
+        let read_config = &config.read_pool;
+        let urls = if read_config.replica_urls.is_empty() {
+            std::slice::from_ref(&config.url)
+        } else {
+            read_config.replica_urls.as_slice()
+        };
+        let replicas = urls
+            .iter()
+            .map(|url| {
+                pool_options(config, read_config.max_connections, 0)
+                    .connect_lazy(url)
+                    .map(Arc::new)
+                    .map_err(|e| anyhow::anyhow!("Invalid read replica URL: {}", e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::new(
+            Arc::new(primary),
+            config.max_connections,
+            replicas,
+            read_config.max_connections,
+            read_config.routes.iter().copied().collect(),
+        ))
+    }
+
+    fn new(
+        primary: Arc<PgPool>,
+        primary_max: u32,
+        replicas: Vec<Arc<PgPool>>,
+        replica_max: u32,
+        routes: HashSet<QueryClass>,
+    ) -> Self {
+        Self {
+            primary,
+            primary_max,
+            replicas,
+            replica_max,
+            routes,
+            next_replica: AtomicUsize::new(0),
+        }
+    }
+
+    /// 主库连接池
+    pub fn primary(&self) -> Arc<PgPool> {
+        self.primary.clone()
+    }
+
+    /// 按查询类型选择连接池
+    pub fn for_query(&self, class: QueryClass) -> Arc<PgPool> {
+        if self.replicas.is_empty() || !self.routes.contains(&class) {
+            return self.primary.clone();
+        }
+        let index = self.next_replica.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+        self.replicas[index].clone()
+    }
+
+    /// 各连接池当前的连接使用情况
+    pub fn usage(&self) -> Vec<PoolUsage> {
+        std::iter::once((PRIMARY_POOL.to_string(), &self.primary, self.primary_max))
+            .chain(
+                self.replicas
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pool)| (format!("read_{}", i), pool, self.replica_max)),
+            )
+            .map(|(name, pool, max_connections)| {
+                let idle = pool.num_idle() as u32;
+                PoolUsage {
+                    pool: name,
+                    in_use: pool.size().saturating_sub(idle),
+                    idle,
+                    max_connections,
+                }
+            })
+            .collect()
+    }
+
+    /// 定期上报连接池使用情况，连接用尽时记录告警
+    pub fn start_metrics(self: Arc<Self>, metrics: Arc<AppMetrics>, interval: std::time::Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                for usage in self.usage() {
+                    let _ = metrics.set_db_pool_connections(&usage.pool, "in_use", usage.in_use as i64);
+                    let _ = metrics.set_db_pool_connections(&usage.pool, "idle", usage.idle as i64);
+                    let _ = metrics.set_db_pool_connections(&usage.pool, "max", usage.max_connections as i64);
+                    if usage.saturation() >= 1.0 {
+                        tracing::warn!(
+                            "Database pool {} is saturated ({} connections in use)",
+                            usage.pool,
+                            usage.in_use
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn pool_options(config: &DatabaseConfig, max_connections: u32, min_connections: u32) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(max_connections)
+        .min_connections(min_connections.min(max_connections))
+        .acquire_timeout(config.connect_timeout)
+        .idle_timeout(config.idle_timeout)
+        .max_lifetime(config.max_lifetime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lazy_pool() -> Arc<PgPool> {
+        Arc::new(
+            PgPoolOptions::new()
+                .connect_lazy("postgresql://localhost:5432/trading_engine")
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_query_routing() {
+        let primary = lazy_pool();
+        let replicas = vec![lazy_pool(), lazy_pool()];
+        let pools = DbPools::new(
+            primary.clone(),
+            20,
+            replicas.clone(),
+            10,
+            [QueryClass::History].into_iter().collect(),
+        );
+
+        // 事务性和未配置路由的查询使用主库
+        assert!(Arc::ptr_eq(&pools.for_query(QueryClass::Transactional), &primary));
+        assert!(Arc::ptr_eq(&pools.for_query(QueryClass::Reporting), &primary));
+
+        // 读路由在副本间轮询
+        assert!(Arc::ptr_eq(&pools.for_query(QueryClass::History), &replicas[0]));
+        assert!(Arc::ptr_eq(&pools.for_query(QueryClass::History), &replicas[1]));
+        assert!(Arc::ptr_eq(&pools.for_query(QueryClass::History), &replicas[0]));
+
+        let usage = pools.usage();
+        assert_eq!(usage.len(), 3);
+        assert_eq!(usage[0].pool, "primary");
+        assert_eq!(usage[2].pool, "read_1");
+        assert_eq!(usage[0].saturation(), 0.0);
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{PortfolioStop, PortfolioStopEvent, TradingError, TradingResult};
+
+/// 账户级回撤止损设置及审计事件
+///
+/// 每个用户一条止损设置，触发、撤单、平仓和解锁都写入事件表，只追加不修改。
+const SCHEMA: [&str; 3] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS portfolio_stops (
+        user_id UUID PRIMARY KEY,
+        basis TEXT NOT NULL,
+        max_drawdown NUMERIC NOT NULL,
+        reference_equity NUMERIC NOT NULL,
+        reference_date DATE NOT NULL,
+        locked BOOLEAN NOT NULL DEFAULT FALSE,
+        triggered_at TIMESTAMPTZ,
+        updated_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS portfolio_stop_events (
+        id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        kind TEXT NOT NULL,
+        details JSONB NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_portfolio_stop_events_user ON portfolio_stop_events (user_id, created_at DESC)",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn row_to_stop(row: PgRow) -> TradingResult<PortfolioStop> {
+    let basis: String = row.get("basis");
+    Ok(PortfolioStop {
+        user_id: row.get("user_id"),
+        basis: basis.parse()?,
+        max_drawdown: row.get("max_drawdown"),
+        reference_equity: row.get("reference_equity"),
+        reference_date: row.get("reference_date"),
+        locked: row.get("locked"),
+        triggered_at: row.get("triggered_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// 账户级止损存储
+#[derive(Clone)]
+pub struct PortfolioStopStore {
+    pool: Arc<PgPool>,
+}
+
+impl PortfolioStopStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 写入止损设置
+    pub async fn upsert(&self, stop: &PortfolioStop) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO portfolio_stops (
+                user_id, basis, max_drawdown, reference_equity, reference_date,
+                locked, triggered_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (user_id) DO UPDATE SET
+                basis = EXCLUDED.basis,
+                max_drawdown = EXCLUDED.max_drawdown,
+                reference_equity = EXCLUDED.reference_equity,
+                reference_date = EXCLUDED.reference_date,
+                locked = EXCLUDED.locked,
+                triggered_at = EXCLUDED.triggered_at,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(stop.user_id)
+        .bind(stop.basis.to_string())
+        .bind(stop.max_drawdown)
+        .bind(stop.reference_equity)
+        .bind(stop.reference_date)
+        .bind(stop.locked)
+        .bind(stop.triggered_at)
+        .bind(stop.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> TradingResult<Option<PortfolioStop>> {
+        sqlx::query("SELECT * FROM portfolio_stops WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .map(row_to_stop)
+            .transpose()
+    }
+
+    /// 删除止损设置，已锁定的账户不能删除
+    pub async fn delete(&self, user_id: Uuid) -> TradingResult<bool> {
+        let result = sqlx::query("DELETE FROM portfolio_stops WHERE user_id = $1 AND NOT locked")
+            .bind(user_id)
+            .execute(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 尚未触发的止损设置
+    pub async fn list_armed(&self) -> TradingResult<Vec<PortfolioStop>> {
+        sqlx::query("SELECT * FROM portfolio_stops WHERE NOT locked ORDER BY user_id")
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(row_to_stop)
+            .collect()
+    }
+
+    /// 已触发锁定的止损设置，用于重试未完成的撤单和平仓
+    pub async fn list_locked(&self) -> TradingResult<Vec<PortfolioStop>> {
+        sqlx::query("SELECT * FROM portfolio_stops WHERE locked ORDER BY user_id")
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(row_to_stop)
+            .collect()
+    }
+
+    /// 账户交易是否被止损锁定
+    pub async fn is_locked(&self, user_id: Uuid) -> TradingResult<bool> {
+        let locked: Option<bool> = sqlx::query_scalar("SELECT locked FROM portfolio_stops WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(locked.unwrap_or(false))
+    }
+
+    pub async fn record_event(&self, event: &PortfolioStopEvent) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO portfolio_stop_events (id, user_id, kind, details, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(event.id)
+        .bind(event.user_id)
+        .bind(event.kind.to_string())
+        .bind(&event.details)
+        .bind(event.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 用户的审计事件，按时间倒序
+    pub async fn list_events(&self, user_id: Uuid, limit: u32) -> TradingResult<Vec<PortfolioStopEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM portfolio_stop_events
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let kind: String = row.get("kind");
+                Ok(PortfolioStopEvent {
+                    id: row.get("id"),
+                    user_id: row.get("user_id"),
+                    kind: kind.parse()?,
+                    details: row.get("details"),
+                    created_at: row.get("created_at"),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,306 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use shared_utils::{leader::FENCE_SCHEMA, Fence};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgPool, Postgres, Row,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{
+    ReferralAttribution, ReferralCode, ReferralEntryType, ReferralLedgerEntry, TradingError,
+    TradingResult,
+};
+
+/// 推荐码、注册归属和返佣账本
+///
+/// 每个用户只有一个推荐码，每个被推荐人只能归属一次；
+/// 账本按 (source_id, entry_type) 唯一，重复上报的成交不会重复分成。
+const SCHEMA: [&str; 6] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS referral_codes (
+        code TEXT PRIMARY KEY,
+        user_id UUID NOT NULL UNIQUE,
+        created_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS referral_attributions (
+        referee_id UUID PRIMARY KEY,
+        referrer_id UUID NOT NULL,
+        code TEXT NOT NULL,
+        attributed_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_referral_attributions_referrer ON referral_attributions (referrer_id)",
+    r#"
+    CREATE TABLE IF NOT EXISTS referral_ledger (
+        id UUID PRIMARY KEY,
+        referrer_id UUID NOT NULL,
+        referee_id UUID,
+        entry_type TEXT NOT NULL,
+        amount NUMERIC NOT NULL,
+        currency TEXT NOT NULL,
+        source_id UUID,
+        fee_amount NUMERIC,
+        created_at TIMESTAMPTZ NOT NULL,
+        UNIQUE (source_id, entry_type)
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_referral_ledger_referrer_time ON referral_ledger (referrer_id, created_at DESC)",
+    FENCE_SCHEMA,
+];
+
+const INSERT_ENTRY: &str = r#"
+    INSERT INTO referral_ledger (
+        id, referrer_id, referee_id, entry_type, amount, currency,
+        source_id, fee_amount, created_at
+    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+    ON CONFLICT DO NOTHING
+"#;
+
+const PENDING_BALANCES: &str = r#"
+    SELECT referrer_id, currency,
+        SUM(CASE WHEN entry_type = 'accrual' THEN amount ELSE -amount END) AS pending
+    FROM referral_ledger
+    WHERE created_at < $1
+    GROUP BY referrer_id, currency
+    HAVING SUM(CASE WHEN entry_type = 'accrual' THEN amount ELSE -amount END) > 0
+"#;
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn insert_entry_query(entry: &ReferralLedgerEntry) -> Query<'static, Postgres, PgArguments> {
+    sqlx::query(INSERT_ENTRY)
+        .bind(entry.id)
+        .bind(entry.referrer_id)
+        .bind(entry.referee_id)
+        .bind(entry.entry_type.to_string())
+        .bind(entry.amount)
+        .bind(entry.currency.clone())
+        .bind(entry.source_id)
+        .bind(entry.fee_amount)
+        .bind(entry.created_at)
+}
+
+fn row_to_balance(row: PgRow) -> (Uuid, String, Decimal) {
+    (row.get("referrer_id"), row.get("currency"), row.get("pending"))
+}
+
+#[derive(Clone)]
+pub struct ReferralStore {
+    pool: Arc<PgPool>,
+}
+
+impl ReferralStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 写入推荐码，码冲突或用户已有推荐码时返回 false
+    pub async fn insert_code(&self, code: &ReferralCode) -> TradingResult<bool> {
+        let result = sqlx::query(
+            "INSERT INTO referral_codes (code, user_id, created_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        )
+        .bind(&code.code)
+        .bind(code.user_id)
+        .bind(code.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn get_code_by_user(&self, user_id: Uuid) -> TradingResult<Option<ReferralCode>> {
+        let row = sqlx::query("SELECT * FROM referral_codes WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(row.map(|row| ReferralCode {
+            code: row.get("code"),
+            user_id: row.get("user_id"),
+            created_at: row.get("created_at"),
+        }))
+    }
+
+    pub async fn get_code(&self, code: &str) -> TradingResult<Option<ReferralCode>> {
+        let row = sqlx::query("SELECT * FROM referral_codes WHERE code = $1")
+            .bind(code)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(row.map(|row| ReferralCode {
+            code: row.get("code"),
+            user_id: row.get("user_id"),
+            created_at: row.get("created_at"),
+        }))
+    }
+
+    /// 写入注册归属，已归属时返回 false
+    pub async fn insert_attribution(&self, attribution: &ReferralAttribution) -> TradingResult<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO referral_attributions (referee_id, referrer_id, code, attributed_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (referee_id) DO NOTHING
+            "#,
+        )
+        .bind(attribution.referee_id)
+        .bind(attribution.referrer_id)
+        .bind(&attribution.code)
+        .bind(attribution.attributed_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn get_attribution(&self, referee_id: Uuid) -> TradingResult<Option<ReferralAttribution>> {
+        let row = sqlx::query("SELECT * FROM referral_attributions WHERE referee_id = $1")
+            .bind(referee_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(row.map(|row| ReferralAttribution {
+            referee_id: row.get("referee_id"),
+            referrer_id: row.get("referrer_id"),
+            code: row.get("code"),
+            attributed_at: row.get("attributed_at"),
+        }))
+    }
+
+    pub async fn count_referees(&self, referrer_id: Uuid) -> TradingResult<i64> {
+        let row = sqlx::query("SELECT COUNT(*) AS count FROM referral_attributions WHERE referrer_id = $1")
+            .bind(referrer_id)
+            .fetch_one(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(row.get("count"))
+    }
+
+    /// 写入账本条目，同一来源重复写入时返回 false
+    pub async fn insert_entry(&self, entry: &ReferralLedgerEntry) -> TradingResult<bool> {
+        let result = insert_entry_query(entry)
+            .execute(&*self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 分页查询推荐人的账本，按时间倒序
+    pub async fn list_entries(
+        &self,
+        referrer_id: Uuid,
+        limit: u32,
+        offset: u32,
+    ) -> TradingResult<Vec<ReferralLedgerEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM referral_ledger
+            WHERE referrer_id = $1
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(referrer_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(row_to_entry).collect()
+    }
+
+    /// 按币种汇总推荐人的累计与已结算金额
+    pub async fn totals(&self, referrer_id: Uuid) -> TradingResult<Vec<(String, ReferralEntryType, Decimal)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT currency, entry_type, SUM(amount) AS total FROM referral_ledger
+            WHERE referrer_id = $1
+            GROUP BY currency, entry_type
+            "#,
+        )
+        .bind(referrer_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let entry_type: String = row.get("entry_type");
+                Ok((row.get("currency"), entry_type.parse()?, row.get("total")))
+            })
+            .collect()
+    }
+
+    /// 在防护令牌保护的事务内读取所有推荐人按币种的待结算余额，写入 `payouts` 据此生成的结算条目
+    ///
+    /// 先登记令牌再读取余额：旧领导者的事务被拒绝，新领导者等待旧事务结束后
+    /// 才读取余额，不会重复结算。令牌已被取代时不写入并返回 None。
+    pub async fn settle_pending<F>(
+        &self,
+        before: DateTime<Utc>,
+        fence: &Fence,
+        payouts: F,
+    ) -> TradingResult<Option<usize>>
+    where
+        F: FnOnce(Vec<(Uuid, String, Decimal)>) -> Vec<ReferralLedgerEntry>,
+    {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        if !fence.guard_pg(&mut tx).await.map_err(db_error)? {
+            return Ok(None);
+        }
+        let balances = sqlx::query(PENDING_BALANCES)
+            .bind(before)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(row_to_balance)
+            .collect();
+
+        let mut settled = 0;
+        for entry in payouts(balances) {
+            let result = insert_entry_query(&entry).execute(&mut *tx).await.map_err(db_error)?;
+            settled += result.rows_affected() as usize;
+        }
+        tx.commit().await.map_err(db_error)?;
+        Ok(Some(settled))
+    }
+}
+
+fn row_to_entry(row: PgRow) -> TradingResult<ReferralLedgerEntry> {
+    let entry_type: String = row.get("entry_type");
+    Ok(ReferralLedgerEntry {
+        id: row.get("id"),
+        referrer_id: row.get("referrer_id"),
+        referee_id: row.get("referee_id"),
+        entry_type: entry_type.parse()?,
+        amount: row.get("amount"),
+        currency: row.get("currency"),
+        source_id: row.get("source_id"),
+        fee_amount: row.get("fee_amount"),
+        created_at: row.get("created_at"),
+    })
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::engines::risk_engine::UserRiskConfig;
+use crate::models::{Symbol, TradingError, TradingResult};
+
+const SCHEMA: [&str; 1] = [r#"
+    CREATE TABLE IF NOT EXISTS user_risk_configs (
+        user_id UUID PRIMARY KEY,
+        max_position_value NUMERIC NOT NULL,
+        max_daily_loss NUMERIC NOT NULL,
+        max_leverage NUMERIC NOT NULL,
+        allowed_symbols TEXT[],
+        blocked_symbols TEXT[] NOT NULL DEFAULT '{}',
+        max_order_value NUMERIC NOT NULL,
+        max_orders_per_minute INTEGER NOT NULL,
+        margin_call_threshold NUMERIC NOT NULL,
+        liquidation_threshold NUMERIC NOT NULL,
+        is_active BOOLEAN NOT NULL DEFAULT TRUE,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+    "#];
+
+/// 交易对以 BASE/QUOTE 保存，避免无分隔格式解析出错
+fn symbols_to_text(symbols: &[Symbol]) -> Vec<String> {
+    symbols
+        .iter()
+        .map(|symbol| symbol.to_string_with_separator("/"))
+        .collect()
+}
+
+fn symbols_from_text(symbols: Vec<String>) -> Vec<Symbol> {
+    symbols
+        .iter()
+        .filter_map(|symbol| Symbol::from_string(symbol))
+        .collect()
+}
+
+/// 用户风控配置存储
+#[derive(Clone)]
+pub struct RiskConfigStore {
+    pool: Arc<PgPool>,
+}
+
+impl RiskConfigStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 写入用户风控配置
+    pub async fn upsert(&self, config: &UserRiskConfig) -> TradingResult<()> {
+        let query = r#"
+            INSERT INTO user_risk_configs (
+                user_id, max_position_value, max_daily_loss, max_leverage,
+                allowed_symbols, blocked_symbols, max_order_value, max_orders_per_minute,
+                margin_call_threshold, liquidation_threshold, is_active, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())
+            ON CONFLICT (user_id) DO UPDATE SET
+                max_position_value = EXCLUDED.max_position_value,
+                max_daily_loss = EXCLUDED.max_daily_loss,
+                max_leverage = EXCLUDED.max_leverage,
+                allowed_symbols = EXCLUDED.allowed_symbols,
+                blocked_symbols = EXCLUDED.blocked_symbols,
+                max_order_value = EXCLUDED.max_order_value,
+                max_orders_per_minute = EXCLUDED.max_orders_per_minute,
+                margin_call_threshold = EXCLUDED.margin_call_threshold,
+                liquidation_threshold = EXCLUDED.liquidation_threshold,
+                is_active = EXCLUDED.is_active,
+                updated_at = NOW()
+        "#;
+
+        sqlx::query(query)
+            .bind(config.user_id)
+            .bind(config.max_position_value)
+            .bind(config.max_daily_loss)
+            .bind(config.max_leverage)
+            .bind(config.allowed_symbols.as_deref().map(symbols_to_text))
+            .bind(symbols_to_text(&config.blocked_symbols))
+            .bind(config.max_order_value)
+            .bind(config.max_orders_per_minute as i32)
+            .bind(config.margin_call_threshold)
+            .bind(config.liquidation_threshold)
+            .bind(config.is_active)
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 查询用户风控配置
+    pub async fn get(&self, user_id: Uuid) -> TradingResult<Option<UserRiskConfig>> {
+        let row = sqlx::query("SELECT * FROM user_risk_configs WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(row.map(|row| Self::row_to_config(&row)))
+    }
+
+    fn row_to_config(row: &PgRow) -> UserRiskConfig {
+        UserRiskConfig {
+            user_id: row.get("user_id"),
+            max_position_value: row.get("max_position_value"),
+            max_daily_loss: row.get("max_daily_loss"),
+            max_leverage: row.get("max_leverage"),
+            allowed_symbols: row
+                .get::<Option<Vec<String>>, _>("allowed_symbols")
+                .map(symbols_from_text),
+            blocked_symbols: symbols_from_text(row.get("blocked_symbols")),
+            max_order_value: row.get("max_order_value"),
+            max_orders_per_minute: row.get::<i32, _>("max_orders_per_minute").max(0) as u32,
+            margin_call_threshold: row.get("margin_call_threshold"),
+            liquidation_threshold: row.get("liquidation_threshold"),
+            is_active: row.get("is_active"),
+        }
+    }
+}
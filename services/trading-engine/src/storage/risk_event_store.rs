@@ -0,0 +1,252 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{RiskEventRecord, RiskEventStatus, RiskSeverity, Timestamp, TradingError, TradingResult};
+
+/// 风险事件表和归档表
+///
+/// 活动表按严重程度优先级和时间排队；已解决的事件超过保留期后整行移入结构相同的归档表。
+const SCHEMA: [&str; 5] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS risk_events (
+        id UUID PRIMARY KEY,
+        event_type TEXT NOT NULL,
+        user_id UUID,
+        symbol TEXT,
+        severity TEXT NOT NULL,
+        priority SMALLINT NOT NULL,
+        message TEXT NOT NULL,
+        data JSONB NOT NULL,
+        status TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL,
+        acknowledged_at TIMESTAMPTZ,
+        acknowledged_by UUID,
+        resolved_at TIMESTAMPTZ,
+        resolved_by UUID,
+        resolution_note TEXT,
+        escalation_level INTEGER NOT NULL DEFAULT 0,
+        escalated_at TIMESTAMPTZ,
+        updated_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_risk_events_queue ON risk_events (status, priority DESC, created_at)",
+    "CREATE INDEX IF NOT EXISTS idx_risk_events_resolved ON risk_events (resolved_at) WHERE status = 'resolved'",
+    "CREATE TABLE IF NOT EXISTS risk_events_archive (LIKE risk_events INCLUDING DEFAULTS)",
+    "CREATE INDEX IF NOT EXISTS idx_risk_events_archive_resolved ON risk_events_archive (resolved_at)",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn row_to_event(row: PgRow) -> TradingResult<RiskEventRecord> {
+    let severity: String = row.get("severity");
+    let status: String = row.get("status");
+    let escalation_level: i32 = row.get("escalation_level");
+    Ok(RiskEventRecord {
+        id: row.get("id"),
+        event_type: row.get("event_type"),
+        user_id: row.get("user_id"),
+        symbol: row.get("symbol"),
+        severity: severity.parse()?,
+        message: row.get("message"),
+        data: row.get("data"),
+        status: status.parse()?,
+        created_at: row.get("created_at"),
+        acknowledged_at: row.get("acknowledged_at"),
+        acknowledged_by: row.get("acknowledged_by"),
+        resolved_at: row.get("resolved_at"),
+        resolved_by: row.get("resolved_by"),
+        resolution_note: row.get("resolution_note"),
+        escalation_level: escalation_level.max(0) as u32,
+        escalated_at: row.get("escalated_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// 风险事件存储
+#[derive(Clone)]
+pub struct RiskEventStore {
+    pool: Arc<PgPool>,
+}
+
+impl RiskEventStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn insert(&self, event: &RiskEventRecord) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO risk_events (
+                id, event_type, user_id, symbol, severity, priority, message, data,
+                status, created_at, escalation_level, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+        )
+        .bind(event.id)
+        .bind(&event.event_type)
+        .bind(event.user_id)
+        .bind(&event.symbol)
+        .bind(event.severity.to_string())
+        .bind(event.severity.priority())
+        .bind(&event.message)
+        .bind(&event.data)
+        .bind(event.status.to_string())
+        .bind(event.created_at)
+        .bind(event.escalation_level as i32)
+        .bind(event.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    pub async fn get(&self, id: Uuid) -> TradingResult<Option<RiskEventRecord>> {
+        sqlx::query("SELECT * FROM risk_events WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .map(row_to_event)
+            .transpose()
+    }
+
+    /// 按队列顺序列出事件：严重程度高的在前，同级按触发时间先后
+    pub async fn list(
+        &self,
+        status: Option<RiskEventStatus>,
+        min_severity: Option<RiskSeverity>,
+        limit: u32,
+    ) -> TradingResult<Vec<RiskEventRecord>> {
+        sqlx::query(
+            r#"
+            SELECT * FROM risk_events
+            WHERE ($1::TEXT IS NULL OR status = $1) AND priority >= $2
+            ORDER BY priority DESC, created_at
+            LIMIT $3
+            "#,
+        )
+        .bind(status.map(|s| s.to_string()))
+        .bind(min_severity.map(|s| s.priority()).unwrap_or(0))
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(row_to_event)
+        .collect()
+    }
+
+    /// 各严重程度未解决事件的数量：(严重程度, 状态, 数量)
+    pub async fn queue_depths(&self) -> TradingResult<Vec<(RiskSeverity, RiskEventStatus, i64)>> {
+        sqlx::query(
+            r#"
+            SELECT severity, status, COUNT(*) AS count
+            FROM risk_events
+            WHERE status <> 'resolved'
+            GROUP BY severity, status
+            "#,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|row| -> TradingResult<(RiskSeverity, RiskEventStatus, i64)> {
+            let severity: String = row.get("severity");
+            let status: String = row.get("status");
+            Ok((severity.parse()?, status.parse()?, row.get("count")))
+        })
+        .collect()
+    }
+
+    /// 保存处理状态，只在状态未被并发修改时生效，返回是否更新
+    pub async fn update(&self, event: &RiskEventRecord, expected: RiskEventStatus) -> TradingResult<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE risk_events
+            SET status = $3,
+                acknowledged_at = $4,
+                acknowledged_by = $5,
+                resolved_at = $6,
+                resolved_by = $7,
+                resolution_note = $8,
+                escalation_level = $9,
+                escalated_at = $10,
+                updated_at = $11
+            WHERE id = $1 AND status = $2
+            "#,
+        )
+        .bind(event.id)
+        .bind(expected.to_string())
+        .bind(event.status.to_string())
+        .bind(event.acknowledged_at)
+        .bind(event.acknowledged_by)
+        .bind(event.resolved_at)
+        .bind(event.resolved_by)
+        .bind(&event.resolution_note)
+        .bind(event.escalation_level as i32)
+        .bind(event.escalated_at)
+        .bind(event.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 不低于指定严重程度的未确认事件
+    pub async fn list_unacknowledged(&self, min_severity: RiskSeverity) -> TradingResult<Vec<RiskEventRecord>> {
+        sqlx::query(
+            r#"
+            SELECT * FROM risk_events
+            WHERE status = 'open' AND priority >= $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(min_severity.priority())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(row_to_event)
+        .collect()
+    }
+
+    /// 把解决时间早于 before 的事件移入归档表，返回移动的条数
+    pub async fn archive_resolved(&self, before: Timestamp) -> TradingResult<u64> {
+        let result = sqlx::query(
+            r#"
+            WITH moved AS (
+                DELETE FROM risk_events
+                WHERE status = 'resolved' AND resolved_at < $1
+                RETURNING *
+            )
+            INSERT INTO risk_events_archive SELECT * FROM moved
+            "#,
+        )
+        .bind(before)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+
+    /// 删除超过归档保留期的事件
+    pub async fn purge_archive(&self, before: Timestamp) -> TradingResult<u64> {
+        let result = sqlx::query("DELETE FROM risk_events_archive WHERE resolved_at < $1")
+            .bind(before)
+            .execute(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
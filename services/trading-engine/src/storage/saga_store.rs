@@ -0,0 +1,141 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use shared_models::decimal;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+
+use crate::models::{OrderSaga, TradingError, TradingResult};
+
+const SCHEMA: [&str; 2] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS order_sagas (
+        id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        status TEXT NOT NULL,
+        completed_steps TEXT[] NOT NULL DEFAULT '{}',
+        pending_step TEXT,
+        order_snapshot TEXT NOT NULL,
+        error TEXT,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_order_sagas_open ON order_sagas (updated_at) WHERE status IN ('running', 'compensating')",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+/// 下单 saga 状态存储
+#[derive(Clone)]
+pub struct SagaStore {
+    pool: Arc<PgPool>,
+}
+
+impl SagaStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 保存 saga 当前状态
+    pub async fn save(&self, saga: &OrderSaga) -> TradingResult<()> {
+        let snapshot = decimal::canonical(|| serde_json::to_string(&saga.order))
+            .map_err(|e| TradingError::SerializationError(e.to_string()))?;
+        let completed: Vec<String> = saga.completed.iter().map(|step| step.to_string()).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO order_sagas (
+                id, user_id, status, completed_steps, pending_step, order_snapshot,
+                error, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                completed_steps = EXCLUDED.completed_steps,
+                pending_step = EXCLUDED.pending_step,
+                order_snapshot = EXCLUDED.order_snapshot,
+                error = EXCLUDED.error,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(saga.id)
+        .bind(saga.order.user_id)
+        .bind(saga.status.to_string())
+        .bind(completed)
+        .bind(saga.pending.map(|step| step.to_string()))
+        .bind(snapshot)
+        .bind(&saga.error)
+        .bind(saga.created_at)
+        .bind(saga.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// 在 `before` 之前停止更新的未完成 saga
+    pub async fn list_stale(&self, before: DateTime<Utc>, limit: i64) -> TradingResult<Vec<OrderSaga>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM order_sagas
+            WHERE status IN ('running', 'compensating') AND updated_at < $1
+            ORDER BY updated_at
+            LIMIT $2
+            "#,
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(Self::row_to_saga).collect()
+    }
+
+    /// 清理已结束的旧 saga，补偿失败的保留待人工处理
+    pub async fn purge_finished_before(&self, before: DateTime<Utc>) -> TradingResult<u64> {
+        let result = sqlx::query(
+            "DELETE FROM order_sagas WHERE status IN ('completed', 'compensated') AND updated_at < $1",
+        )
+        .bind(before)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+
+    fn row_to_saga(row: &PgRow) -> TradingResult<OrderSaga> {
+        let snapshot: String = row.get("order_snapshot");
+        let order = serde_json::from_str(&snapshot)
+            .map_err(|e| TradingError::SerializationError(e.to_string()))?;
+        let completed = row
+            .get::<Vec<String>, _>("completed_steps")
+            .iter()
+            .map(|step| step.parse())
+            .collect::<TradingResult<Vec<_>>>()?;
+        let pending = row
+            .get::<Option<String>, _>("pending_step")
+            .map(|step| step.parse())
+            .transpose()?;
+
+        Ok(OrderSaga {
+            id: row.get("id"),
+            order,
+            status: row.get::<String, _>("status").parse()?,
+            completed,
+            pending,
+            error: row.get("error"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        })
+    }
+}
@@ -0,0 +1,324 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{
+    apply_balance_deltas, Amount, SandboxAccount, SandboxFill, Symbol, TradingError,
+    TradingResult,
+};
+
+/// 模拟盘账户、余额和成交
+///
+/// 与正式的订单、成交和盈亏快照表完全分开，重置只清理本用户的模拟数据。
+const SCHEMA: [&str; 4] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS sandbox_accounts (
+        user_id UUID PRIMARY KEY,
+        created_at TIMESTAMPTZ NOT NULL,
+        reset_at TIMESTAMPTZ NOT NULL,
+        reset_count INTEGER NOT NULL DEFAULT 0,
+        last_active_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS sandbox_balances (
+        user_id UUID NOT NULL REFERENCES sandbox_accounts (user_id) ON DELETE CASCADE,
+        currency TEXT NOT NULL,
+        amount NUMERIC NOT NULL,
+        PRIMARY KEY (user_id, currency)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS sandbox_fills (
+        id UUID PRIMARY KEY,
+        user_id UUID NOT NULL REFERENCES sandbox_accounts (user_id) ON DELETE CASCADE,
+        symbol TEXT NOT NULL,
+        side TEXT NOT NULL,
+        quantity NUMERIC NOT NULL,
+        price NUMERIC NOT NULL,
+        fee NUMERIC NOT NULL,
+        fee_currency TEXT NOT NULL,
+        venue TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_sandbox_fills_user_time ON sandbox_fills (user_id, created_at DESC)",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+#[derive(Clone)]
+pub struct SandboxStore {
+    pool: Arc<PgPool>,
+}
+
+impl SandboxStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_account(&self, user_id: Uuid) -> TradingResult<Option<SandboxAccount>> {
+        let row = sqlx::query("SELECT * FROM sandbox_accounts WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let balances = sqlx::query("SELECT currency, amount FROM sandbox_balances WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(|row| (row.get("currency"), row.get("amount")))
+            .collect();
+
+        Ok(Some(SandboxAccount {
+            user_id: row.get("user_id"),
+            balances,
+            created_at: row.get("created_at"),
+            reset_at: row.get("reset_at"),
+            reset_count: row.get("reset_count"),
+            last_active_at: row.get("last_active_at"),
+        }))
+    }
+
+    /// 开通模拟账户，已存在时返回 false
+    pub async fn create_account(
+        &self,
+        user_id: Uuid,
+        balances: &BTreeMap<String, Amount>,
+        now: DateTime<Utc>,
+    ) -> TradingResult<bool> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sandbox_accounts (user_id, created_at, reset_at, reset_count, last_active_at)
+            VALUES ($1, $2, $2, 0, $2)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        Self::write_balances(&mut tx, user_id, balances).await?;
+        tx.commit().await.map_err(db_error)?;
+        Ok(true)
+    }
+
+    /// 清空成交并恢复初始余额
+    pub async fn reset_account(
+        &self,
+        user_id: Uuid,
+        balances: &BTreeMap<String, Amount>,
+        now: DateTime<Utc>,
+    ) -> TradingResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let result = sqlx::query(
+            r#"
+            UPDATE sandbox_accounts
+            SET reset_at = $2, reset_count = reset_count + 1, last_active_at = $2
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        if result.rows_affected() == 0 {
+            return Err(TradingError::InvalidOrder("Sandbox account not provisioned".to_string()));
+        }
+
+        for statement in [
+            "DELETE FROM sandbox_fills WHERE user_id = $1",
+            "DELETE FROM sandbox_balances WHERE user_id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+        Self::write_balances(&mut tx, user_id, balances).await?;
+        tx.commit().await.map_err(db_error)
+    }
+
+    async fn write_balances(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: Uuid,
+        balances: &BTreeMap<String, Amount>,
+    ) -> TradingResult<()> {
+        for (currency, amount) in balances {
+            sqlx::query(
+                r#"
+                INSERT INTO sandbox_balances (user_id, currency, amount) VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, currency) DO UPDATE SET amount = EXCLUDED.amount
+                "#,
+            )
+            .bind(user_id)
+            .bind(currency)
+            .bind(amount)
+            .execute(&mut **tx)
+            .await
+            .map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    /// 在同一事务中校验并更新余额、写入成交，余额不足时不做任何修改
+    pub async fn record_fill(
+        &self,
+        fill: &SandboxFill,
+        deltas: &[(String, Amount)],
+    ) -> TradingResult<BTreeMap<String, Amount>> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        // 锁定账户行，同一用户的模拟成交串行执行
+        sqlx::query("SELECT user_id FROM sandbox_accounts WHERE user_id = $1 FOR UPDATE")
+            .bind(fill.user_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| TradingError::InvalidOrder("Sandbox account not provisioned".to_string()))?;
+
+        let mut balances: BTreeMap<String, Amount> =
+            sqlx::query("SELECT currency, amount FROM sandbox_balances WHERE user_id = $1")
+                .bind(fill.user_id)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(db_error)?
+                .into_iter()
+                .map(|row| (row.get("currency"), row.get("amount")))
+                .collect();
+        apply_balance_deltas(&mut balances, deltas)?;
+
+        let changed: BTreeMap<String, Amount> = deltas
+            .iter()
+            .map(|(currency, _)| (currency.clone(), balances[currency]))
+            .collect();
+        Self::write_balances(&mut tx, fill.user_id, &changed).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO sandbox_fills (
+                id, user_id, symbol, side, quantity, price, fee, fee_currency, venue, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(fill.id)
+        .bind(fill.user_id)
+        .bind(&fill.symbol)
+        .bind(fill.side.to_string())
+        .bind(fill.quantity)
+        .bind(fill.price)
+        .bind(fill.fee)
+        .bind(&fill.fee_currency)
+        .bind(&fill.venue)
+        .bind(fill.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query("UPDATE sandbox_accounts SET last_active_at = $2 WHERE user_id = $1")
+            .bind(fill.user_id)
+            .bind(fill.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(balances)
+    }
+
+    pub async fn touch(&self, user_id: Uuid, now: DateTime<Utc>) -> TradingResult<()> {
+        sqlx::query("UPDATE sandbox_accounts SET last_active_at = $2 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(now)
+            .execute(&*self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    pub async fn list_fills(&self, user_id: Uuid, limit: u32, offset: u32) -> TradingResult<Vec<SandboxFill>> {
+        sqlx::query(
+            "SELECT * FROM sandbox_fills WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(user_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .iter()
+        .map(row_to_fill)
+        .collect()
+    }
+
+    /// 成交笔数，以及按计价币种的成交额和按币种的手续费
+    pub async fn fill_totals(
+        &self,
+        user_id: Uuid,
+    ) -> TradingResult<(i64, BTreeMap<String, Amount>, BTreeMap<String, Amount>)> {
+        let fills = sqlx::query("SELECT * FROM sandbox_fills WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .iter()
+            .map(row_to_fill)
+            .collect::<TradingResult<Vec<_>>>()?;
+
+        let mut volume = BTreeMap::new();
+        let mut fees = BTreeMap::new();
+        for fill in &fills {
+            let quote = Symbol::from_string(&fill.symbol)
+                .map(|symbol| symbol.quote)
+                .unwrap_or_else(|| fill.symbol.clone());
+            *volume.entry(quote).or_insert(Decimal::ZERO) += fill.quantity * fill.price;
+            *fees.entry(fill.fee_currency.clone()).or_insert(Decimal::ZERO) += fill.fee;
+        }
+        Ok((fills.len() as i64, volume, fees))
+    }
+}
+
+fn row_to_fill(row: &PgRow) -> TradingResult<SandboxFill> {
+    let side: String = row.get("side");
+    Ok(SandboxFill {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        symbol: row.get("symbol"),
+        side: side
+            .parse()
+            .map_err(|e| TradingError::InvalidOrder(format!("Invalid side: {}", e)))?,
+        quantity: row.get("quantity"),
+        price: row.get("price"),
+        fee: row.get("fee"),
+        fee_currency: row.get("fee_currency"),
+        venue: row.get("venue"),
+        created_at: row.get("created_at"),
+    })
+}
@@ -0,0 +1,220 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{ScheduledOrder, ScheduledOrderStatus, Timestamp, TradingError, TradingResult};
+
+/// 定时订单
+///
+/// 下单参数以 JSONB 保存，状态只通过比较并设置推进，重启或切换领导者后从表中恢复。
+const SCHEMA: [&str; 4] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS scheduled_orders (
+        id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        request JSONB NOT NULL,
+        activate_at TIMESTAMPTZ NOT NULL,
+        cancel_at TIMESTAMPTZ,
+        status TEXT NOT NULL,
+        order_id UUID,
+        error TEXT,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_scheduled_orders_user ON scheduled_orders (user_id, created_at DESC)",
+    "CREATE INDEX IF NOT EXISTS idx_scheduled_orders_activate ON scheduled_orders (activate_at) WHERE status = 'scheduled'",
+    "CREATE INDEX IF NOT EXISTS idx_scheduled_orders_cancel ON scheduled_orders (cancel_at) WHERE status = 'active'",
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn row_to_scheduled(row: PgRow) -> TradingResult<ScheduledOrder> {
+    let request: serde_json::Value = row.get("request");
+    let status: String = row.get("status");
+    Ok(ScheduledOrder {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        request: serde_json::from_value(request).map_err(|e| TradingError::SerializationError(e.to_string()))?,
+        activate_at: row.get("activate_at"),
+        cancel_at: row.get("cancel_at"),
+        status: status.parse()?,
+        order_id: row.get("order_id"),
+        error: row.get("error"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// 定时订单存储
+#[derive(Clone)]
+pub struct ScheduledOrderStore {
+    pool: Arc<PgPool>,
+}
+
+impl ScheduledOrderStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn insert(&self, scheduled: &ScheduledOrder) -> TradingResult<()> {
+        let request =
+            serde_json::to_value(&scheduled.request).map_err(|e| TradingError::SerializationError(e.to_string()))?;
+        sqlx::query(
+            r#"
+            INSERT INTO scheduled_orders (
+                id, user_id, request, activate_at, cancel_at, status,
+                order_id, error, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(scheduled.id)
+        .bind(scheduled.user_id)
+        .bind(request)
+        .bind(scheduled.activate_at)
+        .bind(scheduled.cancel_at)
+        .bind(scheduled.status.to_string())
+        .bind(scheduled.order_id)
+        .bind(&scheduled.error)
+        .bind(scheduled.created_at)
+        .bind(scheduled.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    pub async fn get(&self, user_id: Uuid, id: Uuid) -> TradingResult<Option<ScheduledOrder>> {
+        sqlx::query("SELECT * FROM scheduled_orders WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .map(row_to_scheduled)
+            .transpose()
+    }
+
+    /// 用户的定时订单，按创建时间倒序
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        status: Option<ScheduledOrderStatus>,
+        limit: u32,
+    ) -> TradingResult<Vec<ScheduledOrder>> {
+        sqlx::query(
+            r#"
+            SELECT * FROM scheduled_orders
+            WHERE user_id = $1 AND ($2::TEXT IS NULL OR status = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(status.map(|s| s.to_string()))
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(row_to_scheduled)
+        .collect()
+    }
+
+    /// 到达激活时间的定时订单
+    pub async fn due_activations(&self, now: Timestamp, limit: u32) -> TradingResult<Vec<ScheduledOrder>> {
+        sqlx::query(
+            r#"
+            SELECT * FROM scheduled_orders
+            WHERE status = 'scheduled' AND activate_at <= $1
+            ORDER BY activate_at
+            LIMIT $2
+            "#,
+        )
+        .bind(now)
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(row_to_scheduled)
+        .collect()
+    }
+
+    /// 已激活且到达撤单时间的定时订单
+    pub async fn due_cancellations(&self, now: Timestamp, limit: u32) -> TradingResult<Vec<ScheduledOrder>> {
+        sqlx::query(
+            r#"
+            SELECT * FROM scheduled_orders
+            WHERE status = 'active' AND cancel_at <= $1
+            ORDER BY cancel_at
+            LIMIT $2
+            "#,
+        )
+        .bind(now)
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(row_to_scheduled)
+        .collect()
+    }
+
+    /// 状态从 `from` 推进到 `to`，返回是否由本次调用完成推进
+    pub async fn transition(
+        &self,
+        id: Uuid,
+        from: ScheduledOrderStatus,
+        to: ScheduledOrderStatus,
+        error: Option<&str>,
+    ) -> TradingResult<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE scheduled_orders
+            SET status = $3, error = COALESCE($4, error), updated_at = $5
+            WHERE id = $1 AND status = $2
+            "#,
+        )
+        .bind(id)
+        .bind(from.to_string())
+        .bind(to.to_string())
+        .bind(error)
+        .bind(Utc::now())
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 记录激活后的订单ID，并在未设置撤单时间时直接完成
+    pub async fn record_activation(&self, id: Uuid, order_id: Uuid) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE scheduled_orders
+            SET order_id = $2,
+                status = CASE WHEN cancel_at IS NULL THEN 'completed' ELSE status END,
+                updated_at = $3
+            WHERE id = $1 AND status = 'active'
+            "#,
+        )
+        .bind(id)
+        .bind(order_id)
+        .bind(Utc::now())
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
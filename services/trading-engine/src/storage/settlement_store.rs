@@ -0,0 +1,674 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{
+    AccountSnapshot, Amount, FundingRate, FundingSummary, SettledBalance, SettledPosition,
+    SettlementEntry, SettlementEntryKind, SettlementRun, StatementFormat, StatementSummary,
+    TradingError, TradingResult,
+};
+
+/// 日终结算运行、账户快照、计提和对账单
+///
+/// 所有表以营业日和用户为主键，重复运行同一营业日时覆盖计提和对账单，快照只写一次。
+/// 资金费率按交易对和营业日记录。资金费计入余额时在 settlement_postings 记录入账金额，
+/// 重复运行时先冲回上次入账，保证每个营业日只入账一次。
+const SCHEMA: [&str; 10] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS settlement_runs (
+        business_date DATE PRIMARY KEY,
+        status TEXT NOT NULL,
+        period_start TIMESTAMPTZ NOT NULL,
+        period_end TIMESTAMPTZ NOT NULL,
+        accounts INTEGER NOT NULL DEFAULT 0,
+        attempts INTEGER NOT NULL DEFAULT 1,
+        started_at TIMESTAMPTZ NOT NULL,
+        completed_at TIMESTAMPTZ
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS settlement_accounts (
+        business_date DATE NOT NULL,
+        user_id UUID NOT NULL,
+        taken_at TIMESTAMPTZ NOT NULL,
+        PRIMARY KEY (business_date, user_id)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS settlement_balances (
+        business_date DATE NOT NULL,
+        user_id UUID NOT NULL,
+        currency TEXT NOT NULL,
+        total NUMERIC NOT NULL,
+        available NUMERIC NOT NULL,
+        frozen NUMERIC NOT NULL,
+        PRIMARY KEY (business_date, user_id, currency)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS settlement_positions (
+        business_date DATE NOT NULL,
+        user_id UUID NOT NULL,
+        symbol TEXT NOT NULL,
+        quote_currency TEXT NOT NULL,
+        side TEXT NOT NULL,
+        size NUMERIC NOT NULL,
+        entry_price NUMERIC NOT NULL,
+        mark_price NUMERIC NOT NULL,
+        unrealized_pnl NUMERIC NOT NULL,
+        PRIMARY KEY (business_date, user_id, symbol, side)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS settlement_entries (
+        business_date DATE NOT NULL,
+        user_id UUID NOT NULL,
+        kind TEXT NOT NULL,
+        reference TEXT NOT NULL,
+        currency TEXT NOT NULL,
+        amount NUMERIC NOT NULL,
+        PRIMARY KEY (business_date, user_id, kind, reference, currency)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS settlement_statements (
+        business_date DATE NOT NULL,
+        user_id UUID NOT NULL,
+        format TEXT NOT NULL,
+        content BYTEA NOT NULL,
+        generated_at TIMESTAMPTZ NOT NULL,
+        PRIMARY KEY (business_date, user_id, format)
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_settlement_statements_user ON settlement_statements (user_id, business_date DESC)",
+    r#"
+    CREATE TABLE IF NOT EXISTS funding_rates (
+        symbol TEXT NOT NULL,
+        business_date DATE NOT NULL,
+        funding_time TIMESTAMPTZ NOT NULL,
+        rate NUMERIC NOT NULL,
+        PRIMARY KEY (symbol, business_date)
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_funding_rates_time ON funding_rates (symbol, funding_time DESC)",
+    r#"
+    CREATE TABLE IF NOT EXISTS settlement_postings (
+        business_date DATE NOT NULL,
+        user_id UUID NOT NULL,
+        reference TEXT NOT NULL,
+        currency TEXT NOT NULL,
+        amount NUMERIC NOT NULL,
+        posted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        PRIMARY KEY (business_date, user_id, reference, currency)
+    )
+    "#,
+];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn row_to_run(row: PgRow) -> TradingResult<SettlementRun> {
+    let status: String = row.get("status");
+    Ok(SettlementRun {
+        business_date: row.get("business_date"),
+        status: status.parse()?,
+        period_start: row.get("period_start"),
+        period_end: row.get("period_end"),
+        accounts: row.get("accounts"),
+        attempts: row.get("attempts"),
+        started_at: row.get("started_at"),
+        completed_at: row.get("completed_at"),
+    })
+}
+
+#[derive(Clone)]
+pub struct SettlementStore {
+    pool: Arc<PgPool>,
+}
+
+impl SettlementStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_run(&self, business_date: NaiveDate) -> TradingResult<Option<SettlementRun>> {
+        sqlx::query("SELECT * FROM settlement_runs WHERE business_date = $1")
+            .bind(business_date)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .map(row_to_run)
+            .transpose()
+    }
+
+    /// 开始一次运行，已有记录时重置状态并递增运行次数
+    pub async fn begin_run(
+        &self,
+        business_date: NaiveDate,
+        period: (DateTime<Utc>, DateTime<Utc>),
+        now: DateTime<Utc>,
+    ) -> TradingResult<SettlementRun> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO settlement_runs (
+                business_date, status, period_start, period_end, accounts, attempts, started_at
+            ) VALUES ($1, 'running', $2, $3, 0, 1, $4)
+            ON CONFLICT (business_date) DO UPDATE SET
+                status = 'running',
+                attempts = settlement_runs.attempts + 1,
+                started_at = EXCLUDED.started_at,
+                completed_at = NULL
+            RETURNING *
+            "#,
+        )
+        .bind(business_date)
+        .bind(period.0)
+        .bind(period.1)
+        .bind(now)
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        row_to_run(row)
+    }
+
+    pub async fn complete_run(
+        &self,
+        business_date: NaiveDate,
+        accounts: usize,
+        now: DateTime<Utc>,
+    ) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE settlement_runs SET status = 'completed', accounts = $2, completed_at = $3
+            WHERE business_date = $1
+            "#,
+        )
+        .bind(business_date)
+        .bind(accounts as i32)
+        .bind(now)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 需要结算的账户：营业日内有成交、已有当日快照或前一营业日已结算的用户
+    pub async fn accounts_to_settle(
+        &self,
+        business_date: NaiveDate,
+        period: (DateTime<Utc>, DateTime<Utc>),
+    ) -> TradingResult<Vec<Uuid>> {
+        let query = r#"
+            SELECT user_id FROM trades WHERE executed_at >= $2 AND executed_at < $3
+            UNION
+            SELECT user_id FROM settlement_accounts
+            WHERE business_date = $1 OR business_date = $1 - 1
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(business_date)
+            .bind(period.0)
+            .bind(period.1)
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(rows.into_iter().map(|row| row.get("user_id")).collect())
+    }
+
+    /// 写入账户快照，当日已有快照时返回 false 且不覆盖
+    pub async fn insert_snapshot(&self, snapshot: &AccountSnapshot) -> TradingResult<bool> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO settlement_accounts (business_date, user_id, taken_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (business_date, user_id) DO NOTHING
+            "#,
+        )
+        .bind(snapshot.business_date)
+        .bind(snapshot.user_id)
+        .bind(snapshot.taken_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        for balance in &snapshot.balances {
+            sqlx::query(
+                r#"
+                INSERT INTO settlement_balances (
+                    business_date, user_id, currency, total, available, frozen
+                ) VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(snapshot.business_date)
+            .bind(snapshot.user_id)
+            .bind(&balance.currency)
+            .bind(balance.total)
+            .bind(balance.available)
+            .bind(balance.frozen)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        for position in &snapshot.positions {
+            sqlx::query(
+                r#"
+                INSERT INTO settlement_positions (
+                    business_date, user_id, symbol, quote_currency, side, size,
+                    entry_price, mark_price, unrealized_pnl
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (business_date, user_id, symbol, side) DO UPDATE SET
+                    size = settlement_positions.size + EXCLUDED.size
+                "#,
+            )
+            .bind(snapshot.business_date)
+            .bind(snapshot.user_id)
+            .bind(&position.symbol)
+            .bind(&position.quote_currency)
+            .bind(position.side.to_string())
+            .bind(position.size)
+            .bind(position.entry_price)
+            .bind(position.mark_price)
+            .bind(position.unrealized_pnl)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(true)
+    }
+
+    pub async fn get_snapshot(
+        &self,
+        business_date: NaiveDate,
+        user_id: Uuid,
+    ) -> TradingResult<Option<AccountSnapshot>> {
+        let row = sqlx::query(
+            "SELECT taken_at FROM settlement_accounts WHERE business_date = $1 AND user_id = $2",
+        )
+        .bind(business_date)
+        .bind(user_id)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let balances = sqlx::query(
+            r#"
+            SELECT * FROM settlement_balances
+            WHERE business_date = $1 AND user_id = $2
+            ORDER BY currency
+            "#,
+        )
+        .bind(business_date)
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|row| SettledBalance {
+            currency: row.get("currency"),
+            total: row.get("total"),
+            available: row.get("available"),
+            frozen: row.get("frozen"),
+        })
+        .collect();
+
+        let positions = sqlx::query(
+            r#"
+            SELECT * FROM settlement_positions
+            WHERE business_date = $1 AND user_id = $2
+            ORDER BY symbol, side
+            "#,
+        )
+        .bind(business_date)
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|row| {
+            let side: String = row.get("side");
+            let side = side
+                .parse()
+                .map_err(|e| TradingError::SerializationError(format!("Invalid position side: {}", e)))?;
+            Ok(SettledPosition {
+                symbol: row.get("symbol"),
+                quote_currency: row.get("quote_currency"),
+                side,
+                size: row.get("size"),
+                entry_price: row.get("entry_price"),
+                mark_price: row.get("mark_price"),
+                unrealized_pnl: row.get("unrealized_pnl"),
+            })
+        })
+        .collect::<TradingResult<Vec<_>>>()?;
+
+        Ok(Some(AccountSnapshot {
+            business_date,
+            user_id,
+            balances,
+            positions,
+            taken_at: row.get("taken_at"),
+        }))
+    }
+
+    /// 营业日内各币种的成交手续费合计
+    pub async fn fee_totals(
+        &self,
+        user_id: Uuid,
+        period: (DateTime<Utc>, DateTime<Utc>),
+    ) -> TradingResult<Vec<(String, Amount)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT fee_currency, SUM(fee) AS fee FROM trades
+            WHERE user_id = $1 AND executed_at >= $2 AND executed_at < $3
+            GROUP BY fee_currency
+            ORDER BY fee_currency
+            "#,
+        )
+        .bind(user_id)
+        .bind(period.0)
+        .bind(period.1)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get("fee_currency"), row.get("fee")))
+            .collect())
+    }
+
+    /// 替换账户当日的计提，并将资金费计入余额
+    ///
+    /// 手续费在成交时已从余额扣减，这里只入账资金费。上次运行的入账在同一事务中冲回后
+    /// 按本次计提重新入账，重复运行同一营业日时余额不会重复变动。
+    pub async fn replace_entries(
+        &self,
+        business_date: NaiveDate,
+        user_id: Uuid,
+        entries: &[SettlementEntry],
+    ) -> TradingResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query("DELETE FROM settlement_entries WHERE business_date = $1 AND user_id = $2")
+            .bind(business_date)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO settlement_entries (
+                    business_date, user_id, kind, reference, currency, amount
+                ) VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (business_date, user_id, kind, reference, currency) DO UPDATE SET
+                    amount = settlement_entries.amount + EXCLUDED.amount
+                "#,
+            )
+            .bind(business_date)
+            .bind(user_id)
+            .bind(entry.kind.to_string())
+            .bind(&entry.reference)
+            .bind(&entry.currency)
+            .bind(entry.amount)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        let reversed = sqlx::query(
+            "DELETE FROM settlement_postings WHERE business_date = $1 AND user_id = $2 RETURNING currency, amount",
+        )
+        .bind(business_date)
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        let reversals = reversed
+            .iter()
+            .map(|row| (row.get::<String, _>("currency"), -row.get::<Amount, _>("amount")));
+        let postings = entries
+            .iter()
+            .filter(|entry| entry.kind == SettlementEntryKind::Funding)
+            .map(|entry| (entry.currency.clone(), entry.amount));
+        for (currency, amount) in reversals.chain(postings) {
+            sqlx::query(
+                r#"
+                INSERT INTO account_balances (user_id, currency, total)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, currency) DO UPDATE SET
+                    total = account_balances.total + EXCLUDED.total,
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(user_id)
+            .bind(&currency)
+            .bind(amount)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        for entry in entries.iter().filter(|entry| entry.kind == SettlementEntryKind::Funding) {
+            sqlx::query(
+                r#"
+                INSERT INTO settlement_postings (business_date, user_id, reference, currency, amount)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (business_date, user_id, reference, currency) DO UPDATE SET
+                    amount = settlement_postings.amount + EXCLUDED.amount
+                "#,
+            )
+            .bind(business_date)
+            .bind(user_id)
+            .bind(&entry.reference)
+            .bind(&entry.currency)
+            .bind(entry.amount)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 写入对账单，重复生成时覆盖
+    pub async fn upsert_statement(
+        &self,
+        business_date: NaiveDate,
+        user_id: Uuid,
+        format: StatementFormat,
+        content: &[u8],
+        now: DateTime<Utc>,
+    ) -> TradingResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO settlement_statements (business_date, user_id, format, content, generated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (business_date, user_id, format) DO UPDATE SET
+                content = EXCLUDED.content,
+                generated_at = EXCLUDED.generated_at
+            "#,
+        )
+        .bind(business_date)
+        .bind(user_id)
+        .bind(format.to_string())
+        .bind(content)
+        .bind(now)
+        .execute(&*self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    pub async fn get_statement(
+        &self,
+        user_id: Uuid,
+        business_date: NaiveDate,
+        format: StatementFormat,
+    ) -> TradingResult<Option<Vec<u8>>> {
+        let row = sqlx::query(
+            r#"
+            SELECT content FROM settlement_statements
+            WHERE business_date = $1 AND user_id = $2 AND format = $3
+            "#,
+        )
+        .bind(business_date)
+        .bind(user_id)
+        .bind(format.to_string())
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(row.map(|row| row.get("content")))
+    }
+
+    /// 用户的对账单列表，按营业日倒序
+    pub async fn list_statements(&self, user_id: Uuid, limit: u32) -> TradingResult<Vec<StatementSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT business_date, format, octet_length(content)::BIGINT AS size_bytes, generated_at
+            FROM settlement_statements
+            WHERE user_id = $1
+            ORDER BY business_date DESC, format
+            LIMIT $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let format: String = row.get("format");
+                Ok(StatementSummary {
+                    business_date: row.get("business_date"),
+                    format: format.parse()?,
+                    size_bytes: row.get("size_bytes"),
+                    generated_at: row.get("generated_at"),
+                })
+            })
+            .collect()
+    }
+
+    /// 记录营业日各交易对的资金费率，重复结算时覆盖
+    pub async fn upsert_funding_rates(
+        &self,
+        business_date: NaiveDate,
+        funding_time: DateTime<Utc>,
+        rate: Amount,
+        symbols: &[String],
+    ) -> TradingResult<()> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for symbol in symbols {
+            sqlx::query(
+                r#"
+                INSERT INTO funding_rates (symbol, business_date, funding_time, rate)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (symbol, business_date) DO UPDATE SET
+                    funding_time = EXCLUDED.funding_time,
+                    rate = EXCLUDED.rate
+                "#,
+            )
+            .bind(symbol)
+            .bind(business_date)
+            .bind(funding_time)
+            .bind(rate)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    /// 交易对在时间区间内的资金费率，按计提时刻倒序
+    pub async fn funding_history(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: u32,
+    ) -> TradingResult<Vec<FundingRate>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT symbol, business_date, funding_time, rate FROM funding_rates
+            WHERE symbol = $1 AND funding_time >= $2 AND funding_time < $3
+            ORDER BY funding_time DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(symbol)
+        .bind(start)
+        .bind(end)
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FundingRate {
+                symbol: row.get("symbol"),
+                business_date: row.get("business_date"),
+                funding_time: row.get("funding_time"),
+                rate: row.get("rate"),
+            })
+            .collect())
+    }
+
+    /// 用户在交易对上自指定营业日起累计的资金费
+    pub async fn funding_summary(
+        &self,
+        user_id: Uuid,
+        symbol: &str,
+        since: NaiveDate,
+    ) -> TradingResult<FundingSummary> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COALESCE(SUM(-amount) FILTER (WHERE amount < 0), 0) AS paid,
+                COALESCE(SUM(amount) FILTER (WHERE amount > 0), 0) AS received
+            FROM settlement_entries
+            WHERE user_id = $1 AND kind = $2 AND reference = $3 AND business_date >= $4
+            "#,
+        )
+        .bind(user_id)
+        .bind(SettlementEntryKind::Funding.to_string())
+        .bind(symbol)
+        .bind(since)
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let paid: Amount = row.get("paid");
+        let received: Amount = row.get("received");
+        Ok(FundingSummary {
+            paid,
+            received,
+            net: received - paid,
+        })
+    }
+}
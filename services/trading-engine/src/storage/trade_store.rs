@@ -1,20 +1,295 @@
 use anyhow::Result;
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::models::{TradingError, TradingResult};
+use shared_protocols::kafka::KafkaTopics;
+
+use super::OutboxStore;
+use crate::models::{Fill, LiquidityFeeSummary, Side, Symbol, TradeQuery, TradingError, TradingResult};
+
+/// 成交表及查询索引
+///
+/// 查询总是按用户过滤并按 (executed_at, id) 倒序分页，
+/// 索引覆盖按交易对、策略标签和订单的常用过滤条件。
+const SCHEMA: [&str; 6] = [
+    r#"
+    CREATE TABLE IF NOT EXISTS trades (
+        id UUID PRIMARY KEY,
+        user_id UUID NOT NULL,
+        order_id UUID NOT NULL,
+        symbol TEXT NOT NULL,
+        side TEXT NOT NULL,
+        price NUMERIC NOT NULL,
+        quantity NUMERIC NOT NULL,
+        quote_quantity NUMERIC NOT NULL,
+        fee NUMERIC NOT NULL DEFAULT 0,
+        fee_currency TEXT NOT NULL,
+        is_maker BOOLEAN NOT NULL DEFAULT FALSE,
+        venue TEXT NOT NULL,
+        strategy_tag TEXT,
+        executed_at TIMESTAMPTZ NOT NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_trades_user_time ON trades (user_id, executed_at DESC, id DESC)",
+    "CREATE INDEX IF NOT EXISTS idx_trades_user_symbol_time ON trades (user_id, symbol, executed_at DESC, id DESC)",
+    "CREATE INDEX IF NOT EXISTS idx_trades_user_tag_time ON trades (user_id, strategy_tag, executed_at DESC, id DESC) WHERE strategy_tag IS NOT NULL",
+    "CREATE INDEX IF NOT EXISTS idx_trades_order ON trades (order_id)",
+    "ALTER TABLE trades ADD COLUMN IF NOT EXISTS environment TEXT NOT NULL DEFAULT 'live'",
+];
+
+/// 单次查询的最大条数
+pub const MAX_TRADE_PAGE: u32 = 1000;
 
 /// 交易记录存储
 #[derive(Clone)]
 pub struct TradeStore {
     pool: Arc<PgPool>,
+    /// 成交历史和报表查询使用的连接池，未单独配置时与主库相同
+    read_pool: Arc<PgPool>,
 }
 
 impl TradeStore {
     pub fn new(pool: Arc<PgPool>) -> Self {
-        Self { pool }
+        Self {
+            read_pool: pool.clone(),
+            pool,
+        }
+    }
+
+    /// 使用独立的读连接池
+    pub fn with_read_pool(mut self, read_pool: Arc<PgPool>) -> Self {
+        self.read_pool = read_pool;
+        self
     }
 
-    // TODO: 实现交易记录相关的数据库操作
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// 记录成交，重复的成交ID忽略；新成交在同一事务写入 fill_recorded 事件
+    pub async fn insert_fill(&self, fill: &Fill) -> TradingResult<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        let query = r#"
+            INSERT INTO trades (
+                id, user_id, order_id, symbol, side, price, quantity, quote_quantity,
+                fee, fee_currency, is_maker, venue, strategy_tag, executed_at, environment
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
+            )
+            ON CONFLICT (id) DO NOTHING
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(fill.id)
+            .bind(fill.user_id)
+            .bind(fill.order_id)
+            .bind(fill.symbol.to_string())
+            .bind(fill.side.to_string())
+            .bind(fill.price)
+            .bind(fill.quantity)
+            .bind(fill.quote_quantity)
+            .bind(fill.fee)
+            .bind(&fill.fee_currency)
+            .bind(fill.is_maker)
+            .bind(&fill.venue)
+            .bind(&fill.strategy_tag)
+            .bind(fill.executed_at)
+            .bind(fill.environment.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+        if result.rows_affected() == 0 {
+            return Ok(());
+        }
+
+        // 按订单分区，同一订单的成交保持顺序
+        OutboxStore::enqueue(
+            &mut tx,
+            KafkaTopics::TRADING_TRADES,
+            &fill.order_id.to_string(),
+            "fill_recorded",
+            fill,
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 按条件查询用户成交，按成交时间倒序
+    ///
+    /// 多取一条用于判断是否还有下一页，返回 (本页成交, 是否有更多)。
+    pub async fn list_fills(&self, user_id: Uuid, query: &TradeQuery) -> TradingResult<(Vec<Fill>, bool)> {
+        let limit = query.limit.clamp(1, MAX_TRADE_PAGE);
+
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("SELECT * FROM trades WHERE user_id = ");
+        builder.push_bind(user_id);
+        if let Some(symbol) = &query.symbol {
+            builder.push(" AND symbol = ").push_bind(symbol.to_string());
+        }
+        if let Some(side) = &query.side {
+            builder.push(" AND side = ").push_bind(side.to_string());
+        }
+        if let Some(tag) = &query.strategy_tag {
+            builder.push(" AND strategy_tag = ").push_bind(tag.clone());
+        }
+        if let Some(environment) = &query.environment {
+            builder.push(" AND environment = ").push_bind(environment.to_string());
+        }
+        if let Some(start) = query.start_time {
+            builder.push(" AND executed_at >= ").push_bind(start);
+        }
+        if let Some(end) = query.end_time {
+            builder.push(" AND executed_at < ").push_bind(end);
+        }
+        if let Some(cursor) = &query.cursor {
+            builder
+                .push(" AND (executed_at, id) < (")
+                .push_bind(cursor.executed_at)
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        builder
+            .push(" ORDER BY executed_at DESC, id DESC LIMIT ")
+            .push_bind(limit as i64 + 1);
+
+        let rows = builder
+            .build()
+            .fetch_all(&*self.read_pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        let mut fills = rows
+            .into_iter()
+            .map(|row| self.row_to_fill(row))
+            .collect::<TradingResult<Vec<_>>>()?;
+        let has_more = fills.len() > limit as usize;
+        fills.truncate(limit as usize);
+        Ok((fills, has_more))
+    }
+
+    /// 按交易所、流动性方向和手续费币种汇总时间段内的成交和手续费
+    pub async fn fee_report(
+        &self,
+        user_id: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TradingResult<Vec<LiquidityFeeSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT venue, is_maker, fee_currency, COUNT(*) AS trade_count,
+                   SUM(quantity) AS quantity, SUM(quote_quantity) AS quote_quantity, SUM(fee) AS fee
+            FROM trades
+            WHERE user_id = $1 AND executed_at >= $2 AND executed_at < $3
+            GROUP BY venue, is_maker, fee_currency
+            ORDER BY venue, is_maker DESC, fee_currency
+            "#,
+        )
+        .bind(user_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&*self.read_pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                LiquidityFeeSummary::new(
+                    row.get("venue"),
+                    row.get("is_maker"),
+                    row.get("fee_currency"),
+                    row.get("trade_count"),
+                    row.get("quantity"),
+                    row.get("quote_quantity"),
+                    row.get("fee"),
+                )
+            })
+            .collect())
+    }
+
+    /// 查询订单的全部成交
+    pub async fn get_order_fills(&self, user_id: Uuid, order_id: Uuid) -> TradingResult<Vec<Fill>> {
+        let rows = sqlx::query(
+            "SELECT * FROM trades WHERE order_id = $1 AND user_id = $2 ORDER BY executed_at, id",
+        )
+        .bind(order_id)
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(|row| self.row_to_fill(row)).collect()
+    }
+
+    /// 查询截止时间之前的全部实盘成交，按时间升序，用于重放批次
+    pub async fn list_fills_until(&self, user_id: Uuid, end: DateTime<Utc>) -> TradingResult<Vec<Fill>> {
+        let rows = sqlx::query(
+            "SELECT * FROM trades WHERE user_id = $1 AND executed_at < $2 AND environment = 'live' ORDER BY executed_at, id",
+        )
+        .bind(user_id)
+        .bind(end)
+        .fetch_all(&*self.read_pool)
+        .await
+        .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(|row| self.row_to_fill(row)).collect()
+    }
+
+    /// 查询一组订单的全部成交，按时间升序，用于订单流重放
+    pub async fn list_fills_for_orders(&self, order_ids: &[Uuid]) -> TradingResult<Vec<Fill>> {
+        if order_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows = sqlx::query("SELECT * FROM trades WHERE order_id = ANY($1) ORDER BY executed_at, id")
+            .bind(order_ids)
+            .fetch_all(&*self.read_pool)
+            .await
+            .map_err(|e| TradingError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(|row| self.row_to_fill(row)).collect()
+    }
+
+    fn row_to_fill(&self, row: PgRow) -> TradingResult<Fill> {
+        let symbol_str: String = row.get("symbol");
+        let symbol = Symbol::from_string(&symbol_str)
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Invalid symbol: {}", symbol_str)))?;
+
+        let side_str: String = row.get("side");
+        let side = side_str
+            .parse::<Side>()
+            .map_err(|e| TradingError::InvalidOrder(format!("Invalid side: {}", e)))?;
+        let environment: String = row.get("environment");
+
+        Ok(Fill {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            order_id: row.get("order_id"),
+            symbol,
+            side,
+            price: row.get("price"),
+            quantity: row.get("quantity"),
+            quote_quantity: row.get("quote_quantity"),
+            fee: row.get("fee"),
+            fee_currency: row.get("fee_currency"),
+            is_maker: row.get("is_maker"),
+            venue: row.get("venue"),
+            strategy_tag: row.get("strategy_tag"),
+            environment: environment.parse()?,
+            executed_at: row.get("executed_at"),
+        })
+    }
 }
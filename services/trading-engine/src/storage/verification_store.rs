@@ -0,0 +1,142 @@
+use anyhow::Result;
+use chrono::Utc;
+use shared_protocols::kafka::KafkaTopics;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::OutboxStore;
+use crate::models::{
+    TradingError, TradingResult, UserVerification, VerificationChanged, VerificationLevel,
+};
+
+/// 用户认证等级表，没有记录的用户视为未认证
+const SCHEMA: [&str; 1] = [r#"
+    CREATE TABLE IF NOT EXISTS user_verification_levels (
+        user_id UUID PRIMARY KEY,
+        level TEXT NOT NULL,
+        updated_by UUID,
+        reason TEXT,
+        updated_at TIMESTAMPTZ NOT NULL
+    )
+    "#];
+
+fn db_error(e: sqlx::Error) -> TradingError {
+    TradingError::DatabaseError(e.to_string())
+}
+
+fn row_to_verification(row: PgRow) -> TradingResult<UserVerification> {
+    let level: String = row.get("level");
+    Ok(UserVerification {
+        user_id: row.get("user_id"),
+        level: level
+            .parse::<VerificationLevel>()
+            .map_err(|e| TradingError::SerializationError(e.to_string()))?,
+        updated_by: row.get("updated_by"),
+        reason: row.get("reason"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// 用户认证等级存储
+#[derive(Clone)]
+pub struct VerificationStore {
+    pool: Arc<PgPool>,
+}
+
+impl VerificationStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&*self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> TradingResult<Option<UserVerification>> {
+        sqlx::query("SELECT * FROM user_verification_levels WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(db_error)?
+            .map(row_to_verification)
+            .transpose()
+    }
+
+    /// 修改认证等级，等级变化时同一事务写入 verification_level_changed 事件
+    ///
+    /// 返回修改后的记录和变更事件，等级未变化时不产生事件。
+    pub async fn set_level(
+        &self,
+        user_id: Uuid,
+        level: VerificationLevel,
+        updated_by: Uuid,
+        reason: Option<String>,
+    ) -> TradingResult<(UserVerification, Option<VerificationChanged>)> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        // 锁定原记录，并发修改时按顺序产生事件
+        let previous =
+            sqlx::query("SELECT * FROM user_verification_levels WHERE user_id = $1 FOR UPDATE")
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(db_error)?
+                .map(row_to_verification)
+                .transpose()?
+                .map(|v| v.level)
+                .unwrap_or_default();
+
+        let verification = UserVerification {
+            user_id,
+            level,
+            updated_by: Some(updated_by),
+            reason,
+            updated_at: Utc::now(),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO user_verification_levels (user_id, level, updated_by, reason, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE SET
+                level = EXCLUDED.level,
+                updated_by = EXCLUDED.updated_by,
+                reason = EXCLUDED.reason,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(level.as_str())
+        .bind(updated_by)
+        .bind(&verification.reason)
+        .bind(verification.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        let change = (previous != level).then(|| VerificationChanged {
+            user_id,
+            previous_level: previous,
+            level,
+            updated_by,
+            reason: verification.reason.clone(),
+            changed_at: verification.updated_at,
+        });
+        if let Some(change) = &change {
+            OutboxStore::enqueue(
+                &mut tx,
+                KafkaTopics::USER_EVENTS,
+                &user_id.to_string(),
+                "verification_level_changed",
+                change,
+            )
+            .await?;
+        }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok((verification, change))
+    }
+}
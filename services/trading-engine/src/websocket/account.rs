@@ -3,28 +3,29 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         State,
     },
+    http::{HeaderMap, StatusCode},
     response::Response,
 };
 use futures_util::{sink::SinkExt, stream::StreamExt};
 use serde_json::json;
+use tokio::sync::broadcast;
 use tokio::time::{interval, Duration};
 use uuid::Uuid;
 
-use crate::state::AppState;
+use crate::{handlers::authenticated_user, state::AppState};
 
 /// 账户WebSocket处理器
 pub async fn account_websocket(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> Response {
-    ws.on_upgrade(|socket| handle_account_socket(socket, state))
+) -> Result<Response, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    Ok(ws.on_upgrade(move |socket| handle_account_socket(socket, state, user_id)))
 }
 
-async fn handle_account_socket(socket: WebSocket, state: AppState) {
+async fn handle_account_socket(socket: WebSocket, state: AppState, user_id: Uuid) {
     let (mut sender, mut receiver) = socket.split();
-    
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
 
     // 发送欢迎消息
     let welcome_msg = json!({
@@ -40,6 +41,11 @@ async fn handle_account_socket(socket: WebSocket, state: AppState) {
     // 创建定时器发送账户更新
     let mut update_interval = interval(Duration::from_secs(10));
 
+    // 本账户的日终结算事件
+    let mut settlement_events = state.settlement_service.subscribe();
+    // 本账户的站内通知
+    let mut notification_events = state.notification_service.subscribe();
+
     loop {
         tokio::select! {
             // 处理客户端消息
@@ -64,6 +70,42 @@ async fn handle_account_socket(socket: WebSocket, state: AppState) {
                 }
             }
             
+            // 转发日终结算事件
+            event = settlement_events.recv() => {
+                match event {
+                    Ok(event) if event.user_id() == Some(user_id) => {
+                        let message = json!({
+                            "type": "settlement",
+                            "data": event,
+                            "timestamp": chrono::Utc::now()
+                        });
+                        if sender.send(Message::Text(message.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+
+            // 推送新的站内通知
+            notification = notification_events.recv() => {
+                match notification {
+                    Ok(notification) if notification.user_id == user_id => {
+                        let message = json!({
+                            "type": "notification",
+                            "data": notification,
+                            "timestamp": chrono::Utc::now()
+                        });
+                        if sender.send(Message::Text(message.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+
             // 定期发送账户更新
             _ = update_interval.tick() => {
                 if let Err(e) = send_account_update(&state, user_id, &mut sender).await {
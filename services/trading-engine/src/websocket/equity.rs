@@ -0,0 +1,89 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use futures_util::{sink::SinkExt, stream::StreamExt};
+use serde_json::json;
+use tokio::time::{interval, MissedTickBehavior};
+use uuid::Uuid;
+
+use crate::{
+    handlers::authenticated_user,
+    services::equity_stream_service::{should_push, EquitySnapshot},
+    state::AppState,
+};
+
+/// 账户权益WebSocket处理器
+///
+/// 按实时标记价格推送余额加未实现盈亏，权益变化超过阈值时立即推送，否则按固定周期推送。
+pub async fn equity_websocket(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    Ok(ws.on_upgrade(move |socket| handle_equity_socket(socket, state, user_id)))
+}
+
+async fn handle_equity_socket(socket: WebSocket, state: AppState, user_id: Uuid) {
+    let (mut sender, mut receiver) = socket.split();
+    let config = state.equity_stream.config().clone();
+
+    let welcome_msg = json!({
+        "type": "welcome",
+        "message": "Connected to account equity stream",
+        "push_interval_ms": config.push_interval.as_millis() as u64,
+        "min_change_ratio": config.min_change_ratio,
+        "timestamp": chrono::Utc::now()
+    });
+    if sender.send(Message::Text(welcome_msg.to_string())).await.is_err() {
+        return;
+    }
+
+    let mut sample_interval = interval(config.sample_interval);
+    sample_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_sent: Option<EquitySnapshot> = None;
+
+    loop {
+        tokio::select! {
+            msg = receiver.next() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        tracing::error!("Equity WebSocket error: {}", e);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            _ = sample_interval.tick() => {
+                let snapshot = match state.equity_stream.snapshot(user_id).await {
+                    Ok(snapshot) => snapshot,
+                    Err(e) => {
+                        tracing::error!("Failed to compute equity for {}: {}", user_id, e);
+                        continue;
+                    }
+                };
+                if !should_push(last_sent.as_ref(), &snapshot, &config) {
+                    continue;
+                }
+
+                let update = json!({
+                    "type": "equity",
+                    "data": snapshot
+                });
+                if sender.send(Message::Text(update.to_string())).await.is_err() {
+                    break;
+                }
+                last_sent = Some(snapshot);
+            }
+        }
+    }
+
+    tracing::info!("Equity WebSocket connection closed for {}", user_id);
+}
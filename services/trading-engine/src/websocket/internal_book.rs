@@ -0,0 +1,97 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use futures_util::{sink::SinkExt, stream::StreamExt};
+use shared_protocols::internal_book::{InternalBookEvent, InternalBookRequest};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::state::AppState;
+
+/// 内部撮合行情WebSocket处理器
+///
+/// 行情服务以 INTERNAL 交易所身份订阅，连接后先下发全部快照，再推送增量和成交。
+pub async fn internal_book_websocket(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+) -> Response {
+    ws.on_upgrade(|socket| handle_internal_book_socket(socket, state))
+}
+
+async fn handle_internal_book_socket(socket: WebSocket, state: AppState) {
+    let (mut sender, mut receiver) = socket.split();
+
+    // 先订阅再取快照，快照之后的增量不会丢失，重复的由订阅方按序号丢弃
+    let mut events = state.book_feed.subscribe();
+    if send_events(&mut sender, state.book_feed.snapshots(None).await).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            msg = receiver.next() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<InternalBookRequest>(&text) {
+                            Ok(InternalBookRequest::Resync { symbol }) => {
+                                tracing::info!("Internal book resync requested for {:?}", symbol);
+                                let snapshots = state.book_feed.snapshots(symbol.as_deref()).await;
+                                if send_events(&mut sender, snapshots).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Err(e) => tracing::warn!("Invalid internal book request: {}", e),
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        tracing::error!("Internal book WebSocket error: {}", e);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            event = events.recv() => {
+                match event {
+                    Ok(event) => {
+                        if send_events(&mut sender, vec![event]).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        // 落后时丢失了增量，重新下发全部快照
+                        tracing::warn!("Internal book subscriber lagged by {} events, resending snapshots", skipped);
+                        let snapshots = state.book_feed.snapshots(None).await;
+                        if send_events(&mut sender, snapshots).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    tracing::info!("Internal book WebSocket connection closed");
+}
+
+async fn send_events<S>(sender: &mut S, events: Vec<InternalBookEvent>) -> Result<(), ()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    for event in events {
+        let text = match serde_json::to_string(&event) {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::error!("Failed to serialize internal book event: {}", e);
+                continue;
+            }
+        };
+        sender.send(Message::Text(text)).await.map_err(|_| ())?;
+    }
+    Ok(())
+}
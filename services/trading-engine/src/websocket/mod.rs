@@ -1,3 +1,5 @@
 pub mod account;
+pub mod equity;
+pub mod internal_book;
 pub mod orders;
 pub mod positions;
@@ -3,28 +3,31 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         State,
     },
+    http::{HeaderMap, StatusCode},
     response::Response,
 };
 use futures_util::{sink::SinkExt, stream::StreamExt};
 use serde_json::json;
-use tokio::time::{interval, Duration};
 use uuid::Uuid;
 
-use crate::state::AppState;
+use crate::{handlers::authenticated_user, state::AppState};
 
 /// 订单WebSocket处理器
+///
+/// 订阅订单事件总线，只推送当前用户的订单受理、成交和撤销事件，以及算法单的执行进度。
 pub async fn orders_websocket(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> Response {
-    ws.on_upgrade(|socket| handle_orders_socket(socket, state))
+) -> Result<Response, StatusCode> {
+    let user_id = authenticated_user(&headers)?;
+    Ok(ws.on_upgrade(move |socket| handle_orders_socket(socket, state, user_id)))
 }
 
-async fn handle_orders_socket(socket: WebSocket, state: AppState) {
+async fn handle_orders_socket(socket: WebSocket, state: AppState, user_id: Uuid) {
     let (mut sender, mut receiver) = socket.split();
-    
-    // TODO: 从JWT token中获取用户ID
-    let user_id = Uuid::new_v4(); // 临时使用随机ID
+    let mut events = state.order_events.subscribe(user_id);
+    let mut subscribed = true;
 
     // 发送欢迎消息
     let welcome_msg = json!({
@@ -37,16 +40,13 @@ async fn handle_orders_socket(socket: WebSocket, state: AppState) {
         return;
     }
 
-    // 创建定时器发送订单更新
-    let mut update_interval = interval(Duration::from_secs(5));
-
     loop {
         tokio::select! {
             // 处理客户端消息
             msg = receiver.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        if let Err(e) = handle_client_message(&text, &state, user_id, &mut sender).await {
+                        if let Err(e) = handle_client_message(&text, &state, user_id, &mut subscribed, &mut sender).await {
                             tracing::error!("Error handling client message: {}", e);
                             break;
                         }
@@ -64,9 +64,26 @@ async fn handle_orders_socket(socket: WebSocket, state: AppState) {
                 }
             }
             
-            // 定期发送订单更新
-            _ = update_interval.tick() => {
-                if let Err(e) = send_orders_update(&state, user_id, &mut sender).await {
+            // 推送订单事件
+            event = events.recv() => {
+                let result = match event {
+                    Some(Ok(event)) if subscribed => {
+                        let update = json!({
+                            "type": "order_event",
+                            "data": event,
+                            "timestamp": chrono::Utc::now()
+                        });
+                        sender.send(Message::Text(update.to_string())).await.map_err(Into::into)
+                    }
+                    Some(Ok(_)) => Ok(()),
+                    // 落后丢失了事件，推送完整的订单快照让客户端重新同步
+                    Some(Err(skipped)) => {
+                        tracing::warn!("Orders WebSocket for {} lagged, skipped {} events", user_id, skipped);
+                        send_orders_update(&state, user_id, &mut sender).await
+                    }
+                    None => break,
+                };
+                if let Err(e) = result {
                     tracing::error!("Error sending orders update: {}", e);
                     break;
                 }
@@ -81,12 +98,14 @@ async fn handle_client_message(
     text: &str,
     state: &AppState,
     user_id: Uuid,
+    subscribed: &mut bool,
     sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let request: serde_json::Value = serde_json::from_str(text)?;
     
     match request.get("type").and_then(|t| t.as_str()) {
         Some("subscribe") => {
+            *subscribed = true;
             let response = json!({
                 "type": "subscribed",
                 "message": "Subscribed to orders updates",
@@ -95,6 +114,7 @@ async fn handle_client_message(
             sender.send(Message::Text(response.to_string())).await?;
         }
         Some("unsubscribe") => {
+            *subscribed = false;
             let response = json!({
                 "type": "unsubscribed",
                 "message": "Unsubscribed from orders updates",
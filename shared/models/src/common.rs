@@ -79,6 +79,9 @@ pub enum Exchange {
     Bybit,
     KuCoin,
     Gate,
+    Kraken,
+    /// 内部撮合引擎
+    Internal,
 }
 
 impl std::fmt::Display for Exchange {
@@ -90,6 +93,8 @@ impl std::fmt::Display for Exchange {
             Exchange::Bybit => write!(f, "bybit"),
             Exchange::KuCoin => write!(f, "kucoin"),
             Exchange::Gate => write!(f, "gate"),
+            Exchange::Kraken => write!(f, "kraken"),
+            Exchange::Internal => write!(f, "internal"),
         }
     }
 }
@@ -109,6 +114,75 @@ impl Exchange {
             Exchange::Bybit => "bybit",
             Exchange::KuCoin => "kucoin",
             Exchange::Gate => "gate",
+            Exchange::Kraken => "kraken",
+            Exchange::Internal => "internal",
+        }
+    }
+}
+
+impl std::str::FromStr for Exchange {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "binance" => Ok(Exchange::Binance),
+            "okx" => Ok(Exchange::OKX),
+            "huobi" => Ok(Exchange::Huobi),
+            "bybit" => Ok(Exchange::Bybit),
+            "kucoin" => Ok(Exchange::KuCoin),
+            "gate" => Ok(Exchange::Gate),
+            "kraken" => Ok(Exchange::Kraken),
+            "internal" => Ok(Exchange::Internal),
+            _ => Err(CommonError::Validation(format!("Unknown exchange: {}", s))),
+        }
+    }
+}
+
+/// 账户结算和展示币种
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SettlementCurrency {
+    #[default]
+    Usdt,
+    Usdc,
+    Btc,
+}
+
+impl SettlementCurrency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SettlementCurrency::Usdt => "USDT",
+            SettlementCurrency::Usdc => "USDC",
+            SettlementCurrency::Btc => "BTC",
+        }
+    }
+
+    /// 金额小数位数
+    pub fn precision(&self) -> u32 {
+        crate::decimal::currency_precision(self.as_str())
+    }
+
+    /// 按币种精度四舍五入金额
+    pub fn round(&self, value: Decimal) -> Decimal {
+        crate::decimal::round_amount(value, self.as_str())
+    }
+}
+
+impl std::fmt::Display for SettlementCurrency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for SettlementCurrency {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "USDT" => Ok(SettlementCurrency::Usdt),
+            "USDC" => Ok(SettlementCurrency::Usdc),
+            "BTC" => Ok(SettlementCurrency::Btc),
+            _ => Err(CommonError::Validation(format!("Unsupported settlement currency: {}", s))),
         }
     }
 }
@@ -121,12 +195,19 @@ pub struct Symbol {
     pub quote_asset: String,
     pub exchange: Exchange,
     pub status: SymbolStatus,
+    #[serde(with = "crate::decimal")]
     pub min_qty: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_qty: Decimal,
+    #[serde(with = "crate::decimal")]
     pub step_size: Decimal,
+    #[serde(with = "crate::decimal")]
     pub min_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub tick_size: Decimal,
+    #[serde(with = "crate::decimal")]
     pub min_notional: Decimal,
 }
 
@@ -251,6 +332,37 @@ impl Interval {
             Interval::OneMonth => 2592000, // 30 days
         }
     }
+
+    /// 转换为毫秒
+    pub fn to_millis(&self) -> i64 {
+        self.to_seconds() as i64 * 1000
+    }
+}
+
+impl std::str::FromStr for Interval {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1s" => Ok(Interval::OneSecond),
+            "1m" => Ok(Interval::OneMinute),
+            "3m" => Ok(Interval::ThreeMinutes),
+            "5m" => Ok(Interval::FiveMinutes),
+            "15m" => Ok(Interval::FifteenMinutes),
+            "30m" => Ok(Interval::ThirtyMinutes),
+            "1h" => Ok(Interval::OneHour),
+            "2h" => Ok(Interval::TwoHours),
+            "4h" => Ok(Interval::FourHours),
+            "6h" => Ok(Interval::SixHours),
+            "8h" => Ok(Interval::EightHours),
+            "12h" => Ok(Interval::TwelveHours),
+            "1d" => Ok(Interval::OneDay),
+            "3d" => Ok(Interval::ThreeDays),
+            "1w" => Ok(Interval::OneWeek),
+            "1M" => Ok(Interval::OneMonth),
+            _ => Err(CommonError::Validation(format!("Unknown interval: {}", s))),
+        }
+    }
 }
 
 /// 配置项结构
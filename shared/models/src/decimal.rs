@@ -0,0 +1,209 @@
+//! 小数序列化约定
+//!
+//! 共享模型中的小数统一序列化为字符串，避免浮点转换丢失精度；
+//! 反序列化同时接受字符串和数字。旧版客户端可以通过请求头或查询参数
+//! 按请求切换为数字输出，切换只在 [`scope`] 包裹的异步任务内生效。
+//!
+//! 用法：`#[serde(with = "crate::decimal")]`，可选字段使用 `crate::decimal::option`。
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{de, Deserializer, Serializer};
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+
+/// 选择数字格式的请求头
+pub const DECIMAL_FORMAT_HEADER: &str = "x-decimal-format";
+/// 选择数字格式的查询参数
+pub const DECIMAL_FORMAT_QUERY: &str = "decimal_format";
+
+/// 小数输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecimalFormat {
+    /// 字符串，保留完整精度
+    #[default]
+    String,
+    /// JSON数字，兼容旧版客户端，超出双精度范围的部分会丢失
+    Number,
+}
+
+impl FromStr for DecimalFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "string" | "str" => Ok(DecimalFormat::String),
+            "number" | "numeric" | "float" => Ok(DecimalFormat::Number),
+            other => Err(format!("Invalid decimal format: {}", other)),
+        }
+    }
+}
+
+impl DecimalFormat {
+    /// 从请求头和原始查询字符串中解析格式，请求头优先，无法识别时使用字符串
+    pub fn from_request(header: Option<&str>, query: Option<&str>) -> Self {
+        let from_query = || {
+            query?.split('&').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                (key == DECIMAL_FORMAT_QUERY).then_some(value)
+            })
+        };
+        header
+            .or_else(from_query)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+tokio::task_local! {
+    static FORMAT: DecimalFormat;
+}
+
+/// 当前任务的小数输出格式，未设置时为字符串
+pub fn current_format() -> DecimalFormat {
+    FORMAT.try_with(|format| *format).unwrap_or_default()
+}
+
+/// 在指定格式下执行，期间序列化的共享模型小数按该格式输出
+pub async fn scope<F: Future>(format: DecimalFormat, future: F) -> F::Output {
+    FORMAT.scope(format, future).await
+}
+
+/// 以字符串格式同步执行，用于持久化和消息的序列化，不受当前请求的输出格式影响
+pub fn canonical<R>(f: impl FnOnce() -> R) -> R {
+    FORMAT.sync_scope(DecimalFormat::String, f)
+}
+
+pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    match current_format() {
+        DecimalFormat::String => serializer.serialize_str(&value.normalize().to_string()),
+        DecimalFormat::Number => match value.to_f64() {
+            Some(number) => serializer.serialize_f64(number),
+            None => serializer.serialize_str(&value.normalize().to_string()),
+        },
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    deserializer.deserialize_any(DecimalVisitor)
+}
+
+struct DecimalVisitor;
+
+impl<'de> de::Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal string or number")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        let text = value.trim();
+        Decimal::from_str(text)
+            .or_else(|_| Decimal::from_scientific(text))
+            .map_err(|_| E::custom(format!("invalid decimal \"{}\"", value)))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+        Decimal::from_f64(value).ok_or_else(|| E::custom(format!("invalid decimal {}", value)))
+    }
+}
+
+/// 未配置币种的默认金额精度
+pub const DEFAULT_CURRENCY_PRECISION: u32 = 8;
+
+/// 币种金额的小数位数，稳定币保留4位，其他币种按最小单位
+pub fn currency_precision(currency: &str) -> u32 {
+    match currency.to_uppercase().as_str() {
+        "USDT" | "USDC" | "USD" => 4,
+        _ => DEFAULT_CURRENCY_PRECISION,
+    }
+}
+
+/// 按币种精度四舍五入金额
+pub fn round_amount(value: Decimal, currency: &str) -> Decimal {
+    value.round_dp_with_strategy(
+        currency_precision(currency),
+        rust_decimal::RoundingStrategy::MidpointAwayFromZero,
+    )
+}
+
+/// 按币种精度格式化金额，保留固定小数位
+pub fn format_amount(value: Decimal, currency: &str) -> String {
+    let precision = currency_precision(currency);
+    let mut rounded = round_amount(value, currency);
+    rounded.rescale(precision);
+    rounded.to_string()
+}
+
+/// 可选小数，字段缺失或为 null 时为 None，需配合 `#[serde(default)]`
+pub mod option {
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "crate::decimal")] Decimal);
+
+    pub fn serialize<S: Serializer>(value: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(value)| value))
+    }
+}
+
+/// 值为小数的映射
+pub mod map {
+    use rust_decimal::Decimal;
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "crate::decimal")] Decimal);
+
+    pub fn serialize<K, S>(value: &HashMap<K, Decimal>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        S: Serializer,
+    {
+        struct Value<'a>(&'a Decimal);
+
+        impl Serialize for Value<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                super::serialize(self.0, serializer)
+            }
+        }
+
+        let mut map = serializer.serialize_map(Some(value.len()))?;
+        for (key, value) in value {
+            map.serialize_entry(key, &Value(value))?;
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, K, D>(deserializer: D) -> Result<HashMap<K, Decimal>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        D: Deserializer<'de>,
+    {
+        Ok(HashMap::<K, Wrapper>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(key, Wrapper(value))| (key, value))
+            .collect())
+    }
+}
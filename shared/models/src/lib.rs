@@ -1,7 +1,9 @@
 pub mod common;
+pub mod decimal;
 pub mod market;
 pub mod risk;
 pub mod strategy;
+pub mod timestamp;
 pub mod trading;
 pub mod user;
 
@@ -9,5 +11,6 @@ pub use common::*;
 pub use market::*;
 pub use risk::*;
 pub use strategy::*;
+pub use timestamp::Timestamp;
 pub use trading::*;
 pub use user::*;
@@ -12,11 +12,17 @@ pub struct MarketTick {
     pub exchange: Exchange,
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub bid: Decimal,
+    #[serde(with = "crate::decimal")]
     pub ask: Decimal,
+    #[serde(with = "crate::decimal")]
     pub bid_volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub ask_volume: Decimal,
     pub trade_id: Option<String>,
     pub is_buyer_maker: Option<bool>,
@@ -33,14 +39,22 @@ pub struct Kline {
     pub interval: Interval,
     pub open_time: DateTime<Utc>,
     pub close_time: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub open: Decimal,
+    #[serde(with = "crate::decimal")]
     pub high: Decimal,
+    #[serde(with = "crate::decimal")]
     pub low: Decimal,
+    #[serde(with = "crate::decimal")]
     pub close: Decimal,
+    #[serde(with = "crate::decimal")]
     pub volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub quote_volume: Decimal,
     pub trades_count: u32,
+    #[serde(with = "crate::decimal")]
     pub taker_buy_base_volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub taker_buy_quote_volume: Decimal,
     pub is_closed: bool,
     /// 数据质量标记
@@ -61,7 +75,9 @@ pub struct OrderBook {
 /// 订单簿价格层级
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBookLevel {
+    #[serde(with = "crate::decimal")]
     pub price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
 }
 
@@ -71,20 +87,35 @@ pub struct Ticker24hr {
     pub exchange: Exchange,
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub price_change: Decimal,
+    #[serde(with = "crate::decimal")]
     pub price_change_percent: Decimal,
+    #[serde(with = "crate::decimal")]
     pub weighted_avg_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub prev_close_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub last_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub last_qty: Decimal,
+    #[serde(with = "crate::decimal")]
     pub bid_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub bid_qty: Decimal,
+    #[serde(with = "crate::decimal")]
     pub ask_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub ask_qty: Decimal,
+    #[serde(with = "crate::decimal")]
     pub open_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub high_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub low_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub quote_volume: Decimal,
     pub open_time: DateTime<Utc>,
     pub close_time: DateTime<Utc>,
@@ -124,8 +155,11 @@ pub struct Trade {
     pub symbol: String,
     pub trade_id: String,
     pub timestamp: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
+    #[serde(with = "crate::decimal")]
     pub quote_quantity: Decimal,
     pub side: String, // "buy" or "sell"
     pub is_buyer_maker: bool,
@@ -148,10 +182,14 @@ pub struct MarketDataQuery {
 pub struct PriceInfo {
     pub exchange: Exchange,
     pub symbol: String,
+    #[serde(with = "crate::decimal")]
     pub price: Decimal,
     pub timestamp: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub volume_24h: Decimal,
+    #[serde(with = "crate::decimal")]
     pub change_24h: Decimal,
+    #[serde(with = "crate::decimal")]
     pub change_percent_24h: Decimal,
 }
 
@@ -166,6 +204,164 @@ pub struct DepthSnapshot {
     pub last_update_id: u64,
 }
 
+/// 订单簿深度指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthMetrics {
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
+    pub best_bid: Decimal,
+    #[serde(with = "crate::decimal")]
+    pub best_ask: Decimal,
+    #[serde(with = "crate::decimal")]
+    pub mid_price: Decimal,
+    #[serde(with = "crate::decimal")]
+    pub spread_bps: Decimal,
+    /// 按最优档数量加权的微观价格
+    #[serde(with = "crate::decimal")]
+    pub microprice: Decimal,
+    /// 不同档位深度下的买卖不平衡
+    pub imbalances: Vec<DepthImbalance>,
+    /// 距中间价一定基点范围内的流动性
+    pub liquidity: Vec<LiquidityBand>,
+}
+
+/// 指定档位的买卖不平衡
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthImbalance {
+    pub levels: usize,
+    #[serde(with = "crate::decimal")]
+    pub bid_quantity: Decimal,
+    #[serde(with = "crate::decimal")]
+    pub ask_quantity: Decimal,
+    /// (bid - ask) / (bid + ask)，取值范围 [-1, 1]
+    #[serde(with = "crate::decimal")]
+    pub imbalance: Decimal,
+}
+
+/// 中间价附近的流动性
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityBand {
+    pub bps: u32,
+    #[serde(with = "crate::decimal")]
+    pub bid_notional: Decimal,
+    #[serde(with = "crate::decimal")]
+    pub ask_notional: Decimal,
+}
+
+/// 交易对的日内季节性画像
+///
+/// 按星期和小时（UTC）统计成交量和波动，比值相对于全部时段的均值，
+/// 小于1表示该时段比平均更清淡或更平稳。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalityProfile {
+    pub exchange: Exchange,
+    pub symbol: String,
+    /// 统计使用的历史天数
+    pub lookback_days: u32,
+    pub computed_at: DateTime<Utc>,
+    pub buckets: Vec<SeasonalityBucket>,
+}
+
+impl SeasonalityProfile {
+    /// 指定星期（1=周一）和小时的统计
+    pub fn bucket(&self, day_of_week: u8, hour: u8) -> Option<&SeasonalityBucket> {
+        self.buckets
+            .iter()
+            .find(|b| b.day_of_week == day_of_week && b.hour == hour)
+    }
+}
+
+/// 某个星期和小时的成交量与波动统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalityBucket {
+    /// 1=周一 … 7=周日
+    pub day_of_week: u8,
+    /// 0-23，UTC
+    pub hour: u8,
+    /// 参与统计的小时数
+    pub samples: u32,
+    #[serde(with = "crate::decimal")]
+    pub avg_volume: Decimal,
+    /// 平均成交量与全部时段均值之比
+    #[serde(with = "crate::decimal")]
+    pub volume_ratio: Decimal,
+    /// 平均小时振幅（基点）
+    #[serde(with = "crate::decimal")]
+    pub avg_range_bps: Decimal,
+    /// 平均振幅与全部时段均值之比
+    #[serde(with = "crate::decimal")]
+    pub volatility_ratio: Decimal,
+}
+
+/// 大额成交（鲸鱼单）事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhaleTrade {
+    pub id: Uuid,
+    pub exchange: Exchange,
+    pub symbol: String,
+    pub kind: WhaleTradeKind,
+    /// 主动方向：buy / sell
+    pub side: String,
+    /// 成交量加权均价
+    #[serde(with = "crate::decimal")]
+    pub price: Decimal,
+    #[serde(with = "crate::decimal")]
+    pub quantity: Decimal,
+    #[serde(with = "crate::decimal")]
+    pub notional: Decimal,
+    pub trade_count: u32,
+    /// 触发检测的成交额阈值
+    #[serde(with = "crate::decimal")]
+    pub threshold: Decimal,
+    /// 成交前的参考价格
+    #[serde(with = "crate::decimal")]
+    pub reference_price: Decimal,
+    /// 成交结束时的价格
+    #[serde(with = "crate::decimal")]
+    pub end_price: Decimal,
+    /// 价格冲击（基点，带符号）
+    #[serde(with = "crate::decimal")]
+    pub price_impact_bps: Decimal,
+    pub first_trade_time: DateTime<Utc>,
+    pub last_trade_time: DateTime<Utc>,
+}
+
+/// 大额成交类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhaleTradeKind {
+    /// 单笔大额成交
+    Single,
+    /// 短时间内同方向连续成交累计
+    Burst,
+}
+
+/// K线收盘状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleStatus {
+    /// 到达收盘时间，首次定稿
+    Final,
+    /// 宽限期内收到修正
+    Revised,
+    /// 宽限期结束，数据不再变化
+    Sealed,
+}
+
+/// K线收盘事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleClose {
+    pub kline: Kline,
+    pub status: CandleStatus,
+    /// 定稿后的修正次数
+    pub revision: u32,
+    pub finalized_at: DateTime<Utc>,
+    /// 宽限期结束时间，之后的更新会被丢弃
+    pub stable_at: DateTime<Utc>,
+}
+
 /// WebSocket市场数据消息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketDataMessage {
@@ -183,11 +379,18 @@ pub struct MarketDataStats {
     pub symbol: String,
     pub date: chrono::NaiveDate,
     pub total_trades: u64,
+    #[serde(with = "crate::decimal")]
     pub total_volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total_quote_volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub avg_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub high_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub low_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub open_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub close_price: Decimal,
 }
@@ -95,9 +95,11 @@ pub struct RiskLimit {
     pub exchange: Option<Exchange>,
     pub symbol: Option<String>,
     pub limit_type: RiskLimitType,
+    #[serde(with = "crate::decimal")]
     pub value: Decimal,
     pub period: Option<RiskPeriod>,
     pub is_active: bool,
+    #[serde(with = "crate::decimal")]
     pub current_usage: Decimal,
     pub last_reset: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
@@ -138,9 +140,13 @@ pub struct RiskCheckRequest {
     pub symbol: String,
     pub side: OrderSide,
     pub order_type: OrderType,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub price: Option<Decimal>,
+    #[serde(with = "crate::decimal::map")]
     pub current_positions: HashMap<String, Decimal>,
+    #[serde(with = "crate::decimal")]
     pub account_balance: Decimal,
     pub metadata: HashMap<String, serde_json::Value>,
 }
@@ -196,7 +202,9 @@ pub struct RiskWarning {
 /// 订单修改建议
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderModification {
+    #[serde(with = "crate::decimal::option", default)]
     pub new_quantity: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub new_price: Option<Decimal>,
     pub reason: String,
 }
@@ -207,6 +215,7 @@ pub struct RiskMetric {
     pub id: Uuid,
     pub user_id: Uuid,
     pub metric_type: RiskMetricType,
+    #[serde(with = "crate::decimal")]
     pub value: Decimal,
     pub timestamp: DateTime<Utc>,
     pub period: RiskPeriod,
@@ -259,13 +268,20 @@ pub enum RiskReportType {
 /// 风险摘要
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskSummary {
+    #[serde(with = "crate::decimal")]
     pub overall_risk_score: Decimal,
     pub risk_level: RiskLevel,
+    #[serde(with = "crate::decimal")]
     pub total_exposure: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_drawdown: Decimal,
+    #[serde(with = "crate::decimal")]
     pub var_95: Decimal,
+    #[serde(with = "crate::decimal")]
     pub var_99: Decimal,
+    #[serde(with = "crate::decimal")]
     pub volatility: Decimal,
+    #[serde(with = "crate::decimal")]
     pub sharpe_ratio: Decimal,
     pub violation_count: u32,
     pub warning_count: u32,
@@ -290,6 +306,7 @@ pub struct RiskEvent {
     pub title: String,
     pub description: String,
     pub affected_positions: Vec<String>,
+    #[serde(with = "crate::decimal::option", default)]
     pub impact_amount: Option<Decimal>,
     pub status: RiskEventStatus,
     pub created_at: DateTime<Utc>,
@@ -339,6 +356,7 @@ pub struct RiskConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskMonitoringSettings {
     pub real_time_monitoring: bool,
+    #[serde(with = "crate::decimal::map")]
     pub alert_thresholds: HashMap<RiskMetricType, Decimal>,
     pub notification_channels: Vec<NotificationChannel>,
     pub monitoring_frequency: u32, // 秒
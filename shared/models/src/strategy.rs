@@ -115,7 +115,7 @@ pub struct Condition {
 pub enum Operand {
     Indicator { name: String, shift: u32 },
     Price { source: PriceSource, shift: u32 },
-    Value(Decimal),
+    Value(#[serde(with = "crate::decimal")] Decimal),
     Variable(String),
 }
 
@@ -144,9 +144,13 @@ pub enum LogicalOperator {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionSizing {
     pub method: PositionSizingMethod,
+    #[serde(with = "crate::decimal")]
     pub base_amount: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub risk_percentage: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub max_position_size: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub leverage: Option<Decimal>,
 }
 
@@ -163,11 +167,16 @@ pub enum PositionSizingMethod {
 /// 风险设置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskSettings {
+    #[serde(with = "crate::decimal")]
     pub max_drawdown: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub stop_loss: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub take_profit: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub max_daily_loss: Option<Decimal>,
     pub max_positions: Option<u32>,
+    #[serde(with = "crate::decimal::option", default)]
     pub max_correlation: Option<Decimal>,
     pub trailing_stop: Option<TrailingStop>,
 }
@@ -175,6 +184,7 @@ pub struct RiskSettings {
 /// 追踪止损
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrailingStop {
+    #[serde(with = "crate::decimal")]
     pub distance: Decimal,
     pub distance_type: TrailingStopType,
 }
@@ -190,19 +200,30 @@ pub enum TrailingStopType {
 /// 策略性能
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyPerformance {
+    #[serde(with = "crate::decimal")]
     pub total_return: Decimal,
+    #[serde(with = "crate::decimal")]
     pub annualized_return: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_drawdown: Decimal,
+    #[serde(with = "crate::decimal")]
     pub sharpe_ratio: Decimal,
+    #[serde(with = "crate::decimal")]
     pub sortino_ratio: Decimal,
+    #[serde(with = "crate::decimal")]
     pub win_rate: Decimal,
+    #[serde(with = "crate::decimal")]
     pub profit_factor: Decimal,
     pub total_trades: u64,
     pub winning_trades: u64,
     pub losing_trades: u64,
+    #[serde(with = "crate::decimal")]
     pub avg_win: Decimal,
+    #[serde(with = "crate::decimal")]
     pub avg_loss: Decimal,
+    #[serde(with = "crate::decimal")]
     pub largest_win: Decimal,
+    #[serde(with = "crate::decimal")]
     pub largest_loss: Decimal,
     pub updated_at: DateTime<Utc>,
 }
@@ -216,11 +237,17 @@ pub struct StrategySignal {
     pub exchange: Exchange,
     pub side: OrderSide,
     pub signal_type: SignalType,
+    #[serde(with = "crate::decimal")]
     pub strength: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub price: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub quantity: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub stop_loss: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub take_profit: Option<Decimal>,
+    #[serde(with = "crate::decimal")]
     pub confidence: Decimal,
     pub metadata: HashMap<String, serde_json::Value>,
     pub created_at: DateTime<Utc>,
@@ -235,8 +262,11 @@ pub struct BacktestConfig {
     pub name: String,
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub initial_capital: Decimal,
+    #[serde(with = "crate::decimal")]
     pub commission: Decimal,
+    #[serde(with = "crate::decimal")]
     pub slippage: Decimal,
     pub benchmark: Option<String>,
     pub data_frequency: Interval,
@@ -272,8 +302,11 @@ pub enum BacktestStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EquityPoint {
     pub timestamp: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub equity: Decimal,
+    #[serde(with = "crate::decimal")]
     pub drawdown: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub benchmark: Option<Decimal>,
 }
 
@@ -285,10 +318,15 @@ pub struct BacktestTrade {
     pub side: OrderSide,
     pub entry_time: DateTime<Utc>,
     pub exit_time: DateTime<Utc>,
+    #[serde(with = "crate::decimal")]
     pub entry_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub exit_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
+    #[serde(with = "crate::decimal")]
     pub pnl: Decimal,
+    #[serde(with = "crate::decimal")]
     pub commission: Decimal,
     pub duration: i64, // 持仓时间（秒）
 }
@@ -296,19 +334,32 @@ pub struct BacktestTrade {
 /// 回测指标
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestMetrics {
+    #[serde(with = "crate::decimal")]
     pub total_return: Decimal,
+    #[serde(with = "crate::decimal")]
     pub annualized_return: Decimal,
+    #[serde(with = "crate::decimal")]
     pub volatility: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_drawdown: Decimal,
     pub max_drawdown_duration: i64,
+    #[serde(with = "crate::decimal")]
     pub sharpe_ratio: Decimal,
+    #[serde(with = "crate::decimal")]
     pub sortino_ratio: Decimal,
+    #[serde(with = "crate::decimal")]
     pub calmar_ratio: Decimal,
+    #[serde(with = "crate::decimal")]
     pub win_rate: Decimal,
+    #[serde(with = "crate::decimal")]
     pub profit_factor: Decimal,
+    #[serde(with = "crate::decimal")]
     pub expectancy: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub beta: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub alpha: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub information_ratio: Option<Decimal>,
 }
 
@@ -316,9 +367,13 @@ pub struct BacktestMetrics {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationParameter {
     pub name: String,
+    #[serde(with = "crate::decimal")]
     pub min_value: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_value: Decimal,
+    #[serde(with = "crate::decimal")]
     pub step: Decimal,
+    #[serde(with = "crate::decimal")]
     pub current_value: Decimal,
 }
 
@@ -327,6 +382,7 @@ pub struct OptimizationParameter {
 pub struct OptimizationResult {
     pub id: Uuid,
     pub strategy_id: Uuid,
+    #[serde(with = "crate::decimal::map")]
     pub parameters: HashMap<String, Decimal>,
     pub performance: StrategyPerformance,
     pub rank: u32,
@@ -338,8 +394,11 @@ pub struct OptimizationResult {
 pub struct StrategyRuntime {
     pub strategy_id: Uuid,
     pub status: StrategyStatus,
+    #[serde(with = "crate::decimal::map")]
     pub current_positions: HashMap<String, Decimal>,
+    #[serde(with = "crate::decimal")]
     pub unrealized_pnl: Decimal,
+    #[serde(with = "crate::decimal")]
     pub realized_pnl: Decimal,
     pub total_trades: u64,
     pub last_signal: Option<DateTime<Utc>>,
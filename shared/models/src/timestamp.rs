@@ -0,0 +1,166 @@
+//! 统一时间戳
+//!
+//! 交易所和内部事件混用秒、毫秒、微秒和纳秒整数，直接传递 `i64` 容易混淆单位。
+//! [`Timestamp`] 以纳秒保存 Unix 时间，只能通过带单位的构造函数创建，
+//! 默认序列化为毫秒整数，字段可以通过 `#[serde(with = ...)]` 选择其他格式。
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+const NANOS_PER_MICRO: i64 = 1_000;
+const NANOS_PER_MILLI: i64 = 1_000_000;
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Unix时间戳，纳秒精度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Utc::now().into()
+    }
+
+    pub const fn from_secs(secs: i64) -> Self {
+        Self(secs.saturating_mul(NANOS_PER_SEC))
+    }
+
+    pub const fn from_millis(millis: i64) -> Self {
+        Self(millis.saturating_mul(NANOS_PER_MILLI))
+    }
+
+    pub const fn from_micros(micros: i64) -> Self {
+        Self(micros.saturating_mul(NANOS_PER_MICRO))
+    }
+
+    pub const fn from_nanos(nanos: i64) -> Self {
+        Self(nanos)
+    }
+
+    pub const fn as_secs(&self) -> i64 {
+        self.0.div_euclid(NANOS_PER_SEC)
+    }
+
+    pub const fn as_millis(&self) -> i64 {
+        self.0.div_euclid(NANOS_PER_MILLI)
+    }
+
+    pub const fn as_micros(&self) -> i64 {
+        self.0.div_euclid(NANOS_PER_MICRO)
+    }
+
+    pub const fn as_nanos(&self) -> i64 {
+        self.0
+    }
+
+    pub fn to_datetime(&self) -> DateTime<Utc> {
+        Utc.timestamp_nanos(self.0)
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    /// 超出纳秒可表示范围（1677年至2262年之外）时取边界值
+    fn from(value: DateTime<Utc>) -> Self {
+        match value.timestamp_nanos_opt() {
+            Some(nanos) => Self(nanos),
+            None if value.timestamp() < 0 => Self(i64::MIN),
+            None => Self(i64::MAX),
+        }
+    }
+}
+
+impl From<Timestamp> for DateTime<Utc> {
+    fn from(value: Timestamp) -> Self {
+        value.to_datetime()
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_datetime().to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        millis::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        millis::deserialize(deserializer)
+    }
+}
+
+/// 整数按指定单位解析，字符串按RFC3339解析
+struct TimestampVisitor {
+    from_integer: fn(i64) -> Timestamp,
+    unit: &'static str,
+}
+
+impl<'de> de::Visitor<'de> for TimestampVisitor {
+    type Value = Timestamp;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an integer timestamp in {} or an RFC3339 string", self.unit)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Timestamp, E> {
+        Ok((self.from_integer)(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Timestamp, E> {
+        i64::try_from(value)
+            .map(self.from_integer)
+            .map_err(|_| E::custom(format!("timestamp out of range: {}", value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Timestamp, E> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|at| at.with_timezone(&Utc).into())
+            .map_err(|_| E::custom(format!("invalid timestamp \"{}\"", value)))
+    }
+}
+
+macro_rules! integer_format {
+    ($module:ident, $unit:literal, $from:ident, $to:ident) => {
+        #[doc = concat!("序列化为", $unit, "整数，反序列化同时接受RFC3339字符串")]
+        pub mod $module {
+            use super::{Timestamp, TimestampVisitor};
+            use serde::{Deserializer, Serializer};
+
+            pub fn serialize<S: Serializer>(value: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_i64(value.$to())
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+                deserializer.deserialize_any(TimestampVisitor {
+                    from_integer: Timestamp::$from,
+                    unit: $unit,
+                })
+            }
+        }
+    };
+}
+
+integer_format!(secs, "秒", from_secs, as_secs);
+integer_format!(millis, "毫秒", from_millis, as_millis);
+integer_format!(nanos, "纳秒", from_nanos, as_nanos);
+
+/// 序列化为RFC3339字符串，反序列化同时接受毫秒整数
+pub mod rfc3339 {
+    use super::{Timestamp, TimestampVisitor};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        deserializer.deserialize_any(TimestampVisitor {
+            from_integer: Timestamp::from_millis,
+            unit: "毫秒",
+        })
+    }
+}
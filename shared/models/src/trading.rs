@@ -18,14 +18,22 @@ pub struct Order {
     pub side: OrderSide,
     pub order_type: OrderType,
     pub time_in_force: TimeInForce,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub price: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub stop_price: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub iceberg_qty: Option<Decimal>,
     pub status: OrderStatus,
+    #[serde(with = "crate::decimal")]
     pub filled_quantity: Decimal,
+    #[serde(with = "crate::decimal")]
     pub remaining_quantity: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub avg_price: Option<Decimal>,
+    #[serde(with = "crate::decimal")]
     pub commission: Decimal,
     pub commission_asset: String,
     pub created_at: DateTime<Utc>,
@@ -94,9 +102,13 @@ pub struct Trade {
     pub symbol: String,
     pub trade_id: String,
     pub side: OrderSide,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
+    #[serde(with = "crate::decimal")]
     pub price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub quote_quantity: Decimal,
+    #[serde(with = "crate::decimal")]
     pub commission: Decimal,
     pub commission_asset: String,
     pub is_maker: bool,
@@ -112,13 +124,21 @@ pub struct Position {
     pub exchange: Exchange,
     pub symbol: String,
     pub side: PositionSide,
+    #[serde(with = "crate::decimal")]
     pub size: Decimal,
+    #[serde(with = "crate::decimal")]
     pub entry_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub mark_price: Decimal,
+    #[serde(with = "crate::decimal")]
     pub unrealized_pnl: Decimal,
+    #[serde(with = "crate::decimal")]
     pub realized_pnl: Decimal,
+    #[serde(with = "crate::decimal")]
     pub margin: Decimal,
+    #[serde(with = "crate::decimal")]
     pub leverage: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub liquidation_price: Option<Decimal>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -139,8 +159,11 @@ pub struct Balance {
     pub user_id: Uuid,
     pub exchange: Exchange,
     pub asset: String,
+    #[serde(with = "crate::decimal")]
     pub free: Decimal,
+    #[serde(with = "crate::decimal")]
     pub locked: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total: Decimal,
     pub updated_at: DateTime<Utc>,
 }
@@ -154,11 +177,17 @@ pub struct Account {
     pub account_type: AccountType,
     pub status: AccountStatus,
     pub balances: Vec<Balance>,
+    #[serde(with = "crate::decimal")]
     pub total_wallet_balance: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total_unrealized_pnl: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total_margin_balance: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total_position_initial_margin: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total_open_order_initial_margin: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_withdraw_amount: Decimal,
     pub can_trade: bool,
     pub can_withdraw: bool,
@@ -216,9 +245,13 @@ pub struct CreateOrderRequest {
     pub side: OrderSide,
     pub order_type: OrderType,
     pub time_in_force: Option<TimeInForce>,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub price: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub stop_price: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub iceberg_qty: Option<Decimal>,
     pub client_order_id: Option<String>,
     pub reduce_only: Option<bool>,
@@ -229,8 +262,11 @@ pub struct CreateOrderRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModifyOrderRequest {
     pub order_id: Uuid,
+    #[serde(with = "crate::decimal::option", default)]
     pub quantity: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub price: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub stop_price: Option<Decimal>,
 }
 
@@ -257,13 +293,21 @@ pub struct TradingStats {
     pub period_start: DateTime<Utc>,
     pub period_end: DateTime<Utc>,
     pub total_trades: u64,
+    #[serde(with = "crate::decimal")]
     pub total_volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total_quote_volume: Decimal,
+    #[serde(with = "crate::decimal")]
     pub total_commission: Decimal,
+    #[serde(with = "crate::decimal")]
     pub realized_pnl: Decimal,
+    #[serde(with = "crate::decimal")]
     pub win_rate: Decimal,
+    #[serde(with = "crate::decimal")]
     pub profit_factor: Decimal,
+    #[serde(with = "crate::decimal")]
     pub max_drawdown: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub sharpe_ratio: Option<Decimal>,
 }
 
@@ -275,10 +319,15 @@ pub struct TradingSignal {
     pub symbol: String,
     pub side: OrderSide,
     pub signal_type: SignalType,
+    #[serde(with = "crate::decimal")]
     pub strength: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub price: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub quantity: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub stop_loss: Option<Decimal>,
+    #[serde(with = "crate::decimal::option", default)]
     pub take_profit: Option<Decimal>,
     pub timestamp: DateTime<Utc>,
     pub metadata: HashMap<String, serde_json::Value>,
@@ -304,11 +353,17 @@ pub struct ExecutionReport {
     pub side: OrderSide,
     pub order_type: OrderType,
     pub status: OrderStatus,
+    #[serde(with = "crate::decimal")]
     pub quantity: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub price: Option<Decimal>,
+    #[serde(with = "crate::decimal")]
     pub filled_quantity: Decimal,
+    #[serde(with = "crate::decimal")]
     pub remaining_quantity: Decimal,
+    #[serde(with = "crate::decimal::option", default)]
     pub avg_price: Option<Decimal>,
+    #[serde(with = "crate::decimal")]
     pub commission: Decimal,
     pub commission_asset: String,
     pub timestamp: DateTime<Utc>,
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::common::Exchange;
+use crate::common::{CommonError, Exchange};
 
 /// 用户信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +19,9 @@ pub struct User {
     pub email_verified: bool,
     pub phone_verified: bool,
     pub two_factor_enabled: bool,
+    /// KYC 认证等级，决定可用的交易功能
+    #[serde(default)]
+    pub verification_level: VerificationLevel,
     pub roles: Vec<Role>,
     pub preferences: UserPreferences,
     pub created_at: DateTime<Utc>,
@@ -37,6 +40,48 @@ pub enum UserStatus {
     PendingVerification,
 }
 
+/// KYC 认证等级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationLevel {
+    /// 未认证：只读和模拟交易
+    #[default]
+    Unverified,
+    /// 基础认证：限额交易
+    Level1,
+    /// 高级认证：不限额
+    Level2,
+}
+
+impl VerificationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationLevel::Unverified => "unverified",
+            VerificationLevel::Level1 => "level1",
+            VerificationLevel::Level2 => "level2",
+        }
+    }
+}
+
+impl std::fmt::Display for VerificationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for VerificationLevel {
+    type Err = CommonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unverified" => Ok(VerificationLevel::Unverified),
+            "level1" => Ok(VerificationLevel::Level1),
+            "level2" => Ok(VerificationLevel::Level2),
+            _ => Err(CommonError::Validation(format!("Unknown verification level: {}", s))),
+        }
+    }
+}
+
 /// 用户角色
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Role {
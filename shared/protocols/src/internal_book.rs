@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 内部订单簿价格层级，数量为0表示该价位已删除
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl BookLevel {
+    pub fn new(price: Decimal, quantity: Decimal) -> Self {
+        Self { price, quantity }
+    }
+}
+
+/// 内部撮合引擎行情事件
+///
+/// 由交易引擎推送，行情服务作为 INTERNAL 交易所接入。
+/// 每个交易对的快照和增量共用一个递增序号，订阅方发现序号不连续时发送
+/// [`InternalBookRequest::Resync`] 请求重新下发快照。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InternalBookEvent {
+    /// 全量快照
+    Snapshot {
+        symbol: String,
+        sequence: u64,
+        bids: Vec<BookLevel>,
+        asks: Vec<BookLevel>,
+        timestamp: DateTime<Utc>,
+    },
+    /// 增量更新
+    Diff {
+        symbol: String,
+        sequence: u64,
+        bids: Vec<BookLevel>,
+        asks: Vec<BookLevel>,
+        timestamp: DateTime<Utc>,
+    },
+    /// 成交
+    Trade {
+        symbol: String,
+        trade_id: String,
+        price: Decimal,
+        quantity: Decimal,
+        /// 主动方方向，buy 或 sell
+        side: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// 挂单返佣变化，返佣率为挂单成交额的比例，为0表示该侧无返佣
+    MakerRebate {
+        symbol: String,
+        /// 订单簿不平衡度，正数表示买盘较厚
+        imbalance: Decimal,
+        bid_rebate_rate: Decimal,
+        ask_rebate_rate: Decimal,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl InternalBookEvent {
+    pub fn symbol(&self) -> &str {
+        match self {
+            InternalBookEvent::Snapshot { symbol, .. }
+            | InternalBookEvent::Diff { symbol, .. }
+            | InternalBookEvent::Trade { symbol, .. }
+            | InternalBookEvent::MakerRebate { symbol, .. } => symbol,
+        }
+    }
+
+    /// 快照和增量的序号，成交和返佣变化不带序号
+    pub fn sequence(&self) -> Option<u64> {
+        match self {
+            InternalBookEvent::Snapshot { sequence, .. } | InternalBookEvent::Diff { sequence, .. } => {
+                Some(*sequence)
+            }
+            InternalBookEvent::Trade { .. } | InternalBookEvent::MakerRebate { .. } => None,
+        }
+    }
+}
+
+/// 订阅方发给内部行情流的请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InternalBookRequest {
+    /// 请求重新下发快照，未指定交易对时下发全部
+    Resync { symbol: Option<String> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_book_event_roundtrip() {
+        let event = InternalBookEvent::Diff {
+            symbol: "BTCUSDT".to_string(),
+            sequence: 7,
+            bids: vec![BookLevel::new(Decimal::new(50_000, 0), Decimal::ZERO)],
+            asks: Vec::new(),
+            timestamp: Utc::now(),
+        };
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"type\":\"diff\""));
+        let parsed: InternalBookEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.symbol(), "BTCUSDT");
+        assert_eq!(parsed.sequence(), Some(7));
+
+        let request: InternalBookRequest =
+            serde_json::from_str(r#"{"type":"resync","symbol":"BTCUSDT"}"#).unwrap();
+        assert!(matches!(request, InternalBookRequest::Resync { symbol: Some(_) }));
+    }
+}
@@ -49,6 +49,10 @@ impl KafkaTopics {
     pub const MARKET_ORDERBOOK: &'static str = "market.orderbook";
     pub const MARKET_TRADES: &'static str = "market.trades";
     pub const MARKET_TICKER24HR: &'static str = "market.ticker24hr";
+    pub const MARKET_DEPTH_METRICS: &'static str = "market.depth_metrics";
+    pub const MARKET_WHALE_TRADES: &'static str = "market.whale_trades";
+    pub const MARKET_CANDLE_CLOSES: &'static str = "market.candle_closes";
+    pub const MARKET_WS_USAGE: &'static str = "market.ws_usage";
 
     // 交易事件主题
     pub const TRADING_ORDERS: &'static str = "trading.orders";
@@ -89,6 +93,8 @@ pub enum MarketDataEvent {
     OrderBookUpdate(OrderBook),
     TradeUpdate(shared_models::market::Trade),
     Ticker24hrUpdate(Ticker24hr),
+    DepthMetricsUpdate(DepthMetrics),
+    WhaleTradeDetected(WhaleTrade),
 }
 
 /// 交易事件
@@ -324,6 +330,14 @@ impl MessageHandler<MarketDataEvent> for MarketDataHandler {
                 tracing::info!("Received 24hr ticker update: {}", ticker.symbol);
                 // 处理24小时统计更新逻辑
             }
+            MarketDataEvent::DepthMetricsUpdate(metrics) => {
+                tracing::info!("Received depth metrics update: {} microprice {}", metrics.symbol, metrics.microprice);
+                // 处理深度指标更新逻辑
+            }
+            MarketDataEvent::WhaleTradeDetected(whale) => {
+                tracing::info!("Received whale trade: {} {} notional {}", whale.symbol, whale.side, whale.notional);
+                // 处理大额成交逻辑
+            }
         }
         Ok(())
     }
@@ -1,9 +1,11 @@
 pub mod grpc;
 pub mod http;
+pub mod internal_book;
 pub mod kafka;
 pub mod websocket;
 
 pub use grpc::*;
 pub use http::*;
+pub use internal_book::*;
 pub use kafka::*;
 pub use websocket::*;
@@ -0,0 +1,405 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::config_check::ConfigReport;
+use crate::metrics::AppMetrics;
+
+/// 缓存配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// 最大条目数，超出后淘汰最久未使用的条目
+    pub capacity: usize,
+    /// 条目存活时间
+    pub ttl_seconds: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            ttl_seconds: 300,
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_seconds)
+    }
+
+    /// 字段检查
+    pub fn check(&self, path: &str, report: &mut ConfigReport) {
+        report.range(&format!("{}.capacity", path), self.capacity, 1, 10_000_000);
+        report.range(&format!("{}.ttl_seconds", path), self.ttl_seconds, 1, 86_400);
+    }
+}
+
+/// 缓存统计
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// 等待同一键的加载完成后命中的请求
+    pub coalesced: u64,
+    pub loads: u64,
+    pub load_errors: u64,
+    pub capacity_evictions: u64,
+    pub expired: u64,
+    pub entries: usize,
+}
+
+struct Entry<V> {
+    value: V,
+    expires_at: Instant,
+    /// 最近访问序号，对应 `order` 中的键
+    tick: u64,
+}
+
+struct CacheState<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    /// 访问序号 -> 键，最小的为最久未使用
+    order: BTreeMap<u64, K>,
+    next_tick: u64,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// 进程内 LRU + TTL 缓存
+///
+/// 用于读多写少的查询结果（合约信息、用户风控配置、令牌校验等）。
+/// `get_or_try_load` 对同一键的并发未命中只执行一次加载，其余请求等待结果，
+/// 避免缓存失效时大量请求同时打到下游。
+pub struct Cache<K, V> {
+    name: String,
+    config: CacheConfig,
+    state: Mutex<CacheState<K, V>>,
+    /// 正在加载的键
+    inflight: Mutex<HashMap<K, Arc<tokio::sync::Mutex<()>>>>,
+    stats: Mutex<CacheStats>,
+    metrics: Option<Arc<AppMetrics>>,
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new(name: impl Into<String>, config: CacheConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                next_tick: 0,
+            }),
+            inflight: Mutex::new(HashMap::new()),
+            stats: Mutex::new(CacheStats::default()),
+            metrics: None,
+        }
+    }
+
+    /// 上报命中、未命中和淘汰指标
+    pub fn with_metrics(mut self, metrics: Arc<AppMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 读取未过期的条目并刷新访问顺序，不计入统计
+    fn lookup(&self, key: &K) -> Option<V> {
+        let now = Instant::now();
+        let mut state = lock(&self.state);
+        let tick = state.next_tick;
+        let entry = state.entries.get_mut(key)?;
+
+        if entry.expires_at <= now {
+            let old_tick = entry.tick;
+            state.entries.remove(key);
+            state.order.remove(&old_tick);
+            let entries = state.entries.len();
+            drop(state);
+            self.record_eviction("expired", entries);
+            return None;
+        }
+
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let value = entry.value.clone();
+        state.next_tick += 1;
+        state.order.remove(&old_tick);
+        state.order.insert(tick, key.clone());
+        Some(value)
+    }
+
+    /// 查询缓存
+    pub fn get(&self, key: &K) -> Option<V> {
+        let value = self.lookup(key);
+        self.record_request(value.is_some());
+        value
+    }
+
+    /// 写入条目，使用配置的存活时间
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_with_ttl(key, value, self.config.ttl());
+    }
+
+    /// 写入条目，使用指定的存活时间
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) {
+        let mut evicted = 0;
+        let mut state = lock(&self.state);
+        let tick = state.next_tick;
+        state.next_tick += 1;
+
+        let entry = Entry {
+            value,
+            expires_at: Instant::now() + ttl,
+            tick,
+        };
+        if let Some(previous) = state.entries.insert(key.clone(), entry) {
+            state.order.remove(&previous.tick);
+        }
+        state.order.insert(tick, key);
+
+        while state.entries.len() > self.config.capacity.max(1) {
+            let oldest = match state.order.pop_first() {
+                Some((_, oldest)) => oldest,
+                None => break,
+            };
+            state.entries.remove(&oldest);
+            evicted += 1;
+        }
+        let entries = state.entries.len();
+        drop(state);
+
+        for _ in 0..evicted {
+            self.record_eviction("capacity", entries);
+        }
+        self.set_entries(entries);
+    }
+
+    /// 删除条目
+    pub fn invalidate(&self, key: &K) -> Option<V> {
+        let mut state = lock(&self.state);
+        let entry = state.entries.remove(key)?;
+        state.order.remove(&entry.tick);
+        let entries = state.entries.len();
+        drop(state);
+        self.set_entries(entries);
+        Some(entry.value)
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        let mut state = lock(&self.state);
+        state.entries.clear();
+        state.order.clear();
+        drop(state);
+        self.set_entries(0);
+    }
+
+    /// 删除所有过期条目，返回删除数量
+    pub fn purge_expired(&self) -> usize {
+        let now = Instant::now();
+        let mut state = lock(&self.state);
+        let expired: Vec<(K, u64)> = state
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expires_at <= now)
+            .map(|(key, entry)| (key.clone(), entry.tick))
+            .collect();
+        for (key, tick) in &expired {
+            state.entries.remove(key);
+            state.order.remove(tick);
+        }
+        let entries = state.entries.len();
+        drop(state);
+
+        for _ in 0..expired.len() {
+            self.record_eviction("expired", entries);
+        }
+        expired.len()
+    }
+
+    pub fn len(&self) -> usize {
+        lock(&self.state).entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 读取缓存，未命中时调用 `loader` 加载并写入
+    ///
+    /// 同一键同时只有一个加载在执行，其他调用等待后直接读取结果。
+    /// 加载失败不写入缓存，等待中的调用会各自重试。
+    pub async fn get_or_try_load<F, Fut, E>(&self, key: K, loader: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.lookup(&key) {
+            self.record_request(true);
+            return Ok(value);
+        }
+
+        let gate = lock(&self.inflight)
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone();
+        let _guard = gate.lock().await;
+
+        if let Some(value) = self.lookup(&key) {
+            lock(&self.stats).coalesced += 1;
+            self.record_request(true);
+            self.release(&key, &gate);
+            return Ok(value);
+        }
+
+        self.record_request(false);
+        let result = loader().await;
+        match &result {
+            Ok(value) => {
+                lock(&self.stats).loads += 1;
+                self.insert(key.clone(), value.clone());
+            }
+            Err(_) => lock(&self.stats).load_errors += 1,
+        }
+        self.release(&key, &gate);
+        result
+    }
+
+    /// 加载结束后移除键的加载锁，已被替换的锁不动
+    fn release(&self, key: &K, gate: &Arc<tokio::sync::Mutex<()>>) {
+        let mut inflight = lock(&self.inflight);
+        if inflight.get(key).map(|current| Arc::ptr_eq(current, gate)).unwrap_or(false) {
+            inflight.remove(key);
+        }
+    }
+
+    /// 获取统计信息
+    pub fn stats(&self) -> CacheStats {
+        let mut stats = lock(&self.stats).clone();
+        stats.entries = self.len();
+        stats
+    }
+
+    fn record_request(&self, hit: bool) {
+        {
+            let mut stats = lock(&self.stats);
+            if hit {
+                stats.hits += 1;
+            } else {
+                stats.misses += 1;
+            }
+        }
+        if let Some(metrics) = &self.metrics {
+            let _ = metrics.record_cache_request(&self.name, if hit { "hit" } else { "miss" });
+        }
+    }
+
+    fn record_eviction(&self, reason: &str, entries: usize) {
+        {
+            let mut stats = lock(&self.stats);
+            if reason == "expired" {
+                stats.expired += 1;
+            } else {
+                stats.capacity_evictions += 1;
+            }
+        }
+        if let Some(metrics) = &self.metrics {
+            let _ = metrics.record_cache_eviction(&self.name, reason);
+        }
+        self.set_entries(entries);
+    }
+
+    fn set_entries(&self, entries: usize) {
+        if let Some(metrics) = &self.metrics {
+            let _ = metrics.set_cache_entries(&self.name, entries as i64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn cache(capacity: usize) -> Cache<String, u32> {
+        Cache::new(
+            "test",
+            CacheConfig {
+                capacity,
+                ttl_seconds: 60,
+            },
+        )
+    }
+
+    #[test]
+    fn test_lru_and_ttl() {
+        let cache = cache(2);
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 2);
+        // 访问 a 后 b 成为最久未使用
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        cache.insert("c".to_string(), 3);
+        assert_eq!(cache.get(&"b".to_string()), None);
+        assert_eq!(cache.get(&"a".to_string()), Some(1));
+        assert_eq!(cache.get(&"c".to_string()), Some(3));
+
+        cache.insert_with_ttl("d".to_string(), 4, Duration::ZERO);
+        assert_eq!(cache.get(&"d".to_string()), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.capacity_evictions, 2);
+        assert_eq!(stats.expired, 1);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_single_flight_load() {
+        let cache = Arc::new(cache(10));
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let mut handles = Vec::new();
+        for _ in 0..8 {
+            let cache = cache.clone();
+            let calls = calls.clone();
+            handles.push(tokio::spawn(async move {
+                cache
+                    .get_or_try_load("k".to_string(), || async {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        Ok::<_, String>(7)
+                    })
+                    .await
+            }));
+        }
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), Ok(7));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let failed = cache
+            .get_or_try_load("e".to_string(), || async { Err::<u32, _>("down".to_string()) })
+            .await;
+        assert!(failed.is_err());
+        assert_eq!(cache.get(&"e".to_string()), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.loads, 1);
+        assert_eq!(stats.load_errors, 1);
+        assert_eq!(stats.coalesced, 7);
+    }
+}
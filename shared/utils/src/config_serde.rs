@@ -0,0 +1,309 @@
+//! 服务配置共用的反序列化辅助
+//!
+//! 配置来自 TOML 文件、默认值和环境变量三种来源，同一个字段可能是
+//! 字符串（环境变量）、整数或浮点数（TOML）。这里的辅助函数统一接受
+//! 这些表示，保证覆盖配置时的行为一致。
+
+/// 带单位的时长字段，例如 "500ms"、"30s"、"5m"、"1h30m"、"1d"
+///
+/// 用法：`#[serde(with = "shared_utils::config_serde::duration")]`。
+/// 不带单位的数字会被拒绝，避免把 "30" 误当成毫秒或秒。
+pub mod duration {
+    use serde::{de, Deserializer, Serializer};
+    use std::fmt;
+    use std::time::Duration;
+
+    const UNITS: &str = "ms, s, m, h, d";
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        deserializer.deserialize_any(DurationVisitor)
+    }
+
+    /// 格式化为最大的整数单位
+    pub fn format(value: &Duration) -> String {
+        let millis = value.as_millis();
+        if millis == 0 {
+            return "0s".to_string();
+        }
+        for (unit, size) in [("d", 86_400_000), ("h", 3_600_000), ("m", 60_000), ("s", 1000)] {
+            if millis % size == 0 {
+                return format!("{}{}", millis / size, unit);
+            }
+        }
+        format!("{}ms", millis)
+    }
+
+    fn unit_seconds(unit: &str) -> Option<f64> {
+        match unit {
+            "ms" | "msec" => Some(0.001),
+            "s" | "sec" | "secs" => Some(1.0),
+            "m" | "min" | "mins" => Some(60.0),
+            "h" | "hr" | "hrs" => Some(3600.0),
+            "d" | "day" | "days" => Some(86_400.0),
+            _ => None,
+        }
+    }
+
+    /// 解析带单位的时长，支持 "1h30m"、"1h 30m" 这样的组合
+    pub fn parse(value: &str) -> Result<Duration, String> {
+        let text = value.trim();
+        if text.is_empty() {
+            return Err("empty duration, expected e.g. \"30s\"".to_string());
+        }
+        if text.parse::<f64>().is_ok() {
+            return Err(format!(
+                "duration \"{}\" is missing a unit, did you mean \"{}s\"? (supported: {})",
+                text, text, UNITS
+            ));
+        }
+
+        let mut seconds = 0.0;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let number_end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let (number, tail) = rest.split_at(number_end);
+            let unit_end = tail
+                .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+                .unwrap_or(tail.len());
+            let (unit, tail) = tail.split_at(unit_end);
+
+            let number: f64 = number
+                .parse()
+                .map_err(|_| format!("invalid duration \"{}\", expected e.g. \"30s\"", text))?;
+            let scale = unit_seconds(unit).ok_or_else(|| {
+                format!(
+                    "unknown duration unit \"{}\" in \"{}\" (supported: {})",
+                    unit, text, UNITS
+                )
+            })?;
+            seconds += number * scale;
+            rest = tail.trim_start();
+        }
+        Ok(Duration::from_secs_f64(seconds))
+    }
+
+    fn missing_unit<E: de::Error>(value: impl fmt::Display) -> E {
+        E::custom(format!(
+            "duration {} is missing a unit, did you mean \"{}s\"? (supported: {})",
+            value, value, UNITS
+        ))
+    }
+
+    struct DurationVisitor;
+
+    impl<'de> de::Visitor<'de> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a duration with a unit, e.g. \"30s\"")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Duration, E> {
+            parse(value).map_err(E::custom)
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Duration, E> {
+            Err(missing_unit(value))
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Duration, E> {
+            Err(missing_unit(value))
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Duration, E> {
+            Err(missing_unit(value))
+        }
+    }
+
+    /// 值为带单位时长的映射
+    pub mod map {
+        use serde::{de::Error, Deserialize, Deserializer, Serializer};
+        use std::collections::HashMap;
+        use std::time::Duration;
+
+        pub fn serialize<S: Serializer>(
+            value: &HashMap<String, Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(value.iter().map(|(k, v)| (k, super::format(v))))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<HashMap<String, Duration>, D::Error> {
+            let raw = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+            raw.into_iter()
+                .map(|(key, value)| {
+                    let text = match &value {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    super::parse(&text)
+                        .map(|duration| (key.clone(), duration))
+                        .map_err(|e| D::Error::custom(format!("{}: {}", key, e)))
+                })
+                .collect()
+        }
+    }
+}
+
+/// 精确小数字段，接受 "0.01"、0.01 和 1 三种写法
+///
+/// 字符串按原样精确解析；浮点数按其最短十进制表示解析，
+/// 不会引入二进制误差。序列化为字符串以保留精度。
+/// 用法：`#[serde(with = "shared_utils::config_serde::decimal")]`。
+pub mod decimal {
+    use rust_decimal::Decimal;
+    use serde::{de, Deserializer, Serializer};
+    use std::fmt;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.normalize().to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+
+    /// 解析小数，支持科学计数法
+    pub fn parse(value: &str) -> Result<Decimal, String> {
+        let text = value.trim().replace('_', "");
+        Decimal::from_str(&text)
+            .or_else(|_| Decimal::from_scientific(&text))
+            .map_err(|_| format!("invalid decimal \"{}\"", value))
+    }
+
+    struct DecimalVisitor;
+
+    impl<'de> de::Visitor<'de> for DecimalVisitor {
+        type Value = Decimal;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a decimal number or numeric string, e.g. \"0.01\"")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+            parse(value).map_err(E::custom)
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+            Ok(Decimal::from(value))
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+            Ok(Decimal::from(value))
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+            if !value.is_finite() {
+                return Err(E::custom(format!("invalid decimal {}", value)));
+            }
+            // f64 的 Display 是能还原该值的最短十进制表示
+            parse(&value.to_string()).map_err(E::custom)
+        }
+    }
+
+    /// 可选的精确小数字段
+    pub mod option {
+        use rust_decimal::Decimal;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(value: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => super::serialize(value, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+            #[derive(Deserialize)]
+            struct Wrapper(#[serde(with = "super")] Decimal);
+
+            Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(value)| value))
+        }
+    }
+
+    /// 值为精确小数的映射
+    pub mod map {
+        use rust_decimal::Decimal;
+        use serde::{Deserialize, Deserializer, Serializer};
+        use std::collections::HashMap;
+
+        pub fn serialize<S: Serializer>(
+            value: &HashMap<String, Decimal>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(value.iter().map(|(k, v)| (k, v.normalize().to_string())))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<HashMap<String, Decimal>, D::Error> {
+            #[derive(Deserialize)]
+            struct Wrapper(#[serde(with = "super")] Decimal);
+
+            Ok(HashMap::<String, Wrapper>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(key, Wrapper(value))| (key, value))
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Sample {
+        #[serde(with = "duration")]
+        timeout: Duration,
+        #[serde(with = "decimal")]
+        slippage: Decimal,
+        #[serde(default, with = "decimal::option")]
+        max_size: Option<Decimal>,
+    }
+
+    #[test]
+    fn test_duration_units() {
+        assert_eq!(duration::parse("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(duration::parse("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(duration::parse("1h 30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(duration::parse("1h30m15s").unwrap(), Duration::from_secs(5415));
+        assert!(duration::parse("30").unwrap_err().contains("\"30s\""));
+        assert!(duration::parse("3w").is_err());
+        assert_eq!(duration::format(&Duration::from_secs(7200)), "2h");
+        assert_eq!(duration::format(&Duration::from_millis(1500)), "1500ms");
+    }
+
+    #[test]
+    fn test_decimal_sources() {
+        // 环境变量覆盖时所有值都是字符串
+        let from_env: Sample =
+            serde_json::from_value(serde_json::json!({"timeout": "30s", "slippage": "0.01", "max_size": "5"}))
+                .unwrap();
+        let from_toml: Sample =
+            serde_json::from_value(serde_json::json!({"timeout": "30s", "slippage": 0.01, "max_size": 5}))
+                .unwrap();
+        assert_eq!(from_env.slippage, Decimal::new(1, 2));
+        assert_eq!(from_env.slippage, from_toml.slippage);
+        assert_eq!(from_env.max_size, from_toml.max_size);
+
+        let missing: Sample = serde_json::from_value(serde_json::json!({"timeout": "1s", "slippage": "1e-3"})).unwrap();
+        assert_eq!(missing.max_size, None);
+        assert_eq!(missing.slippage, Decimal::new(1, 3));
+        assert_eq!(serde_json::to_value(&missing).unwrap()["slippage"], "0.001");
+
+        assert!(serde_json::from_value::<Sample>(serde_json::json!({"timeout": "1s", "slippage": "abc"})).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use shared_models::decimal::{self, DecimalFormat, DECIMAL_FORMAT_HEADER};
+
+/// 按请求选择小数输出格式的中间件
+///
+/// 默认输出字符串；请求头 `x-decimal-format: number` 或查询参数
+/// `decimal_format=number` 时，本次响应中共享模型的小数输出为JSON数字。
+pub async fn decimal_format_middleware(request: Request, next: Next) -> Response {
+    let format = DecimalFormat::from_request(
+        request
+            .headers()
+            .get(DECIMAL_FORMAT_HEADER)
+            .and_then(|value| value.to_str().ok()),
+        request.uri().query(),
+    );
+
+    if format == DecimalFormat::String {
+        return next.run(request).await;
+    }
+    decimal::scope(format, next.run(request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use shared_models::market::OrderBookLevel;
+
+    #[tokio::test]
+    async fn test_decimal_format_scope() {
+        let level = OrderBookLevel {
+            price: Decimal::new(123_450, 4),
+            quantity: Decimal::ONE,
+        };
+        assert_eq!(
+            serde_json::to_string(&level).unwrap(),
+            r#"{"price":"12.345","quantity":"1"}"#
+        );
+
+        let numeric = decimal::scope(DecimalFormat::Number, async {
+            serde_json::to_string(&level).unwrap()
+        })
+        .await;
+        assert_eq!(numeric, r#"{"price":12.345,"quantity":1.0}"#);
+
+        // 两种格式都能反序列化
+        let parsed: OrderBookLevel = serde_json::from_str(&numeric).unwrap();
+        assert_eq!(parsed.price, level.price);
+        let parsed: OrderBookLevel = serde_json::from_str(r#"{"price":"1e-2","quantity":"5"}"#).unwrap();
+        assert_eq!(parsed.price, Decimal::new(1, 2));
+    }
+
+    #[test]
+    fn test_decimal_format_from_request() {
+        assert_eq!(DecimalFormat::from_request(Some("number"), None), DecimalFormat::Number);
+        assert_eq!(
+            DecimalFormat::from_request(None, Some("symbol=BTCUSDT&decimal_format=number")),
+            DecimalFormat::Number
+        );
+        assert_eq!(
+            DecimalFormat::from_request(Some("string"), Some("decimal_format=number")),
+            DecimalFormat::String
+        );
+        assert_eq!(DecimalFormat::from_request(None, Some("decimal_format=bogus")), DecimalFormat::String);
+    }
+}
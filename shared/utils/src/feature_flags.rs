@@ -0,0 +1,312 @@
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::config_serde::duration;
+
+/// 平台内已知的功能开关名
+pub mod flags {
+    /// 新版智能订单路由
+    pub const SMART_ORDER_ROUTING_V2: &str = "smart_order_routing_v2";
+    /// AI信号自动下单
+    pub const AI_SIGNAL_EXECUTION: &str = "ai_signal_execution";
+}
+
+/// 开关规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FlagRule {
+    /// 全量开启或关闭
+    Boolean { enabled: bool },
+    /// 按用户稳定分桶灰度，0-100
+    Percentage { percent: f64 },
+    /// 指定用户开启，其余用户按比例灰度
+    Users {
+        #[serde(default)]
+        allow: HashSet<String>,
+        #[serde(default)]
+        deny: HashSet<String>,
+        #[serde(default)]
+        percent: f64,
+    },
+}
+
+/// 功能开关
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    pub name: String,
+    #[serde(flatten)]
+    pub rule: FlagRule,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FeatureFlag {
+    pub fn new(name: &str, rule: FlagRule) -> Self {
+        Self {
+            name: name.to_string(),
+            rule,
+            description: String::new(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// 针对用户计算开关状态，未提供用户时只有全量开启才返回 true
+    pub fn evaluate(&self, user_id: Option<&str>) -> bool {
+        match &self.rule {
+            FlagRule::Boolean { enabled } => *enabled,
+            FlagRule::Percentage { percent } => self.in_rollout(user_id, *percent),
+            FlagRule::Users {
+                allow,
+                deny,
+                percent,
+            } => match user_id {
+                Some(user) if deny.contains(user) => false,
+                Some(user) if allow.contains(user) => true,
+                _ => self.in_rollout(user_id, *percent),
+            },
+        }
+    }
+
+    fn in_rollout(&self, user_id: Option<&str>, percent: f64) -> bool {
+        if percent >= 100.0 {
+            return true;
+        }
+        if percent <= 0.0 {
+            return false;
+        }
+        match user_id {
+            Some(user) => (rollout_bucket(&self.name, user) as f64) < percent * 100.0,
+            None => false,
+        }
+    }
+}
+
+/// 用户在某个开关下的稳定分桶，范围 0..10000
+///
+/// 分桶同时取决于开关名，不同开关的灰度用户互不相关。
+pub fn rollout_bucket(flag: &str, user_id: &str) -> u32 {
+    let digest = Sha256::digest(format!("{}:{}", flag, user_id).as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % 10_000
+}
+
+/// 功能开关配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureFlagConfig {
+    /// 为空时只使用配置中的默认值
+    pub redis_url: Option<String>,
+    /// 存放开关的Redis哈希键，字段为开关名，值为JSON
+    pub redis_key: String,
+    /// 从Redis刷新的间隔
+    #[serde(with = "duration")]
+    pub refresh_interval: Duration,
+    /// 配置中的默认开关，Redis中同名开关优先
+    pub defaults: HashMap<String, FlagRule>,
+}
+
+impl Default for FeatureFlagConfig {
+    fn default() -> Self {
+        Self {
+            redis_url: None,
+            redis_key: "feature_flags".to_string(),
+            refresh_interval: Duration::from_secs(15),
+            defaults: HashMap::new(),
+        }
+    }
+}
+
+/// 功能开关客户端
+///
+/// 开关在本地缓存，读取不访问网络；后台任务定期从Redis刷新，
+/// 因此修改Redis中的开关后各服务在一个刷新周期内生效，无需重新部署。
+/// 未定义的开关视为关闭。
+#[derive(Clone)]
+pub struct FeatureFlags {
+    config: FeatureFlagConfig,
+    flags: Arc<RwLock<HashMap<String, FeatureFlag>>>,
+    redis: Option<ConnectionManager>,
+}
+
+impl FeatureFlags {
+    /// 只使用配置默认值的开关客户端
+    pub fn from_config(config: FeatureFlagConfig) -> Self {
+        let flags = Self::defaults(&config);
+        Self {
+            config,
+            flags: Arc::new(RwLock::new(flags)),
+            redis: None,
+        }
+    }
+
+    /// 连接Redis并加载开关，连接失败时退回配置默认值
+    pub async fn connect(config: FeatureFlagConfig) -> Self {
+        let mut client = Self::from_config(config);
+        let Some(url) = client.config.redis_url.clone() else {
+            return client;
+        };
+
+        let connection = match redis::Client::open(url.as_str()) {
+            Ok(redis_client) => ConnectionManager::new(redis_client).await,
+            Err(e) => Err(e),
+        };
+        match connection {
+            Ok(connection) => {
+                client.redis = Some(connection);
+                if let Err(e) = client.refresh().await {
+                    tracing::warn!("Failed to load feature flags from Redis: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Feature flags fall back to config defaults: {}", e),
+        }
+        client
+    }
+
+    fn defaults(config: &FeatureFlagConfig) -> HashMap<String, FeatureFlag> {
+        config
+            .defaults
+            .iter()
+            .map(|(name, rule)| (name.clone(), FeatureFlag::new(name, rule.clone())))
+            .collect()
+    }
+
+    /// 从Redis重新加载开关，返回加载的开关数
+    pub async fn refresh(&self) -> redis::RedisResult<usize> {
+        let Some(mut redis) = self.redis.clone() else {
+            return Ok(0);
+        };
+        let raw: HashMap<String, String> = redis.hgetall(&self.config.redis_key).await?;
+
+        let mut flags = Self::defaults(&self.config);
+        let mut loaded = 0;
+        for (name, value) in raw {
+            match serde_json::from_str::<FeatureFlag>(&value) {
+                Ok(mut flag) => {
+                    flag.name = name.clone();
+                    flags.insert(name, flag);
+                    loaded += 1;
+                }
+                Err(e) => tracing::warn!("Ignoring invalid feature flag {}: {}", name, e),
+            }
+        }
+        *self.flags.write().await = flags;
+        Ok(loaded)
+    }
+
+    /// 启动后台刷新任务
+    pub fn start_refresh(&self) {
+        if self.redis.is_none() {
+            return;
+        }
+        let client = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(client.config.refresh_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = client.refresh().await {
+                    tracing::warn!("Feature flag refresh failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// 全局开关状态
+    pub async fn is_enabled(&self, name: &str) -> bool {
+        self.evaluate(name, None).await
+    }
+
+    /// 针对用户的开关状态
+    pub async fn is_enabled_for(&self, name: &str, user_id: &str) -> bool {
+        self.evaluate(name, Some(user_id)).await
+    }
+
+    async fn evaluate(&self, name: &str, user_id: Option<&str>) -> bool {
+        self.flags
+            .read()
+            .await
+            .get(name)
+            .is_some_and(|flag| flag.evaluate(user_id))
+    }
+
+    /// 当前所有开关
+    pub async fn list(&self) -> Vec<FeatureFlag> {
+        let mut flags: Vec<FeatureFlag> = self.flags.read().await.values().cloned().collect();
+        flags.sort_by(|a, b| a.name.cmp(&b.name));
+        flags
+    }
+
+    /// 写入开关，有Redis时同步写入以便其他服务刷新后生效
+    pub async fn set(&self, mut flag: FeatureFlag) -> redis::RedisResult<()> {
+        flag.updated_at = Utc::now();
+        if let Some(mut redis) = self.redis.clone() {
+            let value = serde_json::to_string(&flag).map_err(|e| {
+                redis::RedisError::from((redis::ErrorKind::TypeError, "serialize", e.to_string()))
+            })?;
+            redis.hset::<_, _, _, ()>(&self.config.redis_key, &flag.name, value).await?;
+        }
+        self.flags.write().await.insert(flag.name.clone(), flag);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules() {
+        let on = FeatureFlag::new("a", FlagRule::Boolean { enabled: true });
+        assert!(on.evaluate(None));
+
+        let half = FeatureFlag::new("b", FlagRule::Percentage { percent: 50.0 });
+        assert!(!half.evaluate(None));
+        let enabled = (0..1000)
+            .filter(|i| half.evaluate(Some(&format!("user-{}", i))))
+            .count();
+        assert!((400..600).contains(&enabled));
+        // 同一用户结果稳定
+        assert_eq!(half.evaluate(Some("user-1")), half.evaluate(Some("user-1")));
+
+        let targeted = FeatureFlag::new(
+            "c",
+            FlagRule::Users {
+                allow: ["alice".to_string()].into_iter().collect(),
+                deny: ["bob".to_string()].into_iter().collect(),
+                percent: 100.0,
+            },
+        );
+        assert!(targeted.evaluate(Some("alice")));
+        assert!(!targeted.evaluate(Some("bob")));
+        assert!(targeted.evaluate(Some("carol")));
+    }
+
+    #[tokio::test]
+    async fn test_config_defaults() {
+        let mut config = FeatureFlagConfig::default();
+        config.defaults.insert(
+            flags::AI_SIGNAL_EXECUTION.to_string(),
+            FlagRule::Boolean { enabled: true },
+        );
+        let client = FeatureFlags::from_config(config);
+        assert!(client.is_enabled(flags::AI_SIGNAL_EXECUTION).await);
+        assert!(!client.is_enabled(flags::SMART_ORDER_ROUTING_V2).await);
+
+        client
+            .set(FeatureFlag::new(flags::SMART_ORDER_ROUTING_V2, FlagRule::Percentage { percent: 100.0 }))
+            .await
+            .unwrap();
+        assert!(client.is_enabled_for(flags::SMART_ORDER_ROUTING_V2, "u1").await);
+
+        let json = serde_json::to_string(&client.list().await[1]).unwrap();
+        assert!(json.contains("\"type\":\"percentage\""));
+    }
+}
@@ -0,0 +1,372 @@
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config_serde::duration;
+
+/// 领导者选举配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeaderElectionConfig {
+    /// 为空时视为单副本部署，本实例总是领导者
+    pub redis_url: Option<String>,
+    pub key_prefix: String,
+    /// 租约时长，持有期间每三分之一租约续期一次
+    #[serde(with = "duration")]
+    pub lease_ttl: Duration,
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        Self {
+            redis_url: None,
+            key_prefix: "leader".to_string(),
+            lease_ttl: Duration::from_secs(30),
+        }
+    }
+}
+
+/// 获取或续期租约：已持有时续期并返回原令牌，无人持有时递增令牌后获取，否则返回 -1
+const ACQUIRE_SCRIPT: &str = r#"
+local holder = redis.call('HGET', KEYS[1], 'holder')
+if holder == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return tonumber(redis.call('HGET', KEYS[1], 'token'))
+end
+if holder then
+    return -1
+end
+local token = redis.call('INCR', KEYS[2])
+redis.call('HSET', KEYS[1], 'holder', ARGV[1], 'token', token)
+redis.call('PEXPIRE', KEYS[1], ARGV[2])
+return token
+"#;
+
+/// 只续期自己持有且令牌未变的租约
+const RENEW_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], 'holder') == ARGV[1]
+    and redis.call('HGET', KEYS[1], 'token') == ARGV[3] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+/// 仍持有租约且令牌未变时返回 1
+const VERIFY_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], 'holder') == ARGV[1]
+    and redis.call('HGET', KEYS[1], 'token') == ARGV[2] then
+    return 1
+end
+return 0
+"#;
+
+/// 数据库中每个任务已见过的最大令牌，与受保护的写入在同一事务中更新
+pub const FENCE_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS leader_fences (
+        job TEXT PRIMARY KEY,
+        token BIGINT NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+"#;
+
+/// 登记令牌，已登记更大的令牌时不返回行；冲突行在事务结束前保持锁定
+const FENCE_SQL: &str = r#"
+    INSERT INTO leader_fences (job, token, updated_at) VALUES ($1, $2, NOW())
+    ON CONFLICT (job) DO UPDATE SET token = EXCLUDED.token, updated_at = NOW()
+    WHERE leader_fences.token <= EXCLUDED.token
+    RETURNING token
+"#;
+
+const RELEASE_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], 'holder') == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// 单例任务的领导者租约
+///
+/// 令牌在每次易主时单调递增，可作为防护令牌：下游写入时带上令牌，
+/// 拒绝比已见过的令牌更小的写入，避免失去租约的旧领导者覆盖数据。
+/// 租约在释放前由后台任务续期，续期失败时 `is_valid` 返回 false。
+pub struct Lease {
+    pub job: String,
+    pub token: u64,
+    fence: Fence,
+    renewal: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Lease {
+    /// 租约仍然有效，长任务在提交结果前应检查
+    pub fn is_valid(&self) -> bool {
+        self.fence.is_valid()
+    }
+
+    /// 交给下游写入路径的防护令牌
+    pub fn fence(&self) -> Fence {
+        self.fence.clone()
+    }
+}
+
+/// 租约的防护令牌
+///
+/// 写入Postgres时用 `guard_pg` 在同一事务中登记令牌，旧令牌的事务被拒绝；
+/// 不支持事务的存储在每次写入前用 `verify` 向Redis确认仍持有租约。
+/// 单副本模式下没有其他写入方，只检查租约是否有效。
+#[derive(Clone)]
+pub struct Fence {
+    job: String,
+    token: u64,
+    valid: Arc<AtomicBool>,
+    redis: Option<FenceRedis>,
+}
+
+#[derive(Clone)]
+struct FenceRedis {
+    connection: ConnectionManager,
+    lease_key: String,
+    instance_id: String,
+}
+
+impl Fence {
+    pub fn job(&self) -> &str {
+        &self.job
+    }
+
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid.load(Ordering::SeqCst)
+    }
+
+    /// 向Redis确认租约仍由本实例以该令牌持有，失败时标记租约失效
+    pub async fn verify(&self) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+        let Some(redis) = &self.redis else {
+            return true;
+        };
+        let mut connection = redis.connection.clone();
+        let held: redis::RedisResult<i64> = redis::Script::new(VERIFY_SCRIPT)
+            .key(&redis.lease_key)
+            .arg(&redis.instance_id)
+            .arg(self.token)
+            .invoke_async(&mut connection)
+            .await;
+        if !matches!(held, Ok(1)) {
+            tracing::warn!("Fencing token {} for {} is no longer current", self.token, self.job);
+            self.valid.store(false, Ordering::SeqCst);
+            return false;
+        }
+        true
+    }
+
+    /// 在写入事务中登记令牌，已有更大的令牌时返回 false，调用方应回滚
+    ///
+    /// 表结构见 [`FENCE_SCHEMA`]。同一任务的事务在令牌行上串行执行，
+    /// 新领导者的写入会等待旧领导者的事务结束后再读取数据。
+    pub async fn guard_pg(&self, conn: &mut sqlx::PgConnection) -> sqlx::Result<bool> {
+        if !self.is_valid() {
+            return Ok(false);
+        }
+        if self.redis.is_none() {
+            return Ok(true);
+        }
+        let accepted = sqlx::query(FENCE_SQL)
+            .bind(&self.job)
+            .bind(self.token as i64)
+            .fetch_optional(&mut *conn)
+            .await?
+            .is_some();
+        if !accepted {
+            tracing::warn!("Rejected write from {} with stale fencing token {}", self.job, self.token);
+            self.valid.store(false, Ordering::SeqCst);
+        }
+        Ok(accepted)
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        if let Some(renewal) = self.renewal.take() {
+            renewal.abort();
+        }
+    }
+}
+
+/// 基于Redis租约的领导者选举
+///
+/// 多副本部署时，定时任务在每次执行前调用 `acquire`，
+/// 只有拿到租约的副本执行。租约在任务结束后保留到过期，
+/// 同一副本下次执行时直接续期，领导者不会在副本间频繁切换。
+#[derive(Clone)]
+pub struct LeaderElection {
+    config: LeaderElectionConfig,
+    instance_id: String,
+    redis: Option<ConnectionManager>,
+    /// 单副本模式下的本地令牌
+    local_token: Arc<AtomicU64>,
+}
+
+impl LeaderElection {
+    /// 不连接Redis，本实例总是领导者
+    pub fn single_instance(config: LeaderElectionConfig) -> Self {
+        Self {
+            config,
+            instance_id: Self::new_instance_id(),
+            redis: None,
+            local_token: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 连接Redis，连接失败时返回错误而不是退回单副本模式，避免多个副本同时执行
+    pub async fn connect(config: LeaderElectionConfig) -> redis::RedisResult<Self> {
+        let Some(url) = config.redis_url.clone() else {
+            return Ok(Self::single_instance(config));
+        };
+        let client = redis::Client::open(url.as_str())?;
+        let redis = ConnectionManager::new(client).await?;
+        let mut election = Self::single_instance(config);
+        election.redis = Some(redis);
+        Ok(election)
+    }
+
+    fn new_instance_id() -> String {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "local".to_string());
+        format!("{}-{}", host, uuid::Uuid::new_v4().simple())
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    fn lease_key(&self, job: &str) -> String {
+        format!("{}:{}", self.config.key_prefix, job)
+    }
+
+    fn token_key(&self, job: &str) -> String {
+        format!("{}:{}:token", self.config.key_prefix, job)
+    }
+
+    fn ttl_millis(&self) -> u64 {
+        self.config.lease_ttl.as_millis().max(1000) as u64
+    }
+
+    /// 尝试成为任务的领导者，其他副本持有租约时返回 None
+    pub async fn acquire(&self, job: &str) -> Option<Lease> {
+        let Some(mut redis) = self.redis.clone() else {
+            let token = self.local_token.fetch_add(1, Ordering::SeqCst) + 1;
+            return Some(Lease {
+                job: job.to_string(),
+                token,
+                fence: Fence {
+                    job: job.to_string(),
+                    token,
+                    valid: Arc::new(AtomicBool::new(true)),
+                    redis: None,
+                },
+                renewal: None,
+            });
+        };
+
+        let result: redis::RedisResult<i64> = redis::Script::new(ACQUIRE_SCRIPT)
+            .key(self.lease_key(job))
+            .key(self.token_key(job))
+            .arg(&self.instance_id)
+            .arg(self.ttl_millis())
+            .invoke_async(&mut redis)
+            .await;
+
+        let token = match result {
+            Ok(token) if token > 0 => token as u64,
+            Ok(_) => return None,
+            Err(e) => {
+                tracing::warn!("Leader election for {} failed: {}", job, e);
+                return None;
+            }
+        };
+
+        let valid = Arc::new(AtomicBool::new(true));
+        let renewal = self.spawn_renewal(job, token, valid.clone());
+        Some(Lease {
+            job: job.to_string(),
+            token,
+            fence: Fence {
+                job: job.to_string(),
+                token,
+                valid,
+                redis: Some(FenceRedis {
+                    connection: redis,
+                    lease_key: self.lease_key(job),
+                    instance_id: self.instance_id.clone(),
+                }),
+            },
+            renewal: Some(renewal),
+        })
+    }
+
+    fn spawn_renewal(&self, job: &str, token: u64, valid: Arc<AtomicBool>) -> tokio::task::JoinHandle<()> {
+        let election = self.clone();
+        let job = job.to_string();
+        tokio::spawn(async move {
+            let Some(mut redis) = election.redis.clone() else {
+                return;
+            };
+            let mut ticker = tokio::time::interval(election.config.lease_ttl / 3);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let renewed: redis::RedisResult<i64> = redis::Script::new(RENEW_SCRIPT)
+                    .key(election.lease_key(&job))
+                    .arg(&election.instance_id)
+                    .arg(election.ttl_millis())
+                    .arg(token)
+                    .invoke_async(&mut redis)
+                    .await;
+                if !matches!(renewed, Ok(1)) {
+                    tracing::warn!("Lost leadership of {} (token {})", job, token);
+                    valid.store(false, Ordering::SeqCst);
+                    return;
+                }
+            }
+        })
+    }
+
+    /// 主动放弃领导者身份，例如服务关闭时
+    pub async fn release(&self, job: &str) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let result: redis::RedisResult<i64> = redis::Script::new(RELEASE_SCRIPT)
+            .key(self.lease_key(job))
+            .arg(&self.instance_id)
+            .invoke_async(&mut redis)
+            .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to release leadership of {}: {}", job, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_single_instance_is_always_leader() {
+        let election = LeaderElection::connect(LeaderElectionConfig::default()).await.unwrap();
+        let first = election.acquire("retention").await.unwrap();
+        let second = election.acquire("retention").await.unwrap();
+        assert!(first.is_valid());
+        assert!(second.token > first.token);
+
+        let fence = second.fence();
+        assert_eq!(fence.token(), second.token);
+        assert!(fence.verify().await);
+    }
+}
@@ -1,19 +1,42 @@
 pub mod auth;
+pub mod cache;
 pub mod config;
+pub mod config_check;
+pub mod config_serde;
 pub mod crypto;
+pub mod decimal_format;
 pub mod error;
+pub mod feature_flags;
 pub mod http;
+pub mod internal_auth;
+pub mod leader;
 pub mod logging;
 pub mod metrics;
+pub mod request_trace;
+pub mod retry;
+pub mod sharding;
 pub mod time;
 pub mod validation;
 
 pub use auth::*;
+pub use cache::{Cache, CacheConfig, CacheStats};
 pub use config::*;
+pub use config_check::{
+    check_config_requested, deserialize_checked, run_config_check, ConfigIssue, ConfigReport,
+    ConfigSeverity, CHECK_CONFIG_FLAG,
+};
 pub use crypto::*;
+pub use decimal_format::decimal_format_middleware;
 pub use error::*;
+pub use feature_flags::{FeatureFlag, FeatureFlagConfig, FeatureFlags, FlagRule};
 pub use http::*;
+pub use internal_auth::{
+    internal_auth_middleware, InternalAuth, InternalAuthConfig, InternalPrincipal, InternalRole,
+};
+pub use leader::{Fence, LeaderElection, LeaderElectionConfig, Lease};
 pub use logging::*;
 pub use metrics::*;
+pub use request_trace::{request_trace_routes, RequestLogBuffer, RequestLogEntry, RequestTrace};
+pub use retry::{Jitter, RetryConfig, RetryPolicy};
 pub use time::*;
 pub use validation::*;
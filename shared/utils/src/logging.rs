@@ -1,20 +1,62 @@
 use anyhow::Result;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderValue, StatusCode,
+    },
+    middleware::Next,
+    response::Response,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::io;
-use tracing::{Event, Subscriber};
-use tracing_appender::{non_blocking, rolling};
+use std::sync::{Arc, RwLock};
+use tracing::{Event, Instrument, Subscriber};
+use tracing_appender::{non_blocking, non_blocking::NonBlocking, rolling};
 use tracing_subscriber::{
-    fmt::{self, format::Writer, time::FormatTime, FmtContext, FormatEvent, FormatFields},
-    EnvFilter,
+    fmt::{
+        self,
+        format::Writer,
+        time::FormatTime,
+        writer::{BoxMakeWriter, MakeWriterExt},
+        FmtContext, FormatEvent, FormatFields, FormattedFields,
+    },
+    layer::{Layered, SubscriberExt},
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
 };
 
+use crate::request_trace::{RequestLogBuffer, RequestLogLayer};
+
+/// 选择日志格式的环境变量：pretty / compact / json
+pub const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+/// 请求ID请求头
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 补充请求ID时读取的错误响应体上限
+const MAX_ERROR_BODY_BYTES: usize = 1024 * 1024;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
 /// 日志配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LoggingConfig {
     pub level: String,
     pub format: LogFormat,
     pub output: LogOutput,
     pub file_config: Option<FileConfig>,
+    /// 按模块覆盖日志级别，如 `market_data::connectors = "debug"`
+    pub modules: BTreeMap<String, String>,
 }
 
 impl Default for LoggingConfig {
@@ -24,21 +66,78 @@ impl Default for LoggingConfig {
             format: LogFormat::Pretty,
             output: LogOutput::Stdout,
             file_config: None,
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// 转换为 EnvFilter 指令
+    pub fn directives(&self) -> String {
+        log_directives(&self.level, &self.modules)
+    }
+}
+
+/// 服务配置中的日志级别，启动后通过 [`LogLevelHandle::apply`] 生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogLevelConfig {
+    pub level: String,
+    /// 按模块覆盖日志级别
+    pub modules: BTreeMap<String, String>,
+}
+
+impl Default for LogLevelConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            modules: BTreeMap::new(),
         }
     }
 }
 
+impl LogLevelConfig {
+    /// 检查指令能否解析
+    pub fn check(&self, path: &str, report: &mut crate::config_check::ConfigReport) {
+        if let Err(e) = EnvFilter::try_new(log_directives(&self.level, &self.modules)) {
+            report.error(path, format!("invalid log level directives: {}", e));
+        }
+    }
+}
+
+/// 由默认级别和模块级别生成 EnvFilter 指令
+pub fn log_directives(level: &str, modules: &BTreeMap<String, String>) -> String {
+    let mut directives = vec![level.to_string()];
+    directives.extend(modules.iter().map(|(module, level)| format!("{}={}", module, level)));
+    directives.join(",")
+}
+
 /// 日志格式
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LogFormat {
     Pretty,
     Compact,
+    /// 每行一个JSON对象，包含当前span和span链上的字段
     Json,
     Custom,
 }
 
+impl LogFormat {
+    fn from_env() -> Option<Self> {
+        match std::env::var(LOG_FORMAT_ENV).ok()?.to_lowercase().as_str() {
+            "pretty" => Some(LogFormat::Pretty),
+            "compact" => Some(LogFormat::Compact),
+            "json" => Some(LogFormat::Json),
+            "custom" => Some(LogFormat::Custom),
+            _ => None,
+        }
+    }
+}
+
 /// 日志输出
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LogOutput {
     Stdout,
     Stderr,
@@ -47,7 +146,7 @@ pub enum LogOutput {
 }
 
 /// 文件配置
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileConfig {
     pub directory: String,
     pub filename_prefix: String,
@@ -56,7 +155,8 @@ pub struct FileConfig {
 }
 
 /// 文件轮转
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FileRotation {
     Never,
     Minutely,
@@ -64,122 +164,109 @@ pub enum FileRotation {
     Daily,
 }
 
+type FilteredRegistry = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+
+/// 运行时日志级别控制
+///
+/// 初始化时返回，替换过滤器后立即生效，无需重启。
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    handle: reload::Handle<EnvFilter, Registry>,
+    directives: Arc<RwLock<String>>,
+    request_logs: RequestLogBuffer,
+}
+
+impl LogLevelHandle {
+    /// 最近请求的日志，供按请求ID查询
+    pub fn request_logs(&self) -> RequestLogBuffer {
+        self.request_logs.clone()
+    }
+
+    /// 当前生效的过滤指令
+    pub fn current(&self) -> String {
+        self.directives
+            .read()
+            .map(|directives| directives.clone())
+            .unwrap_or_default()
+    }
+
+    /// 替换过滤指令，指令无效时保持原过滤器
+    pub fn set(&self, directives: &str) -> Result<()> {
+        let filter = EnvFilter::try_new(directives)?;
+        self.handle.reload(filter)?;
+        if let Ok(mut current) = self.directives.write() {
+            *current = directives.to_string();
+        }
+        tracing::info!(directives = directives, "Log filter updated");
+        Ok(())
+    }
+
+    /// 应用配置中的级别，设置了 RUST_LOG 时以环境变量为准
+    pub fn apply(&self, level: &str, modules: &BTreeMap<String, String>) -> Result<()> {
+        if std::env::var(EnvFilter::DEFAULT_ENV).is_ok() {
+            return Ok(());
+        }
+        self.set(&log_directives(level, modules))
+    }
+}
+
 /// 日志初始化器
 pub struct LoggingInitializer;
 
 impl LoggingInitializer {
     /// 初始化日志系统
-    pub fn init(config: LoggingConfig) -> Result<()> {
-        let env_filter =
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.level));
-
-        match config.output {
-            LogOutput::Stdout => match config.format {
-                LogFormat::Pretty => {
-                    tracing_subscriber::fmt()
-                        .pretty()
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-                LogFormat::Compact => {
-                    tracing_subscriber::fmt()
-                        .compact()
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-                LogFormat::Json => {
-                    tracing_subscriber::fmt()
-                        .json()
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-                LogFormat::Custom => {
-                    tracing_subscriber::fmt()
-                        .event_format(CustomFormatter::new())
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-            },
-            LogOutput::Stderr => match config.format {
-                LogFormat::Pretty => {
-                    tracing_subscriber::fmt()
-                        .pretty()
-                        .with_writer(std::io::stderr)
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-                LogFormat::Compact => {
-                    tracing_subscriber::fmt()
-                        .compact()
-                        .with_writer(std::io::stderr)
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-                LogFormat::Json => {
-                    tracing_subscriber::fmt()
-                        .json()
-                        .with_writer(std::io::stderr)
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-                LogFormat::Custom => {
-                    tracing_subscriber::fmt()
-                        .event_format(CustomFormatter::new())
-                        .with_writer(std::io::stderr)
-                        .with_env_filter(env_filter)
-                        .init();
-                }
-            },
-            LogOutput::File => {
-                if let Some(file_config) = config.file_config {
-                    let file_appender = Self::create_file_appender(&file_config);
-                    let (non_blocking, _guard) = non_blocking(file_appender);
-                    std::mem::forget(_guard); // 防止guard被丢弃
-
-                    match config.format {
-                        LogFormat::Pretty => {
-                            tracing_subscriber::fmt()
-                                .pretty()
-                                .with_writer(non_blocking)
-                                .with_env_filter(env_filter)
-                                .init();
-                        }
-                        LogFormat::Compact => {
-                            tracing_subscriber::fmt()
-                                .compact()
-                                .with_writer(non_blocking)
-                                .with_env_filter(env_filter)
-                                .init();
-                        }
-                        LogFormat::Json => {
-                            tracing_subscriber::fmt()
-                                .json()
-                                .with_writer(non_blocking)
-                                .with_env_filter(env_filter)
-                                .init();
-                        }
-                        LogFormat::Custom => {
-                            tracing_subscriber::fmt()
-                                .event_format(CustomFormatter::new())
-                                .with_writer(non_blocking)
-                                .with_env_filter(env_filter)
-                                .init();
-                        }
-                    }
-                } else {
-                    return Err(anyhow::anyhow!("File config is required for file output"));
-                }
-            }
-            LogOutput::Both => {
-                // 简化实现：使用stdout输出
-                tracing_subscriber::fmt()
-                    .pretty()
-                    .with_env_filter(env_filter)
-                    .init();
-            }
-        }
+    pub fn init(config: LoggingConfig) -> Result<LogLevelHandle> {
+        let directives = std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_else(|_| config.directives());
+        let (filter, handle) = reload::Layer::new(EnvFilter::try_new(&directives)?);
+
+        let writer = Self::create_writer(&config)?;
+        let fmt_layer: Box<dyn Layer<FilteredRegistry> + Send + Sync> = match config.format {
+            LogFormat::Pretty => fmt::layer().pretty().with_writer(writer).boxed(),
+            LogFormat::Compact => fmt::layer().compact().with_writer(writer).boxed(),
+            LogFormat::Json => fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .with_writer(writer)
+                .boxed(),
+            LogFormat::Custom => fmt::layer()
+                .event_format(CustomFormatter::new())
+                .with_writer(writer)
+                .boxed(),
+        };
 
-        Ok(())
+        let request_logs = RequestLogBuffer::default();
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(fmt_layer)
+            .with(RequestLogLayer::new(request_logs.clone()))
+            .try_init()?;
+
+        Ok(LogLevelHandle {
+            handle,
+            directives: Arc::new(RwLock::new(directives)),
+            request_logs,
+        })
+    }
+
+    /// 创建输出
+    fn create_writer(config: &LoggingConfig) -> Result<BoxMakeWriter> {
+        Ok(match config.output {
+            LogOutput::Stdout => BoxMakeWriter::new(io::stdout),
+            LogOutput::Stderr => BoxMakeWriter::new(io::stderr),
+            LogOutput::File => BoxMakeWriter::new(Self::create_file_writer(config)?),
+            LogOutput::Both => BoxMakeWriter::new(io::stdout.and(Self::create_file_writer(config)?)),
+        })
+    }
+
+    fn create_file_writer(config: &LoggingConfig) -> Result<NonBlocking> {
+        let file_config = config
+            .file_config
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("File config is required for file output"))?;
+        let (non_blocking, guard) = non_blocking(Self::create_file_appender(file_config));
+        std::mem::forget(guard); // 防止guard被丢弃
+        Ok(non_blocking)
     }
 
     /// 创建文件appender
@@ -202,19 +289,28 @@ impl LoggingInitializer {
     }
 
     /// 快速初始化（开发环境）
-    pub fn init_dev() -> Result<()> {
+    pub fn init_dev() -> Result<LogLevelHandle> {
         let config = LoggingConfig {
             level: "debug".to_string(),
-            format: LogFormat::Pretty,
-            output: LogOutput::Stdout,
-            file_config: None,
+            ..Default::default()
         };
 
         Self::init(config)
     }
 
+    /// 按 LOG_FORMAT 初始化：json 用于生产环境，未设置时同开发环境
+    pub fn init_from_env() -> Result<LogLevelHandle> {
+        match LogFormat::from_env() {
+            Some(format) if format != LogFormat::Pretty => Self::init(LoggingConfig {
+                format,
+                ..Default::default()
+            }),
+            _ => Self::init_dev(),
+        }
+    }
+
     /// 快速初始化（生产环境）
-    pub fn init_prod(log_dir: &str) -> Result<()> {
+    pub fn init_prod(log_dir: &str) -> Result<LogLevelHandle> {
         let config = LoggingConfig {
             level: "info".to_string(),
             format: LogFormat::Json,
@@ -225,12 +321,162 @@ impl LoggingInitializer {
                 rotation: FileRotation::Daily,
                 max_files: Some(30),
             }),
+            modules: BTreeMap::new(),
         };
 
         Self::init(config)
     }
 }
 
+/// 请求日志上下文
+///
+/// 每个请求在 `request` span 中处理，span 带有 request_id、method、path、user_id 和 symbol，
+/// JSON 日志中的每条事件都会附带这些字段。认证或解析出交易对后可以补充记录。
+pub struct LogContext;
+
+impl LogContext {
+    /// 记录当前请求的用户
+    pub fn record_user(user_id: &str) {
+        tracing::Span::current().record("user_id", user_id);
+    }
+
+    /// 记录当前请求的交易对
+    pub fn record_symbol(symbol: &str) {
+        tracing::Span::current().record("symbol", symbol);
+    }
+}
+
+/// 当前请求的ID，在 [`request_span_middleware`] 处理的请求中可用，调用下游服务时应随请求头转发
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+/// 为每个请求创建日志span的中间件
+///
+/// 沿用 `x-request-id`，没有时生成并写回请求头，便于下游和响应使用同一个ID。
+/// 响应头带上请求ID；JSON错误响应（`success: false`）中未填写 `request_id` 时补上。
+pub async fn request_span_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = match request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(request_id) => request_id.to_string(),
+        None => {
+            let request_id = uuid::Uuid::new_v4().to_string();
+            if let Ok(value) = request_id.parse() {
+                request.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
+            request_id
+        }
+    };
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        user_id = tracing::field::Empty,
+        symbol = tracing::field::Empty,
+    );
+    if let Some(user_id) = request.headers().get("x-user-id").and_then(|v| v.to_str().ok()) {
+        span.record("user_id", user_id);
+    }
+    if let Some(symbol) = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("symbol="))
+    }) {
+        span.record("symbol", symbol);
+    }
+
+    let response = REQUEST_ID
+        .scope(request_id.clone(), next.run(request).instrument(span))
+        .await;
+    attach_request_id(response, &request_id).await
+}
+
+/// 在响应头和JSON错误响应体中写入请求ID
+async fn attach_request_id(response: Response, request_id: &str) -> Response {
+    let (mut parts, body) = response.into_parts();
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        parts.headers.insert(REQUEST_ID_HEADER, value);
+    }
+
+    let is_json_error = (parts.status.is_client_error() || parts.status.is_server_error())
+        && parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json_error {
+        return Response::from_parts(parts, body);
+    }
+
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to read error response body: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut map))
+            if map.get("success") == Some(&serde_json::Value::Bool(false))
+                && matches!(map.get("request_id"), None | Some(serde_json::Value::Null)) =>
+        {
+            map.insert("request_id".to_string(), json!(request_id));
+            parts.headers.remove(CONTENT_LENGTH);
+            Body::from(serde_json::to_vec(&map).unwrap_or_else(|_| bytes.to_vec()))
+        }
+        _ => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
+/// 日志级别修改请求，`directives` 优先于 `level` 和 `modules`
+#[derive(Debug, Deserialize)]
+pub struct LogLevelUpdate {
+    pub directives: Option<String>,
+    pub level: Option<String>,
+    #[serde(default)]
+    pub modules: BTreeMap<String, String>,
+}
+
+async fn get_log_level(State(handle): State<LogLevelHandle>) -> Json<serde_json::Value> {
+    Json(json!({ "directives": handle.current() }))
+}
+
+async fn set_log_level(
+    State(handle): State<LogLevelHandle>,
+    Json(update): Json<LogLevelUpdate>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let directives = match (update.directives, update.level) {
+        (Some(directives), _) => directives,
+        (None, Some(level)) => log_directives(&level, &update.modules),
+        (None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "either directives or level is required".to_string(),
+            ))
+        }
+    };
+    handle
+        .set(&directives)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid log directives: {}", e)))?;
+    Ok(Json(json!({ "directives": handle.current() })))
+}
+
+/// 查看和修改日志级别的管理路由，需挂在内部认证保护的路径下
+pub fn log_level_routes<S>(path: &str, handle: LogLevelHandle) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route(path, get(get_log_level).put(set_log_level))
+        .with_state(handle)
+}
+
 /// 自定义格式化器
 pub struct CustomFormatter {
     timer: fmt::time::SystemTime,
@@ -273,10 +519,17 @@ where
         let target = event.metadata().target();
         write!(writer, "[{}] ", target)?;
 
-        // Span信息
+        // Span信息，带上字段以便按 request_id 检索
         if let Some(scope) = ctx.event_scope() {
             for span in scope.from_root() {
-                write!(writer, "{}:", span.name())?;
+                write!(writer, "{}", span.name())?;
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>() {
+                    if !fields.is_empty() {
+                        write!(writer, "{{{}}}", fields)?;
+                    }
+                }
+                writer.write_char(':')?;
             }
             writer.write_char(' ')?;
         }
@@ -526,6 +779,57 @@ mod tests {
         assert_eq!(data["nested"]["amount"], 500.0);
     }
 
+    #[test]
+    fn test_log_directives() {
+        let mut config = LoggingConfig::default();
+        assert_eq!(config.directives(), "info");
+
+        config.modules.insert("trading_engine::engines".to_string(), "debug".to_string());
+        config.modules.insert("hyper".to_string(), "warn".to_string());
+        assert_eq!(config.directives(), "info,hyper=warn,trading_engine::engines=debug");
+        assert!(EnvFilter::try_new(config.directives()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_in_error_responses() {
+        use axum::response::IntoResponse;
+
+        let seen = REQUEST_ID
+            .scope("req-42".to_string(), async { current_request_id() })
+            .await;
+        assert_eq!(seen.as_deref(), Some("req-42"));
+        assert!(current_request_id().is_none());
+
+        let error = (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "success": false, "error": { "code": "INVALID" }, "request_id": null })),
+        )
+            .into_response();
+        let response = attach_request_id(error, "req-42").await;
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-42");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["request_id"], "req-42");
+        assert_eq!(body["error"]["code"], "INVALID");
+
+        // 成功响应和已有请求ID的错误响应只加响应头
+        let ok = Json(json!({ "success": true })).into_response();
+        let response = attach_request_id(ok, "req-43").await;
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-43");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"success":true}"#);
+
+        let upstream = (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "success": false, "request_id": "req-1" })),
+        )
+            .into_response();
+        let response = attach_request_id(upstream, "req-44").await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["request_id"], "req-1");
+    }
+
     #[test]
     fn test_sensitive_field_detection() {
         assert!(LogFilter::is_sensitive_field("password"));
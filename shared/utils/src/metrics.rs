@@ -250,6 +250,20 @@ impl AppMetrics {
         collector.register_counter_vec("trading_volume", "Trading volume", &["symbol", "exchange"])?;
         collector.register_gauge_vec("account_balance", "Account balance", &["user_id", "asset"])?;
         collector.register_int_gauge_vec("active_positions", "Active positions", &["user_id", "symbol"])?;
+        collector.register_int_counter_vec("risk_rejections_total", "Orders rejected by risk checks", &["check", "symbol"])?;
+
+        // 缓存指标
+        collector.register_int_counter_vec("cache_requests_total", "Cache lookups", &["cache", "result"])?;
+        collector.register_int_counter_vec("cache_evictions_total", "Cache evictions", &["cache", "reason"])?;
+        collector.register_int_gauge_vec("cache_entries", "Cache entries", &["cache"])?;
+
+        // 分级存储写入指标
+        collector.register_int_counter_vec("storage_writes_total", "Storage writes by durability class", &["class", "outcome"])?;
+        collector.register_histogram_vec("storage_write_ack_seconds", "Time until a storage write is acknowledged", &["class"], prometheus::DEFAULT_BUCKETS.to_vec())?;
+        collector.register_int_gauge("storage_wal_pending", "Durable writes in the WAL not yet persisted")?;
+
+        // 数据库连接池指标
+        collector.register_int_gauge_vec("db_pool_connections", "Database pool connections by state", &["pool", "state"])?;
 
         // 系统指标
         collector.register_gauge("memory_usage_bytes", "Memory usage in bytes")?;
@@ -287,6 +301,54 @@ impl AppMetrics {
         Ok(())
     }
 
+    /// 记录风控拒单
+    pub fn record_risk_rejection(&self, check: &str, symbol: &str) -> Result<()> {
+        self.collector.inc_counter_vec("risk_rejections_total", &[check, symbol])?;
+        Ok(())
+    }
+
+    /// 记录缓存查询，result 为 hit 或 miss
+    pub fn record_cache_request(&self, cache: &str, result: &str) -> Result<()> {
+        self.collector.inc_counter_vec("cache_requests_total", &[cache, result])?;
+        Ok(())
+    }
+
+    /// 记录缓存淘汰，reason 为 capacity 或 expired
+    pub fn record_cache_eviction(&self, cache: &str, reason: &str) -> Result<()> {
+        self.collector.inc_counter_vec("cache_evictions_total", &[cache, reason])?;
+        Ok(())
+    }
+
+    /// 设置缓存条目数
+    pub fn set_cache_entries(&self, cache: &str, count: i64) -> Result<()> {
+        self.collector.int_gauge_vecs.get("cache_entries").unwrap().with_label_values(&[cache]).set(count);
+        Ok(())
+    }
+
+    /// 记录存储写入，outcome 为 accepted、persisted、failed 或 dropped
+    pub fn record_storage_write(&self, class: &str, outcome: &str, count: u64) -> Result<()> {
+        self.collector.int_counter_vecs.get("storage_writes_total").unwrap().with_label_values(&[class, outcome]).inc_by(count);
+        Ok(())
+    }
+
+    /// 记录存储写入的确认延迟
+    pub fn record_storage_write_ack(&self, class: &str, duration: Duration) -> Result<()> {
+        self.collector.observe_histogram_vec("storage_write_ack_seconds", &[class], duration.as_secs_f64())?;
+        Ok(())
+    }
+
+    /// 设置预写日志中待补写的条数
+    pub fn set_storage_wal_pending(&self, count: i64) -> Result<()> {
+        self.collector.int_gauges.get("storage_wal_pending").unwrap().set(count);
+        Ok(())
+    }
+
+    /// 设置数据库连接池的连接数，state 为 in_use、idle 或 max
+    pub fn set_db_pool_connections(&self, pool: &str, state: &str, count: i64) -> Result<()> {
+        self.collector.int_gauge_vecs.get("db_pool_connections").unwrap().with_label_values(&[pool, state]).set(count);
+        Ok(())
+    }
+
     /// 记录交易量
     pub fn record_trading_volume(&self, symbol: &str, exchange: &str, volume: f64) -> Result<()> {
         self.collector.counter_vecs.get("trading_volume").unwrap().with_label_values(&[symbol, exchange]).inc_by(volume);
@@ -0,0 +1,278 @@
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// 保留日志的最大请求数，超出后淘汰最早的请求
+const MAX_TRACED_REQUESTS: usize = 10_000;
+
+/// 单个请求保留的最大日志条数
+const MAX_ENTRIES_PER_REQUEST: usize = 200;
+
+/// 请求内的一条日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// 按请求ID查询的日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestTrace {
+    pub request_id: String,
+    pub entries: Vec<RequestLogEntry>,
+    /// 超出单请求上限未保留的条数
+    pub truncated: usize,
+}
+
+#[derive(Default)]
+struct TracedRequest {
+    entries: Vec<RequestLogEntry>,
+    truncated: usize,
+}
+
+#[derive(Default)]
+struct BufferState {
+    requests: HashMap<String, TracedRequest>,
+    order: VecDeque<String>,
+}
+
+/// 最近请求的日志缓冲
+///
+/// 只在内存中保留最近的请求，用于排查单个请求在各服务中的处理过程，不替代日志收集。
+#[derive(Clone)]
+pub struct RequestLogBuffer {
+    state: Arc<Mutex<BufferState>>,
+    max_requests: usize,
+    max_entries: usize,
+}
+
+impl Default for RequestLogBuffer {
+    fn default() -> Self {
+        Self::new(MAX_TRACED_REQUESTS, MAX_ENTRIES_PER_REQUEST)
+    }
+}
+
+impl RequestLogBuffer {
+    pub fn new(max_requests: usize, max_entries: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BufferState::default())),
+            max_requests: max_requests.max(1),
+            max_entries,
+        }
+    }
+
+    fn push(&self, request_id: &str, entry: RequestLogEntry) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if !state.requests.contains_key(request_id) {
+            while state.order.len() >= self.max_requests {
+                if let Some(oldest) = state.order.pop_front() {
+                    state.requests.remove(&oldest);
+                }
+            }
+            state.order.push_back(request_id.to_string());
+        }
+
+        let request = state.requests.entry(request_id.to_string()).or_default();
+        if request.entries.len() < self.max_entries {
+            request.entries.push(entry);
+        } else {
+            request.truncated += 1;
+        }
+    }
+
+    /// 请求的日志，按记录顺序
+    pub fn get(&self, request_id: &str) -> RequestTrace {
+        let (entries, truncated) = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|state| {
+                state
+                    .requests
+                    .get(request_id)
+                    .map(|request| (request.entries.clone(), request.truncated))
+            })
+            .unwrap_or_default();
+
+        RequestTrace {
+            request_id: request_id.to_string(),
+            entries,
+            truncated,
+        }
+    }
+}
+
+/// 记录在span扩展中的请求ID
+struct SpanRequestId(String);
+
+#[derive(Default)]
+struct RequestIdVisitor(Option<String>);
+
+impl Visit for RequestIdVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "request_id" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "request_id" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+/// 把带有 request_id 的span中产生的日志写入缓冲
+pub struct RequestLogLayer {
+    buffer: RequestLogBuffer,
+}
+
+impl RequestLogLayer {
+    pub fn new(buffer: RequestLogBuffer) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S> Layer<S> for RequestLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = RequestIdVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(request_id), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(SpanRequestId(request_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        let Some(request_id) = scope
+            .from_root()
+            .find_map(|span| span.extensions().get::<SpanRequestId>().map(|id| id.0.clone()))
+        else {
+            return;
+        };
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.buffer.push(
+            &request_id,
+            RequestLogEntry {
+                timestamp: Utc::now(),
+                level: metadata.level().to_string(),
+                target: metadata.target().to_string(),
+                message: visitor.message,
+                fields: visitor.fields,
+            },
+        );
+    }
+}
+
+async fn get_request_trace(
+    State(buffer): State<RequestLogBuffer>,
+    Path(request_id): Path<String>,
+) -> Json<RequestTrace> {
+    Json(buffer.get(&request_id))
+}
+
+/// 按请求ID查询本服务日志的管理路由，路径需包含 `:request_id`，挂在内部认证保护的路径下
+pub fn request_trace_routes<S>(path: &str, buffer: RequestLogBuffer) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route(path, get(get_request_trace))
+        .with_state(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_request_log_capture() {
+        let buffer = RequestLogBuffer::new(2, 2);
+        let subscriber = tracing_subscriber::registry().with(RequestLogLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request_id = "req-1";
+            let span = tracing::info_span!("request", request_id = %request_id);
+            let _guard = span.enter();
+            tracing::info!(order_id = "o-1", "Order accepted");
+            tracing::debug_span!("inner").in_scope(|| tracing::warn!("Slow venue"));
+            tracing::info!("Dropped by per-request cap");
+        });
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(RequestLogLayer::new(buffer.clone())),
+            || tracing::info!("Outside any request"),
+        );
+
+        let trace = buffer.get("req-1");
+        assert_eq!(trace.entries.len(), 2);
+        assert_eq!(trace.truncated, 1);
+        assert_eq!(trace.entries[0].message, "Order accepted");
+        assert_eq!(trace.entries[0].fields.get("order_id").map(String::as_str), Some("o-1"));
+        assert_eq!(trace.entries[1].level, "WARN");
+
+        // 超出请求数上限时淘汰最早的请求
+        for request_id in ["req-2", "req-3"] {
+            buffer.push(
+                request_id,
+                RequestLogEntry {
+                    timestamp: Utc::now(),
+                    level: "INFO".to_string(),
+                    target: "test".to_string(),
+                    message: String::new(),
+                    fields: BTreeMap::new(),
+                },
+            );
+        }
+        assert!(buffer.get("req-1").entries.is_empty());
+        assert_eq!(buffer.get("req-3").entries.len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+//! 重试与退避
+//!
+//! 各服务的重连、请求重试和后台任务失败退避统一使用 [`RetryPolicy`]：
+//! 指数退避并封顶，按 [`Jitter`] 加入随机抖动，可限制重试次数和总耗时，
+//! 由调用方提供的判断函数决定错误是否值得重试。
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::config_serde::duration;
+
+/// 随机抖动方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Jitter {
+    /// 不加抖动
+    None,
+    /// 在 [0, 退避时间) 内均匀取值
+    #[default]
+    Full,
+    /// 一半固定、一半随机，保证最短等待时间
+    Equal,
+}
+
+/// 重试配置，嵌入各服务的配置文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// 第一次重试前的退避时间
+    #[serde(with = "duration")]
+    pub initial_delay: Duration,
+    /// 单次退避时间上限
+    #[serde(with = "duration")]
+    pub max_delay: Duration,
+    /// 每次失败后退避时间的倍数，小于1时按1处理
+    pub multiplier: f64,
+    pub jitter: Jitter,
+    /// 最多重试次数（不含首次执行），不设置时不限
+    pub max_retries: Option<u32>,
+    /// 从首次执行开始的总耗时上限，0 表示不限
+    #[serde(with = "duration")]
+    pub max_elapsed: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: Jitter::Full,
+            max_retries: Some(3),
+            max_elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn policy(&self) -> RetryPolicy {
+        let policy = RetryPolicy::exponential(self.initial_delay, self.max_delay)
+            .with_multiplier(self.multiplier)
+            .with_jitter(self.jitter);
+        let policy = match self.max_retries {
+            Some(max_retries) => policy.with_max_retries(max_retries),
+            None => policy,
+        };
+        if self.max_elapsed.is_zero() {
+            policy
+        } else {
+            policy.with_max_elapsed(self.max_elapsed)
+        }
+    }
+}
+
+/// [0, 1) 内的随机数，用于退避抖动
+pub fn random_jitter() -> f64 {
+    rand::random::<f64>()
+}
+
+/// 重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    jitter: Jitter,
+    max_retries: Option<u32>,
+    max_elapsed: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// 从 `initial_delay` 开始每次翻倍、最长 `max_delay` 的退避，默认全抖动且不限次数
+    pub fn exponential(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            max_delay,
+            multiplier: 2.0,
+            jitter: Jitter::Full,
+            max_retries: None,
+            max_elapsed: None,
+        }
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// 第 `attempt` 次重试（从0开始）前未加抖动的退避时间
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt.min(64) as i32);
+        let delay = self.initial_delay.as_secs_f64() * factor;
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()).max(0.0))
+    }
+
+    /// 第 `attempt` 次重试前的退避时间，`random` 取值 [0, 1)
+    pub fn delay_with_jitter(&self, attempt: u32, random: f64) -> Duration {
+        let base = self.base_delay(attempt);
+        let random = random.clamp(0.0, 1.0);
+        match self.jitter {
+            Jitter::None => base,
+            Jitter::Full => base.mul_f64(random),
+            Jitter::Equal => base / 2 + (base / 2).mul_f64(random),
+        }
+    }
+
+    /// 第 `attempt` 次重试前的退避时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.delay_with_jitter(attempt, random_jitter())
+    }
+
+    /// 已重试 `retries` 次、耗时 `elapsed` 后是否还能再等待 `delay` 重试一次
+    pub fn allows(&self, retries: u32, elapsed: Duration, delay: Duration) -> bool {
+        if self.max_retries.is_some_and(|max| retries >= max) {
+            return false;
+        }
+        !self.max_elapsed.is_some_and(|max| elapsed + delay > max)
+    }
+
+    /// 执行操作，失败时按策略重试，返回最后一次的错误
+    pub async fn retry<F, Fut, T, E>(&self, operation: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: std::fmt::Display,
+    {
+        self.retry_if(operation, |_| true).await
+    }
+
+    /// 执行操作，只重试 `is_retryable` 返回 true 的错误
+    pub async fn retry_if<F, Fut, T, E, P>(&self, mut operation: F, is_retryable: P) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        P: Fn(&E) -> bool,
+        E: std::fmt::Display,
+    {
+        let started = Instant::now();
+        let mut retries = 0;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let delay = self.delay(retries);
+                    if !is_retryable(&e) || !self.allows(retries, started.elapsed(), delay) {
+                        return Err(e);
+                    }
+                    retries += 1;
+                    debug!("Operation failed, retrying in {:?} (attempt {}): {}", delay, retries, e);
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::exponential(Duration::from_secs(1), Duration::from_secs(10));
+        assert_eq!(policy.base_delay(0), Duration::from_secs(1));
+        assert_eq!(policy.base_delay(3), Duration::from_secs(8));
+        assert_eq!(policy.base_delay(u32::MAX), Duration::from_secs(10));
+
+        assert_eq!(policy.delay_with_jitter(2, 0.5), Duration::from_secs(2));
+        assert!(policy.delay(2) < Duration::from_secs(4));
+
+        let equal = policy.clone().with_jitter(Jitter::Equal);
+        assert_eq!(equal.delay_with_jitter(2, 0.0), Duration::from_secs(2));
+        assert_eq!(equal.delay_with_jitter(2, 0.5), Duration::from_secs(3));
+
+        let none = policy.with_jitter(Jitter::None).with_multiplier(3.0);
+        assert_eq!(none.delay_with_jitter(2, 0.3), Duration::from_secs(9));
+
+        let jitter = random_jitter();
+        assert!((0.0..1.0).contains(&jitter));
+    }
+
+    #[test]
+    fn test_allows() {
+        let policy = RetryPolicy::exponential(Duration::from_secs(1), Duration::from_secs(10))
+            .with_max_retries(2)
+            .with_max_elapsed(Duration::from_secs(5));
+        assert!(policy.allows(1, Duration::ZERO, Duration::from_secs(1)));
+        assert!(!policy.allows(2, Duration::ZERO, Duration::from_secs(1)));
+        assert!(!policy.allows(0, Duration::from_secs(4), Duration::from_secs(2)));
+
+        let config = RetryConfig {
+            max_retries: None,
+            ..RetryConfig::default()
+        };
+        assert!(config.policy().allows(1000, Duration::from_secs(3600), Duration::from_secs(30)));
+    }
+
+    #[tokio::test]
+    async fn test_retry_if() {
+        let policy = RetryPolicy::exponential(Duration::from_millis(1), Duration::from_millis(5))
+            .with_max_retries(3);
+
+        let attempts = AtomicU32::new(0);
+        let result: Result<u32, String> = policy
+            .retry(|| async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err("temporary".to_string()),
+                    n => Ok(n),
+                }
+            })
+            .await;
+        assert_eq!(result, Ok(2));
+
+        // 不可重试的错误直接返回
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), String> = policy
+            .retry_if(
+                || async {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err("fatal".to_string())
+                },
+                |e| e != "fatal",
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        // 超过次数上限后返回最后一次错误
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), String> = policy
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err("temporary".to_string())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+}
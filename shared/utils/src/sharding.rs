@@ -0,0 +1,233 @@
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 默认的Redis键前缀
+pub const DEFAULT_SHARD_PREFIX: &str = "market_data:shards";
+
+/// 分片分配中的交易对键，例如 binance:BTCUSDT
+pub fn shard_key(exchange: &str, symbol: &str) -> String {
+    format!("{}:{}", exchange.to_lowercase(), symbol.to_uppercase())
+}
+
+/// 跨进程稳定的64位哈希（FNV-1a加splitmix64扰动）
+///
+/// 标准库的哈希每次启动随机化，不能用于多实例间一致的分片。
+pub fn stable_hash(value: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in value.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// 带虚拟节点的一致性哈希环
+///
+/// 实例增减时只有落在变化区间内的交易对会迁移。
+#[derive(Debug, Clone, Default)]
+pub struct HashRing {
+    ring: BTreeMap<u64, String>,
+}
+
+impl HashRing {
+    pub fn new<'a>(members: impl IntoIterator<Item = &'a str>, virtual_nodes: usize) -> Self {
+        let mut ring = BTreeMap::new();
+        for member in members {
+            for replica in 0..virtual_nodes.max(1) {
+                ring.insert(stable_hash(&format!("{}#{}", member, replica)), member.to_string());
+            }
+        }
+        Self { ring }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// 键所属的实例
+    pub fn owner(&self, key: &str) -> Option<&str> {
+        let hash = stable_hash(key);
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, member)| member.as_str())
+    }
+}
+
+/// 分片成员
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardMember {
+    pub instance_id: String,
+    /// 对外服务地址，网关按此地址转发
+    pub url: String,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// 由协调者发布的分片分配
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShardAssignments {
+    /// 每次分配变化时递增
+    pub version: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub members: Vec<ShardMember>,
+    /// 交易对键 -> 实例ID
+    pub assignments: HashMap<String, String>,
+}
+
+impl ShardAssignments {
+    /// 按一致性哈希计算分配
+    pub fn compute(
+        members: Vec<ShardMember>,
+        keys: impl IntoIterator<Item = String>,
+        virtual_nodes: usize,
+    ) -> Self {
+        let ring = HashRing::new(members.iter().map(|m| m.instance_id.as_str()), virtual_nodes);
+        let assignments = keys
+            .into_iter()
+            .filter_map(|key| {
+                let owner = ring.owner(&key)?.to_string();
+                Some((key, owner))
+            })
+            .collect();
+        Self {
+            version: 0,
+            updated_at: None,
+            members,
+            assignments,
+        }
+    }
+
+    /// 两次分配的成员和归属是否相同
+    pub fn same_layout(&self, other: &ShardAssignments) -> bool {
+        let ids = |a: &ShardAssignments| {
+            let mut ids: Vec<String> = a.members.iter().map(|m| m.instance_id.clone()).collect();
+            ids.sort();
+            ids
+        };
+        self.assignments == other.assignments && ids(self) == ids(other)
+    }
+
+    pub fn owner(&self, exchange: &str, symbol: &str) -> Option<&ShardMember> {
+        let instance_id = self.assignments.get(&shard_key(exchange, symbol))?;
+        self.members.iter().find(|m| &m.instance_id == instance_id)
+    }
+
+    /// 分配给指定实例的交易对键
+    pub fn owned_by<'a>(&'a self, instance_id: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        self.assignments
+            .iter()
+            .filter(move |(_, owner)| owner.as_str() == instance_id)
+            .map(|(key, _)| key)
+    }
+}
+
+fn members_key(prefix: &str) -> String {
+    format!("{}:members", prefix)
+}
+
+fn assignments_key(prefix: &str) -> String {
+    format!("{}:assignments", prefix)
+}
+
+/// 上报成员心跳
+pub async fn heartbeat(redis: &mut ConnectionManager, prefix: &str, member: &ShardMember) -> anyhow::Result<()> {
+    let value = serde_json::to_string(member)?;
+    redis
+        .hset::<_, _, _, ()>(members_key(prefix), &member.instance_id, value)
+        .await?;
+    Ok(())
+}
+
+/// 读取最近 `ttl` 内有心跳的成员，并清理过期成员
+pub async fn live_members(
+    redis: &mut ConnectionManager,
+    prefix: &str,
+    ttl: chrono::Duration,
+) -> anyhow::Result<Vec<ShardMember>> {
+    let raw: HashMap<String, String> = redis.hgetall(members_key(prefix)).await?;
+    let cutoff = Utc::now() - ttl;
+    let mut live = Vec::new();
+    for (instance_id, value) in raw {
+        match serde_json::from_str::<ShardMember>(&value) {
+            Ok(member) if member.last_seen >= cutoff => live.push(member),
+            _ => {
+                redis.hdel::<_, _, ()>(members_key(prefix), &instance_id).await?;
+            }
+        }
+    }
+    live.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+    Ok(live)
+}
+
+/// 读取当前分配，尚未发布时返回 None
+pub async fn load_assignments(redis: &mut ConnectionManager, prefix: &str) -> anyhow::Result<Option<ShardAssignments>> {
+    let raw: Option<String> = redis.get(assignments_key(prefix)).await?;
+    Ok(match raw {
+        Some(raw) => Some(serde_json::from_str(&raw)?),
+        None => None,
+    })
+}
+
+/// 发布分配
+pub async fn publish_assignments(
+    redis: &mut ConnectionManager,
+    prefix: &str,
+    assignments: &ShardAssignments,
+) -> anyhow::Result<()> {
+    let value = serde_json::to_string(assignments)?;
+    redis.set::<_, _, ()>(assignments_key(prefix), value).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: &str) -> ShardMember {
+        ShardMember {
+            instance_id: id.to_string(),
+            url: format!("http://{}:8081", id),
+            last_seen: Utc::now(),
+        }
+    }
+
+    fn keys() -> Vec<String> {
+        (0..300).map(|i| shard_key("binance", &format!("SYM{}USDT", i))).collect()
+    }
+
+    #[test]
+    fn test_assignment_is_balanced_and_deterministic() {
+        let members = vec![member("md-0"), member("md-1"), member("md-2")];
+        let first = ShardAssignments::compute(members.clone(), keys(), 64);
+        let second = ShardAssignments::compute(members.into_iter().rev().collect(), keys(), 64);
+        assert!(first.same_layout(&second));
+
+        for id in ["md-0", "md-1", "md-2"] {
+            let owned = first.owned_by(id).count();
+            assert!((50..150).contains(&owned), "{} owns {}", id, owned);
+        }
+    }
+
+    #[test]
+    fn test_rebalance_moves_only_affected_symbols() {
+        let before = ShardAssignments::compute(vec![member("md-0"), member("md-1"), member("md-2")], keys(), 64);
+        let after = ShardAssignments::compute(vec![member("md-0"), member("md-1")], keys(), 64);
+
+        for (key, owner) in &before.assignments {
+            if owner != "md-2" {
+                assert_eq!(&after.assignments[key], owner);
+            }
+        }
+        assert_eq!(after.owned_by("md-2").count(), 0);
+        assert_eq!(
+            after.owner("binance", "sym1usdt").map(|m| m.instance_id.as_str()),
+            after.assignments.get("binance:SYM1USDT").map(String::as_str)
+        );
+    }
+}